@@ -12,4 +12,89 @@ pub struct Cli {
 pub enum Commands {
     /// Print version information
     Version,
+
+    /// Migrate a database and populate it from a seed fixture
+    Seed {
+        /// Built-in fixture name (e.g. `demo`) or path to a TOML/JSON file
+        fixture: String,
+
+        /// PostgreSQL connection URL
+        #[arg(
+            long,
+            env = "DATABASE_URL",
+            default_value = "postgres://localhost:5432/nize"
+        )]
+        database_url: String,
+
+        /// Key the server encrypts secret config values with
+        #[arg(
+            long,
+            env = "MCP_ENCRYPTION_KEY",
+            default_value = "nize-mcp-default-dev-key-change-in-production",
+            hide_default_value = true
+        )]
+        encryption_key: String,
+    },
+
+    /// Move stored document blobs from one storage backend to another
+    MigrateBlobs {
+        /// Backend to move blobs out of (`filesystem` or `s3`)
+        #[arg(long)]
+        from: String,
+
+        /// Backend to move blobs into (`filesystem` or `s3`)
+        #[arg(long)]
+        to: String,
+
+        /// PostgreSQL connection URL
+        #[arg(
+            long,
+            env = "DATABASE_URL",
+            default_value = "postgres://localhost:5432/nize"
+        )]
+        database_url: String,
+
+        /// Key the server encrypts secret config values with
+        #[arg(
+            long,
+            env = "MCP_ENCRYPTION_KEY",
+            default_value = "nize-mcp-default-dev-key-change-in-production",
+            hide_default_value = true
+        )]
+        encryption_key: String,
+    },
+    /// Copy the database into an empty external PostgreSQL database and
+    /// verify the copy (e.g. to move off the desktop's bundled PGlite)
+    MigrateDatabase {
+        /// PostgreSQL connection URL of the empty target database
+        #[arg(long, env = "TARGET_DATABASE_URL", hide_env_values = true)]
+        target_url: String,
+
+        /// PostgreSQL connection URL of the database to copy
+        #[arg(
+            long,
+            env = "DATABASE_URL",
+            default_value = "postgres://localhost:5432/nize"
+        )]
+        database_url: String,
+    },
+
+    /// Scan for dangling rows and cross-table inconsistencies
+    CheckIntegrity {
+        /// Also repair what the checks find (a dry run unless `--apply`)
+        #[arg(long)]
+        repair: bool,
+
+        /// Commit the repair instead of rolling it back
+        #[arg(long, requires = "repair")]
+        apply: bool,
+
+        /// PostgreSQL connection URL
+        #[arg(
+            long,
+            env = "DATABASE_URL",
+            default_value = "postgres://localhost:5432/nize"
+        )]
+        database_url: String,
+    },
 }
@@ -25,7 +25,200 @@ fn run() -> Result<()> {
         Commands::Version => {
             println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
         }
+        Commands::Seed {
+            fixture,
+            database_url,
+            encryption_key,
+        } => {
+            tokio::runtime::Runtime::new()?.block_on(seed(
+                fixture,
+                database_url,
+                encryption_key,
+            ))?;
+        }
+        Commands::MigrateBlobs {
+            from,
+            to,
+            database_url,
+            encryption_key,
+        } => {
+            tokio::runtime::Runtime::new()?.block_on(migrate_blobs(
+                from,
+                to,
+                database_url,
+                encryption_key,
+            ))?;
+        }
+        Commands::MigrateDatabase {
+            target_url,
+            database_url,
+        } => {
+            tokio::runtime::Runtime::new()?.block_on(migrate_database(target_url, database_url))?;
+        }
+        Commands::CheckIntegrity {
+            repair,
+            apply,
+            database_url,
+        } => {
+            tokio::runtime::Runtime::new()?.block_on(check_integrity(
+                *repair,
+                *apply,
+                database_url,
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn seed(fixture: &str, database_url: &str, encryption_key: &str) -> Result<()> {
+    let fixture =
+        nize_core::seed::load_fixture(fixture).map_err(|e| Error::Custom(e.to_string()))?;
+
+    let pool = sqlx::PgPool::connect(database_url)
+        .await
+        .map_err(|e| Error::Custom(format!("connect: {e}")))?;
+    nize_core::migrate::migrate(&pool)
+        .await
+        .map_err(|e| Error::Custom(format!("migrate: {e}")))?;
+
+    let report = nize_core::seed::apply_fixture(&pool, &fixture, encryption_key)
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?;
+    log::info!(
+        "seeded {} users, {} conversations, {} documents, {} MCP servers ({} already present)",
+        report.users_created,
+        report.conversations_created,
+        report.documents_created,
+        report.servers_created,
+        report.skipped
+    );
+
+    pool.close().await;
+    Ok(())
+}
+
+async fn migrate_blobs(
+    from: &str,
+    to: &str,
+    database_url: &str,
+    encryption_key: &str,
+) -> Result<()> {
+    use nize_core::blobs::{self, BlobBackend, BlobStore};
+
+    let parse = |name: &str| BlobBackend::parse(name).map_err(|e| Error::Custom(e.to_string()));
+    let (from, to) = (parse(from)?, parse(to)?);
+
+    let pool = sqlx::PgPool::connect(database_url)
+        .await
+        .map_err(|e| Error::Custom(format!("connect: {e}")))?;
+    let cache = std::sync::Arc::new(tokio::sync::RwLock::new(
+        nize_core::config::cache::ConfigCache::new(),
+    ));
+    let store = |backend| BlobStore::from_config(&pool, &cache, encryption_key, backend);
+    let source = store(from)
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?;
+    let target = store(to).await.map_err(|e| Error::Custom(e.to_string()))?;
+
+    let report = blobs::migrate_blobs(&pool, &source, &target)
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?;
+    log::info!(
+        "moved {} blobs ({} bytes) from {} to {}, {} failed",
+        report.moved,
+        report.bytes,
+        from.as_str(),
+        to.as_str(),
+        report.failed
+    );
+
+    pool.close().await;
+    if report.failed > 0 {
+        return Err(Error::Custom(format!(
+            "{} blobs failed to migrate; rerun to retry",
+            report.failed
+        )));
+    }
+    Ok(())
+}
+
+async fn migrate_database(target_url: &str, database_url: &str) -> Result<()> {
+    let pool = sqlx::PgPool::connect(database_url)
+        .await
+        .map_err(|e| Error::Custom(format!("connect: {e}")))?;
+
+    let report = nize_core::db_transfer::transfer(&pool, target_url)
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?;
+    for table in report.tables.iter().filter(|t| !t.verified) {
+        log::info!(
+            "{}: {} rows copied, {} on the target, checksums {} / {}",
+            table.table,
+            table.source_rows,
+            table.target_rows,
+            table.source_checksum,
+            table.target_checksum
+        );
+    }
+    log::info!(
+        "copied {} rows in {} tables and {} sequences",
+        report.rows,
+        report.tables.len(),
+        report.sequences
+    );
+
+    pool.close().await;
+    if !report.verified {
+        return Err(Error::Custom(
+            "the copy does not match the source in the tables above; do not switch to it".into(),
+        ));
+    }
+    log::info!("copy verified; point DATABASE_URL (or the desktop's database setting) at it");
+    Ok(())
+}
+
+async fn check_integrity(repair: bool, apply: bool, database_url: &str) -> Result<()> {
+    use nize_core::integrity::{self, RepairRequest};
+
+    let pool = sqlx::PgPool::connect(database_url)
+        .await
+        .map_err(|e| Error::Custom(format!("connect: {e}")))?;
+
+    let report = integrity::audit(&pool)
+        .await
+        .map_err(|e| Error::Custom(e.to_string()))?;
+    for check in report.checks.iter().filter(|c| c.count > 0) {
+        log::info!(
+            "{}: {} ({}), e.g. {}",
+            check.name,
+            check.count,
+            check.description,
+            check.samples.join(", ")
+        );
+        if let Some(hint) = check.hint {
+            log::info!("  {hint}");
+        }
+    }
+    log::info!("{} integrity issues found", report.issues);
+
+    if repair {
+        let report = integrity::repair(&pool, RepairRequest { dry_run: !apply })
+            .await
+            .map_err(|e| Error::Custom(e.to_string()))?;
+        for result in &report.repairs {
+            log::info!("{}: {} rows", result.name, result.rows);
+        }
+        if report.dry_run {
+            log::info!(
+                "{} rows would change; rerun with --apply to commit",
+                report.rows
+            );
+        } else {
+            log::info!("{} rows repaired", report.rows);
+        }
     }
 
+    pool.close().await;
     Ok(())
 }
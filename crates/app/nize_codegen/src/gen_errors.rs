@@ -0,0 +1,481 @@
+//! Generates `errors.rs` — a typed error enum per operation from its
+//! non-2xx OpenAPI responses.
+//!
+//! Each operation with error responses gets `<Method><Path>Error` (named
+//! like its route constant) with one variant per status code, carrying the
+//! response's model. Status families (`4XX`, `5XX`) become variants that
+//! also keep the actual status. Several models under one status code
+//! (`anyOf`) get an untagged companion enum. Every enum has a catch-all
+//! `Unexpected` variant for statuses the spec does not declare and bodies
+//! that do not match it; `default` responses are left to it too.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::gen_models::ref_struct_name;
+use crate::gen_routes::{collect_methods, operation, route_const_name};
+use crate::schema::{Operation, PathItem, ResponseObject, SchemaObject};
+
+/// Helpers shared by the generated `impl`s, emitted once.
+const HELPERS: &str = "\
+/// Body of a response the spec does not describe: its JSON, else its text.
+fn unexpected_body(body: &[u8]) -> serde_json::Value {
+    serde_json::from_slice(body).unwrap_or_else(|_| {
+        serde_json::Value::String(String::from_utf8_lossy(body).into_owned())
+    })
+}
+
+/// `HTTP 404: Document not found`, with the body's `message` when it has one.
+fn describe(
+    f: &mut std::fmt::Formatter<'_>,
+    status: u16,
+    body: Option<serde_json::Value>,
+) -> std::fmt::Result {
+    match body.as_ref().and_then(|b| b.get(\"message\")).and_then(|m| m.as_str()) {
+        Some(message) => write!(f, \"HTTP {status}: {message}\"),
+        None => write!(f, \"HTTP {status}\"),
+    }
+}
+";
+
+/// Status codes an error response applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Statuses {
+    Exact(u16),
+    /// A status family: `4XX` is `Family(4)`.
+    Family(u16),
+}
+
+impl Statuses {
+    /// Parse a response key. 1xx, 2xx and `default` are not error responses.
+    fn parse(key: &str) -> Option<Self> {
+        let statuses = match key.strip_suffix("XX") {
+            Some(family) => Statuses::Family(family.parse().ok()?),
+            None => Statuses::Exact(key.parse().ok()?),
+        };
+        let family = match statuses {
+            Statuses::Exact(code) => code / 100,
+            Statuses::Family(family) => family,
+        };
+        (3..=5).contains(&family).then_some(statuses)
+    }
+
+    fn pattern(self) -> String {
+        match self {
+            Statuses::Exact(code) => code.to_string(),
+            Statuses::Family(family) => format!("{}..={}", family * 100, family * 100 + 99),
+        }
+    }
+
+    fn variant_name(self) -> String {
+        let name = match self {
+            Statuses::Exact(400) => "BadRequest",
+            Statuses::Exact(401) => "Unauthorized",
+            Statuses::Exact(402) => "PaymentRequired",
+            Statuses::Exact(403) => "Forbidden",
+            Statuses::Exact(404) => "NotFound",
+            Statuses::Exact(405) => "MethodNotAllowed",
+            Statuses::Exact(406) => "NotAcceptable",
+            Statuses::Exact(408) => "RequestTimeout",
+            Statuses::Exact(409) => "Conflict",
+            Statuses::Exact(410) => "Gone",
+            Statuses::Exact(412) => "PreconditionFailed",
+            Statuses::Exact(413) => "PayloadTooLarge",
+            Statuses::Exact(415) => "UnsupportedMediaType",
+            Statuses::Exact(422) => "UnprocessableEntity",
+            Statuses::Exact(423) => "Locked",
+            Statuses::Exact(429) => "TooManyRequests",
+            Statuses::Exact(500) => "InternalServerError",
+            Statuses::Exact(501) => "NotImplemented",
+            Statuses::Exact(502) => "BadGateway",
+            Statuses::Exact(503) => "ServiceUnavailable",
+            Statuses::Exact(504) => "GatewayTimeout",
+            Statuses::Exact(code) => return format!("Status{code}"),
+            Statuses::Family(3) => "Redirection",
+            Statuses::Family(4) => "ClientError",
+            Statuses::Family(_) => "ServerError",
+        };
+        name.to_string()
+    }
+
+    /// How the response is named in doc comments: `404`, `4XX`.
+    fn label(self) -> String {
+        match self {
+            Statuses::Exact(code) => code.to_string(),
+            Statuses::Family(family) => format!("{family}XX"),
+        }
+    }
+}
+
+/// One variant of an operation's error enum.
+struct Variant {
+    statuses: Statuses,
+    name: String,
+    /// Rust type of the body; `None` for responses without a JSON body.
+    body: Option<String>,
+}
+
+/// Generate the contents of `errors.rs`. Response models are imported
+/// from `models_path` (e.g. `super::models`).
+pub fn generate(
+    paths: &BTreeMap<String, PathItem>,
+    schemas: &BTreeMap<String, SchemaObject>,
+    models_path: &str,
+) -> String {
+    let mut items = String::new();
+    let mut models = BTreeSet::new();
+    let mut untagged = false;
+
+    for (path, item) in paths {
+        for method in collect_methods(item) {
+            let Some(op) = operation(item, method) else {
+                continue;
+            };
+            let enum_name = format!("{}Error", pascal_case(&route_const_name(method, path)));
+            let mut companions = String::new();
+            let mut variants = Vec::new();
+            for (key, response) in &op.responses {
+                let Some(statuses) = Statuses::parse(key) else {
+                    continue;
+                };
+                let name = statuses.variant_name();
+                let body = body_type(
+                    response,
+                    schemas,
+                    &format!("{enum_name}{name}"),
+                    &mut models,
+                    &mut companions,
+                    &format!("{method} {path}"),
+                    statuses,
+                );
+                untagged |= !companions.is_empty();
+                variants.push(Variant {
+                    statuses,
+                    name,
+                    body,
+                });
+            }
+            if variants.is_empty() {
+                continue;
+            }
+            variants.sort_by_key(|v| v.statuses);
+            write_enum(&mut items, &enum_name, method, path, op, &variants);
+            items.push_str(&companions);
+        }
+    }
+
+    if items.is_empty() {
+        return String::new();
+    }
+    let mut out = String::new();
+    out.push_str(if untagged {
+        "use serde::{Deserialize, Serialize};\n"
+    } else {
+        "use serde::Serialize;\n"
+    });
+    if !models.is_empty() {
+        let models: Vec<_> = models.into_iter().collect();
+        writeln!(out, "use {models_path}::{{{}}};", models.join(", ")).unwrap();
+    }
+    out.push('\n');
+    out.push_str(HELPERS);
+    out.push_str(&items);
+    out
+}
+
+/// Rust type of a response body, recording the models it uses. Several
+/// alternative models get a companion enum named `companion`, appended to
+/// `companions`.
+fn body_type(
+    response: &ResponseObject,
+    schemas: &BTreeMap<String, SchemaObject>,
+    companion: &str,
+    models: &mut BTreeSet<String>,
+    companions: &mut String,
+    operation_label: &str,
+    statuses: Statuses,
+) -> Option<String> {
+    let schema = response.content.get("application/json")?.schema.as_ref()?;
+    if let Some(ref_path) = &schema.ref_path {
+        let name = ref_struct_name(ref_path).to_string();
+        models.insert(name.clone());
+        return Some(name);
+    }
+    let refs: Vec<&str> = schema
+        .any_of
+        .iter()
+        .filter_map(|alt| alt.ref_path.as_deref())
+        .collect();
+    if refs.is_empty() || refs.len() != schema.any_of.len() {
+        return Some("serde_json::Value".to_string());
+    }
+
+    // Untagged enums take the first alternative that parses, so try the
+    // models with the most required fields first.
+    let mut alternatives: Vec<(&str, usize)> = refs
+        .iter()
+        .map(|r| {
+            let key = r.rsplit('/').next().unwrap_or(r);
+            let required = schemas.get(key).map_or(0, |s| s.required.len());
+            (ref_struct_name(r), required)
+        })
+        .collect();
+    alternatives.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    writeln!(
+        companions,
+        "\n/// Bodies of the {} response of `{operation_label}`.",
+        statuses.label()
+    )
+    .unwrap();
+    companions.push_str("#[derive(Debug, Clone, Serialize, Deserialize)]\n#[serde(untagged)]\n");
+    writeln!(companions, "pub enum {companion} {{").unwrap();
+    for (name, _) in &alternatives {
+        models.insert(name.to_string());
+        writeln!(companions, "    {name}({name}),").unwrap();
+    }
+    companions.push_str("}\n");
+    Some(companion.to_string())
+}
+
+fn write_enum(
+    out: &mut String,
+    enum_name: &str,
+    method: &str,
+    path: &str,
+    op: &Operation,
+    variants: &[Variant],
+) {
+    out.push('\n');
+    match &op.operation_id {
+        Some(id) => writeln!(out, "/// Error responses of `{method} {path}` (`{id}`).").unwrap(),
+        None => writeln!(out, "/// Error responses of `{method} {path}`.").unwrap(),
+    }
+    out.push_str("#[derive(Debug, Clone)]\n");
+    writeln!(out, "pub enum {enum_name} {{").unwrap();
+    for v in variants {
+        writeln!(out, "    /// {} response.", v.statuses.label()).unwrap();
+        let fields = match (v.statuses, &v.body) {
+            (Statuses::Exact(_), None) => String::new(),
+            (Statuses::Exact(_), Some(body)) => format!("({body})"),
+            (Statuses::Family(_), None) => "(u16)".to_string(),
+            (Statuses::Family(_), Some(body)) => format!("(u16, {body})"),
+        };
+        writeln!(out, "    {}{fields},", v.name).unwrap();
+    }
+    out.push_str(
+        "    /// A status the spec does not declare, or a body that does not match it.\n\
+         \x20   Unexpected { status: u16, body: serde_json::Value },\n\
+         }\n\n",
+    );
+
+    // Parsing and status
+    writeln!(out, "impl {enum_name} {{").unwrap();
+    out.push_str(
+        "    /// Parse an error response from its status code and body.\n\
+         \x20   pub fn from_response(status: u16, body: &[u8]) -> Self {\n\
+         \x20       let parsed = match status {\n",
+    );
+    for v in variants {
+        let pattern = v.statuses.pattern();
+        let name = &v.name;
+        let arm = match (v.statuses, &v.body) {
+            (Statuses::Exact(_), None) => format!("Some(Self::{name})"),
+            (Statuses::Exact(_), Some(_)) => {
+                format!("serde_json::from_slice(body).ok().map(Self::{name})")
+            }
+            (Statuses::Family(_), None) => format!("Some(Self::{name}(status))"),
+            (Statuses::Family(_), Some(_)) => format!(
+                "serde_json::from_slice(body)\n\
+                 \x20               .ok()\n\
+                 \x20               .map(|body| Self::{name}(status, body))"
+            ),
+        };
+        writeln!(out, "            {pattern} => {arm},").unwrap();
+    }
+    out.push_str(
+        "            _ => None,\n\
+         \x20       };\n\
+         \x20       parsed.unwrap_or_else(|| Self::Unexpected {\n\
+         \x20           status,\n\
+         \x20           body: unexpected_body(body),\n\
+         \x20       })\n\
+         \x20   }\n\n\
+         \x20   /// HTTP status code of the response.\n\
+         \x20   pub fn status(&self) -> u16 {\n\
+         \x20       match self {\n",
+    );
+    for v in variants {
+        let name = &v.name;
+        let arm = match (v.statuses, &v.body) {
+            (Statuses::Exact(code), None) => format!("Self::{name} => {code}"),
+            (Statuses::Exact(code), Some(_)) => format!("Self::{name}(_) => {code}"),
+            (Statuses::Family(_), None) => format!("Self::{name}(status) => *status"),
+            (Statuses::Family(_), Some(_)) => format!("Self::{name}(status, _) => *status"),
+        };
+        writeln!(out, "            {arm},").unwrap();
+    }
+    out.push_str(
+        "            Self::Unexpected { status, .. } => *status,\n\
+         \x20       }\n\
+         \x20   }\n\
+         }\n\n",
+    );
+
+    // The body alone serializes, so handlers can answer with it.
+    writeln!(out, "impl Serialize for {enum_name} {{").unwrap();
+    out.push_str(
+        "    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {\n\
+         \x20       match self {\n",
+    );
+    for v in variants {
+        let name = &v.name;
+        let arm = match (v.statuses, &v.body) {
+            (Statuses::Exact(_), None) => format!("Self::{name} => serializer.serialize_unit()"),
+            (Statuses::Exact(_), Some(_)) => {
+                format!("Self::{name}(body) => body.serialize(serializer)")
+            }
+            (Statuses::Family(_), None) => {
+                format!("Self::{name}(_) => serializer.serialize_unit()")
+            }
+            (Statuses::Family(_), Some(_)) => {
+                format!("Self::{name}(_, body) => body.serialize(serializer)")
+            }
+        };
+        writeln!(out, "            {arm},").unwrap();
+    }
+    out.push_str(
+        "            Self::Unexpected { body, .. } => body.serialize(serializer),\n\
+         \x20       }\n\
+         \x20   }\n\
+         }\n\n",
+    );
+
+    writeln!(out, "impl std::fmt::Display for {enum_name} {{").unwrap();
+    out.push_str(
+        "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {\n\
+         \x20       describe(f, self.status(), serde_json::to_value(self).ok())\n\
+         \x20   }\n\
+         }\n\n",
+    );
+    writeln!(out, "impl std::error::Error for {enum_name} {{}}").unwrap();
+}
+
+/// `POST_INGEST_ID` → `PostIngestId`.
+fn pascal_case(const_name: &str) -> String {
+    const_name
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let lower = part.to_lowercase();
+            let mut chars = lower.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+info:
+  title: test
+  version: 1.0.0
+paths:
+  /ingest:
+    post:
+      operationId: IngestRoutes_upload
+      responses:
+        '201':
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/Ingest.IngestResponse'
+        '401':
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/NizeApi.UnauthorizedError'
+        '403':
+          content:
+            application/json:
+              schema:
+                anyOf:
+                  - $ref: '#/components/schemas/NizeApi.ForbiddenError'
+                  - $ref: '#/components/schemas/NizeApi.QuotaExceededError'
+        '5XX':
+          content:
+            application/json:
+              schema:
+                $ref: '#/components/schemas/NizeApi.ErrorResponse'
+        default:
+          description: Unexpected error
+  /hello:
+    get:
+      responses:
+        '200':
+          description: ok
+components:
+  schemas:
+    NizeApi.ForbiddenError:
+      type: object
+      required: [error, message]
+    NizeApi.QuotaExceededError:
+      type: object
+      required: [error, message, quota]
+"##;
+
+    fn generated() -> String {
+        let doc: crate::schema::OpenApiDoc = serde_yaml::from_str(SPEC).unwrap();
+        generate(&doc.paths, &doc.components.schemas, "super::models")
+    }
+
+    #[test]
+    fn error_enums_have_a_variant_per_status() {
+        let out = generated();
+        assert!(out.contains("/// Error responses of `POST /ingest` (`IngestRoutes_upload`)."));
+        assert!(out.contains("pub enum PostIngestError {"));
+        assert!(out.contains("    Unauthorized(UnauthorizedError),"));
+        assert!(out.contains("    Forbidden(PostIngestErrorForbidden),"));
+        assert!(out.contains("    ServerError(u16, ErrorResponse),"));
+        assert!(out.contains(
+            "            401 => serde_json::from_slice(body).ok().map(Self::Unauthorized),"
+        ));
+        assert!(out.contains("            500..=599 => serde_json::from_slice(body)"));
+        assert!(out.contains("Self::ServerError(status, _) => *status,"));
+        assert!(!out.contains("Default"));
+        assert!(!out.contains("GetHelloError"));
+        assert!(out.contains(
+            "use super::models::{ErrorResponse, ForbiddenError, QuotaExceededError, UnauthorizedError};"
+        ));
+    }
+
+    #[test]
+    fn shared_statuses_try_the_most_specific_model_first() {
+        let out = generated();
+        assert!(out.contains(
+            "#[serde(untagged)]\npub enum PostIngestErrorForbidden {\n    \
+             QuotaExceededError(QuotaExceededError),\n    \
+             ForbiddenError(ForbiddenError),\n}"
+        ));
+    }
+
+    #[test]
+    fn specs_without_error_responses_generate_nothing() {
+        let doc: crate::schema::OpenApiDoc =
+            serde_yaml::from_str("info:\n  title: t\npaths: {}\n").unwrap();
+        assert_eq!(
+            generate(&doc.paths, &doc.components.schemas, "super::models"),
+            ""
+        );
+        assert_eq!(Statuses::parse("default"), None);
+        assert_eq!(Statuses::parse("204"), None);
+        assert_eq!(Statuses::parse("4XX"), Some(Statuses::Family(4)));
+        assert_eq!(pascal_case("GET_INGEST_ID_CONTENT"), "GetIngestIdContent");
+    }
+}
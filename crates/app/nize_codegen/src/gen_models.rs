@@ -10,12 +10,7 @@ use crate::writer::escape_rust_str;
 fn rust_type(prop: &PropertyObject, required: bool) -> String {
     // Handle $ref to another schema
     if let Some(ref_path) = &prop.ref_path {
-        let ref_name = ref_path.rsplit('/').next().unwrap_or(ref_path);
-        // Strip namespace prefix (e.g. "Auth.AuthUser" → "AuthUser")
-        let struct_name = match ref_name.rsplit_once('.') {
-            Some((_, suffix)) => suffix,
-            None => ref_name,
-        };
+        let struct_name = ref_struct_name(ref_path);
         if !required {
             return format!("Option<{struct_name}>");
         }
@@ -56,6 +51,16 @@ fn rust_type(prop: &PropertyObject, required: bool) -> String {
     }
 }
 
+/// Struct generated for a `$ref`, without its namespace prefix
+/// (e.g. `#/components/schemas/Auth.AuthUser` → `AuthUser`).
+pub(crate) fn ref_struct_name(ref_path: &str) -> &str {
+    let ref_name = ref_path.rsplit('/').next().unwrap_or(ref_path);
+    match ref_name.rsplit_once('.') {
+        Some((_, suffix)) => suffix,
+        None => ref_name,
+    }
+}
+
 /// Convert a camelCase field name to snake_case.
 fn to_snake_case(s: &str) -> String {
     let mut out = String::new();
@@ -87,7 +92,7 @@ fn escape_rust_keyword(name: &str) -> String {
 pub fn generate(schemas: &BTreeMap<String, SchemaObject>) -> String {
     let mut out = String::new();
 
-    out.push_str("use serde::{Deserialize, Serialize};\n\n");
+    out.push_str("use schemars::JsonSchema;\nuse serde::{Deserialize, Serialize};\n\n");
 
     for (name, schema) in schemas {
         generate_struct(&mut out, name, schema);
@@ -111,7 +116,11 @@ fn generate_struct(out: &mut String, name: &str, schema: &SchemaObject) {
         }
     }
 
-    writeln!(out, "#[derive(Debug, Clone, Serialize, Deserialize)]").unwrap();
+    writeln!(
+        out,
+        "#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]"
+    )
+    .unwrap();
     writeln!(out, "pub struct {struct_name} {{").unwrap();
 
     for (field_name, prop) in &schema.properties {
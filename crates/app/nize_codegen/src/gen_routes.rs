@@ -1,16 +1,65 @@
-//! Generates `routes.rs` — route path constants from OpenAPI paths.
+//! Generates `routes.rs` — route path constants from OpenAPI paths, the
+//! auth tier of every operation from its security requirements, the API
+//! version from `info.version`, the spec hash, and the deprecated
+//! operations.
 
 use std::collections::BTreeMap;
 use std::fmt::Write;
 
-use crate::schema::PathItem;
+use crate::schema::{Info, Operation, PathItem, SecurityRequirement};
 use crate::writer::escape_rust_str;
 
+/// Security scheme that marks an operation as admin-only.
+const ADMIN_SCHEME: &str = "AdminAuth";
+
+/// Preamble of `routes.rs`: the `AuthTier` enum the route table refers to.
+const AUTH_TIER_ENUM: &str = "\
+/// Authentication an operation requires, derived from its OpenAPI security
+/// requirements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthTier {
+    /// Anonymous access allowed (`@useAuth(NoAuth)`).
+    Public,
+    /// Any authenticated user (`BearerAuth`).
+    Protected,
+    /// An authenticated user holding the route's admin permission (`AdminAuth`).
+    Admin,
+}
+
+";
+
 /// Generate the contents of `routes.rs`.
-pub fn generate(paths: &BTreeMap<String, PathItem>) -> String {
+///
+/// `default_security` is the document-wide requirement inherited by
+/// operations that declare none. Fails when an operation has no security
+/// requirement at all, so every route has an explicit tier.
+pub fn generate(
+    info: &Info,
+    paths: &BTreeMap<String, PathItem>,
+    default_security: Option<&[SecurityRequirement]>,
+    spec_hash: &str,
+) -> Result<String, String> {
     let mut out = String::new();
+    let mut table = String::new();
+    let mut deprecations = String::new();
 
     out.push_str("//! Route path constants extracted from the OpenAPI specification.\n\n");
+    out.push_str(AUTH_TIER_ENUM);
+    out.push_str(
+        "/// Version segment of the route prefix (`/api/v1`), from the spec's\n\
+         /// `info.version`.\n",
+    );
+    writeln!(
+        out,
+        "pub const API_VERSION: &str = \"{}\";\n",
+        version_segment(&info.version)
+    )
+    .unwrap();
+    out.push_str(
+        "/// Fingerprint of the API spec this code was generated from\n\
+         /// (`nize_codegen::spec_hash`), reported at `GET /meta`.\n",
+    );
+    writeln!(out, "pub const SPEC_HASH: &str = \"{spec_hash}\";\n").unwrap();
 
     for (path, item) in paths {
         let methods = collect_methods(item);
@@ -26,14 +75,67 @@ pub fn generate(paths: &BTreeMap<String, PathItem>) -> String {
             }
             writeln!(out, "pub const {const_name}: &str = \"{path}\";").unwrap();
             out.push('\n');
+
+            let security = operation(item, method)
+                .and_then(|o| o.security.as_deref())
+                .or(default_security)
+                .ok_or_else(|| format!("{method} {path} has no security requirement"))?;
+            let tier = auth_tier(security);
+            writeln!(table, "    (\"{method}\", {const_name}, AuthTier::{tier}),").unwrap();
+
+            if let Some(op) = operation(item, method).filter(|o| o.deprecated) {
+                let sunset = match &op.sunset {
+                    Some(date) => format!("Some(\"{}\")", escape_rust_str(date)),
+                    None => "None".to_string(),
+                };
+                writeln!(deprecations, "    (\"{method}\", {const_name}, {sunset}),").unwrap();
+            }
         }
     }
 
-    out
+    out.push_str("/// Every operation as `(method, path, tier)`.\n");
+    out.push_str("pub const ROUTE_AUTH: &[(&str, &str, AuthTier)] = &[\n");
+    out.push_str(&table);
+    out.push_str("];\n\n");
+
+    out.push_str(
+        "/// Deprecated operations as `(method, path, sunset)`, where `sunset` is\n\
+         /// the ISO date after which the operation may be removed.\n",
+    );
+    out.push_str("pub const ROUTE_DEPRECATIONS: &[(&str, &str, Option<&str>)] = &[\n");
+    out.push_str(&deprecations);
+    out.push_str("];\n");
+
+    Ok(out)
+}
+
+/// Map security requirements to an `AuthTier` variant name. An empty
+/// requirement allows anonymous access; otherwise the [`ADMIN_SCHEME`]
+/// marks admin routes and any other scheme means a signed-in user.
+fn auth_tier(security: &[SecurityRequirement]) -> &'static str {
+    if security.is_empty() || security.iter().any(|r| r.is_empty()) {
+        "Public"
+    } else if security.iter().any(|r| r.contains_key(ADMIN_SCHEME)) {
+        "Admin"
+    } else {
+        "Protected"
+    }
+}
+
+/// Route prefix segment for a spec version: `v` and its major number
+/// (`1.2.0` → `v1`). Specs without a version, or before 1.0, are `v1`.
+fn version_segment(version: &str) -> String {
+    let major: u32 = version
+        .trim_start_matches('v')
+        .split('.')
+        .next()
+        .and_then(|m| m.parse().ok())
+        .unwrap_or(0);
+    format!("v{}", major.max(1))
 }
 
 /// Collect HTTP methods defined on a path item.
-fn collect_methods(item: &PathItem) -> Vec<&'static str> {
+pub(crate) fn collect_methods(item: &PathItem) -> Vec<&'static str> {
     let mut methods = Vec::new();
     if item.get.is_some() {
         methods.push("GET");
@@ -55,7 +157,7 @@ fn collect_methods(item: &PathItem) -> Vec<&'static str> {
 
 /// Build a SCREAMING_SNAKE constant name from method + path.
 /// e.g. GET /api/hello → GET_API_HELLO
-fn route_const_name(method: &str, path: &str) -> String {
+pub(crate) fn route_const_name(method: &str, path: &str) -> String {
     let path_part: String = path
         .trim_start_matches('/')
         .replace('/', "_")
@@ -66,15 +168,85 @@ fn route_const_name(method: &str, path: &str) -> String {
     format!("{method}_{path_part}")
 }
 
-/// Get the description from the operation for a given method.
-fn method_description(item: &PathItem, method: &str) -> Option<String> {
-    let op = match method {
+/// Get the operation for a given method.
+pub(crate) fn operation<'a>(item: &'a PathItem, method: &str) -> Option<&'a Operation> {
+    match method {
         "GET" => item.get.as_ref(),
         "POST" => item.post.as_ref(),
         "PUT" => item.put.as_ref(),
         "DELETE" => item.delete.as_ref(),
         "PATCH" => item.patch.as_ref(),
         _ => None,
-    };
-    op.and_then(|o| o.description.clone())
+    }
+}
+
+/// Get the description from the operation for a given method.
+fn method_description(item: &PathItem, method: &str) -> Option<String> {
+    operation(item, method).and_then(|o| o.description.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+info:
+  title: test
+  version: 2.1.0
+security:
+  - BearerAuth: []
+paths:
+  /items:
+    get:
+      responses: {}
+  /login:
+    post:
+      security:
+        - {}
+      responses: {}
+  /old-login:
+    post:
+      security:
+        - {}
+      deprecated: true
+      x-sunset: "2027-01-31"
+      responses: {}
+  /admin/items:
+    delete:
+      security:
+        - AdminAuth: []
+      responses: {}
+"##;
+
+    #[test]
+    fn route_table_follows_security_requirements() {
+        let doc: crate::schema::OpenApiDoc = serde_yaml::from_str(SPEC).unwrap();
+        let out = generate(&doc.info, &doc.paths, doc.security.as_deref(), "0f").unwrap();
+        assert!(out.contains("(\"GET\", GET_ITEMS, AuthTier::Protected),"));
+        assert!(out.contains("(\"POST\", POST_LOGIN, AuthTier::Public),"));
+        assert!(out.contains("(\"DELETE\", DELETE_ADMIN_ITEMS, AuthTier::Admin),"));
+    }
+
+    #[test]
+    fn version_and_deprecations_are_generated() {
+        let doc: crate::schema::OpenApiDoc = serde_yaml::from_str(SPEC).unwrap();
+        let out = generate(&doc.info, &doc.paths, doc.security.as_deref(), "0f").unwrap();
+        assert!(out.contains("pub const API_VERSION: &str = \"v2\";"));
+        assert!(out.contains("pub const SPEC_HASH: &str = \"0f\";"));
+        assert!(out.contains("(\"POST\", POST_OLD_LOGIN, Some(\"2027-01-31\")),"));
+        assert!(!out.contains("(\"POST\", POST_LOGIN, Some"));
+
+        assert_eq!(version_segment(""), "v1");
+        assert_eq!(version_segment("0"), "v1");
+        assert_eq!(version_segment("1.0.0"), "v1");
+    }
+
+    #[test]
+    fn operations_without_security_are_rejected() {
+        let doc: crate::schema::OpenApiDoc =
+            serde_yaml::from_str(&SPEC.replace("security:\n  - BearerAuth: []\n", "")).unwrap();
+        let err = generate(&doc.info, &doc.paths, doc.security.as_deref(), "0f").unwrap_err();
+        assert_eq!(err, "GET /items has no security requirement");
+    }
 }
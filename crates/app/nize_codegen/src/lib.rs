@@ -1,8 +1,12 @@
 //! Code generator library for Nize API models and route constants.
 //!
 //! Reads an OpenAPI 3.0 YAML file (produced by TypeSpec) and emits
-//! Rust source files into `crates/lib/nize_api/src/generated/`.
+//! Rust source files into `crates/lib/nize_api/src/generated/`. The typed
+//! error enums are also available to API clients through
+//! [`client_error_types`].
 
+mod gen_errors;
+mod gen_mock;
 mod gen_models;
 mod gen_routes;
 mod schema;
@@ -12,6 +16,14 @@ use std::path::Path;
 
 use schema::OpenApiDoc;
 
+/// Optional outputs beyond models and route constants.
+#[derive(Debug, Clone, Default)]
+pub struct GenerateOptions {
+    /// Emit `mock.rs`: an Axum router answering every operation with an
+    /// example response, compiled behind nize_api's `mock-server` feature.
+    pub mock_server: bool,
+}
+
 /// Generate all Rust source files from an OpenAPI YAML spec.
 ///
 /// * `spec_path`  — path to the OpenAPI YAML file
@@ -20,20 +32,32 @@ use schema::OpenApiDoc;
 /// Returns `Ok(true)` if files were generated (stale), `Ok(false)` if
 /// already up-to-date.
 pub fn generate(spec_path: &Path, output_dir: &Path) -> Result<bool, String> {
+    generate_with_options(spec_path, output_dir, &GenerateOptions::default())
+}
+
+/// Like [`generate`], with optional extra outputs.
+pub fn generate_with_options(
+    spec_path: &Path,
+    output_dir: &Path,
+    options: &GenerateOptions,
+) -> Result<bool, String> {
     let hash_path = output_dir.join(".hash");
 
-    // Read the spec source
+    // Read the spec source; options are part of the staleness key.
     let yaml_str = std::fs::read_to_string(spec_path)
         .map_err(|e| format!("Failed to read {}: {e}", spec_path.display()))?;
+    let hash_input = format!("{yaml_str}\n{options:?}");
 
     // Check staleness
-    if is_up_to_date(&yaml_str, &hash_path) {
+    if is_up_to_date(&hash_input, &hash_path) {
         return Ok(false);
     }
 
     // Parse
     let doc: OpenApiDoc = serde_yaml::from_str(&yaml_str)
         .map_err(|e| format!("Failed to parse OpenAPI YAML: {e}"))?;
+    let raw: serde_json::Value = serde_yaml::from_str(&yaml_str)
+        .map_err(|e| format!("Failed to parse OpenAPI YAML: {e}"))?;
 
     // Create output directory
     std::fs::create_dir_all(output_dir)
@@ -46,24 +70,66 @@ pub fn generate(spec_path: &Path, output_dir: &Path) -> Result<bool, String> {
         &gen_models::generate(&doc.components.schemas),
     )?;
 
-    // Generate route constants
-    generate_file(output_dir, "routes.rs", &gen_routes::generate(&doc.paths))?;
+    // Generate route constants and auth tiers
+    let routes = gen_routes::generate(
+        &doc.info,
+        &doc.paths,
+        doc.security.as_deref(),
+        &spec_hash(&raw),
+    )?;
+    generate_file(output_dir, "routes.rs", &routes)?;
+
+    // Generate typed error responses
+    let errors = gen_errors::generate(&doc.paths, &doc.components.schemas, "super::models");
+    generate_file(
+        output_dir,
+        "errors.rs",
+        &format!("//! Typed error responses of every operation.\n\n{errors}"),
+    )?;
 
-    // Generate mod.rs re-exports
-    let mod_rs = "\
+    // Generate mock server
+    let mut mod_rs = String::from(
+        "\
+pub mod errors;
 pub mod models;
 pub mod routes;
-";
-    generate_file(output_dir, "mod.rs", mod_rs)?;
+",
+    );
+    if options.mock_server {
+        generate_file(
+            output_dir,
+            "mock.rs",
+            &gen_mock::generate(&doc.paths, &doc.components.schemas),
+        )?;
+        mod_rs.push_str("#[cfg(feature = \"mock-server\")]\npub mod mock;\n");
+    }
+
+    // Generate mod.rs re-exports
+    generate_file(output_dir, "mod.rs", &mod_rs)?;
 
     // Write hash file for staleness check
-    let hash = compute_hash(&yaml_str);
+    let hash = compute_hash(&hash_input);
     std::fs::write(&hash_path, &hash)
         .map_err(|e| format!("Failed to write {}: {e}", hash_path.display()))?;
 
     Ok(true)
 }
 
+/// Typed error responses for an API client, from the spec in JSON form.
+///
+/// Returns items to include in a client module: the model structs in a
+/// nested `models` module, then an error enum per operation whose
+/// `from_response` parses a non-2xx status and body.
+pub fn client_error_types(spec: &serde_json::Value) -> Result<String, String> {
+    let doc: OpenApiDoc = serde_json::from_value(spec.clone())
+        .map_err(|e| format!("Failed to parse OpenAPI JSON: {e}"))?;
+    Ok(format!(
+        "pub mod models {{\n{}}}\n\n{}",
+        gen_models::generate(&doc.components.schemas),
+        gen_errors::generate(&doc.paths, &doc.components.schemas, "self::models"),
+    ))
+}
+
 fn generate_file(output_dir: &Path, filename: &str, content: &str) -> Result<(), String> {
     let path = output_dir.join(filename);
     writer::write_generated_file(&path, content)
@@ -80,6 +146,16 @@ fn is_up_to_date(yaml_str: &str, hash_path: &Path) -> bool {
     stored_hash.trim() == current_hash
 }
 
+/// Fingerprint of an OpenAPI document, independent of how it was written.
+///
+/// Hashes the document's compact JSON form, so the YAML the server is
+/// generated from and the JSON the client is generated from give the same
+/// value. The server reports it at `GET /meta` (`routes::SPEC_HASH`) for
+/// clients to compare with the one they were built from.
+pub fn spec_hash(doc: &serde_json::Value) -> String {
+    compute_hash(&doc.to_string())
+}
+
 /// Compute a hash of the input string.
 ///
 /// Uses FNV-1a 128-bit — sufficient for staleness detection.
@@ -91,3 +167,29 @@ fn compute_hash(input: &str) -> String {
     }
     format!("{h:032x}")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spec_hash_ignores_the_serialization() {
+        let yaml: serde_json::Value = serde_yaml::from_str(
+            "openapi: 3.0.0\ninfo:\n  title: test\n  version: '1'\npaths: {}\n",
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(
+            r#"{
+  "openapi": "3.0.0",
+  "info": { "title": "test", "version": "1" },
+  "paths": {}
+}"#,
+        )
+        .unwrap();
+        assert_eq!(spec_hash(&yaml), spec_hash(&json));
+        assert_ne!(
+            spec_hash(&yaml),
+            spec_hash(&serde_json::json!({ "openapi": "3.1.0" }))
+        );
+    }
+}
@@ -1,6 +1,8 @@
 //! Code generator CLI for Nize API models.
 //!
-//! Thin wrapper around [`nize_codegen::generate`].
+//! Thin wrapper around [`nize_codegen::generate_with_options`].
+//!
+//! Pass `--mock-server` to also emit the contract-testing mock router.
 
 use std::path::PathBuf;
 
@@ -33,10 +35,14 @@ fn main() {
         .join("src")
         .join("generated");
 
+    let options = nize_codegen::GenerateOptions {
+        mock_server: std::env::args().any(|a| a == "--mock-server"),
+    };
+
     println!("Reading: {}", spec_path.display());
     println!("Output:  {}", output_dir.display());
 
-    match nize_codegen::generate(&spec_path, &output_dir) {
+    match nize_codegen::generate_with_options(&spec_path, &output_dir, &options) {
         Ok(true) => println!("Done — generated files written."),
         Ok(false) => println!("Done — already up-to-date."),
         Err(e) => {
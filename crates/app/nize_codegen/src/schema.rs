@@ -11,8 +11,16 @@ pub struct OpenApiDoc {
     pub paths: BTreeMap<String, PathItem>,
     #[serde(default)]
     pub components: Components,
+    /// Document-wide security requirements, inherited by operations that
+    /// declare none.
+    #[serde(default)]
+    pub security: Option<Vec<SecurityRequirement>>,
 }
 
+/// One OpenAPI security requirement: scheme name → required scopes.
+/// An empty requirement (`{}`) allows anonymous access.
+pub type SecurityRequirement = BTreeMap<String, Vec<String>>;
+
 /// API metadata.
 #[derive(Debug, Deserialize)]
 pub struct Info {
@@ -46,6 +54,31 @@ pub struct Operation {
     pub description: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub responses: BTreeMap<String, ResponseObject>,
+    #[serde(default)]
+    pub security: Option<Vec<SecurityRequirement>>,
+    /// Marked `#deprecated` in the TypeSpec source.
+    #[serde(default)]
+    pub deprecated: bool,
+    /// ISO date after which a deprecated operation may be removed
+    /// (`@extension("x-sunset", "2027-01-31")`).
+    #[serde(default, rename = "x-sunset")]
+    pub sunset: Option<String>,
+}
+
+/// A response declared on an operation, keyed by status code.
+#[derive(Debug, Default, Deserialize)]
+pub struct ResponseObject {
+    #[serde(default)]
+    pub content: BTreeMap<String, MediaTypeObject>,
+}
+
+/// A media type entry within a response.
+#[derive(Debug, Deserialize)]
+pub struct MediaTypeObject {
+    #[serde(default)]
+    pub schema: Option<PropertyObject>,
 }
 
 /// Components section.
@@ -66,6 +99,10 @@ pub struct SchemaObject {
     pub required: Vec<String>,
     #[serde(default)]
     pub properties: BTreeMap<String, PropertyObject>,
+    #[serde(default)]
+    pub example: Option<serde_json::Value>,
+    #[serde(rename = "enum", default)]
+    pub enum_values: Vec<serde_json::Value>,
 }
 
 /// A property within a schema.
@@ -83,4 +120,14 @@ pub struct PropertyObject {
     pub items: Option<Box<PropertyObject>>,
     #[serde(rename = "allOf", default)]
     pub all_of: Vec<PropertyObject>,
+    /// Alternatives, as emitted for responses of several error models
+    /// sharing a status code.
+    #[serde(rename = "anyOf", default)]
+    pub any_of: Vec<PropertyObject>,
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub example: Option<serde_json::Value>,
+    #[serde(rename = "enum", default)]
+    pub enum_values: Vec<serde_json::Value>,
 }
@@ -0,0 +1,68 @@
+// @awa-component: DESKTOP-ApiCompat
+//! Checking that the API sidecar speaks the spec the app was built from.
+//!
+//! The app's typed client is generated from the API spec; the sidecar
+//! reports the spec it was generated from at `GET /api/meta`. After a
+//! partial update the two can differ, and requests then fail or lose
+//! fields without an obvious error. [`verify`] compares them once the
+//! sidecar is ready: release builds refuse to start on a mismatch and show
+//! the startup error screen, debug builds — where the spec and the sidecar
+//! are often rebuilt separately — only log a warning. The result is
+//! available to the UI through [`get_api_compatibility`].
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use nize_api_client::compat::{self, Compatibility};
+use tracing::{info, warn};
+
+use crate::AppServices;
+use crate::startup_error::StartupError;
+
+/// How long to wait for the sidecar's metadata.
+const META_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Compare the sidecar on `port` with this build. `Ok(None)` when its
+/// metadata could not be read, which is not treated as a mismatch.
+pub fn verify(port: u16) -> Result<Option<Compatibility>, StartupError> {
+    let http = reqwest::Client::builder()
+        .timeout(META_TIMEOUT)
+        .build()
+        .map_err(|e| format!("http client: {e}"))?;
+    let base_url = format!("http://127.0.0.1:{port}");
+    let result = tauri::async_runtime::block_on(compat::check(&http, &base_url));
+    let compatibility = match result {
+        Ok(compatibility) => compatibility,
+        Err(e) => {
+            warn!("Could not read the API sidecar's spec hash: {e}");
+            return Ok(None);
+        }
+    };
+    if compatibility.compatible {
+        info!(spec_hash = %compatibility.client_spec_hash, "API sidecar matches the client spec");
+        return Ok(Some(compatibility));
+    }
+    let message = format!(
+        "The API server (version {}, spec {}) was built from a different API spec than \
+         this app (spec {}). Reinstall the app so both parts come from the same release.",
+        compatibility.server.server_version,
+        compatibility.server.spec_hash,
+        compatibility.client_spec_hash
+    );
+    if cfg!(debug_assertions) {
+        warn!("{message}");
+        Ok(Some(compatibility))
+    } else {
+        Err(message.into())
+    }
+}
+
+/// Whether the running sidecar matches this build; `None` when it is not
+/// running or its metadata could not be read.
+#[tauri::command]
+pub async fn get_api_compatibility(
+    state: tauri::State<'_, Mutex<AppServices>>,
+) -> Result<Option<Compatibility>, String> {
+    let guard = state.lock().map_err(|e| format!("lock: {e}"))?;
+    Ok(guard.sidecar.as_ref().and_then(|s| s.compatibility.clone()))
+}
@@ -0,0 +1,318 @@
+// @awa-component: DESKTOP-AppSettings
+//! Per-user desktop preferences: window behavior, tray, update channel and
+//! telemetry opt-in.
+//!
+//! Stored as `settings.json` in the app data directory, stamped with a
+//! schema `version`. Files written by an older version are migrated step by
+//! step ([`MIGRATIONS`]) when loaded and rewritten on the next save; fields
+//! missing from a file take their defaults. A file from a newer version is
+//! read as far as it is understood but never overwritten, so a downgrade
+//! does not lose settings.
+//!
+//! The frontend reads settings with [`get_app_settings`] and changes them
+//! with [`update_app_settings`], which takes a partial object, validates it
+//! against the schema and emits [`CHANGED_EVENT`] with the new settings.
+//!
+//! Settings that must be read before the database and API sidecar start
+//! (database choice, encryption at rest, local mode) are kept in their own
+//! files next to the PGlite data directory instead; they go through
+//! [`load_launch_settings`] and [`save_launch_settings`].
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use nize_core::db::default_pglite_data_dir;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::warn;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Current schema version.
+pub const SETTINGS_VERSION: u64 = 1;
+
+/// Event emitted to all windows after settings change; the payload is the
+/// full [`AppSettings`].
+pub const CHANGED_EVENT: &str = "app-settings-changed";
+
+/// Serializes read-modify-write cycles of the settings file.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Desktop preferences.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    pub window: WindowSettings,
+    pub tray: TraySettings,
+    pub update_channel: UpdateChannel,
+    /// Share anonymous usage counters (see the API's telemetry reporter).
+    pub telemetry_opt_in: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WindowSettings {
+    /// Reopen the main window at its last size and position.
+    pub restore_state: bool,
+    /// Start with the main window hidden.
+    pub start_minimized: bool,
+    /// Hide to the tray instead of quitting when the window is closed.
+    pub close_to_tray: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TraySettings {
+    /// Show the tray icon.
+    pub enabled: bool,
+    /// Badge the tray icon with the unread notification count.
+    pub show_unread_count: bool,
+}
+
+/// Release channel the updater follows.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            window: WindowSettings::default(),
+            tray: TraySettings::default(),
+            update_channel: UpdateChannel::Stable,
+            telemetry_opt_in: false,
+        }
+    }
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            restore_state: true,
+            start_minimized: false,
+            close_to_tray: false,
+        }
+    }
+}
+
+impl Default for TraySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            show_unread_count: true,
+        }
+    }
+}
+
+/// On-disk form: the settings plus their schema version.
+#[derive(Serialize)]
+struct SettingsFile<'a> {
+    version: u64,
+    #[serde(flatten)]
+    settings: &'a AppSettings,
+}
+
+// ---------------------------------------------------------------------------
+// Migrations
+// ---------------------------------------------------------------------------
+
+/// `MIGRATIONS[n]` upgrades a version `n + 1` object to version `n + 2`.
+/// Empty while the schema is at version 1.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[];
+
+/// Bring a stored object up to [`SETTINGS_VERSION`] and parse it. Returns
+/// the settings and the version the file was written with. Fields this
+/// version does not know (from a newer file) are ignored.
+fn migrate(mut stored: Map<String, Value>) -> Result<(AppSettings, u64), String> {
+    let version = match stored.remove("version") {
+        // Hand-written files without a version are read as version 1.
+        None => 1,
+        Some(v) => v
+            .as_u64()
+            .filter(|v| *v >= 1)
+            .ok_or_else(|| format!("invalid settings version: {v}"))?,
+    };
+    for step in MIGRATIONS.iter().skip(version as usize - 1) {
+        step(&mut stored);
+    }
+    let settings = serde_json::from_value(Value::Object(stored))
+        .map_err(|e| format!("invalid settings: {e}"))?;
+    Ok((settings, version))
+}
+
+// ---------------------------------------------------------------------------
+// Persistence
+// ---------------------------------------------------------------------------
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join("settings.json"))
+        .map_err(|e| format!("resolve data dir: {e}"))
+}
+
+/// Read the settings file: `None` when there is none yet.
+fn read(app: &AppHandle) -> Result<Option<(AppSettings, u64)>, String> {
+    let path = settings_path(app)?;
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("read {}: {e}", path.display())),
+    };
+    let stored: Map<String, Value> =
+        serde_json::from_str(&content).map_err(|e| format!("parse {}: {e}", path.display()))?;
+    migrate(stored).map(Some)
+}
+
+/// Load settings, falling back to defaults when missing or unreadable.
+pub fn load_settings(app: &AppHandle) -> AppSettings {
+    match read(app) {
+        Ok(Some((settings, _))) => settings,
+        Ok(None) => AppSettings::default(),
+        Err(e) => {
+            warn!("Using default app settings: {e}");
+            AppSettings::default()
+        }
+    }
+}
+
+fn save_settings(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    write_json(
+        &settings_path(app)?,
+        &SettingsFile {
+            version: SETTINGS_VERSION,
+            settings,
+        },
+    )
+}
+
+/// Write `value` as pretty JSON to `path`, creating its directory. Writes
+/// then renames so a crash never leaves a truncated file.
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("create data dir: {e}"))?;
+    }
+    let json =
+        serde_json::to_string_pretty(value).map_err(|e| format!("serialize settings: {e}"))?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| format!("write {}: {e}", tmp.display()))?;
+    fs::rename(&tmp, path).map_err(|e| format!("replace {}: {e}", path.display()))
+}
+
+// ---------------------------------------------------------------------------
+// Launch settings
+// ---------------------------------------------------------------------------
+
+/// Path of the launch settings file `file_name`, next to the PGlite data
+/// directory.
+fn launch_settings_path(file_name: &str) -> Option<PathBuf> {
+    default_pglite_data_dir()
+        .and_then(|dir| dir.parent().map(Path::to_path_buf))
+        .map(|dir| dir.join(file_name))
+}
+
+/// Load the launch settings file `file_name`, falling back to defaults
+/// when missing or unreadable.
+pub(crate) fn load_launch_settings<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    let Some(path) = launch_settings_path(file_name) else {
+        return T::default();
+    };
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("Using default settings, {} is invalid: {e}", path.display());
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Replace the launch settings file `file_name`.
+pub(crate) fn save_launch_settings<T: Serialize>(
+    file_name: &str,
+    settings: &T,
+) -> Result<(), String> {
+    let path = launch_settings_path(file_name).ok_or("Data directory not available")?;
+    write_json(&path, settings)
+}
+
+/// Apply a partial update: objects merge key by key, everything else
+/// replaces. Unknown keys are rejected and the result must still match
+/// the schema.
+fn apply_patch(settings: &AppSettings, patch: Value) -> Result<AppSettings, String> {
+    fn merge(target: &mut Value, patch: Value, path: &str) -> Result<(), String> {
+        match (target, patch) {
+            (Value::Object(target), Value::Object(patch)) => {
+                for (key, value) in patch {
+                    let path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{path}.{key}")
+                    };
+                    let slot = target
+                        .get_mut(&key)
+                        .ok_or_else(|| format!("unknown setting: {path}"))?;
+                    merge(slot, value, &path)?;
+                }
+                Ok(())
+            }
+            (target, patch) => {
+                *target = patch;
+                Ok(())
+            }
+        }
+    }
+
+    if !patch.is_object() {
+        return Err("settings update must be an object".into());
+    }
+    let mut merged = serde_json::to_value(settings).map_err(|e| e.to_string())?;
+    merge(&mut merged, patch, "")?;
+    serde_json::from_value(merged).map_err(|e| format!("invalid settings: {e}"))
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn get_app_settings(app: AppHandle) -> Result<AppSettings, String> {
+    Ok(load_settings(&app))
+}
+
+/// Merge `patch` into the stored settings, persist them and notify every
+/// window. Unknown fields and wrongly typed values are rejected.
+#[tauri::command]
+pub async fn update_app_settings(app: AppHandle, patch: Value) -> Result<AppSettings, String> {
+    let settings = {
+        let _guard = WRITE_LOCK.lock().map_err(|e| format!("lock: {e}"))?;
+        let current = match read(&app)? {
+            Some((_, version)) if version > SETTINGS_VERSION => {
+                return Err(format!(
+                    "settings were saved by a newer version of Nize (schema {version}); \
+                     update Nize to change them"
+                ));
+            }
+            Some((settings, _)) => settings,
+            None => AppSettings::default(),
+        };
+        let updated = apply_patch(&current, patch)?;
+        if updated == current {
+            return Ok(current);
+        }
+        save_settings(&app, &updated)?;
+        updated
+    };
+    if let Err(e) = app.emit(CHANGED_EVENT, &settings) {
+        warn!("Failed to emit {CHANGED_EVENT}: {e}");
+    }
+    Ok(settings)
+}
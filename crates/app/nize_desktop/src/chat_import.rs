@@ -0,0 +1,284 @@
+// @awa-component: DESKTOP-ChatImport
+//! Import conversations from ChatGPT and Claude data exports.
+//!
+//! [`import_chats`] reads an export — the `.zip` archive as downloaded, or
+//! the `conversations.json` extracted from it — parses it with
+//! [`nize_core::chat_import`] and saves each conversation through the API
+//! sidecar (`POST /conversations`, then `PUT /conversations/{id}/messages`),
+//! authenticated with the webview's cookie. Progress is reported with
+//! [`PROGRESS_EVENT`] after every conversation.
+//!
+//! Imported conversations are recorded in `imported-conversations.json` in
+//! the app data directory, keyed by source app and source id, so importing
+//! the same export again only adds what is new. A recorded conversation
+//! that no longer exists on the server (deleted, or owned by another
+//! account) is imported again.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use nize_core::chat_import::{self, ImportSource, ImportedConversation};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+use tracing::{info, warn};
+
+use crate::quick_capture::{access_token, api_base, check};
+
+/// Event emitted to all windows after each conversation; the payload is an
+/// [`ImportProgress`].
+pub const PROGRESS_EVENT: &str = "chat-import-progress";
+
+/// File holding the conversations inside an export archive.
+const CONVERSATIONS_FILE: &str = "conversations.json";
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Progress of a running import.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportProgress {
+    /// Conversations found in the export.
+    pub total: usize,
+    /// Conversations handled so far, whatever the outcome.
+    pub done: usize,
+    pub imported: usize,
+    /// Already imported earlier.
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// A conversation that could not be imported.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportFailure {
+    pub title: String,
+    pub error: String,
+}
+
+/// Outcome of an import.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    #[serde(flatten)]
+    pub progress: ImportProgress,
+    pub failures: Vec<ImportFailure>,
+}
+
+/// Conversations imported so far: import key → Nize conversation ID.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Ledger {
+    conversations: HashMap<String, String>,
+}
+
+// ---------------------------------------------------------------------------
+// Reading exports
+// ---------------------------------------------------------------------------
+
+/// Read `conversations.json` from an export archive or a bare JSON file.
+fn read_export(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("open {}: {e}", path.display()))?;
+    let mut magic = [0u8; 4];
+    let is_zip = file.read_exact(&mut magic).is_ok() && magic == *b"PK\x03\x04";
+    if !is_zip {
+        return fs::read_to_string(path).map_err(|e| format!("read {}: {e}", path.display()));
+    }
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(path).map_err(|e| e.to_string())?)
+        .map_err(|e| format!("open archive {}: {e}", path.display()))?;
+    // Exports keep the file at the root; accept it one folder down for
+    // archives that were unpacked and zipped again.
+    let name = archive
+        .file_names()
+        .filter(|n| *n == CONVERSATIONS_FILE || n.ends_with(&format!("/{CONVERSATIONS_FILE}")))
+        .min_by_key(|n| n.len())
+        .map(str::to_string)
+        .ok_or_else(|| format!("{CONVERSATIONS_FILE} not found in {}", path.display()))?;
+    let mut json = String::new();
+    archive
+        .by_name(&name)
+        .map_err(|e| format!("read {name}: {e}"))?
+        .read_to_string(&mut json)
+        .map_err(|e| format!("read {name}: {e}"))?;
+    Ok(json)
+}
+
+// ---------------------------------------------------------------------------
+// Persistence
+// ---------------------------------------------------------------------------
+
+fn ledger_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join("imported-conversations.json"))
+        .map_err(|e| format!("resolve data dir: {e}"))
+}
+
+/// Load the ledger, starting empty when missing or unreadable.
+fn load_ledger(path: &Path) -> Ledger {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable {}: {e}", path.display());
+            Ledger::default()
+        }),
+        Err(_) => Ledger::default(),
+    }
+}
+
+fn save_ledger(path: &Path, ledger: &Ledger) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("create data dir: {e}"))?;
+    }
+    let json =
+        serde_json::to_string_pretty(ledger).map_err(|e| format!("serialize ledger: {e}"))?;
+    // Write then rename so a crash never leaves a truncated file.
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json).map_err(|e| format!("write {}: {e}", tmp.display()))?;
+    fs::rename(&tmp, path).map_err(|e| format!("replace {}: {e}", path.display()))
+}
+
+// ---------------------------------------------------------------------------
+// API
+// ---------------------------------------------------------------------------
+
+/// Whether a previously imported conversation is still visible to the
+/// current user.
+async fn still_exists(
+    http: &reqwest::Client,
+    base: &str,
+    token: &str,
+    id: &str,
+) -> Result<bool, String> {
+    let resp = http
+        .get(format!("{base}/conversations/{id}"))
+        .bearer_auth(token)
+        .send()
+        .await
+        .map_err(|e| format!("look up conversation: {e}"))?;
+    match resp.status() {
+        s if s.is_success() => Ok(true),
+        reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::FORBIDDEN => Ok(false),
+        _ => check(resp).await.map(|_| true),
+    }
+}
+
+async fn save_conversation(
+    http: &reqwest::Client,
+    base: &str,
+    token: &str,
+    conversation: &ImportedConversation,
+) -> Result<String, String> {
+    let created = http
+        .post(format!("{base}/conversations"))
+        .bearer_auth(token)
+        .json(&serde_json::json!({ "title": conversation.title }))
+        .send()
+        .await
+        .map_err(|e| format!("create conversation: {e}"))?;
+    let created = check(created).await?;
+    let id = created["id"]
+        .as_str()
+        .ok_or("create conversation: missing id")?
+        .to_string();
+
+    let saved = match http
+        .put(format!("{base}/conversations/{id}/messages"))
+        .bearer_auth(token)
+        .json(&serde_json::json!({ "messages": conversation.messages }))
+        .send()
+        .await
+    {
+        Ok(resp) => check(resp).await.map(|_| ()),
+        Err(e) => Err(format!("save messages: {e}")),
+    };
+    if let Err(e) = saved {
+        // Don't leave an empty conversation behind.
+        let _ = http
+            .delete(format!("{base}/conversations/{id}"))
+            .bearer_auth(token)
+            .send()
+            .await;
+        return Err(e);
+    }
+
+    Ok(id)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Import every conversation in the export at `path` that has not been
+/// imported before. Conversations that fail are reported and the import
+/// carries on.
+#[tauri::command]
+pub async fn import_chats(
+    app: AppHandle,
+    window: WebviewWindow,
+    path: PathBuf,
+    source: ImportSource,
+) -> Result<ImportReport, String> {
+    let token = access_token(&window)?;
+    let base = api_base(&app)?;
+
+    let json = tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || read_export(&path)
+    })
+    .await
+    .map_err(|e| format!("read export: {e}"))??;
+    let conversations = chat_import::parse(source, &json).map_err(|e| e.to_string())?;
+
+    let ledger_path = ledger_path(&app)?;
+    let mut ledger = load_ledger(&ledger_path);
+    let http = reqwest::Client::new();
+    let mut progress = ImportProgress {
+        total: conversations.len(),
+        ..Default::default()
+    };
+    let mut failures = Vec::new();
+
+    for conversation in &conversations {
+        let key = conversation.key();
+        let known = match ledger.conversations.get(&key) {
+            Some(id) => still_exists(&http, &base, &token, id).await?,
+            None => false,
+        };
+        if known {
+            progress.skipped += 1;
+        } else {
+            match save_conversation(&http, &base, &token, conversation).await {
+                Ok(id) => {
+                    ledger.conversations.insert(key, id);
+                    save_ledger(&ledger_path, &ledger)?;
+                    progress.imported += 1;
+                }
+                Err(error) => {
+                    warn!(title = %conversation.title, "chat import failed: {error}");
+                    progress.failed += 1;
+                    failures.push(ImportFailure {
+                        title: conversation.title.clone(),
+                        error,
+                    });
+                }
+            }
+        }
+        progress.done += 1;
+        if let Err(e) = app.emit(PROGRESS_EVENT, &progress) {
+            warn!("Failed to emit {PROGRESS_EVENT}: {e}");
+        }
+    }
+
+    info!(
+        %source,
+        path = %path.display(),
+        imported = progress.imported,
+        skipped = progress.skipped,
+        failed = progress.failed,
+        "chat import finished"
+    );
+    Ok(ImportReport { progress, failures })
+}
@@ -0,0 +1,165 @@
+// @awa-component: DESKTOP-DbEncryption
+//! Optional encryption at rest for the bundled PGlite database.
+//!
+//! When enabled, the PGlite data directory is replaced by an AES-256-GCM
+//! sealed snapshot (see [`PgLiteManager::with_encryption_key`]) whose key is
+//! generated once and held in the OS keychain (macOS Keychain, Windows
+//! Credential Manager, Secret Service on Linux) — never on disk.
+//!
+//! The setting is persisted in `db-encryption.json` next to the data
+//! directory, because it must be read before the database starts. Changes
+//! apply on the next launch, when the existing data is sealed or unsealed in
+//! place. An external `DATABASE_URL` database is not affected: encrypt it on
+//! the server (e.g. an encrypted volume or the provider's at-rest option).
+
+use std::path::Path;
+
+use nize_core::db::{PgLiteManager, generate_encryption_key};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::app_settings;
+
+/// Keychain service and account holding the PGlite key.
+const KEYCHAIN_SERVICE: &str = "nize";
+const KEYCHAIN_ACCOUNT: &str = "pglite-data-key";
+
+/// Launch settings file holding the encryption setting.
+const SETTINGS_FILE: &str = "db-encryption.json";
+
+/// Persisted encryption settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DbEncryptionSettings {
+    pub enabled: bool,
+}
+
+/// Encryption state reported to the UI.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DbEncryptionStatus {
+    /// Setting to apply on the next launch.
+    pub enabled: bool,
+    /// Whether the database is currently stored encrypted.
+    pub active: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Settings persistence
+// ---------------------------------------------------------------------------
+
+fn load_settings() -> DbEncryptionSettings {
+    app_settings::load_launch_settings(SETTINGS_FILE)
+}
+
+fn save_settings(settings: &DbEncryptionSettings) -> Result<(), String> {
+    app_settings::save_launch_settings(SETTINGS_FILE, settings)
+}
+
+// ---------------------------------------------------------------------------
+// Keychain
+// ---------------------------------------------------------------------------
+
+fn keychain_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).map_err(|e| format!("keychain: {e}"))
+}
+
+/// Read the key, generating and storing one on first use.
+fn load_or_create_key() -> Result<String, String> {
+    let entry = keychain_entry()?;
+    match entry.get_password() {
+        Ok(key) => Ok(key),
+        Err(keyring::Error::NoEntry) => {
+            let key = generate_encryption_key();
+            entry
+                .set_password(&key)
+                .map_err(|e| format!("store key in keychain: {e}"))?;
+            info!("Generated PGlite encryption key in the OS keychain");
+            Ok(key)
+        }
+        Err(e) => Err(format!("read key from keychain: {e}")),
+    }
+}
+
+fn load_key() -> Result<String, String> {
+    keychain_entry()?
+        .get_password()
+        .map_err(|e| format!("read key from keychain: {e}"))
+}
+
+// ---------------------------------------------------------------------------
+// Startup
+// ---------------------------------------------------------------------------
+
+/// Apply the encryption setting to `pglite` before it starts, sealing or
+/// unsealing existing data when the setting changed since the last launch.
+pub fn prepare(
+    pglite: PgLiteManager,
+    bun_bin: &Path,
+    server_script: &Path,
+) -> Result<PgLiteManager, String> {
+    let sealed = pglite.sealed_path().exists();
+
+    if load_settings().enabled {
+        let key = load_or_create_key()?;
+        if pglite.has_plaintext_data() {
+            if sealed {
+                return Err(format!(
+                    "both {} and a plaintext data directory exist; remove one",
+                    pglite.sealed_path().display()
+                ));
+            }
+            info!("Encrypting existing PGlite data");
+            pglite
+                .seal_data_dir(&key, bun_bin, server_script)
+                .map_err(|e| format!("encrypt PGlite data: {e}"))?;
+        }
+        return Ok(pglite.with_encryption_key(key));
+    }
+
+    if sealed {
+        info!("Decrypting PGlite data");
+        pglite
+            .unseal_data_dir(&load_key()?, bun_bin, server_script)
+            .map_err(|e| format!("decrypt PGlite data: {e}"))?;
+    }
+
+    Ok(pglite)
+}
+
+/// Warn that the encryption setting does not cover an external database.
+pub fn note_external_database() {
+    if load_settings().enabled {
+        warn!(
+            "Database encryption applies to the bundled PGlite database only; \
+             configure at-rest encryption on the external database server"
+        );
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Current encryption setting and whether the data is stored encrypted.
+#[tauri::command]
+pub async fn get_db_encryption() -> Result<DbEncryptionStatus, String> {
+    let active = PgLiteManager::with_default_data_dir()
+        .map(|mgr| mgr.sealed_path().exists())
+        .unwrap_or(false);
+    Ok(DbEncryptionStatus {
+        enabled: load_settings().enabled,
+        active,
+    })
+}
+
+/// Turn encryption at rest on or off. Takes effect on the next launch.
+#[tauri::command]
+pub async fn set_db_encryption(enabled: bool) -> Result<DbEncryptionStatus, String> {
+    if enabled {
+        // Fail now, not at the next launch, when the keychain is unusable.
+        load_or_create_key()?;
+    }
+    save_settings(&DbEncryptionSettings { enabled })?;
+    get_db_encryption().await
+}
@@ -0,0 +1,153 @@
+// @awa-component: DESKTOP-ExternalDatabase
+//! Switching between the bundled PGlite database and an external
+//! PostgreSQL server.
+//!
+//! After the admin copied the data into an external database (`POST
+//! /admin/database/transfer` or `nize_cli migrate-database`), the app can
+//! be pointed at it with [`use_external_database`]. The choice is kept in
+//! `database.json` next to the PGlite data directory, because it must be
+//! read before the database starts, and the URL — which usually holds a
+//! password — in the OS keychain. It applies on the next launch.
+//!
+//! The PGlite data directory is left as it was, so
+//! [`use_bundled_database`] rolls back to it; anything written to the
+//! external database in the meantime is not carried back. A `DATABASE_URL`
+//! environment variable overrides both.
+
+use std::sync::OnceLock;
+
+use nize_core::db_transfer::validate_target_url;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::app_settings;
+
+/// Keychain service and account holding the external database URL.
+const KEYCHAIN_SERVICE: &str = "nize";
+const KEYCHAIN_ACCOUNT: &str = "external-database-url";
+
+/// Launch settings file holding the database choice.
+const SETTINGS_FILE: &str = "database.json";
+
+/// Whether this launch runs on an external database, set by
+/// [`external_url`].
+static IN_USE: OnceLock<bool> = OnceLock::new();
+
+/// Persisted database choice.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct DatabaseSettings {
+    /// Use the URL in the keychain instead of the bundled database.
+    external: bool,
+}
+
+/// Database choice reported to the UI.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseChoice {
+    /// The external database is chosen for the next launch.
+    pub external: bool,
+    /// Its URL with the password masked.
+    pub url: Option<String>,
+    /// `DATABASE_URL` is set and overrides the choice.
+    pub env_override: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Settings persistence
+// ---------------------------------------------------------------------------
+
+fn load_settings() -> DatabaseSettings {
+    app_settings::load_launch_settings(SETTINGS_FILE)
+}
+
+fn save_settings(settings: &DatabaseSettings) -> Result<(), String> {
+    app_settings::save_launch_settings(SETTINGS_FILE, settings)
+}
+
+fn keychain_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).map_err(|e| format!("keychain: {e}"))
+}
+
+fn stored_url() -> Option<String> {
+    match keychain_entry().and_then(|e| e.get_password().map_err(|e| e.to_string())) {
+        Ok(url) => Some(url),
+        Err(e) => {
+            warn!("External database URL unavailable: {e}");
+            None
+        }
+    }
+}
+
+/// `url` with its password replaced by `***`.
+fn masked(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) if parsed.password().is_some() => {
+            let _ = parsed.set_password(Some("***"));
+            parsed.to_string()
+        }
+        Ok(parsed) => parsed.to_string(),
+        Err(_) => "(unparseable URL)".to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Startup
+// ---------------------------------------------------------------------------
+
+/// Whether this launch runs on an external database rather than PGlite.
+pub fn is_external() -> bool {
+    IN_USE.get().copied().unwrap_or(false)
+}
+
+/// The external database to use instead of PGlite, if any:
+/// `DATABASE_URL`, else the chosen one. A chosen database whose URL is
+/// missing from the keychain falls back to PGlite.
+pub fn external_url() -> Option<String> {
+    let url = match std::env::var("DATABASE_URL") {
+        Ok(url) => Some(url),
+        Err(_) if load_settings().external => stored_url(),
+        Err(_) => None,
+    };
+    let _ = IN_USE.set(url.is_some());
+    url
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// The database chosen for the next launch.
+#[tauri::command]
+pub async fn get_database_choice() -> Result<DatabaseChoice, String> {
+    let external = load_settings().external;
+    Ok(DatabaseChoice {
+        external,
+        url: external.then(stored_url).flatten().map(|u| masked(&u)),
+        env_override: std::env::var_os("DATABASE_URL").is_some(),
+    })
+}
+
+/// Use the external database at `url` from the next launch. Only switch to
+/// a database a transfer verified.
+#[tauri::command]
+pub async fn use_external_database(url: String) -> Result<DatabaseChoice, String> {
+    validate_target_url(&url).map_err(|e| e.to_string())?;
+    keychain_entry()?
+        .set_password(url.trim())
+        .map_err(|e| format!("store URL in keychain: {e}"))?;
+    save_settings(&DatabaseSettings { external: true })?;
+    get_database_choice().await
+}
+
+/// Go back to the bundled database, as it was when the data was copied,
+/// from the next launch.
+#[tauri::command]
+pub async fn use_bundled_database() -> Result<DatabaseChoice, String> {
+    save_settings(&DatabaseSettings { external: false })?;
+    match keychain_entry()?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => warn!("Failed to remove external database URL from keychain: {e}"),
+    }
+    get_database_choice().await
+}
@@ -0,0 +1,63 @@
+// @awa-component: DESKTOP-Hardware
+//! Hardware probe for gating local models.
+//!
+//! The machine is probed once per launch (see [`nize_core::hardware`]) and
+//! the profile handed to the API sidecar in its environment, which stores
+//! it so the server can refuse local embedding and transcription providers
+//! the machine cannot run and pick sensible defaults on the first run. The
+//! frontend reads the same profile with [`get_hardware_profile`] to grey out
+//! what is unsupported.
+
+use std::process::Command;
+use std::sync::OnceLock;
+
+use nize_core::hardware::{self, Capabilities, HardwareProfile};
+use serde::Serialize;
+use tracing::info;
+
+/// Environment variable the sidecar reads the profile from.
+const PROFILE_ENV: &str = "NIZE_HARDWARE_PROFILE";
+
+/// Profile probed for this launch.
+static PROFILE: OnceLock<HardwareProfile> = OnceLock::new();
+
+/// The machine and what it can run, for the UI.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HardwareReport {
+    pub profile: HardwareProfile,
+    pub capabilities: Capabilities,
+}
+
+/// This launch's profile, probing on first use.
+fn profile() -> &'static HardwareProfile {
+    PROFILE.get_or_init(|| {
+        let profile = hardware::probe();
+        info!(
+            cpu = %profile.cpu.brand,
+            memory_gib = profile.memory.total_bytes / (1024 * 1024 * 1024),
+            gpus = profile.gpus.len(),
+            "Probed hardware"
+        );
+        profile
+    })
+}
+
+/// Pass the probed profile to the sidecar `cmd`.
+pub fn configure_sidecar(cmd: &mut Command) {
+    if let Ok(json) = serde_json::to_string(profile()) {
+        cmd.env(PROFILE_ENV, json);
+    }
+}
+
+/// The machine's hardware and which local features it supports.
+#[tauri::command]
+pub async fn get_hardware_profile() -> Result<HardwareReport, String> {
+    let profile = tauri::async_runtime::spawn_blocking(|| profile().clone())
+        .await
+        .map_err(|e| format!("probe hardware: {e}"))?;
+    Ok(HardwareReport {
+        capabilities: hardware::assess(&profile),
+        profile,
+    })
+}
@@ -11,7 +11,24 @@ use serde::Deserialize;
 use tauri::Manager;
 use tracing::{error, info};
 
+use crate::startup_error::StartupError;
+
+mod api_compat;
+mod app_settings;
+mod chat_import;
+mod db_encryption;
+mod external_database;
+mod hardware;
+mod local_mode;
 mod mcp_clients;
+mod notifications;
+mod offline_queue;
+mod quick_capture;
+mod shutdown_report;
+mod startup_error;
+mod startup_sweep;
+mod storage;
+mod system_status;
 
 /// In dev builds, rebuild sidecar binaries (`nize_desktop_server`,
 /// `nize_terminator`) so they pick up any Rust source changes since the last
@@ -86,6 +103,8 @@ struct ApiSidecar {
     port: u16,
     /// Bound port of the MCP server.
     mcp_port: u16,
+    /// Whether the sidecar was generated from the client's spec.
+    compatibility: Option<nize_api_client::compat::Compatibility>,
 }
 
 // @awa-impl: PLAN-012-3.1 — nize-web sidecar state
@@ -110,14 +129,18 @@ struct AppServices {
     terminator: Option<Child>,
     /// Path to the cleanup manifest file.
     manifest_path: Option<PathBuf>,
+    /// Why the services did not start, for the error screen.
+    startup_error: Option<StartupError>,
 }
 
-/// Spawns the `nize_desktop_server` binary and reads the port from its JSON stdout line.
+/// Spawns the `nize_desktop_server` binary and reads the port from its JSON
+/// stdout line, or the failed startup self-check it printed instead.
 fn start_api_sidecar(
     database_url: &str,
     max_connections: u32,
     manifest_path: Option<&Path>,
-) -> Result<ApiSidecar, String> {
+    data_dir: Option<&Path>,
+) -> Result<ApiSidecar, StartupError> {
     let exe = std::env::current_exe().map_err(|e| format!("current_exe: {e}"))?;
     let sidecar_path = exe
         .parent()
@@ -152,6 +175,27 @@ fn start_api_sidecar(
         cmd.arg("--terminator-manifest").arg(manifest);
     }
 
+    // Lets the storage monitor measure the bundled database on disk.
+    if let Some(dir) = data_dir {
+        cmd.arg("--data-dir").arg(dir);
+    }
+
+    // Error message translations shipped as resources.
+    if let Some(locales) = locales_dir(&exe) {
+        cmd.arg("--locales-dir").arg(locales);
+    }
+
+    // Resume from the caches saved when the previous sidecar stopped.
+    if let Some(path) = warm_start_path() {
+        cmd.arg("--warm-start").arg(path);
+    }
+
+    // Local single-user mode: the token travels in the environment.
+    local_mode::configure_sidecar(&mut cmd);
+
+    // Hardware profile for gating local models.
+    hardware::configure_sidecar(&mut cmd);
+
     let mut child = cmd
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
@@ -166,8 +210,16 @@ fn start_api_sidecar(
         .read_line(&mut first_line)
         .map_err(|e| format!("read sidecar stdout: {e}"))?;
 
-    let ready: SidecarReady =
-        serde_json::from_str(&first_line).map_err(|e| format!("parse sidecar JSON: {e}"))?;
+    if first_line.trim().is_empty() {
+        return Err("API sidecar exited during startup".into());
+    }
+    let ready: SidecarReady = match serde_json::from_str(&first_line) {
+        Ok(ready) => ready,
+        Err(e) => {
+            return Err(startup_error::parse_sidecar_failure(&first_line)
+                .unwrap_or_else(|| format!("parse sidecar JSON: {e}").into()));
+        }
+    };
 
     info!(
         port = ready.port,
@@ -175,6 +227,14 @@ fn start_api_sidecar(
         "API sidecar ready"
     );
 
+    let compatibility = match api_compat::verify(ready.port) {
+        Ok(compatibility) => compatibility,
+        Err(e) => {
+            kill_child_gracefully(&mut child);
+            return Err(e);
+        }
+    };
+
     let client = ApiClient::new(&format!("http://127.0.0.1:{}", ready.port));
 
     Ok(ApiSidecar {
@@ -182,9 +242,21 @@ fn start_api_sidecar(
         _process: child,
         port: ready.port,
         mcp_port: ready.mcp_port,
+        compatibility,
     })
 }
 
+/// Bundled error message translations: `Contents/Resources/locales` in the
+/// macOS .app, or the API crate's `locales` directory in dev.
+fn locales_dir(exe: &Path) -> Option<PathBuf> {
+    let resource = exe
+        .parent()
+        .and_then(Path::parent)
+        .map(|p| p.join("Resources").join("locales"));
+    let dev = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../lib/nize_api/locales");
+    resource.into_iter().chain([dev]).find(|p| p.is_dir())
+}
+
 // @awa-impl: PLAN-012-3.2 — spawn nize-web sidecar
 // @awa-impl: PLAN-021 — nize-web sidecar only used in production (not dev)
 /// Spawns `bun nize-web-server.mjs --port=0` and reads the port from its JSON stdout line.
@@ -276,6 +348,11 @@ fn manifest_path() -> PathBuf {
     std::env::temp_dir().join(format!("nize-{pid}-cleanup.manifest"))
 }
 
+/// Warm-start state of the API sidecar: `<data dir>/nize/warm-start.json`.
+fn warm_start_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| d.join("nize").join("warm-start.json"))
+}
+
 // @awa-impl: PLAN-005 — create manifest and spawn terminator
 /// Creates an empty manifest file and spawns `nize_terminator` watching our PID.
 fn create_manifest_and_spawn_terminator(manifest: &Path) -> Result<Child, String> {
@@ -390,6 +467,12 @@ pub fn run() {
     #[cfg(debug_assertions)]
     rebuild_sidecars();
 
+    // Report cleanup results left by a previous unclean shutdown.
+    shutdown_report::report_previous_shutdowns(&std::env::temp_dir());
+
+    // Clean up after runs whose terminator never got to (e.g. a machine crash).
+    let sweep = startup_sweep::sweep_stale_manifests(&std::env::temp_dir());
+
     // @awa-impl: PLAN-005 — spawn terminator before managed processes
     // 1. Create empty manifest file.
     // 2. Spawn nize_terminator watching our PID.
@@ -407,17 +490,19 @@ pub fn run() {
         }
     };
 
-    // External database override via environment variable.
-    if let Ok(db_url) = std::env::var("DATABASE_URL") {
-        info!(url = %db_url, "Using DATABASE_URL from environment");
-
-        let sidecar = match start_api_sidecar(&db_url, 5, Some(&manifest_path)) {
-            Ok(s) => Some(s),
-            Err(e) => {
-                error!("Failed to start API sidecar: {e}");
-                None
-            }
-        };
+    // External database: DATABASE_URL, or the one chosen in the app.
+    if let Some(db_url) = external_database::external_url() {
+        info!("Using an external database instead of PGlite");
+        db_encryption::note_external_database();
+
+        let (sidecar, startup_error) =
+            match start_api_sidecar(&db_url, 5, Some(&manifest_path), None) {
+                Ok(s) => (Some(s), None),
+                Err(e) => {
+                    error!("Failed to start API sidecar: {e}");
+                    (None, Some(e))
+                }
+            };
 
         return run_tauri(AppServices {
             sidecar,
@@ -426,6 +511,7 @@ pub fn run() {
             _pglite: None,
             terminator,
             manifest_path: Some(manifest_path),
+            startup_error,
         });
     }
 
@@ -464,10 +550,11 @@ pub fn run() {
         };
 
         if !server_script.exists() {
-            error!(
+            let message = format!(
                 "pglite-server.mjs not found at {}; set DATABASE_URL to use an external database",
                 server_script.display()
             );
+            error!("{message}");
             return run_tauri(AppServices {
                 sidecar: None,
                 #[cfg(not(debug_assertions))]
@@ -475,11 +562,12 @@ pub fn run() {
                 _pglite: None,
                 terminator,
                 manifest_path: Some(manifest_path),
+                startup_error: Some(message.into()),
             });
         }
 
         // PGlite mode: spawn node pglite-server.mjs.
-        let mut pglite = match PgLiteManager::with_default_data_dir() {
+        let pglite = match PgLiteManager::with_default_data_dir() {
             Ok(mgr) => mgr,
             Err(e) => {
                 error!("Failed to create PgLiteManager: {e}");
@@ -490,10 +578,38 @@ pub fn run() {
                     _pglite: None,
                     terminator,
                     manifest_path: Some(manifest_path),
+                    startup_error: Some(format!("Failed to set up the database: {e}").into()),
                 });
             }
         };
 
+        // @awa-impl: DESKTOP-DbEncryption — seal or unseal before starting.
+        let mut pglite = match db_encryption::prepare(pglite, &bun_bin, &server_script) {
+            Ok(mgr) => mgr,
+            Err(e) => {
+                error!("Failed to apply database encryption setting: {e}");
+                return run_tauri(AppServices {
+                    sidecar: None,
+                    #[cfg(not(debug_assertions))]
+                    nize_web: None,
+                    _pglite: None,
+                    terminator,
+                    manifest_path: Some(manifest_path),
+                    startup_error: Some(
+                        format!("Failed to apply database encryption setting: {e}").into(),
+                    ),
+                });
+            }
+        };
+
+        // A crashed PGlite leaves its data directory locked; reclaim it
+        // unless another instance may be using it.
+        if !sweep.other_instance_running
+            && let Err(e) = pglite.reclaim_stale_lock()
+        {
+            error!("Failed to reclaim PGlite data directory: {e}");
+        }
+
         if let Err(e) = pglite.start(&bun_bin, &server_script) {
             error!("PGlite start failed: {e}");
             return run_tauri(AppServices {
@@ -503,6 +619,7 @@ pub fn run() {
                 _pglite: None,
                 terminator,
                 manifest_path: Some(manifest_path),
+                startup_error: Some(format!("The bundled database failed to start: {e}").into()),
             });
         }
 
@@ -516,13 +633,14 @@ pub fn run() {
         let db_url = pglite.connection_url();
         info!(url = %db_url, "PGlite started");
 
-        let sidecar = match start_api_sidecar(&db_url, 1, Some(&manifest_path)) {
-            Ok(s) => Some(s),
-            Err(e) => {
-                error!("Failed to start API sidecar: {e}");
-                None
-            }
-        };
+        let (sidecar, startup_error) =
+            match start_api_sidecar(&db_url, 1, Some(&manifest_path), Some(pglite.data_dir())) {
+                Ok(s) => (Some(s), None),
+                Err(e) => {
+                    error!("Failed to start API sidecar: {e}");
+                    (None, Some(e))
+                }
+            };
 
         // @awa-impl: PLAN-012-3.4 — start nize-web sidecar after API sidecar
         // @awa-impl: PLAN-021 — in dev, Tauri loads Next.js directly via devUrl;
@@ -574,6 +692,7 @@ pub fn run() {
             _pglite: Some(pglite),
             terminator,
             manifest_path: Some(manifest_path),
+            startup_error,
         }
     };
 
@@ -586,15 +705,42 @@ fn run_tauri(services: AppServices) {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .plugin(tauri_plugin_notification::init())
         .manage(Mutex::new(services))
         .invoke_handler(tauri::generate_handler![
             hello_world,
             get_api_port,
             get_mcp_port,
             get_nize_web_port,
+            startup_error::get_startup_error,
+            api_compat::get_api_compatibility,
+            app_settings::get_app_settings,
+            app_settings::update_app_settings,
+            chat_import::import_chats,
+            db_encryption::get_db_encryption,
+            db_encryption::set_db_encryption,
+            external_database::get_database_choice,
+            external_database::use_external_database,
+            external_database::use_bundled_database,
+            hardware::get_hardware_profile,
+            local_mode::get_local_mode,
+            local_mode::set_local_mode,
+            local_mode::get_local_token,
             mcp_clients::get_mcp_client_statuses,
             mcp_clients::configure_mcp_client,
-            mcp_clients::remove_mcp_client
+            mcp_clients::apply_mcp_client_link,
+            mcp_clients::remove_mcp_client,
+            quick_capture::get_quick_capture_settings,
+            quick_capture::set_quick_capture_shortcut,
+            quick_capture::submit_quick_capture,
+            quick_capture::hide_quick_capture,
+            offline_queue::get_offline_queue,
+            offline_queue::replay_offline_queue,
+            offline_queue::retry_queued_request,
+            offline_queue::discard_queued_request,
+            storage::get_storage_usage,
+            system_status::get_system_status
         ])
         .setup(|app| {
             #[cfg(debug_assertions)]
@@ -603,6 +749,16 @@ fn run_tauri(services: AppServices) {
                     win.open_devtools();
                 }
             }
+
+            // A taken or malformed shortcut must not prevent startup.
+            let settings = quick_capture::load_settings(app.handle());
+            if let Err(e) = quick_capture::register_shortcut(app.handle(), &settings.shortcut) {
+                error!("Failed to register quick-capture shortcut: {e}");
+            }
+
+            notifications::spawn_bridge(app.handle().clone());
+            system_status::spawn_monitor(app.handle().clone());
+            offline_queue::init(app.handle())?;
             Ok(())
         })
         .build(tauri::generate_context!())
@@ -0,0 +1,110 @@
+// @awa-component: DESKTOP-LocalMode
+//! Local single-user mode: use the app without registering or signing in.
+//!
+//! When enabled, a fresh random token is minted at every launch and handed
+//! to the API sidecar in its environment. The sidecar provisions a single
+//! local user and accepts the token as that user's access token; the
+//! webview exchanges it for a regular session (`POST /auth/local`) and the
+//! desktop's own API calls use it when nobody is signed in.
+//!
+//! The setting is persisted in `local-mode.json` next to the data
+//! directory, because it must be read before the sidecar starts. Changes
+//! apply on the next launch.
+
+use std::process::Command;
+use std::sync::OnceLock;
+
+use nize_core::auth::local::generate_token;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::app_settings;
+
+/// Environment variable the sidecar reads the token from.
+const TOKEN_ENV: &str = "NIZE_LOCAL_TOKEN";
+
+/// Launch settings file holding the local mode setting.
+const SETTINGS_FILE: &str = "local-mode.json";
+
+/// Token minted for this launch, if local mode is on.
+static TOKEN: OnceLock<String> = OnceLock::new();
+
+/// Persisted local mode settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalModeSettings {
+    pub enabled: bool,
+}
+
+/// Local mode state reported to the UI.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalModeStatus {
+    /// Setting to apply on the next launch.
+    pub enabled: bool,
+    /// Whether this launch runs in local mode.
+    pub active: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Settings persistence
+// ---------------------------------------------------------------------------
+
+fn load_settings() -> LocalModeSettings {
+    app_settings::load_launch_settings(SETTINGS_FILE)
+}
+
+fn save_settings(settings: &LocalModeSettings) -> Result<(), String> {
+    app_settings::save_launch_settings(SETTINGS_FILE, settings)
+}
+
+// ---------------------------------------------------------------------------
+// Startup
+// ---------------------------------------------------------------------------
+
+/// Pass the launch's local token to the sidecar `cmd` when local mode is
+/// on, minting it on first use. Otherwise make sure the sidecar does not
+/// inherit one.
+pub fn configure_sidecar(cmd: &mut Command) {
+    if !load_settings().enabled {
+        cmd.env_remove(TOKEN_ENV);
+        return;
+    }
+    let token = TOKEN.get_or_init(|| {
+        info!("Local single-user mode enabled");
+        generate_token()
+    });
+    cmd.env(TOKEN_ENV, token);
+}
+
+/// The local token of this launch, if local mode is on.
+pub(crate) fn token() -> Option<&'static str> {
+    TOKEN.get().map(String::as_str)
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+/// Current local mode setting and whether this launch uses it.
+#[tauri::command]
+pub async fn get_local_mode() -> Result<LocalModeStatus, String> {
+    Ok(LocalModeStatus {
+        enabled: load_settings().enabled,
+        active: token().is_some(),
+    })
+}
+
+/// Turn local mode on or off. Takes effect on the next launch.
+#[tauri::command]
+pub async fn set_local_mode(enabled: bool) -> Result<LocalModeStatus, String> {
+    save_settings(&LocalModeSettings { enabled })?;
+    get_local_mode().await
+}
+
+/// The local token for the webview to sign in with, or `None` when this
+/// launch is not in local mode.
+#[tauri::command]
+pub async fn get_local_token() -> Result<Option<String>, String> {
+    Ok(token().map(str::to_string))
+}
@@ -499,6 +499,51 @@ pub fn configure_client(client: McpClient, mcp_port: u16, token: &str) -> Result
     }
 }
 
+// ---------------------------------------------------------------------------
+// Deep links
+// ---------------------------------------------------------------------------
+
+/// Prefix of the links produced by `GET /auth/mcp-tokens/{id}/client-config?format=deeplink`.
+const CONFIGURE_LINK_PREFIX: &str = "nize://mcp-clients/configure?";
+
+/// Parse a `nize://mcp-clients/configure?client=..&port=..&token=..` link.
+fn parse_configure_link(link: &str) -> Result<(McpClient, u16, String), String> {
+    let query = link
+        .strip_prefix(CONFIGURE_LINK_PREFIX)
+        .ok_or("not a Nize MCP client link")?;
+
+    let (mut client, mut port, mut token) = (None, None, None);
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        match key {
+            "client" => {
+                client = serde_json::from_value::<McpClient>(serde_json::json!(value)).ok();
+            }
+            "port" => port = value.parse::<u16>().ok(),
+            // Tokens are alphanumeric, so no percent-decoding is needed.
+            "token" if value.chars().all(|c| c.is_ascii_alphanumeric()) => {
+                token = Some(value.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    Ok((
+        client.ok_or("link has no valid client")?,
+        port.ok_or("link has no valid port")?,
+        token
+            .filter(|t| !t.is_empty())
+            .ok_or("link has no valid token")?,
+    ))
+}
+
+/// Configure the client named in a deep link.
+pub fn apply_configure_link(link: &str) -> Result<McpClient, String> {
+    let (client, port, token) = parse_configure_link(link)?;
+    configure_client(client, port, &token)?;
+    Ok(client)
+}
+
 // ---------------------------------------------------------------------------
 // Config removal
 // ---------------------------------------------------------------------------
@@ -552,6 +597,12 @@ pub async fn configure_mcp_client(
     Ok(format!("{} configured successfully", client.display_name()))
 }
 
+#[tauri::command]
+pub async fn apply_mcp_client_link(link: String) -> Result<String, String> {
+    let client = apply_configure_link(&link)?;
+    Ok(format!("{} configured successfully", client.display_name()))
+}
+
 // @awa-impl: PLAN-011-2.5
 #[tauri::command]
 pub async fn remove_mcp_client(client: McpClient) -> Result<(), String> {
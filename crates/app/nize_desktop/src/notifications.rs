@@ -0,0 +1,305 @@
+// @awa-component: DESKTOP-NotificationBridge
+//! Native OS notifications bridged from the server event stream.
+//!
+//! A background task keeps a `GET /events` (SSE) connection open to the API
+//! sidecar and raises a native notification for each event whose category
+//! the user has enabled. Categories are toggled with the user config keys
+//! `notifications.desktop.{ingest,tasks,toolApprovals}`, re-read on every
+//! event so changes apply without reconnecting. Critical announcements
+//! (such as a planned migration that needs a restart) are always raised;
+//! other announcements only appear in the app. Nothing is shown while the
+//! main window has focus — the user is already looking at the app.
+//!
+//! The connection is authenticated with the main webview's `nize_access`
+//! cookie, the same way quick capture authenticates.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tracing::{debug, info, warn};
+
+use crate::quick_capture::{access_token, api_base};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Label of the main application window.
+const MAIN_WINDOW_LABEL: &str = "main";
+
+/// Delay before reconnecting after the stream ends or fails.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Delay before retrying while nobody is signed in.
+const SIGNED_OUT_DELAY: Duration = Duration::from_secs(30);
+
+/// Kind broadcast when an admin announcement becomes active.
+const ANNOUNCEMENT_KIND: &str = "announcement.published";
+
+/// Event categories that can be toggled individually.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Category {
+    Ingest,
+    Tasks,
+    ToolApprovals,
+}
+
+impl Category {
+    /// Category of a server event kind (`ingest.completed`, `task.failed`, …).
+    fn from_kind(kind: &str) -> Option<Self> {
+        match kind.split_once('.').map(|(prefix, _)| prefix) {
+            Some("ingest") => Some(Self::Ingest),
+            Some("task") => Some(Self::Tasks),
+            _ if kind == "tool.approval_requested" => Some(Self::ToolApprovals),
+            _ => None,
+        }
+    }
+
+    /// User config key holding this category's toggle.
+    fn config_key(self) -> &'static str {
+        match self {
+            Self::Ingest => "notifications.desktop.ingest",
+            Self::Tasks => "notifications.desktop.tasks",
+            Self::ToolApprovals => "notifications.desktop.toolApprovals",
+        }
+    }
+}
+
+/// Event payload sent by the API (`data:` line of an SSE event).
+#[derive(Debug, Deserialize)]
+struct ServerEvent {
+    kind: String,
+    title: String,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    payload: serde_json::Value,
+}
+
+impl ServerEvent {
+    fn is_critical_announcement(&self) -> bool {
+        self.kind == ANNOUNCEMENT_KIND && self.payload["severity"] == "critical"
+    }
+}
+
+/// Effective user config item (subset of `GET /config/user`).
+#[derive(Debug, Deserialize)]
+struct ConfigItem {
+    key: String,
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConfigList {
+    items: Vec<ConfigItem>,
+}
+
+/// Per-category toggles. Everything is enabled until the user config says
+/// otherwise.
+#[derive(Debug, Clone, Copy)]
+struct Toggles {
+    ingest: bool,
+    tasks: bool,
+    tool_approvals: bool,
+}
+
+impl Default for Toggles {
+    fn default() -> Self {
+        Self {
+            ingest: true,
+            tasks: true,
+            tool_approvals: true,
+        }
+    }
+}
+
+impl Toggles {
+    fn from_config(items: &[ConfigItem]) -> Self {
+        let enabled = |category: Category| {
+            items
+                .iter()
+                .find(|item| item.key == category.config_key())
+                .is_none_or(|item| item.value != "false")
+        };
+        Self {
+            ingest: enabled(Category::Ingest),
+            tasks: enabled(Category::Tasks),
+            tool_approvals: enabled(Category::ToolApprovals),
+        }
+    }
+
+    fn allows(self, category: Category) -> bool {
+        match category {
+            Category::Ingest => self.ingest,
+            Category::Tasks => self.tasks,
+            Category::ToolApprovals => self.tool_approvals,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Bridge
+// ---------------------------------------------------------------------------
+
+/// Spawn the background task bridging server events to native notifications.
+pub fn spawn_bridge(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let http = reqwest::Client::new();
+        let mut toggles = Toggles::default();
+        loop {
+            let delay = match stream_events(&app, &http, &mut toggles).await {
+                Ok(()) => {
+                    debug!("event stream ended, reconnecting");
+                    RECONNECT_DELAY
+                }
+                Err(BridgeError::SignedOut) => SIGNED_OUT_DELAY,
+                Err(BridgeError::Other(e)) => {
+                    debug!("event stream unavailable: {e}");
+                    RECONNECT_DELAY
+                }
+            };
+            tokio::time::sleep(delay).await;
+        }
+    });
+}
+
+enum BridgeError {
+    /// No access cookie in the main webview yet.
+    SignedOut,
+    Other(String),
+}
+
+impl From<String> for BridgeError {
+    fn from(e: String) -> Self {
+        Self::Other(e)
+    }
+}
+
+/// Connect to `GET /events` and dispatch events until the stream closes.
+async fn stream_events(
+    app: &AppHandle,
+    http: &reqwest::Client,
+    toggles: &mut Toggles,
+) -> Result<(), BridgeError> {
+    let base = api_base(app)?;
+    let window = app
+        .get_webview_window(MAIN_WINDOW_LABEL)
+        .ok_or_else(|| "main window not found".to_string())?;
+    let token = access_token(&window).map_err(|_| BridgeError::SignedOut)?;
+
+    let mut resp = http
+        .get(format!("{base}/events"))
+        .bearer_auth(&token)
+        .header(reqwest::header::ACCEPT, "text/event-stream")
+        .send()
+        .await
+        .map_err(|e| format!("connect: {e}"))?;
+    if resp.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(BridgeError::SignedOut);
+    }
+    if !resp.status().is_success() {
+        return Err(format!("connect: {}", resp.status()).into());
+    }
+    info!("connected to server event stream");
+
+    let mut buffer = String::new();
+    while let Some(chunk) = resp.chunk().await.map_err(|e| format!("read: {e}"))? {
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+        // Normalize CRLF so blocks always end in a blank "\n\n" line.
+        if buffer.contains('\r') {
+            buffer = buffer.replace("\r\n", "\n");
+        }
+        while let Some(end) = buffer.find("\n\n") {
+            let block: String = buffer.drain(..end + 2).collect();
+            if let Some(data) = sse_data(&block) {
+                handle_event(app, http, &base, &token, toggles, &data).await;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Concatenated `data:` lines of one SSE block (`None` for comments and
+/// keep-alives).
+fn sse_data(block: &str) -> Option<String> {
+    let lines: Vec<&str> = block
+        .lines()
+        .filter_map(|line| line.strip_prefix("data:"))
+        .map(|data| data.strip_prefix(' ').unwrap_or(data))
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Raise a native notification for an event if its category is enabled.
+async fn handle_event(
+    app: &AppHandle,
+    http: &reqwest::Client,
+    base: &str,
+    token: &str,
+    toggles: &mut Toggles,
+    data: &str,
+) {
+    let event: ServerEvent = match serde_json::from_str(data) {
+        Ok(event) => event,
+        Err(e) => {
+            warn!("Ignoring malformed server event: {e}");
+            return;
+        }
+    };
+    if event.kind == ANNOUNCEMENT_KIND {
+        if event.is_critical_announcement() && !main_window_focused(app) {
+            show(app, &event);
+        }
+        return;
+    }
+    let Some(category) = Category::from_kind(&event.kind) else {
+        return;
+    };
+
+    // Keep the last known toggles if the token has expired since connecting.
+    match fetch_toggles(http, base, token).await {
+        Ok(current) => *toggles = current,
+        Err(e) => debug!("Using cached notification settings: {e}"),
+    }
+    if !toggles.allows(category) || main_window_focused(app) {
+        return;
+    }
+    show(app, &event);
+}
+
+fn show(app: &AppHandle, event: &ServerEvent) {
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title(&event.title)
+        .body(&event.body)
+        .show()
+    {
+        warn!("Failed to show notification: {e}");
+    }
+}
+
+async fn fetch_toggles(http: &reqwest::Client, base: &str, token: &str) -> Result<Toggles, String> {
+    let resp = http
+        .get(format!("{base}/config/user"))
+        .bearer_auth(token)
+        .send()
+        .await
+        .map_err(|e| format!("fetch config: {e}"))?;
+    if !resp.status().is_success() {
+        return Err(format!("fetch config: {}", resp.status()));
+    }
+    let list: ConfigList = resp
+        .json()
+        .await
+        .map_err(|e| format!("parse config: {e}"))?;
+    Ok(Toggles::from_config(&list.items))
+}
+
+fn main_window_focused(app: &AppHandle) -> bool {
+    app.get_webview_window(MAIN_WINDOW_LABEL)
+        .and_then(|win| win.is_focused().ok())
+        .unwrap_or(false)
+}
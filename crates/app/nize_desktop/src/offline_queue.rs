@@ -0,0 +1,142 @@
+// @awa-component: DESKTOP-OfflineQueue
+//! Offline queue of mutating API requests.
+//!
+//! Wraps [`nize_api_client::offline::OfflineQueue`], persisted as
+//! `offline-queue.json` in the app data directory. Quick-capture notes go
+//! through it, so a capture made while the API is unreachable is kept and
+//! sent later. The frontend replays the queue with
+//! [`replay_offline_queue`] when connectivity returns (authenticated with
+//! the webview's cookie, so no credentials are stored), resolves conflicts
+//! with [`retry_queued_request`] or [`discard_queued_request`], and follows
+//! [`CHANGED_EVENT`] for the queue state.
+
+use std::path::PathBuf;
+
+use nize_api_client::offline::{
+    NewRequest, OfflineQueue, QueueSnapshot, ReplayReport, SendOutcome,
+};
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
+use tauri::{AppHandle, Emitter, Manager, State, WebviewWindow};
+use tracing::{error, warn};
+
+use crate::quick_capture::{access_token, api_base};
+
+/// Event emitted to all windows when the queue changes; the payload is a
+/// [`QueueSnapshot`].
+pub const CHANGED_EVENT: &str = "offline-queue-changed";
+
+fn queue_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join("offline-queue.json"))
+        .map_err(|e| format!("resolve data dir: {e}"))
+}
+
+/// Open the queue and register it as Tauri state. An unreadable queue file
+/// is moved aside so the app still starts with an empty queue.
+pub fn init(app: &AppHandle) -> Result<(), String> {
+    let path = queue_path(app)?;
+    let queue = match OfflineQueue::open(&path) {
+        Ok(queue) => queue,
+        Err(e) => {
+            error!("Failed to open offline queue, starting empty: {e}");
+            let aside = path.with_extension("json.corrupt");
+            if let Err(e) = std::fs::rename(&path, &aside) {
+                warn!("Failed to move {} aside: {e}", path.display());
+            }
+            OfflineQueue::open(&path).map_err(|e| e.to_string())?
+        }
+    };
+    app.manage(queue);
+    Ok(())
+}
+
+fn emit_changed(app: &AppHandle, queue: &OfflineQueue) {
+    if let Err(e) = app.emit(CHANGED_EVENT, queue.snapshot()) {
+        warn!("Failed to emit {CHANGED_EVENT}: {e}");
+    }
+}
+
+fn auth_headers(token: &str) -> Result<HeaderMap, String> {
+    let mut headers = HeaderMap::new();
+    let value = HeaderValue::from_str(&format!("Bearer {token}"))
+        .map_err(|e| format!("invalid access token: {e}"))?;
+    headers.insert(AUTHORIZATION, value);
+    Ok(headers)
+}
+
+/// Send `request` with the caller's credentials, queuing it if the API is
+/// unreachable.
+pub(crate) async fn send_or_queue(
+    app: &AppHandle,
+    token: &str,
+    request: NewRequest,
+) -> Result<SendOutcome, String> {
+    let queue = app.state::<OfflineQueue>();
+    let outcome = queue
+        .send_or_queue(
+            &reqwest::Client::new(),
+            &api_base(app)?,
+            &auth_headers(token)?,
+            request,
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    if matches!(outcome, SendOutcome::Queued(_)) {
+        emit_changed(app, &queue);
+    }
+    Ok(outcome)
+}
+
+#[tauri::command]
+pub async fn get_offline_queue(queue: State<'_, OfflineQueue>) -> Result<QueueSnapshot, String> {
+    Ok(queue.snapshot())
+}
+
+/// Send pending requests with the calling window's credentials.
+#[tauri::command]
+pub async fn replay_offline_queue(
+    app: AppHandle,
+    window: WebviewWindow,
+    queue: State<'_, OfflineQueue>,
+) -> Result<ReplayReport, String> {
+    if queue.snapshot().pending == 0 {
+        return Ok(ReplayReport::default());
+    }
+    let headers = auth_headers(&access_token(&window)?)?;
+    let report = queue
+        .replay(&reqwest::Client::new(), &api_base(&app)?, &headers)
+        .await
+        .map_err(|e| e.to_string());
+    emit_changed(&app, &queue);
+    report
+}
+
+/// Mark a conflicted or failed request pending again; `force` ignores the
+/// conflict and overwrites the server's version on replay.
+#[tauri::command]
+pub async fn retry_queued_request(
+    app: AppHandle,
+    queue: State<'_, OfflineQueue>,
+    id: u64,
+    force: bool,
+) -> Result<QueueSnapshot, String> {
+    if !queue.retry(id, force).map_err(|e| e.to_string())? {
+        return Err(format!("No queued request {id}"));
+    }
+    emit_changed(&app, &queue);
+    Ok(queue.snapshot())
+}
+
+#[tauri::command]
+pub async fn discard_queued_request(
+    app: AppHandle,
+    queue: State<'_, OfflineQueue>,
+    id: u64,
+) -> Result<QueueSnapshot, String> {
+    if !queue.discard(id).map_err(|e| e.to_string())? {
+        return Err(format!("No queued request {id}"));
+    }
+    emit_changed(&app, &queue);
+    Ok(queue.snapshot())
+}
@@ -0,0 +1,359 @@
+// @awa-component: DESKTOP-QuickCapture
+//! Quick-capture window summoned by a global keyboard shortcut.
+//!
+//! The shortcut toggles a small always-on-top window that loads the
+//! nize-web `/quick-capture` page. The page hands captured text back to
+//! [`submit_quick_capture`], which posts it to the API sidecar either as the
+//! first message of a new conversation or as a note. Requests are
+//! authenticated with the webview's `nize_access` cookie, so the capture
+//! window needs no login of its own. Notes captured while the API is
+//! unreachable are kept in the offline queue and sent later.
+//!
+//! The shortcut is configurable and persisted in `quick-capture.json` under
+//! the app config directory.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use nize_api_client::offline::{NewRequest, SendOutcome};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use tracing::{error, info};
+
+use crate::AppServices;
+use crate::offline_queue;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Tauri window label of the quick-capture window.
+pub const WINDOW_LABEL: &str = "quick-capture";
+
+/// Shortcut used until the user configures another one.
+pub const DEFAULT_SHORTCUT: &str = "CommandOrControl+Shift+Space";
+
+/// Auth cookie set by the API on login.
+const ACCESS_COOKIE: &str = "nize_access";
+
+/// Maximum length of a conversation title derived from captured text.
+const MAX_TITLE_CHARS: usize = 60;
+
+/// Persisted quick-capture settings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickCaptureSettings {
+    pub shortcut: String,
+}
+
+impl Default for QuickCaptureSettings {
+    fn default() -> Self {
+        Self {
+            shortcut: DEFAULT_SHORTCUT.to_string(),
+        }
+    }
+}
+
+/// Where captured text is sent.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CaptureTarget {
+    /// First user message of a new conversation.
+    Message,
+    /// Markdown note created through `POST /notes`.
+    Note,
+}
+
+/// Outcome of a successful capture.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureResult {
+    pub target: CaptureTarget,
+    /// Conversation or note ID; `None` when the note was queued.
+    pub id: Option<String>,
+    /// The API was unreachable and the note waits in the offline queue.
+    pub queued: bool,
+}
+
+// ---------------------------------------------------------------------------
+// Settings persistence
+// ---------------------------------------------------------------------------
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join("quick-capture.json"))
+        .map_err(|e| format!("resolve config dir: {e}"))
+}
+
+/// Load settings, falling back to defaults when missing or unreadable.
+pub fn load_settings(app: &AppHandle) -> QuickCaptureSettings {
+    let Ok(path) = settings_path(app) else {
+        return QuickCaptureSettings::default();
+    };
+    fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_settings(app: &AppHandle, settings: &QuickCaptureSettings) -> Result<(), String> {
+    let path = settings_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("create config dir: {e}"))?;
+    }
+    let json =
+        serde_json::to_string_pretty(settings).map_err(|e| format!("serialize settings: {e}"))?;
+    fs::write(&path, json).map_err(|e| format!("write {}: {e}", path.display()))
+}
+
+// ---------------------------------------------------------------------------
+// Shortcut + window
+// ---------------------------------------------------------------------------
+
+/// Replace any registered quick-capture shortcut with `shortcut`.
+pub fn register_shortcut(app: &AppHandle, shortcut: &str) -> Result<(), String> {
+    let parsed: Shortcut = shortcut
+        .parse()
+        .map_err(|e| format!("invalid shortcut '{shortcut}': {e}"))?;
+
+    let global = app.global_shortcut();
+    global
+        .unregister_all()
+        .map_err(|e| format!("unregister shortcuts: {e}"))?;
+    global
+        .on_shortcut(parsed, |app, _shortcut, event| {
+            if event.state == ShortcutState::Pressed
+                && let Err(e) = toggle_window(app)
+            {
+                error!("Failed to toggle quick-capture window: {e}");
+            }
+        })
+        .map_err(|e| format!("register shortcut '{shortcut}': {e}"))?;
+
+    info!(shortcut, "quick-capture shortcut registered");
+    Ok(())
+}
+
+/// Show the quick-capture window (creating it on first use), or hide it if
+/// it is already focused.
+fn toggle_window(app: &AppHandle) -> Result<(), String> {
+    if let Some(win) = app.get_webview_window(WINDOW_LABEL) {
+        if win.is_visible().unwrap_or(false) && win.is_focused().unwrap_or(false) {
+            return win.hide().map_err(|e| e.to_string());
+        }
+        win.show().map_err(|e| e.to_string())?;
+        return win.set_focus().map_err(|e| e.to_string());
+    }
+
+    WebviewWindowBuilder::new(app, WINDOW_LABEL, capture_url(app)?)
+        .title("Quick Capture")
+        .inner_size(560.0, 180.0)
+        .resizable(false)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .center()
+        .focused(true)
+        .build()
+        .map(|_| ())
+        .map_err(|e| format!("create quick-capture window: {e}"))
+}
+
+/// URL of the capture page: the dev server in debug builds, the nize-web
+/// sidecar in release builds.
+fn capture_url(app: &AppHandle) -> Result<WebviewUrl, String> {
+    #[cfg(debug_assertions)]
+    {
+        let _ = app;
+        Ok(WebviewUrl::App("quick-capture".into()))
+    }
+    #[cfg(not(debug_assertions))]
+    {
+        let state = app.state::<Mutex<AppServices>>();
+        let guard = state.lock().map_err(|e| format!("lock: {e}"))?;
+        let port = guard
+            .nize_web
+            .as_ref()
+            .map(|s| s.port)
+            .ok_or("nize-web sidecar not running")?;
+        let url = format!("http://127.0.0.1:{port}/quick-capture")
+            .parse()
+            .map_err(|e| format!("capture URL: {e}"))?;
+        Ok(WebviewUrl::External(url))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Submission
+// ---------------------------------------------------------------------------
+
+/// Conversation title derived from the first line of the captured text.
+fn title_from_text(text: &str) -> String {
+    let first_line = text.lines().next().unwrap_or_default().trim();
+    if first_line.chars().count() <= MAX_TITLE_CHARS {
+        return first_line.to_string();
+    }
+    let truncated: String = first_line.chars().take(MAX_TITLE_CHARS - 1).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+/// Base URL of the API sidecar (`http://127.0.0.1:<port>/api`).
+pub(crate) fn api_base(app: &AppHandle) -> Result<String, String> {
+    let state = app.state::<Mutex<AppServices>>();
+    let guard = state.lock().map_err(|e| format!("lock: {e}"))?;
+    match &guard.sidecar {
+        Some(s) => Ok(format!("http://127.0.0.1:{}/api", s.port)),
+        None => Err("API sidecar not running".into()),
+    }
+}
+
+/// Read the access token from the webview cookie store, falling back to
+/// the local token in local mode.
+pub(crate) fn access_token(window: &WebviewWindow) -> Result<String, String> {
+    window
+        .cookies()
+        .map_err(|e| format!("read cookies: {e}"))?
+        .into_iter()
+        .find(|c| c.name() == ACCESS_COOKIE)
+        .map(|c| c.value().to_string())
+        .or_else(|| crate::local_mode::token().map(str::to_string))
+        .ok_or_else(|| "Not signed in — open Nize and log in first".to_string())
+}
+
+/// Turn a non-2xx response into an error string using the API's `message`.
+pub(crate) async fn check(resp: reqwest::Response) -> Result<serde_json::Value, String> {
+    let status = resp.status();
+    let body: serde_json::Value = resp.json().await.unwrap_or_default();
+    if status.is_success() {
+        return Ok(body);
+    }
+    let message = body["message"].as_str().unwrap_or("request failed");
+    Err(format!("{status}: {message}"))
+}
+
+async fn submit_message(
+    http: &reqwest::Client,
+    base: &str,
+    token: &str,
+    text: &str,
+) -> Result<String, String> {
+    let created = http
+        .post(format!("{base}/conversations"))
+        .bearer_auth(token)
+        .json(&serde_json::json!({ "title": title_from_text(text) }))
+        .send()
+        .await
+        .map_err(|e| format!("create conversation: {e}"))?;
+    let created = check(created).await?;
+    let id = created["id"]
+        .as_str()
+        .ok_or("create conversation: missing id")?
+        .to_string();
+
+    let message = serde_json::json!({
+        "id": "quick-capture-0",
+        "role": "user",
+        "parts": [{ "type": "text", "text": text }],
+    });
+    let saved = http
+        .put(format!("{base}/conversations/{id}/messages"))
+        .bearer_auth(token)
+        .json(&serde_json::json!({ "messages": [message] }))
+        .send()
+        .await
+        .map_err(|e| format!("save message: {e}"))?;
+    check(saved).await?;
+
+    Ok(id)
+}
+
+/// Create a note, queuing it if the API is unreachable. Returns the note ID,
+/// or `None` when queued.
+async fn submit_note(app: &AppHandle, token: &str, text: &str) -> Result<Option<String>, String> {
+    let request = NewRequest {
+        method: "POST".into(),
+        path: "/notes".into(),
+        body: Some(serde_json::json!({ "body": text, "tags": ["quick-capture"] })),
+        guard: None,
+        label: format!("Quick-capture note: {}", title_from_text(text)),
+        idempotency_key: None,
+    };
+    match offline_queue::send_or_queue(app, token, request).await? {
+        SendOutcome::Queued(_) => Ok(None),
+        SendOutcome::Sent { status, body } if status.is_success() => body["id"]
+            .as_str()
+            .map(|id| Some(id.to_string()))
+            .ok_or_else(|| "create note: missing id".to_string()),
+        SendOutcome::Sent { status, body } => {
+            let message = body["message"].as_str().unwrap_or("request failed");
+            Err(format!("create note: {status}: {message}"))
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tauri commands
+// ---------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn get_quick_capture_settings(app: AppHandle) -> Result<QuickCaptureSettings, String> {
+    Ok(load_settings(&app))
+}
+
+/// Re-register the global shortcut and persist it. The previous shortcut
+/// stays active if the new one cannot be registered.
+#[tauri::command]
+pub async fn set_quick_capture_shortcut(app: AppHandle, shortcut: String) -> Result<(), String> {
+    let previous = load_settings(&app);
+    if let Err(e) = register_shortcut(&app, &shortcut) {
+        let _ = register_shortcut(&app, &previous.shortcut);
+        return Err(e);
+    }
+    save_settings(&app, &QuickCaptureSettings { shortcut })
+}
+
+/// Submit captured text and hide the capture window on success.
+#[tauri::command]
+pub async fn submit_quick_capture(
+    app: AppHandle,
+    window: WebviewWindow,
+    text: String,
+    target: CaptureTarget,
+) -> Result<CaptureResult, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Nothing to capture".into());
+    }
+
+    let token = access_token(&window)?;
+
+    let id = match target {
+        CaptureTarget::Message => {
+            let base = api_base(&app)?;
+            let http = reqwest::Client::new();
+            Some(submit_message(&http, &base, &token, text).await?)
+        }
+        CaptureTarget::Note => submit_note(&app, &token, text).await?,
+    };
+    info!(?target, ?id, "quick capture submitted");
+
+    if window.label() == WINDOW_LABEL {
+        let _ = window.hide();
+    }
+    Ok(CaptureResult {
+        target,
+        queued: id.is_none(),
+        id,
+    })
+}
+
+#[tauri::command]
+pub async fn hide_quick_capture(app: AppHandle) -> Result<(), String> {
+    match app.get_webview_window(WINDOW_LABEL) {
+        Some(win) => win.hide().map_err(|e| e.to_string()),
+        None => Ok(()),
+    }
+}
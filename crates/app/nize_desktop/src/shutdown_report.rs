@@ -0,0 +1,94 @@
+// @awa-component: DESKTOP-ShutdownReport
+//! Startup report of previous unclean shutdowns.
+//!
+//! `nize_terminator` only runs cleanup when the app died without a
+//! graceful exit, and leaves a `nize-<pid>-cleanup.result.json` file next
+//! to the manifest recording each command it ran. On startup we log every
+//! such file — the unclean shutdown itself, commands that failed, and
+//! processes (e.g. PGlite) that were still running after cleanup — and
+//! then remove it so it is reported once.
+
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use tracing::{error, warn};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CleanupReport {
+    parent_pid: u32,
+    started_at: u64,
+    success: bool,
+    error: Option<String>,
+    #[serde(default)]
+    commands: Vec<CommandResult>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CommandResult {
+    command: String,
+    status: String,
+    exit_code: Option<i32>,
+    error: Option<String>,
+    still_running: Option<u32>,
+}
+
+/// Log and remove cleanup result files left by previous runs in `dir`.
+pub fn report_previous_shutdowns(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if !(name.starts_with("nize-") && name.ends_with("-cleanup.result.json")) {
+            continue;
+        }
+
+        let path = entry.path();
+        match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| serde_json::from_str::<CleanupReport>(&s).map_err(|e| e.to_string()))
+        {
+            Ok(report) => log_report(&report),
+            Err(e) => warn!(path = %path.display(), "Unreadable cleanup result: {e}"),
+        }
+        if let Err(e) = fs::remove_file(&path) {
+            warn!(path = %path.display(), "Failed to remove cleanup result: {e}");
+        }
+    }
+}
+
+fn log_report(report: &CleanupReport) {
+    warn!(
+        pid = report.parent_pid,
+        cleaned_up_at = report.started_at,
+        success = report.success,
+        "Previous run did not shut down cleanly"
+    );
+    if let Some(e) = &report.error {
+        error!(pid = report.parent_pid, "Cleanup did not run: {e}");
+    }
+    for cmd in &report.commands {
+        if cmd.status != "ok" {
+            error!(
+                command = %cmd.command,
+                status = %cmd.status,
+                exit_code = ?cmd.exit_code,
+                error = ?cmd.error,
+                "Cleanup command failed"
+            );
+        }
+        if let Some(pid) = cmd.still_running {
+            error!(
+                pid,
+                command = %cmd.command,
+                "Process was still running after cleanup and may need to be stopped manually"
+            );
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// @awa-component: DESKTOP-StartupError
+//! Startup failures for the error screen.
+//!
+//! When the API sidecar's startup self-check fails it prints
+//! `{"error": {"message", "checks"}}` instead of its ready line, each check
+//! carrying a remediation hint. That report — or a plain message when
+//! something failed before the sidecar could run its checks (PGlite,
+//! spawning the sidecar) — is kept in [`crate::AppServices`] and returned
+//! by [`get_startup_error`], so the frontend shows what to fix instead of a
+//! broken app.
+
+use std::fmt;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::AppServices;
+
+/// One check of the sidecar's startup self-check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupCheck {
+    pub name: String,
+    /// `passed`, `failed` or `skipped`.
+    pub status: String,
+    pub message: Option<String>,
+    pub hint: Option<String>,
+}
+
+/// Why the app's services did not start.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupError {
+    pub message: String,
+    /// The sidecar's checks; empty when it never got to run them.
+    #[serde(default)]
+    pub checks: Vec<StartupCheck>,
+}
+
+impl StartupError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            checks: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for StartupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for StartupError {
+    fn from(message: String) -> Self {
+        Self::new(message)
+    }
+}
+
+impl From<&str> for StartupError {
+    fn from(message: &str) -> Self {
+        Self::new(message)
+    }
+}
+
+/// The sidecar's failure line.
+#[derive(Deserialize)]
+struct SidecarFailure {
+    error: StartupError,
+}
+
+/// Parse the sidecar's stdout line as a failed self-check.
+pub fn parse_sidecar_failure(line: &str) -> Option<StartupError> {
+    serde_json::from_str::<SidecarFailure>(line)
+        .ok()
+        .map(|f| f.error)
+}
+
+#[tauri::command]
+pub async fn get_startup_error(
+    state: tauri::State<'_, Mutex<AppServices>>,
+) -> Result<Option<StartupError>, String> {
+    let guard = state.lock().map_err(|e| format!("lock: {e}"))?;
+    Ok(guard.startup_error.clone())
+}
@@ -0,0 +1,189 @@
+// @awa-component: DESKTOP-StartupSweep
+//! Startup reclamation of resources left behind by a crashed machine.
+//!
+//! When the whole machine goes down, or the app is killed together with
+//! `nize_terminator`, the cleanup manifest is never run: it stays in the
+//! temp directory, and PGlite, nize-web or stdio MCP processes may still be
+//! running on the next start.
+//!
+//! Before starting new services we look at every `nize-<pid>-cleanup.manifest`
+//! whose owning PID is dead, run its commands the way the terminator would,
+//! and delete it. A `kill` command only runs if its target is alive and was
+//! started before the manifest was last written; otherwise the PID has been
+//! recycled by an unrelated process and is left alone.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use tracing::{info, warn};
+
+/// Slack for the coarse (one second) process start times reported by `ps`.
+const START_TIME_SLACK: Duration = Duration::from_secs(2);
+
+/// Result of a startup sweep.
+pub struct SweepOutcome {
+    /// Another Nize instance owns a live manifest, so shared resources such
+    /// as the PGlite data directory may still be in use.
+    pub other_instance_running: bool,
+}
+
+/// Run and delete the cleanup manifests of dead previous runs in `dir`.
+pub fn sweep_stale_manifests(dir: &Path) -> SweepOutcome {
+    let mut outcome = SweepOutcome {
+        other_instance_running: false,
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return outcome;
+    };
+    let own_pid = std::process::id();
+
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(owner) = name.to_str().and_then(manifest_owner) else {
+            continue;
+        };
+        if owner == own_pid {
+            continue;
+        }
+        if pid_alive(owner) {
+            outcome.other_instance_running = true;
+            continue;
+        }
+
+        let path = entry.path();
+        warn!(pid = owner, path = %path.display(), "Found cleanup manifest from a dead run");
+        match fs::read_to_string(&path) {
+            Ok(contents) => {
+                let written_at = entry.metadata().and_then(|m| m.modified()).ok();
+                run_stale_commands(&contents, written_at);
+            }
+            Err(e) => warn!(path = %path.display(), "Failed to read stale manifest: {e}"),
+        }
+        if let Err(e) = fs::remove_file(&path) {
+            warn!(path = %path.display(), "Failed to remove stale manifest: {e}");
+        }
+    }
+    outcome
+}
+
+/// Owning PID of a `nize-<pid>-cleanup.manifest` file name.
+fn manifest_owner(name: &str) -> Option<u32> {
+    name.strip_prefix("nize-")?
+        .strip_suffix("-cleanup.manifest")?
+        .parse()
+        .ok()
+}
+
+fn run_stale_commands(contents: &str, written_at: Option<SystemTime>) {
+    let commands = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+    for cmd in commands {
+        if let Some(target) = kill_target(cmd) {
+            if !pid_alive(target) {
+                continue;
+            }
+            let started_before = match (process_started_at(target), written_at) {
+                (Some(started), Some(written)) => started <= written + START_TIME_SLACK,
+                _ => false,
+            };
+            if !started_before {
+                info!(
+                    pid = target,
+                    "Skipping stale kill: PID now belongs to another process"
+                );
+                continue;
+            }
+        }
+
+        info!(command = cmd, "Running stale cleanup command");
+        #[cfg(unix)]
+        let result = Command::new("sh").arg("-c").arg(cmd).status();
+        #[cfg(windows)]
+        let result = Command::new("cmd").arg("/C").arg(cmd).status();
+        match result {
+            Ok(status) if status.success() => {}
+            Ok(status) => {
+                warn!(command = cmd, code = ?status.code(), "Stale cleanup command failed")
+            }
+            Err(e) => warn!(command = cmd, "Failed to run stale cleanup command: {e}"),
+        }
+    }
+}
+
+/// PID targeted by a `kill <pid>` or `taskkill /PID <pid> ...` command.
+fn kill_target(cmd: &str) -> Option<u32> {
+    let mut words = cmd.split_whitespace();
+    match words.next()? {
+        "kill" => words.last()?.parse().ok(),
+        "taskkill" => {
+            let mut words = words.skip_while(|w| !w.eq_ignore_ascii_case("/PID"));
+            words.next()?;
+            words.next()?.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Whether a process with this PID exists.
+#[cfg(unix)]
+fn pid_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Whether a process with this PID exists.
+#[cfg(windows)]
+fn pid_alive(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/NH", "/FO", "CSV"])
+        .output()
+        .is_ok_and(|o| String::from_utf8_lossy(&o.stdout).contains(&format!("\"{pid}\"")))
+}
+
+/// When the process with this PID was started.
+#[cfg(unix)]
+fn process_started_at(pid: u32) -> Option<SystemTime> {
+    let output = Command::new("ps")
+        .args(["-o", "etime=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let elapsed = parse_etime(String::from_utf8_lossy(&output.stdout).trim())?;
+    SystemTime::now().checked_sub(elapsed)
+}
+
+/// When the process with this PID was started.
+#[cfg(windows)]
+fn process_started_at(pid: u32) -> Option<SystemTime> {
+    let script =
+        format!("[DateTimeOffset]::new((Get-Process -Id {pid}).StartTime).ToUnixTimeSeconds()");
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-Command", &script])
+        .output()
+        .ok()?;
+    let secs: u64 = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .ok()?;
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Parse a `ps` elapsed time, `[[dd-]hh:]mm:ss`.
+#[cfg(unix)]
+fn parse_etime(s: &str) -> Option<Duration> {
+    let (days, clock) = match s.split_once('-') {
+        Some((d, rest)) => (d.parse::<u64>().ok()?, rest),
+        None => (0, s),
+    };
+    let mut secs = 0;
+    for part in clock.split(':') {
+        secs = secs * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(Duration::from_secs(days * 86_400 + secs))
+}
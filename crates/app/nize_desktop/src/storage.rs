@@ -0,0 +1,55 @@
+// @awa-component: DESKTOP-Storage
+//! Disk usage of the bundled PGlite database.
+//!
+//! [`get_storage_usage`] measures the data directory (and the sealed
+//! snapshot, when encrypted at rest) directly on disk, so the settings page
+//! can show it even when the database or API sidecar failed to start — e.g.
+//! because the disk is full. Limits, alerts and maintenance (VACUUM, pruning
+//! old traces, orphaned blob cleanup) are handled by the API's
+//! `/admin/storage` endpoints.
+
+use std::path::PathBuf;
+
+use nize_core::db::default_pglite_data_dir;
+use nize_core::storage::dir_size;
+use serde::Serialize;
+
+/// Disk usage reported to the UI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageUsage {
+    /// PGlite data directory; `None` when the platform has no data dir.
+    pub data_dir: Option<PathBuf>,
+    /// Size of the data directory.
+    pub data_dir_bytes: u64,
+    /// Size of the sealed snapshot (encryption at rest), if present.
+    pub sealed_bytes: Option<u64>,
+}
+
+#[tauri::command]
+pub async fn get_storage_usage() -> Result<StorageUsage, String> {
+    tauri::async_runtime::spawn_blocking(measure)
+        .await
+        .map_err(|e| format!("measure storage: {e}"))?
+}
+
+/// Measure the data directory. Blocks while walking it.
+pub(crate) fn measure() -> Result<StorageUsage, String> {
+    let Some(data_dir) = default_pglite_data_dir() else {
+        return Ok(StorageUsage {
+            data_dir: None,
+            data_dir_bytes: 0,
+            sealed_bytes: None,
+        });
+    };
+    let data_dir_bytes =
+        dir_size(&data_dir).map_err(|e| format!("measure {}: {e}", data_dir.display()))?;
+    let sealed_bytes = std::fs::metadata(data_dir.with_extension("sealed"))
+        .ok()
+        .map(|m| m.len());
+    Ok(StorageUsage {
+        data_dir: Some(data_dir),
+        data_dir_bytes,
+        sealed_bytes,
+    })
+}
@@ -0,0 +1,290 @@
+// @awa-component: DESKTOP-SystemStatus
+//! Health of the app's subsystems for the Settings → Diagnostics page.
+//!
+//! [`get_system_status`] gathers, in one structure, whether the API sidecar
+//! process is alive, the sidecar's `GET /readyz` report (database
+//! reachability, pending migrations, MCP client pool), the bundled PGlite
+//! process and the database's disk usage. Each part is collected
+//! independently, so a failed sidecar still leaves the rest to show.
+//!
+//! [`spawn_monitor`] refreshes the status every [`REFRESH_INTERVAL`] and
+//! emits [`CHANGED_EVENT`] whenever it differs from the previous one, so the
+//! page can follow it without polling.
+
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use nize_core::migrate::MigrationStatus;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::warn;
+
+use crate::AppServices;
+use crate::storage::{self, StorageUsage};
+
+/// Event emitted to all windows when the status changes; the payload is a
+/// [`SystemStatus`].
+pub const CHANGED_EVENT: &str = "system-status-changed";
+
+/// How often the monitor refreshes the status.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long to wait for the sidecar's readiness report.
+const READYZ_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Everything the Diagnostics page shows.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemStatus {
+    pub sidecar: SidecarStatus,
+    pub api: ApiStatus,
+    pub database: DatabaseStatus,
+    /// From the sidecar's readiness report; `None` when it is unavailable.
+    pub migrations: Option<MigrationStatus>,
+    /// From the sidecar's readiness report; `None` when it is unavailable.
+    pub mcp_pool: Option<McpPoolStats>,
+    pub storage: StorageStatus,
+    /// When the status was collected, in milliseconds since the epoch.
+    pub checked_at_ms: u64,
+}
+
+/// The API sidecar process.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SidecarStatus {
+    /// Whether the sidecar was started and has not exited.
+    pub running: bool,
+    pub port: Option<u16>,
+    pub mcp_port: Option<u16>,
+    /// Exit code, once the process has exited.
+    pub exit_code: Option<i32>,
+}
+
+/// The sidecar's `GET /readyz` answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiStatus {
+    /// The sidecar answered.
+    pub reachable: bool,
+    /// The sidecar reported itself ready to serve requests.
+    pub ready: bool,
+    /// Why the report could not be read.
+    pub error: Option<String>,
+}
+
+/// The database the sidecar uses.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseStatus {
+    /// Whether the app runs the bundled PGlite (rather than an external
+    /// database).
+    pub bundled: bool,
+    /// Whether the bundled PGlite was started.
+    pub running: bool,
+    pub pid: Option<u32>,
+    /// Whether the bundled database is encrypted at rest.
+    pub encrypted: bool,
+    /// The sidecar could reach the database, per its readiness report.
+    pub reachable: Option<bool>,
+    pub pool_connections: Option<u32>,
+    pub idle_connections: Option<usize>,
+}
+
+/// Disk usage of the bundled database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageStatus {
+    pub usage: Option<StorageUsage>,
+    /// Why the data directory could not be measured.
+    pub error: Option<String>,
+}
+
+/// Size of the sidecar's MCP client pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpPoolStats {
+    pub connections: usize,
+    pub managed_processes: usize,
+    pub managed_memory_bytes: u64,
+    pub restarts: u32,
+}
+
+/// Body of the sidecar's `GET /readyz`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Readiness {
+    ready: bool,
+    database: ReadinessDatabase,
+    migrations: Option<MigrationStatus>,
+    mcp_pool: Option<McpPoolStats>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadinessDatabase {
+    reachable: bool,
+    pool_connections: u32,
+    idle_connections: usize,
+}
+
+/// Snapshot of the processes held in [`AppServices`].
+struct Processes {
+    sidecar: SidecarStatus,
+    database: DatabaseStatus,
+}
+
+fn inspect_processes(app: &AppHandle) -> Result<Processes, String> {
+    let state = app.state::<Mutex<AppServices>>();
+    let mut guard = state.lock().map_err(|e| format!("lock: {e}"))?;
+
+    let sidecar = match guard.sidecar.as_mut() {
+        Some(s) => {
+            let exit_code = match s._process.try_wait() {
+                Ok(Some(status)) => Some(status.code().unwrap_or(-1)),
+                Ok(None) => None,
+                Err(e) => {
+                    warn!("Failed to check API sidecar: {e}");
+                    None
+                }
+            };
+            SidecarStatus {
+                running: exit_code.is_none(),
+                port: Some(s.port),
+                mcp_port: Some(s.mcp_port),
+                exit_code,
+            }
+        }
+        None => SidecarStatus {
+            running: false,
+            port: None,
+            mcp_port: None,
+            exit_code: None,
+        },
+    };
+
+    let pglite = guard._pglite.as_ref();
+    let database = DatabaseStatus {
+        bundled: !crate::external_database::is_external(),
+        running: pglite.is_some_and(|p| p.is_started()),
+        pid: pglite.and_then(|p| p.child_pid()),
+        encrypted: pglite.is_some_and(|p| p.is_encrypted()),
+        reachable: None,
+        pool_connections: None,
+        idle_connections: None,
+    };
+    Ok(Processes { sidecar, database })
+}
+
+async fn fetch_readiness(http: &reqwest::Client, port: u16) -> Result<Readiness, String> {
+    // 503 still carries the report.
+    http.get(format!("http://127.0.0.1:{port}/readyz"))
+        .timeout(READYZ_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("request failed: {e}"))?
+        .json::<Readiness>()
+        .await
+        .map_err(|e| format!("invalid readiness report: {e}"))
+}
+
+/// Collect the current status.
+async fn collect(app: &AppHandle, http: &reqwest::Client) -> Result<SystemStatus, String> {
+    let Processes {
+        sidecar,
+        mut database,
+    } = inspect_processes(app)?;
+
+    let readiness = match sidecar.port.filter(|_| sidecar.running) {
+        Some(port) => fetch_readiness(http, port).await,
+        None => Err("API sidecar not running".into()),
+    };
+    let (api, migrations, mcp_pool) = match readiness {
+        Ok(r) => {
+            database.reachable = Some(r.database.reachable);
+            database.pool_connections = Some(r.database.pool_connections);
+            database.idle_connections = Some(r.database.idle_connections);
+            let api = ApiStatus {
+                reachable: true,
+                ready: r.ready,
+                error: None,
+            };
+            (api, r.migrations, r.mcp_pool)
+        }
+        Err(e) => {
+            let api = ApiStatus {
+                reachable: false,
+                ready: false,
+                error: Some(e),
+            };
+            (api, None, None)
+        }
+    };
+
+    let storage = match tauri::async_runtime::spawn_blocking(storage::measure).await {
+        Ok(Ok(usage)) => StorageStatus {
+            usage: Some(usage),
+            error: None,
+        },
+        Ok(Err(e)) => StorageStatus {
+            usage: None,
+            error: Some(e),
+        },
+        Err(e) => StorageStatus {
+            usage: None,
+            error: Some(format!("measure storage: {e}")),
+        },
+    };
+
+    Ok(SystemStatus {
+        sidecar,
+        api,
+        database,
+        migrations,
+        mcp_pool,
+        storage,
+        checked_at_ms: now_ms(),
+    })
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Whether two statuses differ in anything but when they were collected.
+fn changed(previous: &SystemStatus, current: &SystemStatus) -> bool {
+    SystemStatus {
+        checked_at_ms: current.checked_at_ms,
+        ..previous.clone()
+    } != *current
+}
+
+#[tauri::command]
+pub async fn get_system_status(app: AppHandle) -> Result<SystemStatus, String> {
+    collect(&app, &reqwest::Client::new()).await
+}
+
+/// Refresh the status in the background and emit [`CHANGED_EVENT`] when it
+/// changes.
+pub fn spawn_monitor(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let http = reqwest::Client::new();
+        let mut previous: Option<SystemStatus> = None;
+        loop {
+            match collect(&app, &http).await {
+                Ok(status) => {
+                    if previous.as_ref().is_none_or(|p| changed(p, &status)) {
+                        if let Err(e) = app.emit(CHANGED_EVENT, &status) {
+                            warn!("Failed to emit {CHANGED_EVENT}: {e}");
+                        }
+                        previous = Some(status);
+                    }
+                }
+                Err(e) => warn!("Failed to collect system status: {e}"),
+            }
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+        }
+    });
+}
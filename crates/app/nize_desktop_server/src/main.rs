@@ -1,39 +1,68 @@
 //! Nize desktop sidecar server binary.
 //!
 //! Started by the Tauri desktop app as a child process.
-//! Prints `{"port": N}` to stdout so the parent can discover the bound port.
+//! Prints `{"port": N}` to stdout so the parent can discover the bound port,
+//! or `{"error": {...}}` when the startup self-check fails (see
+//! `nize_api::startup`).
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 
 use clap::Parser;
+use nize_api::config::file::{ConfigFile, ConfigFileError};
+use nize_api::startup::{self, StartupChecks};
+use nize_core::db::QueryLimits;
 use sqlx::postgres::PgPoolOptions;
 use tokio_util::sync::CancellationToken;
-use tracing::info;
+use tracing::{info, warn};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
 /// CLI arguments for the desktop sidecar.
+///
+/// Settings left unset fall back to their environment variable, then to the
+/// `--config` file, then to the built-in default.
 #[derive(Parser, Debug)]
 #[command(name = "nize_desktop_server", about = "Nize desktop sidecar server")]
 struct Args {
-    /// Port to listen on (0 = ephemeral).
-    #[arg(long, default_value_t = 0)]
-    port: u16,
+    /// TOML configuration file (see `nize_api::config::file`).
+    #[arg(long, env = "NIZE_CONFIG")]
+    config: Option<PathBuf>,
 
-    /// MCP server port (default 19560; override with NIZE_MCP_PORT).
-    #[arg(long, env = "NIZE_MCP_PORT", default_value_t = 19560)]
-    mcp_port: u16,
+    /// Print the effective configuration with secrets redacted, then exit.
+    #[arg(long, default_value_t = false)]
+    print_config: bool,
 
-    /// PostgreSQL connection URL.
-    #[arg(
-        long,
-        env = "DATABASE_URL",
-        default_value = "postgres://localhost:5432/nize"
-    )]
-    database_url: String,
+    /// Port to listen on (0 = ephemeral) [default: 0].
+    #[arg(long)]
+    port: Option<u16>,
+
+    /// MCP server port [default: 19560].
+    #[arg(long, env = "NIZE_MCP_PORT")]
+    mcp_port: Option<u16>,
 
-    /// Maximum number of database connections in the pool.
+    /// PostgreSQL connection URL [default: postgres://localhost:5432/nize].
+    #[arg(long, env = "DATABASE_URL")]
+    database_url: Option<String>,
+
+    /// Maximum number of database connections in the pool [default: 5].
     ///
     /// Set to 1 when the backend is PGlite (single-connection only) so that
     /// concurrent requests queue at the pool level instead of failing.
-    #[arg(long, default_value_t = 5)]
-    max_connections: u32,
+    #[arg(long)]
+    max_connections: Option<u32>,
+
+    /// `statement_timeout` of every pooled connection in milliseconds, so a
+    /// runaway query cannot stall the single PGlite connection (0 = none)
+    /// [default: 60000].
+    #[arg(long, env = "NIZE_DB_STATEMENT_TIMEOUT_MS")]
+    statement_timeout_ms: Option<u64>,
+
+    /// Statements slower than this many milliseconds are logged and listed
+    /// at `GET /admin/diagnostics/slow-queries` [default: 500].
+    #[arg(long, env = "NIZE_DB_SLOW_QUERY_MS")]
+    slow_query_ms: Option<u64>,
 
     /// Run as a managed sidecar: exit automatically when the parent process dies.
     ///
@@ -48,49 +77,381 @@ struct Args {
     /// When set, stdio MCP server PIDs are appended to this file so the
     /// terminator can kill them on crash recovery.
     #[arg(long)]
-    terminator_manifest: Option<std::path::PathBuf>,
+    terminator_manifest: Option<PathBuf>,
+
+    /// Restart a managed MCP server process whose resident memory exceeds
+    /// this many MiB (unset = no limit).
+    #[arg(long, env = "NIZE_MCP_MAX_CHILD_MEMORY_MB")]
+    mcp_max_child_memory_mb: Option<u64>,
+
+    /// Directory of `<language>.json` error message bundles, added to the
+    /// built-in English texts.
+    #[arg(long, env = "NIZE_LOCALES_DIR")]
+    locales_dir: Option<PathBuf>,
+
+    /// PGlite data directory, measured by the storage monitor.
+    #[arg(long, env = "NIZE_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// Loopback port serving the MCP OAuth callback, kept fixed so OAuth
+    /// redirect URIs survive the API's ephemeral port changing
+    /// [default: 19561].
+    #[arg(long, env = "NIZE_OAUTH_CALLBACK_PORT")]
+    oauth_callback_port: Option<u16>,
+
+    /// Enable local single-user mode: accept this token as the local user's
+    /// access token. Minted by the desktop app at startup and passed in the
+    /// environment so it does not show up in process listings.
+    #[arg(long, env = "NIZE_LOCAL_TOKEN", hide_env_values = true)]
+    local_token: Option<String>,
+
+    /// Hardware profile JSON probed by the desktop app, stored to gate
+    /// local model providers and to pick first-run defaults.
+    #[arg(long, env = "NIZE_HARDWARE_PROFILE")]
+    hardware_profile: Option<String>,
+
+    /// Seed the database after migrating: a built-in fixture name (e.g.
+    /// `demo`) or a path to a TOML/JSON fixture file.
+    #[arg(long)]
+    seed: Option<String>,
+
+    /// Save warm-start state (config cache, connected MCP servers) to this
+    /// file on shutdown and restore it on the next boot, so a restart does
+    /// not start cold. Also stops on SIGTERM and Ctrl-C to save it.
+    #[arg(long, env = "NIZE_WARM_START", value_name = "FILE")]
+    warm_start: Option<PathBuf>,
+}
+
+const DEFAULT_MCP_PORT: u16 = 19560;
+const DEFAULT_OAUTH_CALLBACK_PORT: u16 = 19561;
+const DEFAULT_DATABASE_URL: &str = "postgres://localhost:5432/nize";
+const DEFAULT_MAX_CONNECTIONS: u32 = 5;
+const DEFAULT_STATEMENT_TIMEOUT: Duration = Duration::from_secs(60);
+const DEFAULT_ENCRYPTION_KEY: &str = "nize-mcp-default-dev-key-change-in-production";
+
+/// Settings resolved from flags, env vars, the config file and defaults.
+struct Settings {
+    port: u16,
+    mcp_port: u16,
+    oauth_callback_port: u16,
+    database_url: String,
+    max_connections: u32,
+    min_connections: Option<u32>,
+    query_limits: QueryLimits,
+    jwt_secret: String,
+    encryption_key: String,
+    metrics_local_only: bool,
+    chat_url: Option<String>,
+    mcp_url: Option<String>,
+    read_only: bool,
+    require_email_verification: bool,
+    /// Configured origins, without the desktop webviews' own.
+    allowed_origins: Vec<String>,
+    locales_dir: Option<PathBuf>,
+    /// The same settings as a config file, secrets shown by their source.
+    effective: ConfigFile,
+}
+
+/// Apply flag > env > file > default precedence.
+fn resolve_settings(args: &Args, file: ConfigFile) -> Result<Settings, ConfigFileError> {
+    use nize_api::config::{self as api_config, file::SecretSource};
+
+    let port = args.port.or(file.server.port).unwrap_or(0);
+    let mcp_port = args
+        .mcp_port
+        .or(file.server.mcp_port)
+        .unwrap_or(DEFAULT_MCP_PORT);
+    let oauth_callback_port = args
+        .oauth_callback_port
+        .or(file.server.oauth_callback_port)
+        .unwrap_or(DEFAULT_OAUTH_CALLBACK_PORT);
+    let database_url = args
+        .database_url
+        .clone()
+        .or(file.database.url)
+        .unwrap_or_else(|| DEFAULT_DATABASE_URL.into());
+    let max_connections = args
+        .max_connections
+        .or(file.database.max_connections)
+        .unwrap_or(DEFAULT_MAX_CONNECTIONS);
+    let min_connections = file.database.min_connections;
+    let statement_timeout = args
+        .statement_timeout_ms
+        .or(file.database.statement_timeout_ms)
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_STATEMENT_TIMEOUT);
+    let slow_query_threshold = args
+        .slow_query_ms
+        .or(file.database.slow_query_ms)
+        .map(Duration::from_millis)
+        .unwrap_or(QueryLimits::default().slow_query_threshold);
+    let query_limits = QueryLimits {
+        statement_timeout: Some(statement_timeout),
+        slow_query_threshold,
+    };
+
+    // Secrets: env var, then the file's source, then the default.
+    let (jwt_secret, jwt_source) = match (
+        nize_api::services::auth::jwt_secret_from_env(),
+        file.auth.jwt_secret,
+    ) {
+        (Some((name, secret)), _) => (secret, Some(SecretSource::Env(name.into()))),
+        (None, Some(source)) => (source.resolve()?, Some(source)),
+        (None, None) => (nize_api::services::auth::resolve_jwt_secret(), None),
+    };
+    let (encryption_key, encryption_source) = match (
+        std::env::var("MCP_ENCRYPTION_KEY").ok(),
+        file.auth.encryption_key,
+    ) {
+        (Some(key), _) => (key, Some(SecretSource::Env("MCP_ENCRYPTION_KEY".into()))),
+        (None, Some(source)) => (source.resolve()?, Some(source)),
+        (None, None) => (DEFAULT_ENCRYPTION_KEY.into(), None),
+    };
+
+    let metrics_local_only = api_config::bool_from_env("METRICS_LOCAL_ONLY")
+        .or(file.features.metrics_local_only)
+        .unwrap_or(false);
+    let read_only = api_config::bool_from_env("NIZE_READ_ONLY")
+        .or(file.features.read_only)
+        .unwrap_or(false);
+    let require_email_verification = api_config::require_email_verification_from_env()
+        .or(file.auth.require_email_verification)
+        .unwrap_or(false);
+    let chat_url = api_config::chat_url_from_env().or(file.server.chat_url);
+    let mcp_url = api_config::mcp_url_from_env().or(file.server.mcp_url);
+
+    let mut allowed_origins = api_config::allowed_origins_from_env();
+    if allowed_origins.is_empty() {
+        allowed_origins = file
+            .cors
+            .allowed_origins
+            .unwrap_or_default()
+            .iter()
+            .flat_map(|o| api_config::parse_origins(o))
+            .collect();
+    }
+    let locales_dir = args.locales_dir.clone().or(file.server.locales_dir);
+
+    let mut effective = ConfigFile::default();
+    effective.server.port = Some(port);
+    effective.server.mcp_port = Some(mcp_port);
+    effective.server.oauth_callback_port = Some(oauth_callback_port);
+    effective.server.chat_url = chat_url.clone();
+    effective.server.mcp_url = mcp_url.clone();
+    effective.server.locales_dir = locales_dir.clone();
+    effective.database.url = Some(database_url.clone());
+    effective.database.max_connections = Some(max_connections);
+    effective.database.min_connections = min_connections;
+    effective.database.statement_timeout_ms = Some(statement_timeout.as_millis() as u64);
+    effective.database.slow_query_ms = Some(slow_query_threshold.as_millis() as u64);
+    effective.auth.jwt_secret = jwt_source;
+    effective.auth.encryption_key = encryption_source;
+    effective.auth.require_email_verification = Some(require_email_verification);
+    effective.cors.allowed_origins = Some(allowed_origins.clone());
+    effective.features.read_only = Some(read_only);
+    effective.features.metrics_local_only = Some(metrics_local_only);
+
+    Ok(Settings {
+        port,
+        mcp_port,
+        oauth_callback_port,
+        database_url,
+        max_connections,
+        min_connections,
+        query_limits,
+        jwt_secret,
+        encryption_key,
+        metrics_local_only,
+        chat_url,
+        mcp_url,
+        read_only,
+        require_email_verification,
+        allowed_origins,
+        locales_dir,
+        effective,
+    })
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenvy::dotenv().ok();
 
+    let args = Args::parse();
+    let file = match &args.config {
+        Some(path) => ConfigFile::load(path)?,
+        None => ConfigFile::default(),
+    };
+    let settings = resolve_settings(&args, file)?;
+
+    if args.print_config {
+        print!("{}", settings.effective.redacted().to_toml());
+        return Ok(());
+    }
+
+    let slow_queries = std::sync::Arc::new(nize_api::diagnostics::SlowQueryLog::new(
+        settings.query_limits,
+    ));
+
     // Write logs to stderr so stdout is reserved for the JSON port message.
-    tracing_subscriber::fmt()
-        .with_writer(std::io::stderr)
-        .with_env_filter(
+    tracing_subscriber::registry()
+        .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "info,nize_api=debug,nize_core=debug".parse().unwrap()),
         )
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(slow_queries.layer())
         .init();
 
-    let args = Args::parse();
-
-    info!(database_url = %args.database_url, port = args.port, "starting nize_desktop_server");
+    info!(
+        database_url = %nize_api::config::file::redact_url(&settings.database_url),
+        port = settings.port,
+        config = ?args.config,
+        "starting nize_desktop_server"
+    );
 
     info!(
-        max_connections = args.max_connections,
+        max_connections = settings.max_connections,
         sidecar = args.sidecar,
         "configuring connection pool"
     );
 
-    let pool = PgPoolOptions::new()
-        .max_connections(args.max_connections)
-        .acquire_timeout(std::time::Duration::from_secs(30))
-        .test_before_acquire(true)
-        .connect(&args.database_url)
-        .await?;
+    // Startup self-check: every dependency is checked and reported with a
+    // remediation hint before giving up.
+    let mut checks = StartupChecks::new();
+
+    // Bind the listeners first so client configs can embed the MCP URL.
+    let bind_addr = format!("127.0.0.1:{}", settings.port);
+    let listener = checks.record(
+        startup::CHECK_API_PORT,
+        tokio::net::TcpListener::bind(&bind_addr).await,
+        |e| startup::bind_hint(e, settings.port, "--port or [server] port"),
+    );
+    let mcp_listener = checks.record(
+        startup::CHECK_MCP_PORT,
+        tokio::net::TcpListener::bind(("127.0.0.1", settings.mcp_port)).await,
+        |e| {
+            startup::bind_hint(
+                e,
+                settings.mcp_port,
+                "--mcp-port, NIZE_MCP_PORT or [server] mcp_port",
+            )
+        },
+    );
+
+    let mut pool_options = PgPoolOptions::new()
+        .max_connections(settings.max_connections)
+        .acquire_timeout(Duration::from_secs(30))
+        .test_before_acquire(true);
+    if let Some(min) = settings.min_connections {
+        pool_options = pool_options.min_connections(min);
+    }
+    let pool = checks.record(
+        startup::CHECK_DATABASE,
+        settings
+            .query_limits
+            .connect(&settings.database_url, pool_options)
+            .await,
+        startup::database_hint,
+    );
+
+    let vector = match &pool {
+        Some(pool) => match startup::vector_available(pool).await {
+            Ok(true) => {
+                checks.pass(startup::CHECK_PGVECTOR);
+                Some(())
+            }
+            Ok(false) => {
+                checks.fail(
+                    startup::CHECK_PGVECTOR,
+                    "The pgvector extension is not available",
+                    startup::PGVECTOR_HINT,
+                );
+                None
+            }
+            Err(e) => checks.record(startup::CHECK_PGVECTOR, Err(e), |_| {
+                startup::PGVECTOR_HINT.into()
+            }),
+        },
+        None => {
+            checks.skip(startup::CHECK_PGVECTOR, startup::CHECK_DATABASE);
+            None
+        }
+    };
 
     // Run database migrations.
-    info!("running database migrations");
-    nize_api::migrate(&pool).await?;
+    let migrated = match (&pool, vector) {
+        (Some(pool), Some(())) => {
+            info!("running database migrations");
+            checks.record(
+                startup::CHECK_MIGRATIONS,
+                nize_api::migrate(pool).await,
+                startup::migration_hint,
+            )
+        }
+        (None, _) => {
+            checks.skip(startup::CHECK_MIGRATIONS, startup::CHECK_DATABASE);
+            None
+        }
+        (Some(_), None) => {
+            checks.skip(startup::CHECK_MIGRATIONS, startup::CHECK_PGVECTOR);
+            None
+        }
+    };
+
+    let jwt_keys = match (&pool, migrated) {
+        (Some(pool), Some(())) => checks.record(
+            startup::CHECK_SECRETS,
+            nize_core::auth::keys::JwtKeys::load(
+                pool,
+                &settings.jwt_secret,
+                &settings.encryption_key,
+            )
+            .await,
+            startup::secrets_hint,
+        ),
+        _ => {
+            checks.skip(startup::CHECK_SECRETS, startup::CHECK_MIGRATIONS);
+            None
+        }
+    };
+
+    let (Some(listener), Some(mcp_listener), Some(pool), Some(jwt_keys)) =
+        (listener, mcp_listener, pool, jwt_keys)
+    else {
+        println!("{}", checks.error_json());
+        std::process::exit(1);
+    };
+    let local_addr = listener.local_addr()?;
+    let mcp_addr = mcp_listener.local_addr()?;
+
+    if let Some(fixture) = &args.seed {
+        info!(fixture = %fixture, "seeding database");
+        let fixture = nize_core::seed::load_fixture(fixture)?;
+        let report =
+            nize_core::seed::apply_fixture(&pool, &fixture, &settings.encryption_key).await?;
+        info!(?report, "seeding complete");
+    }
 
     let config = nize_api::config::ApiConfig {
-        bind_addr: format!("127.0.0.1:{}", args.port),
-        pg_connection_url: args.database_url,
-        jwt_secret: nize_api::services::auth::resolve_jwt_secret(),
-        mcp_encryption_key: std::env::var("MCP_ENCRYPTION_KEY")
-            .unwrap_or_else(|_| "nize-mcp-default-dev-key-change-in-production".into()),
+        bind_addr,
+        pg_connection_url: settings.database_url,
+        jwt_secret: settings.jwt_secret,
+        mcp_encryption_key: settings.encryption_key,
+        metrics_local_only: settings.metrics_local_only,
+        chat_url: settings.chat_url,
+        mcp_url: settings
+            .mcp_url
+            .or_else(|| Some(format!("http://{mcp_addr}/mcp"))),
+        read_only: settings.read_only,
+        // The desktop webviews' own origins, plus any configured extras.
+        allowed_origins: nize_api::cors::DESKTOP_ORIGINS
+            .iter()
+            .map(|o| o.to_string())
+            .chain(settings.allowed_origins)
+            .collect(),
+        data_dir: args.data_dir,
+        oauth_callback_port: Some(settings.oauth_callback_port),
+        require_email_verification: settings.require_email_verification,
     };
 
     // Clone pool for MCP server before moving into API state.
@@ -100,46 +461,137 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         nize_core::config::cache::ConfigCache::new(),
     ));
 
+    let metrics = std::sync::Arc::new(nize_api::metrics::MetricsRegistry::new());
+
+    // A bad profile only loses the gating, so it is not fatal.
+    if let Some(json) = args.hardware_profile {
+        match serde_json::from_str::<nize_core::hardware::HardwareProfile>(&json) {
+            Ok(profile) => {
+                if let Err(e) = nize_core::hardware::report(&pool, &profile).await {
+                    warn!("failed to store hardware profile: {e}");
+                }
+            }
+            Err(e) => warn!("ignoring invalid hardware profile: {e}"),
+        }
+    }
+
+    let local_mode = match args.local_token {
+        Some(token) => {
+            let local_mode =
+                nize_api::services::local_mode::LocalMode::provision(&pool, token).await?;
+            info!(user_id = %local_mode.user().id, "local single-user mode enabled");
+            Some(std::sync::Arc::new(local_mode))
+        }
+        None => None,
+    };
+
+    // MCP client connections, shared by the API and the MCP server.
+    let mut client_pool = match args.terminator_manifest {
+        Some(path) => nize_core::mcp::execution::ClientPool::with_manifest(path),
+        None => nize_core::mcp::execution::ClientPool::new(),
+    };
+    client_pool.set_max_child_memory(args.mcp_max_child_memory_mb.map(|mb| mb * 1024 * 1024));
+    let client_pool = std::sync::Arc::new(client_pool);
+    metrics.set_mcp_pool(client_pool.clone());
+
+    let i18n = nize_api::i18n::Catalog::load(settings.locales_dir.as_deref())?;
+    info!(locales = ?i18n.locales(), "loaded error message bundles");
+
     let state = nize_api::AppState {
         pool,
         config: config.clone(),
         config_cache: config_cache.clone(),
-        oauth_state: std::sync::Arc::new(nize_core::mcp::oauth::OAuthStateStore::new()),
+        metrics: metrics.clone(),
+        events: std::sync::Arc::new(nize_api::events::EventBus::new()),
+        streams: std::sync::Arc::new(nize_api::streams::StreamStore::new()),
+        slow_queries,
+        jwt_keys: std::sync::Arc::new(jwt_keys),
+        telemetry: std::sync::Arc::new(nize_core::telemetry::UsageCounters::new()),
+        i18n: std::sync::Arc::new(i18n),
+        local_mode,
+        mcp_clients: client_pool.clone(),
     };
 
-    let app = nize_api::router(state);
+    if config.read_only {
+        info!(
+            "read-only mode: mutating endpoints and the task and connector schedulers are disabled"
+        );
+    } else {
+        if let Some(chat_url) = config.chat_url.clone() {
+            info!(chat_url = %chat_url, "starting task scheduler");
+            nize_api::services::task_scheduler::spawn_task_scheduler(state.clone(), chat_url);
+        }
+        nize_api::services::connector_sync::spawn_connector_scheduler(state.clone());
+    }
 
-    let listener = tokio::net::TcpListener::bind(&config.bind_addr).await?;
-    let local_addr = listener.local_addr()?;
+    // Idle unless `system.telemetry.enabled` is set with an endpoint.
+    nize_api::services::telemetry::spawn_telemetry_reporter(state.clone());
+
+    nize_api::services::storage::spawn_storage_monitor(state.clone());
+
+    nize_api::services::announcements::spawn_announcement_publisher(state.clone());
+
+    // A taken callback port only breaks MCP OAuth, so it is not fatal.
+    let oauth_callback = match config.oauth_callback_port {
+        Some(port) => match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => Some((listener, nize_api::oauth_callback_router(state.clone()))),
+            Err(e) => {
+                warn!(
+                    port,
+                    "OAuth callback port unavailable, MCP OAuth will fail: {e}"
+                );
+                None
+            }
+        },
+        None => None,
+    };
+
+    let app = nize_api::router(state);
 
     // Build MCP server on a separate port.
     let mcp_ct = CancellationToken::new();
-    let mcp_app = nize_mcp::mcp_router_with_manifest(
+
+    let warm_start = args
+        .warm_start
+        .map(|path| nize_core::warm_start::WarmStart::new(path, &config.pg_connection_url));
+    if let Some(warm_start) = &warm_start
+        && let Err(e) = warm_start
+            .restore(&mcp_pool, &config_cache, &client_pool)
+            .await
+    {
+        warn!("failed to restore warm-start state: {e}");
+    }
+
+    let mcp_app = nize_mcp::mcp_router_with_client_pool(
         mcp_pool,
-        config_cache,
+        config_cache.clone(),
         mcp_ct.clone(),
-        args.terminator_manifest,
+        client_pool.clone(),
         config.mcp_encryption_key.clone(),
     );
-    let mcp_bind = format!("127.0.0.1:{}", args.mcp_port);
-    let mcp_listener = tokio::net::TcpListener::bind(&mcp_bind).await?;
-    let mcp_addr = mcp_listener.local_addr()?;
 
     // Report both bound ports as JSON on stdout so the parent process (Tauri) can read them.
     println!(
         "{}",
-        serde_json::json!({"port": local_addr.port(), "mcpPort": mcp_addr.port()})
+        serde_json::json!({
+            "port": local_addr.port(),
+            "mcpPort": mcp_addr.port(),
+            "checks": checks.checks(),
+        })
     );
 
     if args.sidecar {
         info!("sidecar mode: will exit when parent pipe closes");
-        tokio::spawn(async {
-            use tokio::io::AsyncReadExt;
-            let mut stdin = tokio::io::stdin();
-            let mut buf = [0u8; 1];
-            // Blocks until the parent dies and the OS closes the pipe → EOF.
-            let _ = stdin.read(&mut buf).await;
-            info!("parent pipe closed, shutting down");
+    }
+    if args.sidecar || warm_start.is_some() {
+        let sidecar = args.sidecar;
+        tokio::spawn(async move {
+            nize_api::shutdown::requested(sidecar, warm_start.is_some()).await;
+            if let Some(warm_start) = warm_start
+                && let Err(e) = warm_start.save(&config_cache, &client_pool).await
+            {
+                warn!("failed to save warm-start state: {e}");
+            }
             std::process::exit(0);
         });
     }
@@ -157,8 +609,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
+    // Spawn the OAuth callback listener, stopped together with MCP.
+    if let Some((listener, callback_app)) = oauth_callback {
+        info!(addr = %listener.local_addr()?, "OAuth callback listening");
+        let mcp_ct = mcp_ct.clone();
+        tokio::spawn(async move {
+            axum::serve(listener, callback_app)
+                .with_graceful_shutdown(async move { mcp_ct.cancelled().await })
+                .await
+        });
+    }
+
     // Run REST API on the main task.
-    let api_result = axum::serve(listener, app).await;
+    // With connect info, so security activity can record client addresses.
+    let api_result = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await;
 
     // When the REST API exits, also cancel MCP.
     mcp_ct.cancel();
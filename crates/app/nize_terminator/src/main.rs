@@ -5,13 +5,21 @@
 //! when the parent dies. Designed to survive SIGKILL of the parent process.
 
 mod pid_watch;
+mod report;
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, ExitCode};
+use std::time::{Duration, Instant};
 
 use clap::Parser;
 
+use report::{CleanupReport, CommandResult, CommandStatus};
+
+/// How long killed processes get to exit before they are reported as
+/// still running.
+const KILL_GRACE: Duration = Duration::from_secs(3);
+
 /// Process reaper that watches a parent PID and runs cleanup commands on its death.
 #[derive(Parser)]
 #[command(name = "nize_terminator")]
@@ -32,7 +40,13 @@ fn main() -> ExitCode {
     pid_watch::wait_for_pid_exit(args.parent_pid);
 
     // @awa-impl: PLAN-005 — read manifest and execute cleanup commands
-    let exit_code = run_cleanup(&args.manifest);
+    let report = run_cleanup(&args.manifest, args.parent_pid);
+
+    // Leave a result file for the next desktop start to report.
+    let result_path = report::result_path(&args.manifest);
+    if let Err(e) = report.write(&result_path) {
+        eprintln!("nize_terminator: failed to write result file: {e}");
+    }
 
     // @awa-impl: PLAN-005 — delete manifest after cleanup
     if args.manifest.exists() {
@@ -41,53 +55,118 @@ fn main() -> ExitCode {
         }
     }
 
-    exit_code
+    exit_code(&report)
 }
 
-/// Read the manifest file and execute each command via `sh -c`.
-///
-/// Returns `ExitCode::SUCCESS` if all commands succeed, `ExitCode::FAILURE` otherwise.
-fn run_cleanup(manifest: &PathBuf) -> ExitCode {
+/// Read the manifest file, execute each command via `sh -c`, and verify
+/// that processes targeted by `kill` commands are gone.
+fn run_cleanup(manifest: &Path, parent_pid: u32) -> CleanupReport {
+    let started_at = report::now_ms();
+    let mut report = CleanupReport {
+        parent_pid,
+        started_at,
+        finished_at: started_at,
+        success: true,
+        error: None,
+        commands: Vec::new(),
+    };
+
     let contents = match fs::read_to_string(manifest) {
         Ok(c) => c,
         Err(e) => {
             eprintln!("nize_terminator: failed to read manifest: {e}");
-            return ExitCode::FAILURE;
+            report.success = false;
+            report.error = Some(format!("failed to read manifest: {e}"));
+            report.finished_at = report::now_ms();
+            return report;
         }
     };
 
-    let commands = parse_manifest(&contents);
-
-    if commands.is_empty() {
-        return ExitCode::SUCCESS;
-    }
-
-    let mut all_ok = true;
-    for cmd in &commands {
-        eprintln!("nize_terminator: executing: {cmd}");
-        // @awa-impl: PLAN-006-3.3
-        #[cfg(unix)]
-        let result = Command::new("sh").arg("-c").arg(cmd).status();
-        #[cfg(windows)]
-        let result = Command::new("cmd").arg("/C").arg(cmd).status();
-
-        match result {
-            Ok(status) if status.success() => {}
-            Ok(status) => {
-                eprintln!(
-                    "nize_terminator: command exited with {}: {cmd}",
-                    status.code().unwrap_or(-1)
-                );
-                all_ok = false;
-            }
-            Err(e) => {
-                eprintln!("nize_terminator: failed to execute command: {e}");
-                all_ok = false;
+    for cmd in parse_manifest(&contents) {
+        report.commands.push(run_command(cmd));
+    }
+
+    verify_killed(&mut report.commands);
+
+    report.success = report
+        .commands
+        .iter()
+        .all(|c| c.status == CommandStatus::Ok && c.still_running.is_none());
+    report.finished_at = report::now_ms();
+    report
+}
+
+/// Execute one manifest command and record its outcome.
+fn run_command(cmd: &str) -> CommandResult {
+    eprintln!("nize_terminator: executing: {cmd}");
+    let started_at = report::now_ms();
+    // @awa-impl: PLAN-006-3.3
+    #[cfg(unix)]
+    let result = Command::new("sh").arg("-c").arg(cmd).status();
+    #[cfg(windows)]
+    let result = Command::new("cmd").arg("/C").arg(cmd).status();
+
+    let (status, exit_code, error) = match result {
+        Ok(status) if status.success() => (CommandStatus::Ok, status.code(), None),
+        Ok(status) => {
+            eprintln!(
+                "nize_terminator: command exited with {}: {cmd}",
+                status.code().unwrap_or(-1)
+            );
+            (CommandStatus::Failed, status.code(), None)
+        }
+        Err(e) => {
+            eprintln!("nize_terminator: failed to execute command: {e}");
+            (CommandStatus::Error, None, Some(e.to_string()))
+        }
+    };
+
+    CommandResult {
+        command: cmd.to_string(),
+        status,
+        exit_code,
+        error,
+        still_running: None,
+        started_at,
+        finished_at: report::now_ms(),
+    }
+}
+
+/// Wait up to [`KILL_GRACE`] for the targets of `kill` commands to exit,
+/// marking any that survive.
+fn verify_killed(commands: &mut [CommandResult]) {
+    let deadline = Instant::now() + KILL_GRACE;
+    for result in commands.iter_mut() {
+        let Some(pid) = kill_target(&result.command) else {
+            continue;
+        };
+        while pid_watch::is_pid_alive(pid) {
+            if Instant::now() >= deadline {
+                eprintln!("nize_terminator: process {pid} still running after cleanup");
+                result.still_running = Some(pid);
+                break;
             }
+            std::thread::sleep(Duration::from_millis(100));
         }
     }
+}
+
+/// PID targeted by a `kill <pid>` or `taskkill /PID <pid> ...` command.
+fn kill_target(cmd: &str) -> Option<u32> {
+    let mut words = cmd.split_whitespace();
+    match words.next()? {
+        "kill" => words.last()?.parse().ok(),
+        "taskkill" => {
+            let mut words = words.skip_while(|w| !w.eq_ignore_ascii_case("/PID"));
+            words.next()?;
+            words.next()?.parse().ok()
+        }
+        _ => None,
+    }
+}
 
-    if all_ok {
+fn exit_code(report: &CleanupReport) -> ExitCode {
+    if report.success {
         ExitCode::SUCCESS
     } else {
         ExitCode::FAILURE
@@ -145,7 +224,7 @@ kill 12345
         let dir = tempfile::tempdir().expect("tempdir");
         let manifest = dir.path().join("cleanup.manifest");
         fs::write(&manifest, "true\ntrue\n").expect("write manifest");
-        let code = run_cleanup(&manifest);
+        let code = exit_code(&run_cleanup(&manifest, 1));
         assert_eq!(code, ExitCode::SUCCESS);
     }
 
@@ -155,7 +234,7 @@ kill 12345
         let dir = tempfile::tempdir().expect("tempdir");
         let manifest = dir.path().join("cleanup.manifest");
         fs::write(&manifest, "true\nfalse\ntrue\n").expect("write manifest");
-        let code = run_cleanup(&manifest);
+        let code = exit_code(&run_cleanup(&manifest, 1));
         assert_eq!(code, ExitCode::FAILURE);
     }
 
@@ -163,7 +242,7 @@ kill 12345
     #[test]
     fn run_cleanup_nonexistent_manifest() {
         let path = PathBuf::from("/nonexistent/cleanup.manifest");
-        let code = run_cleanup(&path);
+        let code = exit_code(&run_cleanup(&path, 1));
         assert_eq!(code, ExitCode::FAILURE);
     }
 
@@ -173,7 +252,67 @@ kill 12345
         let dir = tempfile::tempdir().expect("tempdir");
         let manifest = dir.path().join("cleanup.manifest");
         fs::write(&manifest, "\n\n# just comments\n").expect("write manifest");
-        let code = run_cleanup(&manifest);
+        let code = exit_code(&run_cleanup(&manifest, 1));
         assert_eq!(code, ExitCode::SUCCESS);
     }
+
+    // @awa-test: PLAN-005-CleanupReport
+    #[test]
+    fn kill_target_parses_kill_commands() {
+        assert_eq!(kill_target("kill 12345"), Some(12345));
+        assert_eq!(kill_target("kill -9 42"), Some(42));
+        assert_eq!(kill_target("taskkill /PID 77 /F"), Some(77));
+        assert_eq!(kill_target("pg_ctl stop"), None);
+        assert_eq!(kill_target("kill"), None);
+    }
+
+    // @awa-test: PLAN-005-CleanupReport
+    #[cfg(unix)]
+    #[test]
+    fn run_cleanup_records_each_command() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let manifest = dir.path().join("nize-1-cleanup.manifest");
+        fs::write(&manifest, "true\nexit 3\n").expect("write manifest");
+        let report = run_cleanup(&manifest, 1);
+
+        assert!(!report.success);
+        assert_eq!(report.commands.len(), 2);
+        assert_eq!(report.commands[0].status, CommandStatus::Ok);
+        assert_eq!(report.commands[1].status, CommandStatus::Failed);
+        assert_eq!(report.commands[1].exit_code, Some(3));
+        assert!(report.commands[1].finished_at >= report.commands[1].started_at);
+
+        let path = report::result_path(&manifest);
+        assert_eq!(path, dir.path().join("nize-1-cleanup.result.json"));
+        report.write(&path).expect("write result");
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["parentPid"], 1);
+        assert_eq!(json["commands"][1]["status"], "failed");
+        assert_eq!(json["commands"][1]["exitCode"], 3);
+    }
+
+    // @awa-test: PLAN-005-CleanupReport
+    #[cfg(unix)]
+    #[test]
+    fn run_cleanup_verifies_killed_process_exits() {
+        let mut child = Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .expect("spawn sleep");
+        let pid = child.id();
+        // Reap the child once killed so the PID disappears.
+        let reaper = std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let manifest = dir.path().join("cleanup.manifest");
+        fs::write(&manifest, format!("kill {pid}\n")).expect("write manifest");
+        let report = run_cleanup(&manifest, 1);
+        reaper.join().expect("reaper thread");
+
+        assert!(report.success);
+        assert_eq!(report.commands[0].still_running, None);
+    }
 }
@@ -43,6 +43,12 @@ pub fn wait_for_pid_exit(pid: u32) {
     poll_wait(pid);
 }
 
+/// Check whether a PID is still alive.
+#[cfg(unix)]
+pub(crate) fn is_pid_alive(pid: u32) -> bool {
+    is_pid_alive_unix(pid)
+}
+
 /// Check whether a PID is still alive via `kill(pid, 0)`.
 #[cfg(unix)]
 fn is_pid_alive_unix(pid: u32) -> bool {
@@ -180,6 +186,20 @@ fn windows_poll_wait(pid: u32) {
     }
 }
 
+/// Check whether a PID is still alive via `OpenProcess`.
+#[cfg(target_os = "windows")]
+pub(crate) fn is_pid_alive(pid: u32) -> bool {
+    use windows_sys::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    // SAFETY: OpenProcess with minimal access just to check existence.
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    if handle.is_null() {
+        return false;
+    }
+    unsafe { windows_sys::Win32::Foundation::CloseHandle(handle) };
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,83 @@
+// @awa-component: PLAN-005-CleanupReport
+//! Structured cleanup result written next to the manifest.
+//!
+//! The terminator only runs after an unclean shutdown (a graceful exit
+//! kills it and deletes the manifest), so the presence of a result file is
+//! itself the record of one. The desktop app reads and removes these files
+//! on its next start.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+/// Outcome of one manifest command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommandStatus {
+    /// The command exited successfully.
+    Ok,
+    /// The command ran but exited with a non-zero status.
+    Failed,
+    /// The command could not be started.
+    Error,
+}
+
+/// Result of one manifest command.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandResult {
+    pub command: String,
+    pub status: CommandStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// For `kill` commands: the PID they target, if it was still alive
+    /// after the grace period.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub still_running: Option<u32>,
+    /// Unix time in milliseconds.
+    pub started_at: u64,
+    /// Unix time in milliseconds.
+    pub finished_at: u64,
+}
+
+/// Result of a whole cleanup run.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupReport {
+    pub parent_pid: u32,
+    /// Unix time in milliseconds.
+    pub started_at: u64,
+    /// Unix time in milliseconds.
+    pub finished_at: u64,
+    pub success: bool,
+    /// Set when the manifest itself could not be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub commands: Vec<CommandResult>,
+}
+
+impl CleanupReport {
+    /// Write the report as pretty JSON to `path`.
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        fs::write(path, json)
+    }
+}
+
+/// Result file path for a manifest:
+/// `nize-<pid>-cleanup.manifest` → `nize-<pid>-cleanup.result.json`.
+pub fn result_path(manifest: &Path) -> PathBuf {
+    manifest.with_extension("result.json")
+}
+
+/// Current Unix time in milliseconds.
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
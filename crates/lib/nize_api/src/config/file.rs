@@ -0,0 +1,294 @@
+//! TOML configuration file for the API server (`--config <path>`).
+//!
+//! Every setting is optional. The server resolves each one as command-line
+//! flag > environment variable > file > built-in default, so a file can
+//! hold a deployment's baseline while flags and env vars still override it.
+//!
+//! ```toml
+//! [server]
+//! port = 3100
+//! mcp_port = 3101
+//! oauth_callback_port = 3102
+//! chat_url = "http://127.0.0.1:3000"
+//!
+//! [database]
+//! url = "postgres://nize@localhost:5432/nize"
+//! max_connections = 10
+//! statement_timeout_ms = 30000
+//! slow_query_ms = 500
+//!
+//! [auth]
+//! jwt_secret = { file = "/run/secrets/nize-jwt" }
+//! encryption_key = { env = "NIZE_ENCRYPTION_KEY" }
+//! require_email_verification = true
+//!
+//! [cors]
+//! allowed_origins = ["https://nize.example.com"]
+//!
+//! [features]
+//! read_only = false
+//! metrics_local_only = true
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Shown in place of secret values by [`ConfigFile::redacted`].
+pub const REDACTED: &str = "<redacted>";
+
+/// Errors loading a configuration file.
+#[derive(Debug, Error)]
+pub enum ConfigFileError {
+    #[error("IO error reading {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid config file {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+
+    #[error("Secret not available: {0}")]
+    Secret(String),
+}
+
+/// Contents of a configuration file. Unknown keys are rejected so typos
+/// do not silently fall back to defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub server: ServerSection,
+    pub database: DatabaseSection,
+    pub auth: AuthSection,
+    pub cors: CorsSection,
+    pub features: FeaturesSection,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSection {
+    /// REST API port (0 = ephemeral).
+    pub port: Option<u16>,
+    /// MCP server port (0 = ephemeral).
+    pub mcp_port: Option<u16>,
+    /// See [`super::ApiConfig::oauth_callback_port`].
+    pub oauth_callback_port: Option<u16>,
+    /// See [`super::ApiConfig::chat_url`].
+    pub chat_url: Option<String>,
+    /// See [`super::ApiConfig::mcp_url`].
+    pub mcp_url: Option<String>,
+    /// Directory of error message bundles (see [`crate::i18n`]).
+    pub locales_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseSection {
+    /// PostgreSQL connection URL.
+    pub url: Option<String>,
+    /// Upper bound of the connection pool.
+    pub max_connections: Option<u32>,
+    /// Connections kept open while idle.
+    pub min_connections: Option<u32>,
+    /// `statement_timeout` of every pooled connection; `0` disables it.
+    pub statement_timeout_ms: Option<u64>,
+    /// Statements running longer than this are logged as slow.
+    pub slow_query_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthSection {
+    /// Legacy HS256 JWT secret (see [`super::ApiConfig::jwt_secret`]).
+    pub jwt_secret: Option<SecretSource>,
+    /// Key for secrets stored in the database (see
+    /// [`super::ApiConfig::mcp_encryption_key`]).
+    pub encryption_key: Option<SecretSource>,
+    /// See [`super::ApiConfig::require_email_verification`].
+    pub require_email_verification: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsSection {
+    /// See [`super::ApiConfig::allowed_origins`].
+    pub allowed_origins: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeaturesSection {
+    pub read_only: Option<bool>,
+    pub metrics_local_only: Option<bool>,
+}
+
+/// Where a secret comes from, so files need not contain the secret itself:
+/// `{ value = "…" }`, `{ env = "VAR" }` or `{ file = "/path" }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretSource {
+    Value(String),
+    Env(String),
+    /// Read from a file; surrounding whitespace is trimmed.
+    File(PathBuf),
+}
+
+impl SecretSource {
+    /// Read the secret. Empty secrets are an error.
+    pub fn resolve(&self) -> Result<String, ConfigFileError> {
+        let secret = match self {
+            SecretSource::Value(value) => value.clone(),
+            SecretSource::Env(name) => std::env::var(name)
+                .map_err(|_| ConfigFileError::Secret(format!("env var {name} is not set")))?,
+            SecretSource::File(path) => std::fs::read_to_string(path)
+                .map_err(|source| ConfigFileError::Io {
+                    path: path.clone(),
+                    source,
+                })?
+                .trim()
+                .to_string(),
+        };
+        if secret.is_empty() {
+            return Err(ConfigFileError::Secret(format!(
+                "{} is empty",
+                self.describe()
+            )));
+        }
+        Ok(secret)
+    }
+
+    /// The source with any inline value hidden.
+    pub fn redacted(&self) -> Self {
+        match self {
+            SecretSource::Value(_) => SecretSource::Value(REDACTED.into()),
+            other => other.clone(),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            SecretSource::Value(_) => "inline secret".into(),
+            SecretSource::Env(name) => format!("env var {name}"),
+            SecretSource::File(path) => format!("secret file {}", path.display()),
+        }
+    }
+}
+
+impl ConfigFile {
+    /// Read and parse `path`.
+    pub fn load(path: &Path) -> Result<Self, ConfigFileError> {
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|source| ConfigFileError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// A copy safe to print: inline secrets and database passwords hidden.
+    pub fn redacted(&self) -> Self {
+        let mut copy = self.clone();
+        copy.database.url = copy.database.url.as_deref().map(redact_url);
+        copy.auth.jwt_secret = copy.auth.jwt_secret.as_ref().map(SecretSource::redacted);
+        copy.auth.encryption_key = copy
+            .auth
+            .encryption_key
+            .as_ref()
+            .map(SecretSource::redacted);
+        copy
+    }
+
+    /// Render as TOML, in the same format [`ConfigFile::load`] reads.
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("config file serializes to TOML")
+    }
+}
+
+/// Hide the password of a connection URL (as `redacted`, which needs no
+/// percent-encoding); unparsable URLs are hidden entirely since they may
+/// still contain one.
+pub fn redact_url(raw: &str) -> String {
+    match url::Url::parse(raw) {
+        Ok(mut url) => {
+            if url.password().is_some() {
+                let _ = url.set_password(Some("redacted"));
+            }
+            url.to_string()
+        }
+        Err(_) => REDACTED.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sections_and_rejects_unknown_keys() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            [server]
+            port = 3100
+            [database]
+            url = "postgres://nize:hunter2@db:5432/nize"
+            max_connections = 10
+            [auth]
+            jwt_secret = { value = "s3cret" }
+            encryption_key = { env = "NIZE_TEST_UNSET_KEY" }
+            require_email_verification = false
+            [cors]
+            allowed_origins = ["https://nize.example.com"]
+            [features]
+            read_only = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(file.server.port, Some(3100));
+        assert_eq!(file.server.mcp_port, None);
+        assert_eq!(file.database.max_connections, Some(10));
+        assert_eq!(file.features.read_only, Some(true));
+        assert_eq!(file.auth.require_email_verification, Some(false));
+        assert_eq!(
+            file.auth.jwt_secret.as_ref().unwrap().resolve().unwrap(),
+            "s3cret"
+        );
+        assert!(
+            file.auth
+                .encryption_key
+                .as_ref()
+                .unwrap()
+                .resolve()
+                .is_err()
+        );
+
+        assert!(toml::from_str::<ConfigFile>("[server]\nprot = 1").is_err());
+        assert!(toml::from_str::<ConfigFile>("[databse]").is_err());
+    }
+
+    #[test]
+    fn redacted_output_hides_secrets_and_round_trips() {
+        let mut file = ConfigFile::default();
+        file.database.url = Some("postgres://nize:hunter2@db:5432/nize".into());
+        file.auth.jwt_secret = Some(SecretSource::Value("s3cret".into()));
+        file.auth.encryption_key = Some(SecretSource::File("/run/secrets/key".into()));
+
+        let text = file.redacted().to_toml();
+        assert!(!text.contains("hunter2"), "{text}");
+        assert!(!text.contains("s3cret"), "{text}");
+        assert!(text.contains("/run/secrets/key"), "{text}");
+
+        let parsed: ConfigFile = toml::from_str(&text).unwrap();
+        assert_eq!(parsed, file.redacted());
+        assert_eq!(
+            parsed.database.url.as_deref(),
+            Some("postgres://nize:redacted@db:5432/nize")
+        );
+    }
+}
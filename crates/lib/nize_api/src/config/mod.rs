@@ -0,0 +1,187 @@
+//! API server configuration.
+
+pub mod file;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::services::auth::resolve_jwt_secret;
+
+/// Configuration for the API server.
+#[derive(Clone, Debug)]
+pub struct ApiConfig {
+    /// Address to bind the HTTP listener (e.g. "127.0.0.1:3100").
+    pub bind_addr: String,
+    /// PostgreSQL connection URL.
+    pub pg_connection_url: String,
+    /// Legacy HS256 JWT secret, used until the first signing key rotation.
+    pub jwt_secret: String,
+    /// Encryption key for MCP server secrets (API keys, OAuth secrets).
+    pub mcp_encryption_key: String,
+    /// Allow unauthenticated `GET /metrics` from loopback clients.
+    pub metrics_local_only: bool,
+    /// Base URL of the chat app that runs scheduled tasks (e.g.
+    /// "http://127.0.0.1:3000"). The task scheduler is disabled when unset.
+    pub chat_url: Option<String>,
+    /// URL MCP clients connect to (e.g. "http://127.0.0.1:3101/mcp"), used
+    /// in generated client configs. Unset disables them.
+    pub mcp_url: Option<String>,
+    /// Reject mutating endpoints with 403 (reads, login and chat still
+    /// work). For demo deployments and read replicas.
+    pub read_only: bool,
+    /// Browser origins allowed to call the API with credentials (CORS) and
+    /// to send cookie-authenticated mutations, in addition to the
+    /// `cors.allowedOrigins` config value. Entries may use `*.` subdomain
+    /// and `:*` port wildcards (see [`crate::cors`]). With neither set,
+    /// only loopback origins are allowed.
+    pub allowed_origins: Vec<String>,
+    /// Data directory of a local database (the desktop's PGlite
+    /// directory), whose size the storage monitor reports. Unset for an
+    /// external server, where only the database size is known.
+    pub data_dir: Option<PathBuf>,
+    /// Loopback port of a listener that serves only the MCP OAuth callback,
+    /// so redirect URIs registered with providers stay the same when the
+    /// API port changes between runs. Unset serves the callback on
+    /// `bind_addr`.
+    pub oauth_callback_port: Option<u16>,
+    /// New accounts must verify their email address before they can sign
+    /// in. The first account (the admin) is exempt. Verification links
+    /// point at `chat_url`.
+    pub require_email_verification: bool,
+}
+
+impl ApiConfig {
+    /// Reads configuration from environment variables with sensible defaults.
+    ///
+    /// | Variable           | Default                                     |
+    /// |--------------------|---------------------------------------------|
+    /// | `BIND_ADDR`        | `127.0.0.1:3100`                            |
+    /// | `DATABASE_URL`     | `postgres://localhost:5432/nize`             |
+    /// | `JWT_SECRET` / `AUTH_SECRET` | generated & persisted to file        |
+    /// | `METRICS_LOCAL_ONLY` | `false`                                   |
+    /// | `NIZE_CHAT_URL`    | unset (task scheduler disabled)             |
+    /// | `NIZE_MCP_URL`     | unset (client configs disabled)             |
+    /// | `NIZE_READ_ONLY`   | `false`                                     |
+    /// | `NIZE_ALLOWED_ORIGINS` | unset (loopback only)                   |
+    /// | `NIZE_DATA_DIR`    | unset (database size only)                  |
+    /// | `NIZE_OAUTH_CALLBACK_PORT` | unset (callback on `BIND_ADDR`)     |
+    /// | `NIZE_REQUIRE_EMAIL_VERIFICATION` | `true`                       |
+    pub fn from_env() -> Self {
+        Self {
+            bind_addr: std::env::var("BIND_ADDR").unwrap_or_else(|_| "127.0.0.1:3100".into()),
+            pg_connection_url: std::env::var("DATABASE_URL")
+                .unwrap_or_else(|_| "postgres://localhost:5432/nize".into()),
+            jwt_secret: resolve_jwt_secret(),
+            mcp_encryption_key: std::env::var("MCP_ENCRYPTION_KEY")
+                .unwrap_or_else(|_| "nize-mcp-default-dev-key-change-in-production".into()),
+            metrics_local_only: metrics_local_only_from_env(),
+            chat_url: chat_url_from_env(),
+            mcp_url: mcp_url_from_env(),
+            read_only: read_only_from_env(),
+            allowed_origins: allowed_origins_from_env(),
+            data_dir: data_dir_from_env(),
+            oauth_callback_port: oauth_callback_port_from_env(),
+            require_email_verification: require_email_verification_from_env().unwrap_or(true),
+        }
+    }
+
+    /// Address the MCP OAuth callback is served on: the dedicated callback
+    /// listener when one is configured, otherwise the API itself.
+    pub fn oauth_callback_addr(&self) -> String {
+        match self.oauth_callback_port {
+            Some(port) => format!("127.0.0.1:{port}"),
+            None => self.bind_addr.clone(),
+        }
+    }
+}
+
+/// Reads `NIZE_CHAT_URL`, ignoring an empty value.
+pub fn chat_url_from_env() -> Option<String> {
+    std::env::var("NIZE_CHAT_URL")
+        .ok()
+        .map(|v| v.trim().trim_end_matches('/').to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Reads `NIZE_MCP_URL`, ignoring an empty value. Overrides the URL
+/// derived from the MCP listener's address.
+pub fn mcp_url_from_env() -> Option<String> {
+    std::env::var("NIZE_MCP_URL")
+        .ok()
+        .map(|v| v.trim().trim_end_matches('/').to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Reads `NIZE_DATA_DIR`, ignoring an empty value.
+pub fn data_dir_from_env() -> Option<PathBuf> {
+    std::env::var_os("NIZE_DATA_DIR")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Reads `NIZE_OAUTH_CALLBACK_PORT`, ignoring an empty or invalid value.
+pub fn oauth_callback_port_from_env() -> Option<u16> {
+    std::env::var("NIZE_OAUTH_CALLBACK_PORT")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// Reads `NIZE_DB_STATEMENT_TIMEOUT_MS`, ignoring an empty or invalid
+/// value. `0` disables the timeout.
+pub fn statement_timeout_from_env() -> Option<Duration> {
+    millis_from_env("NIZE_DB_STATEMENT_TIMEOUT_MS")
+}
+
+/// Reads `NIZE_DB_SLOW_QUERY_MS`, ignoring an empty or invalid value.
+pub fn slow_query_threshold_from_env() -> Option<Duration> {
+    millis_from_env("NIZE_DB_SLOW_QUERY_MS")
+}
+
+fn millis_from_env(name: &str) -> Option<Duration> {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .map(Duration::from_millis)
+}
+
+/// Reads `METRICS_LOCAL_ONLY` (`1` / `true` enable it).
+pub fn metrics_local_only_from_env() -> bool {
+    bool_from_env("METRICS_LOCAL_ONLY").unwrap_or(false)
+}
+
+/// Reads `NIZE_READ_ONLY` (`1` / `true` enable it).
+pub fn read_only_from_env() -> bool {
+    bool_from_env("NIZE_READ_ONLY").unwrap_or(false)
+}
+
+/// Reads `NIZE_REQUIRE_EMAIL_VERIFICATION`, `None` when unset so each
+/// server can apply its own default.
+pub fn require_email_verification_from_env() -> Option<bool> {
+    bool_from_env("NIZE_REQUIRE_EMAIL_VERIFICATION")
+}
+
+/// Reads a boolean env var: `1` / `true` are true, any other value false,
+/// and `None` when unset (so a config file can supply the value).
+pub fn bool_from_env(name: &str) -> Option<bool> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+/// Reads `NIZE_ALLOWED_ORIGINS`, a comma-separated list of origins
+/// (e.g. `http://localhost:3000,https://nize.example.com`).
+pub fn allowed_origins_from_env() -> Vec<String> {
+    std::env::var("NIZE_ALLOWED_ORIGINS")
+        .map(|v| parse_origins(&v))
+        .unwrap_or_default()
+}
+
+/// Split a comma-separated origin list, dropping blanks and trailing
+/// slashes (browsers send origins without them).
+pub fn parse_origins(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|o| o.trim().trim_end_matches('/').to_string())
+        .filter(|o| !o.is_empty())
+        .collect()
+}
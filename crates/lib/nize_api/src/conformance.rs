@@ -0,0 +1,1529 @@
+//! Conformance of handler responses with the API spec.
+//!
+//! Every operation of the generated OpenAPI document (written by
+//! `scripts/generate-api.sh` from the TypeSpec sources in `.awa/specs`) is
+//! matched to the handler the router serves it with. The JSON Schema of
+//! the handler's response type, derived with `schemars`, must name the
+//! same fields at every level as the operation's success response in the
+//! spec, so a field renamed, added or dropped on one side only — or a body
+//! built as untyped JSON where the spec has a model — fails the test.
+//!
+//! Fields are named in camelCase, as in the TypeSpec sources; response
+//! types derive `Serialize` and `JsonSchema` with
+//! `#[serde(rename_all = "camelCase")]`. Values the spec leaves free-form
+//! (`Record<unknown>`, `unknown`) are not checked below their own key.
+
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::path::Path;
+
+use axum::Json;
+use axum::body::Bytes;
+use axum::http::{Method, StatusCode};
+use axum::response::{Html, Response, Sse};
+use axum_extra::either::Either;
+use axum_extra::extract::CookieJar;
+use schemars::JsonSchema;
+use serde_json::Value;
+
+use crate::generated::routes;
+use crate::handlers::config as config_handlers;
+use crate::handlers::{
+    account, admin_permissions, admin_roles, analytics, announcements, auth, chat, connectors,
+    conversations, database, diagnostics as diagnostics_handlers, evals, events as events_handlers,
+    feedback, hello, ingest, integrity, mcp_config, mcp_recordings, mcp_tokens, meta, moderation,
+    notes, notifications, oauth, permissions, providers, regeneration, security_activity,
+    signing_keys, storage, streams as stream_handlers, sync, tags, tasks, telemetry, trace, usage,
+    workspaces,
+};
+
+/// The generated OpenAPI document, relative to this crate.
+const SPEC_PATH: &str = "../../../codegen/nize-api/tsp-output/@typespec/openapi3/openapi.yaml";
+
+/// What a response carries.
+#[derive(Debug)]
+enum Body {
+    /// A JSON body with this JSON Schema.
+    Json(Value),
+    /// No body.
+    Empty,
+    /// A streamed or non-JSON body.
+    Raw,
+}
+
+impl Body {
+    fn kind(&self) -> &'static str {
+        match self {
+            Body::Json(_) => "JSON",
+            Body::Empty => "no body",
+            Body::Raw => "a non-JSON body",
+        }
+    }
+}
+
+/// Response types whose body shape is known from the type: the bodies a
+/// successful response may carry.
+trait ResponseBody {
+    fn bodies() -> Vec<Body>;
+}
+
+impl<T: JsonSchema> ResponseBody for Json<T> {
+    fn bodies() -> Vec<Body> {
+        vec![Body::Json(schemars::schema_for!(T).to_value())]
+    }
+}
+
+impl<T: ResponseBody, E> ResponseBody for Result<T, E> {
+    fn bodies() -> Vec<Body> {
+        T::bodies()
+    }
+}
+
+impl<L: ResponseBody, R: ResponseBody> ResponseBody for Either<L, R> {
+    fn bodies() -> Vec<Body> {
+        let mut bodies = L::bodies();
+        bodies.extend(R::bodies());
+        bodies
+    }
+}
+
+/// Response parts (status, headers, cookies) followed by the body.
+impl<P, T: ResponseBody> ResponseBody for (P, T) {
+    fn bodies() -> Vec<Body> {
+        T::bodies()
+    }
+}
+
+impl<P1, P2, T: ResponseBody> ResponseBody for (P1, P2, T) {
+    fn bodies() -> Vec<Body> {
+        T::bodies()
+    }
+}
+
+macro_rules! empty_body {
+    ($($ty:ty),*) => {
+        $(impl ResponseBody for $ty {
+            fn bodies() -> Vec<Body> {
+                vec![Body::Empty]
+            }
+        })*
+    };
+}
+
+empty_body!((), StatusCode, CookieJar);
+
+/// Headers.
+impl<K, V, const N: usize> ResponseBody for [(K, V); N] {
+    fn bodies() -> Vec<Body> {
+        vec![Body::Empty]
+    }
+}
+
+macro_rules! raw_body {
+    ($($ty:ty),*) => {
+        $(impl ResponseBody for $ty {
+            fn bodies() -> Vec<Body> {
+                vec![Body::Raw]
+            }
+        })*
+    };
+}
+
+raw_body!(Response, Bytes, String, Html<String>);
+
+impl<S> ResponseBody for Sse<S> {
+    fn bodies() -> Vec<Body> {
+        vec![Body::Raw]
+    }
+}
+
+/// Handler functions, by the response type of their future.
+trait Handler<Args> {
+    fn bodies(&self) -> Vec<Body>;
+}
+
+macro_rules! handler {
+    ($($arg:ident),*) => {
+        impl<F, Fut, $($arg,)*> Handler<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> Fut,
+            Fut: Future,
+            Fut::Output: ResponseBody,
+        {
+            fn bodies(&self) -> Vec<Body> {
+                <Fut::Output as ResponseBody>::bodies()
+            }
+        }
+    };
+}
+
+handler!();
+handler!(A1);
+handler!(A1, A2);
+handler!(A1, A2, A3);
+handler!(A1, A2, A3, A4);
+handler!(A1, A2, A3, A4, A5);
+handler!(A1, A2, A3, A4, A5, A6);
+handler!(A1, A2, A3, A4, A5, A6, A7);
+handler!(A1, A2, A3, A4, A5, A6, A7, A8);
+
+/// An operation as the router serves it.
+struct Operation {
+    method: Method,
+    path: &'static str,
+    bodies: Vec<Body>,
+}
+
+fn op<Args>(method: Method, path: &'static str, handler: impl Handler<Args>) -> Operation {
+    Operation {
+        method,
+        path,
+        bodies: handler.bodies(),
+    }
+}
+
+/// Every spec operation with the handler `router` registers for it.
+fn operations() -> Vec<Operation> {
+    vec![
+        op(Method::GET, routes::GET_HELLO, hello::hello_world),
+        op(Method::GET, routes::GET_META, meta::meta_handler),
+        op(Method::POST, routes::POST_AUTH_LOGIN, auth::login_handler),
+        op(
+            Method::POST,
+            routes::POST_AUTH_REGISTER,
+            auth::register_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_AUTH_REFRESH,
+            auth::refresh_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_AUTH_LOCAL,
+            auth::local_login_handler,
+        ),
+        op(Method::POST, routes::POST_AUTH_LOGOUT, auth::logout_handler),
+        op(
+            Method::GET,
+            routes::GET_AUTH_STATUS,
+            auth::auth_status_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_AUTH_VERIFY,
+            auth::verify_email_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_AUTH_VERIFY_RESEND,
+            auth::resend_verification_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_AUTH_JWKS,
+            signing_keys::jwks_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_AUTH_OAUTH_MCP_CALLBACK,
+            oauth::oauth_callback_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_PERMISSIONS_SHARED_TOKEN,
+            permissions::access_shared_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_PERMISSIONS_SHARED_TOKEN_MESSAGES,
+            permissions::shared_messages_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_ACCOUNT,
+            account::delete_account_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ACCOUNT_EXPORT,
+            account::create_export_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ACCOUNT_EXPORT_ID,
+            account::get_export_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ACCOUNT_EXPORT_ID_DOWNLOAD,
+            account::download_export_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ACCOUNT_IMPORT,
+            account::import_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_AUTH_MCP_TOKENS,
+            mcp_tokens::create_mcp_token_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_AUTH_MCP_TOKENS,
+            mcp_tokens::list_mcp_tokens_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_AUTH_MCP_TOKENS_ID,
+            mcp_tokens::revoke_mcp_token_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_AUTH_MCP_TOKENS_ID_CLIENT_CONFIG,
+            mcp_tokens::client_config_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_AUTH_ACTIVITY,
+            security_activity::list_activity_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_AUTH_LOGOUT_ALL,
+            auth::logout_all_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_CONFIG_USER,
+            config_handlers::user_config_list_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_CONFIG_USER_KEY,
+            config_handlers::user_config_update_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_CONFIG_USER_KEY,
+            config_handlers::user_config_reset_handler,
+        ),
+        op(Method::POST, routes::POST_CHAT, chat::chat_handler),
+        op(
+            Method::POST,
+            routes::POST_CHAT_ESTIMATE,
+            chat::estimate_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_CHAT_STREAMS_ID_POLL,
+            stream_handlers::poll_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_CONVERSATIONS,
+            conversations::list_conversations_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_CONVERSATIONS,
+            conversations::create_conversation_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_CONVERSATIONS_ID,
+            conversations::get_conversation_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_CONVERSATIONS_ID,
+            conversations::update_conversation_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_CONVERSATIONS_ID,
+            conversations::delete_conversation_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_CONVERSATIONS_ID_MESSAGES,
+            conversations::save_messages_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_CONVERSATIONS_ID_SUMMARY,
+            conversations::save_summary_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_CONVERSATIONS_ID_TOOLS,
+            conversations::get_tool_selection_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_CONVERSATIONS_ID_TOOLS,
+            conversations::set_tool_selection_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_CONVERSATIONS_ID_FEEDBACK,
+            feedback::list_feedback_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_CONVERSATIONS_ID_MESSAGES_MESSAGEID_FEEDBACK,
+            feedback::set_feedback_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_CONVERSATIONS_ID_MESSAGES_MESSAGEID_FEEDBACK,
+            feedback::delete_feedback_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_CONVERSATIONS_ID_MESSAGES_MESSAGEID_REGENERATE,
+            regeneration::regenerate_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_CONVERSATIONS_ID_MESSAGES_MESSAGEID_CANDIDATES,
+            regeneration::list_candidates_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_CONVERSATIONS_ID_MESSAGES_MESSAGEID_CANDIDATES_CANDIDATEID_SELECT,
+            regeneration::select_candidate_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_CONVERSATIONS_ID_TRACE,
+            trace::record_trace_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_INGEST,
+            ingest::list_documents_handler,
+        ),
+        op(Method::POST, routes::POST_INGEST, ingest::upload_handler),
+        op(
+            Method::POST,
+            routes::POST_INGEST_RETRIEVE,
+            ingest::retrieve_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_INGEST_SEARCH,
+            ingest::search_documents_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_INGEST_ID,
+            ingest::get_document_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_INGEST_ID_CONTENT,
+            ingest::download_document_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_INGEST_ID,
+            ingest::delete_document_handler,
+        ),
+        op(Method::GET, routes::GET_NOTES, notes::list_notes_handler),
+        op(Method::POST, routes::POST_NOTES, notes::create_note_handler),
+        op(
+            Method::GET,
+            routes::GET_NOTES_SEARCH,
+            notes::search_notes_handler,
+        ),
+        op(Method::GET, routes::GET_NOTES_ID, notes::get_note_handler),
+        op(
+            Method::PATCH,
+            routes::PATCH_NOTES_ID,
+            notes::update_note_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_NOTES_ID,
+            notes::delete_note_handler,
+        ),
+        op(Method::GET, routes::GET_USAGE_LIMITS, usage::limits_handler),
+        op(
+            Method::GET,
+            routes::GET_EVENTS,
+            events_handlers::events_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_EVENTS_STREAMS,
+            events_handlers::create_event_stream_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ANNOUNCEMENTS,
+            announcements::list_announcements_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ANNOUNCEMENTS_ID_DISMISS,
+            announcements::dismiss_announcement_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_NOTIFICATIONS,
+            notifications::list_notifications_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_NOTIFICATIONS_ID_READ,
+            notifications::mark_read_handler,
+        ),
+        op(Method::GET, routes::GET_SYNC, sync::pull_handler),
+        op(Method::POST, routes::POST_SYNC, sync::push_handler),
+        op(Method::GET, routes::GET_TAGS, tags::list_tags_handler),
+        op(Method::POST, routes::POST_TAGS, tags::create_tag_handler),
+        op(
+            Method::PATCH,
+            routes::PATCH_TAGS_ID,
+            tags::update_tag_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_TAGS_ID,
+            tags::delete_tag_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_TAGS_RESOURCETYPE_RESOURCEID,
+            tags::list_resource_tags_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_TAGS_RESOURCETYPE_RESOURCEID,
+            tags::attach_tag_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_TAGS_RESOURCETYPE_RESOURCEID_TAGID,
+            tags::detach_tag_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_CONNECTORS,
+            connectors::list_connectors_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_CONNECTORS,
+            connectors::create_connector_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_CONNECTORS_ID,
+            connectors::get_connector_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_CONNECTORS_ID,
+            connectors::update_connector_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_CONNECTORS_ID,
+            connectors::delete_connector_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_CONNECTORS_ID_SYNC,
+            connectors::sync_connector_handler,
+        ),
+        op(Method::GET, routes::GET_TASKS, tasks::list_tasks_handler),
+        op(Method::POST, routes::POST_TASKS, tasks::create_task_handler),
+        op(Method::GET, routes::GET_TASKS_ID, tasks::get_task_handler),
+        op(
+            Method::PATCH,
+            routes::PATCH_TASKS_ID,
+            tasks::update_task_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_TASKS_ID,
+            tasks::delete_task_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_TASKS_ID_RUN,
+            tasks::run_task_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_WORKSPACES,
+            workspaces::list_workspaces_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_WORKSPACES,
+            workspaces::create_workspace_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_WORKSPACES_ID,
+            workspaces::update_workspace_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_WORKSPACES_ID,
+            workspaces::delete_workspace_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_WORKSPACES_ID_MCP_SERVERS,
+            workspaces::list_servers_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_WORKSPACES_ID_MCP_SERVERS_SERVERID,
+            workspaces::share_server_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_WORKSPACES_ID_MCP_SERVERS_SERVERID,
+            workspaces::unshare_server_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_WORKSPACES_ID_MCP_SERVERS_SERVERID_SECRETS,
+            workspaces::set_server_secrets_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_WORKSPACES_ID_MEMBERS,
+            workspaces::list_members_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_WORKSPACES_ID_MEMBERS,
+            workspaces::add_member_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_WORKSPACES_ID_MEMBERS_USERID,
+            workspaces::update_member_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_WORKSPACES_ID_MEMBERS_USERID,
+            workspaces::remove_member_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_PERMISSIONS_RESOURCETYPE_RESOURCEID_GRANTS,
+            permissions::create_grant_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_PERMISSIONS_RESOURCETYPE_RESOURCEID_GRANTS,
+            permissions::list_grants_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_PERMISSIONS_GRANTS_GRANTID,
+            permissions::revoke_grant_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_PERMISSIONS_RESOURCETYPE_RESOURCEID_LINKS,
+            permissions::create_link_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_PERMISSIONS_RESOURCETYPE_RESOURCEID_LINKS,
+            permissions::list_links_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_PERMISSIONS_LINKS_LINKID,
+            permissions::update_link_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_PERMISSIONS_LINKS_LINKID,
+            permissions::revoke_link_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_MCP_SERVERS,
+            mcp_config::list_servers_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_MCP_SERVERS,
+            mcp_config::add_server_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_MCP_SERVERS_SERVERID,
+            mcp_config::update_server_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_MCP_SERVERS_SERVERID,
+            mcp_config::delete_server_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_MCP_SERVERS_SERVERID_PREFERENCE,
+            mcp_config::update_preference_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_MCP_SERVERS_SERVERID_TOOLS,
+            mcp_config::list_server_tools_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_MCP_SERVERS_SERVERID_TOOLS_TOOLID,
+            mcp_config::update_tool_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_MCP_SERVERS_SERVERID_OAUTH_STATUS,
+            mcp_config::oauth_status_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_MCP_SERVERS_SERVERID_OAUTH_INITIATE,
+            mcp_config::oauth_initiate_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_MCP_SERVERS_SERVERID_OAUTH_REVOKE,
+            mcp_config::oauth_revoke_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_MCP_TEST_CONNECTION,
+            mcp_config::test_connection_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_MCP_ROUTING,
+            mcp_config::list_routes_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_MCP_ROUTING_DOMAIN,
+            mcp_config::set_route_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_MCP_ROUTING_DOMAIN,
+            mcp_config::delete_route_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_MCP_CATALOG,
+            mcp_config::list_catalog_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_MCP_CATALOG_SLUG_INSTALL,
+            mcp_config::install_catalog_entry_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_CONFIG,
+            config_handlers::admin_config_list_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_ADMIN_CONFIG_SCOPE_KEY,
+            config_handlers::admin_config_update_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_CONFIG_CACHE,
+            config_handlers::admin_cache_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ADMIN_CONFIG_CACHE_REFRESH,
+            config_handlers::admin_cache_refresh_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_CONFIG_HISTORY,
+            config_handlers::admin_config_history_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ADMIN_CONFIG_ROLLBACK_VERSION,
+            config_handlers::admin_config_rollback_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_TELEMETRY_PREVIEW,
+            telemetry::preview_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_STORAGE,
+            storage::usage_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ADMIN_STORAGE_MAINTENANCE,
+            storage::maintenance_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_INTEGRITY,
+            integrity::audit_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ADMIN_INTEGRITY_REPAIR,
+            integrity::repair_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ADMIN_DATABASE_TRANSFER,
+            database::transfer_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ADMIN_PROVIDERS_TEST,
+            providers::test_providers_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_PERMISSIONS_GRANTS,
+            admin_permissions::list_all_grants_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_ADMIN_PERMISSIONS_GRANTS_GRANTID,
+            admin_permissions::admin_revoke_grant_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_PERMISSIONS_GROUPS,
+            admin_permissions::list_all_groups_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_PERMISSIONS_LINKS,
+            admin_permissions::list_all_links_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_ADMIN_PERMISSIONS_LINKS_LINKID,
+            admin_permissions::admin_revoke_link_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_ADMIN_PERMISSIONS_USERS_USERID_ADMIN,
+            admin_permissions::set_admin_role_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_ROLES,
+            admin_roles::list_roles_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ADMIN_ROLES,
+            admin_roles::create_role_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_ADMIN_ROLES_ID,
+            admin_roles::update_role_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_ADMIN_ROLES_ID,
+            admin_roles::delete_role_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_USERS_USERID_ROLES,
+            admin_roles::list_user_roles_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_ADMIN_USERS_USERID_ROLES_ROLEID,
+            admin_roles::assign_role_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_ADMIN_USERS_USERID_ROLES_ROLEID,
+            admin_roles::unassign_role_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_MCP_ADMIN_SERVERS,
+            mcp_config::admin_list_servers_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_MCP_ADMIN_SERVERS,
+            mcp_config::admin_create_server_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_MCP_ADMIN_SERVERS_SERVERID,
+            mcp_config::admin_update_server_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_MCP_ADMIN_SERVERS_SERVERID,
+            mcp_config::admin_delete_server_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_MCP_ADMIN_TEST_ALL,
+            mcp_config::admin_test_all_servers_handler,
+        ),
+        op(
+            Method::PATCH,
+            routes::PATCH_MCP_ADMIN_SERVERS_SERVERID_TOOLS_TOOLID,
+            mcp_config::admin_update_tool_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_MCP_ADMIN_SERVERS_SERVERID_TOOLS_TOOLID_TRANSFORM,
+            mcp_config::admin_set_transform_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_MCP_ADMIN_SERVERS_SERVERID_TOOLS_TOOLID_TRANSFORM,
+            mcp_config::admin_delete_transform_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_MCP_ADMIN_TRANSFORMS,
+            mcp_config::admin_list_transforms_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_MCP_ADMIN_ROUTING,
+            mcp_config::admin_list_routes_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_MCP_ADMIN_ROUTING_DOMAIN,
+            mcp_config::admin_set_route_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_MCP_ADMIN_ROUTING_DOMAIN,
+            mcp_config::admin_delete_route_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_MCP_ADMIN_CATALOG_SLUG,
+            mcp_config::admin_upsert_catalog_entry_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_MCP_ADMIN_CATALOG_SLUG,
+            mcp_config::admin_delete_catalog_entry_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_MCP_ADMIN_CATALOG_SLUG_INSTALL,
+            mcp_config::admin_install_catalog_entry_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_MCP_ADMIN_PROCESSES,
+            mcp_config::admin_list_processes_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_MCP_ADMIN_RECORDINGS,
+            mcp_recordings::list_recordings_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_MCP_ADMIN_RECORDINGS,
+            mcp_recordings::start_recording_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_MCP_ADMIN_RECORDINGS_ID,
+            mcp_recordings::get_recording_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_MCP_ADMIN_RECORDINGS_ID,
+            mcp_recordings::delete_recording_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_MCP_ADMIN_RECORDINGS_ID_STOP,
+            mcp_recordings::stop_recording_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_MCP_ADMIN_RECORDINGS_ID_REPLAY,
+            mcp_recordings::replay_recording_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_ANALYTICS_TOOLS,
+            analytics::tool_analytics_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_DIAGNOSTICS_SLOW_QUERIES,
+            diagnostics_handlers::slow_queries_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_FEEDBACK_EXPORT,
+            feedback::export_feedback_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_EVALS_SUITES,
+            evals::list_suites_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ADMIN_EVALS_RUNS,
+            evals::create_run_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_EVALS_RUNS,
+            evals::list_runs_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_EVALS_RUNS_ID,
+            evals::get_run_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_AUTH_KEYS,
+            signing_keys::list_keys_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ADMIN_AUTH_KEYS_ROTATE,
+            signing_keys::rotate_key_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_ANNOUNCEMENTS,
+            announcements::admin_list_announcements_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ADMIN_ANNOUNCEMENTS,
+            announcements::admin_create_announcement_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_ADMIN_ANNOUNCEMENTS_ID,
+            announcements::admin_update_announcement_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_ADMIN_ANNOUNCEMENTS_ID,
+            announcements::admin_delete_announcement_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_MODERATION_RULES,
+            moderation::list_rules_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ADMIN_MODERATION_RULES,
+            moderation::create_rule_handler,
+        ),
+        op(
+            Method::PUT,
+            routes::PUT_ADMIN_MODERATION_RULES_ID,
+            moderation::update_rule_handler,
+        ),
+        op(
+            Method::DELETE,
+            routes::DELETE_ADMIN_MODERATION_RULES_ID,
+            moderation::delete_rule_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_ADMIN_MODERATION_RESULTS,
+            moderation::list_results_handler,
+        ),
+        op(
+            Method::POST,
+            routes::POST_ADMIN_MODERATION_RESULTS_ID_REVIEW,
+            moderation::review_result_handler,
+        ),
+        op(
+            Method::GET,
+            routes::GET_DEV_CHAT_TRACE,
+            trace::chat_trace_handler,
+        ),
+    ]
+}
+
+/// The generated OpenAPI document, as JSON.
+fn spec() -> Value {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(SPEC_PATH);
+    let source = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "{}: {e}; run scripts/generate-api.sh to generate it",
+            path.display()
+        )
+    });
+    serde_yaml::from_str(&source).expect("OpenAPI document")
+}
+
+/// The spec operation `method path`, if any.
+fn spec_operation<'a>(spec: &'a Value, method: &Method, path: &str) -> Option<&'a Value> {
+    spec["paths"]
+        .get(path)?
+        .get(method.as_str().to_ascii_lowercase())
+}
+
+/// An operation's success response bodies by status, as the spec declares
+/// them. A union of JSON bodies under one status counts as one body per
+/// alternative.
+fn spec_bodies(operation: &Value) -> Vec<(&str, Body)> {
+    let Some(responses) = operation["responses"].as_object() else {
+        return Vec::new();
+    };
+    let mut bodies = Vec::new();
+    for (status, response) in responses
+        .iter()
+        .filter(|(status, _)| status.starts_with('2'))
+    {
+        let status = status.as_str();
+        match response["content"].as_object() {
+            None => bodies.push((status, Body::Empty)),
+            Some(content) if content.is_empty() => bodies.push((status, Body::Empty)),
+            Some(content) => match content.get("application/json") {
+                Some(media) => match media["schema"]["anyOf"].as_array() {
+                    Some(alternatives) => bodies.extend(
+                        alternatives
+                            .iter()
+                            .map(|schema| (status, Body::Json(schema.clone()))),
+                    ),
+                    None => bodies.push((status, Body::Json(media["schema"].clone()))),
+                },
+                None => bodies.push((status, Body::Raw)),
+            },
+        }
+    }
+    bodies
+}
+
+/// A schema reduced to what conformance compares.
+enum Shape<'a> {
+    /// Free-form: any JSON.
+    Any,
+    /// An object: named fields, and the schema of any other keys.
+    Object {
+        fields: BTreeMap<&'a str, &'a Value>,
+        values: Option<&'a Value>,
+    },
+    /// An array of items.
+    Array(Option<&'a Value>),
+    /// A string, number or boolean.
+    Scalar,
+}
+
+impl Shape<'_> {
+    fn kind(&self) -> &'static str {
+        match self {
+            Shape::Any => "free-form JSON",
+            Shape::Object { .. } => "an object",
+            Shape::Array(_) => "an array",
+            Shape::Scalar => "a scalar",
+        }
+    }
+}
+
+/// Follow `$ref`s (`#/components/schemas/…` in the spec, `#/$defs/…` in
+/// a derived schema) within `root`.
+fn resolve<'a>(mut schema: &'a Value, root: &'a Value) -> &'a Value {
+    while let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        schema = reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer))
+            .unwrap_or_else(|| panic!("unresolved $ref {reference}"));
+    }
+    schema
+}
+
+fn is_null(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("null")
+}
+
+/// The types a schema names, without `null`.
+fn types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+    .into_iter()
+    .filter(|t| *t != "null")
+    .collect()
+}
+
+fn shape<'a>(schema: &'a Value, root: &'a Value) -> Shape<'a> {
+    let schema = resolve(schema, root);
+    let Some(map) = schema.as_object() else {
+        return Shape::Any;
+    };
+
+    let mut shapes = Vec::new();
+    for key in ["allOf", "anyOf", "oneOf"] {
+        for alternative in map.get(key).and_then(Value::as_array).into_iter().flatten() {
+            if !is_null(alternative) {
+                shapes.push(shape(alternative, root));
+            }
+        }
+    }
+    let types = types(schema);
+    if map.contains_key("properties")
+        || map.contains_key("additionalProperties")
+        || types.contains(&"object")
+    {
+        let fields = map
+            .get("properties")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .map(|(name, field)| (name.as_str(), field))
+            .collect();
+        let values = match map.get("additionalProperties") {
+            None | Some(Value::Bool(false)) => None,
+            Some(values) => Some(values),
+        };
+        shapes.push(Shape::Object { fields, values });
+    } else if types.contains(&"array") {
+        shapes.push(Shape::Array(map.get("items")));
+    } else if !types.is_empty() || map.contains_key("enum") || map.contains_key("const") {
+        shapes.push(Shape::Scalar);
+    }
+
+    // Alternatives and `allOf` parts: objects merge their fields.
+    let mut merged: Option<Shape> = None;
+    for next in shapes {
+        merged = Some(match (merged, next) {
+            (None, next) => next,
+            (
+                Some(Shape::Object { mut fields, values }),
+                Shape::Object {
+                    fields: more,
+                    values: more_values,
+                },
+            ) => {
+                for (name, field) in more {
+                    fields.entry(name).or_insert(field);
+                }
+                Shape::Object {
+                    fields,
+                    values: values.or(more_values),
+                }
+            }
+            (Some(Shape::Any), _) | (_, Shape::Any) => Shape::Any,
+            (Some(Shape::Scalar), Shape::Scalar) => Shape::Scalar,
+            (Some(Shape::Array(items)), Shape::Array(_)) => Shape::Array(items),
+            // Unions of different kinds are free-form.
+            _ => Shape::Any,
+        });
+    }
+    merged.unwrap_or(Shape::Any)
+}
+
+/// Compares a spec schema with a derived one.
+struct Checker<'a> {
+    spec: &'a Value,
+    derived: &'a Value,
+    seen: HashSet<(*const Value, *const Value)>,
+    errors: Vec<String>,
+}
+
+impl<'a> Checker<'a> {
+    fn compare(&mut self, expected: &'a Value, actual: &'a Value, at: &str) {
+        let (expected, actual) = (resolve(expected, self.spec), resolve(actual, self.derived));
+        if !self.seen.insert((expected, actual)) {
+            return;
+        }
+        match (shape(expected, self.spec), shape(actual, self.derived)) {
+            // Scalars have no fields to disagree on, however they are typed.
+            (Shape::Any, _) | (Shape::Scalar, Shape::Scalar | Shape::Any) => {}
+            (
+                Shape::Object {
+                    fields: expected_fields,
+                    values: expected_values,
+                },
+                Shape::Object {
+                    fields: actual_fields,
+                    values: actual_values,
+                },
+            ) => {
+                if expected_fields.is_empty() {
+                    // A map: every value follows the spec's value schema.
+                    if let Some(expected_values) = expected_values {
+                        for (name, actual) in &actual_fields {
+                            self.compare(expected_values, actual, &format!("{at}/{name}"));
+                        }
+                        if let Some(actual_values) = actual_values {
+                            self.compare(expected_values, actual_values, &format!("{at}/*"));
+                        }
+                    }
+                    return;
+                }
+                for name in expected_fields.keys() {
+                    if !actual_fields.contains_key(name) {
+                        self.errors.push(format!("{at}: missing field `{name}`"));
+                    }
+                }
+                for (name, actual) in &actual_fields {
+                    match expected_fields.get(name) {
+                        Some(expected) => self.compare(expected, actual, &format!("{at}/{name}")),
+                        None => self
+                            .errors
+                            .push(format!("{at}: field `{name}` is not in the spec")),
+                    }
+                }
+            }
+            (Shape::Array(expected), Shape::Array(actual)) => {
+                if let (Some(expected), Some(actual)) = (expected, actual) {
+                    self.compare(expected, actual, &format!("{at}/[]"));
+                }
+            }
+            // `Record<unknown>`: any object will do.
+            (Shape::Object { fields, values }, Shape::Any)
+                if fields.is_empty()
+                    && values.is_none_or(|v| matches!(shape(v, self.spec), Shape::Any)) => {}
+            (expected, Shape::Any) => self.errors.push(format!(
+                "{at}: untyped JSON where the spec has {}",
+                expected.kind()
+            )),
+            (expected, actual) => self.errors.push(format!(
+                "{at}: {} where the spec has {}",
+                actual.kind(),
+                expected.kind()
+            )),
+        }
+    }
+}
+
+/// Differences between a spec schema and a derived one.
+fn compare(spec: &Value, expected: &Value, derived: &Value) -> Vec<String> {
+    let mut checker = Checker {
+        spec,
+        derived,
+        seen: HashSet::new(),
+        errors: Vec::new(),
+    };
+    checker.compare(expected, derived, "body");
+    checker.errors
+}
+
+/// Problems with the bodies a handler responds with against the spec's
+/// `expected` success bodies. Each body must match one the spec declares,
+/// and each JSON body the spec declares must be one the handler can send.
+fn check_bodies(spec: &Value, expected: &[(&str, Body)], actual: &[Body]) -> Vec<String> {
+    let spec_json: Vec<(&str, &Value)> = expected
+        .iter()
+        .filter_map(|(status, body)| match body {
+            Body::Json(schema) => Some((*status, schema)),
+            _ => None,
+        })
+        .collect();
+    let declares = |kind: fn(&Body) -> bool| expected.iter().any(|(_, body)| kind(body));
+    let mut problems = Vec::new();
+    let mut matched = HashSet::new();
+    for body in actual {
+        match body {
+            Body::Json(derived) if !spec_json.is_empty() => {
+                let mut closest: Option<Vec<String>> = None;
+                for (i, (_, schema)) in spec_json.iter().enumerate() {
+                    let errors = compare(spec, schema, derived);
+                    if errors.is_empty() {
+                        matched.insert(i);
+                        closest = Some(errors);
+                        break;
+                    }
+                    if closest.as_ref().is_none_or(|c| errors.len() < c.len()) {
+                        closest = Some(errors);
+                    }
+                }
+                problems.extend(closest.unwrap_or_default());
+            }
+            Body::Empty if declares(|b| matches!(b, Body::Empty)) => {}
+            Body::Raw if declares(|b| matches!(b, Body::Raw)) => {}
+            Body::Raw if !spec_json.is_empty() => {
+                problems.push("untyped response where the spec has JSON".into());
+            }
+            body => problems.push(format!(
+                "responds with {} where the spec has {}",
+                body.kind(),
+                expected
+                    .iter()
+                    .map(|(status, body)| format!("{} ({status})", body.kind()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+    if problems.is_empty() {
+        for (i, (status, schema)) in spec_json.iter().enumerate() {
+            if !matched.contains(&i) {
+                let name = schema["$ref"]
+                    .as_str()
+                    .and_then(|r| r.rsplit('/').next())
+                    .unwrap_or("inline");
+                problems.push(format!(
+                    "never responds with the spec's {status} body ({name})"
+                ));
+            }
+        }
+    }
+    problems
+}
+
+fn is_camel_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Property names anywhere below `schema`, with their paths.
+fn property_names(schema: &Value, at: &str, found: &mut Vec<(String, String)>) {
+    match schema {
+        Value::Object(map) => {
+            for (key, child) in map {
+                if key == "properties"
+                    && let Some(properties) = child.as_object()
+                {
+                    for (name, property) in properties {
+                        found.push((at.to_string(), name.clone()));
+                        property_names(property, &format!("{at}/{name}"), found);
+                    }
+                } else {
+                    property_names(child, at, found);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                property_names(item, at, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[test]
+fn every_spec_operation_has_a_handler() {
+    let spec = spec();
+    let operations = operations();
+    let mut problems = Vec::new();
+    for (path, item) in spec["paths"].as_object().expect("paths") {
+        for method in item.as_object().into_iter().flatten().map(|(m, _)| m) {
+            let Ok(method) = method.to_ascii_uppercase().parse::<Method>() else {
+                continue;
+            };
+            if !operations
+                .iter()
+                .any(|op| op.method == method && op.path == path)
+            {
+                problems.push(format!("{method} {path} has no handler in the table"));
+            }
+        }
+    }
+    for op in &operations {
+        if spec_operation(&spec, &op.method, op.path).is_none() {
+            problems.push(format!("{} {} is not in the spec", op.method, op.path));
+        }
+    }
+    assert!(problems.is_empty(), "\n{}", problems.join("\n"));
+}
+
+#[test]
+fn responses_match_the_spec() {
+    let spec = spec();
+    let mut problems = Vec::new();
+    for op in operations() {
+        let Some(operation) = spec_operation(&spec, &op.method, op.path) else {
+            continue;
+        };
+        for problem in check_bodies(&spec, &spec_bodies(operation), &op.bodies) {
+            problems.push(format!("{} {}: {problem}", op.method, op.path));
+        }
+    }
+    assert!(
+        problems.is_empty(),
+        "{} problems:\n{}",
+        problems.len(),
+        problems.join("\n")
+    );
+}
+
+#[test]
+fn spec_fields_are_camel_case() {
+    let mut found = Vec::new();
+    property_names(&spec(), "", &mut found);
+    assert!(!found.is_empty());
+    let bad: Vec<String> = found
+        .into_iter()
+        .filter(|(_, name)| !is_camel_case(name))
+        .map(|(at, name)| format!("{at}/{name}"))
+        .collect();
+    assert!(bad.is_empty(), "not camelCase: {bad:?}");
+}
+
+#[test]
+fn field_names_are_compared_at_every_level() {
+    #[derive(serde::Serialize, JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    struct Item {
+        item_id: String,
+        created_at: String,
+    }
+    #[derive(serde::Serialize, JsonSchema)]
+    struct Page {
+        items: Vec<Item>,
+        extra: Option<serde_json::Value>,
+    }
+
+    let spec = serde_json::json!({
+        "components": { "schemas": {
+            "Item": {
+                "type": "object",
+                "properties": { "itemId": { "type": "string" }, "updatedAt": { "type": "string" } },
+            },
+        } },
+        "schema": {
+            "type": "object",
+            "properties": {
+                "items": { "type": "array", "items": { "$ref": "#/components/schemas/Item" } },
+                "extra": { "type": "object", "properties": { "name": { "type": "string" } } },
+            },
+        },
+    });
+    let expected = [("200", Body::Json(spec["schema"].clone()))];
+    let problems = check_bodies(&spec, &expected, &Json::<Page>::bodies());
+    assert_eq!(
+        problems,
+        vec![
+            "body/extra: untyped JSON where the spec has an object",
+            "body/items/[]: missing field `updatedAt`",
+            "body/items/[]: field `createdAt` is not in the spec",
+        ]
+    );
+}
@@ -0,0 +1,273 @@
+//! Browser origins allowed to call the API with credentials.
+//!
+//! The allowed list combines the deployment's
+//! [`ApiConfig::allowed_origins`](crate::config::ApiConfig) (flags, env and
+//! config file) with the `cors.allowedOrigins` system config value, which
+//! admins can change at runtime: the value is read through the config
+//! cache, so an update applies from the next request. Entries are origins
+//! such as `https://nize.example.com`, optionally with a `*.` wildcard for
+//! any subdomain (`https://*.example.com`) or `:*` for any port
+//! (`http://localhost:*`).
+//!
+//! With nothing configured, CORS only admits loopback origins and the CSRF
+//! check does not restrict origins.
+
+use axum::http::HeaderValue;
+use axum::http::request::Parts;
+use nize_core::config::resolver;
+use tower_http::cors::AllowOrigin;
+use tracing::warn;
+
+use crate::AppState;
+
+/// System config key holding extra allowed origins, comma-separated.
+pub const CONFIG_KEY: &str = "cors.allowedOrigins";
+
+/// Origins the desktop app's webviews load from: the Tauri scheme on each
+/// platform and the nize-web sidecar or dev server on an ephemeral
+/// loopback port.
+pub const DESKTOP_ORIGINS: &[&str] = &[
+    "tauri://localhost",
+    "http://tauri.localhost",
+    "https://tauri.localhost",
+    "http://localhost:*",
+    "http://127.0.0.1:*",
+];
+
+/// Allowed by CORS when no origins are configured.
+const LOOPBACK_ORIGINS: &[&str] = &["http://localhost:*", "http://127.0.0.1:*", "http://[::1]:*"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Host {
+    Exact(String),
+    /// Any subdomain of the domain, but not the domain itself.
+    Subdomains(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Port {
+    /// No port: the scheme's default.
+    Default,
+    Exact(u16),
+    Any,
+}
+
+/// One entry of the allowed list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginPattern {
+    scheme: String,
+    host: Host,
+    port: Port,
+}
+
+impl OriginPattern {
+    /// Parse `scheme://host[:port]`, where the host may start with `*.` and
+    /// the port may be `*`. Trailing slashes are ignored.
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid origin pattern: {pattern}");
+        let (scheme, host, port) =
+            split(pattern.trim().trim_end_matches('/')).ok_or_else(invalid)?;
+        let host = match host.strip_prefix("*.") {
+            Some(domain) if !domain.is_empty() && !domain.contains('*') => {
+                Host::Subdomains(domain.to_string())
+            }
+            None if !host.contains('*') => Host::Exact(host.to_string()),
+            _ => return Err(invalid()),
+        };
+        let port = match port {
+            None => Port::Default,
+            Some("*") => Port::Any,
+            Some(p) => Port::Exact(p.parse().map_err(|_| invalid())?),
+        };
+        Ok(Self {
+            scheme: scheme.to_string(),
+            host,
+            port,
+        })
+    }
+
+    /// Whether the `Origin` header value `origin` matches.
+    pub fn matches(&self, origin: &str) -> bool {
+        let Some((scheme, host, port)) = split(origin) else {
+            return false;
+        };
+        let port_matches = match (self.port, port) {
+            (Port::Any, _) => true,
+            (Port::Default, None) => true,
+            (Port::Exact(want), Some(got)) => got.parse() == Ok(want),
+            _ => false,
+        };
+        let host_matches = match &self.host {
+            Host::Exact(want) => host == *want,
+            Host::Subdomains(domain) => host
+                .strip_suffix(domain.as_str())
+                .and_then(|sub| sub.strip_suffix('.'))
+                .is_some_and(|sub| !sub.is_empty()),
+        };
+        scheme == self.scheme && host_matches && port_matches
+    }
+}
+
+/// Split an origin into lowercase scheme, host and optional port.
+fn split(origin: &str) -> Option<(String, String, Option<&str>)> {
+    let (scheme, rest) = origin.split_once("://")?;
+    if scheme.is_empty() || rest.is_empty() || rest.contains(['/', '?', '#', '@']) {
+        return None;
+    }
+    // Bracketed IPv6 hosts contain colons of their own.
+    let port_at = match rest.rfind(']') {
+        Some(end) => rest[end..].find(':').map(|i| end + i),
+        None => rest.rfind(':'),
+    };
+    let (host, port) = match port_at {
+        Some(i) => (&rest[..i], Some(&rest[i + 1..])),
+        None => (rest, None),
+    };
+    if host.is_empty() || port == Some("") {
+        return None;
+    }
+    Some((scheme.to_ascii_lowercase(), host.to_ascii_lowercase(), port))
+}
+
+/// The configured allowed origins.
+#[derive(Debug, Clone, Default)]
+pub struct AllowedOrigins {
+    patterns: Vec<OriginPattern>,
+}
+
+impl AllowedOrigins {
+    /// Parse entries, skipping (and logging) invalid ones.
+    pub fn parse<'a>(entries: impl IntoIterator<Item = &'a str>) -> Self {
+        let patterns = entries
+            .into_iter()
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .filter_map(|e| {
+                OriginPattern::parse(e)
+                    .inspect_err(|e| warn!("ignoring allowed origin: {e}"))
+                    .ok()
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Whether no origins are configured.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether `origin` matches any entry.
+    pub fn matches(&self, origin: &str) -> bool {
+        self.patterns.iter().any(|p| p.matches(origin))
+    }
+
+    /// Whether CORS admits `origin`: a configured origin, or a loopback
+    /// origin when none are configured.
+    pub fn admits(&self, origin: &str) -> bool {
+        if self.is_empty() {
+            Self::parse(LOOPBACK_ORIGINS.iter().copied()).matches(origin)
+        } else {
+            self.matches(origin)
+        }
+    }
+}
+
+/// The deployment's origins plus the current `cors.allowedOrigins` value.
+pub async fn allowed_origins(state: &AppState) -> AllowedOrigins {
+    let configured = resolver::get_system_value(&state.pool, &state.config_cache, CONFIG_KEY)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("failed to read {CONFIG_KEY}: {e}");
+            String::new()
+        });
+    AllowedOrigins::parse(
+        state
+            .config
+            .allowed_origins
+            .iter()
+            .map(String::as_str)
+            .chain(configured.split(',')),
+    )
+}
+
+/// CORS origin policy, resolved per request so config changes apply live.
+pub fn allow_origin(state: &AppState) -> AllowOrigin {
+    let state = state.clone();
+    AllowOrigin::async_predicate(move |origin: HeaderValue, _parts: &Parts| {
+        let state = state.clone();
+        async move {
+            match origin.to_str() {
+                Ok(origin) => allowed_origins(&state).await.admits(origin),
+                Err(_) => false,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(p: &str) -> OriginPattern {
+        OriginPattern::parse(p).unwrap()
+    }
+
+    #[test]
+    fn exact_origin_matches_scheme_host_and_port() {
+        let p = pattern("https://nize.example.com/");
+        assert!(p.matches("https://nize.example.com"));
+        assert!(p.matches("https://NIZE.example.com"));
+        assert!(!p.matches("http://nize.example.com"));
+        assert!(!p.matches("https://nize.example.com:8443"));
+        assert!(!p.matches("https://nize.example.com.evil.io"));
+    }
+
+    #[test]
+    fn wildcard_subdomain_excludes_the_domain_itself() {
+        let p = pattern("https://*.example.com");
+        assert!(p.matches("https://a.example.com"));
+        assert!(p.matches("https://a.b.example.com"));
+        assert!(!p.matches("https://example.com"));
+        assert!(!p.matches("https://badexample.com"));
+    }
+
+    #[test]
+    fn wildcard_port_matches_any_port() {
+        let p = pattern("http://localhost:*");
+        assert!(p.matches("http://localhost:3000"));
+        assert!(p.matches("http://localhost"));
+        assert!(!p.matches("http://localhost.evil.io:3000"));
+        assert!(pattern("http://[::1]:*").matches("http://[::1]:8080"));
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        for p in [
+            "localhost",
+            "https://",
+            "https://a.*.com",
+            "https://*.",
+            "http://h:x",
+        ] {
+            assert!(OriginPattern::parse(p).is_err(), "{p}");
+        }
+    }
+
+    #[test]
+    fn unconfigured_admits_only_loopback() {
+        let none = AllowedOrigins::default();
+        assert!(none.admits("http://127.0.0.1:5173"));
+        assert!(!none.admits("https://evil.io"));
+
+        let some = AllowedOrigins::parse(["https://nize.example.com", "bogus"]);
+        assert!(some.admits("https://nize.example.com"));
+        assert!(!some.admits("http://127.0.0.1:5173"));
+    }
+
+    #[test]
+    fn desktop_origins_parse() {
+        let desktop = AllowedOrigins::parse(DESKTOP_ORIGINS.iter().copied());
+        assert!(desktop.matches("tauri://localhost"));
+        assert!(desktop.matches("http://127.0.0.1:49152"));
+    }
+}
@@ -0,0 +1,290 @@
+//! Slow database statement tracking.
+//!
+//! sqlx logs statements slower than the pool's threshold (see
+//! [`QueryLimits`]) as `WARN` events on the `sqlx::query` target. The
+//! [`SlowQueryLayer`], installed in the server's tracing subscriber, turns
+//! those events into per-statement statistics, attributed to the route and
+//! request ID of the enclosing request span (see
+//! [`crate::middleware::request_context`]). `GET /admin/diagnostics/slow-queries`
+//! lists the statements that took the most time.
+//!
+//! Statistics are kept in memory since the server started, for at most
+//! [`MAX_STATEMENTS`] distinct statements.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use nize_core::db::QueryLimits;
+use schemars::JsonSchema;
+use serde::Serialize;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Name of the span wrapping each API request.
+pub const REQUEST_SPAN: &str = "request";
+
+/// Distinct statements tracked; the least recently seen is evicted.
+pub const MAX_STATEMENTS: usize = 200;
+
+/// Longest statement text kept.
+const MAX_STATEMENT_LEN: usize = 2000;
+
+/// Target sqlx logs statements on.
+const SQLX_TARGET: &str = "sqlx::query";
+
+/// Statistics for one slow statement.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowQueryStats {
+    pub statement: String,
+    /// Times the statement exceeded the threshold.
+    pub count: u64,
+    pub total_ms: f64,
+    pub max_ms: f64,
+    pub last_seen: DateTime<Utc>,
+    /// Route template of the request that last ran it slowly, if any.
+    pub last_route: Option<String>,
+    pub last_request_id: Option<String>,
+}
+
+/// In-memory statistics of slow statements.
+pub struct SlowQueryLog {
+    limits: QueryLimits,
+    stats: Mutex<HashMap<String, SlowQueryStats>>,
+}
+
+impl SlowQueryLog {
+    pub fn new(limits: QueryLimits) -> Self {
+        Self {
+            limits,
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The limits the pool was connected with.
+    pub fn limits(&self) -> QueryLimits {
+        self.limits
+    }
+
+    /// Tracing layer feeding this log.
+    pub fn layer(self: &Arc<Self>) -> SlowQueryLayer {
+        SlowQueryLayer { log: self.clone() }
+    }
+
+    /// Record one slow run of `statement`.
+    pub fn record(&self, statement: &str, elapsed: Duration, request: Option<&RequestContext>) {
+        let statement = truncate(statement.trim(), MAX_STATEMENT_LEN);
+        let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
+        let mut stats = self.stats.lock().unwrap();
+        if !stats.contains_key(statement) && stats.len() >= MAX_STATEMENTS {
+            let oldest = stats
+                .iter()
+                .min_by_key(|(_, s)| s.last_seen)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                stats.remove(&oldest);
+            }
+        }
+        let entry = stats
+            .entry(statement.to_string())
+            .or_insert_with(|| SlowQueryStats {
+                statement: statement.to_string(),
+                count: 0,
+                total_ms: 0.0,
+                max_ms: 0.0,
+                last_seen: Utc::now(),
+                last_route: None,
+                last_request_id: None,
+            });
+        entry.count += 1;
+        entry.total_ms += elapsed_ms;
+        entry.max_ms = entry.max_ms.max(elapsed_ms);
+        entry.last_seen = Utc::now();
+        entry.last_route = request.and_then(|r| r.route.clone());
+        entry.last_request_id = request.and_then(|r| r.request_id.clone());
+    }
+
+    /// The `limit` statements with the most total time, slowest first.
+    pub fn top(&self, limit: usize) -> Vec<SlowQueryStats> {
+        let mut top: Vec<_> = self.stats.lock().unwrap().values().cloned().collect();
+        top.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+        top.truncate(limit);
+        top
+    }
+}
+
+fn truncate(s: &str, max: usize) -> &str {
+    match s.char_indices().nth(max) {
+        Some((i, _)) => &s[..i],
+        None => s,
+    }
+}
+
+/// Fields of a request span, stored in its extensions.
+#[derive(Debug, Clone, Default)]
+pub struct RequestContext {
+    pub request_id: Option<String>,
+    pub route: Option<String>,
+}
+
+impl Visit for RequestContext {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "request_id" => self.request_id = Some(value.to_string()),
+            "route" => self.route = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.record_str(field, &format!("{value:?}"));
+    }
+}
+
+/// Fields of a sqlx statement event.
+#[derive(Debug, Default)]
+struct StatementEvent {
+    message: String,
+    summary: String,
+    statement: String,
+    elapsed_secs: Option<f64>,
+}
+
+impl StatementEvent {
+    fn is_slow(&self) -> bool {
+        self.message.starts_with("slow statement")
+    }
+
+    /// The full statement; sqlx leaves it empty when the summary is the
+    /// whole statement.
+    fn statement(&self) -> &str {
+        if self.statement.trim().is_empty() {
+            &self.summary
+        } else {
+            &self.statement
+        }
+    }
+}
+
+impl Visit for StatementEvent {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "summary" => self.summary = value.to_string(),
+            "db.statement" => self.statement = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        }
+    }
+}
+
+/// Tracing layer recording sqlx slow statement events in a [`SlowQueryLog`].
+pub struct SlowQueryLayer {
+    log: Arc<SlowQueryLog>,
+}
+
+impl<S> Layer<S> for SlowQueryLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if attrs.metadata().name() != REQUEST_SPAN {
+            return;
+        }
+        let mut request = RequestContext::default();
+        attrs.record(&mut request);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(request);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if event.metadata().target() != SQLX_TARGET {
+            return;
+        }
+        let mut fields = StatementEvent::default();
+        event.record(&mut fields);
+        let Some(elapsed_secs) = fields.elapsed_secs.filter(|_| fields.is_slow()) else {
+            return;
+        };
+        let request = ctx.event_scope(event).and_then(|scope| {
+            scope
+                .from_root()
+                .find_map(|span| span.extensions().get::<RequestContext>().cloned())
+        });
+        self.log.record(
+            fields.statement(),
+            Duration::from_secs_f64(elapsed_secs),
+            request.as_ref(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn top_orders_by_total_time() {
+        let log = SlowQueryLog::new(QueryLimits::default());
+        log.record("SELECT 1", Duration::from_millis(600), None);
+        log.record("SELECT 1", Duration::from_millis(700), None);
+        log.record("SELECT 2", Duration::from_millis(900), None);
+
+        let top = log.top(10);
+        assert_eq!(top[0].statement, "SELECT 1");
+        assert_eq!(top[0].count, 2);
+        assert_eq!(top[0].max_ms, 700.0);
+        assert_eq!(log.top(1).len(), 1);
+    }
+
+    #[test]
+    fn layer_attributes_slow_statements_to_the_request() {
+        let log = Arc::new(SlowQueryLog::new(QueryLimits::default()));
+        let subscriber = tracing_subscriber::registry().with(log.layer());
+        tracing::subscriber::with_default(subscriber, || {
+            let span =
+                tracing::info_span!(REQUEST_SPAN, request_id = "req-1", route = "/conversations");
+            let _guard = span.enter();
+            tracing::warn!(
+                target: "sqlx::query",
+                summary = "select * from conversations …",
+                db.statement = "\n\nSELECT * FROM conversations WHERE user_id = $1\n",
+                elapsed_secs = 1.5,
+                "slow statement: execution time exceeded alert threshold"
+            );
+            tracing::debug!(
+                target: "sqlx::query",
+                summary = "select 1",
+                db.statement = "",
+                elapsed_secs = 0.001,
+            );
+        });
+
+        let top = log.top(10);
+        assert_eq!(top.len(), 1);
+        assert_eq!(
+            top[0].statement,
+            "SELECT * FROM conversations WHERE user_id = $1"
+        );
+        assert_eq!(top[0].last_route.as_deref(), Some("/conversations"));
+        assert_eq!(top[0].last_request_id.as_deref(), Some("req-1"));
+    }
+}
@@ -5,9 +5,16 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use nize_core::mcp::{McpErrorCategory, McpErrorInfo};
+use nize_core::moderation::ContentBlocked;
+use nize_core::quotas::QuotaExceeded;
 use thiserror::Error;
 
-use crate::generated::models::ErrorResponse;
+use crate::generated::models::{
+    ContentBlockedError, ErrorResponse, McpErrorDetail, McpErrorResponse, ModerationDetail,
+    QuotaDetail, QuotaExceededError,
+};
+use crate::i18n::Message;
 
 /// Convenience alias for handler return types.
 pub type AppResult<T> = Result<T, AppError>;
@@ -15,63 +22,236 @@ pub type AppResult<T> = Result<T, AppError>;
 /// Application-level errors with HTTP status mapping.
 #[derive(Debug, Error)]
 pub enum AppError {
-    #[error("Validation error: {0}")]
-    Validation(String),
-
-    #[error("Not found: {0}")]
-    NotFound(String),
-
     #[error("Database unavailable: {0}")]
     DbUnavailable(String),
 
     #[error("Sidecar unavailable: {0}")]
     SidecarUnavailable(String),
 
-    #[error("Unauthorized: {0}")]
-    Unauthorized(String),
+    /// A client error with a translatable message, rendered in the
+    /// caller's language by [`crate::middleware::locale`].
+    #[error("{1}")]
+    Localized(ErrorKind, Message),
 
-    #[error("Forbidden: {0}")]
-    Forbidden(String),
+    /// Mutation attempted while the server runs in read-only mode.
+    #[error("Server is in read-only mode")]
+    ReadOnly,
 
     #[error("Internal server error")]
     Internal(String),
+
+    /// Failure talking to an upstream MCP server; the body carries the
+    /// structured [`McpErrorInfo`] so clients can decide whether to retry.
+    #[error("MCP error: {}", .0.message)]
+    Mcp(McpErrorInfo),
+
+    /// The request would pass a per-user quota; the body carries the
+    /// limit and current usage.
+    #[error("{0}")]
+    QuotaExceeded(QuotaExceeded),
+
+    /// Content rejected by moderation; the body names the stored result
+    /// and the findings that blocked it.
+    #[error("{0}")]
+    ContentBlocked(ContentBlocked),
+}
+
+impl AppError {
+    /// 400 `validation_error` with a translatable message.
+    pub fn validation(message: Message) -> Self {
+        AppError::Localized(ErrorKind::Validation, message)
+    }
+
+    /// 404 `not_found` with a translatable message.
+    pub fn not_found(message: Message) -> Self {
+        AppError::Localized(ErrorKind::NotFound, message)
+    }
+
+    /// 401 `unauthorized` with a translatable message.
+    pub fn unauthorized(message: Message) -> Self {
+        AppError::Localized(ErrorKind::Unauthorized, message)
+    }
+
+    /// 403 `forbidden` with a translatable message.
+    pub fn forbidden(message: Message) -> Self {
+        AppError::Localized(ErrorKind::Forbidden, message)
+    }
+
+    /// 409 `conflict` with a translatable message.
+    pub fn conflict(message: Message) -> Self {
+        AppError::Localized(ErrorKind::Conflict, message)
+    }
+
+    /// 422 `unprocessable` with a translatable message.
+    pub fn unprocessable(message: Message) -> Self {
+        AppError::Localized(ErrorKind::Unprocessable, message)
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error, message) = match &self {
-            AppError::Validation(m) => (StatusCode::BAD_REQUEST, "validation_error", m.as_str()),
-            AppError::NotFound(m) => (StatusCode::NOT_FOUND, "not_found", m.as_str()),
-            AppError::DbUnavailable(m) => (
-                StatusCode::SERVICE_UNAVAILABLE,
-                "db_unavailable",
-                m.as_str(),
-            ),
+        let (status, error, message): (_, _, ResponseMessage) = match self {
+            AppError::DbUnavailable(m) => {
+                (StatusCode::SERVICE_UNAVAILABLE, "db_unavailable", m.into())
+            }
             AppError::SidecarUnavailable(m) => (
                 StatusCode::SERVICE_UNAVAILABLE,
                 "sidecar_unavailable",
-                m.as_str(),
+                m.into(),
+            ),
+            AppError::Localized(kind, m) => (kind.status(), kind.code(), m.into()),
+            AppError::ReadOnly => (
+                StatusCode::FORBIDDEN,
+                "read_only",
+                Message::new("read_only").into(),
             ),
-            AppError::Unauthorized(m) => (StatusCode::UNAUTHORIZED, "unauthorized", m.as_str()),
-            AppError::Forbidden(m) => (StatusCode::FORBIDDEN, "forbidden", m.as_str()),
             AppError::Internal(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "internal_error",
-                "Internal server error",
+                Message::new("internal_error").into(),
             ),
+            AppError::Mcp(info) => return mcp_error_response(info),
+            AppError::QuotaExceeded(e) => return quota_error_response(&e),
+            AppError::ContentBlocked(e) => return content_blocked_response(e),
         };
         let body = Json(ErrorResponse {
             error: error.to_string(),
-            message: message.to_string(),
+            message: message.text(),
         });
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        if let ResponseMessage::Keyed(message) = message {
+            response.extensions_mut().insert(message);
+        }
+        response
+    }
+}
+
+/// Status and error code of an [`AppError::Localized`] error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Validation,
+    NotFound,
+    Unauthorized,
+    Forbidden,
+    Conflict,
+    Unprocessable,
+}
+
+impl ErrorKind {
+    fn status(self) -> StatusCode {
+        match self {
+            ErrorKind::Validation => StatusCode::BAD_REQUEST,
+            ErrorKind::NotFound => StatusCode::NOT_FOUND,
+            ErrorKind::Unauthorized => StatusCode::UNAUTHORIZED,
+            ErrorKind::Forbidden => StatusCode::FORBIDDEN,
+            ErrorKind::Conflict => StatusCode::CONFLICT,
+            ErrorKind::Unprocessable => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            ErrorKind::Validation => "validation_error",
+            ErrorKind::NotFound => "not_found",
+            ErrorKind::Unauthorized => "unauthorized",
+            ErrorKind::Forbidden => "forbidden",
+            ErrorKind::Conflict => "conflict",
+            ErrorKind::Unprocessable => "unprocessable",
+        }
+    }
+}
+
+/// An error body's message: fixed text, or a [`Message`] the locale
+/// middleware can translate (attached to the response as an extension).
+enum ResponseMessage {
+    Text(String),
+    Keyed(Message),
+}
+
+impl ResponseMessage {
+    fn text(&self) -> String {
+        match self {
+            ResponseMessage::Text(text) => text.clone(),
+            ResponseMessage::Keyed(message) => message.to_string(),
+        }
     }
 }
 
+impl From<String> for ResponseMessage {
+    fn from(text: String) -> Self {
+        ResponseMessage::Text(text)
+    }
+}
+
+impl From<Message> for ResponseMessage {
+    fn from(message: Message) -> Self {
+        ResponseMessage::Keyed(message)
+    }
+}
+
+/// Render an upstream MCP failure with its structured details.
+fn mcp_error_response(info: McpErrorInfo) -> Response {
+    let status = match info.category {
+        McpErrorCategory::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        McpErrorCategory::ResourceExhausted => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::BAD_GATEWAY,
+    };
+    let body = Json(McpErrorResponse {
+        error: "mcp_error".to_string(),
+        message: info.message.clone(),
+        mcp: McpErrorDetail {
+            category: info.category.as_str().to_string(),
+            message: info.message,
+            retryable: info.retryable,
+            server_id: info.server_id,
+            upstream_code: info.upstream_code.map(i64::from),
+        },
+    });
+    (status, body).into_response()
+}
+
+/// Render a quota rejection with its limit and usage.
+fn quota_error_response(e: &QuotaExceeded) -> Response {
+    let message = Message::new("quota_exceeded")
+        .arg("used", e.used)
+        .arg("limit", e.limit)
+        .arg("quota", e.quota.as_str());
+    let body = Json(QuotaExceededError {
+        error: "quota_exceeded".to_string(),
+        message: message.to_string(),
+        quota: QuotaDetail {
+            quota: e.quota.as_str().to_string(),
+            limit: e.limit,
+            used: e.used,
+            requested: e.requested,
+        },
+    });
+    let mut response = (StatusCode::FORBIDDEN, body).into_response();
+    response.extensions_mut().insert(message);
+    response
+}
+
+/// Render a moderation rejection with the findings that caused it.
+fn content_blocked_response(e: ContentBlocked) -> Response {
+    let message = Message::new("content_blocked").arg("source", e.content_source);
+    let body = Json(ContentBlockedError {
+        error: "content_blocked".to_string(),
+        message: message.to_string(),
+        moderation: ModerationDetail {
+            source: e.content_source.to_string(),
+            result_id: e.result_id.to_string(),
+            findings: e.labels,
+        },
+    });
+    let mut response = (StatusCode::UNPROCESSABLE_ENTITY, body).into_response();
+    response.extensions_mut().insert(message);
+    response
+}
+
 impl From<sqlx::Error> for AppError {
     fn from(e: sqlx::Error) -> Self {
         match e {
-            sqlx::Error::RowNotFound => AppError::NotFound("row not found".into()),
+            sqlx::Error::RowNotFound => AppError::not_found(Message::new("row_not_found")),
             _ => AppError::Internal(e.to_string()),
         }
     }
@@ -81,10 +261,10 @@ impl From<nize_core::auth::AuthError> for AppError {
     fn from(e: nize_core::auth::AuthError) -> Self {
         match e {
             nize_core::auth::AuthError::CredentialError => {
-                AppError::Unauthorized("Invalid credentials".into())
+                AppError::unauthorized(Message::new("auth.invalid_credentials"))
             }
-            nize_core::auth::AuthError::TokenError(msg) => AppError::Unauthorized(msg),
-            nize_core::auth::AuthError::ValidationError(msg) => AppError::Validation(msg),
+            nize_core::auth::AuthError::TokenError(msg) => AppError::unauthorized(msg),
+            nize_core::auth::AuthError::ValidationError(msg) => AppError::validation(msg),
             nize_core::auth::AuthError::DbError(e) => AppError::from(e),
             nize_core::auth::AuthError::Internal(msg) => AppError::Internal(msg),
         }
@@ -93,21 +273,400 @@ impl From<nize_core::auth::AuthError> for AppError {
 
 impl From<nize_core::mcp::McpError> for AppError {
     fn from(e: nize_core::mcp::McpError) -> Self {
+        use nize_core::mcp::McpError;
+
+        let info = e.info();
+        match e {
+            McpError::NotFound(msg) => AppError::not_found(msg),
+            McpError::Forbidden(msg) => AppError::forbidden(msg),
+            McpError::Validation(msg) => AppError::validation(msg),
+            McpError::DuplicateServer(name) => {
+                AppError::validation(Message::new("mcp.duplicate_server").arg("name", name))
+            }
+            McpError::InvalidTransport(msg) => AppError::validation(msg),
+            McpError::EncryptionError(msg) => AppError::Internal(msg),
+            McpError::DbError(e) => AppError::from(e),
+            McpError::ConnectionFailed(_)
+            | McpError::ResourceExhausted(_)
+            | McpError::Timeout(_)
+            | McpError::Upstream { .. } => AppError::Mcp(info),
+            // Keep the server attribution on upstream failures.
+            McpError::Server { source, .. } => match AppError::from(*source) {
+                AppError::Mcp(_) => AppError::Mcp(info),
+                other => other,
+            },
+        }
+    }
+}
+
+impl From<nize_core::idempotency::IdempotencyError> for AppError {
+    fn from(e: nize_core::idempotency::IdempotencyError) -> Self {
+        use nize_core::idempotency::IdempotencyError;
+
+        match e {
+            IdempotencyError::Sealed(e) => AppError::Internal(e.to_string()),
+            IdempotencyError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::tags::TagError> for AppError {
+    fn from(e: nize_core::tags::TagError) -> Self {
+        use nize_core::tags::TagError;
+
+        match e {
+            TagError::Validation(msg) => AppError::validation(msg),
+            TagError::NotFound(msg) => AppError::not_found(msg),
+            TagError::Duplicate(name) => {
+                AppError::validation(Message::new("tags.duplicate").arg("name", name))
+            }
+            TagError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::permissions::PermissionError> for AppError {
+    fn from(e: nize_core::permissions::PermissionError) -> Self {
+        use nize_core::permissions::PermissionError;
+
+        match e {
+            PermissionError::Validation(msg) => AppError::validation(msg),
+            PermissionError::NotFound(msg) => AppError::not_found(msg),
+            PermissionError::Forbidden(msg) => AppError::forbidden(msg),
+            PermissionError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::pricing::PricingError> for AppError {
+    fn from(e: nize_core::pricing::PricingError) -> Self {
+        use nize_core::pricing::PricingError;
+
+        match e {
+            e @ PricingError::InvalidTable(_) => AppError::Internal(e.to_string()),
+            PricingError::Config(e) => AppError::from(e),
+            PricingError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::connectors::ConnectorError> for AppError {
+    fn from(e: nize_core::connectors::ConnectorError) -> Self {
+        use nize_core::connectors::ConnectorError;
+
+        match e {
+            ConnectorError::Validation(msg) => AppError::validation(msg),
+            ConnectorError::NotFound(msg) => AppError::not_found(msg),
+            ConnectorError::Io(e) => AppError::Internal(e.to_string()),
+            ConnectorError::Remote(msg) => AppError::Internal(msg),
+            ConnectorError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::tasks::TaskError> for AppError {
+    fn from(e: nize_core::tasks::TaskError) -> Self {
+        use nize_core::tasks::TaskError;
+
+        match e {
+            TaskError::Validation(msg) => AppError::validation(msg),
+            TaskError::NotFound(msg) => AppError::not_found(msg),
+            TaskError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::feedback::FeedbackError> for AppError {
+    fn from(e: nize_core::feedback::FeedbackError) -> Self {
+        use nize_core::feedback::FeedbackError;
+
+        match e {
+            FeedbackError::Validation(msg) => AppError::validation(msg),
+            FeedbackError::NotFound(msg) => AppError::not_found(msg),
+            FeedbackError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::regeneration::RegenerationError> for AppError {
+    fn from(e: nize_core::regeneration::RegenerationError) -> Self {
+        use nize_core::regeneration::RegenerationError;
+
+        match e {
+            RegenerationError::Validation(msg) => AppError::validation(msg),
+            RegenerationError::NotFound(msg) => AppError::not_found(msg),
+            RegenerationError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::chat_trace::TraceError> for AppError {
+    fn from(e: nize_core::chat_trace::TraceError) -> Self {
+        use nize_core::chat_trace::TraceError;
+
         match e {
-            nize_core::mcp::McpError::NotFound(msg) => AppError::NotFound(msg),
-            nize_core::mcp::McpError::Forbidden(msg) => AppError::Forbidden(msg),
-            nize_core::mcp::McpError::Validation(msg) => AppError::Validation(msg),
-            nize_core::mcp::McpError::ServerLimitExceeded(n) => {
-                AppError::Validation(format!("Maximum of {n} user servers allowed"))
+            TraceError::Validation(msg) => AppError::validation(msg),
+            TraceError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::eval::EvalError> for AppError {
+    fn from(e: nize_core::eval::EvalError) -> Self {
+        use nize_core::eval::EvalError;
+
+        match e {
+            EvalError::NotFound(msg) => AppError::not_found(msg),
+            EvalError::Parse(msg) => AppError::validation(msg),
+            EvalError::Io(e) => AppError::Internal(e.to_string()),
+            EvalError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::auth::rbac::RoleError> for AppError {
+    fn from(e: nize_core::auth::rbac::RoleError) -> Self {
+        use nize_core::auth::rbac::RoleError;
+
+        match e {
+            RoleError::Validation(msg) => AppError::validation(msg),
+            RoleError::NotFound(msg) => AppError::not_found(msg),
+            RoleError::Duplicate(name) => {
+                AppError::validation(Message::new("roles.duplicate").arg("name", name))
+            }
+            RoleError::BuiltIn(name) => {
+                AppError::validation(Message::new("roles.built_in").arg("name", name))
+            }
+            RoleError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::account::AccountError> for AppError {
+    fn from(e: nize_core::account::AccountError) -> Self {
+        use nize_core::account::AccountError;
+
+        match e {
+            AccountError::Validation(msg) => AppError::validation(msg),
+            AccountError::NotFound(msg) => AppError::not_found(msg),
+            AccountError::Db(e) => AppError::from(e),
+            AccountError::Blob(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::workspaces::WorkspaceError> for AppError {
+    fn from(e: nize_core::workspaces::WorkspaceError) -> Self {
+        use nize_core::workspaces::WorkspaceError;
+
+        match e {
+            WorkspaceError::Validation(msg) => AppError::validation(msg),
+            WorkspaceError::NotFound(msg) => AppError::not_found(msg),
+            WorkspaceError::Forbidden(msg) => AppError::forbidden(msg),
+            WorkspaceError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::blobs::BlobError> for AppError {
+    fn from(e: nize_core::blobs::BlobError) -> Self {
+        use nize_core::blobs::BlobError;
+
+        match e {
+            BlobError::NotFound(_) => {
+                AppError::not_found(Message::new("documents.content_not_found"))
+            }
+            BlobError::TooLarge(max) => {
+                AppError::validation(Message::new("documents.too_large").arg("max", max))
+            }
+            BlobError::Db(e) => AppError::from(e),
+            e @ (BlobError::Config(_) | BlobError::Backend(_) | BlobError::Io(_)) => {
+                AppError::Internal(e.to_string())
+            }
+        }
+    }
+}
+
+impl From<nize_core::ingest::IngestError> for AppError {
+    fn from(e: nize_core::ingest::IngestError) -> Self {
+        AppError::Internal(e.to_string())
+    }
+}
+
+impl From<nize_core::quotas::QuotaError> for AppError {
+    fn from(e: nize_core::quotas::QuotaError) -> Self {
+        use nize_core::quotas::QuotaError;
+
+        match e {
+            QuotaError::Exceeded(e) => AppError::QuotaExceeded(e),
+            QuotaError::Config(e) => AppError::from(e),
+            QuotaError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::telemetry::TelemetryError> for AppError {
+    fn from(e: nize_core::telemetry::TelemetryError) -> Self {
+        use nize_core::telemetry::TelemetryError;
+
+        match e {
+            TelemetryError::Submit(msg) => AppError::Internal(msg),
+            TelemetryError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::storage::StorageError> for AppError {
+    fn from(e: nize_core::storage::StorageError) -> Self {
+        use nize_core::storage::StorageError;
+
+        match e {
+            StorageError::Db(e) => AppError::from(e),
+            StorageError::Config(e) => AppError::from(e),
+            StorageError::Blob(e) => AppError::from(e),
+            e @ StorageError::Io { .. } => AppError::Internal(e.to_string()),
+        }
+    }
+}
+
+impl From<nize_core::integrity::IntegrityError> for AppError {
+    fn from(e: nize_core::integrity::IntegrityError) -> Self {
+        match e {
+            nize_core::integrity::IntegrityError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::db_transfer::TransferError> for AppError {
+    fn from(e: nize_core::db_transfer::TransferError) -> Self {
+        use nize_core::db_transfer::TransferError;
+
+        match e {
+            TransferError::Db(e) => AppError::from(e),
+            TransferError::InvalidTarget(reason) => {
+                AppError::validation(Message::new("transfer.invalid_target").arg("reason", reason))
             }
-            nize_core::mcp::McpError::DuplicateServer(name) => {
-                AppError::Validation(format!("Server with name '{name}' already exists"))
+            TransferError::TargetNotEmpty(reason) => AppError::validation(
+                Message::new("transfer.target_not_empty").arg("reason", reason),
+            ),
+            TransferError::SourceOutdated(pending) => AppError::validation(
+                Message::new("transfer.source_outdated").arg("pending", pending),
+            ),
+            e @ TransferError::Migrate(_) => AppError::Internal(e.to_string()),
+        }
+    }
+}
+
+impl From<nize_core::announcements::AnnouncementError> for AppError {
+    fn from(e: nize_core::announcements::AnnouncementError) -> Self {
+        use nize_core::announcements::AnnouncementError;
+
+        match e {
+            AnnouncementError::Validation(msg) => AppError::validation(msg),
+            AnnouncementError::NotFound(msg) => AppError::not_found(msg),
+            AnnouncementError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<nize_core::moderation::ModerationError> for AppError {
+    fn from(e: nize_core::moderation::ModerationError) -> Self {
+        use nize_core::moderation::ModerationError;
+
+        match e {
+            ModerationError::Validation(msg) => AppError::validation(msg),
+            ModerationError::NotFound(msg) => AppError::not_found(msg),
+            ModerationError::Db(e) => AppError::from(e),
+            e @ (ModerationError::Config(_) | ModerationError::Provider(_)) => {
+                AppError::Internal(e.to_string())
             }
-            nize_core::mcp::McpError::InvalidTransport(msg) => AppError::Validation(msg),
-            nize_core::mcp::McpError::ConnectionFailed(msg) => AppError::Validation(msg),
-            nize_core::mcp::McpError::ResourceExhausted(msg) => AppError::Validation(msg),
-            nize_core::mcp::McpError::EncryptionError(msg) => AppError::Internal(msg),
-            nize_core::mcp::McpError::DbError(e) => AppError::from(e),
         }
     }
 }
+
+impl From<nize_core::sync::SyncError> for AppError {
+    fn from(e: nize_core::sync::SyncError) -> Self {
+        use nize_core::sync::SyncError;
+
+        match e {
+            SyncError::Validation(msg) => AppError::validation(msg),
+            SyncError::Db(e) => AppError::from(e),
+        }
+    }
+}
+
+impl From<crate::streams::StreamError> for AppError {
+    fn from(e: crate::streams::StreamError) -> Self {
+        use crate::streams::StreamError;
+
+        match e {
+            StreamError::NotFound => AppError::not_found(Message::new("streams.not_found")),
+            StreamError::TooMany => AppError::validation(Message::new("streams.too_many")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nize_core::mcp::McpError;
+
+    use super::*;
+
+    async fn body_json(resp: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("read body");
+        serde_json::from_slice(&bytes).expect("json body")
+    }
+
+    #[tokio::test]
+    async fn upstream_mcp_errors_carry_structured_details() {
+        let server_id = uuid::Uuid::new_v4();
+        let err = McpError::Upstream {
+            code: -32601,
+            message: "no such tool".into(),
+        }
+        .with_server(server_id);
+
+        let resp = AppError::from(err).into_response();
+        assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+        let body = body_json(resp).await;
+        assert_eq!(body["error"], "mcp_error");
+        assert_eq!(body["mcp"]["category"], "upstream");
+        assert_eq!(body["mcp"]["retryable"], false);
+        assert_eq!(body["mcp"]["upstreamCode"], -32601);
+        assert_eq!(body["mcp"]["serverId"], server_id.to_string());
+    }
+
+    #[tokio::test]
+    async fn attributed_client_errors_keep_their_status() {
+        let err = McpError::NotFound(Message::new("mcp.tool_not_found"))
+            .with_server(uuid::Uuid::new_v4());
+        let resp = AppError::from(err).into_response();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn blocked_content_names_the_result_and_findings() {
+        let result_id = uuid::Uuid::new_v4();
+        let resp = AppError::ContentBlocked(ContentBlocked {
+            content_source: nize_core::moderation::Source::Prompt,
+            result_id,
+            labels: vec!["Threats".into()],
+        })
+        .into_response();
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = body_json(resp).await;
+        assert_eq!(body["error"], "content_blocked");
+        assert_eq!(body["moderation"]["source"], "prompt");
+        assert_eq!(body["moderation"]["resultId"], result_id.to_string());
+        assert_eq!(body["moderation"]["findings"][0], "Threats");
+    }
+
+    #[tokio::test]
+    async fn timeouts_map_to_gateway_timeout() {
+        let resp = AppError::from(McpError::Timeout("30s".into())).into_response();
+        assert_eq!(resp.status(), StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(body_json(resp).await["mcp"]["retryable"], true);
+    }
+}
@@ -0,0 +1,135 @@
+//! In-process server event bus.
+//!
+//! Background work (task runs, note indexing, account exports) publishes [`ServerEvent`]s on
+//! the [`EventBus`]; `GET /events` streams a user's events to connected
+//! clients such as the desktop app's notification bridge. Events are not
+//! persisted — a client that is not connected misses them.
+//!
+//! Task runs publish the kind of the notification they create
+//! (`task.completed` / `task.failed`). Announcements are broadcast to every
+//! connected user.
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Events buffered per subscriber before slow subscribers start lagging.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// Kind published when a document or note finished ingesting.
+pub const KIND_INGEST_COMPLETED: &str = "ingest.completed";
+/// Kind published when moderation blocked a document being ingested in
+/// the background, which was then removed.
+pub const KIND_INGEST_BLOCKED: &str = "ingest.blocked";
+/// Kind published when a tool call is waiting for the user's approval.
+pub const KIND_TOOL_APPROVAL_REQUESTED: &str = "tool.approval_requested";
+/// Kind published when an account export is ready for download.
+pub const KIND_EXPORT_COMPLETED: &str = "export.completed";
+/// Kind published when an account export failed.
+pub const KIND_EXPORT_FAILED: &str = "export.failed";
+/// Kind published when background MCP tool discovery finished.
+pub const KIND_MCP_DISCOVERY_COMPLETED: &str = "mcp.discovery_completed";
+/// Kind published when background MCP tool discovery failed.
+pub const KIND_MCP_DISCOVERY_FAILED: &str = "mcp.discovery_failed";
+/// Kind published when an eval run finished (completed or failed).
+pub const KIND_EVAL_COMPLETED: &str = "eval.completed";
+/// Kind published to storage administrators when storage crosses a soft
+/// limit.
+pub const KIND_STORAGE_WARNING: &str = "storage.warning";
+/// Kind published when a connector finished syncing its source.
+pub const KIND_CONNECTOR_SYNCED: &str = "connector.synced";
+/// Kind published when a connector sync failed.
+pub const KIND_CONNECTOR_FAILED: &str = "connector.failed";
+/// Kind broadcast when an announcement becomes active.
+pub const KIND_ANNOUNCEMENT_PUBLISHED: &str = "announcement.published";
+
+/// An event addressed to a single user, or to everyone.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerEvent {
+    /// Recipient; `None` broadcasts to every user.
+    #[serde(skip)]
+    pub user_id: Option<Uuid>,
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+    pub payload: serde_json::Value,
+}
+
+/// Broadcast channel fanning events out to every subscriber.
+pub struct EventBus {
+    tx: broadcast::Sender<ServerEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { tx }
+    }
+
+    /// Publish an event. Dropped silently when nobody is subscribed.
+    pub fn publish(&self, event: ServerEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    /// Subscribe to all events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerEvent> {
+        self.tx.subscribe()
+    }
+}
+
+impl ServerEvent {
+    /// Whether `user_id` should receive this event.
+    pub fn is_for(&self, user_id: &Uuid) -> bool {
+        self.user_id.is_none_or(|recipient| recipient == *user_id)
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn subscribers_receive_events_published_after_subscribing() {
+        let bus = EventBus::new();
+        bus.publish(ServerEvent {
+            user_id: Some(Uuid::new_v4()),
+            kind: nize_core::notifications::KIND_TASK_COMPLETED.into(),
+            title: "missed".into(),
+            body: String::new(),
+            payload: serde_json::Value::Null,
+        });
+
+        let mut rx = bus.subscribe();
+        bus.publish(ServerEvent {
+            user_id: Some(Uuid::new_v4()),
+            kind: KIND_INGEST_COMPLETED.into(),
+            title: "seen".into(),
+            body: String::new(),
+            payload: serde_json::Value::Null,
+        });
+
+        assert_eq!(rx.recv().await.unwrap().title, "seen");
+    }
+
+    #[test]
+    fn broadcast_events_are_for_everyone() {
+        let user = Uuid::new_v4();
+        let event = |user_id| ServerEvent {
+            user_id,
+            kind: KIND_ANNOUNCEMENT_PUBLISHED.into(),
+            title: String::new(),
+            body: String::new(),
+            payload: serde_json::Value::Null,
+        };
+        assert!(event(None).is_for(&user));
+        assert!(event(Some(user)).is_for(&user));
+        assert!(!event(Some(Uuid::new_v4())).is_for(&user));
+    }
+}
@@ -0,0 +1,298 @@
+//! Account takeout handlers: export, import and erasure of all of a user's
+//! data.
+//!
+//! Exports are built in the background; clients poll the export (or wait
+//! for the `export.completed` event) and then download the archive.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::{HeaderName, StatusCode, header};
+use axum_extra::extract::CookieJar;
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+use nize_core::account::{self, Archive, ArchiveDocument, ExportRow};
+use nize_core::documents;
+use nize_core::ingest;
+use nize_core::quotas::{self, Quota};
+use nize_core::workspaces::Scope;
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::events::{KIND_EXPORT_COMPLETED, KIND_EXPORT_FAILED, ServerEvent};
+use crate::handlers::ingest::{MAX_UPLOAD_BYTES, clean_filename};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::services::{self, config, cookies};
+
+/// Largest archive accepted by `POST /account/import`.
+pub const MAX_IMPORT_BYTES: usize = 64 * 1024 * 1024;
+
+/// An export job.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountExport {
+    pub id: Uuid,
+    /// `running`, `completed` or `failed`.
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+}
+
+impl From<&ExportRow> for AccountExport {
+    fn from(row: &ExportRow) -> Self {
+        Self {
+            id: row.id,
+            status: row.status.clone(),
+            error: row.error.clone(),
+            created_at: row.created_at.to_rfc3339(),
+            completed_at: row.completed_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+/// Body of `POST /account/import`: how many items were restored, by kind.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountImportResponse {
+    pub conversations: usize,
+    pub notes: usize,
+    pub documents: usize,
+    pub tags: usize,
+    pub tasks: usize,
+    pub config: usize,
+    pub mcp_servers: usize,
+    pub skipped_mcp_servers: usize,
+}
+
+/// `POST /account/export` — start exporting the user's data. Returns the
+/// running export when one is already in progress.
+pub async fn create_export_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+) -> AppResult<(StatusCode, Json<AccountExport>)> {
+    let user_id = parse_user_id(&user.0.sub)?;
+
+    let (row, started) = account::create_export(&state.pool, &user_id).await?;
+    if started {
+        spawn_export(&state, &row);
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(AccountExport::from(&row))))
+}
+
+/// `GET /account/export/{id}` — get the status of an export.
+pub async fn get_export_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<Json<AccountExport>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let export_id = parse_uuid(&id)?;
+
+    let row = account::get_export(&state.pool, &user_id, &export_id).await?;
+
+    Ok(Json(AccountExport::from(&row)))
+}
+
+/// `GET /account/export/{id}/download` — download a completed archive as a
+/// JSON attachment.
+pub async fn download_export_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<([(HeaderName, String); 1], Json<Archive>)> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let export_id = parse_uuid(&id)?;
+
+    let row = account::get_export(&state.pool, &user_id, &export_id).await?;
+    let archive = account::get_export_archive(&state.pool, &user_id, &export_id).await?;
+    let disposition = format!(
+        "attachment; filename=\"nize-export-{}.json\"",
+        row.created_at.format("%Y%m%d")
+    );
+
+    Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(archive)))
+}
+
+/// `POST /account/import` — restore an export archive into the account.
+/// Config overrides that no longer validate, and documents that cannot be
+/// stored or are blocked by moderation, are skipped.
+pub async fn import_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Json(archive): Json<Archive>,
+) -> AppResult<Json<AccountImportResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+
+    let summary = account::import_archive(&state.pool, &user_id, &archive).await?;
+
+    let mut config_imported = 0;
+    for item in &archive.config {
+        match config::update_user_config(
+            &state.pool,
+            &state.config_cache,
+            &user.0.sub,
+            &item.key,
+            &item.value,
+            &state.config.mcp_encryption_key,
+        )
+        .await
+        {
+            Ok(_) => config_imported += 1,
+            Err(e) => tracing::warn!("Skipping imported config {}: {e}", item.key),
+        }
+    }
+
+    let mut documents_imported = 0;
+    for document in &archive.documents {
+        match import_document(&state, &user_id, document).await {
+            Ok(()) => documents_imported += 1,
+            Err(e) => tracing::warn!("Skipping imported document {}: {e}", document.filename),
+        }
+    }
+
+    for note_id in &summary.note_ids {
+        spawn_embed_note(&state, *note_id);
+    }
+
+    Ok(Json(AccountImportResponse {
+        conversations: summary.conversations,
+        notes: summary.notes,
+        documents: documents_imported,
+        tags: summary.tags,
+        tasks: summary.tasks,
+        config: config_imported,
+        mcp_servers: summary.mcp_servers,
+        skipped_mcp_servers: summary.skipped_mcp_servers,
+    }))
+}
+
+/// `DELETE /account` — erase the account and all of its data, and sign out.
+pub async fn delete_account_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    jar: CookieJar,
+) -> AppResult<(CookieJar, StatusCode)> {
+    let user_id = parse_user_id(&user.0.sub)?;
+
+    let released = account::erase_account(&state.pool, &user_id).await?;
+    tracing::info!(user_id = %user_id, "Erased account");
+    services::documents::delete_released(&state, released).await;
+
+    let jar = jar
+        .add(cookies::clear_access_cookie())
+        .add(cookies::clear_refresh_cookie())
+        .add(cookies::clear_csrf_cookie());
+    Ok((jar, StatusCode::NO_CONTENT))
+}
+
+/// Build an export without blocking the response, publishing an
+/// `export.completed` (or `export.failed`) event when done.
+fn spawn_export(state: &AppState, row: &ExportRow) {
+    let state = state.clone();
+    let (export_id, user_id) = (row.id, row.user_id);
+    tokio::spawn(async move {
+        let result = account::run_export(
+            &state.pool,
+            &state.config_cache,
+            &state.config.mcp_encryption_key,
+            &export_id,
+            &user_id,
+        )
+        .await;
+        let (kind, title, body) = match result {
+            Ok(()) => (
+                KIND_EXPORT_COMPLETED,
+                "Export ready",
+                "Your data export is ready to download.".to_string(),
+            ),
+            Err(e) => {
+                tracing::warn!("Account export {export_id} failed: {e}");
+                (KIND_EXPORT_FAILED, "Export failed", e.to_string())
+            }
+        };
+        state.events.publish(ServerEvent {
+            user_id: Some(user_id),
+            kind: kind.into(),
+            title: title.into(),
+            body,
+            payload: serde_json::json!({ "exportId": export_id }),
+        });
+    });
+}
+
+/// Store an archived document's content as a new personal document,
+/// extract and index it like an upload.
+async fn import_document(
+    state: &AppState,
+    user_id: &Uuid,
+    document: &ArchiveDocument,
+) -> AppResult<()> {
+    let filename = clean_filename(&document.filename)?;
+    let bytes = document.decode_content()?;
+    quotas::ensure_within(
+        &state.pool,
+        &state.config_cache,
+        user_id,
+        &[
+            (Quota::Documents, 1),
+            (Quota::StorageBytes, bytes.len() as i64),
+        ],
+    )
+    .await?;
+
+    let store = services::documents::upload_store(state).await?;
+    let blob = store.put_bytes(bytes, Some(MAX_UPLOAD_BYTES)).await?;
+    let row = documents::create_document(
+        &state.pool,
+        &Scope::personal(*user_id),
+        &filename,
+        &document.mime_type,
+        &blob,
+        store.backend(),
+    )
+    .await?;
+    if let Err(e) = account::restore_document_details(&state.pool, user_id, &row.id, document).await
+    {
+        services::documents::discard(state, &row).await;
+        return Err(e.into());
+    }
+
+    let audio = ingest::is_audio_mime(&row.mime_type);
+    if !audio && let Err(e) = services::documents::extract_chunks(state, &store, &row).await {
+        services::documents::discard(state, &row).await;
+        return Err(e);
+    }
+    services::documents::spawn_index(state, store, row, audio);
+    Ok(())
+}
+
+/// Embed an imported note in the background.
+fn spawn_embed_note(state: &AppState, note_id: Uuid) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err(e) = nize_core::embedding::indexer::embed_note(
+            &state.pool,
+            &state.config_cache,
+            &note_id,
+            &state.config.mcp_encryption_key,
+        )
+        .await
+        {
+            tracing::warn!("Failed to embed imported note {note_id}: {e}");
+        }
+    });
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
@@ -1,48 +1,85 @@
 // @awa-component: PLAN-017-AdminPermissionsHandler
 //
-//! Admin permission request handlers — demo stubs.
+//! Admin permission request handlers — the group listing is a demo stub.
 
 use axum::Json;
-use axum::extract::Path;
+use axum::extract::{Path, State};
 use axum::http::StatusCode;
+use uuid::Uuid;
 
-use crate::error::AppResult;
+use nize_core::permissions;
 
-/// `GET /admin/permissions/grants` — list all grants (demo).
-pub async fn list_all_grants_handler() -> AppResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "grants": []
-    })))
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::generated::models::SetAdminRoleRequest;
+use crate::handlers::permissions::{GrantListResponse, LinkListResponse, ShareLink};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+
+/// `GET /admin/permissions/grants` — list all grants.
+pub async fn list_all_grants_handler(
+    State(state): State<AppState>,
+) -> AppResult<Json<GrantListResponse>> {
+    let grants = permissions::list_all_grants(&state.pool).await?;
+    Ok(Json(GrantListResponse { grants }))
 }
 
-/// `DELETE /admin/permissions/grants/{grantId}` — admin revoke grant (demo).
-pub async fn admin_revoke_grant_handler(Path(_grant_id): Path<String>) -> StatusCode {
-    StatusCode::NO_CONTENT
+/// `DELETE /admin/permissions/grants/{grantId}` — admin revoke grant.
+pub async fn admin_revoke_grant_handler(
+    State(state): State<AppState>,
+    Path(grant_id): Path<String>,
+) -> AppResult<StatusCode> {
+    permissions::revoke_grant(&state.pool, None, &parse_uuid(&grant_id)?).await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
 /// `GET /admin/permissions/groups` — list all groups (demo).
-pub async fn list_all_groups_handler() -> AppResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "grants": []
-    })))
+pub async fn list_all_groups_handler() -> AppResult<Json<GrantListResponse>> {
+    Ok(Json(GrantListResponse { grants: Vec::new() }))
 }
 
-/// `GET /admin/permissions/links` — list all links (demo).
-pub async fn list_all_links_handler() -> AppResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "links": []
-    })))
+/// `GET /admin/permissions/links` — list all share links.
+pub async fn list_all_links_handler(
+    State(state): State<AppState>,
+) -> AppResult<Json<LinkListResponse>> {
+    let links = permissions::list_all_links(&state.pool)
+        .await?
+        .into_iter()
+        .map(|l| ShareLink::new(&state, l))
+        .collect();
+    Ok(Json(LinkListResponse { links }))
 }
 
-/// `DELETE /admin/permissions/links/{linkId}` — admin revoke link (demo).
-pub async fn admin_revoke_link_handler(Path(_link_id): Path<String>) -> StatusCode {
-    StatusCode::NO_CONTENT
+/// `DELETE /admin/permissions/links/{linkId}` — admin revoke share link.
+pub async fn admin_revoke_link_handler(
+    State(state): State<AppState>,
+    Path(link_id): Path<String>,
+) -> AppResult<StatusCode> {
+    permissions::revoke_link(&state.pool, None, &parse_uuid(&link_id)?).await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
-/// `PATCH /admin/permissions/users/{userId}/admin` — set admin role (demo).
+/// `PATCH /admin/permissions/users/{userId}/admin` — set or clear the
+/// legacy admin flag (equivalent to the built-in Administrator role).
 pub async fn set_admin_role_handler(
-    Path(_user_id): Path<String>,
-    Json(_body): Json<serde_json::Value>,
-) -> StatusCode {
-    StatusCode::NO_CONTENT
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(user_id): Path<String>,
+    Json(body): Json<SetAdminRoleRequest>,
+) -> AppResult<StatusCode> {
+    let granted_by = Uuid::parse_str(&user.0.sub)
+        .map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))?;
+    let user_id = Uuid::parse_str(&user_id)
+        .map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))?;
+    let is_admin = body
+        .is_admin
+        .ok_or_else(|| AppError::validation(Message::new("admin.is_admin_required")))?;
+
+    nize_core::auth::rbac::set_admin_flag(&state.pool, &user_id, is_admin, &granted_by).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
 }
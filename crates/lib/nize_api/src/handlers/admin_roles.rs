@@ -0,0 +1,240 @@
+//! Admin role management handlers (RBAC).
+//!
+//! Roles bundle permission strings; users hold the union of their roles'
+//! permissions. All endpoints require the `users.manage` permission.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::auth::activity::{self, ClientInfo};
+use nize_core::auth::rbac::{self, RoleRow, RoleWithUsage};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::services::security_activity;
+
+/// A role and the permissions it grants.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Role {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+    pub built_in: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<&RoleRow> for Role {
+    fn from(row: &RoleRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name.clone(),
+            description: row.description.clone(),
+            permissions: row.permissions.clone(),
+            built_in: row.built_in,
+            created_at: row.created_at.to_rfc3339(),
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// A role with the number of users holding it.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleWithUserCount {
+    #[serde(flatten)]
+    pub role: Role,
+    pub user_count: i64,
+}
+
+impl From<&RoleWithUsage> for RoleWithUserCount {
+    fn from(row: &RoleWithUsage) -> Self {
+        Self {
+            role: Role::from(&row.role),
+            user_count: row.user_count,
+        }
+    }
+}
+
+/// An assignable permission.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Permission {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// Body of `GET /admin/roles`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RoleListResponse {
+    pub roles: Vec<RoleWithUserCount>,
+    pub permissions: Vec<Permission>,
+}
+
+/// Body of `GET /admin/users/{userId}/roles`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct UserRolesResponse {
+    pub roles: Vec<Role>,
+    /// Effective permissions: the union of the roles'.
+    pub permissions: Vec<String>,
+}
+
+/// `GET /admin/roles` — list roles with user counts, plus the catalog of
+/// assignable permissions.
+pub async fn list_roles_handler(
+    State(state): State<AppState>,
+) -> AppResult<Json<RoleListResponse>> {
+    let rows = rbac::list_roles(&state.pool).await?;
+
+    Ok(Json(RoleListResponse {
+        roles: rows.iter().map(RoleWithUserCount::from).collect(),
+        permissions: rbac::PERMISSIONS
+            .iter()
+            .map(|&(name, description)| Permission { name, description })
+            .collect(),
+    }))
+}
+
+/// Request body for creating a role.
+#[derive(Debug, Deserialize)]
+pub struct CreateRoleBody {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+/// `POST /admin/roles` — create a custom role.
+pub async fn create_role_handler(
+    State(state): State<AppState>,
+    Json(body): Json<CreateRoleBody>,
+) -> AppResult<(StatusCode, Json<Role>)> {
+    let row = rbac::create_role(
+        &state.pool,
+        &body.name,
+        body.description.as_deref().unwrap_or(""),
+        &body.permissions,
+    )
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(Role::from(&row))))
+}
+
+/// Request body for updating a role. Omitted fields are left unchanged.
+#[derive(Debug, Deserialize)]
+pub struct UpdateRoleBody {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub permissions: Option<Vec<String>>,
+}
+
+/// `PATCH /admin/roles/{id}` — update a custom role.
+pub async fn update_role_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateRoleBody>,
+) -> AppResult<Json<Role>> {
+    let role_id = parse_uuid(&id)?;
+
+    let row = rbac::update_role(
+        &state.pool,
+        &role_id,
+        body.name.as_deref(),
+        body.description.as_deref(),
+        body.permissions.as_deref(),
+    )
+    .await?;
+
+    Ok(Json(Role::from(&row)))
+}
+
+/// `DELETE /admin/roles/{id}` — delete a custom role.
+pub async fn delete_role_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    let role_id = parse_uuid(&id)?;
+
+    rbac::delete_role(&state.pool, &role_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /admin/users/{userId}/roles` — a user's roles and effective
+/// permissions.
+pub async fn list_user_roles_handler(
+    State(state): State<AppState>,
+    Path(user_id): Path<String>,
+) -> AppResult<Json<UserRolesResponse>> {
+    let user_id = parse_uuid(&user_id)?;
+
+    let roles = rbac::list_user_roles(&state.pool, &user_id).await?;
+    let permissions = rbac::user_permissions(&state.pool, &user_id).await?;
+
+    Ok(Json(UserRolesResponse {
+        roles: roles.iter().map(Role::from).collect(),
+        permissions,
+    }))
+}
+
+/// `PUT /admin/users/{userId}/roles/{roleId}` — assign a role.
+pub async fn assign_role_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((user_id, role_id)): Path<(String, String)>,
+) -> AppResult<Json<Role>> {
+    let granted_by = parse_user_id(&user.0.sub)?;
+    let user_id = parse_uuid(&user_id)?;
+    let role_id = parse_uuid(&role_id)?;
+
+    let row = rbac::assign_role(&state.pool, &user_id, &role_id, &granted_by).await?;
+    security_activity::record(
+        &state.pool,
+        &user_id.to_string(),
+        activity::KIND_ROLE_GRANTED,
+        &ClientInfo::default(),
+        serde_json::json!({
+            "roleId": row.id,
+            "roleName": row.name,
+            "grantedBy": granted_by,
+        }),
+    )
+    .await;
+
+    Ok(Json(Role::from(&row)))
+}
+
+/// `DELETE /admin/users/{userId}/roles/{roleId}` — remove a role
+/// assignment.
+pub async fn unassign_role_handler(
+    State(state): State<AppState>,
+    Path((user_id, role_id)): Path<(String, String)>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_uuid(&user_id)?;
+    let role_id = parse_uuid(&role_id)?;
+
+    if rbac::unassign_role(&state.pool, &user_id, &role_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(Message::new(
+            "roles.assignment_not_found",
+        )))
+    }
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
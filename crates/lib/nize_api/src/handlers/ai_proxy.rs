@@ -7,25 +7,71 @@
 //! 2. Reads target URL and provider type from query params
 //! 3. Decrypts the user's API key for that provider from config
 //! 4. Injects the provider-specific auth header
+//!
+//! Besides the built-in providers, `provider` may name an admin-configured
+//! OpenAI-compatible provider (see [`nize_core::providers`]); requests to
+//! it may only target its base URL and carry its configured key and header.
 //! 5. Proxies the request and streams the response back
+//!
+//! With content moderation on (see [`crate::services::moderation`]) the
+//! latest user message is checked before the request goes out, and the
+//! response is checked before it comes back — buffered in full, so
+//! streamed responses arrive in one piece while response checks are on.
+//!
+//! With `transport=poll` a successful response is not returned directly:
+//! the handler answers `202` with a `streamId` and copies the response body
+//! into a buffered stream (see [`crate::streams`]) for clients behind
+//! proxies that break streaming responses.
 
-use axum::body::Body;
+use axum::body::{Body, Bytes};
 use axum::extract::{Query, State};
 use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
+use futures_util::StreamExt;
+use serde_json::Value;
+use uuid::Uuid;
+
+use nize_core::moderation::{Action, Moderator, Source};
+use nize_core::providers::{self, CompatibleEndpoint};
 
 use crate::AppState;
-use crate::error::AppError;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
 use crate::middleware::auth::AuthenticatedUser;
 use crate::services::config;
+use crate::services::moderation::{self, Subject};
+use crate::streams::Utf8Decoder;
+
+/// Route of the proxy. It is outside the API spec, so it has no generated
+/// constant in [`crate::generated::routes`].
+pub const ROUTE: &str = "/ai-proxy";
+
+/// Keys holding nested response content that may carry text.
+const CONTAINER_KEYS: &[&str] = &[
+    "choices",
+    "message",
+    "delta",
+    "candidates",
+    "content",
+    "parts",
+    "output",
+    "response",
+    "item",
+];
+
+/// Keys whose string values are response text.
+const TEXT_KEYS: &[&str] = &["text", "content", "delta"];
 
 /// Query parameters for the AI proxy endpoint.
 #[derive(Debug, serde::Deserialize)]
 pub struct AiProxyQuery {
     /// Target URL to proxy the request to.
     pub target: String,
-    /// Provider type: "anthropic", "openai", or "google".
+    /// Provider type: "anthropic", "openai", "google", or the name of an
+    /// OpenAI-compatible provider.
     pub provider: String,
+    /// `poll` to receive the response through a buffered stream instead.
+    pub transport: Option<String>,
 }
 
 /// Provider type → config key + auth header mapping.
@@ -36,6 +82,12 @@ struct ProviderMapping {
     auth_header_prefix: &'static str,
 }
 
+/// Where a proxied request goes.
+enum Upstream {
+    BuiltIn(ProviderMapping),
+    Compatible(CompatibleEndpoint),
+}
+
 fn get_provider_mapping(provider: &str) -> Option<ProviderMapping> {
     match provider {
         "anthropic" => Some(ProviderMapping {
@@ -70,44 +122,87 @@ pub async fn ai_proxy_handler(
     body: Body,
 ) -> Result<Response, AppError> {
     // Validate provider type
-    let mapping = get_provider_mapping(&params.provider).ok_or_else(|| {
-        AppError::Forbidden(format!("Unknown provider type: {}", params.provider))
-    })?;
+    let upstream = match get_provider_mapping(&params.provider) {
+        Some(mapping) => Upstream::BuiltIn(mapping),
+        None => providers::endpoint(
+            &state.pool,
+            &state.config_cache,
+            &state.config.mcp_encryption_key,
+            &params.provider,
+        )
+        .await
+        .map(Upstream::Compatible)
+        .ok_or_else(|| {
+            AppError::forbidden(
+                Message::new("ai_proxy.unknown_provider").arg("provider", &params.provider),
+            )
+        })?,
+    };
+
+    let poll = match params.transport.as_deref() {
+        None | Some("stream") => false,
+        Some("poll") => true,
+        Some(other) => {
+            return Err(AppError::validation(
+                Message::new("ai_proxy.invalid_transport").arg("transport", other),
+            ));
+        }
+    };
 
     // Validate target URL
     let target_url: url::Url = params
         .target
         .parse()
-        .map_err(|_| AppError::Validation("Invalid target URL".into()))?;
-
-    // Ensure target is HTTPS (or localhost for dev)
-    let host = target_url.host_str().unwrap_or("");
-    let is_safe = target_url.scheme() == "https"
-        || host == "localhost"
-        || host == "127.0.0.1"
-        || host == "::1";
-    if !is_safe {
-        return Err(AppError::Validation(
-            "Target URL must use HTTPS or localhost".into(),
-        ));
-    }
-
-    // Decrypt the API key for this provider
-    let api_key = config::decrypt_secret_config_value(
-        &state.pool,
-        &state.config_cache,
-        &user.0.sub,
-        mapping.config_key,
-        &state.config.mcp_encryption_key,
-        Some(mapping.env_fallback),
-    )
-    .await?
-    .ok_or_else(|| {
-        AppError::Validation(format!(
-            "No API key configured for provider: {}",
-            params.provider
-        ))
-    })?;
+        .map_err(|_| AppError::validation(Message::new("ai_proxy.invalid_target_url")))?;
+
+    let auth = match &upstream {
+        Upstream::BuiltIn(mapping) => {
+            // Ensure target is HTTPS (or localhost for dev)
+            let host = target_url.host_str().unwrap_or("");
+            let is_safe = target_url.scheme() == "https"
+                || host == "localhost"
+                || host == "127.0.0.1"
+                || host == "::1";
+            if !is_safe {
+                return Err(AppError::validation(Message::new(
+                    "ai_proxy.insecure_target_url",
+                )));
+            }
+
+            // Decrypt the API key for this provider
+            let api_key = config::decrypt_secret_config_value(
+                &state.pool,
+                &state.config_cache,
+                &user.0.sub,
+                mapping.config_key,
+                &state.config.mcp_encryption_key,
+                Some(mapping.env_fallback),
+            )
+            .await?
+            .ok_or_else(|| {
+                AppError::validation(
+                    Message::new("ai_proxy.api_key_missing").arg("provider", &params.provider),
+                )
+            })?;
+            Some((
+                mapping.auth_header_name.to_string(),
+                format!("{}{}", mapping.auth_header_prefix, api_key),
+            ))
+        }
+        // The admin chose the base URL, which may be plain HTTP on a LAN;
+        // the key must not go anywhere else.
+        Upstream::Compatible(endpoint) => {
+            if !endpoint.provider.owns_url(&target_url) {
+                return Err(AppError::validation(
+                    Message::new("ai_proxy.target_outside_base_url")
+                        .arg("provider", &params.provider),
+                ));
+            }
+            endpoint
+                .auth_header()
+                .map(|(name, value)| (name.to_string(), value))
+        }
+    };
 
     // Build the outbound request
     let client = reqwest::Client::new();
@@ -128,13 +223,18 @@ pub async fn ai_proxy_handler(
     }
 
     // Inject the provider-specific auth header
-    let auth_value = format!("{}{}", mapping.auth_header_prefix, api_key);
-    req_builder = req_builder.header(mapping.auth_header_name, &auth_value);
+    if let Some((name, value)) = auth {
+        req_builder = req_builder.header(name, value);
+    }
 
     // Stream the request body
-    let body_bytes = axum::body::to_bytes(body, 10 * 1024 * 1024)
+    let mut body_bytes = axum::body::to_bytes(body, 10 * 1024 * 1024)
         .await
         .map_err(|e| AppError::Internal(format!("Failed to read request body: {e}")))?;
+    let user_id = Uuid::parse_str(&user.0.sub).ok();
+    if let Some(moderator) = moderation::moderator(&state, Source::Prompt).await? {
+        body_bytes = moderate_prompt(&state, &moderator, user_id.as_ref(), body_bytes).await?;
+    }
     req_builder = req_builder.body(body_bytes);
 
     // Execute the upstream request
@@ -149,21 +249,50 @@ pub async fn ai_proxy_handler(
 
     let mut response_builder = Response::builder().status(status);
 
-    // Forward response headers (content-type, etc.)
+    // Successful responses are buffered for moderation; errors pass through.
+    let response_moderator = if status.is_success() {
+        moderation::moderator(&state, Source::Response).await?
+    } else {
+        None
+    };
+
+    if poll && status.is_success() {
+        return start_poll_stream(state, user_id, upstream_response, response_moderator);
+    }
+
+    // Forward response headers (content-type, etc.). A buffered body gets
+    // its own length instead of the upstream transfer encoding.
     for (name, value) in upstream_response.headers() {
         let name_str = name.as_str().to_lowercase();
         if matches!(
             name_str.as_str(),
             "content-type" | "transfer-encoding" | "x-request-id"
-        ) && let Ok(v) = value.to_str()
+        ) && !(response_moderator.is_some() && name_str == "transfer-encoding")
+            && let Ok(v) = value.to_str()
         {
             response_builder = response_builder.header(name.as_str(), v);
         }
     }
 
-    // Stream the response body
-    let body_stream = upstream_response.bytes_stream();
-    let body = Body::from_stream(body_stream);
+    let body = match response_moderator {
+        Some(moderator) => {
+            let event_stream = upstream_response
+                .headers()
+                .get("content-type")
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.starts_with("text/event-stream"));
+            let bytes = upstream_response
+                .bytes()
+                .await
+                .map_err(|e| AppError::Internal(format!("Upstream read failed: {e}")))?;
+            Body::from(
+                moderate_response(&state, &moderator, user_id.as_ref(), bytes, event_stream)
+                    .await?,
+            )
+        }
+        // Stream the response body
+        None => Body::from_stream(upstream_response.bytes_stream()),
+    };
 
     response_builder
         .body(body)
@@ -171,6 +300,260 @@ pub async fn ai_proxy_handler(
         .map(IntoResponse::into_response)
 }
 
+/// Copy a successful upstream response into a new buffered stream, answering
+/// `202 {streamId, contentType}`. A response that fails moderation ends the
+/// stream with the error instead of its content.
+fn start_poll_stream(
+    state: AppState,
+    user_id: Option<Uuid>,
+    upstream: reqwest::Response,
+    moderator: Option<Moderator>,
+) -> Result<Response, AppError> {
+    let owner =
+        user_id.ok_or_else(|| AppError::unauthorized(Message::new("auth.invalid_user_id")))?;
+    let stream_id = state.streams.create(owner)?;
+    let content_type = upstream
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let event_stream = content_type
+        .as_deref()
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+
+    tokio::spawn(async move {
+        let streams = state.streams.clone();
+        let error = match moderator {
+            Some(moderator) => {
+                let checked = match upstream.bytes().await {
+                    Ok(bytes) => {
+                        moderate_response(&state, &moderator, user_id.as_ref(), bytes, event_stream)
+                            .await
+                            .map_err(|e| e.to_string())
+                    }
+                    Err(e) => Err(format!("Upstream read failed: {e}")),
+                };
+                match checked {
+                    Ok(bytes) => {
+                        streams.push(
+                            &stream_id,
+                            None,
+                            String::from_utf8_lossy(&bytes).into_owned(),
+                        );
+                        None
+                    }
+                    Err(e) => Some(e),
+                }
+            }
+            None => {
+                let mut body = upstream.bytes_stream();
+                let mut decoder = Utf8Decoder::default();
+                loop {
+                    match body.next().await {
+                        Some(Ok(bytes)) => {
+                            let text = decoder.push(&bytes);
+                            if !text.is_empty() && !streams.push(&stream_id, None, text) {
+                                // Nobody is polling any more.
+                                return;
+                            }
+                        }
+                        Some(Err(e)) => break Some(format!("Upstream read failed: {e}")),
+                        None => {
+                            let text = decoder.finish();
+                            if !text.is_empty() {
+                                streams.push(&stream_id, None, text);
+                            }
+                            break None;
+                        }
+                    }
+                }
+            }
+        };
+        streams.finish(&stream_id, error);
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        axum::Json(serde_json::json!({ "streamId": stream_id, "contentType": content_type })),
+    )
+        .into_response())
+}
+
+/// Moderate the latest user message of a provider request, returning the
+/// body to send (re-encoded if anything was redacted). Bodies that are not
+/// JSON, or whose latest message is not from the user, pass unchecked.
+async fn moderate_prompt(
+    state: &AppState,
+    moderator: &Moderator,
+    user_id: Option<&Uuid>,
+    body: Bytes,
+) -> AppResult<Bytes> {
+    let Ok(mut request) = serde_json::from_slice::<Value>(&body) else {
+        return Ok(body);
+    };
+    let mut fields = prompt_text_fields(&mut request);
+    if fields.is_empty() {
+        return Ok(body);
+    }
+    let subject = Subject {
+        source: Source::Prompt,
+        user_id,
+        resource_id: None,
+    };
+    if moderation::apply(state, moderator, subject, &mut fields, "\n").await?
+        != Some(Action::Redact)
+    {
+        return Ok(body);
+    }
+    encode(&request)
+}
+
+/// Moderate a buffered provider response, returning the body to send back.
+/// An event stream is checked as the concatenation of the text of all its
+/// events, so matches split across events are still found.
+async fn moderate_response(
+    state: &AppState,
+    moderator: &Moderator,
+    user_id: Option<&Uuid>,
+    body: Bytes,
+    event_stream: bool,
+) -> AppResult<Bytes> {
+    let subject = Subject {
+        source: Source::Response,
+        user_id,
+        resource_id: None,
+    };
+    if !event_stream {
+        let Ok(mut response) = serde_json::from_slice::<Value>(&body) else {
+            return Ok(body);
+        };
+        let mut fields = Vec::new();
+        collect_response_text(&mut response, &mut fields);
+        if moderation::apply(state, moderator, subject, &mut fields, "\n").await?
+            != Some(Action::Redact)
+        {
+            return Ok(body);
+        }
+        return encode(&response);
+    }
+
+    let Ok(text) = std::str::from_utf8(&body) else {
+        return Ok(body);
+    };
+    let mut events: Vec<(&str, Option<Value>)> = text
+        .split_inclusive('\n')
+        .map(|line| {
+            let data = line
+                .strip_prefix("data:")
+                .and_then(|data| serde_json::from_str::<Value>(data.trim()).ok());
+            (line, data)
+        })
+        .collect();
+    let mut fields = Vec::new();
+    for data in events.iter_mut().filter_map(|(_, data)| data.as_mut()) {
+        collect_response_text(data, &mut fields);
+    }
+    if moderation::apply(state, moderator, subject, &mut fields, "").await? != Some(Action::Redact)
+    {
+        return Ok(body);
+    }
+    let mut rewritten = String::with_capacity(text.len());
+    for (line, data) in &events {
+        match data {
+            Some(data) => {
+                let ending = &line[line.trim_end_matches(['\r', '\n']).len()..];
+                rewritten.push_str("data: ");
+                rewritten.push_str(&data.to_string());
+                rewritten.push_str(ending);
+            }
+            None => rewritten.push_str(line),
+        }
+    }
+    Ok(Bytes::from(rewritten))
+}
+
+fn encode(value: &Value) -> AppResult<Bytes> {
+    serde_json::to_vec(value)
+        .map(Bytes::from)
+        .map_err(|e| AppError::Internal(format!("Failed to encode body: {e}")))
+}
+
+/// Text of the request's latest message when the user sent it: the last
+/// entry of `messages` (Anthropic, OpenAI chat), `contents` (Google) or
+/// `input` (OpenAI responses), or `input` itself when it is a string.
+/// Earlier turns were checked when they were sent.
+fn prompt_text_fields(request: &mut Value) -> Vec<&mut String> {
+    let Some(object) = request.as_object_mut() else {
+        return Vec::new();
+    };
+    let Some(key) = ["messages", "contents", "input"]
+        .into_iter()
+        .find(|key| object.contains_key(*key))
+    else {
+        return Vec::new();
+    };
+    match object.get_mut(key) {
+        Some(Value::String(text)) => vec![text],
+        Some(Value::Array(messages)) => match messages.last_mut() {
+            Some(message) if message.get("role").and_then(Value::as_str) == Some("user") => {
+                message_text_fields(message)
+            }
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// A message's `content` string, or the `text` of its `content` or `parts`
+/// entries. Tool results and attachments carry no `text` and are skipped.
+fn message_text_fields(message: &mut Value) -> Vec<&mut String> {
+    let mut fields = Vec::new();
+    let Some(object) = message.as_object_mut() else {
+        return fields;
+    };
+    for (key, value) in object.iter_mut() {
+        match (key.as_str(), value) {
+            ("content", Value::String(text)) => fields.push(text),
+            ("content" | "parts", Value::Array(parts)) => fields.extend(
+                parts
+                    .iter_mut()
+                    .filter_map(|part| match part.get_mut("text") {
+                        Some(Value::String(text)) => Some(text),
+                        _ => None,
+                    }),
+            ),
+            _ => {}
+        }
+    }
+    fields
+}
+
+/// Collect the text of a provider response or stream event: message
+/// content, deltas and candidate parts of every supported provider.
+fn collect_response_text<'a>(value: &'a mut Value, fields: &mut Vec<&'a mut String>) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                collect_response_text(item, fields);
+            }
+        }
+        Value::Object(object) => {
+            for (key, value) in object.iter_mut() {
+                if value.is_string() {
+                    if let Value::String(text) = value
+                        && TEXT_KEYS.contains(&key.as_str())
+                    {
+                        fields.push(text);
+                    }
+                } else if CONTAINER_KEYS.contains(&key.as_str()) {
+                    collect_response_text(value, fields);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,4 +597,63 @@ mod tests {
         assert_eq!(m.auth_header_name, "x-goog-api-key");
         assert_eq!(m.auth_header_prefix, "");
     }
+
+    fn texts(fields: Vec<&mut String>) -> Vec<String> {
+        fields.into_iter().map(|text| text.clone()).collect()
+    }
+
+    #[test]
+    fn prompt_checks_only_a_trailing_user_message() {
+        let mut anthropic = serde_json::json!({
+            "system": "be nice",
+            "messages": [
+                { "role": "user", "content": "first" },
+                { "role": "assistant", "content": "reply" },
+                { "role": "user", "content": [
+                    { "type": "text", "text": "latest" },
+                    { "type": "image", "source": {} }
+                ] }
+            ]
+        });
+        assert_eq!(texts(prompt_text_fields(&mut anthropic)), ["latest"]);
+
+        let mut google = serde_json::json!({
+            "contents": [{ "role": "user", "parts": [{ "text": "hi" }] }]
+        });
+        assert_eq!(texts(prompt_text_fields(&mut google)), ["hi"]);
+
+        let mut tool_step = serde_json::json!({
+            "messages": [
+                { "role": "user", "content": "question" },
+                { "role": "tool", "content": "result" }
+            ]
+        });
+        assert!(prompt_text_fields(&mut tool_step).is_empty());
+    }
+
+    #[test]
+    fn response_text_covers_messages_and_stream_events() {
+        let mut openai = serde_json::json!({
+            "choices": [{ "message": { "role": "assistant", "content": "answer" } }],
+            "usage": { "total_tokens": 3 }
+        });
+        let mut fields = Vec::new();
+        collect_response_text(&mut openai, &mut fields);
+        assert_eq!(texts(fields), ["answer"]);
+
+        let mut anthropic_delta = serde_json::json!({
+            "type": "content_block_delta",
+            "delta": { "type": "text_delta", "text": "chunk" }
+        });
+        let mut fields = Vec::new();
+        collect_response_text(&mut anthropic_delta, &mut fields);
+        assert_eq!(texts(fields), ["chunk"]);
+
+        let mut tool_use = serde_json::json!({
+            "content": [{ "type": "tool_use", "input": { "text": "args" } }]
+        });
+        let mut fields = Vec::new();
+        collect_response_text(&mut tool_use, &mut fields);
+        assert!(fields.is_empty());
+    }
 }
@@ -0,0 +1,133 @@
+//! Admin analytics endpoints.
+
+use std::collections::BTreeMap;
+
+use axum::Json;
+use axum::extract::{Query, State};
+use chrono::{NaiveDate, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::mcp::analytics::{self, AnalyticsRange, ToolUsageRow, UsageFilter};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+
+/// Query params for tool analytics.
+#[derive(Debug, Deserialize)]
+pub struct ToolAnalyticsParams {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[serde(rename = "serverId")]
+    pub server_id: Option<String>,
+    #[serde(rename = "userId")]
+    pub user_id: Option<String>,
+    #[serde(rename = "toolName")]
+    pub tool_name: Option<String>,
+}
+
+/// One day of usage for a single user/server/tool.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolUsageRollup {
+    pub day: String,
+    pub user_id: Uuid,
+    pub server_id: Uuid,
+    pub server_name: String,
+    pub tool_name: String,
+    pub call_count: i64,
+    pub success_count: i64,
+    pub success_rate: f64,
+    pub p50_latency_ms: f64,
+    pub p95_latency_ms: f64,
+    /// Failure counts keyed by error category.
+    pub error_categories: BTreeMap<String, i64>,
+}
+
+impl From<&ToolUsageRow> for ToolUsageRollup {
+    fn from(row: &ToolUsageRow) -> Self {
+        Self {
+            day: row.day.to_string(),
+            user_id: row.user_id,
+            server_id: row.server_id,
+            server_name: row.server_name.clone(),
+            tool_name: row.tool_name.clone(),
+            call_count: row.call_count,
+            success_count: row.success_count,
+            success_rate: row.success_rate(),
+            p50_latency_ms: row.p50_latency_ms,
+            p95_latency_ms: row.p95_latency_ms,
+            // The rollup always writes an object of counts.
+            error_categories: serde_json::from_value(row.error_categories.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Body of `GET /admin/analytics/tools`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ToolAnalyticsResponse {
+    pub from: String,
+    pub to: String,
+    pub rollups: Vec<ToolUsageRollup>,
+}
+
+/// `GET /admin/analytics/tools` — daily per-user/per-server/per-tool usage.
+///
+/// Rollups for the requested range are refreshed from the raw execution
+/// events before reading, so today's numbers are always current.
+pub async fn tool_analytics_handler(
+    State(state): State<AppState>,
+    Query(params): Query<ToolAnalyticsParams>,
+) -> AppResult<Json<ToolAnalyticsResponse>> {
+    let range = AnalyticsRange::new(
+        params.from.as_deref().map(parse_date).transpose()?,
+        params.to.as_deref().map(parse_date).transpose()?,
+        Utc::now().date_naive(),
+    )?;
+    let filter = UsageFilter {
+        user_id: params.user_id.as_deref().map(parse_uuid).transpose()?,
+        server_id: params.server_id.as_deref().map(parse_uuid).transpose()?,
+        tool_name: params.tool_name.filter(|t| !t.is_empty()),
+    };
+
+    analytics::rollup(&state.pool, &range).await?;
+    let rows = analytics::tool_usage(&state.pool, &range, &filter).await?;
+
+    Ok(Json(ToolAnalyticsResponse {
+        from: range.from.to_string(),
+        to: range.to.to_string(),
+        rollups: rows.iter().map(ToolUsageRollup::from).collect(),
+    }))
+}
+
+/// Parse a `YYYY-MM-DD` query parameter.
+fn parse_date(s: &str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| AppError::validation(Message::new("request.invalid_date").arg("value", s)))
+}
+
+/// Parse a query parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorKind;
+
+    #[test]
+    fn parse_date_accepts_iso_days_only() {
+        assert_eq!(
+            parse_date("2026-03-10").unwrap(),
+            NaiveDate::from_ymd_opt(2026, 3, 10).unwrap()
+        );
+        assert!(matches!(
+            parse_date("03/10/2026"),
+            Err(AppError::Localized(ErrorKind::Validation, _))
+        ));
+    }
+}
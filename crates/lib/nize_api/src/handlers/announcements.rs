@@ -0,0 +1,97 @@
+//! Announcement request handlers.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use schemars::JsonSchema;
+use serde::Serialize;
+use uuid::Uuid;
+
+use nize_core::announcements::{self, Announcement, AnnouncementInput};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::services::announcements::publish_due;
+
+/// Body of `GET /announcements` and `GET /admin/announcements`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AnnouncementListResponse {
+    pub items: Vec<Announcement>,
+}
+
+/// `GET /announcements` — active announcements the user has not dismissed.
+pub async fn list_announcements_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+) -> AppResult<Json<AnnouncementListResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let items = announcements::list_active(&state.pool, &user_id).await?;
+    Ok(Json(AnnouncementListResponse { items }))
+}
+
+/// `POST /announcements/{id}/dismiss` — hide an announcement for the user.
+pub async fn dismiss_announcement_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    announcements::dismiss(&state.pool, &user_id, &parse_uuid(&id)?).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /admin/announcements` — every announcement, latest start first.
+pub async fn admin_list_announcements_handler(
+    State(state): State<AppState>,
+) -> AppResult<Json<AnnouncementListResponse>> {
+    let items = announcements::list_all(&state.pool).await?;
+    Ok(Json(AnnouncementListResponse { items }))
+}
+
+/// `POST /admin/announcements` — create an announcement.
+pub async fn admin_create_announcement_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Json(body): Json<AnnouncementInput>,
+) -> AppResult<(StatusCode, Json<Announcement>)> {
+    let created_by = parse_user_id(&user.0.sub)?;
+    let announcement = announcements::create(&state.pool, &body, &created_by).await?;
+    publish_due(&state).await;
+    Ok((StatusCode::CREATED, Json(announcement)))
+}
+
+/// `PUT /admin/announcements/{id}` — replace an announcement.
+pub async fn admin_update_announcement_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<AnnouncementInput>,
+) -> AppResult<Json<Announcement>> {
+    let announcement = announcements::update(&state.pool, &parse_uuid(&id)?, &body).await?;
+    publish_due(&state).await;
+    Ok(Json(announcement))
+}
+
+/// `DELETE /admin/announcements/{id}` — delete an announcement.
+pub async fn admin_delete_announcement_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    if announcements::delete(&state.pool, &parse_uuid(&id)?).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(
+            Message::new("announcements.not_found").arg("id", id),
+        ))
+    }
+}
+
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
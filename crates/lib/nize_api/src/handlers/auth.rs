@@ -4,9 +4,15 @@
 //
 //! Authentication request handlers.
 
-use axum::Json;
-use axum::extract::State;
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Extension, Json};
+use axum_extra::either::Either;
 use axum_extra::extract::CookieJar;
+use serde::Deserialize;
+use tracing::warn;
 
 use crate::AppState;
 use crate::error::AppResult;
@@ -14,8 +20,16 @@ use crate::generated::models::{
     AuthStatusResponse, LoginRequest, LogoutRequest, LogoutResponse, RefreshRequest,
     RegisterRequest, TokenResponse,
 };
-use crate::services::auth;
+use crate::middleware::csrf::{self, CSRF_HEADER};
+use crate::services::auth::{self, Registration};
 use crate::services::cookies;
+use crate::services::email_verification::{
+    self, ResendVerificationRequest, VerificationPendingResponse, VerifyEmailResponse,
+};
+use crate::services::security_activity;
+
+/// Response header carrying the CSRF token issued with a session.
+type CsrfHeader = [(&'static str, String); 1];
 
 // @awa-impl: AUTH-1_AC-1, AUTH-1_AC-2
 /// `POST /auth/login` — authenticate with email + password.
@@ -23,69 +37,151 @@ use crate::services::cookies;
 pub async fn login_handler(
     State(state): State<AppState>,
     jar: CookieJar,
+    headers: HeaderMap,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
     Json(body): Json<LoginRequest>,
-) -> AppResult<(CookieJar, Json<TokenResponse>)> {
+) -> AppResult<(CookieJar, CsrfHeader, Json<TokenResponse>)> {
     let resp = auth::login(
         &state.pool,
         &body.email,
         &body.password,
-        state.config.jwt_secret.as_bytes(),
+        &state.jwt_keys,
+        state.config.require_email_verification,
     )
     .await?;
-    let jar = jar
-        .add(cookies::access_cookie(&resp.access_token, resp.expires_in))
-        .add(cookies::refresh_cookie(&resp.refresh_token));
-    Ok((jar, Json(resp)))
+    record_login(&state, &resp, &headers, peer, "password").await;
+    let (jar, csrf) = start_session(jar, &resp, csrf::new_token());
+    Ok((jar, csrf, Json(resp)))
 }
 
 // @awa-impl: AUTH-1.1_AC-2, AUTH-1.1_AC-4
 /// `POST /auth/register` — create a new user account.
-/// Sets httpOnly auth cookies alongside the JSON response.
+/// Sets httpOnly auth cookies alongside the JSON response. When email
+/// verification is required, mails a verification link instead and
+/// answers 202 without a session.
 pub async fn register_handler(
     State(state): State<AppState>,
     jar: CookieJar,
     Json(body): Json<RegisterRequest>,
-) -> AppResult<(CookieJar, Json<TokenResponse>)> {
-    let resp = auth::register(
+) -> AppResult<
+    Either<
+        (CookieJar, CsrfHeader, Json<TokenResponse>),
+        (StatusCode, Json<VerificationPendingResponse>),
+    >,
+> {
+    let registration = auth::register(
         &state.pool,
         &body.email,
         &body.password,
         body.name.as_deref(),
-        state.config.jwt_secret.as_bytes(),
+        &state.jwt_keys,
+        state.config.require_email_verification,
     )
     .await?;
-    let jar = jar
-        .add(cookies::access_cookie(&resp.access_token, resp.expires_in))
-        .add(cookies::refresh_cookie(&resp.refresh_token));
-    Ok((jar, Json(resp)))
+    match registration {
+        Registration::Session(resp) => {
+            let (jar, csrf) = start_session(jar, &resp, csrf::new_token());
+            Ok(Either::E1((jar, csrf, Json(resp))))
+        }
+        Registration::VerificationPending { user_id } => {
+            // The account exists either way; the user can ask for a new link.
+            if let Err(e) =
+                email_verification::send_verification(&state, &user_id, &body.email).await
+            {
+                warn!(email = %body.email, "Failed to send verification email: {e}");
+            }
+            let resp = VerificationPendingResponse {
+                verification_required: true,
+                email: body.email,
+            };
+            Ok(Either::E2((StatusCode::ACCEPTED, Json(resp))))
+        }
+    }
+}
+
+/// Query parameters of `GET /auth/verify`.
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailParams {
+    pub token: String,
+}
+
+/// `GET /auth/verify?token=` — verify the email address a link was sent to.
+pub async fn verify_email_handler(
+    State(state): State<AppState>,
+    Query(params): Query<VerifyEmailParams>,
+) -> AppResult<Json<VerifyEmailResponse>> {
+    let resp = email_verification::verify(&state, &params.token).await?;
+    Ok(Json(resp))
+}
+
+/// `POST /auth/verify/resend` — mail a new verification link. Always
+/// answers 202, so it does not reveal which addresses have accounts.
+pub async fn resend_verification_handler(
+    State(state): State<AppState>,
+    Json(body): Json<ResendVerificationRequest>,
+) -> StatusCode {
+    if let Err(e) = email_verification::resend(&state, &body.email).await {
+        warn!(email = %body.email, "Failed to resend verification email: {e}");
+    }
+    StatusCode::ACCEPTED
+}
+
+/// Body of `POST /auth/local`.
+#[derive(Debug, Deserialize)]
+pub struct LocalLoginRequest {
+    pub token: String,
+}
+
+/// `POST /auth/local` — sign in as the local user with the desktop's local
+/// token. Sets httpOnly auth cookies alongside the JSON response.
+pub async fn local_login_handler(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    headers: HeaderMap,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    Json(body): Json<LocalLoginRequest>,
+) -> AppResult<(CookieJar, CsrfHeader, Json<TokenResponse>)> {
+    let resp = auth::local_login(
+        &state.pool,
+        state.local_mode.as_deref(),
+        &body.token,
+        &state.jwt_keys,
+    )
+    .await?;
+    record_login(&state, &resp, &headers, peer, "local").await;
+    let (jar, csrf) = start_session(jar, &resp, csrf::new_token());
+    Ok((jar, csrf, Json(resp)))
 }
 
 // @awa-impl: AUTH-3_AC-1, AUTH-3_AC-2
 /// `POST /auth/refresh` — exchange a refresh token for a new token pair.
 /// Checks refresh token from cookie first, then from JSON body.
-/// Sets new httpOnly auth cookies.
+/// Sets new httpOnly auth cookies and keeps the session's CSRF token.
 pub async fn refresh_handler(
     State(state): State<AppState>,
     jar: CookieJar,
     Json(body): Json<RefreshRequest>,
-) -> AppResult<(CookieJar, Json<TokenResponse>)> {
+) -> AppResult<(CookieJar, CsrfHeader, Json<TokenResponse>)> {
     // Prefer cookie refresh token, fall back to body
     let refresh_token = jar
         .get(cookies::REFRESH_COOKIE)
         .map(|c| c.value().to_string())
         .or(body.refresh_token)
-        .ok_or_else(|| crate::error::AppError::Unauthorized("Missing refresh token".into()))?;
+        .ok_or_else(|| {
+            crate::error::AppError::unauthorized(crate::i18n::Message::new(
+                "auth.missing_refresh_token",
+            ))
+        })?;
 
-    let resp = auth::refresh(
-        &state.pool,
-        &refresh_token,
-        state.config.jwt_secret.as_bytes(),
-    )
-    .await?;
-    let jar = jar
-        .add(cookies::access_cookie(&resp.access_token, resp.expires_in))
-        .add(cookies::refresh_cookie(&resp.refresh_token));
-    Ok((jar, Json(resp)))
+    let resp = auth::refresh(&state.pool, &refresh_token, &state.jwt_keys).await?;
+    // Keep the existing token so other open tabs stay valid
+    let token = jar
+        .get(cookies::CSRF_COOKIE)
+        .map(|c| c.value().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(csrf::new_token);
+    let (jar, csrf) = start_session(jar, &resp, token);
+    Ok((jar, csrf, Json(resp)))
 }
 
 // @awa-impl: AUTH-4_AC-1, AUTH-4_AC-2
@@ -104,16 +200,18 @@ pub async fn logout_handler(
     let resp = auth::logout(&state.pool, refresh_token.as_deref()).await?;
     let jar = jar
         .add(cookies::clear_access_cookie())
-        .add(cookies::clear_refresh_cookie());
+        .add(cookies::clear_refresh_cookie())
+        .add(cookies::clear_csrf_cookie());
     Ok((jar, Json(resp)))
 }
 
 /// `POST /auth/logout/all` — revoke all refresh tokens for the user (demo).
-pub async fn logout_all_handler(jar: CookieJar) -> AppResult<(CookieJar, Json<serde_json::Value>)> {
+pub async fn logout_all_handler(jar: CookieJar) -> AppResult<(CookieJar, Json<LogoutResponse>)> {
     let jar = jar
         .add(cookies::clear_access_cookie())
-        .add(cookies::clear_refresh_cookie());
-    Ok((jar, Json(serde_json::json!({ "success": true }))))
+        .add(cookies::clear_refresh_cookie())
+        .add(cookies::clear_csrf_cookie());
+    Ok((jar, Json(LogoutResponse { success: true })))
 }
 
 /// `GET /auth/status` — check whether an admin user has been created.
@@ -123,3 +221,36 @@ pub async fn auth_status_handler(
     let resp = auth::admin_exists(&state.pool).await?;
     Ok(Json(resp))
 }
+
+/// Record a sign-in in the user's security activity.
+async fn record_login(
+    state: &AppState,
+    resp: &TokenResponse,
+    headers: &HeaderMap,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
+    method: &str,
+) {
+    let client = security_activity::client_info(headers, peer.map(|Extension(ConnectInfo(a))| a));
+    security_activity::record(
+        &state.pool,
+        &resp.user.id,
+        nize_core::auth::activity::KIND_LOGIN,
+        &client,
+        serde_json::json!({ "method": method }),
+    )
+    .await;
+}
+
+/// Set the auth and CSRF cookies for a new token pair, returning the CSRF
+/// token header for the client to echo on mutations.
+fn start_session(
+    jar: CookieJar,
+    resp: &TokenResponse,
+    csrf_token: String,
+) -> (CookieJar, CsrfHeader) {
+    let jar = jar
+        .add(cookies::access_cookie(&resp.access_token, resp.expires_in))
+        .add(cookies::refresh_cookie(&resp.refresh_token))
+        .add(cookies::csrf_cookie(&csrf_token));
+    (jar, [(CSRF_HEADER, csrf_token)])
+}
@@ -1,18 +1,145 @@
 // @awa-component: PLAN-017-ChatHandler
 //
-//! Chat request handler — demo stub.
+//! Chat request handlers — the demo chat stub and cost estimates.
 
 use axum::Json;
+use axum::extract::State;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::error::AppResult;
+use nize_core::config::resolver;
+use nize_core::permissions::PermissionLevel;
+use nize_core::pricing::{self, CostEstimate, TokenCounts};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::handlers::conversations::require_access;
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::middleware::workspace::ActiveWorkspace;
+
+/// Config key of the chat model used when a request names none.
+const MODEL_CONFIG_KEY: &str = "agent.model.name";
+
+/// Most documents one estimate counts.
+const MAX_ESTIMATE_DOCUMENTS: usize = 50;
+
+/// Request body for `POST /chat/estimate`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateBody {
+    pub prompt: String,
+    pub model: Option<String>,
+    pub conversation_id: Option<Uuid>,
+    #[serde(default)]
+    pub document_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub context: Vec<String>,
+    pub expected_output_tokens: Option<u64>,
+}
+
+/// Parse status and payload of a structured reply.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct StructuredOutput {
+    /// `valid`, `invalid` (parsed but does not match the schema) or
+    /// `unparsed` (not JSON).
+    pub status: String,
+    /// The parsed reply (null when unparsed).
+    pub data: serde_json::Value,
+    pub errors: Vec<String>,
+    pub attempts: u32,
+}
+
+/// Body of `POST /chat` in non-streaming mode.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatCompletionResponse {
+    pub content: String,
+    pub conversation_id: Uuid,
+    pub message_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured: Option<StructuredOutput>,
+}
 
 /// `POST /chat` — send a chat message (demo: returns simple JSON).
 pub async fn chat_handler(
     Json(_body): Json<serde_json::Value>,
-) -> AppResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "content": "Hello! This is a demo response from the Nize chat endpoint.",
-        "conversationId": "00000000-0000-0000-0000-000000000001",
-        "messageId": "00000000-0000-0000-0000-000000000002"
-    })))
+) -> AppResult<Json<ChatCompletionResponse>> {
+    Ok(Json(ChatCompletionResponse {
+        content: "Hello! This is a demo response from the Nize chat endpoint.".into(),
+        conversation_id: Uuid::from_u128(1),
+        message_id: Uuid::from_u128(2),
+        structured: None,
+    }))
+}
+
+/// `POST /chat/estimate` — estimate the tokens and cost of a message before
+/// it is sent, without calling the provider. The conversation and
+/// documents must be readable by the caller.
+pub async fn estimate_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Json(body): Json<EstimateBody>,
+) -> AppResult<Json<CostEstimate>> {
+    let user_id = Uuid::parse_str(&user.0.sub)
+        .map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))?;
+    if body.document_ids.len() > MAX_ESTIMATE_DOCUMENTS {
+        return Err(AppError::validation(
+            Message::new("chat.too_many_estimate_documents").arg("max", MAX_ESTIMATE_DOCUMENTS),
+        ));
+    }
+
+    let model = match body.model.map(|m| m.trim().to_string()) {
+        Some(model) if !model.is_empty() => model,
+        _ => {
+            resolver::get_effective_value(
+                &state.pool,
+                &state.config_cache,
+                MODEL_CONFIG_KEY,
+                Some(&user.0.sub),
+            )
+            .await?
+            .value
+        }
+    };
+
+    let history = match body.conversation_id {
+        Some(conv_id) => {
+            let scope = workspace.scope(user_id);
+            require_access(&state, &scope, &conv_id, PermissionLevel::View).await?;
+            pricing::conversation_tokens(&state.pool, &conv_id).await?
+        }
+        None => 0,
+    };
+
+    let mut documents = body
+        .context
+        .iter()
+        .map(|c| pricing::estimate_tokens(c))
+        .sum();
+    for document_id in &body.document_ids {
+        nize_core::documents::get_document(&state.pool, &workspace.scope(user_id), document_id)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::RowNotFound => AppError::not_found(
+                    Message::new("chat.document_not_found").arg("id", document_id),
+                ),
+                e => AppError::from(e),
+            })?;
+        documents += pricing::document_tokens(&state.pool, document_id).await?;
+    }
+
+    let tokens = TokenCounts {
+        prompt: pricing::estimate_tokens(&body.prompt),
+        history,
+        documents,
+        output: body
+            .expected_output_tokens
+            .unwrap_or(pricing::DEFAULT_OUTPUT_TOKENS),
+    };
+    let (table, warn_above) = pricing::load(&state.pool, &state.config_cache).await?;
+    let price = pricing::price_for(&table, &model);
+    Ok(Json(pricing::estimate(&model, tokens, price, warn_above)))
 }
@@ -4,10 +4,15 @@
 
 use axum::Json;
 use axum::extract::{Path, Query, State};
-use serde::{Deserialize, Deserializer};
+use nize_core::config::cache::{CacheEntryInfo, CacheStats};
+use nize_core::config::snapshots::ConfigChange;
+use nize_core::models::config::ResolvedConfigItem;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::AppState;
 use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
 use crate::middleware::auth::AuthenticatedUser;
 use crate::services::config;
 
@@ -97,13 +102,61 @@ pub struct AdminUpdateConfigRequest {
     pub value: Option<String>,
 }
 
+/// Body of `GET /config/user`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct UserConfigListResponse {
+    pub items: Vec<ResolvedConfigItem>,
+}
+
+/// Body of `GET /admin/config`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AdminConfigListResponse {
+    pub items: Vec<config::AdminConfigItem>,
+}
+
+/// Body of `GET /admin/config/history`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConfigHistoryResponse {
+    pub items: Vec<config::ConfigHistoryEntry>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Body of `POST /admin/config/rollback/{version}`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigRollbackResponse {
+    /// The version the rollback created.
+    pub version: i32,
+    pub restored_from: i32,
+    pub changes: Vec<ConfigChange>,
+}
+
+/// Body of `GET /admin/config/cache`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConfigCacheResponse {
+    pub entries: Vec<CacheEntryInfo>,
+    pub stats: CacheStats,
+}
+
+/// Body of `POST /admin/config/cache/refresh`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConfigCacheRefreshResponse {
+    /// Entries removed.
+    pub invalidated: usize,
+    /// Entries reloaded from the database.
+    pub warmed: usize,
+    pub stats: CacheStats,
+}
+
 /// `GET /config/user` — list all config items resolved for the authenticated user.
 pub async fn user_config_list_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Json<UserConfigListResponse>> {
     let items = config::get_user_config(&state.pool, &state.config_cache, &user.0.sub).await?;
-    Ok(Json(serde_json::json!({ "items": items })))
+    Ok(Json(UserConfigListResponse { items }))
 }
 
 /// `PATCH /config/user/{key}` — update a user config override.
@@ -112,10 +165,10 @@ pub async fn user_config_update_handler(
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
     Path(key): Path<String>,
     Json(body): Json<UpdateConfigRequest>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Json<ResolvedConfigItem>> {
     let value = body
         .value
-        .ok_or_else(|| AppError::Validation("value is required".into()))?;
+        .ok_or_else(|| AppError::validation(Message::new("config.value_required_in_body")))?;
 
     let item = config::update_user_config(
         &state.pool,
@@ -126,7 +179,7 @@ pub async fn user_config_update_handler(
         &state.config.mcp_encryption_key,
     )
     .await?;
-    Ok(Json(serde_json::to_value(item).unwrap()))
+    Ok(Json(item))
 }
 
 /// `DELETE /config/user/{key}` — reset a user config override.
@@ -153,7 +206,7 @@ pub struct AdminConfigQuery {
 pub async fn admin_config_list_handler(
     State(state): State<AppState>,
     Query(params): Query<AdminConfigQuery>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Json<AdminConfigListResponse>> {
     let scope = params.scope.as_deref().map(|s| match s {
         "system" => nize_core::models::config::ConfigScope::System,
         _ => nize_core::models::config::ConfigScope::UserOverride,
@@ -167,24 +220,29 @@ pub async fn admin_config_list_handler(
         params.search.as_deref(),
     )
     .await?;
-    Ok(Json(serde_json::json!({ "items": items })))
+    Ok(Json(AdminConfigListResponse { items }))
 }
 
 /// `PATCH /admin/config/{scope}/{key}` — update a config value at a specific scope (admin).
 pub async fn admin_config_update_handler(
     State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
     Path((scope_str, key)): Path<(String, String)>,
     Json(body): Json<AdminUpdateConfigRequest>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Json<config::AdminConfigValue>> {
     let scope = match scope_str.as_str() {
         "system" => nize_core::models::config::ConfigScope::System,
         "user-override" => nize_core::models::config::ConfigScope::UserOverride,
-        _ => return Err(AppError::Validation(format!("Invalid scope: {scope_str}"))),
+        _ => {
+            return Err(AppError::validation(
+                Message::new("config.invalid_scope").arg("scope", scope_str),
+            ));
+        }
     };
 
     let value = body
         .value
-        .ok_or_else(|| AppError::Validation("value is required".into()))?;
+        .ok_or_else(|| AppError::validation(Message::new("config.value_required_in_body")))?;
 
     let cv = config::update_admin_config(
         &state.pool,
@@ -194,7 +252,114 @@ pub async fn admin_config_update_handler(
         &value,
         body.user_id.as_deref(),
         &state.config.mcp_encryption_key,
+        &parse_actor(&user)?,
+    )
+    .await?;
+    Ok(Json(cv))
+}
+
+/// Query params for config history.
+#[derive(Debug, Deserialize)]
+pub struct ConfigHistoryQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// `GET /admin/config/history` — system config versions with diffs.
+pub async fn admin_config_history_handler(
+    State(state): State<AppState>,
+    Query(params): Query<ConfigHistoryQuery>,
+) -> AppResult<Json<ConfigHistoryResponse>> {
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let (items, total) = config::get_config_history(&state.pool, limit, offset).await?;
+    Ok(Json(ConfigHistoryResponse {
+        items,
+        total,
+        limit,
+        offset,
+    }))
+}
+
+/// `POST /admin/config/rollback/{version}` — restore a system config version.
+pub async fn admin_config_rollback_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(version): Path<i32>,
+) -> AppResult<Json<ConfigRollbackResponse>> {
+    let (new_version, changes) = config::rollback_config(
+        &state.pool,
+        &state.config_cache,
+        version,
+        &parse_actor(&user)?,
+    )
+    .await?;
+    Ok(Json(ConfigRollbackResponse {
+        version: new_version,
+        restored_from: version,
+        changes,
+    }))
+}
+
+/// The acting admin's user ID.
+fn parse_actor(user: &AuthenticatedUser) -> Result<uuid::Uuid, AppError> {
+    uuid::Uuid::parse_str(&user.0.sub)
+        .map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// `GET /admin/config/cache` — config cache entries and hit stats.
+pub async fn admin_cache_handler(
+    State(state): State<AppState>,
+) -> AppResult<Json<ConfigCacheResponse>> {
+    let (entries, stats) = config::get_cache_snapshot(&state.pool, &state.config_cache).await?;
+    Ok(Json(ConfigCacheResponse { entries, stats }))
+}
+
+/// Admin cache refresh request. Omitted filters match everything.
+#[derive(Debug, Deserialize)]
+pub struct AdminCacheRefreshRequest {
+    pub scope: Option<String>,
+    pub key: Option<String>,
+    #[serde(rename = "userId")]
+    pub user_id: Option<String>,
+    /// Reload invalidated values from the database (default true).
+    pub warm: Option<bool>,
+}
+
+/// `POST /admin/config/cache/refresh` — invalidate and re-warm cache entries.
+pub async fn admin_cache_refresh_handler(
+    State(state): State<AppState>,
+    Json(body): Json<AdminCacheRefreshRequest>,
+) -> AppResult<Json<ConfigCacheRefreshResponse>> {
+    let scope = match body.scope.as_deref() {
+        None => None,
+        Some("system") => Some(nize_core::models::config::ConfigScope::System),
+        Some("user-override") => Some(nize_core::models::config::ConfigScope::UserOverride),
+        Some(s) => {
+            return Err(AppError::validation(
+                Message::new("config.invalid_scope").arg("scope", s),
+            ));
+        }
+    };
+    if let Some(uid) = body.user_id.as_deref()
+        && uuid::Uuid::parse_str(uid).is_err()
+    {
+        return Err(AppError::validation(Message::new("config.invalid_user_id")));
+    }
+
+    let result = config::refresh_cache(
+        &state.pool,
+        &state.config_cache,
+        scope.as_ref(),
+        body.key.as_deref(),
+        body.user_id.as_deref(),
+        body.warm.unwrap_or(true),
     )
     .await?;
-    Ok(Json(serde_json::to_value(cv).unwrap()))
+    let (_, stats) = config::get_cache_snapshot(&state.pool, &state.config_cache).await?;
+    Ok(Json(ConfigCacheRefreshResponse {
+        invalidated: result.invalidated,
+        warmed: result.warmed,
+        stats,
+    }))
 }
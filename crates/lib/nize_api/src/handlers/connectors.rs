@@ -0,0 +1,248 @@
+//! Ingest connector request handlers.
+//!
+//! Connectors are personal. Syncs happen in the background connector
+//! scheduler (see [`crate::services::connector_sync`]); the sync endpoint
+//! only makes a connector due immediately. Folder connectors may only
+//! point at directories under `ingest.connectors.roots`. Secrets (IMAP
+//! passwords) are encrypted before they are stored and never returned.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::connectors::{self, ConnectorRow, ConnectorUpdate, NewConnector};
+use nize_core::mcp::secrets;
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+
+/// A connector. Its secret is never returned, only whether it has one.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Connector {
+    pub id: Uuid,
+    pub kind: String,
+    pub name: String,
+    pub source: String,
+    pub settings: serde_json::Value,
+    pub has_secret: bool,
+    pub schedule: String,
+    pub enabled: bool,
+    pub next_sync_at: Option<String>,
+    pub last_sync_at: Option<String>,
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    pub files_added: i32,
+    pub files_updated: i32,
+    pub files_deleted: i32,
+    pub file_count: i32,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<&ConnectorRow> for Connector {
+    fn from(row: &ConnectorRow) -> Self {
+        Self {
+            id: row.id,
+            kind: row.kind.clone(),
+            name: row.name.clone(),
+            source: row.source.clone(),
+            settings: row.settings.clone(),
+            has_secret: row.secret.is_some(),
+            schedule: row.schedule.clone(),
+            enabled: row.enabled,
+            next_sync_at: row.next_sync_at.map(|t| t.to_rfc3339()),
+            last_sync_at: row.last_sync_at.map(|t| t.to_rfc3339()),
+            last_status: row.last_status.clone(),
+            last_error: row.last_error.clone(),
+            files_added: row.files_added,
+            files_updated: row.files_updated,
+            files_deleted: row.files_deleted,
+            file_count: row.file_count,
+            created_at: row.created_at.to_rfc3339(),
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `GET /connectors`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConnectorListResponse {
+    pub connectors: Vec<Connector>,
+}
+
+/// `GET /connectors` — list the user's connectors.
+pub async fn list_connectors_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+) -> AppResult<Json<ConnectorListResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+
+    let rows = connectors::list_connectors(&state.pool, &user_id).await?;
+
+    Ok(Json(ConnectorListResponse {
+        connectors: rows.iter().map(Connector::from).collect(),
+    }))
+}
+
+/// Request body for creating a connector.
+#[derive(Debug, Deserialize)]
+pub struct CreateConnectorBody {
+    pub kind: String,
+    pub name: String,
+    pub source: String,
+    #[serde(default)]
+    pub settings: serde_json::Value,
+    pub secret: Option<String>,
+    pub schedule: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// `POST /connectors` — create a connector. Its first sync starts on the
+/// scheduler's next tick.
+pub async fn create_connector_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Json(body): Json<CreateConnectorBody>,
+) -> AppResult<(StatusCode, Json<Connector>)> {
+    let user_id = parse_user_id(&user.0.sub)?;
+
+    let roots = connectors::allowed_roots(&state.pool, &state.config_cache).await?;
+    let secret = encrypt_secret(&state, body.secret.as_deref())?;
+    let new = NewConnector {
+        kind: &body.kind,
+        name: &body.name,
+        source: &body.source,
+        settings: &body.settings,
+        secret: secret.as_deref(),
+        schedule: body
+            .schedule
+            .as_deref()
+            .unwrap_or(connectors::DEFAULT_SCHEDULE),
+        enabled: body.enabled,
+    };
+    let row = connectors::create_connector(&state.pool, &user_id, &new, &roots).await?;
+
+    Ok((StatusCode::CREATED, Json(Connector::from(&row))))
+}
+
+/// `GET /connectors/{id}` — get a connector and the status of its last sync.
+pub async fn get_connector_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Connector>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let connector_id = parse_uuid(&id)?;
+
+    let row = connectors::get_connector(&state.pool, &user_id, &connector_id).await?;
+
+    Ok(Json(Connector::from(&row)))
+}
+
+/// Request body for updating a connector.
+#[derive(Debug, Deserialize)]
+pub struct UpdateConnectorBody {
+    pub name: Option<String>,
+    pub source: Option<String>,
+    pub settings: Option<serde_json::Value>,
+    pub secret: Option<String>,
+    pub schedule: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// `PATCH /connectors/{id}` — update a connector.
+pub async fn update_connector_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateConnectorBody>,
+) -> AppResult<Json<Connector>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let connector_id = parse_uuid(&id)?;
+
+    let roots = connectors::allowed_roots(&state.pool, &state.config_cache).await?;
+    let secret = encrypt_secret(&state, body.secret.as_deref())?;
+    let update = ConnectorUpdate {
+        name: body.name.as_deref(),
+        source: body.source.as_deref(),
+        settings: body.settings.as_ref(),
+        secret: secret.as_deref(),
+        schedule: body.schedule.as_deref(),
+        enabled: body.enabled,
+    };
+    let row =
+        connectors::update_connector(&state.pool, &user_id, &connector_id, &update, &roots).await?;
+
+    Ok(Json(Connector::from(&row)))
+}
+
+/// `DELETE /connectors/{id}` — delete a connector (its documents are kept).
+pub async fn delete_connector_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let connector_id = parse_uuid(&id)?;
+
+    if connectors::delete_connector(&state.pool, &user_id, &connector_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(Message::new("connectors.not_found")))
+    }
+}
+
+/// `POST /connectors/{id}/sync` — sync a connector on the scheduler's next
+/// tick.
+pub async fn sync_connector_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<(StatusCode, Json<Connector>)> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let connector_id = parse_uuid(&id)?;
+
+    let connector = connectors::get_connector(&state.pool, &user_id, &connector_id).await?;
+    if !connector.enabled {
+        return Err(AppError::validation(Message::new("connectors.disabled")));
+    }
+
+    let row = connectors::trigger_sync(&state.pool, &user_id, &connector_id).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(Connector::from(&row))))
+}
+
+/// Encrypt a secret given in a request for storage.
+fn encrypt_secret(state: &AppState, secret: Option<&str>) -> AppResult<Option<String>> {
+    match secret {
+        Some("") => Err(AppError::validation(Message::new(
+            "connectors.secret_empty",
+        ))),
+        Some(secret) => Ok(Some(secrets::encrypt(
+            secret,
+            &state.config.mcp_encryption_key,
+        )?)),
+        None => Ok(None),
+    }
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
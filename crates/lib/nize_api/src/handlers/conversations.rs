@@ -2,54 +2,200 @@
 //
 //! Conversations request handlers.
 
+use std::collections::BTreeMap;
+
 use axum::Json;
 use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use nize_core::conversations::{ConversationRow, MessageRow, RollingSummaryRow, ToolSelection};
+use nize_core::mcp::analytics::{self, ToolUseRow};
+use nize_core::permissions::{self, PermissionLevel, ResourceType};
+use nize_core::quotas::{self, Quota};
+use nize_core::workspaces::Scope;
+
 use crate::AppState;
 use crate::error::{AppError, AppResult};
+use crate::i18n;
 use crate::middleware::auth::AuthenticatedUser;
+use crate::middleware::workspace::ActiveWorkspace;
+
+/// A conversation without its messages.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSummary {
+    pub id: Uuid,
+    pub workspace_id: Option<Uuid>,
+    pub title: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<&ConversationRow> for ConversationSummary {
+    fn from(row: &ConversationRow) -> Self {
+        Self {
+            id: row.id,
+            workspace_id: row.workspace_id,
+            title: row.title.clone(),
+            created_at: row.created_at.to_rfc3339(),
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `GET /conversations`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConversationListResponse {
+    pub items: Vec<ConversationSummary>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// A stored message in the AI SDK UIMessage format. Fields the server does
+/// not know about are kept as the client saved them.
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Message {
+    pub id: String,
+    pub role: String,
+    pub parts: Vec<MessagePart>,
+    /// The client's timestamp, or when the message was stored.
+    pub created_at: Option<String>,
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+/// One part of a [`Message`].
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct MessagePart {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+impl From<MessageRow> for Message {
+    fn from(row: MessageRow) -> Self {
+        let mut message: Message = serde_json::from_value(row.message_data).unwrap_or_default();
+        if message.created_at.is_none() {
+            message.created_at = Some(row.created_at.to_rfc3339());
+        }
+        message
+    }
+}
+
+/// Rolling summary of a conversation's older messages.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RollingSummary {
+    pub text: String,
+    pub message_count: i32,
+    pub updated_at: String,
+}
+
+impl From<&RollingSummaryRow> for RollingSummary {
+    fn from(row: &RollingSummaryRow) -> Self {
+        Self {
+            text: row.summary.clone(),
+            message_count: row.message_count,
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// One tool execution of a conversation turn.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolUse {
+    pub server_id: Uuid,
+    pub server_name: String,
+    pub tool_name: String,
+    pub success: bool,
+    pub latency_ms: i32,
+    pub error_category: Option<String>,
+    pub created_at: String,
+}
+
+impl From<ToolUseRow> for ToolUse {
+    fn from(row: ToolUseRow) -> Self {
+        Self {
+            server_id: row.server_id,
+            server_name: row.server_name,
+            tool_name: row.tool_name,
+            success: row.success,
+            latency_ms: row.latency_ms,
+            error_category: row.error_category,
+            created_at: row.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `GET /conversations/{id}`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Conversation {
+    pub id: Uuid,
+    pub workspace_id: Option<Uuid>,
+    pub title: String,
+    pub messages: Vec<Message>,
+    pub summary: Option<RollingSummary>,
+    pub tool_selection: Option<ToolSelection>,
+    /// Tools each turn used, keyed by the id of the user message that
+    /// started it.
+    pub tools_used: BTreeMap<String, Vec<ToolUse>>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Body of `GET` and `PUT /conversations/{id}/tools`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConversationToolSelection {
+    pub selection: Option<ToolSelection>,
+}
 
 /// Query params for listing conversations.
 #[derive(Debug, Deserialize)]
 pub struct ListParams {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    pub tag: Option<String>,
 }
 
-/// `GET /conversations` — list conversations for the authenticated user.
+/// `GET /conversations` — list conversations in the active scope (the
+/// user's own, or the active workspace's).
 pub async fn list_conversations_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
     Query(params): Query<ListParams>,
-) -> AppResult<Json<serde_json::Value>> {
-    let user_id = parse_user_id(&user.0.sub)?;
+) -> AppResult<Json<ConversationListResponse>> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
     let limit = params.limit.unwrap_or(20).min(100);
     let offset = params.offset.unwrap_or(0);
+    let tag = params.tag.map(|t| t.trim().to_lowercase());
 
-    let (rows, total) =
-        nize_core::conversations::list_conversations(&state.pool, &user_id, limit, offset).await?;
-
-    let items: Vec<serde_json::Value> = rows
-        .into_iter()
-        .map(|r| {
-            serde_json::json!({
-                "id": r.id,
-                "title": r.title,
-                "createdAt": r.created_at.to_rfc3339(),
-                "updatedAt": r.updated_at.to_rfc3339(),
-            })
-        })
-        .collect();
-
-    Ok(Json(serde_json::json!({
-        "items": items,
-        "total": total,
-        "limit": limit,
-        "offset": offset,
-    })))
+    let (rows, total) = nize_core::conversations::list_conversations(
+        &state.pool,
+        &scope,
+        tag.as_deref(),
+        limit,
+        offset,
+    )
+    .await?;
+
+    Ok(Json(ConversationListResponse {
+        items: rows.iter().map(ConversationSummary::from).collect(),
+        total,
+        limit,
+        offset,
+    }))
 }
 
 /// Request body for creating a conversation.
@@ -62,91 +208,108 @@ pub struct CreateConversationBody {
 pub async fn create_conversation_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
     Json(body): Json<CreateConversationBody>,
-) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
-    let user_id = parse_user_id(&user.0.sub)?;
+) -> AppResult<(StatusCode, Json<ConversationSummary>)> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
     let title = body.title.as_deref().unwrap_or("New Chat");
 
-    let row = nize_core::conversations::create_conversation(&state.pool, &user_id, title).await?;
+    quotas::ensure_within(
+        &state.pool,
+        &state.config_cache,
+        &scope.user_id,
+        &[(Quota::Conversations, 1)],
+    )
+    .await?;
+
+    let row = nize_core::conversations::create_conversation(&state.pool, &scope, title).await?;
 
-    Ok((
-        StatusCode::CREATED,
-        Json(serde_json::json!({
-            "id": row.id,
-            "title": row.title,
-            "createdAt": row.created_at.to_rfc3339(),
-            "updatedAt": row.updated_at.to_rfc3339(),
-        })),
-    ))
+    Ok((StatusCode::CREATED, Json(ConversationSummary::from(&row))))
 }
 
-/// `GET /conversations/{id}` — get a conversation with messages.
+/// `GET /conversations/{id}` — get a conversation with messages, and the
+/// tools each turn used keyed by the id of the user message that started it.
 pub async fn get_conversation_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
     Path(id): Path<String>,
-) -> AppResult<Json<serde_json::Value>> {
-    let user_id = parse_user_id(&user.0.sub)?;
+) -> AppResult<Json<Conversation>> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
     let conv_id = parse_uuid(&id)?;
 
-    let row = nize_core::conversations::get_conversation(&state.pool, &user_id, &conv_id).await?;
+    let row = require_access(&state, &scope, &conv_id, PermissionLevel::View).await?;
 
     let message_rows = nize_core::conversations::get_messages(&state.pool, &conv_id).await?;
 
-    let messages: Vec<serde_json::Value> =
-        message_rows.into_iter().map(|m| m.message_data).collect();
+    let summary = nize_core::conversations::get_summary(&state.pool, &conv_id)
+        .await?
+        .map(|s| RollingSummary::from(&s));
 
-    Ok(Json(serde_json::json!({
-        "id": row.id,
-        "title": row.title,
-        "messages": messages,
-        "createdAt": row.created_at.to_rfc3339(),
-        "updatedAt": row.updated_at.to_rfc3339(),
-    })))
+    let mut tools_used: BTreeMap<String, Vec<ToolUse>> = BTreeMap::new();
+    for tool_use in analytics::conversation_tool_uses(&state.pool, &conv_id).await? {
+        let Some(turn_id) = tool_use.turn_id.clone() else {
+            continue;
+        };
+        tools_used.entry(turn_id).or_default().push(tool_use.into());
+    }
+
+    Ok(Json(Conversation {
+        id: row.id,
+        workspace_id: row.workspace_id,
+        title: row.title.clone(),
+        messages: message_rows.into_iter().map(Message::from).collect(),
+        summary,
+        tool_selection: row.tool_selection(),
+        tools_used,
+        created_at: row.created_at.to_rfc3339(),
+        updated_at: row.updated_at.to_rfc3339(),
+    }))
 }
 
 /// `PATCH /conversations/{id}` — update a conversation (e.g., title).
 pub async fn update_conversation_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
     Path(id): Path<String>,
     Json(body): Json<serde_json::Value>,
-) -> AppResult<Json<serde_json::Value>> {
-    let user_id = parse_user_id(&user.0.sub)?;
+) -> AppResult<Json<ConversationSummary>> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
     let conv_id = parse_uuid(&id)?;
 
     let title = body
         .get("title")
         .and_then(|v| v.as_str())
-        .ok_or_else(|| AppError::Validation("title is required".into()))?;
+        .ok_or_else(|| AppError::validation(i18n::Message::new("conversations.title_required")))?;
 
-    let row = nize_core::conversations::update_conversation(&state.pool, &user_id, &conv_id, title)
-        .await?;
+    require_manage(&state, &scope, &conv_id).await?;
+    let row =
+        nize_core::conversations::update_conversation(&state.pool, &scope, &conv_id, title).await?;
 
-    Ok(Json(serde_json::json!({
-        "id": row.id,
-        "title": row.title,
-        "createdAt": row.created_at.to_rfc3339(),
-        "updatedAt": row.updated_at.to_rfc3339(),
-    })))
+    Ok(Json(ConversationSummary::from(&row)))
 }
 
 /// `DELETE /conversations/{id}` — delete a conversation and all its messages.
 pub async fn delete_conversation_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
     Path(id): Path<String>,
 ) -> AppResult<StatusCode> {
-    let user_id = parse_user_id(&user.0.sub)?;
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
     let conv_id = parse_uuid(&id)?;
 
+    require_manage(&state, &scope, &conv_id).await?;
     let deleted =
-        nize_core::conversations::delete_conversation(&state.pool, &user_id, &conv_id).await?;
+        nize_core::conversations::delete_conversation(&state.pool, &scope, &conv_id).await?;
 
     if deleted {
         Ok(StatusCode::NO_CONTENT)
     } else {
-        Err(AppError::NotFound("Conversation not found".into()))
+        Err(AppError::not_found(i18n::Message::new(
+            "conversations.not_found",
+        )))
     }
 }
 
@@ -160,26 +323,202 @@ pub struct SaveMessagesBody {
 pub async fn save_messages_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
     Path(id): Path<String>,
     Json(body): Json<SaveMessagesBody>,
 ) -> AppResult<StatusCode> {
-    let user_id = parse_user_id(&user.0.sub)?;
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
     let conv_id = parse_uuid(&id)?;
 
-    // Verify the conversation belongs to this user
-    nize_core::conversations::get_conversation(&state.pool, &user_id, &conv_id).await?;
+    // Verify the conversation is in scope or shared for continuing
+    let conversation = require_access(&state, &scope, &conv_id, PermissionLevel::Execute).await?;
+
+    ensure_message_storage(&state, &conversation.user_id, &conv_id, &body.messages).await?;
 
     nize_core::conversations::save_messages(&state.pool, &conv_id, &body.messages).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Request body for storing a rolling summary.
+#[derive(Debug, Deserialize)]
+pub struct SaveSummaryBody {
+    pub summary: String,
+    #[serde(rename = "messageCount")]
+    pub message_count: i32,
+}
+
+/// `PUT /conversations/{id}/summary` — store the rolling summary of the
+/// conversation's first `messageCount` messages.
+pub async fn save_summary_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path(id): Path<String>,
+    Json(body): Json<SaveSummaryBody>,
+) -> AppResult<Json<RollingSummary>> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    let conv_id = parse_uuid(&id)?;
+
+    if body.summary.trim().is_empty() {
+        return Err(AppError::validation(i18n::Message::new(
+            "conversations.summary_required",
+        )));
+    }
+    if body.message_count < 1 {
+        return Err(AppError::validation(i18n::Message::new(
+            "conversations.message_count_too_small",
+        )));
+    }
+
+    let row = nize_core::conversations::set_summary(
+        &state.pool,
+        &scope,
+        &conv_id,
+        &body.summary,
+        body.message_count,
+    )
+    .await?;
+
+    Ok(Json(RollingSummary::from(&row)))
+}
+
+/// Request body for a conversation's tool selection.
+#[derive(Debug, Deserialize)]
+pub struct ToolSelectionBody {
+    pub selection: Option<ToolSelection>,
+}
+
+/// `GET /conversations/{id}/tools` — get the pinned tool selection.
+pub async fn get_tool_selection_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path(id): Path<String>,
+) -> AppResult<Json<ConversationToolSelection>> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    let conv_id = parse_uuid(&id)?;
+
+    let row = nize_core::conversations::get_conversation(&state.pool, &scope, &conv_id).await?;
+
+    Ok(Json(ConversationToolSelection {
+        selection: row.tool_selection(),
+    }))
+}
+
+/// `PUT /conversations/{id}/tools` — pin the servers and domains the chat
+/// may use in the conversation, or clear the pin with `selection: null`.
+pub async fn set_tool_selection_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path(id): Path<String>,
+    Json(body): Json<ToolSelectionBody>,
+) -> AppResult<Json<ConversationToolSelection>> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    let conv_id = parse_uuid(&id)?;
+
+    let selection = body.selection.map(ToolSelection::normalized);
+    if let Some(selection) = &selection {
+        for server_id in &selection.server_ids {
+            let server_id = server_id.to_string();
+            if !nize_core::mcp::queries::user_has_server_access(
+                &state.pool,
+                &user.0.sub,
+                &server_id,
+            )
+            .await?
+            {
+                return Err(AppError::validation(
+                    i18n::Message::new("conversations.server_unavailable").arg("id", server_id),
+                ));
+            }
+        }
+    }
+
+    require_manage(&state, &scope, &conv_id).await?;
+    let row = nize_core::conversations::set_tool_selection(
+        &state.pool,
+        &scope,
+        &conv_id,
+        selection.as_ref(),
+    )
+    .await?;
+
+    Ok(Json(ConversationToolSelection {
+        selection: row.tool_selection(),
+    }))
+}
+
+/// Fail with `QuotaExceeded` when replacing the conversation's messages
+/// with `messages` would pass the storage quota of its creator `owner_id`,
+/// whom messages count against.
+pub(crate) async fn ensure_message_storage(
+    state: &AppState,
+    owner_id: &Uuid,
+    conv_id: &Uuid,
+    messages: &[serde_json::Value],
+) -> AppResult<()> {
+    let limits = quotas::get_limits(&state.pool, &state.config_cache).await?;
+    if limits.storage_bytes.is_some() {
+        let added = quotas::message_bytes(&state.pool, messages).await?
+            - quotas::conversation_message_bytes(&state.pool, conv_id).await?;
+        let usage = quotas::get_usage(&state.pool, owner_id).await?;
+        quotas::check(&limits, &usage, Quota::StorageBytes, added)
+            .map_err(AppError::QuotaExceeded)?;
+    }
+    Ok(())
+}
+
+/// Fail with `Forbidden` unless the caller may change the conversation —
+/// workspace members can read each other's conversations but only change
+/// their own (owners and admins can change all).
+/// The conversation if it is in scope, or shared with the user at `needed`
+/// or above (see [`nize_core::permissions`]). Scoped access allows
+/// everything up to the manage check of [`require_manage`].
+pub(crate) async fn require_access(
+    state: &AppState,
+    scope: &Scope,
+    conv_id: &Uuid,
+    needed: PermissionLevel,
+) -> AppResult<ConversationRow> {
+    match nize_core::conversations::get_conversation(&state.pool, scope, conv_id).await {
+        Err(sqlx::Error::RowNotFound) => {
+            permissions::require_level(
+                &state.pool,
+                &scope.user_id,
+                ResourceType::Conversation,
+                conv_id,
+                needed,
+            )
+            .await?;
+            Ok(nize_core::conversations::get_conversation_by_id(&state.pool, conv_id).await?)
+        }
+        row => Ok(row?),
+    }
+}
+
+pub(crate) async fn require_manage(
+    state: &AppState,
+    scope: &Scope,
+    conv_id: &Uuid,
+) -> AppResult<()> {
+    let row = nize_core::conversations::get_conversation(&state.pool, scope, conv_id).await?;
+    if !scope.can_manage(&row.user_id) {
+        return Err(AppError::forbidden(i18n::Message::new(
+            "conversations.change_forbidden",
+        )));
+    }
+    Ok(())
+}
+
 /// Parse a user ID string into a UUID.
 fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
-    Uuid::parse_str(sub).map_err(|_| AppError::Unauthorized("Invalid user ID".into()))
+    Uuid::parse_str(sub)
+        .map_err(|_| AppError::unauthorized(i18n::Message::new("auth.invalid_user_id")))
 }
 
 /// Parse a path parameter string into a UUID.
 fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
-    Uuid::parse_str(s).map_err(|_| AppError::Validation("Invalid UUID".into()))
+    Uuid::parse_str(s).map_err(|_| AppError::validation(i18n::Message::new("request.invalid_uuid")))
 }
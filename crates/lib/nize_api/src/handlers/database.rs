@@ -0,0 +1,20 @@
+//! Database transfer handler.
+
+use axum::Json;
+use axum::extract::State;
+
+use nize_core::db_transfer::{self, TransferReport, TransferRequest};
+
+use crate::AppState;
+use crate::error::AppResult;
+
+/// `POST /admin/database/transfer` — copy this database into an empty
+/// external PostgreSQL database and verify the copy.
+pub async fn transfer_handler(
+    State(state): State<AppState>,
+    Json(request): Json<TransferRequest>,
+) -> AppResult<Json<TransferReport>> {
+    Ok(Json(
+        db_transfer::transfer(&state.pool, &request.target_url).await?,
+    ))
+}
@@ -0,0 +1,46 @@
+//! Admin diagnostics endpoints.
+
+use axum::Json;
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::AppState;
+use crate::diagnostics::SlowQueryStats;
+use crate::error::AppResult;
+
+/// Query params for the slow query list.
+#[derive(Debug, Deserialize)]
+pub struct SlowQueryParams {
+    /// Statements to return; defaults to 20, at most
+    /// [`MAX_STATEMENTS`](crate::diagnostics::MAX_STATEMENTS).
+    pub limit: Option<usize>,
+}
+
+/// Body of `GET /admin/diagnostics/slow-queries`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SlowQueriesResponse {
+    pub statement_timeout_ms: Option<u64>,
+    pub slow_query_threshold_ms: u64,
+    pub queries: Vec<SlowQueryStats>,
+}
+
+/// `GET /admin/diagnostics/slow-queries` — statements that exceeded the
+/// slow query threshold since the server started, most total time first,
+/// with the pool's statement timeout and threshold.
+pub async fn slow_queries_handler(
+    State(state): State<AppState>,
+    Query(params): Query<SlowQueryParams>,
+) -> AppResult<Json<SlowQueriesResponse>> {
+    let limit = params
+        .limit
+        .unwrap_or(20)
+        .min(crate::diagnostics::MAX_STATEMENTS);
+    let limits = state.slow_queries.limits();
+    Ok(Json(SlowQueriesResponse {
+        statement_timeout_ms: limits.statement_timeout.map(|t| t.as_millis() as u64),
+        slow_query_threshold_ms: limits.slow_query_threshold.as_millis() as u64,
+        queries: state.slow_queries.top(limit),
+    }))
+}
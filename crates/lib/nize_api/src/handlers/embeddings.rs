@@ -2,14 +2,27 @@
 //
 //! Admin embedding management endpoints.
 
+use std::collections::HashMap;
+
 use axum::Json;
 use axum::extract::State;
 use base64::{Engine, engine::general_purpose};
 use reqwest::Client;
 use serde::Deserialize;
+use uuid::Uuid;
+
+use nize_core::embedding::EmbeddingError;
+use nize_core::embedding::ann;
+use nize_core::embedding::indexer;
+use nize_core::embedding::models::ModelCoverage;
 
 use crate::AppState;
 use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+
+/// Route of [`search_handler`]. The admin embedding endpoints are outside
+/// the API spec, so they have no generated constants.
+pub const SEARCH_ROUTE: &str = "/admin/embeddings/search";
 
 fn encode_page_token(offset: i64) -> String {
     general_purpose::STANDARD_NO_PAD.encode(offset.to_be_bytes())
@@ -26,18 +39,27 @@ fn decode_page_token(token: &str) -> Option<i64> {
     }
 }
 
-/// `GET /admin/embeddings/models` — list registered embedding models.
+/// `GET /admin/embeddings/models` — list registered embedding models with
+/// how many tools and stored chunks each has embedded.
 pub async fn list_models_handler(
     State(state): State<AppState>,
 ) -> AppResult<Json<serde_json::Value>> {
-    let rows = sqlx::query_as::<_, (String, String, i32, String, String)>(
-        "SELECT provider, name, dimensions, table_name, tool_table_name \
+    let rows = sqlx::query_as::<_, (Uuid, String, String, i32, String)>(
+        "SELECT id, provider, name, dimensions, table_name \
          FROM embedding_models ORDER BY provider, name",
     )
     .fetch_all(&state.pool)
     .await
     .map_err(|e| AppError::Internal(format!("Failed to list embedding models: {e}")))?;
 
+    let coverage: HashMap<Uuid, ModelCoverage> =
+        nize_core::embedding::models::get_model_coverage(&state.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to count embeddings: {e}")))?
+            .into_iter()
+            .map(|c| (c.model_id, c))
+            .collect();
+
     // Resolve active model name from config
     let active_model_config = nize_core::embedding::config::EmbeddingConfig::resolve(
         &state.pool,
@@ -56,19 +78,23 @@ pub async fn list_models_handler(
 
     let models: Vec<serde_json::Value> = rows
         .into_iter()
-        .map(
-            |(provider, name, dimensions, table_name, tool_table_name)| {
-                let is_active = provider == active_provider && name == active_model;
-                serde_json::json!({
-                    "provider": provider,
-                    "name": name,
-                    "dimensions": dimensions,
-                    "tableName": table_name,
-                    "toolTableName": tool_table_name,
-                    "isActive": is_active,
-                })
-            },
-        )
+        .map(|(id, provider, name, dimensions, table_name)| {
+            let is_active = provider == active_provider && name == active_model;
+            let c = coverage.get(&id);
+            serde_json::json!({
+                "provider": provider,
+                "name": name,
+                "dimensions": dimensions,
+                "tableName": table_name,
+                "isActive": is_active,
+                "coverage": {
+                    "toolEmbeddings": c.map_or(0, |c| c.tool_embeddings),
+                    "tools": c.map_or(0, |c| c.tools),
+                    "chunkEmbeddings": c.map_or(0, |c| c.chunk_embeddings),
+                    "chunks": c.map_or(0, |c| c.chunks),
+                },
+            })
+        })
         .collect();
 
     Ok(Json(serde_json::json!({ "models": models })))
@@ -90,7 +116,9 @@ pub async fn search_handler(
     Json(body): Json<SearchRequest>,
 ) -> AppResult<Json<serde_json::Value>> {
     if body.query.trim().is_empty() {
-        return Err(AppError::Validation("query is required".into()));
+        return Err(AppError::validation(Message::new(
+            "embeddings.query_required",
+        )));
     }
 
     let page_size = body.page_size.unwrap_or(20).clamp(1, 200);
@@ -144,22 +172,30 @@ pub async fn search_handler(
                   t.description AS tool_description,
                   s.name AS server_name,
                   te.domain,
-                  1 - (te.embedding <=> $1::vector) AS similarity
-           FROM "{}" te
+                  1 - ({distance}) AS similarity
+           FROM tool_embeddings te
            JOIN mcp_server_tools t ON t.id = te.tool_id
            JOIN mcp_servers s ON s.id = te.server_id
-           ORDER BY te.embedding <=> $1::vector
+           WHERE {model_filter}
+           ORDER BY {distance}
            LIMIT $2 OFFSET $3"#,
-        model_config.tool_table_name
+        distance = model_config.distance_sql("te.embedding", "$1"),
+        model_filter = model_config.filter_sql("te"),
     );
 
+    let search_params =
+        nize_core::embedding::ann::SearchParams::resolve(&state.pool, &state.config_cache).await;
+    let search_error = |e: sqlx::Error| AppError::Internal(format!("Search query error: {e}"));
+    let mut tx = state.pool.begin().await.map_err(search_error)?;
+    search_params.apply(&mut tx).await.map_err(search_error)?;
     let rows = sqlx::query_as::<_, (String, String, String, String, f64)>(&query)
         .bind(&embedding_sql)
         .bind(page_size + 1)
         .bind(offset)
-        .fetch_all(&state.pool)
+        .fetch_all(&mut *tx)
         .await
-        .map_err(|e| AppError::Internal(format!("Search query error: {e}")))?;
+        .map_err(search_error)?;
+    tx.commit().await.map_err(search_error)?;
 
     let has_more = (rows.len() as i64) > page_size;
     let search_results: Vec<serde_json::Value> = rows
@@ -193,7 +229,8 @@ pub async fn search_handler(
     })))
 }
 
-/// `POST /admin/embeddings/reindex` — re-index all server tools.
+/// `POST /admin/embeddings/reindex` — embed new and changed tools of every
+/// server.
 pub async fn reindex_handler(State(state): State<AppState>) -> AppResult<Json<serde_json::Value>> {
     let servers = nize_core::mcp::queries::list_all_servers(&state.pool)
         .await
@@ -231,3 +268,71 @@ pub async fn reindex_handler(State(state): State<AppState>) -> AppResult<Json<se
         "errors": errors,
     })))
 }
+
+/// `GET /admin/embeddings/stale` — servers whose tools changed or were
+/// added since the active model last embedded them.
+pub async fn stale_handler(State(state): State<AppState>) -> AppResult<Json<serde_json::Value>> {
+    let config = nize_core::embedding::config::EmbeddingConfig::resolve(
+        &state.pool,
+        &state.config_cache,
+        &state.config.mcp_encryption_key,
+    )
+    .await
+    .map_err(|e| AppError::Internal(format!("Embedding config error: {e}")))?;
+    let model_config = nize_core::embedding::models::get_active_model(&state.pool, &config)
+        .await
+        .map_err(|e| AppError::Internal(format!("Embedding model error: {e}")))?;
+
+    let servers = indexer::stale_tool_servers(&state.pool, &model_config.id)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to check tool embeddings: {e}")))?;
+
+    Ok(Json(serde_json::json!({
+        "servers": servers,
+        "model": model_config.model,
+        "provider": model_config.provider,
+    })))
+}
+
+/// `GET /admin/embeddings/indexes` — ANN index status per model and table,
+/// with the configured build and search parameters.
+pub async fn list_indexes_handler(
+    State(state): State<AppState>,
+) -> AppResult<Json<serde_json::Value>> {
+    let indexes = ann::status(&state.pool)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to read index status: {e}")))?;
+    let index_params = ann::IndexParams::resolve(&state.pool, &state.config_cache).await;
+    let search_params = ann::SearchParams::resolve(&state.pool, &state.config_cache).await;
+
+    Ok(Json(serde_json::json!({
+        "indexes": indexes,
+        "indexParams": index_params,
+        "searchParams": search_params,
+    })))
+}
+
+/// `POST /admin/embeddings/indexes/rebuild` — rebuild ANN indexes with the
+/// configured parameters, optionally only for one model and/or table.
+pub async fn rebuild_indexes_handler(
+    State(state): State<AppState>,
+    Json(body): Json<ann::RebuildRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let params = ann::IndexParams::resolve(&state.pool, &state.config_cache).await;
+    let rebuilt = ann::rebuild(&state.pool, &params, &body)
+        .await
+        .map_err(|e| match e {
+            EmbeddingError::UnknownTable(table) => {
+                AppError::validation(Message::new("embeddings.unknown_table").arg("table", table))
+            }
+            EmbeddingError::ModelNotFound(id) => {
+                AppError::not_found(Message::new("embeddings.model_not_found").arg("id", id))
+            }
+            e => AppError::Internal(format!("Index rebuild failed: {e}")),
+        })?;
+
+    Ok(Json(serde_json::json!({
+        "rebuilt": rebuilt,
+        "params": params,
+    })))
+}
@@ -0,0 +1,247 @@
+//! Admin eval handlers: start suite runs and read their scores.
+//!
+//! Runs execute in the background (see
+//! [`crate::services::eval_runner`]); clients poll the run or wait for the
+//! `eval.completed` event.
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use nize_core::eval::{self, EvalResultRow, EvalRunRow, Suite};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::services::eval_runner;
+
+/// Default and maximum number of runs listed.
+const DEFAULT_RUN_LIMIT: i64 = 50;
+const MAX_RUN_LIMIT: i64 = 500;
+
+/// A built-in suite, without its cases.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EvalSuiteSummary {
+    pub name: String,
+    pub description: String,
+    pub model: Option<String>,
+    pub case_count: usize,
+}
+
+/// Body of `GET /admin/evals/suites`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct EvalSuiteListResponse {
+    pub items: Vec<EvalSuiteSummary>,
+}
+
+/// An eval run and its overall score.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EvalRun {
+    pub id: Uuid,
+    pub suite: String,
+    pub model: Option<String>,
+    /// `running`, `completed` or `failed`.
+    pub status: String,
+    pub case_count: i32,
+    pub passed_count: i32,
+    pub score: Option<f64>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub completed_at: Option<String>,
+}
+
+impl From<&EvalRunRow> for EvalRun {
+    fn from(row: &EvalRunRow) -> Self {
+        Self {
+            id: row.id,
+            suite: row.suite.clone(),
+            model: row.model.clone(),
+            status: row.status.clone(),
+            case_count: row.case_count,
+            passed_count: row.passed_count,
+            score: row.score,
+            error: row.error.clone(),
+            created_at: row.created_at.to_rfc3339(),
+            completed_at: row.completed_at.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+/// Body of `GET /admin/evals/runs`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct EvalRunListResponse {
+    pub items: Vec<EvalRun>,
+}
+
+/// How one case of a run scored.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EvalCaseResult {
+    pub case_name: String,
+    pub prompt: String,
+    pub response: Option<String>,
+    pub tool_calls: Vec<String>,
+    pub assertions: Vec<Map<String, Value>>,
+    pub score: f64,
+    pub passed: bool,
+    pub error: Option<String>,
+    pub duration_ms: i32,
+}
+
+impl From<&EvalResultRow> for EvalCaseResult {
+    fn from(row: &EvalResultRow) -> Self {
+        // Both columns are written from the scored case, so they parse back.
+        Self {
+            case_name: row.case_name.clone(),
+            prompt: row.prompt.clone(),
+            response: row.response.clone(),
+            tool_calls: serde_json::from_value(row.tool_calls.clone()).unwrap_or_default(),
+            assertions: serde_json::from_value(row.assertions.clone()).unwrap_or_default(),
+            score: row.score,
+            passed: row.passed,
+            error: row.error.clone(),
+            duration_ms: row.duration_ms,
+        }
+    }
+}
+
+/// Body of `GET /admin/evals/runs/{id}`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EvalRunDetail {
+    #[serde(flatten)]
+    pub run: EvalRun,
+    pub results: Vec<EvalCaseResult>,
+    /// The previous completed run of the same suite and model.
+    pub previous_run: Option<EvalRun>,
+    /// Cases that passed in the previous run and fail in this one.
+    pub regressions: Vec<String>,
+}
+
+/// `GET /admin/evals/suites` — built-in suites.
+pub async fn list_suites_handler() -> AppResult<Json<EvalSuiteListResponse>> {
+    let mut items = Vec::new();
+    for (name, _) in eval::BUILTIN_SUITES {
+        let suite = eval::load_suite(name)?;
+        items.push(EvalSuiteSummary {
+            case_count: suite.cases.len(),
+            name: suite.name,
+            description: suite.description,
+            model: suite.model,
+        });
+    }
+    Ok(Json(EvalSuiteListResponse { items }))
+}
+
+/// Request body for starting a run. Exactly one of `suite` (a built-in
+/// name) or `definition` (an inline suite) is required.
+#[derive(Debug, Deserialize)]
+pub struct CreateRunBody {
+    pub suite: Option<String>,
+    pub definition: Option<Value>,
+    pub model: Option<String>,
+}
+
+/// `POST /admin/evals/runs` — run a suite through the chat pipeline.
+pub async fn create_run_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Json(body): Json<CreateRunBody>,
+) -> AppResult<(StatusCode, Json<EvalRun>)> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let chat_url = state
+        .config
+        .chat_url
+        .clone()
+        .ok_or_else(|| AppError::SidecarUnavailable("Chat app is not configured".into()))?;
+
+    let suite: Suite = match (body.suite, body.definition) {
+        (Some(name), None) => eval::builtin_suite(&name).ok_or_else(|| {
+            AppError::not_found(Message::new("evals.suite_not_found").arg("name", name))
+        })??,
+        (None, Some(definition)) => eval::parse_json(&definition.to_string())?,
+        _ => {
+            return Err(AppError::validation(Message::new(
+                "evals.suite_or_definition_required",
+            )));
+        }
+    };
+    let model = body
+        .model
+        .filter(|m| !m.trim().is_empty())
+        .or_else(|| suite.model.clone());
+    if model.as_ref().is_some_and(|m| !m.contains(':')) {
+        return Err(AppError::validation(Message::new(
+            "regeneration.invalid_model_spec",
+        )));
+    }
+
+    let run = eval::create_run(&state.pool, &user_id, &suite, model.as_deref()).await?;
+    eval_runner::spawn_eval_run(state.clone(), chat_url, user.0, run.clone(), suite);
+
+    Ok((StatusCode::ACCEPTED, Json(EvalRun::from(&run))))
+}
+
+/// Query params for listing runs.
+#[derive(Debug, Deserialize)]
+pub struct ListRunsParams {
+    pub suite: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// `GET /admin/evals/runs` — recent runs, newest first.
+pub async fn list_runs_handler(
+    State(state): State<AppState>,
+    Query(params): Query<ListRunsParams>,
+) -> AppResult<Json<EvalRunListResponse>> {
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_RUN_LIMIT)
+        .clamp(1, MAX_RUN_LIMIT);
+    let runs = eval::list_runs(&state.pool, params.suite.as_deref(), limit).await?;
+
+    Ok(Json(EvalRunListResponse {
+        items: runs.iter().map(EvalRun::from).collect(),
+    }))
+}
+
+/// `GET /admin/evals/runs/{id}` — a run with its case results, compared
+/// against the previous completed run of the same suite and model.
+pub async fn get_run_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<EvalRunDetail>> {
+    let run_id = parse_uuid(&id)?;
+
+    let run = eval::get_run(&state.pool, &run_id).await?;
+    let results = eval::list_results(&state.pool, &run_id).await?;
+    let previous = eval::previous_run(&state.pool, &run).await?;
+    let regressions = match &previous {
+        Some(p) => eval::regressions(&eval::list_results(&state.pool, &p.id).await?, &results),
+        None => Vec::new(),
+    };
+
+    Ok(Json(EvalRunDetail {
+        run: EvalRun::from(&run),
+        results: results.iter().map(EvalCaseResult::from).collect(),
+        previous_run: previous.as_ref().map(EvalRun::from),
+        regressions,
+    }))
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
@@ -0,0 +1,113 @@
+//! Server event stream handlers: SSE, or a buffered stream for clients
+//! whose network breaks SSE.
+
+use std::convert::Infallible;
+
+use axum::Json;
+use axum::extract::State;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::Stream;
+use schemars::JsonSchema;
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use crate::i18n::Message;
+use crate::streams::IDLE_TIMEOUT;
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::middleware::auth::AuthenticatedUser;
+
+/// `GET /events` — stream the authenticated user's server events as SSE.
+///
+/// Each event is sent with its kind as the SSE event name and the JSON
+/// `{kind, title, body, payload}` as data.
+pub async fn events_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let rx = state.events.subscribe();
+
+    let stream = futures_util::stream::unfold(rx, move |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) if event.is_for(&user_id) => {
+                    match Event::default().event(&event.kind).json_data(&event) {
+                        Ok(sse) => return Some((Ok(sse), rx)),
+                        Err(e) => tracing::warn!("Failed to encode server event: {e}"),
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "event subscriber lagged, events dropped");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Body of `POST /events/streams`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EventStreamCreated {
+    pub stream_id: Uuid,
+}
+
+/// `POST /events/streams` — open a buffered stream of the authenticated
+/// user's server events, for polling at `GET /chat/streams/{id}/poll`. Each
+/// chunk carries the event kind as `event` and the same JSON as the SSE
+/// stream as `data`. The stream closes once it is no longer polled.
+pub async fn create_event_stream_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+) -> AppResult<Json<EventStreamCreated>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let stream_id = state.streams.create(user_id)?;
+    let mut rx = state.events.subscribe();
+
+    tokio::spawn(async move {
+        let streams = state.streams;
+        loop {
+            let event = tokio::select! {
+                received = rx.recv() => received,
+                // Wake up now and then to notice an abandoned stream.
+                _ = tokio::time::sleep(IDLE_TIMEOUT) => {
+                    if !streams.is_open(&stream_id) {
+                        return;
+                    }
+                    continue;
+                }
+            };
+            match event {
+                Ok(event) if event.is_for(&user_id) => match serde_json::to_string(&event) {
+                    Ok(data) => {
+                        if !streams.push(&stream_id, Some(&event.kind), data) {
+                            return;
+                        }
+                    }
+                    Err(e) => tracing::warn!("Failed to encode server event: {e}"),
+                },
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "event subscriber lagged, events dropped");
+                }
+                Err(RecvError::Closed) => {
+                    streams.finish(&stream_id, None);
+                    return;
+                }
+            }
+        }
+    });
+
+    Ok(Json(EventStreamCreated { stream_id }))
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
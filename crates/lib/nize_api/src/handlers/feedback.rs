@@ -0,0 +1,212 @@
+//! Message feedback handlers: thumbs up/down on assistant replies and the
+//! admin JSONL export. Conversations shared with `comment` access or more
+//! can be rated too.
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderName, StatusCode, header};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::feedback::{self, ExportFilter, FeedbackExportRow, FeedbackInput, FeedbackRow};
+use nize_core::permissions::PermissionLevel;
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::handlers::conversations::require_access;
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::middleware::workspace::ActiveWorkspace;
+
+/// The caller's rating of one message.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageFeedback {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub message_id: String,
+    pub rating: String,
+    pub category: Option<String>,
+    pub comment: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<&FeedbackRow> for MessageFeedback {
+    fn from(row: &FeedbackRow) -> Self {
+        Self {
+            id: row.id,
+            conversation_id: row.conversation_id,
+            message_id: row.message_id.clone(),
+            rating: row.rating.clone(),
+            category: row.category.clone(),
+            comment: row.comment.clone(),
+            created_at: row.created_at.to_rfc3339(),
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `GET /conversations/{id}/feedback`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ConversationFeedbackResponse {
+    pub items: Vec<MessageFeedback>,
+}
+
+/// One line of the feedback export.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FeedbackExportLine {
+    #[serde(flatten)]
+    feedback: MessageFeedback,
+    user_id: Uuid,
+    message: serde_json::Value,
+    prompt: Option<serde_json::Value>,
+}
+
+impl From<FeedbackExportRow> for FeedbackExportLine {
+    fn from(row: FeedbackExportRow) -> Self {
+        Self {
+            feedback: MessageFeedback::from(&row.feedback),
+            user_id: row.feedback.user_id,
+            message: row.message,
+            prompt: row.prompt,
+        }
+    }
+}
+
+/// `GET /conversations/{id}/feedback` — the caller's feedback on messages
+/// of a conversation.
+pub async fn list_feedback_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path(id): Path<String>,
+) -> AppResult<Json<ConversationFeedbackResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let conv_id = parse_uuid(&id)?;
+
+    let scope = workspace.scope(user_id);
+    require_access(&state, &scope, &conv_id, PermissionLevel::View).await?;
+    let rows = feedback::list_conversation_feedback(&state.pool, &user_id, &conv_id).await?;
+
+    Ok(Json(ConversationFeedbackResponse {
+        items: rows.iter().map(MessageFeedback::from).collect(),
+    }))
+}
+
+/// Request body for rating a message.
+#[derive(Debug, Deserialize)]
+pub struct SetFeedbackBody {
+    pub rating: String,
+    pub category: Option<String>,
+    pub comment: Option<String>,
+}
+
+/// `PUT /conversations/{id}/messages/{messageId}/feedback` — rate an
+/// assistant message.
+pub async fn set_feedback_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path((id, message_id)): Path<(String, String)>,
+    Json(body): Json<SetFeedbackBody>,
+) -> AppResult<Json<MessageFeedback>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let conv_id = parse_uuid(&id)?;
+    let input = FeedbackInput {
+        rating: body.rating.parse()?,
+        category: body.category,
+        comment: body.comment,
+    };
+
+    let scope = workspace.scope(user_id);
+    require_access(&state, &scope, &conv_id, PermissionLevel::Comment).await?;
+    let row = feedback::set_feedback(&state.pool, &user_id, &conv_id, &message_id, input).await?;
+
+    Ok(Json(MessageFeedback::from(&row)))
+}
+
+/// `DELETE /conversations/{id}/messages/{messageId}/feedback` — remove the
+/// caller's feedback on a message.
+pub async fn delete_feedback_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path((id, message_id)): Path<(String, String)>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let conv_id = parse_uuid(&id)?;
+
+    let scope = workspace.scope(user_id);
+    require_access(&state, &scope, &conv_id, PermissionLevel::Comment).await?;
+    if feedback::delete_feedback(&state.pool, &user_id, &conv_id, &message_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(Message::new("feedback.not_found")))
+    }
+}
+
+/// Query params for the feedback export.
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub rating: Option<String>,
+}
+
+/// `GET /admin/feedback/export` — all feedback as JSONL, one record per
+/// line with the rated message and its prompt.
+pub async fn export_feedback_handler(
+    State(state): State<AppState>,
+    Query(params): Query<ExportParams>,
+) -> AppResult<([(HeaderName, String); 2], String)> {
+    let filter = ExportFilter {
+        since: params.since.as_deref().map(parse_timestamp).transpose()?,
+        until: params.until.as_deref().map(parse_timestamp).transpose()?,
+        rating: params.rating.as_deref().map(str::parse).transpose()?,
+    };
+
+    let rows = feedback::export_feedback(&state.pool, &filter).await?;
+    let mut body = String::new();
+    for row in rows {
+        let line = FeedbackExportLine::from(row);
+        body.push_str(
+            &serde_json::to_string(&line).map_err(|e| AppError::Internal(e.to_string()))?,
+        );
+        body.push('\n');
+    }
+
+    let disposition = format!(
+        "attachment; filename=\"nize-feedback-{}.jsonl\"",
+        Utc::now().format("%Y%m%d")
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    ))
+}
+
+/// Parse an RFC 3339 query parameter.
+fn parse_timestamp(s: &str) -> Result<DateTime<Utc>, AppError> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| {
+            AppError::validation(Message::new("request.invalid_timestamp").arg("value", s))
+        })
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
@@ -0,0 +1,63 @@
+//! Readiness endpoint for process supervisors and the desktop app.
+
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use serde::Serialize;
+use tracing::warn;
+
+use nize_core::migrate::MigrationStatus;
+
+use crate::AppState;
+use crate::metrics::McpPoolStats;
+
+/// Body of `GET /readyz`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Readiness {
+    /// The database answers and has every embedded migration applied.
+    pub ready: bool,
+    pub database: DatabaseReadiness,
+    /// `None` when the database could not be asked.
+    pub migrations: Option<MigrationStatus>,
+    /// `None` when no MCP server runs in this process.
+    pub mcp_pool: Option<McpPoolStats>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseReadiness {
+    pub reachable: bool,
+    pub pool_connections: u32,
+    pub idle_connections: usize,
+}
+
+/// `GET /readyz` — database reachability, migration state and pool sizes.
+/// Answers 503 until the server can serve requests. Unauthenticated, so it
+/// reports counts only.
+pub async fn readyz_handler(State(state): State<AppState>) -> (StatusCode, Json<Readiness>) {
+    let migrations = match nize_core::migrate::status(&state.pool).await {
+        Ok(status) => Some(status),
+        Err(e) => {
+            warn!("Readiness check could not query the database: {e}");
+            None
+        }
+    };
+    let ready = migrations.is_some_and(|m| m.pending == 0);
+    let body = Readiness {
+        ready,
+        database: DatabaseReadiness {
+            reachable: migrations.is_some(),
+            pool_connections: state.pool.size(),
+            idle_connections: state.pool.num_idle(),
+        },
+        migrations,
+        mcp_pool: state.metrics.mcp_pool_stats(),
+    };
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(body))
+}
@@ -8,7 +8,8 @@ use crate::AppState;
 use crate::error::AppResult;
 use crate::generated::models::HelloWorldResponse;
 
-/// `GET /hello` — verifies core lib, DB connection, and Bun sidecar.
+/// `GET /hello` — verifies core lib, DB connection, and Bun sidecar, and
+/// reports the storage level.
 pub async fn hello_world(State(state): State<AppState>) -> AppResult<Json<HelloWorldResponse>> {
     let greeting = nize_core::hello::hello_world();
 
@@ -24,10 +25,19 @@ pub async fn hello_world(State(state): State<AppState>) -> AppResult<Json<HelloW
         }
     };
 
+    let storage_level = match crate::services::storage::measure(&state).await {
+        Ok(report) => Some(report.level.as_str().to_string()),
+        Err(e) => {
+            warn!("Storage measurement failed: {e}");
+            None
+        }
+    };
+
     Ok(Json(HelloWorldResponse {
         greeting,
         db_connected,
         bun_available,
         bun_version,
+        storage_level,
     }))
 }
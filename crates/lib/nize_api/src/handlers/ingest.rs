@@ -1,75 +1,544 @@
 // @awa-component: PLAN-017-IngestHandler
 //
-//! Ingestion request handlers — demo stubs.
+//! Ingestion request handlers.
+//!
+//! Uploads are streamed straight into blob storage (see
+//! [`nize_core::blobs`]) — the request body is the file itself, never
+//! buffered in memory — and downloads stream back out the same way. Text
+//! is extracted and chunked right after upload (see [`nize_core::ingest`])
+//! and embedded in the background; audio is transcribed in the background
+//! too. Extracted text passes content moderation before it is stored, and
+//! blocked uploads are removed. `GET /ingest/search` retrieves passages from
+//! the caller's documents as chat context; `POST /ingest/retrieve` also
+//! covers documents shared with them.
+//!
+//! Every endpoint acts in the workspace named by the `X-Nize-Workspace`
+//! header, or on the user's personal documents without it.
 
 use axum::Json;
-use axum::extract::Path;
-use axum::http::StatusCode;
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use futures_util::StreamExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::error::AppResult;
+use nize_core::blobs::BlobError;
+use nize_core::documents::search::{AccessReason, DocumentSearchHit, RetrievedChunk};
+use nize_core::documents::{self, DocumentRow, search};
+use nize_core::ingest;
+use nize_core::permissions::PermissionLevel;
+use nize_core::quotas::{self, Quota, QuotaExceeded};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::middleware::workspace::ActiveWorkspace;
+use crate::services::documents as document_blobs;
+
+/// Largest file accepted by `POST /ingest`.
+pub const MAX_UPLOAD_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Longest stored filename.
+const MAX_FILENAME_CHARS: usize = 255;
+
+/// Query params for uploading a file.
+#[derive(Debug, Deserialize)]
+pub struct UploadParams {
+    pub filename: String,
+}
+
+/// Document metadata.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Document {
+    pub id: Uuid,
+    pub workspace_id: Option<Uuid>,
+    pub filename: String,
+    pub mime_type: String,
+    pub size: i64,
+    /// Generated title; not produced yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Generated summary; not produced yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// Generated labels; not produced yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<Vec<String>>,
+    /// Generated category; not produced yet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<&DocumentRow> for Document {
+    fn from(row: &DocumentRow) -> Self {
+        Self {
+            id: row.id,
+            workspace_id: row.workspace_id,
+            filename: row.filename.clone(),
+            mime_type: row.mime_type.clone(),
+            size: row.size_bytes,
+            title: None,
+            summary: None,
+            labels: None,
+            category: None,
+            created_at: row.created_at.to_rfc3339(),
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `POST /ingest`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IngestResponse {
+    pub document: Document,
+    /// Chunks extracted now; 0 for audio, which is transcribed later.
+    pub chunk_count: usize,
+}
+
+/// Body of `GET /ingest`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DocumentListResponse {
+    pub items: Vec<Document>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Citation of the email a search result comes from.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSearchEmail {
+    pub subject: String,
+    pub from: Option<String>,
+    pub sent_at: Option<String>,
+}
+
+/// A search hit as returned by both search endpoints.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSearchResult {
+    pub document_id: Uuid,
+    pub filename: String,
+    pub content: String,
+    pub page: Option<i32>,
+    pub start_ms: Option<i64>,
+    pub end_ms: Option<i64>,
+    pub similarity: f64,
+    pub rerank_score: Option<f64>,
+    pub email: Option<DocumentSearchEmail>,
+}
+
+impl From<&DocumentSearchHit> for DocumentSearchResult {
+    fn from(h: &DocumentSearchHit) -> Self {
+        Self {
+            document_id: h.document_id,
+            filename: h.filename.clone(),
+            content: h.content.clone(),
+            page: h.page,
+            start_ms: h.start_ms,
+            end_ms: h.end_ms,
+            similarity: h.similarity,
+            rerank_score: h.rerank_score,
+            email: h.email_subject.as_ref().map(|subject| DocumentSearchEmail {
+                subject: subject.clone(),
+                from: h.email_sender.clone(),
+                sent_at: h.email_sent_at.map(|t| t.to_rfc3339()),
+            }),
+        }
+    }
+}
+
+/// Body of `GET /ingest/search`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DocumentSearchResponse {
+    pub results: Vec<DocumentSearchResult>,
+    /// Results rendered as a markdown block for chat context.
+    pub context: String,
+}
+
+/// Why a retrieved chunk's document is readable by the caller.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RetrievalAccess {
+    pub reason: AccessReason,
+    pub level: PermissionLevel,
+    pub source_id: Option<Uuid>,
+    pub explanation: String,
+}
+
+impl From<&RetrievedChunk> for RetrievalAccess {
+    fn from(chunk: &RetrievedChunk) -> Self {
+        Self {
+            reason: chunk.access,
+            level: chunk.access_level,
+            source_id: chunk.access_source_id,
+            explanation: chunk.explanation(),
+        }
+    }
+}
+
+/// A chunk returned by retrieval.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RetrievalResult {
+    #[serde(flatten)]
+    pub hit: DocumentSearchResult,
+    /// Present only when `explain` was requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access: Option<RetrievalAccess>,
+}
+
+/// Body of `POST /ingest/retrieve`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RetrieveResponse {
+    pub results: Vec<RetrievalResult>,
+    /// Results rendered as a markdown block for chat context.
+    pub context: String,
+}
+
+/// `POST /ingest?filename=…` — store the request body as a document. The
+/// `Content-Type` header is recorded as the file's MIME type.
+pub async fn upload_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Query(params): Query<UploadParams>,
+    headers: HeaderMap,
+    body: Body,
+) -> AppResult<(StatusCode, Json<IngestResponse>)> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    let filename = clean_filename(&params.filename)?;
+    let mime_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+    let declared = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<i64>().ok());
+
+    // Count the document up front; bound the stream by the storage left.
+    let limits = quotas::get_limits(&state.pool, &state.config_cache).await?;
+    let usage = quotas::get_usage(&state.pool, &scope.user_id).await?;
+    quotas::check(&limits, &usage, Quota::Documents, 1).map_err(AppError::QuotaExceeded)?;
+    let storage_left = limits
+        .storage_bytes
+        .map(|limit| (limit - usage.storage_bytes).max(0) as u64);
+    if let Some(declared) = declared {
+        quotas::check(&limits, &usage, Quota::StorageBytes, declared)
+            .map_err(AppError::QuotaExceeded)?;
+    }
+    let max_bytes = storage_left.map_or(MAX_UPLOAD_BYTES, |left| left.min(MAX_UPLOAD_BYTES));
+
+    let store = document_blobs::upload_store(&state).await?;
+    let blob = match store.put(body.into_data_stream(), Some(max_bytes)).await {
+        Ok(blob) => blob,
+        Err(BlobError::TooLarge(_)) if max_bytes < MAX_UPLOAD_BYTES => {
+            return Err(AppError::QuotaExceeded(QuotaExceeded {
+                quota: Quota::StorageBytes,
+                limit: limits.storage_bytes.unwrap_or_default(),
+                used: usage.storage_bytes,
+                requested: declared.unwrap_or(max_bytes as i64 + 1),
+            }));
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let row = documents::create_document(
+        &state.pool,
+        &scope,
+        &filename,
+        &mime_type,
+        &blob,
+        store.backend(),
+    )
+    .await?;
+    // Audio is transcribed in the background; other files are extracted
+    // now and only embedding is deferred. Blocked content is not kept.
+    let audio = ingest::is_audio_mime(&row.mime_type);
+    let chunk_count = if audio {
+        0
+    } else {
+        match document_blobs::extract_chunks(&state, &store, &row).await {
+            Ok(count) => count,
+            Err(e) => {
+                document_blobs::discard(&state, &row).await;
+                return Err(e);
+            }
+        }
+    };
+    document_blobs::spawn_index(&state, store, row.clone(), audio);
 
-/// `POST /ingest` — upload and ingest file (demo).
-pub async fn upload_handler() -> AppResult<(StatusCode, Json<serde_json::Value>)> {
     Ok((
         StatusCode::CREATED,
-        Json(serde_json::json!({
-            "document": {
-                "id": "00000000-0000-0000-0000-000000000001",
-                "filename": "demo-file.txt",
-                "mimeType": "text/plain",
-                "size": 1024,
-                "title": "Demo File",
-                "summary": "A demo document for testing.",
-                "labels": ["demo", "test"],
-                "category": "general",
-                "createdAt": "2026-02-16T00:00:00Z",
-                "updatedAt": "2026-02-16T00:00:00Z"
-            },
-            "chunkCount": 3
-        })),
+        Json(IngestResponse {
+            document: Document::from(&row),
+            chunk_count,
+        }),
     ))
 }
 
-/// `GET /ingest` — list documents (demo).
-pub async fn list_documents_handler() -> AppResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "items": [
-            {
-                "id": "00000000-0000-0000-0000-000000000001",
-                "filename": "demo-file.txt",
-                "mimeType": "text/plain",
-                "size": 1024,
-                "title": "Demo File",
-                "summary": "A demo document for testing.",
-                "labels": ["demo", "test"],
-                "category": "general",
-                "createdAt": "2026-02-16T00:00:00Z",
-                "updatedAt": "2026-02-16T00:00:00Z"
-            }
+/// Query params for listing documents.
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// `GET /ingest` — list the documents in scope (the user's own, or the
+/// active workspace's).
+pub async fn list_documents_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Query(params): Query<ListParams>,
+) -> AppResult<Json<DocumentListResponse>> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    let limit = params.limit.unwrap_or(20).min(100);
+    let offset = params.offset.unwrap_or(0);
+
+    let (rows, total) = documents::list_documents(&state.pool, &scope, limit, offset).await?;
+
+    Ok(Json(DocumentListResponse {
+        items: rows.iter().map(Document::from).collect(),
+        total,
+        limit,
+        offset,
+    }))
+}
+
+/// Query params for searching documents.
+#[derive(Debug, Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+    pub limit: Option<i64>,
+}
+
+/// `GET /ingest/search?q=…` — semantic search over the chunks of the
+/// documents in scope, with a ready-to-use chat context block.
+pub async fn search_documents_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Query(params): Query<SearchParams>,
+) -> AppResult<Json<DocumentSearchResponse>> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    if params.q.trim().is_empty() {
+        return Err(AppError::validation(Message::new("request.q_required")));
+    }
+    let limit = params.limit.unwrap_or(search::DEFAULT_TOP_K).clamp(1, 50);
+
+    let hits = search::search_documents(
+        &state.pool,
+        &state.config_cache,
+        &state.config.mcp_encryption_key,
+        &scope,
+        &params.q,
+        limit,
+        0.0,
+    )
+    .await
+    .map_err(|e| AppError::Internal(format!("Document search error: {e}")))?;
+
+    Ok(Json(DocumentSearchResponse {
+        results: hits.iter().map(DocumentSearchResult::from).collect(),
+        context: search::format_context(&hits),
+    }))
+}
+
+/// Request body for `POST /ingest/retrieve`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetrieveBody {
+    pub query: String,
+    pub top_k: Option<i64>,
+    pub min_similarity: Option<f64>,
+    /// Share-link tokens the caller holds for documents shared with them.
+    #[serde(default)]
+    pub link_tokens: Vec<String>,
+    #[serde(default)]
+    pub explain: bool,
+}
+
+/// Most share-link tokens one retrieval accepts.
+const MAX_LINK_TOKENS: usize = 50;
+
+/// `POST /ingest/retrieve` — chat context from every document the caller
+/// may read, with access checked in the query. With `explain`, each result
+/// says why it was allowed.
+pub async fn retrieve_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Json(body): Json<RetrieveBody>,
+) -> AppResult<Json<RetrieveResponse>> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    if body.query.trim().is_empty() {
+        return Err(AppError::validation(Message::new(
+            "embeddings.query_required",
+        )));
+    }
+    if body.link_tokens.len() > MAX_LINK_TOKENS {
+        return Err(AppError::validation(
+            Message::new("ingest.too_many_link_tokens").arg("max", MAX_LINK_TOKENS),
+        ));
+    }
+    let min_similarity = body.min_similarity.unwrap_or(0.0);
+    if !(-1.0..=1.0).contains(&min_similarity) {
+        return Err(AppError::validation(Message::new(
+            "ingest.min_similarity_out_of_range",
+        )));
+    }
+    let top_k = body.top_k.unwrap_or(search::DEFAULT_TOP_K).clamp(1, 50);
+
+    let chunks = search::retrieve(
+        &state.pool,
+        &state.config_cache,
+        &state.config.mcp_encryption_key,
+        &scope,
+        &body.link_tokens,
+        &body.query,
+        top_k,
+        min_similarity,
+    )
+    .await
+    .map_err(|e| AppError::Internal(format!("Document retrieval error: {e}")))?;
+
+    let results = chunks
+        .iter()
+        .map(|chunk| RetrievalResult {
+            hit: DocumentSearchResult::from(&chunk.hit),
+            access: body.explain.then(|| RetrievalAccess::from(chunk)),
+        })
+        .collect();
+    let hits: Vec<DocumentSearchHit> = chunks.into_iter().map(|c| c.hit).collect();
+
+    Ok(Json(RetrieveResponse {
+        results,
+        context: search::format_context(&hits),
+    }))
+}
+
+/// `GET /ingest/{id}` — get document metadata.
+pub async fn get_document_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Document>> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    let document_id = parse_uuid(&id)?;
+
+    let row = documents::get_document(&state.pool, &scope, &document_id).await?;
+
+    Ok(Json(Document::from(&row)))
+}
+
+/// `GET /ingest/{id}/content` — stream the document's bytes as an
+/// attachment.
+pub async fn download_document_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path(id): Path<String>,
+) -> AppResult<Response> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    let document_id = parse_uuid(&id)?;
+
+    let row = documents::get_document(&state.pool, &scope, &document_id).await?;
+    let backend = nize_core::blobs::blob_backend(&state.pool, &row.sha256)
+        .await?
+        .ok_or_else(|| AppError::not_found(Message::new("documents.content_not_found")))?;
+    let stream = document_blobs::store_for(&state, backend)
+        .await?
+        .get(&row.sha256)
+        .await?;
+
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        row.filename.replace(['"', '\\'], "_")
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, row.mime_type.clone()),
+            (header::CONTENT_LENGTH, row.size_bytes.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
         ],
-        "total": 1,
-        "limit": 20,
-        "offset": 0
-    })))
-}
-
-/// `GET /ingest/{id}` — get document by ID (demo).
-pub async fn get_document_handler(Path(_id): Path<String>) -> AppResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "id": "00000000-0000-0000-0000-000000000001",
-        "filename": "demo-file.txt",
-        "mimeType": "text/plain",
-        "size": 1024,
-        "title": "Demo File",
-        "summary": "A demo document for testing.",
-        "labels": ["demo", "test"],
-        "category": "general",
-        "createdAt": "2026-02-16T00:00:00Z",
-        "updatedAt": "2026-02-16T00:00:00Z"
-    })))
-}
-
-/// `DELETE /ingest/{id}` — delete document (demo).
-pub async fn delete_document_handler(Path(_id): Path<String>) -> StatusCode {
-    StatusCode::NO_CONTENT
+        Body::from_stream(stream.map(|chunk| chunk.map_err(std::io::Error::other))),
+    )
+        .into_response())
+}
+
+/// `DELETE /ingest/{id}` — delete a document, and its blob once no other
+/// document shares it.
+pub async fn delete_document_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    let document_id = parse_uuid(&id)?;
+
+    let row = documents::delete_document(&state.pool, &scope, &document_id)
+        .await?
+        .ok_or_else(|| AppError::not_found(Message::new("documents.not_found")))?;
+    document_blobs::release(&state, &row.sha256).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Keep only the final path component of an uploaded filename.
+pub(crate) fn clean_filename(raw: &str) -> Result<String, AppError> {
+    let name = raw
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_FILENAME_CHARS)
+        .collect::<String>();
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(AppError::validation(Message::new(
+            "ingest.filename_required",
+        )));
+    }
+    Ok(name)
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filenames_lose_directories_and_control_characters() {
+        assert_eq!(clean_filename("../../etc/passwd").unwrap(), "passwd");
+        assert_eq!(
+            clean_filename("C:\\docs\\report.pdf").unwrap(),
+            "report.pdf"
+        );
+        assert_eq!(clean_filename(" a\nb.txt ").unwrap(), "ab.txt");
+        assert!(clean_filename("dir/").is_err());
+        assert!(clean_filename("..").is_err());
+    }
 }
@@ -0,0 +1,23 @@
+//! Referential integrity handlers.
+
+use axum::Json;
+use axum::extract::State;
+
+use nize_core::integrity::{self, IntegrityReport, RepairReport, RepairRequest};
+
+use crate::AppState;
+use crate::error::AppResult;
+
+/// `GET /admin/integrity` — run every consistency check.
+pub async fn audit_handler(State(state): State<AppState>) -> AppResult<Json<IntegrityReport>> {
+    Ok(Json(integrity::audit(&state.pool).await?))
+}
+
+/// `POST /admin/integrity/repair` — repair what the checks find; a dry
+/// run (the default) reports the changes and rolls them back.
+pub async fn repair_handler(
+    State(state): State<AppState>,
+    Json(request): Json<RepairRequest>,
+) -> AppResult<Json<RepairReport>> {
+    Ok(Json(integrity::repair(&state.pool, request).await?))
+}
@@ -2,16 +2,31 @@
 //
 //! MCP server configuration request handlers.
 
+use std::collections::HashMap;
+
 use axum::Json;
 use axum::extract::{Path, State};
 use axum::http::StatusCode;
+use schemars::JsonSchema;
+use serde::Serialize;
 
 use crate::AppState;
 use crate::error::{AppError, AppResult};
+use crate::events::{KIND_MCP_DISCOVERY_COMPLETED, KIND_MCP_DISCOVERY_FAILED, ServerEvent};
+use crate::i18n::Message;
 use crate::middleware::auth::AuthenticatedUser;
-use crate::services::mcp_config;
-use nize_core::mcp::execution::OAuthHeaders;
-use nize_core::models::mcp::{OAuthConfig, ServerConfig, TransportType};
+use crate::middleware::workspace::ActiveWorkspace;
+use crate::services::mcp_config::{self, ServerTestSummary};
+use nize_core::mcp::audit::AuditLog;
+use nize_core::mcp::catalog::{self, CatalogEntry, CatalogEntryView};
+use nize_core::mcp::execution::{ChildProcessStats, OAuthHeaders};
+use nize_core::mcp::routing;
+use nize_core::mcp::secrets::{self, SecretBinding};
+use nize_core::mcp::transforms::{self, OutputTransformRow, TransformRules};
+use nize_core::models::mcp::{
+    AdminServerView, AuthType, DeleteResult, OAuthConfig, ServerConfig, ServerToolView,
+    TestConnectionResult, TransportType, UserServerView,
+};
 
 // ---------------------------------------------------------------------------
 // Request / response DTOs
@@ -63,6 +78,13 @@ pub struct UpdatePreferenceRequest {
     pub enabled: bool,
 }
 
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetDomainRouteRequest {
+    /// Servers in order of preference.
+    pub server_ids: Vec<uuid::Uuid>,
+}
+
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TestConnectionRequest {
@@ -102,6 +124,10 @@ pub struct UpdateAdminServerRequest {
     pub domain: Option<String>,
     pub visibility: Option<String>,
     pub enabled: Option<bool>,
+    /// Pre-connect at startup and exempt from idle eviction.
+    pub keep_warm: Option<bool>,
+    /// Caller context keys attached to the server's tool calls.
+    pub forwarded_context: Option<Vec<String>>,
     /// Updated transport configuration.
     pub config: Option<ServerConfig>,
     pub api_key: Option<String>,
@@ -109,6 +135,139 @@ pub struct UpdateAdminServerRequest {
     pub client_secret: Option<String>,
 }
 
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallCatalogEntryRequest {
+    /// Server name; defaults to the entry's.
+    pub name: Option<String>,
+    /// Tool domain; defaults to the entry's.
+    pub domain: Option<String>,
+    /// Values of the entry's secrets, by secret name.
+    #[serde(default)]
+    pub secrets: HashMap<String, String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminInstallCatalogEntryRequest {
+    pub name: Option<String>,
+    pub domain: Option<String>,
+    #[serde(default = "default_visible")]
+    pub visibility: String,
+    #[serde(default)]
+    pub secrets: HashMap<String, String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetToolTransformRequest {
+    #[serde(flatten)]
+    pub rules: TransformRules,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Body of `GET /mcp/servers`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct UserServerListResponse {
+    pub servers: Vec<UserServerView>,
+}
+
+/// Body of `GET /mcp/servers/{serverId}/tools`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerToolsResponse {
+    pub server_id: String,
+    pub tools: Vec<ServerToolView>,
+}
+
+/// Body of `GET /mcp/catalog`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CatalogListResponse {
+    pub entries: Vec<CatalogEntryView>,
+}
+
+/// A domain's servers in order of preference.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainRoute {
+    pub domain: String,
+    pub server_ids: Vec<uuid::Uuid>,
+    pub updated_at: String,
+}
+
+impl From<&routing::DomainRouteRow> for DomainRoute {
+    fn from(row: &routing::DomainRouteRow) -> Self {
+        Self {
+            domain: row.domain.clone(),
+            server_ids: row.server_ids.clone(),
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `GET /mcp/routing`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct UserDomainRoutesResponse {
+    /// The user's own routes.
+    pub routes: Vec<DomainRoute>,
+    /// Admin defaults, used for domains the user has not routed.
+    pub defaults: Vec<DomainRoute>,
+}
+
+/// Body of `GET /mcp/admin/routing`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DomainRoutesResponse {
+    pub routes: Vec<DomainRoute>,
+}
+
+/// Body of `GET /mcp/servers/{serverId}/oauth/status`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthStatusResponse {
+    pub connected: bool,
+    pub expires_at: Option<String>,
+}
+
+/// Body of `POST /mcp/servers/{serverId}/oauth/initiate`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct InitiateOAuthResponse {
+    pub auth_url: String,
+}
+
+/// Body of `GET /mcp/admin/servers`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AdminServerListResponse {
+    pub servers: Vec<AdminServerView>,
+}
+
+/// Body of `POST /mcp/admin/test-all`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TestAllServersResponse {
+    pub servers: Vec<ServerTestSummary>,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub auth_required: usize,
+}
+
+/// Body of `GET /mcp/admin/transforms`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct OutputTransformListResponse {
+    pub transforms: Vec<OutputTransformRow>,
+}
+
+/// Body of `GET /mcp/admin/processes`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ChildProcessListResponse {
+    pub processes: Vec<ChildProcessStats>,
+}
+
 // ---------------------------------------------------------------------------
 // User MCP server endpoints
 // ---------------------------------------------------------------------------
@@ -117,19 +276,29 @@ pub struct UpdateAdminServerRequest {
 pub async fn list_servers_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Json<UserServerListResponse>> {
     let servers = mcp_config::get_servers_for_user(&state.pool, &user.0.sub).await?;
-    Ok(Json(serde_json::json!({ "servers": servers })))
+    Ok(Json(UserServerListResponse { servers }))
 }
 
 /// `POST /mcp/servers` — add user MCP server.
 pub async fn add_server_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(audit): axum::Extension<AuditLog>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
     Json(body): Json<CreateUserServerRequest>,
-) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
+) -> AppResult<(StatusCode, Json<UserServerView>)> {
+    nize_core::quotas::ensure_within(
+        &state.pool,
+        &state.config_cache,
+        &parse_user_id(&user.0.sub)?,
+        &[(nize_core::quotas::Quota::McpServers, 1)],
+    )
+    .await?;
     let server = mcp_config::create_user_server(
         &state.pool,
+        &audit,
         &user.0.sub,
         &body.name,
         body.description.as_deref().unwrap_or(""),
@@ -145,21 +314,30 @@ pub async fn add_server_handler(
         &state.config.mcp_encryption_key,
     )
     .await?;
-    Ok((
-        StatusCode::CREATED,
-        Json(serde_json::to_value(server).unwrap()),
-    ))
+    // Servers added while a workspace is active are shared with its members.
+    if let Some(membership) = workspace.0 {
+        nize_core::mcp::sharing::share_server(
+            &state.pool,
+            &server.id,
+            Some(&membership.workspace_id),
+            &state.config.mcp_encryption_key,
+        )
+        .await?;
+    }
+    Ok((StatusCode::CREATED, Json(server)))
 }
 
 /// `PATCH /mcp/servers/{serverId}` — update user MCP server.
 pub async fn update_server_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(audit): axum::Extension<AuditLog>,
     Path(server_id): Path<String>,
     Json(body): Json<UpdateUserServerRequest>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Json<UserServerView>> {
     let server = mcp_config::update_user_server(
         &state.pool,
+        &audit,
         &user.0.sub,
         &server_id,
         body.name.as_deref(),
@@ -173,16 +351,17 @@ pub async fn update_server_handler(
         &state.config.mcp_encryption_key,
     )
     .await?;
-    Ok(Json(serde_json::to_value(server).unwrap()))
+    Ok(Json(server))
 }
 
 /// `DELETE /mcp/servers/{serverId}` — remove user MCP server.
 pub async fn delete_server_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(audit): axum::Extension<AuditLog>,
     Path(server_id): Path<String>,
 ) -> AppResult<StatusCode> {
-    mcp_config::delete_user_server(&state.pool, &user.0.sub, &server_id).await?;
+    mcp_config::delete_user_server(&state.pool, &audit, &user.0.sub, &server_id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -200,10 +379,127 @@ pub async fn update_preference_handler(
 /// `GET /mcp/servers/{serverId}/tools` — list server tools.
 pub async fn list_server_tools_handler(
     State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
     Path(server_id): Path<String>,
-) -> AppResult<Json<serde_json::Value>> {
-    let tools = mcp_config::get_server_tools(&state.pool, &server_id).await?;
-    Ok(Json(serde_json::json!({ "tools": tools })))
+) -> AppResult<Json<ServerToolsResponse>> {
+    let tools = mcp_config::get_server_tools(&state.pool, &server_id, &user.0.sub).await?;
+    Ok(Json(ServerToolsResponse { server_id, tools }))
+}
+
+/// `PATCH /mcp/servers/{serverId}/tools/{toolId}` — switch a tool on or off
+/// for the current user.
+pub async fn update_tool_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((server_id, tool_id)): Path<(String, String)>,
+    Json(body): Json<UpdatePreferenceRequest>,
+) -> AppResult<StatusCode> {
+    mcp_config::set_user_tool_enabled(&state.pool, &user.0.sub, &server_id, &tool_id, body.enabled)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------------
+// Catalog endpoints
+// ---------------------------------------------------------------------------
+
+/// `GET /mcp/catalog` — list known MCP servers.
+pub async fn list_catalog_handler(
+    State(state): State<AppState>,
+) -> AppResult<Json<CatalogListResponse>> {
+    let entries = catalog::list_entries(&state.pool).await?;
+    Ok(Json(CatalogListResponse { entries }))
+}
+
+/// `POST /mcp/catalog/{slug}/install` — add a user server from a catalog
+/// entry.
+pub async fn install_catalog_entry_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(audit): axum::Extension<AuditLog>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path(slug): Path<String>,
+    Json(body): Json<InstallCatalogEntryRequest>,
+) -> AppResult<(StatusCode, Json<UserServerView>)> {
+    nize_core::quotas::ensure_within(
+        &state.pool,
+        &state.config_cache,
+        &parse_user_id(&user.0.sub)?,
+        &[(nize_core::quotas::Quota::McpServers, 1)],
+    )
+    .await?;
+    let server = mcp_config::install_catalog_entry_for_user(
+        &state.pool,
+        &audit,
+        &user.0.sub,
+        &slug,
+        body.name.as_deref(),
+        body.domain.as_deref(),
+        &body.secrets,
+        &state.config.mcp_encryption_key,
+    )
+    .await?;
+    if let Some(membership) = workspace.0 {
+        nize_core::mcp::sharing::share_server(
+            &state.pool,
+            &server.id,
+            Some(&membership.workspace_id),
+            &state.config.mcp_encryption_key,
+        )
+        .await?;
+    }
+    Ok((StatusCode::CREATED, Json(server)))
+}
+
+// ---------------------------------------------------------------------------
+// Domain routing endpoints
+// ---------------------------------------------------------------------------
+
+/// `GET /mcp/routing` — the user's domain routes and the admin defaults.
+pub async fn list_routes_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+) -> AppResult<Json<UserDomainRoutesResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let routes = routing::list_routes(&state.pool, Some(&user_id)).await?;
+    let defaults = routing::list_routes(&state.pool, None).await?;
+    Ok(Json(UserDomainRoutesResponse {
+        routes: routes.iter().map(DomainRoute::from).collect(),
+        defaults: defaults.iter().map(DomainRoute::from).collect(),
+    }))
+}
+
+/// `PUT /mcp/routing/{domain}` — set the user's server order for a domain.
+pub async fn set_route_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(domain): Path<String>,
+    Json(body): Json<SetDomainRouteRequest>,
+) -> AppResult<Json<DomainRoute>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let row = routing::set_route(&state.pool, Some(&user_id), &domain, &body.server_ids).await?;
+    Ok(Json(DomainRoute::from(&row)))
+}
+
+/// `DELETE /mcp/routing/{domain}` — fall back to the admin default route.
+pub async fn delete_route_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(domain): Path<String>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    if routing::delete_route(&state.pool, Some(&user_id), &domain).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(
+            Message::new("mcp.route_not_found").arg("domain", domain),
+        ))
+    }
+}
+
+fn parse_user_id(sub: &str) -> Result<uuid::Uuid, AppError> {
+    uuid::Uuid::parse_str(sub)
+        .map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
 }
 
 // ---------------------------------------------------------------------------
@@ -216,7 +512,7 @@ pub async fn oauth_status_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
     Path(server_id): Path<String>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Json<OAuthStatusResponse>> {
     let has_token =
         nize_core::mcp::queries::has_valid_oauth_token(&state.pool, &user.0.sub, &server_id)
             .await
@@ -234,10 +530,10 @@ pub async fn oauth_status_handler(
         None => (false, None),
     };
 
-    Ok(Json(serde_json::json!({
-        "connected": connected,
-        "expiresAt": expires_at,
-    })))
+    Ok(Json(OAuthStatusResponse {
+        connected,
+        expires_at,
+    }))
 }
 
 /// `POST /mcp/servers/{serverId}/oauth/initiate` — initiate OAuth flow.
@@ -246,45 +542,41 @@ pub async fn oauth_initiate_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
     Path(server_id): Path<String>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Json<InitiateOAuthResponse>> {
     use nize_core::mcp::oauth::{
         OAuthPendingState, compute_code_challenge, generate_code_verifier, generate_state,
+        save_pending_state,
     };
 
     // Load server to get OAuth config
     let server = nize_core::mcp::queries::get_server(&state.pool, &server_id)
         .await?
-        .ok_or_else(|| AppError::Validation(format!("Server {server_id} not found")))?;
+        .ok_or_else(|| {
+            AppError::validation(Message::new("mcp.server_not_found").arg("id", &server_id))
+        })?;
 
     let oauth_config_json = server
         .oauth_config
-        .ok_or_else(|| AppError::Validation("Server has no OAuth configuration".into()))?;
+        .ok_or_else(|| AppError::validation(Message::new("mcp.oauth_not_configured")))?;
 
     let oauth_config: nize_core::models::mcp::OAuthConfig =
-        serde_json::from_value(oauth_config_json.clone())
-            .map_err(|e| AppError::Validation(format!("Invalid OAuth config: {e}")))?;
+        serde_json::from_value(oauth_config_json.clone()).map_err(|e| {
+            AppError::validation(Message::new("mcp.invalid_oauth_config").arg("error", e))
+        })?;
 
-    // Load and decrypt client_secret
-    let encrypted_secret =
-        nize_core::mcp::queries::get_oauth_client_secret_encrypted(&state.pool, &server_id)
-            .await?
-            .ok_or_else(|| {
-                AppError::Validation("No OAuth client secret stored for server".into())
-            })?;
-
-    let client_secret =
-        nize_core::mcp::secrets::decrypt(&encrypted_secret, &state.config.mcp_encryption_key)
-            .map_err(|e| AppError::Internal(format!("Failed to decrypt client secret: {e}")))?;
+    // Fail early if the client secret is missing; the callback reads it again
+    crate::handlers::oauth::oauth_client_secret(&state, &server_id).await?;
 
     // Generate PKCE params
     let code_verifier = generate_code_verifier();
     let code_challenge = compute_code_challenge(&code_verifier);
     let state_param = generate_state();
 
-    // Build redirect_uri from current API bind address
+    // Build redirect_uri from the OAuth callback address, which stays the
+    // same across restarts when a callback port is configured
     let redirect_uri = format!(
         "http://{}{}{}",
-        state.config.bind_addr,
+        state.config.oauth_callback_addr(),
         crate::API_PREFIX,
         crate::generated::routes::GET_AUTH_OAUTH_MCP_CALLBACK,
     );
@@ -295,15 +587,20 @@ pub async fn oauth_initiate_handler(
         user_id: user.0.sub.clone(),
         pkce_verifier: code_verifier,
         oauth_config_json,
-        client_secret,
         redirect_uri: redirect_uri.clone(),
-        created_at: std::time::Instant::now(),
     };
-    state.oauth_state.insert(state_param.clone(), pending);
+    save_pending_state(
+        &state.pool,
+        &state_param,
+        &pending,
+        &state.config.mcp_encryption_key,
+    )
+    .await?;
 
     // Build Google authorization URL
-    let mut auth_url = url::Url::parse(&oauth_config.authorization_url)
-        .map_err(|e| AppError::Validation(format!("Invalid authorization URL: {e}")))?;
+    let mut auth_url = url::Url::parse(&oauth_config.authorization_url).map_err(|e| {
+        AppError::validation(Message::new("mcp.invalid_authorization_url").arg("error", e))
+    })?;
     auth_url
         .query_pairs_mut()
         .append_pair("client_id", &oauth_config.client_id)
@@ -316,9 +613,9 @@ pub async fn oauth_initiate_handler(
         .append_pair("code_challenge", &code_challenge)
         .append_pair("code_challenge_method", "S256");
 
-    Ok(Json(serde_json::json!({
-        "authUrl": auth_url.as_str(),
-    })))
+    Ok(Json(InitiateOAuthResponse {
+        auth_url: auth_url.into(),
+    }))
 }
 
 /// `POST /mcp/servers/{serverId}/oauth/revoke` — revoke OAuth token.
@@ -334,10 +631,14 @@ pub async fn oauth_revoke_handler(
 
     if let Some(row) = token_row {
         // Best-effort revoke at Google
-        if let Ok(access_token) = nize_core::mcp::secrets::decrypt(
+        // The row is deleted below, so there is no point re-encrypting it.
+        if let Ok(access_token) = secrets::decrypt_bound(
             &row.access_token_encrypted,
             &state.config.mcp_encryption_key,
-        ) {
+            &SecretBinding::access_token(&user.0.sub, &server_id),
+        )
+        .map(|d| d.plaintext)
+        {
             let _ = reqwest::Client::new()
                 .post("https://oauth2.googleapis.com/revoke")
                 .form(&[("token", &access_token)])
@@ -361,7 +662,7 @@ pub async fn test_connection_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
     Json(body): Json<TestConnectionRequest>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Json<TestConnectionResult>> {
     // Determine OAuth requirement for this test request.
     // Prefer server-level oauth_config when serverId is present so all transports
     // (including managed) share the same OAuth behavior.
@@ -381,10 +682,14 @@ pub async fn test_connection_handler(
             match nize_core::mcp::queries::get_oauth_token(&state.pool, &user.0.sub, sid).await {
                 Ok(Some(row)) => {
                     let id_token = match row.id_token_encrypted.as_deref() {
-                        Some(encrypted) => match nize_core::mcp::secrets::decrypt(
+                        Some(encrypted) => match secrets::decrypt_row(
+                            &state.pool,
                             encrypted,
                             &state.config.mcp_encryption_key,
-                        ) {
+                            &SecretBinding::id_token(&user.0.sub, sid),
+                        )
+                        .await
+                        {
                             Ok(token) => Some(token),
                             Err(e) => {
                                 tracing::warn!("Failed to decrypt OAuth ID token for test: {e}");
@@ -394,10 +699,14 @@ pub async fn test_connection_handler(
                         None => None,
                     };
 
-                    let access_token = match nize_core::mcp::secrets::decrypt(
+                    let access_token = match secrets::decrypt_row(
+                        &state.pool,
                         &row.access_token_encrypted,
                         &state.config.mcp_encryption_key,
-                    ) {
+                        &SecretBinding::access_token(&user.0.sub, sid),
+                    )
+                    .await
+                    {
                         Ok(token) => Some(token),
                         Err(e) => {
                             tracing::warn!("Failed to decrypt OAuth access token for test: {e}");
@@ -425,13 +734,13 @@ pub async fn test_connection_handler(
     // If OAuth is required and no token is available, return authRequired
     // instead of attempting a connection.
     if server_uses_oauth && oauth_headers.is_none() {
-        let result = nize_core::models::mcp::TestConnectionResult {
+        let result = TestConnectionResult {
             success: false,
             error: Some("OAuth authorization required".to_string()),
             auth_required: Some(true),
             ..Default::default()
         };
-        return Ok(Json(serde_json::to_value(result).unwrap()));
+        return Ok(Json(result));
     }
 
     let result = mcp_config::test_connection(
@@ -442,48 +751,29 @@ pub async fn test_connection_handler(
     .await;
 
     // When test succeeds and we know which server, persist discovered tools + embeddings
-    if result.success && !result.tools.is_empty() {
-        if let Some(ref server_id) = body.server_id {
-            if let Err(e) =
-                mcp_config::store_tools_from_test(&state.pool, server_id, &result.tools).await
-            {
-                tracing::warn!("Failed to store tools from test for server {server_id}: {e}");
-            }
-
-            // Mark server as available after successful connection
-            if let Err(e) = nize_core::mcp::queries::update_server(
-                &state.pool,
-                server_id,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                Some(true),
-                None,
-            )
-            .await
-            {
-                tracing::warn!("Failed to set server available: {e}");
-            }
-
-            // Generate embeddings for the discovered tools
-            if let Err(e) = nize_core::embedding::indexer::embed_server_tools(
-                &state.pool,
-                &state.config_cache,
-                server_id,
-                &state.config.mcp_encryption_key,
-            )
-            .await
-            {
-                tracing::warn!("Failed to embed tools for server {server_id}: {e}");
-            }
+    if result.success
+        && !result.tools.is_empty()
+        && let Some(ref server_id) = body.server_id
+    {
+        // Tools and availability are saved together so a failure cannot
+        // leave the server available with a stale tool list.
+        if let Err(e) =
+            mcp_config::save_discovered_tools(&state.pool, server_id, &result.tools).await
+        {
+            tracing::warn!("Failed to store tools from test for server {server_id}: {e}");
+        } else if let Err(e) = nize_core::embedding::indexer::embed_server_tools(
+            &state.pool,
+            &state.config_cache,
+            server_id,
+            &state.config.mcp_encryption_key,
+        )
+        .await
+        {
+            tracing::warn!("Failed to embed tools for server {server_id}: {e}");
         }
     }
 
-    Ok(Json(serde_json::to_value(result).unwrap()))
+    Ok(Json(result))
 }
 
 // ---------------------------------------------------------------------------
@@ -493,19 +783,21 @@ pub async fn test_connection_handler(
 /// `GET /mcp/admin/servers` — list admin MCP servers.
 pub async fn admin_list_servers_handler(
     State(state): State<AppState>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Json<AdminServerListResponse>> {
     let servers = mcp_config::get_all_servers(&state.pool).await?;
-    Ok(Json(serde_json::json!({ "servers": servers })))
+    Ok(Json(AdminServerListResponse { servers }))
 }
 
 /// `POST /mcp/admin/servers` — create admin MCP server.
 pub async fn admin_create_server_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(audit): axum::Extension<AuditLog>,
     Json(body): Json<CreateAdminServerRequest>,
-) -> AppResult<(StatusCode, Json<serde_json::Value>)> {
-    let server = mcp_config::create_built_in_server(
+) -> AppResult<(StatusCode, Json<AdminServerView>)> {
+    let mut server = mcp_config::create_built_in_server(
         &state.pool,
+        &audit,
         &user.0.sub,
         &body.name,
         body.description.as_deref().unwrap_or(""),
@@ -519,51 +811,37 @@ pub async fn admin_create_server_handler(
     )
     .await?;
 
-    // Discover and store tools from the server (skip for OAuth — no tokens yet)
+    // Discover tools in the background (skip for OAuth — no tokens yet)
     let is_oauth = match &body.config {
         ServerConfig::Http(http) => http.auth_type == "oauth",
         _ => false,
     };
-    let test_result = if is_oauth {
-        Default::default()
-    } else {
-        mcp_config::test_connection(&body.config, body.api_key.as_deref(), None).await
-    };
-    if !test_result.tools.is_empty() {
-        if let Err(e) =
-            mcp_config::store_tools_from_test(&state.pool, &server.id, &test_result.tools).await
-        {
-            tracing::warn!("Failed to store tools for server {}: {e}", server.id);
-        }
-
-        // Generate embeddings for the newly stored tools
-        if let Err(e) = nize_core::embedding::indexer::embed_server_tools(
-            &state.pool,
-            &state.config_cache,
+    if !is_oauth {
+        server.discovery = Some(mcp_config::start_discovery(&state.pool, &server.id).await?);
+        spawn_discovery(
+            &state,
+            &user.0.sub,
             &server.id,
-            &state.config.mcp_encryption_key,
-        )
-        .await
-        {
-            tracing::warn!("Failed to embed tools for server {}: {e}", server.id);
-        }
+            body.config.clone(),
+            body.api_key.clone(),
+            None,
+        );
     }
 
-    Ok((
-        StatusCode::CREATED,
-        Json(serde_json::to_value(server).unwrap()),
-    ))
+    Ok((StatusCode::CREATED, Json(server)))
 }
 
 /// `PATCH /mcp/admin/servers/{serverId}` — update admin MCP server.
 pub async fn admin_update_server_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(audit): axum::Extension<AuditLog>,
     Path(server_id): Path<String>,
     Json(body): Json<UpdateAdminServerRequest>,
-) -> AppResult<Json<serde_json::Value>> {
-    let server = mcp_config::update_built_in_server(
+) -> AppResult<Json<AdminServerView>> {
+    let mut server = mcp_config::update_built_in_server(
         &state.pool,
+        &audit,
         &user.0.sub,
         &server_id,
         body.name.as_deref(),
@@ -571,6 +849,8 @@ pub async fn admin_update_server_handler(
         body.domain.as_deref(),
         body.visibility.as_deref(),
         body.enabled,
+        body.keep_warm,
+        body.forwarded_context.as_deref(),
         body.config.as_ref(),
         body.api_key.as_deref(),
         body.oauth_config.as_ref(),
@@ -579,7 +859,12 @@ pub async fn admin_update_server_handler(
     )
     .await?;
 
-    // Re-discover and store tools when config changes
+    // Pin and pre-connect, or unpin, the live connection
+    if body.keep_warm.is_some() || body.enabled.is_some() {
+        spawn_keep_warm(&state, &server.id);
+    }
+
+    // Re-discover tools in the background when config changes
     if let Some(config) = &body.config {
         // For OAuth servers, look up stored OAuth headers
         let oauth_headers = match config {
@@ -589,10 +874,14 @@ pub async fn admin_update_server_handler(
                 {
                     Ok(Some(row)) => {
                         let id_token = match row.id_token_encrypted.as_deref() {
-                            Some(encrypted) => match nize_core::mcp::secrets::decrypt(
+                            Some(encrypted) => match secrets::decrypt_row(
+                                &state.pool,
                                 encrypted,
                                 &state.config.mcp_encryption_key,
-                            ) {
+                                &SecretBinding::id_token(&user.0.sub, &server_id),
+                            )
+                            .await
+                            {
                                 Ok(token) => Some(token),
                                 Err(e) => {
                                     tracing::warn!(
@@ -603,10 +892,14 @@ pub async fn admin_update_server_handler(
                             },
                             None => None,
                         };
-                        let access_token = match nize_core::mcp::secrets::decrypt(
+                        let access_token = match secrets::decrypt_row(
+                            &state.pool,
                             &row.access_token_encrypted,
                             &state.config.mcp_encryption_key,
-                        ) {
+                            &SecretBinding::access_token(&user.0.sub, &server_id),
+                        )
+                        .await
+                        {
                             Ok(token) => Some(token),
                             Err(e) => {
                                 tracing::warn!(
@@ -629,58 +922,299 @@ pub async fn admin_update_server_handler(
             _ => None,
         };
 
-        let test_result =
-            mcp_config::test_connection(config, body.api_key.as_deref(), oauth_headers.as_ref())
-                .await;
-        if !test_result.tools.is_empty() {
-            if let Err(e) =
-                mcp_config::store_tools_from_test(&state.pool, &server.id, &test_result.tools).await
-            {
-                tracing::warn!("Failed to store tools for server {}: {e}", server.id);
-            }
+        server.discovery = Some(mcp_config::start_discovery(&state.pool, &server.id).await?);
+        spawn_discovery(
+            &state,
+            &user.0.sub,
+            &server.id,
+            config.clone(),
+            body.api_key.clone(),
+            oauth_headers,
+        );
+    }
 
-            // Mark server as available after successful tool discovery
-            if let Err(e) = nize_core::mcp::queries::update_server(
-                &state.pool,
-                &server_id,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                Some(true),
-                None,
-            )
-            .await
-            {
-                tracing::warn!("Failed to set server available: {e}");
-            }
+    Ok(Json(server))
+}
 
-            // Generate embeddings for the newly stored tools
-            if let Err(e) = nize_core::embedding::indexer::embed_server_tools(
-                &state.pool,
-                &state.config_cache,
-                &server.id,
-                &state.config.mcp_encryption_key,
-            )
-            .await
-            {
-                tracing::warn!("Failed to embed tools for server {}: {e}", server.id);
-            }
+/// `POST /mcp/admin/test-all` — test every server, refreshing tool lists
+/// and availability.
+pub async fn admin_test_all_servers_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+) -> AppResult<Json<TestAllServersResponse>> {
+    let results = mcp_config::test_all_servers(
+        &state.pool,
+        &state.config_cache,
+        &state.config.mcp_encryption_key,
+        &user.0.sub,
+    )
+    .await?;
+    let succeeded = results.iter().filter(|r| r.success).count();
+    let auth_required = results.iter().filter(|r| r.auth_required).count();
+    Ok(Json(TestAllServersResponse {
+        failed: results.len() - succeeded - auth_required,
+        servers: results,
+        succeeded,
+        auth_required,
+    }))
+}
+
+/// Apply a server's keep-warm setting to the MCP client pool in the
+/// background, since pre-connecting may take a while.
+fn spawn_keep_warm(state: &AppState, server_id: &str) {
+    let Ok(server_id) = uuid::Uuid::parse_str(server_id) else {
+        return;
+    };
+    let clients = state.mcp_clients.clone();
+    let pool = state.pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = clients.apply_keep_warm(&pool, server_id).await {
+            tracing::warn!(%server_id, "Failed to apply keep-warm setting: {e}");
         }
-    }
+    });
+}
 
-    Ok(Json(serde_json::to_value(server).unwrap()))
+/// Run tool discovery without blocking the response, publishing an
+/// `mcp.discovery_completed` (or `mcp.discovery_failed`) event to the admin
+/// who triggered it.
+fn spawn_discovery(
+    state: &AppState,
+    user_id: &str,
+    server_id: &str,
+    config: ServerConfig,
+    api_key: Option<String>,
+    oauth_headers: Option<OAuthHeaders>,
+) {
+    let Ok(user_id) = uuid::Uuid::parse_str(user_id) else {
+        return;
+    };
+    let state = state.clone();
+    let server_id = server_id.to_string();
+    tokio::spawn(async move {
+        let (kind, title, body) = match mcp_config::discover_tools(
+            &state.pool,
+            &state.config_cache,
+            &state.config.mcp_encryption_key,
+            &server_id,
+            &config,
+            api_key.as_deref(),
+            oauth_headers.as_ref(),
+        )
+        .await
+        {
+            Ok(count) => (
+                KIND_MCP_DISCOVERY_COMPLETED,
+                "Tools discovered",
+                format!("Found {count} tools."),
+            ),
+            Err(e) => {
+                tracing::warn!("Tool discovery for server {server_id} failed: {e}");
+                (
+                    KIND_MCP_DISCOVERY_FAILED,
+                    "Tool discovery failed",
+                    e.to_string(),
+                )
+            }
+        };
+        state.events.publish(ServerEvent {
+            user_id: Some(user_id),
+            kind: kind.into(),
+            title: title.into(),
+            body,
+            payload: serde_json::json!({ "serverId": server_id }),
+        });
+    });
+}
+
+/// `PATCH /mcp/admin/servers/{serverId}/tools/{toolId}` — switch a tool on
+/// or off for every user.
+pub async fn admin_update_tool_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(audit): axum::Extension<AuditLog>,
+    Path((server_id, tool_id)): Path<(String, String)>,
+    Json(body): Json<UpdatePreferenceRequest>,
+) -> AppResult<StatusCode> {
+    mcp_config::set_admin_tool_enabled(
+        &state.pool,
+        &audit,
+        &user.0.sub,
+        &server_id,
+        &tool_id,
+        body.enabled,
+    )
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /mcp/admin/transforms` — list tool output transform templates.
+pub async fn admin_list_transforms_handler(
+    State(state): State<AppState>,
+) -> AppResult<Json<OutputTransformListResponse>> {
+    let transforms = transforms::list_transforms(&state.pool).await?;
+    Ok(Json(OutputTransformListResponse { transforms }))
+}
+
+/// `PUT /mcp/admin/servers/{serverId}/tools/{toolId}/transform` — set how a
+/// tool's output is narrowed and truncated before it reaches the model.
+pub async fn admin_set_transform_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(audit): axum::Extension<AuditLog>,
+    Path((server_id, tool_id)): Path<(String, String)>,
+    Json(body): Json<SetToolTransformRequest>,
+) -> AppResult<Json<OutputTransformRow>> {
+    let row = mcp_config::set_tool_transform(
+        &state.pool,
+        &audit,
+        &user.0.sub,
+        &server_id,
+        &tool_id,
+        &body.rules,
+        body.enabled,
+    )
+    .await?;
+    Ok(Json(row))
+}
+
+/// `DELETE /mcp/admin/servers/{serverId}/tools/{toolId}/transform` — return
+/// a tool's output unchanged again.
+pub async fn admin_delete_transform_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(audit): axum::Extension<AuditLog>,
+    Path((server_id, tool_id)): Path<(String, String)>,
+) -> AppResult<StatusCode> {
+    if mcp_config::delete_tool_transform(&state.pool, &audit, &user.0.sub, &server_id, &tool_id)
+        .await?
+    {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(
+            Message::new("mcp.no_output_transform").arg("id", tool_id),
+        ))
+    }
 }
 
 /// `DELETE /mcp/admin/servers/{serverId}` — delete admin MCP server.
 pub async fn admin_delete_server_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(audit): axum::Extension<AuditLog>,
     Path(server_id): Path<String>,
-) -> AppResult<Json<serde_json::Value>> {
-    let result = mcp_config::delete_built_in_server(&state.pool, &user.0.sub, &server_id).await?;
-    Ok(Json(serde_json::to_value(result).unwrap()))
+) -> AppResult<Json<DeleteResult>> {
+    let result =
+        mcp_config::delete_built_in_server(&state.pool, &audit, &user.0.sub, &server_id).await?;
+    Ok(Json(result))
+}
+
+/// `PUT /mcp/admin/catalog/{slug}` — add a catalog entry, or replace one
+/// (including a shipped entry).
+pub async fn admin_upsert_catalog_entry_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(slug): Path<String>,
+    Json(mut body): Json<CatalogEntry>,
+) -> AppResult<Json<CatalogEntryView>> {
+    body.slug = slug;
+    let entry = catalog::upsert_entry(&state.pool, &body, &parse_user_id(&user.0.sub)?).await?;
+    Ok(Json(entry))
+}
+
+/// `DELETE /mcp/admin/catalog/{slug}` — remove an admin-defined catalog
+/// entry, restoring the shipped one it replaced, if any.
+pub async fn admin_delete_catalog_entry_handler(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> AppResult<StatusCode> {
+    if catalog::delete_entry(&state.pool, &slug).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(
+            Message::new("mcp.catalog_entry_not_found").arg("slug", slug),
+        ))
+    }
+}
+
+/// `POST /mcp/admin/catalog/{slug}/install` — create a built-in server from
+/// a catalog entry and discover its tools.
+pub async fn admin_install_catalog_entry_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(audit): axum::Extension<AuditLog>,
+    Path(slug): Path<String>,
+    Json(body): Json<AdminInstallCatalogEntryRequest>,
+) -> AppResult<(StatusCode, Json<AdminServerView>)> {
+    let (mut server, installed) = mcp_config::install_catalog_entry_as_admin(
+        &state.pool,
+        &audit,
+        &user.0.sub,
+        &slug,
+        body.name.as_deref(),
+        body.domain.as_deref(),
+        &body.visibility,
+        &body.secrets,
+        &state.config.mcp_encryption_key,
+    )
+    .await?;
+
+    // OAuth servers have no tokens yet; discovery runs once authorized.
+    if server.auth_type != AuthType::OAuth {
+        server.discovery = Some(mcp_config::start_discovery(&state.pool, &server.id).await?);
+        spawn_discovery(
+            &state,
+            &user.0.sub,
+            &server.id,
+            installed.config,
+            installed.api_key,
+            None,
+        );
+    }
+
+    Ok((StatusCode::CREATED, Json(server)))
+}
+
+/// `GET /mcp/admin/routing` — default domain routes.
+pub async fn admin_list_routes_handler(
+    State(state): State<AppState>,
+) -> AppResult<Json<DomainRoutesResponse>> {
+    let routes = routing::list_routes(&state.pool, None).await?;
+    Ok(Json(DomainRoutesResponse {
+        routes: routes.iter().map(DomainRoute::from).collect(),
+    }))
+}
+
+/// `PUT /mcp/admin/routing/{domain}` — set the default server order for a
+/// domain.
+pub async fn admin_set_route_handler(
+    State(state): State<AppState>,
+    Path(domain): Path<String>,
+    Json(body): Json<SetDomainRouteRequest>,
+) -> AppResult<Json<DomainRoute>> {
+    let row = routing::set_route(&state.pool, None, &domain, &body.server_ids).await?;
+    Ok(Json(DomainRoute::from(&row)))
+}
+
+/// `DELETE /mcp/admin/routing/{domain}` — remove the default route.
+pub async fn admin_delete_route_handler(
+    State(state): State<AppState>,
+    Path(domain): Path<String>,
+) -> AppResult<StatusCode> {
+    if routing::delete_route(&state.pool, None, &domain).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(
+            Message::new("mcp.route_not_found").arg("domain", domain),
+        ))
+    }
+}
+
+/// `GET /mcp/admin/processes` — CPU, memory, uptime and restarts of each
+/// managed MCP server process.
+pub async fn admin_list_processes_handler(
+    State(state): State<AppState>,
+) -> Json<ChildProcessListResponse> {
+    Json(ChildProcessListResponse {
+        processes: state.metrics.mcp_child_stats(),
+    })
 }
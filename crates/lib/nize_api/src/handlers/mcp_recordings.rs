@@ -0,0 +1,131 @@
+//! Tool-call recording and replay request handlers.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use nize_core::mcp::recording::{
+    self, LiveBackend, MockBackend, RecordingArchive, RecordingSession, ReplayReport,
+    StartRecording,
+};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplayMode {
+    #[default]
+    Live,
+    Mock,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayRequest {
+    #[serde(default)]
+    pub mode: ReplayMode,
+    /// Live mode: send every call to this server instead of the recorded one.
+    pub server_id: Option<Uuid>,
+}
+
+/// Body of `GET /mcp/admin/recordings`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct RecordingListResponse {
+    pub sessions: Vec<RecordingSession>,
+}
+
+/// `GET /mcp/admin/recordings` — list recording sessions.
+pub async fn list_recordings_handler(
+    State(state): State<AppState>,
+) -> AppResult<Json<RecordingListResponse>> {
+    let sessions = recording::list(&state.pool).await?;
+    Ok(Json(RecordingListResponse { sessions }))
+}
+
+/// `POST /mcp/admin/recordings` — start recording a user's or a server's
+/// tool calls.
+pub async fn start_recording_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Json(body): Json<StartRecording>,
+) -> AppResult<(StatusCode, Json<RecordingSession>)> {
+    let session = recording::start(&state.pool, &body, &parse_user_id(&user.0.sub)?).await?;
+    Ok((StatusCode::CREATED, Json(session)))
+}
+
+/// `GET /mcp/admin/recordings/{id}` — export a session with its calls.
+pub async fn get_recording_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<RecordingArchive>> {
+    Ok(Json(load_archive(&state, &id).await?))
+}
+
+/// `POST /mcp/admin/recordings/{id}/stop` — stop capturing calls.
+pub async fn stop_recording_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<Json<RecordingSession>> {
+    recording::stop(&state.pool, &parse_uuid(&id)?)
+        .await?
+        .map(Json)
+        .ok_or_else(|| AppError::not_found(Message::new("mcp.recording_not_found").arg("id", id)))
+}
+
+/// `DELETE /mcp/admin/recordings/{id}` — delete a session and its calls.
+pub async fn delete_recording_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    if recording::delete(&state.pool, &parse_uuid(&id)?).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(
+            Message::new("mcp.recording_not_found").arg("id", id),
+        ))
+    }
+}
+
+/// `POST /mcp/admin/recordings/{id}/replay` — re-run a session's calls and
+/// compare the outcomes with the recorded ones.
+pub async fn replay_recording_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+    Json(body): Json<ReplayRequest>,
+) -> AppResult<Json<ReplayReport>> {
+    let archive = load_archive(&state, &id).await?;
+    let report = match body.mode {
+        ReplayMode::Live => {
+            let backend = LiveBackend::new(
+                &state.pool,
+                &user.0.sub,
+                body.server_id,
+                &state.config.mcp_encryption_key,
+            );
+            recording::replay(&archive, &backend).await
+        }
+        ReplayMode::Mock => recording::replay(&archive, &MockBackend::from_archive(&archive)).await,
+    };
+    Ok(Json(report))
+}
+
+async fn load_archive(state: &AppState, id: &str) -> AppResult<RecordingArchive> {
+    recording::archive(&state.pool, &parse_uuid(id)?)
+        .await?
+        .ok_or_else(|| AppError::not_found(Message::new("mcp.recording_not_found").arg("id", id)))
+}
+
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
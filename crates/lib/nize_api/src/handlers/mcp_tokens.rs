@@ -1,19 +1,36 @@
 //! MCP token management request handlers.
 
-use axum::Json;
-use axum::extract::{Path, State};
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::HeaderMap;
+use axum::{Extension, Json};
+use axum_extra::either::Either;
+use nize_core::auth::activity;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 use crate::AppState;
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
 use crate::generated::models::{
     CreateMcpTokenRequest, CreateMcpTokenResponse, McpTokenInfo, McpTokenListResponse,
+    SuccessResponse,
 };
+use crate::i18n::Message;
 use crate::middleware::auth::AuthenticatedUser;
+use crate::services::mcp_client_config::{self, ClientKind, ClientSnippet, TOKEN_PLACEHOLDER};
+use crate::services::security_activity;
+
+/// Request header carrying a token's plaintext so client configs can embed
+/// it. Kept out of the query string so it does not end up in access logs.
+pub const MCP_TOKEN_HEADER: &str = "x-mcp-token";
 
 /// `POST /auth/mcp-tokens` — create a new MCP API token.
 pub async fn create_mcp_token_handler(
     State(state): State<AppState>,
     axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    headers: HeaderMap,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
     Json(body): Json<CreateMcpTokenRequest>,
 ) -> AppResult<Json<CreateMcpTokenResponse>> {
     let overwrite = body.overwrite.unwrap_or(false);
@@ -22,12 +39,26 @@ pub async fn create_mcp_token_handler(
         &user.0.sub,
         &body.name,
         overwrite,
+        body.read_only.unwrap_or(false),
     )
     .await?;
+    security_activity::record(
+        &state.pool,
+        &user.0.sub,
+        activity::KIND_MCP_TOKEN_CREATED,
+        &security_activity::client_info(&headers, peer.map(|Extension(ConnectInfo(a))| a)),
+        serde_json::json!({
+            "tokenId": record.id,
+            "name": record.name,
+            "readOnly": record.read_only,
+        }),
+    )
+    .await;
     Ok(Json(CreateMcpTokenResponse {
         id: record.id,
         token: plaintext,
         name: record.name,
+        read_only: record.read_only,
         created_at: record.created_at.to_rfc3339(),
     }))
 }
@@ -43,6 +74,7 @@ pub async fn list_mcp_tokens_handler(
         .map(|r| McpTokenInfo {
             id: r.id,
             name: r.name,
+            read_only: r.read_only,
             created_at: r.created_at.to_rfc3339(),
             expires_at: r.expires_at.map(|t| t.to_rfc3339()),
             revoked_at: r.revoked_at.map(|t| t.to_rfc3339()),
@@ -54,9 +86,140 @@ pub async fn list_mcp_tokens_handler(
 /// `DELETE /auth/mcp-tokens/{id}` — revoke an MCP API token.
 pub async fn revoke_mcp_token_handler(
     State(state): State<AppState>,
-    axum::Extension(_user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    headers: HeaderMap,
+    peer: Option<Extension<ConnectInfo<SocketAddr>>>,
     Path(token_id): Path<String>,
-) -> AppResult<Json<serde_json::Value>> {
+) -> AppResult<Json<SuccessResponse>> {
     nize_core::auth::mcp_tokens::revoke_mcp_token(&state.pool, &token_id).await?;
-    Ok(Json(serde_json::json!({"success": true})))
+    security_activity::record(
+        &state.pool,
+        &user.0.sub,
+        activity::KIND_MCP_TOKEN_REVOKED,
+        &security_activity::client_info(&headers, peer.map(|Extension(ConnectInfo(a))| a)),
+        serde_json::json!({ "tokenId": token_id }),
+    )
+    .await;
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// Query params for client configs.
+#[derive(Debug, Deserialize)]
+pub struct ClientConfigParams {
+    /// Only this client (all clients when omitted).
+    pub client: Option<ClientKind>,
+    /// `snippets` (default) or `deeplink`.
+    pub format: Option<String>,
+}
+
+/// Body of `GET /auth/mcp-tokens/{id}/client-config` with `format=snippets`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct McpClientConfigResponse {
+    pub mcp_url: String,
+    /// False when snippets carry a placeholder instead of the token.
+    pub token_included: bool,
+    pub items: Vec<ClientSnippet>,
+}
+
+/// Body of `GET /auth/mcp-tokens/{id}/client-config` with `format=deeplink`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct McpClientDeepLinkResponse {
+    pub client: ClientKind,
+    pub link: String,
+}
+
+/// `GET /auth/mcp-tokens/{id}/client-config` — ready-to-paste MCP client
+/// configs for a token, or a deep link for the desktop app.
+///
+/// Tokens are stored hashed, so the plaintext must be sent in
+/// [`MCP_TOKEN_HEADER`] to be embedded; snippets otherwise carry a
+/// placeholder. Deep links always need it.
+pub async fn client_config_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(token_id): Path<String>,
+    Query(params): Query<ClientConfigParams>,
+    headers: HeaderMap,
+) -> AppResult<Either<Json<McpClientConfigResponse>, Json<McpClientDeepLinkResponse>>> {
+    uuid::Uuid::parse_str(&token_id)
+        .map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))?;
+    let mcp_url =
+        state.config.mcp_url.clone().ok_or_else(|| {
+            AppError::SidecarUnavailable("MCP server URL is not configured".into())
+        })?;
+
+    let token = headers
+        .get(MCP_TOKEN_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|t| !t.is_empty());
+    let not_found =
+        || AppError::not_found(Message::new("auth.mcp_token_not_found").arg("id", &token_id));
+    match token {
+        Some(t) => {
+            let matches = nize_core::auth::mcp_tokens::check_mcp_token(
+                &state.pool,
+                &user.0.sub,
+                &token_id,
+                t,
+            )
+            .await?
+            .ok_or_else(not_found)?;
+            if !matches {
+                return Err(AppError::validation(
+                    Message::new("auth.mcp_token_header_mismatch").arg("header", MCP_TOKEN_HEADER),
+                ));
+            }
+        }
+        None => {
+            let records =
+                nize_core::auth::mcp_tokens::list_mcp_tokens(&state.pool, &user.0.sub).await?;
+            records
+                .iter()
+                .find(|r| r.id == token_id && r.revoked_at.is_none())
+                .ok_or_else(not_found)?;
+        }
+    }
+
+    match params.format.as_deref().unwrap_or("snippets") {
+        "snippets" => {
+            let clients = match params.client {
+                Some(c) => vec![c],
+                None => ClientKind::ALL.to_vec(),
+            };
+            let items: Vec<_> = clients
+                .into_iter()
+                .map(|c| {
+                    mcp_client_config::snippet(c, &mcp_url, token.unwrap_or(TOKEN_PLACEHOLDER))
+                })
+                .collect();
+            Ok(Either::E1(Json(McpClientConfigResponse {
+                mcp_url,
+                token_included: token.is_some(),
+                items,
+            })))
+        }
+        "deeplink" => {
+            let client = params.client.ok_or_else(|| {
+                AppError::validation(Message::new("auth.deep_link_client_required"))
+            })?;
+            let token = token.ok_or_else(|| {
+                AppError::validation(
+                    Message::new("auth.deep_link_header_required").arg("header", MCP_TOKEN_HEADER),
+                )
+            })?;
+            let link = mcp_client_config::deep_link(client, &mcp_url, token).ok_or_else(|| {
+                AppError::validation(
+                    Message::new("auth.deep_link_unsupported")
+                        .arg("client", client.display_name())
+                        .arg("url", mcp_url),
+                )
+            })?;
+            Ok(Either::E2(Json(McpClientDeepLinkResponse { client, link })))
+        }
+        other => Err(AppError::validation(
+            Message::new("auth.invalid_config_format").arg("format", other),
+        )),
+    }
 }
@@ -0,0 +1,17 @@
+//! API metadata — which spec the server was generated from.
+
+use axum::Json;
+
+use crate::generated::models::MetaResponse;
+use crate::generated::routes;
+
+/// `GET /meta` — the spec hash and versions of this server. Clients
+/// generated from a different spec compare the hash and warn before
+/// requests start failing in subtle ways.
+pub async fn meta_handler() -> Json<MetaResponse> {
+    Json(MetaResponse {
+        api_version: routes::API_VERSION.to_string(),
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        spec_hash: routes::SPEC_HASH.to_string(),
+    })
+}
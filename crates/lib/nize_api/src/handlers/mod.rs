@@ -1,16 +1,46 @@
 //! Request handlers.
 
+pub mod account;
 pub mod admin_permissions;
+pub mod admin_roles;
 pub mod ai_proxy;
+pub mod analytics;
+pub mod announcements;
 pub mod auth;
 pub mod chat;
 pub mod config;
+pub mod connectors;
 pub mod conversations;
+pub mod database;
+pub mod diagnostics;
 pub mod embeddings;
+pub mod evals;
+pub mod events;
+pub mod feedback;
+pub mod health;
 pub mod hello;
 pub mod ingest;
+pub mod integrity;
 pub mod mcp_config;
+pub mod mcp_recordings;
 pub mod mcp_tokens;
+pub mod meta;
+pub mod metrics;
+pub mod moderation;
+pub mod notes;
+pub mod notifications;
 pub mod oauth;
 pub mod permissions;
+pub mod providers;
+pub mod regeneration;
+pub mod security_activity;
+pub mod signing_keys;
+pub mod storage;
+pub mod streams;
+pub mod sync;
+pub mod tags;
+pub mod tasks;
+pub mod telemetry;
 pub mod trace;
+pub mod usage;
+pub mod workspaces;
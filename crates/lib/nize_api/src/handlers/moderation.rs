@@ -0,0 +1,140 @@
+//! Content moderation admin handlers — rules and result review.
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::moderation::rules::{self, Rule, RuleInput};
+use nize_core::moderation::{self, ModerationResult};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+
+/// Body of `GET /admin/moderation/rules`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ModerationRuleListResponse {
+    pub items: Vec<Rule>,
+}
+
+/// Body of `GET /admin/moderation/results`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ModerationResultListResponse {
+    pub items: Vec<ModerationResult>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// `GET /admin/moderation/rules` — every rule, by label.
+pub async fn list_rules_handler(
+    State(state): State<AppState>,
+) -> AppResult<Json<ModerationRuleListResponse>> {
+    let items = rules::list_rules(&state.pool).await?;
+    Ok(Json(ModerationRuleListResponse { items }))
+}
+
+/// `POST /admin/moderation/rules` — create a rule.
+pub async fn create_rule_handler(
+    State(state): State<AppState>,
+    Json(body): Json<RuleInput>,
+) -> AppResult<(StatusCode, Json<Rule>)> {
+    let rule = rules::create_rule(&state.pool, &body).await?;
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+/// `PUT /admin/moderation/rules/{id}` — replace a rule.
+pub async fn update_rule_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<RuleInput>,
+) -> AppResult<Json<Rule>> {
+    let rule = rules::update_rule(&state.pool, &parse_uuid(&id)?, &body).await?;
+    Ok(Json(rule))
+}
+
+/// `DELETE /admin/moderation/rules/{id}` — delete a rule.
+pub async fn delete_rule_handler(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    if rules::delete_rule(&state.pool, &parse_uuid(&id)?).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(
+            Message::new("moderation.rule_not_found").arg("id", id),
+        ))
+    }
+}
+
+/// Query params for listing results.
+#[derive(Debug, Deserialize)]
+pub struct ResultListParams {
+    /// `pending` (default) or `all`.
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// `GET /admin/moderation/results` — results newest first, by default
+/// only those awaiting review.
+pub async fn list_results_handler(
+    State(state): State<AppState>,
+    Query(params): Query<ResultListParams>,
+) -> AppResult<Json<ModerationResultListResponse>> {
+    let pending_only = match params.status.as_deref() {
+        None | Some("pending") => true,
+        Some("all") => false,
+        Some(other) => {
+            return Err(AppError::validation(
+                Message::new("moderation.invalid_status_filter").arg("status", other),
+            ));
+        }
+    };
+    let limit = params.limit.unwrap_or(50).clamp(1, 200);
+    let offset = params.offset.unwrap_or(0).max(0);
+    let (items, total) = moderation::list_results(&state.pool, pending_only, limit, offset).await?;
+    Ok(Json(ModerationResultListResponse {
+        items,
+        total,
+        limit,
+        offset,
+    }))
+}
+
+/// Body for reviewing a result.
+#[derive(Debug, Default, Deserialize)]
+pub struct ReviewRequest {
+    pub note: Option<String>,
+}
+
+/// `POST /admin/moderation/results/{id}/review` — mark a result reviewed.
+pub async fn review_result_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+    Json(body): Json<ReviewRequest>,
+) -> AppResult<Json<ModerationResult>> {
+    let reviewer = parse_user_id(&user.0.sub)?;
+    let result = moderation::review_result(
+        &state.pool,
+        &parse_uuid(&id)?,
+        &reviewer,
+        body.note.as_deref(),
+    )
+    .await?;
+    Ok(Json(result))
+}
+
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
@@ -0,0 +1,365 @@
+//! Notes request handlers.
+//!
+//! Creating a note, or changing its title or body, re-indexes it in the
+//! background so it becomes searchable via `GET /notes/search`.
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::notes::NoteRow;
+use nize_core::notes::search::NoteSearchHit;
+use nize_core::quotas::{self, Quota};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::events::{KIND_INGEST_COMPLETED, ServerEvent};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+
+/// Maximum length of a title derived from the note body.
+const MAX_DERIVED_TITLE_CHARS: usize = 120;
+
+/// A note.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Note {
+    pub id: Uuid,
+    pub title: String,
+    pub body: String,
+    pub tags: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<&NoteRow> for Note {
+    fn from(row: &NoteRow) -> Self {
+        Self {
+            id: row.id,
+            title: row.title.clone(),
+            body: row.body.clone(),
+            tags: row.tags.clone(),
+            created_at: row.created_at.to_rfc3339(),
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `GET /notes`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct NoteListResponse {
+    pub items: Vec<Note>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// A note chunk matching a search query.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSearchResult {
+    pub note_id: Uuid,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub content: String,
+    pub similarity: f64,
+}
+
+impl From<&NoteSearchHit> for NoteSearchResult {
+    fn from(h: &NoteSearchHit) -> Self {
+        Self {
+            note_id: h.note_id,
+            title: h.title.clone(),
+            tags: h.tags.clone(),
+            content: h.content.clone(),
+            similarity: h.similarity,
+        }
+    }
+}
+
+/// Body of `GET /notes/search`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct NoteSearchResponse {
+    pub results: Vec<NoteSearchResult>,
+    /// Results rendered as a markdown block for chat context.
+    pub context: String,
+}
+
+/// Query params for listing notes.
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub tag: Option<String>,
+}
+
+/// `GET /notes` — list notes for the authenticated user.
+pub async fn list_notes_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Query(params): Query<ListParams>,
+) -> AppResult<Json<NoteListResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let limit = params.limit.unwrap_or(20).min(100);
+    let offset = params.offset.unwrap_or(0);
+    let tag = params.tag.map(|t| t.trim().to_lowercase());
+
+    let (rows, total) =
+        nize_core::notes::list_notes(&state.pool, &user_id, tag.as_deref(), limit, offset).await?;
+
+    Ok(Json(NoteListResponse {
+        items: rows.iter().map(Note::from).collect(),
+        total,
+        limit,
+        offset,
+    }))
+}
+
+/// Request body for creating a note.
+#[derive(Debug, Deserialize)]
+pub struct CreateNoteBody {
+    pub title: Option<String>,
+    pub body: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// `POST /notes` — create a note.
+pub async fn create_note_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Json(body): Json<CreateNoteBody>,
+) -> AppResult<(StatusCode, Json<Note>)> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    if body.body.trim().is_empty() {
+        return Err(AppError::validation(Message::new("notes.body_required")));
+    }
+    let title = match body.title.as_deref() {
+        Some(title) => title.trim().to_string(),
+        None => title_from_body(&body.body),
+    };
+
+    let tags = nize_core::tags::normalize_names(&body.tags)?;
+
+    let bytes = (title.len() + body.body.len()) as i64;
+    quotas::ensure_within(
+        &state.pool,
+        &state.config_cache,
+        &user_id,
+        &[(Quota::Documents, 1), (Quota::StorageBytes, bytes)],
+    )
+    .await?;
+
+    let row =
+        nize_core::notes::create_note(&state.pool, &user_id, &title, &body.body, &tags).await?;
+    spawn_index(&state, &row);
+
+    Ok((StatusCode::CREATED, Json(Note::from(&row))))
+}
+
+/// `GET /notes/{id}` — get a note.
+pub async fn get_note_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Note>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let note_id = parse_uuid(&id)?;
+
+    let row = nize_core::notes::get_note(&state.pool, &user_id, &note_id).await?;
+
+    Ok(Json(Note::from(&row)))
+}
+
+/// Request body for updating a note. Omitted fields are left unchanged.
+#[derive(Debug, Deserialize)]
+pub struct UpdateNoteBody {
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+/// `PATCH /notes/{id}` — update a note's title, body or tags.
+pub async fn update_note_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateNoteBody>,
+) -> AppResult<Json<Note>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let note_id = parse_uuid(&id)?;
+
+    if body.body.as_deref().is_some_and(|b| b.trim().is_empty()) {
+        return Err(AppError::validation(Message::new("notes.body_empty")));
+    }
+    let tags = body
+        .tags
+        .as_deref()
+        .map(nize_core::tags::normalize_names)
+        .transpose()?;
+
+    if body.title.is_some() || body.body.is_some() {
+        let current = nize_core::notes::get_note(&state.pool, &user_id, &note_id).await?;
+        let title = body
+            .title
+            .as_deref()
+            .map_or(current.title.len(), |t| t.trim().len());
+        let text = body.body.as_deref().map_or(current.body.len(), str::len);
+        let added = (title + text) as i64 - (current.title.len() + current.body.len()) as i64;
+        quotas::ensure_within(
+            &state.pool,
+            &state.config_cache,
+            &user_id,
+            &[(Quota::StorageBytes, added)],
+        )
+        .await?;
+    }
+
+    let row = nize_core::notes::update_note(
+        &state.pool,
+        &user_id,
+        &note_id,
+        body.title.as_deref().map(str::trim),
+        body.body.as_deref(),
+        tags.as_deref(),
+    )
+    .await?;
+
+    // Tags are not part of the embedded text; only content changes re-index.
+    if body.title.is_some() || body.body.is_some() {
+        spawn_index(&state, &row);
+    }
+
+    Ok(Json(Note::from(&row)))
+}
+
+/// `DELETE /notes/{id}` — delete a note and its embeddings.
+pub async fn delete_note_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let note_id = parse_uuid(&id)?;
+
+    let deleted = nize_core::notes::delete_note(&state.pool, &user_id, &note_id).await?;
+
+    if deleted {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(Message::new("notes.not_found")))
+    }
+}
+
+/// Query params for searching notes.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchParams {
+    pub q: String,
+    pub limit: Option<i64>,
+    pub tag: Option<String>,
+    pub min_similarity: Option<f64>,
+}
+
+/// `GET /notes/search` — semantic search over the user's notes.
+pub async fn search_notes_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Query(params): Query<SearchParams>,
+) -> AppResult<Json<NoteSearchResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    if params.q.trim().is_empty() {
+        return Err(AppError::validation(Message::new("request.q_required")));
+    }
+    let limit = params
+        .limit
+        .unwrap_or(nize_core::notes::search::DEFAULT_TOP_K)
+        .clamp(1, 50);
+    let tag = params.tag.map(|t| t.trim().to_lowercase());
+    let min_similarity = params.min_similarity.unwrap_or(0.0).clamp(0.0, 1.0);
+
+    let hits = nize_core::notes::search::search_notes(
+        &state.pool,
+        &state.config_cache,
+        &state.config.mcp_encryption_key,
+        &user_id,
+        &params.q,
+        tag.as_deref(),
+        limit,
+        min_similarity,
+    )
+    .await
+    .map_err(|e| AppError::Internal(format!("Note search error: {e}")))?;
+
+    Ok(Json(NoteSearchResponse {
+        results: hits.iter().map(NoteSearchResult::from).collect(),
+        context: nize_core::notes::search::format_context(&hits),
+    }))
+}
+
+/// Re-chunk and re-embed a note without blocking the response, publishing
+/// an `ingest.completed` event when done.
+fn spawn_index(state: &AppState, note: &NoteRow) {
+    let state = state.clone();
+    let (note_id, user_id, title) = (note.id, note.user_id, note.title.clone());
+    tokio::spawn(async move {
+        match nize_core::embedding::indexer::embed_note(
+            &state.pool,
+            &state.config_cache,
+            &note_id,
+            &state.config.mcp_encryption_key,
+        )
+        .await
+        {
+            Ok(chunks) => state.events.publish(ServerEvent {
+                user_id: Some(user_id),
+                kind: KIND_INGEST_COMPLETED.into(),
+                title: "Note indexed".into(),
+                body: title,
+                payload: serde_json::json!({ "noteId": note_id, "chunks": chunks }),
+            }),
+            Err(e) => tracing::warn!("Failed to embed note {note_id}: {e}"),
+        }
+    });
+}
+
+/// Default title: the first non-empty line with leading `#`s stripped,
+/// capped at [`MAX_DERIVED_TITLE_CHARS`].
+fn title_from_body(body: &str) -> String {
+    body.lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or_default()
+        .chars()
+        .take(MAX_DERIVED_TITLE_CHARS)
+        .collect()
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn title_from_body_uses_first_heading_or_line() {
+        assert_eq!(title_from_body("\n# Trip plan\n\nboots"), "Trip plan");
+        assert_eq!(title_from_body("buy milk\nand eggs"), "buy milk");
+        assert_eq!(title_from_body("   "), "");
+        assert_eq!(
+            title_from_body(&"x".repeat(500)).len(),
+            MAX_DERIVED_TITLE_CHARS
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Notification request handlers.
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::notifications::NotificationRow;
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+
+/// A notification.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub id: Uuid,
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+    pub payload: serde_json::Value,
+    pub read_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<&NotificationRow> for Notification {
+    fn from(row: &NotificationRow) -> Self {
+        Self {
+            id: row.id,
+            kind: row.kind.clone(),
+            title: row.title.clone(),
+            body: row.body.clone(),
+            payload: row.payload.clone(),
+            read_at: row.read_at.map(|t| t.to_rfc3339()),
+            created_at: row.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `GET /notifications`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationListResponse {
+    pub items: Vec<Notification>,
+    pub unread_count: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Query params for listing notifications.
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    #[serde(default)]
+    pub unread: bool,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// `GET /notifications` — list the user's notifications, newest first.
+pub async fn list_notifications_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Query(params): Query<ListParams>,
+) -> AppResult<Json<NotificationListResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let limit = params.limit.unwrap_or(20).min(100);
+    let offset = params.offset.unwrap_or(0);
+
+    let (rows, unread_count) = nize_core::notifications::list_notifications(
+        &state.pool,
+        &user_id,
+        params.unread,
+        limit,
+        offset,
+    )
+    .await?;
+
+    Ok(Json(NotificationListResponse {
+        items: rows.iter().map(Notification::from).collect(),
+        unread_count,
+        limit,
+        offset,
+    }))
+}
+
+/// `POST /notifications/{id}/read` — mark a notification as read.
+pub async fn mark_read_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let notification_id = parse_uuid(&id)?;
+
+    if nize_core::notifications::mark_read(&state.pool, &user_id, &notification_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(Message::new("notifications.not_found")))
+    }
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
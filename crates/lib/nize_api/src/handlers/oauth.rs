@@ -4,10 +4,12 @@
 
 use axum::extract::{Query, State};
 use axum::http::StatusCode;
-use axum::response::{IntoResponse, Response};
+use axum::response::Html;
 
 use crate::AppState;
 use crate::error::AppError;
+use crate::i18n::Message;
+use nize_core::mcp::secrets::{self, SecretBinding, ServerKey};
 
 /// Query parameters for OAuth callback.
 #[derive(serde::Deserialize)]
@@ -22,7 +24,7 @@ pub struct OAuthCallbackParams {
 pub async fn oauth_callback_handler(
     State(state): State<AppState>,
     Query(params): Query<OAuthCallbackParams>,
-) -> Response {
+) -> (StatusCode, Html<String>) {
     match handle_callback_inner(&state, params).await {
         Ok(server_id) => {
             // Return HTML that closes the window and signals success to the opener
@@ -43,12 +45,7 @@ pub async fn oauth_callback_handler(
 </body></html>"#,
                 server_id,
             );
-            (
-                StatusCode::OK,
-                [("content-type", "text/html; charset=utf-8")],
-                html,
-            )
-                .into_response()
+            (StatusCode::OK, Html(html))
         }
         Err(e) => {
             let html = format!(
@@ -68,12 +65,7 @@ pub async fn oauth_callback_handler(
 </body></html>"#,
                 e, e,
             );
-            (
-                StatusCode::BAD_REQUEST,
-                [("content-type", "text/html; charset=utf-8")],
-                html,
-            )
-                .into_response()
+            (StatusCode::BAD_REQUEST, Html(html))
         }
     }
 }
@@ -85,22 +77,27 @@ async fn handle_callback_inner(
 ) -> Result<String, AppError> {
     // Check for OAuth error response
     if let Some(error) = params.error {
-        return Err(AppError::Validation(format!(
-            "OAuth provider returned error: {error}"
-        )));
+        return Err(AppError::validation(
+            Message::new("oauth.provider_error").arg("error", error),
+        ));
     }
 
     let code = params
         .code
-        .ok_or_else(|| AppError::Validation("Missing authorization code".into()))?;
+        .ok_or_else(|| AppError::validation(Message::new("oauth.missing_code")))?;
     let state_param = params
         .state
-        .ok_or_else(|| AppError::Validation("Missing state parameter".into()))?;
+        .ok_or_else(|| AppError::validation(Message::new("oauth.missing_state")))?;
 
     // Look up pending PKCE state
-    let pending = state.oauth_state.take(&state_param).ok_or_else(|| {
-        AppError::Validation("Invalid or expired OAuth state — please retry authorization".into())
-    })?;
+    let pending = nize_core::mcp::oauth::take_pending_state(
+        &state.pool,
+        &state_param,
+        &state.config.mcp_encryption_key,
+    )
+    .await?
+    .ok_or_else(|| AppError::validation(Message::new("oauth.invalid_state")))?;
+    let client_secret = oauth_client_secret(state, &pending.server_id).await?;
 
     // Parse OAuth config to get token_url and client_id
     let oauth_config: nize_core::models::mcp::OAuthConfig =
@@ -111,7 +108,7 @@ async fn handle_callback_inner(
     let token_resp = nize_core::mcp::oauth::exchange_authorization_code(
         &oauth_config.token_url,
         &oauth_config.client_id,
-        &pending.client_secret,
+        &client_secret,
         &code,
         &pending.redirect_uri,
         &pending.pkce_verifier,
@@ -119,23 +116,28 @@ async fn handle_callback_inner(
     .await
     .map_err(|e| AppError::Internal(format!("Token exchange failed: {e}")))?;
 
-    // Encrypt tokens
+    // Encrypt tokens, each bound to this user+server row
     let key = &state.config.mcp_encryption_key;
+    let (user_id, server_id) = (pending.user_id.as_str(), pending.server_id.as_str());
 
     let id_token_encrypted = match &token_resp.id_token {
         Some(t) => Some(
-            nize_core::mcp::secrets::encrypt(t, key)
+            secrets::encrypt_bound(t, key, &SecretBinding::id_token(user_id, server_id))
                 .map_err(|e| AppError::Internal(format!("Encrypt id_token: {e}")))?,
         ),
         None => None,
     };
 
-    let access_token_encrypted = nize_core::mcp::secrets::encrypt(&token_resp.access_token, key)
-        .map_err(|e| AppError::Internal(format!("Encrypt access_token: {e}")))?;
+    let access_token_encrypted = secrets::encrypt_bound(
+        &token_resp.access_token,
+        key,
+        &SecretBinding::access_token(user_id, server_id),
+    )
+    .map_err(|e| AppError::Internal(format!("Encrypt access_token: {e}")))?;
 
     let refresh_token_encrypted = match &token_resp.refresh_token {
         Some(t) => Some(
-            nize_core::mcp::secrets::encrypt(t, key)
+            secrets::encrypt_bound(t, key, &SecretBinding::refresh_token(user_id, server_id))
                 .map_err(|e| AppError::Internal(format!("Encrypt refresh_token: {e}")))?,
         ),
         None => None,
@@ -203,6 +205,27 @@ async fn handle_callback_inner(
     Ok(pending.server_id)
 }
 
+/// Load and decrypt a server's OAuth client secret.
+pub(crate) async fn oauth_client_secret(
+    state: &AppState,
+    server_id: &str,
+) -> Result<String, AppError> {
+    let (encrypted_secret, key_id) =
+        nize_core::mcp::queries::get_server_secrets(&state.pool, server_id)
+            .await?
+            .and_then(|row| Some((row.oauth_client_secret_encrypted?, row.encryption_key_id)))
+            .ok_or_else(|| AppError::validation(Message::new("oauth.client_secret_missing")))?;
+
+    secrets::decrypt_row(
+        &state.pool,
+        &encrypted_secret,
+        &ServerKey::from_id(&state.config.mcp_encryption_key, &key_id).key,
+        &SecretBinding::oauth_client_secret(server_id),
+    )
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to decrypt client secret: {e}")))
+}
+
 /// Discover and store tools from an OAuth-authenticated MCP server.
 ///
 /// Called after a successful OAuth token exchange. Loads the server config,
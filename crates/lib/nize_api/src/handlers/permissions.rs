@@ -1,85 +1,302 @@
 // @awa-component: PLAN-017-PermissionsHandler
 //
-//! Permission request handlers — demo stubs.
+//! Permission request handlers — grants and share links.
 
 use axum::Json;
-use axum::extract::Path;
+use axum::extract::{Path, State};
 use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
+use uuid::Uuid;
 
-use crate::error::AppResult;
+use nize_core::permissions::{self, GrantRow, PermissionLevel, ResourceType, ShareLinkRow};
 
-/// `POST /permissions/{resourceType}/{resourceId}/grants` — create grant (demo).
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+
+/// Request body for `POST /permissions/{resourceType}/{resourceId}/grants`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateGrantBody {
+    pub email: String,
+    pub level: PermissionLevel,
+}
+
+/// Request body for `POST /permissions/{resourceType}/{resourceId}/links`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateLinkBody {
+    pub level: PermissionLevel,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Request body for `PATCH /permissions/links/{linkId}`. An explicit
+/// `"expiresAt": null` removes the expiry; an absent one keeps it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateLinkBody {
+    pub level: Option<PermissionLevel>,
+    #[serde(default, deserialize_with = "present")]
+    pub expires_at: Option<Option<DateTime<Utc>>>,
+}
+
+/// Deserialize a field that is present (possibly `null`) as `Some`.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// A share link as returned by the API, with its URL.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ShareLink {
+    #[serde(flatten)]
+    pub link: ShareLinkRow,
+    pub url: String,
+}
+
+impl ShareLink {
+    pub(crate) fn new(state: &AppState, link: ShareLinkRow) -> Self {
+        let url = share_url(state.config.chat_url.as_deref(), &link.token);
+        Self { link, url }
+    }
+}
+
+/// Body of the grant listings.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct GrantListResponse {
+    pub grants: Vec<GrantRow>,
+}
+
+/// Body of the share link listings.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct LinkListResponse {
+    pub links: Vec<ShareLink>,
+}
+
+/// Body of `GET /permissions/shared/{token}`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedResourceResponse {
+    pub resource_type: ResourceType,
+    pub resource_id: Uuid,
+    pub level: PermissionLevel,
+}
+
+/// Body of `GET /permissions/shared/{token}/messages`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SharedConversationResponse {
+    pub id: Uuid,
+    pub title: String,
+    pub level: PermissionLevel,
+    /// Stored message objects, oldest first.
+    pub messages: Vec<serde_json::Value>,
+}
+
+/// `POST /permissions/{resourceType}/{resourceId}/grants` — share a resource
+/// with a person, or change the level of their grant.
 pub async fn create_grant_handler(
-    Path((_resource_type, _resource_id)): Path<(String, String)>,
-    Json(_body): Json<serde_json::Value>,
-) -> AppResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "id": "00000000-0000-0000-0000-000000000001",
-        "granterId": "00000000-0000-0000-0000-000000000100",
-        "granteeId": "00000000-0000-0000-0000-000000000200",
-        "granteeEmail": "demo@example.com",
-        "resourceType": "conversation",
-        "resourceId": "00000000-0000-0000-0000-000000000300",
-        "level": "view",
-        "cascade": false,
-        "createdAt": "2026-02-16T00:00:00Z"
-    })))
-}
-
-/// `GET /permissions/{resourceType}/{resourceId}/grants` — list grants (demo).
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((resource_type, resource_id)): Path<(String, String)>,
+    Json(body): Json<CreateGrantBody>,
+) -> AppResult<Json<GrantRow>> {
+    let grant = permissions::create_grant(
+        &state.pool,
+        &parse_user_id(&user.0.sub)?,
+        resource_type.parse()?,
+        &parse_uuid(&resource_id)?,
+        &body.email,
+        body.level,
+    )
+    .await?;
+    Ok(Json(grant))
+}
+
+/// `GET /permissions/{resourceType}/{resourceId}/grants` — list grants.
 pub async fn list_grants_handler(
-    Path((_resource_type, _resource_id)): Path<(String, String)>,
-) -> AppResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "grants": []
-    })))
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((resource_type, resource_id)): Path<(String, String)>,
+) -> AppResult<Json<GrantListResponse>> {
+    let grants = permissions::list_grants(
+        &state.pool,
+        &parse_user_id(&user.0.sub)?,
+        resource_type.parse()?,
+        &parse_uuid(&resource_id)?,
+    )
+    .await?;
+    Ok(Json(GrantListResponse { grants }))
 }
 
-/// `DELETE /permissions/grants/{grantId}` — revoke grant (demo).
-pub async fn revoke_grant_handler(Path(_grant_id): Path<String>) -> StatusCode {
-    StatusCode::NO_CONTENT
+/// `DELETE /permissions/grants/{grantId}` — revoke a grant.
+pub async fn revoke_grant_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(grant_id): Path<String>,
+) -> AppResult<StatusCode> {
+    permissions::revoke_grant(
+        &state.pool,
+        Some(&parse_user_id(&user.0.sub)?),
+        &parse_uuid(&grant_id)?,
+    )
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
-/// `POST /permissions/{resourceType}/{resourceId}/links` — create share link (demo).
+/// `POST /permissions/{resourceType}/{resourceId}/links` — create a share
+/// link.
 pub async fn create_link_handler(
-    Path((_resource_type, _resource_id)): Path<(String, String)>,
-    Json(_body): Json<serde_json::Value>,
-) -> AppResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "id": "00000000-0000-0000-0000-000000000001",
-        "ownerId": "00000000-0000-0000-0000-000000000100",
-        "resourceType": "conversation",
-        "resourceId": "00000000-0000-0000-0000-000000000300",
-        "token": "demo-share-token",
-        "level": "view",
-        "cascade": false,
-        "createdAt": "2026-02-16T00:00:00Z",
-        "url": "https://nize.local/permissions/shared/demo-share-token"
-    })))
-}
-
-/// `GET /permissions/{resourceType}/{resourceId}/links` — list share links (demo).
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((resource_type, resource_id)): Path<(String, String)>,
+    Json(body): Json<CreateLinkBody>,
+) -> AppResult<Json<ShareLink>> {
+    let link = permissions::create_link(
+        &state.pool,
+        &parse_user_id(&user.0.sub)?,
+        resource_type.parse()?,
+        &parse_uuid(&resource_id)?,
+        body.level,
+        body.expires_at,
+    )
+    .await?;
+    Ok(Json(ShareLink::new(&state, link)))
+}
+
+/// `GET /permissions/{resourceType}/{resourceId}/links` — list share links.
 pub async fn list_links_handler(
-    Path((_resource_type, _resource_id)): Path<(String, String)>,
-) -> AppResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "links": []
-    })))
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((resource_type, resource_id)): Path<(String, String)>,
+) -> AppResult<Json<LinkListResponse>> {
+    let links = permissions::list_links(
+        &state.pool,
+        &parse_user_id(&user.0.sub)?,
+        resource_type.parse()?,
+        &parse_uuid(&resource_id)?,
+    )
+    .await?;
+    let links = links
+        .into_iter()
+        .map(|l| ShareLink::new(&state, l))
+        .collect();
+    Ok(Json(LinkListResponse { links }))
 }
 
-/// `DELETE /permissions/links/{linkId}` — revoke share link (demo).
-pub async fn revoke_link_handler(Path(_link_id): Path<String>) -> StatusCode {
-    StatusCode::NO_CONTENT
+/// `PATCH /permissions/links/{linkId}` — change a link's level or expiry;
+/// its token and URL stay the same.
+pub async fn update_link_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(link_id): Path<String>,
+    Json(body): Json<UpdateLinkBody>,
+) -> AppResult<Json<ShareLink>> {
+    let link = permissions::update_link(
+        &state.pool,
+        &parse_user_id(&user.0.sub)?,
+        &parse_uuid(&link_id)?,
+        body.level,
+        body.expires_at,
+    )
+    .await?;
+    Ok(Json(ShareLink::new(&state, link)))
 }
 
-/// `GET /permissions/shared/{token}` — access shared resource (demo).
+/// `DELETE /permissions/links/{linkId}` — revoke a share link.
+pub async fn revoke_link_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(link_id): Path<String>,
+) -> AppResult<StatusCode> {
+    permissions::revoke_link(
+        &state.pool,
+        Some(&parse_user_id(&user.0.sub)?),
+        &parse_uuid(&link_id)?,
+    )
+    .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /permissions/shared/{token}` — what a share link grants. 404 for
+/// unknown and expired links.
 pub async fn access_shared_handler(
-    Path(_token): Path<String>,
-) -> AppResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "resourceType": "conversation",
-        "resourceId": "00000000-0000-0000-0000-000000000300",
-        "level": "view",
-        "cascade": false
-    })))
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> AppResult<Json<SharedResourceResponse>> {
+    let link = permissions::resolve_link(&state.pool, &token).await?;
+    Ok(Json(SharedResourceResponse {
+        resource_type: link.resource_type,
+        resource_id: link.resource_id,
+        level: link.level,
+    }))
+}
+
+/// `GET /permissions/shared/{token}/messages` — read a conversation through
+/// a share link (`view` and up).
+pub async fn shared_messages_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> AppResult<Json<SharedConversationResponse>> {
+    let link = permissions::require_link_level(&state.pool, &token, PermissionLevel::View).await?;
+    if link.resource_type != ResourceType::Conversation {
+        return Err(AppError::not_found(Message::new(
+            "permissions.link_not_for_conversation",
+        )));
+    }
+    let conversation =
+        nize_core::conversations::get_conversation_by_id(&state.pool, &link.resource_id).await?;
+    let messages = nize_core::conversations::get_messages(&state.pool, &link.resource_id)
+        .await?
+        .into_iter()
+        .map(|m| m.message_data)
+        .collect();
+    Ok(Json(SharedConversationResponse {
+        id: conversation.id,
+        title: conversation.title,
+        level: link.level,
+        messages,
+    }))
+}
+
+/// URL of a share link: on the web app when its URL is configured.
+fn share_url(chat_url: Option<&str>, token: &str) -> String {
+    let base = chat_url.unwrap_or_default().trim_end_matches('/');
+    format!("{base}/api/permissions/shared/{token}")
+}
+
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn update_body_tells_null_from_absent_expiry() {
+        let body: UpdateLinkBody = serde_json::from_str(r#"{"level":"execute"}"#).unwrap();
+        assert_eq!(body.level, Some(PermissionLevel::Execute));
+        assert_eq!(body.expires_at, None);
+        let body: UpdateLinkBody = serde_json::from_str(r#"{"expiresAt":null}"#).unwrap();
+        assert_eq!(body.expires_at, Some(None));
+    }
+
+    #[test]
+    fn share_urls_point_at_the_api() {
+        assert_eq!(
+            share_url(Some("https://nize.example.com/"), "abc"),
+            "https://nize.example.com/api/permissions/shared/abc"
+        );
+        assert_eq!(share_url(None, "abc"), "/api/permissions/shared/abc");
+    }
 }
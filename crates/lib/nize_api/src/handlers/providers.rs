@@ -0,0 +1,210 @@
+//! Provider credential test handler.
+
+use axum::Json;
+use axum::extract::State;
+use futures_util::future::join_all;
+use nize_core::config::resolver;
+use nize_core::embedding::config::EmbeddingConfig;
+use nize_core::provider_check::{self, Api, CheckResult, CheckTarget};
+use nize_core::providers;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::services::config;
+
+/// LLM providers: name, API key config key, env fallback, API.
+const LLM_PROVIDERS: &[(&str, &str, &str, Api)] = &[
+    (
+        "anthropic",
+        "agent.apiKey.anthropic",
+        "ANTHROPIC_API_KEY",
+        Api::Anthropic,
+    ),
+    (
+        "openai",
+        "agent.apiKey.openai",
+        "OPENAI_API_KEY",
+        Api::OpenAi,
+    ),
+    (
+        "google",
+        "agent.apiKey.google",
+        "GOOGLE_GENERATIVE_AI_API_KEY",
+        Api::Google,
+    ),
+];
+
+/// Name selecting the embedding provider check.
+const EMBEDDING: &str = "embedding";
+
+/// Request body for `POST /admin/providers/test`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProviderTestRequest {
+    /// Providers to check (`anthropic`, `openai`, `google`, the name of an
+    /// OpenAI-compatible provider, `embedding`); all when omitted.
+    pub providers: Option<Vec<String>>,
+}
+
+/// Result of one provider check.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderTestResult {
+    pub provider: String,
+    /// `llm` or `embedding`.
+    pub kind: &'static str,
+    /// Model checked for availability, if one is configured.
+    pub model: Option<String>,
+    #[serde(flatten)]
+    pub result: CheckResult,
+}
+
+/// Body of `POST /admin/providers/test`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ProviderTestResponse {
+    pub results: Vec<ProviderTestResult>,
+}
+
+/// `POST /admin/providers/test` — verify the system LLM and embedding
+/// credentials with a model listing per provider, including the
+/// OpenAI-compatible providers with chat models. User overrides are not
+/// consulted, and nothing from the providers' responses is stored.
+pub async fn test_providers_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ProviderTestRequest>,
+) -> AppResult<Json<ProviderTestResponse>> {
+    let selected = |name: &str| {
+        request
+            .providers
+            .as_ref()
+            .is_none_or(|p| p.iter().any(|s| s == name))
+    };
+    let compatible: Vec<_> = providers::endpoints(
+        &state.pool,
+        &state.config_cache,
+        &state.config.mcp_encryption_key,
+    )
+    .await
+    .into_iter()
+    .filter(|e| !e.provider.models.is_empty())
+    .collect();
+    if let Some(unknown) = request.providers.iter().flatten().find(|p| {
+        p.as_str() != EMBEDDING
+            && !LLM_PROVIDERS.iter().any(|(name, ..)| name == p)
+            && !compatible.iter().any(|e| &e.provider.name == *p)
+    }) {
+        return Err(AppError::validation(
+            Message::new("providers.unknown_provider").arg("provider", unknown),
+        ));
+    }
+
+    // `agent.model.name` is `provider:model`.
+    let chat_model =
+        resolver::get_system_value(&state.pool, &state.config_cache, "agent.model.name")
+            .await
+            .unwrap_or_default();
+    let chat_model = chat_model.split_once(':');
+
+    let mut checks = Vec::new();
+    for &(name, key, env, api) in LLM_PROVIDERS.iter().filter(|(name, ..)| selected(name)) {
+        let api_key = config::decrypt_system_secret_value(
+            &state.pool,
+            key,
+            &state.config.mcp_encryption_key,
+            Some(env),
+        )
+        .await?;
+        let base_url = resolver::get_system_value(
+            &state.pool,
+            &state.config_cache,
+            &format!("agent.baseUrl.{name}"),
+        )
+        .await
+        .ok()
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| api.default_base_url().to_string());
+        let model = chat_model
+            .filter(|(provider, _)| *provider == name)
+            .map(|(_, model)| model.to_string());
+        checks.push((
+            name.to_string(),
+            "llm",
+            Some(CheckTarget {
+                api,
+                base_url,
+                api_key,
+                auth_header: None,
+                model,
+            }),
+        ));
+    }
+    for endpoint in compatible.iter().filter(|e| selected(&e.provider.name)) {
+        let name = &endpoint.provider.name;
+        let model = chat_model
+            .filter(|(provider, _)| provider == name)
+            .map(|(_, model)| model.to_string());
+        checks.push((
+            name.clone(),
+            "llm",
+            Some(CheckTarget::compatible(endpoint, model)),
+        ));
+    }
+
+    if selected(EMBEDDING) {
+        let embedding = EmbeddingConfig::resolve(
+            &state.pool,
+            &state.config_cache,
+            &state.config.mcp_encryption_key,
+        )
+        .await
+        .map_err(|e| AppError::Internal(format!("Embedding config error: {e}")))?;
+        // The local provider runs in-process; there is nothing to call.
+        let target = match embedding.provider.as_str() {
+            "openai" => Some(CheckTarget {
+                api: Api::OpenAi,
+                base_url: Api::OpenAi.default_base_url().to_string(),
+                api_key: embedding.openai_api_key,
+                auth_header: None,
+                model: Some(embedding.active_model),
+            }),
+            "ollama" => Some(CheckTarget {
+                api: Api::Ollama,
+                base_url: embedding.ollama_base_url,
+                api_key: None,
+                auth_header: None,
+                model: Some(embedding.active_model),
+            }),
+            _ => embedding
+                .compatible
+                .as_ref()
+                .map(|endpoint| CheckTarget::compatible(endpoint, Some(embedding.active_model))),
+        };
+        checks.push((embedding.provider, "embedding", target));
+    }
+
+    let client = reqwest::Client::new();
+    let results = join_all(checks.into_iter().map(|(provider, kind, target)| {
+        let client = &client;
+        async move {
+            let result = match &target {
+                Some(target) => provider_check::check(client, target).await,
+                None => CheckResult {
+                    ok: true,
+                    ..Default::default()
+                },
+            };
+            ProviderTestResult {
+                provider,
+                kind,
+                model: target.and_then(|t| t.model),
+                result,
+            }
+        }
+    }))
+    .await;
+
+    Ok(Json(ProviderTestResponse { results }))
+}
@@ -0,0 +1,204 @@
+//! Reply regeneration handlers: re-run an assistant turn with other
+//! sampling parameters and choose which candidate the conversation shows.
+//!
+//! The reply is generated by the chat app (`POST
+//! {chat_url}/api/chat/regenerate`) from the history before the turn,
+//! authenticated as the caller.
+
+use std::time::Duration;
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::regeneration::{self, CandidateRow, RegenerationParams, Turn};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::handlers::conversations::require_manage;
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::middleware::workspace::ActiveWorkspace;
+use crate::services::auth::generate_access_token;
+use crate::services::cookies::ACCESS_COOKIE;
+
+/// Upper bound on generating one reply. Kept below the access token
+/// lifetime.
+const GENERATE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Response from the chat app's regenerate endpoint.
+#[derive(Debug, Deserialize)]
+struct RegenerateResponse {
+    /// The new assistant UIMessage.
+    message: serde_json::Value,
+}
+
+/// A candidate reply to a turn.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageCandidate {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub message_id: String,
+    /// The candidate's assistant UIMessage.
+    pub message: serde_json::Value,
+    /// Overrides it was generated with; `None` for the original reply.
+    pub params: Option<RegenerationParams>,
+    pub selected: bool,
+    pub created_at: String,
+}
+
+impl From<&CandidateRow> for MessageCandidate {
+    fn from(row: &CandidateRow) -> Self {
+        Self {
+            id: row.id,
+            conversation_id: row.conversation_id,
+            message_id: row.message_id.clone(),
+            message: row.message_data.clone(),
+            params: row
+                .params
+                .clone()
+                .and_then(|v| serde_json::from_value(v).ok()),
+            selected: row.selected,
+            created_at: row.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `GET /conversations/{id}/messages/{messageId}/candidates`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct MessageCandidatesResponse {
+    pub items: Vec<MessageCandidate>,
+}
+
+/// `POST /conversations/{id}/messages/{messageId}/regenerate` — generate
+/// another reply to the turn, optionally with a different temperature,
+/// model or tool usage, and store it as an unselected candidate.
+pub async fn regenerate_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path((id, message_id)): Path<(String, String)>,
+    Json(params): Json<RegenerationParams>,
+) -> AppResult<(StatusCode, Json<MessageCandidate>)> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    let conv_id = parse_uuid(&id)?;
+    let params = params.validate()?;
+    let chat_url = state
+        .config
+        .chat_url
+        .clone()
+        .ok_or_else(|| AppError::SidecarUnavailable("Chat app is not configured".into()))?;
+
+    require_manage(&state, &scope, &conv_id).await?;
+    let messages = regeneration::load_messages(&state.pool, &conv_id).await?;
+    let turn = regeneration::find_turn(&messages, &message_id)?;
+
+    let reply = generate_reply(&state, &user, &chat_url, &conv_id, &turn, &params).await?;
+    let row =
+        regeneration::add_candidate(&state.pool, &conv_id, &message_id, &turn, reply, &params)
+            .await?;
+
+    Ok((StatusCode::CREATED, Json(MessageCandidate::from(&row))))
+}
+
+/// `GET /conversations/{id}/messages/{messageId}/candidates` — the reply's
+/// candidates, the original first.
+pub async fn list_candidates_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path((id, message_id)): Path<(String, String)>,
+) -> AppResult<Json<MessageCandidatesResponse>> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    let conv_id = parse_uuid(&id)?;
+
+    nize_core::conversations::get_conversation(&state.pool, &scope, &conv_id).await?;
+    let rows = regeneration::list_candidates(&state.pool, &conv_id, &message_id).await?;
+
+    Ok(Json(MessageCandidatesResponse {
+        items: rows.iter().map(MessageCandidate::from).collect(),
+    }))
+}
+
+/// `POST /conversations/{id}/messages/{messageId}/candidates/{candidateId}/select`
+/// — make a candidate the reply the conversation shows.
+pub async fn select_candidate_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path((id, message_id, candidate_id)): Path<(String, String, String)>,
+) -> AppResult<Json<MessageCandidate>> {
+    let scope = workspace.scope(parse_user_id(&user.0.sub)?);
+    let conv_id = parse_uuid(&id)?;
+    let candidate_id = parse_uuid(&candidate_id)?;
+
+    require_manage(&state, &scope, &conv_id).await?;
+    let row =
+        regeneration::select_candidate(&state.pool, &conv_id, &message_id, &candidate_id).await?;
+
+    Ok(Json(MessageCandidate::from(&row)))
+}
+
+/// Generate a reply to `turn` through the chat app.
+async fn generate_reply(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    chat_url: &str,
+    conv_id: &Uuid,
+    turn: &Turn,
+    params: &RegenerationParams,
+) -> AppResult<serde_json::Value> {
+    let claims = &user.0;
+    let token = generate_access_token(&claims.sub, &claims.email, &claims.roles, &state.jwt_keys)?;
+    // The stored summary only applies if it covers no more than the history.
+    let summary = nize_core::conversations::get_summary(&state.pool, conv_id)
+        .await?
+        .filter(|s| s.message_count as usize <= turn.history.len())
+        .map(|s| serde_json::json!({ "text": s.summary, "messageCount": s.message_count }));
+
+    let client = reqwest::Client::builder()
+        .timeout(GENERATE_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    let response = client
+        .post(format!("{chat_url}/api/chat/regenerate"))
+        .header(reqwest::header::COOKIE, format!("{ACCESS_COOKIE}={token}"))
+        .json(&serde_json::json!({
+            "conversationId": conv_id,
+            "messages": turn.history,
+            "summary": summary,
+            "temperature": params.temperature,
+            "model": params.model,
+            "toolsEnabled": params.tools_enabled,
+        }))
+        .send()
+        .await
+        .map_err(|e| AppError::SidecarUnavailable(format!("Chat app unreachable: {e}")))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AppError::Internal(format!(
+            "Chat app returned {status}: {body}"
+        )));
+    }
+    let reply = response
+        .json::<RegenerateResponse>()
+        .await
+        .map_err(|e| AppError::Internal(format!("Invalid chat app response: {e}")))?;
+    Ok(reply.message)
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
@@ -0,0 +1,74 @@
+//! Security activity request handlers.
+
+use axum::Json;
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::auth::activity::{self, ActivityRow};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+
+/// A security-relevant event on the account.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityActivity {
+    pub id: Uuid,
+    pub kind: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub details: serde_json::Value,
+    pub created_at: String,
+}
+
+impl From<ActivityRow> for SecurityActivity {
+    fn from(row: ActivityRow) -> Self {
+        Self {
+            id: row.id,
+            kind: row.kind,
+            ip_address: row.ip_address,
+            user_agent: row.user_agent,
+            details: row.details,
+            created_at: row.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `GET /auth/activity`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SecurityActivityListResponse {
+    pub items: Vec<SecurityActivity>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Query params for listing activity.
+#[derive(Debug, Deserialize)]
+pub struct ListParams {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// `GET /auth/activity` — the user's recent security activity, newest
+/// first: sign-ins, MCP token changes and roles granted.
+pub async fn list_activity_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Query(params): Query<ListParams>,
+) -> AppResult<Json<SecurityActivityListResponse>> {
+    let user_id = Uuid::parse_str(&user.0.sub)
+        .map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))?;
+    let limit = params.limit.unwrap_or(50).clamp(1, 200);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let rows = activity::list_activity(&state.pool, &user_id, limit, offset).await?;
+    Ok(Json(SecurityActivityListResponse {
+        items: rows.into_iter().map(SecurityActivity::from).collect(),
+        limit,
+        offset,
+    }))
+}
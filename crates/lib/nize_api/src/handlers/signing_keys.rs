@@ -0,0 +1,70 @@
+//! JWT signing key handlers: the public JWKS and admin key rotation.
+
+use axum::Json;
+use axum::extract::State;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use nize_core::auth::keys::{self, Jwks, SigningKeyRow};
+
+use crate::AppState;
+use crate::error::AppResult;
+
+/// `GET /auth/jwks` — public keys for verifying access tokens.
+pub async fn jwks_handler(State(state): State<AppState>) -> Json<Jwks> {
+    Json(state.jwt_keys.jwks())
+}
+
+/// A signing key, without key material.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningKeyInfo {
+    pub kid: String,
+    pub algorithm: String,
+    /// Signs new tokens; retired keys only verify.
+    pub active: bool,
+    pub created_at: String,
+    pub retired_at: Option<String>,
+    pub verify_until: Option<String>,
+}
+
+impl From<&SigningKeyRow> for SigningKeyInfo {
+    fn from(row: &SigningKeyRow) -> Self {
+        Self {
+            kid: row.kid.clone(),
+            algorithm: row.algorithm.clone(),
+            active: row.retired_at.is_none(),
+            created_at: row.created_at.to_rfc3339(),
+            retired_at: row.retired_at.map(|t| t.to_rfc3339()),
+            verify_until: row.verify_until.map(|t| t.to_rfc3339()),
+        }
+    }
+}
+
+/// Body of `GET /admin/auth/keys`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SigningKeyListResponse {
+    pub items: Vec<SigningKeyInfo>,
+}
+
+/// `GET /admin/auth/keys` — signing keys, newest first.
+pub async fn list_keys_handler(
+    State(state): State<AppState>,
+) -> AppResult<Json<SigningKeyListResponse>> {
+    let rows = keys::list_keys(&state.pool).await?;
+    Ok(Json(SigningKeyListResponse {
+        items: rows.iter().map(SigningKeyInfo::from).collect(),
+    }))
+}
+
+/// `POST /admin/auth/keys/rotate` — create a new active key; the previous
+/// one keeps verifying for the configured overlap.
+pub async fn rotate_key_handler(State(state): State<AppState>) -> AppResult<Json<SigningKeyInfo>> {
+    let row = keys::rotate(
+        &state.pool,
+        &state.jwt_keys,
+        &state.config.mcp_encryption_key,
+    )
+    .await?;
+    Ok(Json(SigningKeyInfo::from(&row)))
+}
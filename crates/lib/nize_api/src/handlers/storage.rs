@@ -0,0 +1,24 @@
+//! Storage handlers.
+
+use axum::Json;
+use axum::extract::State;
+
+use nize_core::storage::{MaintenanceReport, MaintenanceRequest, StorageReport};
+
+use crate::AppState;
+use crate::error::AppResult;
+use crate::services::storage;
+
+/// `GET /admin/storage` — measure storage usage now.
+pub async fn usage_handler(State(state): State<AppState>) -> AppResult<Json<StorageReport>> {
+    Ok(Json(storage::measure(&state).await?))
+}
+
+/// `POST /admin/storage/maintenance` — run the requested maintenance
+/// operations.
+pub async fn maintenance_handler(
+    State(state): State<AppState>,
+    Json(request): Json<MaintenanceRequest>,
+) -> AppResult<Json<MaintenanceReport>> {
+    Ok(Json(storage::maintain(&state, &request).await?))
+}
@@ -0,0 +1,49 @@
+//! Long-poll handler for buffered streams (see [`crate::streams`]).
+
+use std::time::Duration;
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::streams::{MAX_WAIT, Poll};
+
+/// Query params for polling a stream.
+#[derive(Debug, Deserialize)]
+pub struct PollParams {
+    /// Sequence number of the last chunk received; 0 or omitted on the
+    /// first poll.
+    pub after: Option<u64>,
+    /// Seconds to wait for new chunks; defaults to and is capped at 30.
+    pub wait: Option<u64>,
+}
+
+/// `GET /chat/streams/{id}/poll` — chunks after `after`, held open until
+/// some arrive, the stream ends or `wait` elapses. Keep polling with the
+/// last received `seq` until `done` is set.
+pub async fn poll_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+    Query(params): Query<PollParams>,
+) -> AppResult<Json<Poll>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let id = Uuid::parse_str(&id)
+        .map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))?;
+    let wait = params.wait.map(Duration::from_secs).unwrap_or(MAX_WAIT);
+    let poll = state
+        .streams
+        .poll(&id, &user_id, params.after.unwrap_or(0), wait)
+        .await?;
+    Ok(Json(poll))
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
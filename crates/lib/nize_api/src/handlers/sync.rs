@@ -0,0 +1,486 @@
+//! Delta sync handlers for offline-capable clients.
+//!
+//! `GET /sync` pages through changes to the user's personal conversations,
+//! documents and config overrides since a cursor; `POST /sync` applies a
+//! batch of local edits. Each edit names the version it was based on; when
+//! the server copy has moved on, the edit is reported as a conflict with
+//! the server copy instead of being written, unless the batch asks to
+//! overwrite. See [`nize_core::sync`].
+
+use axum::Json;
+use axum::extract::{Query, State};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::config::queries;
+use nize_core::conversations::ToolSelection;
+use nize_core::quotas::{self, Quota};
+use nize_core::sync::{
+    self, ConversationChange, Cursor, Deletion, Entity, Outcome, Precondition, SyncedConfig,
+    SyncedConversation, SyncedDocument,
+};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::handlers::conversations::ensure_message_storage;
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::services::config;
+use crate::services::documents as document_blobs;
+
+/// Most changes accepted in one push.
+const MAX_PUSH_CHANGES: usize = 200;
+
+/// A personal conversation with its messages.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConversation {
+    pub id: Uuid,
+    pub title: String,
+    pub tool_selection: Option<ToolSelection>,
+    pub messages: Vec<serde_json::Value>,
+    pub version: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<SyncedConversation> for SyncConversation {
+    fn from(c: SyncedConversation) -> Self {
+        Self {
+            id: c.row.id,
+            tool_selection: c.row.tool_selection(),
+            title: c.row.title,
+            messages: c.messages,
+            version: c.version.to_string(),
+            created_at: c.row.created_at.to_rfc3339(),
+            updated_at: c.row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// A document's metadata.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncDocument {
+    pub id: Uuid,
+    pub filename: String,
+    pub mime_type: String,
+    pub size: i64,
+    pub version: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<SyncedDocument> for SyncDocument {
+    fn from(d: SyncedDocument) -> Self {
+        Self {
+            id: d.row.id,
+            filename: d.row.filename,
+            mime_type: d.row.mime_type,
+            size: d.row.size_bytes,
+            version: d.version.to_string(),
+            created_at: d.row.created_at.to_rfc3339(),
+            updated_at: d.row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// A config override of the user.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncConfigValue {
+    pub key: String,
+    pub value: String,
+    pub version: String,
+    pub updated_at: String,
+}
+
+impl From<SyncedConfig> for SyncConfigValue {
+    fn from(c: SyncedConfig) -> Self {
+        Self {
+            key: c.key,
+            value: c.value,
+            version: c.version.to_string(),
+            updated_at: c.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// A record deleted since the cursor.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SyncDeletion {
+    pub entity: Entity,
+    pub id: String,
+}
+
+impl From<Deletion> for SyncDeletion {
+    fn from(d: Deletion) -> Self {
+        Self {
+            entity: d.entity,
+            id: d.id,
+        }
+    }
+}
+
+/// Body of `GET /sync`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPullResponse {
+    pub cursor: String,
+    pub has_more: bool,
+    pub reset: bool,
+    pub conversations: Vec<SyncConversation>,
+    pub documents: Vec<SyncDocument>,
+    pub config: Vec<SyncConfigValue>,
+    pub deleted: Vec<SyncDeletion>,
+}
+
+/// The server copy of a record, reported with a conflict.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum SyncRecord {
+    Conversation(SyncConversation),
+    Document(SyncDocument),
+    Config(SyncConfigValue),
+}
+
+/// What became of one change, tagged by `status`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum SyncChangeStatus {
+    /// Written; the record's new version, `None` once deleted.
+    Applied { version: Option<String> },
+    /// Not written: the server copy moved on. `None` when it was deleted.
+    Conflict { server: Option<SyncRecord> },
+    /// Refused, with why.
+    Rejected { error: String },
+}
+
+impl<T: Into<SyncRecord>> From<Outcome<T>> for SyncChangeStatus {
+    fn from(outcome: Outcome<T>) -> Self {
+        match outcome {
+            Outcome::Applied(version) => Self::Applied {
+                version: version.map(|v| v.to_string()),
+            },
+            Outcome::Conflict(server) => Self::Conflict {
+                server: server.map(Into::into),
+            },
+        }
+    }
+}
+
+impl From<SyncedConversation> for SyncRecord {
+    fn from(c: SyncedConversation) -> Self {
+        Self::Conversation(c.into())
+    }
+}
+
+impl From<SyncedDocument> for SyncRecord {
+    fn from(d: SyncedDocument) -> Self {
+        Self::Document(d.into())
+    }
+}
+
+impl From<SyncedConfig> for SyncRecord {
+    fn from(c: SyncedConfig) -> Self {
+        Self::Config(c.into())
+    }
+}
+
+/// The result of one pushed change.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SyncChangeResult {
+    pub entity: Entity,
+    pub id: String,
+    #[serde(flatten)]
+    pub status: SyncChangeStatus,
+}
+
+/// Body of `POST /sync`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SyncPushResponse {
+    pub results: Vec<SyncChangeResult>,
+}
+
+/// Query params for pulling changes.
+#[derive(Debug, Deserialize)]
+pub struct PullParams {
+    /// Cursor from the previous pull; omitted for a full sync.
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// `GET /sync` — changes since `cursor`, oldest first. Keep pulling with
+/// the returned cursor while `hasMore` is set.
+pub async fn pull_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Query(params): Query<PullParams>,
+) -> AppResult<Json<SyncPullResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let cursor = params
+        .cursor
+        .as_deref()
+        .filter(|c| !c.is_empty())
+        .map(Cursor::parse)
+        .transpose()?;
+    let limit = params.limit.unwrap_or(100);
+
+    let changes = sync::pull(&state.pool, &user_id, cursor.as_ref(), limit).await?;
+
+    Ok(Json(SyncPullResponse {
+        cursor: changes.cursor.to_string(),
+        has_more: changes.has_more,
+        reset: changes.reset,
+        conversations: changes.conversations.into_iter().map(Into::into).collect(),
+        documents: changes.documents.into_iter().map(Into::into).collect(),
+        config: changes.config.into_iter().map(Into::into).collect(),
+        deleted: changes.deleted.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// What a client change does.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeOp {
+    Upsert,
+    Delete,
+}
+
+/// A local edit made by the client.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientChange {
+    pub entity: Entity,
+    /// Record id; the key for config.
+    pub id: String,
+    pub op: ChangeOp,
+    /// Version the edit was based on; omitted for records the client
+    /// created.
+    pub base_version: Option<String>,
+    /// Upserts: `{title?, messages?}` for conversations, `{value}` for
+    /// config.
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+/// Request body for pushing changes.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushRequest {
+    pub changes: Vec<ClientChange>,
+    /// `reject` (default) reports conflicting changes without writing
+    /// them; `overwrite` writes them over the server copy.
+    pub on_conflict: Option<String>,
+}
+
+/// `POST /sync` — apply client changes in order and report each one as
+/// `applied`, `conflict` or `rejected`.
+pub async fn push_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Json(body): Json<PushRequest>,
+) -> AppResult<Json<SyncPushResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let overwrite = match body.on_conflict.as_deref() {
+        None | Some("reject") => false,
+        Some("overwrite") => true,
+        Some(other) => {
+            return Err(AppError::validation(
+                Message::new("sync.invalid_on_conflict").arg("value", other),
+            ));
+        }
+    };
+    if body.changes.len() > MAX_PUSH_CHANGES {
+        return Err(AppError::validation(
+            Message::new("sync.too_many_changes").arg("max", MAX_PUSH_CHANGES),
+        ));
+    }
+
+    let mut results = Vec::with_capacity(body.changes.len());
+    for change in &body.changes {
+        let status = match apply(&state, &user_id, &user.0.sub, change, overwrite).await {
+            Ok(status) => status,
+            Err(e) => SyncChangeStatus::Rejected {
+                error: rejection(e)?,
+            },
+        };
+        results.push(SyncChangeResult {
+            entity: change.entity,
+            id: change.id.clone(),
+            status,
+        });
+    }
+
+    Ok(Json(SyncPushResponse { results }))
+}
+
+/// Apply one change.
+async fn apply(
+    state: &AppState,
+    user_id: &Uuid,
+    sub: &str,
+    change: &ClientChange,
+    overwrite: bool,
+) -> AppResult<SyncChangeStatus> {
+    let precondition = match (&change.base_version, overwrite) {
+        (_, true) => Precondition::Any,
+        (Some(version), false) => Precondition::Version(version.parse().map_err(|_| {
+            AppError::validation(Message::new("sync.invalid_base_version").arg("version", version))
+        })?),
+        (None, false) => Precondition::Absent,
+    };
+
+    match (change.entity, change.op) {
+        (Entity::Conversation, op) => {
+            let id = parse_uuid(&change.id)?;
+            let outcome = match op {
+                ChangeOp::Upsert => {
+                    let data: ConversationChange = serde_json::from_value(change.data.clone())
+                        .map_err(|e| {
+                            AppError::validation(Message::new("sync.invalid_data").arg("error", e))
+                        })?;
+                    let current = sync::get_conversation(&state.pool, user_id, &id).await?;
+                    if current.is_none() && precondition.holds(None) {
+                        quotas::ensure_within(
+                            &state.pool,
+                            &state.config_cache,
+                            user_id,
+                            &[(Quota::Conversations, 1)],
+                        )
+                        .await?;
+                    }
+                    if let Some(messages) = &data.messages {
+                        ensure_message_storage(state, user_id, &id, messages).await?;
+                    }
+                    sync::apply_conversation(&state.pool, user_id, &id, precondition, &data).await?
+                }
+                ChangeOp::Delete => {
+                    sync::delete_conversation(&state.pool, user_id, &id, precondition).await?
+                }
+            };
+            Ok(outcome.into())
+        }
+        (Entity::Document, ChangeOp::Upsert) => Err(AppError::validation(Message::new(
+            "sync.documents_not_synced",
+        ))),
+        (Entity::Document, ChangeOp::Delete) => {
+            let id = parse_uuid(&change.id)?;
+            let (outcome, deleted) =
+                sync::delete_document(&state.pool, user_id, &id, precondition).await?;
+            if let Some(row) = deleted {
+                document_blobs::release(state, &row.sha256).await?;
+            }
+            Ok(outcome.into())
+        }
+        (Entity::Config, op) => {
+            let key = change.id.as_str();
+            let def = queries::get_definition(&state.pool, key)
+                .await?
+                .ok_or_else(|| {
+                    AppError::not_found(Message::new("config.key_not_found").arg("key", key))
+                })?;
+            if def.display_type == "secret" {
+                return Err(AppError::validation(
+                    Message::new("sync.secret_not_synced").arg("key", key),
+                ));
+            }
+            let current = sync::get_config(&state.pool, user_id, key).await?;
+            if !precondition.holds(current.as_ref().map(|c| c.version)) {
+                return Ok(Outcome::Conflict(current).into());
+            }
+            match op {
+                ChangeOp::Upsert => {
+                    let value = change
+                        .data
+                        .get("value")
+                        .and_then(|v| v.as_str())
+                        .ok_or_else(|| AppError::validation(Message::new("sync.value_required")))?;
+                    config::update_user_config(
+                        &state.pool,
+                        &state.config_cache,
+                        sub,
+                        key,
+                        value,
+                        &state.config.mcp_encryption_key,
+                    )
+                    .await?;
+                    let version = sync::get_config(&state.pool, user_id, key)
+                        .await?
+                        .map(|c| c.version);
+                    Ok(Outcome::<SyncedConfig>::Applied(version).into())
+                }
+                ChangeOp::Delete => {
+                    config::reset_user_config(&state.pool, &state.config_cache, sub, key).await?;
+                    Ok(Outcome::<SyncedConfig>::Applied(None).into())
+                }
+            }
+        }
+    }
+}
+
+/// The message for a change the server refused; errors that are not the
+/// client's fault abort the push.
+fn rejection(e: AppError) -> AppResult<String> {
+    match e {
+        e @ (AppError::QuotaExceeded(_) | AppError::Localized(..)) => Ok(e.to_string()),
+        e => Err(e),
+    }
+}
+
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a record id into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejection_keeps_client_errors_and_passes_on_server_errors() {
+        assert_eq!(
+            rejection(AppError::validation(Message::new("request.invalid_uuid"))).unwrap(),
+            "Invalid UUID"
+        );
+        assert!(rejection(AppError::Internal("db down".into())).is_err());
+    }
+
+    #[test]
+    fn client_change_parses() {
+        let change: ClientChange = serde_json::from_value(serde_json::json!({
+            "entity": "conversation",
+            "id": "0190a0f0-0000-7000-8000-000000000000",
+            "op": "upsert",
+            "baseVersion": "1234",
+            "data": { "title": "Trip" },
+        }))
+        .unwrap();
+        assert_eq!(change.entity, Entity::Conversation);
+        assert!(matches!(change.op, ChangeOp::Upsert));
+        assert_eq!(change.base_version.as_deref(), Some("1234"));
+    }
+
+    #[test]
+    fn change_results_carry_their_status_inline() {
+        let result = SyncChangeResult {
+            entity: Entity::Config,
+            id: "ui.theme".into(),
+            status: Outcome::<SyncedConfig>::Applied(Some(7)).into(),
+        };
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            serde_json::json!({
+                "entity": "config",
+                "id": "ui.theme",
+                "status": "applied",
+                "version": "7",
+            })
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Tag request handlers.
+//!
+//! Tags are personal: every endpoint is scoped to the authenticated user and
+//! attaching a tag never changes who can see the resource.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::tags::{TagRef, TagResourceType, TagRow, TagWithCount};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+
+/// A tag.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub id: Uuid,
+    pub name: String,
+    pub color: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<&TagRow> for Tag {
+    fn from(row: &TagRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name.clone(),
+            color: row.color.clone(),
+            created_at: row.created_at.to_rfc3339(),
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// A tag with the number of resources it is attached to.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TagWithUsage {
+    #[serde(flatten)]
+    pub tag: Tag,
+    pub usage_count: i64,
+}
+
+impl From<&TagWithCount> for TagWithUsage {
+    fn from(row: &TagWithCount) -> Self {
+        Self {
+            tag: Tag::from(&row.tag),
+            usage_count: row.usage_count,
+        }
+    }
+}
+
+/// Body of `GET /tags`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TagListResponse {
+    pub tags: Vec<TagWithUsage>,
+}
+
+/// Body of `GET /tags/{resourceType}/{resourceId}`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ResourceTagsResponse {
+    pub tags: Vec<Tag>,
+}
+
+/// `GET /tags` — list the user's tags with usage counts.
+pub async fn list_tags_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+) -> AppResult<Json<TagListResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+
+    let rows = nize_core::tags::list_tags(&state.pool, &user_id).await?;
+
+    Ok(Json(TagListResponse {
+        tags: rows.iter().map(TagWithUsage::from).collect(),
+    }))
+}
+
+/// Request body for creating a tag.
+#[derive(Debug, Deserialize)]
+pub struct CreateTagBody {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+/// `POST /tags` — create a tag.
+pub async fn create_tag_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Json(body): Json<CreateTagBody>,
+) -> AppResult<(StatusCode, Json<Tag>)> {
+    let user_id = parse_user_id(&user.0.sub)?;
+
+    let row = nize_core::tags::create_tag(&state.pool, &user_id, &body.name, body.color.as_deref())
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(Tag::from(&row))))
+}
+
+/// Request body for updating a tag. An empty `color` clears it.
+#[derive(Debug, Deserialize)]
+pub struct UpdateTagBody {
+    pub name: Option<String>,
+    pub color: Option<String>,
+}
+
+/// `PATCH /tags/{id}` — rename or recolor a tag.
+pub async fn update_tag_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateTagBody>,
+) -> AppResult<Json<Tag>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let tag_id = parse_uuid(&id)?;
+
+    let row = nize_core::tags::update_tag(
+        &state.pool,
+        &user_id,
+        &tag_id,
+        body.name.as_deref(),
+        body.color.as_deref(),
+    )
+    .await?;
+
+    Ok(Json(Tag::from(&row)))
+}
+
+/// `DELETE /tags/{id}` — delete a tag and detach it everywhere.
+pub async fn delete_tag_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let tag_id = parse_uuid(&id)?;
+
+    if nize_core::tags::delete_tag(&state.pool, &user_id, &tag_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(Message::new("tags.not_found")))
+    }
+}
+
+/// `GET /tags/{resourceType}/{resourceId}` — tags attached to a resource.
+pub async fn list_resource_tags_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((resource_type, resource_id)): Path<(String, String)>,
+) -> AppResult<Json<ResourceTagsResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let resource_type: TagResourceType = resource_type.parse()?;
+    let resource_id = parse_uuid(&resource_id)?;
+
+    let rows =
+        nize_core::tags::resource_tags(&state.pool, &user_id, resource_type, &resource_id).await?;
+
+    Ok(Json(ResourceTagsResponse {
+        tags: rows.iter().map(Tag::from).collect(),
+    }))
+}
+
+/// Request body for attaching a tag: an existing tag ID, or a name (the tag
+/// is created if missing).
+#[derive(Debug, Deserialize)]
+pub struct AttachTagBody {
+    #[serde(rename = "tagId")]
+    pub tag_id: Option<String>,
+    pub name: Option<String>,
+}
+
+/// `POST /tags/{resourceType}/{resourceId}` — attach a tag to a resource.
+pub async fn attach_tag_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((resource_type, resource_id)): Path<(String, String)>,
+    Json(body): Json<AttachTagBody>,
+) -> AppResult<Json<Tag>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let resource_type: TagResourceType = resource_type.parse()?;
+    let resource_id = parse_uuid(&resource_id)?;
+
+    let tag_id = body.tag_id.as_deref().map(parse_uuid).transpose()?;
+    let tag = match (&tag_id, body.name.as_deref()) {
+        (Some(id), None) => TagRef::Id(id),
+        (None, Some(name)) => TagRef::Name(name),
+        _ => {
+            return Err(AppError::validation(Message::new(
+                "tags.tag_id_or_name_required",
+            )));
+        }
+    };
+
+    let row = nize_core::tags::attach_tag(&state.pool, &user_id, tag, resource_type, &resource_id)
+        .await?;
+
+    Ok(Json(Tag::from(&row)))
+}
+
+/// `DELETE /tags/{resourceType}/{resourceId}/{tagId}` — detach a tag.
+pub async fn detach_tag_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((resource_type, resource_id, tag_id)): Path<(String, String, String)>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let resource_type: TagResourceType = resource_type.parse()?;
+    let resource_id = parse_uuid(&resource_id)?;
+    let tag_id = parse_uuid(&tag_id)?;
+
+    let detached =
+        nize_core::tags::detach_tag(&state.pool, &user_id, &tag_id, resource_type, &resource_id)
+            .await?;
+
+    if detached {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(Message::new("tags.not_attached")))
+    }
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
@@ -0,0 +1,242 @@
+//! Scheduled task request handlers.
+//!
+//! Tasks are personal. Runs happen in the background task scheduler; the
+//! run endpoint only makes a task due immediately. Schedules run in the
+//! user's time zone, which responses include along with the next run in
+//! local time.
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use chrono::{DateTime, FixedOffset};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::tasks::TaskRow;
+use nize_core::timezone;
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+
+/// A scheduled task, with its next run in the user's time zone.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub id: Uuid,
+    pub name: String,
+    pub prompt: String,
+    pub schedule: String,
+    pub enabled: bool,
+    pub timezone: String,
+    pub conversation_id: Option<Uuid>,
+    pub next_run_at: Option<String>,
+    pub next_run_at_local: Option<String>,
+    pub last_run_at: Option<String>,
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl Task {
+    fn new(row: &TaskRow, zone: &str, next_run_local: Option<DateTime<FixedOffset>>) -> Self {
+        Self {
+            id: row.id,
+            name: row.name.clone(),
+            prompt: row.prompt.clone(),
+            schedule: row.schedule.clone(),
+            enabled: row.enabled,
+            timezone: zone.to_string(),
+            conversation_id: row.conversation_id,
+            next_run_at: row.next_run_at.map(|t| t.to_rfc3339()),
+            next_run_at_local: next_run_local.map(|t| t.to_rfc3339()),
+            last_run_at: row.last_run_at.map(|t| t.to_rfc3339()),
+            last_status: row.last_status.clone(),
+            last_error: row.last_error.clone(),
+            created_at: row.created_at.to_rfc3339(),
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `GET /tasks`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct TaskListResponse {
+    pub tasks: Vec<Task>,
+}
+
+/// `GET /tasks` — list the user's scheduled tasks.
+pub async fn list_tasks_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+) -> AppResult<Json<TaskListResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+
+    let rows = nize_core::tasks::list_tasks(&state.pool, &user_id).await?;
+
+    let tasks = load_tasks(&state, &user_id, &rows).await?;
+    Ok(Json(TaskListResponse { tasks }))
+}
+
+/// Request body for creating a task.
+#[derive(Debug, Deserialize)]
+pub struct CreateTaskBody {
+    pub name: String,
+    pub prompt: String,
+    pub schedule: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// `POST /tasks` — create a scheduled task.
+pub async fn create_task_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Json(body): Json<CreateTaskBody>,
+) -> AppResult<(StatusCode, Json<Task>)> {
+    let user_id = parse_user_id(&user.0.sub)?;
+
+    let row = nize_core::tasks::create_task(
+        &state.pool,
+        &user_id,
+        &body.name,
+        &body.prompt,
+        &body.schedule,
+        body.enabled,
+    )
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(load_task(&state, &user_id, &row).await?),
+    ))
+}
+
+/// `GET /tasks/{id}` — get a scheduled task.
+pub async fn get_task_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<Json<Task>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let task_id = parse_uuid(&id)?;
+
+    let row = nize_core::tasks::get_task(&state.pool, &user_id, &task_id).await?;
+
+    Ok(Json(load_task(&state, &user_id, &row).await?))
+}
+
+/// Request body for updating a task.
+#[derive(Debug, Deserialize)]
+pub struct UpdateTaskBody {
+    pub name: Option<String>,
+    pub prompt: Option<String>,
+    pub schedule: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+/// `PATCH /tasks/{id}` — update a scheduled task.
+pub async fn update_task_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+    Json(body): Json<UpdateTaskBody>,
+) -> AppResult<Json<Task>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let task_id = parse_uuid(&id)?;
+
+    let row = nize_core::tasks::update_task(
+        &state.pool,
+        &user_id,
+        &task_id,
+        body.name.as_deref(),
+        body.prompt.as_deref(),
+        body.schedule.as_deref(),
+        body.enabled,
+    )
+    .await?;
+
+    Ok(Json(load_task(&state, &user_id, &row).await?))
+}
+
+/// `DELETE /tasks/{id}` — delete a scheduled task (its conversation is kept).
+pub async fn delete_task_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let task_id = parse_uuid(&id)?;
+
+    if nize_core::tasks::delete_task(&state.pool, &user_id, &task_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::not_found(Message::new("tasks.not_found")))
+    }
+}
+
+/// `POST /tasks/{id}/run` — run a task on the scheduler's next tick.
+pub async fn run_task_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<(StatusCode, Json<Task>)> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let task_id = parse_uuid(&id)?;
+
+    if state.config.chat_url.is_none() {
+        return Err(AppError::SidecarUnavailable(
+            "Task scheduler is not running".into(),
+        ));
+    }
+
+    let task = nize_core::tasks::get_task(&state.pool, &user_id, &task_id).await?;
+    if !task.enabled {
+        return Err(AppError::validation(Message::new("tasks.disabled")));
+    }
+
+    let row = nize_core::tasks::trigger_task(&state.pool, &user_id, &task_id).await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(load_task(&state, &user_id, &row).await?),
+    ))
+}
+
+/// The response for one task.
+async fn load_task(state: &AppState, user_id: &Uuid, row: &TaskRow) -> AppResult<Task> {
+    let mut tasks = load_tasks(state, user_id, std::slice::from_ref(row)).await?;
+    Ok(tasks.remove(0))
+}
+
+/// The responses for `rows`, with next runs in the user's time zone.
+async fn load_tasks(state: &AppState, user_id: &Uuid, rows: &[TaskRow]) -> AppResult<Vec<Task>> {
+    let mut conn = state.pool.acquire().await?;
+    let zone = timezone::user_timezone(&mut conn, user_id).await?;
+    let mut tasks = Vec::with_capacity(rows.len());
+    for row in rows {
+        let next_run_local = match row.next_run_at {
+            Some(at) => Some(timezone::with_offset(&mut conn, at, &zone).await?),
+            None => None,
+        };
+        tasks.push(Task::new(row, &zone, next_run_local));
+    }
+    Ok(tasks)
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
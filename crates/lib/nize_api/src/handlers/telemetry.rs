@@ -0,0 +1,14 @@
+//! Telemetry handlers.
+
+use axum::Json;
+use axum::extract::State;
+
+use crate::AppState;
+use crate::error::AppResult;
+use crate::services::telemetry::{self, TelemetryPreview};
+
+/// `GET /admin/telemetry/preview` — the exact report the next submission
+/// would send, with the settings that govern it.
+pub async fn preview_handler(State(state): State<AppState>) -> AppResult<Json<TelemetryPreview>> {
+    Ok(Json(telemetry::preview(&state).await?))
+}
@@ -1,14 +1,108 @@
 // @awa-component: PLAN-017-TraceHandler
 //
-//! Dev chat-trace handler — demo stub.
+//! Chat trace handlers: the chat service records per-step traces of its
+//! replies; developers read the latest one per conversation.
 
 use axum::Json;
+use axum::extract::{Path, Query, State};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::error::AppResult;
+use nize_core::chat_trace::{self, ChatTraceRow};
 
-/// `GET /dev/chat_trace` — retrieve chat trace (demo).
-pub async fn chat_trace_handler() -> AppResult<Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "events": []
-    })))
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::middleware::workspace::ActiveWorkspace;
+
+/// One step of a trace. Fields beyond `type` and `timestamp` depend on the
+/// event type.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TraceEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(flatten)]
+    pub details: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A recorded trace.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatTraceResponse {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub message_id: String,
+    pub events: Vec<TraceEvent>,
+    pub created_at: String,
+    pub expires_at: String,
+}
+
+impl From<ChatTraceRow> for ChatTraceResponse {
+    fn from(row: ChatTraceRow) -> Self {
+        Self {
+            id: row.id,
+            conversation_id: row.conversation_id,
+            message_id: row.message_id,
+            // Recording checks that each event is an object with a type.
+            events: serde_json::from_value(row.events).unwrap_or_default(),
+            created_at: row.created_at.to_rfc3339(),
+            expires_at: row.expires_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Query parameters for [`chat_trace_handler`].
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatTraceQuery {
+    pub conversation_id: String,
+}
+
+/// `GET /dev/chat_trace` — the most recent trace of a conversation.
+pub async fn chat_trace_handler(
+    State(state): State<AppState>,
+    Query(query): Query<ChatTraceQuery>,
+) -> AppResult<Json<ChatTraceResponse>> {
+    let conv_id = parse_uuid(&query.conversation_id)?;
+    let row = chat_trace::latest_trace(&state.pool, &conv_id)
+        .await?
+        .ok_or_else(|| AppError::not_found(Message::new("trace.not_found")))?;
+    Ok(Json(ChatTraceResponse::from(row)))
+}
+
+/// Request body for recording a trace.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordTraceBody {
+    pub message_id: String,
+    pub events: serde_json::Value,
+}
+
+/// `POST /conversations/{id}/trace` — record the trace of a reply.
+pub async fn record_trace_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    axum::Extension(workspace): axum::Extension<ActiveWorkspace>,
+    Path(id): Path<String>,
+    Json(body): Json<RecordTraceBody>,
+) -> AppResult<Json<ChatTraceResponse>> {
+    let user_id = Uuid::parse_str(&user.0.sub)
+        .map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))?;
+    let conv_id = parse_uuid(&id)?;
+
+    let scope = workspace.scope(user_id);
+    nize_core::conversations::get_conversation(&state.pool, &scope, &conv_id).await?;
+    let row =
+        chat_trace::record_trace(&state.pool, &conv_id, &body.message_id, &body.events).await?;
+
+    Ok(Json(ChatTraceResponse::from(row)))
+}
+
+/// Parse a path or query parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
 }
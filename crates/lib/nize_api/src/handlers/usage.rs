@@ -0,0 +1,53 @@
+//! Usage handlers: per-user quota consumption.
+
+use axum::Json;
+use axum::extract::State;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use nize_core::quotas::{self, Quota};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+
+/// Consumption of one quota.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct QuotaUsage {
+    /// `None` when unlimited.
+    pub limit: Option<i64>,
+    pub used: i64,
+}
+
+/// Body of `GET /usage/limits`: one entry per [`Quota`].
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageLimitsResponse {
+    pub conversations: QuotaUsage,
+    pub documents: QuotaUsage,
+    pub storage_bytes: QuotaUsage,
+    pub mcp_servers: QuotaUsage,
+}
+
+/// `GET /usage/limits` — the caller's consumption against each quota.
+pub async fn limits_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+) -> AppResult<Json<UsageLimitsResponse>> {
+    let user_id = uuid::Uuid::parse_str(&user.0.sub)
+        .map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))?;
+    let limits = quotas::get_limits(&state.pool, &state.config_cache).await?;
+    let usage = quotas::get_usage(&state.pool, &user_id).await?;
+
+    let entry = |quota| QuotaUsage {
+        limit: limits.get(quota),
+        used: usage.get(quota),
+    };
+    Ok(Json(UsageLimitsResponse {
+        conversations: entry(Quota::Conversations),
+        documents: entry(Quota::Documents),
+        storage_bytes: entry(Quota::StorageBytes),
+        mcp_servers: entry(Quota::McpServers),
+    }))
+}
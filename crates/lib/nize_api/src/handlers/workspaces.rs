@@ -0,0 +1,460 @@
+//! Workspace request handlers.
+//!
+//! Workspaces and their memberships are managed here; which workspace a
+//! request acts in is chosen per request with the `X-Nize-Workspace` header
+//! (see [`crate::middleware::workspace`]).
+
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use nize_core::mcp::sharing::{self, SharedServerRow};
+use nize_core::models::mcp::{TransportType, VisibilityTier};
+use nize_core::workspaces::{self, MemberRow, WorkspaceRole, WorkspaceRow};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+
+/// A workspace.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    pub id: Uuid,
+    pub name: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<&WorkspaceRow> for Workspace {
+    fn from(row: &WorkspaceRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name.clone(),
+            created_by: row.created_by,
+            created_at: row.created_at.to_rfc3339(),
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// A workspace with the caller's role in it.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceWithRole {
+    #[serde(flatten)]
+    pub workspace: Workspace,
+    pub role: WorkspaceRole,
+    pub member_count: i64,
+}
+
+impl From<&workspaces::WorkspaceWithRole> for WorkspaceWithRole {
+    fn from(row: &workspaces::WorkspaceWithRole) -> Self {
+        Self {
+            workspace: Workspace::from(&row.workspace),
+            role: row.role,
+            member_count: row.member_count,
+        }
+    }
+}
+
+/// Body of `GET /workspaces`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct WorkspaceListResponse {
+    pub workspaces: Vec<WorkspaceWithRole>,
+}
+
+/// A workspace member.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceMember {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: Option<String>,
+    pub role: WorkspaceRole,
+    pub created_at: String,
+}
+
+impl From<&MemberRow> for WorkspaceMember {
+    fn from(row: &MemberRow) -> Self {
+        Self {
+            user_id: row.user_id,
+            email: row.email.clone(),
+            name: row.name.clone(),
+            role: row.role,
+            created_at: row.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `GET /workspaces/{id}/members`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct WorkspaceMemberListResponse {
+    pub members: Vec<WorkspaceMember>,
+}
+
+/// An MCP server shared with a workspace: which secrets are set, not
+/// their values.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceMcpServer {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub domain: String,
+    pub transport: TransportType,
+    pub owner_id: Option<Uuid>,
+    pub enabled: bool,
+    pub has_api_key: bool,
+    pub has_client_secret: bool,
+    pub updated_at: String,
+}
+
+impl From<&SharedServerRow> for WorkspaceMcpServer {
+    fn from(row: &SharedServerRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name.clone(),
+            description: row.description.clone(),
+            domain: row.domain.clone(),
+            transport: row.transport.clone(),
+            owner_id: row.owner_id,
+            enabled: row.enabled,
+            has_api_key: row.has_api_key,
+            has_client_secret: row.has_oauth_client_secret,
+            updated_at: row.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Body of `GET /workspaces/{id}/mcp-servers`.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct WorkspaceMcpServerListResponse {
+    pub servers: Vec<WorkspaceMcpServer>,
+}
+
+/// `GET /workspaces` — list the workspaces the user is a member of.
+pub async fn list_workspaces_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+) -> AppResult<Json<WorkspaceListResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+
+    let rows = nize_core::workspaces::list_workspaces(&state.pool, &user_id).await?;
+
+    Ok(Json(WorkspaceListResponse {
+        workspaces: rows.iter().map(WorkspaceWithRole::from).collect(),
+    }))
+}
+
+/// Request body for creating or renaming a workspace.
+#[derive(Debug, Deserialize)]
+pub struct WorkspaceBody {
+    pub name: String,
+}
+
+/// `POST /workspaces` — create a workspace owned by the user.
+pub async fn create_workspace_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Json(body): Json<WorkspaceBody>,
+) -> AppResult<(StatusCode, Json<WorkspaceWithRole>)> {
+    let user_id = parse_user_id(&user.0.sub)?;
+
+    let row = nize_core::workspaces::create_workspace(&state.pool, &user_id, &body.name).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(WorkspaceWithRole {
+            workspace: Workspace::from(&row),
+            role: WorkspaceRole::Owner,
+            member_count: 1,
+        }),
+    ))
+}
+
+/// `PATCH /workspaces/{id}` — rename a workspace (owners and admins).
+pub async fn update_workspace_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+    Json(body): Json<WorkspaceBody>,
+) -> AppResult<Json<Workspace>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let workspace_id = parse_uuid(&id)?;
+
+    let row =
+        nize_core::workspaces::rename_workspace(&state.pool, &user_id, &workspace_id, &body.name)
+            .await?;
+
+    Ok(Json(Workspace::from(&row)))
+}
+
+/// `DELETE /workspaces/{id}` — delete a workspace and its shared resources
+/// (owners only).
+pub async fn delete_workspace_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let workspace_id = parse_uuid(&id)?;
+
+    nize_core::workspaces::delete_workspace(&state.pool, &user_id, &workspace_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /workspaces/{id}/members` — list a workspace's members.
+pub async fn list_members_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<Json<WorkspaceMemberListResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let workspace_id = parse_uuid(&id)?;
+
+    let rows = nize_core::workspaces::list_members(&state.pool, &user_id, &workspace_id).await?;
+
+    Ok(Json(WorkspaceMemberListResponse {
+        members: rows.iter().map(WorkspaceMember::from).collect(),
+    }))
+}
+
+/// Request body for adding a member.
+#[derive(Debug, Deserialize)]
+pub struct AddMemberBody {
+    pub email: String,
+    pub role: Option<String>,
+}
+
+/// `POST /workspaces/{id}/members` — add a registered user by email
+/// (owners and admins). Defaults to the `member` role.
+pub async fn add_member_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+    Json(body): Json<AddMemberBody>,
+) -> AppResult<(StatusCode, Json<WorkspaceMember>)> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let workspace_id = parse_uuid(&id)?;
+    let role = match body.role.as_deref() {
+        Some(role) => role.parse::<WorkspaceRole>()?,
+        None => WorkspaceRole::Member,
+    };
+
+    let row =
+        nize_core::workspaces::add_member(&state.pool, &user_id, &workspace_id, &body.email, role)
+            .await?;
+
+    Ok((StatusCode::CREATED, Json(WorkspaceMember::from(&row))))
+}
+
+/// Request body for changing a member's role.
+#[derive(Debug, Deserialize)]
+pub struct UpdateMemberBody {
+    pub role: String,
+}
+
+/// `PATCH /workspaces/{id}/members/{userId}` — change a member's role.
+pub async fn update_member_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((id, member_id)): Path<(String, String)>,
+    Json(body): Json<UpdateMemberBody>,
+) -> AppResult<Json<WorkspaceMember>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let workspace_id = parse_uuid(&id)?;
+    let member_id = parse_uuid(&member_id)?;
+    let role = body.role.parse::<WorkspaceRole>()?;
+
+    let row = nize_core::workspaces::update_member_role(
+        &state.pool,
+        &user_id,
+        &workspace_id,
+        &member_id,
+        role,
+    )
+    .await?;
+
+    Ok(Json(WorkspaceMember::from(&row)))
+}
+
+/// `DELETE /workspaces/{id}/members/{userId}` — remove a member, or leave
+/// the workspace when `userId` is the caller.
+pub async fn remove_member_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((id, member_id)): Path<(String, String)>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let workspace_id = parse_uuid(&id)?;
+    let member_id = parse_uuid(&member_id)?;
+
+    nize_core::workspaces::remove_member(&state.pool, &user_id, &workspace_id, &member_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `GET /workspaces/{id}/mcp-servers` — list the MCP servers shared with a
+/// workspace, with which secrets are set but not their values.
+pub async fn list_servers_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path(id): Path<String>,
+) -> AppResult<Json<WorkspaceMcpServerListResponse>> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let workspace_id = parse_uuid(&id)?;
+
+    nize_core::workspaces::require_membership(&state.pool, &workspace_id, &user_id).await?;
+    let rows = sharing::list_workspace_servers(&state.pool, &workspace_id).await?;
+
+    Ok(Json(WorkspaceMcpServerListResponse {
+        servers: rows.iter().map(WorkspaceMcpServer::from).collect(),
+    }))
+}
+
+/// `PUT /workspaces/{id}/mcp-servers/{serverId}` — share one of the caller's
+/// MCP servers with a workspace they are a member of. Its secrets move to
+/// the workspace's key.
+pub async fn share_server_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((id, server_id)): Path<(String, String)>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let workspace_id = parse_uuid(&id)?;
+    parse_uuid(&server_id)?;
+
+    nize_core::workspaces::require_membership(&state.pool, &workspace_id, &user_id).await?;
+    let server = nize_core::mcp::queries::get_server(&state.pool, &server_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::not_found(Message::new("mcp.server_not_found").arg("id", &server_id))
+        })?;
+    if server.visibility != VisibilityTier::User || server.owner_id != Some(user_id) {
+        return Err(AppError::forbidden(Message::new(
+            "workspaces.share_own_servers_only",
+        )));
+    }
+
+    sharing::share_server(
+        &state.pool,
+        &server_id,
+        Some(&workspace_id),
+        &state.config.mcp_encryption_key,
+    )
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `DELETE /workspaces/{id}/mcp-servers/{serverId}` — stop sharing a server
+/// with a workspace (its owner, or workspace owners and admins). The server
+/// stays with its owner, its secrets under the deployment key.
+pub async fn unshare_server_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((id, server_id)): Path<(String, String)>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let workspace_id = parse_uuid(&id)?;
+    parse_uuid(&server_id)?;
+
+    let membership =
+        nize_core::workspaces::require_membership(&state.pool, &workspace_id, &user_id).await?;
+    let owner_id = shared_server_owner(&state, &workspace_id, &server_id).await?;
+    if owner_id != Some(user_id) && !membership.role.can_manage() {
+        return Err(AppError::forbidden(Message::new(
+            "workspaces.unshare_forbidden",
+        )));
+    }
+
+    sharing::share_server(
+        &state.pool,
+        &server_id,
+        None,
+        &state.config.mcp_encryption_key,
+    )
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Request body for setting a shared server's secrets.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSecretsBody {
+    pub api_key: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+/// `PUT /workspaces/{id}/mcp-servers/{serverId}/secrets` — set a shared
+/// server's API key and/or OAuth client secret, encrypted under the
+/// workspace's key (owners and admins).
+pub async fn set_server_secrets_handler(
+    State(state): State<AppState>,
+    axum::Extension(user): axum::Extension<AuthenticatedUser>,
+    Path((id, server_id)): Path<(String, String)>,
+    Json(body): Json<ServerSecretsBody>,
+) -> AppResult<StatusCode> {
+    let user_id = parse_user_id(&user.0.sub)?;
+    let workspace_id = parse_uuid(&id)?;
+    parse_uuid(&server_id)?;
+
+    let membership =
+        nize_core::workspaces::require_membership(&state.pool, &workspace_id, &user_id).await?;
+    if !membership.role.can_manage() {
+        return Err(AppError::forbidden(Message::new(
+            "workspaces.shared_secrets_forbidden",
+        )));
+    }
+
+    sharing::set_workspace_secrets(
+        &state.pool,
+        &workspace_id,
+        &server_id,
+        body.api_key.as_deref(),
+        body.client_secret.as_deref(),
+        &state.config.mcp_encryption_key,
+    )
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Owner of a server shared with the workspace, or `NotFound` if it is not
+/// shared there.
+async fn shared_server_owner(
+    state: &AppState,
+    workspace_id: &Uuid,
+    server_id: &str,
+) -> Result<Option<Uuid>, AppError> {
+    let shared = nize_core::mcp::queries::get_server_workspace(&state.pool, server_id).await?;
+    if shared != Some(*workspace_id) {
+        return Err(AppError::not_found(
+            Message::new("mcp.server_not_shared").arg("id", server_id),
+        ));
+    }
+    let server = nize_core::mcp::queries::get_server(&state.pool, server_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::not_found(Message::new("mcp.server_not_found").arg("id", server_id))
+        })?;
+    Ok(server.owner_id)
+}
+
+/// Parse a user ID string into a UUID.
+fn parse_user_id(sub: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(sub).map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))
+}
+
+/// Parse a path parameter string into a UUID.
+fn parse_uuid(s: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(s).map_err(|_| AppError::validation(Message::new("request.invalid_uuid")))
+}
@@ -0,0 +1,228 @@
+//! Localized API error messages.
+//!
+//! User-facing error texts are [`Message`]s: stable keys
+//! (`auth.invalid_credentials`, `read_only`, …) rendered from per-language
+//! bundles, flat JSON objects mapping keys to templates with `{name}`
+//! placeholders. English is built into `nize_core`; further bundles are
+//! read from a directory of `<language-tag>.json` files
+//! (`--locales-dir` / `NIZE_LOCALES_DIR`), which is how the desktop app
+//! ships its translations.
+//!
+//! [`crate::middleware::locale`] negotiates the language from
+//! `Accept-Language` and renders the message of error responses that carry
+//! a [`Message`].
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+pub use nize_core::i18n::Message;
+
+/// Language used when negotiation finds nothing better; always complete.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Errors loading a locale directory.
+#[derive(Debug, Error)]
+pub enum LocaleError {
+    #[error("IO error reading {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid locale bundle {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+/// Message templates by language tag (lowercase, e.g. `de`, `pt-br`).
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    bundles: HashMap<String, HashMap<String, String>>,
+}
+
+impl Catalog {
+    /// Only the built-in English bundle.
+    pub fn builtin() -> Self {
+        let english = nize_core::i18n::english().clone();
+        Self {
+            bundles: HashMap::from([(DEFAULT_LOCALE.to_string(), english)]),
+        }
+    }
+
+    /// The built-in bundle plus every `<tag>.json` in `dir`. A bundle for
+    /// `en` adds to and overrides the built-in texts.
+    pub fn load(dir: Option<&Path>) -> Result<Self, LocaleError> {
+        let mut catalog = Self::builtin();
+        let Some(dir) = dir else {
+            return Ok(catalog);
+        };
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| LocaleError::Io { path, source }
+        };
+        for entry in std::fs::read_dir(dir).map_err(io_error(dir))? {
+            let path = entry.map_err(io_error(dir))?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(tag) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let text = std::fs::read_to_string(&path).map_err(io_error(&path))?;
+            let bundle: HashMap<String, String> =
+                serde_json::from_str(&text).map_err(|source| LocaleError::Parse {
+                    path: path.clone(),
+                    source,
+                })?;
+            catalog
+                .bundles
+                .entry(tag.to_ascii_lowercase())
+                .or_default()
+                .extend(bundle);
+        }
+        Ok(catalog)
+    }
+
+    /// Language tags with a bundle, sorted.
+    pub fn locales(&self) -> Vec<&str> {
+        let mut locales: Vec<&str> = self.bundles.keys().map(String::as_str).collect();
+        locales.sort_unstable();
+        locales
+    }
+
+    /// Pick the best available language for an `Accept-Language` header:
+    /// ranges in order of quality, each matched exactly and then by its
+    /// primary subtag (`de-CH` → `de`). Falls back to [`DEFAULT_LOCALE`].
+    pub fn negotiate(&self, accept_language: Option<&str>) -> &str {
+        let mut ranges: Vec<(String, f32)> = accept_language
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|part| {
+                let mut fields = part.split(';');
+                let tag = fields.next()?.trim().to_ascii_lowercase();
+                let q = fields
+                    .filter_map(|p| p.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (!tag.is_empty() && q > 0.0).then_some((tag, q))
+            })
+            .collect();
+        // Stable, so equal qualities keep the client's order.
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        for (tag, _) in &ranges {
+            if tag == "*" {
+                break;
+            }
+            if let Some((locale, _)) = self.bundles.get_key_value(tag.as_str()) {
+                return locale;
+            }
+            if let Some((locale, _)) = self.bundles.get_key_value(primary(tag)) {
+                return locale;
+            }
+        }
+        DEFAULT_LOCALE
+    }
+
+    /// Render `message` in `locale`, falling back to its primary language,
+    /// then English, then the bare key.
+    pub fn render(&self, locale: &str, message: &Message) -> String {
+        message.render(&|key| {
+            [locale, primary(locale), DEFAULT_LOCALE]
+                .iter()
+                .find_map(|l| self.bundles.get(*l)?.get(key))
+                .map(String::as_str)
+        })
+    }
+}
+
+/// `de` for `de-ch`.
+fn primary(tag: &str) -> &str {
+    tag.split('-').next().unwrap_or(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shipped() -> Catalog {
+        Catalog::load(Some(&Path::new(env!("CARGO_MANIFEST_DIR")).join("locales")))
+            .expect("shipped bundles load")
+    }
+
+    #[test]
+    fn negotiation_honours_quality_and_primary_subtags() {
+        let catalog = shipped();
+        assert_eq!(catalog.negotiate(None), "en");
+        assert_eq!(catalog.negotiate(Some("de-CH, en;q=0.5")), "de");
+        assert_eq!(catalog.negotiate(Some("fr, en;q=0.4, de;q=0.8")), "de");
+        assert_eq!(catalog.negotiate(Some("de;q=0, fr")), "en");
+        assert_eq!(catalog.negotiate(Some("*")), "en");
+    }
+
+    #[test]
+    fn render_fills_arguments_and_falls_back_to_english() {
+        let catalog = shipped();
+        let message = Message::new("auth.password_too_short").arg("min", 8);
+        assert_eq!(
+            catalog.render("de", &message),
+            "Das Passwort muss mindestens 8 Zeichen lang sein"
+        );
+        assert_eq!(
+            catalog.render("fr", &message),
+            "Password must be at least 8 characters"
+        );
+        assert_eq!(
+            message.to_string(),
+            "Password must be at least 8 characters"
+        );
+        assert_eq!(
+            catalog.render("de", &Message::new("no.such.key")),
+            "no.such.key"
+        );
+    }
+
+    #[test]
+    fn nested_messages_render_in_the_same_language() {
+        let message = Message::new("config.provider_cannot_run")
+            .arg("value", "whisper")
+            .nested("reason", Message::new("hardware.no_vector_extensions"));
+        assert_eq!(
+            shipped().render("de", &message),
+            "whisper kann auf diesem Rechner nicht laufen: \
+             Der CPU fehlt AVX2 oder NEON, daher wären lokale Modelle zu langsam"
+        );
+        assert_eq!(
+            message.to_string(),
+            "whisper cannot run on this machine: \
+             The CPU lacks AVX2 or NEON, so local models would be too slow"
+        );
+    }
+
+    #[test]
+    fn shipped_bundles_match_the_english_keys_and_placeholders() {
+        let catalog = shipped();
+        let placeholders = |text: &str| {
+            let mut names: Vec<String> = text
+                .split('{')
+                .skip(1)
+                .filter_map(|s| s.split_once('}').map(|(name, _)| name.to_string()))
+                .collect();
+            names.sort();
+            names
+        };
+        let english = &catalog.bundles[DEFAULT_LOCALE];
+        for (locale, bundle) in &catalog.bundles {
+            for (key, text) in bundle {
+                let source = english
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{locale}: unknown key {key}"));
+                assert_eq!(placeholders(text), placeholders(source), "{locale}: {key}");
+            }
+        }
+    }
+}
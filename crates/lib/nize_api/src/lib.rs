@@ -3,35 +3,58 @@
 //! HTTP API library for Nize.
 
 pub mod config;
+#[cfg(test)]
+mod conformance;
+pub mod cors;
+pub mod diagnostics;
 pub mod error;
+pub mod events;
 pub mod generated;
 pub mod handlers;
+pub mod i18n;
+pub mod metrics;
 pub mod middleware;
 pub mod services;
+pub mod shutdown;
+pub mod startup;
+pub mod streams;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
 
 use std::sync::Arc;
 
 use axum::Router;
+use axum::extract::DefaultBodyLimit;
 use axum::http::Method;
 use axum::http::header;
-use axum::routing::{delete, get, patch, post, put};
+use axum::routing::{MethodRouter, delete, get, patch, post, put};
 use sqlx::PgPool;
 use tokio::sync::RwLock;
-use tower_http::cors::{AllowHeaders, AllowOrigin, CorsLayer};
+use tower_http::cors::{AllowHeaders, CorsLayer};
 
 use crate::config::ApiConfig;
-use crate::generated::routes;
+use crate::generated::routes::{self, AuthTier};
 use crate::handlers::config as config_handlers;
 use crate::handlers::{
-    admin_permissions, ai_proxy, auth, chat, conversations, embeddings, hello, ingest, mcp_config,
-    mcp_tokens, oauth, permissions, trace,
+    account, admin_permissions, admin_roles, ai_proxy, analytics, announcements, auth, chat,
+    connectors, conversations, database, diagnostics as diagnostics_handlers, embeddings, evals,
+    events as events_handlers, feedback, health, hello, ingest, integrity, mcp_config,
+    mcp_recordings, mcp_tokens, meta, metrics as metrics_handlers, moderation, notes,
+    notifications, oauth, permissions, providers, regeneration, security_activity, signing_keys,
+    storage, streams as stream_handlers, sync, tags, tasks, telemetry, trace, usage, workspaces,
 };
 
+use crate::metrics::MetricsRegistry;
+use nize_core::auth::rbac;
 use nize_core::config::cache::ConfigCache;
+use nize_core::mcp::execution::ClientPool;
 
-/// Path prefix under which all API routes are nested.
+/// Path prefix under which all API routes are nested: unversioned, and
+/// followed by the version for versioned routes (see
+/// [`middleware::version`]).
 pub const API_PREFIX: &str = "/api";
-use nize_core::mcp::oauth::OAuthStateStore;
+use nize_core::auth::keys::JwtKeys;
+use nize_core::telemetry::UsageCounters;
 
 /// Shared application state passed to all handlers.
 #[derive(Clone)]
@@ -42,8 +65,24 @@ pub struct AppState {
     pub config: ApiConfig,
     /// In-memory config cache.
     pub config_cache: Arc<RwLock<ConfigCache>>,
-    /// In-memory OAuth PKCE state store.
-    pub oauth_state: Arc<OAuthStateStore>,
+    /// Request and pool metrics exposed at `GET /metrics`.
+    pub metrics: Arc<MetricsRegistry>,
+    /// Server events streamed to clients at `GET /events`.
+    pub events: Arc<events::EventBus>,
+    /// Buffered streams polled at `GET /chat/streams/{id}/poll`.
+    pub streams: Arc<streams::StreamStore>,
+    /// Slow database statements, fed by the server's tracing subscriber.
+    pub slow_queries: Arc<diagnostics::SlowQueryLog>,
+    /// JWT signing and verification keys.
+    pub jwt_keys: Arc<JwtKeys>,
+    /// Anonymous usage counters for opt-in telemetry.
+    pub telemetry: Arc<UsageCounters>,
+    /// Error message translations, negotiated per request.
+    pub i18n: Arc<i18n::Catalog>,
+    /// Local single-user mode, enabled only by the desktop sidecar.
+    pub local_mode: Option<Arc<services::local_mode::LocalMode>>,
+    /// MCP client connections, shared with the MCP server.
+    pub mcp_clients: Arc<ClientPool>,
 }
 
 /// Run embedded database migrations.
@@ -55,10 +94,10 @@ pub async fn migrate(pool: &PgPool) -> Result<(), sqlx::migrate::MigrateError> {
 
 /// Builds the Axum router with all routes and shared state.
 pub fn router(state: AppState) -> Router {
-    // CORS: allow credentials (cookies) with permissive origins.
-    // In production, restrict allow_origin to specific domains.
+    // CORS: allow credentials (cookies) from the configured origins,
+    // resolved per request (see [`cors`]).
     let cors = CorsLayer::new()
-        .allow_origin(AllowOrigin::mirror_request())
+        .allow_origin(cors::allow_origin(&state))
         .allow_methods([
             Method::GET,
             Method::POST,
@@ -71,17 +110,34 @@ pub fn router(state: AppState) -> Router {
             header::AUTHORIZATION,
             header::ACCEPT,
             header::COOKIE,
+            header::HeaderName::from_static(middleware::workspace::WORKSPACE_HEADER),
+            header::HeaderName::from_static(middleware::csrf::CSRF_HEADER),
+            header::HeaderName::from_static(mcp_tokens::MCP_TOKEN_HEADER),
+            header::HeaderName::from_static(middleware::idempotency::IDEMPOTENCY_KEY_HEADER),
         ]))
+        .expose_headers([
+            header::HeaderName::from_static(middleware::csrf::CSRF_HEADER),
+            header::HeaderName::from_static(middleware::request_context::REQUEST_ID_HEADER),
+            header::HeaderName::from_static(middleware::idempotency::REPLAYED_HEADER),
+        ])
         .allow_credentials(true);
 
     // Public routes (no auth required)
-    let public = Router::new()
+    let public = TierRouter::new(AuthTier::Public)
         .route(routes::GET_HELLO, get(hello::hello_world))
+        .route(routes::GET_META, get(meta::meta_handler))
         .route(routes::POST_AUTH_LOGIN, post(auth::login_handler))
         .route(routes::POST_AUTH_REGISTER, post(auth::register_handler))
         .route(routes::POST_AUTH_REFRESH, post(auth::refresh_handler))
+        .route(routes::POST_AUTH_LOCAL, post(auth::local_login_handler))
         .route(routes::POST_AUTH_LOGOUT, post(auth::logout_handler))
         .route(routes::GET_AUTH_STATUS, get(auth::auth_status_handler))
+        .route(routes::GET_AUTH_VERIFY, get(auth::verify_email_handler))
+        .route(
+            routes::POST_AUTH_VERIFY_RESEND,
+            post(auth::resend_verification_handler),
+        )
+        .route(routes::GET_AUTH_JWKS, get(signing_keys::jwks_handler))
         .route(
             routes::GET_AUTH_OAUTH_MCP_CALLBACK,
             get(oauth::oauth_callback_handler),
@@ -89,10 +145,35 @@ pub fn router(state: AppState) -> Router {
         .route(
             routes::GET_PERMISSIONS_SHARED_TOKEN,
             get(permissions::access_shared_handler),
-        );
+        )
+        .route(
+            routes::GET_PERMISSIONS_SHARED_TOKEN_MESSAGES,
+            get(permissions::shared_messages_handler),
+        )
+        .into_router();
 
     // Protected routes (require auth)
-    let protected = Router::new()
+    let protected = TierRouter::new(AuthTier::Protected)
+        .route(
+            routes::DELETE_ACCOUNT,
+            delete(account::delete_account_handler),
+        )
+        .route(
+            routes::POST_ACCOUNT_EXPORT,
+            post(account::create_export_handler),
+        )
+        .route(
+            routes::GET_ACCOUNT_EXPORT_ID,
+            get(account::get_export_handler),
+        )
+        .route(
+            routes::GET_ACCOUNT_EXPORT_ID_DOWNLOAD,
+            get(account::download_export_handler),
+        )
+        .route(
+            routes::POST_ACCOUNT_IMPORT,
+            post(account::import_handler).layer(DefaultBodyLimit::max(account::MAX_IMPORT_BYTES)),
+        )
         .route(
             routes::POST_AUTH_MCP_TOKENS,
             post(mcp_tokens::create_mcp_token_handler),
@@ -105,6 +186,14 @@ pub fn router(state: AppState) -> Router {
             routes::DELETE_AUTH_MCP_TOKENS_ID,
             delete(mcp_tokens::revoke_mcp_token_handler),
         )
+        .route(
+            routes::GET_AUTH_MCP_TOKENS_ID_CLIENT_CONFIG,
+            get(mcp_tokens::client_config_handler),
+        )
+        .route(
+            routes::GET_AUTH_ACTIVITY,
+            get(security_activity::list_activity_handler),
+        )
         .route(routes::POST_AUTH_LOGOUT_ALL, post(auth::logout_all_handler))
         .route(
             routes::GET_CONFIG_USER,
@@ -120,8 +209,13 @@ pub fn router(state: AppState) -> Router {
         )
         // Chat
         .route(routes::POST_CHAT, post(chat::chat_handler))
+        .route(routes::POST_CHAT_ESTIMATE, post(chat::estimate_handler))
+        .route(
+            routes::GET_CHAT_STREAMS_ID_POLL,
+            get(stream_handlers::poll_handler),
+        )
         // AI Proxy
-        .route("/ai-proxy", post(ai_proxy::ai_proxy_handler))
+        .route(ai_proxy::ROUTE, post(ai_proxy::ai_proxy_handler))
         // Conversations
         .route(
             routes::GET_CONVERSATIONS,
@@ -147,14 +241,200 @@ pub fn router(state: AppState) -> Router {
             routes::PUT_CONVERSATIONS_ID_MESSAGES,
             put(conversations::save_messages_handler),
         )
+        .route(
+            routes::PUT_CONVERSATIONS_ID_SUMMARY,
+            put(conversations::save_summary_handler),
+        )
+        .route(
+            routes::GET_CONVERSATIONS_ID_TOOLS,
+            get(conversations::get_tool_selection_handler),
+        )
+        .route(
+            routes::PUT_CONVERSATIONS_ID_TOOLS,
+            put(conversations::set_tool_selection_handler),
+        )
+        // Message feedback
+        .route(
+            routes::GET_CONVERSATIONS_ID_FEEDBACK,
+            get(feedback::list_feedback_handler),
+        )
+        .route(
+            routes::PUT_CONVERSATIONS_ID_MESSAGES_MESSAGEID_FEEDBACK,
+            put(feedback::set_feedback_handler),
+        )
+        .route(
+            routes::DELETE_CONVERSATIONS_ID_MESSAGES_MESSAGEID_FEEDBACK,
+            delete(feedback::delete_feedback_handler),
+        )
+        // Reply regeneration
+        .route(
+            routes::POST_CONVERSATIONS_ID_MESSAGES_MESSAGEID_REGENERATE,
+            post(regeneration::regenerate_handler),
+        )
+        .route(
+            routes::GET_CONVERSATIONS_ID_MESSAGES_MESSAGEID_CANDIDATES,
+            get(regeneration::list_candidates_handler),
+        )
+        .route(
+            routes::POST_CONVERSATIONS_ID_MESSAGES_MESSAGEID_CANDIDATES_CANDIDATEID_SELECT,
+            post(regeneration::select_candidate_handler),
+        )
+        // Chat traces
+        .route(
+            routes::POST_CONVERSATIONS_ID_TRACE,
+            post(trace::record_trace_handler),
+        )
         // Ingest
         .route(routes::GET_INGEST, get(ingest::list_documents_handler))
         .route(routes::POST_INGEST, post(ingest::upload_handler))
+        .route(routes::POST_INGEST_RETRIEVE, post(ingest::retrieve_handler))
+        .route(
+            routes::GET_INGEST_SEARCH,
+            get(ingest::search_documents_handler),
+        )
         .route(routes::GET_INGEST_ID, get(ingest::get_document_handler))
+        .route(
+            routes::GET_INGEST_ID_CONTENT,
+            get(ingest::download_document_handler),
+        )
         .route(
             routes::DELETE_INGEST_ID,
             delete(ingest::delete_document_handler),
         )
+        // Notes
+        .route(routes::GET_NOTES, get(notes::list_notes_handler))
+        .route(routes::POST_NOTES, post(notes::create_note_handler))
+        .route(routes::GET_NOTES_SEARCH, get(notes::search_notes_handler))
+        .route(routes::GET_NOTES_ID, get(notes::get_note_handler))
+        .route(routes::PATCH_NOTES_ID, patch(notes::update_note_handler))
+        .route(routes::DELETE_NOTES_ID, delete(notes::delete_note_handler))
+        // Usage
+        .route(routes::GET_USAGE_LIMITS, get(usage::limits_handler))
+        // Server events
+        .route(routes::GET_EVENTS, get(events_handlers::events_handler))
+        .route(
+            routes::POST_EVENTS_STREAMS,
+            post(events_handlers::create_event_stream_handler),
+        )
+        // Announcements
+        .route(
+            routes::GET_ANNOUNCEMENTS,
+            get(announcements::list_announcements_handler),
+        )
+        .route(
+            routes::POST_ANNOUNCEMENTS_ID_DISMISS,
+            post(announcements::dismiss_announcement_handler),
+        )
+        // Notifications
+        .route(
+            routes::GET_NOTIFICATIONS,
+            get(notifications::list_notifications_handler),
+        )
+        .route(
+            routes::POST_NOTIFICATIONS_ID_READ,
+            post(notifications::mark_read_handler),
+        )
+        // Delta sync
+        .route(routes::GET_SYNC, get(sync::pull_handler))
+        .route(routes::POST_SYNC, post(sync::push_handler))
+        // Tags
+        .route(routes::GET_TAGS, get(tags::list_tags_handler))
+        .route(routes::POST_TAGS, post(tags::create_tag_handler))
+        .route(routes::PATCH_TAGS_ID, patch(tags::update_tag_handler))
+        .route(routes::DELETE_TAGS_ID, delete(tags::delete_tag_handler))
+        .route(
+            routes::GET_TAGS_RESOURCETYPE_RESOURCEID,
+            get(tags::list_resource_tags_handler),
+        )
+        .route(
+            routes::POST_TAGS_RESOURCETYPE_RESOURCEID,
+            post(tags::attach_tag_handler),
+        )
+        .route(
+            routes::DELETE_TAGS_RESOURCETYPE_RESOURCEID_TAGID,
+            delete(tags::detach_tag_handler),
+        )
+        // Connectors
+        .route(
+            routes::GET_CONNECTORS,
+            get(connectors::list_connectors_handler),
+        )
+        .route(
+            routes::POST_CONNECTORS,
+            post(connectors::create_connector_handler),
+        )
+        .route(
+            routes::GET_CONNECTORS_ID,
+            get(connectors::get_connector_handler),
+        )
+        .route(
+            routes::PATCH_CONNECTORS_ID,
+            patch(connectors::update_connector_handler),
+        )
+        .route(
+            routes::DELETE_CONNECTORS_ID,
+            delete(connectors::delete_connector_handler),
+        )
+        .route(
+            routes::POST_CONNECTORS_ID_SYNC,
+            post(connectors::sync_connector_handler),
+        )
+        // Tasks
+        .route(routes::GET_TASKS, get(tasks::list_tasks_handler))
+        .route(routes::POST_TASKS, post(tasks::create_task_handler))
+        .route(routes::GET_TASKS_ID, get(tasks::get_task_handler))
+        .route(routes::PATCH_TASKS_ID, patch(tasks::update_task_handler))
+        .route(routes::DELETE_TASKS_ID, delete(tasks::delete_task_handler))
+        .route(routes::POST_TASKS_ID_RUN, post(tasks::run_task_handler))
+        // Workspaces
+        .route(
+            routes::GET_WORKSPACES,
+            get(workspaces::list_workspaces_handler),
+        )
+        .route(
+            routes::POST_WORKSPACES,
+            post(workspaces::create_workspace_handler),
+        )
+        .route(
+            routes::PATCH_WORKSPACES_ID,
+            patch(workspaces::update_workspace_handler),
+        )
+        .route(
+            routes::DELETE_WORKSPACES_ID,
+            delete(workspaces::delete_workspace_handler),
+        )
+        .route(
+            routes::GET_WORKSPACES_ID_MCP_SERVERS,
+            get(workspaces::list_servers_handler),
+        )
+        .route(
+            routes::PUT_WORKSPACES_ID_MCP_SERVERS_SERVERID,
+            put(workspaces::share_server_handler),
+        )
+        .route(
+            routes::DELETE_WORKSPACES_ID_MCP_SERVERS_SERVERID,
+            delete(workspaces::unshare_server_handler),
+        )
+        .route(
+            routes::PUT_WORKSPACES_ID_MCP_SERVERS_SERVERID_SECRETS,
+            put(workspaces::set_server_secrets_handler),
+        )
+        .route(
+            routes::GET_WORKSPACES_ID_MEMBERS,
+            get(workspaces::list_members_handler),
+        )
+        .route(
+            routes::POST_WORKSPACES_ID_MEMBERS,
+            post(workspaces::add_member_handler),
+        )
+        .route(
+            routes::PATCH_WORKSPACES_ID_MEMBERS_USERID,
+            patch(workspaces::update_member_handler),
+        )
+        .route(
+            routes::DELETE_WORKSPACES_ID_MEMBERS_USERID,
+            delete(workspaces::remove_member_handler),
+        )
         // Permissions — grants
         .route(
             routes::POST_PERMISSIONS_RESOURCETYPE_RESOURCEID_GRANTS,
@@ -177,6 +457,10 @@ pub fn router(state: AppState) -> Router {
             routes::GET_PERMISSIONS_RESOURCETYPE_RESOURCEID_LINKS,
             get(permissions::list_links_handler),
         )
+        .route(
+            routes::PATCH_PERMISSIONS_LINKS_LINKID,
+            patch(permissions::update_link_handler),
+        )
         .route(
             routes::DELETE_PERMISSIONS_LINKS_LINKID,
             delete(permissions::revoke_link_handler),
@@ -206,6 +490,10 @@ pub fn router(state: AppState) -> Router {
             routes::GET_MCP_SERVERS_SERVERID_TOOLS,
             get(mcp_config::list_server_tools_handler),
         )
+        .route(
+            routes::PATCH_MCP_SERVERS_SERVERID_TOOLS_TOOLID,
+            patch(mcp_config::update_tool_handler),
+        )
         .route(
             routes::GET_MCP_SERVERS_SERVERID_OAUTH_STATUS,
             get(mcp_config::oauth_status_handler),
@@ -222,86 +510,608 @@ pub fn router(state: AppState) -> Router {
             routes::POST_MCP_TEST_CONNECTION,
             post(mcp_config::test_connection_handler),
         )
-        .layer(axum::middleware::from_fn_with_state(
-            state.clone(),
-            middleware::auth::require_auth,
-        ));
-
-    // Admin routes (require admin role)
-    let admin = Router::new()
         .route(
-            routes::GET_ADMIN_CONFIG,
-            get(config_handlers::admin_config_list_handler),
+            routes::GET_MCP_ROUTING,
+            get(mcp_config::list_routes_handler),
         )
         .route(
-            routes::PATCH_ADMIN_CONFIG_SCOPE_KEY,
-            patch(config_handlers::admin_config_update_handler),
+            routes::PUT_MCP_ROUTING_DOMAIN,
+            put(mcp_config::set_route_handler),
         )
-        // Admin permissions
         .route(
-            routes::GET_ADMIN_PERMISSIONS_GRANTS,
-            get(admin_permissions::list_all_grants_handler),
+            routes::DELETE_MCP_ROUTING_DOMAIN,
+            delete(mcp_config::delete_route_handler),
         )
         .route(
-            routes::DELETE_ADMIN_PERMISSIONS_GRANTS_GRANTID,
-            delete(admin_permissions::admin_revoke_grant_handler),
+            routes::GET_MCP_CATALOG,
+            get(mcp_config::list_catalog_handler),
         )
         .route(
-            routes::GET_ADMIN_PERMISSIONS_GROUPS,
-            get(admin_permissions::list_all_groups_handler),
+            routes::POST_MCP_CATALOG_SLUG_INSTALL,
+            post(mcp_config::install_catalog_entry_handler),
         )
-        .route(
-            routes::GET_ADMIN_PERMISSIONS_LINKS,
-            get(admin_permissions::list_all_links_handler),
+        .into_router()
+        // Layers run last-added first: authenticate, resolve the active
+        // workspace, then replay retried requests.
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::idempotency::deduplicate,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::workspace::resolve_workspace,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::auth::require_auth,
+        ));
+
+    // Admin routes. Each group requires a permission from the caller's
+    // roles (see `nize_core::auth::rbac`); `require_auth` runs first.
+    let needs = |permission: &'static str| {
+        axum::middleware::from_fn_with_state(
+            (state.clone(), permission),
+            middleware::auth::require_permission,
         )
-        .route(
-            routes::DELETE_ADMIN_PERMISSIONS_LINKS_LINKID,
-            delete(admin_permissions::admin_revoke_link_handler),
+    };
+    let admin = Router::new()
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(
+                    routes::GET_ADMIN_CONFIG,
+                    get(config_handlers::admin_config_list_handler),
+                )
+                .route(
+                    routes::PATCH_ADMIN_CONFIG_SCOPE_KEY,
+                    patch(config_handlers::admin_config_update_handler),
+                )
+                .route(
+                    routes::GET_ADMIN_CONFIG_CACHE,
+                    get(config_handlers::admin_cache_handler),
+                )
+                .route(
+                    routes::POST_ADMIN_CONFIG_CACHE_REFRESH,
+                    post(config_handlers::admin_cache_refresh_handler),
+                )
+                .route(
+                    routes::GET_ADMIN_CONFIG_HISTORY,
+                    get(config_handlers::admin_config_history_handler),
+                )
+                .route(
+                    routes::POST_ADMIN_CONFIG_ROLLBACK_VERSION,
+                    post(config_handlers::admin_config_rollback_handler),
+                )
+                .route(
+                    routes::GET_ADMIN_TELEMETRY_PREVIEW,
+                    get(telemetry::preview_handler),
+                )
+                .route(routes::GET_ADMIN_STORAGE, get(storage::usage_handler))
+                .route(
+                    routes::POST_ADMIN_STORAGE_MAINTENANCE,
+                    post(storage::maintenance_handler),
+                )
+                .route(routes::GET_ADMIN_INTEGRITY, get(integrity::audit_handler))
+                .route(
+                    routes::POST_ADMIN_INTEGRITY_REPAIR,
+                    post(integrity::repair_handler),
+                )
+                .route(
+                    routes::POST_ADMIN_DATABASE_TRANSFER,
+                    post(database::transfer_handler),
+                )
+                .route(
+                    routes::POST_ADMIN_PROVIDERS_TEST,
+                    post(providers::test_providers_handler),
+                )
+                .into_router()
+                .route_layer(needs(rbac::PERM_CONFIG_WRITE)),
         )
-        .route(
-            routes::PATCH_ADMIN_PERMISSIONS_USERS_USERID_ADMIN,
-            patch(admin_permissions::set_admin_role_handler),
+        // Admin permissions
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(
+                    routes::GET_ADMIN_PERMISSIONS_GRANTS,
+                    get(admin_permissions::list_all_grants_handler),
+                )
+                .route(
+                    routes::DELETE_ADMIN_PERMISSIONS_GRANTS_GRANTID,
+                    delete(admin_permissions::admin_revoke_grant_handler),
+                )
+                .route(
+                    routes::GET_ADMIN_PERMISSIONS_GROUPS,
+                    get(admin_permissions::list_all_groups_handler),
+                )
+                .route(
+                    routes::GET_ADMIN_PERMISSIONS_LINKS,
+                    get(admin_permissions::list_all_links_handler),
+                )
+                .route(
+                    routes::DELETE_ADMIN_PERMISSIONS_LINKS_LINKID,
+                    delete(admin_permissions::admin_revoke_link_handler),
+                )
+                .into_router()
+                .route_layer(needs(rbac::PERM_PERMISSIONS_ADMIN)),
+        )
+        // Admin users and roles
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(
+                    routes::PATCH_ADMIN_PERMISSIONS_USERS_USERID_ADMIN,
+                    patch(admin_permissions::set_admin_role_handler),
+                )
+                .route(
+                    routes::GET_ADMIN_ROLES,
+                    get(admin_roles::list_roles_handler),
+                )
+                .route(
+                    routes::POST_ADMIN_ROLES,
+                    post(admin_roles::create_role_handler),
+                )
+                .route(
+                    routes::PATCH_ADMIN_ROLES_ID,
+                    patch(admin_roles::update_role_handler),
+                )
+                .route(
+                    routes::DELETE_ADMIN_ROLES_ID,
+                    delete(admin_roles::delete_role_handler),
+                )
+                .route(
+                    routes::GET_ADMIN_USERS_USERID_ROLES,
+                    get(admin_roles::list_user_roles_handler),
+                )
+                .route(
+                    routes::PUT_ADMIN_USERS_USERID_ROLES_ROLEID,
+                    put(admin_roles::assign_role_handler),
+                )
+                .route(
+                    routes::DELETE_ADMIN_USERS_USERID_ROLES_ROLEID,
+                    delete(admin_roles::unassign_role_handler),
+                )
+                .into_router()
+                .route_layer(needs(rbac::PERM_USERS_MANAGE)),
         )
         // Admin MCP servers
-        .route(
-            routes::GET_MCP_ADMIN_SERVERS,
-            get(mcp_config::admin_list_servers_handler),
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(
+                    routes::GET_MCP_ADMIN_SERVERS,
+                    get(mcp_config::admin_list_servers_handler),
+                )
+                .route(
+                    routes::POST_MCP_ADMIN_SERVERS,
+                    post(mcp_config::admin_create_server_handler),
+                )
+                .route(
+                    routes::PATCH_MCP_ADMIN_SERVERS_SERVERID,
+                    patch(mcp_config::admin_update_server_handler),
+                )
+                .route(
+                    routes::DELETE_MCP_ADMIN_SERVERS_SERVERID,
+                    delete(mcp_config::admin_delete_server_handler),
+                )
+                .route(
+                    routes::POST_MCP_ADMIN_TEST_ALL,
+                    post(mcp_config::admin_test_all_servers_handler),
+                )
+                .route(
+                    routes::PATCH_MCP_ADMIN_SERVERS_SERVERID_TOOLS_TOOLID,
+                    patch(mcp_config::admin_update_tool_handler),
+                )
+                .route(
+                    routes::PUT_MCP_ADMIN_SERVERS_SERVERID_TOOLS_TOOLID_TRANSFORM,
+                    put(mcp_config::admin_set_transform_handler),
+                )
+                .route(
+                    routes::DELETE_MCP_ADMIN_SERVERS_SERVERID_TOOLS_TOOLID_TRANSFORM,
+                    delete(mcp_config::admin_delete_transform_handler),
+                )
+                .route(
+                    routes::GET_MCP_ADMIN_TRANSFORMS,
+                    get(mcp_config::admin_list_transforms_handler),
+                )
+                .route(
+                    routes::GET_MCP_ADMIN_ROUTING,
+                    get(mcp_config::admin_list_routes_handler),
+                )
+                .route(
+                    routes::PUT_MCP_ADMIN_ROUTING_DOMAIN,
+                    put(mcp_config::admin_set_route_handler),
+                )
+                .route(
+                    routes::DELETE_MCP_ADMIN_ROUTING_DOMAIN,
+                    delete(mcp_config::admin_delete_route_handler),
+                )
+                .route(
+                    routes::PUT_MCP_ADMIN_CATALOG_SLUG,
+                    put(mcp_config::admin_upsert_catalog_entry_handler),
+                )
+                .route(
+                    routes::DELETE_MCP_ADMIN_CATALOG_SLUG,
+                    delete(mcp_config::admin_delete_catalog_entry_handler),
+                )
+                .route(
+                    routes::POST_MCP_ADMIN_CATALOG_SLUG_INSTALL,
+                    post(mcp_config::admin_install_catalog_entry_handler),
+                )
+                .route(
+                    routes::GET_MCP_ADMIN_PROCESSES,
+                    get(mcp_config::admin_list_processes_handler),
+                )
+                .route(
+                    routes::GET_MCP_ADMIN_RECORDINGS,
+                    get(mcp_recordings::list_recordings_handler),
+                )
+                .route(
+                    routes::POST_MCP_ADMIN_RECORDINGS,
+                    post(mcp_recordings::start_recording_handler),
+                )
+                .route(
+                    routes::GET_MCP_ADMIN_RECORDINGS_ID,
+                    get(mcp_recordings::get_recording_handler),
+                )
+                .route(
+                    routes::DELETE_MCP_ADMIN_RECORDINGS_ID,
+                    delete(mcp_recordings::delete_recording_handler),
+                )
+                .route(
+                    routes::POST_MCP_ADMIN_RECORDINGS_ID_STOP,
+                    post(mcp_recordings::stop_recording_handler),
+                )
+                .route(
+                    routes::POST_MCP_ADMIN_RECORDINGS_ID_REPLAY,
+                    post(mcp_recordings::replay_recording_handler),
+                )
+                .into_router()
+                .route_layer(needs(rbac::PERM_MCP_ADMIN)),
         )
-        .route(
-            routes::POST_MCP_ADMIN_SERVERS,
-            post(mcp_config::admin_create_server_handler),
+        // Admin analytics
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(
+                    routes::GET_ADMIN_ANALYTICS_TOOLS,
+                    get(analytics::tool_analytics_handler),
+                )
+                .into_router()
+                .route_layer(needs(rbac::PERM_ANALYTICS_READ)),
         )
-        .route(
-            routes::PATCH_MCP_ADMIN_SERVERS_SERVERID,
-            patch(mcp_config::admin_update_server_handler),
+        // Admin diagnostics
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(
+                    routes::GET_ADMIN_DIAGNOSTICS_SLOW_QUERIES,
+                    get(diagnostics_handlers::slow_queries_handler),
+                )
+                .into_router()
+                .route_layer(needs(rbac::PERM_METRICS_READ)),
         )
-        .route(
-            routes::DELETE_MCP_ADMIN_SERVERS_SERVERID,
-            delete(mcp_config::admin_delete_server_handler),
+        // Admin feedback export
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(
+                    routes::GET_ADMIN_FEEDBACK_EXPORT,
+                    get(feedback::export_feedback_handler),
+                )
+                .into_router()
+                .route_layer(needs(rbac::PERM_FEEDBACK_EXPORT)),
         )
-        // Admin embeddings
-        .route(
-            "/admin/embeddings/models",
-            get(embeddings::list_models_handler),
+        // Admin evals
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(
+                    routes::GET_ADMIN_EVALS_SUITES,
+                    get(evals::list_suites_handler),
+                )
+                .route(
+                    routes::POST_ADMIN_EVALS_RUNS,
+                    post(evals::create_run_handler),
+                )
+                .route(routes::GET_ADMIN_EVALS_RUNS, get(evals::list_runs_handler))
+                .route(routes::GET_ADMIN_EVALS_RUNS_ID, get(evals::get_run_handler))
+                .into_router()
+                .route_layer(needs(rbac::PERM_EVALS_RUN)),
         )
-        .route("/admin/embeddings/search", post(embeddings::search_handler))
-        .route(
-            "/admin/embeddings/reindex",
-            post(embeddings::reindex_handler),
+        // Admin signing keys
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(
+                    routes::GET_ADMIN_AUTH_KEYS,
+                    get(signing_keys::list_keys_handler),
+                )
+                .route(
+                    routes::POST_ADMIN_AUTH_KEYS_ROTATE,
+                    post(signing_keys::rotate_key_handler),
+                )
+                .into_router()
+                .route_layer(needs(rbac::PERM_AUTH_KEYS)),
+        )
+        // Admin announcements
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(
+                    routes::GET_ADMIN_ANNOUNCEMENTS,
+                    get(announcements::admin_list_announcements_handler),
+                )
+                .route(
+                    routes::POST_ADMIN_ANNOUNCEMENTS,
+                    post(announcements::admin_create_announcement_handler),
+                )
+                .route(
+                    routes::PUT_ADMIN_ANNOUNCEMENTS_ID,
+                    put(announcements::admin_update_announcement_handler),
+                )
+                .route(
+                    routes::DELETE_ADMIN_ANNOUNCEMENTS_ID,
+                    delete(announcements::admin_delete_announcement_handler),
+                )
+                .into_router()
+                .route_layer(needs(rbac::PERM_ANNOUNCEMENTS_MANAGE)),
+        )
+        // Admin content moderation
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(
+                    routes::GET_ADMIN_MODERATION_RULES,
+                    get(moderation::list_rules_handler),
+                )
+                .route(
+                    routes::POST_ADMIN_MODERATION_RULES,
+                    post(moderation::create_rule_handler),
+                )
+                .route(
+                    routes::PUT_ADMIN_MODERATION_RULES_ID,
+                    put(moderation::update_rule_handler),
+                )
+                .route(
+                    routes::DELETE_ADMIN_MODERATION_RULES_ID,
+                    delete(moderation::delete_rule_handler),
+                )
+                .route(
+                    routes::GET_ADMIN_MODERATION_RESULTS,
+                    get(moderation::list_results_handler),
+                )
+                .route(
+                    routes::POST_ADMIN_MODERATION_RESULTS_ID_REVIEW,
+                    post(moderation::review_result_handler),
+                )
+                .into_router()
+                .route_layer(needs(rbac::PERM_MODERATION_MANAGE)),
+        )
+        // Admin embeddings
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(
+                    "/admin/embeddings/models",
+                    get(embeddings::list_models_handler),
+                )
+                .route(embeddings::SEARCH_ROUTE, post(embeddings::search_handler))
+                .route(
+                    "/admin/embeddings/reindex",
+                    post(embeddings::reindex_handler),
+                )
+                .route("/admin/embeddings/stale", get(embeddings::stale_handler))
+                .route(
+                    "/admin/embeddings/indexes",
+                    get(embeddings::list_indexes_handler),
+                )
+                .route(
+                    "/admin/embeddings/indexes/rebuild",
+                    post(embeddings::rebuild_indexes_handler),
+                )
+                .into_router()
+                .route_layer(needs(rbac::PERM_EMBEDDINGS_ADMIN)),
         )
         // Dev trace
-        .route(routes::GET_DEV_CHAT_TRACE, get(trace::chat_trace_handler))
+        .merge(
+            TierRouter::new(AuthTier::Admin)
+                .route(routes::GET_DEV_CHAT_TRACE, get(trace::chat_trace_handler))
+                .into_router()
+                .route_layer(needs(rbac::PERM_DEV_TRACE)),
+        )
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::idempotency::deduplicate,
+        ))
         .layer(axum::middleware::from_fn_with_state(
             state.clone(),
-            middleware::auth::require_admin,
+            middleware::auth::require_auth,
         ));
 
     // All routes are nested under /api so they don't collide with
     // the Next.js frontend routes when served on the same origin.
-    let api = Router::new().merge(public).merge(protected).merge(admin);
+    let api = Router::new()
+        .merge(public)
+        .merge(protected)
+        .merge(admin)
+        .layer(axum::middleware::from_fn(middleware::version::negotiate))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::csrf::verify_csrf,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::read_only::reject_mutations,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::audit::flush_audit,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::locale::localize_errors,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::metrics::track_metrics,
+        ))
+        .layer(axum::middleware::from_fn(
+            middleware::request_context::trace_request,
+        ));
 
+    // Metrics scrape endpoint (`metrics.read`, or loopback when local-only is set).
+    let metrics = Router::new()
+        .route("/metrics", get(metrics_handlers::metrics_handler))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::metrics::require_metrics_access,
+        ));
+
+    // Versioned routes, plus the unversioned alias older clients use.
     Router::new()
+        .nest(&middleware::version::versioned_prefix(), api.clone())
         .nest(API_PREFIX, api)
+        .merge(metrics)
+        .route("/readyz", get(health::readyz_handler))
         .layer(cors)
         .with_state(state)
 }
+
+/// Router for the dedicated OAuth callback listener
+/// ([`ApiConfig::oauth_callback_port`]): only the MCP OAuth callback, at
+/// the same path as on the API.
+pub fn oauth_callback_router(state: AppState) -> Router {
+    Router::new()
+        .route(
+            &format!("{API_PREFIX}{}", routes::GET_AUTH_OAUTH_MCP_CALLBACK),
+            get(oauth::oauth_callback_handler),
+        )
+        .with_state(state)
+}
+
+/// Router for one auth tier. Registering a route checks it against the
+/// tier the API spec's security requirements assign it
+/// ([`routes::ROUTE_AUTH`]), so a spec route added to the wrong tier fails
+/// when the router is built. Routes outside the spec are not checked.
+struct TierRouter {
+    tier: AuthTier,
+    router: Router<AppState>,
+}
+
+impl TierRouter {
+    fn new(tier: AuthTier) -> Self {
+        Self {
+            tier,
+            router: Router::new(),
+        }
+    }
+
+    /// Add a route; panics if the spec puts any operation on `path` in a
+    /// different tier.
+    fn route(self, path: &str, method_router: MethodRouter<AppState>) -> Self {
+        for (method, _, tier) in routes::ROUTE_AUTH.iter().filter(|(_, p, _)| *p == path) {
+            assert_eq!(
+                *tier, self.tier,
+                "{method} {path} is {tier:?} in the API spec but registered as {:?}",
+                self.tier
+            );
+        }
+        Self {
+            tier: self.tier,
+            router: self.router.route(path, method_router),
+        }
+    }
+
+    fn into_router(self) -> Router<AppState> {
+        self.router
+    }
+}
+
+/// Builds a database-free mock router that answers every spec operation
+/// with its generated example response, nested under [`API_PREFIX`].
+///
+/// Intended for contract tests of the frontend and `nize_api_client`.
+#[cfg(feature = "mock-server")]
+pub fn mock_router() -> Router {
+    Router::new().nest(API_PREFIX, generated::mock::mock_routes())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::test_support::TestStateBuilder;
+
+    /// Routes answer under the versioned prefix and the unversioned alias,
+    /// and a client asking the alias for an unknown version is turned away.
+    #[tokio::test]
+    async fn routes_are_served_under_both_prefixes() {
+        use middleware::version::VERSION_HEADER;
+
+        let app = router(TestStateBuilder::new().build());
+        for prefix in [
+            middleware::version::versioned_prefix(),
+            API_PREFIX.to_string(),
+        ] {
+            let req = Request::builder()
+                .uri(format!("{prefix}{}", routes::GET_AUTH_STATUS))
+                .header(VERSION_HEADER, routes::API_VERSION)
+                .body(Body::empty())
+                .unwrap();
+            let resp = app.clone().oneshot(req).await.expect("request");
+            assert_ne!(resp.status(), StatusCode::NOT_FOUND, "{prefix}");
+            assert_eq!(resp.headers()[VERSION_HEADER], routes::API_VERSION);
+        }
+
+        let req = Request::builder()
+            .uri(format!("{API_PREFIX}{}", routes::GET_AUTH_STATUS))
+            .header(VERSION_HEADER, "v999")
+            .body(Body::empty())
+            .unwrap();
+        let resp = app.oneshot(req).await.expect("request");
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    /// Every spec operation is routed, and only public ones answer without
+    /// credentials. (Building the router also checks admin vs protected.)
+    #[tokio::test]
+    async fn spec_routes_are_registered_in_their_auth_tier() {
+        let app = router(TestStateBuilder::new().build());
+        for (method, path, tier) in routes::ROUTE_AUTH {
+            let uri = path
+                .split('/')
+                .map(|s| {
+                    if s.starts_with('{') {
+                        "00000000-0000-0000-0000-000000000000"
+                    } else {
+                        s
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            let req = Request::builder()
+                .method(*method)
+                .uri(format!("{API_PREFIX}{uri}"))
+                .header("content-type", "application/json")
+                .body(Body::from("{}"))
+                .unwrap();
+            let resp = app.clone().oneshot(req).await.expect("request");
+            let status = resp.status();
+            let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+            // Public handlers may reject with 401 themselves; only the auth
+            // middleware answers "Missing authentication".
+            let gated =
+                status == StatusCode::UNAUTHORIZED && json["message"] == "Missing authentication";
+
+            assert_ne!(
+                status,
+                StatusCode::NOT_FOUND,
+                "{method} {path} is not routed"
+            );
+            assert_ne!(
+                status,
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{method} {path} is not routed"
+            );
+            assert_eq!(
+                gated,
+                *tier != AuthTier::Public,
+                "{method} {path} is {tier:?} in the API spec"
+            );
+        }
+    }
+}
@@ -0,0 +1,432 @@
+//! In-process request metrics with Prometheus text exposition.
+//!
+//! A deliberately small registry: request counters and latency histograms
+//! keyed by method, matched route template and status code, plus gauges for
+//! the database pool, the MCP client pool, each managed MCP process and each
+//! in-memory cache (see [`nize_core::cache`]).
+//! Rendered on demand by
+//! `GET /metrics` so self-hosters can scrape without an OTel collector.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use nize_core::cache::{self, CacheSnapshot};
+use nize_core::mcp::execution::{ChildProcessStats, ClientPool};
+use sqlx::PgPool;
+
+/// Latency histogram bucket upper bounds, in seconds.
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Route label used for requests that did not match any route.
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Label set identifying one request series.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct RouteKey {
+    method: String,
+    route: String,
+    status: u16,
+}
+
+/// Accumulated stats for one request series.
+#[derive(Debug, Clone, Default)]
+struct RouteStats {
+    count: u64,
+    sum_seconds: f64,
+    /// Cumulative counts per entry in [`LATENCY_BUCKETS`].
+    buckets: [u64; LATENCY_BUCKETS.len()],
+}
+
+/// Size of the MCP client pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpPoolStats {
+    /// Pooled MCP client connections.
+    pub connections: usize,
+    /// Pooled connections backed by a managed process.
+    pub managed_processes: usize,
+    /// Resident memory of all managed processes, in bytes.
+    pub managed_memory_bytes: u64,
+    /// Managed processes spawned again since the pool was created.
+    pub restarts: u32,
+}
+
+/// Shared metrics registry held in [`crate::AppState`].
+#[derive(Default)]
+pub struct MetricsRegistry {
+    routes: Mutex<BTreeMap<RouteKey, RouteStats>>,
+    mcp_pool: OnceLock<Arc<ClientPool>>,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the MCP client pool so its size is exported as a gauge.
+    ///
+    /// Only the first registration takes effect.
+    pub fn set_mcp_pool(&self, pool: Arc<ClientPool>) {
+        let _ = self.mcp_pool.set(pool);
+    }
+
+    /// Size of the registered MCP client pool, if any.
+    pub fn mcp_pool_stats(&self) -> Option<McpPoolStats> {
+        self.mcp_pool
+            .get()
+            .map(|pool| pool_stats(pool, &pool.child_stats()))
+    }
+
+    /// Resource usage of each managed MCP process; empty when no pool is
+    /// registered.
+    pub fn mcp_child_stats(&self) -> Vec<ChildProcessStats> {
+        self.mcp_pool
+            .get()
+            .map(|pool| pool.child_stats())
+            .unwrap_or_default()
+    }
+
+    /// Record one completed request.
+    pub fn record(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        let key = RouteKey {
+            method: method.to_string(),
+            route: route.to_string(),
+            status,
+        };
+        let mut routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = routes.entry(key).or_default();
+        stats.count += 1;
+        stats.sum_seconds += secs;
+        for (bucket, bound) in stats.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if secs <= bound {
+                *bucket += 1;
+            }
+        }
+    }
+
+    /// Render all metrics in the Prometheus text exposition format (v0.0.4).
+    pub fn render(&self, db_pool: &PgPool) -> String {
+        let mut out = String::new();
+        self.render_requests(&mut out);
+
+        write_gauge(
+            &mut out,
+            "nize_db_pool_connections",
+            "Open database connections.",
+            db_pool.size() as f64,
+        );
+        write_gauge(
+            &mut out,
+            "nize_db_pool_idle_connections",
+            "Idle database connections.",
+            db_pool.num_idle() as f64,
+        );
+
+        if let Some(pool) = self.mcp_pool.get() {
+            // Sample the processes once: CPU usage is measured between samples.
+            let children = pool.child_stats();
+            let stats = pool_stats(pool, &children);
+            write_gauge(
+                &mut out,
+                "nize_mcp_pool_connections",
+                "Pooled MCP client connections.",
+                stats.connections as f64,
+            );
+            write_gauge(
+                &mut out,
+                "nize_mcp_pool_managed_processes",
+                "Pooled MCP connections backed by a managed process.",
+                stats.managed_processes as f64,
+            );
+            write_gauge(
+                &mut out,
+                "nize_mcp_pool_restarts",
+                "Managed MCP processes spawned again since startup.",
+                stats.restarts as f64,
+            );
+            render_children(&mut out, &children);
+        }
+
+        render_caches(&mut out, &cache::stats());
+
+        out
+    }
+
+    fn render_requests(&self, out: &mut String) {
+        let routes = self.routes.lock().unwrap_or_else(|e| e.into_inner());
+
+        let _ = writeln!(
+            out,
+            "# HELP nize_http_requests_total Total HTTP requests handled."
+        );
+        let _ = writeln!(out, "# TYPE nize_http_requests_total counter");
+        for (key, stats) in routes.iter() {
+            let _ = writeln!(
+                out,
+                "nize_http_requests_total{{{}}} {}",
+                labels(key),
+                stats.count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP nize_http_request_duration_seconds HTTP request latency."
+        );
+        let _ = writeln!(out, "# TYPE nize_http_request_duration_seconds histogram");
+        for (key, stats) in routes.iter() {
+            let labels = labels(key);
+            for (count, bound) in stats.buckets.iter().zip(LATENCY_BUCKETS) {
+                let _ = writeln!(
+                    out,
+                    "nize_http_request_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {count}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "nize_http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                stats.count
+            );
+            let _ = writeln!(
+                out,
+                "nize_http_request_duration_seconds_sum{{{labels}}} {}",
+                stats.sum_seconds
+            );
+            let _ = writeln!(
+                out,
+                "nize_http_request_duration_seconds_count{{{labels}}} {}",
+                stats.count
+            );
+        }
+    }
+}
+
+fn pool_stats(pool: &ClientPool, children: &[ChildProcessStats]) -> McpPoolStats {
+    McpPoolStats {
+        connections: pool.connection_count(),
+        managed_processes: pool.managed_count(),
+        managed_memory_bytes: children.iter().map(|c| c.memory_bytes).sum(),
+        restarts: pool.total_restarts(),
+    }
+}
+
+/// Name, help text and value of a per-process gauge.
+type ChildGauge = (&'static str, &'static str, fn(&ChildProcessStats) -> f64);
+
+/// Per-process gauges for managed MCP servers, labelled by server.
+fn render_children(out: &mut String, children: &[ChildProcessStats]) {
+    let gauges: [ChildGauge; 4] = [
+        (
+            "nize_mcp_child_memory_bytes",
+            "Resident memory of a managed MCP process.",
+            |c| c.memory_bytes as f64,
+        ),
+        (
+            "nize_mcp_child_cpu_percent",
+            "CPU usage of a managed MCP process, in percent of one core.",
+            |c| c.cpu_percent as f64,
+        ),
+        (
+            "nize_mcp_child_uptime_seconds",
+            "Seconds since a managed MCP process was spawned.",
+            |c| c.uptime_secs as f64,
+        ),
+        (
+            "nize_mcp_child_restarts",
+            "Times a managed MCP server's process was spawned again.",
+            |c| c.restarts as f64,
+        ),
+    ];
+    for (name, help, value) in gauges {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} gauge");
+        for child in children {
+            let _ = writeln!(
+                out,
+                "{name}{{server_id=\"{}\",server=\"{}\"}} {}",
+                child.server_id,
+                escape_label(&child.server_name),
+                value(child)
+            );
+        }
+    }
+}
+
+/// Name, type, help text and value of a per-cache series.
+type CacheSeries = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&CacheSnapshot) -> f64,
+);
+
+/// Size and counters of each in-memory cache, labelled by cache name.
+fn render_caches(out: &mut String, caches: &[CacheSnapshot]) {
+    let series: [CacheSeries; 5] = [
+        (
+            "nize_cache_entries",
+            "gauge",
+            "Entries held by an in-memory cache.",
+            |c| c.entries as f64,
+        ),
+        (
+            "nize_cache_capacity",
+            "gauge",
+            "Most entries an in-memory cache holds.",
+            |c| c.capacity as f64,
+        ),
+        (
+            "nize_cache_hits_total",
+            "counter",
+            "Lookups answered by an in-memory cache.",
+            |c| c.hits as f64,
+        ),
+        (
+            "nize_cache_misses_total",
+            "counter",
+            "Lookups an in-memory cache could not answer.",
+            |c| c.misses as f64,
+        ),
+        (
+            "nize_cache_evictions_total",
+            "counter",
+            "Entries dropped from a full in-memory cache.",
+            |c| c.evictions as f64,
+        ),
+    ];
+    for (name, kind, help, value) in series {
+        let _ = writeln!(out, "# HELP {name} {help}");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        for c in caches {
+            let _ = writeln!(
+                out,
+                "{name}{{cache=\"{}\"}} {}",
+                escape_label(c.name),
+                value(c)
+            );
+        }
+    }
+}
+
+/// Format the label set for a request series.
+fn labels(key: &RouteKey) -> String {
+    format!(
+        "method=\"{}\",route=\"{}\",status=\"{}\"",
+        escape_label(&key.method),
+        escape_label(&key.route),
+        key.status
+    )
+}
+
+/// Escape a label value per the exposition format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn write_gauge(out: &mut String, name: &str, help: &str, value: f64) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} gauge");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rendered_requests(registry: &MetricsRegistry) -> String {
+        let mut out = String::new();
+        registry.render_requests(&mut out);
+        out
+    }
+
+    #[test]
+    fn caches_are_labelled_by_name() {
+        let mut out = String::new();
+        render_caches(
+            &mut out,
+            &[CacheSnapshot {
+                name: "config",
+                capacity: 100,
+                entries: 3,
+                hits: 7,
+                misses: 2,
+                evictions: 0,
+            }],
+        );
+        assert!(out.contains("# TYPE nize_cache_hits_total counter"));
+        assert!(out.contains("nize_cache_hits_total{cache=\"config\"} 7"));
+        assert!(out.contains("nize_cache_entries{cache=\"config\"} 3"));
+    }
+
+    #[test]
+    fn record_counts_per_route_and_status() {
+        let registry = MetricsRegistry::new();
+        registry.record("GET", "/api/hello", 200, Duration::from_millis(3));
+        registry.record("GET", "/api/hello", 200, Duration::from_millis(30));
+        registry.record("GET", "/api/hello", 500, Duration::from_millis(3));
+
+        let out = rendered_requests(&registry);
+        assert!(out.contains(
+            "nize_http_requests_total{method=\"GET\",route=\"/api/hello\",status=\"200\"} 2"
+        ));
+        assert!(out.contains(
+            "nize_http_requests_total{method=\"GET\",route=\"/api/hello\",status=\"500\"} 1"
+        ));
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let registry = MetricsRegistry::new();
+        registry.record("POST", "/api/chat", 200, Duration::from_millis(20));
+
+        let out = rendered_requests(&registry);
+        let labels = "method=\"POST\",route=\"/api/chat\",status=\"200\"";
+        assert!(out.contains(&format!(
+            "nize_http_request_duration_seconds_bucket{{{labels},le=\"0.01\"}} 0"
+        )));
+        assert!(out.contains(&format!(
+            "nize_http_request_duration_seconds_bucket{{{labels},le=\"0.025\"}} 1"
+        )));
+        assert!(out.contains(&format!(
+            "nize_http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} 1"
+        )));
+        assert!(out.contains(&format!(
+            "nize_http_request_duration_seconds_count{{{labels}}} 1"
+        )));
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn child_gauges_are_labelled_by_server() {
+        let child = ChildProcessStats {
+            server_id: uuid::Uuid::nil(),
+            server_name: "git \"local\"".into(),
+            transport: nize_core::models::mcp::TransportType::Stdio,
+            pid: 42,
+            uptime_secs: 90,
+            restarts: 2,
+            cpu_percent: 12.5,
+            memory_bytes: 1024,
+        };
+        let mut out = String::new();
+        render_children(&mut out, &[child]);
+        let labels =
+            "server_id=\"00000000-0000-0000-0000-000000000000\",server=\"git \\\"local\\\"\"";
+        assert!(out.contains(&format!("nize_mcp_child_memory_bytes{{{labels}}} 1024\n")));
+        assert!(out.contains(&format!("nize_mcp_child_cpu_percent{{{labels}}} 12.5\n")));
+        assert!(out.contains(&format!("nize_mcp_child_restarts{{{labels}}} 2\n")));
+        assert!(out.contains("# TYPE nize_mcp_child_uptime_seconds gauge"));
+    }
+}
@@ -0,0 +1,37 @@
+//! Audit middleware — one [`AuditLog`] per request.
+//!
+//! Handlers take the log from request extensions and pass it to services,
+//! which record entries instead of writing them. The log is flushed in one
+//! batch once the handler has returned, before the response is sent, so a
+//! client that reads the audit trail after a change sees the change's entry.
+
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::error;
+
+use nize_core::mcp::audit::AuditLog;
+
+use crate::AppState;
+
+/// Axum middleware: injects an [`AuditLog`] into request extensions and
+/// writes its entries after the handler has run. A failed flush is logged;
+/// the response is not affected.
+pub async fn flush_audit(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let log = AuditLog::new();
+    request.extensions_mut().insert(log.clone());
+
+    let response = next.run(request).await;
+
+    if !log.is_empty() {
+        let count = log.len();
+        if let Err(e) = log.flush(&state.pool).await {
+            error!(count, "Failed to write audit log: {e}");
+        }
+    }
+    response
+}
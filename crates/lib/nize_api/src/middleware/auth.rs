@@ -9,9 +9,11 @@ use axum::{
     response::Response,
 };
 use axum_extra::extract::CookieJar;
+use uuid::Uuid;
 
 use crate::AppState;
 use crate::error::AppError;
+use crate::i18n::Message;
 use crate::services::auth::{TokenClaims, verify_access_token};
 use crate::services::cookies::ACCESS_COOKIE;
 
@@ -29,23 +31,7 @@ pub async fn require_auth(
     mut request: Request,
     next: Next,
 ) -> Result<Response, AppError> {
-    // Try cookie first
-    let token = jar
-        .get(ACCESS_COOKIE)
-        .map(|c| c.value().to_string())
-        .or_else(|| {
-            // Fall back to Authorization: Bearer header
-            request
-                .headers()
-                .get(AUTHORIZATION)
-                .and_then(|v| v.to_str().ok())
-                .and_then(|h| h.strip_prefix("Bearer ").map(|t| t.to_string()))
-        })
-        .ok_or_else(|| AppError::Unauthorized("Missing authentication".into()))?;
-
-    // @awa-impl: AUTH-2_AC-4
-    let claims = verify_access_token(&token, state.config.jwt_secret.as_bytes())
-        .ok_or_else(|| AppError::Unauthorized("Invalid or expired token".into()))?;
+    let claims = authenticate(&state, &jar, &request)?;
 
     // @awa-impl: AUTH-2_AC-2
     request.extensions_mut().insert(AuthenticatedUser(claims));
@@ -53,34 +39,73 @@ pub async fn require_auth(
     Ok(next.run(request).await)
 }
 
-/// Axum middleware: requires the user to have an admin role.
-pub async fn require_admin(
-    State(state): State<AppState>,
-    jar: CookieJar,
-    mut request: Request,
-    next: Next,
-) -> Result<Response, AppError> {
-    // Re-use auth extraction logic
+/// Extract and verify the access token of a request (cookie first, then
+/// `Authorization: Bearer`). In local mode the local token is accepted as
+/// the local user's.
+pub(crate) fn authenticate(
+    state: &AppState,
+    jar: &CookieJar,
+    request: &Request,
+) -> Result<TokenClaims, AppError> {
+    // Try cookie first
     let token = jar
         .get(ACCESS_COOKIE)
         .map(|c| c.value().to_string())
         .or_else(|| {
+            // Fall back to Authorization: Bearer header
             request
                 .headers()
                 .get(AUTHORIZATION)
                 .and_then(|v| v.to_str().ok())
                 .and_then(|h| h.strip_prefix("Bearer ").map(|t| t.to_string()))
         })
-        .ok_or_else(|| AppError::Unauthorized("Missing authentication".into()))?;
-
-    let claims = verify_access_token(&token, state.config.jwt_secret.as_bytes())
-        .ok_or_else(|| AppError::Unauthorized("Invalid or expired token".into()))?;
+        .ok_or_else(|| AppError::unauthorized(Message::new("auth.missing_authentication")))?;
 
-    if !claims.roles.contains(&"admin".to_string()) {
-        return Err(AppError::Forbidden("Admin access required".into()));
+    if let Some(local_mode) = &state.local_mode
+        && local_mode.accepts(&token)
+    {
+        return Ok(local_mode.claims());
     }
 
-    request.extensions_mut().insert(AuthenticatedUser(claims));
+    // @awa-impl: AUTH-2_AC-4
+    verify_access_token(&token, &state.jwt_keys)
+        .ok_or_else(|| AppError::unauthorized(Message::new("auth.invalid_token")))
+}
+
+/// Axum middleware: requires the authenticated user to hold a permission.
+///
+/// Runs after [`require_auth`]; the state pairs the app state with the
+/// required permission string (see `nize_core::auth::rbac`). Permissions
+/// come from the user's roles and are read per request, so role changes
+/// apply without re-issuing tokens.
+pub async fn require_permission(
+    State((state, permission)): State<(AppState, &'static str)>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let user = request
+        .extensions()
+        .get::<AuthenticatedUser>()
+        .ok_or_else(|| AppError::unauthorized(Message::new("auth.missing_authentication")))?;
+    ensure_permission(&state, &user.0, permission).await?;
 
     Ok(next.run(request).await)
 }
+
+/// Fail with `Forbidden` unless the token's user holds `permission`.
+pub(crate) async fn ensure_permission(
+    state: &AppState,
+    claims: &TokenClaims,
+    permission: &str,
+) -> Result<(), AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))?;
+
+    let permissions = nize_core::auth::rbac::user_permissions(&state.pool, &user_id).await?;
+    if !nize_core::auth::rbac::grants(&permissions, permission) {
+        return Err(AppError::forbidden(
+            Message::new("auth.permission_required").arg("permission", permission),
+        ));
+    }
+    Ok(())
+}
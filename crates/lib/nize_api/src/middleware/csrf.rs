@@ -0,0 +1,202 @@
+//! CSRF protection for cookie-authenticated requests.
+//!
+//! Login, registration and refresh issue a random token twice: in the
+//! httpOnly [`CSRF_COOKIE`](crate::services::cookies::CSRF_COOKIE) and in
+//! the [`CSRF_HEADER`] response header. Browser clients keep the header
+//! value in memory and echo it on every mutation; [`verify_csrf`] rejects
+//! mutations whose header does not match the cookie.
+//!
+//! The check only applies where CSRF is possible: mutations that carry an
+//! auth cookie and come from a browser. Requests authenticated by a Bearer
+//! token, and server-side callers that forward cookies (no `Origin` or
+//! `Sec-Fetch-Site` header, which browsers always send on mutations), are
+//! exempt. A Bearer header beside an access cookie does not exempt a
+//! request: authentication prefers the cookie (see
+//! [`crate::middleware::auth`]), so the header proves nothing. When allowed origins are configured (see [`crate::cors`]),
+//! browser mutations from other origins are rejected outright.
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header::{AUTHORIZATION, ORIGIN};
+use axum::http::{HeaderMap, Method};
+use axum::middleware::Next;
+use axum::response::Response;
+use axum_extra::extract::CookieJar;
+use nize_core::auth::constant_time_eq;
+use rand::distr::Alphanumeric;
+use rand::{Rng, rng};
+
+use crate::AppState;
+use crate::cors::{self, AllowedOrigins};
+use crate::error::AppError;
+use crate::generated::routes;
+use crate::i18n::Message;
+use crate::middleware::version;
+use crate::services::cookies::{ACCESS_COOKIE, CSRF_COOKIE, REFRESH_COOKIE};
+
+/// Request and response header carrying the CSRF token.
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Routes that establish a session (and issue the token) themselves.
+const EXEMPT_ROUTES: &[&str] = &[
+    routes::POST_AUTH_LOGIN,
+    routes::POST_AUTH_REGISTER,
+    routes::POST_AUTH_REFRESH,
+    routes::POST_AUTH_LOCAL,
+];
+
+/// Generate a new random CSRF token.
+pub fn new_token() -> String {
+    rng()
+        .sample_iter(&Alphanumeric)
+        .take(43)
+        .map(char::from)
+        .collect()
+}
+
+/// Axum middleware: rejects cookie-authenticated browser mutations without
+/// a matching CSRF token, or from an origin outside the allowed list.
+pub async fn verify_csrf(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if needs_check(&jar, &request) {
+        let allowed = cors::allowed_origins(&state).await;
+        check(&allowed, &jar, request.headers())?;
+    }
+    Ok(next.run(request).await)
+}
+
+fn needs_check(jar: &CookieJar, request: &Request) -> bool {
+    let headers = request.headers();
+    let is_mutation = !matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    );
+    let has_access_cookie = jar.get(ACCESS_COOKIE).is_some();
+    let has_auth_cookie = has_access_cookie || jar.get(REFRESH_COOKIE).is_some();
+    // Only exempt Bearer requests the token authenticates: with an access
+    // cookie present, authentication uses the cookie.
+    let bearer_authenticates = !has_access_cookie
+        && headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("Bearer "));
+    let from_browser = headers.contains_key(ORIGIN) || headers.contains_key("sec-fetch-site");
+    let exempt = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|p| version::spec_path(p.as_str()))
+        .is_some_and(|route| EXEMPT_ROUTES.contains(&route));
+
+    is_mutation && has_auth_cookie && !bearer_authenticates && from_browser && !exempt
+}
+
+fn check(
+    allowed_origins: &AllowedOrigins,
+    jar: &CookieJar,
+    headers: &HeaderMap,
+) -> Result<(), AppError> {
+    if !allowed_origins.is_empty() {
+        let origin = headers.get(ORIGIN).and_then(|v| v.to_str().ok());
+        if let Some(origin) = origin
+            && !allowed_origins.matches(origin)
+        {
+            return Err(AppError::forbidden(
+                Message::new("csrf.origin_not_allowed").arg("origin", origin),
+            ));
+        }
+    }
+
+    let cookie = jar.get(CSRF_COOKIE).map(|c| c.value());
+    let header = headers.get(CSRF_HEADER).and_then(|v| v.to_str().ok());
+    match (cookie, header) {
+        (Some(cookie), Some(header)) if !cookie.is_empty() && constant_time_eq(cookie, header) => {
+            Ok(())
+        }
+        _ => Err(AppError::forbidden(Message::new("csrf.invalid_token"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::header::COOKIE;
+    use axum::http::{HeaderValue, StatusCode};
+    use axum_extra::extract::cookie::Cookie;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::router;
+    use crate::test_support::TestStateBuilder;
+
+    fn jar(token: &str) -> CookieJar {
+        CookieJar::new().add(Cookie::new(CSRF_COOKIE, token.to_string()))
+    }
+
+    fn headers(origin: &str, token: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ORIGIN, HeaderValue::from_str(origin).unwrap());
+        if let Some(token) = token {
+            headers.insert(CSRF_HEADER, HeaderValue::from_str(token).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn token_must_match_cookie() {
+        let token = new_token();
+        let any = AllowedOrigins::default();
+        assert!(check(&any, &jar(&token), &headers("http://a", Some(&token))).is_ok());
+        assert!(check(&any, &jar(&token), &headers("http://a", Some("other"))).is_err());
+        assert!(check(&any, &jar(&token), &headers("http://a", None)).is_err());
+        assert!(check(&any, &CookieJar::new(), &headers("http://a", Some(""))).is_err());
+    }
+
+    #[test]
+    fn origin_must_be_allowed_when_configured() {
+        let token = new_token();
+        let allowed = AllowedOrigins::parse(["http://localhost:3000"]);
+        let ok = headers("http://localhost:3000", Some(&token));
+        let evil = headers("http://localhost:8080", Some(&token));
+        assert!(check(&allowed, &jar(&token), &ok).is_ok());
+        assert!(check(&allowed, &jar(&token), &evil).is_err());
+    }
+
+    async fn post_from_browser(cookie: Option<&str>, bearer: Option<&str>) -> StatusCode {
+        let mut req = Request::builder()
+            .method(Method::POST)
+            .uri("/api/conversations")
+            .header(ORIGIN, "http://localhost:8080")
+            .header("content-type", "application/json");
+        if let Some(cookie) = cookie {
+            req = req.header(COOKIE, format!("{ACCESS_COOKIE}={cookie}"));
+        }
+        if let Some(bearer) = bearer {
+            req = req.header(AUTHORIZATION, format!("Bearer {bearer}"));
+        }
+        router(TestStateBuilder::new().build())
+            .oneshot(req.body(Body::from("{}")).unwrap())
+            .await
+            .expect("request")
+            .status()
+    }
+
+    #[tokio::test]
+    async fn bearer_beside_a_cookie_does_not_skip_the_check() {
+        assert_eq!(
+            post_from_browser(Some("session"), Some("junk")).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            post_from_browser(Some("session"), None).await,
+            StatusCode::FORBIDDEN
+        );
+        // Without a cookie the Bearer token authenticates, and fails here.
+        assert_eq!(
+            post_from_browser(None, Some("junk")).await,
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Idempotency-Key middleware.
+//!
+//! An authenticated `POST` carrying [`IDEMPOTENCY_KEY_HEADER`] runs at most
+//! once per user and key (see [`nize_core::idempotency`]): retries with the
+//! same method, path, workspace and body get the first response back,
+//! marked with [`REPLAYED_HEADER`]. Server errors release the key so a
+//! retry runs again. Requests or responses with a body over
+//! [`MAX_BODY_BYTES`] (uploads to `/ingest`, for one) and streamed
+//! responses are passed through without deduplication or storage.
+//! Stored bodies are encrypted with the deployment key. Requests without
+//! the header are untouched.
+
+use axum::body::{Body, Bytes, HttpBody};
+use axum::extract::{Request, State};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::{HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use futures_util::{StreamExt, stream};
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use nize_core::idempotency::{self, Claim, StoredResponse};
+
+use crate::AppState;
+use crate::error::AppError;
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+use crate::middleware::workspace::WORKSPACE_HEADER;
+
+/// Request header carrying the client's idempotency key.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Response header set to `true` on replayed responses.
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Largest request or response body handled; requests with a larger body
+/// run without deduplication, larger responses are not stored.
+pub const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Axum middleware: replays the stored response of a retried `POST`, or
+/// runs the request and stores its response. Runs after
+/// [`require_auth`](super::auth::require_auth).
+pub async fn deduplicate(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if request.method() != Method::POST {
+        return Ok(next.run(request).await);
+    }
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(next.run(request).await);
+    };
+    let key = key
+        .to_str()
+        .ok()
+        .filter(|key| idempotency::is_valid_key(key))
+        .ok_or_else(|| {
+            AppError::validation(
+                Message::new("idempotency.invalid_key").arg("max", idempotency::MAX_KEY_LEN),
+            )
+        })?
+        .to_string();
+    let user_id = request
+        .extensions()
+        .get::<AuthenticatedUser>()
+        .and_then(|user| Uuid::parse_str(&user.0.sub).ok())
+        .ok_or_else(|| AppError::unauthorized(Message::new("auth.missing_authentication")))?;
+
+    let (parts, body) = request.into_parts();
+    let body = match buffer_small(body).await? {
+        Ok(body) => body,
+        Err(body) => return Ok(next.run(Request::from_parts(parts, body)).await),
+    };
+    let workspace = parts
+        .headers
+        .get(WORKSPACE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|w| !w.is_empty());
+    let path = parts
+        .uri
+        .path_and_query()
+        .map_or(parts.uri.path(), |p| p.as_str());
+    let fingerprint = idempotency::fingerprint(parts.method.as_str(), path, workspace, &body);
+
+    let encryption_key = state.config.mcp_encryption_key.as_str();
+    match idempotency::claim(&state.pool, encryption_key, &user_id, &key, &fingerprint).await? {
+        Claim::Claimed => {}
+        Claim::InProgress => {
+            return Err(AppError::conflict(
+                Message::new("idempotency.in_progress").arg("key", &key),
+            ));
+        }
+        Claim::Mismatch => {
+            return Err(AppError::unprocessable(
+                Message::new("idempotency.key_reused").arg("key", &key),
+            ));
+        }
+        Claim::Completed(stored) => return Ok(replay(stored)),
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    Ok(finish(&state.pool, encryption_key, &user_id, &key, response).await)
+}
+
+/// Read `body` if it fits in [`MAX_BODY_BYTES`]; otherwise hand back an
+/// equivalent body, with what was already read put back in front.
+async fn buffer_small(body: Body) -> Result<Result<Bytes, Body>, AppError> {
+    if body.size_hint().lower() > MAX_BODY_BYTES as u64 {
+        return Ok(Err(body));
+    }
+    let mut read = Vec::new();
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk =
+            chunk.map_err(|_| AppError::validation(Message::new("idempotency.body_unreadable")))?;
+        read.extend_from_slice(&chunk);
+        if read.len() > MAX_BODY_BYTES {
+            let head = stream::once(async move { Ok::<_, axum::Error>(Bytes::from(read)) });
+            return Ok(Err(Body::from_stream(head.chain(stream))));
+        }
+    }
+    Ok(Ok(Bytes::from(read)))
+}
+
+/// Store `response` for the key, or release the key when it is not worth
+/// replaying.
+async fn finish(
+    pool: &PgPool,
+    encryption_key: &str,
+    user_id: &Uuid,
+    key: &str,
+    response: Response,
+) -> Response {
+    let storable = !response.status().is_server_error()
+        && response
+            .body()
+            .size_hint()
+            .exact()
+            .is_some_and(|len| len <= MAX_BODY_BYTES as u64);
+    if !storable {
+        release(pool, user_id, key).await;
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            release(pool, user_id, key).await;
+            return AppError::Internal(format!("buffer response: {e}")).into_response();
+        }
+    };
+    let stored = StoredResponse {
+        status: parts.status.as_u16(),
+        content_type: parts
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string),
+        body: bytes.to_vec(),
+    };
+    if let Err(e) = idempotency::complete(pool, encryption_key, user_id, key, &stored).await {
+        warn!("Failed to store idempotent response: {e}");
+        release(pool, user_id, key).await;
+    }
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+async fn release(pool: &PgPool, user_id: &Uuid, key: &str) {
+    if let Err(e) = idempotency::release(pool, user_id, key).await {
+        warn!("Failed to release idempotency key: {e}");
+    }
+}
+
+/// The stored response, marked as a replay.
+fn replay(stored: StoredResponse) -> Response {
+    let status = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+    let mut response = (status, stored.body).into_response();
+    let headers = response.headers_mut();
+    match stored
+        .content_type
+        .and_then(|v| HeaderValue::from_str(&v).ok())
+    {
+        Some(content_type) => headers.insert(CONTENT_TYPE, content_type),
+        None => headers.remove(CONTENT_TYPE),
+    };
+    headers.insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn replays_keep_status_and_content_type() {
+        let response = replay(StoredResponse {
+            status: 201,
+            content_type: Some("application/json".into()),
+            body: br#"{"id":"c1"}"#.to_vec(),
+        });
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+        assert_eq!(response.headers()[REPLAYED_HEADER], "true");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], br#"{"id":"c1"}"#);
+
+        let empty = replay(StoredResponse {
+            status: 204,
+            content_type: None,
+            body: Vec::new(),
+        });
+        assert_eq!(empty.status(), StatusCode::NO_CONTENT);
+        assert!(empty.headers().get(CONTENT_TYPE).is_none());
+    }
+
+    #[tokio::test]
+    async fn large_bodies_pass_through_intact() {
+        let small = buffer_small(Body::from("{}")).await.unwrap().unwrap();
+        assert_eq!(&small[..], b"{}");
+
+        let chunks: Vec<Result<Bytes, axum::Error>> = (0..3)
+            .map(|i| Ok(Bytes::from(vec![i as u8; MAX_BODY_BYTES / 2 + 1])))
+            .collect();
+        let body = Body::from_stream(stream::iter(chunks));
+        let Err(passed) = buffer_small(body).await.unwrap() else {
+            panic!("large body was buffered");
+        };
+        let bytes = axum::body::to_bytes(passed, usize::MAX).await.unwrap();
+        assert_eq!(bytes.len(), 3 * (MAX_BODY_BYTES / 2 + 1));
+        assert!(bytes[..MAX_BODY_BYTES / 2 + 1].iter().all(|&b| b == 0));
+        assert!(bytes[bytes.len() - 1] == 2);
+    }
+}
@@ -0,0 +1,112 @@
+//! Error message localization middleware.
+//!
+//! Error responses built from a keyed [`Message`] carry it as a response
+//! extension. This layer negotiates the caller's language from
+//! `Accept-Language` against the loaded [`Catalog`](crate::i18n::Catalog)
+//! and rewrites the body's `message` in that language. The error code is
+//! left alone: clients branch on `error`, people read `message`.
+
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::header::{ACCEPT_LANGUAGE, CONTENT_LANGUAGE, CONTENT_LENGTH, VARY};
+use axum::http::{HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use crate::AppState;
+use crate::i18n::{DEFAULT_LOCALE, Message};
+
+/// Error bodies are small; anything larger is left untouched upstream.
+const MAX_ERROR_BODY: usize = 64 * 1024;
+
+/// Axum middleware: renders keyed error messages in the negotiated
+/// language and marks the response with `Content-Language`.
+pub async fn localize_errors(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let accept_language = request
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned);
+
+    let mut response = next.run(request).await;
+    let Some(message) = response.extensions_mut().remove::<Message>() else {
+        return response;
+    };
+
+    let locale = state.i18n.negotiate(accept_language.as_deref());
+    let headers = response.headers_mut();
+    headers.append(VARY, HeaderValue::from_static("accept-language"));
+    if let Ok(value) = HeaderValue::from_str(locale) {
+        headers.insert(CONTENT_LANGUAGE, value);
+    }
+    if locale == DEFAULT_LOCALE {
+        // Already rendered in English.
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, MAX_ERROR_BODY).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let body = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(mut json) if json.get("message").is_some() => {
+            json["message"] = state.i18n.render(locale, &message).into();
+            parts.headers.remove(CONTENT_LENGTH);
+            Body::from(json.to_string())
+        }
+        _ => Body::from(bytes),
+    };
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use axum::http::{Method, Request};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::i18n::Catalog;
+    use crate::router;
+    use crate::test_support::TestStateBuilder;
+
+    async fn send(accept_language: Option<&str>) -> (Response, serde_json::Value) {
+        let mut req = Request::builder()
+            .method(Method::GET)
+            .uri("/api/conversations");
+        if let Some(value) = accept_language {
+            req = req.header(ACCEPT_LANGUAGE, value);
+        }
+        let catalog = Catalog::load(Some(&Path::new(env!("CARGO_MANIFEST_DIR")).join("locales")))
+            .expect("shipped bundles");
+        let resp = router(TestStateBuilder::new().i18n(catalog).build())
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .expect("request");
+        let (parts, body) = resp.into_parts();
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        let json = serde_json::from_slice(&bytes).unwrap();
+        (Response::from_parts(parts, Body::empty()), json)
+    }
+
+    #[tokio::test]
+    async fn errors_are_rendered_in_the_negotiated_language() {
+        let (resp, json) = send(Some("de-DE,de;q=0.9,en;q=0.8")).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(resp.headers()[CONTENT_LANGUAGE], "de");
+        assert_eq!(json["error"], "unauthorized");
+        assert_eq!(json["message"], "Anmeldung erforderlich");
+
+        let (resp, json) = send(None).await;
+        assert_eq!(resp.headers()[CONTENT_LANGUAGE], "en");
+        assert_eq!(json["message"], "Missing authentication");
+
+        let (_, json) = send(Some("ja")).await;
+        assert_eq!(json["message"], "Missing authentication");
+    }
+}
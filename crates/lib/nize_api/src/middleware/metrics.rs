@@ -0,0 +1,127 @@
+//! Metrics middleware — request recording and `/metrics` access control.
+
+use std::net::SocketAddr;
+use std::time::Instant;
+
+use axum::extract::{ConnectInfo, MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use axum_extra::extract::CookieJar;
+
+use nize_core::auth::rbac;
+
+use crate::AppState;
+use crate::error::AppError;
+use crate::metrics::UNMATCHED_ROUTE;
+
+/// Axum middleware: records count and latency for every request, labelled
+/// by the matched route template (not the raw path) to bound cardinality.
+pub async fn track_metrics(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    let start = Instant::now();
+
+    let response = next.run(request).await;
+
+    state
+        .metrics
+        .record(&method, &route, response.status().as_u16(), start.elapsed());
+    if route != UNMATCHED_ROUTE {
+        state
+            .telemetry
+            .record_request(&route, response.status().as_u16());
+    }
+    response
+}
+
+/// Axum middleware: guards `GET /metrics`.
+///
+/// When `metrics_local_only` is enabled, loopback clients may scrape without
+/// credentials. Everyone else needs a token with the `metrics.read`
+/// permission.
+pub async fn require_metrics_access(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if state.config.metrics_local_only && is_local_request(&state, &request) {
+        return Ok(next.run(request).await);
+    }
+    let claims = super::auth::authenticate(&state, &jar, &request)?;
+    super::auth::ensure_permission(&state, &claims, rbac::PERM_METRICS_READ).await?;
+    Ok(next.run(request).await)
+}
+
+/// Whether the request comes from a loopback peer.
+///
+/// Uses the peer address when the server was started with connect info;
+/// otherwise falls back to whether the listener itself is loopback-only.
+fn is_local_request(state: &AppState, request: &Request) -> bool {
+    match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => addr.ip().is_loopback(),
+        None => state
+            .config
+            .bind_addr
+            .parse::<SocketAddr>()
+            .is_ok_and(|addr| addr.ip().is_loopback()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    use crate::AppState;
+    use crate::router;
+    use crate::test_support::TestStateBuilder;
+
+    fn test_state(metrics_local_only: bool) -> AppState {
+        TestStateBuilder::new()
+            .config(|c| c.metrics_local_only = metrics_local_only)
+            .build()
+    }
+
+    async fn get(app: axum::Router, uri: &str) -> (StatusCode, String) {
+        let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let resp = app.oneshot(req).await.expect("request");
+        let status = resp.status();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("read body");
+        (status, String::from_utf8_lossy(&body).into_owned())
+    }
+
+    #[tokio::test]
+    async fn requests_are_labelled_by_route_template() {
+        let state = test_state(true);
+        let app = router(state.clone());
+
+        let (status, _) = get(app.clone(), "/api/conversations/abc").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, body) = get(app, "/metrics").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(
+            body.contains("route=\"/api/conversations/{id}\",status=\"401\"} 1"),
+            "unexpected metrics body: {body}"
+        );
+    }
+
+    #[tokio::test]
+    async fn metrics_require_admin_without_local_flag() {
+        let app = router(test_state(false));
+        let (status, _) = get(app, "/metrics").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+}
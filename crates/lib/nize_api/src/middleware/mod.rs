@@ -1,3 +1,12 @@
 //! Middleware layers.
 
+pub mod audit;
 pub mod auth;
+pub mod csrf;
+pub mod idempotency;
+pub mod locale;
+pub mod metrics;
+pub mod read_only;
+pub mod request_context;
+pub mod version;
+pub mod workspace;
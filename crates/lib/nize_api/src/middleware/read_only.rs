@@ -0,0 +1,130 @@
+//! Read-only mode middleware.
+//!
+//! With `read_only` set in [`ApiConfig`](crate::config::ApiConfig), every
+//! mutating request is rejected with 403 `read_only` — except the few that
+//! only manage the session or produce output without persisting anything
+//! ([`ALLOWED_MUTATIONS`]). Every response carries [`READ_ONLY_HEADER`] so
+//! clients (the chat app) can skip persistence instead of failing.
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::{HeaderValue, Method};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use crate::AppState;
+use crate::error::AppError;
+use crate::generated::routes;
+use crate::handlers::{ai_proxy, embeddings};
+use crate::middleware::version;
+
+/// Response header set to `true` while the server is read-only.
+pub const READ_ONLY_HEADER: &str = "x-nize-read-only";
+
+/// Non-GET routes still served in read-only mode.
+const ALLOWED_MUTATIONS: &[&str] = &[
+    routes::POST_AUTH_LOGIN,
+    routes::POST_AUTH_REFRESH,
+    routes::POST_AUTH_LOGOUT,
+    routes::POST_CHAT,
+    ai_proxy::ROUTE,
+    embeddings::SEARCH_ROUTE,
+];
+
+/// Axum middleware: rejects mutations in read-only mode and marks every
+/// response with [`READ_ONLY_HEADER`].
+pub async fn reject_mutations(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.config.read_only {
+        return next.run(request).await;
+    }
+
+    let mut response = if is_mutation(&request) && !is_allowed(&request) {
+        AppError::ReadOnly.into_response()
+    } else {
+        next.run(request).await
+    };
+    response
+        .headers_mut()
+        .insert(READ_ONLY_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+fn is_mutation(request: &Request) -> bool {
+    !matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    )
+}
+
+fn is_allowed(request: &Request) -> bool {
+    request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|p| version::spec_path(p.as_str()))
+        .is_some_and(|route| ALLOWED_MUTATIONS.contains(&route))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::router;
+    use crate::test_support::TestStateBuilder;
+
+    async fn send(read_only: bool, method: Method, uri: &str) -> Response {
+        let req = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from("{}"))
+            .unwrap();
+        router(
+            TestStateBuilder::new()
+                .config(|c| c.read_only = read_only)
+                .build(),
+        )
+        .oneshot(req)
+        .await
+        .expect("request")
+    }
+
+    #[tokio::test]
+    async fn mutations_are_rejected_in_read_only_mode() {
+        let resp = send(true, Method::POST, "/api/conversations").await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        assert_eq!(resp.headers()[READ_ONLY_HEADER], "true");
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"], "read_only");
+
+        // Reads pass through (and fail auth as usual).
+        let resp = send(true, Method::GET, "/api/conversations").await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(resp.headers()[READ_ONLY_HEADER], "true");
+
+        // Chat is allowed.
+        let resp = send(true, Method::POST, "/api/chat").await;
+        assert_ne!(resp.status(), StatusCode::FORBIDDEN);
+        let resp = send(true, Method::POST, "/api/v1/chat").await;
+        assert_ne!(resp.status(), StatusCode::FORBIDDEN);
+
+        // Testing a connection stores the discovered tools, so it is not.
+        let resp = send(true, Method::POST, "/api/mcp/test-connection").await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn writable_servers_are_unaffected() {
+        let resp = send(false, Method::POST, "/api/conversations").await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert!(resp.headers().get(READ_ONLY_HEADER).is_none());
+    }
+}
@@ -0,0 +1,73 @@
+//! Request ID and tracing span middleware.
+//!
+//! Every API request runs inside a [`REQUEST_SPAN`] span carrying its
+//! request ID, method and route template, so logs emitted while handling it
+//! — including sqlx slow statement warnings — can be traced back to the
+//! request. The ID is taken from the caller's `x-request-id` header when
+//! present and sensible, generated otherwise, and echoed on the response.
+
+use axum::extract::{MatchedPath, Request};
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::diagnostics::REQUEST_SPAN;
+use crate::metrics::UNMATCHED_ROUTE;
+
+/// Header carrying the request ID.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest caller-supplied request ID accepted.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Axum middleware: runs the request in a span tagged with its request ID
+/// and route, and returns the ID in `x-request-id`.
+pub async fn trace_request(request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| UNMATCHED_ROUTE.to_string());
+    let span = tracing::info_span!(
+        REQUEST_SPAN,
+        request_id = %request_id,
+        method = %request.method(),
+        route = %route,
+    );
+
+    let mut response = next.run(request).instrument(span).await;
+    // Proxied responses may already carry an upstream request ID.
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response
+            .headers_mut()
+            .entry(REQUEST_ID_HEADER)
+            .or_insert(value);
+    }
+    response
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_ids_must_be_short_visible_ascii() {
+        assert!(is_valid_request_id("0190a0f0-req"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("has space"));
+        assert!(!is_valid_request_id(&"x".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+}
@@ -0,0 +1,167 @@
+//! API versioning.
+//!
+//! Every route is served under `/api/{version}` ([`routes::API_VERSION`],
+//! e.g. `/api/v1`) and, for clients built before versioning, under the
+//! unversioned [`API_PREFIX`] alias. [`negotiate`] tags every response
+//! with [`VERSION_HEADER`], rejects requests that ask for a version this
+//! server does not serve, and marks responses of operations the spec
+//! deprecates ([`routes::ROUTE_DEPRECATIONS`]) with `Deprecation` and
+//! `Sunset` headers, so older desktop clients keep working while they
+//! learn what is going away.
+
+use axum::extract::{MatchedPath, OriginalUri, Request};
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use chrono::NaiveDate;
+
+use crate::API_PREFIX;
+use crate::error::AppError;
+use crate::generated::routes;
+use crate::i18n::Message;
+
+/// Request header naming the version a client expects; response header
+/// naming the version that served it.
+pub const VERSION_HEADER: &str = "x-nize-api-version";
+
+/// Versions this server serves.
+pub const SUPPORTED_VERSIONS: &[&str] = &[routes::API_VERSION];
+
+/// The API version that served a request, in its extensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiVersion(pub &'static str);
+
+/// Route prefix of the current version (`/api/v1`).
+pub fn versioned_prefix() -> String {
+    format!("{API_PREFIX}/{}", routes::API_VERSION)
+}
+
+/// Split a request path into the version it addresses (`None` for the
+/// unversioned alias) and the spec path. `None` outside [`API_PREFIX`].
+pub fn split_path(path: &str) -> Option<(Option<&str>, &str)> {
+    let rest = path.strip_prefix(API_PREFIX)?;
+    if !(rest.is_empty() || rest.starts_with('/')) {
+        return None;
+    }
+    let segment = rest[1.min(rest.len())..].split('/').next().unwrap_or("");
+    if is_version(segment) {
+        Some((Some(segment), &rest[1 + segment.len()..]))
+    } else {
+        Some((None, rest))
+    }
+}
+
+/// The spec path (`/auth/login`) of a request path under either prefix.
+pub fn spec_path(path: &str) -> Option<&str> {
+    split_path(path).map(|(_, spec)| spec)
+}
+
+fn is_version(segment: &str) -> bool {
+    segment
+        .strip_prefix('v')
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Sunset date of a deprecated operation: `Some(None)` when it has none.
+pub fn deprecation(method: &str, spec_path: &str) -> Option<Option<&'static str>> {
+    routes::ROUTE_DEPRECATIONS
+        .iter()
+        .find(|(m, p, _)| *m == method && *p == spec_path)
+        .map(|(_, _, sunset)| *sunset)
+}
+
+/// An ISO date (`2027-01-31`) as an HTTP date, as `Sunset` expects.
+fn http_date(date: &str) -> Option<String> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(
+        date.and_hms_opt(0, 0, 0)?
+            .and_utc()
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string(),
+    )
+}
+
+/// Axum middleware: version negotiation and deprecation headers.
+pub async fn negotiate(mut request: Request, next: Next) -> Response {
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let addressed = split_path(&path).and_then(|(version, _)| version);
+
+    // A version in the path wins; the header only matters on the alias.
+    if addressed.is_none()
+        && let Some(requested) = request
+            .headers()
+            .get(VERSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+        && !SUPPORTED_VERSIONS.contains(&requested)
+    {
+        return AppError::validation(
+            Message::new("api.unsupported_version")
+                .arg("version", requested)
+                .arg("supported", SUPPORTED_VERSIONS.join(", ")),
+        )
+        .into_response();
+    }
+
+    let deprecated = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|p| spec_path(p.as_str()))
+        .and_then(|spec| deprecation(request.method().as_str(), spec));
+    request
+        .extensions_mut()
+        .insert(ApiVersion(routes::API_VERSION));
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(
+        VERSION_HEADER,
+        HeaderValue::from_static(routes::API_VERSION),
+    );
+    if let Some(sunset) = deprecated {
+        headers.insert("deprecation", HeaderValue::from_static("true"));
+        if let Some(value) = sunset
+            .and_then(http_date)
+            .and_then(|d| HeaderValue::from_str(&d).ok())
+        {
+            headers.insert("sunset", value);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_split_into_version_and_spec_path() {
+        let v = routes::API_VERSION;
+        assert_eq!(
+            split_path(&format!("/api/{v}/auth/login")),
+            Some((Some(v), "/auth/login"))
+        );
+        assert_eq!(split_path("/api/auth/login"), Some((None, "/auth/login")));
+        assert_eq!(split_path("/api/vaults/1"), Some((None, "/vaults/1")));
+        assert_eq!(split_path("/api/v2"), Some((Some("v2"), "")));
+        assert_eq!(split_path("/apix/notes"), None);
+        assert_eq!(split_path("/metrics"), None);
+        assert_eq!(
+            spec_path(&format!("{}/notes", versioned_prefix())),
+            Some("/notes")
+        );
+    }
+
+    #[test]
+    fn sunset_dates_are_http_dates() {
+        assert_eq!(
+            http_date("2027-01-31").as_deref(),
+            Some("Sun, 31 Jan 2027 00:00:00 GMT")
+        );
+        assert_eq!(http_date("soon"), None);
+    }
+}
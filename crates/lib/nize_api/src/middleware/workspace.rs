@@ -0,0 +1,80 @@
+//! Workspace middleware — resolves the active workspace from the
+//! `X-Nize-Workspace` request header.
+//!
+//! Runs after [`require_auth`](super::auth::require_auth). Without the header
+//! (or with an empty one) requests act on the user's personal resources.
+//! With it, the caller must be a member of the named workspace.
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+use nize_core::workspaces::{Membership, Scope};
+
+use crate::AppState;
+use crate::error::AppError;
+use crate::i18n::Message;
+use crate::middleware::auth::AuthenticatedUser;
+
+/// Request header naming the active workspace.
+pub const WORKSPACE_HEADER: &str = "x-nize-workspace";
+
+/// The active workspace of a request, if any.
+#[derive(Debug, Clone, Copy)]
+pub struct ActiveWorkspace(pub Option<Membership>);
+
+impl ActiveWorkspace {
+    /// Resource scope for `user_id` in this workspace.
+    pub fn scope(&self, user_id: Uuid) -> Scope {
+        Scope {
+            user_id,
+            workspace: self.0,
+        }
+    }
+}
+
+/// Axum middleware: reads `X-Nize-Workspace`, checks the caller's membership
+/// and injects [`ActiveWorkspace`] into request extensions.
+pub async fn resolve_workspace(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let header = request
+        .headers()
+        .get(WORKSPACE_HEADER)
+        .map(|v| {
+            v.to_str()
+                .map(|s| s.trim().to_string())
+                .map_err(|_| AppError::validation(Message::new("workspace.invalid_header")))
+        })
+        .transpose()?
+        .filter(|s| !s.is_empty());
+
+    let membership = match header {
+        None => None,
+        Some(value) => {
+            let workspace_id = Uuid::parse_str(&value)
+                .map_err(|_| AppError::validation(Message::new("workspace.invalid_id")))?;
+            let user = request
+                .extensions()
+                .get::<AuthenticatedUser>()
+                .ok_or_else(|| {
+                    AppError::unauthorized(Message::new("auth.missing_authentication"))
+                })?;
+            let user_id = Uuid::parse_str(&user.0.sub)
+                .map_err(|_| AppError::unauthorized(Message::new("auth.invalid_user_id")))?;
+            let membership =
+                nize_core::workspaces::get_membership(&state.pool, &workspace_id, &user_id)
+                    .await?
+                    .ok_or_else(|| AppError::forbidden(Message::new("workspace.not_member")))?;
+            Some(membership)
+        }
+    };
+
+    request.extensions_mut().insert(ActiveWorkspace(membership));
+    Ok(next.run(request).await)
+}
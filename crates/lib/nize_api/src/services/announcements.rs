@@ -0,0 +1,58 @@
+//! Announcement publishing.
+//!
+//! Every [`TICK`] (and right after an admin saves an announcement) the
+//! publisher takes announcements whose window has started and broadcasts an
+//! `announcement.published` event for each, so connected clients show them
+//! without polling; the desktop app raises critical ones as native
+//! notifications. Publication is recorded in the database, so each
+//! announcement goes out once across restarts.
+
+use std::time::Duration;
+
+use tracing::warn;
+
+use nize_core::announcements::{self, Announcement};
+
+use crate::AppState;
+use crate::events::{KIND_ANNOUNCEMENT_PUBLISHED, ServerEvent};
+
+/// How often the publisher looks for announcements that became active.
+const TICK: Duration = Duration::from_secs(60);
+
+/// Spawn the periodic announcement publisher.
+pub fn spawn_announcement_publisher(state: AppState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            publish_due(&state).await;
+        }
+    })
+}
+
+/// Broadcast every announcement that became active since the last run.
+pub async fn publish_due(state: &AppState) {
+    match announcements::take_due(&state.pool).await {
+        Ok(due) => {
+            for announcement in &due {
+                state.events.publish(event(announcement));
+            }
+        }
+        Err(e) => warn!(error = %e, "failed to publish announcements"),
+    }
+}
+
+fn event(announcement: &Announcement) -> ServerEvent {
+    ServerEvent {
+        user_id: None,
+        kind: KIND_ANNOUNCEMENT_PUBLISHED.into(),
+        title: announcement.title.clone(),
+        body: announcement.body.clone(),
+        payload: serde_json::json!({
+            "id": announcement.id,
+            "severity": announcement.severity,
+            "dismissible": announcement.dismissible,
+            "endsAt": announcement.ends_at,
+        }),
+    }
+}
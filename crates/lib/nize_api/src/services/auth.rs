@@ -9,13 +9,18 @@ use rand::distr::Alphanumeric;
 use rand::{Rng, rng};
 use sha2::{Digest, Sha256};
 use sqlx::PgPool;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::error::{AppError, AppResult};
 use crate::generated::models::{AuthStatusResponse, AuthUser, LogoutResponse, TokenResponse};
+use crate::i18n::Message;
+use crate::services::local_mode::LocalMode;
+
+use nize_core::auth::password::{PasswordPolicy, hash_password_with, needs_rehash};
 
 // Re-export from nize_core for backward compatibility.
-pub use nize_core::auth::jwt::{resolve_jwt_secret, verify_access_token};
+pub use nize_core::auth::jwt::{jwt_secret_from_env, resolve_jwt_secret, verify_access_token};
+pub use nize_core::auth::keys::JwtKeys;
 pub use nize_core::models::auth::TokenClaims;
 
 /// Access token lifetime: 15 minutes.
@@ -24,20 +29,56 @@ const ACCESS_TOKEN_EXPIRY_SECS: i64 = 15 * 60;
 /// Refresh token lifetime: 30 days.
 const REFRESH_TOKEN_EXPIRY_DAYS: i64 = 30;
 
+/// Shortest password accepted at registration.
+const MIN_PASSWORD_LEN: usize = 8;
+
 // ---------------------------------------------------------------------------
 // Password hashing (delegate to nize_core)
 // ---------------------------------------------------------------------------
 
 // @awa-impl: AUTH-1.1_AC-1
-/// Hash a password with bcrypt (cost 10).
-pub fn hash_password(password: &str) -> AppResult<String> {
-    nize_core::auth::password::hash_password(password).map_err(AppError::from)
+/// Hash a password with `policy`. Runs on the blocking pool: an Argon2id
+/// or bcrypt hash takes long enough to stall the async runtime.
+pub async fn hash_password(password: &str, policy: &PasswordPolicy) -> AppResult<String> {
+    let (password, policy) = (password.to_string(), *policy);
+    tokio::task::spawn_blocking(move || hash_password_with(&password, &policy))
+        .await
+        .map_err(|e| AppError::Internal(format!("hash password: {e}")))?
+        .map_err(AppError::from)
 }
 
 // @awa-impl: AUTH-1.1_AC-1
-/// Verify a password against a bcrypt hash.
-pub fn verify_password(password: &str, hash: &str) -> AppResult<bool> {
-    nize_core::auth::password::verify_password(password, hash).map_err(AppError::from)
+/// Verify a password against a stored hash of any supported algorithm, on
+/// the blocking pool like [`hash_password`].
+pub async fn verify_password(password: &str, hash: &str) -> AppResult<bool> {
+    let (password, hash) = (password.to_string(), hash.to_string());
+    tokio::task::spawn_blocking(move || {
+        nize_core::auth::password::verify_password(&password, &hash)
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("verify password: {e}")))?
+    .map_err(AppError::from)
+}
+
+/// Re-hash a verified password whose stored hash does not match the
+/// configured policy. Failures are logged, never surfaced: the login has
+/// already succeeded and the old hash still works.
+async fn upgrade_password_hash(pool: &PgPool, user_id: &str, password: &str, hash: &str) {
+    let result = async {
+        let policy = PasswordPolicy::load(pool).await?;
+        if !needs_rehash(hash, &policy) {
+            return Ok(false);
+        }
+        let upgraded = hash_password(password, &policy).await?;
+        nize_core::auth::queries::update_password_hash(pool, user_id, &upgraded).await?;
+        Ok::<_, AppError>(true)
+    }
+    .await;
+    match result {
+        Ok(true) => info!(user_id, "upgraded password hash"),
+        Ok(false) => {}
+        Err(e) => warn!(user_id, "Failed to upgrade password hash: {e}"),
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -65,15 +106,14 @@ fn hash_refresh_token(token: &str) -> String {
 // ---------------------------------------------------------------------------
 
 // @awa-impl: AUTH-1_AC-1, AUTH-1_AC-3
-/// Generate a signed JWT access token (HS256, 15 min expiry).
+/// Generate a JWT access token signed with the active key (15 min expiry).
 pub fn generate_access_token(
     user_id: &str,
     email: &str,
     roles: &[String],
-    secret: &[u8],
+    keys: &JwtKeys,
 ) -> AppResult<String> {
-    nize_core::auth::jwt::generate_access_token(user_id, email, roles, secret)
-        .map_err(AppError::from)
+    nize_core::auth::jwt::generate_access_token(user_id, email, roles, keys).map_err(AppError::from)
 }
 
 // ---------------------------------------------------------------------------
@@ -115,34 +155,61 @@ fn build_token_response(
 // Public auth operations
 // ---------------------------------------------------------------------------
 
+/// Outcome of a registration.
+#[derive(Debug)]
+pub enum Registration {
+    /// The new user is signed in.
+    Session(TokenResponse),
+    /// The new user must verify their email address before signing in.
+    VerificationPending { user_id: String },
+}
+
 // @awa-impl: AUTH-1_AC-1, AUTH-1_AC-2
-/// Authenticate with email + password.
+/// Authenticate with email + password. With `require_verification`,
+/// accounts whose email is not verified are refused.
 pub async fn login(
     pool: &PgPool,
     email: &str,
     password: &str,
-    jwt_secret: &[u8],
+    jwt_keys: &JwtKeys,
+    require_verification: bool,
 ) -> AppResult<TokenResponse> {
     let row = nize_core::auth::queries::find_user_by_email(pool, email).await?;
 
     let (user_id, name, pw_hash) = match row {
         // @awa-impl: AUTH-1_AC-2 — generic error for wrong email
-        None => return Err(AppError::Unauthorized("Invalid credentials".into())),
+        None => {
+            return Err(AppError::unauthorized(Message::new(
+                "auth.invalid_credentials",
+            )));
+        }
         Some(r) => r,
     };
 
     let pw_hash = match pw_hash {
-        None => return Err(AppError::Unauthorized("Invalid credentials".into())),
+        None => {
+            return Err(AppError::unauthorized(Message::new(
+                "auth.invalid_credentials",
+            )));
+        }
         Some(h) => h,
     };
 
     // @awa-impl: AUTH-1_AC-2 — generic error for wrong password
-    if !verify_password(password, &pw_hash)? {
-        return Err(AppError::Unauthorized("Invalid credentials".into()));
+    if !verify_password(password, &pw_hash).await? {
+        return Err(AppError::unauthorized(Message::new(
+            "auth.invalid_credentials",
+        )));
+    }
+    upgrade_password_hash(pool, &user_id, password, &pw_hash).await;
+
+    // After the password check, so it tells strangers nothing.
+    if require_verification && !nize_core::auth::verification::is_verified(pool, &user_id).await? {
+        return Err(AppError::forbidden(Message::new("auth.email_not_verified")));
     }
 
     let roles = get_user_roles(pool, &user_id).await?;
-    let access_token = generate_access_token(&user_id, email, &roles, jwt_secret)?;
+    let access_token = generate_access_token(&user_id, email, &roles, jwt_keys)?;
     let refresh_token = generate_refresh_token();
     let token_hash = hash_refresh_token(&refresh_token);
 
@@ -162,31 +229,35 @@ pub async fn login(
 
 // @awa-impl: AUTH-1.1_AC-2, AUTH-1.1_AC-4
 // @awa-impl: PRM-9_AC-1 — first user is admin
-/// Register a new user account. First user gets admin role.
+/// Register a new user account. First user gets admin role. With
+/// `require_verification`, every later account starts unverified and no
+/// session is issued.
 pub async fn register(
     pool: &PgPool,
     email: &str,
     password: &str,
     name: Option<&str>,
-    jwt_secret: &[u8],
-) -> AppResult<TokenResponse> {
+    jwt_keys: &JwtKeys,
+    require_verification: bool,
+) -> AppResult<Registration> {
     // @awa-impl: AUTH-1.1_AC-2
-    if password.len() < 8 {
-        return Err(AppError::Validation(
-            "Password must be at least 8 characters".into(),
+    if password.len() < MIN_PASSWORD_LEN {
+        return Err(AppError::validation(
+            Message::new("auth.password_too_short").arg("min", MIN_PASSWORD_LEN),
         ));
     }
 
     // Check duplicate email
     if nize_core::auth::queries::email_exists(pool, email).await? {
-        return Err(AppError::Validation("Email already registered".into()));
+        return Err(AppError::validation(Message::new("auth.email_taken")));
     }
 
     // Check if this is the first user
     let is_first_user = nize_core::auth::queries::user_count(pool).await? == 0;
 
     // @awa-impl: AUTH-1.1_AC-1
-    let pw_hash = hash_password(password)?;
+    let policy = PasswordPolicy::load(pool).await?;
+    let pw_hash = hash_password(password, &policy).await?;
 
     let user_id = nize_core::auth::queries::create_user(pool, email, name, &pw_hash).await?;
 
@@ -196,22 +267,57 @@ pub async fn register(
         nize_core::auth::queries::grant_role(pool, &user_id, "admin").await?;
         roles.push("admin".to_string());
         info!(email, "first user granted admin role");
+    } else if require_verification {
+        nize_core::auth::verification::mark_unverified(pool, &user_id).await?;
+        return Ok(Registration::VerificationPending { user_id });
     }
 
-    let access_token = generate_access_token(&user_id, email, &roles, jwt_secret)?;
+    let access_token = generate_access_token(&user_id, email, &roles, jwt_keys)?;
     let refresh_token = generate_refresh_token();
     let token_hash = hash_refresh_token(&refresh_token);
 
     let expires_at = Utc::now() + Duration::days(REFRESH_TOKEN_EXPIRY_DAYS);
     nize_core::auth::queries::store_refresh_token(pool, &token_hash, &user_id, expires_at).await?;
 
-    Ok(build_token_response(
+    Ok(Registration::Session(build_token_response(
         &user_id,
         email,
         name,
         &roles,
         access_token,
         refresh_token,
+    )))
+}
+
+/// Sign in as the local user with the desktop's local token.
+pub async fn local_login(
+    pool: &PgPool,
+    local_mode: Option<&LocalMode>,
+    token: &str,
+    jwt_keys: &JwtKeys,
+) -> AppResult<TokenResponse> {
+    let local_mode =
+        local_mode.ok_or_else(|| AppError::not_found(Message::new("auth.local_mode_disabled")))?;
+    if !local_mode.accepts(token) {
+        return Err(AppError::unauthorized(Message::new("auth.invalid_token")));
+    }
+    let user = local_mode.user();
+
+    let roles = get_user_roles(pool, &user.id).await?;
+    let access_token = generate_access_token(&user.id, &user.email, &roles, jwt_keys)?;
+    let refresh_token = generate_refresh_token();
+    let token_hash = hash_refresh_token(&refresh_token);
+
+    let expires_at = Utc::now() + Duration::days(REFRESH_TOKEN_EXPIRY_DAYS);
+    nize_core::auth::queries::store_refresh_token(pool, &token_hash, &user.id, expires_at).await?;
+
+    Ok(build_token_response(
+        &user.id,
+        &user.email,
+        user.name.as_deref(),
+        &roles,
+        access_token,
+        refresh_token,
     ))
 }
 
@@ -220,7 +326,7 @@ pub async fn register(
 pub async fn refresh(
     pool: &PgPool,
     refresh_token: &str,
-    jwt_secret: &[u8],
+    jwt_keys: &JwtKeys,
 ) -> AppResult<TokenResponse> {
     let token_hash = hash_refresh_token(refresh_token);
 
@@ -229,7 +335,11 @@ pub async fn refresh(
 
     let (token_id, user_id) = match row {
         // @awa-impl: AUTH-3_AC-3
-        None => return Err(AppError::Unauthorized("Invalid refresh token".into())),
+        None => {
+            return Err(AppError::unauthorized(Message::new(
+                "auth.invalid_refresh_token",
+            )));
+        }
         Some(r) => r,
     };
 
@@ -239,12 +349,12 @@ pub async fn refresh(
     // Fetch user
     let user = nize_core::auth::queries::get_user_by_id(pool, &user_id)
         .await?
-        .ok_or_else(|| AppError::Unauthorized("User not found".into()))?;
+        .ok_or_else(|| AppError::unauthorized(Message::new("auth.user_not_found")))?;
 
     let roles = get_user_roles(pool, &user_id).await?;
 
     // Issue new token pair
-    let access_token = generate_access_token(&user_id, &user.email, &roles, jwt_secret)?;
+    let access_token = generate_access_token(&user_id, &user.email, &roles, jwt_keys)?;
     let new_refresh = generate_refresh_token();
     let new_hash = hash_refresh_token(&new_refresh);
 
@@ -6,16 +6,25 @@ use std::sync::Arc;
 
 use sqlx::PgPool;
 use tokio::sync::RwLock;
+use uuid::Uuid;
 
 use nize_core::config::ConfigError;
-use nize_core::config::cache::ConfigCache;
+use nize_core::config::cache::{CacheEntryInfo, CacheStats, ConfigCache};
 use nize_core::config::queries;
 use nize_core::config::resolver;
+use nize_core::config::snapshots::{self, ConfigChange};
 use nize_core::config::validation;
+use nize_core::hardware;
 use nize_core::mcp::secrets;
+use nize_core::mcp::templating;
 use nize_core::models::config::{ConfigScope, ConfigValue, ResolvedConfigItem};
+use nize_core::pricing;
+use nize_core::providers;
+use nize_core::tasks;
+use nize_core::timezone;
 
 use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
 
 // ---------------------------------------------------------------------------
 // Error conversion
@@ -24,8 +33,8 @@ use crate::error::{AppError, AppResult};
 impl From<ConfigError> for AppError {
     fn from(e: ConfigError) -> Self {
         match e {
-            ConfigError::NotFound(msg) => AppError::NotFound(msg),
-            ConfigError::ValidationError(msg) => AppError::Validation(msg),
+            ConfigError::NotFound(msg) => AppError::not_found(msg),
+            ConfigError::ValidationError(msg) => AppError::validation(msg),
             ConfigError::DbError(e) => AppError::from(e),
         }
     }
@@ -62,6 +71,79 @@ fn mask_secret_items(items: Vec<ResolvedConfigItem>) -> Vec<ResolvedConfigItem>
         .collect()
 }
 
+// ---------------------------------------------------------------------------
+// Key-specific checks and effects
+// ---------------------------------------------------------------------------
+
+/// Checks beyond the definition's validators.
+async fn validate_special(pool: &PgPool, key: &str, value: &str) -> AppResult<()> {
+    if key == timezone::TIMEZONE_CONFIG_KEY {
+        let mut conn = pool.acquire().await?;
+        if !timezone::is_valid(&mut conn, value.trim()).await? {
+            return Err(AppError::validation(
+                Message::new("config.unknown_time_zone").arg("value", value),
+            ));
+        }
+    }
+    if key == providers::PROVIDERS_CONFIG_KEY {
+        let parsed = providers::parse(value).map_err(AppError::validation)?;
+        let conflicts = providers::dimension_conflicts(pool, &parsed).await?;
+        if let Some((provider, model, dimensions)) = conflicts.first() {
+            return Err(AppError::validation(
+                Message::new("config.embedding_dimensions_fixed")
+                    .arg("provider", provider)
+                    .arg("model", model)
+                    .arg("dimensions", dimensions),
+            ));
+        }
+    }
+    if key == providers::API_KEYS_CONFIG_KEY {
+        providers::parse_api_keys(value).map_err(AppError::validation)?;
+    }
+    if key == pricing::PRICING_CONFIG_KEY {
+        pricing::parse(value).map_err(AppError::validation)?;
+    }
+    if key == templating::ALLOWED_VARIABLES_CONFIG_KEY {
+        templating::validate_allowlist(value).map_err(AppError::validation)?;
+    }
+    if let Some(reason) = hardware::local_provider_refusal(pool, key, value).await? {
+        return Err(AppError::validation(
+            Message::new("config.provider_cannot_run")
+                .arg("value", value)
+                .nested("reason", reason),
+        ));
+    }
+    Ok(())
+}
+
+/// Apply side effects of a changed value: a new time zone moves the next
+/// runs of the affected users' tasks (`user_id`, or everyone for a system
+/// change), and new OpenAI-compatible providers register their embedding
+/// models.
+async fn after_change(pool: &PgPool, key: &str, user_id: Option<&str>) -> AppResult<()> {
+    if key == timezone::TIMEZONE_CONFIG_KEY {
+        match user_id.and_then(|id| Uuid::parse_str(id).ok()) {
+            Some(user_id) => tasks::reschedule_user_tasks(pool, &user_id).await?,
+            None => tasks::reschedule_all_tasks(pool).await?,
+        };
+    }
+    if key == providers::PROVIDERS_CONFIG_KEY && user_id.is_none() {
+        let value = queries::get_value(pool, key, &ConfigScope::System, None)
+            .await?
+            .map(|v| v.value)
+            .unwrap_or_default();
+        match providers::parse(&value) {
+            Ok(parsed) => {
+                let mut tx = pool.begin().await?;
+                providers::register_embedding_models(&mut tx, &parsed).await?;
+                tx.commit().await?;
+            }
+            Err(e) => tracing::warn!("Not registering models of invalid {key}: {e}"),
+        }
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // User config operations
 // ---------------------------------------------------------------------------
@@ -90,16 +172,19 @@ pub async fn update_user_config(
     // Verify definition exists
     let def = queries::get_definition(pool, key)
         .await?
-        .ok_or_else(|| AppError::NotFound(format!("Config key not found: {key}")))?;
+        .ok_or_else(|| AppError::not_found(Message::new("config.key_not_found").arg("key", key)))?;
 
     // Validate
-    if let Some(ref validators) = def.validators {
-        let errors = validation::validate_value(value, validators);
-        if !errors.is_empty() {
-            return Err(AppError::Validation(errors.join("; ")));
-        }
+    if let Some(ref validators) = def.validators
+        && let Some(error) = validation::validate_value(value, validators)
+            .into_iter()
+            .next()
+    {
+        return Err(AppError::validation(error));
     }
 
+    validate_special(pool, key, value).await?;
+
     // Encrypt secret values before storage
     let store_value = if def.display_type == "secret" && !value.is_empty() {
         secrets::encrypt(value, encryption_key)
@@ -123,6 +208,7 @@ pub async fn update_user_config(
         let mut c = cache.write().await;
         c.invalidate(key, ConfigScope::UserOverride.as_str(), Some(user_id));
     }
+    after_change(pool, key, Some(user_id)).await?;
 
     // Mask the response value for secret display types
     let mut result = ResolvedConfigItem::from_definition(&def, Some(&cv.value), true);
@@ -142,7 +228,7 @@ pub async fn reset_user_config(
     // Verify definition exists
     queries::get_definition(pool, key)
         .await?
-        .ok_or_else(|| AppError::NotFound(format!("Config key not found: {key}")))?;
+        .ok_or_else(|| AppError::not_found(Message::new("config.key_not_found").arg("key", key)))?;
 
     let deleted =
         queries::delete_value(pool, key, &ConfigScope::UserOverride, Some(user_id)).await?;
@@ -152,6 +238,9 @@ pub async fn reset_user_config(
         let mut c = cache.write().await;
         c.invalidate(key, ConfigScope::UserOverride.as_str(), Some(user_id));
     }
+    if deleted {
+        after_change(pool, key, Some(user_id)).await?;
+    }
 
     Ok(deleted)
 }
@@ -161,7 +250,7 @@ pub async fn reset_user_config(
 // ---------------------------------------------------------------------------
 
 /// Admin config item — includes scope metadata.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct AdminConfigItem {
     pub key: String,
     pub category: String,
@@ -180,7 +269,7 @@ pub struct AdminConfigItem {
 }
 
 /// A config value with scope info for admin views.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
 pub struct AdminConfigValue {
     pub id: String,
     pub scope: String,
@@ -191,6 +280,19 @@ pub struct AdminConfigValue {
     pub updated_at: String,
 }
 
+impl AdminConfigValue {
+    /// `v` as shown to admins, with `value` (masked for secrets).
+    fn new(v: &ConfigValue, value: String) -> Self {
+        Self {
+            id: v.id.clone(),
+            scope: v.scope.as_str().to_string(),
+            user_id: v.user_id.clone(),
+            value,
+            updated_at: v.updated_at.to_rfc3339(),
+        }
+    }
+}
+
 /// Get admin config view — definitions with all values.
 pub async fn get_admin_config(
     pool: &PgPool,
@@ -241,16 +343,13 @@ pub async fn get_admin_config(
                 .get(&def.key)
                 .map(|vs| {
                     vs.iter()
-                        .map(|v| AdminConfigValue {
-                            id: v.id.clone(),
-                            scope: v.scope.as_str().to_string(),
-                            user_id: v.user_id.clone(),
-                            value: if is_secret {
+                        .map(|v| {
+                            let value = if is_secret {
                                 mask_secret_value(&v.value)
                             } else {
                                 v.value.clone()
-                            },
-                            updated_at: v.updated_at.to_rfc3339(),
+                            };
+                            AdminConfigValue::new(v, value)
                         })
                         .collect()
                 })
@@ -277,6 +376,7 @@ pub async fn get_admin_config(
 
 /// Update an admin config value (system or user-override scope).
 // @awa-impl: PLAN-028-1.2
+#[allow(clippy::too_many_arguments)]
 pub async fn update_admin_config(
     pool: &PgPool,
     cache: &Arc<RwLock<ConfigCache>>,
@@ -285,20 +385,24 @@ pub async fn update_admin_config(
     value: &str,
     user_id: Option<&str>,
     encryption_key: &str,
-) -> AppResult<ConfigValue> {
+    actor_id: &Uuid,
+) -> AppResult<AdminConfigValue> {
     // Verify definition exists
     let def = queries::get_definition(pool, key)
         .await?
-        .ok_or_else(|| AppError::NotFound(format!("Config key not found: {key}")))?;
+        .ok_or_else(|| AppError::not_found(Message::new("config.key_not_found").arg("key", key)))?;
 
     // Validate
-    if let Some(ref validators) = def.validators {
-        let errors = validation::validate_value(value, validators);
-        if !errors.is_empty() {
-            return Err(AppError::Validation(errors.join("; ")));
-        }
+    if let Some(ref validators) = def.validators
+        && let Some(error) = validation::validate_value(value, validators)
+            .into_iter()
+            .next()
+    {
+        return Err(AppError::validation(error));
     }
 
+    validate_special(pool, key, value).await?;
+
     // Encrypt secret values before storage
     let store_value = if def.display_type == "secret" && !value.is_empty() {
         secrets::encrypt(value, encryption_key)
@@ -307,20 +411,234 @@ pub async fn update_admin_config(
         value.to_string()
     };
 
-    let cv = queries::upsert_value(pool, key, scope, user_id, &store_value).await?;
+    // System changes are snapshotted (see `snapshots`) in the same transaction
+    let cv = if *scope == ConfigScope::System {
+        let mut tx = pool.begin().await?;
+        snapshots::ensure_baseline(&mut tx).await?;
+        let cv = queries::upsert_value(&mut *tx, key, scope, user_id, &store_value).await?;
+        snapshots::record_update(&mut tx, key, Some(actor_id)).await?;
+        tx.commit().await?;
+        cv
+    } else {
+        queries::upsert_value(pool, key, scope, user_id, &store_value).await?
+    };
 
     // Invalidate cache
     {
         let mut c = cache.write().await;
         c.invalidate_all_for_key(key);
     }
+    after_change(pool, key, user_id).await?;
 
     // Mask the stored value for secret display types
-    let mut cv = cv;
-    if def.display_type == "secret" {
-        cv.value = mask_secret_value(value);
+    let shown = if def.display_type == "secret" {
+        mask_secret_value(value)
+    } else {
+        cv.value.clone()
+    };
+    Ok(AdminConfigValue::new(&cv, shown))
+}
+
+// ---------------------------------------------------------------------------
+// Snapshots and rollback
+// ---------------------------------------------------------------------------
+
+/// A config version with its changes against the previous version.
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHistoryEntry {
+    pub version: i32,
+    pub action: String,
+    pub key: Option<String>,
+    pub restored_from: Option<i32>,
+    pub actor_id: Option<Uuid>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub changes: Vec<ConfigChange>,
+}
+
+/// Keys with `display_type = "secret"`.
+async fn secret_keys(pool: &PgPool) -> AppResult<std::collections::HashSet<String>> {
+    Ok(queries::get_all_definitions(pool)
+        .await?
+        .into_iter()
+        .filter(|def| def.display_type == "secret")
+        .map(|def| def.key)
+        .collect())
+}
+
+/// Hide secret values (stored encrypted) in changes.
+fn mask_secret_changes(
+    changes: Vec<ConfigChange>,
+    secret_keys: &std::collections::HashSet<String>,
+) -> Vec<ConfigChange> {
+    let mask = |v: Option<String>| {
+        v.map(|v| {
+            if v.is_empty() {
+                v
+            } else {
+                "••••".to_string()
+            }
+        })
+    };
+    changes
+        .into_iter()
+        .map(|mut change| {
+            if secret_keys.contains(&change.key) {
+                change.before = mask(change.before);
+                change.after = mask(change.after);
+            }
+            change
+        })
+        .collect()
+}
+
+/// List config versions, newest first, with diffs against their
+/// predecessors (the baseline lists no changes).
+pub async fn get_config_history(
+    pool: &PgPool,
+    limit: i64,
+    offset: i64,
+) -> AppResult<(Vec<ConfigHistoryEntry>, i64)> {
+    let secret_keys = secret_keys(pool).await?;
+    let (rows, total) = snapshots::list_snapshots(pool, limit, offset).await?;
+    let entries = rows
+        .into_iter()
+        .map(|(row, previous)| {
+            let changes = match &previous {
+                Some(previous) => snapshots::diff(&previous.state(), &row.state()),
+                None => Vec::new(),
+            };
+            ConfigHistoryEntry {
+                version: row.version,
+                action: row.action,
+                key: row.key,
+                restored_from: row.restored_from,
+                actor_id: row.actor_id,
+                created_at: row.created_at,
+                changes: mask_secret_changes(changes, &secret_keys),
+            }
+        })
+        .collect();
+    Ok((entries, total))
+}
+
+/// Restore the system config of `version` and invalidate the cache for
+/// the changed keys. Returns the new version and the changes applied.
+pub async fn rollback_config(
+    pool: &PgPool,
+    cache: &Arc<RwLock<ConfigCache>>,
+    version: i32,
+    actor_id: &Uuid,
+) -> AppResult<(i32, Vec<ConfigChange>)> {
+    let result = snapshots::rollback(pool, version, Some(actor_id)).await?;
+    {
+        let mut c = cache.write().await;
+        for change in &result.changes {
+            c.invalidate_all_for_key(&change.key);
+        }
     }
-    Ok(cv)
+    for change in &result.changes {
+        after_change(pool, &change.key, None).await?;
+    }
+    let secret_keys = secret_keys(pool).await?;
+    Ok((
+        result.version,
+        mask_secret_changes(result.changes, &secret_keys),
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// Cache inspection
+// ---------------------------------------------------------------------------
+
+/// Current cache entries (secret values masked) and counters.
+pub async fn get_cache_snapshot(
+    pool: &PgPool,
+    cache: &Arc<RwLock<ConfigCache>>,
+) -> AppResult<(Vec<CacheEntryInfo>, CacheStats)> {
+    let secret_keys: std::collections::HashSet<String> = queries::get_all_definitions(pool)
+        .await?
+        .into_iter()
+        .filter(|def| def.display_type == "secret")
+        .map(|def| def.key)
+        .collect();
+
+    let c = cache.read().await;
+    let entries = c
+        .entries()
+        .into_iter()
+        .map(|mut e| {
+            if secret_keys.contains(&e.key) {
+                e.value = mask_secret_value(&e.value);
+            }
+            e
+        })
+        .collect();
+    Ok((entries, c.stats()))
+}
+
+/// Outcome of a cache refresh.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CacheRefreshResult {
+    /// Entries removed.
+    pub invalidated: usize,
+    /// Entries reloaded from the database.
+    pub warmed: usize,
+}
+
+/// Invalidate cache entries matching `scope`, `key` and `user_id` (`None`
+/// matches anything), then optionally reload them from the database.
+///
+/// Warming reloads system values and, when `user_id` is given, that user's
+/// overrides; other users' overrides are reloaded lazily on next read.
+pub async fn refresh_cache(
+    pool: &PgPool,
+    cache: &Arc<RwLock<ConfigCache>>,
+    scope: Option<&ConfigScope>,
+    key: Option<&str>,
+    user_id: Option<&str>,
+    warm: bool,
+) -> AppResult<CacheRefreshResult> {
+    let invalidated =
+        cache
+            .write()
+            .await
+            .invalidate_matching(scope.map(ConfigScope::as_str), key, user_id);
+
+    let mut warmed = 0;
+    if warm {
+        let mut values = Vec::new();
+        if scope.is_none_or(|s| *s == ConfigScope::System) && user_id.is_none() {
+            values.extend(queries::get_system_values(pool).await?);
+        }
+        if scope.is_none_or(|s| *s == ConfigScope::UserOverride)
+            && let Some(uid) = user_id
+        {
+            values.extend(queries::get_user_values(pool, uid).await?);
+        }
+
+        let mut c = cache.write().await;
+        for v in values.iter().filter(|v| key.is_none_or(|k| v.key == k)) {
+            c.set(
+                &v.key,
+                v.scope.as_str(),
+                v.user_id.as_deref(),
+                v.value.clone(),
+            );
+            warmed += 1;
+        }
+        drop(c);
+
+        // Pick up TTL changes made since startup.
+        if key.is_none_or(|k| k.starts_with("system.cache.")) {
+            resolver::reload_cache_ttls(pool, cache).await?;
+        }
+    }
+
+    Ok(CacheRefreshResult {
+        invalidated,
+        warmed,
+    })
 }
 
 // ---------------------------------------------------------------------------
@@ -340,15 +658,7 @@ pub async fn decrypt_secret_config_value(
     encryption_key: &str,
     env_fallback: Option<&str>,
 ) -> AppResult<Option<String>> {
-    // Verify definition exists and is a secret
-    let def = queries::get_definition(pool, key)
-        .await?
-        .ok_or_else(|| AppError::NotFound(format!("Config key not found: {key}")))?;
-    if def.display_type != "secret" {
-        return Err(AppError::Validation(format!(
-            "Config key {key} is not a secret"
-        )));
-    }
+    require_secret_definition(pool, key).await?;
 
     // Try user-override first
     if let Some(v) =
@@ -360,6 +670,41 @@ pub async fn decrypt_secret_config_value(
         return Ok(Some(decrypted));
     }
 
+    system_secret_value(pool, key, encryption_key, env_fallback).await
+}
+
+/// Resolve and decrypt a secret config value, ignoring user overrides.
+///
+/// Resolution order: system → env var → None.
+pub async fn decrypt_system_secret_value(
+    pool: &PgPool,
+    key: &str,
+    encryption_key: &str,
+    env_fallback: Option<&str>,
+) -> AppResult<Option<String>> {
+    require_secret_definition(pool, key).await?;
+    system_secret_value(pool, key, encryption_key, env_fallback).await
+}
+
+/// Verify `key` is defined as a secret.
+async fn require_secret_definition(pool: &PgPool, key: &str) -> AppResult<()> {
+    let def = queries::get_definition(pool, key)
+        .await?
+        .ok_or_else(|| AppError::not_found(Message::new("config.key_not_found").arg("key", key)))?;
+    if def.display_type != "secret" {
+        return Err(AppError::validation(
+            Message::new("config.not_a_secret").arg("key", key),
+        ));
+    }
+    Ok(())
+}
+
+async fn system_secret_value(
+    pool: &PgPool,
+    key: &str,
+    encryption_key: &str,
+    env_fallback: Option<&str>,
+) -> AppResult<Option<String>> {
     // Try system scope
     if let Some(v) = queries::get_value(pool, key, &ConfigScope::System, None).await?
         && !v.value.is_empty()
@@ -0,0 +1,569 @@
+//! Background scheduler for ingest connectors.
+//!
+//! Every [`TICK`] the scheduler claims due connectors (see
+//! [`nize_core::connectors`]) and syncs each one. For folders the source is
+//! scanned, new files become documents, changed files replace their
+//! document's content (keeping its ID and tags), and the documents of
+//! removed files are deleted. For mailboxes each folder is read from the
+//! last UID ingested: new messages become documents (and, if enabled, so do
+//! their attachments), and the documents of messages removed from the
+//! server are deleted. Everything goes through the same quotas, extraction,
+//! moderation and embedding as uploads (see [`crate::services::documents`]),
+//! and the documents are personal to the connector's owner.
+//! The outcome is recorded on the connector and published on the event bus.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+
+use tracing::{debug, error, warn};
+
+use nize_core::blobs::BlobStore;
+use nize_core::connectors::imap::{FolderCursor, ImapSession, ImapSettings, ImapSource};
+use nize_core::connectors::mail::{self, EmailMessageRow, MailAttachment, ParsedMessage};
+use nize_core::connectors::{self, ConnectorItemRow, ConnectorRow, SyncStats, fs};
+use nize_core::documents::{self, DocumentRow};
+use nize_core::ingest;
+use nize_core::mcp::secrets;
+use nize_core::quotas::{self, Quota};
+use nize_core::workspaces::Scope;
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::events::{KIND_CONNECTOR_FAILED, KIND_CONNECTOR_SYNCED, ServerEvent};
+use crate::handlers::ingest::MAX_UPLOAD_BYTES;
+use crate::i18n::Message;
+use crate::services::documents as document_blobs;
+
+/// How often due connectors are claimed.
+const TICK: Duration = Duration::from_secs(30);
+
+/// Maximum connectors claimed per tick.
+const BATCH_SIZE: i64 = 5;
+
+/// Most messages ingested per mailbox folder in one sync; the rest follow
+/// on the next.
+const MAX_MESSAGES_PER_SYNC: usize = 500;
+
+/// Spawn the periodic connector scheduler.
+pub fn spawn_connector_scheduler(state: AppState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            let due =
+                match connectors::claim_due_connectors(&state.pool, chrono::Utc::now(), BATCH_SIZE)
+                    .await
+                {
+                    Ok(due) => due,
+                    Err(e) => {
+                        warn!(error = %e, "failed to claim due connectors");
+                        continue;
+                    }
+                };
+            for connector in due {
+                let state = state.clone();
+                tokio::spawn(async move {
+                    sync_and_record(&state, connector).await;
+                });
+            }
+        }
+    })
+}
+
+/// Sync a claimed connector and record the outcome.
+async fn sync_and_record(state: &AppState, connector: ConnectorRow) {
+    debug!(connector_id = %connector.id, "syncing connector");
+    let outcome = sync(state, &connector).await.map_err(|e| error_message(&e));
+
+    if let Err(e) = connectors::finish_sync(
+        &state.pool,
+        &connector.id,
+        outcome.as_ref().map_err(String::as_str),
+    )
+    .await
+    {
+        error!(connector_id = %connector.id, error = %e, "failed to record connector sync");
+    }
+
+    let (kind, body, payload) = match &outcome {
+        Ok(stats) => (
+            KIND_CONNECTOR_SYNCED,
+            format!(
+                "{} added, {} updated, {} deleted",
+                stats.added, stats.updated, stats.deleted
+            ),
+            serde_json::json!({
+                "connectorId": connector.id,
+                "added": stats.added,
+                "updated": stats.updated,
+                "deleted": stats.deleted,
+            }),
+        ),
+        Err(e) => {
+            warn!(connector_id = %connector.id, error = %e, "connector sync failed");
+            (
+                KIND_CONNECTOR_FAILED,
+                e.clone(),
+                serde_json::json!({ "connectorId": connector.id }),
+            )
+        }
+    };
+    state.events.publish(ServerEvent {
+        user_id: Some(connector.user_id),
+        kind: kind.to_string(),
+        title: connector.name.clone(),
+        body,
+        payload,
+    });
+}
+
+/// Sync a connector's source into its owner's documents.
+pub async fn sync(state: &AppState, connector: &ConnectorRow) -> AppResult<SyncStats> {
+    match connector.kind.as_str() {
+        connectors::KIND_FILESYSTEM => sync_folder(state, connector).await,
+        connectors::KIND_IMAP => sync_mailbox(state, connector).await,
+        other => Err(AppError::validation(
+            Message::new("connectors.unknown_kind").arg("kind", other),
+        )),
+    }
+}
+
+async fn sync_folder(state: &AppState, connector: &ConnectorRow) -> AppResult<SyncStats> {
+    // Re-checked on every sync: the allowed roots may have changed.
+    let roots = connectors::allowed_roots(&state.pool, &state.config_cache).await?;
+    let folder = fs::check_source(&connector.source, &roots)?;
+    let scan_folder = folder.clone();
+    let scanned = tokio::task::spawn_blocking(move || fs::scan(&scan_folder))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+    let items = connectors::list_items(&state.pool, &connector.id).await?;
+    let plan = connectors::plan_sync(&items, &scanned);
+    let store = document_blobs::upload_store(state).await?;
+
+    let mut stats = SyncStats {
+        file_count: scanned.len() as i32,
+        ..SyncStats::default()
+    };
+    for file in &plan.added {
+        if ingest_file(state, &store, connector, &folder, file, None).await? {
+            stats.added += 1;
+        }
+    }
+    for (file, item) in &plan.changed {
+        if ingest_file(state, &store, connector, &folder, file, Some(item)).await? {
+            stats.updated += 1;
+        }
+    }
+    for item in &plan.deleted {
+        if remove_item(state, connector, item).await? {
+            stats.deleted += 1;
+        }
+    }
+    Ok(stats)
+}
+
+/// Ingest a new or changed file, creating its document or replacing the
+/// content of the one it was ingested as before. Returns whether a
+/// document was written: files whose content did not change, that are too
+/// large or that moderation blocks are only recorded, so they are not
+/// retried until they change again.
+async fn ingest_file(
+    state: &AppState,
+    store: &BlobStore,
+    connector: &ConnectorRow,
+    folder: &Path,
+    file: &fs::ScannedFile,
+    previous: Option<&ConnectorItemRow>,
+) -> AppResult<bool> {
+    let path = folder.join(&file.path);
+    let sha256 = fs::hash_file(&path).await?;
+    if let Some(item) = previous
+        && item.sha256 == sha256
+    {
+        connectors::upsert_item(
+            &state.pool,
+            &connector.id,
+            file,
+            &sha256,
+            item.document_id.as_ref(),
+        )
+        .await?;
+        return Ok(false);
+    }
+    if file.size as u64 > MAX_UPLOAD_BYTES {
+        warn!(connector_id = %connector.id, path = %file.path, "file too large to ingest");
+        connectors::upsert_item(&state.pool, &connector.id, file, &sha256, None).await?;
+        return Ok(false);
+    }
+
+    let existing = match previous.and_then(|item| item.document_id) {
+        Some(id) => documents::find_document(&state.pool, &id).await?,
+        None => None,
+    };
+    let grown = file.size - existing.as_ref().map_or(0, |row| row.size_bytes);
+    let new_documents = i64::from(existing.is_none());
+    quotas::ensure_within(
+        &state.pool,
+        &state.config_cache,
+        &connector.user_id,
+        &[
+            (Quota::Documents, new_documents),
+            (Quota::StorageBytes, grown),
+        ],
+    )
+    .await?;
+
+    let mime_type = fs::mime_type(&file.path).unwrap_or("application/octet-stream");
+    let blob = store.put_file(&path, Some(MAX_UPLOAD_BYTES)).await?;
+    let row = match existing {
+        Some(existing) => {
+            let (row, replaced) = documents::replace_blob(
+                &state.pool,
+                &existing.id,
+                mime_type,
+                &blob,
+                store.backend(),
+            )
+            .await?;
+            if replaced != blob.sha256 {
+                document_blobs::release(state, &replaced).await?;
+            }
+            row
+        }
+        None => {
+            let filename = file.path.rsplit('/').next().unwrap_or(&file.path);
+            documents::create_document(
+                &state.pool,
+                &Scope::personal(connector.user_id),
+                filename,
+                mime_type,
+                &blob,
+                store.backend(),
+            )
+            .await?
+        }
+    };
+
+    if !index(state, store, &row).await? {
+        connectors::upsert_item(&state.pool, &connector.id, file, &blob.sha256, None).await?;
+        return Ok(false);
+    }
+    connectors::upsert_item(
+        &state.pool,
+        &connector.id,
+        file,
+        &blob.sha256,
+        Some(&row.id),
+    )
+    .await?;
+    Ok(true)
+}
+
+async fn sync_mailbox(state: &AppState, connector: &ConnectorRow) -> AppResult<SyncStats> {
+    let source = ImapSource::parse(&connector.source)?;
+    let settings = ImapSettings::from_value(&connector.settings)?;
+    let password = match &connector.secret {
+        Some(secret) => secrets::decrypt(secret, &state.config.mcp_encryption_key)?,
+        None => {
+            return Err(AppError::validation(Message::new(
+                "connectors.password_missing",
+            )));
+        }
+    };
+    let store = document_blobs::upload_store(state).await?;
+    let ingested = mail::list_messages(&state.pool, &connector.id).await?;
+
+    let mut stats = SyncStats::default();
+    // Folders removed from the settings.
+    for message in ingested
+        .iter()
+        .filter(|m| !settings.folders.contains(&m.folder))
+    {
+        if remove_message(state, connector, message).await? {
+            stats.deleted += 1;
+        }
+    }
+    connectors::retain_cursors(&state.pool, &connector.id, &settings.folders).await?;
+
+    let mut session = ImapSession::connect(&source, &settings.username, &password).await?;
+    let mut synced = Ok(());
+    for folder in &settings.folders {
+        synced = sync_mail_folder(
+            state,
+            &store,
+            connector,
+            &settings,
+            &mut session,
+            folder,
+            &ingested,
+            &mut stats,
+        )
+        .await;
+        if synced.is_err() {
+            break;
+        }
+    }
+    session.logout().await;
+    synced?;
+
+    stats.file_count = mail::count_messages(&state.pool, &connector.id).await? as i32;
+    Ok(stats)
+}
+
+/// Sync one mailbox folder from its cursor. The cursor advances after every
+/// message, so a failed sync resumes where it stopped.
+#[allow(clippy::too_many_arguments)]
+async fn sync_mail_folder(
+    state: &AppState,
+    store: &BlobStore,
+    connector: &ConnectorRow,
+    settings: &ImapSettings,
+    session: &mut ImapSession,
+    folder: &str,
+    ingested: &[EmailMessageRow],
+    stats: &mut SyncStats,
+) -> AppResult<()> {
+    let uid_validity = session.examine(folder).await?;
+    let uids = session.uids().await?;
+    let cursor = connectors::get_cursor(&state.pool, &connector.id, folder)
+        .await?
+        .as_deref()
+        .and_then(FolderCursor::parse);
+    let in_folder = ingested.iter().filter(|m| m.folder == folder);
+
+    let mut cursor = match cursor {
+        Some(cursor) if cursor.uid_validity == uid_validity => {
+            let present: HashSet<i64> = uids.iter().map(|uid| i64::from(*uid)).collect();
+            for message in in_folder.filter(|m| !present.contains(&m.uid)) {
+                if remove_message(state, connector, message).await? {
+                    stats.deleted += 1;
+                }
+            }
+            cursor
+        }
+        // A new folder, or one the server renumbered: ingest it afresh.
+        _ => {
+            for message in in_folder {
+                if remove_message(state, connector, message).await? {
+                    stats.deleted += 1;
+                }
+            }
+            let cursor = FolderCursor {
+                uid_validity,
+                last_uid: 0,
+            };
+            connectors::set_cursor(&state.pool, &connector.id, folder, &cursor.to_string()).await?;
+            cursor
+        }
+    };
+
+    let new: Vec<u32> = uids
+        .into_iter()
+        .filter(|uid| *uid > cursor.last_uid)
+        .take(MAX_MESSAGES_PER_SYNC)
+        .collect();
+    let sizes: HashMap<u32, u64> = session.sizes(&new).await?.into_iter().collect();
+    for uid in new {
+        if sizes.get(&uid).is_some_and(|size| *size > MAX_UPLOAD_BYTES) {
+            warn!(connector_id = %connector.id, folder, uid, "message too large to ingest");
+        } else if let Some(raw) = session.fetch(uid).await? {
+            match mail::parse_message(&raw) {
+                Some(message) => {
+                    stats.added +=
+                        ingest_message(state, store, connector, settings, folder, uid, &message)
+                            .await?;
+                }
+                None => warn!(connector_id = %connector.id, folder, uid, "unparseable message"),
+            }
+        }
+        cursor.last_uid = uid;
+        connectors::set_cursor(&state.pool, &connector.id, folder, &cursor.to_string()).await?;
+    }
+    Ok(())
+}
+
+/// Ingest a message, and its attachments if the connector takes them.
+/// Returns the number of documents created.
+async fn ingest_message(
+    state: &AppState,
+    store: &BlobStore,
+    connector: &ConnectorRow,
+    settings: &ImapSettings,
+    folder: &str,
+    uid: u32,
+    message: &ParsedMessage,
+) -> AppResult<i32> {
+    let mut created = 0;
+    if ingest_mail_document(state, store, connector, folder, uid, message, None).await? {
+        created += 1;
+    }
+    if !settings.attachments {
+        return Ok(created);
+    }
+    for attachment in &message.attachments {
+        if attachment.content.len() as u64 > MAX_UPLOAD_BYTES {
+            warn!(connector_id = %connector.id, folder, uid, "attachment too large to ingest");
+            continue;
+        }
+        if ingest_mail_document(
+            state,
+            store,
+            connector,
+            folder,
+            uid,
+            message,
+            Some(attachment),
+        )
+        .await?
+        {
+            created += 1;
+        }
+    }
+    Ok(created)
+}
+
+/// Store and index the text of a message, or one of its attachments,
+/// recording the message it came from. Returns `false` when moderation
+/// blocked it.
+#[allow(clippy::too_many_arguments)]
+async fn ingest_mail_document(
+    state: &AppState,
+    store: &BlobStore,
+    connector: &ConnectorRow,
+    folder: &str,
+    uid: u32,
+    message: &ParsedMessage,
+    attachment: Option<&MailAttachment>,
+) -> AppResult<bool> {
+    let (filename, mime_type, content) = match attachment {
+        Some(attachment) => (
+            attachment.filename.clone(),
+            attachment.mime_type,
+            attachment.content.clone(),
+        ),
+        None => (
+            message.filename(),
+            "text/plain",
+            message.text.clone().into_bytes(),
+        ),
+    };
+    quotas::ensure_within(
+        &state.pool,
+        &state.config_cache,
+        &connector.user_id,
+        &[
+            (Quota::Documents, 1),
+            (Quota::StorageBytes, content.len() as i64),
+        ],
+    )
+    .await?;
+    let blob = store.put_bytes(content, Some(MAX_UPLOAD_BYTES)).await?;
+    let row = documents::create_document(
+        &state.pool,
+        &Scope::personal(connector.user_id),
+        &filename,
+        mime_type,
+        &blob,
+        store.backend(),
+    )
+    .await?;
+    mail::record_message(
+        &state.pool,
+        &row.id,
+        &connector.id,
+        folder,
+        uid,
+        message,
+        attachment.map(|a| a.filename.as_str()),
+    )
+    .await?;
+    index(state, store, &row).await
+}
+
+/// Delete a document ingested from a message that was removed. Returns
+/// whether it still existed.
+async fn remove_message(
+    state: &AppState,
+    connector: &ConnectorRow,
+    message: &EmailMessageRow,
+) -> AppResult<bool> {
+    let deleted = documents::delete_document(
+        &state.pool,
+        &Scope::personal(connector.user_id),
+        &message.document_id,
+    )
+    .await?;
+    if let Some(row) = &deleted {
+        document_blobs::release(state, &row.sha256).await?;
+    }
+    Ok(deleted.is_some())
+}
+
+/// Extract, moderate and embed a document. Returns `false` when moderation
+/// blocked it and it was discarded.
+async fn index(state: &AppState, store: &BlobStore, row: &DocumentRow) -> AppResult<bool> {
+    // Audio is extracted (transcribed) as part of indexing.
+    let audio = ingest::is_audio_mime(&row.mime_type);
+    if !audio {
+        match document_blobs::extract_chunks(state, store, row).await {
+            Ok(_) => {}
+            Err(AppError::ContentBlocked(_)) => {
+                document_blobs::discard(state, row).await;
+                return Ok(false);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    document_blobs::index(state, store, row, audio).await;
+    Ok(true)
+}
+
+/// Delete the document of a removed file and forget the file. Returns
+/// whether a document was deleted.
+async fn remove_item(
+    state: &AppState,
+    connector: &ConnectorRow,
+    item: &ConnectorItemRow,
+) -> AppResult<bool> {
+    let deleted = match item.document_id {
+        Some(id) => {
+            documents::delete_document(&state.pool, &Scope::personal(connector.user_id), &id)
+                .await?
+        }
+        None => None,
+    };
+    if let Some(row) = &deleted {
+        document_blobs::release(state, &row.sha256).await?;
+    }
+    connectors::delete_item(&state.pool, &connector.id, &item.path).await?;
+    Ok(deleted.is_some())
+}
+
+/// The message recorded for a failed sync. Internal errors keep their
+/// detail, which their `Display` hides from API clients.
+fn error_message(e: &AppError) -> String {
+    match e {
+        AppError::Internal(msg) => msg.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_messages_keep_internal_detail() {
+        assert_eq!(
+            error_message(&AppError::Internal("disk full".into())),
+            "disk full"
+        );
+        assert_eq!(
+            error_message(&AppError::validation(Message::new(
+                "connectors.folder_not_absolute"
+            ))),
+            "folder must be an absolute path"
+        );
+    }
+}
@@ -11,6 +11,8 @@ use time::Duration;
 pub const ACCESS_COOKIE: &str = "nize_access";
 /// Cookie name for the refresh token.
 pub const REFRESH_COOKIE: &str = "nize_refresh";
+/// Cookie name for the CSRF token (see `middleware::csrf`).
+pub const CSRF_COOKIE: &str = "nize_csrf";
 
 /// Build a httpOnly cookie for the access token.
 pub fn access_cookie(token: &str, max_age_secs: i64) -> Cookie<'static> {
@@ -34,6 +36,18 @@ pub fn refresh_cookie(token: &str) -> Cookie<'static> {
         .build()
 }
 
+/// Build a httpOnly cookie for the CSRF token, living as long as the
+/// refresh token.
+pub fn csrf_cookie(token: &str) -> Cookie<'static> {
+    Cookie::build((CSRF_COOKIE.to_string(), token.to_string()))
+        .http_only(true)
+        .secure(false) // TODO: set true in production
+        .same_site(SameSite::Lax)
+        .path("/".to_string())
+        .max_age(Duration::days(30))
+        .build()
+}
+
 /// Build expired cookies to clear auth state.
 pub fn clear_access_cookie() -> Cookie<'static> {
     Cookie::build((ACCESS_COOKIE.to_string(), String::new()))
@@ -55,3 +69,14 @@ pub fn clear_refresh_cookie() -> Cookie<'static> {
         .max_age(Duration::ZERO)
         .build()
 }
+
+/// Build expired cookie to clear the CSRF token.
+pub fn clear_csrf_cookie() -> Cookie<'static> {
+    Cookie::build((CSRF_COOKIE.to_string(), String::new()))
+        .http_only(true)
+        .secure(false)
+        .same_site(SameSite::Lax)
+        .path("/".to_string())
+        .max_age(Duration::ZERO)
+        .build()
+}
@@ -0,0 +1,170 @@
+//! Document blob and extraction helpers shared by the ingest and account
+//! handlers.
+
+use nize_core::blobs::{self, BlobBackend, BlobStore};
+use nize_core::documents::{self, DocumentRow};
+use nize_core::embedding;
+use nize_core::ingest::{self, ocr::OcrProvider, transcribe::Transcriber};
+use nize_core::moderation::Source;
+use nize_core::notes::chunker::DEFAULT_MAX_CHUNK_CHARS;
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::events::{KIND_INGEST_BLOCKED, KIND_INGEST_COMPLETED, ServerEvent};
+use crate::services::moderation::{self, Subject};
+
+/// The store new uploads go to (`storage.blobs.backend`).
+pub async fn upload_store(state: &AppState) -> AppResult<BlobStore> {
+    Ok(BlobStore::configured(
+        &state.pool,
+        &state.config_cache,
+        &state.config.mcp_encryption_key,
+    )
+    .await?)
+}
+
+/// The store for `backend`, for blobs recorded there.
+pub async fn store_for(state: &AppState, backend: BlobBackend) -> AppResult<BlobStore> {
+    Ok(BlobStore::from_config(
+        &state.pool,
+        &state.config_cache,
+        &state.config.mcp_encryption_key,
+        backend,
+    )
+    .await?)
+}
+
+/// Delete the bytes of released blobs. Failures are logged: the records
+/// are already gone, so at worst the bytes are orphaned.
+pub async fn delete_released(state: &AppState, released: Vec<(String, BlobBackend)>) {
+    for (sha256, backend) in released {
+        let result = match store_for(state, backend).await {
+            Ok(store) => store.delete(&sha256).await.map_err(Into::into),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::warn!(sha256 = %sha256, error = %e, "failed to delete blob");
+        }
+    }
+}
+
+/// Release a document's blob once nothing references it.
+pub async fn release(state: &AppState, sha256: &str) -> AppResult<()> {
+    if let Some(backend) = blobs::release_blob(&state.pool, sha256).await? {
+        delete_released(state, vec![(sha256.to_string(), backend)]).await;
+    }
+    Ok(())
+}
+
+/// Delete a document whose content moderation blocked, and its blob once
+/// nothing else references it. Failures are logged.
+pub async fn discard(state: &AppState, row: &DocumentRow) {
+    let result = match documents::remove_document(&state.pool, &row.id).await {
+        Ok(_) => release(state, &row.sha256).await,
+        Err(e) => Err(e.into()),
+    };
+    if let Err(e) = result {
+        tracing::warn!(document_id = %row.id, error = %e, "failed to discard blocked document");
+    }
+}
+
+/// Extract a stored document's text (OCRing images and scanned pages and
+/// transcribing audio, when enabled), moderate it and replace its chunks.
+/// Returns the number of chunks.
+///
+/// Only a moderation block is returned, as [`AppError::ContentBlocked`];
+/// other failures are logged: the upload itself succeeded and the file
+/// stays downloadable.
+pub async fn extract_chunks(
+    state: &AppState,
+    store: &BlobStore,
+    row: &DocumentRow,
+) -> AppResult<usize> {
+    match try_extract_chunks(state, store, row).await {
+        Ok(count) => Ok(count),
+        Err(e @ AppError::ContentBlocked(_)) => Err(e),
+        Err(e) => {
+            tracing::warn!(document_id = %row.id, error = %e, "document extraction failed");
+            Ok(0)
+        }
+    }
+}
+
+async fn try_extract_chunks(
+    state: &AppState,
+    store: &BlobStore,
+    row: &DocumentRow,
+) -> AppResult<usize> {
+    let key = &state.config.mcp_encryption_key;
+    let ocr = OcrProvider::configured(&state.pool, &state.config_cache, key).await?;
+    let transcriber = Transcriber::configured(&state.pool, &state.config_cache, key).await?;
+    let file = store.spool(&row.sha256).await?;
+    let mut chunks = ingest::extract_chunks(
+        file.path(),
+        &row.filename,
+        &row.mime_type,
+        ocr.as_ref(),
+        transcriber.as_ref(),
+        DEFAULT_MAX_CHUNK_CHARS,
+    )
+    .await?;
+    if let Some(moderator) = moderation::moderator(state, Source::Document).await? {
+        let subject = Subject {
+            source: Source::Document,
+            user_id: Some(&row.user_id),
+            resource_id: Some(&row.id),
+        };
+        let mut parts: Vec<&mut String> = chunks.iter_mut().map(|c| &mut c.content).collect();
+        moderation::apply(state, &moderator, subject, &mut parts, "\n").await?;
+    }
+    Ok(documents::replace_chunks(&state.pool, &row.id, &chunks)
+        .await?
+        .len())
+}
+
+/// Embed a document's chunks without blocking the response; see [`index`].
+pub fn spawn_index(state: &AppState, store: BlobStore, row: DocumentRow, extract: bool) {
+    let state = state.clone();
+    tokio::spawn(async move { index(&state, &store, &row, extract).await });
+}
+
+/// Embed a document's chunks, publishing an `ingest.completed` event when
+/// done. With `extract`, the chunks are extracted first — used for audio,
+/// whose transcription can take minutes; if moderation blocks it the
+/// document is discarded and an `ingest.blocked` event published instead.
+pub async fn index(state: &AppState, store: &BlobStore, row: &DocumentRow, extract: bool) {
+    if extract
+        && let Err(AppError::ContentBlocked(blocked)) = extract_chunks(state, store, row).await
+    {
+        discard(state, row).await;
+        state.events.publish(ServerEvent {
+            user_id: Some(row.user_id),
+            kind: KIND_INGEST_BLOCKED.into(),
+            title: "Document blocked".into(),
+            body: row.filename.clone(),
+            payload: serde_json::json!({
+                "documentId": row.id,
+                "resultId": blocked.result_id,
+                "findings": blocked.labels,
+            }),
+        });
+        return;
+    }
+    match embedding::indexer::embed_document(
+        &state.pool,
+        &state.config_cache,
+        &row.id,
+        &state.config.mcp_encryption_key,
+    )
+    .await
+    {
+        Ok(chunks) => state.events.publish(ServerEvent {
+            user_id: Some(row.user_id),
+            kind: KIND_INGEST_COMPLETED.into(),
+            title: "Document indexed".into(),
+            body: row.filename.clone(),
+            payload: serde_json::json!({ "documentId": row.id, "chunks": chunks }),
+        }),
+        Err(e) => tracing::warn!("Failed to embed document {}: {e}", row.id),
+    }
+}
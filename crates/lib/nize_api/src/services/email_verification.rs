@@ -0,0 +1,126 @@
+//! Email verification — mails verification links and redeems them.
+//!
+//! Links point at the web app's [`VERIFY_PATH`] page under
+//! [`ApiConfig::chat_url`](crate::config::ApiConfig::chat_url), which
+//! calls `GET /auth/verify` with the token.
+
+use chrono::Utc;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use nize_core::auth::verification::{self, VerifyOutcome};
+use nize_core::email::{Email, Mailer};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+use crate::i18n::Message;
+
+/// Web app page that redeems a verification token.
+pub const VERIFY_PATH: &str = "/verify-email";
+
+/// Body of `POST /auth/register` when the account must be verified first.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationPendingResponse {
+    pub verification_required: bool,
+    pub email: String,
+}
+
+/// Body of `GET /auth/verify`.
+#[derive(Debug, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyEmailResponse {
+    pub verified: bool,
+}
+
+/// Body of `POST /auth/verify/resend`.
+#[derive(Debug, Deserialize)]
+pub struct ResendVerificationRequest {
+    pub email: String,
+}
+
+/// Link to the web app page that verifies `token`.
+pub fn verification_link(chat_url: Option<&str>, token: &str) -> String {
+    let base = chat_url.unwrap_or_default().trim_end_matches('/');
+    format!("{base}{VERIFY_PATH}?token={token}")
+}
+
+fn verification_email(to: &str, link: &str) -> Email {
+    Email {
+        to: to.to_string(),
+        subject: "Verify your Nize account".into(),
+        body: format!(
+            "Welcome to Nize!\n\n\
+             Open this link to verify your email address and activate your account:\n\n\
+             {link}\n\n\
+             The link is valid for {} hours. If you did not sign up, ignore this email.\n",
+            verification::TOKEN_TTL.num_hours()
+        ),
+    }
+}
+
+/// Issue a fresh token for an unverified account and mail its link.
+pub async fn send_verification(state: &AppState, user_id: &str, email: &str) -> AppResult<()> {
+    let token = verification::issue_token(&state.pool, user_id).await?;
+    let link = verification_link(state.config.chat_url.as_deref(), &token);
+    let mailer = Mailer::configured(
+        &state.pool,
+        &state.config_cache,
+        &state.config.mcp_encryption_key,
+    )
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    mailer
+        .send(&verification_email(email, &link))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+/// Verify the account `token` was issued for.
+pub async fn verify(state: &AppState, token: &str) -> AppResult<VerifyEmailResponse> {
+    match verification::verify(&state.pool, token).await? {
+        VerifyOutcome::Verified { email, .. } => {
+            info!(email, "email address verified");
+            Ok(VerifyEmailResponse { verified: true })
+        }
+        VerifyOutcome::Expired => Err(AppError::validation(Message::new(
+            "auth.verification_expired",
+        ))),
+        VerifyOutcome::Invalid => Err(AppError::validation(Message::new(
+            "auth.invalid_verification_token",
+        ))),
+    }
+}
+
+/// Mail a new link to the unverified account registered with `email`, at
+/// most once per [`verification::RESEND_INTERVAL`]. Does nothing for
+/// unknown or already verified addresses.
+pub async fn resend(state: &AppState, email: &str) -> AppResult<()> {
+    let Some(pending) = verification::find_pending(&state.pool, email).await? else {
+        return Ok(());
+    };
+    if !verification::can_resend(pending.last_issued_at, Utc::now()) {
+        return Ok(());
+    }
+    send_verification(state, &pending.user_id, email).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_point_at_the_web_app() {
+        assert_eq!(
+            verification_link(Some("https://nize.example.com/"), "abc"),
+            "https://nize.example.com/verify-email?token=abc"
+        );
+        assert_eq!(verification_link(None, "abc"), "/verify-email?token=abc");
+        assert!(
+            verification_email("a@example.com", "LINK")
+                .body
+                .contains("LINK")
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Background runner for eval suites.
+//!
+//! Each case's prompt goes through the chat app (`POST
+//! {chat_url}/api/chat/evals/run`) with the same model config, context
+//! handling and tools as a live chat, authenticated as the admin who started
+//! the run. Nothing is persisted as a conversation; the reply and tool calls
+//! are scored and stored as eval results, and an `eval.completed` event is
+//! published when the run finishes.
+
+use std::time::{Duration, Instant};
+
+use tracing::{debug, error, warn};
+
+use nize_core::eval::{self, CaseResult, EvalCase, EvalOutput, EvalRunRow, Suite};
+use nize_core::models::auth::TokenClaims;
+
+use crate::AppState;
+use crate::events::{KIND_EVAL_COMPLETED, ServerEvent};
+use crate::services::auth::generate_access_token;
+use crate::services::cookies::ACCESS_COOKIE;
+
+/// Upper bound on a single case. Kept below the access token lifetime.
+const CASE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Run `suite` in the background, recording results on `run`.
+pub fn spawn_eval_run(
+    state: AppState,
+    chat_url: String,
+    claims: TokenClaims,
+    run: EvalRunRow,
+    suite: Suite,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = reqwest::Client::builder()
+            .timeout(CASE_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        let mut error = None;
+        for case in &suite.cases {
+            debug!(run_id = %run.id, case = %case.name, "running eval case");
+            let started = Instant::now();
+            let outcome = run_case(&state, &client, &chat_url, &claims, &run, case).await;
+            let duration_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+            let result = match outcome {
+                Ok(output) => CaseResult::scored(case, output, duration_ms),
+                Err(e) => {
+                    warn!(run_id = %run.id, case = %case.name, error = %e, "eval case failed");
+                    CaseResult::failed(case, e, duration_ms)
+                }
+            };
+            if let Err(e) = eval::record_result(&state.pool, &run.id, &result).await {
+                error = Some(format!("Failed to record result: {e}"));
+                break;
+            }
+        }
+
+        match eval::finish_run(&state.pool, &run.id, error.as_deref()).await {
+            Ok(finished) => publish_completed(&state, &claims, &finished),
+            Err(e) => error!(run_id = %run.id, error = %e, "failed to finish eval run"),
+        }
+    })
+}
+
+/// Send one case's prompt through the chat app.
+async fn run_case(
+    state: &AppState,
+    client: &reqwest::Client,
+    chat_url: &str,
+    claims: &TokenClaims,
+    run: &EvalRunRow,
+    case: &EvalCase,
+) -> Result<EvalOutput, String> {
+    // A fresh token per case: a long suite can outlive a single token.
+    let token = generate_access_token(&claims.sub, &claims.email, &claims.roles, &state.jwt_keys)
+        .map_err(|e| e.to_string())?;
+
+    let response = client
+        .post(format!("{chat_url}/api/chat/evals/run"))
+        .header(reqwest::header::COOKIE, format!("{ACCESS_COOKIE}={token}"))
+        .json(&serde_json::json!({
+            "prompt": case.prompt,
+            "model": run.model,
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Chat app unreachable: {e}"))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Chat app returned {status}: {body}"));
+    }
+    response
+        .json::<EvalOutput>()
+        .await
+        .map_err(|e| format!("Invalid chat app response: {e}"))
+}
+
+fn publish_completed(state: &AppState, claims: &TokenClaims, run: &EvalRunRow) {
+    let Ok(user_id) = uuid::Uuid::parse_str(&claims.sub) else {
+        return;
+    };
+    let body = match (&run.error, run.score) {
+        (Some(e), _) => e.clone(),
+        (None, Some(score)) => format!(
+            "{}/{} cases passed (score {:.2})",
+            run.passed_count, run.case_count, score
+        ),
+        (None, None) => "No cases were run".to_string(),
+    };
+    state.events.publish(ServerEvent {
+        user_id: Some(user_id),
+        kind: KIND_EVAL_COMPLETED.into(),
+        title: format!("Eval run: {}", run.suite),
+        body,
+        payload: serde_json::json!({ "runId": run.id, "status": run.status }),
+    });
+}
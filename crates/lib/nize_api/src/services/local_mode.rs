@@ -0,0 +1,90 @@
+//! Local single-user mode (desktop only).
+//!
+//! When the desktop sidecar is started with a local token, the single
+//! local user is provisioned at startup (see `nize_core::auth::local`) and
+//! [`crate::middleware::auth`] accepts the token as that user's access
+//! token. The webview exchanges it for a regular cookie session at
+//! `POST /auth/local`. Without a token — always the case for server
+//! deployments — nothing changes.
+
+use chrono::Utc;
+use sqlx::PgPool;
+
+use nize_core::auth::AuthError;
+use nize_core::auth::local;
+use nize_core::models::auth::User;
+
+use crate::i18n::Message;
+use crate::services::auth::TokenClaims;
+
+/// Lifetime of the claims built for a local token, matching access tokens.
+const CLAIMS_EXPIRY_SECS: i64 = 15 * 60;
+
+/// The local token and the user it signs in as.
+#[derive(Debug)]
+pub struct LocalMode {
+    token: String,
+    user: User,
+    roles: Vec<String>,
+}
+
+impl LocalMode {
+    /// Provision the local user for `token`.
+    pub async fn provision(pool: &PgPool, token: String) -> Result<Self, AuthError> {
+        if token.len() < local::MIN_TOKEN_LEN {
+            return Err(AuthError::ValidationError(
+                Message::new("auth.local_token_too_short").arg("min", local::MIN_TOKEN_LEN),
+            ));
+        }
+        let user = local::provision_user(pool).await?;
+        let roles = nize_core::auth::queries::get_user_roles(pool, &user.id).await?;
+        Ok(Self { token, user, roles })
+    }
+
+    /// Whether `presented` is the local token.
+    pub fn accepts(&self, presented: &str) -> bool {
+        nize_core::auth::constant_time_eq(&self.token, presented)
+    }
+
+    /// The local user.
+    pub fn user(&self) -> &User {
+        &self.user
+    }
+
+    /// Claims standing in for a verified access token of the local user.
+    pub fn claims(&self) -> TokenClaims {
+        let now = Utc::now().timestamp();
+        TokenClaims {
+            sub: self.user.id.clone(),
+            email: self.user.email.clone(),
+            roles: self.roles.clone(),
+            exp: now + CLAIMS_EXPIRY_SECS,
+            iat: now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claims_stand_in_for_the_local_user() {
+        let local_mode = LocalMode {
+            token: local::generate_token(),
+            user: User {
+                id: uuid::Uuid::new_v4().to_string(),
+                email: local::LOCAL_USER_EMAIL.into(),
+                name: None,
+            },
+            roles: vec!["admin".into()],
+        };
+        assert!(local_mode.accepts(&local_mode.token.clone()));
+        assert!(!local_mode.accepts("not-the-token"));
+
+        let claims = local_mode.claims();
+        assert_eq!(claims.sub, local_mode.user.id);
+        assert_eq!(claims.roles, vec!["admin".to_string()]);
+        assert!(claims.exp > claims.iat);
+    }
+}
@@ -0,0 +1,200 @@
+//! Ready-to-paste MCP client configurations.
+//!
+//! Builds the snippet each supported client expects for connecting to the
+//! local MCP server with a bearer token, and the `nize://` deep link the
+//! desktop app's `mcp_clients` module applies to write the config directly.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Stands in for the token when the caller did not supply its plaintext.
+pub const TOKEN_PLACEHOLDER: &str = "<YOUR_NIZE_MCP_TOKEN>";
+
+/// Deep link consumed by the desktop app.
+pub const DEEP_LINK_BASE: &str = "nize://mcp-clients/configure";
+
+/// Entry name used in every client config.
+const SERVER_NAME: &str = "nize";
+
+/// MCP clients with generated configs. Names match the desktop app's
+/// `McpClient` where both exist.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ClientKind {
+    ClaudeDesktop,
+    ClaudeCode,
+    Cursor,
+    CopilotVscode,
+}
+
+impl ClientKind {
+    pub const ALL: &[ClientKind] = &[
+        ClientKind::ClaudeDesktop,
+        ClientKind::ClaudeCode,
+        ClientKind::Cursor,
+        ClientKind::CopilotVscode,
+    ];
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            ClientKind::ClaudeDesktop => "Claude Desktop",
+            ClientKind::ClaudeCode => "Claude Code",
+            ClientKind::Cursor => "Cursor",
+            ClientKind::CopilotVscode => "GitHub Copilot (VS Code)",
+        }
+    }
+
+    /// Whether the desktop app can write this client's config from a deep
+    /// link.
+    pub fn supports_deep_link(self) -> bool {
+        !matches!(self, ClientKind::Cursor)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ClientKind::ClaudeDesktop => "claudeDesktop",
+            ClientKind::ClaudeCode => "claudeCode",
+            ClientKind::Cursor => "cursor",
+            ClientKind::CopilotVscode => "copilotVscode",
+        }
+    }
+}
+
+/// A config snippet and where it goes.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientSnippet {
+    pub client: ClientKind,
+    pub display_name: &'static str,
+    /// `json` to merge into a config file, `shell` to run in a terminal.
+    pub format: &'static str,
+    /// Where the snippet goes.
+    pub location: &'static str,
+    pub content: String,
+}
+
+/// Build `client`'s snippet for `mcp_url` and `token`.
+pub fn snippet(client: ClientKind, mcp_url: &str, token: &str) -> ClientSnippet {
+    let auth = format!("Bearer {token}");
+    let http_entry = serde_json::json!({
+        "type": "http",
+        "url": mcp_url,
+        "headers": { "Authorization": auth },
+    });
+    let (format, location, content) = match client {
+        // Claude Desktop only speaks stdio; bridge through mcp-remote.
+        ClientKind::ClaudeDesktop => (
+            "json",
+            "claude_desktop_config.json",
+            pretty(serde_json::json!({
+                "mcpServers": { SERVER_NAME: {
+                    "command": "npx",
+                    "args": [
+                        "-y",
+                        "mcp-remote",
+                        mcp_url,
+                        "--allow-http",
+                        "--header",
+                        "Authorization:${AUTH_TOKEN}",
+                    ],
+                    "env": { "AUTH_TOKEN": auth },
+                }},
+            })),
+        ),
+        ClientKind::ClaudeCode => (
+            "shell",
+            "terminal",
+            format!(
+                "claude mcp add --transport http {SERVER_NAME} {mcp_url} --header \"Authorization: {auth}\""
+            ),
+        ),
+        ClientKind::Cursor => (
+            "json",
+            "~/.cursor/mcp.json",
+            pretty(serde_json::json!({
+                "mcpServers": { SERVER_NAME: {
+                    "url": mcp_url,
+                    "headers": { "Authorization": auth },
+                }},
+            })),
+        ),
+        ClientKind::CopilotVscode => (
+            "json",
+            "VS Code user mcp.json",
+            pretty(serde_json::json!({ "servers": { SERVER_NAME: http_entry } })),
+        ),
+    };
+    ClientSnippet {
+        client,
+        display_name: client.display_name(),
+        format,
+        location,
+        content,
+    }
+}
+
+/// Deep link asking the desktop app to configure `client`. `None` when the
+/// client is not supported or `mcp_url` is not a local MCP server (the
+/// desktop app only writes `http://127.0.0.1:{port}/mcp` entries).
+pub fn deep_link(client: ClientKind, mcp_url: &str, token: &str) -> Option<String> {
+    if !client.supports_deep_link() {
+        return None;
+    }
+    let url = url::Url::parse(mcp_url).ok()?;
+    if url.scheme() != "http" || url.host_str() != Some("127.0.0.1") || url.path() != "/mcp" {
+        return None;
+    }
+    let port = url.port()?;
+    let mut link = url::Url::parse(DEEP_LINK_BASE).ok()?;
+    link.query_pairs_mut()
+        .append_pair("client", client.as_str())
+        .append_pair("port", &port.to_string())
+        .append_pair("token", token);
+    Some(link.into())
+}
+
+fn pretty(value: serde_json::Value) -> String {
+    serde_json::to_string_pretty(&value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "http://127.0.0.1:3101/mcp";
+
+    #[test]
+    fn json_snippets_embed_url_and_token() {
+        for client in [
+            ClientKind::ClaudeDesktop,
+            ClientKind::Cursor,
+            ClientKind::CopilotVscode,
+        ] {
+            let s = snippet(client, URL, "tok");
+            let json: serde_json::Value = serde_json::from_str(&s.content).unwrap();
+            let text = json.to_string();
+            assert!(text.contains(URL), "{client:?}");
+            assert!(text.contains("Bearer tok"), "{client:?}");
+        }
+        let vscode = snippet(ClientKind::CopilotVscode, URL, "tok");
+        assert!(vscode.content.contains("\"servers\""));
+    }
+
+    #[test]
+    fn deep_link_requires_local_url_and_supported_client() {
+        let link = deep_link(ClientKind::ClaudeCode, URL, "tok").unwrap();
+        assert_eq!(
+            link,
+            "nize://mcp-clients/configure?client=claudeCode&port=3101&token=tok"
+        );
+        assert!(deep_link(ClientKind::Cursor, URL, "tok").is_none());
+        assert!(
+            deep_link(
+                ClientKind::ClaudeCode,
+                "https://nize.example.com/mcp",
+                "tok"
+            )
+            .is_none()
+        );
+    }
+}
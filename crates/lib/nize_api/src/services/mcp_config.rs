@@ -3,23 +3,33 @@
 //! Business logic for managing MCP server registrations, user preferences,
 //! and connection testing. Ported from reference project's ConfigService.
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures_util::{StreamExt, stream};
 use sqlx::PgPool;
-use tracing::{error, info};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use uuid::Uuid;
 
+use nize_core::config::cache::ConfigCache;
 use nize_core::mcp::McpError;
-use nize_core::mcp::execution::OAuthHeaders;
+use nize_core::mcp::audit::{AuditEntry, AuditLog};
+use nize_core::mcp::catalog::{self, CatalogEntry, InstantiatedServer};
+use nize_core::mcp::context;
+use nize_core::mcp::execution::{self, OAuthHeaders};
 use nize_core::mcp::queries;
+use nize_core::mcp::sandbox;
+use nize_core::mcp::sharing;
+use nize_core::mcp::transforms::{self, OutputTransformRow, TransformRules};
 use nize_core::models::mcp::{
-    AdminServerView, AuthType, DeleteResult, HttpServerConfig, McpServerRow, McpToolSummary,
-    OAuthConfig, ServerConfig, ServerStatus, SseServerConfig, TestConnectionResult, TransportType,
-    UserServerView, VisibilityTier,
+    AdminServerView, AuthType, DISCOVERY_FAILED, DISCOVERY_SUCCEEDED, DeleteResult,
+    HttpServerConfig, McpDiscoveryRow, McpServerRow, McpServerStatsRow, McpServerToolRow,
+    McpToolSummary, OAuthConfig, ServerConfig, ServerStatus, ServerToolView, SseServerConfig,
+    TestConnectionResult, TransportType, UserServerView, VisibilityTier,
 };
 
-/// Maximum number of user-owned servers.
-const USER_SERVER_LIMIT: usize = 10;
-
-/// Default encryption key ID.
-const DEFAULT_ENCRYPTION_KEY_ID: &str = "v1";
+use crate::i18n::Message;
 
 // =============================================================================
 // Validation helpers
@@ -28,28 +38,28 @@ const DEFAULT_ENCRYPTION_KEY_ID: &str = "v1";
 /// Validate an HTTP server config.
 fn validate_http_config(url: &str, auth_type_str: &str) -> Result<(), McpError> {
     if url.trim().is_empty() {
-        return Err(McpError::InvalidTransport(
-            "HTTP config requires a non-empty URL".into(),
-        ));
+        return Err(McpError::InvalidTransport(Message::new(
+            "mcp.http_url_required",
+        )));
     }
 
     let parsed = url::Url::parse(url)
-        .map_err(|_| McpError::InvalidTransport(format!("Invalid URL format: {url}")))?;
+        .map_err(|_| McpError::InvalidTransport(Message::new("mcp.invalid_url").arg("url", url)))?;
 
     let is_localhost = parsed
         .host_str()
         .is_some_and(|h| h == "localhost" || h == "127.0.0.1" || h == "::1");
 
     if parsed.scheme() != "https" && !is_localhost {
-        return Err(McpError::InvalidTransport(
-            "HTTP URL must use HTTPS (HTTP only allowed for localhost)".into(),
-        ));
+        return Err(McpError::InvalidTransport(Message::new(
+            "mcp.http_url_insecure",
+        )));
     }
 
     if !["none", "api-key", "oauth"].contains(&auth_type_str) {
-        return Err(McpError::InvalidTransport(format!(
-            "Invalid authType: {auth_type_str}"
-        )));
+        return Err(McpError::InvalidTransport(
+            Message::new("mcp.invalid_auth_type").arg("auth_type", auth_type_str),
+        ));
     }
 
     Ok(())
@@ -59,29 +69,29 @@ fn validate_http_config(url: &str, auth_type_str: &str) -> Result<(), McpError>
 /// Validate an SSE server config.
 fn validate_sse_config(sse: &SseServerConfig) -> Result<(), McpError> {
     if sse.url.trim().is_empty() {
-        return Err(McpError::InvalidTransport(
-            "SSE config requires a non-empty URL".into(),
-        ));
+        return Err(McpError::InvalidTransport(Message::new(
+            "mcp.sse_url_required",
+        )));
     }
 
-    let parsed = url::Url::parse(&sse.url)
-        .map_err(|_| McpError::InvalidTransport(format!("Invalid SSE URL format: {}", sse.url)))?;
+    let parsed = url::Url::parse(&sse.url).map_err(|_| {
+        McpError::InvalidTransport(Message::new("mcp.invalid_sse_url").arg("url", &sse.url))
+    })?;
 
     let is_localhost = parsed
         .host_str()
         .is_some_and(|h| h == "localhost" || h == "127.0.0.1" || h == "::1");
 
     if parsed.scheme() != "https" && !is_localhost {
-        return Err(McpError::InvalidTransport(
-            "SSE URL must use HTTPS (HTTP only allowed for localhost)".into(),
-        ));
+        return Err(McpError::InvalidTransport(Message::new(
+            "mcp.sse_url_insecure",
+        )));
     }
 
     if !["none", "api-key", "oauth"].contains(&sse.auth_type.as_str()) {
-        return Err(McpError::InvalidTransport(format!(
-            "Invalid SSE authType: {}",
-            sse.auth_type
-        )));
+        return Err(McpError::InvalidTransport(
+            Message::new("mcp.invalid_sse_auth_type").arg("auth_type", &sse.auth_type),
+        ));
     }
 
     Ok(())
@@ -93,26 +103,30 @@ fn validate_managed_config(
     config: &nize_core::models::mcp::ManagedHttpServerConfig,
 ) -> Result<(), McpError> {
     if config.command.trim().is_empty() {
-        return Err(McpError::InvalidTransport(
-            "Managed config requires a non-empty command".into(),
-        ));
+        return Err(McpError::InvalidTransport(Message::new(
+            "mcp.managed_command_required",
+        )));
     }
 
     if config.port == 0 {
-        return Err(McpError::InvalidTransport(
-            "Managed config requires a non-zero port".into(),
-        ));
+        return Err(McpError::InvalidTransport(Message::new(
+            "mcp.managed_port_required",
+        )));
     }
 
     Ok(())
 }
 
+/// Validate the sandbox block of a transport that spawns a process.
+fn validate_sandbox(config: &ServerConfig) -> Result<(), McpError> {
+    config.sandbox().map_or(Ok(()), sandbox::validate)
+}
+
 /// Compute server status for a user.
-async fn compute_status(
-    pool: &PgPool,
+fn compute_status(
     server: &McpServerRow,
-    user_id: &str,
     user_pref: Option<bool>,
+    has_valid_oauth_token: bool,
 ) -> ServerStatus {
     if !server.available {
         return ServerStatus::Unavailable;
@@ -123,50 +137,66 @@ async fn compute_status(
     }
 
     let auth_type = queries::extract_auth_type(&server.config);
-    if auth_type == AuthType::OAuth {
-        let has_token = queries::has_valid_oauth_token(pool, user_id, &server.id.to_string())
-            .await
-            .unwrap_or(false);
-        if !has_token {
-            return ServerStatus::Unauthorized;
-        }
+    if auth_type == AuthType::OAuth && !has_valid_oauth_token {
+        return ServerStatus::Unauthorized;
     }
 
     ServerStatus::Enabled
 }
 
-/// Convert a McpServerRow to UserServerView.
-async fn to_user_view(
+/// Fetch listing stats for `servers` in one query, keyed by server ID.
+async fn server_stats(
     pool: &PgPool,
+    servers: &[McpServerRow],
+    user_id: Option<&str>,
+) -> Result<HashMap<Uuid, McpServerStatsRow>, McpError> {
+    let ids: Vec<Uuid> = servers.iter().map(|s| s.id).collect();
+    let stats = queries::get_server_stats(pool, &ids, user_id).await?;
+    Ok(stats.into_iter().map(|s| (s.server_id, s)).collect())
+}
+
+/// Convert a McpServerRow to UserServerView.
+fn to_user_view(
     server: &McpServerRow,
     user_id: &str,
     user_pref: Option<bool>,
-) -> Result<UserServerView, McpError> {
-    let tool_count = queries::get_tool_count(pool, &server.id.to_string()).await?;
-    let status = compute_status(pool, server, user_id, user_pref).await;
+    stats: Option<&McpServerStatsRow>,
+) -> UserServerView {
+    let has_token = stats.is_some_and(|s| s.has_valid_oauth_token);
 
-    Ok(UserServerView {
+    UserServerView {
         id: server.id.to_string(),
         name: server.name.clone(),
         description: server.description.clone(),
         domain: server.domain.clone(),
         visibility: server.visibility.clone(),
-        status,
-        tool_count,
+        status: compute_status(server, user_pref, has_token),
+        tool_count: stats.map_or(0, |s| s.tool_count),
         is_owned: server
             .owner_id
             .map(|o| o.to_string() == user_id)
             .unwrap_or(false),
         created_at: server.created_at.to_rfc3339(),
         updated_at: server.updated_at.to_rfc3339(),
-    })
+    }
+}
+
+/// Build the user view of a single server.
+async fn user_view(
+    pool: &PgPool,
+    server: &McpServerRow,
+    user_id: &str,
+) -> Result<UserServerView, McpError> {
+    let stats = server_stats(pool, std::slice::from_ref(server), Some(user_id)).await?;
+    Ok(to_user_view(server, user_id, None, stats.get(&server.id)))
 }
 
 /// Convert a McpServerRow to AdminServerView.
-async fn to_admin_view(pool: &PgPool, server: &McpServerRow) -> Result<AdminServerView, McpError> {
-    let tool_count = queries::get_tool_count(pool, &server.id.to_string()).await?;
-    let user_preference_count =
-        queries::get_user_preference_count(pool, &server.id.to_string()).await?;
+fn to_admin_view(
+    server: &McpServerRow,
+    stats: Option<&McpServerStatsRow>,
+    discovery: Option<McpDiscoveryRow>,
+) -> AdminServerView {
     let auth_type = queries::extract_auth_type(&server.config);
 
     // For admin view, status is simple: enabled if available, unavailable otherwise
@@ -178,26 +208,60 @@ async fn to_admin_view(pool: &PgPool, server: &McpServerRow) -> Result<AdminServ
         ServerStatus::Enabled
     };
 
-    Ok(AdminServerView {
+    AdminServerView {
         id: server.id.to_string(),
         name: server.name.clone(),
         description: server.description.clone(),
         domain: server.domain.clone(),
         visibility: server.visibility.clone(),
         status,
-        tool_count,
+        tool_count: stats.map_or(0, |s| s.tool_count),
         is_owned: server.owner_id.is_some(),
         transport: server.transport.clone(),
         auth_type,
         owner_id: server.owner_id.map(|o| o.to_string()),
-        user_preference_count,
+        user_preference_count: stats.map_or(0, |s| s.user_preference_count),
         enabled: server.enabled,
         available: server.available,
+        keep_warm: server.keep_warm,
+        forwarded_context: server.forwarded_context.clone(),
         config: server.config.clone(),
         oauth_config: server.oauth_config.clone(),
+        discovery,
         created_at: server.created_at.to_rfc3339(),
         updated_at: server.updated_at.to_rfc3339(),
-    })
+    }
+}
+
+/// Build admin views for `servers` with batched stats and discovery lookups.
+async fn admin_views(
+    pool: &PgPool,
+    servers: &[McpServerRow],
+) -> Result<Vec<AdminServerView>, McpError> {
+    let stats = server_stats(pool, servers, None).await?;
+    let ids: Vec<Uuid> = servers.iter().map(|s| s.id).collect();
+    let mut discoveries: HashMap<Uuid, McpDiscoveryRow> = queries::list_discoveries(pool, &ids)
+        .await?
+        .into_iter()
+        .map(|d| (d.server_id, d))
+        .collect();
+
+    Ok(servers
+        .iter()
+        .map(|server| {
+            to_admin_view(
+                server,
+                stats.get(&server.id),
+                discoveries.remove(&server.id),
+            )
+        })
+        .collect())
+}
+
+/// Build the admin view of a single server.
+async fn admin_view(pool: &PgPool, server: &McpServerRow) -> Result<AdminServerView, McpError> {
+    let mut views = admin_views(pool, std::slice::from_ref(server)).await?;
+    Ok(views.remove(0))
 }
 
 // =============================================================================
@@ -211,24 +275,27 @@ pub async fn get_servers_for_user(
 ) -> Result<Vec<UserServerView>, McpError> {
     let servers = queries::list_servers_for_user(pool, user_id).await?;
     let prefs = queries::get_user_preferences(pool, user_id).await?;
-    let pref_map: std::collections::HashMap<_, _> = prefs
-        .iter()
-        .map(|p| (p.server_id.to_string(), p.enabled))
-        .collect();
+    let pref_map: HashMap<_, _> = prefs.iter().map(|p| (p.server_id, p.enabled)).collect();
+    let stats = server_stats(pool, &servers, Some(user_id)).await?;
 
-    let mut views = Vec::with_capacity(servers.len());
-    for server in &servers {
-        let user_pref = pref_map.get(&server.id.to_string()).copied();
-        let view = to_user_view(pool, server, user_id, user_pref).await?;
-        views.push(view);
-    }
-    Ok(views)
+    Ok(servers
+        .iter()
+        .map(|server| {
+            to_user_view(
+                server,
+                user_id,
+                pref_map.get(&server.id).copied(),
+                stats.get(&server.id),
+            )
+        })
+        .collect())
 }
 
 /// Create a new user MCP server.
 #[allow(clippy::too_many_arguments)]
 pub async fn create_user_server(
     pool: &PgPool,
+    audit: &AuditLog,
     user_id: &str,
     name: &str,
     description: &str,
@@ -247,9 +314,9 @@ pub async fn create_user_server(
     match transport {
         TransportType::Http | TransportType::Sse => {}
         _ => {
-            return Err(McpError::Validation(
-                "Users can only create http or sse servers; managed transports require admin privileges".into(),
-            ));
+            return Err(McpError::Validation(Message::new(
+                "mcp.managed_transport_admin_only",
+            )));
         }
     }
 
@@ -268,36 +335,30 @@ pub async fn create_user_server(
     // Validate OAuth fields when auth_type is "oauth"
     if auth_type_str == "oauth" {
         if oauth_config.is_none() {
-            return Err(McpError::Validation(
-                "oauthConfig is required when authType is 'oauth'".into(),
-            ));
+            return Err(McpError::Validation(Message::new(
+                "mcp.oauth_config_required",
+            )));
         }
         if client_secret.is_none() {
-            return Err(McpError::Validation(
-                "clientSecret is required when authType is 'oauth'".into(),
-            ));
+            return Err(McpError::Validation(Message::new(
+                "mcp.oauth_client_secret_required",
+            )));
         }
         // Validate scopes include required openid + email
         if let Some(cfg) = oauth_config {
             if !cfg.scopes.iter().any(|s| s == "openid") {
-                return Err(McpError::Validation(
-                    "OAuth scopes must include 'openid'".into(),
-                ));
+                return Err(McpError::Validation(Message::new(
+                    "mcp.oauth_openid_scope_required",
+                )));
             }
             if !cfg.scopes.iter().any(|s| s == "email") {
-                return Err(McpError::Validation(
-                    "OAuth scopes must include 'email'".into(),
-                ));
+                return Err(McpError::Validation(Message::new(
+                    "mcp.oauth_email_scope_required",
+                )));
             }
         }
     }
 
-    // Check server limit
-    let count = queries::count_user_servers(pool, user_id).await?;
-    if count >= USER_SERVER_LIMIT as i64 {
-        return Err(McpError::ServerLimitExceeded(USER_SERVER_LIMIT));
-    }
-
     // Check duplicate name
     if queries::user_has_server_named(pool, user_id, name).await? {
         return Err(McpError::DuplicateServer(name.to_string()));
@@ -327,11 +388,14 @@ pub async fn create_user_server(
     let oauth_config_json = oauth_config
         .map(serde_json::to_value)
         .transpose()
-        .map_err(|e| McpError::Validation(format!("Failed to serialize oauth_config: {e}")))?;
+        .map_err(|e| {
+            McpError::Validation(Message::new("mcp.oauth_config_serialize_failed").arg("error", e))
+        })?;
 
-    // Insert server
+    // Insert server and its secrets atomically
+    let mut tx = pool.begin().await?;
     let server = queries::insert_user_server(
-        pool,
+        &mut *tx,
         user_id,
         name,
         description,
@@ -343,51 +407,41 @@ pub async fn create_user_server(
     .await?;
     let server_id = server.id.to_string();
 
-    // Store encrypted API key if provided
-    if let Some(key) = api_key
-        && auth_type_str == "api-key"
-    {
-        let encrypted = nize_core::mcp::secrets::encrypt(key, encryption_key)?;
-        queries::store_api_key(pool, &server_id, &encrypted, DEFAULT_ENCRYPTION_KEY_ID).await?;
-    }
-
-    // Store encrypted OAuth client secret if provided
-    if let Some(secret) = client_secret
-        && auth_type_str == "oauth"
-    {
-        let encrypted = nize_core::mcp::secrets::encrypt(secret, encryption_key)?;
-        queries::store_oauth_client_secret(pool, &server_id, &encrypted, DEFAULT_ENCRYPTION_KEY_ID)
-            .await?;
-    }
+    // Store the encrypted API key or OAuth client secret, if provided
+    sharing::store_secrets(
+        &mut tx,
+        &server_id,
+        api_key.filter(|_| auth_type_str == "api-key"),
+        client_secret.filter(|_| auth_type_str == "oauth"),
+        encryption_key,
+    )
+    .await?;
+    tx.commit().await?;
 
-    // Log audit
+    // Audit
     let details = serde_json::json!({
         "visibility": "user",
         "transport": "http",
         "domain": domain,
     });
-    if let Err(e) = queries::insert_audit_log(
-        pool,
+    audit.record(AuditEntry::new(
         user_id,
         Some(&server_id),
         name,
         "created",
-        Some(&details),
-    )
-    .await
-    {
-        error!("Failed to write audit log: {e}");
-    }
+        details,
+    ));
 
     info!(server_id = %server_id, "Created user MCP server: {name}");
 
-    to_user_view(pool, &server, user_id, None).await
+    user_view(pool, &server, user_id).await
 }
 
 /// Update a user MCP server.
 #[allow(clippy::too_many_arguments)]
 pub async fn update_user_server(
     pool: &PgPool,
+    audit: &AuditLog,
     user_id: &str,
     server_id: &str,
     name: Option<&str>,
@@ -401,9 +455,9 @@ pub async fn update_user_server(
     encryption_key: &str,
 ) -> Result<UserServerView, McpError> {
     // Verify server exists and is owned by user
-    let existing = queries::get_server(pool, server_id)
-        .await?
-        .ok_or_else(|| McpError::NotFound(format!("Server {server_id} not found")))?;
+    let existing = queries::get_server(pool, server_id).await?.ok_or_else(|| {
+        McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id))
+    })?;
 
     if existing.visibility != VisibilityTier::User
         || existing
@@ -411,9 +465,7 @@ pub async fn update_user_server(
             .map(|o| o.to_string() != user_id)
             .unwrap_or(true)
     {
-        return Err(McpError::Forbidden(
-            "Cannot modify a server you don't own".into(),
-        ));
+        return Err(McpError::Forbidden(Message::new("mcp.modify_forbidden")));
     }
 
     // Validate URL if provided
@@ -455,8 +507,9 @@ pub async fn update_user_server(
         None
     };
 
+    let mut tx = pool.begin().await?;
     let server = queries::update_server(
-        pool,
+        &mut *tx,
         server_id,
         name,
         description,
@@ -471,38 +524,32 @@ pub async fn update_user_server(
     .await?;
 
     // Store encrypted API key if provided
-    if let Some(key) = api_key {
-        let encrypted = nize_core::mcp::secrets::encrypt(key, encryption_key)?;
-        queries::store_api_key(pool, server_id, &encrypted, DEFAULT_ENCRYPTION_KEY_ID).await?;
-    }
+    sharing::store_secrets(&mut tx, server_id, api_key, None, encryption_key).await?;
+    tx.commit().await?;
 
     // Audit
     let details = serde_json::json!({ "action": "user_update" });
-    if let Err(e) = queries::insert_audit_log(
-        pool,
+    audit.record(AuditEntry::new(
         user_id,
         Some(server_id),
         &server.name,
         "updated",
-        Some(&details),
-    )
-    .await
-    {
-        error!("Failed to write audit log: {e}");
-    }
+        details,
+    ));
 
-    to_user_view(pool, &server, user_id, None).await
+    user_view(pool, &server, user_id).await
 }
 
 /// Delete a user MCP server.
 pub async fn delete_user_server(
     pool: &PgPool,
+    audit: &AuditLog,
     user_id: &str,
     server_id: &str,
 ) -> Result<(), McpError> {
-    let existing = queries::get_server(pool, server_id)
-        .await?
-        .ok_or_else(|| McpError::NotFound(format!("Server {server_id} not found")))?;
+    let existing = queries::get_server(pool, server_id).await?.ok_or_else(|| {
+        McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id))
+    })?;
 
     if existing.visibility != VisibilityTier::User
         || existing
@@ -510,9 +557,7 @@ pub async fn delete_user_server(
             .map(|o| o.to_string() != user_id)
             .unwrap_or(true)
     {
-        return Err(McpError::Forbidden(
-            "Cannot delete a server you don't own".into(),
-        ));
+        return Err(McpError::Forbidden(Message::new("mcp.delete_forbidden")));
     }
 
     let name = existing.name.clone();
@@ -520,11 +565,7 @@ pub async fn delete_user_server(
 
     // Audit
     let details = serde_json::json!({ "action": "user_delete" });
-    if let Err(e) =
-        queries::insert_audit_log(pool, user_id, None, &name, "deleted", Some(&details)).await
-    {
-        error!("Failed to write audit log: {e}");
-    }
+    audit.record(AuditEntry::new(user_id, None, &name, "deleted", details));
 
     info!(server_id = %server_id, "Deleted user MCP server: {name}");
     Ok(())
@@ -538,33 +579,178 @@ pub async fn set_user_preference(
     enabled: bool,
 ) -> Result<(), McpError> {
     // Verify server exists
-    queries::get_server(pool, server_id)
-        .await?
-        .ok_or_else(|| McpError::NotFound(format!("Server {server_id} not found")))?;
+    queries::get_server(pool, server_id).await?.ok_or_else(|| {
+        McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id))
+    })?;
 
     queries::set_user_preference(pool, user_id, server_id, enabled).await
 }
 
-/// Get tools for a server.
+/// Get tools for a server with their enablement for a user.
 pub async fn get_server_tools(
     pool: &PgPool,
     server_id: &str,
-) -> Result<Vec<McpToolSummary>, McpError> {
+    user_id: &str,
+) -> Result<Vec<ServerToolView>, McpError> {
     // Verify server exists
-    queries::get_server(pool, server_id)
-        .await?
-        .ok_or_else(|| McpError::NotFound(format!("Server {server_id} not found")))?;
+    queries::get_server(pool, server_id).await?.ok_or_else(|| {
+        McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id))
+    })?;
 
     let tool_rows = queries::list_server_tools(pool, server_id).await?;
+    let mut enablement: HashMap<Uuid, queries::ToolEnablementRow> =
+        queries::list_tool_enablement(pool, server_id, user_id)
+            .await?
+            .into_iter()
+            .map(|row| (row.tool_id, row))
+            .collect();
     Ok(tool_rows
         .into_iter()
-        .map(|t| McpToolSummary {
-            name: t.name,
-            description: t.description,
+        .map(|row| {
+            let state = enablement.remove(&row.id);
+            ServerToolView {
+                id: row.id,
+                admin_enabled: state.as_ref().is_none_or(|s| s.admin_enabled),
+                enabled: state.as_ref().is_none_or(|s| s.user_enabled),
+                tool: McpToolSummary::from_row(row),
+            }
         })
         .collect())
 }
 
+/// Switch one of a server's tools on or off for a user. The user must have
+/// access to the server.
+pub async fn set_user_tool_enabled(
+    pool: &PgPool,
+    user_id: &str,
+    server_id: &str,
+    tool_id: &str,
+    enabled: bool,
+) -> Result<(), McpError> {
+    if !queries::user_has_server_access(pool, user_id, server_id).await? {
+        return Err(McpError::NotFound(
+            Message::new("mcp.server_not_found").arg("id", server_id),
+        ));
+    }
+    let tool = queries::get_server_tool(pool, server_id, tool_id)
+        .await?
+        .ok_or_else(|| {
+            McpError::NotFound(Message::new("mcp.tool_id_not_found").arg("id", tool_id))
+        })?;
+    queries::set_user_tool_preference(pool, user_id, server_id, &tool.name, enabled).await
+}
+
+/// Switch one of a server's tools on or off for every user (admin).
+pub async fn set_admin_tool_enabled(
+    pool: &PgPool,
+    audit: &AuditLog,
+    admin_id: &str,
+    server_id: &str,
+    tool_id: &str,
+    enabled: bool,
+) -> Result<(), McpError> {
+    let server = queries::get_server(pool, server_id).await?.ok_or_else(|| {
+        McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id))
+    })?;
+    let tool = queries::get_server_tool(pool, server_id, tool_id)
+        .await?
+        .ok_or_else(|| {
+            McpError::NotFound(Message::new("mcp.tool_id_not_found").arg("id", tool_id))
+        })?;
+    queries::set_tool_override(pool, server_id, &tool.name, enabled, admin_id).await?;
+
+    // Audit
+    let details = serde_json::json!({ "tool": tool.name, "enabled": enabled });
+    audit.record(AuditEntry::new(
+        admin_id,
+        Some(server_id),
+        &server.name,
+        if enabled {
+            "tool_enabled"
+        } else {
+            "tool_disabled"
+        },
+        details,
+    ));
+    Ok(())
+}
+
+/// Set the output transform template of a tool (admin).
+pub async fn set_tool_transform(
+    pool: &PgPool,
+    audit: &AuditLog,
+    admin_id: &str,
+    server_id: &str,
+    tool_id: &str,
+    rules: &TransformRules,
+    enabled: bool,
+) -> Result<OutputTransformRow, McpError> {
+    let (server, tool) = admin_tool(pool, server_id, tool_id).await?;
+    let admin_uuid = Uuid::parse_str(admin_id).map_err(|_| {
+        McpError::Validation(Message::new("mcp.invalid_user_id").arg("id", admin_id))
+    })?;
+    let row = transforms::set_transform(
+        pool,
+        &tool.server_id,
+        &tool.name,
+        rules,
+        enabled,
+        &admin_uuid,
+    )
+    .await?;
+
+    let details = serde_json::json!({ "tool": tool.name, "rules": rules, "enabled": enabled });
+    audit.record(AuditEntry::new(
+        admin_id,
+        Some(server_id),
+        &server.name,
+        "tool_transform_set",
+        details,
+    ));
+    Ok(row)
+}
+
+/// Remove the output transform template of a tool (admin). Returns whether
+/// it had one.
+pub async fn delete_tool_transform(
+    pool: &PgPool,
+    audit: &AuditLog,
+    admin_id: &str,
+    server_id: &str,
+    tool_id: &str,
+) -> Result<bool, McpError> {
+    let (server, tool) = admin_tool(pool, server_id, tool_id).await?;
+    let deleted = transforms::delete_transform(pool, &tool.server_id, &tool.name).await?;
+    if deleted {
+        let details = serde_json::json!({ "tool": tool.name });
+        audit.record(AuditEntry::new(
+            admin_id,
+            Some(server_id),
+            &server.name,
+            "tool_transform_deleted",
+            details,
+        ));
+    }
+    Ok(deleted)
+}
+
+/// Look up a server and one of its tools.
+async fn admin_tool(
+    pool: &PgPool,
+    server_id: &str,
+    tool_id: &str,
+) -> Result<(McpServerRow, McpServerToolRow), McpError> {
+    let server = queries::get_server(pool, server_id).await?.ok_or_else(|| {
+        McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id))
+    })?;
+    let tool = queries::get_server_tool(pool, server_id, tool_id)
+        .await?
+        .ok_or_else(|| {
+            McpError::NotFound(Message::new("mcp.tool_id_not_found").arg("id", tool_id))
+        })?;
+    Ok((server, tool))
+}
+
 // =============================================================================
 // Admin operations
 // =============================================================================
@@ -572,18 +758,14 @@ pub async fn get_server_tools(
 /// List all servers (admin).
 pub async fn get_all_servers(pool: &PgPool) -> Result<Vec<AdminServerView>, McpError> {
     let servers = queries::list_all_servers(pool).await?;
-    let mut views = Vec::with_capacity(servers.len());
-    for server in &servers {
-        let view = to_admin_view(pool, server).await?;
-        views.push(view);
-    }
-    Ok(views)
+    admin_views(pool, &servers).await
 }
 
 /// Create a built-in server (admin).
 #[allow(clippy::too_many_arguments)]
 pub async fn create_built_in_server(
     pool: &PgPool,
+    audit: &AuditLog,
     admin_id: &str,
     name: &str,
     description: &str,
@@ -599,9 +781,9 @@ pub async fn create_built_in_server(
         "hidden" => VisibilityTier::Hidden,
         "visible" => VisibilityTier::Visible,
         _ => {
-            return Err(McpError::Validation(format!(
-                "Invalid visibility: {visibility}"
-            )));
+            return Err(McpError::Validation(
+                Message::new("mcp.invalid_visibility").arg("visibility", visibility),
+            ));
         }
     };
 
@@ -618,16 +800,20 @@ pub async fn create_built_in_server(
     if let ServerConfig::ManagedSse(m) | ServerConfig::ManagedHttp(m) = config {
         validate_managed_config(m)?;
     }
+    validate_sandbox(config)?;
 
     // Serialize config to JSON (includes transport tag)
-    let config_json = serde_json::to_value(config)
-        .map_err(|e| McpError::Validation(format!("Failed to serialize config: {e}")))?;
+    let config_json = serde_json::to_value(config).map_err(|e| {
+        McpError::Validation(Message::new("mcp.config_serialize_failed").arg("error", e))
+    })?;
 
     // Serialize oauth_config if provided
     let oauth_config_json = oauth_config
         .map(serde_json::to_value)
         .transpose()
-        .map_err(|e| McpError::Validation(format!("Failed to serialize oauth_config: {e}")))?;
+        .map_err(|e| {
+            McpError::Validation(Message::new("mcp.oauth_config_serialize_failed").arg("error", e))
+        })?;
 
     // OAuth servers start as unavailable until user authorizes
     let auth_type_str = match config {
@@ -637,8 +823,10 @@ pub async fn create_built_in_server(
     };
     let available = auth_type_str != "oauth";
 
+    // Insert server and its secrets atomically
+    let mut tx = pool.begin().await?;
     let server = queries::insert_built_in_server(
-        pool,
+        &mut *tx,
         name,
         description,
         domain,
@@ -652,18 +840,9 @@ pub async fn create_built_in_server(
     .await?;
     let server_id = server.id.to_string();
 
-    // Store encrypted API key if provided
-    if let Some(key) = api_key {
-        let encrypted = nize_core::mcp::secrets::encrypt(key, encryption_key)?;
-        queries::store_api_key(pool, &server_id, &encrypted, DEFAULT_ENCRYPTION_KEY_ID).await?;
-    }
-
-    // Store encrypted OAuth client secret if provided
-    if let Some(secret) = client_secret {
-        let encrypted = nize_core::mcp::secrets::encrypt(secret, encryption_key)?;
-        queries::store_oauth_client_secret(pool, &server_id, &encrypted, DEFAULT_ENCRYPTION_KEY_ID)
-            .await?;
-    }
+    // Store encrypted API key and OAuth client secret if provided
+    sharing::store_secrets(&mut tx, &server_id, api_key, client_secret, encryption_key).await?;
+    tx.commit().await?;
 
     // Audit
     let transport_str = match &tp {
@@ -678,27 +857,23 @@ pub async fn create_built_in_server(
         "transport": transport_str,
         "domain": domain,
     });
-    if let Err(e) = queries::insert_audit_log(
-        pool,
+    audit.record(AuditEntry::new(
         admin_id,
         Some(&server_id),
         name,
         "created",
-        Some(&details),
-    )
-    .await
-    {
-        error!("Failed to write audit log: {e}");
-    }
+        details,
+    ));
 
     info!(server_id = %server_id, "Created built-in MCP server: {name}");
-    to_admin_view(pool, &server).await
+    admin_view(pool, &server).await
 }
 
 /// Update a built-in server (admin).
 #[allow(clippy::too_many_arguments)]
 pub async fn update_built_in_server(
     pool: &PgPool,
+    audit: &AuditLog,
     admin_id: &str,
     server_id: &str,
     name: Option<&str>,
@@ -706,6 +881,8 @@ pub async fn update_built_in_server(
     domain: Option<&str>,
     visibility: Option<&str>,
     enabled: Option<bool>,
+    keep_warm: Option<bool>,
+    forwarded_context: Option<&[String]>,
     config: Option<&ServerConfig>,
     api_key: Option<&str>,
     oauth_config: Option<&OAuthConfig>,
@@ -713,21 +890,23 @@ pub async fn update_built_in_server(
     encryption_key: &str,
 ) -> Result<AdminServerView, McpError> {
     // Verify server exists and is not user-owned
-    let existing = queries::get_server(pool, server_id)
-        .await?
-        .ok_or_else(|| McpError::NotFound(format!("Server {server_id} not found")))?;
+    let existing = queries::get_server(pool, server_id).await?.ok_or_else(|| {
+        McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id))
+    })?;
 
     if existing.visibility == VisibilityTier::User {
-        return Err(McpError::Forbidden(
-            "Cannot admin-edit a user-owned server".into(),
-        ));
+        return Err(McpError::Forbidden(Message::new(
+            "mcp.admin_edit_forbidden",
+        )));
     }
 
     let vis = visibility
         .map(|v| match v {
             "hidden" => Ok(VisibilityTier::Hidden),
             "visible" => Ok(VisibilityTier::Visible),
-            _ => Err(McpError::Validation(format!("Invalid visibility: {v}"))),
+            _ => Err(McpError::Validation(
+                Message::new("mcp.invalid_visibility").arg("visibility", v),
+            )),
         })
         .transpose()?;
 
@@ -741,6 +920,12 @@ pub async fn update_built_in_server(
     if let Some(ServerConfig::ManagedSse(m) | ServerConfig::ManagedHttp(m)) = config {
         validate_managed_config(m)?;
     }
+    if let Some(config) = config {
+        validate_sandbox(config)?;
+    }
+    if let Some(keys) = forwarded_context {
+        context::validate_keys(keys)?;
+    }
 
     // Build config JSON from provided config or leave unchanged
     let config_json = config.map(|c| serde_json::to_value(c).unwrap());
@@ -750,10 +935,14 @@ pub async fn update_built_in_server(
     let oauth_config_json = oauth_config
         .map(serde_json::to_value)
         .transpose()
-        .map_err(|e| McpError::Validation(format!("Failed to serialize oauth_config: {e}")))?;
-
-    let server = queries::update_server(
-        pool,
+        .map_err(|e| {
+            McpError::Validation(Message::new("mcp.oauth_config_serialize_failed").arg("error", e))
+        })?;
+
+    // Apply the update, secrets and token revocation atomically
+    let mut tx = pool.begin().await?;
+    let mut server = queries::update_server(
+        &mut *tx,
         server_id,
         name,
         description,
@@ -767,19 +956,16 @@ pub async fn update_built_in_server(
     )
     .await?;
 
-    // Store encrypted API key if provided
-    if let Some(key) = api_key {
-        let encrypted = nize_core::mcp::secrets::encrypt(key, encryption_key)?;
-        queries::store_api_key(pool, server_id, &encrypted, DEFAULT_ENCRYPTION_KEY_ID).await?;
+    if let Some(keep_warm) = keep_warm {
+        server = queries::set_server_keep_warm(&mut *tx, server_id, keep_warm).await?;
     }
-
-    // Store encrypted OAuth client secret if provided
-    if let Some(secret) = client_secret {
-        let encrypted = nize_core::mcp::secrets::encrypt(secret, encryption_key)?;
-        queries::store_oauth_client_secret(pool, server_id, &encrypted, DEFAULT_ENCRYPTION_KEY_ID)
-            .await?;
+    if let Some(keys) = forwarded_context {
+        server = queries::set_server_forwarded_context(&mut *tx, server_id, keys).await?;
     }
 
+    // Store encrypted API key and OAuth client secret if provided
+    sharing::store_secrets(&mut tx, server_id, api_key, client_secret, encryption_key).await?;
+
     // Invalidate all user OAuth tokens when OAuth config actually changes
     let oauth_config_changed = match (oauth_config, &existing.oauth_config) {
         (Some(new_cfg), Some(existing_json)) => {
@@ -792,44 +978,41 @@ pub async fn update_built_in_server(
         (None, _) => false,      // No new config provided
     };
     if oauth_config_changed || client_secret.is_some() {
-        let revoked = queries::delete_all_oauth_tokens_for_server(pool, server_id).await?;
+        let revoked = queries::delete_all_oauth_tokens_for_server(&mut *tx, server_id).await?;
         if revoked > 0 {
             info!(server_id = %server_id, revoked = revoked, "Revoked OAuth tokens after config change");
         }
     }
+    tx.commit().await?;
 
     // Audit
     let details = serde_json::json!({ "action": "admin_update" });
-    if let Err(e) = queries::insert_audit_log(
-        pool,
+    audit.record(AuditEntry::new(
         admin_id,
         Some(server_id),
         &server.name,
         "updated",
-        Some(&details),
-    )
-    .await
-    {
-        error!("Failed to write audit log: {e}");
-    }
+        details,
+    ));
 
-    to_admin_view(pool, &server).await
+    admin_view(pool, &server).await
 }
 
 /// Delete a built-in server (admin).
 pub async fn delete_built_in_server(
     pool: &PgPool,
+    audit: &AuditLog,
     admin_id: &str,
     server_id: &str,
 ) -> Result<DeleteResult, McpError> {
-    let existing = queries::get_server(pool, server_id)
-        .await?
-        .ok_or_else(|| McpError::NotFound(format!("Server {server_id} not found")))?;
+    let existing = queries::get_server(pool, server_id).await?.ok_or_else(|| {
+        McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id))
+    })?;
 
     if existing.visibility == VisibilityTier::User {
-        return Err(McpError::Forbidden(
-            "Cannot admin-delete a user-owned server. The owner must delete it.".into(),
-        ));
+        return Err(McpError::Forbidden(Message::new(
+            "mcp.admin_delete_forbidden",
+        )));
     }
 
     let affected_users = queries::get_user_preference_count(pool, server_id).await?;
@@ -839,11 +1022,7 @@ pub async fn delete_built_in_server(
 
     // Audit
     let details = serde_json::json!({ "action": "admin_delete", "affectedUsers": affected_users });
-    if let Err(e) =
-        queries::insert_audit_log(pool, admin_id, None, &name, "deleted", Some(&details)).await
-    {
-        error!("Failed to write audit log: {e}");
-    }
+    audit.record(AuditEntry::new(admin_id, None, &name, "deleted", details));
 
     info!(server_id = %server_id, "Admin deleted MCP server: {name}");
 
@@ -860,6 +1039,114 @@ pub async fn delete_built_in_server(
     })
 }
 
+// =============================================================================
+// Catalog
+// =============================================================================
+
+/// Load a catalog entry and fill in its secrets.
+async fn instantiate_catalog_entry(
+    pool: &PgPool,
+    slug: &str,
+    secrets: &HashMap<String, String>,
+) -> Result<(CatalogEntry, InstantiatedServer), McpError> {
+    let entry = catalog::get_entry(pool, slug)
+        .await?
+        .ok_or_else(|| {
+            McpError::NotFound(Message::new("mcp.catalog_slug_not_found").arg("slug", slug))
+        })?
+        .entry;
+    let server = catalog::instantiate(&entry, secrets)?;
+    Ok((entry, server))
+}
+
+/// Create a user server from a catalog entry. `name` and `domain` default
+/// to the entry's.
+#[allow(clippy::too_many_arguments)]
+pub async fn install_catalog_entry_for_user(
+    pool: &PgPool,
+    audit: &AuditLog,
+    user_id: &str,
+    slug: &str,
+    name: Option<&str>,
+    domain: Option<&str>,
+    secrets: &HashMap<String, String>,
+    encryption_key: &str,
+) -> Result<UserServerView, McpError> {
+    let (entry, server) = instantiate_catalog_entry(pool, slug, secrets).await?;
+    let (transport, url, headers, auth_type, api_key_header) = match &server.config {
+        ServerConfig::Http(c) => (
+            TransportType::Http,
+            &c.url,
+            c.headers.as_ref(),
+            &c.auth_type,
+            c.api_key_header.as_deref(),
+        ),
+        ServerConfig::Sse(c) => (
+            TransportType::Sse,
+            &c.url,
+            c.headers.as_ref(),
+            &c.auth_type,
+            c.api_key_header.as_deref(),
+        ),
+        _ => {
+            return Err(McpError::Forbidden(
+                Message::new("mcp.install_admin_only").arg("name", &entry.name),
+            ));
+        }
+    };
+    create_user_server(
+        pool,
+        audit,
+        user_id,
+        name.unwrap_or(&entry.name),
+        &entry.description,
+        domain.unwrap_or(&entry.domain),
+        url,
+        &transport,
+        auth_type,
+        server.api_key.as_deref(),
+        api_key_header,
+        headers,
+        entry.oauth_config.as_ref(),
+        server.client_secret.as_deref(),
+        encryption_key,
+    )
+    .await
+}
+
+/// Create a built-in server from a catalog entry. Returns the instantiated
+/// config too, so the caller can discover its tools.
+#[allow(clippy::too_many_arguments)]
+pub async fn install_catalog_entry_as_admin(
+    pool: &PgPool,
+    audit: &AuditLog,
+    admin_id: &str,
+    slug: &str,
+    name: Option<&str>,
+    domain: Option<&str>,
+    visibility: &str,
+    secrets: &HashMap<String, String>,
+    encryption_key: &str,
+) -> Result<(AdminServerView, InstantiatedServer), McpError> {
+    let (entry, server) = instantiate_catalog_entry(pool, slug, secrets).await?;
+    let view = create_built_in_server(
+        pool,
+        audit,
+        admin_id,
+        name.unwrap_or(&entry.name),
+        &entry.description,
+        domain.unwrap_or(&entry.domain),
+        visibility,
+        &server.config,
+        server.api_key.as_deref(),
+        entry.oauth_config.as_ref(),
+        server.client_secret.as_deref(),
+        encryption_key,
+    )
+    .await?;
+    Ok((view, server))
+}
+
 // =============================================================================
 // Connection testing
 // =============================================================================
@@ -908,5 +1195,239 @@ pub async fn store_tools_from_test(
     server_id: &str,
     tools: &[McpToolSummary],
 ) -> Result<(), McpError> {
-    queries::replace_server_tools(pool, server_id, tools).await
+    let mut tx = pool.begin().await?;
+    queries::replace_server_tools(&mut tx, server_id, tools).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Store discovered tools and mark the server available in one transaction,
+/// so a server is never left available with a stale or partial tool list.
+pub async fn save_discovered_tools(
+    pool: &PgPool,
+    server_id: &str,
+    tools: &[McpToolSummary],
+) -> Result<(), McpError> {
+    let mut tx = pool.begin().await?;
+    save_tools_in(&mut tx, server_id, tools).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn save_tools_in(
+    conn: &mut sqlx::PgConnection,
+    server_id: &str,
+    tools: &[McpToolSummary],
+) -> Result<(), McpError> {
+    queries::replace_server_tools(conn, server_id, tools).await?;
+    queries::update_server(
+        &mut *conn,
+        server_id,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(true),
+        None,
+    )
+    .await?;
+    Ok(())
+}
+
+// =============================================================================
+// Tool discovery
+// =============================================================================
+
+/// Mark tool discovery as running for a server. The returned status is
+/// reported in the admin view until [`discover_tools`] finishes.
+pub async fn start_discovery(pool: &PgPool, server_id: &str) -> Result<McpDiscoveryRow, McpError> {
+    queries::start_discovery(pool, server_id).await
+}
+
+/// Connect to a server, store its tools and mark it available, recording
+/// the outcome as the server's discovery status. Returns the tool count.
+///
+/// Tools, availability and the final status are written in one transaction;
+/// embeddings are generated afterwards and failures there are only logged.
+#[allow(clippy::too_many_arguments)]
+pub async fn discover_tools(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+    server_id: &str,
+    config: &ServerConfig,
+    api_key: Option<&str>,
+    oauth_headers: Option<&OAuthHeaders>,
+) -> Result<usize, McpError> {
+    let result = test_connection(config, api_key, oauth_headers).await;
+    if !result.success {
+        let message = result
+            .error
+            .unwrap_or_else(|| "Connection failed".to_string());
+        queries::finish_discovery(pool, server_id, DISCOVERY_FAILED, 0, Some(&message)).await?;
+        return Err(McpError::ConnectionFailed(message));
+    }
+
+    let count = result.tools.len();
+    let saved: Result<(), McpError> = async {
+        let mut tx = pool.begin().await?;
+        save_tools_in(&mut tx, server_id, &result.tools).await?;
+        queries::finish_discovery(&mut *tx, server_id, DISCOVERY_SUCCEEDED, count as i32, None)
+            .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    .await;
+    if let Err(e) = saved {
+        let message = e.to_string();
+        if let Err(e) =
+            queries::finish_discovery(pool, server_id, DISCOVERY_FAILED, 0, Some(&message)).await
+        {
+            warn!("Failed to record discovery failure for server {server_id}: {e}");
+        }
+        return Err(e);
+    }
+
+    if let Err(e) = nize_core::embedding::indexer::embed_server_tools(
+        pool,
+        config_cache,
+        server_id,
+        encryption_key,
+    )
+    .await
+    {
+        warn!("Failed to embed tools for server {server_id}: {e}");
+    }
+
+    Ok(count)
+}
+
+// =============================================================================
+// Bulk connection testing
+// =============================================================================
+
+/// Servers tested at the same time by [`test_all_servers`].
+const TEST_ALL_CONCURRENCY: usize = 4;
+
+/// Outcome of testing one server with [`test_all_servers`].
+#[derive(Debug, Clone, serde::Serialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerTestSummary {
+    pub server_id: String,
+    pub name: String,
+    pub success: bool,
+    /// Availability before the test.
+    pub was_available: bool,
+    pub tool_count: usize,
+    pub error: Option<String>,
+    /// The server uses OAuth and the admin has no usable tokens for it, so
+    /// it was not tested and its availability is unchanged.
+    pub auth_required: bool,
+}
+
+/// Test every registered server, a few at a time, as `admin_id`.
+///
+/// Reachable servers get their tool lists refreshed and are marked
+/// available; unreachable ones are marked unavailable. Results are sorted
+/// by server name.
+pub async fn test_all_servers(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+    admin_id: &str,
+) -> Result<Vec<ServerTestSummary>, McpError> {
+    let servers = queries::list_all_servers(pool).await?;
+    let mut results: Vec<ServerTestSummary> = stream::iter(servers)
+        .map(|server| test_server(pool, config_cache, encryption_key, admin_id, server))
+        .buffer_unordered(TEST_ALL_CONCURRENCY)
+        .collect()
+        .await;
+    results.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(results)
+}
+
+async fn test_server(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+    admin_id: &str,
+    server: McpServerRow,
+) -> ServerTestSummary {
+    let server_id = server.id.to_string();
+    let mut summary = ServerTestSummary {
+        server_id: server_id.clone(),
+        name: server.name.clone(),
+        success: false,
+        was_available: server.available,
+        tool_count: 0,
+        error: None,
+        auth_required: false,
+    };
+
+    let oauth_headers =
+        match execution::resolve_oauth_headers(pool, admin_id, server.id, encryption_key).await {
+            Ok(headers) => headers,
+            Err(e) => {
+                summary.auth_required = true;
+                summary.error = Some(e.to_string());
+                return summary;
+            }
+        };
+
+    let outcome: Result<usize, McpError> = async {
+        let config = server
+            .config
+            .ok_or_else(|| McpError::Validation(Message::new("mcp.server_config_missing")))
+            .and_then(|c| {
+                serde_json::from_value::<ServerConfig>(c).map_err(|e| {
+                    McpError::Validation(Message::new("mcp.invalid_server_config").arg("error", e))
+                })
+            })?;
+        let api_key = sharing::load_api_key(pool, &server_id, encryption_key).await?;
+        start_discovery(pool, &server_id).await?;
+        discover_tools(
+            pool,
+            config_cache,
+            encryption_key,
+            &server_id,
+            &config,
+            api_key.as_deref(),
+            oauth_headers.as_ref(),
+        )
+        .await
+    }
+    .await;
+
+    match outcome {
+        Ok(count) => {
+            summary.success = true;
+            summary.tool_count = count;
+        }
+        Err(e) => {
+            warn!("Connection test for server {server_id} failed: {e}");
+            summary.error = Some(e.to_string());
+            if server.available
+                && let Err(e) = queries::update_server(
+                    pool,
+                    &server_id,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(false),
+                    None,
+                )
+                .await
+            {
+                warn!("Failed to mark server {server_id} unavailable: {e}");
+            }
+        }
+    }
+    summary
 }
@@ -1,6 +1,18 @@
 //! Auth service modules.
 
+pub mod announcements;
 pub mod auth;
 pub mod config;
+pub mod connector_sync;
 pub mod cookies;
+pub mod documents;
+pub mod email_verification;
+pub mod eval_runner;
+pub mod local_mode;
+pub mod mcp_client_config;
 pub mod mcp_config;
+pub mod moderation;
+pub mod security_activity;
+pub mod storage;
+pub mod task_scheduler;
+pub mod telemetry;
@@ -0,0 +1,76 @@
+//! Content moderation hook for chat and ingest.
+//!
+//! Callers fetch the [`Moderator`] for a source with [`moderator`] (`None`
+//! when those checks are off) and run content through [`apply`], which
+//! stores a result for admin review whenever something matched, redacts
+//! in place, and turns a block into [`AppError::ContentBlocked`].
+
+use uuid::Uuid;
+
+use nize_core::moderation::{self, Action, ContentBlocked, Moderator, Source};
+
+use crate::AppState;
+use crate::error::{AppError, AppResult};
+
+/// The moderator for `source`, or `None` when its checks are off.
+pub async fn moderator(state: &AppState, source: Source) -> AppResult<Option<Moderator>> {
+    Ok(Moderator::configured(
+        &state.pool,
+        &state.config_cache,
+        &state.config.mcp_encryption_key,
+        source,
+    )
+    .await?)
+}
+
+/// Who and what a piece of checked content belongs to.
+#[derive(Debug, Clone, Copy)]
+pub struct Subject<'a> {
+    pub source: Source,
+    pub user_id: Option<&'a Uuid>,
+    /// The document, for document checks.
+    pub resource_id: Option<&'a Uuid>,
+}
+
+/// Check `parts`, joined by `separator`, and act on the verdict. Returns
+/// the action taken (`None` when clean); redact-rule matches are masked in
+/// `parts`.
+pub async fn apply(
+    state: &AppState,
+    moderator: &Moderator,
+    subject: Subject<'_>,
+    parts: &mut [&mut String],
+    separator: &str,
+) -> AppResult<Option<Action>> {
+    let text = parts
+        .iter()
+        .map(|part| part.as_str())
+        .collect::<Vec<_>>()
+        .join(separator);
+    let verdict = moderator.check(&text).await;
+    let Some(result_id) = moderation::record(
+        &state.pool,
+        subject.user_id,
+        subject.source,
+        &verdict,
+        &text,
+        subject.resource_id,
+    )
+    .await?
+    else {
+        return Ok(None);
+    };
+    let action = verdict.action();
+    match action {
+        Some(Action::Block) => Err(AppError::ContentBlocked(ContentBlocked {
+            content_source: subject.source,
+            result_id,
+            labels: verdict.blocking_labels(),
+        })),
+        Some(Action::Redact) => {
+            moderator.redact(parts, separator);
+            Ok(action)
+        }
+        _ => Ok(action),
+    }
+}
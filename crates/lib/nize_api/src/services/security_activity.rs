@@ -0,0 +1,101 @@
+//! Security activity recording — who did what from where, for the user's
+//! security activity page (`nize_core::auth::activity`).
+
+use std::net::SocketAddr;
+
+use axum::http::{HeaderMap, header};
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use nize_core::auth::activity::{self, ClientInfo};
+
+/// Header carrying the client address when behind a reverse proxy.
+const FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Describe the client of a request.
+///
+/// The IP address is the peer's, unless the peer is a loopback reverse
+/// proxy, in which case the first `X-Forwarded-For` hop is used. Forwarded
+/// headers from any other peer are ignored, as anyone can set them.
+pub fn client_info(headers: &HeaderMap, peer: Option<SocketAddr>) -> ClientInfo {
+    let forwarded = || {
+        headers
+            .get(FORWARDED_FOR)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let ip_address = match peer {
+        Some(addr) if addr.ip().is_loopback() => {
+            forwarded().or_else(|| Some(addr.ip().to_string()))
+        }
+        Some(addr) => Some(addr.ip().to_string()),
+        None => None,
+    };
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    ClientInfo {
+        ip_address,
+        user_agent,
+    }
+}
+
+/// Record an event for a user. Failures are logged, not returned: the
+/// action the event describes has already happened.
+pub async fn record(
+    pool: &PgPool,
+    user_id: &str,
+    kind: &str,
+    client: &ClientInfo,
+    details: serde_json::Value,
+) {
+    let Ok(user_id) = Uuid::parse_str(user_id) else {
+        warn!("Not recording {kind} activity for invalid user ID {user_id}");
+        return;
+    };
+    if let Err(e) = activity::record(pool, &user_id, kind, client, &details).await {
+        warn!("Failed to record {kind} activity for {user_id}: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, value.parse().unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn client_info_uses_peer_address() {
+        let h = headers(&[("user-agent", "curl/8.0"), ("x-forwarded-for", "1.2.3.4")]);
+        let info = client_info(&h, Some("203.0.113.9:5000".parse().unwrap()));
+        assert_eq!(info.ip_address.as_deref(), Some("203.0.113.9"));
+        assert_eq!(info.user_agent.as_deref(), Some("curl/8.0"));
+    }
+
+    #[test]
+    fn client_info_trusts_forwarded_for_from_loopback() {
+        let h = headers(&[("x-forwarded-for", "198.51.100.7, 10.0.0.1")]);
+        let info = client_info(&h, Some("127.0.0.1:5000".parse().unwrap()));
+        assert_eq!(info.ip_address.as_deref(), Some("198.51.100.7"));
+
+        let info = client_info(&HeaderMap::new(), Some("127.0.0.1:5000".parse().unwrap()));
+        assert_eq!(info.ip_address.as_deref(), Some("127.0.0.1"));
+    }
+
+    #[test]
+    fn client_info_without_peer() {
+        let info = client_info(&headers(&[("x-forwarded-for", "1.2.3.4")]), None);
+        assert_eq!(info, ClientInfo::default());
+    }
+}
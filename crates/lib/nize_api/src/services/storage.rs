@@ -0,0 +1,121 @@
+//! Storage monitoring.
+//!
+//! Every [`TICK`] the monitor measures storage (see [`nize_core::storage`])
+//! against the `system.storage.*` soft limits. When the level rises to
+//! warning or critical, every user with `config.write` gets a notification
+//! and a `storage.warning` event; falling back below a limit re-arms the
+//! alert. The level is kept in memory, so a server restarted above a limit
+//! alerts once more.
+
+use std::time::Duration;
+
+use tracing::{debug, error, warn};
+
+use nize_core::auth::rbac;
+use nize_core::notifications;
+use nize_core::storage::{
+    self, MaintenanceReport, MaintenanceRequest, StorageLevel, StorageReport,
+};
+
+use crate::AppState;
+use crate::error::AppResult;
+use crate::events::{KIND_STORAGE_WARNING, ServerEvent};
+
+/// How often the monitor measures storage.
+const TICK: Duration = Duration::from_secs(15 * 60);
+
+/// Measure storage now.
+pub async fn measure(state: &AppState) -> AppResult<StorageReport> {
+    Ok(storage::measure(
+        &state.pool,
+        &state.config_cache,
+        state.config.data_dir.as_deref(),
+    )
+    .await?)
+}
+
+/// Run maintenance operations.
+pub async fn maintain(
+    state: &AppState,
+    request: &MaintenanceRequest,
+) -> AppResult<MaintenanceReport> {
+    let report = storage::run_maintenance(
+        &state.pool,
+        &state.config_cache,
+        &state.config.mcp_encryption_key,
+        request,
+    )
+    .await?;
+    debug!(?report, "storage maintenance finished");
+    Ok(report)
+}
+
+/// Spawn the periodic storage monitor.
+pub fn spawn_storage_monitor(state: AppState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+        let mut level = StorageLevel::Ok;
+        loop {
+            interval.tick().await;
+            match measure(&state).await {
+                Ok(report) => {
+                    if report.level > level {
+                        alert(&state, &report).await;
+                    }
+                    level = report.level;
+                }
+                Err(e) => warn!(error = %e, "storage measurement failed"),
+            }
+        }
+    })
+}
+
+/// Notify storage administrators that a limit was crossed.
+async fn alert(state: &AppState, report: &StorageReport) {
+    warn!(
+        total_bytes = report.total_bytes,
+        level = ?report.level,
+        "storage above configured limit"
+    );
+    let admins = match rbac::users_with_permission(&state.pool, rbac::PERM_CONFIG_WRITE).await {
+        Ok(admins) => admins,
+        Err(e) => {
+            error!(error = %e, "failed to list storage administrators");
+            return;
+        }
+    };
+    let title = match report.level {
+        StorageLevel::Critical => "Storage critically full",
+        _ => "Storage running full",
+    };
+    let body = format!(
+        "The database uses {} MiB. Run storage maintenance or raise the limit.",
+        report.total_bytes / (1024 * 1024)
+    );
+    let payload = serde_json::json!({
+        "level": report.level,
+        "totalBytes": report.total_bytes,
+        "thresholds": report.thresholds,
+    });
+    for user_id in admins {
+        state.events.publish(ServerEvent {
+            user_id: Some(user_id),
+            kind: KIND_STORAGE_WARNING.into(),
+            title: title.into(),
+            body: body.clone(),
+            payload: payload.clone(),
+        });
+        if let Err(e) = notifications::create_notification(
+            &state.pool,
+            &user_id,
+            KIND_STORAGE_WARNING,
+            title,
+            &body,
+            &payload,
+        )
+        .await
+        {
+            error!(user_id = %user_id, error = %e, "failed to create storage notification");
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Background scheduler for assistant tasks.
+//!
+//! Every [`TICK`] the scheduler claims due tasks and runs each prompt through
+//! the chat app (`POST {chat_url}/api/chat/tasks/run`), authenticated with a
+//! short-lived access token for the task owner. The outcome is recorded on
+//! the task, reported to the owner as a notification and published on the
+//! event bus.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use tracing::{debug, error, warn};
+
+use nize_core::notifications::{self, KIND_TASK_COMPLETED, KIND_TASK_FAILED};
+use nize_core::tasks::{self, TaskRow};
+
+use crate::AppState;
+use crate::events::ServerEvent;
+use crate::services::auth::generate_access_token;
+use crate::services::cookies::ACCESS_COOKIE;
+
+/// How often due tasks are claimed.
+const TICK: Duration = Duration::from_secs(30);
+
+/// Maximum tasks claimed per tick.
+const BATCH_SIZE: i64 = 10;
+
+/// Upper bound on a single run. Kept below the access token lifetime.
+const RUN_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Longest reply excerpt included in a notification body.
+const NOTIFICATION_BODY_CHARS: usize = 280;
+
+/// Response from the chat app's task endpoint.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TaskRunResponse {
+    conversation_id: uuid::Uuid,
+    content: String,
+}
+
+/// Spawn the periodic task scheduler.
+pub fn spawn_task_scheduler(state: AppState, chat_url: String) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = reqwest::Client::builder()
+            .timeout(RUN_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            let due =
+                match tasks::claim_due_tasks(&state.pool, chrono::Utc::now(), BATCH_SIZE).await {
+                    Ok(due) => due,
+                    Err(e) => {
+                        warn!(error = %e, "failed to claim due tasks");
+                        continue;
+                    }
+                };
+            for task in due {
+                let state = state.clone();
+                let client = client.clone();
+                let chat_url = chat_url.clone();
+                tokio::spawn(async move {
+                    run_and_record(&state, &client, &chat_url, task).await;
+                });
+            }
+        }
+    })
+}
+
+/// Run a claimed task and record its outcome.
+async fn run_and_record(state: &AppState, client: &reqwest::Client, chat_url: &str, task: TaskRow) {
+    debug!(task_id = %task.id, "running task");
+    let outcome = run_task(state, client, chat_url, &task).await;
+
+    let (conversation_id, error) = match &outcome {
+        Ok(run) => (Some(run.conversation_id), None),
+        Err(e) => (task.conversation_id, Some(e.as_str())),
+    };
+    if let Err(e) =
+        tasks::finish_task_run(&state.pool, &task.id, conversation_id.as_ref(), error).await
+    {
+        error!(task_id = %task.id, error = %e, "failed to record task run");
+    }
+
+    let (kind, body) = match &outcome {
+        Ok(run) => (KIND_TASK_COMPLETED, excerpt(&run.content)),
+        Err(e) => {
+            warn!(task_id = %task.id, error = %e, "task run failed");
+            (KIND_TASK_FAILED, excerpt(e))
+        }
+    };
+    let payload = serde_json::json!({
+        "taskId": task.id,
+        "conversationId": conversation_id,
+    });
+    state.events.publish(ServerEvent {
+        user_id: Some(task.user_id),
+        kind: kind.to_string(),
+        title: task.name.clone(),
+        body: body.clone(),
+        payload: payload.clone(),
+    });
+    if let Err(e) = notifications::create_notification(
+        &state.pool,
+        &task.user_id,
+        kind,
+        &task.name,
+        &body,
+        &payload,
+    )
+    .await
+    {
+        error!(task_id = %task.id, error = %e, "failed to create task notification");
+    }
+}
+
+/// Call the chat app for one task run.
+async fn run_task(
+    state: &AppState,
+    client: &reqwest::Client,
+    chat_url: &str,
+    task: &TaskRow,
+) -> Result<TaskRunResponse, String> {
+    let user_id = task.user_id.to_string();
+    let user = nize_core::auth::queries::get_user_by_id(&state.pool, &user_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Task owner no longer exists".to_string())?;
+    let roles = nize_core::auth::queries::get_user_roles(&state.pool, &user_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let token = generate_access_token(&user_id, &user.email, &roles, &state.jwt_keys)
+        .map_err(|e| e.to_string())?;
+
+    let response = client
+        .post(format!("{chat_url}/api/chat/tasks/run"))
+        .header(reqwest::header::COOKIE, format!("{ACCESS_COOKIE}={token}"))
+        .json(&serde_json::json!({
+            "taskId": task.id,
+            "name": task.name,
+            "prompt": task.prompt,
+            "conversationId": task.conversation_id,
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Chat app unreachable: {e}"))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Chat app returned {status}: {body}"));
+    }
+    response
+        .json::<TaskRunResponse>()
+        .await
+        .map_err(|e| format!("Invalid chat app response: {e}"))
+}
+
+/// First [`NOTIFICATION_BODY_CHARS`] characters of `text`.
+fn excerpt(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(NOTIFICATION_BODY_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn excerpt_truncates_on_char_boundaries() {
+        assert_eq!(excerpt("  short  "), "short");
+        let long = "é".repeat(NOTIFICATION_BODY_CHARS + 5);
+        let cut = excerpt(&long);
+        assert_eq!(cut.chars().count(), NOTIFICATION_BODY_CHARS + 1);
+        assert!(cut.ends_with('…'));
+    }
+}
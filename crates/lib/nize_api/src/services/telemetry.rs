@@ -0,0 +1,119 @@
+//! Opt-in anonymous usage reporting.
+//!
+//! Request counts are aggregated in [`AppState::telemetry`] by the metrics
+//! middleware. Every [`TICK`] the reporter checks `system.telemetry.*`; when
+//! enabled with an endpoint and the interval has elapsed since the last
+//! accepted report, it submits the report and clears the submitted counts.
+//! `GET /admin/telemetry/preview` returns exactly what would be sent.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use schemars::JsonSchema;
+use serde::Serialize;
+use tracing::{debug, warn};
+
+use nize_core::config::resolver;
+use nize_core::telemetry::{self, Report};
+
+use crate::AppState;
+use crate::error::AppResult;
+
+/// How often the reporter checks whether a report is due.
+const TICK: Duration = Duration::from_secs(60 * 60);
+
+/// Upper bound on a single submission.
+const SUBMIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Effective telemetry settings.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetrySettings {
+    pub enabled: bool,
+    /// `None` when unset; nothing is sent without an endpoint.
+    pub endpoint: Option<String>,
+    pub interval_hours: i64,
+}
+
+impl TelemetrySettings {
+    /// Whether reports are actually submitted.
+    pub fn active(&self) -> bool {
+        self.enabled && self.endpoint.is_some()
+    }
+}
+
+/// What the reporter would submit now.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryPreview {
+    #[serde(flatten)]
+    pub settings: TelemetrySettings,
+    pub last_submitted_at: Option<chrono::DateTime<Utc>>,
+    pub report: Report,
+}
+
+/// Read the telemetry settings from system config.
+pub async fn settings(state: &AppState) -> AppResult<TelemetrySettings> {
+    let pool = &state.pool;
+    let cache = &state.config_cache;
+    let enabled = resolver::get_system_value(pool, cache, telemetry::ENABLED_CONFIG_KEY).await?;
+    let endpoint = resolver::get_system_value(pool, cache, telemetry::ENDPOINT_CONFIG_KEY).await?;
+    let interval = resolver::get_system_value(pool, cache, telemetry::INTERVAL_CONFIG_KEY).await?;
+    Ok(TelemetrySettings {
+        enabled: enabled == "true",
+        endpoint: Some(endpoint.trim().to_string()).filter(|e| !e.is_empty()),
+        interval_hours: interval.parse::<i64>().unwrap_or(24).max(1),
+    })
+}
+
+/// Build the report the reporter would submit now, with the settings that
+/// govern it.
+pub async fn preview(state: &AppState) -> AppResult<TelemetryPreview> {
+    let settings = settings(state).await?;
+    let persisted = telemetry::get_state(&state.pool).await?;
+    Ok(TelemetryPreview {
+        settings,
+        last_submitted_at: persisted.last_submitted_at,
+        report: state.telemetry.report(persisted.install_id),
+    })
+}
+
+/// Spawn the periodic reporter. It does nothing while telemetry is disabled.
+pub fn spawn_telemetry_reporter(state: AppState) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = reqwest::Client::builder()
+            .timeout(SUBMIT_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            if let Err(e) = report_if_due(&state, &client).await {
+                warn!(error = %e, "telemetry report failed");
+            }
+        }
+    })
+}
+
+/// Submit a report when telemetry is active and the interval has elapsed.
+async fn report_if_due(state: &AppState, client: &reqwest::Client) -> AppResult<()> {
+    let settings = settings(state).await?;
+    let Some(endpoint) = settings.endpoint.as_deref().filter(|_| settings.enabled) else {
+        return Ok(());
+    };
+    let persisted = telemetry::get_state(&state.pool).await?;
+    let now = Utc::now();
+    if persisted
+        .last_submitted_at
+        .is_some_and(|at| now - at < chrono::Duration::hours(settings.interval_hours))
+    {
+        return Ok(());
+    }
+
+    let report = state.telemetry.report(persisted.install_id);
+    telemetry::submit(client, endpoint, &report).await?;
+    state.telemetry.subtract(&report);
+    telemetry::mark_submitted(&state.pool, report.period_end).await?;
+    debug!(endpoint, "telemetry report submitted");
+    Ok(())
+}
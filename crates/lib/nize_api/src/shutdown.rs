@@ -0,0 +1,60 @@
+//! Shutdown requests for the server binaries.
+//!
+//! A sidecar stops when its parent closes the stdin pipe, which also
+//! happens when the parent dies (even via SIGKILL). With warm-start state
+//! to save (see [`nize_core::warm_start`]) the binaries also stop on
+//! SIGTERM and Ctrl-C instead of being killed outright, so the state can be
+//! written first.
+
+use tracing::info;
+
+/// Wait until the process is asked to stop: for a `sidecar` when the parent
+/// pipe closes, with `signals` on SIGTERM or Ctrl-C.
+pub async fn requested(sidecar: bool, signals: bool) {
+    let parent_gone = async {
+        if sidecar {
+            use tokio::io::AsyncReadExt;
+            let mut buf = [0u8; 1];
+            // Blocks until the parent dies and the OS closes the pipe → EOF.
+            let _ = tokio::io::stdin().read(&mut buf).await;
+        } else {
+            std::future::pending::<()>().await;
+        }
+    };
+    let signalled = async {
+        if signals {
+            terminate().await;
+        } else {
+            std::future::pending::<()>().await;
+        }
+    };
+    tokio::select! {
+        () = parent_gone => info!("parent pipe closed, shutting down"),
+        () = signalled => info!("shutdown signal received, shutting down"),
+    }
+}
+
+/// SIGTERM or Ctrl-C. Signals that cannot be listened for never arrive.
+async fn terminate() {
+    let ctrl_c = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let sigterm = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let sigterm = std::future::pending::<()>();
+    tokio::select! {
+        () = ctrl_c => {}
+        () = sigterm => {}
+    }
+}
@@ -0,0 +1,318 @@
+//! Buffered streams for clients that cannot hold a streaming connection.
+//!
+//! Some proxies buffer or cut `text/event-stream` responses. Such clients
+//! start a stream that the server fills in the background — a chat
+//! completion through `POST /ai-proxy?transport=poll`, or their server
+//! events through `POST /events/streams` — and then long-poll
+//! `GET /chat/streams/{id}/poll?after=<seq>` for the chunks they have not
+//! seen yet. Chunks carry increasing sequence numbers starting at 1; a poll
+//! acknowledges every chunk up to `after`, which is then dropped.
+//!
+//! Streams live in memory only. One that is not polled for
+//! [`IDLE_TIMEOUT`] is dropped and its producer stops; a finished stream is
+//! kept for [`IDLE_TIMEOUT`] after it ended so the client can fetch the
+//! tail.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+/// How long a stream survives without being polled.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Longest a poll waits for new chunks.
+pub const MAX_WAIT: Duration = Duration::from_secs(30);
+
+/// Most streams one user may have open at once.
+const MAX_STREAMS_PER_USER: usize = 16;
+
+/// A piece of stream output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Chunk {
+    pub seq: u64,
+    /// Event name, for streams of named events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    pub data: String,
+}
+
+/// Result of a poll.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Poll {
+    /// Chunks after the requested sequence number, oldest first.
+    pub chunks: Vec<Chunk>,
+    /// The stream has ended; no chunks follow the ones returned.
+    pub done: bool,
+    /// Why the stream ended early, if it failed.
+    pub error: Option<String>,
+}
+
+/// Errors from stream operations.
+#[derive(Debug, thiserror::Error)]
+pub enum StreamError {
+    #[error("Stream not found")]
+    NotFound,
+    #[error("Too many open streams")]
+    TooMany,
+}
+
+struct Inner {
+    chunks: VecDeque<Chunk>,
+    next_seq: u64,
+    done: bool,
+    error: Option<String>,
+    touched: Instant,
+}
+
+struct Entry {
+    user_id: Uuid,
+    inner: Mutex<Inner>,
+    /// Bumped whenever a chunk is added or the stream ends.
+    changed: watch::Sender<u64>,
+}
+
+impl Entry {
+    fn expired(&self, now: Instant) -> bool {
+        let inner = self.inner.lock().unwrap();
+        now.duration_since(inner.touched) > IDLE_TIMEOUT
+    }
+}
+
+/// In-memory store of pollable streams.
+#[derive(Default)]
+pub struct StreamStore {
+    streams: Mutex<HashMap<Uuid, Arc<Entry>>>,
+}
+
+impl StreamStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open a stream owned by `user_id`, dropping expired streams first.
+    pub fn create(&self, user_id: Uuid) -> Result<Uuid, StreamError> {
+        let now = Instant::now();
+        let mut streams = self.streams.lock().unwrap();
+        streams.retain(|_, entry| !entry.expired(now));
+        if streams.values().filter(|e| e.user_id == user_id).count() >= MAX_STREAMS_PER_USER {
+            return Err(StreamError::TooMany);
+        }
+        let id = Uuid::now_v7();
+        let (changed, _) = watch::channel(0);
+        streams.insert(
+            id,
+            Arc::new(Entry {
+                user_id,
+                inner: Mutex::new(Inner {
+                    chunks: VecDeque::new(),
+                    next_seq: 1,
+                    done: false,
+                    error: None,
+                    touched: now,
+                }),
+                changed,
+            }),
+        );
+        Ok(id)
+    }
+
+    /// The live stream `id`, removing it if it expired.
+    fn live(&self, id: &Uuid) -> Option<Arc<Entry>> {
+        let mut streams = self.streams.lock().unwrap();
+        let entry = streams.get(id)?.clone();
+        if entry.expired(Instant::now()) {
+            streams.remove(id);
+            return None;
+        }
+        Some(entry)
+    }
+
+    /// Whether stream `id` still has a client. Producers stop once this
+    /// turns false.
+    pub fn is_open(&self, id: &Uuid) -> bool {
+        self.live(id).is_some()
+    }
+
+    /// Append a chunk. Returns false when the stream is gone, in which case
+    /// the producer should stop.
+    pub fn push(&self, id: &Uuid, event: Option<&str>, data: String) -> bool {
+        let Some(entry) = self.live(id) else {
+            return false;
+        };
+        let seq = {
+            let mut inner = entry.inner.lock().unwrap();
+            if inner.done {
+                return false;
+            }
+            let seq = inner.next_seq;
+            inner.next_seq += 1;
+            inner.chunks.push_back(Chunk {
+                seq,
+                event: event.map(str::to_string),
+                data,
+            });
+            seq
+        };
+        entry.changed.send_replace(seq);
+        true
+    }
+
+    /// End stream `id`, with the error that cut it short if any. The
+    /// client has [`IDLE_TIMEOUT`] to fetch what is left.
+    pub fn finish(&self, id: &Uuid, error: Option<String>) {
+        let Some(entry) = self.live(id) else {
+            return;
+        };
+        let mut inner = entry.inner.lock().unwrap();
+        inner.done = true;
+        inner.error = error;
+        inner.touched = Instant::now();
+        let next = inner.next_seq;
+        drop(inner);
+        entry.changed.send_replace(next);
+    }
+
+    /// Chunks of `user_id`'s stream `id` after `after`, waiting up to `wait`
+    /// for some to arrive. Chunks up to `after` are dropped.
+    pub async fn poll(
+        &self,
+        id: &Uuid,
+        user_id: &Uuid,
+        after: u64,
+        wait: Duration,
+    ) -> Result<Poll, StreamError> {
+        let entry = self
+            .live(id)
+            .filter(|e| e.user_id == *user_id)
+            .ok_or(StreamError::NotFound)?;
+        let mut changed = entry.changed.subscribe();
+        let deadline = Instant::now() + wait.min(MAX_WAIT);
+        loop {
+            changed.mark_unchanged();
+            {
+                let mut inner = entry.inner.lock().unwrap();
+                inner.touched = Instant::now();
+                while inner.chunks.front().is_some_and(|c| c.seq <= after) {
+                    inner.chunks.pop_front();
+                }
+                if !inner.chunks.is_empty() || inner.done || Instant::now() >= deadline {
+                    return Ok(Poll {
+                        chunks: inner.chunks.iter().cloned().collect(),
+                        done: inner.done,
+                        error: inner.error.clone(),
+                    });
+                }
+            }
+            // A timeout or closed channel falls through to a final read.
+            let _ = tokio::time::timeout_at(deadline, changed.changed()).await;
+        }
+    }
+}
+
+/// Decodes a byte stream as UTF-8, holding back sequences split across
+/// chunks so multi-byte characters are never cut in half.
+#[derive(Debug, Default)]
+pub struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    /// Decode the next bytes, returning the text completed so far.
+    /// Invalid bytes are replaced.
+    pub fn push(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let complete = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        let rest = self.pending.split_off(complete);
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending = rest;
+        text
+    }
+
+    /// Flush whatever is left at the end of the stream.
+    pub fn finish(&mut self) -> String {
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn poll_returns_unacknowledged_chunks_and_end() {
+        let store = StreamStore::new();
+        let user = Uuid::new_v4();
+        let id = store.create(user).unwrap();
+        assert!(store.push(&id, None, "a".into()));
+        assert!(store.push(&id, Some("kind"), "b".into()));
+
+        let poll = store.poll(&id, &user, 0, Duration::ZERO).await.unwrap();
+        assert_eq!(poll.chunks.len(), 2);
+        assert_eq!(poll.chunks[1].seq, 2);
+        assert_eq!(poll.chunks[1].event.as_deref(), Some("kind"));
+
+        store.finish(&id, Some("upstream closed".into()));
+        assert!(!store.push(&id, None, "late".into()));
+        let poll = store.poll(&id, &user, 2, Duration::ZERO).await.unwrap();
+        assert!(poll.chunks.is_empty());
+        assert!(poll.done);
+        assert_eq!(poll.error.as_deref(), Some("upstream closed"));
+    }
+
+    #[tokio::test]
+    async fn poll_waits_for_the_next_chunk() {
+        let store = Arc::new(StreamStore::new());
+        let user = Uuid::new_v4();
+        let id = store.create(user).unwrap();
+
+        let producer = {
+            let store = store.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                store.push(&id, None, "late".into());
+            })
+        };
+        let poll = store
+            .poll(&id, &user, 0, Duration::from_secs(5))
+            .await
+            .unwrap();
+        producer.await.unwrap();
+        assert_eq!(poll.chunks.len(), 1);
+        assert_eq!(poll.chunks[0].data, "late");
+    }
+
+    #[tokio::test]
+    async fn streams_are_private_to_their_owner() {
+        let store = StreamStore::new();
+        let id = store.create(Uuid::new_v4()).unwrap();
+        let err = store
+            .poll(&id, &Uuid::new_v4(), 0, Duration::ZERO)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, StreamError::NotFound));
+    }
+
+    #[test]
+    fn decoder_keeps_split_characters_whole() {
+        let mut decoder = Utf8Decoder::default();
+        let bytes = "héllo".as_bytes();
+        assert_eq!(decoder.push(&bytes[..2]), "h");
+        assert_eq!(decoder.push(&bytes[2..]), "éllo");
+        assert_eq!(decoder.push(&[0xE2, 0x82]), "");
+        assert_eq!(decoder.finish(), "\u{FFFD}");
+    }
+}
@@ -0,0 +1,410 @@
+//! In-process test harness for integration tests (feature `test-support`).
+//!
+//! [`TestApp::spawn`] provisions a fresh, migrated database, builds an
+//! [`AppState`] and wraps the router in a [`TestClient`] that drives requests
+//! through `tower::ServiceExt::oneshot` — no listener, no network.
+//!
+//! Database selection:
+//!
+//! | `NIZE_TEST_DATABASE_URL` | Behaviour                                          |
+//! |--------------------------|----------------------------------------------------|
+//! | unset                    | ephemeral PG via `LocalDbManager::ephemeral()`     |
+//! | set (any database URL)   | a uniquely named database on that server, dropped by [`TestApp::shutdown`] |
+//!
+//! Helpers panic on failure; they are meant for tests only.
+//!
+//! [`TestStateBuilder`] is the one place tests construct an [`AppState`];
+//! unit tests that need no database use it with its default lazy pool.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use axum::body::{Body, Bytes};
+use axum::http::{HeaderMap, Method, Request, StatusCode, header};
+use serde::de::DeserializeOwned;
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+use tokio::sync::RwLock;
+use tower::ServiceExt;
+
+use nize_core::auth::password::PasswordPolicy;
+use nize_core::config::cache::ConfigCache;
+use nize_core::db::LocalDbManager;
+
+use crate::config::ApiConfig;
+use crate::i18n::Catalog;
+use crate::metrics::MetricsRegistry;
+use crate::services::auth::{generate_access_token, hash_password};
+use crate::{AppState, router};
+
+/// Env var pointing the harness at an existing PostgreSQL server.
+pub const TEST_DATABASE_URL_ENV: &str = "NIZE_TEST_DATABASE_URL";
+
+/// JWT secret used by every harness instance.
+pub const TEST_JWT_SECRET: &str = "nize-test-jwt-secret";
+
+/// Password assigned to users created via [`TestApp::create_user`].
+pub const TEST_PASSWORD: &str = "test-password-123";
+
+/// Database URL of the default state: nothing listens there, so a test
+/// that reaches the database fails fast instead of hanging.
+const UNREACHABLE_DATABASE_URL: &str = "postgres://localhost:1/nize";
+
+/// Builds an [`AppState`] with test defaults.
+pub struct TestStateBuilder {
+    pool: Option<PgPool>,
+    config: ApiConfig,
+    i18n: Catalog,
+}
+
+impl Default for TestStateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestStateBuilder {
+    /// State backed by a lazy pool on an unreachable database.
+    pub fn new() -> Self {
+        Self {
+            pool: None,
+            config: ApiConfig {
+                bind_addr: "127.0.0.1:0".into(),
+                pg_connection_url: UNREACHABLE_DATABASE_URL.into(),
+                jwt_secret: TEST_JWT_SECRET.into(),
+                mcp_encryption_key: "nize-test-encryption-key".into(),
+                metrics_local_only: false,
+                chat_url: None,
+                mcp_url: None,
+                read_only: false,
+                allowed_origins: Vec::new(),
+                data_dir: None,
+                oauth_callback_port: None,
+                require_email_verification: false,
+            },
+            i18n: Catalog::builtin(),
+        }
+    }
+
+    /// Use a connected pool for the database at `connection_url`.
+    pub fn pool(mut self, pool: PgPool, connection_url: &str) -> Self {
+        self.pool = Some(pool);
+        self.config.pg_connection_url = connection_url.into();
+        self
+    }
+
+    /// Adjust the configuration.
+    pub fn config(mut self, adjust: impl FnOnce(&mut ApiConfig)) -> Self {
+        adjust(&mut self.config);
+        self
+    }
+
+    /// Use `catalog` for localized messages instead of the built-in one.
+    pub fn i18n(mut self, catalog: Catalog) -> Self {
+        self.i18n = catalog;
+        self
+    }
+
+    pub fn build(self) -> AppState {
+        let pool = self.pool.unwrap_or_else(|| {
+            PgPoolOptions::new()
+                .acquire_timeout(Duration::from_millis(200))
+                .connect_lazy(&self.config.pg_connection_url)
+                .expect("lazy pool")
+        });
+        AppState {
+            pool,
+            jwt_keys: Arc::new(nize_core::auth::keys::JwtKeys::from_secret(
+                &self.config.jwt_secret,
+            )),
+            config: self.config,
+            config_cache: Arc::new(RwLock::new(ConfigCache::new())),
+            metrics: Arc::new(MetricsRegistry::new()),
+            events: Arc::new(crate::events::EventBus::new()),
+            streams: Arc::new(crate::streams::StreamStore::new()),
+            slow_queries: Arc::new(crate::diagnostics::SlowQueryLog::new(Default::default())),
+            telemetry: Arc::new(nize_core::telemetry::UsageCounters::new()),
+            i18n: Arc::new(self.i18n),
+            local_mode: None,
+            mcp_clients: Arc::new(nize_core::mcp::execution::ClientPool::new()),
+        }
+    }
+}
+
+/// Backing database for a [`TestApp`].
+enum TestDb {
+    /// Ephemeral local PostgreSQL instance (stopped on shutdown).
+    Ephemeral(LocalDbManager),
+    /// Temporary database on an external server (dropped on shutdown).
+    External {
+        admin_url: String,
+        database_name: String,
+    },
+}
+
+/// A user created by the harness, with a ready-to-use access token.
+#[derive(Debug, Clone)]
+pub struct TestUser {
+    pub id: String,
+    pub email: String,
+    pub roles: Vec<String>,
+    pub access_token: String,
+}
+
+/// A fully wired API instance backed by a fresh database.
+pub struct TestApp {
+    pub state: AppState,
+    pub client: TestClient,
+    db: TestDb,
+}
+
+impl TestApp {
+    /// Provision a database, run migrations and build the router.
+    pub async fn spawn() -> Self {
+        let (db, connection_url) = match std::env::var(TEST_DATABASE_URL_ENV) {
+            Ok(admin_url) => create_external_database(admin_url).await,
+            Err(_) => start_ephemeral_database().await,
+        };
+
+        let pool = PgPool::connect(&connection_url)
+            .await
+            .expect("connect to test database");
+        crate::migrate(&pool).await.expect("run migrations");
+
+        let state = TestStateBuilder::new().pool(pool, &connection_url).build();
+        let client = TestClient::new(router(state.clone()));
+
+        Self { state, client, db }
+    }
+
+    /// Register a user through `POST /auth/register`, exactly as a client
+    /// would. The first registered user becomes admin.
+    pub async fn register_user(&self, email: &str, password: &str) -> TestUser {
+        let resp = self
+            .client
+            .post(
+                "/api/auth/register",
+                serde_json::json!({ "email": email, "password": password }),
+            )
+            .await;
+        assert_eq!(
+            resp.status,
+            StatusCode::OK,
+            "register failed: {}",
+            resp.text()
+        );
+
+        let body: serde_json::Value = resp.json();
+        TestUser {
+            id: body["user"]["id"].as_str().unwrap_or_default().to_string(),
+            email: email.to_string(),
+            roles: serde_json::from_value(body["user"]["roles"].clone()).unwrap_or_default(),
+            access_token: body["accessToken"].as_str().unwrap_or_default().to_string(),
+        }
+    }
+
+    /// Insert a user directly with the given roles and mint a token for it.
+    ///
+    /// Faster than [`Self::register_user`] and independent of first-user
+    /// admin promotion.
+    pub async fn create_user(&self, email: &str, roles: &[&str]) -> TestUser {
+        let policy = PasswordPolicy::load(&self.state.pool)
+            .await
+            .expect("load password policy");
+        let pw_hash = hash_password(TEST_PASSWORD, &policy)
+            .await
+            .expect("hash password");
+        let id = nize_core::auth::queries::create_user(&self.state.pool, email, None, &pw_hash)
+            .await
+            .expect("create user");
+        for role in roles {
+            nize_core::auth::queries::grant_role(&self.state.pool, &id, role)
+                .await
+                .expect("grant role");
+        }
+
+        let roles: Vec<String> = roles.iter().map(|r| r.to_string()).collect();
+        let access_token = self.mint_token(&id, email, &roles);
+        TestUser {
+            id,
+            email: email.to_string(),
+            roles,
+            access_token,
+        }
+    }
+
+    /// Mint an access token signed with the harness secret.
+    pub fn mint_token(&self, user_id: &str, email: &str, roles: &[String]) -> String {
+        generate_access_token(user_id, email, roles, &self.state.jwt_keys)
+            .expect("mint access token")
+    }
+
+    /// A client that sends `Authorization: Bearer` for `user`.
+    pub fn client_for(&self, user: &TestUser) -> TestClient {
+        self.client.with_token(&user.access_token)
+    }
+
+    /// Close the pool and tear down the backing database.
+    pub async fn shutdown(self) {
+        self.state.pool.close().await;
+        match self.db {
+            TestDb::Ephemeral(mut db) => db.stop().await.expect("stop ephemeral database"),
+            TestDb::External {
+                admin_url,
+                database_name,
+            } => {
+                let admin = PgPool::connect(&admin_url)
+                    .await
+                    .expect("connect to admin database");
+                sqlx::query(&format!("DROP DATABASE IF EXISTS \"{database_name}\""))
+                    .execute(&admin)
+                    .await
+                    .expect("drop test database");
+                admin.close().await;
+            }
+        }
+    }
+}
+
+async fn start_ephemeral_database() -> (TestDb, String) {
+    let mut db = LocalDbManager::ephemeral()
+        .await
+        .expect("LocalDbManager::ephemeral");
+    db.setup().await.expect("db setup");
+    db.start().await.expect("db start");
+    let url = db.connection_url();
+    (TestDb::Ephemeral(db), url)
+}
+
+async fn create_external_database(admin_url: String) -> (TestDb, String) {
+    let database_name = format!("nize_test_{}", uuid::Uuid::new_v4().simple());
+
+    let admin = PgPool::connect(&admin_url)
+        .await
+        .expect("connect to NIZE_TEST_DATABASE_URL");
+    // CREATE DATABASE cannot use bind parameters
+    sqlx::query(&format!("CREATE DATABASE \"{database_name}\""))
+        .execute(&admin)
+        .await
+        .expect("create test database");
+    admin.close().await;
+
+    let mut url: url::Url = admin_url.parse().expect("valid database URL");
+    url.set_path(&database_name);
+    let url = url.to_string();
+
+    let pool = PgPool::connect(&url)
+        .await
+        .expect("connect to test database");
+    sqlx::query("CREATE EXTENSION IF NOT EXISTS vector")
+        .execute(&pool)
+        .await
+        .expect("enable vector extension");
+    pool.close().await;
+
+    (
+        TestDb::External {
+            admin_url,
+            database_name,
+        },
+        url,
+    )
+}
+
+/// Drives requests through the router in-process.
+#[derive(Clone)]
+pub struct TestClient {
+    router: Router,
+    token: Option<String>,
+}
+
+impl TestClient {
+    pub fn new(router: Router) -> Self {
+        Self {
+            router,
+            token: None,
+        }
+    }
+
+    /// Clone this client, authenticating subsequent requests with `token`.
+    pub fn with_token(&self, token: &str) -> Self {
+        Self {
+            router: self.router.clone(),
+            token: Some(token.to_string()),
+        }
+    }
+
+    pub async fn get(&self, path: &str) -> TestResponse {
+        self.request(Method::GET, path, None).await
+    }
+
+    pub async fn delete(&self, path: &str) -> TestResponse {
+        self.request(Method::DELETE, path, None).await
+    }
+
+    pub async fn post(&self, path: &str, body: serde_json::Value) -> TestResponse {
+        self.request(Method::POST, path, Some(body)).await
+    }
+
+    pub async fn patch(&self, path: &str, body: serde_json::Value) -> TestResponse {
+        self.request(Method::PATCH, path, Some(body)).await
+    }
+
+    pub async fn put(&self, path: &str, body: serde_json::Value) -> TestResponse {
+        self.request(Method::PUT, path, Some(body)).await
+    }
+
+    /// Send a request with an optional JSON body.
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> TestResponse {
+        let mut builder = Request::builder().method(method).uri(path);
+        if let Some(token) = &self.token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+        }
+        let req = match body {
+            Some(json) => builder
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(json.to_string())),
+            None => builder.body(Body::empty()),
+        }
+        .expect("build request");
+
+        let resp = self.router.clone().oneshot(req).await.expect("request");
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .expect("read body");
+
+        TestResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+}
+
+/// A buffered response from [`TestClient`].
+#[derive(Debug)]
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+impl TestResponse {
+    /// Deserialize the body as JSON, panicking with the raw body on failure.
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body)
+            .unwrap_or_else(|e| panic!("invalid JSON body ({e}): {}", self.text()))
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
@@ -0,0 +1,31 @@
+//! Integration test — register users through the harness and exercise
+//! role-gated routes with their tokens.
+
+use axum::http::StatusCode;
+use nize_api::test_support::TestApp;
+
+#[tokio::test]
+async fn first_registered_user_is_admin_and_tokens_gate_routes() {
+    let app = TestApp::spawn().await;
+
+    let admin = app.register_user("admin@example.com", "password123").await;
+    assert_eq!(admin.roles, vec!["admin".to_string()]);
+
+    let member = app.create_user("member@example.com", &[]).await;
+
+    // Unauthenticated
+    let resp = app.client.get("/api/conversations").await;
+    assert_eq!(resp.status, StatusCode::UNAUTHORIZED);
+
+    // Authenticated member
+    let resp = app.client_for(&member).get("/api/conversations").await;
+    assert_eq!(resp.status, StatusCode::OK, "{}", resp.text());
+
+    // Admin-only route
+    let resp = app.client_for(&member).get("/api/admin/config").await;
+    assert_eq!(resp.status, StatusCode::FORBIDDEN);
+    let resp = app.client_for(&admin).get("/api/admin/config").await;
+    assert_eq!(resp.status, StatusCode::OK, "{}", resp.text());
+
+    app.shutdown().await;
+}
@@ -3,7 +3,7 @@
 
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
-use nize_api::{AppState, config::ApiConfig};
+use nize_api::test_support::{TestApp, TestStateBuilder};
 use nize_core::db::LocalDbManager;
 use tower::ServiceExt;
 
@@ -20,19 +20,9 @@ async fn hello_endpoint_returns_expected_shape() {
         .await
         .expect("connect to ephemeral PG");
 
-    let state = AppState {
-        pool,
-        config: ApiConfig {
-            bind_addr: "127.0.0.1:0".into(),
-            pg_connection_url: db.connection_url(),
-            jwt_secret: "test-secret".into(),
-            mcp_encryption_key: "test-encryption-key".into(),
-        },
-        config_cache: std::sync::Arc::new(tokio::sync::RwLock::new(
-            nize_core::config::cache::ConfigCache::new(),
-        )),
-        oauth_state: std::sync::Arc::new(nize_core::mcp::oauth::OAuthStateStore::new()),
-    };
+    let state = TestStateBuilder::new()
+        .pool(pool, &db.connection_url())
+        .build();
 
     let app = nize_api::router(state);
 
@@ -82,3 +72,18 @@ async fn hello_endpoint_returns_expected_shape() {
     // Clean up
     db.stop().await.expect("db stop");
 }
+
+#[tokio::test]
+async fn hello_endpoint_via_test_app() {
+    // The harness provisions a migrated database (or uses
+    // NIZE_TEST_DATABASE_URL) and drives the router in-process.
+    let app = TestApp::spawn().await;
+
+    let resp = app.client.get("/api/hello").await;
+    assert_eq!(resp.status, StatusCode::OK);
+
+    let json: serde_json::Value = resp.json();
+    assert_eq!(json["dbConnected"], true, "db should be connected");
+
+    app.shutdown().await;
+}
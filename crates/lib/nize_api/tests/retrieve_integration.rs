@@ -0,0 +1,196 @@
+//! Integration test — document retrieval returns only chunks of documents
+//! the caller may read, with the reason each one was allowed.
+
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
+use nize_api::test_support::{TestApp, TestClient};
+use nize_core::config::queries::upsert_value;
+use nize_core::models::config::ConfigScope;
+use serde_json::{Value, json};
+
+/// Embed with the offline `local` provider under a model registered here,
+/// since the seeded models need a running Ollama or an OpenAI key.
+async fn use_local_embeddings(app: &TestApp) {
+    sqlx::query(
+        "INSERT INTO embedding_models (provider, name, table_name, dimensions) \
+         VALUES ('local', 'retrieve-test', 'chunk_embeddings_local_retrieve_test', 64)",
+    )
+    .execute(&app.state.pool)
+    .await
+    .expect("register local model");
+    for (key, value) in [
+        ("embedding.provider", "local"),
+        ("embedding.activeModel", "retrieve-test"),
+    ] {
+        upsert_value(&app.state.pool, key, &ConfigScope::System, None, value)
+            .await
+            .expect("set embedding config");
+    }
+}
+
+/// Upload `text` as a document of `client`'s user and embed it right away
+/// instead of waiting for the background indexer.
+async fn upload(app: &TestApp, client: &TestClient, filename: &str, text: &str) -> String {
+    let resp = client
+        .post(&format!("/api/ingest?filename={filename}"), json!(text))
+        .await;
+    assert_eq!(resp.status, StatusCode::CREATED, "{}", resp.text());
+    let id = resp.json::<Value>()["document"]["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    nize_core::embedding::indexer::embed_document(
+        &app.state.pool,
+        &app.state.config_cache,
+        &id.parse().unwrap(),
+        &app.state.config.mcp_encryption_key,
+    )
+    .await
+    .expect("embed document");
+    id
+}
+
+/// Retrieve for `client` with every similarity allowed and map each
+/// returned document to the access reason given for it.
+async fn readable(client: &TestClient, link_tokens: &[&str]) -> HashMap<String, String> {
+    let resp = client
+        .post(
+            "/api/ingest/retrieve",
+            json!({
+                "query": "hiking trail",
+                "topK": 50,
+                "minSimilarity": -1,
+                "linkTokens": link_tokens,
+                "explain": true,
+            }),
+        )
+        .await;
+    assert_eq!(resp.status, StatusCode::OK, "{}", resp.text());
+    resp.json::<Value>()["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| {
+            (
+                hit["documentId"].as_str().unwrap().to_string(),
+                hit["access"]["reason"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+async fn share(client: &TestClient, document_id: &str, email: &str) {
+    let resp = client
+        .post(
+            &format!("/api/permissions/document/{document_id}/grants"),
+            json!({ "email": email, "level": "view" }),
+        )
+        .await;
+    assert_eq!(resp.status, StatusCode::OK, "{}", resp.text());
+}
+
+#[tokio::test]
+async fn retrieve_filters_by_ownership_grants_and_links() {
+    let app = TestApp::spawn().await;
+    use_local_embeddings(&app).await;
+    let alice = app.create_user("alice@example.com", &[]).await;
+    let bob = app.create_user("bob@example.com", &[]).await;
+    let carol = app.create_user("carol@example.com", &[]).await;
+    let alice_client = app.client_for(&alice);
+    let bob_client = app.client_for(&bob);
+
+    let own = upload(&app, &alice_client, "own.txt", "Hiking trail notes").await;
+    let granted = upload(&app, &bob_client, "granted.txt", "Shared hiking trail").await;
+    let linked = upload(&app, &bob_client, "linked.txt", "Linked hiking trail").await;
+    let private = upload(
+        &app,
+        &app.client_for(&carol),
+        "private.txt",
+        "Private hiking trail",
+    )
+    .await;
+
+    // Grants match the grantee's email case-insensitively
+    share(&bob_client, &granted, "Alice@Example.com").await;
+    let resp = bob_client
+        .post(
+            &format!("/api/permissions/document/{linked}/links"),
+            json!({ "level": "view" }),
+        )
+        .await;
+    assert_eq!(resp.status, StatusCode::OK, "{}", resp.text());
+    let link: Value = resp.json();
+    let token = link["token"].as_str().unwrap().to_string();
+
+    let found = readable(&alice_client, &[&token]).await;
+    assert_eq!(found.get(&own).map(String::as_str), Some("owner"));
+    assert_eq!(found.get(&granted).map(String::as_str), Some("grant"));
+    assert_eq!(found.get(&linked).map(String::as_str), Some("link"));
+    assert!(!found.contains_key(&private), "{found:?}");
+
+    // Without the token, or once the link has expired, it grants nothing
+    assert!(!readable(&alice_client, &[]).await.contains_key(&linked));
+    sqlx::query(
+        "UPDATE share_links SET expires_at = now() - interval '1 minute' WHERE id = $1::uuid",
+    )
+    .bind(link["id"].as_str().unwrap())
+    .execute(&app.state.pool)
+    .await
+    .unwrap();
+    let found = readable(&alice_client, &[&token]).await;
+    assert!(!found.contains_key(&linked), "{found:?}");
+    assert!(found.contains_key(&own) && found.contains_key(&granted));
+
+    // Carol sees only her own document
+    let found = readable(&app.client_for(&carol), &[]).await;
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert!(found.contains_key(&private));
+
+    app.shutdown().await;
+}
+
+#[tokio::test]
+async fn email_grants_reach_only_verified_accounts() {
+    let app = TestApp::spawn().await;
+    use_local_embeddings(&app).await;
+    assert!(!app.state.config.require_email_verification);
+    let bob = app.create_user("bob@example.com", &[]).await;
+    let bob_client = app.client_for(&bob);
+    let early = upload(&app, &bob_client, "early.txt", "Early hiking trail").await;
+    let later = upload(&app, &bob_client, "later.txt", "Later hiking trail").await;
+
+    // Shared before the address has an account; with verification
+    // disabled, registering it counts as verified and picks the grant up
+    share(&bob_client, &early, "Dave@Example.com").await;
+    let dave = app
+        .register_user("dave@example.com", "correct horse battery staple")
+        .await;
+    let dave_client = app.client_for(&dave);
+    let found = readable(&dave_client, &[]).await;
+    assert_eq!(found.get(&early).map(String::as_str), Some("grant"));
+
+    // An account left unverified from when verification was required gets
+    // nothing through its email — neither existing nor new grants — until
+    // it is verified
+    sqlx::query("UPDATE users SET email_verified = NULL WHERE id = $1::uuid")
+        .bind(&dave.id)
+        .execute(&app.state.pool)
+        .await
+        .unwrap();
+    share(&bob_client, &later, "dave@example.com").await;
+    assert!(readable(&dave_client, &[]).await.is_empty());
+
+    sqlx::query("UPDATE users SET email_verified = now() WHERE id = $1::uuid")
+        .bind(&dave.id)
+        .execute(&app.state.pool)
+        .await
+        .unwrap();
+    let found = readable(&dave_client, &[]).await;
+    assert!(
+        found.contains_key(&early) && found.contains_key(&later),
+        "{found:?}"
+    );
+
+    app.shutdown().await;
+}
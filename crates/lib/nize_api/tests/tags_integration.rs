@@ -0,0 +1,93 @@
+//! Integration test — personal tags on notes, conversations and documents,
+//! and tag filters on list endpoints.
+
+use axum::http::StatusCode;
+use nize_api::test_support::TestApp;
+
+#[tokio::test]
+async fn tags_attach_filter_and_stay_personal() {
+    let app = TestApp::spawn().await;
+    let alice = app.create_user("alice@example.com", &[]).await;
+    let bob = app.create_user("bob@example.com", &[]).await;
+    let client = app.client_for(&alice);
+
+    // Note tags are created on the fly and normalized
+    let resp = client
+        .post(
+            "/api/notes",
+            serde_json::json!({ "body": "# Trip\nPack boots", "tags": [" Travel ", "travel"] }),
+        )
+        .await;
+    assert_eq!(resp.status, StatusCode::CREATED, "{}", resp.text());
+    let note: serde_json::Value = resp.json();
+    assert_eq!(note["title"], "Trip");
+    assert_eq!(note["tags"], serde_json::json!(["travel"]));
+
+    // Attach the same tag to a conversation by name
+    let resp = client
+        .post(
+            "/api/conversations",
+            serde_json::json!({ "title": "Plans" }),
+        )
+        .await;
+    let conv_id = resp.json::<serde_json::Value>()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let resp = client
+        .post(
+            &format!("/api/tags/conversation/{conv_id}"),
+            serde_json::json!({ "name": "travel" }),
+        )
+        .await;
+    assert_eq!(resp.status, StatusCode::OK, "{}", resp.text());
+    let tag_id = resp.json::<serde_json::Value>()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let resp = client.get("/api/tags").await;
+    let tags: serde_json::Value = resp.json();
+    assert_eq!(tags["tags"][0]["name"], "travel");
+    assert_eq!(tags["tags"][0]["usageCount"], 2);
+
+    // Tag filters
+    let resp = client.get("/api/conversations?tag=travel").await;
+    assert_eq!(resp.json::<serde_json::Value>()["total"], 1);
+    let resp = client.get("/api/notes?tag=work").await;
+    assert_eq!(resp.json::<serde_json::Value>()["total"], 0);
+
+    // Other users cannot see or use the tag
+    let resp = app.client_for(&bob).get("/api/tags").await;
+    assert_eq!(
+        resp.json::<serde_json::Value>()["tags"],
+        serde_json::json!([])
+    );
+    let resp = app
+        .client_for(&bob)
+        .post(
+            &format!("/api/tags/conversation/{conv_id}"),
+            serde_json::json!({ "tagId": tag_id }),
+        )
+        .await;
+    assert_eq!(resp.status, StatusCode::NOT_FOUND);
+
+    // Only existing documents of the user can be tagged
+    let resp = client
+        .post(
+            "/api/tags/document/00000000-0000-7000-8000-000000000000",
+            serde_json::json!({ "name": "travel" }),
+        )
+        .await;
+    assert_eq!(resp.status, StatusCode::NOT_FOUND);
+
+    // Detach, then unknown resource types are rejected
+    let resp = client
+        .delete(&format!("/api/tags/conversation/{conv_id}/{tag_id}"))
+        .await;
+    assert_eq!(resp.status, StatusCode::NO_CONTENT);
+    let resp = client.get(&format!("/api/tags/folder/{conv_id}")).await;
+    assert_eq!(resp.status, StatusCode::BAD_REQUEST);
+
+    app.shutdown().await;
+}
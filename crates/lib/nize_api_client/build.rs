@@ -18,10 +18,19 @@ fn main() {
     let mut json: serde_json::Value = serde_json::from_reader(file)
         .unwrap_or_else(|e| panic!("failed to parse {}: {e}", src.display()));
 
+    // Fingerprint of the spec as written, compared with the server's
+    // `GET /meta` (see `compat`).
+    let spec_hash = nize_codegen::spec_hash(&json);
+
+    // Typed error responses, generated before they are stripped below.
+    let error_types = nize_codegen::client_error_types(&json)
+        .unwrap_or_else(|e| panic!("failed to generate error types: {e}"));
+
     // Progenitor requires at most one success (2xx) response per operation.
     // Our TypeSpec defines proper error responses (4xx/5xx) via @error models
     // which produce multiple response entries. Strip non-2xx responses so
-    // progenitor only sees the success path; errors are handled at runtime.
+    // progenitor only sees the success path; errors are parsed at runtime
+    // with the generated `errors` enums.
     strip_error_responses(&mut json);
 
     let spec: openapiv3::OpenAPI = serde_json::from_value(json)
@@ -33,10 +42,14 @@ fn main() {
     let ast = syn::parse2(tokens).unwrap();
     let content = prettyplease::unparse(&ast);
 
-    let mut out_file = Path::new(&env::var("OUT_DIR").unwrap()).to_path_buf();
-    out_file.push("codegen.rs");
-
-    fs::write(out_file, content).unwrap();
+    let out_dir = Path::new(&env::var("OUT_DIR").unwrap()).to_path_buf();
+    fs::write(out_dir.join("codegen.rs"), content).unwrap();
+    fs::write(out_dir.join("errors.rs"), error_types).unwrap();
+    fs::write(
+        out_dir.join("spec_hash.rs"),
+        format!("pub const SPEC_HASH: &str = \"{spec_hash}\";\n"),
+    )
+    .unwrap();
 }
 
 /// Remove non-2xx responses from every operation in the OpenAPI spec.
@@ -0,0 +1,90 @@
+//! Spec compatibility between this client and a server.
+//!
+//! The client is generated from the API spec at build time and embeds that
+//! spec's fingerprint as [`SPEC_HASH`]; a server reports the fingerprint of
+//! the spec it was generated from at `GET /api/meta`. When the two differ,
+//! client and server were built from different specs — after a partial
+//! update, say — and requests may fail or silently drop fields. [`check`]
+//! fetches the server's metadata and compares.
+
+use serde::{Deserialize, Serialize};
+
+include!(concat!(env!("OUT_DIR"), "/spec_hash.rs"));
+
+/// Path of the server's metadata endpoint, relative to its base URL.
+pub const META_PATH: &str = "/api/meta";
+
+/// Body of `GET /api/meta`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerMeta {
+    pub spec_hash: String,
+    pub api_version: String,
+    pub server_version: String,
+}
+
+/// Whether this client and a server were generated from the same spec.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Compatibility {
+    pub compatible: bool,
+    /// [`SPEC_HASH`] of this client.
+    pub client_spec_hash: String,
+    pub server: ServerMeta,
+}
+
+impl Compatibility {
+    /// Compare `server` with this client.
+    pub fn of(server: ServerMeta) -> Self {
+        Self {
+            compatible: server.spec_hash == SPEC_HASH,
+            client_spec_hash: SPEC_HASH.to_string(),
+            server,
+        }
+    }
+}
+
+/// Fetch the metadata of the server at `base_url` and compare it with this
+/// client.
+pub async fn check(
+    http: &reqwest::Client,
+    base_url: &str,
+) -> Result<Compatibility, reqwest::Error> {
+    let meta: ServerMeta = http
+        .get(format!("{}{META_PATH}", base_url.trim_end_matches('/')))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(Compatibility::of(meta))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(spec_hash: &str) -> ServerMeta {
+        ServerMeta {
+            spec_hash: spec_hash.into(),
+            api_version: "v1".into(),
+            server_version: "0.1.0".into(),
+        }
+    }
+
+    #[test]
+    fn compatible_only_with_the_same_spec() {
+        assert!(Compatibility::of(meta(SPEC_HASH)).compatible);
+        let other = Compatibility::of(meta("0123"));
+        assert!(!other.compatible);
+        assert_eq!(other.client_spec_hash, SPEC_HASH);
+    }
+
+    #[test]
+    fn meta_reads_the_api_shape() {
+        let parsed: ServerMeta =
+            serde_json::from_str(r#"{"specHash":"ab","apiVersion":"v1","serverVersion":"0.1.0"}"#)
+                .unwrap();
+        assert_eq!(parsed, meta("ab"));
+    }
+}
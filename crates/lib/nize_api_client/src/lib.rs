@@ -1 +1,11 @@
 include!(concat!(env!("OUT_DIR"), "/codegen.rs"));
+
+/// Typed error responses: an enum per operation, named like the server's
+/// route constants (`GET /ingest/{id}` → `GetIngestIdError`), whose
+/// `from_response` parses the status and body of an unexpected response.
+pub mod errors {
+    include!(concat!(env!("OUT_DIR"), "/errors.rs"));
+}
+
+pub mod compat;
+pub mod offline;
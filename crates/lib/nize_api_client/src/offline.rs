@@ -0,0 +1,682 @@
+//! Offline queue for mutating requests.
+//!
+//! On a flaky network a conversation save or config change can fail only
+//! because the server is unreachable. [`OfflineQueue::send_or_queue`] sends
+//! a mutating request and, when the server cannot be reached, persists it
+//! to a JSON file instead; [`OfflineQueue::replay`] sends queued requests in
+//! order once connectivity returns.
+//!
+//! Conflict detection: a request may carry a [`ConflictGuard`] — a resource
+//! to re-read before replaying and the value one of its fields had when the
+//! change was made (typically `updatedAt`). If the value changed in the
+//! meantime, or the server answers `409`/`412`, the entry is marked
+//! [`EntryStatus::Conflict`] and left for the user to retry (optionally
+//! ignoring the guard) or discard. Other client errors mark the entry
+//! [`EntryStatus::Failed`]. Neither blocks the entries behind it.
+//!
+//! Duplicates: a `POST` gets an idempotency key when it is first sent and
+//! keeps it in the queue, so when a send timed out after the server had
+//! already handled it, the replay gets the stored response back instead of
+//! creating the resource twice.
+//!
+//! Credentials are never persisted: callers pass the auth headers to each
+//! send and replay.
+
+use std::hash::{BuildHasher, RandomState};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+/// Request header making a `POST` safe to retry.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Errors from queue operations.
+#[derive(Debug, thiserror::Error)]
+pub enum QueueError {
+    #[error("IO error on {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid queue file {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+
+    #[error("Only mutating requests can be queued, not {0}")]
+    NotMutating(String),
+
+    #[error("Request failed: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+/// State of a queued request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryStatus {
+    /// Waiting to be replayed.
+    Pending,
+    /// The resource changed since the request was made; needs a decision.
+    Conflict,
+    /// The server rejected the request; it will not be retried by itself.
+    Failed,
+}
+
+/// A resource whose field must still have `expected` when a queued request
+/// is replayed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictGuard {
+    /// API path to `GET`, e.g. `/conversations/{id}`.
+    pub path: String,
+    /// JSON pointer into the response, e.g. `/updatedAt`.
+    pub pointer: String,
+    pub expected: serde_json::Value,
+}
+
+/// A mutating request to send.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewRequest {
+    /// `POST`, `PUT`, `PATCH` or `DELETE`.
+    pub method: String,
+    /// API path relative to the base URL, e.g. `/notes`.
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guard: Option<ConflictGuard>,
+    /// What the request does, for display (e.g. "Save note").
+    pub label: String,
+    /// Sent as [`IDEMPOTENCY_KEY_HEADER`]; assigned to `POST`s that have
+    /// none when they are first sent or queued.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+}
+
+impl NewRequest {
+    /// Give a `POST` its idempotency key, if it has none yet.
+    fn assign_idempotency_key(&mut self) {
+        if self.idempotency_key.is_none() && self.method.eq_ignore_ascii_case("POST") {
+            self.idempotency_key = Some(new_idempotency_key());
+        }
+    }
+
+    /// A request for this method, path, body and idempotency key.
+    fn builder(
+        &self,
+        http: &reqwest::Client,
+        method: Method,
+        base_url: &str,
+        headers: &HeaderMap,
+    ) -> reqwest::RequestBuilder {
+        let mut builder = http
+            .request(method, url(base_url, &self.path))
+            .headers(headers.clone());
+        if let Some(key) = self
+            .idempotency_key
+            .as_deref()
+            .and_then(|key| HeaderValue::from_str(key).ok())
+        {
+            builder = builder.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        if let Some(body) = &self.body {
+            builder = builder.json(body);
+        }
+        builder
+    }
+}
+
+/// A persisted request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedRequest {
+    pub id: u64,
+    #[serde(flatten)]
+    pub request: NewRequest,
+    pub status: EntryStatus,
+    /// Replay attempts that could not reach the server.
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Unix time in milliseconds.
+    pub queued_at_ms: u64,
+}
+
+/// Queue state for the UI.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueSnapshot {
+    pub entries: Vec<QueuedRequest>,
+    pub pending: usize,
+    pub conflicts: usize,
+    pub failed: usize,
+    pub replaying: bool,
+    pub last_replay_at_ms: Option<u64>,
+}
+
+/// Result of [`OfflineQueue::send_or_queue`].
+#[derive(Debug, Clone)]
+pub enum SendOutcome {
+    /// The server answered (with any status).
+    Sent {
+        status: StatusCode,
+        body: serde_json::Value,
+    },
+    /// The server was unreachable; the request was queued.
+    Queued(Box<QueuedRequest>),
+}
+
+/// What one [`OfflineQueue::replay`] did.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayReport {
+    pub sent: usize,
+    pub conflicts: usize,
+    pub failed: usize,
+    /// Entries still pending.
+    pub remaining: usize,
+    /// Replay stopped because the server was still unreachable.
+    pub offline: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueueFile {
+    next_id: u64,
+    entries: Vec<QueuedRequest>,
+    #[serde(default)]
+    last_replay_at_ms: Option<u64>,
+}
+
+/// A request queue persisted to a JSON file.
+#[derive(Debug)]
+pub struct OfflineQueue {
+    path: PathBuf,
+    state: Mutex<QueueFile>,
+    replaying: AtomicBool,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// A fresh idempotency key: the time plus two randomly seeded hashes.
+fn new_idempotency_key() -> String {
+    let now = now_ms();
+    let random = || RandomState::new().hash_one(now);
+    format!("offline-{now:x}-{:016x}{:016x}", random(), random())
+}
+
+fn parse_method(method: &str) -> Result<Method, QueueError> {
+    match method.to_ascii_uppercase().as_str() {
+        "POST" => Ok(Method::POST),
+        "PUT" => Ok(Method::PUT),
+        "PATCH" => Ok(Method::PATCH),
+        "DELETE" => Ok(Method::DELETE),
+        _ => Err(QueueError::NotMutating(method.to_string())),
+    }
+}
+
+/// The server could not be reached (or a proxy in front of it could not
+/// reach it): worth retrying later.
+fn is_unreachable_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout()
+}
+
+fn is_unreachable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+            | StatusCode::TOO_MANY_REQUESTS
+    )
+}
+
+fn is_conflict_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::CONFLICT | StatusCode::PRECONDITION_FAILED
+    )
+}
+
+fn url(base_url: &str, path: &str) -> String {
+    format!(
+        "{}/{}",
+        base_url.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+/// The server is still handling an earlier send of the same request (same
+/// idempotency key); worth retrying later.
+fn is_still_running(status: StatusCode, body: &serde_json::Value) -> bool {
+    status == StatusCode::CONFLICT && body.get("error").and_then(|e| e.as_str()) == Some("conflict")
+}
+
+/// Error message from an API error body.
+fn error_message(status: StatusCode, body: &serde_json::Value) -> String {
+    match body.get("message").and_then(|m| m.as_str()) {
+        Some(message) => format!("{status}: {message}"),
+        None => status.to_string(),
+    }
+}
+
+/// Outcome of replaying one entry.
+enum Replayed {
+    Sent,
+    Conflict(String),
+    Failed(String),
+    Unreachable(String),
+}
+
+impl OfflineQueue {
+    /// Open the queue at `path`, creating it empty if missing.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, QueueError> {
+        let path = path.into();
+        let state = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|source| QueueError::Parse {
+                path: path.clone(),
+                source,
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => QueueFile::default(),
+            Err(source) => return Err(QueueError::Io { path, source }),
+        };
+        Ok(Self {
+            path,
+            state: Mutex::new(state),
+            replaying: AtomicBool::new(false),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the queue atomically (temp file + rename).
+    fn persist(&self, state: &QueueFile) -> Result<(), QueueError> {
+        let io = |source| QueueError::Io {
+            path: self.path.clone(),
+            source,
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(io)?;
+        }
+        let json = serde_json::to_vec_pretty(state).map_err(|source| QueueError::Parse {
+            path: self.path.clone(),
+            source,
+        })?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).map_err(io)?;
+        std::fs::rename(&tmp, &self.path).map_err(io)
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut QueueFile) -> T) -> Result<T, QueueError> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let result = f(&mut state);
+        self.persist(&state)?;
+        Ok(result)
+    }
+
+    /// Add a request to the end of the queue.
+    pub fn enqueue(&self, mut request: NewRequest) -> Result<QueuedRequest, QueueError> {
+        parse_method(&request.method)?;
+        request.assign_idempotency_key();
+        self.update(|state| {
+            state.next_id += 1;
+            let entry = QueuedRequest {
+                id: state.next_id,
+                request,
+                status: EntryStatus::Pending,
+                attempts: 0,
+                last_error: None,
+                queued_at_ms: now_ms(),
+            };
+            state.entries.push(entry.clone());
+            entry
+        })
+    }
+
+    /// Current entries and counts.
+    pub fn snapshot(&self) -> QueueSnapshot {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let count = |status| state.entries.iter().filter(|e| e.status == status).count();
+        QueueSnapshot {
+            pending: count(EntryStatus::Pending),
+            conflicts: count(EntryStatus::Conflict),
+            failed: count(EntryStatus::Failed),
+            entries: state.entries.clone(),
+            replaying: self.replaying.load(Ordering::Acquire),
+            last_replay_at_ms: state.last_replay_at_ms,
+        }
+    }
+
+    /// Remove an entry. Returns whether it existed.
+    pub fn discard(&self, id: u64) -> Result<bool, QueueError> {
+        self.update(|state| {
+            let before = state.entries.len();
+            state.entries.retain(|e| e.id != id);
+            state.entries.len() != before
+        })
+    }
+
+    /// Mark a conflicted or failed entry pending again; with `force`, its
+    /// conflict guard is dropped so the change overwrites the server's.
+    /// Returns whether the entry exists.
+    pub fn retry(&self, id: u64, force: bool) -> Result<bool, QueueError> {
+        self.update(|state| {
+            let Some(entry) = state.entries.iter_mut().find(|e| e.id == id) else {
+                return false;
+            };
+            entry.status = EntryStatus::Pending;
+            entry.last_error = None;
+            if force {
+                entry.request.guard = None;
+            }
+            true
+        })
+    }
+
+    /// Send `request`, queuing it if the server is unreachable. Entries
+    /// already waiting go first: while any are pending, `request` is queued
+    /// behind them so changes reach the server in order.
+    pub async fn send_or_queue(
+        &self,
+        http: &reqwest::Client,
+        base_url: &str,
+        headers: &HeaderMap,
+        mut request: NewRequest,
+    ) -> Result<SendOutcome, QueueError> {
+        let method = parse_method(&request.method)?;
+        if self.snapshot().pending > 0 {
+            return Ok(SendOutcome::Queued(Box::new(self.enqueue(request)?)));
+        }
+        // Assigned before sending, so a replay after a timeout is
+        // recognised as the same request.
+        request.assign_idempotency_key();
+        match request
+            .builder(http, method, base_url, headers)
+            .send()
+            .await
+        {
+            Ok(resp) if is_unreachable_status(resp.status()) => {
+                Ok(SendOutcome::Queued(Box::new(self.enqueue(request)?)))
+            }
+            Ok(resp) => {
+                let status = resp.status();
+                let body = resp.json().await.unwrap_or_default();
+                Ok(SendOutcome::Sent { status, body })
+            }
+            Err(e) if is_unreachable_error(&e) => {
+                Ok(SendOutcome::Queued(Box::new(self.enqueue(request)?)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn replay_one(
+        http: &reqwest::Client,
+        base_url: &str,
+        headers: &HeaderMap,
+        entry: &QueuedRequest,
+    ) -> Replayed {
+        if let Some(guard) = &entry.request.guard {
+            let resp = match http
+                .get(url(base_url, &guard.path))
+                .headers(headers.clone())
+                .send()
+                .await
+            {
+                Ok(resp) => resp,
+                Err(e) => return Replayed::Unreachable(e.to_string()),
+            };
+            let status = resp.status();
+            if is_unreachable_status(status) {
+                return Replayed::Unreachable(status.to_string());
+            }
+            if status == StatusCode::NOT_FOUND {
+                return Replayed::Conflict("The resource was deleted on the server".into());
+            }
+            let body: serde_json::Value = resp.json().await.unwrap_or_default();
+            if !status.is_success() {
+                return Replayed::Failed(error_message(status, &body));
+            }
+            let current = body.pointer(&guard.pointer);
+            if current != Some(&guard.expected) {
+                return Replayed::Conflict(format!(
+                    "The resource changed on the server ({} is now {})",
+                    guard.pointer,
+                    current.unwrap_or(&serde_json::Value::Null)
+                ));
+            }
+        }
+
+        let method = match parse_method(&entry.request.method) {
+            Ok(method) => method,
+            Err(e) => return Replayed::Failed(e.to_string()),
+        };
+        let resp = match entry
+            .request
+            .builder(http, method, base_url, headers)
+            .send()
+            .await
+        {
+            Ok(resp) => resp,
+            Err(e) => return Replayed::Unreachable(e.to_string()),
+        };
+        let status = resp.status();
+        if status.is_success() {
+            return Replayed::Sent;
+        }
+        if is_unreachable_status(status) {
+            return Replayed::Unreachable(status.to_string());
+        }
+        let body: serde_json::Value = resp.json().await.unwrap_or_default();
+        if is_still_running(status, &body) {
+            Replayed::Unreachable(error_message(status, &body))
+        } else if is_conflict_status(status) {
+            Replayed::Conflict(error_message(status, &body))
+        } else {
+            Replayed::Failed(error_message(status, &body))
+        }
+    }
+
+    /// Send pending entries in order. Stops at the first entry the server
+    /// cannot be reached for; returns an empty report if a replay is already
+    /// running.
+    pub async fn replay(
+        &self,
+        http: &reqwest::Client,
+        base_url: &str,
+        headers: &HeaderMap,
+    ) -> Result<ReplayReport, QueueError> {
+        if self.replaying.swap(true, Ordering::AcqRel) {
+            return Ok(ReplayReport::default());
+        }
+        let result = self.replay_pending(http, base_url, headers).await;
+        self.replaying.store(false, Ordering::Release);
+        result
+    }
+
+    async fn replay_pending(
+        &self,
+        http: &reqwest::Client,
+        base_url: &str,
+        headers: &HeaderMap,
+    ) -> Result<ReplayReport, QueueError> {
+        let pending: Vec<QueuedRequest> = self
+            .snapshot()
+            .entries
+            .into_iter()
+            .filter(|e| e.status == EntryStatus::Pending)
+            .collect();
+
+        let mut report = ReplayReport::default();
+        for entry in &pending {
+            let outcome = Self::replay_one(http, base_url, headers, entry).await;
+            let stop = matches!(outcome, Replayed::Unreachable(_));
+            self.update(|state| {
+                match outcome {
+                    Replayed::Sent => {
+                        state.entries.retain(|e| e.id != entry.id);
+                        report.sent += 1;
+                    }
+                    outcome => {
+                        let Some(stored) = state.entries.iter_mut().find(|e| e.id == entry.id)
+                        else {
+                            return;
+                        };
+                        match outcome {
+                            Replayed::Conflict(message) => {
+                                stored.status = EntryStatus::Conflict;
+                                stored.last_error = Some(message);
+                                report.conflicts += 1;
+                            }
+                            Replayed::Failed(message) => {
+                                stored.status = EntryStatus::Failed;
+                                stored.last_error = Some(message);
+                                report.failed += 1;
+                            }
+                            Replayed::Unreachable(message) => {
+                                stored.attempts += 1;
+                                stored.last_error = Some(message);
+                                report.offline = true;
+                            }
+                            Replayed::Sent => unreachable!(),
+                        }
+                    }
+                }
+                state.last_replay_at_ms = Some(now_ms());
+            })?;
+            if stop {
+                break;
+            }
+        }
+        report.remaining = self.snapshot().pending;
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(label: &str) -> NewRequest {
+        NewRequest {
+            method: "post".into(),
+            path: "/notes".into(),
+            body: Some(serde_json::json!({ "body": label })),
+            guard: None,
+            label: label.into(),
+            idempotency_key: None,
+        }
+    }
+
+    #[test]
+    fn entries_persist_across_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.json");
+        let queue = OfflineQueue::open(&path).unwrap();
+        let first = queue.enqueue(note("a")).unwrap();
+        let second = queue.enqueue(note("b")).unwrap();
+        assert!(second.id > first.id);
+        assert!(queue.discard(first.id).unwrap());
+        assert!(!queue.discard(first.id).unwrap());
+
+        let reopened = OfflineQueue::open(&path).unwrap();
+        let snapshot = reopened.snapshot();
+        assert_eq!(snapshot.pending, 1);
+        assert_eq!(snapshot.entries[0].request.label, "b");
+        assert!(reopened.enqueue(note("c")).unwrap().id > second.id);
+    }
+
+    #[test]
+    fn only_mutating_requests_are_queued() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = OfflineQueue::open(dir.path().join("queue.json")).unwrap();
+        let mut get = note("a");
+        get.method = "GET".into();
+        assert!(matches!(
+            queue.enqueue(get),
+            Err(QueueError::NotMutating(_))
+        ));
+    }
+
+    #[test]
+    fn forced_retry_drops_the_guard() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue = OfflineQueue::open(dir.path().join("queue.json")).unwrap();
+        let mut request = note("a");
+        request.guard = Some(ConflictGuard {
+            path: "/notes/1".into(),
+            pointer: "/updatedAt".into(),
+            expected: serde_json::json!("2026-01-01T00:00:00Z"),
+        });
+        let entry = queue.enqueue(request).unwrap();
+        queue
+            .update(|state| state.entries[0].status = EntryStatus::Conflict)
+            .unwrap();
+        assert_eq!(queue.snapshot().conflicts, 1);
+
+        assert!(queue.retry(entry.id, true).unwrap());
+        let snapshot = queue.snapshot();
+        assert_eq!(snapshot.pending, 1);
+        assert!(snapshot.entries[0].request.guard.is_none());
+        assert!(!queue.retry(entry.id + 1, false).unwrap());
+    }
+
+    #[test]
+    fn posts_keep_one_idempotency_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queue.json");
+        let queue = OfflineQueue::open(&path).unwrap();
+        let first = queue.enqueue(note("a")).unwrap();
+        let second = queue.enqueue(note("b")).unwrap();
+        let key = first.request.idempotency_key.clone().unwrap();
+        assert_ne!(Some(&key), second.request.idempotency_key.as_ref());
+
+        let reopened = OfflineQueue::open(&path).unwrap();
+        assert_eq!(
+            reopened.snapshot().entries[0].request.idempotency_key,
+            Some(key)
+        );
+
+        let mut delete = note("c");
+        delete.method = "DELETE".into();
+        assert!(
+            queue
+                .enqueue(delete)
+                .unwrap()
+                .request
+                .idempotency_key
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn statuses_are_classified() {
+        assert!(is_unreachable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_unreachable_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(is_conflict_status(StatusCode::PRECONDITION_FAILED));
+        assert!(is_still_running(
+            StatusCode::CONFLICT,
+            &serde_json::json!({ "error": "conflict" })
+        ));
+        assert!(!is_still_running(
+            StatusCode::CONFLICT,
+            &serde_json::json!({})
+        ));
+        assert_eq!(url("http://h/api/", "/notes"), "http://h/api/notes");
+    }
+}
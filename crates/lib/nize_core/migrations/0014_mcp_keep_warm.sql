@@ -0,0 +1,3 @@
+-- Add keep_warm flag to mcp_servers: pinned servers are pre-connected at
+-- startup and exempt from idle eviction.
+ALTER TABLE mcp_servers ADD COLUMN IF NOT EXISTS keep_warm BOOLEAN NOT NULL DEFAULT false;
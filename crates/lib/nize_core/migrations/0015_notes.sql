@@ -0,0 +1,71 @@
+-- Notes (markdown quick captures) and their chunk embeddings for retrieval.
+
+CREATE TABLE IF NOT EXISTS notes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    title VARCHAR(500) NOT NULL DEFAULT '',
+    body TEXT NOT NULL DEFAULT '',
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_notes_user_id ON notes(user_id);
+CREATE INDEX IF NOT EXISTS idx_notes_tags ON notes USING gin(tags);
+
+-- Chunks of a note body, rebuilt whenever the note changes
+CREATE TABLE IF NOT EXISTS note_chunks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    note_id UUID NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    chunk_index INT NOT NULL,
+    content TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS note_chunks_note_index_idx
+    ON note_chunks(note_id, chunk_index);
+
+-- ---------------------------------------------------------------------------
+-- Note embedding tables (one per model, registered in embedding_models)
+-- ---------------------------------------------------------------------------
+
+ALTER TABLE embedding_models ADD COLUMN IF NOT EXISTS note_table_name VARCHAR(120);
+
+UPDATE embedding_models
+SET note_table_name = 'note_' || table_name
+WHERE note_table_name IS NULL;
+
+ALTER TABLE embedding_models ALTER COLUMN note_table_name SET NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS embedding_models_note_table_name_idx
+    ON embedding_models(note_table_name);
+
+CREATE TABLE IF NOT EXISTS note_chunk_embeddings_openai_text_embedding_3_small (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    chunk_id UUID NOT NULL REFERENCES note_chunks(id) ON DELETE CASCADE,
+    note_id UUID NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    embedding VECTOR(1536) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE UNIQUE INDEX IF NOT EXISTS note_embeddings_openai_te3s_chunk_idx
+    ON note_chunk_embeddings_openai_text_embedding_3_small(chunk_id);
+CREATE INDEX IF NOT EXISTS note_embeddings_openai_te3s_note_idx
+    ON note_chunk_embeddings_openai_text_embedding_3_small(note_id);
+CREATE INDEX IF NOT EXISTS note_embeddings_openai_te3s_embedding_idx
+    ON note_chunk_embeddings_openai_text_embedding_3_small
+    USING hnsw (embedding vector_cosine_ops);
+
+CREATE TABLE IF NOT EXISTS note_chunk_embeddings_ollama_nomic_embed_text (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    chunk_id UUID NOT NULL REFERENCES note_chunks(id) ON DELETE CASCADE,
+    note_id UUID NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    embedding VECTOR(768) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE UNIQUE INDEX IF NOT EXISTS note_embeddings_ollama_net_chunk_idx
+    ON note_chunk_embeddings_ollama_nomic_embed_text(chunk_id);
+CREATE INDEX IF NOT EXISTS note_embeddings_ollama_net_note_idx
+    ON note_chunk_embeddings_ollama_nomic_embed_text(note_id);
+CREATE INDEX IF NOT EXISTS note_embeddings_ollama_net_embedding_idx
+    ON note_chunk_embeddings_ollama_nomic_embed_text
+    USING hnsw (embedding vector_cosine_ops);
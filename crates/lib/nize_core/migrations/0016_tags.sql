@@ -0,0 +1,60 @@
+-- Personal tags shared across documents, conversations and notes.
+-- Tags are owned by a user and only organize that user's resources; they
+-- play no part in permission checks.
+
+DO $$ BEGIN
+    CREATE TYPE tag_resource_type AS ENUM ('document', 'conversation', 'note');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS tags (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(64) NOT NULL,
+    color VARCHAR(7),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS tags_user_name_idx ON tags(user_id, name);
+
+-- Polymorphic association: resource_id points into the table named by
+-- resource_type, so there is no FK on it. Owners delete assignments when
+-- the resource is deleted.
+CREATE TABLE IF NOT EXISTS tag_assignments (
+    tag_id UUID NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    resource_type tag_resource_type NOT NULL,
+    resource_id UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (tag_id, resource_type, resource_id)
+);
+
+CREATE INDEX IF NOT EXISTS tag_assignments_resource_idx
+    ON tag_assignments(resource_type, resource_id);
+
+-- ---------------------------------------------------------------------------
+-- Move note tags from the notes.tags array into the shared tables
+-- ---------------------------------------------------------------------------
+
+DO $$ BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'notes' AND column_name = 'tags'
+    ) THEN
+        INSERT INTO tags (user_id, name)
+        SELECT DISTINCT n.user_id, t.name
+        FROM notes n CROSS JOIN LATERAL unnest(n.tags) AS t(name)
+        ON CONFLICT (user_id, name) DO NOTHING;
+
+        INSERT INTO tag_assignments (tag_id, resource_type, resource_id)
+        SELECT tg.id, 'note', n.id
+        FROM notes n
+        CROSS JOIN LATERAL unnest(n.tags) AS t(name)
+        JOIN tags tg ON tg.user_id = n.user_id AND tg.name = t.name
+        ON CONFLICT DO NOTHING;
+
+        DROP INDEX IF EXISTS idx_notes_tags;
+        ALTER TABLE notes DROP COLUMN tags;
+    END IF;
+END $$;
@@ -0,0 +1,36 @@
+-- ---------------------------------------------------------------------------
+-- mcp_tool_executions: One row per proxied tool call (raw analytics events)
+-- ---------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS mcp_tool_executions (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    server_id UUID NOT NULL REFERENCES mcp_servers(id) ON DELETE CASCADE,
+    tool_name TEXT NOT NULL,
+    success BOOLEAN NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    error_category TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS mcp_tool_executions_created_idx ON mcp_tool_executions (created_at);
+
+-- ---------------------------------------------------------------------------
+-- mcp_tool_usage_daily: Daily per-user/per-server/per-tool rollups
+-- ---------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS mcp_tool_usage_daily (
+    day DATE NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    server_id UUID NOT NULL REFERENCES mcp_servers(id) ON DELETE CASCADE,
+    tool_name TEXT NOT NULL,
+    call_count BIGINT NOT NULL,
+    success_count BIGINT NOT NULL,
+    p50_latency_ms DOUBLE PRECISION NOT NULL,
+    p95_latency_ms DOUBLE PRECISION NOT NULL,
+    error_categories JSONB NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (day, user_id, server_id, tool_name)
+);
+
+CREATE INDEX IF NOT EXISTS mcp_tool_usage_daily_server_idx ON mcp_tool_usage_daily (server_id, day);
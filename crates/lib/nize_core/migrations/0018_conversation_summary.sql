@@ -0,0 +1,47 @@
+-- Rolling conversation summaries for chat context-window management.
+-- summary covers the first summary_message_count messages of the
+-- conversation; the chat service sends it in place of those messages.
+
+ALTER TABLE conversations ADD COLUMN IF NOT EXISTS summary TEXT;
+ALTER TABLE conversations ADD COLUMN IF NOT EXISTS summary_message_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE conversations ADD COLUMN IF NOT EXISTS summary_updated_at TIMESTAMPTZ;
+
+-- agent.context.targetTokens — token budget for the composed chat context
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'agent.context.targetTokens',
+    'agent',
+    'number',
+    'number',
+    '24000',
+    'Context Token Budget',
+    'Approximate token budget for conversation history sent to the model; older turns are summarized to stay within it',
+    '[{"type":"min","value":2000,"message":"Token budget must be at least 2000"},{"type":"max","value":1000000,"message":"Token budget must be at most 1000000"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+-- agent.context.summarizerModel — model used to summarize older turns
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'agent.context.summarizerModel',
+    'agent',
+    'string',
+    'text',
+    '',
+    'Summarizer Model',
+    'Model used to summarize older conversation turns (format: provider:model); empty uses the chat model'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
@@ -0,0 +1,43 @@
+-- Scheduled assistant tasks and user notifications.
+
+-- ---------------------------------------------------------------------------
+-- tasks: Prompts run on a cron schedule through the chat pipeline
+-- ---------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS tasks (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(200) NOT NULL,
+    prompt TEXT NOT NULL,
+    -- Cron expression evaluated in UTC
+    schedule VARCHAR(200) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    -- Conversation results are appended to (created on first run)
+    conversation_id UUID REFERENCES conversations(id) ON DELETE SET NULL,
+    next_run_at TIMESTAMPTZ,
+    last_run_at TIMESTAMPTZ,
+    last_status VARCHAR(20),
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS tasks_user_idx ON tasks (user_id);
+CREATE INDEX IF NOT EXISTS tasks_due_idx ON tasks (next_run_at) WHERE enabled;
+
+-- ---------------------------------------------------------------------------
+-- notifications: Per-user events (e.g. task completed / failed)
+-- ---------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS notifications (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL,
+    title VARCHAR(500) NOT NULL,
+    body TEXT NOT NULL DEFAULT '',
+    payload JSONB NOT NULL DEFAULT '{}',
+    read_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS notifications_user_created_idx ON notifications (user_id, created_at DESC);
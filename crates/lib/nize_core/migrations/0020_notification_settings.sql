@@ -0,0 +1,59 @@
+-- Per-category toggles for native desktop notifications. The desktop app's
+-- notification bridge reads the user's effective values from /config/user.
+
+-- notifications.desktop.ingest — ingestion finished
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'notifications.desktop.ingest',
+    'notifications',
+    'boolean',
+    'boolean',
+    'true',
+    'Notify When Ingestion Completes',
+    'Show a desktop notification when a document or note has been indexed'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- notifications.desktop.tasks — scheduled task finished
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'notifications.desktop.tasks',
+    'notifications',
+    'boolean',
+    'boolean',
+    'true',
+    'Notify When Tasks Finish',
+    'Show a desktop notification when a scheduled task run succeeds or fails'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- notifications.desktop.toolApprovals — tool call awaiting approval
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'notifications.desktop.toolApprovals',
+    'notifications',
+    'boolean',
+    'boolean',
+    'true',
+    'Notify On Tool Approval Requests',
+    'Show a desktop notification when a tool call is waiting for your approval'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
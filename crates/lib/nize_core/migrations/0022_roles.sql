@@ -0,0 +1,32 @@
+-- Role-based access control.
+-- Roles bundle permission strings (e.g. mcp.admin, config.write,
+-- users.manage; '*' grants everything). Users get permissions from the roles
+-- assigned to them. The legacy `admin` entry in user_roles keeps working: it
+-- is treated as an assignment of the built-in Administrator role.
+
+CREATE TABLE IF NOT EXISTS roles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(64) NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT '',
+    permissions TEXT[] NOT NULL DEFAULT '{}',
+    built_in BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS role_assignments (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    granted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    PRIMARY KEY (user_id, role_id)
+);
+
+CREATE INDEX IF NOT EXISTS role_assignments_role_idx ON role_assignments(role_id);
+
+INSERT INTO roles (name, description, permissions, built_in)
+VALUES ('administrator', 'Full access to every admin feature', ARRAY['*'], true)
+ON CONFLICT (name) DO UPDATE SET
+    description = EXCLUDED.description,
+    permissions = EXCLUDED.permissions,
+    built_in = true;
@@ -0,0 +1,27 @@
+-- Account takeout: background exports of a user's data, and cleanup so that
+-- deleting a user erases everything they own.
+
+-- ---------------------------------------------------------------------------
+-- account_exports: Export jobs and their finished archives
+-- ---------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS account_exports (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- running | completed | failed
+    status VARCHAR(20) NOT NULL,
+    archive JSONB,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS account_exports_user_idx ON account_exports (user_id, created_at DESC);
+
+-- ---------------------------------------------------------------------------
+-- mcp_config_audit: keep audit rows of erased users, without the actor
+-- ---------------------------------------------------------------------------
+
+-- The FK was declared ON DELETE SET NULL on a NOT NULL column, which made
+-- deleting any user with audit entries fail.
+ALTER TABLE mcp_config_audit ALTER COLUMN actor_id DROP NOT NULL;
@@ -0,0 +1,12 @@
+-- Tool discovery runs in the background after a server is created or its
+-- config changes; the outcome of the latest run is kept per server.
+
+CREATE TABLE IF NOT EXISTS mcp_server_discovery (
+    server_id UUID PRIMARY KEY REFERENCES mcp_servers(id) ON DELETE CASCADE,
+    -- running | succeeded | failed
+    status VARCHAR(20) NOT NULL,
+    tool_count INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    finished_at TIMESTAMPTZ
+);
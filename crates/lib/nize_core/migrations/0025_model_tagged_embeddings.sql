@@ -0,0 +1,99 @@
+-- Model-tagged embedding storage: tool and note embeddings move from one
+-- table per model into shared tables where every row records the model and
+-- dimension it was produced with. Each registered model gets its own
+-- partial HNSW index, so searches filtered by model stay indexed.
+
+-- ---------------------------------------------------------------------------
+-- tool_embeddings: MCP tool embeddings for semantic discovery
+-- ---------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS tool_embeddings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tool_id UUID NOT NULL REFERENCES mcp_server_tools(id) ON DELETE CASCADE,
+    server_id UUID NOT NULL REFERENCES mcp_servers(id) ON DELETE CASCADE,
+    domain TEXT NOT NULL,
+    model_id UUID NOT NULL REFERENCES embedding_models(id) ON DELETE CASCADE,
+    model VARCHAR(100) NOT NULL,
+    dimensions INTEGER NOT NULL,
+    embedding VECTOR NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (vector_dims(embedding) = dimensions)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS tool_embeddings_tool_model_idx
+    ON tool_embeddings(tool_id, model_id);
+CREATE INDEX IF NOT EXISTS tool_embeddings_server_idx
+    ON tool_embeddings(server_id);
+CREATE INDEX IF NOT EXISTS tool_embeddings_model_domain_idx
+    ON tool_embeddings(model_id, domain);
+
+-- ---------------------------------------------------------------------------
+-- note_chunk_embeddings: note chunk embeddings for retrieval
+-- ---------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS note_chunk_embeddings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    chunk_id UUID NOT NULL REFERENCES note_chunks(id) ON DELETE CASCADE,
+    note_id UUID NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    model_id UUID NOT NULL REFERENCES embedding_models(id) ON DELETE CASCADE,
+    model VARCHAR(100) NOT NULL,
+    dimensions INTEGER NOT NULL,
+    embedding VECTOR NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (vector_dims(embedding) = dimensions)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS note_chunk_embeddings_chunk_model_idx
+    ON note_chunk_embeddings(chunk_id, model_id);
+CREATE INDEX IF NOT EXISTS note_chunk_embeddings_note_idx
+    ON note_chunk_embeddings(note_id);
+
+-- ---------------------------------------------------------------------------
+-- Move rows out of the per-model tables and index each model
+-- ---------------------------------------------------------------------------
+
+DO $$
+DECLARE
+    m RECORD;
+    suffix TEXT;
+BEGIN
+    FOR m IN SELECT * FROM embedding_models LOOP
+        IF to_regclass(quote_ident(m.tool_table_name)) IS NOT NULL THEN
+            EXECUTE format(
+                'INSERT INTO tool_embeddings
+                     (id, tool_id, server_id, domain, model_id, model, dimensions, embedding, created_at)
+                 SELECT id, tool_id, server_id, domain, %L, %L, %s, embedding, created_at
+                 FROM %I
+                 ON CONFLICT DO NOTHING',
+                m.id, m.name, m.dimensions, m.tool_table_name);
+            EXECUTE format('DROP TABLE %I', m.tool_table_name);
+        END IF;
+
+        IF to_regclass(quote_ident(m.note_table_name)) IS NOT NULL THEN
+            EXECUTE format(
+                'INSERT INTO note_chunk_embeddings
+                     (id, chunk_id, note_id, model_id, model, dimensions, embedding, created_at)
+                 SELECT id, chunk_id, note_id, %L, %L, %s, embedding, created_at
+                 FROM %I
+                 ON CONFLICT DO NOTHING',
+                m.id, m.name, m.dimensions, m.note_table_name);
+            EXECUTE format('DROP TABLE %I', m.note_table_name);
+        END IF;
+
+        -- Searches cast to the model's dimension and filter by model_id,
+        -- matching these index expressions and predicates.
+        suffix := replace(m.id::text, '-', '');
+        EXECUTE format(
+            'CREATE INDEX IF NOT EXISTS %I ON tool_embeddings
+                 USING hnsw ((embedding::vector(%s)) vector_cosine_ops)
+                 WHERE model_id = %L',
+            'tool_embeddings_' || suffix || '_hnsw', m.dimensions, m.id);
+        EXECUTE format(
+            'CREATE INDEX IF NOT EXISTS %I ON note_chunk_embeddings
+                 USING hnsw ((embedding::vector(%s)) vector_cosine_ops)
+                 WHERE model_id = %L',
+            'note_chunk_embeddings_' || suffix || '_hnsw', m.dimensions, m.id);
+    END LOOP;
+END $$;
+
+-- The registry no longer names per-model tool and note tables.
+ALTER TABLE embedding_models DROP COLUMN IF EXISTS tool_table_name;
+ALTER TABLE embedding_models DROP COLUMN IF EXISTS note_table_name;
@@ -0,0 +1,13 @@
+-- Embedding inputs are normalized and truncated to the model's input limit
+-- before they are sent to the provider. Each model records its limit, and
+-- each stored embedding records whether its input was cut short.
+
+ALTER TABLE embedding_models ADD COLUMN IF NOT EXISTS max_input_tokens INTEGER NOT NULL DEFAULT 2048;
+
+-- Provider limits: OpenAI text-embedding-3 accepts 8191 tokens; Ollama
+-- serves nomic-embed-text with its default 2048-token context.
+UPDATE embedding_models SET max_input_tokens = 8191
+WHERE provider = 'openai' AND max_input_tokens = 2048;
+
+ALTER TABLE tool_embeddings ADD COLUMN IF NOT EXISTS truncated BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE note_chunk_embeddings ADD COLUMN IF NOT EXISTS truncated BOOLEAN NOT NULL DEFAULT false;
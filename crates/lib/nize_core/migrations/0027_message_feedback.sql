@@ -0,0 +1,22 @@
+-- Per-message feedback on assistant replies. Message rows are rewritten on
+-- every save, so feedback references the stable UIMessage id inside
+-- message_data and keeps a snapshot of the rated reply and the prompt
+-- that produced it for offline evaluation.
+
+CREATE TABLE IF NOT EXISTS message_feedback (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    conversation_id UUID NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    message_id TEXT NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    rating VARCHAR(10) NOT NULL CHECK (rating IN ('up', 'down')),
+    category VARCHAR(32),
+    comment TEXT,
+    message JSONB NOT NULL,
+    prompt JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE UNIQUE INDEX IF NOT EXISTS message_feedback_message_user_idx
+    ON message_feedback(conversation_id, message_id, user_id);
+CREATE INDEX IF NOT EXISTS message_feedback_created_idx
+    ON message_feedback(created_at);
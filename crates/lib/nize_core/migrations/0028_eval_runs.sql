@@ -0,0 +1,36 @@
+-- Eval harness: one row per run of a suite against a model, one row per
+-- case with the reply, the tools called and each assertion's outcome.
+-- Scores are comparable across runs of the same suite and model.
+
+CREATE TABLE IF NOT EXISTS eval_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    suite VARCHAR(100) NOT NULL,
+    model VARCHAR(200),
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'running'
+        CHECK (status IN ('running', 'completed', 'failed')),
+    case_count INTEGER NOT NULL DEFAULT 0,
+    passed_count INTEGER NOT NULL DEFAULT 0,
+    score DOUBLE PRECISION,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    completed_at TIMESTAMPTZ
+);
+CREATE INDEX IF NOT EXISTS eval_runs_suite_created_idx
+    ON eval_runs(suite, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS eval_results (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    run_id UUID NOT NULL REFERENCES eval_runs(id) ON DELETE CASCADE,
+    case_name VARCHAR(200) NOT NULL,
+    prompt TEXT NOT NULL,
+    response TEXT,
+    tool_calls JSONB NOT NULL DEFAULT '[]',
+    assertions JSONB NOT NULL DEFAULT '[]',
+    score DOUBLE PRECISION NOT NULL,
+    passed BOOLEAN NOT NULL,
+    error TEXT,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS eval_results_run_idx ON eval_results(run_id);
@@ -0,0 +1,16 @@
+-- Per-domain MCP server routing: an ordered list of preferred servers for a
+-- domain. Rows with a NULL user_id are the admin defaults; user rows
+-- override them. Tool discovery ranks by the route and tool execution falls
+-- back along it.
+
+CREATE TABLE IF NOT EXISTS mcp_domain_routes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    domain TEXT NOT NULL,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    server_ids UUID[] NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE UNIQUE INDEX IF NOT EXISTS mcp_domain_routes_admin_idx
+    ON mcp_domain_routes(domain) WHERE user_id IS NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS mcp_domain_routes_user_idx
+    ON mcp_domain_routes(user_id, domain) WHERE user_id IS NOT NULL;
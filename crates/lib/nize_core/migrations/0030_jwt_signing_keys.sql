@@ -0,0 +1,81 @@
+-- JWT signing keys for rotation. The active key (retired_at IS NULL) signs
+-- new access tokens; retired keys keep verifying until verify_until.
+-- private_key is encrypted with the server's encryption key: the secret for
+-- HS256, a PEM for RS256, base64 PKCS#8 for EdDSA.
+
+CREATE TABLE jwt_signing_keys (
+    kid TEXT PRIMARY KEY,
+    algorithm TEXT NOT NULL CHECK (algorithm IN ('HS256', 'RS256', 'EdDSA')),
+    private_key TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    retired_at TIMESTAMPTZ,
+    verify_until TIMESTAMPTZ
+);
+
+-- At most one active key.
+CREATE UNIQUE INDEX jwt_signing_keys_active_idx
+    ON jwt_signing_keys ((true))
+    WHERE retired_at IS NULL;
+
+-- system.auth.jwtAlgorithm — algorithm for newly rotated keys
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, possible_values, validators)
+VALUES (
+    'system.auth.jwtAlgorithm',
+    'system',
+    'string',
+    'selector',
+    'HS256',
+    'JWT Signing Algorithm',
+    'Algorithm for new signing keys, applied on the next key rotation. RS256 uses the RSA private key below; EdDSA keys are generated.',
+    '["HS256","RS256","EdDSA"]'::jsonb,
+    '[{"type":"required","message":"Algorithm is required"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    possible_values = EXCLUDED.possible_values,
+    validators = EXCLUDED.validators;
+
+-- system.auth.jwtKeyOverlapMinutes — how long retired keys keep verifying
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'system.auth.jwtKeyOverlapMinutes',
+    'system',
+    'number',
+    'number',
+    '60',
+    'JWT Key Overlap',
+    'Minutes a rotated-out signing key keeps verifying tokens (at least 15, the access token lifetime)',
+    '[{"type":"min","value":15,"message":"Overlap must be at least 15 minutes"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+-- system.auth.jwtRsaPrivateKey — RS256 private key
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'system.auth.jwtRsaPrivateKey',
+    'system',
+    'string',
+    'secret',
+    '',
+    'JWT RSA Private Key',
+    'PEM-encoded RSA private key (PKCS#1 or PKCS#8) used when rotating to RS256. Set a new key before each RS256 rotation.'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
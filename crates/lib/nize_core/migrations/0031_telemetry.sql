@@ -0,0 +1,67 @@
+-- Opt-in anonymous usage telemetry. A single row holds the random install
+-- ID sent with reports and when the last report was accepted.
+
+CREATE TABLE telemetry_state (
+    id BOOLEAN PRIMARY KEY DEFAULT true CHECK (id),
+    install_id UUID NOT NULL,
+    last_submitted_at TIMESTAMPTZ
+);
+
+-- system.telemetry.enabled — opt-in switch, off by default
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'system.telemetry.enabled',
+    'system',
+    'boolean',
+    'boolean',
+    'false',
+    'Send Anonymous Usage Statistics',
+    'Periodically send anonymous feature and error counts, the app version and OS to the telemetry endpoint. No content or user data is included; see the preview in the admin API.'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- system.telemetry.endpoint — where reports are sent
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'system.telemetry.endpoint',
+    'system',
+    'string',
+    'text',
+    '',
+    'Telemetry Endpoint',
+    'URL reports are POSTed to as JSON. Nothing is sent while empty.'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- system.telemetry.intervalHours — time between reports
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'system.telemetry.intervalHours',
+    'system',
+    'number',
+    'number',
+    '24',
+    'Telemetry Interval',
+    'Hours between usage reports',
+    '[{"type":"min","value":1,"message":"Interval must be at least 1 hour"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
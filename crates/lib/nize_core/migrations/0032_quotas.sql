@@ -0,0 +1,87 @@
+-- Per-user quotas for shared deployments. Enforced when creating
+-- conversations, notes, messages and MCP servers; 0 = unlimited.
+-- maxMcpServers defaults to the previous hard-coded limit.
+
+-- system.quotas.maxConversations
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'system.quotas.maxConversations',
+    'system',
+    'number',
+    'number',
+    '0',
+    'Max Conversations per User',
+    'Conversations a user may create. 0 = unlimited.',
+    '[{"type":"min","value":0,"message":"Limit must be 0 (unlimited) or more"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+-- system.quotas.maxDocuments
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'system.quotas.maxDocuments',
+    'system',
+    'number',
+    'number',
+    '0',
+    'Max Documents per User',
+    'Notes a user may create. 0 = unlimited.',
+    '[{"type":"min","value":0,"message":"Limit must be 0 (unlimited) or more"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+-- system.quotas.maxStorageBytes
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'system.quotas.maxStorageBytes',
+    'system',
+    'number',
+    'number',
+    '0',
+    'Max Storage per User (bytes)',
+    'Bytes of note text and conversation messages a user may store. 0 = unlimited.',
+    '[{"type":"min","value":0,"message":"Limit must be 0 (unlimited) or more"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+-- system.quotas.maxMcpServers
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'system.quotas.maxMcpServers',
+    'system',
+    'number',
+    'number',
+    '10',
+    'Max MCP Servers per User',
+    'User-owned MCP servers a user may add. 0 = unlimited.',
+    '[{"type":"min","value":0,"message":"Limit must be 0 (unlimited) or more"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
@@ -0,0 +1,192 @@
+-- Blob storage for uploaded documents. Blobs are content-addressed by
+-- SHA-256 and shared between identical uploads; a blob is dropped once no
+-- document references it. backend records where each blob lives so a
+-- backend switch keeps old blobs readable until they are migrated
+-- (`nize_cli migrate-blobs`).
+
+CREATE TABLE IF NOT EXISTS blobs (
+    sha256 TEXT PRIMARY KEY,
+    size_bytes BIGINT NOT NULL,
+    backend TEXT NOT NULL CHECK (backend IN ('filesystem', 's3')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_blobs_backend ON blobs(backend);
+
+CREATE TABLE IF NOT EXISTS documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    filename VARCHAR(500) NOT NULL,
+    mime_type VARCHAR(255) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    sha256 TEXT NOT NULL REFERENCES blobs(sha256),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_documents_user_id ON documents(user_id);
+
+-- storage.blobs.backend — where new blobs are stored
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, possible_values, validators)
+VALUES (
+    'storage.blobs.backend',
+    'storage',
+    'string',
+    'selector',
+    'filesystem',
+    'Blob Storage Backend',
+    'Backend for newly uploaded documents. Existing blobs stay where they are until migrated.',
+    '["filesystem","s3"]'::jsonb,
+    '[{"type":"required","message":"Backend is required"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    possible_values = EXCLUDED.possible_values,
+    validators = EXCLUDED.validators;
+
+-- storage.blobs.path — filesystem root
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'storage.blobs.path',
+    'storage',
+    'string',
+    'text',
+    '',
+    'Blob Directory',
+    'Directory for the filesystem backend (empty = the app data directory)'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- storage.blobs.s3.endpoint
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'storage.blobs.s3.endpoint',
+    'storage',
+    'string',
+    'text',
+    '',
+    'S3 Endpoint',
+    'S3-compatible service URL, e.g. https://s3.eu-west-1.amazonaws.com or http://127.0.0.1:9000'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- storage.blobs.s3.bucket
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'storage.blobs.s3.bucket',
+    'storage',
+    'string',
+    'text',
+    '',
+    'S3 Bucket',
+    'Bucket for document blobs'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- storage.blobs.s3.region
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'storage.blobs.s3.region',
+    'storage',
+    'string',
+    'text',
+    'us-east-1',
+    'S3 Region',
+    'Signing region (use us-east-1 for most self-hosted stores)'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- storage.blobs.s3.accessKeyId
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'storage.blobs.s3.accessKeyId',
+    'storage',
+    'string',
+    'text',
+    '',
+    'S3 Access Key ID',
+    'Access key ID for the bucket'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- storage.blobs.s3.secretAccessKey
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'storage.blobs.s3.secretAccessKey',
+    'storage',
+    'string',
+    'secret',
+    '',
+    'S3 Secret Access Key',
+    'Secret access key for the bucket'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- storage.blobs.s3.pathStyle
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'storage.blobs.s3.pathStyle',
+    'storage',
+    'boolean',
+    'boolean',
+    'true',
+    'S3 Path-Style Addressing',
+    'Address the bucket in the URL path instead of the host name (needed by most self-hosted stores)'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- Quotas now cover uploaded documents as well as notes.
+UPDATE config_definitions
+SET description = 'Notes and uploaded documents a user may create. 0 = unlimited.'
+WHERE key = 'system.quotas.maxDocuments';
+
+UPDATE config_definitions
+SET description = 'Bytes of note text, conversation messages and uploaded documents a user may store. 0 = unlimited.'
+WHERE key = 'system.quotas.maxStorageBytes';
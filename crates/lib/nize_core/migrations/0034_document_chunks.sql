@@ -0,0 +1,133 @@
+-- Extracted text of uploaded documents, split into chunks, plus OCR for
+-- images and scanned PDF pages. page is the 1-based PDF page (NULL for
+-- unpaged formats); ocr_confidence is the page's mean OCR word confidence
+-- (0..1), NULL when the text came from the file itself.
+
+CREATE TABLE IF NOT EXISTS document_chunks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    chunk_index INT NOT NULL,
+    content TEXT NOT NULL,
+    page INT,
+    ocr_confidence REAL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS document_chunks_document_index_idx
+    ON document_chunks(document_id, chunk_index);
+
+-- ingest.ocr.enabled
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.ocr.enabled',
+    'ingest',
+    'boolean',
+    'boolean',
+    'false',
+    'OCR',
+    'Recognize text in uploaded images and scanned PDF pages'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- ingest.ocr.provider
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, possible_values)
+VALUES (
+    'ingest.ocr.provider',
+    'ingest',
+    'string',
+    'selector',
+    'tesseract',
+    'OCR Provider',
+    'tesseract runs the local binary; http calls the OCR service below',
+    '["tesseract","http"]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    possible_values = EXCLUDED.possible_values;
+
+-- ingest.ocr.languages
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.ocr.languages',
+    'ingest',
+    'string',
+    'text',
+    'eng',
+    'OCR Languages',
+    'Tesseract language codes joined by +, e.g. eng+deu'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- ingest.ocr.tesseractPath
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.ocr.tesseractPath',
+    'ingest',
+    'string',
+    'text',
+    'tesseract',
+    'Tesseract Binary',
+    'Path to the tesseract executable'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- ingest.ocr.endpoint
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.ocr.endpoint',
+    'ingest',
+    'string',
+    'text',
+    '',
+    'OCR Service URL',
+    'Endpoint for the http provider: receives the image as the request body and returns {"text", "confidence"}'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- ingest.ocr.apiKey
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.ocr.apiKey',
+    'ingest',
+    'string',
+    'secret',
+    '',
+    'OCR Service API Key',
+    'Bearer token for the http provider'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
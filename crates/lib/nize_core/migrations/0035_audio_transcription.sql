@@ -0,0 +1,193 @@
+-- Audio transcription and document retrieval. Transcribed chunks record
+-- their offsets into the recording (start_ms/end_ms, NULL for other
+-- documents), and document chunks get model-tagged embeddings like note
+-- chunks so uploads are searchable.
+
+ALTER TABLE document_chunks ADD COLUMN IF NOT EXISTS start_ms BIGINT;
+ALTER TABLE document_chunks ADD COLUMN IF NOT EXISTS end_ms BIGINT;
+
+CREATE TABLE IF NOT EXISTS document_chunk_embeddings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    chunk_id UUID NOT NULL REFERENCES document_chunks(id) ON DELETE CASCADE,
+    document_id UUID NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    model_id UUID NOT NULL REFERENCES embedding_models(id) ON DELETE CASCADE,
+    model VARCHAR(100) NOT NULL,
+    dimensions INTEGER NOT NULL,
+    embedding VECTOR NOT NULL,
+    truncated BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (vector_dims(embedding) = dimensions)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS document_chunk_embeddings_chunk_model_idx
+    ON document_chunk_embeddings(chunk_id, model_id);
+CREATE INDEX IF NOT EXISTS document_chunk_embeddings_document_idx
+    ON document_chunk_embeddings(document_id);
+
+-- One partial HNSW index per registered model, as for note chunks.
+DO $$
+DECLARE
+    m RECORD;
+BEGIN
+    FOR m IN SELECT * FROM embedding_models LOOP
+        EXECUTE format(
+            'CREATE INDEX IF NOT EXISTS %I ON document_chunk_embeddings
+                 USING hnsw ((embedding::vector(%s)) vector_cosine_ops)
+                 WHERE model_id = %L',
+            'document_chunk_embeddings_' || replace(m.id::text, '-', '') || '_hnsw',
+            m.dimensions, m.id);
+    END LOOP;
+END $$;
+
+-- ingest.transcription.enabled
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.transcription.enabled',
+    'ingest',
+    'boolean',
+    'boolean',
+    'false',
+    'Audio Transcription',
+    'Transcribe uploaded audio files so recordings become searchable'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- ingest.transcription.provider
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, possible_values)
+VALUES (
+    'ingest.transcription.provider',
+    'ingest',
+    'string',
+    'selector',
+    'whisper',
+    'Transcription Provider',
+    'whisper runs a local whisper.cpp build; http calls an OpenAI-compatible transcription API',
+    '["whisper","http"]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    possible_values = EXCLUDED.possible_values;
+
+-- ingest.transcription.language
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.transcription.language',
+    'ingest',
+    'string',
+    'text',
+    'auto',
+    'Spoken Language',
+    'Language code of the recordings, or auto to detect'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- ingest.transcription.whisperPath
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.transcription.whisperPath',
+    'ingest',
+    'string',
+    'text',
+    'whisper-cli',
+    'whisper.cpp Binary',
+    'Path to the whisper.cpp command-line executable (audio is converted with ffmpeg first)'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- ingest.transcription.whisperModel
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.transcription.whisperModel',
+    'ingest',
+    'string',
+    'text',
+    '',
+    'whisper.cpp Model',
+    'Path to the ggml model file, e.g. ggml-base.en.bin'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- ingest.transcription.endpoint
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.transcription.endpoint',
+    'ingest',
+    'string',
+    'text',
+    'https://api.openai.com/v1/audio/transcriptions',
+    'Transcription API URL',
+    'OpenAI-compatible /audio/transcriptions endpoint for the http provider'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- ingest.transcription.apiKey
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.transcription.apiKey',
+    'ingest',
+    'string',
+    'secret',
+    '',
+    'Transcription API Key',
+    'Bearer token for the http provider'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- ingest.transcription.model
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.transcription.model',
+    'ingest',
+    'string',
+    'text',
+    'whisper-1',
+    'Transcription Model',
+    'Model name sent to the http provider'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
@@ -0,0 +1,24 @@
+-- Per-tool enablement. Admins can switch a tool off for everyone; users can
+-- switch tools off for themselves. Both are keyed by tool name rather than
+-- mcp_server_tools.id because rediscovery replaces the tool rows.
+
+CREATE TABLE IF NOT EXISTS mcp_tool_overrides (
+    server_id UUID NOT NULL REFERENCES mcp_servers(id) ON DELETE CASCADE,
+    tool_name TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (server_id, tool_name)
+);
+
+CREATE TABLE IF NOT EXISTS user_mcp_tool_preferences (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    server_id UUID NOT NULL REFERENCES mcp_servers(id) ON DELETE CASCADE,
+    tool_name TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, server_id, tool_name)
+);
+
+CREATE INDEX IF NOT EXISTS user_mcp_tool_preferences_server_idx
+    ON user_mcp_tool_preferences (server_id);
@@ -0,0 +1,44 @@
+-- Soft limits for database storage. Crossing one notifies administrators;
+-- nothing is deleted automatically.
+
+-- system.storage.warnMb — size at which storage is reported as a warning
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'system.storage.warnMb',
+    'system',
+    'number',
+    'number',
+    '2048',
+    'Storage Warning Threshold',
+    'Database size in MiB (the data directory for the embedded database) at which administrators are warned. 0 disables the warning.',
+    '[{"type":"min","value":0,"message":"Threshold cannot be negative"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+-- system.storage.criticalMb — size at which storage is reported as critical
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'system.storage.criticalMb',
+    'system',
+    'number',
+    'number',
+    '8192',
+    'Storage Critical Threshold',
+    'Database size in MiB at which administrators are alerted that storage is critical. 0 disables the alert.',
+    '[{"type":"min","value":0,"message":"Threshold cannot be negative"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
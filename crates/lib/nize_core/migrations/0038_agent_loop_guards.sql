@@ -0,0 +1,76 @@
+-- Guards for the multi-step tool-call loop, and per-iteration traces of
+-- chat turns for debugging.
+
+CREATE TABLE IF NOT EXISTS chat_traces (
+    id UUID PRIMARY KEY,
+    conversation_id UUID NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    message_id TEXT NOT NULL,
+    events JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS chat_traces_conversation_idx
+    ON chat_traces (conversation_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS chat_traces_expires_idx ON chat_traces (expires_at);
+
+-- agent.tools.maxTokensPerTurn — token budget for one user turn
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'agent.tools.maxTokensPerTurn',
+    'agent',
+    'number',
+    'number',
+    '200000',
+    'Max Tokens per Turn',
+    'Stop calling tools once the model steps of one reply have used this many input and output tokens in total. 0 means unlimited.',
+    '[{"type":"min","value":0,"message":"Token budget cannot be negative"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+-- agent.tools.maxRepeatedCalls — stop when the model repeats itself
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'agent.tools.maxRepeatedCalls',
+    'agent',
+    'number',
+    'number',
+    '3',
+    'Max Repeated Tool Calls',
+    'Stop calling tools when the model makes the same tool call with the same arguments this many times in one reply. 0 disables the check.',
+    '[{"type":"min","value":0,"message":"Value cannot be negative"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+-- agent.trace.enabled — record per-step traces of chat replies
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'agent.trace.enabled',
+    'agent',
+    'boolean',
+    'boolean',
+    'false',
+    'Record Chat Traces',
+    'Record each model step of a reply (tool calls, token usage, why the loop stopped) for the developer trace view. Traces are kept for 7 days.'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
@@ -0,0 +1,6 @@
+-- Per-conversation tool selection. NULL means the conversation uses the
+-- user's MCP preferences; otherwise a JSON object
+-- {"serverIds": [uuid, ...], "domains": [text, ...]} restricting tool
+-- discovery and execution in the conversation to those servers and domains.
+
+ALTER TABLE conversations ADD COLUMN IF NOT EXISTS tool_selection JSONB;
@@ -0,0 +1,31 @@
+-- Versioned snapshots of the system-scope config. Every admin change to a
+-- system value records the full resulting state, so any version can be
+-- listed with its diff against the previous one and restored. Secret values
+-- are stored as found in config_values (encrypted).
+
+CREATE TABLE IF NOT EXISTS config_snapshots (
+    version INTEGER GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    -- {"<key>": "<stored value>", ...} of all system-scope values
+    "values" JSONB NOT NULL,
+    -- 'baseline' (state before the first recorded change), 'update' or 'rollback'
+    action TEXT NOT NULL,
+    -- Changed key for 'update'
+    key TEXT,
+    -- Restored version for 'rollback'
+    restored_from INTEGER,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Audit log of admin config changes.
+CREATE TABLE IF NOT EXISTS config_audit (
+    id UUID PRIMARY KEY,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    -- 'config.update' or 'config.rollback'
+    action TEXT NOT NULL,
+    key TEXT,
+    details JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS config_audit_created_idx ON config_audit (created_at);
@@ -0,0 +1,23 @@
+-- Per-user time zone for schedules and displayed times. Names are checked
+-- against the database's tz database when set.
+
+-- ui.timezone — IANA zone name (e.g. Europe/Berlin)
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'ui.timezone',
+    'ui',
+    'string',
+    'text',
+    'UTC',
+    'Time Zone',
+    'IANA time zone (e.g. Europe/Berlin) in which scheduled tasks run and times are shown.',
+    '[{"type":"required","message":"Time zone is required"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
@@ -0,0 +1,13 @@
+-- Admin-defined MCP catalog entries. The shipped catalog is embedded in the
+-- binary; entries here extend it, and an entry whose slug matches a
+-- shipped one replaces it.
+
+CREATE TABLE IF NOT EXISTS mcp_catalog_entries (
+    slug TEXT PRIMARY KEY,
+    -- The full catalog entry (name, description, domain, config template,
+    -- OAuth config and secret descriptions)
+    entry JSONB NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
@@ -0,0 +1,39 @@
+-- Tool-call recording sessions. While a session is active, every tool call
+-- by its user and/or to its server is captured in mcp_recorded_calls so the
+-- session can be exported and replayed.
+
+CREATE TABLE IF NOT EXISTS mcp_recording_sessions (
+    id UUID PRIMARY KEY,
+    label TEXT NOT NULL DEFAULT '',
+    -- Scope: calls by this user, to this server, or both when both are set
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    server_id UUID REFERENCES mcp_servers(id) ON DELETE CASCADE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    stopped_at TIMESTAMPTZ,
+    call_count INTEGER NOT NULL DEFAULT 0,
+    CHECK (user_id IS NOT NULL OR server_id IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_mcp_recording_sessions_active
+    ON mcp_recording_sessions (expires_at)
+    WHERE stopped_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS mcp_recorded_calls (
+    session_id UUID NOT NULL REFERENCES mcp_recording_sessions(id) ON DELETE CASCADE,
+    -- Position in the session, from 1
+    seq INTEGER NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Kept without a foreign key so a call stays replayable against another
+    -- server after its own is removed
+    server_id UUID NOT NULL,
+    server_name TEXT NOT NULL,
+    tool_name TEXT NOT NULL,
+    arguments JSONB,
+    -- {"kind": "result", "result": ...} or {"kind": "error", "category", "message"}
+    outcome JSONB NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (session_id, seq)
+);
@@ -0,0 +1,124 @@
+-- ANN index tuning for the embedding tables. Build parameters apply when an
+-- index is created or rebuilt; search parameters apply per query.
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, possible_values, validators)
+VALUES (
+    'embedding.index.method',
+    'embedding',
+    'string',
+    'selector',
+    'hnsw',
+    'ANN Index Method',
+    'Index type built per embedding model: hnsw (better recall and latency, slower to build) or ivfflat (faster to build, smaller). Existing indexes change on rebuild',
+    '["hnsw","ivfflat"]'::jsonb,
+    '[{"type":"required","message":"Index method is required"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    possible_values = EXCLUDED.possible_values,
+    validators = EXCLUDED.validators;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'embedding.index.hnsw.m',
+    'embedding',
+    'number',
+    'number',
+    '16',
+    'HNSW Connections (m)',
+    'Connections per node in HNSW indexes. Higher improves recall at the cost of build time and index size',
+    '[{"type":"min","value":2,"message":"m must be at least 2"},{"type":"max","value":100,"message":"m must be at most 100"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'embedding.index.hnsw.efConstruction',
+    'embedding',
+    'number',
+    'number',
+    '64',
+    'HNSW Build Candidates (ef_construction)',
+    'Candidate list size while building HNSW indexes. Higher improves recall at the cost of build time; raised to at least 2 × m',
+    '[{"type":"min","value":4,"message":"ef_construction must be at least 4"},{"type":"max","value":1000,"message":"ef_construction must be at most 1000"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'embedding.index.ivfflat.lists',
+    'embedding',
+    'number',
+    'number',
+    '0',
+    'IVFFlat Lists',
+    'Number of lists in IVFFlat indexes. 0 derives it from the row count at build time (rows / 1000, or its square root above a million rows)',
+    '[{"type":"min","value":0,"message":"Lists must be non-negative"},{"type":"max","value":32768,"message":"Lists must be at most 32768"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'embedding.search.hnsw.efSearch',
+    'embedding',
+    'number',
+    'number',
+    '40',
+    'HNSW Search Candidates (ef_search)',
+    'Candidate list size while searching HNSW indexes. Higher improves recall at the cost of latency',
+    '[{"type":"min","value":1,"message":"ef_search must be at least 1"},{"type":"max","value":1000,"message":"ef_search must be at most 1000"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'embedding.search.ivfflat.probes',
+    'embedding',
+    'number',
+    'number',
+    '1',
+    'IVFFlat Probes',
+    'Lists probed while searching IVFFlat indexes. Higher improves recall at the cost of latency',
+    '[{"type":"min","value":1,"message":"Probes must be at least 1"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
@@ -0,0 +1,192 @@
+-- Content moderation: admin-managed keyword/regex rules, an optional
+-- moderation provider, and the results of every check that matched, kept
+-- for admin review.
+
+CREATE TABLE IF NOT EXISTS moderation_rules (
+    id UUID PRIMARY KEY,
+    label TEXT NOT NULL,
+    -- 'keyword' matches the whole word case-insensitively; 'regex' is a
+    -- Rust regex
+    kind TEXT NOT NULL CHECK (kind IN ('keyword', 'regex')),
+    pattern TEXT NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('flag', 'redact', 'block')),
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS moderation_results (
+    id UUID PRIMARY KEY,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    source TEXT NOT NULL CHECK (source IN ('prompt', 'response', 'document')),
+    -- Strongest action taken
+    action TEXT NOT NULL CHECK (action IN ('flag', 'redact', 'block')),
+    -- [{"origin": "rule" | "provider", "label", "action"}]
+    findings JSONB NOT NULL,
+    -- Start of the checked content, before redaction
+    excerpt TEXT NOT NULL DEFAULT '',
+    -- The document for 'document' results
+    resource_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    reviewed_at TIMESTAMPTZ,
+    reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    review_note TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_moderation_results_pending
+    ON moderation_results (created_at DESC)
+    WHERE reviewed_at IS NULL;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'moderation.enabled',
+    'moderation',
+    'boolean',
+    'boolean',
+    'false',
+    'Content Moderation',
+    'Check chat prompts, model responses and ingested documents against the moderation rules and provider'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'moderation.checkPrompts',
+    'moderation',
+    'boolean',
+    'boolean',
+    'true',
+    'Moderate Prompts',
+    'Check the latest user message of each chat request before it is sent to the model'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'moderation.checkResponses',
+    'moderation',
+    'boolean',
+    'boolean',
+    'true',
+    'Moderate Responses',
+    'Check model responses before they reach the user. Streamed responses are buffered while this is on'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'moderation.checkDocuments',
+    'moderation',
+    'boolean',
+    'boolean',
+    'true',
+    'Moderate Documents',
+    'Check the extracted text of uploaded documents before it is indexed'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, possible_values, validators)
+VALUES (
+    'moderation.provider',
+    'moderation',
+    'string',
+    'selector',
+    'none',
+    'Moderation Provider',
+    'External moderation service consulted in addition to the rules (none uses the rules only)',
+    '["none","openai"]'::jsonb,
+    '[{"type":"required","message":"Moderation provider is required"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    possible_values = EXCLUDED.possible_values,
+    validators = EXCLUDED.validators;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, possible_values, validators)
+VALUES (
+    'moderation.providerAction',
+    'moderation',
+    'string',
+    'selector',
+    'flag',
+    'Provider Action',
+    'What to do with content the provider flags: flag it for review, or block it',
+    '["flag","block"]'::jsonb,
+    '[{"type":"required","message":"Provider action is required"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    possible_values = EXCLUDED.possible_values,
+    validators = EXCLUDED.validators;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'moderation.openai.model',
+    'moderation',
+    'string',
+    'text',
+    'omni-moderation-latest',
+    'OpenAI Moderation Model',
+    'Model used by the openai provider',
+    '[{"type":"required","message":"Moderation model is required"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'moderation.openai.apiKey',
+    'moderation',
+    'string',
+    'secret',
+    '',
+    'OpenAI Moderation API Key',
+    'API key for the openai provider'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
@@ -0,0 +1,175 @@
+-- Query-time reranking of vector search candidates. A cross-encoder scores
+-- the top candidates against the query; each use case is switched on
+-- separately and has its own latency budget.
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, possible_values, validators)
+VALUES (
+    'embedding.rerank.provider',
+    'embedding',
+    'string',
+    'selector',
+    'none',
+    'Reranker',
+    'Reranking service: none, tei (a local cross-encoder served by text-embeddings-inference) or api (a Cohere-compatible rerank API such as Cohere, Jina or Voyage)',
+    '["none","tei","api"]'::jsonb,
+    '[{"type":"required","message":"Reranker is required"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    possible_values = EXCLUDED.possible_values,
+    validators = EXCLUDED.validators;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'embedding.rerank.endpoint',
+    'embedding',
+    'string',
+    'text',
+    '',
+    'Reranker Endpoint',
+    'tei: the server base URL, e.g. http://localhost:8080. api: the full rerank URL, e.g. https://api.cohere.com/v2/rerank'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'embedding.rerank.model',
+    'embedding',
+    'string',
+    'text',
+    'rerank-v3.5',
+    'Reranker Model',
+    'Model name sent to the api reranker; tei serves a single model and ignores it'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'embedding.rerank.apiKey',
+    'embedding',
+    'string',
+    'secret',
+    '',
+    'Reranker API Key',
+    'Bearer token for the reranker; optional for tei'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'embedding.rerank.candidates',
+    'embedding',
+    'number',
+    'number',
+    '50',
+    'Rerank Candidates',
+    'Vector search results passed to the reranker per query; the best of them by rerank score are returned',
+    '[{"type":"min","value":1,"message":"Candidates must be at least 1"},{"type":"max","value":500,"message":"Candidates must be at most 500"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'embedding.rerank.tools.enabled',
+    'embedding',
+    'boolean',
+    'boolean',
+    'false',
+    'Rerank Tool Discovery',
+    'Rerank tool discovery results when a reranker is configured'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'embedding.rerank.tools.timeoutMs',
+    'embedding',
+    'number',
+    'number',
+    '300',
+    'Tool Discovery Rerank Budget (ms)',
+    'Longest tool discovery waits for the reranker; past it the vector order is kept',
+    '[{"type":"min","value":10,"message":"Budget must be at least 10 ms"},{"type":"max","value":30000,"message":"Budget must be at most 30000 ms"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'embedding.rerank.documents.enabled',
+    'embedding',
+    'boolean',
+    'boolean',
+    'false',
+    'Rerank Document Search',
+    'Rerank document search results when a reranker is configured'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'embedding.rerank.documents.timeoutMs',
+    'embedding',
+    'number',
+    'number',
+    '1000',
+    'Document Search Rerank Budget (ms)',
+    'Longest document search waits for the reranker; past it the vector order is kept',
+    '[{"type":"min","value":10,"message":"Budget must be at least 10 ms"},{"type":"max","value":30000,"message":"Budget must be at most 30000 ms"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
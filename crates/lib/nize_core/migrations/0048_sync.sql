@@ -0,0 +1,95 @@
+-- Change tracking for delta sync (GET/POST /sync). Each synced row records
+-- the transaction that last wrote it in change_txid, and deleting a row
+-- leaves a tombstone. Sync cursors are positions in transaction order, so
+-- a pull never hands out a position past a transaction that is still
+-- running and a late commit cannot be skipped.
+--
+-- Synced: the user's personal conversations (their messages are written
+-- together with the conversation row), their documents, and their config
+-- overrides.
+
+ALTER TABLE conversations
+    ADD COLUMN IF NOT EXISTS change_txid xid8 NOT NULL DEFAULT pg_current_xact_id();
+ALTER TABLE documents
+    ADD COLUMN IF NOT EXISTS change_txid xid8 NOT NULL DEFAULT pg_current_xact_id();
+ALTER TABLE config_values
+    ADD COLUMN IF NOT EXISTS change_txid xid8 NOT NULL DEFAULT pg_current_xact_id();
+
+CREATE INDEX IF NOT EXISTS idx_conversations_user_change ON conversations(user_id, change_txid);
+CREATE INDEX IF NOT EXISTS idx_documents_user_change ON documents(user_id, change_txid);
+CREATE INDEX IF NOT EXISTS idx_config_values_user_change ON config_values(user_id, change_txid);
+
+CREATE OR REPLACE FUNCTION sync_touch() RETURNS trigger AS $$
+BEGIN
+    NEW.change_txid := pg_current_xact_id();
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS conversations_sync_touch ON conversations;
+CREATE TRIGGER conversations_sync_touch BEFORE UPDATE ON conversations
+    FOR EACH ROW EXECUTE FUNCTION sync_touch();
+DROP TRIGGER IF EXISTS documents_sync_touch ON documents;
+CREATE TRIGGER documents_sync_touch BEFORE UPDATE ON documents
+    FOR EACH ROW EXECUTE FUNCTION sync_touch();
+DROP TRIGGER IF EXISTS config_values_sync_touch ON config_values;
+CREATE TRIGGER config_values_sync_touch BEFORE UPDATE ON config_values
+    FOR EACH ROW EXECUTE FUNCTION sync_touch();
+
+-- Deleted rows. entity_id is the row's id, or the key for config. No
+-- foreign key on user_id: rows removed together with their user get no
+-- tombstone, and the user's tombstones are dropped with them.
+CREATE TABLE IF NOT EXISTS sync_tombstones (
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL,
+    entity TEXT NOT NULL CHECK (entity IN ('conversation', 'document', 'config')),
+    entity_id TEXT NOT NULL,
+    change_txid xid8 NOT NULL DEFAULT pg_current_xact_id(),
+    deleted_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_sync_tombstones_user_change ON sync_tombstones(user_id, change_txid);
+CREATE INDEX IF NOT EXISTS idx_sync_tombstones_deleted_at ON sync_tombstones(deleted_at);
+
+-- Trigger arguments: the entity name and the column holding its id.
+CREATE OR REPLACE FUNCTION sync_tombstone() RETURNS trigger AS $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM users WHERE id = OLD.user_id) THEN
+        INSERT INTO sync_tombstones (user_id, entity, entity_id)
+        VALUES (OLD.user_id, TG_ARGV[0], to_jsonb(OLD) ->> TG_ARGV[1]);
+    END IF;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS conversations_sync_tombstone ON conversations;
+CREATE TRIGGER conversations_sync_tombstone AFTER DELETE ON conversations
+    FOR EACH ROW WHEN (OLD.workspace_id IS NULL)
+    EXECUTE FUNCTION sync_tombstone('conversation', 'id');
+DROP TRIGGER IF EXISTS documents_sync_tombstone ON documents;
+CREATE TRIGGER documents_sync_tombstone AFTER DELETE ON documents
+    FOR EACH ROW EXECUTE FUNCTION sync_tombstone('document', 'id');
+DROP TRIGGER IF EXISTS config_values_sync_tombstone ON config_values;
+CREATE TRIGGER config_values_sync_tombstone AFTER DELETE ON config_values
+    FOR EACH ROW WHEN (OLD.scope = 'user-override' AND OLD.user_id IS NOT NULL)
+    EXECUTE FUNCTION sync_tombstone('config', 'key');
+
+CREATE OR REPLACE FUNCTION sync_drop_user_tombstones() RETURNS trigger AS $$
+BEGIN
+    DELETE FROM sync_tombstones WHERE user_id = OLD.id;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS users_sync_drop_tombstones ON users;
+CREATE TRIGGER users_sync_drop_tombstones AFTER DELETE ON users
+    FOR EACH ROW EXECUTE FUNCTION sync_drop_user_tombstones();
+
+-- Tombstones are pruned after a while; a cursor from before the newest
+-- pruned tombstone may have missed deletions and must start over.
+CREATE TABLE IF NOT EXISTS sync_state (
+    id BOOLEAN PRIMARY KEY DEFAULT true CHECK (id),
+    pruned_before BIGINT NOT NULL DEFAULT 0
+);
+
+INSERT INTO sync_state (id) VALUES (true) ON CONFLICT (id) DO NOTHING;
@@ -0,0 +1,17 @@
+-- Pending MCP OAuth authorizations, kept between initiate and callback so a
+-- flow survives an API restart. Rows are keyed by a hash of the state
+-- parameter, consumed by the callback and pruned once expired.
+
+CREATE TABLE IF NOT EXISTS mcp_oauth_pending (
+    state_hash TEXT PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    server_id UUID NOT NULL REFERENCES mcp_servers(id) ON DELETE CASCADE,
+    pkce_verifier_encrypted TEXT NOT NULL,
+    -- Snapshot of the server's OAuth config when the flow started
+    oauth_config JSONB NOT NULL,
+    redirect_uri TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_mcp_oauth_pending_expires ON mcp_oauth_pending (expires_at);
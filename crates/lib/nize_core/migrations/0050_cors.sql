@@ -0,0 +1,21 @@
+-- Browser origins allowed to call the API with credentials, editable at
+-- runtime on top of the deployment's configured origins.
+
+-- cors.allowedOrigins — comma-separated origins; `*.` and `:*` wildcards
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'cors.allowedOrigins',
+    'system',
+    'string',
+    'text',
+    '',
+    'Allowed Origins',
+    'Comma-separated browser origins allowed to call the API with cookies, in addition to those set at deployment (e.g. https://nize.example.com, https://*.example.com, http://localhost:*). With none set, only loopback origins are allowed.'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
@@ -0,0 +1,21 @@
+-- Alternative assistant replies produced by regenerating a turn. Message rows
+-- are replaced whenever a conversation is saved, so candidates are keyed by
+-- the UIMessage id of the reply they stand in for. The first regeneration
+-- also stores the original reply; exactly one candidate per message is
+-- selected, and its content is what the conversation shows.
+
+CREATE TABLE IF NOT EXISTS message_candidates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    conversation_id UUID NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    message_id TEXT NOT NULL,
+    message_data JSONB NOT NULL,
+    -- Sampling overrides the candidate was generated with; NULL for the original
+    params JSONB,
+    selected BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_candidates_message
+    ON message_candidates (conversation_id, message_id, created_at);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_message_candidates_selected
+    ON message_candidates (conversation_id, message_id) WHERE selected;
@@ -0,0 +1,4 @@
+-- Read-only MCP tokens: sessions authenticated with one may list, search and
+-- browse, but not run tools that change anything.
+
+ALTER TABLE mcp_tokens ADD COLUMN IF NOT EXISTS read_only BOOLEAN NOT NULL DEFAULT false;
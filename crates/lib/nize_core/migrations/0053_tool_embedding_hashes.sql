@@ -0,0 +1,12 @@
+-- Tool embeddings record a hash of the tool name and description they were
+-- computed from, so re-indexing skips unchanged tools and admins can list
+-- servers whose tool descriptions changed since they were last embedded.
+
+ALTER TABLE tool_embeddings ADD COLUMN IF NOT EXISTS content_hash TEXT;
+
+-- Until now tool rows were replaced on every discovery, dropping their
+-- embeddings, so every existing embedding matches its tool's current text.
+UPDATE tool_embeddings e
+SET content_hash = encode(sha256(convert_to(t.name || E'\n' || t.description, 'UTF8')), 'hex')
+FROM mcp_server_tools t
+WHERE t.id = e.tool_id AND e.content_hash IS NULL;
@@ -0,0 +1,72 @@
+-- Ingest connectors: external sources kept in sync with a user's documents.
+
+-- ---------------------------------------------------------------------------
+-- connectors: A source (e.g. a local folder) synced on a cron schedule
+-- ---------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS connectors (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Connector type: 'filesystem'
+    kind VARCHAR(50) NOT NULL,
+    name VARCHAR(200) NOT NULL,
+    -- Where the connector reads from; a directory path for 'filesystem'
+    source TEXT NOT NULL,
+    -- Cron expression evaluated in UTC
+    schedule VARCHAR(200) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    next_sync_at TIMESTAMPTZ,
+    last_sync_at TIMESTAMPTZ,
+    last_status VARCHAR(20),
+    last_error TEXT,
+    -- Outcome of the last successful sync
+    files_added INT NOT NULL DEFAULT 0,
+    files_updated INT NOT NULL DEFAULT 0,
+    files_deleted INT NOT NULL DEFAULT 0,
+    -- Files currently tracked
+    file_count INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS connectors_user_idx ON connectors (user_id);
+CREATE INDEX IF NOT EXISTS connectors_due_idx ON connectors (next_sync_at) WHERE enabled;
+
+-- ---------------------------------------------------------------------------
+-- connector_items: The state of each source file at its last sync
+-- ---------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS connector_items (
+    connector_id UUID NOT NULL REFERENCES connectors(id) ON DELETE CASCADE,
+    -- Path relative to the source, '/'-separated
+    path TEXT NOT NULL,
+    -- The document it was ingested as; NULL when it was not ingested (too
+    -- large, blocked by moderation) or the user deleted the document
+    document_id UUID REFERENCES documents(id) ON DELETE SET NULL,
+    size_bytes BIGINT NOT NULL,
+    modified_at TIMESTAMPTZ NOT NULL,
+    sha256 VARCHAR(64) NOT NULL,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (connector_id, path)
+);
+
+CREATE INDEX IF NOT EXISTS connector_items_document_idx ON connector_items (document_id);
+
+-- ingest.connectors.roots
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'ingest.connectors.roots',
+    'ingest',
+    'string',
+    'text',
+    '',
+    'Connector Folders',
+    'Comma-separated directories that folder connectors may sync from, including their subdirectories. Empty disables folder connectors.'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
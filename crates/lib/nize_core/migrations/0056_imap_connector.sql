@@ -0,0 +1,44 @@
+-- Email (IMAP) connector: kind-specific settings and credentials on
+-- connectors, per-folder sync positions, and the metadata of ingested
+-- messages for citation.
+
+-- Kind-specific options, e.g. {"folders": ["INBOX"], "attachments": false}
+ALTER TABLE connectors ADD COLUMN IF NOT EXISTS settings JSONB NOT NULL DEFAULT '{}';
+-- Credential (e.g. an IMAP password), encrypted with the server's secret key
+ALTER TABLE connectors ADD COLUMN IF NOT EXISTS secret TEXT;
+
+-- ---------------------------------------------------------------------------
+-- connector_cursors: How far a connector has synced each part of its source
+-- ---------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS connector_cursors (
+    connector_id UUID NOT NULL REFERENCES connectors(id) ON DELETE CASCADE,
+    -- Part of the source, e.g. an IMAP folder
+    scope TEXT NOT NULL,
+    -- Position in that part, in a connector-specific format
+    cursor TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (connector_id, scope)
+);
+
+-- ---------------------------------------------------------------------------
+-- email_messages: The message a document was ingested from
+-- ---------------------------------------------------------------------------
+
+CREATE TABLE IF NOT EXISTS email_messages (
+    document_id UUID PRIMARY KEY REFERENCES documents(id) ON DELETE CASCADE,
+    connector_id UUID REFERENCES connectors(id) ON DELETE SET NULL,
+    folder TEXT NOT NULL,
+    uid BIGINT NOT NULL,
+    -- Message-ID header, without angle brackets
+    message_id TEXT,
+    subject TEXT NOT NULL DEFAULT '',
+    sender TEXT NOT NULL DEFAULT '',
+    recipients TEXT NOT NULL DEFAULT '',
+    sent_at TIMESTAMPTZ,
+    -- Attachment file name; NULL for the message text itself
+    attachment TEXT
+);
+
+CREATE INDEX IF NOT EXISTS email_messages_connector_folder_idx
+    ON email_messages (connector_id, folder, uid);
@@ -0,0 +1,131 @@
+-- Content-addressed chunk store shared by documents and notes. Chunk text
+-- is stored once per distinct content in chunks, keyed by its SHA-256, and
+-- embedded once per model in chunk_embeddings; document_chunks and
+-- note_chunks map each document or note to the chunks it consists of.
+-- ref_count, the number of mappings to a chunk, is kept by triggers, so it
+-- stays right when documents, notes or users are deleted.
+
+CREATE TABLE IF NOT EXISTS chunks (
+    -- Hex SHA-256 of the UTF-8 content
+    hash TEXT PRIMARY KEY,
+    content TEXT NOT NULL,
+    ref_count INTEGER NOT NULL DEFAULT 0 CHECK (ref_count >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Chunks nothing maps to any more, due for removal
+CREATE INDEX IF NOT EXISTS chunks_unreferenced_idx ON chunks(hash) WHERE ref_count = 0;
+
+CREATE TABLE IF NOT EXISTS chunk_embeddings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    chunk_hash TEXT NOT NULL REFERENCES chunks(hash) ON DELETE CASCADE,
+    model_id UUID NOT NULL REFERENCES embedding_models(id) ON DELETE CASCADE,
+    model VARCHAR(100) NOT NULL,
+    dimensions INTEGER NOT NULL,
+    embedding VECTOR NOT NULL,
+    truncated BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (vector_dims(embedding) = dimensions)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS chunk_embeddings_chunk_model_idx
+    ON chunk_embeddings(chunk_hash, model_id);
+
+-- ---------------------------------------------------------------------------
+-- Move chunk text into the store
+-- ---------------------------------------------------------------------------
+
+ALTER TABLE document_chunks ADD COLUMN IF NOT EXISTS chunk_hash TEXT;
+ALTER TABLE note_chunks ADD COLUMN IF NOT EXISTS chunk_hash TEXT;
+
+UPDATE document_chunks
+SET chunk_hash = encode(sha256(convert_to(content, 'UTF8')), 'hex')
+WHERE chunk_hash IS NULL;
+UPDATE note_chunks
+SET chunk_hash = encode(sha256(convert_to(content, 'UTF8')), 'hex')
+WHERE chunk_hash IS NULL;
+
+INSERT INTO chunks (hash, content, ref_count)
+SELECT chunk_hash, MIN(content), COUNT(*)
+FROM (
+    SELECT chunk_hash, content FROM document_chunks
+    UNION ALL
+    SELECT chunk_hash, content FROM note_chunks
+) mapped
+GROUP BY chunk_hash
+ON CONFLICT (hash) DO NOTHING;
+
+-- Keep one existing vector per content and model, so nothing has to be
+-- embedded again. They were computed with the document filename or note
+-- title in front of the chunk; new embeddings are of the chunk alone.
+INSERT INTO chunk_embeddings
+    (chunk_hash, model_id, model, dimensions, embedding, truncated, created_at)
+SELECT DISTINCT ON (c.chunk_hash, e.model_id)
+       c.chunk_hash, e.model_id, e.model, e.dimensions, e.embedding, e.truncated, e.created_at
+FROM document_chunk_embeddings e
+JOIN document_chunks c ON c.id = e.chunk_id
+ORDER BY c.chunk_hash, e.model_id, e.created_at DESC
+ON CONFLICT (chunk_hash, model_id) DO NOTHING;
+
+INSERT INTO chunk_embeddings
+    (chunk_hash, model_id, model, dimensions, embedding, truncated, created_at)
+SELECT DISTINCT ON (c.chunk_hash, e.model_id)
+       c.chunk_hash, e.model_id, e.model, e.dimensions, e.embedding, e.truncated, e.created_at
+FROM note_chunk_embeddings e
+JOIN note_chunks c ON c.id = e.chunk_id
+ORDER BY c.chunk_hash, e.model_id, e.created_at DESC
+ON CONFLICT (chunk_hash, model_id) DO NOTHING;
+
+DROP TABLE IF EXISTS document_chunk_embeddings;
+DROP TABLE IF EXISTS note_chunk_embeddings;
+
+ALTER TABLE document_chunks ALTER COLUMN chunk_hash SET NOT NULL;
+ALTER TABLE document_chunks
+    ADD CONSTRAINT document_chunks_chunk_hash_fkey FOREIGN KEY (chunk_hash) REFERENCES chunks(hash);
+ALTER TABLE document_chunks DROP COLUMN IF EXISTS content;
+CREATE INDEX IF NOT EXISTS document_chunks_chunk_hash_idx ON document_chunks(chunk_hash);
+
+ALTER TABLE note_chunks ALTER COLUMN chunk_hash SET NOT NULL;
+ALTER TABLE note_chunks
+    ADD CONSTRAINT note_chunks_chunk_hash_fkey FOREIGN KEY (chunk_hash) REFERENCES chunks(hash);
+ALTER TABLE note_chunks DROP COLUMN IF EXISTS content;
+CREATE INDEX IF NOT EXISTS note_chunks_chunk_hash_idx ON note_chunks(chunk_hash);
+
+-- ---------------------------------------------------------------------------
+-- Reference counting
+-- ---------------------------------------------------------------------------
+
+CREATE OR REPLACE FUNCTION chunk_ref_count() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        UPDATE chunks SET ref_count = ref_count + 1 WHERE hash = NEW.chunk_hash;
+    END IF;
+    IF TG_OP IN ('DELETE', 'UPDATE') THEN
+        UPDATE chunks SET ref_count = ref_count - 1 WHERE hash = OLD.chunk_hash;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS document_chunks_ref_count ON document_chunks;
+CREATE TRIGGER document_chunks_ref_count
+    AFTER INSERT OR DELETE OR UPDATE OF chunk_hash ON document_chunks
+    FOR EACH ROW EXECUTE FUNCTION chunk_ref_count();
+DROP TRIGGER IF EXISTS note_chunks_ref_count ON note_chunks;
+CREATE TRIGGER note_chunks_ref_count
+    AFTER INSERT OR DELETE OR UPDATE OF chunk_hash ON note_chunks
+    FOR EACH ROW EXECUTE FUNCTION chunk_ref_count();
+
+-- One partial HNSW index per registered model, as for the tables replaced.
+DO $$
+DECLARE
+    m RECORD;
+BEGIN
+    FOR m IN SELECT * FROM embedding_models LOOP
+        EXECUTE format(
+            'CREATE INDEX IF NOT EXISTS %I ON chunk_embeddings
+                 USING hnsw ((embedding::vector(%s)) vector_cosine_ops)
+                 WHERE model_id = %L',
+            'chunk_embeddings_' || replace(m.id::text, '-', '') || '_hnsw',
+            m.dimensions, m.id);
+    END LOOP;
+END $$;
@@ -0,0 +1,19 @@
+-- Per-tool output transformation templates. Admins can narrow a tool's
+-- output to what the model needs (a JSONPath selection) and cap its size
+-- before it is returned to the model. Keyed by tool name rather than
+-- mcp_server_tools.id because rediscovery replaces the tool rows.
+
+CREATE TABLE IF NOT EXISTS mcp_tool_output_transforms (
+    server_id UUID NOT NULL REFERENCES mcp_servers(id) ON DELETE CASCADE,
+    tool_name TEXT NOT NULL,
+    -- JSONPath applied to JSON text output, e.g. $.items[*].title
+    selector TEXT,
+    -- Truncation rules; NULL leaves that dimension unlimited
+    max_array_items INTEGER CHECK (max_array_items > 0),
+    max_string_chars INTEGER CHECK (max_string_chars > 0),
+    max_output_chars INTEGER CHECK (max_output_chars > 0),
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (server_id, tool_name)
+);
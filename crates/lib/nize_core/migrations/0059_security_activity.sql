@@ -0,0 +1,17 @@
+-- Security-relevant events per user (sign-ins, MCP token changes, roles
+-- granted), shown to the user on their security activity page.
+
+CREATE TABLE IF NOT EXISTS security_activity (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    -- Client that caused the event; NULL when it did not come from the user
+    -- (e.g. a role granted by an admin)
+    ip_address TEXT,
+    user_agent TEXT,
+    details JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_security_activity_user_created
+    ON security_activity(user_id, created_at DESC);
@@ -0,0 +1,46 @@
+-- OpenAI-compatible provider endpoints (vLLM, LM Studio, llama.cpp, ...)
+-- configured at runtime instead of in code. Both values are validated when
+-- saved; saving the provider list registers its embedding models.
+
+-- providers.compatible — JSON array of endpoints
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'providers.compatible',
+    'providers',
+    'string',
+    'longText',
+    '',
+    'OpenAI-Compatible Providers',
+    'JSON array of OpenAI-compatible endpoints, e.g. [{"name":"vllm","baseUrl":"http://localhost:8000/v1","models":["meta-llama/Llama-3.1-8B-Instruct"],"embeddingModels":[{"name":"BAAI/bge-m3","dimensions":1024,"maxInputTokens":8192}]}]. Chat models are selected as name:model; providers with embedding models become embedding provider choices. authHeader (default Authorization) and authPrefix (default "Bearer ") set how the API key is sent.'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- providers.compatible.apiKeys — JSON object of API keys by provider name
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'providers.compatible.apiKeys',
+    'providers',
+    'string',
+    'secret',
+    '',
+    'OpenAI-Compatible Provider API Keys',
+    'JSON object of API keys by provider name, e.g. {"vllm":"sk-..."}. Providers without a key are called without one.'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- The embedding provider choices grow with the configured providers.
+UPDATE config_definitions
+SET description = 'The embedding provider to use (openai requires API key, ollama requires local server, local is deterministic/offline, or an OpenAI-compatible provider with embedding models)'
+WHERE key = 'embedding.provider';
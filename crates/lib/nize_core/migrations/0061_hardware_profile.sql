@@ -0,0 +1,22 @@
+-- Hardware profile reported by the desktop app at startup, used to gate
+-- local embedding and transcription providers and to pick first-run
+-- defaults. Server deployments never set it and are not gated.
+
+-- system.hardware — JSON hardware profile
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'system.hardware',
+    'system',
+    'string',
+    'longText',
+    '',
+    'Hardware Profile',
+    'CPU, memory and GPUs of the desktop machine, reported by the desktop app at every launch. Local embedding and transcription providers are rejected when the machine cannot run them; leave empty to disable the check.'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
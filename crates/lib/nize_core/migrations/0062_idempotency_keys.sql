@@ -0,0 +1,20 @@
+-- Idempotency keys: the first response to a POST carrying an
+-- Idempotency-Key header, kept for a while so a retried request gets the
+-- same response instead of running twice.
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    key TEXT NOT NULL,
+    -- SHA-256 of the method, path, workspace and body the key was first used with
+    fingerprint TEXT NOT NULL,
+    -- NULL while the first request is still running
+    status SMALLINT,
+    content_type TEXT,
+    body BYTEA,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (user_id, key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_expires
+    ON idempotency_keys(expires_at);
@@ -0,0 +1,21 @@
+-- Profile variables in MCP tool arguments: which {{user.*}} and
+-- {{workspace.*}} references the MCP server resolves before a call.
+
+-- mcp.templating.allowedVariables — comma-separated allowlist
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'mcp.templating.allowedVariables',
+    'mcp',
+    'string',
+    'text',
+    'user.id,user.email,user.name,user.timezone,workspace.id,workspace.name',
+    'Tool Argument Variables',
+    'Comma-separated profile variables that tool arguments may reference as {{user.email}}, resolved server-side so the model never sees the values. Known variables: user.id, user.email, user.name, user.timezone, workspace.id, workspace.name. Calls referencing any other variable are refused; leave empty to disable.'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
@@ -0,0 +1,3 @@
+-- Caller context keys (conversationId, workspaceId, locale) a server
+-- receives in the `_meta` of its tool calls; none by default.
+ALTER TABLE mcp_servers ADD COLUMN IF NOT EXISTS forwarded_context TEXT[] NOT NULL DEFAULT '{}';
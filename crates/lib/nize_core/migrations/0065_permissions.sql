@@ -0,0 +1,54 @@
+-- Grants and share links on conversations and documents.
+--
+-- Access levels are ordered: each allows everything the ones before it do.
+--   view     read the resource
+--   comment  also leave feedback on its messages
+--   execute  also continue it (run the model and tools in it)
+--   edit     also change it
+--   full     also share it further
+
+DO $$ BEGIN
+    CREATE TYPE permission_level AS ENUM ('view', 'comment', 'execute', 'edit', 'full');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+DO $$ BEGIN
+    CREATE TYPE permission_resource_type AS ENUM ('conversation', 'document');
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+-- A grant names the grantee by email; grantee_id is set once an account
+-- with that address exists.
+CREATE TABLE IF NOT EXISTS permission_grants (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    granter_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    grantee_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    grantee_email TEXT NOT NULL,
+    resource_type permission_resource_type NOT NULL,
+    resource_id UUID NOT NULL,
+    level permission_level NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS permission_grants_resource_email_idx
+    ON permission_grants(resource_type, resource_id, lower(grantee_email));
+CREATE INDEX IF NOT EXISTS permission_grants_grantee_idx ON permission_grants(grantee_id);
+
+-- Anyone holding a link's token gets its level until it expires or is
+-- revoked. The level can change without a new token.
+CREATE TABLE IF NOT EXISTS share_links (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    owner_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    resource_type permission_resource_type NOT NULL,
+    resource_id UUID NOT NULL,
+    token TEXT NOT NULL UNIQUE,
+    level permission_level NOT NULL,
+    expires_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS share_links_resource_idx ON share_links(resource_type, resource_id);
@@ -0,0 +1,43 @@
+-- Provider price table for estimating what a chat request will cost
+-- before it is sent (POST /chat/estimate). The table is validated when
+-- saved; an empty table prices nothing.
+
+-- agent.pricing — JSON object of prices by model spec
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description)
+VALUES (
+    'agent.pricing',
+    'agent',
+    'string',
+    'longText',
+    '',
+    'Model Prices',
+    'JSON object of model prices in USD per million tokens, keyed by model spec (provider:model, or provider:* for all models of a provider), e.g. {"anthropic:claude-haiku-4-5-20251001":{"inputPerMillion":1,"outputPerMillion":5,"contextWindow":200000}}. contextWindow is optional. Used to estimate costs before sending; nothing is charged.'
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description;
+
+-- agent.pricing.warnAboveUsd — cost above which users are warned
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'agent.pricing.warnAboveUsd',
+    'agent',
+    'number',
+    'number',
+    '1',
+    'Warn Above Cost (USD)',
+    'Ask users to confirm a message whose estimated cost passes this many US dollars. 0 = never warn on cost; requests that exceed the model''s context window are still flagged.',
+    '[{"type":"min","value":0,"message":"Threshold cannot be negative"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
@@ -0,0 +1,9 @@
+-- Conversation and turn (the id of the user message that started it) a
+-- tool execution was made for; NULL outside chat.
+ALTER TABLE mcp_tool_executions
+    ADD COLUMN IF NOT EXISTS conversation_id UUID REFERENCES conversations(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS turn_id TEXT;
+
+CREATE INDEX IF NOT EXISTS mcp_tool_executions_conversation_idx
+    ON mcp_tool_executions (conversation_id, created_at)
+    WHERE conversation_id IS NOT NULL;
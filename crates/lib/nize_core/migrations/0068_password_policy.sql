@@ -0,0 +1,111 @@
+-- Password hashing policy. New hashes use the configured algorithm and work
+-- factors; a stored hash made with others (every bcrypt hash from before
+-- this migration, under the Argon2id default) is re-hashed on the user's
+-- next successful login.
+
+-- system.auth.passwordAlgorithm — algorithm for new password hashes
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, possible_values, validators)
+VALUES (
+    'system.auth.passwordAlgorithm',
+    'system',
+    'string',
+    'selector',
+    'argon2id',
+    'Password Hashing Algorithm',
+    'Algorithm for new password hashes. Existing hashes are upgraded when their users next sign in.',
+    '["argon2id","bcrypt"]'::jsonb,
+    '[{"type":"required","message":"Algorithm is required"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    possible_values = EXCLUDED.possible_values,
+    validators = EXCLUDED.validators;
+
+-- system.auth.passwordBcryptCost — bcrypt work factor
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'system.auth.passwordBcryptCost',
+    'system',
+    'number',
+    'number',
+    '10',
+    'bcrypt Cost',
+    'bcrypt cost factor (10-16) when bcrypt is the hashing algorithm; each step doubles the work',
+    '[{"type":"min","value":10,"message":"Cost must be at least 10"},{"type":"max","value":16,"message":"Cost must be at most 16"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+-- system.auth.passwordArgon2MemoryKib — Argon2id memory cost
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'system.auth.passwordArgon2MemoryKib',
+    'system',
+    'number',
+    'number',
+    '19456',
+    'Argon2id Memory (KiB)',
+    'Memory each Argon2id hash uses, in KiB (at least 9728)',
+    '[{"type":"min","value":9728,"message":"Memory must be at least 9728 KiB"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+-- system.auth.passwordArgon2Iterations — Argon2id time cost
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'system.auth.passwordArgon2Iterations',
+    'system',
+    'number',
+    'number',
+    '2',
+    'Argon2id Iterations',
+    'Passes Argon2id makes over its memory',
+    '[{"type":"min","value":1,"message":"At least one iteration is required"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
+
+-- system.auth.passwordArgon2Parallelism — Argon2id lanes
+INSERT INTO config_definitions (key, category, type, display_type, default_value, label, description, validators)
+VALUES (
+    'system.auth.passwordArgon2Parallelism',
+    'system',
+    'number',
+    'number',
+    '1',
+    'Argon2id Parallelism',
+    'Lanes Argon2id hashes in parallel',
+    '[{"type":"min","value":1,"message":"At least one lane is required"}]'::jsonb
+)
+ON CONFLICT (key) DO UPDATE SET
+    category = EXCLUDED.category,
+    type = EXCLUDED.type,
+    display_type = EXCLUDED.display_type,
+    default_value = EXCLUDED.default_value,
+    label = EXCLUDED.label,
+    description = EXCLUDED.description,
+    validators = EXCLUDED.validators;
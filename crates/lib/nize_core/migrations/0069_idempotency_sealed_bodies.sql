@@ -0,0 +1,11 @@
+-- Stored responses can carry one-time secrets (a new MCP token, for
+-- one), so keep them encrypted like other secrets. Plaintext bodies
+-- already stored are dropped; their keys run again on retry.
+
+DELETE FROM idempotency_keys WHERE status IS NOT NULL;
+
+ALTER TABLE idempotency_keys DROP COLUMN IF EXISTS body;
+
+-- The response body, encrypted with the deployment key and bound to the
+-- user and key
+ALTER TABLE idempotency_keys ADD COLUMN IF NOT EXISTS sealed_body TEXT;
@@ -0,0 +1,18 @@
+-- Documents can belong to a workspace, like conversations and MCP servers
+-- (0021). A document uploaded while a workspace is active is visible to
+-- all of its members; deleting the workspace deletes its documents.
+
+ALTER TABLE documents
+    ADD COLUMN IF NOT EXISTS workspace_id UUID REFERENCES workspaces(id) ON DELETE CASCADE;
+
+-- Backfill: uploads ignored the workspace header until now, so every
+-- existing document is personal to its uploader, which is what the NULL
+-- the column starts with means.
+
+CREATE INDEX IF NOT EXISTS idx_documents_workspace_id ON documents(workspace_id);
+
+-- Only personal documents are synced, so only they leave tombstones.
+DROP TRIGGER IF EXISTS documents_sync_tombstone ON documents;
+CREATE TRIGGER documents_sync_tombstone AFTER DELETE ON documents
+    FOR EACH ROW WHEN (OLD.workspace_id IS NULL)
+    EXECUTE FUNCTION sync_tombstone('document', 'id');
@@ -0,0 +1,1076 @@
+//! Account takeout and erasure.
+//!
+//! [`build_archive`] collects everything a user owns into a portable
+//! [`Archive`]: conversations with their messages, notes, documents with
+//! their contents, tags, tasks, config overrides and MCP server
+//! registrations. Secrets never leave the server — secret config values,
+//! stored API keys / OAuth credentials and custom server headers are left
+//! out.
+//!
+//! Exports run in the background: [`create_export`] records a job in
+//! `account_exports`, [`run_export`] builds and stores the archive. An
+//! archive is restored with [`import_archive`] (under fresh IDs, so it can
+//! be imported into any account), and [`erase_account`] deletes a user
+//! together with all of their data, handing back the blobs only they used
+//! so their bytes can be deleted too.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::sync::Arc;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use thiserror::Error;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::auth::mcp_tokens;
+use crate::auth::rbac::{self, RoleError};
+use crate::blobs::{self, BlobBackend, BlobError, BlobStore};
+use crate::config::cache::ConfigCache;
+use crate::i18n::Message;
+use crate::models::mcp::{ServerConfig, TransportType};
+use crate::tags::{self, TagError, TagResourceType};
+use crate::tasks::{TaskError, schedule};
+use crate::uuid::uuidv7;
+
+/// `format` marker of takeout archives.
+pub const ARCHIVE_FORMAT: &str = "nize-takeout";
+/// Current archive version. Older versions stay importable.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Export job status while the archive is being built.
+pub const STATUS_RUNNING: &str = "running";
+/// Export job status once the archive is ready for download.
+pub const STATUS_COMPLETED: &str = "completed";
+/// Export job status after a failure (see `error`).
+pub const STATUS_FAILED: &str = "failed";
+
+/// Errors that can occur in account operations.
+#[derive(Debug, Error)]
+pub enum AccountError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Not found: {0}")]
+    NotFound(Message),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+
+    #[error("Blob storage error: {0}")]
+    Blob(#[from] BlobError),
+}
+
+impl From<RoleError> for AccountError {
+    fn from(e: RoleError) -> Self {
+        match e {
+            RoleError::Db(e) => AccountError::Db(e),
+            RoleError::NotFound(msg) => AccountError::NotFound(msg),
+            RoleError::Validation(msg) => AccountError::Validation(msg),
+            RoleError::Duplicate(name) => {
+                AccountError::Validation(Message::new("roles.duplicate").arg("name", name))
+            }
+            RoleError::BuiltIn(name) => {
+                AccountError::Validation(Message::new("roles.built_in").arg("name", name))
+            }
+        }
+    }
+}
+
+impl From<TagError> for AccountError {
+    fn from(e: TagError) -> Self {
+        match e {
+            TagError::Db(e) => AccountError::Db(e),
+            TagError::NotFound(msg) => AccountError::NotFound(msg),
+            TagError::Validation(msg) => AccountError::Validation(msg),
+            TagError::Duplicate(name) => {
+                AccountError::Validation(Message::new("tags.duplicate").arg("name", name))
+            }
+        }
+    }
+}
+
+impl From<TaskError> for AccountError {
+    fn from(e: TaskError) -> Self {
+        match e {
+            TaskError::Db(e) => AccountError::Db(e),
+            TaskError::NotFound(msg) => AccountError::NotFound(msg),
+            TaskError::Validation(msg) => AccountError::Validation(msg),
+        }
+    }
+}
+
+// =============================================================================
+// Archive
+// =============================================================================
+
+/// A user's complete data set.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Archive {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub account: ArchiveAccount,
+    #[serde(default)]
+    pub conversations: Vec<ArchiveConversation>,
+    #[serde(default)]
+    pub notes: Vec<ArchiveNote>,
+    #[serde(default)]
+    pub documents: Vec<ArchiveDocument>,
+    #[serde(default)]
+    pub tags: Vec<ArchiveTag>,
+    #[serde(default)]
+    pub tasks: Vec<ArchiveTask>,
+    #[serde(default)]
+    pub config: Vec<ArchiveConfigValue>,
+    #[serde(default)]
+    pub mcp_servers: Vec<ArchiveMcpServer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveAccount {
+    pub email: String,
+    pub name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A conversation; `messages` are the stored UI messages, in order.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveConversation {
+    pub id: Uuid,
+    pub title: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub messages: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveNote {
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An uploaded document; `content` holds its bytes, base64-encoded.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveDocument {
+    pub filename: String,
+    pub mime_type: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub content: String,
+}
+
+impl ArchiveDocument {
+    /// The document's bytes.
+    pub fn decode_content(&self) -> Result<Vec<u8>, AccountError> {
+        BASE64.decode(&self.content).map_err(|e| {
+            AccountError::Validation(
+                Message::new("account.invalid_document_content")
+                    .arg("filename", &self.filename)
+                    .arg("error", e),
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveTag {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+/// A task; `conversation_id` refers to an archived conversation.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveTask {
+    pub name: String,
+    pub prompt: String,
+    pub schedule: String,
+    pub enabled: bool,
+    pub conversation_id: Option<Uuid>,
+}
+
+/// A (non-secret) user config override.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveConfigValue {
+    pub key: String,
+    pub value: String,
+}
+
+/// A user-owned MCP server registration, without credentials or headers.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveMcpServer {
+    pub name: String,
+    pub description: String,
+    pub domain: String,
+    pub config: ServerConfig,
+    pub oauth_config: Option<serde_json::Value>,
+}
+
+/// Conversation row plus its tag names, for export.
+#[derive(sqlx::FromRow)]
+struct ConversationExportRow {
+    id: Uuid,
+    title: String,
+    tags: Vec<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct NoteExportRow {
+    title: String,
+    body: String,
+    tags: Vec<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct DocumentExportRow {
+    filename: String,
+    mime_type: String,
+    sha256: String,
+    tags: Vec<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ServerExportRow {
+    name: String,
+    description: String,
+    domain: String,
+    config: Option<serde_json::Value>,
+    oauth_config: Option<serde_json::Value>,
+}
+
+/// Tag names of the resource `{alias}.id` of the given type, as a column.
+fn tag_names_column(resource_type: &str, alias: &str) -> String {
+    format!(
+        r#"
+        ARRAY(
+            SELECT t.name::text
+            FROM tag_assignments a
+            JOIN tags t ON t.id = a.tag_id
+            WHERE a.resource_type = '{resource_type}' AND a.resource_id = {alias}.id
+            ORDER BY t.name
+        ) AS tags
+        "#
+    )
+}
+
+/// Drop custom headers (which commonly carry tokens) from a server config.
+fn strip_headers(config: ServerConfig) -> ServerConfig {
+    match config {
+        ServerConfig::Http(mut c) => {
+            c.headers = None;
+            ServerConfig::Http(c)
+        }
+        ServerConfig::Sse(mut c) => {
+            c.headers = None;
+            ServerConfig::Sse(c)
+        }
+        other => other,
+    }
+}
+
+/// Read a blob's bytes from whichever backend holds it, building each
+/// backend's store once.
+async fn read_blob(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+    stores: &mut HashMap<BlobBackend, BlobStore>,
+    sha256: &str,
+) -> Result<Vec<u8>, AccountError> {
+    let backend = blobs::blob_backend(pool, sha256)
+        .await?
+        .ok_or_else(|| BlobError::NotFound(sha256.to_string()))?;
+    let store = match stores.entry(backend) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => {
+            entry.insert(BlobStore::from_config(pool, config_cache, encryption_key, backend).await?)
+        }
+    };
+    let mut stream = store.get(sha256).await?;
+    let mut bytes = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        bytes.extend_from_slice(&chunk?);
+    }
+    Ok(bytes)
+}
+
+/// Collect all of a user's data into an [`Archive`]. Document contents are
+/// read from blob storage, whose secrets are decrypted with
+/// `encryption_key`.
+pub async fn build_archive(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+    user_id: &Uuid,
+) -> Result<Archive, AccountError> {
+    let account = sqlx::query_as::<_, ArchiveAccount>(
+        "SELECT email, name, created_at FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AccountError::NotFound(Message::new("account.user_not_found")))?;
+
+    let conversation_rows = sqlx::query_as::<_, ConversationExportRow>(&format!(
+        r#"
+        SELECT c.id, c.title, {}, c.created_at, c.updated_at
+        FROM conversations c
+        WHERE c.user_id = $1
+        ORDER BY c.created_at
+        "#,
+        tag_names_column("conversation", "c")
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut conversations = Vec::with_capacity(conversation_rows.len());
+    for row in conversation_rows {
+        let messages = sqlx::query_scalar::<_, serde_json::Value>(
+            "SELECT message_data FROM messages WHERE conversation_id = $1 ORDER BY sort_order",
+        )
+        .bind(row.id)
+        .fetch_all(pool)
+        .await?;
+        conversations.push(ArchiveConversation {
+            id: row.id,
+            title: row.title,
+            tags: row.tags,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            messages,
+        });
+    }
+
+    let notes = sqlx::query_as::<_, NoteExportRow>(&format!(
+        r#"
+        SELECT n.title, n.body, {}, n.created_at, n.updated_at
+        FROM notes n
+        WHERE n.user_id = $1
+        ORDER BY n.created_at
+        "#,
+        tag_names_column("note", "n")
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|n| ArchiveNote {
+        title: n.title,
+        body: n.body,
+        tags: n.tags,
+        created_at: n.created_at,
+        updated_at: n.updated_at,
+    })
+    .collect();
+
+    let document_rows = sqlx::query_as::<_, DocumentExportRow>(&format!(
+        r#"
+        SELECT d.filename, d.mime_type, d.sha256, {}, d.created_at, d.updated_at
+        FROM documents d
+        WHERE d.user_id = $1
+        ORDER BY d.created_at
+        "#,
+        tag_names_column("document", "d")
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut stores = HashMap::new();
+    let mut documents = Vec::with_capacity(document_rows.len());
+    for row in document_rows {
+        let content =
+            read_blob(pool, config_cache, encryption_key, &mut stores, &row.sha256).await?;
+        documents.push(ArchiveDocument {
+            filename: row.filename,
+            mime_type: row.mime_type,
+            tags: row.tags,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            content: BASE64.encode(content),
+        });
+    }
+
+    let tags = sqlx::query_as::<_, ArchiveTag>(
+        "SELECT name, color FROM tags WHERE user_id = $1 ORDER BY name",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let tasks = sqlx::query_as::<_, ArchiveTask>(
+        r#"
+        SELECT name, prompt, schedule, enabled, conversation_id
+        FROM tasks
+        WHERE user_id = $1
+        ORDER BY name, created_at
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let config = sqlx::query_as::<_, ArchiveConfigValue>(
+        r#"
+        SELECT v.key, v.value
+        FROM config_values v
+        JOIN config_definitions d ON d.key = v.key
+        WHERE v.scope = 'user-override' AND v.user_id = $1 AND d.display_type <> 'secret'
+        ORDER BY v.key
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mcp_servers = sqlx::query_as::<_, ServerExportRow>(
+        r#"
+        SELECT name, description, domain, config, oauth_config
+        FROM mcp_servers
+        WHERE owner_id = $1 AND visibility = 'user'
+        ORDER BY name
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .filter_map(|s| {
+        let config = serde_json::from_value::<ServerConfig>(s.config?).ok()?;
+        Some(ArchiveMcpServer {
+            name: s.name,
+            description: s.description,
+            domain: s.domain,
+            config: strip_headers(config),
+            oauth_config: s.oauth_config,
+        })
+    })
+    .collect();
+
+    Ok(Archive {
+        format: ARCHIVE_FORMAT.into(),
+        version: ARCHIVE_VERSION,
+        exported_at: Utc::now(),
+        account,
+        conversations,
+        notes,
+        documents,
+        tags,
+        tasks,
+        config,
+        mcp_servers,
+    })
+}
+
+// =============================================================================
+// Export jobs
+// =============================================================================
+
+/// Row returned by export job queries (without the archive itself).
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ExportRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+const EXPORT_COLUMNS: &str = "id, user_id, status, error, created_at, completed_at";
+
+/// Start an export for a user, returning the job and whether it is new. A
+/// running export is returned as is; otherwise previous exports are
+/// discarded (only the latest archive is kept) and a new job is recorded
+/// for [`run_export`] to complete.
+pub async fn create_export(
+    pool: &PgPool,
+    user_id: &Uuid,
+) -> Result<(ExportRow, bool), AccountError> {
+    let mut tx = pool.begin().await?;
+
+    // Serialize concurrent requests of the same user.
+    sqlx::query("SELECT id FROM users WHERE id = $1 FOR UPDATE")
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AccountError::NotFound(Message::new("account.user_not_found")))?;
+
+    let running = sqlx::query_as::<_, ExportRow>(&format!(
+        "SELECT {EXPORT_COLUMNS} FROM account_exports WHERE user_id = $1 AND status = $2"
+    ))
+    .bind(user_id)
+    .bind(STATUS_RUNNING)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let Some(row) = running {
+        return Ok((row, false));
+    }
+
+    sqlx::query("DELETE FROM account_exports WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    let row = sqlx::query_as::<_, ExportRow>(&format!(
+        r#"
+        INSERT INTO account_exports (id, user_id, status)
+        VALUES ($1, $2, $3)
+        RETURNING {EXPORT_COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(user_id)
+    .bind(STATUS_RUNNING)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok((row, true))
+}
+
+/// Build the archive of a job created by [`create_export`] and store it,
+/// marking the job completed — or failed, with the error, when building
+/// the archive fails.
+pub async fn run_export(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+    export_id: &Uuid,
+    user_id: &Uuid,
+) -> Result<(), AccountError> {
+    let archive = build_archive(pool, config_cache, encryption_key, user_id).await;
+    let (status, archive, error) = match archive {
+        Ok(archive) => {
+            let json = serde_json::to_value(&archive).map_err(|e| {
+                AccountError::Validation(
+                    Message::new("account.archive_serialize_failed").arg("error", e),
+                )
+            })?;
+            (STATUS_COMPLETED, Some(json), None)
+        }
+        Err(e) => (STATUS_FAILED, None, Some(e)),
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE account_exports
+        SET status = $1, archive = $2, error = $3, completed_at = now()
+        WHERE id = $4
+        "#,
+    )
+    .bind(status)
+    .bind(archive)
+    .bind(error.as_ref().map(ToString::to_string))
+    .bind(export_id)
+    .execute(pool)
+    .await?;
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Get an export job (scoped to user).
+pub async fn get_export(
+    pool: &PgPool,
+    user_id: &Uuid,
+    export_id: &Uuid,
+) -> Result<ExportRow, AccountError> {
+    sqlx::query_as::<_, ExportRow>(&format!(
+        "SELECT {EXPORT_COLUMNS} FROM account_exports WHERE id = $1 AND user_id = $2"
+    ))
+    .bind(export_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AccountError::NotFound(Message::new("account.export_not_found")))
+}
+
+/// Get the archive of a completed export (scoped to user).
+pub async fn get_export_archive(
+    pool: &PgPool,
+    user_id: &Uuid,
+    export_id: &Uuid,
+) -> Result<Archive, AccountError> {
+    let export = get_export(pool, user_id, export_id).await?;
+    if export.status != STATUS_COMPLETED {
+        return Err(AccountError::Validation(
+            Message::new("account.export_not_ready").arg("status", &export.status),
+        ));
+    }
+    sqlx::query_scalar::<_, Option<sqlx::types::Json<Archive>>>(
+        "SELECT archive FROM account_exports WHERE id = $1",
+    )
+    .bind(export_id)
+    .fetch_one(pool)
+    .await?
+    .map(|archive| archive.0)
+    .ok_or_else(|| AccountError::NotFound(Message::new("account.export_archive_not_found")))
+}
+
+// =============================================================================
+// Import
+// =============================================================================
+
+/// Number of items restored by [`import_archive`], by kind.
+#[derive(Debug, Clone, Default)]
+pub struct ImportSummary {
+    pub conversations: usize,
+    pub notes: usize,
+    pub tags: usize,
+    pub tasks: usize,
+    pub mcp_servers: usize,
+    /// Servers skipped because one with the same name already exists (or
+    /// their transport cannot be owned by users).
+    pub skipped_mcp_servers: usize,
+    /// IDs of the imported notes, which still need embedding.
+    pub note_ids: Vec<Uuid>,
+}
+
+/// Check an archive's format marker and version.
+pub fn validate_archive(archive: &Archive) -> Result<(), AccountError> {
+    if archive.format != ARCHIVE_FORMAT {
+        return Err(AccountError::Validation(
+            Message::new("account.not_an_archive").arg("format", ARCHIVE_FORMAT),
+        ));
+    }
+    if archive.version == 0 || archive.version > ARCHIVE_VERSION {
+        return Err(AccountError::Validation(
+            Message::new("account.unsupported_archive_version").arg("version", archive.version),
+        ));
+    }
+    Ok(())
+}
+
+/// Restore an archive into a user's account, in one transaction. Items get
+/// new IDs and are added next to existing data; tags are merged by name
+/// and MCP servers whose name is taken are skipped. Config overrides and
+/// documents are left to the caller, which owns config validation and
+/// caching, and blob storage and extraction. Imported servers with
+/// credentials stay unavailable until they are re-entered.
+pub async fn import_archive(
+    pool: &PgPool,
+    user_id: &Uuid,
+    archive: &Archive,
+) -> Result<ImportSummary, AccountError> {
+    validate_archive(archive)?;
+    let mut summary = ImportSummary::default();
+    let mut tx = pool.begin().await?;
+
+    for tag in &archive.tags {
+        let name = tags::normalize_name(&tag.name)?;
+        tags::validate_color(tag.color.as_deref())?;
+        sqlx::query(
+            r#"
+            INSERT INTO tags (id, user_id, name, color)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, name) DO UPDATE SET color = COALESCE(tags.color, EXCLUDED.color)
+            "#,
+        )
+        .bind(uuidv7())
+        .bind(user_id)
+        .bind(&name)
+        .bind(&tag.color)
+        .execute(&mut *tx)
+        .await?;
+        summary.tags += 1;
+    }
+
+    let mut conversation_ids: HashMap<Uuid, Uuid> = HashMap::new();
+    for conversation in &archive.conversations {
+        let id = uuidv7();
+        sqlx::query(
+            r#"
+            INSERT INTO conversations (id, user_id, title, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(&conversation.title)
+        .bind(conversation.created_at)
+        .bind(conversation.updated_at)
+        .execute(&mut *tx)
+        .await?;
+        for (i, message) in conversation.messages.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO messages (id, conversation_id, sort_order, message_data)
+                VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(uuidv7())
+            .bind(id)
+            .bind(i as i32)
+            .bind(message)
+            .execute(&mut *tx)
+            .await?;
+        }
+        set_tags(
+            &mut tx,
+            user_id,
+            TagResourceType::Conversation,
+            &id,
+            &conversation.tags,
+        )
+        .await?;
+        conversation_ids.insert(conversation.id, id);
+        summary.conversations += 1;
+    }
+
+    for note in &archive.notes {
+        let id = uuidv7();
+        sqlx::query(
+            r#"
+            INSERT INTO notes (id, user_id, title, body, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(&note.title)
+        .bind(&note.body)
+        .bind(note.created_at)
+        .bind(note.updated_at)
+        .execute(&mut *tx)
+        .await?;
+        set_tags(&mut tx, user_id, TagResourceType::Note, &id, &note.tags).await?;
+        summary.note_ids.push(id);
+        summary.notes += 1;
+    }
+
+    for task in &archive.tasks {
+        let next_run_at = if task.enabled {
+            Some(schedule::next_run_after(&task.schedule, Utc::now())?)
+        } else {
+            None
+        };
+        let conversation_id = task
+            .conversation_id
+            .and_then(|old| conversation_ids.get(&old).copied());
+        sqlx::query(
+            r#"
+            INSERT INTO tasks (id, user_id, name, prompt, schedule, enabled, conversation_id, next_run_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(uuidv7())
+        .bind(user_id)
+        .bind(&task.name)
+        .bind(&task.prompt)
+        .bind(task.schedule.trim())
+        .bind(task.enabled)
+        .bind(conversation_id)
+        .bind(next_run_at)
+        .execute(&mut *tx)
+        .await?;
+        summary.tasks += 1;
+    }
+
+    for server in &archive.mcp_servers {
+        if import_server(&mut tx, user_id, server).await? {
+            summary.mcp_servers += 1;
+        } else {
+            summary.skipped_mcp_servers += 1;
+        }
+    }
+
+    tx.commit().await?;
+    Ok(summary)
+}
+
+/// Give a document restored from an archive its archived timestamps and
+/// tags. The caller stores the content and creates the document first.
+pub async fn restore_document_details(
+    pool: &PgPool,
+    user_id: &Uuid,
+    document_id: &Uuid,
+    document: &ArchiveDocument,
+) -> Result<(), AccountError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("UPDATE documents SET created_at = $2, updated_at = $3 WHERE id = $1")
+        .bind(document_id)
+        .bind(document.created_at)
+        .bind(document.updated_at)
+        .execute(&mut *tx)
+        .await?;
+    set_tags(
+        &mut tx,
+        user_id,
+        TagResourceType::Document,
+        document_id,
+        &document.tags,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Normalize and attach tag names to an imported resource.
+async fn set_tags(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    resource_type: TagResourceType,
+    resource_id: &Uuid,
+    names: &[String],
+) -> Result<(), AccountError> {
+    let names = tags::normalize_names(names)?;
+    tags::set_resource_tags(conn, user_id, resource_type, resource_id, &names).await?;
+    Ok(())
+}
+
+/// Register an archived server for the user. Returns `false` (skipped)
+/// when the name is taken or the transport is not one users may own.
+async fn import_server(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    server: &ArchiveMcpServer,
+) -> Result<bool, AccountError> {
+    let auth_type = match &server.config {
+        ServerConfig::Http(c) => &c.auth_type,
+        ServerConfig::Sse(c) => &c.auth_type,
+        _ => return Ok(false),
+    };
+
+    let taken = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM mcp_servers WHERE owner_id = $1 AND name = $2)",
+    )
+    .bind(user_id)
+    .bind(&server.name)
+    .fetch_one(&mut *conn)
+    .await?;
+    if taken {
+        return Ok(false);
+    }
+
+    let config = strip_headers(server.config.clone());
+    let config_json = serde_json::to_value(&config).map_err(|e| {
+        AccountError::Validation(Message::new("account.invalid_server_config").arg("error", e))
+    })?;
+    let transport: TransportType = config.transport_type();
+    sqlx::query(
+        r#"
+        INSERT INTO mcp_servers (id, name, description, domain, endpoint, visibility, transport, config, oauth_config, owner_id, enabled, available)
+        VALUES ($1, $2, $3, $4, $5, 'user', $6, $7, $8, $9, true, $10)
+        "#,
+    )
+    .bind(uuidv7())
+    .bind(&server.name)
+    .bind(&server.description)
+    .bind(&server.domain)
+    .bind(config.endpoint())
+    .bind(transport)
+    .bind(&config_json)
+    .bind(&server.oauth_config)
+    .bind(user_id)
+    // Credentials are not part of the archive.
+    .bind(auth_type == "none")
+    .execute(&mut *conn)
+    .await?;
+    Ok(true)
+}
+
+// =============================================================================
+// Erasure
+// =============================================================================
+
+/// SQL condition on `workspaces w`: the user bound as `$1` is its only
+/// member.
+const SOLE_MEMBER_FILTER: &str = "EXISTS (SELECT 1 FROM workspace_members m \
+     WHERE m.workspace_id = w.id AND m.user_id = $1) \
+     AND NOT EXISTS (SELECT 1 FROM workspace_members m \
+     WHERE m.workspace_id = w.id AND m.user_id <> $1)";
+
+/// Delete a user and everything they own. Refused while the user is the
+/// last administrator, or the last owner of a workspace that has other
+/// members. Workspaces the user is alone in are deleted with them; all
+/// other data goes by `ON DELETE CASCADE`. Returns the blobs of the
+/// deleted documents that no other document shares, with their backends,
+/// whose records are gone and whose bytes the caller deletes.
+pub async fn erase_account(
+    pool: &PgPool,
+    user_id: &Uuid,
+) -> Result<Vec<(String, BlobBackend)>, AccountError> {
+    let is_administrator = rbac::list_user_roles(pool, user_id)
+        .await?
+        .iter()
+        .any(|r| r.name == rbac::ADMINISTRATOR_ROLE);
+    if is_administrator {
+        rbac::ensure_other_administrator(pool, user_id).await?;
+    }
+
+    let stranded = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT w.name
+        FROM workspaces w
+        JOIN workspace_members m ON m.workspace_id = w.id AND m.user_id = $1 AND m.role = 'owner'
+        WHERE NOT EXISTS (
+                SELECT 1 FROM workspace_members o
+                WHERE o.workspace_id = w.id AND o.role = 'owner' AND o.user_id <> $1
+            )
+          AND EXISTS (
+                SELECT 1 FROM workspace_members o
+                WHERE o.workspace_id = w.id AND o.user_id <> $1
+            )
+        ORDER BY w.name
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    if !stranded.is_empty() {
+        return Err(AccountError::Validation(
+            Message::new("account.workspaces_need_owner").arg("workspaces", stranded.join(", ")),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    // Every document about to go: the user's own and those of the
+    // workspaces deleted with them.
+    let hashes = sqlx::query_scalar::<_, String>(&format!(
+        r#"
+        SELECT DISTINCT d.sha256
+        FROM documents d
+        WHERE d.user_id = $1
+           OR d.workspace_id IN (SELECT w.id FROM workspaces w WHERE {SOLE_MEMBER_FILTER})
+        "#
+    ))
+    .bind(user_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    sqlx::query(&format!(
+        "DELETE FROM workspaces w WHERE {SOLE_MEMBER_FILTER}"
+    ))
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    let result = sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(AccountError::NotFound(Message::new(
+            "account.user_not_found",
+        )));
+    }
+    let released = sqlx::query_as::<_, (String, String)>(
+        r#"
+        DELETE FROM blobs b
+        WHERE b.sha256 = ANY($1)
+          AND NOT EXISTS (SELECT 1 FROM documents d WHERE d.sha256 = b.sha256)
+        RETURNING b.sha256, b.backend
+        "#,
+    )
+    .bind(&hashes)
+    .fetch_all(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    mcp_tokens::forget_validated(&user_id.to_string());
+    released
+        .into_iter()
+        .map(|(sha256, backend)| Ok((sha256, BlobBackend::parse(&backend)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_archive() -> Archive {
+        Archive {
+            format: ARCHIVE_FORMAT.into(),
+            version: ARCHIVE_VERSION,
+            exported_at: Utc::now(),
+            account: ArchiveAccount {
+                email: "user@example.com".into(),
+                name: None,
+                created_at: Utc::now(),
+            },
+            conversations: vec![],
+            notes: vec![],
+            documents: vec![],
+            tags: vec![],
+            tasks: vec![],
+            config: vec![],
+            mcp_servers: vec![],
+        }
+    }
+
+    #[test]
+    fn archives_are_checked_for_format_and_version() {
+        assert!(validate_archive(&empty_archive()).is_ok());
+
+        let mut other = empty_archive();
+        other.format = "something-else".into();
+        assert!(validate_archive(&other).is_err());
+
+        let mut newer = empty_archive();
+        newer.version = ARCHIVE_VERSION + 1;
+        assert!(validate_archive(&newer).is_err());
+    }
+
+    #[test]
+    fn document_contents_round_trip_through_base64() {
+        let mut document = ArchiveDocument {
+            filename: "notes.txt".into(),
+            mime_type: "text/plain".into(),
+            tags: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            content: BASE64.encode(b"hello\0world"),
+        };
+        assert_eq!(document.decode_content().unwrap(), b"hello\0world");
+
+        document.content = "not base64!".into();
+        assert!(matches!(
+            document.decode_content(),
+            Err(AccountError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn server_headers_are_not_exported() {
+        let config: ServerConfig = serde_json::from_value(serde_json::json!({
+            "transport": "http",
+            "url": "https://mcp.example.com",
+            "headers": { "Authorization": "Bearer secret" },
+            "authType": "none",
+        }))
+        .unwrap();
+        let json = serde_json::to_value(strip_headers(config)).unwrap();
+        assert!(json.get("headers").is_none());
+        assert_eq!(json["url"], "https://mcp.example.com");
+    }
+}
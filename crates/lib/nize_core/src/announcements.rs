@@ -0,0 +1,389 @@
+//! Admin announcements — messages shown to every user while active, such as
+//! a planned migration that needs a restart.
+//!
+//! An announcement is active between `starts_at` and `ends_at` (open-ended
+//! without an end). Users can dismiss dismissible announcements, which hides
+//! them for that user only. [`take_due`] marks announcements that have
+//! become active as published, once each, so callers can push them to
+//! connected clients.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Maximum title length in characters.
+pub const MAX_TITLE_CHARS: usize = 200;
+
+/// Maximum body length in characters.
+pub const MAX_BODY_CHARS: usize = 10_000;
+
+/// Errors that can occur in announcement operations.
+#[derive(Debug, Error)]
+pub enum AnnouncementError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Not found: {0}")]
+    NotFound(Message),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// How prominently clients show an announcement.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    /// Also raised as a desktop notification.
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = AnnouncementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "info" => Ok(Self::Info),
+            "warning" => Ok(Self::Warning),
+            "critical" => Ok(Self::Critical),
+            other => Err(AnnouncementError::Validation(
+                Message::new("announcements.unknown_severity").arg("severity", other),
+            )),
+        }
+    }
+}
+
+/// An announcement as written by an admin.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnouncementInput {
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default)]
+    pub severity: Severity,
+    /// Defaults to now.
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    #[serde(default = "default_dismissible")]
+    pub dismissible: bool,
+}
+
+fn default_dismissible() -> bool {
+    true
+}
+
+impl AnnouncementInput {
+    fn validate(&self) -> Result<(), AnnouncementError> {
+        let title = self.title.trim();
+        if title.is_empty() {
+            return Err(AnnouncementError::Validation(Message::new(
+                "announcements.title_required",
+            )));
+        }
+        if title.chars().count() > MAX_TITLE_CHARS {
+            return Err(AnnouncementError::Validation(
+                Message::new("announcements.title_too_long").arg("max", MAX_TITLE_CHARS),
+            ));
+        }
+        if self.body.chars().count() > MAX_BODY_CHARS {
+            return Err(AnnouncementError::Validation(
+                Message::new("announcements.body_too_long").arg("max", MAX_BODY_CHARS),
+            ));
+        }
+        let starts_at = self.starts_at.unwrap_or_else(Utc::now);
+        if self.ends_at.is_some_and(|ends_at| ends_at <= starts_at) {
+            return Err(AnnouncementError::Validation(Message::new(
+                "announcements.ends_before_start",
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A stored announcement.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Announcement {
+    pub id: Uuid,
+    pub title: String,
+    pub body: String,
+    pub severity: Severity,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub dismissible: bool,
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct AnnouncementRow {
+    id: Uuid,
+    title: String,
+    body: String,
+    severity: String,
+    starts_at: DateTime<Utc>,
+    ends_at: Option<DateTime<Utc>>,
+    dismissible: bool,
+    published_at: Option<DateTime<Utc>>,
+    created_by: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<AnnouncementRow> for Announcement {
+    fn from(row: AnnouncementRow) -> Self {
+        Self {
+            id: row.id,
+            title: row.title,
+            body: row.body,
+            // The column is constrained to the known severities.
+            severity: row.severity.parse().unwrap_or_default(),
+            starts_at: row.starts_at,
+            ends_at: row.ends_at,
+            dismissible: row.dismissible,
+            published_at: row.published_at,
+            created_by: row.created_by,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+const COLUMNS: &str = "id, title, body, severity, starts_at, ends_at, dismissible, \
+                       published_at, created_by, created_at, updated_at";
+
+/// Create an announcement.
+pub async fn create(
+    pool: &PgPool,
+    input: &AnnouncementInput,
+    created_by: &Uuid,
+) -> Result<Announcement, AnnouncementError> {
+    input.validate()?;
+    let row = sqlx::query_as::<_, AnnouncementRow>(&format!(
+        r#"
+        INSERT INTO announcements
+            (id, title, body, severity, starts_at, ends_at, dismissible, created_by)
+        VALUES ($1, $2, $3, $4, COALESCE($5, now()), $6, $7, $8)
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(input.title.trim())
+    .bind(&input.body)
+    .bind(input.severity.as_str())
+    .bind(input.starts_at)
+    .bind(input.ends_at)
+    .bind(input.dismissible)
+    .bind(created_by)
+    .fetch_one(pool)
+    .await?;
+    Ok(row.into())
+}
+
+/// Replace an announcement. Moving its start makes it publish again.
+pub async fn update(
+    pool: &PgPool,
+    id: &Uuid,
+    input: &AnnouncementInput,
+) -> Result<Announcement, AnnouncementError> {
+    input.validate()?;
+    let row = sqlx::query_as::<_, AnnouncementRow>(&format!(
+        r#"
+        UPDATE announcements
+        SET title = $2,
+            body = $3,
+            severity = $4,
+            published_at = CASE
+                WHEN starts_at = COALESCE($5, starts_at) THEN published_at
+            END,
+            starts_at = COALESCE($5, starts_at),
+            ends_at = $6,
+            dismissible = $7,
+            updated_at = now()
+        WHERE id = $1
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .bind(input.title.trim())
+    .bind(&input.body)
+    .bind(input.severity.as_str())
+    .bind(input.starts_at)
+    .bind(input.ends_at)
+    .bind(input.dismissible)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        AnnouncementError::NotFound(Message::new("announcements.not_found").arg("id", id))
+    })?;
+    Ok(row.into())
+}
+
+/// Delete an announcement. Returns `false` if it does not exist.
+pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<bool, AnnouncementError> {
+    let result = sqlx::query("DELETE FROM announcements WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Every announcement, latest start first.
+pub async fn list_all(pool: &PgPool) -> Result<Vec<Announcement>, AnnouncementError> {
+    let rows = sqlx::query_as::<_, AnnouncementRow>(&format!(
+        "SELECT {COLUMNS} FROM announcements ORDER BY starts_at DESC"
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(Announcement::from).collect())
+}
+
+/// Active announcements the user has not dismissed, most severe first.
+pub async fn list_active(
+    pool: &PgPool,
+    user_id: &Uuid,
+) -> Result<Vec<Announcement>, AnnouncementError> {
+    let rows = sqlx::query_as::<_, AnnouncementRow>(&format!(
+        r#"
+        SELECT {COLUMNS}
+        FROM announcements a
+        WHERE a.starts_at <= now()
+          AND (a.ends_at IS NULL OR a.ends_at > now())
+          AND NOT EXISTS (
+              SELECT 1 FROM announcement_dismissals d
+              WHERE d.announcement_id = a.id AND d.user_id = $1
+          )
+        "#
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    let mut announcements: Vec<Announcement> = rows.into_iter().map(Announcement::from).collect();
+    announcements.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then(b.starts_at.cmp(&a.starts_at))
+    });
+    Ok(announcements)
+}
+
+/// Hide an announcement for a user.
+pub async fn dismiss(pool: &PgPool, user_id: &Uuid, id: &Uuid) -> Result<(), AnnouncementError> {
+    let dismissible: Option<bool> =
+        sqlx::query_scalar("SELECT dismissible FROM announcements WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+    match dismissible {
+        None => Err(AnnouncementError::NotFound(
+            Message::new("announcements.not_found").arg("id", id),
+        )),
+        Some(false) => Err(AnnouncementError::Validation(Message::new(
+            "announcements.not_dismissible",
+        ))),
+        Some(true) => {
+            sqlx::query(
+                r#"
+                INSERT INTO announcement_dismissals (announcement_id, user_id)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .bind(id)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+            Ok(())
+        }
+    }
+}
+
+/// Mark announcements that are active but not yet published as published,
+/// returning them.
+pub async fn take_due(pool: &PgPool) -> Result<Vec<Announcement>, AnnouncementError> {
+    let rows = sqlx::query_as::<_, AnnouncementRow>(&format!(
+        r#"
+        UPDATE announcements
+        SET published_at = now()
+        WHERE published_at IS NULL
+          AND starts_at <= now()
+          AND (ends_at IS NULL OR ends_at > now())
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(Announcement::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(title: &str) -> AnnouncementInput {
+        AnnouncementInput {
+            title: title.into(),
+            body: String::new(),
+            severity: Severity::Info,
+            starts_at: None,
+            ends_at: None,
+            dismissible: true,
+        }
+    }
+
+    #[test]
+    fn severity_round_trips_and_orders() {
+        for severity in [Severity::Info, Severity::Warning, Severity::Critical] {
+            assert_eq!(severity.as_str().parse::<Severity>().unwrap(), severity);
+        }
+        assert!("urgent".parse::<Severity>().is_err());
+        assert!(Severity::Critical > Severity::Warning);
+        assert!(Severity::Warning > Severity::Info);
+    }
+
+    #[test]
+    fn validate_checks_title_and_window() {
+        assert!(input("Planned maintenance").validate().is_ok());
+        assert!(input("  ").validate().is_err());
+        assert!(input(&"x".repeat(MAX_TITLE_CHARS + 1)).validate().is_err());
+
+        let now = Utc::now();
+        let mut backwards = input("Window");
+        backwards.starts_at = Some(now);
+        backwards.ends_at = Some(now - chrono::Duration::hours(1));
+        assert!(backwards.validate().is_err());
+    }
+}
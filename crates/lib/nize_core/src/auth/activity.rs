@@ -0,0 +1,110 @@
+//! Security activity log — the security-relevant events of each user's
+//! account (sign-ins, MCP token changes, roles granted), kept so users can
+//! review them.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::uuid::uuidv7;
+
+/// A user signed in.
+pub const KIND_LOGIN: &str = "login";
+/// A user created an MCP token.
+pub const KIND_MCP_TOKEN_CREATED: &str = "mcp_token.created";
+/// A user revoked an MCP token.
+pub const KIND_MCP_TOKEN_REVOKED: &str = "mcp_token.revoked";
+/// A user was granted a role.
+pub const KIND_ROLE_GRANTED: &str = "role.granted";
+
+/// Longest user agent stored; longer ones are cut.
+pub const MAX_USER_AGENT_CHARS: usize = 512;
+
+/// Client that caused an event.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// Row returned by activity queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ActivityRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Record an event for a user.
+pub async fn record(
+    pool: &PgPool,
+    user_id: &Uuid,
+    kind: &str,
+    client: &ClientInfo,
+    details: &serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO security_activity (id, user_id, kind, ip_address, user_agent, details)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(uuidv7())
+    .bind(user_id)
+    .bind(kind)
+    .bind(client.ip_address.as_deref())
+    .bind(client.user_agent.as_deref().map(truncate_user_agent))
+    .bind(details)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// List a user's events, newest first.
+pub async fn list_activity(
+    pool: &PgPool,
+    user_id: &Uuid,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<ActivityRow>, sqlx::Error> {
+    sqlx::query_as::<_, ActivityRow>(
+        r#"
+        SELECT id, user_id, kind, ip_address, user_agent, details, created_at
+        FROM security_activity
+        WHERE user_id = $1
+        ORDER BY created_at DESC, id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(user_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+}
+
+fn truncate_user_agent(user_agent: &str) -> &str {
+    match user_agent.char_indices().nth(MAX_USER_AGENT_CHARS) {
+        Some((end, _)) => &user_agent[..end],
+        None => user_agent,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_user_agents_are_cut() {
+        assert_eq!(truncate_user_agent("curl/8.0"), "curl/8.0");
+        let long = "é".repeat(MAX_USER_AGENT_CHARS + 10);
+        assert_eq!(
+            truncate_user_agent(&long).chars().count(),
+            MAX_USER_AGENT_CHARS
+        );
+    }
+}
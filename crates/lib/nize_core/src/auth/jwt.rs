@@ -3,24 +3,24 @@
 use std::path::PathBuf;
 
 use chrono::{Duration, Utc};
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use rand::distr::Alphanumeric;
 use rand::{Rng, rng};
 use tracing::info;
 
 use super::AuthError;
+use super::keys::JwtKeys;
 use crate::models::auth::TokenClaims;
 
 /// Access token lifetime: 15 minutes.
 const ACCESS_TOKEN_EXPIRY_SECS: i64 = 15 * 60;
 
 // @awa-impl: AUTH-1_AC-1, AUTH-1_AC-3
-/// Generate a signed JWT access token (HS256, 15 min expiry).
+/// Generate a JWT access token signed with the active key (15 min expiry).
 pub fn generate_access_token(
     user_id: &str,
     email: &str,
     roles: &[String],
-    secret: &[u8],
+    keys: &JwtKeys,
 ) -> Result<String, AuthError> {
     let now = Utc::now();
     let claims = TokenClaims {
@@ -30,35 +30,19 @@ pub fn generate_access_token(
         exp: (now + Duration::seconds(ACCESS_TOKEN_EXPIRY_SECS)).timestamp(),
         iat: now.timestamp(),
     };
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret),
-    )
-    .map_err(|e| AuthError::TokenError(format!("jwt encode: {e}")))
+    keys.sign(&claims)
 }
 
 // @awa-impl: AUTH-2_AC-4
 /// Verify a JWT access token, returning the claims on success.
-pub fn verify_access_token(token: &str, secret: &[u8]) -> Option<TokenClaims> {
-    let key = DecodingKey::from_secret(secret);
-    let mut validation = Validation::default();
-    validation.validate_exp = true;
-    decode::<TokenClaims>(token, &key, &validation)
-        .ok()
-        .map(|data| data.claims)
+pub fn verify_access_token(token: &str, keys: &JwtKeys) -> Option<TokenClaims> {
+    keys.verify(token)
 }
 
-/// Resolve the JWT secret: env var `JWT_SECRET` → `AUTH_SECRET` → persisted file.
+/// Resolve the legacy HS256 secret used until the first key rotation (see
+/// [`super::keys`]): env var `JWT_SECRET` → `AUTH_SECRET` → persisted file.
 pub fn resolve_jwt_secret() -> String {
-    if let Ok(secret) = std::env::var("JWT_SECRET")
-        && !secret.is_empty()
-    {
-        return secret;
-    }
-    if let Ok(secret) = std::env::var("AUTH_SECRET")
-        && !secret.is_empty()
-    {
+    if let Some((_, secret)) = jwt_secret_from_env() {
         return secret;
     }
     // Generate and persist
@@ -82,6 +66,17 @@ pub fn resolve_jwt_secret() -> String {
     secret
 }
 
+/// The JWT secret from `JWT_SECRET` or `AUTH_SECRET` with the variable it
+/// came from, ignoring empty values.
+pub fn jwt_secret_from_env() -> Option<(&'static str, String)> {
+    ["JWT_SECRET", "AUTH_SECRET"].into_iter().find_map(|name| {
+        std::env::var(name)
+            .ok()
+            .filter(|secret| !secret.is_empty())
+            .map(|secret| (name, secret))
+    })
+}
+
 /// Path to the persisted JWT secret file.
 fn jwt_secret_path() -> PathBuf {
     dirs::data_dir()
@@ -0,0 +1,572 @@
+//! JWT signing keys and rotation.
+//!
+//! Access tokens are signed with the *active* key and carry its `kid` in the
+//! header. Rotating creates a new active key with the algorithm configured in
+//! `system.auth.jwtAlgorithm`; the previous key is retired but keeps verifying
+//! tokens for `system.auth.jwtKeyOverlapMinutes`, so sessions survive a
+//! rotation. Public keys (RS256, EdDSA) are published as a JWKS.
+//!
+//! Keys live in `jwt_signing_keys` with their private material encrypted.
+//! Until the first rotation, the process-wide secret from
+//! [`super::jwt::resolve_jwt_secret`] is the active HS256 key; tokens signed
+//! with it carry no `kid`. After the first rotation it verifies only for the
+//! overlap period.
+
+use std::sync::RwLock;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use rand::distr::Alphanumeric;
+use rand::{Rng, rng};
+use ring::signature::{Ed25519KeyPair, KeyPair};
+use schemars::JsonSchema;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::info;
+
+use super::AuthError;
+use crate::config::ConfigError;
+use crate::config::queries;
+use crate::i18n::Message;
+use crate::mcp::secrets;
+use crate::models::config::ConfigScope;
+
+/// Config key selecting the algorithm for newly rotated keys.
+pub const ALGORITHM_CONFIG_KEY: &str = "system.auth.jwtAlgorithm";
+/// Config key for how long a retired key keeps verifying, in minutes.
+pub const OVERLAP_CONFIG_KEY: &str = "system.auth.jwtKeyOverlapMinutes";
+/// Config key holding the RS256 private key (PEM, secret).
+pub const RSA_KEY_CONFIG_KEY: &str = "system.auth.jwtRsaPrivateKey";
+
+/// Lower bound on the overlap: one access token lifetime.
+const MIN_OVERLAP_MINUTES: i64 = 15;
+
+/// Supported signing algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+    Hs256,
+    Rs256,
+    EdDsa,
+}
+
+impl JwtAlgorithm {
+    /// Name as used in JWT headers, config and the database.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JwtAlgorithm::Hs256 => "HS256",
+            JwtAlgorithm::Rs256 => "RS256",
+            JwtAlgorithm::EdDsa => "EdDSA",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "HS256" => Some(JwtAlgorithm::Hs256),
+            "RS256" => Some(JwtAlgorithm::Rs256),
+            "EdDSA" => Some(JwtAlgorithm::EdDsa),
+            _ => None,
+        }
+    }
+
+    fn jwt(&self) -> Algorithm {
+        match self {
+            JwtAlgorithm::Hs256 => Algorithm::HS256,
+            JwtAlgorithm::Rs256 => Algorithm::RS256,
+            JwtAlgorithm::EdDsa => Algorithm::EdDSA,
+        }
+    }
+}
+
+/// A key usable for verification, and for signing while active.
+struct SigningKey {
+    /// `None` for the legacy process secret.
+    kid: Option<String>,
+    algorithm: JwtAlgorithm,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    /// Public JWK members (`kty`, `n`/`e` or `crv`/`x`); `None` for HS256.
+    public_jwk: Option<serde_json::Value>,
+    /// Retired keys stop verifying after this instant.
+    verify_until: Option<DateTime<Utc>>,
+}
+
+impl SigningKey {
+    /// Build a key from its stored private material: the secret for HS256,
+    /// a PEM for RS256, base64 PKCS#8 for EdDSA.
+    fn from_private(
+        kid: Option<String>,
+        algorithm: JwtAlgorithm,
+        private: &str,
+        verify_until: Option<DateTime<Utc>>,
+    ) -> Result<Self, AuthError> {
+        let (encoding, decoding, public_jwk) = match algorithm {
+            JwtAlgorithm::Hs256 => (
+                EncodingKey::from_secret(private.as_bytes()),
+                DecodingKey::from_secret(private.as_bytes()),
+                None,
+            ),
+            JwtAlgorithm::Rs256 => {
+                let (n, e) = rsa_public_components(private)?;
+                let encoding = EncodingKey::from_rsa_pem(private.as_bytes()).map_err(|e| {
+                    AuthError::ValidationError(Message::new("auth.invalid_rsa_key").arg("error", e))
+                })?;
+                let jwk = serde_json::json!({
+                    "kty": "RSA",
+                    "n": URL_SAFE_NO_PAD.encode(&n),
+                    "e": URL_SAFE_NO_PAD.encode(&e),
+                });
+                (
+                    encoding,
+                    DecodingKey::from_rsa_raw_components(&n, &e),
+                    Some(jwk),
+                )
+            }
+            JwtAlgorithm::EdDsa => {
+                let pkcs8 = base64::engine::general_purpose::STANDARD
+                    .decode(private)
+                    .map_err(|e| AuthError::Internal(format!("Invalid Ed25519 key: {e}")))?;
+                let pair = Ed25519KeyPair::from_pkcs8(&pkcs8)
+                    .map_err(|e| AuthError::Internal(format!("Invalid Ed25519 key: {e}")))?;
+                let public = pair.public_key().as_ref();
+                let jwk = serde_json::json!({
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "x": URL_SAFE_NO_PAD.encode(public),
+                });
+                (
+                    EncodingKey::from_ed_der(&pkcs8),
+                    DecodingKey::from_ed_der(public),
+                    Some(jwk),
+                )
+            }
+        };
+        Ok(Self {
+            kid,
+            algorithm,
+            encoding,
+            decoding,
+            public_jwk,
+            verify_until,
+        })
+    }
+
+    fn verifies_at(&self, now: DateTime<Utc>) -> bool {
+        self.verify_until.is_none_or(|until| now < until)
+    }
+}
+
+struct KeySet {
+    /// Index of the signing key in `keys`.
+    active: usize,
+    keys: Vec<SigningKey>,
+}
+
+/// The process's JWT keys: one active signing key plus retired keys still
+/// inside their overlap period.
+pub struct JwtKeys {
+    legacy_secret: String,
+    set: RwLock<KeySet>,
+}
+
+impl JwtKeys {
+    /// Keys consisting only of the legacy HS256 secret.
+    pub fn from_secret(secret: &str) -> Self {
+        Self {
+            legacy_secret: secret.to_string(),
+            set: RwLock::new(KeySet {
+                active: 0,
+                keys: vec![legacy_key(secret, None)],
+            }),
+        }
+    }
+
+    /// Load keys from the database, falling back to the legacy secret when
+    /// none have been created.
+    pub async fn load(
+        pool: &PgPool,
+        legacy_secret: &str,
+        encryption_key: &str,
+    ) -> Result<Self, AuthError> {
+        let keys = Self::from_secret(legacy_secret);
+        keys.reload(pool, encryption_key).await?;
+        Ok(keys)
+    }
+
+    /// Re-read keys from the database, dropping ones past their overlap.
+    pub async fn reload(&self, pool: &PgPool, encryption_key: &str) -> Result<(), AuthError> {
+        let rows = list_keys(pool).await?;
+        let now = Utc::now();
+
+        let mut keys = Vec::new();
+        let mut active = None;
+        for row in rows
+            .iter()
+            .filter(|r| r.verify_until.is_none_or(|u| now < u))
+        {
+            let algorithm = JwtAlgorithm::parse(&row.algorithm).ok_or_else(|| {
+                AuthError::Internal(format!("Unknown JWT algorithm: {}", row.algorithm))
+            })?;
+            let private = private_key(pool, &row.kid, encryption_key).await?;
+            if row.retired_at.is_none() {
+                active = Some(keys.len());
+            }
+            keys.push(SigningKey::from_private(
+                Some(row.kid.clone()),
+                algorithm,
+                &private,
+                row.verify_until,
+            )?);
+        }
+
+        // The legacy secret verifies until the first rotation's overlap ends.
+        let legacy_until = match rows.iter().map(|r| r.created_at).min() {
+            Some(first) => Some(first + chrono::Duration::minutes(overlap_minutes(pool).await?)),
+            None => None,
+        };
+        let active = match active {
+            Some(i) => {
+                if legacy_until.is_some_and(|u| now < u) {
+                    keys.push(legacy_key(&self.legacy_secret, legacy_until));
+                }
+                i
+            }
+            None => {
+                keys.push(legacy_key(&self.legacy_secret, None));
+                keys.len() - 1
+            }
+        };
+
+        *self.set.write().unwrap_or_else(|e| e.into_inner()) = KeySet { active, keys };
+        Ok(())
+    }
+
+    /// Sign `claims` with the active key.
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, AuthError> {
+        let set = self.set.read().unwrap_or_else(|e| e.into_inner());
+        let key = &set.keys[set.active];
+        let mut header = jsonwebtoken::Header::new(key.algorithm.jwt());
+        header.kid = key.kid.clone();
+        jsonwebtoken::encode(&header, claims, &key.encoding).map_err(|e| {
+            AuthError::TokenError(Message::new("auth.token_encoding_failed").arg("error", e))
+        })
+    }
+
+    /// Verify `token` against the key named by its `kid` (the legacy secret
+    /// when absent), returning the claims.
+    pub fn verify<T: serde::de::DeserializeOwned>(&self, token: &str) -> Option<T> {
+        let header = jsonwebtoken::decode_header(token).ok()?;
+        let set = self.set.read().unwrap_or_else(|e| e.into_inner());
+        let now = Utc::now();
+        let key = set
+            .keys
+            .iter()
+            .find(|k| k.kid == header.kid && k.verifies_at(now))?;
+        let mut validation = jsonwebtoken::Validation::new(key.algorithm.jwt());
+        validation.validate_exp = true;
+        jsonwebtoken::decode::<T>(token, &key.decoding, &validation)
+            .ok()
+            .map(|data| data.claims)
+    }
+
+    /// Public keys of asymmetric keys that still verify, as a JWKS.
+    pub fn jwks(&self) -> Jwks {
+        let set = self.set.read().unwrap_or_else(|e| e.into_inner());
+        let now = Utc::now();
+        let keys: Vec<serde_json::Value> = set
+            .keys
+            .iter()
+            .filter(|k| k.verifies_at(now))
+            .filter_map(|k| {
+                let mut jwk = k.public_jwk.clone()?;
+                jwk["kid"] = serde_json::json!(k.kid);
+                jwk["alg"] = serde_json::json!(k.algorithm.as_str());
+                jwk["use"] = serde_json::json!("sig");
+                Some(jwk)
+            })
+            .collect();
+        Jwks { keys }
+    }
+
+    /// `kid` of the active key, `None` for the legacy secret.
+    pub fn active_kid(&self) -> Option<String> {
+        let set = self.set.read().unwrap_or_else(|e| e.into_inner());
+        set.keys[set.active].kid.clone()
+    }
+}
+
+/// A JSON Web Key Set.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Jwks {
+    /// Public keys, each with its `kid`, `alg` and `use`.
+    pub keys: Vec<serde_json::Value>,
+}
+
+fn legacy_key(secret: &str, verify_until: Option<DateTime<Utc>>) -> SigningKey {
+    SigningKey {
+        kid: None,
+        algorithm: JwtAlgorithm::Hs256,
+        encoding: EncodingKey::from_secret(secret.as_bytes()),
+        decoding: DecodingKey::from_secret(secret.as_bytes()),
+        public_jwk: None,
+        verify_until,
+    }
+}
+
+/// Modulus and exponent of an RSA private key in PKCS#1 or PKCS#8 PEM.
+fn rsa_public_components(pem_str: &str) -> Result<(Vec<u8>, Vec<u8>), AuthError> {
+    let invalid = |e: String| {
+        AuthError::ValidationError(Message::new("auth.invalid_rsa_key").arg("error", e))
+    };
+    let parsed = pem::parse(pem_str.trim()).map_err(|e| invalid(e.to_string()))?;
+    let pair = match parsed.tag() {
+        "PRIVATE KEY" => ring::rsa::KeyPair::from_pkcs8(parsed.contents()),
+        "RSA PRIVATE KEY" => ring::rsa::KeyPair::from_der(parsed.contents()),
+        tag => return Err(invalid(format!("unexpected PEM block '{tag}'"))),
+    }
+    .map_err(|e| invalid(e.to_string()))?;
+    let components = ring::rsa::PublicKeyComponents::<Vec<u8>>::from(pair.public());
+    Ok((components.n, components.e))
+}
+
+// =============================================================================
+// Storage
+// =============================================================================
+
+/// Database row for `jwt_signing_keys`, without the private key.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SigningKeyRow {
+    pub kid: String,
+    pub algorithm: String,
+    pub created_at: DateTime<Utc>,
+    pub retired_at: Option<DateTime<Utc>>,
+    pub verify_until: Option<DateTime<Utc>>,
+}
+
+/// List stored keys, newest first.
+pub async fn list_keys(pool: &PgPool) -> Result<Vec<SigningKeyRow>, AuthError> {
+    let rows = sqlx::query_as::<_, SigningKeyRow>(
+        r#"
+        SELECT kid, algorithm, created_at, retired_at, verify_until
+        FROM jwt_signing_keys
+        ORDER BY created_at DESC
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+async fn private_key(pool: &PgPool, kid: &str, encryption_key: &str) -> Result<String, AuthError> {
+    let encrypted: String =
+        sqlx::query_scalar("SELECT private_key FROM jwt_signing_keys WHERE kid = $1")
+            .bind(kid)
+            .fetch_one(pool)
+            .await?;
+    secrets::decrypt(&encrypted, encryption_key)
+        .map_err(|e| AuthError::Internal(format!("Failed to decrypt JWT key {kid}: {e}")))
+}
+
+/// Create a new active key with the configured algorithm, retire the
+/// current one (it keeps verifying for the configured overlap) and reload
+/// `keys`.
+pub async fn rotate(
+    pool: &PgPool,
+    keys: &JwtKeys,
+    encryption_key: &str,
+) -> Result<SigningKeyRow, AuthError> {
+    let algorithm_name = system_value(pool, ALGORITHM_CONFIG_KEY).await?;
+    let algorithm = JwtAlgorithm::parse(&algorithm_name).ok_or_else(|| {
+        AuthError::ValidationError(
+            Message::new("auth.unsupported_jwt_algorithm").arg("algorithm", algorithm_name),
+        )
+    })?;
+    let overlap = overlap_minutes(pool).await?;
+
+    let private = match algorithm {
+        JwtAlgorithm::Hs256 => rng()
+            .sample_iter(&Alphanumeric)
+            .take(64)
+            .map(char::from)
+            .collect(),
+        JwtAlgorithm::Rs256 => {
+            // ring cannot generate RSA keys; the admin supplies one.
+            let encrypted = system_value(pool, RSA_KEY_CONFIG_KEY).await?;
+            if encrypted.is_empty() {
+                return Err(AuthError::ValidationError(
+                    Message::new("auth.rs256_key_missing").arg("key", RSA_KEY_CONFIG_KEY),
+                ));
+            }
+            secrets::decrypt(&encrypted, encryption_key)
+                .map_err(|e| AuthError::Internal(format!("Failed to decrypt RSA key: {e}")))?
+        }
+        JwtAlgorithm::EdDsa => {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new())
+                .map_err(|e| AuthError::Internal(format!("Ed25519 key generation: {e}")))?;
+            base64::engine::general_purpose::STANDARD.encode(pkcs8.as_ref())
+        }
+    };
+    let kid: String = rng()
+        .sample_iter(&Alphanumeric)
+        .take(16)
+        .map(char::from)
+        .collect();
+    // Reject unusable material before it becomes the active key.
+    SigningKey::from_private(Some(kid.clone()), algorithm, &private, None)?;
+    let encrypted = secrets::encrypt(&private, encryption_key)
+        .map_err(|e| AuthError::Internal(format!("Failed to encrypt JWT key: {e}")))?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        r#"
+        UPDATE jwt_signing_keys
+        SET retired_at = now(), verify_until = now() + make_interval(mins => $1)
+        WHERE retired_at IS NULL
+        "#,
+    )
+    .bind(overlap as i32)
+    .execute(&mut *tx)
+    .await?;
+    let row = sqlx::query_as::<_, SigningKeyRow>(
+        r#"
+        INSERT INTO jwt_signing_keys (kid, algorithm, private_key)
+        VALUES ($1, $2, $3)
+        RETURNING kid, algorithm, created_at, retired_at, verify_until
+        "#,
+    )
+    .bind(&kid)
+    .bind(algorithm.as_str())
+    .bind(&encrypted)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    keys.reload(pool, encryption_key).await?;
+    info!(kid = %kid, algorithm = algorithm.as_str(), "rotated JWT signing key");
+    Ok(row)
+}
+
+/// Overlap period in minutes, never shorter than an access token lifetime.
+async fn overlap_minutes(pool: &PgPool) -> Result<i64, AuthError> {
+    let value = system_value(pool, OVERLAP_CONFIG_KEY).await?;
+    Ok(value
+        .parse::<i64>()
+        .unwrap_or(MIN_OVERLAP_MINUTES)
+        .max(MIN_OVERLAP_MINUTES))
+}
+
+/// System-scope value of `key`, or its definition default.
+async fn system_value(pool: &PgPool, key: &str) -> Result<String, AuthError> {
+    let map = |e: ConfigError| match e {
+        ConfigError::DbError(e) => AuthError::DbError(e),
+        other => AuthError::Internal(other.to_string()),
+    };
+    if let Some(v) = queries::get_value(pool, key, &ConfigScope::System, None)
+        .await
+        .map_err(map)?
+    {
+        return Ok(v.value);
+    }
+    let def = queries::get_definition(pool, key)
+        .await
+        .map_err(map)?
+        .ok_or_else(|| AuthError::Internal(format!("Config key not found: {key}")))?;
+    Ok(def.default_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::auth::TokenClaims;
+
+    fn claims() -> TokenClaims {
+        let now = Utc::now().timestamp();
+        TokenClaims {
+            sub: "u1".into(),
+            email: "a@example.com".into(),
+            roles: vec![],
+            exp: now + 60,
+            iat: now,
+        }
+    }
+
+    fn ed25519_private() -> String {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new()).unwrap();
+        base64::engine::general_purpose::STANDARD.encode(pkcs8.as_ref())
+    }
+
+    #[test]
+    fn legacy_tokens_have_no_kid_and_verify() {
+        let keys = JwtKeys::from_secret("secret");
+        let token = keys.sign(&claims()).unwrap();
+        assert!(jsonwebtoken::decode_header(&token).unwrap().kid.is_none());
+        let verified: TokenClaims = keys.verify(&token).unwrap();
+        assert_eq!(verified.sub, "u1");
+        assert!(
+            JwtKeys::from_secret("other")
+                .verify::<TokenClaims>(&token)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn eddsa_key_signs_with_kid_and_publishes_jwk() {
+        let keys = JwtKeys::from_secret("secret");
+        let ed = SigningKey::from_private(
+            Some("k1".into()),
+            JwtAlgorithm::EdDsa,
+            &ed25519_private(),
+            None,
+        )
+        .unwrap();
+        {
+            let mut set = keys.set.write().unwrap();
+            set.keys.push(ed);
+            set.active = 1;
+        }
+
+        let token = keys.sign(&claims()).unwrap();
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+        assert_eq!(header.kid.as_deref(), Some("k1"));
+        assert_eq!(header.alg, Algorithm::EdDSA);
+        assert!(keys.verify::<TokenClaims>(&token).is_some());
+
+        let published = keys.jwks().keys;
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0]["kid"], "k1");
+        assert_eq!(published[0]["crv"], "Ed25519");
+    }
+
+    #[test]
+    fn expired_overlap_stops_verifying() {
+        let keys = JwtKeys::from_secret("secret");
+        let token = keys.sign(&claims()).unwrap();
+        let ed = SigningKey::from_private(
+            Some("k2".into()),
+            JwtAlgorithm::EdDsa,
+            &ed25519_private(),
+            None,
+        )
+        .unwrap();
+        {
+            let mut set = keys.set.write().unwrap();
+            set.keys[0].verify_until = Some(Utc::now() - chrono::Duration::seconds(1));
+            set.keys.push(ed);
+            set.active = 1;
+        }
+        assert!(keys.verify::<TokenClaims>(&token).is_none());
+    }
+
+    #[test]
+    fn algorithm_names_roundtrip() {
+        for alg in [
+            JwtAlgorithm::Hs256,
+            JwtAlgorithm::Rs256,
+            JwtAlgorithm::EdDsa,
+        ] {
+            assert_eq!(JwtAlgorithm::parse(alg.as_str()), Some(alg));
+        }
+        assert_eq!(JwtAlgorithm::parse("none"), None);
+    }
+}
@@ -0,0 +1,89 @@
+//! Local single-user mode.
+//!
+//! A personal desktop install has one user and no need for accounts. In
+//! local mode the desktop app mints a random token at startup and hands it
+//! to its API sidecar, which accepts it in place of a login for the single
+//! local user provisioned here. Server deployments never enable it.
+
+use rand::distr::Alphanumeric;
+use rand::{Rng, rng};
+use sqlx::PgPool;
+use tracing::info;
+
+use super::AuthError;
+use super::queries;
+use crate::models::auth::User;
+
+/// Email of the user created for local mode.
+pub const LOCAL_USER_EMAIL: &str = "local@nize.localhost";
+
+/// Display name of the user created for local mode.
+pub const LOCAL_USER_NAME: &str = "Local user";
+
+/// Shortest local token accepted, so a misconfigured launcher cannot open
+/// the API with a guessable one.
+pub const MIN_TOKEN_LEN: usize = 32;
+
+/// Generate a local token (64 alphanumeric chars).
+pub fn generate_token() -> String {
+    rng()
+        .sample_iter(&Alphanumeric)
+        .take(64)
+        .map(char::from)
+        .collect()
+}
+
+/// The user local mode signs in as: the local user from an earlier run,
+/// else the first admin (a desktop that had accounts keeps its data), else
+/// a new passwordless admin.
+pub async fn provision_user(pool: &PgPool) -> Result<User, AuthError> {
+    let existing = sqlx::query_as::<_, (String, String, Option<String>)>(
+        r#"
+        SELECT id::text, email, name FROM users WHERE email = $1
+        UNION ALL
+        (SELECT u.id::text, u.email, u.name
+         FROM users u
+         JOIN user_roles r ON r.user_id = u.id AND r.role = 'admin'
+         ORDER BY u.created_at
+         LIMIT 1)
+        LIMIT 1
+        "#,
+    )
+    .bind(LOCAL_USER_EMAIL)
+    .fetch_optional(pool)
+    .await?;
+    if let Some((id, email, name)) = existing {
+        return Ok(User { id, email, name });
+    }
+
+    let id = sqlx::query_scalar::<_, String>(
+        "INSERT INTO users (email, name) VALUES ($1, $2) RETURNING id::text",
+    )
+    .bind(LOCAL_USER_EMAIL)
+    .bind(LOCAL_USER_NAME)
+    .fetch_one(pool)
+    .await?;
+    queries::grant_role(pool, &id, "admin").await?;
+    info!(user_id = %id, "provisioned local user");
+
+    Ok(User {
+        id,
+        email: LOCAL_USER_EMAIL.to_string(),
+        name: Some(LOCAL_USER_NAME.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::constant_time_eq;
+
+    #[test]
+    fn generated_tokens_are_long_enough_and_compare() {
+        let token = generate_token();
+        assert!(token.len() >= MIN_TOKEN_LEN);
+        assert!(constant_time_eq(&token, &token.clone()));
+        assert!(!constant_time_eq(&token, &generate_token()));
+        assert!(!constant_time_eq(&token, &token[1..]));
+    }
+}
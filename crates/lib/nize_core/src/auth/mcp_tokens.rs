@@ -1,16 +1,39 @@
 //! MCP API token management.
 //!
 //! Long-lived bearer tokens for MCP client authentication.
+//!
+//! Validated tokens are cached for [`VALIDATION_TTL`], so MCP requests do
+//! not each query the database. Revoking a token or erasing its user drops
+//! the user's cached tokens on this instance at once; other instances
+//! notice within the TTL.
+
+use std::sync::LazyLock;
+use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use rand::distr::Alphanumeric;
 use rand::{Rng, rng};
 use sha2::{Digest, Sha256};
 use sqlx::PgPool;
 
 use super::AuthError;
-use crate::models::auth::{McpTokenRecord, User};
+use crate::cache::Cache;
+use crate::i18n::Message;
+use crate::models::auth::{McpTokenAuth, McpTokenRecord, User};
 use crate::uuid::uuidv7;
 
+/// How long a validated token is trusted without asking the database.
+pub const VALIDATION_TTL: Duration = Duration::from_secs(30);
+
+/// Recently validated tokens, by hash.
+static VALIDATED: LazyLock<Cache<String, McpTokenAuth>> =
+    LazyLock::new(|| Cache::new("mcp_tokens", 4096, VALIDATION_TTL));
+
+/// Drop the cached validations of `user_id`'s tokens.
+pub fn forget_validated(user_id: &str) {
+    VALIDATED.retain(|_, auth| auth.user.id != user_id);
+}
+
 /// Generate a random token (64 alphanumeric chars).
 fn generate_token() -> String {
     rng()
@@ -32,12 +55,14 @@ fn hash_token(token: &str) -> String {
 /// When `overwrite` is true, any existing active (non-revoked) token with the
 /// same name for this user is revoked before creating the new one.
 /// When `overwrite` is false, returns an error if an active token with the same
-/// name already exists.
+/// name already exists. A `read_only` token cannot run tools that change
+/// anything.
 pub async fn create_mcp_token(
     pool: &PgPool,
     user_id: &str,
     name: &str,
     overwrite: bool,
+    read_only: bool,
 ) -> Result<(String, McpTokenRecord), AuthError> {
     if overwrite {
         // Revoke any existing active token with the same name for this user
@@ -49,6 +74,7 @@ pub async fn create_mcp_token(
         .bind(name)
         .execute(pool)
         .await?;
+        forget_validated(user_id);
     } else {
         // Check for existing active token with same name
         let existing = sqlx::query_as::<_, (i64,)>(
@@ -61,9 +87,9 @@ pub async fn create_mcp_token(
         .await?;
 
         if existing.0 > 0 {
-            return Err(AuthError::ValidationError(format!(
-                "An active token with name '{name}' already exists. Use overwrite=true to replace it."
-            )));
+            return Err(AuthError::ValidationError(
+                Message::new("auth.mcp_token_name_taken").arg("name", name),
+            ));
         }
     }
 
@@ -71,14 +97,15 @@ pub async fn create_mcp_token(
     let token_hash = hash_token(&plaintext);
 
     let row = sqlx::query_as::<_, (String, chrono::DateTime<chrono::Utc>)>(
-        "INSERT INTO mcp_tokens (id, user_id, token_hash, name) \
-         VALUES ($1, $2::uuid, $3, $4) \
+        "INSERT INTO mcp_tokens (id, user_id, token_hash, name, read_only) \
+         VALUES ($1, $2::uuid, $3, $4, $5) \
          RETURNING id::text, created_at",
     )
     .bind(uuidv7())
     .bind(user_id)
     .bind(&token_hash)
     .bind(name)
+    .bind(read_only)
     .fetch_one(pool)
     .await?;
 
@@ -86,6 +113,7 @@ pub async fn create_mcp_token(
         id: row.0,
         user_id: user_id.to_string(),
         name: name.to_string(),
+        read_only,
         created_at: row.1,
         expires_at: None,
         revoked_at: None,
@@ -94,12 +122,19 @@ pub async fn create_mcp_token(
     Ok((plaintext, record))
 }
 
-/// Validate an MCP bearer token. Returns the associated user if valid.
-pub async fn validate_mcp_token(pool: &PgPool, token: &str) -> Result<Option<User>, AuthError> {
+/// Validate an MCP bearer token. Returns the associated user and the
+/// token's restrictions if valid.
+pub async fn validate_mcp_token(
+    pool: &PgPool,
+    token: &str,
+) -> Result<Option<McpTokenAuth>, AuthError> {
     let token_hash = hash_token(token);
+    if let Some(auth) = VALIDATED.get(&token_hash) {
+        return Ok(Some(auth));
+    }
 
-    let row = sqlx::query_as::<_, (String, String, Option<String>)>(
-        "SELECT u.id::text, u.email, u.name \
+    let row = sqlx::query_as::<_, (String, String, Option<String>, bool, Option<DateTime<Utc>>)>(
+        "SELECT u.id::text, u.email, u.name, mt.read_only, mt.expires_at \
          FROM mcp_tokens mt \
          JOIN users u ON u.id = mt.user_id \
          WHERE mt.token_hash = $1 \
@@ -110,15 +145,31 @@ pub async fn validate_mcp_token(pool: &PgPool, token: &str) -> Result<Option<Use
     .fetch_optional(pool)
     .await?;
 
-    Ok(row.map(|(id, email, name)| User { id, email, name }))
+    Ok(row.map(|(id, email, name, read_only, expires_at)| {
+        let auth = McpTokenAuth {
+            user: User { id, email, name },
+            read_only,
+        };
+        // Never trust a cached token past its expiry.
+        let ttl = expires_at
+            .and_then(|at| (at - Utc::now()).to_std().ok())
+            .map_or(VALIDATION_TTL, |left| left.min(VALIDATION_TTL));
+        VALIDATED.insert_with_ttl(token_hash, auth.clone(), ttl);
+        auth
+    }))
 }
 
 /// Revoke an MCP token by ID.
 pub async fn revoke_mcp_token(pool: &PgPool, token_id: &str) -> Result<(), AuthError> {
-    sqlx::query("UPDATE mcp_tokens SET revoked_at = now() WHERE id = $1::uuid")
-        .bind(token_id)
-        .execute(pool)
-        .await?;
+    let user_id: Option<String> = sqlx::query_scalar(
+        "UPDATE mcp_tokens SET revoked_at = now() WHERE id = $1::uuid RETURNING user_id::text",
+    )
+    .bind(token_id)
+    .fetch_optional(pool)
+    .await?;
+    if let Some(user_id) = user_id {
+        forget_validated(&user_id);
+    }
     Ok(())
 }
 
@@ -133,12 +184,13 @@ pub async fn list_mcp_tokens(
             String,
             String,
             String,
+            bool,
             chrono::DateTime<chrono::Utc>,
             Option<chrono::DateTime<chrono::Utc>>,
             Option<chrono::DateTime<chrono::Utc>>,
         ),
     >(
-        "SELECT id::text, user_id::text, name, created_at, expires_at, revoked_at \
+        "SELECT id::text, user_id::text, name, read_only, created_at, expires_at, revoked_at \
          FROM mcp_tokens \
          WHERE user_id = $1::uuid \
          ORDER BY created_at DESC",
@@ -150,10 +202,11 @@ pub async fn list_mcp_tokens(
     Ok(rows
         .into_iter()
         .map(
-            |(id, user_id, name, created_at, expires_at, revoked_at)| McpTokenRecord {
+            |(id, user_id, name, read_only, created_at, expires_at, revoked_at)| McpTokenRecord {
                 id,
                 user_id,
                 name,
+                read_only,
                 created_at,
                 expires_at,
                 revoked_at,
@@ -161,3 +214,26 @@ pub async fn list_mcp_tokens(
         )
         .collect())
 }
+
+/// Check `token` against the user's active token `token_id`. Returns
+/// `None` when the user has no such active token, otherwise whether `token`
+/// is its plaintext.
+pub async fn check_mcp_token(
+    pool: &PgPool,
+    user_id: &str,
+    token_id: &str,
+    token: &str,
+) -> Result<Option<bool>, AuthError> {
+    let stored: Option<String> = sqlx::query_scalar(
+        "SELECT token_hash FROM mcp_tokens \
+         WHERE id = $1::uuid AND user_id = $2::uuid \
+           AND revoked_at IS NULL \
+           AND (expires_at IS NULL OR expires_at > now())",
+    )
+    .bind(token_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(stored.map(|hash| hash == hash_token(token)))
+}
@@ -3,13 +3,21 @@
 //! Provides password hashing, JWT management, and database queries
 //! that can be shared across `nize_api` and `nize_mcp`.
 
+pub mod activity;
 pub mod jwt;
+pub mod keys;
+pub mod local;
 pub mod mcp_tokens;
 pub mod password;
 pub mod queries;
+pub mod rbac;
+pub mod verification;
 
+use subtle::ConstantTimeEq;
 use thiserror::Error;
 
+use crate::i18n::Message;
+
 /// Authentication errors.
 #[derive(Debug, Error)]
 pub enum AuthError {
@@ -17,10 +25,10 @@ pub enum AuthError {
     CredentialError,
 
     #[error("Token error: {0}")]
-    TokenError(String),
+    TokenError(Message),
 
     #[error("Validation error: {0}")]
-    ValidationError(String),
+    ValidationError(Message),
 
     #[error("Database error: {0}")]
     DbError(#[from] sqlx::Error),
@@ -28,3 +36,9 @@ pub enum AuthError {
     #[error("Internal error: {0}")]
     Internal(String),
 }
+
+/// Compare two secrets (tokens, CSRF values) in constant time. Only the
+/// lengths may leak.
+pub fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}
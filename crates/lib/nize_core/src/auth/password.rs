@@ -1,17 +1,347 @@
-//! Password hashing via bcrypt.
+//! Password hashing with upgradeable algorithms.
+//!
+//! Stored hashes are tagged with their algorithm and work factors in the
+//! usual modular crypt format: `$2b$<cost>$…` for bcrypt and
+//! `$argon2id$v=19$m=<KiB>,t=<iterations>,p=<lanes>$…` for Argon2id, so
+//! hashes made with different settings can live side by side. New hashes
+//! use the [`PasswordPolicy`] configured in `system.auth.password*`; a hash
+//! that does not match it ([`needs_rehash`]) is replaced after the next
+//! successful login, when the password is at hand.
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Argon2, Params, Version};
+use sqlx::PgPool;
 
 use super::AuthError;
 
-/// bcrypt cost factor.
+use crate::i18n::Message;
+
+/// Config key selecting the algorithm for new hashes.
+pub const ALGORITHM_CONFIG_KEY: &str = "system.auth.passwordAlgorithm";
+/// Config key for the bcrypt cost factor.
+pub const BCRYPT_COST_CONFIG_KEY: &str = "system.auth.passwordBcryptCost";
+/// Config key for the Argon2id memory cost, in KiB.
+pub const ARGON2_MEMORY_CONFIG_KEY: &str = "system.auth.passwordArgon2MemoryKib";
+/// Config key for the Argon2id iteration count.
+pub const ARGON2_ITERATIONS_CONFIG_KEY: &str = "system.auth.passwordArgon2Iterations";
+/// Config key for the Argon2id degree of parallelism.
+pub const ARGON2_PARALLELISM_CONFIG_KEY: &str = "system.auth.passwordArgon2Parallelism";
+
+/// Every config key of the policy.
+const CONFIG_KEYS: [&str; 5] = [
+    ALGORITHM_CONFIG_KEY,
+    BCRYPT_COST_CONFIG_KEY,
+    ARGON2_MEMORY_CONFIG_KEY,
+    ARGON2_ITERATIONS_CONFIG_KEY,
+    ARGON2_PARALLELISM_CONFIG_KEY,
+];
+
+/// bcrypt cost factor of hashes made before policies existed.
 const BCRYPT_COST: u32 = 10;
 
-/// Hash a password with bcrypt (cost 10).
-pub fn hash_password(password: &str) -> Result<String, AuthError> {
-    bcrypt::hash(password, BCRYPT_COST)
-        .map_err(|e| AuthError::Internal(format!("bcrypt hash: {e}")))
+/// Accepted bcrypt cost factors; below the legacy cost is too weak.
+const BCRYPT_COST_RANGE: std::ops::RangeInclusive<u32> = BCRYPT_COST..=16;
+
+/// Supported hashing algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordAlgorithm {
+    Bcrypt,
+    Argon2id,
+}
+
+impl PasswordAlgorithm {
+    /// Name as used in config.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PasswordAlgorithm::Bcrypt => "bcrypt",
+            PasswordAlgorithm::Argon2id => "argon2id",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "bcrypt" => Some(PasswordAlgorithm::Bcrypt),
+            "argon2id" => Some(PasswordAlgorithm::Argon2id),
+            _ => None,
+        }
+    }
+}
+
+/// Algorithm and work factors for new hashes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub algorithm: PasswordAlgorithm,
+    pub bcrypt_cost: u32,
+    pub argon2_memory_kib: u32,
+    pub argon2_iterations: u32,
+    pub argon2_parallelism: u32,
 }
 
-/// Verify a password against a bcrypt hash.
+impl Default for PasswordPolicy {
+    /// Argon2id with the parameters recommended by OWASP.
+    fn default() -> Self {
+        Self {
+            algorithm: PasswordAlgorithm::Argon2id,
+            bcrypt_cost: BCRYPT_COST,
+            argon2_memory_kib: Params::DEFAULT_M_COST,
+            argon2_iterations: Params::DEFAULT_T_COST,
+            argon2_parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Read the configured policy: system values, else the definition
+    /// defaults, in one query.
+    pub async fn load(pool: &PgPool) -> Result<Self, AuthError> {
+        let values = sqlx::query_as::<_, (String, String)>(
+            "SELECT d.key, COALESCE(v.value, d.default_value) \
+             FROM config_definitions d \
+             LEFT JOIN config_values v \
+               ON v.key = d.key AND v.scope = 'system' AND v.user_id IS NULL \
+             WHERE d.key = ANY($1)",
+        )
+        .bind(&CONFIG_KEYS[..])
+        .fetch_all(pool)
+        .await?;
+        Ok(Self::from_values(|key| {
+            values.iter().find(|(k, _)| k == key).map(|(_, v)| v.trim())
+        }))
+    }
+
+    /// Build the policy from config values looked up by key. Missing or
+    /// unusable values fall back to the defaults rather than weakening new
+    /// hashes or failing logins.
+    fn from_values<'a>(value: impl Fn(&str) -> Option<&'a str>) -> Self {
+        let default = Self::default();
+        let number = |key: &str, fallback: u32| {
+            value(key)
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(fallback)
+        };
+        let bcrypt_cost = number(BCRYPT_COST_CONFIG_KEY, default.bcrypt_cost);
+        let policy = Self {
+            algorithm: value(ALGORITHM_CONFIG_KEY)
+                .and_then(PasswordAlgorithm::parse)
+                .unwrap_or(default.algorithm),
+            bcrypt_cost: if BCRYPT_COST_RANGE.contains(&bcrypt_cost) {
+                bcrypt_cost
+            } else {
+                default.bcrypt_cost
+            },
+            argon2_memory_kib: number(ARGON2_MEMORY_CONFIG_KEY, default.argon2_memory_kib),
+            argon2_iterations: number(ARGON2_ITERATIONS_CONFIG_KEY, default.argon2_iterations),
+            argon2_parallelism: number(ARGON2_PARALLELISM_CONFIG_KEY, default.argon2_parallelism),
+        };
+        if policy.argon2_params().is_err() {
+            return Self {
+                argon2_memory_kib: default.argon2_memory_kib,
+                argon2_iterations: default.argon2_iterations,
+                argon2_parallelism: default.argon2_parallelism,
+                ..policy
+            };
+        }
+        policy
+    }
+
+    fn argon2_params(&self) -> Result<Params, AuthError> {
+        if self.argon2_memory_kib < Params::DEFAULT_M_COST / 2 {
+            return Err(AuthError::ValidationError(
+                Message::new("auth.argon2_memory_too_low").arg("min", Params::DEFAULT_M_COST / 2),
+            ));
+        }
+        Params::new(
+            self.argon2_memory_kib,
+            self.argon2_iterations,
+            self.argon2_parallelism,
+            None,
+        )
+        .map_err(|e| {
+            AuthError::ValidationError(Message::new("auth.invalid_argon2_params").arg("error", e))
+        })
+    }
+}
+
+/// Algorithm and work factors read from a stored hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HashTag {
+    Bcrypt { cost: u32 },
+    Argon2id { params: (u32, u32, u32) },
+}
+
+impl HashTag {
+    fn of(hash: &str) -> Result<Self, AuthError> {
+        let unknown = || AuthError::Internal("unrecognized password hash format".into());
+        if hash.starts_with("$argon2id$") {
+            let parsed = PasswordHash::new(hash).map_err(|_| unknown())?;
+            let params = Params::try_from(&parsed).map_err(|_| unknown())?;
+            return Ok(HashTag::Argon2id {
+                params: (params.m_cost(), params.t_cost(), params.p_cost()),
+            });
+        }
+        let mut fields = hash.split('$');
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some(""), Some("2a" | "2b" | "2x" | "2y"), Some(cost)) => Ok(HashTag::Bcrypt {
+                cost: cost.parse().map_err(|_| unknown())?,
+            }),
+            _ => Err(unknown()),
+        }
+    }
+}
+
+/// Hash a password with `policy`.
+pub fn hash_password_with(password: &str, policy: &PasswordPolicy) -> Result<String, AuthError> {
+    match policy.algorithm {
+        PasswordAlgorithm::Bcrypt => bcrypt::hash(password, policy.bcrypt_cost)
+            .map_err(|e| AuthError::Internal(format!("bcrypt hash: {e}"))),
+        PasswordAlgorithm::Argon2id => {
+            let salt = SaltString::generate(&mut OsRng);
+            argon2(policy.argon2_params()?)
+                .hash_password(password.as_bytes(), &salt)
+                .map(|h| h.to_string())
+                .map_err(|e| AuthError::Internal(format!("argon2 hash: {e}")))
+        }
+    }
+}
+
+/// Verify a password against a stored hash of any supported algorithm.
 pub fn verify_password(password: &str, hash: &str) -> Result<bool, AuthError> {
-    bcrypt::verify(password, hash).map_err(|e| AuthError::Internal(format!("bcrypt verify: {e}")))
+    match HashTag::of(hash)? {
+        HashTag::Bcrypt { .. } => bcrypt::verify(password, hash)
+            .map_err(|e| AuthError::Internal(format!("bcrypt verify: {e}"))),
+        HashTag::Argon2id { .. } => {
+            let parsed = PasswordHash::new(hash)
+                .map_err(|e| AuthError::Internal(format!("argon2 verify: {e}")))?;
+            Ok(Argon2::default()
+                .verify_password(password.as_bytes(), &parsed)
+                .is_ok())
+        }
+    }
+}
+
+/// Whether a stored hash was made with another algorithm or other work
+/// factors than `policy` asks for. Unrecognized hashes need one too.
+pub fn needs_rehash(hash: &str, policy: &PasswordPolicy) -> bool {
+    match (HashTag::of(hash), policy.algorithm) {
+        (Ok(HashTag::Bcrypt { cost }), PasswordAlgorithm::Bcrypt) => cost != policy.bcrypt_cost,
+        (Ok(HashTag::Argon2id { params }), PasswordAlgorithm::Argon2id) => {
+            params
+                != (
+                    policy.argon2_memory_kib,
+                    policy.argon2_iterations,
+                    policy.argon2_parallelism,
+                )
+        }
+        _ => true,
+    }
+}
+
+fn argon2(params: Params) -> Argon2<'static> {
+    Argon2::new(argon2::Algorithm::Argon2id, Version::V0x13, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheapest settings each algorithm accepts, to keep the tests fast.
+    fn cheap(algorithm: PasswordAlgorithm) -> PasswordPolicy {
+        PasswordPolicy {
+            algorithm,
+            bcrypt_cost: 4,
+            argon2_memory_kib: Params::DEFAULT_M_COST / 2,
+            argon2_iterations: 1,
+            argon2_parallelism: 1,
+        }
+    }
+
+    #[test]
+    fn legacy_bcrypt_hashes_verify_and_upgrade() {
+        // The format stored before policies: bcrypt at cost 10.
+        let legacy = bcrypt::hash("hunter22", BCRYPT_COST).unwrap();
+        assert!(verify_password("hunter22", &legacy).unwrap());
+        assert!(!verify_password("hunter23", &legacy).unwrap());
+        assert!(needs_rehash(&legacy, &PasswordPolicy::default()));
+        let bcrypt_policy = PasswordPolicy {
+            algorithm: PasswordAlgorithm::Bcrypt,
+            ..PasswordPolicy::default()
+        };
+        assert!(!needs_rehash(&legacy, &bcrypt_policy));
+
+        let policy = cheap(PasswordAlgorithm::Argon2id);
+        let upgraded = hash_password_with("hunter22", &policy).unwrap();
+        assert!(upgraded.starts_with("$argon2id$v=19$"));
+        assert!(verify_password("hunter22", &upgraded).unwrap());
+        assert!(!verify_password("hunter23", &upgraded).unwrap());
+        assert!(!needs_rehash(&upgraded, &policy));
+    }
+
+    #[test]
+    fn changed_work_factors_need_a_rehash() {
+        let policy = cheap(PasswordAlgorithm::Argon2id);
+        let hash = hash_password_with("hunter22", &policy).unwrap();
+        let stronger = PasswordPolicy {
+            argon2_iterations: 2,
+            ..policy
+        };
+        assert!(needs_rehash(&hash, &stronger));
+        assert!(needs_rehash(&hash, &cheap(PasswordAlgorithm::Bcrypt)));
+
+        let bcrypt = cheap(PasswordAlgorithm::Bcrypt);
+        let hash = hash_password_with("hunter22", &bcrypt).unwrap();
+        assert!(hash.starts_with("$2b$04$"));
+        assert!(!needs_rehash(&hash, &bcrypt));
+        assert!(needs_rehash(
+            &hash,
+            &PasswordPolicy {
+                bcrypt_cost: 5,
+                ..bcrypt
+            }
+        ));
+    }
+
+    #[test]
+    fn unknown_formats_are_rejected() {
+        assert!(verify_password("x", "plaintext").is_err());
+        assert!(verify_password("x", "$argon2i$v=19$m=8,t=1,p=1$c2FsdA$aGFzaA").is_err());
+        assert!(needs_rehash("plaintext", &PasswordPolicy::default()));
+    }
+
+    #[test]
+    fn unusable_config_values_fall_back_to_defaults() {
+        let policy = PasswordPolicy::from_values(|key| match key {
+            ALGORITHM_CONFIG_KEY => Some("bcrypt"),
+            BCRYPT_COST_CONFIG_KEY => Some("12"),
+            ARGON2_ITERATIONS_CONFIG_KEY => Some("3"),
+            _ => None,
+        });
+        assert_eq!(policy.algorithm, PasswordAlgorithm::Bcrypt);
+        assert_eq!(policy.bcrypt_cost, 12);
+        assert_eq!(policy.argon2_iterations, 3);
+        assert_eq!(policy.argon2_memory_kib, Params::DEFAULT_M_COST);
+
+        let policy = PasswordPolicy::from_values(|key| match key {
+            ALGORITHM_CONFIG_KEY => Some("md5"),
+            BCRYPT_COST_CONFIG_KEY => Some("4"),
+            ARGON2_MEMORY_CONFIG_KEY => Some("64"),
+            ARGON2_ITERATIONS_CONFIG_KEY => Some("3"),
+            _ => Some("many"),
+        });
+        assert_eq!(policy, PasswordPolicy::default());
+    }
+
+    #[test]
+    fn weak_argon2_memory_is_refused() {
+        let policy = PasswordPolicy {
+            argon2_memory_kib: 64,
+            ..PasswordPolicy::default()
+        };
+        assert!(hash_password_with("x", &policy).is_err());
+        assert_eq!(
+            PasswordAlgorithm::parse("argon2id"),
+            Some(PasswordAlgorithm::Argon2id)
+        );
+        assert_eq!(PasswordAlgorithm::parse("md5"), None);
+    }
 }
@@ -38,6 +38,21 @@ pub async fn create_user(
     Ok(user_id)
 }
 
+/// Replace a user's password hash, e.g. with one made under a newer
+/// [`super::password::PasswordPolicy`].
+pub async fn update_password_hash(
+    pool: &PgPool,
+    user_id: &str,
+    password_hash: &str,
+) -> Result<(), AuthError> {
+    sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1::uuid")
+        .bind(user_id)
+        .bind(password_hash)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
 /// Fetch roles for a user.
 pub async fn get_user_roles(pool: &PgPool, user_id: &str) -> Result<Vec<String>, AuthError> {
     let rows = sqlx::query_scalar::<_, String>(
@@ -0,0 +1,479 @@
+//! Role-based access control.
+//!
+//! A role bundles permission strings from [`PERMISSIONS`]; users hold the
+//! union of the permissions of their assigned roles. `*` grants everything
+//! and is held by the built-in [`ADMINISTRATOR_ROLE`], which cannot be edited
+//! or deleted.
+//!
+//! The legacy `admin` entry in `user_roles` (the "is admin" flag carried in
+//! access tokens) keeps working and counts as an assignment of the
+//! Administrator role.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Wildcard permission granting every other permission.
+pub const PERM_ALL: &str = "*";
+/// Read and write system config.
+pub const PERM_CONFIG_WRITE: &str = "config.write";
+/// Manage built-in MCP servers.
+pub const PERM_MCP_ADMIN: &str = "mcp.admin";
+/// Manage roles, role assignments and the admin flag.
+pub const PERM_USERS_MANAGE: &str = "users.manage";
+/// View and revoke any user's grants and share links.
+pub const PERM_PERMISSIONS_ADMIN: &str = "permissions.admin";
+/// View usage analytics.
+pub const PERM_ANALYTICS_READ: &str = "analytics.read";
+/// Inspect and reindex embeddings.
+pub const PERM_EMBEDDINGS_ADMIN: &str = "embeddings.admin";
+/// Read developer chat traces.
+pub const PERM_DEV_TRACE: &str = "dev.trace";
+/// Scrape `GET /metrics` from non-local clients.
+pub const PERM_METRICS_READ: &str = "metrics.read";
+/// Export chat message feedback.
+pub const PERM_FEEDBACK_EXPORT: &str = "feedback.export";
+/// Run prompt eval suites and read their scores.
+pub const PERM_EVALS_RUN: &str = "evals.run";
+/// List and rotate JWT signing keys.
+pub const PERM_AUTH_KEYS: &str = "auth.keys";
+/// Create, edit and delete announcements.
+pub const PERM_ANNOUNCEMENTS_MANAGE: &str = "announcements.manage";
+/// Manage moderation rules and review moderation results.
+pub const PERM_MODERATION_MANAGE: &str = "moderation.manage";
+
+/// Every assignable permission with a short description.
+pub const PERMISSIONS: &[(&str, &str)] = &[
+    (PERM_CONFIG_WRITE, "Read and write system config"),
+    (PERM_MCP_ADMIN, "Manage built-in MCP servers"),
+    (PERM_USERS_MANAGE, "Manage roles and role assignments"),
+    (
+        PERM_PERMISSIONS_ADMIN,
+        "View and revoke all grants and share links",
+    ),
+    (PERM_ANALYTICS_READ, "View usage analytics"),
+    (PERM_EMBEDDINGS_ADMIN, "Inspect and reindex embeddings"),
+    (PERM_DEV_TRACE, "Read developer chat traces"),
+    (PERM_METRICS_READ, "Scrape server metrics"),
+    (PERM_FEEDBACK_EXPORT, "Export chat message feedback"),
+    (PERM_EVALS_RUN, "Run prompt eval suites"),
+    (PERM_AUTH_KEYS, "Rotate JWT signing keys"),
+    (PERM_ANNOUNCEMENTS_MANAGE, "Manage announcements"),
+    (
+        PERM_MODERATION_MANAGE,
+        "Manage and review content moderation",
+    ),
+];
+
+/// Name of the built-in role holding every permission.
+pub const ADMINISTRATOR_ROLE: &str = "administrator";
+
+/// Legacy `user_roles` value mapped to [`ADMINISTRATOR_ROLE`].
+pub const LEGACY_ADMIN_ROLE: &str = "admin";
+
+/// Maximum role name length in characters.
+pub const MAX_ROLE_NAME_CHARS: usize = 64;
+
+/// Errors that can occur in role operations.
+#[derive(Debug, Error)]
+pub enum RoleError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Not found: {0}")]
+    NotFound(Message),
+
+    #[error("Role '{0}' already exists")]
+    Duplicate(String),
+
+    #[error("Built-in role '{0}' cannot be changed")]
+    BuiltIn(String),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// Row returned by role queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RoleRow {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+    pub built_in: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A role with the number of users holding it.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RoleWithUsage {
+    #[sqlx(flatten)]
+    pub role: RoleRow,
+    pub user_count: i64,
+}
+
+const ROLE_COLUMNS: &str = "id, name, description, permissions, built_in, created_at, updated_at";
+
+/// Users holding a role: explicit assignments, plus legacy admins for the
+/// Administrator role.
+const ROLE_HOLDERS: &str = r#"
+    SELECT a.user_id, a.role_id FROM role_assignments a
+    UNION
+    SELECT u.user_id, r.id FROM user_roles u
+    JOIN roles r ON r.name = 'administrator'
+    WHERE u.role = 'admin'
+"#;
+
+/// Whether `permissions` grant `permission` (directly or via `*`).
+pub fn grants(permissions: &[String], permission: &str) -> bool {
+    permissions.iter().any(|p| p == PERM_ALL || p == permission)
+}
+
+/// Trim and validate a role name.
+fn validate_name(name: &str) -> Result<String, RoleError> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return Err(RoleError::Validation(Message::new("roles.name_required")));
+    }
+    if name.chars().count() > MAX_ROLE_NAME_CHARS {
+        return Err(RoleError::Validation(
+            Message::new("roles.name_too_long").arg("max", MAX_ROLE_NAME_CHARS),
+        ));
+    }
+    Ok(name)
+}
+
+/// Check that every permission is known, and return them sorted and
+/// deduplicated. `*` is reserved for the built-in Administrator role.
+fn validate_permissions(permissions: &[String]) -> Result<Vec<String>, RoleError> {
+    let mut out = Vec::with_capacity(permissions.len());
+    for p in permissions {
+        let p = p.trim();
+        if !PERMISSIONS.iter().any(|(known, _)| *known == p) {
+            return Err(RoleError::Validation(
+                Message::new("roles.unknown_permission").arg("permission", p),
+            ));
+        }
+        out.push(p.to_string());
+    }
+    out.sort();
+    out.dedup();
+    Ok(out)
+}
+
+/// List all roles with their user counts, built-in roles first.
+pub async fn list_roles(pool: &PgPool) -> Result<Vec<RoleWithUsage>, RoleError> {
+    let rows = sqlx::query_as::<_, RoleWithUsage>(&format!(
+        r#"
+        SELECT r.id, r.name, r.description, r.permissions, r.built_in, r.created_at, r.updated_at,
+               (SELECT COUNT(*) FROM ({ROLE_HOLDERS}) h WHERE h.role_id = r.id) AS user_count
+        FROM roles r
+        ORDER BY r.built_in DESC, r.name
+        "#
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Get a role by ID.
+pub async fn get_role(pool: &PgPool, role_id: &Uuid) -> Result<RoleRow, RoleError> {
+    sqlx::query_as::<_, RoleRow>(&format!("SELECT {ROLE_COLUMNS} FROM roles WHERE id = $1"))
+        .bind(role_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| RoleError::NotFound(Message::new("roles.not_found")))
+}
+
+/// Create a custom role.
+pub async fn create_role(
+    pool: &PgPool,
+    name: &str,
+    description: &str,
+    permissions: &[String],
+) -> Result<RoleRow, RoleError> {
+    let name = validate_name(name)?;
+    let permissions = validate_permissions(permissions)?;
+
+    sqlx::query_as::<_, RoleRow>(&format!(
+        r#"
+        INSERT INTO roles (id, name, description, permissions)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (name) DO NOTHING
+        RETURNING {ROLE_COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(&name)
+    .bind(description.trim())
+    .bind(&permissions)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(RoleError::Duplicate(name))
+}
+
+/// Update a custom role. `None` fields are left unchanged.
+pub async fn update_role(
+    pool: &PgPool,
+    role_id: &Uuid,
+    name: Option<&str>,
+    description: Option<&str>,
+    permissions: Option<&[String]>,
+) -> Result<RoleRow, RoleError> {
+    let current = get_role(pool, role_id).await?;
+    if current.built_in {
+        return Err(RoleError::BuiltIn(current.name));
+    }
+    let name = name.map(validate_name).transpose()?;
+    let permissions = permissions.map(validate_permissions).transpose()?;
+
+    if let Some(name) = &name
+        && *name != current.name
+    {
+        let taken =
+            sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM roles WHERE name = $1)")
+                .bind(name)
+                .fetch_one(pool)
+                .await?;
+        if taken {
+            return Err(RoleError::Duplicate(name.clone()));
+        }
+    }
+
+    let row = sqlx::query_as::<_, RoleRow>(&format!(
+        r#"
+        UPDATE roles
+        SET name = COALESCE($1, name),
+            description = COALESCE($2, description),
+            permissions = COALESCE($3, permissions),
+            updated_at = now()
+        WHERE id = $4
+        RETURNING {ROLE_COLUMNS}
+        "#
+    ))
+    .bind(name)
+    .bind(description.map(str::trim))
+    .bind(permissions)
+    .bind(role_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Delete a custom role; its assignments are removed with it.
+pub async fn delete_role(pool: &PgPool, role_id: &Uuid) -> Result<(), RoleError> {
+    let current = get_role(pool, role_id).await?;
+    if current.built_in {
+        return Err(RoleError::BuiltIn(current.name));
+    }
+    sqlx::query("DELETE FROM roles WHERE id = $1")
+        .bind(role_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Roles held by a user, including Administrator for legacy admins.
+pub async fn list_user_roles(pool: &PgPool, user_id: &Uuid) -> Result<Vec<RoleRow>, RoleError> {
+    let rows = sqlx::query_as::<_, RoleRow>(&format!(
+        r#"
+        SELECT {ROLE_COLUMNS}
+        FROM roles
+        WHERE id IN (SELECT h.role_id FROM ({ROLE_HOLDERS}) h WHERE h.user_id = $1)
+        ORDER BY built_in DESC, name
+        "#
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Effective permissions of a user: the union over their roles.
+pub async fn user_permissions(pool: &PgPool, user_id: &Uuid) -> Result<Vec<String>, RoleError> {
+    let rows = sqlx::query_scalar::<_, String>(&format!(
+        r#"
+        SELECT DISTINCT unnest(r.permissions) AS permission
+        FROM roles r
+        JOIN ({ROLE_HOLDERS}) h ON h.role_id = r.id
+        WHERE h.user_id = $1
+        ORDER BY permission
+        "#
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Users granted `permission` (directly or via `*`), e.g. to notify them.
+pub async fn users_with_permission(
+    pool: &PgPool,
+    permission: &str,
+) -> Result<Vec<Uuid>, RoleError> {
+    let rows = sqlx::query_scalar::<_, Uuid>(&format!(
+        r#"
+        SELECT DISTINCT h.user_id
+        FROM ({ROLE_HOLDERS}) h
+        JOIN roles r ON r.id = h.role_id
+        WHERE $1 = ANY(r.permissions) OR $2 = ANY(r.permissions)
+        "#
+    ))
+    .bind(permission)
+    .bind(PERM_ALL)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+async fn ensure_user_exists(pool: &PgPool, user_id: &Uuid) -> Result<(), RoleError> {
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+    if !exists {
+        return Err(RoleError::NotFound(Message::new("roles.user_not_found")));
+    }
+    Ok(())
+}
+
+/// Assign a role to a user. Assigning twice is a no-op.
+pub async fn assign_role(
+    pool: &PgPool,
+    user_id: &Uuid,
+    role_id: &Uuid,
+    granted_by: &Uuid,
+) -> Result<RoleRow, RoleError> {
+    ensure_user_exists(pool, user_id).await?;
+    let role = get_role(pool, role_id).await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO role_assignments (user_id, role_id, granted_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, role_id) DO NOTHING
+        "#,
+    )
+    .bind(user_id)
+    .bind(role_id)
+    .bind(granted_by)
+    .execute(pool)
+    .await?;
+    Ok(role)
+}
+
+/// Remove a role assignment. Removing the Administrator role also clears
+/// the legacy admin flag. Returns `false` if the user did not hold it.
+pub async fn unassign_role(
+    pool: &PgPool,
+    user_id: &Uuid,
+    role_id: &Uuid,
+) -> Result<bool, RoleError> {
+    let role = get_role(pool, role_id).await?;
+    if role.name == ADMINISTRATOR_ROLE {
+        ensure_other_administrator(pool, user_id).await?;
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut removed =
+        sqlx::query("DELETE FROM role_assignments WHERE user_id = $1 AND role_id = $2")
+            .bind(user_id)
+            .bind(role_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    if role.name == ADMINISTRATOR_ROLE {
+        removed += sqlx::query("DELETE FROM user_roles WHERE user_id = $1 AND role = 'admin'")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    }
+    tx.commit().await?;
+    Ok(removed > 0)
+}
+
+/// Set or clear the legacy admin flag.
+pub async fn set_admin_flag(
+    pool: &PgPool,
+    user_id: &Uuid,
+    is_admin: bool,
+    granted_by: &Uuid,
+) -> Result<(), RoleError> {
+    ensure_user_exists(pool, user_id).await?;
+    if is_admin {
+        sqlx::query(
+            r#"
+            INSERT INTO user_roles (user_id, role, granted_by)
+            VALUES ($1, 'admin', $2)
+            ON CONFLICT (user_id, role) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(granted_by)
+        .execute(pool)
+        .await?;
+    } else {
+        ensure_other_administrator(pool, user_id).await?;
+        sqlx::query("DELETE FROM user_roles WHERE user_id = $1 AND role = 'admin'")
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Fail if taking Administrator away from `user_id` would leave nobody
+/// holding it.
+pub async fn ensure_other_administrator(pool: &PgPool, user_id: &Uuid) -> Result<(), RoleError> {
+    let others = sqlx::query_scalar::<_, i64>(&format!(
+        r#"
+        SELECT COUNT(DISTINCT h.user_id)
+        FROM ({ROLE_HOLDERS}) h
+        JOIN roles r ON r.id = h.role_id
+        WHERE r.name = 'administrator' AND h.user_id <> $1
+        "#
+    ))
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+    if others == 0 {
+        return Err(RoleError::Validation(Message::new(
+            "roles.last_administrator",
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcard_grants_everything() {
+        let admin = vec![PERM_ALL.to_string()];
+        let editor = vec![PERM_CONFIG_WRITE.to_string()];
+        assert!(grants(&admin, PERM_MCP_ADMIN));
+        assert!(grants(&editor, PERM_CONFIG_WRITE));
+        assert!(!grants(&editor, PERM_USERS_MANAGE));
+        assert!(!grants(&[], PERM_CONFIG_WRITE));
+    }
+
+    #[test]
+    fn permissions_are_validated_and_normalized() {
+        let perms = validate_permissions(&[
+            PERM_MCP_ADMIN.to_string(),
+            format!(" {PERM_CONFIG_WRITE}"),
+            PERM_MCP_ADMIN.to_string(),
+        ])
+        .unwrap();
+        assert_eq!(perms, vec![PERM_CONFIG_WRITE, PERM_MCP_ADMIN]);
+        assert!(validate_permissions(&[PERM_ALL.to_string()]).is_err());
+        assert!(validate_permissions(&["users.delete".to_string()]).is_err());
+    }
+}
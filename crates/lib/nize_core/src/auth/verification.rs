@@ -0,0 +1,176 @@
+//! Email verification for self-registered accounts.
+//!
+//! An unverified account has `users.email_verified` unset. It gets a
+//! single-use token, emailed as a link; following the link before
+//! [`TOKEN_TTL`] runs out verifies the account. Tokens are stored hashed,
+//! and issuing a new one replaces the previous one.
+
+use chrono::{DateTime, Duration, Utc};
+use rand::distr::Alphanumeric;
+use rand::{Rng, rng};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use super::AuthError;
+use crate::uuid::uuidv7;
+
+/// How long a verification link stays valid.
+pub const TOKEN_TTL: Duration = Duration::hours(24);
+
+/// Shortest time between two verification emails to the same account.
+pub const RESEND_INTERVAL: Duration = Duration::minutes(1);
+
+/// Result of following a verification link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    Verified {
+        user_id: String,
+        email: String,
+    },
+    /// The token was issued but has run out; a new one can be requested.
+    Expired,
+    /// No such token, or it was already used or replaced.
+    Invalid,
+}
+
+/// An account waiting for verification.
+#[derive(Debug, Clone)]
+pub struct PendingUser {
+    pub user_id: String,
+    /// When its latest token was issued.
+    pub last_issued_at: Option<DateTime<Utc>>,
+}
+
+/// Generate a random token (64 alphanumeric chars).
+fn generate_token() -> String {
+    rng()
+        .sample_iter(&Alphanumeric)
+        .take(64)
+        .map(char::from)
+        .collect()
+}
+
+/// SHA-256 hash a token for storage.
+fn hash_token(token: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(token.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Whether another email may be sent for a token issued at `last_issued_at`.
+pub fn can_resend(last_issued_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    last_issued_at.is_none_or(|at| now - at >= RESEND_INTERVAL)
+}
+
+/// Mark an account as waiting for verification.
+pub async fn mark_unverified(pool: &PgPool, user_id: &str) -> Result<(), AuthError> {
+    sqlx::query("UPDATE users SET email_verified = NULL WHERE id = $1::uuid")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Whether an account's email address is verified.
+pub async fn is_verified(pool: &PgPool, user_id: &str) -> Result<bool, AuthError> {
+    let verified = sqlx::query_scalar::<_, bool>(
+        "SELECT email_verified IS NOT NULL FROM users WHERE id = $1::uuid",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(verified.unwrap_or(false))
+}
+
+/// Issue a verification token for an account, replacing any earlier one.
+/// Returns the plaintext token for the link.
+pub async fn issue_token(pool: &PgPool, user_id: &str) -> Result<String, AuthError> {
+    let token = generate_token();
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM email_verification_tokens WHERE user_id = $1::uuid")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "INSERT INTO email_verification_tokens (id, user_id, token_hash, expires_at) \
+         VALUES ($1, $2::uuid, $3, $4)",
+    )
+    .bind(uuidv7())
+    .bind(user_id)
+    .bind(hash_token(&token))
+    .bind(Utc::now() + TOKEN_TTL)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(token)
+}
+
+/// Verify the account a token was issued for, using up the token.
+pub async fn verify(pool: &PgPool, token: &str) -> Result<VerifyOutcome, AuthError> {
+    let mut tx = pool.begin().await?;
+    let row = sqlx::query_as::<_, (String, String, DateTime<Utc>)>(
+        "SELECT t.user_id::text, u.email, t.expires_at \
+         FROM email_verification_tokens t \
+         JOIN users u ON u.id = t.user_id \
+         WHERE t.token_hash = $1",
+    )
+    .bind(hash_token(token))
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some((user_id, email, expires_at)) = row else {
+        return Ok(VerifyOutcome::Invalid);
+    };
+    if expires_at <= Utc::now() {
+        return Ok(VerifyOutcome::Expired);
+    }
+
+    sqlx::query("UPDATE users SET email_verified = now() WHERE id = $1::uuid")
+        .bind(&user_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("DELETE FROM email_verification_tokens WHERE user_id = $1::uuid")
+        .bind(&user_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(VerifyOutcome::Verified { user_id, email })
+}
+
+/// The unverified account registered with `email`, if any.
+pub async fn find_pending(pool: &PgPool, email: &str) -> Result<Option<PendingUser>, AuthError> {
+    let row = sqlx::query_as::<_, (String, Option<DateTime<Utc>>)>(
+        "SELECT u.id::text, \
+                (SELECT max(t.created_at) FROM email_verification_tokens t WHERE t.user_id = u.id) \
+         FROM users u \
+         WHERE u.email = $1 AND u.email_verified IS NULL",
+    )
+    .bind(email)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|(user_id, last_issued_at)| PendingUser {
+        user_id,
+        last_issued_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_random_and_hashed() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_token());
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_ne!(hash_token(&token), token);
+    }
+
+    #[test]
+    fn resend_waits_for_the_interval() {
+        let now = Utc::now();
+        assert!(can_resend(None, now));
+        assert!(!can_resend(Some(now - Duration::seconds(10)), now));
+        assert!(can_resend(Some(now - RESEND_INTERVAL), now));
+    }
+}
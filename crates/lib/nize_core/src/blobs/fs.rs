@@ -0,0 +1,72 @@
+//! Local filesystem blob backend.
+
+use std::path::PathBuf;
+
+use futures_util::StreamExt;
+use tokio_util::io::ReaderStream;
+
+use super::{BlobError, ByteStream, blob_key, is_sha256};
+
+/// Blobs stored under a root directory, at their content key.
+#[derive(Debug, Clone)]
+pub struct FsStore {
+    root: PathBuf,
+}
+
+impl FsStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Spool directory for uploads in progress. On the same filesystem as
+    /// the blobs so committing is a rename.
+    pub(super) fn tmp_dir(&self) -> PathBuf {
+        self.root.join("tmp")
+    }
+
+    fn path(&self, sha256: &str) -> Result<PathBuf, BlobError> {
+        if !is_sha256(sha256) {
+            return Err(BlobError::NotFound(sha256.to_string()));
+        }
+        Ok(self.root.join(blob_key(sha256)))
+    }
+
+    /// Move a spooled upload to its content path. An existing copy is kept.
+    pub(super) async fn commit(
+        &self,
+        spool: tempfile::NamedTempFile,
+        sha256: &str,
+    ) -> Result<(), BlobError> {
+        let path = self.path(sha256)?;
+        if tokio::fs::try_exists(&path).await? {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        spool.persist(&path).map_err(|e| BlobError::Io(e.error))?;
+        Ok(())
+    }
+
+    pub(super) async fn get(&self, sha256: &str) -> Result<ByteStream, BlobError> {
+        let path = self.path(sha256)?;
+        let file = match tokio::fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(BlobError::NotFound(sha256.to_string()));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Ok(ReaderStream::new(file)
+            .map(|r| r.map_err(BlobError::from))
+            .boxed())
+    }
+
+    pub(super) async fn delete(&self, sha256: &str) -> Result<(), BlobError> {
+        match tokio::fs::remove_file(self.path(sha256)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
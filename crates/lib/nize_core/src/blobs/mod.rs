@@ -0,0 +1,471 @@
+//! Content-addressed blob storage for uploaded documents.
+//!
+//! Blobs are keyed by the SHA-256 of their bytes
+//! (`sha256/ab/cd/abcd…`), so identical uploads share one copy. The backend
+//! — a local directory ([`fs`]) or an S3-compatible bucket ([`s3`]) — is
+//! selected by `storage.blobs.backend`. The `blobs` table records which
+//! backend holds each blob, so blobs written before a backend switch stay
+//! readable until [`migrate_blobs`] moves them.
+
+pub mod fs;
+pub mod s3;
+
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+use thiserror::Error;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::RwLock;
+
+use crate::config::cache::ConfigCache;
+use crate::config::queries;
+use crate::config::resolver;
+use crate::mcp::secrets;
+use crate::models::config::ConfigScope;
+
+/// Config key selecting the backend for new blobs.
+pub const BACKEND_CONFIG_KEY: &str = "storage.blobs.backend";
+
+/// Errors that can occur in blob storage operations.
+#[derive(Debug, Error)]
+pub enum BlobError {
+    #[error("Blob not found: {0}")]
+    NotFound(String),
+
+    #[error("Blob larger than the {0} bytes allowed")]
+    TooLarge(u64),
+
+    #[error("Blob storage misconfigured: {0}")]
+    Config(String),
+
+    #[error("Blob backend error: {0}")]
+    Backend(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// A stream of blob bytes.
+pub type ByteStream = Pin<Box<dyn Stream<Item = Result<Bytes, BlobError>> + Send>>;
+
+/// Storage backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlobBackend {
+    Filesystem,
+    S3,
+}
+
+impl BlobBackend {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BlobBackend::Filesystem => "filesystem",
+            BlobBackend::S3 => "s3",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, BlobError> {
+        match value {
+            "filesystem" => Ok(BlobBackend::Filesystem),
+            "s3" => Ok(BlobBackend::S3),
+            other => Err(BlobError::Config(format!("unknown backend '{other}'"))),
+        }
+    }
+}
+
+/// A stored blob.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredBlob {
+    /// Hex SHA-256 of the content.
+    pub sha256: String,
+    pub size: i64,
+}
+
+/// Object key for a content hash.
+pub fn blob_key(sha256: &str) -> String {
+    format!("sha256/{}/{}/{sha256}", &sha256[..2], &sha256[2..4])
+}
+
+/// Whether `value` is a lowercase hex SHA-256.
+pub fn is_sha256(value: &str) -> bool {
+    value.len() == 64
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+}
+
+/// A configured blob store.
+#[derive(Debug, Clone)]
+pub enum BlobStore {
+    Filesystem(fs::FsStore),
+    S3(s3::S3Store),
+}
+
+impl BlobStore {
+    pub fn backend(&self) -> BlobBackend {
+        match self {
+            BlobStore::Filesystem(_) => BlobBackend::Filesystem,
+            BlobStore::S3(_) => BlobBackend::S3,
+        }
+    }
+
+    /// Build the store for `backend` from system config. Secrets are
+    /// decrypted with `encryption_key`.
+    pub async fn from_config(
+        pool: &PgPool,
+        cache: &Arc<RwLock<ConfigCache>>,
+        encryption_key: &str,
+        backend: BlobBackend,
+    ) -> Result<Self, BlobError> {
+        let value = |key: &'static str| async move {
+            resolver::get_system_value(pool, cache, key)
+                .await
+                .map_err(|e| BlobError::Config(e.to_string()))
+        };
+        match backend {
+            BlobBackend::Filesystem => {
+                let path = value("storage.blobs.path").await?;
+                let root = if path.trim().is_empty() {
+                    default_blob_dir()
+                } else {
+                    PathBuf::from(path.trim())
+                };
+                Ok(BlobStore::Filesystem(fs::FsStore::new(root)))
+            }
+            BlobBackend::S3 => {
+                let secret_access_key =
+                    secret_value(pool, "storage.blobs.s3.secretAccessKey", encryption_key).await?;
+                Ok(BlobStore::S3(s3::S3Store::new(s3::S3Config {
+                    endpoint: value("storage.blobs.s3.endpoint").await?,
+                    bucket: value("storage.blobs.s3.bucket").await?,
+                    region: value("storage.blobs.s3.region").await?,
+                    access_key_id: value("storage.blobs.s3.accessKeyId").await?,
+                    secret_access_key,
+                    path_style: value("storage.blobs.s3.pathStyle").await? == "true",
+                })?))
+            }
+        }
+    }
+
+    /// Build the store selected by `storage.blobs.backend`.
+    pub async fn configured(
+        pool: &PgPool,
+        cache: &Arc<RwLock<ConfigCache>>,
+        encryption_key: &str,
+    ) -> Result<Self, BlobError> {
+        let backend = resolver::get_system_value(pool, cache, BACKEND_CONFIG_KEY)
+            .await
+            .map_err(|e| BlobError::Config(e.to_string()))?;
+        Self::from_config(pool, cache, encryption_key, BlobBackend::parse(&backend)?).await
+    }
+
+    /// Stream `body` into the store. Fails with [`BlobError::TooLarge`]
+    /// once more than `max_bytes` arrive.
+    pub async fn put<S, E>(&self, body: S, max_bytes: Option<u64>) -> Result<StoredBlob, BlobError>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Send,
+        E: std::fmt::Display,
+    {
+        // Spool to a temp file first: the key is the hash of the whole body.
+        let spool_dir = match self {
+            BlobStore::Filesystem(store) => store.tmp_dir(),
+            BlobStore::S3(_) => std::env::temp_dir(),
+        };
+        tokio::fs::create_dir_all(&spool_dir).await?;
+        let spool = tempfile::NamedTempFile::new_in(&spool_dir)?;
+        let mut file = tokio::fs::File::from_std(spool.reopen()?);
+        let mut hasher = Sha256::new();
+        let mut size: u64 = 0;
+
+        let mut body = std::pin::pin!(body);
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(|e| BlobError::Backend(format!("upload stream: {e}")))?;
+            size += chunk.len() as u64;
+            if max_bytes.is_some_and(|max| size > max) {
+                return Err(BlobError::TooLarge(max_bytes.unwrap_or_default()));
+            }
+            hasher.update(&chunk);
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        drop(file);
+
+        let blob = StoredBlob {
+            sha256: format!("{:x}", hasher.finalize()),
+            size: size as i64,
+        };
+        match self {
+            BlobStore::Filesystem(store) => store.commit(spool, &blob.sha256).await?,
+            BlobStore::S3(store) => store.put_file(spool.path(), &blob).await?,
+        }
+        Ok(blob)
+    }
+
+    /// Stream the file at `path` into the store, as [`Self::put`].
+    pub async fn put_file(
+        &self,
+        path: &std::path::Path,
+        max_bytes: Option<u64>,
+    ) -> Result<StoredBlob, BlobError> {
+        let file = tokio::fs::File::open(path).await?;
+        self.put(tokio_util::io::ReaderStream::new(file), max_bytes)
+            .await
+    }
+
+    /// Store `bytes`, as [`Self::put`].
+    pub async fn put_bytes(
+        &self,
+        bytes: Vec<u8>,
+        max_bytes: Option<u64>,
+    ) -> Result<StoredBlob, BlobError> {
+        let body = futures_util::stream::once(async {
+            Ok::<_, std::convert::Infallible>(Bytes::from(bytes))
+        });
+        self.put(body, max_bytes).await
+    }
+
+    /// Stream a blob's bytes.
+    pub async fn get(&self, sha256: &str) -> Result<ByteStream, BlobError> {
+        match self {
+            BlobStore::Filesystem(store) => store.get(sha256).await,
+            BlobStore::S3(store) => store.get(sha256).await,
+        }
+    }
+
+    /// Delete a blob. Missing blobs are not an error.
+    pub async fn delete(&self, sha256: &str) -> Result<(), BlobError> {
+        match self {
+            BlobStore::Filesystem(store) => store.delete(sha256).await,
+            BlobStore::S3(store) => store.delete(sha256).await,
+        }
+    }
+
+    /// Copy a blob from `source` into this store, streaming.
+    pub async fn copy_from(&self, source: &BlobStore, blob: &StoredBlob) -> Result<(), BlobError> {
+        let stream = source.get(&blob.sha256).await?;
+        let copied = self.put(stream, None).await?;
+        if copied.sha256 != blob.sha256 {
+            return Err(BlobError::Backend(format!(
+                "blob {} changed during copy (got {})",
+                blob.sha256, copied.sha256
+            )));
+        }
+        Ok(())
+    }
+
+    /// Copy a blob into a local temp file, for tools that need a path.
+    pub async fn spool(&self, sha256: &str) -> Result<tempfile::NamedTempFile, BlobError> {
+        let spool = tempfile::NamedTempFile::new()?;
+        let mut file = tokio::fs::File::from_std(spool.reopen()?);
+        let mut stream = self.get(sha256).await?;
+        while let Some(chunk) = stream.next().await {
+            file.write_all(&chunk?).await?;
+        }
+        file.flush().await?;
+        Ok(spool)
+    }
+}
+
+/// Default filesystem root: `<data dir>/nize/blobs`.
+pub fn default_blob_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("nize")
+        .join("blobs")
+}
+
+/// Decrypt a secret system config value; empty when unset.
+async fn secret_value(pool: &PgPool, key: &str, encryption_key: &str) -> Result<String, BlobError> {
+    let Some(row) = queries::get_value(pool, key, &ConfigScope::System, None)
+        .await
+        .map_err(|e| BlobError::Config(e.to_string()))?
+    else {
+        return Ok(String::new());
+    };
+    if row.value.is_empty() {
+        return Ok(String::new());
+    }
+    secrets::decrypt(&row.value, encryption_key).map_err(|e| BlobError::Config(e.to_string()))
+}
+
+// =============================================================================
+// Blob records
+// =============================================================================
+
+/// Record a stored blob. A blob already recorded under another backend
+/// now points at the copy just written.
+pub async fn record_blob(
+    conn: &mut PgConnection,
+    blob: &StoredBlob,
+    backend: BlobBackend,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO blobs (sha256, size_bytes, backend)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (sha256) DO UPDATE SET backend = EXCLUDED.backend
+        "#,
+    )
+    .bind(&blob.sha256)
+    .bind(blob.size)
+    .bind(backend.as_str())
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Backend holding a blob, if it is recorded.
+pub async fn blob_backend(pool: &PgPool, sha256: &str) -> Result<Option<BlobBackend>, BlobError> {
+    let backend = sqlx::query_scalar::<_, String>("SELECT backend FROM blobs WHERE sha256 = $1")
+        .bind(sha256)
+        .fetch_optional(pool)
+        .await?;
+    backend.as_deref().map(BlobBackend::parse).transpose()
+}
+
+/// Drop the record of a blob no document references any more. Returns the
+/// backend to delete its bytes from, or `None` while it is still in use.
+pub async fn release_blob(pool: &PgPool, sha256: &str) -> Result<Option<BlobBackend>, BlobError> {
+    let backend = sqlx::query_scalar::<_, String>(
+        r#"
+        DELETE FROM blobs b
+        WHERE b.sha256 = $1
+          AND NOT EXISTS (SELECT 1 FROM documents d WHERE d.sha256 = b.sha256)
+        RETURNING b.backend
+        "#,
+    )
+    .bind(sha256)
+    .fetch_optional(pool)
+    .await?;
+    backend.as_deref().map(BlobBackend::parse).transpose()
+}
+
+/// Drop the records of every unreferenced blob (e.g. after an account's
+/// documents were deleted by cascade). Returns each blob's hash and backend
+/// so the caller can delete the bytes.
+pub async fn release_unreferenced_blobs(
+    pool: &PgPool,
+) -> Result<Vec<(String, BlobBackend)>, BlobError> {
+    let rows = sqlx::query_as::<_, (String, String)>(
+        r#"
+        DELETE FROM blobs b
+        WHERE NOT EXISTS (SELECT 1 FROM documents d WHERE d.sha256 = b.sha256)
+        RETURNING b.sha256, b.backend
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter()
+        .map(|(sha256, backend)| Ok((sha256, BlobBackend::parse(&backend)?)))
+        .collect()
+}
+
+/// Outcome of [`migrate_blobs`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BlobMigrationReport {
+    pub moved: u64,
+    pub bytes: u64,
+    pub failed: u64,
+}
+
+/// Move every blob held by `from` into `to`, streaming each one and
+/// deleting the source copy once the record points at `to`. Failures are
+/// logged and counted; rerunning picks up where it stopped.
+pub async fn migrate_blobs(
+    pool: &PgPool,
+    from: &BlobStore,
+    to: &BlobStore,
+) -> Result<BlobMigrationReport, BlobError> {
+    if from.backend() == to.backend() {
+        return Err(BlobError::Config(
+            "source and target backends are the same".into(),
+        ));
+    }
+    let rows = sqlx::query_as::<_, (String, i64)>(
+        "SELECT sha256, size_bytes FROM blobs WHERE backend = $1 ORDER BY created_at",
+    )
+    .bind(from.backend().as_str())
+    .fetch_all(pool)
+    .await?;
+
+    let mut report = BlobMigrationReport::default();
+    for (sha256, size) in rows {
+        let blob = StoredBlob { sha256, size };
+        if let Err(e) = to.copy_from(from, &blob).await {
+            tracing::warn!(sha256 = %blob.sha256, error = %e, "blob migration failed");
+            report.failed += 1;
+            continue;
+        }
+        sqlx::query("UPDATE blobs SET backend = $2 WHERE sha256 = $1")
+            .bind(&blob.sha256)
+            .bind(to.backend().as_str())
+            .execute(pool)
+            .await?;
+        if let Err(e) = from.delete(&blob.sha256).await {
+            tracing::warn!(sha256 = %blob.sha256, error = %e, "failed to delete migrated blob");
+        }
+        report.moved += 1;
+        report.bytes += blob.size as u64;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_sharded_by_hash_prefix() {
+        let hash = "ab".repeat(32);
+        assert!(is_sha256(&hash));
+        assert_eq!(blob_key(&hash), format!("sha256/ab/ab/{hash}"));
+        assert!(!is_sha256("../etc/passwd"));
+        assert!(!is_sha256(&"AB".repeat(32)));
+    }
+
+    #[tokio::test]
+    async fn filesystem_put_is_content_addressed_and_bounded() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::Filesystem(fs::FsStore::new(dir.path().to_path_buf()));
+        let chunks = || {
+            futures_util::stream::iter(vec![
+                Ok::<_, std::io::Error>(Bytes::from_static(b"hello ")),
+                Ok(Bytes::from_static(b"world")),
+            ])
+        };
+
+        let first = store.put(chunks(), None).await.unwrap();
+        let second = store.put(chunks(), None).await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.size, 11);
+        assert_eq!(
+            first.sha256,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+
+        let mut read = Vec::new();
+        let mut stream = store.get(&first.sha256).await.unwrap();
+        while let Some(chunk) = stream.next().await {
+            read.extend_from_slice(&chunk.unwrap());
+        }
+        assert_eq!(read, b"hello world");
+
+        assert!(matches!(
+            store.put(chunks(), Some(5)).await,
+            Err(BlobError::TooLarge(5))
+        ));
+
+        store.delete(&first.sha256).await.unwrap();
+        assert!(matches!(
+            store.get(&first.sha256).await,
+            Err(BlobError::NotFound(_))
+        ));
+    }
+}
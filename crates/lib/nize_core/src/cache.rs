@@ -0,0 +1,401 @@
+//! Bounded in-memory cache with TTL and metrics.
+//!
+//! [`Cache`] is a size-bounded LRU map whose entries expire after a TTL.
+//! Every method takes `&self` and holds an internal lock only for the map
+//! operation, so a cache can be shared behind an `Arc` and used from async
+//! code; [`Cache::get_or_try_insert_with`] computes a missing value without
+//! holding the lock across the `await`.
+//!
+//! Each cache registers its hit/miss/eviction counters under its name when
+//! created; [`stats`] reports all live caches, e.g. for `GET /metrics`.
+
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Counters of one cache, shared with the registry.
+#[derive(Debug)]
+struct Metrics {
+    name: &'static str,
+    capacity: usize,
+    entries: AtomicUsize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// Counters of every cache created, dropped ones pruned on read.
+static REGISTRY: LazyLock<Mutex<Vec<Weak<Metrics>>>> = LazyLock::new(Mutex::default);
+
+/// Size and counters of the caches sharing a name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheSnapshot {
+    pub name: &'static str,
+    pub capacity: usize,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries dropped to make room, not counting expired ones.
+    pub evictions: u64,
+}
+
+/// Stats of all live caches, ordered by name. Caches sharing a name (e.g.
+/// one per test) are summed.
+pub fn stats() -> Vec<CacheSnapshot> {
+    let mut registry = REGISTRY.lock().unwrap_or_else(|e| e.into_inner());
+    registry.retain(|m| m.strong_count() > 0);
+    let mut by_name: BTreeMap<&'static str, CacheSnapshot> = BTreeMap::new();
+    for metrics in registry.iter().filter_map(Weak::upgrade) {
+        let entry = by_name.entry(metrics.name).or_insert(CacheSnapshot {
+            name: metrics.name,
+            capacity: 0,
+            entries: 0,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        });
+        entry.capacity += metrics.capacity;
+        entry.entries += metrics.entries.load(Ordering::Relaxed);
+        entry.hits += metrics.hits.load(Ordering::Relaxed);
+        entry.misses += metrics.misses.load(Ordering::Relaxed);
+        entry.evictions += metrics.evictions.load(Ordering::Relaxed);
+    }
+    by_name.into_values().collect()
+}
+
+#[derive(Debug)]
+struct Slot<V> {
+    value: V,
+    expires_at: Instant,
+    /// Position in [`Inner::order`].
+    used: u64,
+}
+
+#[derive(Debug)]
+struct Inner<K, V> {
+    slots: HashMap<K, Slot<V>>,
+    /// Keys by last use, oldest first.
+    order: BTreeMap<u64, K>,
+    clock: u64,
+}
+
+impl<K: Clone + Eq + Hash, V> Inner<K, V> {
+    fn touch(&mut self, key: &K) {
+        self.clock += 1;
+        let clock = self.clock;
+        if let Some(slot) = self.slots.get_mut(key) {
+            self.order.remove(&slot.used);
+            slot.used = clock;
+            self.order.insert(clock, key.clone());
+        }
+    }
+
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let slot = self.slots.remove(key)?;
+        self.order.remove(&slot.used);
+        Some(slot.value)
+    }
+}
+
+/// Size-bounded LRU cache with per-entry TTL.
+#[derive(Debug)]
+pub struct Cache<K, V> {
+    inner: Mutex<Inner<K, V>>,
+    ttl: Duration,
+    metrics: Arc<Metrics>,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Cache<K, V> {
+    /// Create a cache of at most `capacity` entries that expire after `ttl`,
+    /// registering its metrics as `name`.
+    pub fn new(name: &'static str, capacity: usize, ttl: Duration) -> Self {
+        let metrics = Arc::new(Metrics {
+            name,
+            capacity,
+            entries: AtomicUsize::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        });
+        REGISTRY
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Arc::downgrade(&metrics));
+        Self {
+            inner: Mutex::new(Inner {
+                slots: HashMap::new(),
+                order: BTreeMap::new(),
+                clock: 0,
+            }),
+            ttl,
+            metrics,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner<K, V>> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn sync_len(&self, inner: &Inner<K, V>) {
+        self.metrics
+            .entries
+            .store(inner.slots.len(), Ordering::Relaxed);
+    }
+
+    /// The cached value of `key`, unless missing or expired.
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let mut inner = self.lock();
+        let found = match inner.slots.get_key_value(key) {
+            Some((k, slot)) if Instant::now() < slot.expires_at => Some(k.clone()),
+            Some(_) => {
+                inner.remove(key);
+                self.sync_len(&inner);
+                None
+            }
+            None => None,
+        };
+        let value = found.and_then(|k| {
+            inner.touch(&k);
+            inner.slots.get::<K>(&k).map(|slot| slot.value.clone())
+        });
+        let counter = if value.is_some() {
+            &self.metrics.hits
+        } else {
+            &self.metrics.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        value
+    }
+
+    /// Cache `value` under `key` for the cache's TTL.
+    pub fn insert(&self, key: K, value: V) {
+        self.insert_with_ttl(key, value, self.ttl);
+    }
+
+    /// Cache `value` under `key` for `ttl`, evicting the least recently
+    /// used entry when full.
+    pub fn insert_with_ttl(&self, key: K, value: V, ttl: Duration) {
+        if self.metrics.capacity == 0 {
+            return;
+        }
+        let mut inner = self.lock();
+        inner.remove(&key);
+        while inner.slots.len() >= self.metrics.capacity {
+            let Some((_, oldest)) = inner.order.pop_first() else {
+                break;
+            };
+            inner.slots.remove(&oldest);
+            self.metrics.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+        inner.clock += 1;
+        let used = inner.clock;
+        inner.order.insert(used, key.clone());
+        inner.slots.insert(
+            key,
+            Slot {
+                value,
+                expires_at: Instant::now() + ttl,
+                used,
+            },
+        );
+        self.sync_len(&inner);
+    }
+
+    /// The cached value of `key`, or the result of `compute` cached. The
+    /// lock is not held while computing, so concurrent misses may each
+    /// compute; errors are returned and not cached.
+    pub async fn get_or_try_insert_with<F, Fut, E>(&self, key: K, compute: F) -> Result<V, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        if let Some(value) = self.get(&key) {
+            return Ok(value);
+        }
+        let value = compute().await?;
+        self.insert(key, value.clone());
+        Ok(value)
+    }
+
+    /// Remove `key`, returning its value even if expired.
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        let mut inner = self.lock();
+        let value = inner.remove(key);
+        self.sync_len(&inner);
+        value
+    }
+
+    /// Keep only the entries for which `keep` is true. Returns the number
+    /// removed.
+    pub fn retain(&self, mut keep: impl FnMut(&K, &V) -> bool) -> usize {
+        let mut inner = self.lock();
+        let doomed: Vec<K> = inner
+            .slots
+            .iter()
+            .filter(|(k, slot)| !keep(k, &slot.value))
+            .map(|(k, _)| k.clone())
+            .collect();
+        for key in &doomed {
+            inner.remove(key);
+        }
+        self.sync_len(&inner);
+        doomed.len()
+    }
+
+    /// Remove all entries.
+    pub fn clear(&self) {
+        let mut inner = self.lock();
+        inner.slots.clear();
+        inner.order.clear();
+        self.sync_len(&inner);
+    }
+
+    /// All entries, including expired ones not yet dropped.
+    pub fn entries(&self) -> Vec<(K, V)> {
+        self.lock()
+            .slots
+            .iter()
+            .map(|(k, slot)| (k.clone(), slot.value.clone()))
+            .collect()
+    }
+
+    /// Number of entries, including expired ones not yet dropped.
+    pub fn len(&self) -> usize {
+        self.lock().slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hits, misses and evictions of this cache alone.
+    pub fn snapshot(&self) -> CacheSnapshot {
+        CacheSnapshot {
+            name: self.metrics.name,
+            capacity: self.metrics.capacity,
+            entries: self.len(),
+            hits: self.metrics.hits.load(Ordering::Relaxed),
+            misses: self.metrics.misses.load(Ordering::Relaxed),
+            evictions: self.metrics.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let cache = Cache::new("test.lru", 2, HOUR);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.get("a"), Some(1));
+        cache.insert("c", 3);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("c"), Some(3));
+
+        let snapshot = cache.snapshot();
+        assert_eq!(snapshot.entries, 2);
+        assert_eq!(snapshot.hits, 3);
+        assert_eq!(snapshot.misses, 1);
+        assert_eq!(snapshot.evictions, 1);
+    }
+
+    #[test]
+    fn replacing_a_key_does_not_evict() {
+        let cache = Cache::new("test.replace", 2, HOUR);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        cache.insert("a", 10);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("a"), Some(10));
+        assert_eq!(cache.get("b"), Some(2));
+        assert_eq!(cache.snapshot().evictions, 0);
+    }
+
+    #[test]
+    fn expired_entries_miss_and_are_dropped() {
+        let cache = Cache::new("test.ttl", 4, HOUR);
+        cache.insert_with_ttl("a", 1, Duration::ZERO);
+        cache.insert("b", 2);
+        assert_eq!(cache.entries().len(), 2);
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.snapshot().misses, 1);
+    }
+
+    #[test]
+    fn retain_and_clear() {
+        let cache = Cache::new("test.retain", 8, HOUR);
+        for (k, v) in [("a", 1), ("b", 2), ("c", 3)] {
+            cache.insert(k, v);
+        }
+        assert_eq!(cache.retain(|_, v| v % 2 == 1), 1);
+        assert_eq!(cache.remove("a"), Some(1));
+        assert_eq!(cache.len(), 1);
+        cache.clear();
+        assert!(cache.is_empty());
+        cache.insert("d", 4);
+        assert_eq!(cache.get("d"), Some(4));
+    }
+
+    #[test]
+    fn zero_capacity_caches_nothing() {
+        let cache = Cache::new("test.zero", 0, HOUR);
+        cache.insert("a", 1);
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn computes_missing_values_once() {
+        let cache = Cache::new("test.compute", 4, HOUR);
+        let first: Result<_, ()> = cache.get_or_try_insert_with("a", || async { Ok(1) }).await;
+        let second: Result<_, ()> = cache.get_or_try_insert_with("a", || async { Ok(2) }).await;
+        assert_eq!((first, second), (Ok(1), Ok(1)));
+        let failed = cache
+            .get_or_try_insert_with("b", || async { Err("down") })
+            .await;
+        assert_eq!(failed, Err("down"));
+        assert_eq!(cache.get("b"), None);
+    }
+
+    #[test]
+    fn registry_sums_caches_by_name() {
+        let a = Cache::new("test.registry", 2, HOUR);
+        let b = Cache::new("test.registry", 3, HOUR);
+        a.insert(1, ());
+        b.insert(1, ());
+        a.get(&1);
+        let find = || stats().into_iter().find(|s| s.name == "test.registry");
+        let snapshot = find().unwrap();
+        assert_eq!(
+            (snapshot.capacity, snapshot.entries, snapshot.hits),
+            (5, 2, 1)
+        );
+        drop(a);
+        drop(b);
+        assert!(find().is_none());
+    }
+}
@@ -0,0 +1,350 @@
+//! Conversations exported from other chat apps.
+//!
+//! Parses the `conversations.json` found in a ChatGPT or Claude data export
+//! into titles and UI messages (`{id, role, parts}`) as stored by the chat
+//! frontend, ready to be saved through the conversations API. Only text
+//! from user and assistant turns is kept; system prompts, tool calls,
+//! reasoning and attachments are dropped. Conversations left with no
+//! messages are skipped.
+//!
+//! Each conversation keeps the id it had in its source app so importers can
+//! recognise one they have already imported (see [`ImportedConversation::key`]).
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Title used when the export has none.
+const UNTITLED: &str = "Imported conversation";
+
+/// Errors that can occur while parsing an export.
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("Invalid {0} export: {1}")]
+    Parse(ImportSource, String),
+}
+
+/// The app an export came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    /// ChatGPT "Export data" archive.
+    #[serde(rename = "chatgpt")]
+    ChatGpt,
+    /// Claude "Export data" archive.
+    Claude,
+}
+
+impl ImportSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImportSource::ChatGpt => "chatgpt",
+            ImportSource::Claude => "claude",
+        }
+    }
+}
+
+impl std::fmt::Display for ImportSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ImportSource::ChatGpt => "ChatGPT",
+            ImportSource::Claude => "Claude",
+        })
+    }
+}
+
+/// A conversation read from an export.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedConversation {
+    pub source: ImportSource,
+    /// The conversation's id in the source app.
+    pub source_id: String,
+    pub title: String,
+    /// UI messages, oldest first.
+    pub messages: Vec<serde_json::Value>,
+}
+
+impl ImportedConversation {
+    /// Stable key identifying this conversation across imports.
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.source.as_str(), self.source_id)
+    }
+}
+
+/// Parse the `conversations.json` of an export.
+pub fn parse(source: ImportSource, json: &str) -> Result<Vec<ImportedConversation>, ImportError> {
+    let invalid = |e: serde_json::Error| ImportError::Parse(source, e.to_string());
+    match source {
+        ImportSource::ChatGpt => {
+            let exported: Vec<ChatGptConversation> = serde_json::from_str(json).map_err(invalid)?;
+            Ok(exported.into_iter().filter_map(chatgpt).collect())
+        }
+        ImportSource::Claude => {
+            let exported: Vec<ClaudeConversation> = serde_json::from_str(json).map_err(invalid)?;
+            Ok(exported.into_iter().filter_map(claude).collect())
+        }
+    }
+}
+
+fn message_json(id: &str, role: &str, text: &str) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "role": role,
+        "parts": [{ "type": "text", "text": text }],
+    })
+}
+
+fn conversation(
+    source: ImportSource,
+    source_id: String,
+    title: Option<String>,
+    messages: Vec<serde_json::Value>,
+) -> Option<ImportedConversation> {
+    if messages.is_empty() {
+        return None;
+    }
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| UNTITLED.to_string());
+    Some(ImportedConversation {
+        source,
+        source_id,
+        title,
+        messages,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// ChatGPT
+// ---------------------------------------------------------------------------
+
+/// A ChatGPT conversation: a tree of nodes, one per message version. The
+/// branch shown in ChatGPT ends at `current_node`.
+#[derive(Debug, Deserialize)]
+struct ChatGptConversation {
+    #[serde(alias = "conversation_id")]
+    id: String,
+    title: Option<String>,
+    #[serde(default)]
+    mapping: HashMap<String, ChatGptNode>,
+    current_node: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptNode {
+    message: Option<ChatGptMessage>,
+    parent: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptMessage {
+    id: String,
+    author: ChatGptAuthor,
+    content: ChatGptContent,
+    #[serde(default)]
+    metadata: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptAuthor {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptContent {
+    content_type: String,
+    #[serde(default)]
+    parts: Vec<serde_json::Value>,
+}
+
+fn chatgpt(exported: ChatGptConversation) -> Option<ImportedConversation> {
+    // Walk from the current node up to the root, guarding against cycles.
+    let mut branch = Vec::new();
+    let mut next = exported.current_node.as_deref();
+    while let Some(id) = next {
+        let Some(node) = exported.mapping.get(id) else {
+            break;
+        };
+        if branch.len() > exported.mapping.len() {
+            break;
+        }
+        branch.push(node);
+        next = node.parent.as_deref();
+    }
+
+    let messages = branch
+        .into_iter()
+        .rev()
+        .filter_map(|node| node.message.as_ref())
+        .filter_map(|m| {
+            let role = match m.author.role.as_str() {
+                "user" | "assistant" => m.author.role.as_str(),
+                _ => return None,
+            };
+            if !matches!(m.content.content_type.as_str(), "text" | "multimodal_text")
+                || m.metadata["is_visually_hidden_from_conversation"] == true
+            {
+                return None;
+            }
+            // Non-string parts are images and other attachments.
+            let text = m
+                .content
+                .parts
+                .iter()
+                .filter_map(|p| p.as_str())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            let text = text.trim();
+            (!text.is_empty()).then(|| message_json(&m.id, role, text))
+        })
+        .collect();
+
+    conversation(ImportSource::ChatGpt, exported.id, exported.title, messages)
+}
+
+// ---------------------------------------------------------------------------
+// Claude
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize)]
+struct ClaudeConversation {
+    uuid: String,
+    name: Option<String>,
+    #[serde(default)]
+    chat_messages: Vec<ClaudeMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeMessage {
+    uuid: String,
+    sender: String,
+    #[serde(default)]
+    text: String,
+    /// Content blocks; newer exports carry the text here as well as in
+    /// `text`.
+    #[serde(default)]
+    content: Vec<ClaudeBlock>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+}
+
+fn claude(exported: ClaudeConversation) -> Option<ImportedConversation> {
+    let messages = exported
+        .chat_messages
+        .iter()
+        .filter_map(|m| {
+            let role = match m.sender.as_str() {
+                "human" => "user",
+                "assistant" => "assistant",
+                _ => return None,
+            };
+            let blocks = m
+                .content
+                .iter()
+                .filter(|b| b.kind == "text")
+                .map(|b| b.text.as_str())
+                .collect::<Vec<_>>();
+            let text = if blocks.is_empty() {
+                m.text.clone()
+            } else {
+                blocks.join("\n\n")
+            };
+            let text = text.trim();
+            (!text.is_empty()).then(|| message_json(&m.uuid, role, text))
+        })
+        .collect();
+
+    conversation(ImportSource::Claude, exported.uuid, exported.name, messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(c: &ImportedConversation) -> Vec<(String, String)> {
+        c.messages
+            .iter()
+            .map(|m| {
+                (
+                    m["role"].as_str().unwrap().to_string(),
+                    m["parts"][0]["text"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn chatgpt_follows_current_branch() {
+        let json = r#"[{
+            "id": "c1",
+            "title": "Trip",
+            "current_node": "n3b",
+            "mapping": {
+                "root": { "message": null, "parent": null },
+                "n1": { "parent": "root", "message": {
+                    "id": "m1", "author": { "role": "system" },
+                    "content": { "content_type": "text", "parts": [""] } } },
+                "n2": { "parent": "n1", "message": {
+                    "id": "m2", "author": { "role": "user" },
+                    "content": { "content_type": "multimodal_text",
+                                 "parts": [{ "asset_pointer": "file-1" }, "Plan a trip"] } } },
+                "n3a": { "parent": "n2", "message": {
+                    "id": "m3a", "author": { "role": "assistant" },
+                    "content": { "content_type": "text", "parts": ["Old answer"] } } },
+                "n3b": { "parent": "n2", "message": {
+                    "id": "m3b", "author": { "role": "assistant" },
+                    "content": { "content_type": "text", "parts": ["New answer"] } } }
+            }
+        }]"#;
+        let parsed = parse(ImportSource::ChatGpt, json).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].key(), "chatgpt:c1");
+        assert_eq!(parsed[0].title, "Trip");
+        assert_eq!(
+            texts(&parsed[0]),
+            vec![
+                ("user".into(), "Plan a trip".into()),
+                ("assistant".into(), "New answer".into()),
+            ]
+        );
+        assert_eq!(parsed[0].messages[1]["id"], "m3b");
+    }
+
+    #[test]
+    fn claude_maps_senders_and_prefers_content_blocks() {
+        let json = r#"[
+            { "uuid": "u1", "name": "", "chat_messages": [
+                { "uuid": "a", "sender": "human", "text": "Hello", "content": [] },
+                { "uuid": "b", "sender": "assistant", "text": "stale",
+                  "content": [{ "type": "text", "text": "Hi there" },
+                              { "type": "tool_use", "name": "search" }] }
+            ] },
+            { "uuid": "u2", "name": "Empty", "chat_messages": [] }
+        ]"#;
+        let parsed = parse(ImportSource::Claude, json).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].key(), "claude:u1");
+        assert_eq!(parsed[0].title, UNTITLED);
+        assert_eq!(
+            texts(&parsed[0]),
+            vec![
+                ("user".into(), "Hello".into()),
+                ("assistant".into(), "Hi there".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn wrong_format_is_rejected() {
+        let err = parse(ImportSource::Claude, r#"{"conversations": []}"#).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid Claude export"));
+    }
+}
@@ -0,0 +1,142 @@
+//! Per-step traces of chat replies, for debugging the tool-call loop.
+//!
+//! When `agent.trace.enabled` is set, the chat service records one trace
+//! per assistant reply: an ordered list of events (model steps with their
+//! tool calls and token usage, and the reason the loop stopped). Events are
+//! opaque JSON objects with at least a `type`; the trace view renders them.
+//! Traces expire after [`TRACE_TTL_DAYS`] and are pruned as new ones arrive.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Days a trace is kept.
+pub const TRACE_TTL_DAYS: i32 = 7;
+
+/// Maximum number of events in one trace.
+pub const MAX_EVENTS: usize = 500;
+
+/// Errors that can occur in trace operations.
+#[derive(Debug, Error)]
+pub enum TraceError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// Row returned by trace queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ChatTraceRow {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub message_id: String,
+    pub events: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+const TRACE_COLUMNS: &str = "id, conversation_id, message_id, events, created_at, expires_at";
+
+/// Check that `events` is a bounded array of objects with a string `type`.
+pub fn validate_events(events: &serde_json::Value) -> Result<(), TraceError> {
+    let events = events
+        .as_array()
+        .ok_or_else(|| TraceError::Validation(Message::new("trace.events_not_array")))?;
+    if events.len() > MAX_EVENTS {
+        return Err(TraceError::Validation(
+            Message::new("trace.too_many_events").arg("max", MAX_EVENTS),
+        ));
+    }
+    if let Some(i) = events
+        .iter()
+        .position(|e| !e.get("type").is_some_and(serde_json::Value::is_string))
+    {
+        return Err(TraceError::Validation(
+            Message::new("trace.invalid_event").arg("index", i),
+        ));
+    }
+    Ok(())
+}
+
+/// Record the trace of one reply and prune expired traces.
+///
+/// The caller must already have checked that the conversation is visible to
+/// the user.
+pub async fn record_trace(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+    message_id: &str,
+    events: &serde_json::Value,
+) -> Result<ChatTraceRow, TraceError> {
+    if message_id.trim().is_empty() {
+        return Err(TraceError::Validation(Message::new(
+            "trace.message_id_required",
+        )));
+    }
+    validate_events(events)?;
+
+    sqlx::query("DELETE FROM chat_traces WHERE expires_at < now()")
+        .execute(pool)
+        .await?;
+    let row = sqlx::query_as::<_, ChatTraceRow>(&format!(
+        r#"
+        INSERT INTO chat_traces (id, conversation_id, message_id, events, expires_at)
+        VALUES ($1, $2, $3, $4, now() + make_interval(days => $5))
+        RETURNING {TRACE_COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(conversation_id)
+    .bind(message_id)
+    .bind(events)
+    .bind(TRACE_TTL_DAYS)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// The most recent unexpired trace of a conversation.
+pub async fn latest_trace(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+) -> Result<Option<ChatTraceRow>, TraceError> {
+    let row = sqlx::query_as::<_, ChatTraceRow>(&format!(
+        r#"
+        SELECT {TRACE_COLUMNS} FROM chat_traces
+        WHERE conversation_id = $1 AND expires_at >= now()
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#
+    ))
+    .bind(conversation_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn events_must_be_typed_objects() {
+        assert!(validate_events(&json!([{ "type": "step", "index": 0 }])).is_ok());
+        assert!(validate_events(&json!([])).is_ok());
+        assert!(validate_events(&json!({ "type": "step" })).is_err());
+        assert!(validate_events(&json!([{ "index": 0 }])).is_err());
+        assert!(validate_events(&json!([{ "type": 1 }])).is_err());
+
+        let many: Vec<_> = (0..=MAX_EVENTS)
+            .map(|_| json!({ "type": "step" }))
+            .collect();
+        assert!(validate_events(&serde_json::Value::Array(many)).is_err());
+    }
+}
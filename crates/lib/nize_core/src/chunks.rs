@@ -0,0 +1,146 @@
+//! Content-addressed chunk store shared by documents and notes.
+//!
+//! Chunk text lives once per distinct content in `chunks`, keyed by its
+//! [`chunk_hash`], and is embedded once per model (see
+//! [`crate::embedding::indexer`]). `document_chunks` and `note_chunks` map
+//! documents and notes to the chunks they consist of, so boilerplate
+//! repeated across them — headers, footers, signatures — is stored,
+//! embedded and indexed once. Database triggers keep each chunk's
+//! `ref_count` (its number of mappings) current, also when documents or
+//! notes are deleted; [`release_unreferenced`] removes the chunks nothing
+//! maps to any more, with their embeddings.
+
+use std::collections::HashSet;
+
+use sha2::{Digest, Sha256};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+/// Key of a chunk: the hex SHA-256 of its UTF-8 content. Migration 0057
+/// computes the same value in SQL; keep them in step.
+pub fn chunk_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Store chunk contents that are not stored yet and return the hash of
+/// each, in order. Existing chunks are locked until the transaction ends,
+/// so [`release_unreferenced`] cannot remove them before the caller maps
+/// them.
+pub async fn put_chunks(
+    conn: &mut PgConnection,
+    contents: &[&str],
+) -> Result<Vec<String>, sqlx::Error> {
+    let hashes: Vec<String> = contents.iter().map(|c| chunk_hash(c)).collect();
+    // ON CONFLICT DO UPDATE may touch each row once per statement.
+    let mut seen = HashSet::new();
+    let (unique_hashes, unique_contents): (Vec<&str>, Vec<&str>) = hashes
+        .iter()
+        .zip(contents)
+        .filter(|(hash, _)| seen.insert(hash.as_str()))
+        .map(|(hash, content)| (hash.as_str(), *content))
+        .unzip();
+    sqlx::query(
+        r#"
+        INSERT INTO chunks (hash, content)
+        SELECT * FROM UNNEST($1::text[], $2::text[])
+        ON CONFLICT (hash) DO UPDATE SET ref_count = chunks.ref_count
+        "#,
+    )
+    .bind(&unique_hashes)
+    .bind(&unique_contents)
+    .execute(conn)
+    .await?;
+    Ok(hashes)
+}
+
+/// Delete the chunks nothing maps to, with their embeddings. Chunks locked
+/// by other transactions (which may be about to map them) are skipped.
+/// Returns the number deleted.
+pub async fn release_unreferenced(conn: &mut PgConnection) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM chunks
+        WHERE hash IN (
+            SELECT k.hash FROM chunks k
+            WHERE k.ref_count = 0
+              AND NOT EXISTS (SELECT 1 FROM document_chunks c WHERE c.chunk_hash = k.hash)
+              AND NOT EXISTS (SELECT 1 FROM note_chunks c WHERE c.chunk_hash = k.hash)
+            FOR UPDATE SKIP LOCKED
+        )
+        "#,
+    )
+    .execute(conn)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// A stored chunk with no embedding under some model.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct UnembeddedChunk {
+    pub hash: String,
+    pub content: String,
+}
+
+/// Those of `hashes` with no embedding under `model_id`, each once.
+pub async fn unembedded(
+    pool: &PgPool,
+    hashes: &[String],
+    model_id: &Uuid,
+) -> Result<Vec<UnembeddedChunk>, sqlx::Error> {
+    sqlx::query_as::<_, UnembeddedChunk>(
+        r#"
+        SELECT k.hash, k.content
+        FROM chunks k
+        WHERE k.hash = ANY($1)
+          AND NOT EXISTS (
+              SELECT 1 FROM chunk_embeddings e WHERE e.chunk_hash = k.hash AND e.model_id = $2
+          )
+        ORDER BY k.hash
+        "#,
+    )
+    .bind(hashes)
+    .bind(model_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Size of the store, and how much mapping chunks by content saves.
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkStoreStats {
+    /// Distinct chunk contents stored.
+    pub chunks: i64,
+    /// Document and note chunks mapped to them.
+    pub references: i64,
+    /// Chunks stored (and embedded) once although mapped more than once.
+    pub shared_chunks: i64,
+}
+
+/// Count the stored chunks and their mappings.
+pub async fn stats(pool: &PgPool) -> Result<ChunkStoreStats, sqlx::Error> {
+    sqlx::query_as::<_, ChunkStoreStats>(
+        r#"
+        SELECT COUNT(*) AS chunks,
+               COALESCE(SUM(ref_count), 0)::BIGINT AS "references",
+               COUNT(*) FILTER (WHERE ref_count > 1) AS shared_chunks
+        FROM chunks
+        "#,
+    )
+    .fetch_one(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_hash_is_sha256_of_content() {
+        assert_eq!(
+            chunk_hash(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(chunk_hash("Copyright 2025"), chunk_hash("Copyright 2025"));
+        assert_ne!(chunk_hash("Copyright 2025"), chunk_hash("Copyright 2026"));
+    }
+}
@@ -1,10 +1,15 @@
 // @awa-component: CFG-ConfigCache
 //
-//! In-memory config cache with TTL-based expiration.
+//! In-memory config cache with TTL-based expiration, built on
+//! [`crate::cache::Cache`] and reported as the `config` cache.
 
-use std::collections::HashMap;
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::Cache;
 
 /// Default TTL for system config: 5 minutes.
 pub const DEFAULT_SYSTEM_TTL_MS: i64 = 300_000;
@@ -12,17 +17,51 @@ pub const DEFAULT_SYSTEM_TTL_MS: i64 = 300_000;
 /// Default TTL for user-override config: 30 seconds.
 pub const DEFAULT_USER_OVERRIDE_TTL_MS: i64 = 30_000;
 
+/// Most entries kept; the least recently used beyond this are dropped.
+pub const CAPACITY: usize = 10_000;
+
 /// A cached entry with expiry.
 #[derive(Debug, Clone)]
 struct CacheEntry {
+    key: String,
+    scope: String,
+    user_id: Option<String>,
     value: String,
+    cached_at: DateTime<Utc>,
     expires_at: DateTime<Utc>,
 }
 
+/// Snapshot of one cache entry, for inspection and warm starts.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheEntryInfo {
+    pub key: String,
+    pub scope: String,
+    pub user_id: Option<String>,
+    pub value: String,
+    pub cached_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Milliseconds since the entry was cached.
+    pub age_ms: i64,
+    /// Whether the entry has expired but not yet been replaced.
+    pub expired: bool,
+}
+
+/// Hit/miss counters and size of the cache.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub system_ttl_ms: i64,
+    pub user_override_ttl_ms: i64,
+}
+
 /// In-memory config cache keyed by `(config_key, scope, user_id)`.
 #[derive(Debug)]
 pub struct ConfigCache {
-    entries: HashMap<String, CacheEntry>,
+    entries: Cache<String, CacheEntry>,
     /// TTL for system scope entries (milliseconds).
     pub system_ttl_ms: i64,
     /// TTL for user-override scope entries (milliseconds).
@@ -33,7 +72,11 @@ impl ConfigCache {
     /// Create a new cache with default TTLs.
     pub fn new() -> Self {
         Self {
-            entries: HashMap::new(),
+            entries: Cache::new(
+                "config",
+                CAPACITY,
+                Duration::from_millis(DEFAULT_SYSTEM_TTL_MS as u64),
+            ),
             system_ttl_ms: DEFAULT_SYSTEM_TTL_MS,
             user_override_ttl_ms: DEFAULT_USER_OVERRIDE_TTL_MS,
         }
@@ -49,14 +92,9 @@ impl ConfigCache {
 
     /// Get a cached value if it exists and has not expired.
     pub fn get(&self, key: &str, scope: &str, user_id: Option<&str>) -> Option<String> {
-        let ck = Self::cache_key(key, scope, user_id);
-        self.entries.get(&ck).and_then(|entry| {
-            if Utc::now() < entry.expires_at {
-                Some(entry.value.clone())
-            } else {
-                None
-            }
-        })
+        self.entries
+            .get(&Self::cache_key(key, scope, user_id))
+            .map(|entry| entry.value)
     }
 
     /// Insert or update a cached value.
@@ -66,8 +104,20 @@ impl ConfigCache {
             "system" => self.system_ttl_ms,
             _ => self.user_override_ttl_ms,
         };
-        let expires_at = Utc::now() + chrono::Duration::milliseconds(ttl_ms);
-        self.entries.insert(ck, CacheEntry { value, expires_at });
+        let cached_at = Utc::now();
+        let expires_at = cached_at + chrono::Duration::milliseconds(ttl_ms);
+        self.entries.insert_with_ttl(
+            ck,
+            CacheEntry {
+                key: key.to_string(),
+                scope: scope.to_string(),
+                user_id: user_id.map(str::to_string),
+                value,
+                cached_at,
+                expires_at,
+            },
+            Duration::from_millis(ttl_ms.max(0) as u64),
+        );
     }
 
     /// Remove a specific entry from the cache.
@@ -78,14 +128,101 @@ impl ConfigCache {
 
     /// Remove all cache entries for a given config key (all scopes, all users).
     pub fn invalidate_all_for_key(&mut self, key: &str) {
-        self.entries
-            .retain(|ck, _| !ck.starts_with(&format!("{key}:")));
+        let prefix = format!("{key}:");
+        self.entries.retain(|ck, _| !ck.starts_with(&prefix));
+    }
+
+    /// Remove entries matching `scope`, `key` and `user_id`; `None` matches
+    /// anything. Returns the number removed.
+    pub fn invalidate_matching(
+        &mut self,
+        scope: Option<&str>,
+        key: Option<&str>,
+        user_id: Option<&str>,
+    ) -> usize {
+        self.entries.retain(|_, e| {
+            !(scope.is_none_or(|s| e.scope == s)
+                && key.is_none_or(|k| e.key == k)
+                && user_id.is_none_or(|u| e.user_id.as_deref() == Some(u)))
+        })
     }
 
     /// Remove all entries from the cache.
     pub fn clear(&mut self) {
         self.entries.clear();
     }
+
+    /// Snapshot of all entries, including expired ones, ordered by key,
+    /// scope and user.
+    pub fn entries(&self) -> Vec<CacheEntryInfo> {
+        let now = Utc::now();
+        let mut items: Vec<CacheEntryInfo> = self
+            .entries
+            .entries()
+            .into_iter()
+            .map(|(_, e)| CacheEntryInfo {
+                key: e.key.clone(),
+                scope: e.scope.clone(),
+                user_id: e.user_id.clone(),
+                value: e.value.clone(),
+                cached_at: e.cached_at,
+                expires_at: e.expires_at,
+                age_ms: (now - e.cached_at).num_milliseconds(),
+                expired: now >= e.expires_at,
+            })
+            .collect();
+        items.sort_by(|a, b| (&a.key, &a.scope, &a.user_id).cmp(&(&b.key, &b.scope, &b.user_id)));
+        items
+    }
+
+    /// Put back entries from an earlier [`Self::entries`] snapshot, each for
+    /// what is left of its TTL and never longer than the current TTL of its
+    /// scope. Expired entries are skipped. Returns the number restored.
+    pub fn restore(&mut self, entries: &[CacheEntryInfo]) -> usize {
+        let now = Utc::now();
+        let mut restored = 0;
+        for e in entries {
+            let ttl_ms = match e.scope.as_str() {
+                "system" => self.system_ttl_ms,
+                _ => self.user_override_ttl_ms,
+            };
+            let expires_at = e
+                .expires_at
+                .min(now + chrono::Duration::milliseconds(ttl_ms));
+            let Ok(remaining) = (expires_at - now).to_std() else {
+                continue;
+            };
+            if remaining.is_zero() {
+                continue;
+            }
+            self.entries.insert_with_ttl(
+                Self::cache_key(&e.key, &e.scope, e.user_id.as_deref()),
+                CacheEntry {
+                    key: e.key.clone(),
+                    scope: e.scope.clone(),
+                    user_id: e.user_id.clone(),
+                    value: e.value.clone(),
+                    cached_at: e.cached_at,
+                    expires_at,
+                },
+                remaining,
+            );
+            restored += 1;
+        }
+        restored
+    }
+
+    /// Current hit/miss counters and size.
+    pub fn stats(&self) -> CacheStats {
+        let snapshot = self.entries.snapshot();
+        CacheStats {
+            entries: snapshot.entries,
+            hits: snapshot.hits,
+            misses: snapshot.misses,
+            system_ttl_ms: self.system_ttl_ms,
+            user_override_ttl_ms: self.user_override_ttl_ms,
+        }
+    }
 }
 
 impl Default for ConfigCache {
@@ -148,6 +285,23 @@ mod tests {
         assert_eq!(cache.get("k2", "system", None), Some("other".to_string()));
     }
 
+    #[test]
+    fn restore_keeps_only_live_entries() {
+        let mut cache = ConfigCache::new();
+        cache.set("k1", "system", None, "v1".to_string());
+        cache.set("k2", "user-override", Some("u1"), "v2".to_string());
+        let mut snapshot = cache.entries();
+        snapshot[0].expires_at = Utc::now() - chrono::Duration::seconds(1);
+
+        let mut restored = ConfigCache::new();
+        assert_eq!(restored.restore(&snapshot), 1);
+        assert!(restored.get("k1", "system", None).is_none());
+        assert_eq!(
+            restored.get("k2", "user-override", Some("u1")),
+            Some("v2".to_string())
+        );
+    }
+
     #[test]
     fn clear_removes_all_entries() {
         let mut cache = ConfigCache::new();
@@ -167,4 +321,39 @@ mod tests {
         // Should be expired
         assert!(cache.get("k1", "system", None).is_none());
     }
+
+    #[test]
+    fn stats_count_hits_and_misses() {
+        let mut cache = ConfigCache::new();
+        cache.set("k1", "system", None, "v1".to_string());
+        cache.get("k1", "system", None);
+        cache.get("k1", "system", None);
+        cache.get("k2", "system", None);
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 1);
+    }
+
+    #[test]
+    fn invalidate_matching_filters_by_scope_and_user() {
+        let mut cache = ConfigCache::new();
+        cache.set("k1", "system", None, "sys".to_string());
+        cache.set("k1", "user-override", Some("u1"), "a".to_string());
+        cache.set("k2", "user-override", Some("u2"), "b".to_string());
+
+        assert_eq!(
+            cache.invalidate_matching(Some("user-override"), None, Some("u1")),
+            1
+        );
+        assert_eq!(
+            cache.invalidate_matching(Some("user-override"), None, None),
+            1
+        );
+        let entries = cache.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "k1");
+        assert_eq!(entries[0].scope, "system");
+        assert!(!entries[0].expired);
+    }
 }
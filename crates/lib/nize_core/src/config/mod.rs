@@ -5,18 +5,21 @@
 pub mod cache;
 pub mod queries;
 pub mod resolver;
+pub mod snapshots;
 pub mod validation;
 
 use thiserror::Error;
 
+use crate::i18n::Message;
+
 /// Configuration errors.
 #[derive(Debug, Error)]
 pub enum ConfigError {
-    #[error("Config key not found: {0}")]
-    NotFound(String),
+    #[error("{0}")]
+    NotFound(Message),
 
     #[error("Validation error: {0}")]
-    ValidationError(String),
+    ValidationError(Message),
 
     #[error("Database error: {0}")]
     DbError(#[from] sqlx::Error),
@@ -2,7 +2,7 @@
 //
 //! Database queries for the configuration system.
 
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
 
 use super::ConfigError;
 use crate::models::config::{ConfigDefinition, ConfigScope, ConfigValidator, ConfigValue};
@@ -212,7 +212,7 @@ pub async fn get_all_values(
 
 /// Upsert a config value.
 pub async fn upsert_value(
-    pool: &PgPool,
+    executor: impl PgExecutor<'_>,
     key: &str,
     scope: &ConfigScope,
     user_id: Option<&str>,
@@ -239,7 +239,7 @@ pub async fn upsert_value(
     .bind(scope.as_str())
     .bind(user_id)
     .bind(value)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await?;
 
     Ok(parse_value_row(row))
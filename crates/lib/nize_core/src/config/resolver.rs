@@ -11,6 +11,7 @@ use tokio::sync::RwLock;
 use super::ConfigError;
 use super::cache::ConfigCache;
 use super::queries;
+use crate::i18n::Message;
 use crate::models::config::{ConfigScope, ResolvedConfigItem};
 
 /// Resolve the effective value for a single config key.
@@ -22,9 +23,9 @@ pub async fn get_effective_value(
     key: &str,
     user_id: Option<&str>,
 ) -> Result<ResolvedConfigItem, ConfigError> {
-    let def = queries::get_definition(pool, key)
-        .await?
-        .ok_or_else(|| ConfigError::NotFound(key.to_string()))?;
+    let def = queries::get_definition(pool, key).await?.ok_or_else(|| {
+        ConfigError::NotFound(Message::new("config.key_not_found").arg("key", key))
+    })?;
 
     let (value, is_overridden) = resolve_value(pool, cache, key, user_id).await?;
 
@@ -118,9 +119,9 @@ pub async fn get_system_value(
     }
 
     // Fall back to definition default
-    let def = queries::get_definition(pool, key)
-        .await?
-        .ok_or_else(|| ConfigError::NotFound(key.to_string()))?;
+    let def = queries::get_definition(pool, key).await?.ok_or_else(|| {
+        ConfigError::NotFound(Message::new("config.key_not_found").arg("key", key))
+    })?;
     Ok(def.default_value)
 }
 
@@ -173,9 +174,9 @@ async fn resolve_value(
     }
 
     // 3. Fall back to definition default
-    let def = queries::get_definition(pool, key)
-        .await?
-        .ok_or_else(|| ConfigError::NotFound(key.to_string()))?;
+    let def = queries::get_definition(pool, key).await?.ok_or_else(|| {
+        ConfigError::NotFound(Message::new("config.key_not_found").arg("key", key))
+    })?;
     Ok((def.default_value, false))
 }
 
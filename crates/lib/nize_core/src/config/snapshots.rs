@@ -0,0 +1,319 @@
+//! Versioned snapshots of the system-scope config.
+//!
+//! Each admin change to a system value records the complete resulting state
+//! as a new version (the first change also records the state before it as a
+//! `baseline`). [`list_snapshots`] pairs versions with their predecessors for
+//! diffing, and [`rollback`] restores a version atomically — itself recorded
+//! as a new version, so a rollback can be rolled back. User overrides are not
+//! snapshotted.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::ConfigError;
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Snapshot action: the state before the first recorded change.
+pub const ACTION_BASELINE: &str = "baseline";
+/// Snapshot action: an admin changed one value.
+pub const ACTION_UPDATE: &str = "update";
+/// Snapshot action: an admin restored an earlier version.
+pub const ACTION_ROLLBACK: &str = "rollback";
+
+/// System-scope values by key, as stored (secrets encrypted).
+pub type ConfigState = BTreeMap<String, String>;
+
+/// Row returned by snapshot queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ConfigSnapshotRow {
+    pub version: i32,
+    pub values: serde_json::Value,
+    pub action: String,
+    pub key: Option<String>,
+    pub restored_from: Option<i32>,
+    pub actor_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ConfigSnapshotRow {
+    /// The snapshotted values.
+    pub fn state(&self) -> ConfigState {
+        serde_json::from_value(self.values.clone()).unwrap_or_default()
+    }
+}
+
+/// One key's change between two states; `None` means unset (default).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    pub key: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Result of a [`rollback`].
+#[derive(Debug, Clone)]
+pub struct RollbackResult {
+    /// Version recording the restored state.
+    pub version: i32,
+    pub restored_from: i32,
+    pub changes: Vec<ConfigChange>,
+}
+
+const SNAPSHOT_COLUMNS: &str =
+    r#"version, "values", action, key, restored_from, actor_id, created_at"#;
+
+/// Keys whose values differ between `before` and `after`, in key order.
+pub fn diff(before: &ConfigState, after: &ConfigState) -> Vec<ConfigChange> {
+    let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|key| before.get(*key) != after.get(*key))
+        .map(|key| ConfigChange {
+            key: key.clone(),
+            before: before.get(key).cloned(),
+            after: after.get(key).cloned(),
+        })
+        .collect()
+}
+
+/// Current system-scope values.
+pub async fn current_state(conn: &mut PgConnection) -> Result<ConfigState, ConfigError> {
+    let rows = sqlx::query_as::<_, (String, String)>(
+        "SELECT key, value FROM config_values WHERE scope = 'system'::config_scope AND user_id IS NULL",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+async fn insert_snapshot(
+    conn: &mut PgConnection,
+    state: &ConfigState,
+    action: &str,
+    key: Option<&str>,
+    restored_from: Option<i32>,
+    actor_id: Option<&Uuid>,
+) -> Result<i32, ConfigError> {
+    let version = sqlx::query_scalar::<_, i32>(
+        r#"
+        INSERT INTO config_snapshots ("values", action, key, restored_from, actor_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING version
+        "#,
+    )
+    .bind(serde_json::to_value(state).unwrap_or_default())
+    .bind(action)
+    .bind(key)
+    .bind(restored_from)
+    .bind(actor_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(version)
+}
+
+/// Record the current state as a `baseline` if no snapshot exists yet.
+/// Call inside the change's transaction, before changing anything.
+pub async fn ensure_baseline(conn: &mut PgConnection) -> Result<(), ConfigError> {
+    // Serialize snapshot writers so versions follow commit order.
+    sqlx::query("LOCK TABLE config_snapshots IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *conn)
+        .await?;
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM config_snapshots)")
+        .fetch_one(&mut *conn)
+        .await?;
+    if !exists {
+        let state = current_state(conn).await?;
+        insert_snapshot(conn, &state, ACTION_BASELINE, None, None, None).await?;
+    }
+    Ok(())
+}
+
+/// Record the current state after an admin changed `key`, with an audit
+/// entry. Call inside the change's transaction, after [`ensure_baseline`]
+/// and the change.
+pub async fn record_update(
+    conn: &mut PgConnection,
+    key: &str,
+    actor_id: Option<&Uuid>,
+) -> Result<i32, ConfigError> {
+    let state = current_state(conn).await?;
+    let version = insert_snapshot(conn, &state, ACTION_UPDATE, Some(key), None, actor_id).await?;
+    audit(
+        conn,
+        actor_id,
+        "config.update",
+        Some(key),
+        serde_json::json!({ "version": version }),
+    )
+    .await?;
+    Ok(version)
+}
+
+async fn audit(
+    conn: &mut PgConnection,
+    actor_id: Option<&Uuid>,
+    action: &str,
+    key: Option<&str>,
+    details: serde_json::Value,
+) -> Result<(), ConfigError> {
+    sqlx::query(
+        "INSERT INTO config_audit (id, actor_id, action, key, details) VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(uuidv7())
+    .bind(actor_id)
+    .bind(action)
+    .bind(key)
+    .bind(details)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// List snapshots, newest first, each with its predecessor (if any) for
+/// diffing. Returns the page and the total count.
+pub async fn list_snapshots(
+    pool: &PgPool,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<(ConfigSnapshotRow, Option<ConfigSnapshotRow>)>, i64), ConfigError> {
+    let total = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM config_snapshots")
+        .fetch_one(pool)
+        .await?;
+    // One extra row: the predecessor of the page's oldest entry.
+    let rows = sqlx::query_as::<_, ConfigSnapshotRow>(&format!(
+        "SELECT {SNAPSHOT_COLUMNS} FROM config_snapshots ORDER BY version DESC LIMIT $1 OFFSET $2"
+    ))
+    .bind(limit + 1)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    let mut page = Vec::with_capacity(rows.len());
+    let mut rows = rows.into_iter().peekable();
+    while let Some(row) = rows.next() {
+        if page.len() as i64 == limit {
+            break;
+        }
+        page.push((row, rows.peek().cloned()));
+    }
+    Ok((page, total))
+}
+
+/// Restore the system-scope values of `version` in one transaction: values
+/// set since are removed or reset, values since removed are restored, and
+/// the result is recorded as a new version with an audit entry. Keys whose
+/// definitions no longer exist are skipped. The caller must invalidate the
+/// config cache for the changed keys.
+pub async fn rollback(
+    pool: &PgPool,
+    version: i32,
+    actor_id: Option<&Uuid>,
+) -> Result<RollbackResult, ConfigError> {
+    let mut tx = pool.begin().await?;
+    ensure_baseline(&mut tx).await?;
+
+    let target = sqlx::query_as::<_, ConfigSnapshotRow>(&format!(
+        "SELECT {SNAPSHOT_COLUMNS} FROM config_snapshots WHERE version = $1"
+    ))
+    .bind(version)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| {
+        ConfigError::NotFound(Message::new("config.version_not_found").arg("version", version))
+    })?;
+
+    let defined: Vec<String> = sqlx::query_scalar("SELECT key FROM config_definitions")
+        .fetch_all(&mut *tx)
+        .await?;
+    let mut target_state = target.state();
+    target_state.retain(|key, _| defined.contains(key));
+
+    let before = current_state(&mut tx).await?;
+    let changes = diff(&before, &target_state);
+    for change in &changes {
+        sqlx::query(
+            "DELETE FROM config_values WHERE key = $1 AND scope = 'system'::config_scope AND user_id IS NULL",
+        )
+        .bind(&change.key)
+        .execute(&mut *tx)
+        .await?;
+        if let Some(value) = &change.after {
+            sqlx::query(
+                "INSERT INTO config_values (key, scope, value, updated_at) VALUES ($1, 'system'::config_scope, $2, now())",
+            )
+            .bind(&change.key)
+            .bind(value)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    let new_version = insert_snapshot(
+        &mut tx,
+        &target_state,
+        ACTION_ROLLBACK,
+        None,
+        Some(version),
+        actor_id,
+    )
+    .await?;
+    audit(
+        &mut tx,
+        actor_id,
+        "config.rollback",
+        None,
+        serde_json::json!({
+            "version": new_version,
+            "restoredFrom": version,
+            "keys": changes.iter().map(|c| &c.key).collect::<Vec<_>>(),
+        }),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(RollbackResult {
+        version: new_version,
+        restored_from: version,
+        changes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(pairs: &[(&str, &str)]) -> ConfigState {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn diff_lists_added_changed_and_removed_keys() {
+        let before = state(&[("a", "1"), ("b", "2"), ("c", "3")]);
+        let after = state(&[("a", "1"), ("b", "20"), ("d", "4")]);
+        let change = |key: &str, before: Option<&str>, after: Option<&str>| ConfigChange {
+            key: key.into(),
+            before: before.map(Into::into),
+            after: after.map(Into::into),
+        };
+        assert_eq!(
+            diff(&before, &after),
+            vec![
+                change("b", Some("2"), Some("20")),
+                change("c", Some("3"), None),
+                change("d", None, Some("4")),
+            ]
+        );
+        assert!(diff(&after, &after).is_empty());
+    }
+}
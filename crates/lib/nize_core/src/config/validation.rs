@@ -2,41 +2,35 @@
 //
 //! Config value validation — runs validators defined on config definitions.
 
+use crate::i18n::Message;
 use crate::models::config::ConfigValidator;
 
 /// Validate a value against a list of validators.
-/// Returns a list of validation error messages (empty = valid).
-pub fn validate_value(value: &str, validators: &[ConfigValidator]) -> Vec<String> {
+/// Returns a list of validation error messages (empty = valid). A
+/// validator's own `message` is used as is, in place of the built-in text.
+pub fn validate_value(value: &str, validators: &[ConfigValidator]) -> Vec<Message> {
     let mut errors = Vec::new();
 
     for validator in validators {
         match validator.validator_type.as_str() {
             "required" => {
                 if value.trim().is_empty() {
-                    errors.push(
-                        validator
-                            .message
-                            .clone()
-                            .unwrap_or_else(|| "Value is required".to_string()),
-                    );
+                    errors.push(error(validator, || Message::new("config.value_required")));
                 }
             }
             "min" => {
                 if let Some(min_val) = validator.value.as_ref().and_then(|v| v.as_f64()) {
                     if let Ok(num) = value.parse::<f64>() {
                         if num < min_val {
-                            errors.push(
-                                validator
-                                    .message
-                                    .clone()
-                                    .unwrap_or_else(|| format!("Value must be at least {min_val}")),
-                            );
+                            errors.push(error(validator, || {
+                                Message::new("config.value_too_small").arg("min", min_val)
+                            }));
                         }
                     }
                     // If value is a string, check length
                     else if (value.len() as f64) < min_val {
-                        errors.push(validator.message.clone().unwrap_or_else(|| {
-                            format!("Value must be at least {min_val} characters")
+                        errors.push(error(validator, || {
+                            Message::new("config.value_too_short").arg("min", min_val)
                         }));
                     }
                 }
@@ -46,24 +40,18 @@ pub fn validate_value(value: &str, validators: &[ConfigValidator]) -> Vec<String
                     && let Ok(num) = value.parse::<f64>()
                     && num > max_val
                 {
-                    errors.push(
-                        validator
-                            .message
-                            .clone()
-                            .unwrap_or_else(|| format!("Value must be at most {max_val}")),
-                    );
+                    errors.push(error(validator, || {
+                        Message::new("config.value_too_large").arg("max", max_val)
+                    }));
                 }
             }
             "regex" => {
                 if let Some(pattern) = validator.value.as_ref().and_then(|v| v.as_str()) {
                     // Use basic string matching; full regex support can be added later
                     if !value.contains(pattern) {
-                        errors.push(
-                            validator
-                                .message
-                                .clone()
-                                .unwrap_or_else(|| format!("Value must match pattern: {pattern}")),
-                        );
+                        errors.push(error(validator, || {
+                            Message::new("config.value_pattern_mismatch").arg("pattern", pattern)
+                        }));
                     }
                 }
             }
@@ -74,11 +62,23 @@ pub fn validate_value(value: &str, validators: &[ConfigValidator]) -> Vec<String
     errors
 }
 
+/// The validator's own message, or the built-in one.
+fn error(validator: &ConfigValidator, builtin: impl FnOnce() -> Message) -> Message {
+    match &validator.message {
+        Some(message) => Message::new("config.validator_message").arg("message", message),
+        None => builtin(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    fn texts(errors: Vec<Message>) -> Vec<String> {
+        errors.iter().map(ToString::to_string).collect()
+    }
+
     fn make_validator(
         vtype: &str,
         value: Option<serde_json::Value>,
@@ -95,7 +95,7 @@ mod tests {
     #[test]
     fn required_rejects_empty() {
         let validators = vec![make_validator("required", None, Some("Required"))];
-        let errors = validate_value("", &validators);
+        let errors = texts(validate_value("", &validators));
         assert_eq!(errors, vec!["Required"]);
     }
 
@@ -103,7 +103,7 @@ mod tests {
     #[test]
     fn required_accepts_non_empty() {
         let validators = vec![make_validator("required", None, Some("Required"))];
-        let errors = validate_value("hello", &validators);
+        let errors = texts(validate_value("hello", &validators));
         assert!(errors.is_empty());
     }
 
@@ -111,7 +111,7 @@ mod tests {
     #[test]
     fn min_rejects_below_threshold() {
         let validators = vec![make_validator("min", Some(json!(0)), Some("Must be >= 0"))];
-        let errors = validate_value("-1", &validators);
+        let errors = texts(validate_value("-1", &validators));
         assert_eq!(errors, vec!["Must be >= 0"]);
     }
 
@@ -119,7 +119,7 @@ mod tests {
     #[test]
     fn min_accepts_at_threshold() {
         let validators = vec![make_validator("min", Some(json!(0)), Some("Must be >= 0"))];
-        let errors = validate_value("0", &validators);
+        let errors = texts(validate_value("0", &validators));
         assert!(errors.is_empty());
     }
 
@@ -127,7 +127,7 @@ mod tests {
     #[test]
     fn max_rejects_above_threshold() {
         let validators = vec![make_validator("max", Some(json!(2)), Some("Must be <= 2"))];
-        let errors = validate_value("3", &validators);
+        let errors = texts(validate_value("3", &validators));
         assert_eq!(errors, vec!["Must be <= 2"]);
     }
 
@@ -135,7 +135,7 @@ mod tests {
     #[test]
     fn max_accepts_at_threshold() {
         let validators = vec![make_validator("max", Some(json!(2)), Some("Must be <= 2"))];
-        let errors = validate_value("2", &validators);
+        let errors = texts(validate_value("2", &validators));
         assert!(errors.is_empty());
     }
 
@@ -146,13 +146,13 @@ mod tests {
             make_validator("min", Some(json!(0)), Some("Min 0")),
             make_validator("max", Some(json!(2)), Some("Max 2")),
         ];
-        let errors = validate_value("-1", &validators);
+        let errors = texts(validate_value("-1", &validators));
         assert_eq!(errors, vec!["Min 0"]);
 
-        let errors = validate_value("3", &validators);
+        let errors = texts(validate_value("3", &validators));
         assert_eq!(errors, vec!["Max 2"]);
 
-        let errors = validate_value("1", &validators);
+        let errors = texts(validate_value("1", &validators));
         assert!(errors.is_empty());
     }
 }
@@ -0,0 +1,214 @@
+//! Local folder connector.
+//!
+//! [`scan`] lists the files under a folder that can be ingested, with their
+//! size and modification time; changes are found by comparing scans (see
+//! [`super::plan_sync`]) rather than by subscribing to file system events,
+//! so edits made while the server was down are picked up too. Hidden files
+//! and folders and symbolic links are skipped, so a scan never leaves the
+//! folder.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, SubsecRound, Utc};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+use super::ConnectorError;
+
+use crate::i18n::Message;
+
+/// Most files a single folder connector may track.
+pub const MAX_FILES: usize = 10_000;
+
+/// A file found by [`scan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedFile {
+    /// Path relative to the folder, `/`-separated.
+    pub path: String,
+    pub size: i64,
+    /// Modification time, truncated to the microseconds the database keeps.
+    pub modified_at: DateTime<Utc>,
+}
+
+/// Parse the comma-separated `ingest.connectors.roots` value.
+pub fn parse_roots(value: &str) -> Vec<PathBuf> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|root| !root.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Resolve a folder a connector should sync, which must be an existing
+/// directory under one of `roots`.
+pub fn check_source(source: &str, roots: &[PathBuf]) -> Result<PathBuf, ConnectorError> {
+    if roots.is_empty() {
+        return Err(ConnectorError::Validation(Message::new(
+            "connectors.folders_disabled",
+        )));
+    }
+    let source = source.trim();
+    if source.is_empty() || !Path::new(source).is_absolute() {
+        return Err(ConnectorError::Validation(Message::new(
+            "connectors.folder_not_absolute",
+        )));
+    }
+    let folder = std::fs::canonicalize(source).map_err(|_| {
+        ConnectorError::Validation(Message::new("connectors.folder_missing").arg("folder", source))
+    })?;
+    if !folder.is_dir() {
+        return Err(ConnectorError::Validation(
+            Message::new("connectors.not_a_folder").arg("folder", source),
+        ));
+    }
+    let allowed = roots
+        .iter()
+        .filter_map(|root| std::fs::canonicalize(root).ok())
+        .any(|root| folder.starts_with(root));
+    if !allowed {
+        return Err(ConnectorError::Validation(
+            Message::new("connectors.folder_not_allowed").arg("folder", source),
+        ));
+    }
+    Ok(folder)
+}
+
+/// MIME type of a file the ingest pipeline can extract, by extension.
+/// `None` for files connectors skip.
+pub fn mime_type(path: &str) -> Option<&'static str> {
+    let (_, extension) = path.rsplit_once('.')?;
+    let mime = match extension.to_ascii_lowercase().as_str() {
+        "txt" | "text" | "log" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "tif" | "tiff" => "image/tiff",
+        "webp" => "image/webp",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "m4a" => "audio/mp4",
+        "ogg" => "audio/ogg",
+        "flac" => "audio/flac",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// List the ingestible files under `folder`, by path. Blocking; fails once
+/// more than [`MAX_FILES`] are found.
+pub fn scan(folder: &Path) -> Result<Vec<ScannedFile>, ConnectorError> {
+    let mut files = Vec::new();
+    let mut pending = vec![folder.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            // `DirEntry::file_type` does not follow symlinks.
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(entry.path());
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            let path = entry.path();
+            let Some(relative) = relative_path(folder, &path) else {
+                continue;
+            };
+            if mime_type(&relative).is_none() {
+                continue;
+            }
+            let metadata = entry.metadata()?;
+            files.push(ScannedFile {
+                path: relative,
+                size: metadata.len() as i64,
+                modified_at: DateTime::<Utc>::from(metadata.modified()?).trunc_subsecs(6),
+            });
+            if files.len() > MAX_FILES {
+                return Err(ConnectorError::Validation(
+                    Message::new("connectors.too_many_files").arg("max", MAX_FILES),
+                ));
+            }
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// `path` relative to `folder`, `/`-separated; `None` for non-UTF-8 names.
+fn relative_path(folder: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(folder).ok()?;
+    let parts: Option<Vec<&str>> = relative.iter().map(|part| part.to_str()).collect();
+    Some(parts?.join("/"))
+}
+
+/// Hex SHA-256 of a file's contents.
+pub async fn hash_file(path: &Path) -> Result<String, ConnectorError> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_lists_ingestible_files_and_skips_hidden_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("sub/deeper")).unwrap();
+        std::fs::create_dir_all(root.join(".git")).unwrap();
+        std::fs::write(root.join("a.md"), "alpha").unwrap();
+        std::fs::write(root.join("sub/deeper/b.PDF"), "%PDF").unwrap();
+        std::fs::write(root.join("sub/c.exe"), "binary").unwrap();
+        std::fs::write(root.join(".hidden.txt"), "secret").unwrap();
+        std::fs::write(root.join(".git/config.txt"), "git").unwrap();
+
+        let files = scan(root).unwrap();
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["a.md", "sub/deeper/b.PDF"]);
+        assert_eq!(files[0].size, 5);
+    }
+
+    #[test]
+    fn sources_must_lie_under_a_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let inside = dir.path().join("notes");
+        std::fs::create_dir_all(&inside).unwrap();
+        let roots = parse_roots(&format!(" {} , ", dir.path().display()));
+        assert_eq!(roots.len(), 1);
+
+        assert!(check_source(inside.to_str().unwrap(), &roots).is_ok());
+        assert!(check_source(&format!("{}/../", inside.display()), &roots).is_ok());
+        assert!(check_source("/", &roots).is_err());
+        assert!(check_source("relative/path", &roots).is_err());
+        assert!(check_source(inside.to_str().unwrap(), &[]).is_err());
+    }
+
+    #[test]
+    fn mime_types_follow_extensions() {
+        assert_eq!(mime_type("notes/a.MD"), Some("text/markdown"));
+        assert_eq!(mime_type("scan.pdf"), Some("application/pdf"));
+        assert_eq!(mime_type("Makefile"), None);
+        assert_eq!(mime_type("tool.exe"), None);
+    }
+}
@@ -0,0 +1,286 @@
+//! Email messages ingested by IMAP connectors.
+//!
+//! [`parse_message`] turns a raw RFC 5322 message into the text ingested as
+//! its document (the main headers followed by the body) and its
+//! attachments. Every document ingested from a message is recorded as an
+//! [`EmailMessageRow`], which search results use to cite the message and
+//! which sync uses to find the documents of messages that were removed.
+
+use chrono::{DateTime, TimeZone, Utc};
+use mail_parser::{Address, MessageParser, MimeHeaders};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{ConnectorError, fs};
+
+/// Longest subject kept in a document's file name, in characters.
+const MAX_FILENAME_SUBJECT_CHARS: usize = 100;
+
+/// A parsed email message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedMessage {
+    /// Message-ID header, without angle brackets.
+    pub message_id: Option<String>,
+    pub subject: String,
+    pub sender: String,
+    pub recipients: String,
+    pub sent_at: Option<DateTime<Utc>>,
+    /// Headers and body text, as ingested.
+    pub text: String,
+    pub attachments: Vec<MailAttachment>,
+}
+
+/// An attachment of a type the ingest pipeline can extract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MailAttachment {
+    pub filename: String,
+    pub mime_type: &'static str,
+    pub content: Vec<u8>,
+}
+
+impl ParsedMessage {
+    /// File name of the message's document: its subject, as a text file.
+    pub fn filename(&self) -> String {
+        let subject: String = self
+            .subject
+            .chars()
+            .map(|c| {
+                if c.is_control() || matches!(c, '/' | '\\') {
+                    '_'
+                } else {
+                    c
+                }
+            })
+            .take(MAX_FILENAME_SUBJECT_CHARS)
+            .collect();
+        let subject = subject.trim();
+        if subject.is_empty() {
+            "(no subject).txt".to_string()
+        } else {
+            format!("{subject}.txt")
+        }
+    }
+}
+
+/// Parse a raw message. `None` when it is not a message at all.
+pub fn parse_message(raw: &[u8]) -> Option<ParsedMessage> {
+    let message = MessageParser::default().parse(raw)?;
+
+    let subject = message.subject().unwrap_or_default().trim().to_string();
+    let sender = message.from().map(format_addresses).unwrap_or_default();
+    let recipients = [message.to(), message.cc()]
+        .into_iter()
+        .flatten()
+        .map(format_addresses)
+        .filter(|list| !list.is_empty())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sent_at = message
+        .date()
+        .and_then(|date| Utc.timestamp_opt(date.to_timestamp(), 0).single());
+
+    let mut text = String::new();
+    for (name, value) in [
+        ("Subject", subject.as_str()),
+        ("From", sender.as_str()),
+        ("To", recipients.as_str()),
+    ] {
+        if !value.is_empty() {
+            text.push_str(&format!("{name}: {value}\n"));
+        }
+    }
+    if let Some(sent_at) = sent_at {
+        text.push_str(&format!("Date: {}\n", sent_at.to_rfc2822()));
+    }
+    if let Some(body) = message.body_text(0) {
+        text.push('\n');
+        text.push_str(body.trim());
+        text.push('\n');
+    }
+
+    let attachments = message
+        .attachments()
+        .filter_map(|part| {
+            let filename = part.attachment_name()?.trim();
+            let mime_type = fs::mime_type(filename)?;
+            Some(MailAttachment {
+                filename: filename.to_string(),
+                mime_type,
+                content: part.contents().to_vec(),
+            })
+        })
+        .collect();
+
+    Some(ParsedMessage {
+        message_id: message.message_id().map(str::to_string),
+        subject,
+        sender,
+        recipients,
+        sent_at,
+        text,
+        attachments,
+    })
+}
+
+/// `Name <address>, …`
+fn format_addresses(address: &Address<'_>) -> String {
+    address
+        .iter()
+        .filter_map(|addr| match (addr.name(), addr.address()) {
+            (Some(name), Some(email)) => Some(format!("{name} <{email}>")),
+            (None, Some(email)) => Some(email.to_string()),
+            (Some(name), None) => Some(name.to_string()),
+            (None, None) => None,
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The message a document was ingested from.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EmailMessageRow {
+    pub document_id: Uuid,
+    pub connector_id: Option<Uuid>,
+    pub folder: String,
+    pub uid: i64,
+    pub message_id: Option<String>,
+    pub subject: String,
+    pub sender: String,
+    pub recipients: String,
+    pub sent_at: Option<DateTime<Utc>>,
+    /// Attachment file name; `None` for the message text itself.
+    pub attachment: Option<String>,
+}
+
+const MESSAGE_COLUMNS: &str = "document_id, connector_id, folder, uid, message_id, subject, \
+     sender, recipients, sent_at, attachment";
+
+/// Record that `document_id` was ingested from `message` (or from its
+/// attachment `attachment`).
+pub async fn record_message(
+    pool: &PgPool,
+    document_id: &Uuid,
+    connector_id: &Uuid,
+    folder: &str,
+    uid: u32,
+    message: &ParsedMessage,
+    attachment: Option<&str>,
+) -> Result<(), ConnectorError> {
+    sqlx::query(
+        r#"
+        INSERT INTO email_messages
+            (document_id, connector_id, folder, uid, message_id, subject, sender,
+             recipients, sent_at, attachment)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (document_id) DO NOTHING
+        "#,
+    )
+    .bind(document_id)
+    .bind(connector_id)
+    .bind(folder)
+    .bind(i64::from(uid))
+    .bind(&message.message_id)
+    .bind(&message.subject)
+    .bind(&message.sender)
+    .bind(&message.recipients)
+    .bind(message.sent_at)
+    .bind(attachment)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// The documents a connector ingested from messages, by folder and UID.
+pub async fn list_messages(
+    pool: &PgPool,
+    connector_id: &Uuid,
+) -> Result<Vec<EmailMessageRow>, ConnectorError> {
+    let rows = sqlx::query_as::<_, EmailMessageRow>(&format!(
+        "SELECT {MESSAGE_COLUMNS} FROM email_messages WHERE connector_id = $1 \
+         ORDER BY folder, uid"
+    ))
+    .bind(connector_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Number of messages (not attachments) a connector has ingested.
+pub async fn count_messages(pool: &PgPool, connector_id: &Uuid) -> Result<i64, ConnectorError> {
+    let count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM email_messages WHERE connector_id = $1 AND attachment IS NULL",
+    )
+    .bind(connector_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAW: &str = "From: Ada Lovelace <ada@example.com>\r\n\
+        To: Charles <charles@example.com>\r\n\
+        Cc: team@example.com\r\n\
+        Subject: Notes on the engine\r\n\
+        Date: Tue, 1 Jul 2025 10:00:00 +0000\r\n\
+        Message-ID: <abc@example.com>\r\n\
+        MIME-Version: 1.0\r\n\
+        Content-Type: multipart/mixed; boundary=\"b\"\r\n\
+        \r\n\
+        --b\r\n\
+        Content-Type: text/plain; charset=utf-8\r\n\
+        \r\n\
+        The analytical engine weaves algebraic patterns.\r\n\
+        --b\r\n\
+        Content-Type: text/markdown\r\n\
+        Content-Disposition: attachment; filename=\"note.md\"\r\n\
+        \r\n\
+        # Note\r\n\
+        --b\r\n\
+        Content-Type: application/octet-stream\r\n\
+        Content-Disposition: attachment; filename=\"tool.exe\"\r\n\
+        \r\n\
+        MZ\r\n\
+        --b--\r\n";
+
+    #[test]
+    fn messages_are_parsed_with_their_headers() {
+        let message = parse_message(RAW.as_bytes()).unwrap();
+        assert_eq!(message.message_id.as_deref(), Some("abc@example.com"));
+        assert_eq!(message.subject, "Notes on the engine");
+        assert_eq!(message.sender, "Ada Lovelace <ada@example.com>");
+        assert_eq!(
+            message.recipients,
+            "Charles <charles@example.com>, team@example.com"
+        );
+        assert_eq!(
+            message.sent_at.unwrap().to_rfc3339(),
+            "2025-07-01T10:00:00+00:00"
+        );
+        assert!(
+            message
+                .text
+                .starts_with("Subject: Notes on the engine\nFrom: Ada")
+        );
+        assert!(message.text.contains("weaves algebraic patterns"));
+        assert_eq!(message.filename(), "Notes on the engine.txt");
+
+        let names: Vec<&str> = message
+            .attachments
+            .iter()
+            .map(|a| a.filename.as_str())
+            .collect();
+        assert_eq!(names, ["note.md"]);
+        assert_eq!(message.attachments[0].mime_type, "text/markdown");
+    }
+
+    #[test]
+    fn filenames_are_sanitized() {
+        let mut message = parse_message(b"Subject: a/b\r\n\r\nbody").unwrap();
+        assert_eq!(message.filename(), "a_b.txt");
+        message.subject.clear();
+        assert_eq!(message.filename(), "(no subject).txt");
+    }
+}
@@ -0,0 +1,737 @@
+//! Ingest connectors — external sources kept in sync with a user's documents.
+//!
+//! A connector pairs a source with a cron schedule (evaluated in UTC, see
+//! [`crate::tasks::schedule`]). The API's connector scheduler claims due
+//! connectors with [`claim_due_connectors`], scans each source, ingests the
+//! files [`plan_sync`] finds added or changed, deletes the documents of
+//! removed files and records the outcome with [`finish_sync`]. The state of
+//! every source file at its last sync is kept as a [`ConnectorItemRow`], so
+//! a sync only touches what changed.
+//!
+//! Local folders ([`fs`], kind [`KIND_FILESYSTEM`]) must lie under one of
+//! the directories an administrator lists in `ingest.connectors.roots`.
+//! Mailboxes ([`imap`], kind [`KIND_IMAP`]) are synced incrementally by
+//! message UID instead: their position in each folder is a cursor (see
+//! [`get_cursor`]) and the ingested messages are recorded by [`mail`].
+
+pub mod fs;
+pub mod imap;
+pub mod mail;
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::config::cache::ConfigCache;
+use crate::config::resolver;
+use crate::i18n::Message;
+use crate::tasks::{TaskError, schedule};
+use crate::uuid::uuidv7;
+use fs::ScannedFile;
+
+/// Config key listing the directories folder connectors may sync from.
+pub const ROOTS_CONFIG_KEY: &str = "ingest.connectors.roots";
+
+/// Kind of a connector syncing a local folder.
+pub const KIND_FILESYSTEM: &str = "filesystem";
+
+/// Kind of a connector syncing an email account over IMAP.
+pub const KIND_IMAP: &str = "imap";
+
+/// Supported connector kinds.
+pub const KINDS: &[&str] = &[KIND_FILESYSTEM, KIND_IMAP];
+
+/// Schedule of connectors created without one: every 15 minutes.
+pub const DEFAULT_SCHEDULE: &str = "*/15 * * * *";
+
+/// Maximum connector name length in characters.
+pub const MAX_CONNECTOR_NAME_CHARS: usize = 200;
+
+/// `last_status` while a sync is in progress.
+pub const STATUS_SYNCING: &str = "syncing";
+/// `last_status` after a successful sync.
+pub const STATUS_SUCCEEDED: &str = "succeeded";
+/// `last_status` after a failed sync.
+pub const STATUS_FAILED: &str = "failed";
+
+/// Errors that can occur in connector operations.
+#[derive(Debug, Error)]
+pub enum ConnectorError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Not found: {0}")]
+    NotFound(Message),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Remote error: {0}")]
+    Remote(String),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+impl From<TaskError> for ConnectorError {
+    fn from(e: TaskError) -> Self {
+        match e {
+            TaskError::Validation(msg) | TaskError::NotFound(msg) => {
+                ConnectorError::Validation(msg)
+            }
+            TaskError::Db(e) => ConnectorError::Db(e),
+        }
+    }
+}
+
+/// Row returned by connector queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ConnectorRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: String,
+    pub name: String,
+    pub source: String,
+    /// Kind-specific options, e.g. [`imap::ImapSettings`].
+    pub settings: serde_json::Value,
+    /// Encrypted credential, e.g. an IMAP password.
+    pub secret: Option<String>,
+    pub schedule: String,
+    pub enabled: bool,
+    pub next_sync_at: Option<DateTime<Utc>>,
+    pub last_sync_at: Option<DateTime<Utc>>,
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    pub files_added: i32,
+    pub files_updated: i32,
+    pub files_deleted: i32,
+    pub file_count: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+const CONNECTOR_COLUMNS: &str = "id, user_id, kind, name, source, settings, secret, \
+     schedule, enabled, next_sync_at, last_sync_at, last_status, last_error, files_added, files_updated, \
+     files_deleted, file_count, created_at, updated_at";
+
+/// State of a source file at its last sync.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ConnectorItemRow {
+    pub connector_id: Uuid,
+    pub path: String,
+    /// `None` when the file was not ingested or its document was deleted.
+    pub document_id: Option<Uuid>,
+    pub size_bytes: i64,
+    pub modified_at: DateTime<Utc>,
+    pub sha256: String,
+}
+
+const ITEM_COLUMNS: &str = "connector_id, path, document_id, size_bytes, modified_at, sha256";
+
+/// A connector to create.
+#[derive(Debug, Clone)]
+pub struct NewConnector<'a> {
+    pub kind: &'a str,
+    pub name: &'a str,
+    pub source: &'a str,
+    pub settings: &'a serde_json::Value,
+    /// Already encrypted.
+    pub secret: Option<&'a str>,
+    pub schedule: &'a str,
+    pub enabled: bool,
+}
+
+/// Changes to a connector; `None` fields are left unchanged.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectorUpdate<'a> {
+    pub name: Option<&'a str>,
+    pub source: Option<&'a str>,
+    pub settings: Option<&'a serde_json::Value>,
+    /// Already encrypted.
+    pub secret: Option<&'a str>,
+    pub schedule: Option<&'a str>,
+    pub enabled: Option<bool>,
+}
+
+/// Counts recorded after a successful sync.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncStats {
+    pub added: i32,
+    pub updated: i32,
+    pub deleted: i32,
+    /// Files tracked after the sync.
+    pub file_count: i32,
+}
+
+/// What a sync has to do, from comparing a scan with the stored items.
+#[derive(Debug, Default)]
+pub struct SyncPlan<'a> {
+    pub added: Vec<&'a ScannedFile>,
+    /// Files whose size or modification time changed, with their state at
+    /// the last sync.
+    pub changed: Vec<(&'a ScannedFile, &'a ConnectorItemRow)>,
+    pub deleted: Vec<&'a ConnectorItemRow>,
+}
+
+/// Compare a scan of the source with the items stored at the last sync.
+pub fn plan_sync<'a>(items: &'a [ConnectorItemRow], scanned: &'a [ScannedFile]) -> SyncPlan<'a> {
+    let known: HashMap<&str, &ConnectorItemRow> = items
+        .iter()
+        .map(|item| (item.path.as_str(), item))
+        .collect();
+    let mut plan = SyncPlan::default();
+    for file in scanned {
+        match known.get(file.path.as_str()) {
+            None => plan.added.push(file),
+            Some(item) if item.size_bytes != file.size || item.modified_at != file.modified_at => {
+                plan.changed.push((file, item))
+            }
+            Some(_) => {}
+        }
+    }
+    let present: HashSet<&str> = scanned.iter().map(|file| file.path.as_str()).collect();
+    plan.deleted = items
+        .iter()
+        .filter(|item| !present.contains(item.path.as_str()))
+        .collect();
+    plan
+}
+
+/// The directories folder connectors may sync from (`ingest.connectors.roots`).
+pub async fn allowed_roots(
+    pool: &PgPool,
+    cache: &Arc<RwLock<ConfigCache>>,
+) -> Result<Vec<PathBuf>, ConnectorError> {
+    let value = resolver::get_system_value(pool, cache, ROOTS_CONFIG_KEY)
+        .await
+        .map_err(|e| {
+            ConnectorError::Validation(Message::new("connectors.roots_unreadable").arg("error", e))
+        })?;
+    Ok(fs::parse_roots(&value))
+}
+
+/// Trim and validate a connector name.
+fn validate_name(name: &str) -> Result<&str, ConnectorError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ConnectorError::Validation(Message::new(
+            "connectors.name_required",
+        )));
+    }
+    if name.chars().count() > MAX_CONNECTOR_NAME_CHARS {
+        return Err(ConnectorError::Validation(
+            Message::new("connectors.name_too_long").arg("max", MAX_CONNECTOR_NAME_CHARS),
+        ));
+    }
+    Ok(name)
+}
+
+fn validate_kind(kind: &str) -> Result<&str, ConnectorError> {
+    KINDS.iter().find(|k| **k == kind).copied().ok_or_else(|| {
+        ConnectorError::Validation(Message::new("connectors.unknown_kind").arg("kind", kind))
+    })
+}
+
+/// Validate a source for `kind`, returning it in the form it is stored.
+fn validate_source(kind: &str, source: &str, roots: &[PathBuf]) -> Result<String, ConnectorError> {
+    match kind {
+        KIND_FILESYSTEM => Ok(fs::check_source(source, roots)?
+            .to_string_lossy()
+            .into_owned()),
+        KIND_IMAP => Ok(imap::ImapSource::parse(source)?.to_url()),
+        other => Err(ConnectorError::Validation(
+            Message::new("connectors.unknown_kind").arg("kind", other),
+        )),
+    }
+}
+
+/// Validate the settings of a `kind` connector, returning them in the form
+/// they are stored.
+fn validate_settings(
+    kind: &str,
+    settings: &serde_json::Value,
+) -> Result<serde_json::Value, ConnectorError> {
+    match kind {
+        KIND_IMAP => Ok(imap::ImapSettings::from_value(settings)?.to_value()),
+        _ if settings.is_null() || settings.as_object().is_some_and(|o| o.is_empty()) => {
+            Ok(serde_json::json!({}))
+        }
+        _ => Err(ConnectorError::Validation(
+            Message::new("connectors.takes_no_settings").arg("kind", kind),
+        )),
+    }
+}
+
+/// Whether `kind` connectors need a secret to sync.
+fn requires_secret(kind: &str) -> bool {
+    kind == KIND_IMAP
+}
+
+/// Next sync time for an (enabled) schedule, counted from now.
+fn next_sync(schedule_expr: &str, enabled: bool) -> Result<Option<DateTime<Utc>>, ConnectorError> {
+    if !enabled {
+        return Ok(None);
+    }
+    Ok(Some(schedule::next_run_after(schedule_expr, Utc::now())?))
+}
+
+/// List a user's connectors, by name.
+pub async fn list_connectors(
+    pool: &PgPool,
+    user_id: &Uuid,
+) -> Result<Vec<ConnectorRow>, ConnectorError> {
+    let rows = sqlx::query_as::<_, ConnectorRow>(&format!(
+        "SELECT {CONNECTOR_COLUMNS} FROM connectors WHERE user_id = $1 ORDER BY name, created_at"
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Get a connector by ID (scoped to user).
+pub async fn get_connector(
+    pool: &PgPool,
+    user_id: &Uuid,
+    connector_id: &Uuid,
+) -> Result<ConnectorRow, ConnectorError> {
+    sqlx::query_as::<_, ConnectorRow>(&format!(
+        "SELECT {CONNECTOR_COLUMNS} FROM connectors WHERE id = $1 AND user_id = $2"
+    ))
+    .bind(connector_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| ConnectorError::NotFound(Message::new("connectors.not_found")))
+}
+
+/// Create a connector. Its source must lie under one of `roots`; the first
+/// sync is due right away.
+pub async fn create_connector(
+    pool: &PgPool,
+    user_id: &Uuid,
+    new: &NewConnector<'_>,
+    roots: &[PathBuf],
+) -> Result<ConnectorRow, ConnectorError> {
+    let kind = validate_kind(new.kind)?;
+    let name = validate_name(new.name)?;
+    let source = validate_source(kind, new.source, roots)?;
+    let settings = validate_settings(kind, new.settings)?;
+    if requires_secret(kind) && new.secret.is_none() {
+        return Err(ConnectorError::Validation(
+            Message::new("connectors.secret_required").arg("kind", kind),
+        ));
+    }
+    let schedule_expr = new.schedule.trim();
+    schedule::parse_schedule(schedule_expr)?;
+    let next_sync_at = new.enabled.then(Utc::now);
+
+    let row = sqlx::query_as::<_, ConnectorRow>(&format!(
+        r#"
+        INSERT INTO connectors
+            (id, user_id, kind, name, source, settings, secret, schedule, enabled, next_sync_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING {CONNECTOR_COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(user_id)
+    .bind(kind)
+    .bind(name)
+    .bind(source)
+    .bind(settings)
+    .bind(new.secret)
+    .bind(schedule_expr)
+    .bind(new.enabled)
+    .bind(next_sync_at)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Update a connector. A new source must lie under one of `roots`; the next
+/// sync is recomputed from the resulting schedule and enabled flag.
+pub async fn update_connector(
+    pool: &PgPool,
+    user_id: &Uuid,
+    connector_id: &Uuid,
+    update: &ConnectorUpdate<'_>,
+    roots: &[PathBuf],
+) -> Result<ConnectorRow, ConnectorError> {
+    let current = get_connector(pool, user_id, connector_id).await?;
+
+    let name = update.name.map(validate_name).transpose()?;
+    let source = update
+        .source
+        .map(|source| validate_source(&current.kind, source, roots))
+        .transpose()?;
+    let settings = update
+        .settings
+        .map(|settings| validate_settings(&current.kind, settings))
+        .transpose()?;
+    let schedule_expr = update.schedule.map(str::trim).unwrap_or(&current.schedule);
+    schedule::parse_schedule(schedule_expr)?;
+    let enabled = update.enabled.unwrap_or(current.enabled);
+    let next_sync_at = next_sync(schedule_expr, enabled)?;
+
+    let row = sqlx::query_as::<_, ConnectorRow>(&format!(
+        r#"
+        UPDATE connectors
+        SET name = COALESCE($1, name),
+            source = COALESCE($2, source),
+            settings = COALESCE($3, settings),
+            secret = COALESCE($4, secret),
+            schedule = $5,
+            enabled = $6,
+            next_sync_at = $7,
+            updated_at = now()
+        WHERE id = $8 AND user_id = $9
+        RETURNING {CONNECTOR_COLUMNS}
+        "#
+    ))
+    .bind(name)
+    .bind(source)
+    .bind(settings)
+    .bind(update.secret)
+    .bind(schedule_expr)
+    .bind(enabled)
+    .bind(next_sync_at)
+    .bind(connector_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| ConnectorError::NotFound(Message::new("connectors.not_found")))?;
+    Ok(row)
+}
+
+/// Delete a connector. The documents it ingested are kept.
+pub async fn delete_connector(
+    pool: &PgPool,
+    user_id: &Uuid,
+    connector_id: &Uuid,
+) -> Result<bool, ConnectorError> {
+    let result = sqlx::query("DELETE FROM connectors WHERE id = $1 AND user_id = $2")
+        .bind(connector_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Make a connector due immediately (it is picked up on the scheduler's
+/// next tick).
+pub async fn trigger_sync(
+    pool: &PgPool,
+    user_id: &Uuid,
+    connector_id: &Uuid,
+) -> Result<ConnectorRow, ConnectorError> {
+    sqlx::query_as::<_, ConnectorRow>(&format!(
+        r#"
+        UPDATE connectors
+        SET next_sync_at = now(), updated_at = now()
+        WHERE id = $1 AND user_id = $2
+        RETURNING {CONNECTOR_COLUMNS}
+        "#
+    ))
+    .bind(connector_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| ConnectorError::NotFound(Message::new("connectors.not_found")))
+}
+
+/// Claim up to `limit` enabled connectors due at `now`: each is marked
+/// syncing and its next sync scheduled, so concurrent schedulers never
+/// claim the same sync twice. Connectors whose schedule no longer parses
+/// are disabled.
+pub async fn claim_due_connectors(
+    pool: &PgPool,
+    now: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<ConnectorRow>, ConnectorError> {
+    let mut tx = pool.begin().await?;
+
+    let due = sqlx::query_as::<_, ConnectorRow>(&format!(
+        r#"
+        SELECT {CONNECTOR_COLUMNS}
+        FROM connectors
+        WHERE enabled AND next_sync_at <= $1
+        ORDER BY next_sync_at
+        LIMIT $2
+        FOR UPDATE SKIP LOCKED
+        "#
+    ))
+    .bind(now)
+    .bind(limit)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut claimed = Vec::with_capacity(due.len());
+    for connector in due {
+        match schedule::next_run_after(&connector.schedule, now) {
+            Ok(next_sync_at) => {
+                let row = sqlx::query_as::<_, ConnectorRow>(&format!(
+                    r#"
+                    UPDATE connectors
+                    SET next_sync_at = $1, last_sync_at = $2, last_status = $3, last_error = NULL
+                    WHERE id = $4
+                    RETURNING {CONNECTOR_COLUMNS}
+                    "#
+                ))
+                .bind(next_sync_at)
+                .bind(now)
+                .bind(STATUS_SYNCING)
+                .bind(connector.id)
+                .fetch_one(&mut *tx)
+                .await?;
+                claimed.push(row);
+            }
+            Err(e) => {
+                sqlx::query(
+                    r#"
+                    UPDATE connectors
+                    SET enabled = false, next_sync_at = NULL, last_status = $1, last_error = $2
+                    WHERE id = $3
+                    "#,
+                )
+                .bind(STATUS_FAILED)
+                .bind(e.to_string())
+                .bind(connector.id)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+    }
+
+    tx.commit().await?;
+    Ok(claimed)
+}
+
+/// Record the outcome of a sync: its counts on success, the error message
+/// on failure.
+pub async fn finish_sync(
+    pool: &PgPool,
+    connector_id: &Uuid,
+    outcome: Result<&SyncStats, &str>,
+) -> Result<(), ConnectorError> {
+    match outcome {
+        Ok(stats) => {
+            sqlx::query(
+                r#"
+                UPDATE connectors
+                SET last_status = $1, last_error = NULL, files_added = $2,
+                    files_updated = $3, files_deleted = $4, file_count = $5
+                WHERE id = $6
+                "#,
+            )
+            .bind(STATUS_SUCCEEDED)
+            .bind(stats.added)
+            .bind(stats.updated)
+            .bind(stats.deleted)
+            .bind(stats.file_count)
+            .bind(connector_id)
+            .execute(pool)
+            .await?;
+        }
+        Err(error) => {
+            sqlx::query("UPDATE connectors SET last_status = $1, last_error = $2 WHERE id = $3")
+                .bind(STATUS_FAILED)
+                .bind(error)
+                .bind(connector_id)
+                .execute(pool)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// The source files a connector tracks, by path.
+pub async fn list_items(
+    pool: &PgPool,
+    connector_id: &Uuid,
+) -> Result<Vec<ConnectorItemRow>, ConnectorError> {
+    let rows = sqlx::query_as::<_, ConnectorItemRow>(&format!(
+        "SELECT {ITEM_COLUMNS} FROM connector_items WHERE connector_id = $1 ORDER BY path"
+    ))
+    .bind(connector_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Record the state of a source file after syncing it, and the document it
+/// was ingested as (if any).
+pub async fn upsert_item(
+    pool: &PgPool,
+    connector_id: &Uuid,
+    file: &ScannedFile,
+    sha256: &str,
+    document_id: Option<&Uuid>,
+) -> Result<(), ConnectorError> {
+    sqlx::query(
+        r#"
+        INSERT INTO connector_items
+            (connector_id, path, document_id, size_bytes, modified_at, sha256)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (connector_id, path) DO UPDATE SET
+            document_id = EXCLUDED.document_id,
+            size_bytes = EXCLUDED.size_bytes,
+            modified_at = EXCLUDED.modified_at,
+            sha256 = EXCLUDED.sha256,
+            synced_at = now()
+        "#,
+    )
+    .bind(connector_id)
+    .bind(&file.path)
+    .bind(document_id)
+    .bind(file.size)
+    .bind(file.modified_at)
+    .bind(sha256)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Forget a source file that was removed.
+pub async fn delete_item(
+    pool: &PgPool,
+    connector_id: &Uuid,
+    path: &str,
+) -> Result<(), ConnectorError> {
+    sqlx::query("DELETE FROM connector_items WHERE connector_id = $1 AND path = $2")
+        .bind(connector_id)
+        .bind(path)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// A connector's position in part of its source (e.g. an IMAP folder).
+pub async fn get_cursor(
+    pool: &PgPool,
+    connector_id: &Uuid,
+    scope: &str,
+) -> Result<Option<String>, ConnectorError> {
+    let cursor = sqlx::query_scalar::<_, String>(
+        "SELECT cursor FROM connector_cursors WHERE connector_id = $1 AND scope = $2",
+    )
+    .bind(connector_id)
+    .bind(scope)
+    .fetch_optional(pool)
+    .await?;
+    Ok(cursor)
+}
+
+/// Record a connector's position in part of its source.
+pub async fn set_cursor(
+    pool: &PgPool,
+    connector_id: &Uuid,
+    scope: &str,
+    cursor: &str,
+) -> Result<(), ConnectorError> {
+    sqlx::query(
+        r#"
+        INSERT INTO connector_cursors (connector_id, scope, cursor)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (connector_id, scope) DO UPDATE SET
+            cursor = EXCLUDED.cursor,
+            updated_at = now()
+        "#,
+    )
+    .bind(connector_id)
+    .bind(scope)
+    .bind(cursor)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Forget the positions in parts of the source a connector no longer syncs.
+pub async fn retain_cursors(
+    pool: &PgPool,
+    connector_id: &Uuid,
+    scopes: &[String],
+) -> Result<(), ConnectorError> {
+    sqlx::query("DELETE FROM connector_cursors WHERE connector_id = $1 AND scope <> ALL($2)")
+        .bind(connector_id)
+        .bind(scopes)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, size: i64, modified_at: DateTime<Utc>) -> ScannedFile {
+        ScannedFile {
+            path: path.into(),
+            size,
+            modified_at,
+        }
+    }
+
+    fn item(path: &str, size: i64, modified_at: DateTime<Utc>) -> ConnectorItemRow {
+        ConnectorItemRow {
+            connector_id: Uuid::nil(),
+            path: path.into(),
+            document_id: None,
+            size_bytes: size,
+            modified_at,
+            sha256: String::new(),
+        }
+    }
+
+    #[test]
+    fn plan_sync_finds_added_changed_and_deleted_files() {
+        let then = Utc::now() - chrono::Duration::hours(1);
+        let now = Utc::now();
+        let items = vec![
+            item("same.md", 10, then),
+            item("grown.md", 10, then),
+            item("touched.md", 10, then),
+            item("gone.md", 10, then),
+        ];
+        let scanned = vec![
+            file("same.md", 10, then),
+            file("grown.md", 12, then),
+            file("touched.md", 10, now),
+            file("new.md", 5, now),
+        ];
+        let plan = plan_sync(&items, &scanned);
+        let added: Vec<&str> = plan.added.iter().map(|f| f.path.as_str()).collect();
+        let changed: Vec<&str> = plan.changed.iter().map(|(f, _)| f.path.as_str()).collect();
+        let deleted: Vec<&str> = plan.deleted.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(added, ["new.md"]);
+        assert_eq!(changed, ["grown.md", "touched.md"]);
+        assert_eq!(deleted, ["gone.md"]);
+    }
+
+    #[test]
+    fn validate_name_and_kind() {
+        assert_eq!(validate_name("  Notes ").unwrap(), "Notes");
+        assert!(validate_name(" ").is_err());
+        assert!(validate_name(&"x".repeat(MAX_CONNECTOR_NAME_CHARS + 1)).is_err());
+        assert_eq!(validate_kind("filesystem").unwrap(), KIND_FILESYSTEM);
+        assert!(validate_kind("dropbox").is_err());
+    }
+
+    #[test]
+    fn settings_are_validated_per_kind() {
+        assert_eq!(
+            validate_settings(KIND_FILESYSTEM, &serde_json::Value::Null).unwrap(),
+            serde_json::json!({})
+        );
+        assert!(validate_settings(KIND_FILESYSTEM, &serde_json::json!({ "x": 1 })).is_err());
+        let imap = validate_settings(KIND_IMAP, &serde_json::json!({ "username": "me" })).unwrap();
+        assert_eq!(imap["folders"], serde_json::json!(["INBOX"]));
+        assert!(validate_settings(KIND_IMAP, &serde_json::json!({})).is_err());
+    }
+}
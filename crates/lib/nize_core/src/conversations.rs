@@ -1,22 +1,75 @@
 //! Conversation and message persistence.
+//!
+//! Conversations are read and written within a [`Scope`]: the user's
+//! personal conversations, or those of the active workspace.
 
 use chrono::{DateTime, Utc};
-use sqlx::PgPool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
+use crate::permissions::{self, ResourceType};
+use crate::tags::{self, TagResourceType};
 use crate::uuid::uuidv7;
+use crate::workspaces::Scope;
 
 /// Row returned by conversation queries.
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct ConversationRow {
     pub id: Uuid,
     pub user_id: Uuid,
+    pub workspace_id: Option<Uuid>,
     pub title: String,
+    /// Pinned [`ToolSelection`]; `None` uses the user's MCP preferences.
+    pub tool_selection: Option<serde_json::Value>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-/// Row returned by message queries.
+impl ConversationRow {
+    /// The pinned tool selection, if any.
+    pub fn tool_selection(&self) -> Option<ToolSelection> {
+        self.tool_selection
+            .as_ref()
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+    }
+}
+
+/// MCP servers and tool domains pinned for a conversation. Tool discovery
+/// and execution in the conversation are restricted to tools on the listed
+/// servers or in the listed domains (on top of the user's own access);
+/// an empty selection leaves no tools.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ToolSelection {
+    pub server_ids: Vec<Uuid>,
+    pub domains: Vec<String>,
+}
+
+impl ToolSelection {
+    /// Trim, drop empty and duplicate entries.
+    pub fn normalized(mut self) -> Self {
+        self.server_ids.sort();
+        self.server_ids.dedup();
+        let mut domains: Vec<String> = self
+            .domains
+            .iter()
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty())
+            .collect();
+        domains.sort();
+        domains.dedup();
+        self.domains = domains;
+        self
+    }
+
+    /// Whether a tool on `server_id` in `domain` is in the selection.
+    pub fn allows(&self, server_id: &Uuid, domain: &str) -> bool {
+        self.server_ids.contains(server_id) || self.domains.iter().any(|d| d == domain)
+    }
+}
+
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct MessageRow {
     pub id: Uuid,
@@ -26,29 +79,70 @@ pub struct MessageRow {
     pub created_at: DateTime<Utc>,
 }
 
-/// List conversations for a user, ordered by most recently updated first.
+/// Rolling summary of a conversation's older messages.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RollingSummaryRow {
+    pub summary: String,
+    /// Number of leading messages the summary covers.
+    pub message_count: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub(crate) const CONVERSATION_COLUMNS: &str =
+    "c.id, c.user_id, c.workspace_id, c.title, c.tool_selection, c.created_at, c.updated_at";
+
+/// SQL condition restricting `conversations c` to a [`Scope`] bound as
+/// `$1` (user) and `$2` (workspace): the active workspace's conversations,
+/// or the user's personal ones when no workspace is active.
+const SCOPE_FILTER: &str = r#"
+    c.workspace_id IS NOT DISTINCT FROM $2::uuid AND ($2::uuid IS NOT NULL OR c.user_id = $1)
+"#;
+
+/// SQL condition restricting `conversations c` to conversations tagged with
+/// the name bound at parameter `$3` (or all when it is NULL).
+const TAG_FILTER: &str = r#"
+    ($3::text IS NULL OR EXISTS (
+        SELECT 1
+        FROM tag_assignments a
+        JOIN tags t ON t.id = a.tag_id
+        WHERE a.resource_type = 'conversation' AND a.resource_id = c.id AND t.name = $3
+    ))
+"#;
+
+/// SQL condition (parameter `$3`) allowing changes to conversations the
+/// user created, or to all in scope when `$3` is true (workspace admins).
+const MANAGE_FILTER: &str = "($3::bool OR c.user_id = $1)";
+
+/// List conversations in scope, ordered by most recently updated first,
+/// optionally filtered to conversations carrying `tag`.
 pub async fn list_conversations(
     pool: &PgPool,
-    user_id: &Uuid,
+    scope: &Scope,
+    tag: Option<&str>,
     limit: i64,
     offset: i64,
 ) -> Result<(Vec<ConversationRow>, i64), sqlx::Error> {
-    let total =
-        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM conversations WHERE user_id = $1")
-            .bind(user_id)
-            .fetch_one(pool)
-            .await?;
+    let total = sqlx::query_scalar::<_, i64>(&format!(
+        "SELECT COUNT(*) FROM conversations c WHERE {SCOPE_FILTER} AND {TAG_FILTER}"
+    ))
+    .bind(scope.user_id)
+    .bind(scope.workspace_id())
+    .bind(tag)
+    .fetch_one(pool)
+    .await?;
 
-    let rows = sqlx::query_as::<_, ConversationRow>(
+    let rows = sqlx::query_as::<_, ConversationRow>(&format!(
         r#"
-        SELECT id, user_id, title, created_at, updated_at
-        FROM conversations
-        WHERE user_id = $1
-        ORDER BY updated_at DESC
-        LIMIT $2 OFFSET $3
-        "#,
-    )
-    .bind(user_id)
+        SELECT {CONVERSATION_COLUMNS}
+        FROM conversations c
+        WHERE {SCOPE_FILTER} AND {TAG_FILTER}
+        ORDER BY c.updated_at DESC
+        LIMIT $4 OFFSET $5
+        "#
+    ))
+    .bind(scope.user_id)
+    .bind(scope.workspace_id())
+    .bind(tag)
     .bind(limit)
     .bind(offset)
     .fetch_all(pool)
@@ -57,79 +151,180 @@ pub async fn list_conversations(
     Ok((rows, total))
 }
 
-/// Create a new conversation.
+/// Create a new conversation in scope.
 pub async fn create_conversation(
     pool: &PgPool,
-    user_id: &Uuid,
+    scope: &Scope,
     title: &str,
 ) -> Result<ConversationRow, sqlx::Error> {
     sqlx::query_as::<_, ConversationRow>(
         r#"
-        INSERT INTO conversations (id, user_id, title)
-        VALUES ($1, $2, $3)
-        RETURNING id, user_id, title, created_at, updated_at
+        INSERT INTO conversations (id, user_id, workspace_id, title)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, user_id, workspace_id, title, tool_selection, created_at, updated_at
         "#,
     )
     .bind(uuidv7())
-    .bind(user_id)
+    .bind(scope.user_id)
+    .bind(scope.workspace_id())
     .bind(title)
     .fetch_one(pool)
     .await
 }
 
-/// Get a conversation by ID (scoped to user).
+/// Get a conversation by ID (scoped).
 pub async fn get_conversation(
     pool: &PgPool,
-    user_id: &Uuid,
+    scope: &Scope,
     conversation_id: &Uuid,
 ) -> Result<ConversationRow, sqlx::Error> {
-    sqlx::query_as::<_, ConversationRow>(
+    sqlx::query_as::<_, ConversationRow>(&format!(
         r#"
-        SELECT id, user_id, title, created_at, updated_at
-        FROM conversations
-        WHERE id = $1 AND user_id = $2
-        "#,
-    )
+        SELECT {CONVERSATION_COLUMNS}
+        FROM conversations c
+        WHERE c.id = $3 AND {SCOPE_FILTER}
+        "#
+    ))
+    .bind(scope.user_id)
+    .bind(scope.workspace_id())
     .bind(conversation_id)
-    .bind(user_id)
     .fetch_one(pool)
     .await
 }
 
-/// Update a conversation title.
+/// Get a conversation regardless of scope, for callers that checked access
+/// another way (grants and share links, see [`crate::permissions`]).
+pub async fn get_conversation_by_id(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+) -> Result<ConversationRow, sqlx::Error> {
+    sqlx::query_as::<_, ConversationRow>(&format!(
+        "SELECT {CONVERSATION_COLUMNS} FROM conversations c WHERE c.id = $1"
+    ))
+    .bind(conversation_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Whether the scope's workspace role lets it manage others' conversations.
+fn manages_all(scope: &Scope) -> bool {
+    scope.workspace.is_some_and(|m| m.role.can_manage())
+}
+
+/// Update a conversation title (scoped; see [`Scope::can_manage`]).
 pub async fn update_conversation(
     pool: &PgPool,
-    user_id: &Uuid,
+    scope: &Scope,
     conversation_id: &Uuid,
     title: &str,
 ) -> Result<ConversationRow, sqlx::Error> {
-    sqlx::query_as::<_, ConversationRow>(
+    sqlx::query_as::<_, ConversationRow>(&format!(
         r#"
-        UPDATE conversations
-        SET title = $1, updated_at = now()
-        WHERE id = $2 AND user_id = $3
-        RETURNING id, user_id, title, created_at, updated_at
-        "#,
-    )
+        UPDATE conversations c
+        SET title = $4, updated_at = now()
+        WHERE c.id = $5 AND {SCOPE_FILTER} AND {MANAGE_FILTER}
+        RETURNING {CONVERSATION_COLUMNS}
+        "#
+    ))
+    .bind(scope.user_id)
+    .bind(scope.workspace_id())
+    .bind(manages_all(scope))
     .bind(title)
     .bind(conversation_id)
-    .bind(user_id)
     .fetch_one(pool)
     .await
 }
 
-/// Delete a conversation (messages cascade).
-pub async fn delete_conversation(
+/// Pin the conversation's tool selection, or clear it with `None`
+/// (scoped; see [`Scope::can_manage`]).
+pub async fn set_tool_selection(
+    pool: &PgPool,
+    scope: &Scope,
+    conversation_id: &Uuid,
+    selection: Option<&ToolSelection>,
+) -> Result<ConversationRow, sqlx::Error> {
+    let selection = selection.map(|s| serde_json::to_value(s).unwrap_or_default());
+    sqlx::query_as::<_, ConversationRow>(&format!(
+        r#"
+        UPDATE conversations c
+        SET tool_selection = $4, updated_at = now()
+        WHERE c.id = $5 AND {SCOPE_FILTER} AND {MANAGE_FILTER}
+        RETURNING {CONVERSATION_COLUMNS}
+        "#
+    ))
+    .bind(scope.user_id)
+    .bind(scope.workspace_id())
+    .bind(manages_all(scope))
+    .bind(selection)
+    .bind(conversation_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// What an MCP session needs of its conversation.
+#[derive(Debug, Clone, Default)]
+pub struct SessionConversation {
+    pub workspace_id: Option<Uuid>,
+    /// Pinned tool selection; `None` uses the user's MCP preferences.
+    pub tool_selection: Option<ToolSelection>,
+}
+
+/// Workspace and tool selection of a conversation the user can read — their
+/// own, or one in a workspace they are a member of — for callers without an
+/// active [`Scope`] (the MCP server). `RowNotFound` when the conversation is
+/// not visible to the user.
+pub async fn session_conversation_for_user(
     pool: &PgPool,
     user_id: &Uuid,
     conversation_id: &Uuid,
-) -> Result<bool, sqlx::Error> {
-    let result = sqlx::query("DELETE FROM conversations WHERE id = $1 AND user_id = $2")
-        .bind(conversation_id)
+) -> Result<SessionConversation, sqlx::Error> {
+    let (workspace_id, selection) =
+        sqlx::query_as::<_, (Option<Uuid>, Option<serde_json::Value>)>(
+            r#"
+        SELECT c.workspace_id, c.tool_selection
+        FROM conversations c
+        WHERE c.id = $2
+          AND (
+            (c.workspace_id IS NULL AND c.user_id = $1)
+            OR c.workspace_id IN (SELECT m.workspace_id FROM workspace_members m WHERE m.user_id = $1)
+          )
+        "#,
+        )
         .bind(user_id)
-        .execute(pool)
+        .bind(conversation_id)
+        .fetch_one(pool)
         .await?;
+    Ok(SessionConversation {
+        workspace_id,
+        tool_selection: selection.and_then(|v| serde_json::from_value(v).ok()),
+    })
+}
 
+/// Delete a conversation (scoped; see [`Scope::can_manage`]). Messages
+/// cascade; tag assignments are removed explicitly.
+pub async fn delete_conversation(
+    pool: &PgPool,
+    scope: &Scope,
+    conversation_id: &Uuid,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(&format!(
+        "DELETE FROM conversations c WHERE c.id = $4 AND {SCOPE_FILTER} AND {MANAGE_FILTER}"
+    ))
+    .bind(scope.user_id)
+    .bind(scope.workspace_id())
+    .bind(manages_all(scope))
+    .bind(conversation_id)
+    .execute(&mut *tx)
+    .await?;
+    if result.rows_affected() > 0 {
+        tags::clear_resource_tags(&mut tx, TagResourceType::Conversation, conversation_id).await?;
+        permissions::clear_resource_shares(&mut tx, ResourceType::Conversation, conversation_id)
+            .await?;
+    }
+
+    tx.commit().await?;
     Ok(result.rows_affected() > 0)
 }
 
@@ -158,11 +353,22 @@ pub async fn save_messages(
     messages: &[serde_json::Value],
 ) -> Result<(), sqlx::Error> {
     let mut tx = pool.begin().await?;
+    write_messages(&mut tx, conversation_id, messages).await?;
+    tx.commit().await?;
+    Ok(())
+}
 
+/// Replace a conversation's messages on `conn`, within the caller's
+/// transaction.
+pub(crate) async fn write_messages(
+    conn: &mut PgConnection,
+    conversation_id: &Uuid,
+    messages: &[serde_json::Value],
+) -> Result<(), sqlx::Error> {
     // Delete existing messages
     sqlx::query("DELETE FROM messages WHERE conversation_id = $1")
         .bind(conversation_id)
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await?;
 
     // Insert new messages with sort_order
@@ -177,16 +383,91 @@ pub async fn save_messages(
         .bind(conversation_id)
         .bind(i as i32)
         .bind(msg)
-        .execute(&mut *tx)
+        .execute(&mut *conn)
         .await?;
     }
 
-    // Touch conversation updated_at
-    sqlx::query("UPDATE conversations SET updated_at = now() WHERE id = $1")
-        .bind(conversation_id)
-        .execute(&mut *tx)
-        .await?;
+    // Touch conversation updated_at; drop the summary if the history was
+    // rewritten to fewer messages than it covers.
+    sqlx::query(
+        r#"
+        UPDATE conversations
+        SET updated_at = now(),
+            summary = CASE WHEN summary_message_count > $2 THEN NULL ELSE summary END,
+            summary_updated_at = CASE WHEN summary_message_count > $2 THEN NULL ELSE summary_updated_at END,
+            summary_message_count = CASE WHEN summary_message_count > $2 THEN 0 ELSE summary_message_count END
+        WHERE id = $1
+        "#,
+    )
+    .bind(conversation_id)
+    .bind(messages.len() as i32)
+    .execute(&mut *conn)
+    .await?;
 
-    tx.commit().await?;
     Ok(())
 }
+
+/// Get the rolling summary of a conversation, if one has been stored.
+pub async fn get_summary(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+) -> Result<Option<RollingSummaryRow>, sqlx::Error> {
+    sqlx::query_as::<_, RollingSummaryRow>(
+        r#"
+        SELECT summary, summary_message_count AS message_count, summary_updated_at AS updated_at
+        FROM conversations
+        WHERE id = $1 AND summary IS NOT NULL
+        "#,
+    )
+    .bind(conversation_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Store the rolling summary of a conversation's first `message_count`
+/// messages (scoped).
+pub async fn set_summary(
+    pool: &PgPool,
+    scope: &Scope,
+    conversation_id: &Uuid,
+    summary: &str,
+    message_count: i32,
+) -> Result<RollingSummaryRow, sqlx::Error> {
+    sqlx::query_as::<_, RollingSummaryRow>(&format!(
+        r#"
+        UPDATE conversations c
+        SET summary = $3, summary_message_count = $4, summary_updated_at = now()
+        WHERE c.id = $5 AND {SCOPE_FILTER}
+        RETURNING c.summary, c.summary_message_count AS message_count, c.summary_updated_at AS updated_at
+        "#
+    ))
+    .bind(scope.user_id)
+    .bind(scope.workspace_id())
+    .bind(summary)
+    .bind(message_count)
+    .bind(conversation_id)
+    .fetch_one(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tool_selection_allows_pinned_servers_and_domains() {
+        let server = Uuid::from_u128(1);
+        let selection = ToolSelection {
+            server_ids: vec![server, server],
+            domains: vec![" files ".into(), String::new()],
+        }
+        .normalized();
+        assert_eq!(selection.server_ids, vec![server]);
+        assert_eq!(selection.domains, vec!["files".to_string()]);
+
+        assert!(selection.allows(&server, "web"));
+        assert!(selection.allows(&Uuid::from_u128(2), "files"));
+        assert!(!selection.allows(&Uuid::from_u128(2), "web"));
+        assert!(!ToolSelection::default().allows(&server, "files"));
+    }
+}
@@ -6,10 +6,11 @@
 //! reachable PostgreSQL instance (local or remote).
 
 use std::net::TcpListener;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use sqlx::postgres::PgPool;
+use sqlx::ConnectOptions;
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
 use thiserror::Error;
 use tokio::process::Command;
 use tokio::time::sleep;
@@ -23,6 +24,9 @@ const PG_READY_TIMEOUT: Duration = Duration::from_secs(30);
 /// Poll interval when waiting for PostgreSQL readiness.
 const PG_READY_POLL: Duration = Duration::from_millis(200);
 
+/// Maximum time an encrypted PGlite server gets to seal its snapshot on stop.
+const PGLITE_SEAL_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// Errors that can occur during database operations.
 #[derive(Debug, Error)]
 pub enum DbError {
@@ -353,6 +357,13 @@ impl LocalDbManager {
 ///
 /// Spawns `bun pglite-server.mjs` and exposes the standard PG wire protocol on
 /// `localhost:<port>`. The `nize_desktop_server` connects via `sqlx::PgPool` unchanged.
+///
+/// With an encryption key ([`with_encryption_key`](Self::with_encryption_key))
+/// the data directory is replaced by an AES-256-GCM sealed snapshot next to
+/// it (`pglite-data.sealed`): the server decrypts it into memory on start and
+/// re-seals it periodically and on stop. The key is passed over stdin only.
+/// [`seal_data_dir`](Self::seal_data_dir) and
+/// [`unseal_data_dir`](Self::unseal_data_dir) convert between the two forms.
 pub struct PgLiteManager {
     /// Path to the PGlite data directory.
     data_dir: PathBuf,
@@ -364,6 +375,10 @@ pub struct PgLiteManager {
     child_pid: Option<u32>,
     /// Whether the server has been started.
     started: bool,
+    /// Hex AES-256 key for the sealed snapshot, when encrypted at rest.
+    encryption_key: Option<String>,
+    /// Encrypted mode keeps the child (and its stdin) for a graceful stop.
+    process: Option<std::process::Child>,
 }
 
 impl PgLiteManager {
@@ -376,9 +391,39 @@ impl PgLiteManager {
             database_name: database_name.to_string(),
             child_pid: None,
             started: false,
+            encryption_key: None,
+            process: None,
         }
     }
 
+    /// Encrypts the database at rest with `key` (64 hex characters, see
+    /// [`generate_encryption_key`]).
+    pub fn with_encryption_key(mut self, key: String) -> Self {
+        self.encryption_key = Some(key);
+        self
+    }
+
+    /// Returns the data directory.
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    /// Returns whether the database is encrypted at rest.
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption_key.is_some()
+    }
+
+    /// Path of the sealed snapshot: the data directory with a `.sealed`
+    /// extension.
+    pub fn sealed_path(&self) -> PathBuf {
+        self.data_dir.with_extension("sealed")
+    }
+
+    /// Returns whether a plaintext data directory exists.
+    pub fn has_plaintext_data(&self) -> bool {
+        self.data_dir.join("PG_VERSION").exists()
+    }
+
     /// Creates a new `PgLiteManager` using the platform-appropriate application data directory.
     ///
     /// Uses `nize/pglite-data` (separate from native PG's `nize/pgdata`).
@@ -408,11 +453,15 @@ impl PgLiteManager {
             self.data_dir.display()
         );
 
-        let mut child = StdCommand::new(bun_bin)
-            .arg(server_script)
+        let mut cmd = StdCommand::new(bun_bin);
+        cmd.arg(server_script)
             .arg(format!("--db={}", self.data_dir.display()))
             .arg(format!("--port={port}"))
-            .arg(format!("--database={}", self.database_name))
+            .arg(format!("--database={}", self.database_name));
+        if self.encryption_key.is_some() {
+            cmd.arg(format!("--sealed={}", self.sealed_path().display()));
+        }
+        let mut child = cmd
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
@@ -420,6 +469,9 @@ impl PgLiteManager {
             .map_err(|e| DbError::Command(format!("spawn pglite-server: {e}")))?;
 
         let pid = child.id();
+        if let (Some(key), Some(stdin)) = (&self.encryption_key, child.stdin.as_mut()) {
+            std::io::Write::write_all(stdin, format!("{key}\n").as_bytes())?;
+        }
 
         // Read the first line of stdout for {"port": N}.
         let stdout = child
@@ -443,6 +495,9 @@ impl PgLiteManager {
         self.port = ready.port;
         self.child_pid = Some(pid);
         self.started = true;
+        if self.encryption_key.is_some() {
+            self.process = Some(child);
+        }
 
         log::info!("PGlite server ready on port {} (pid: {})", self.port, pid);
 
@@ -456,6 +511,28 @@ impl PgLiteManager {
             return Ok(());
         }
 
+        if let Some(mut child) = self.process.take() {
+            // Ask the server to seal its snapshot before exiting.
+            log::info!(
+                "Sealing and stopping PGlite server (pid: {})...",
+                child.id()
+            );
+            if let Some(stdin) = child.stdin.as_mut() {
+                let _ = std::io::Write::write_all(stdin, b"shutdown\n");
+            }
+            let deadline = std::time::Instant::now() + PGLITE_SEAL_TIMEOUT;
+            while child.try_wait()?.is_none() {
+                if std::time::Instant::now() >= deadline {
+                    log::warn!("PGlite server did not exit after sealing; killing it");
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break;
+                }
+                std::thread::sleep(PG_READY_POLL);
+            }
+            self.child_pid = None;
+        }
+
         if let Some(pid) = self.child_pid.take() {
             log::info!("Stopping PGlite server (pid: {pid})...");
             #[cfg(unix)]
@@ -517,6 +594,95 @@ impl PgLiteManager {
             }
         })
     }
+
+    /// Encrypts the plaintext data directory into the sealed snapshot with
+    /// `key` and deletes the directory. Used when encryption at rest is
+    /// turned on for an existing database.
+    pub fn seal_data_dir(
+        &self,
+        key: &str,
+        bun_bin: &std::path::Path,
+        server_script: &std::path::Path,
+    ) -> Result<()> {
+        self.convert(key, bun_bin, server_script, "--seal")?;
+        std::fs::remove_dir_all(&self.data_dir)?;
+        log::info!("Sealed PGlite data into {}", self.sealed_path().display());
+        Ok(())
+    }
+
+    /// Decrypts the sealed snapshot with `key` back into a plaintext data
+    /// directory and deletes the snapshot. Used when encryption at rest is
+    /// turned off.
+    pub fn unseal_data_dir(
+        &self,
+        key: &str,
+        bun_bin: &std::path::Path,
+        server_script: &std::path::Path,
+    ) -> Result<()> {
+        if self.has_plaintext_data() {
+            return Err(DbError::Command(format!(
+                "refusing to unseal over existing data in {}",
+                self.data_dir.display()
+            )));
+        }
+        self.convert(key, bun_bin, server_script, "--unseal")?;
+        std::fs::remove_file(self.sealed_path())?;
+        log::info!("Unsealed PGlite data into {}", self.data_dir.display());
+        Ok(())
+    }
+
+    /// Run `pglite-server.mjs` in a one-shot `--seal` or `--unseal` mode.
+    fn convert(
+        &self,
+        key: &str,
+        bun_bin: &std::path::Path,
+        server_script: &std::path::Path,
+        mode: &str,
+    ) -> Result<()> {
+        use std::process::{Command as StdCommand, Stdio};
+
+        let mut child = StdCommand::new(bun_bin)
+            .arg(server_script)
+            .arg(format!("--db={}", self.data_dir.display()))
+            .arg(format!("--sealed={}", self.sealed_path().display()))
+            .arg(mode)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| DbError::Command(format!("spawn pglite-server {mode}: {e}")))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            std::io::Write::write_all(&mut stdin, format!("{key}\n").as_bytes())?;
+        }
+
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(DbError::Command(format!(
+                "pglite-server {mode} exited with {status}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Removes the `postmaster.pid` lock file a crashed PGlite server leaves
+    /// in its data directory, which would otherwise make the next start fail.
+    ///
+    /// Only call this when no other PGlite server can be using the directory.
+    /// Returns whether a stale lock was removed.
+    pub fn reclaim_stale_lock(&self) -> Result<bool> {
+        if self.started {
+            return Ok(false);
+        }
+        let lock = self.data_dir.join("postmaster.pid");
+        match std::fs::remove_file(&lock) {
+            Ok(()) => {
+                log::warn!("Removed stale PGlite lock {}", lock.display());
+                Ok(true)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
 }
 
 /// Controls how provisioning errors are handled.
@@ -635,6 +801,63 @@ fn find_free_port() -> Result<u16> {
     Ok(port)
 }
 
+/// Limits applied to every pooled connection.
+///
+/// `statement_timeout` makes PostgreSQL cancel statements that run longer,
+/// so one runaway query cannot hold a connection — on PGlite, the only
+/// connection — indefinitely. Statements slower than
+/// `slow_query_threshold` are logged by sqlx at `WARN` on the
+/// `sqlx::query` target, inside the span of the request that ran them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryLimits {
+    /// `None` keeps the server's own setting.
+    pub statement_timeout: Option<Duration>,
+    pub slow_query_threshold: Duration,
+}
+
+impl Default for QueryLimits {
+    fn default() -> Self {
+        Self {
+            statement_timeout: None,
+            slow_query_threshold: Duration::from_millis(500),
+        }
+    }
+}
+
+impl QueryLimits {
+    /// Connect options for `url` with slow statement logging.
+    pub fn connect_options(&self, url: &str) -> Result<PgConnectOptions> {
+        let options: PgConnectOptions = url.parse()?;
+        Ok(options.log_slow_statements(log::LevelFilter::Warn, self.slow_query_threshold))
+    }
+
+    /// Pool options that set `statement_timeout` on each new connection.
+    pub fn pool_options(&self, options: PgPoolOptions) -> PgPoolOptions {
+        let Some(timeout) = self.statement_timeout else {
+            return options;
+        };
+        let set = statement_timeout_sql(timeout);
+        options.after_connect(move |conn, _meta| {
+            let set = set.clone();
+            Box::pin(async move {
+                sqlx::query(&set).execute(conn).await?;
+                Ok(())
+            })
+        })
+    }
+
+    /// Connect a pool with these limits.
+    pub async fn connect(&self, url: &str, options: PgPoolOptions) -> Result<PgPool> {
+        let connect = self.connect_options(url)?;
+        Ok(self.pool_options(options).connect_with(connect).await?)
+    }
+}
+
+/// `SET` statement for a statement timeout; zero disables the timeout.
+fn statement_timeout_sql(timeout: Duration) -> String {
+    format!("SET statement_timeout = {}", timeout.as_millis())
+}
+
 /// Returns the default data directory for the PostgreSQL instance.
 ///
 /// Platform paths:
@@ -655,6 +878,15 @@ pub fn default_pglite_data_dir() -> Option<PathBuf> {
     dirs::data_dir().map(|d| d.join("nize").join("pglite-data"))
 }
 
+/// Generates a random 256-bit PGlite encryption key as 64 hex characters.
+pub fn generate_encryption_key() -> String {
+    use rand::RngCore;
+
+    let mut key = [0u8; 32];
+    rand::rng().fill_bytes(&mut key);
+    key.iter().map(|b| format!("{b:02x}")).collect()
+}
+
 /// Wraps a string in single quotes for shell safety if it contains spaces or
 /// special characters. Single quotes within the value are escaped.
 // @awa-impl: PLAN-006-3.4
@@ -674,6 +906,23 @@ mod tests {
     use super::*;
     use sqlx::Row;
 
+    #[test]
+    fn statement_timeout_is_set_in_milliseconds() {
+        assert_eq!(
+            statement_timeout_sql(Duration::from_secs(30)),
+            "SET statement_timeout = 30000"
+        );
+    }
+
+    #[test]
+    fn connect_options_keep_the_url() {
+        let options = QueryLimits::default()
+            .connect_options("postgres://nize@db.example:5433/app")
+            .unwrap();
+        assert_eq!(options.get_host(), "db.example");
+        assert_eq!(options.get_port(), 5433);
+    }
+
     #[test]
     fn default_data_dir_is_some() {
         let dir = default_data_dir();
@@ -682,6 +931,36 @@ mod tests {
         assert!(dir.ends_with("nize/pgdata") || dir.ends_with("nize\\pgdata"));
     }
 
+    #[test]
+    fn pglite_reclaims_stale_lock() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mgr = PgLiteManager::new(dir.path().to_path_buf(), DEFAULT_DATABASE);
+        assert!(!mgr.reclaim_stale_lock().unwrap());
+
+        std::fs::write(dir.path().join("postmaster.pid"), "-42\n").unwrap();
+        assert!(mgr.reclaim_stale_lock().unwrap());
+        assert!(!dir.path().join("postmaster.pid").exists());
+    }
+
+    #[test]
+    fn encryption_key_is_256_bit_hex() {
+        let key = generate_encryption_key();
+        assert_eq!(key.len(), 64);
+        assert!(key.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(key, generate_encryption_key());
+    }
+
+    #[test]
+    fn sealed_snapshot_sits_next_to_data_dir() {
+        let mgr = PgLiteManager::new(PathBuf::from("/data/nize/pglite-data"), DEFAULT_DATABASE)
+            .with_encryption_key(generate_encryption_key());
+        assert!(mgr.is_encrypted());
+        assert_eq!(
+            mgr.sealed_path(),
+            PathBuf::from("/data/nize/pglite-data.sealed")
+        );
+    }
+
     #[tokio::test]
     async fn ephemeral_manager_has_zero_port() {
         let mgr = LocalDbManager::ephemeral()
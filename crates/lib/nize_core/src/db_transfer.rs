@@ -0,0 +1,331 @@
+//! Copy a database into an external PostgreSQL server.
+//!
+//! The way off the bundled PGlite database: [`transfer`] migrates the
+//! target to the current schema, then copies every table of the source in
+//! one consistent snapshot, batch by batch through JSON so it works over
+//! any Postgres wire connection (PGlite has no `pg_dump`). The target is
+//! filled in one transaction with foreign key triggers off, so a failed copy
+//! leaves nothing behind, and its sequences are moved up to the source's.
+//! Afterwards each table's row count and a checksum over its rows are
+//! compared on both sides; only a [`TransferReport::verified`] copy should
+//! be switched to.
+//!
+//! The source is only read, so switching back to it stays possible — it
+//! just lacks whatever was written to the target since.
+//!
+//! The target must have no users yet. On a single-connection source
+//! (PGlite) the snapshot holds the only connection, so other requests wait
+//! until the copy is done.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgConnection, PgPool};
+use thiserror::Error;
+
+use crate::migrate;
+
+/// Rows fetched and inserted at a time.
+pub const BATCH_ROWS: usize = 500;
+
+/// Errors that can occur while transferring a database.
+#[derive(Debug, Error)]
+pub enum TransferError {
+    #[error("Invalid target: {0}")]
+    InvalidTarget(String),
+
+    #[error("Target database already holds data: {0}")]
+    TargetNotEmpty(String),
+
+    #[error("Source database is not fully migrated ({0} migrations pending)")]
+    SourceOutdated(usize),
+
+    #[error("Migrating the target failed: {0}")]
+    Migrate(String),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// Transfer options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferRequest {
+    /// `postgres://` URL of the target database.
+    pub target_url: String,
+}
+
+/// What was copied of one table and whether the copy matches.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TableTransfer {
+    pub table: String,
+    pub source_rows: i64,
+    pub target_rows: i64,
+    pub source_checksum: String,
+    pub target_checksum: String,
+    pub verified: bool,
+}
+
+/// Result of a transfer.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferReport {
+    pub tables: Vec<TableTransfer>,
+    /// Rows copied across all tables.
+    pub rows: i64,
+    /// Sequences moved up to the source's value.
+    pub sequences: usize,
+    /// Every table's row count and checksum match.
+    pub verified: bool,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+/// Check `url` names a PostgreSQL database.
+pub fn validate_target_url(url: &str) -> Result<(), TransferError> {
+    let url = url.trim();
+    let rest = url
+        .strip_prefix("postgres://")
+        .or_else(|| url.strip_prefix("postgresql://"))
+        .ok_or_else(|| {
+            TransferError::InvalidTarget("URL must start with postgres:// or postgresql://".into())
+        })?;
+    if rest.is_empty() || rest.starts_with('/') {
+        return Err(TransferError::InvalidTarget("URL names no host".into()));
+    }
+    Ok(())
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Statement inserting a JSON array of rows (`$1`) into `table`.
+fn insert_sql(table: &str, columns: &[String]) -> String {
+    let columns = columns
+        .iter()
+        .map(|c| quote_ident(c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let table = quote_ident(table);
+    format!(
+        "INSERT INTO {table} ({columns}) OVERRIDING SYSTEM VALUE \
+         SELECT {columns} FROM json_populate_recordset(NULL::{table}, $1::json)"
+    )
+}
+
+/// Settings that fix the text form of rows, so checksums compare.
+const STABLE_TEXT: [&str; 4] = [
+    "SET LOCAL TIME ZONE 'UTC'",
+    "SET LOCAL DateStyle = 'ISO, YMD'",
+    "SET LOCAL extra_float_digits = 1",
+    "SET LOCAL bytea_output = 'hex'",
+];
+
+/// Apply [`STABLE_TEXT`] to the current transaction.
+async fn stable_text(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    for statement in STABLE_TEXT {
+        sqlx::query(statement).execute(&mut *conn).await?;
+    }
+    Ok(())
+}
+
+/// Row count and checksum of `table`, independent of row order.
+async fn digest(conn: &mut PgConnection, table: &str) -> Result<(i64, String), sqlx::Error> {
+    sqlx::query_as(&format!(
+        "SELECT count(*), coalesce(md5(string_agg(h, '' ORDER BY h)), '') \
+         FROM (SELECT md5(t::text) AS h FROM {} t) rows",
+        quote_ident(table)
+    ))
+    .fetch_one(&mut *conn)
+    .await
+}
+
+/// Application tables, without the migration bookkeeping.
+async fn tables(conn: &mut PgConnection) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT table_name::text FROM information_schema.tables \
+         WHERE table_schema = 'public' AND table_type = 'BASE TABLE' \
+           AND table_name <> '_sqlx_migrations' \
+         ORDER BY 1",
+    )
+    .fetch_all(&mut *conn)
+    .await
+}
+
+/// Columns of `table` that take values, in order.
+async fn insertable_columns(
+    conn: &mut PgConnection,
+    table: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT column_name::text FROM information_schema.columns \
+         WHERE table_schema = 'public' AND table_name = $1 AND is_generated = 'NEVER' \
+         ORDER BY ordinal_position",
+    )
+    .bind(table)
+    .fetch_all(&mut *conn)
+    .await
+}
+
+/// Copy all of `source` into the database at `target_url` and verify it.
+pub async fn transfer(source: &PgPool, target_url: &str) -> Result<TransferReport, TransferError> {
+    let started_at = Utc::now();
+    validate_target_url(target_url)?;
+
+    let pending = migrate::status(source).await?.pending;
+    if pending > 0 {
+        return Err(TransferError::SourceOutdated(pending));
+    }
+
+    let target = PgPoolOptions::new()
+        .max_connections(2)
+        .acquire_timeout(Duration::from_secs(10))
+        .connect(target_url.trim())
+        .await
+        .map_err(|e| TransferError::InvalidTarget(format!("cannot connect: {e}")))?;
+    migrate::migrate(&target)
+        .await
+        .map_err(|e| TransferError::Migrate(e.to_string()))?;
+    let has_users: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users)")
+        .fetch_one(&target)
+        .await?;
+    if has_users {
+        return Err(TransferError::TargetNotEmpty(
+            "it has users; transfer into a new, empty database".into(),
+        ));
+    }
+
+    let mut src = source.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *src)
+        .await?;
+    stable_text(&mut src).await?;
+    let names = tables(&mut src).await?;
+
+    let mut dst = target.begin().await?;
+    sqlx::query("SET LOCAL session_replication_role = replica")
+        .execute(&mut *dst)
+        .await
+        .map_err(|e| {
+            TransferError::InvalidTarget(format!(
+                "cannot turn off foreign key checks ({e}); connect as the database owner \
+                 or a superuser"
+            ))
+        })?;
+    let target_tables = tables(&mut dst).await?;
+    if let Some(missing) = names.iter().find(|t| !target_tables.contains(t)) {
+        return Err(TransferError::Migrate(format!(
+            "table {missing} is missing on the target"
+        )));
+    }
+    let all = names
+        .iter()
+        .map(|t| quote_ident(t))
+        .collect::<Vec<_>>()
+        .join(", ");
+    sqlx::query(&format!("TRUNCATE {all}"))
+        .execute(&mut *dst)
+        .await?;
+
+    let mut rows = 0;
+    let mut source_digests = Vec::with_capacity(names.len());
+    for table in &names {
+        let insert = insert_sql(table, &insertable_columns(&mut dst, table).await?);
+        sqlx::query(&format!(
+            "DECLARE transfer_rows NO SCROLL CURSOR FOR SELECT row_to_json(t)::text FROM {} t",
+            quote_ident(table)
+        ))
+        .execute(&mut *src)
+        .await?;
+        loop {
+            let batch: Vec<String> =
+                sqlx::query_scalar(&format!("FETCH {BATCH_ROWS} FROM transfer_rows"))
+                    .fetch_all(&mut *src)
+                    .await?;
+            if batch.is_empty() {
+                break;
+            }
+            rows += sqlx::query(&insert)
+                .bind(format!("[{}]", batch.join(",")))
+                .execute(&mut *dst)
+                .await?
+                .rows_affected() as i64;
+        }
+        sqlx::query("CLOSE transfer_rows")
+            .execute(&mut *src)
+            .await?;
+        source_digests.push(digest(&mut src, table).await?);
+    }
+
+    let sequences: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT format('%I', sequencename), last_value FROM pg_sequences \
+         WHERE schemaname = 'public' AND last_value IS NOT NULL",
+    )
+    .fetch_all(&mut *src)
+    .await?;
+    for (sequence, value) in &sequences {
+        sqlx::query("SELECT setval($1::regclass, $2, true)")
+            .bind(sequence)
+            .bind(value)
+            .execute(&mut *dst)
+            .await?;
+    }
+
+    dst.commit().await?;
+    src.rollback().await?;
+
+    let mut check = target.begin().await?;
+    stable_text(&mut check).await?;
+    let mut report = Vec::with_capacity(names.len());
+    for (table, (source_rows, source_checksum)) in names.into_iter().zip(source_digests) {
+        let (target_rows, target_checksum) = digest(&mut check, &table).await?;
+        report.push(TableTransfer {
+            verified: source_rows == target_rows && source_checksum == target_checksum,
+            table,
+            source_rows,
+            target_rows,
+            source_checksum,
+            target_checksum,
+        });
+    }
+    check.rollback().await?;
+    target.close().await;
+
+    Ok(TransferReport {
+        verified: report.iter().all(|t| t.verified),
+        tables: report,
+        rows,
+        sequences: sequences.len(),
+        started_at,
+        finished_at: Utc::now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_must_be_a_postgres_url() {
+        assert!(validate_target_url("postgres://nize:pw@db.example.com:5432/nize").is_ok());
+        assert!(validate_target_url(" postgresql://localhost/nize ").is_ok());
+        assert!(validate_target_url("mysql://localhost/nize").is_err());
+        assert!(validate_target_url("postgres://").is_err());
+        assert!(validate_target_url("postgres:///nize").is_err());
+    }
+
+    #[test]
+    fn insert_quotes_identifiers() {
+        let sql = insert_sql("users", &["id".into(), "we\"ird".into()]);
+        assert_eq!(
+            sql,
+            "INSERT INTO \"users\" (\"id\", \"we\"\"ird\") OVERRIDING SYSTEM VALUE \
+             SELECT \"id\", \"we\"\"ird\" FROM json_populate_recordset(NULL::\"users\", $1::json)"
+        );
+    }
+}
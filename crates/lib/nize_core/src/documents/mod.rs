@@ -0,0 +1,295 @@
+//! Ingested documents — uploaded files whose bytes live in blob storage
+//! (see [`crate::blobs`]). A document row holds the metadata and the
+//! content hash of its blob; its extracted text is stored as chunks
+//! (see [`crate::ingest`]) in the shared [`crate::chunks`] store, embedded by
+//! [`crate::embedding::indexer::embed_document`] so [`search`] can
+//! retrieve passages as chat context.
+//!
+//! Like conversations, documents are personal or belong to the workspace
+//! active when they were uploaded (see [`Scope`]).
+
+pub mod search;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::blobs::{self, BlobBackend, StoredBlob};
+use crate::chunks;
+use crate::ingest::ExtractedChunk;
+use crate::uuid::uuidv7;
+use crate::workspaces::Scope;
+
+/// Row returned by document queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DocumentRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub workspace_id: Option<Uuid>,
+    pub filename: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub sha256: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub(crate) const DOCUMENT_COLUMNS: &str = "id, user_id, workspace_id, filename, mime_type, \
+     size_bytes, sha256, created_at, updated_at";
+
+/// SQL condition restricting `documents d` to a [`Scope`] whose user and
+/// workspace are bound at `user` and `workspace` (e.g. `"$1"`, `"$2"`):
+/// the active workspace's documents, or the user's personal ones when no
+/// workspace is active.
+pub(crate) fn scope_filter(user: &str, workspace: &str) -> String {
+    format!(
+        "d.workspace_id IS NOT DISTINCT FROM {workspace}::uuid \
+         AND ({workspace}::uuid IS NOT NULL OR d.user_id = {user})"
+    )
+}
+
+/// List the documents in scope, newest first.
+pub async fn list_documents(
+    pool: &PgPool,
+    scope: &Scope,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<DocumentRow>, i64), sqlx::Error> {
+    let filter = scope_filter("$1", "$2");
+    let total =
+        sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM documents d WHERE {filter}"))
+            .bind(scope.user_id)
+            .bind(scope.workspace_id())
+            .fetch_one(pool)
+            .await?;
+
+    let rows = sqlx::query_as::<_, DocumentRow>(&format!(
+        r#"
+        SELECT {DOCUMENT_COLUMNS}
+        FROM documents d
+        WHERE {filter}
+        ORDER BY created_at DESC
+        LIMIT $3 OFFSET $4
+        "#
+    ))
+    .bind(scope.user_id)
+    .bind(scope.workspace_id())
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((rows, total))
+}
+
+/// Create a document in scope for a blob just stored in `backend`,
+/// recording the blob in the same transaction.
+pub async fn create_document(
+    pool: &PgPool,
+    scope: &Scope,
+    filename: &str,
+    mime_type: &str,
+    blob: &StoredBlob,
+    backend: BlobBackend,
+) -> Result<DocumentRow, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    blobs::record_blob(&mut tx, blob, backend).await?;
+    let row = sqlx::query_as::<_, DocumentRow>(&format!(
+        r#"
+        INSERT INTO documents (id, user_id, workspace_id, filename, mime_type, size_bytes, sha256)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING {DOCUMENT_COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(scope.user_id)
+    .bind(scope.workspace_id())
+    .bind(filename)
+    .bind(mime_type)
+    .bind(blob.size)
+    .bind(&blob.sha256)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Point a document at new content just stored in `backend`, recording
+/// the blob in the same transaction. Returns the previous content hash so
+/// the caller can release its blob.
+pub async fn replace_blob(
+    pool: &PgPool,
+    document_id: &Uuid,
+    mime_type: &str,
+    blob: &StoredBlob,
+    backend: BlobBackend,
+) -> Result<(DocumentRow, String), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    blobs::record_blob(&mut tx, blob, backend).await?;
+    let previous =
+        sqlx::query_scalar::<_, String>("SELECT sha256 FROM documents WHERE id = $1 FOR UPDATE")
+            .bind(document_id)
+            .fetch_one(&mut *tx)
+            .await?;
+    let row = sqlx::query_as::<_, DocumentRow>(&format!(
+        r#"
+        UPDATE documents
+        SET mime_type = $1, size_bytes = $2, sha256 = $3, updated_at = now()
+        WHERE id = $4
+        RETURNING {DOCUMENT_COLUMNS}
+        "#
+    ))
+    .bind(mime_type)
+    .bind(blob.size)
+    .bind(&blob.sha256)
+    .bind(document_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok((row, previous))
+}
+
+/// Get a document by ID (scoped).
+pub async fn get_document(
+    pool: &PgPool,
+    scope: &Scope,
+    document_id: &Uuid,
+) -> Result<DocumentRow, sqlx::Error> {
+    sqlx::query_as::<_, DocumentRow>(&format!(
+        "SELECT {DOCUMENT_COLUMNS} FROM documents d WHERE d.id = $3 AND {}",
+        scope_filter("$1", "$2")
+    ))
+    .bind(scope.user_id)
+    .bind(scope.workspace_id())
+    .bind(document_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Delete a document (scoped; see [`Scope::can_manage`]). Returns the
+/// deleted row so the caller can release its blob.
+pub async fn delete_document(
+    pool: &PgPool,
+    scope: &Scope,
+    document_id: &Uuid,
+) -> Result<Option<DocumentRow>, sqlx::Error> {
+    sqlx::query_as::<_, DocumentRow>(&format!(
+        "DELETE FROM documents d WHERE d.id = $4 AND {} AND ($3::bool OR d.user_id = $1) \
+         RETURNING {DOCUMENT_COLUMNS}",
+        scope_filter("$1", "$2")
+    ))
+    .bind(scope.user_id)
+    .bind(scope.workspace_id())
+    .bind(scope.workspace.is_some_and(|m| m.role.can_manage()))
+    .bind(document_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Delete a document regardless of scope, for cleanup after a failed
+/// upload. Returns whether it existed.
+pub async fn remove_document(pool: &PgPool, document_id: &Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM documents WHERE id = $1")
+        .bind(document_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get a document by ID regardless of owner, for background indexing.
+pub async fn find_document(
+    pool: &PgPool,
+    document_id: &Uuid,
+) -> Result<Option<DocumentRow>, sqlx::Error> {
+    sqlx::query_as::<_, DocumentRow>(&format!(
+        "SELECT {DOCUMENT_COLUMNS} FROM documents WHERE id = $1"
+    ))
+    .bind(document_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// A stored chunk of a document's extracted text.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DocumentChunkRow {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub chunk_index: i32,
+    /// Key of the chunk's content in [`crate::chunks`].
+    pub chunk_hash: String,
+    pub content: String,
+    pub page: Option<i32>,
+    pub ocr_confidence: Option<f32>,
+    pub start_ms: Option<i64>,
+    pub end_ms: Option<i64>,
+}
+
+/// Chunk columns for a query over `document_chunks c JOIN chunks k`.
+const CHUNK_COLUMNS: &str = "c.id, c.document_id, c.chunk_index, c.chunk_hash, k.content, \
+     c.page, c.ocr_confidence, c.start_ms, c.end_ms";
+
+/// Replace a document's chunks. Chunk contents already in the store (from
+/// this or any other document or note) are reused with their embeddings;
+/// contents no longer used anywhere are released.
+pub async fn replace_chunks(
+    pool: &PgPool,
+    document_id: &Uuid,
+    extracted: &[ExtractedChunk],
+) -> Result<Vec<DocumentChunkRow>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let contents: Vec<&str> = extracted.iter().map(|c| c.content.as_str()).collect();
+    let hashes = chunks::put_chunks(&mut tx, &contents).await?;
+    sqlx::query("DELETE FROM document_chunks WHERE document_id = $1")
+        .bind(document_id)
+        .execute(&mut *tx)
+        .await?;
+    let mut rows = Vec::with_capacity(extracted.len());
+    for (index, (chunk, hash)) in extracted.iter().zip(hashes).enumerate() {
+        let row = DocumentChunkRow {
+            id: uuidv7(),
+            document_id: *document_id,
+            chunk_index: index as i32,
+            chunk_hash: hash,
+            content: chunk.content.clone(),
+            page: chunk.page,
+            ocr_confidence: chunk.ocr_confidence,
+            start_ms: chunk.start_ms,
+            end_ms: chunk.end_ms,
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO document_chunks
+                (id, document_id, chunk_index, chunk_hash, page, ocr_confidence, start_ms, end_ms)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(row.id)
+        .bind(row.document_id)
+        .bind(row.chunk_index)
+        .bind(&row.chunk_hash)
+        .bind(row.page)
+        .bind(row.ocr_confidence)
+        .bind(row.start_ms)
+        .bind(row.end_ms)
+        .execute(&mut *tx)
+        .await?;
+        rows.push(row);
+    }
+    chunks::release_unreferenced(&mut tx).await?;
+    tx.commit().await?;
+    Ok(rows)
+}
+
+/// A document's chunks, in order.
+pub async fn list_chunks(
+    pool: &PgPool,
+    document_id: &Uuid,
+) -> Result<Vec<DocumentChunkRow>, sqlx::Error> {
+    sqlx::query_as::<_, DocumentChunkRow>(&format!(
+        "SELECT {CHUNK_COLUMNS} FROM document_chunks c JOIN chunks k ON k.hash = c.chunk_hash \
+         WHERE c.document_id = $1 ORDER BY c.chunk_index"
+    ))
+    .bind(document_id)
+    .fetch_all(pool)
+    .await
+}
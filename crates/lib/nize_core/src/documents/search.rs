@@ -0,0 +1,476 @@
+//! Semantic search over document chunks.
+//!
+//! Embeds the query with the active model and ranks the caller's document
+//! chunks — extracted text, OCR output and audio transcripts alike — by
+//! cosine similarity, optionally reranking the top candidates with a
+//! cross-encoder ([`rerank`]). [`format_context`] renders hits as a markdown block
+//! suitable for injecting into a chat prompt.
+//!
+//! [`search_documents`] covers the documents in the caller's [`Scope`]:
+//! their own, or the active workspace's. [`retrieve`] serves the chat
+//! pipeline: it also covers documents shared with the caller by grant or
+//! share link, filters by access inside the query, and reports why each
+//! chunk was allowed ([`RetrievedChunk::explanation`]).
+
+use std::sync::Arc;
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::config::cache::ConfigCache;
+use crate::embedding::EmbeddingError;
+use crate::embedding::config::EmbeddingConfig;
+use crate::embedding::models::EmbeddingModelConfig;
+use crate::embedding::rerank::{self, RerankStage};
+use crate::embedding::{self, ann, models};
+use crate::permissions::PermissionLevel;
+use crate::workspaces::Scope;
+
+/// Default number of chunks returned by [`search_documents`].
+pub const DEFAULT_TOP_K: i64 = 5;
+
+/// A document chunk matching a search query.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DocumentSearchHit {
+    pub document_id: Uuid,
+    pub chunk_id: Uuid,
+    pub filename: String,
+    pub content: String,
+    pub page: Option<i32>,
+    pub start_ms: Option<i64>,
+    pub end_ms: Option<i64>,
+    pub similarity: f64,
+    /// Cross-encoder relevance, when the hit was reranked.
+    #[sqlx(default)]
+    pub rerank_score: Option<f64>,
+    /// Subject of the email the document was ingested from, if any.
+    #[sqlx(default)]
+    pub email_subject: Option<String>,
+    #[sqlx(default)]
+    pub email_sender: Option<String>,
+    #[sqlx(default)]
+    pub email_sent_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Find the `top_k` document chunks in scope most similar to `query`,
+/// reranked when `embedding.rerank.documents.enabled` is on.
+pub async fn search_documents(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+    scope: &Scope,
+    query: &str,
+    top_k: i64,
+    min_similarity: f64,
+) -> Result<Vec<DocumentSearchHit>, EmbeddingError> {
+    let (model_config, embedding_sql) =
+        embed_query(pool, config_cache, encryption_key, query).await?;
+
+    let sql = format!(
+        r#"SELECT {HIT_COLUMNS},
+                  1 - ({distance}) AS similarity
+           FROM chunk_embeddings de
+           JOIN document_chunks c ON c.chunk_hash = de.chunk_hash
+           JOIN chunks k ON k.hash = de.chunk_hash
+           JOIN documents d ON d.id = c.document_id
+           LEFT JOIN email_messages m ON m.document_id = d.id
+           WHERE {model_filter}
+             AND {scope_filter}
+             AND 1 - ({distance}) >= $4
+           ORDER BY {distance}
+           LIMIT $3"#,
+        distance = model_config.distance_sql("de.embedding", "$1"),
+        model_filter = model_config.filter_sql("de"),
+        scope_filter = super::scope_filter("$2", "$5"),
+    );
+
+    let rerank_stage = RerankStage::configured(
+        pool,
+        config_cache,
+        encryption_key,
+        rerank::UseCase::Documents,
+    )
+    .await;
+    let limit = candidate_limit(&rerank_stage, top_k);
+
+    let search_params = ann::SearchParams::resolve(pool, config_cache).await;
+    let mut tx = pool.begin().await?;
+    search_params.apply(&mut tx).await?;
+    let hits = sqlx::query_as::<_, DocumentSearchHit>(&sql)
+        .bind(&embedding_sql)
+        .bind(scope.user_id)
+        .bind(limit)
+        .bind(min_similarity)
+        .bind(scope.workspace_id())
+        .fetch_all(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(match rerank_stage {
+        Some(stage) => {
+            stage
+                .rerank(
+                    query,
+                    hits,
+                    top_k.max(0) as usize,
+                    |hit| hit.content.clone(),
+                    |hit, score| hit.rerank_score = Some(score),
+                )
+                .await
+        }
+        None => hits,
+    })
+}
+
+/// Why the caller may read a retrieved chunk's document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessReason {
+    /// The caller owns the document.
+    Owner,
+    /// Another member uploaded the document to the active workspace.
+    Workspace,
+    /// The document is shared with the caller's account or email.
+    Grant,
+    /// The caller presented a share link to the document.
+    Link,
+}
+
+impl TryFrom<String> for AccessReason {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.as_str() {
+            "owner" => Ok(Self::Owner),
+            "workspace" => Ok(Self::Workspace),
+            "grant" => Ok(Self::Grant),
+            "link" => Ok(Self::Link),
+            other => Err(format!("Unknown access reason: {other}")),
+        }
+    }
+}
+
+/// A chunk returned by [`retrieve`], with why it was allowed.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RetrievedChunk {
+    #[sqlx(flatten)]
+    pub hit: DocumentSearchHit,
+    #[sqlx(try_from = "String")]
+    pub access: AccessReason,
+    /// Level the caller holds on the document: `Full` for its owner and
+    /// workspace admins.
+    pub access_level: PermissionLevel,
+    /// The grant or share link that allowed the chunk; `None` for owners
+    /// and workspace members.
+    pub access_source_id: Option<Uuid>,
+}
+
+impl RetrievedChunk {
+    /// One-line account of why the chunk was allowed.
+    pub fn explanation(&self) -> String {
+        let level = self.access_level.as_str();
+        match self.access {
+            AccessReason::Owner => "You own this document".to_string(),
+            AccessReason::Workspace => format!("In your active workspace with {level} access"),
+            AccessReason::Grant => format!("Shared with you with {level} access"),
+            AccessReason::Link => format!("Opened through a share link with {level} access"),
+        }
+    }
+}
+
+/// Documents `$2` may read in the scope whose workspace is `$6`, each
+/// once with its strongest reason: in scope (`Full` for the uploader, and
+/// for workspace admins when `$7`), granted to the caller's account or
+/// verified email, or shared by a valid link whose token is in `$5`.
+/// Grants count only without a workspace: every member reads a workspace
+/// conversation, so its context must not draw on what was shared with one
+/// of them. Every level allows reading.
+fn readable_documents_sql() -> String {
+    format!(
+        r#"
+    SELECT DISTINCT ON (document_id) document_id, access, level, source_id
+    FROM (
+        SELECT d.id AS document_id,
+               CASE WHEN d.user_id = $2 THEN 'owner' ELSE 'workspace' END AS access,
+               (CASE WHEN d.user_id = $2 OR $7::bool THEN 'full' ELSE 'view' END)::permission_level
+                   AS level,
+               NULL::uuid AS source_id, 0 AS rank
+        FROM documents d
+        WHERE {scope_filter}
+        UNION ALL
+        SELECT g.resource_id, 'grant', g.level, g.id, 1
+        FROM permission_grants g
+        JOIN users u ON u.id = $2
+        WHERE g.resource_type = 'document'
+          AND $6::uuid IS NULL
+          AND (g.grantee_id = $2
+               OR (lower(g.grantee_email) = lower(u.email) AND u.email_verified IS NOT NULL))
+        UNION ALL
+        SELECT l.resource_id, 'link', l.level, l.id, 2
+        FROM share_links l
+        WHERE l.resource_type = 'document'
+          AND l.token = ANY($5)
+          AND (l.expires_at IS NULL OR l.expires_at > now())
+    ) reasons
+    ORDER BY document_id, level DESC, rank"#,
+        scope_filter = super::scope_filter("$2", "$6"),
+    )
+}
+
+/// Find the `top_k` chunks most similar to `query` among the documents
+/// the caller may read in `scope` — in scope, shared with them, or shared
+/// by one of `link_tokens` — for use as chat context. Access is checked in the query
+/// itself, so unreadable chunks never count against `top_k`. Reranked like
+/// [`search_documents`].
+#[allow(clippy::too_many_arguments)]
+pub async fn retrieve(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+    scope: &Scope,
+    link_tokens: &[String],
+    query: &str,
+    top_k: i64,
+    min_similarity: f64,
+) -> Result<Vec<RetrievedChunk>, EmbeddingError> {
+    let (model_config, embedding_sql) =
+        embed_query(pool, config_cache, encryption_key, query).await?;
+
+    let sql = format!(
+        r#"WITH readable AS ({readable})
+           SELECT {HIT_COLUMNS},
+                  1 - ({distance}) AS similarity,
+                  r.access,
+                  r.level AS access_level,
+                  r.source_id AS access_source_id
+           FROM chunk_embeddings de
+           JOIN document_chunks c ON c.chunk_hash = de.chunk_hash
+           JOIN chunks k ON k.hash = de.chunk_hash
+           JOIN documents d ON d.id = c.document_id
+           JOIN readable r ON r.document_id = d.id
+           LEFT JOIN email_messages m ON m.document_id = d.id
+           WHERE {model_filter}
+             AND 1 - ({distance}) >= $4
+           ORDER BY {distance}
+           LIMIT $3"#,
+        distance = model_config.distance_sql("de.embedding", "$1"),
+        model_filter = model_config.filter_sql("de"),
+        readable = readable_documents_sql(),
+    );
+
+    let rerank_stage = RerankStage::configured(
+        pool,
+        config_cache,
+        encryption_key,
+        rerank::UseCase::Documents,
+    )
+    .await;
+    let limit = candidate_limit(&rerank_stage, top_k);
+
+    let search_params = ann::SearchParams::resolve(pool, config_cache).await;
+    let mut tx = pool.begin().await?;
+    search_params.apply(&mut tx).await?;
+    let chunks = sqlx::query_as::<_, RetrievedChunk>(&sql)
+        .bind(&embedding_sql)
+        .bind(scope.user_id)
+        .bind(limit)
+        .bind(min_similarity)
+        .bind(link_tokens)
+        .bind(scope.workspace_id())
+        .bind(scope.workspace.is_some_and(|m| m.role.can_manage()))
+        .fetch_all(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(match rerank_stage {
+        Some(stage) => {
+            stage
+                .rerank(
+                    query,
+                    chunks,
+                    top_k.max(0) as usize,
+                    |chunk| chunk.hit.content.clone(),
+                    |chunk, score| chunk.hit.rerank_score = Some(score),
+                )
+                .await
+        }
+        None => chunks,
+    })
+}
+
+/// Columns of a [`DocumentSearchHit`] other than its similarity.
+const HIT_COLUMNS: &str = "d.id AS document_id,
+                  c.id AS chunk_id,
+                  d.filename,
+                  k.content,
+                  c.page,
+                  c.start_ms,
+                  c.end_ms,
+                  m.subject AS email_subject,
+                  m.sender AS email_sender,
+                  m.sent_at AS email_sent_at";
+
+/// Embed `query` with the active model. Returns the model and the
+/// embedding formatted as an SQL vector literal: `'[0.1,0.2,...]'`.
+async fn embed_query(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+    query: &str,
+) -> Result<(EmbeddingModelConfig, String), EmbeddingError> {
+    let config = EmbeddingConfig::resolve(pool, config_cache, encryption_key).await?;
+    let model_config = models::get_active_model(pool, &config).await?;
+    let embedding = embedding::embed_query(&config, &model_config, query).await?;
+
+    let embedding_sql = format!(
+        "[{}]",
+        embedding
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    Ok((model_config, embedding_sql))
+}
+
+/// Rows to fetch for `top_k` results: more when a reranker picks from them.
+fn candidate_limit(rerank_stage: &Option<RerankStage>, top_k: i64) -> i64 {
+    match rerank_stage {
+        Some(stage) => stage.candidates(top_k.max(0) as usize) as i64,
+        None => top_k,
+    }
+}
+
+/// Where in the document a hit comes from: `p. 3`, `12:05–13:40`, or
+/// nothing for unpaged text.
+pub fn hit_location(hit: &DocumentSearchHit) -> Option<String> {
+    if let (Some(start), Some(end)) = (hit.start_ms, hit.end_ms) {
+        return Some(format!("{}–{}", timestamp(start), timestamp(end)));
+    }
+    hit.page.map(|page| format!("p. {page}"))
+}
+
+/// The email a hit comes from: `email from Ada <ada@example.com>,
+/// 2025-07-01`, or nothing for documents that are not email.
+pub fn hit_email(hit: &DocumentSearchHit) -> Option<String> {
+    hit.email_subject.as_ref()?;
+    let mut citation = String::from("email");
+    if let Some(sender) = hit.email_sender.as_deref().filter(|s| !s.is_empty()) {
+        citation.push_str(&format!(" from {sender}"));
+    }
+    if let Some(sent_at) = hit.email_sent_at {
+        citation.push_str(&format!(", {}", sent_at.format("%Y-%m-%d")));
+    }
+    Some(citation)
+}
+
+/// `m:ss`, or `h:mm:ss` past the hour.
+fn timestamp(ms: i64) -> String {
+    let secs = ms.max(0) / 1000;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+/// Render search hits as a markdown context block, one section per chunk.
+pub fn format_context(hits: &[DocumentSearchHit]) -> String {
+    hits.iter()
+        .map(|hit| {
+            let notes: Vec<String> = [hit_email(hit), hit_location(hit)]
+                .into_iter()
+                .flatten()
+                .collect();
+            let heading = if notes.is_empty() {
+                format!("### {}\n\n", hit.filename)
+            } else {
+                format!("### {} ({})\n\n", hit.filename, notes.join(", "))
+            };
+            heading + &hit.content
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n---\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(filename: &str, page: Option<i32>, span: Option<(i64, i64)>) -> DocumentSearchHit {
+        DocumentSearchHit {
+            document_id: Uuid::new_v4(),
+            chunk_id: Uuid::new_v4(),
+            filename: filename.to_string(),
+            content: "text".to_string(),
+            page,
+            start_ms: span.map(|s| s.0),
+            end_ms: span.map(|s| s.1),
+            similarity: 0.9,
+            rerank_score: None,
+            email_subject: None,
+            email_sender: None,
+            email_sent_at: None,
+        }
+    }
+
+    #[test]
+    fn format_context_names_pages_and_timestamps() {
+        let context = format_context(&[
+            hit("report.pdf", Some(3), None),
+            hit("standup.m4a", None, Some((725_000, 3_820_500))),
+            hit("notes.txt", None, None),
+        ]);
+        assert_eq!(
+            context,
+            "### report.pdf (p. 3)\n\ntext\n\n---\n\n\
+             ### standup.m4a (12:05–1:03:40)\n\ntext\n\n---\n\n\
+             ### notes.txt\n\ntext"
+        );
+    }
+
+    #[test]
+    fn format_context_cites_emails() {
+        let mut email = hit("Launch plan.txt", None, None);
+        email.email_subject = Some("Launch plan".into());
+        email.email_sender = Some("Ada <ada@example.com>".into());
+        email.email_sent_at = chrono::DateTime::parse_from_rfc3339("2025-07-01T10:00:00Z")
+            .ok()
+            .map(|t| t.to_utc());
+        assert_eq!(
+            format_context(&[email]),
+            "### Launch plan.txt (email from Ada <ada@example.com>, 2025-07-01)\n\ntext"
+        );
+    }
+
+    #[test]
+    fn retrieved_chunks_explain_their_access() {
+        let chunk = |access: &str, level| RetrievedChunk {
+            hit: hit("notes.txt", None, None),
+            access: AccessReason::try_from(access.to_string()).unwrap(),
+            access_level: level,
+            access_source_id: None,
+        };
+        assert_eq!(
+            chunk("owner", PermissionLevel::Full).explanation(),
+            "You own this document"
+        );
+        assert_eq!(
+            chunk("workspace", PermissionLevel::View).explanation(),
+            "In your active workspace with view access"
+        );
+        assert_eq!(
+            chunk("grant", PermissionLevel::Comment).explanation(),
+            "Shared with you with comment access"
+        );
+        assert_eq!(
+            chunk("link", PermissionLevel::View).explanation(),
+            "Opened through a share link with view access"
+        );
+        assert!(AccessReason::try_from("public".to_string()).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+//! Outgoing email.
+//!
+//! A [`Mailer`] sends plain-text [`Email`]s through the SMTP server set in
+//! `email.smtp.url`, from `email.from`. With no server configured — the
+//! desktop app, local development — messages are written to the log
+//! instead, so flows that send mail still work end to end.
+
+use std::sync::Arc;
+
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::config::cache::ConfigCache;
+use crate::config::queries;
+use crate::config::resolver;
+use crate::mcp::secrets;
+use crate::models::config::ConfigScope;
+
+/// Config key of the SMTP server URL (a secret).
+pub const SMTP_URL_CONFIG_KEY: &str = "email.smtp.url";
+
+/// Config key of the sender address.
+pub const FROM_CONFIG_KEY: &str = "email.from";
+
+/// Errors that can occur sending email.
+#[derive(Debug, Error)]
+pub enum EmailError {
+    #[error("Email misconfigured: {0}")]
+    Config(String),
+
+    #[error("Invalid address '{0}'")]
+    Address(String),
+
+    #[error("Failed to send email: {0}")]
+    Send(String),
+}
+
+/// A plain-text message to one recipient.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Where emails go.
+pub enum Mailer {
+    Smtp(Box<SmtpMailer>),
+    /// No mail server configured: log each message.
+    Log,
+}
+
+/// Sends through an SMTP server.
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl Mailer {
+    /// Build the mailer selected by `email.smtp.url`.
+    pub async fn configured(
+        pool: &PgPool,
+        cache: &Arc<RwLock<ConfigCache>>,
+        encryption_key: &str,
+    ) -> Result<Self, EmailError> {
+        let url = smtp_url(pool, encryption_key).await?;
+        if url.trim().is_empty() {
+            return Ok(Mailer::Log);
+        }
+        let from = resolver::get_system_value(pool, cache, FROM_CONFIG_KEY)
+            .await
+            .map_err(|e| EmailError::Config(e.to_string()))?;
+        Self::smtp(url.trim(), &from)
+    }
+
+    /// Mailer for the SMTP server at `url` (`smtp://`, `smtps://`, with
+    /// optional credentials), sending from `from`.
+    pub fn smtp(url: &str, from: &str) -> Result<Self, EmailError> {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(url)
+            .map_err(|e| EmailError::Config(format!("{SMTP_URL_CONFIG_KEY}: {e}")))?
+            .build();
+        Ok(Mailer::Smtp(Box::new(SmtpMailer {
+            transport,
+            from: parse_mailbox(from)?,
+        })))
+    }
+
+    /// Send `email`.
+    pub async fn send(&self, email: &Email) -> Result<(), EmailError> {
+        match self {
+            Mailer::Smtp(smtp) => {
+                let message = Message::builder()
+                    .from(smtp.from.clone())
+                    .to(parse_mailbox(&email.to)?)
+                    .subject(&email.subject)
+                    .header(ContentType::TEXT_PLAIN)
+                    .body(email.body.clone())
+                    .map_err(|e| EmailError::Send(e.to_string()))?;
+                smtp.transport
+                    .send(message)
+                    .await
+                    .map_err(|e| EmailError::Send(e.to_string()))?;
+                Ok(())
+            }
+            Mailer::Log => {
+                info!(
+                    to = %email.to,
+                    subject = %email.subject,
+                    "no mail server configured, email not sent:\n{}",
+                    email.body
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, EmailError> {
+    address
+        .trim()
+        .parse()
+        .map_err(|_| EmailError::Address(address.to_string()))
+}
+
+/// The decrypted `email.smtp.url`, empty when unset.
+async fn smtp_url(pool: &PgPool, encryption_key: &str) -> Result<String, EmailError> {
+    let Some(row) = queries::get_value(pool, SMTP_URL_CONFIG_KEY, &ConfigScope::System, None)
+        .await
+        .map_err(|e| EmailError::Config(e.to_string()))?
+    else {
+        return Ok(String::new());
+    };
+    if row.value.is_empty() {
+        return Ok(String::new());
+    }
+    secrets::decrypt(&row.value, encryption_key).map_err(|e| EmailError::Config(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smtp_mailer_validates_url_and_sender() {
+        assert!(Mailer::smtp("smtp://localhost:2525", "Nize <noreply@example.com>").is_ok());
+        assert!(matches!(
+            Mailer::smtp("smtp://localhost:2525", "not an address"),
+            Err(EmailError::Address(_))
+        ));
+        assert!(matches!(
+            Mailer::smtp("ftp://localhost", "noreply@example.com"),
+            Err(EmailError::Config(_))
+        ));
+    }
+}
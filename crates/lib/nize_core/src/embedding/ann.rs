@@ -0,0 +1,617 @@
+//! Approximate-nearest-neighbour indexes on the embedding tables.
+//!
+//! Every embedding table gets one partial index per registered model, over
+//! the embedding cast to the model's dimension and restricted to its
+//! `model_id` — the shape [`EmbeddingModelConfig::distance_sql`] and
+//! [`EmbeddingModelConfig::filter_sql`] produce, so searches use it.
+//!
+//! The index method and build parameters come from the `embedding.index.*`
+//! config keys; [`ensure_model_indexes`] creates missing indexes with them
+//! (the indexer calls it before storing a model's first embeddings) and
+//! [`rebuild`] rebuilds existing ones after the parameters change. Builds
+//! run `CONCURRENTLY`, so searches and writes continue meanwhile.
+//!
+//! The query-time trade-off between latency and recall comes from the
+//! `embedding.search.*` keys; searches run in a transaction that
+//! [`SearchParams::apply`] configures first.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use super::EmbeddingError;
+use super::models::EmbeddingModelConfig;
+use crate::config::cache::ConfigCache;
+use crate::config::resolver;
+
+/// Tables holding model-tagged embeddings.
+pub const EMBEDDING_TABLES: &[&str] = &["tool_embeddings", "chunk_embeddings"];
+
+/// `hnsw` or `ivfflat`.
+pub const METHOD_CONFIG_KEY: &str = "embedding.index.method";
+/// HNSW: connections per node.
+pub const HNSW_M_CONFIG_KEY: &str = "embedding.index.hnsw.m";
+/// HNSW: candidate list size while building.
+pub const HNSW_EF_CONSTRUCTION_CONFIG_KEY: &str = "embedding.index.hnsw.efConstruction";
+/// IVFFlat: number of lists (`0` derives it from the row count).
+pub const IVFFLAT_LISTS_CONFIG_KEY: &str = "embedding.index.ivfflat.lists";
+/// HNSW: candidate list size while searching.
+pub const HNSW_EF_SEARCH_CONFIG_KEY: &str = "embedding.search.hnsw.efSearch";
+/// IVFFlat: lists probed while searching.
+pub const IVFFLAT_PROBES_CONFIG_KEY: &str = "embedding.search.ivfflat.probes";
+
+/// Index access method.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexMethod {
+    #[default]
+    Hnsw,
+    IvfFlat,
+}
+
+impl IndexMethod {
+    /// pgvector access method name.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hnsw => "hnsw",
+            Self::IvfFlat => "ivfflat",
+        }
+    }
+
+    /// Index name suffix. Kept short: the longest table name plus a model
+    /// id already takes most of Postgres's 63-character limit.
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::Hnsw => "hnsw",
+            Self::IvfFlat => "ivf",
+        }
+    }
+}
+
+impl fmt::Display for IndexMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for IndexMethod {
+    type Err = EmbeddingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hnsw" => Ok(Self::Hnsw),
+            "ivfflat" => Ok(Self::IvfFlat),
+            other => Err(EmbeddingError::Config(format!(
+                "Unknown index method: {other}"
+            ))),
+        }
+    }
+}
+
+/// Index build parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexParams {
+    pub method: IndexMethod,
+    pub hnsw_m: u32,
+    pub hnsw_ef_construction: u32,
+    /// `0` derives the list count from the row count (see [`auto_lists`]).
+    pub ivfflat_lists: u32,
+}
+
+impl Default for IndexParams {
+    /// pgvector's defaults.
+    fn default() -> Self {
+        Self {
+            method: IndexMethod::Hnsw,
+            hnsw_m: 16,
+            hnsw_ef_construction: 64,
+            ivfflat_lists: 0,
+        }
+    }
+}
+
+impl IndexParams {
+    /// Read the parameters from config, falling back to the defaults for
+    /// unset or unreadable keys.
+    pub async fn resolve(pool: &PgPool, cache: &Arc<RwLock<ConfigCache>>) -> Self {
+        let defaults = Self::default();
+        Self {
+            method: resolver::get_system_value(pool, cache, METHOD_CONFIG_KEY)
+                .await
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.method),
+            hnsw_m: number(pool, cache, HNSW_M_CONFIG_KEY, defaults.hnsw_m).await,
+            hnsw_ef_construction: number(
+                pool,
+                cache,
+                HNSW_EF_CONSTRUCTION_CONFIG_KEY,
+                defaults.hnsw_ef_construction,
+            )
+            .await,
+            ivfflat_lists: number(
+                pool,
+                cache,
+                IVFFLAT_LISTS_CONFIG_KEY,
+                defaults.ivfflat_lists,
+            )
+            .await,
+        }
+    }
+
+    /// The index's `WITH (...)` storage parameters for a table holding
+    /// `rows` embeddings of the model.
+    pub fn with_clause(&self, rows: i64) -> String {
+        match self.method {
+            IndexMethod::Hnsw => format!(
+                "m = {}, ef_construction = {}",
+                self.hnsw_m.max(2),
+                self.hnsw_ef_construction.max(2 * self.hnsw_m.max(2)),
+            ),
+            IndexMethod::IvfFlat => {
+                let lists = match self.ivfflat_lists {
+                    0 => auto_lists(rows),
+                    n => n,
+                };
+                format!("lists = {lists}")
+            }
+        }
+    }
+}
+
+/// pgvector's recommended IVFFlat list count: rows / 1000 up to a million
+/// rows, the square root of the row count beyond.
+pub fn auto_lists(rows: i64) -> u32 {
+    let lists = if rows <= 1_000_000 {
+        rows / 1000
+    } else {
+        (rows as f64).sqrt() as i64
+    };
+    lists.clamp(1, 32_768) as u32
+}
+
+/// Query-time search parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchParams {
+    pub hnsw_ef_search: u32,
+    pub ivfflat_probes: u32,
+}
+
+impl Default for SearchParams {
+    /// pgvector's defaults.
+    fn default() -> Self {
+        Self {
+            hnsw_ef_search: 40,
+            ivfflat_probes: 1,
+        }
+    }
+}
+
+impl SearchParams {
+    /// Read the parameters from config, falling back to the defaults for
+    /// unset or unreadable keys.
+    pub async fn resolve(pool: &PgPool, cache: &Arc<RwLock<ConfigCache>>) -> Self {
+        let defaults = Self::default();
+        Self {
+            hnsw_ef_search: number(
+                pool,
+                cache,
+                HNSW_EF_SEARCH_CONFIG_KEY,
+                defaults.hnsw_ef_search,
+            )
+            .await,
+            ivfflat_probes: number(
+                pool,
+                cache,
+                IVFFLAT_PROBES_CONFIG_KEY,
+                defaults.ivfflat_probes,
+            )
+            .await,
+        }
+    }
+
+    /// Set the parameters for the rest of the current transaction. Outside
+    /// a transaction this has no effect.
+    pub async fn apply(&self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "SELECT set_config('hnsw.ef_search', $1, true), \
+                    set_config('ivfflat.probes', $2, true)",
+        )
+        .bind(self.hnsw_ef_search.max(1).to_string())
+        .bind(self.ivfflat_probes.max(1).to_string())
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+}
+
+async fn number(pool: &PgPool, cache: &Arc<RwLock<ConfigCache>>, key: &str, default: u32) -> u32 {
+    resolver::get_system_value(pool, cache, key)
+        .await
+        .ok()
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|n| n.is_finite() && *n >= 0.0)
+        .map_or(default, |n| n as u32)
+}
+
+/// Name of a model's index on `table`.
+pub fn index_name(table: &str, model_id: &Uuid, method: IndexMethod) -> String {
+    format!("{}_{}", index_prefix(table, model_id), method.suffix())
+}
+
+fn index_prefix(table: &str, model_id: &Uuid) -> String {
+    format!("{table}_{}", model_id.simple())
+}
+
+/// Name an index is built under before it replaces the current one.
+fn build_name(table: &str, model_id: &Uuid) -> String {
+    format!("{}_tmp", index_prefix(table, model_id))
+}
+
+/// A model's index on one table.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexStatus {
+    pub table: String,
+    pub model_id: Uuid,
+    pub model: String,
+    pub dimensions: i32,
+    /// Embeddings of the model in the table.
+    pub rows: i64,
+    /// `None` when the model has no index on the table.
+    pub index_name: Option<String>,
+    pub method: Option<IndexMethod>,
+    /// `false` for an index left behind by a failed concurrent build.
+    pub valid: bool,
+    pub size_bytes: i64,
+    /// Storage parameters, e.g. `m=16`.
+    pub options: Vec<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct AnnIndexRow {
+    name: String,
+    method: String,
+    valid: bool,
+    size_bytes: i64,
+    options: Vec<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ModelRow {
+    id: Uuid,
+    name: String,
+    dimensions: i32,
+}
+
+async fn list_models(pool: &PgPool) -> Result<Vec<ModelRow>, EmbeddingError> {
+    Ok(sqlx::query_as::<_, ModelRow>(
+        "SELECT id, name, dimensions FROM embedding_models ORDER BY provider, name",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+/// The pgvector indexes on `table`.
+async fn ann_indexes(pool: &PgPool, table: &str) -> Result<Vec<AnnIndexRow>, EmbeddingError> {
+    Ok(sqlx::query_as::<_, AnnIndexRow>(
+        r#"
+        SELECT c.relname::text AS name,
+               am.amname::text AS method,
+               i.indisvalid AS valid,
+               pg_relation_size(c.oid) AS size_bytes,
+               COALESCE(c.reloptions, '{}')::text[] AS options
+        FROM pg_index i
+        JOIN pg_class c ON c.oid = i.indexrelid
+        JOIN pg_am am ON am.oid = c.relam
+        WHERE i.indrelid = to_regclass($1)
+          AND am.amname IN ('hnsw', 'ivfflat')
+        "#,
+    )
+    .bind(table)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Embedding counts per model in `table`.
+async fn row_counts(pool: &PgPool, table: &str) -> Result<Vec<(Uuid, i64)>, EmbeddingError> {
+    Ok(sqlx::query_as::<_, (Uuid, i64)>(&format!(
+        "SELECT model_id, COUNT(*) FROM {table} GROUP BY model_id"
+    ))
+    .fetch_all(pool)
+    .await?)
+}
+
+/// The current index of a model among a table's indexes, ignoring builds
+/// in progress.
+fn find_index<'a>(
+    indexes: &'a [AnnIndexRow],
+    table: &str,
+    model_id: &Uuid,
+) -> Option<&'a AnnIndexRow> {
+    let prefix = format!("{}_", index_prefix(table, model_id));
+    let build = build_name(table, model_id);
+    indexes
+        .iter()
+        .filter(|i| i.name.starts_with(&prefix) && i.name != build)
+        .max_by_key(|i| i.valid)
+}
+
+/// Every registered model's index on every embedding table.
+pub async fn status(pool: &PgPool) -> Result<Vec<IndexStatus>, EmbeddingError> {
+    let models = list_models(pool).await?;
+    let mut statuses = Vec::new();
+    for table in EMBEDDING_TABLES {
+        let indexes = ann_indexes(pool, table).await?;
+        let counts = row_counts(pool, table).await?;
+        for model in &models {
+            let index = find_index(&indexes, table, &model.id);
+            statuses.push(IndexStatus {
+                table: table.to_string(),
+                model_id: model.id,
+                model: model.name.clone(),
+                dimensions: model.dimensions,
+                rows: counts
+                    .iter()
+                    .find(|(id, _)| *id == model.id)
+                    .map_or(0, |(_, n)| *n),
+                index_name: index.map(|i| i.name.clone()),
+                method: index.and_then(|i| i.method.parse().ok()),
+                valid: index.is_some_and(|i| i.valid),
+                size_bytes: index.map_or(0, |i| i.size_bytes),
+                options: index.map(|i| i.options.clone()).unwrap_or_default(),
+            });
+        }
+    }
+    Ok(statuses)
+}
+
+fn create_sql(
+    name: &str,
+    table: &str,
+    model_id: &Uuid,
+    dimensions: i32,
+    params: &IndexParams,
+    rows: i64,
+) -> String {
+    format!(
+        "CREATE INDEX CONCURRENTLY IF NOT EXISTS {name} ON {table} \
+         USING {method} ((embedding::vector({dimensions})) vector_cosine_ops) \
+         WITH ({with}) WHERE model_id = '{model_id}'",
+        method = params.method.as_str(),
+        with = params.with_clause(rows),
+    )
+}
+
+/// Create the model's missing indexes. Returns the names of the indexes
+/// created.
+pub async fn ensure_model_indexes(
+    pool: &PgPool,
+    params: &IndexParams,
+    model: &EmbeddingModelConfig,
+) -> Result<Vec<String>, EmbeddingError> {
+    let mut created = Vec::new();
+    for table in EMBEDDING_TABLES {
+        let indexes = ann_indexes(pool, table).await?;
+        if find_index(&indexes, table, &model.id).is_some() {
+            continue;
+        }
+        let name = index_name(table, &model.id, params.method);
+        sqlx::query(&create_sql(
+            &name,
+            table,
+            &model.id,
+            model.dimensions,
+            params,
+            0,
+        ))
+        .execute(pool)
+        .await?;
+        created.push(name);
+    }
+    Ok(created)
+}
+
+/// Models whose indexes this process has already ensured.
+static ENSURED: LazyLock<Mutex<HashSet<Uuid>>> = LazyLock::new(Default::default);
+
+/// [`ensure_model_indexes`] with the configured parameters, once per model
+/// per process.
+pub async fn ensure_indexes_once(
+    pool: &PgPool,
+    cache: &Arc<RwLock<ConfigCache>>,
+    model: &EmbeddingModelConfig,
+) -> Result<(), EmbeddingError> {
+    if ENSURED.lock().unwrap().contains(&model.id) {
+        return Ok(());
+    }
+    let params = IndexParams::resolve(pool, cache).await;
+    let created = ensure_model_indexes(pool, &params, model).await?;
+    if !created.is_empty() {
+        tracing::info!(model = %model.model, indexes = ?created, "created embedding indexes");
+    }
+    ENSURED.lock().unwrap().insert(model.id);
+    Ok(())
+}
+
+/// Which indexes [`rebuild`] rebuilds; everything by default.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebuildRequest {
+    pub model_id: Option<Uuid>,
+    pub table: Option<String>,
+}
+
+/// One rebuilt index.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebuiltIndex {
+    pub table: String,
+    pub model_id: Uuid,
+    pub index_name: String,
+    pub method: IndexMethod,
+    /// Storage parameters the index was built with.
+    pub options: String,
+    pub rows: i64,
+    pub duration_ms: u64,
+}
+
+/// Rebuild the selected indexes with `params`. Each index is built under a
+/// temporary name next to the current one, which is then dropped, so
+/// searches stay indexed throughout. Models without an index get one.
+pub async fn rebuild(
+    pool: &PgPool,
+    params: &IndexParams,
+    request: &RebuildRequest,
+) -> Result<Vec<RebuiltIndex>, EmbeddingError> {
+    if let Some(table) = &request.table
+        && !EMBEDDING_TABLES.contains(&table.as_str())
+    {
+        return Err(EmbeddingError::UnknownTable(table.clone()));
+    }
+    let models: Vec<ModelRow> = list_models(pool)
+        .await?
+        .into_iter()
+        .filter(|m| request.model_id.is_none_or(|id| id == m.id))
+        .collect();
+    if let Some(id) = request.model_id
+        && models.is_empty()
+    {
+        return Err(EmbeddingError::ModelNotFound(id.to_string()));
+    }
+
+    let mut rebuilt = Vec::new();
+    for table in EMBEDDING_TABLES {
+        if request.table.as_deref().is_some_and(|t| t != *table) {
+            continue;
+        }
+        let counts = row_counts(pool, table).await?;
+        for model in &models {
+            let started = Instant::now();
+            let rows = counts
+                .iter()
+                .find(|(id, _)| *id == model.id)
+                .map_or(0, |(_, n)| *n);
+            let build = build_name(table, &model.id);
+            let name = index_name(table, &model.id, params.method);
+
+            // A failed earlier build leaves an invalid index behind.
+            sqlx::query(&format!("DROP INDEX CONCURRENTLY IF EXISTS {build}"))
+                .execute(pool)
+                .await?;
+            sqlx::query(&create_sql(
+                &build,
+                table,
+                &model.id,
+                model.dimensions,
+                params,
+                rows,
+            ))
+            .execute(pool)
+            .await?;
+
+            let prefix = format!("{}_", index_prefix(table, &model.id));
+            for old in ann_indexes(pool, table).await? {
+                if old.name.starts_with(&prefix) && old.name != build {
+                    sqlx::query(&format!("DROP INDEX CONCURRENTLY IF EXISTS {}", old.name))
+                        .execute(pool)
+                        .await?;
+                }
+            }
+            sqlx::query(&format!("ALTER INDEX {build} RENAME TO {name}"))
+                .execute(pool)
+                .await?;
+
+            rebuilt.push(RebuiltIndex {
+                table: table.to_string(),
+                model_id: model.id,
+                index_name: name,
+                method: params.method,
+                options: params.with_clause(rows),
+                rows,
+                duration_ms: started.elapsed().as_millis() as u64,
+            });
+        }
+    }
+    Ok(rebuilt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_names_fit_postgres_identifier_limit() {
+        let id = Uuid::new_v4();
+        let longest = EMBEDDING_TABLES.iter().max_by_key(|t| t.len()).unwrap();
+        for method in [IndexMethod::Hnsw, IndexMethod::IvfFlat] {
+            assert!(index_name(longest, &id, method).len() <= 63);
+        }
+        assert!(build_name(longest, &id).len() <= 63);
+        // Matches the names the migrations gave the original indexes.
+        assert_eq!(
+            index_name("tool_embeddings", &Uuid::nil(), IndexMethod::Hnsw),
+            "tool_embeddings_00000000000000000000000000000000_hnsw"
+        );
+    }
+
+    #[test]
+    fn with_clause_follows_method() {
+        let hnsw = IndexParams::default();
+        assert_eq!(hnsw.with_clause(0), "m = 16, ef_construction = 64");
+
+        let ivf = IndexParams {
+            method: IndexMethod::IvfFlat,
+            ..Default::default()
+        };
+        assert_eq!(ivf.with_clause(250_000), "lists = 250");
+        let fixed = IndexParams {
+            ivfflat_lists: 42,
+            ..ivf
+        };
+        assert_eq!(fixed.with_clause(250_000), "lists = 42");
+    }
+
+    #[test]
+    fn auto_lists_scales_with_rows() {
+        assert_eq!(auto_lists(0), 1);
+        assert_eq!(auto_lists(50_000), 50);
+        assert_eq!(auto_lists(4_000_000), 2000);
+    }
+
+    #[test]
+    fn find_index_skips_builds_and_other_models() {
+        let model = Uuid::new_v4();
+        let row = |name: String, valid| AnnIndexRow {
+            name,
+            method: "hnsw".into(),
+            valid,
+            size_bytes: 0,
+            options: vec![],
+        };
+        let indexes = vec![
+            row(build_name("tool_embeddings", &model), true),
+            row(
+                index_name("tool_embeddings", &Uuid::new_v4(), IndexMethod::Hnsw),
+                true,
+            ),
+        ];
+        assert!(find_index(&indexes, "tool_embeddings", &model).is_none());
+
+        let current = index_name("tool_embeddings", &model, IndexMethod::IvfFlat);
+        let indexes = vec![row(current.clone(), true)];
+        assert_eq!(
+            find_index(&indexes, "tool_embeddings", &model).map(|i| i.name.as_str()),
+            Some(current.as_str())
+        );
+    }
+}
@@ -0,0 +1,105 @@
+// @awa-component: EMB-CompatibleProvider
+//
+//! OpenAI-compatible embedding provider.
+//!
+//! Calls the `/embeddings` endpoint of an admin-configured OpenAI-compatible
+//! server (see [`crate::providers`]) with the whole batch in one request,
+//! retrying like the OpenAI provider. The `dimensions` parameter is not
+//! sent, as most such servers reject it; instead the returned vectors are
+//! checked against the configured dimension.
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use super::models::EmbeddingModelConfig;
+use super::{EMBEDDING_RETRY, EmbeddingError, EmbeddingResult};
+use crate::providers::CompatibleEndpoint;
+
+#[derive(Serialize)]
+struct EmbeddingsRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    #[serde(default)]
+    index: usize,
+    embedding: Vec<f64>,
+}
+
+/// Embed a batch of texts via an OpenAI-compatible endpoint.
+pub async fn embed_batch(
+    client: &Client,
+    endpoint: &CompatibleEndpoint,
+    texts: &[String],
+    model_config: &EmbeddingModelConfig,
+) -> Result<Vec<EmbeddingResult>, EmbeddingError> {
+    if texts.is_empty() {
+        return Ok(Vec::new());
+    }
+    let name = &endpoint.provider.name;
+    let url = endpoint.provider.url("embeddings");
+
+    let resp = EMBEDDING_RETRY
+        .send(|| {
+            let request = client.post(&url).json(&EmbeddingsRequest {
+                model: &model_config.model,
+                input: texts,
+            });
+            match endpoint.auth_header() {
+                Some((header, value)) => request.header(header, value),
+                None => request,
+            }
+        })
+        .await
+        .map_err(|e| EmbeddingError::Provider(format!("{name} request failed: {e}")))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp
+            .text()
+            .await
+            .unwrap_or_else(|_| "<no body>".to_string());
+        return Err(EmbeddingError::Provider(format!(
+            "{name} embeddings failed: {status} {body}"
+        )));
+    }
+
+    let mut data: EmbeddingsResponse = resp
+        .json()
+        .await
+        .map_err(|e| EmbeddingError::Provider(format!("{name} response parse error: {e}")))?;
+    if data.data.len() != texts.len() {
+        return Err(EmbeddingError::Provider(format!(
+            "{name} returned {} embeddings for {} texts",
+            data.data.len(),
+            texts.len()
+        )));
+    }
+    data.data.sort_by_key(|d| d.index);
+
+    texts
+        .iter()
+        .zip(data.data)
+        .map(|(text, data)| {
+            if data.embedding.len() != model_config.dimensions as usize {
+                return Err(EmbeddingError::DimensionMismatch {
+                    expected: model_config.dimensions,
+                    actual: data.embedding.len() as i32,
+                });
+            }
+            Ok(EmbeddingResult {
+                text: text.clone(),
+                embedding: data.embedding.into_iter().map(|v| v as f32).collect(),
+                model: model_config.model.clone(),
+                truncated: false,
+            })
+        })
+        .collect()
+}
@@ -15,13 +15,15 @@ use crate::config::cache::ConfigCache;
 use crate::config::queries;
 use crate::config::resolver;
 use crate::mcp::secrets;
+use crate::providers::{self, CompatibleEndpoint};
 
 use super::EmbeddingError;
 
 /// Resolved configuration for which embedding provider/model to use.
 #[derive(Debug, Clone)]
 pub struct EmbeddingConfig {
-    /// Provider name: `"openai"`, `"ollama"`, or `"local"`.
+    /// Provider name: `"openai"`, `"ollama"`, `"local"`, or the name of an
+    /// OpenAI-compatible provider.
     pub provider: String,
     /// Active model name (must match a row in `embedding_models`).
     pub active_model: String,
@@ -29,6 +31,8 @@ pub struct EmbeddingConfig {
     pub ollama_base_url: String,
     /// OpenAI API key (required when provider is `"openai"`).
     pub openai_api_key: Option<String>,
+    /// The OpenAI-compatible provider named by `provider`, if it is one.
+    pub compatible: Option<CompatibleEndpoint>,
 }
 
 impl EmbeddingConfig {
@@ -100,11 +104,18 @@ impl EmbeddingConfig {
             provider
         };
 
+        let compatible = if providers::BUILT_IN_PROVIDERS.contains(&provider.as_str()) {
+            None
+        } else {
+            providers::endpoint(pool, cache, encryption_key, &provider).await
+        };
+
         Ok(Self {
             provider,
             active_model,
             ollama_base_url,
             openai_api_key,
+            compatible,
         })
     }
 
@@ -112,7 +123,7 @@ impl EmbeddingConfig {
     ///
     /// Returns the decrypted plaintext, or an empty string if the value is
     /// empty or the key doesn't exist.
-    async fn resolve_secret_config(
+    pub(crate) async fn resolve_secret_config(
         pool: &PgPool,
         key: &str,
         encryption_key: &str,
@@ -148,6 +159,7 @@ impl EmbeddingConfig {
             ollama_base_url: env::var("OLLAMA_BASE_URL")
                 .unwrap_or_else(|_| "http://localhost:11434".to_string()),
             openai_api_key,
+            compatible: None,
         }
     }
 }
@@ -167,6 +179,7 @@ mod tests {
             active_model: "nomic-embed-text".to_string(),
             ollama_base_url: "http://localhost:11434".to_string(),
             openai_api_key: None,
+            compatible: None,
         };
         assert_eq!(config.provider, "local");
         assert_eq!(config.active_model, "nomic-embed-text");
@@ -182,6 +195,7 @@ mod tests {
             active_model: "nomic-embed-text".to_string(),
             ollama_base_url: "http://localhost:11434".to_string(),
             openai_api_key: None,
+            compatible: None,
         };
         assert_eq!(config.provider, "ollama");
     }
@@ -194,6 +208,7 @@ mod tests {
             active_model: "text-embedding-3-small".to_string(),
             ollama_base_url: "http://localhost:11434".to_string(),
             openai_api_key: Some("sk-test-key".to_string()),
+            compatible: None,
         };
         assert_eq!(config.provider, "openai");
         assert_eq!(config.openai_api_key.as_deref(), Some("sk-test-key"));
@@ -207,6 +222,7 @@ mod tests {
             active_model: "custom-model".to_string(),
             ollama_base_url: "http://localhost:11434".to_string(),
             openai_api_key: None,
+            compatible: None,
         };
         assert_eq!(config.active_model, "custom-model");
     }
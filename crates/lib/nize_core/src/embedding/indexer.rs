@@ -1,25 +1,54 @@
 // @awa-component: EMB-ToolIndexer
 //
-//! Tool embedding indexer — generates and stores embeddings for MCP server tools.
+//! Embedding indexer — generates and stores embeddings for MCP server tools,
+//! notes and documents.
 //!
 //! After tools are saved via [`crate::mcp::queries::replace_server_tools`],
 //! call [`embed_server_tools`] to generate embeddings for semantic discovery.
+//! Each tool embedding records the [`tool_content_hash`] it was computed
+//! from, so only new and changed tools are re-embedded and
+//! [`stale_tool_servers`] can report servers that are out of date.
+//! After a note is created or edited, call [`embed_note`] to re-chunk and
+//! embed it for retrieval. After a document's chunks are extracted, call
+//! [`embed_document`] to embed them. Note and document chunks live in the
+//! content-addressed [`crate::chunks`] store, so each distinct chunk text is
+//! embedded once per model, however many notes and documents contain it.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use reqwest::Client;
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
 use tokio::sync::RwLock;
+use uuid::Uuid;
 
+use crate::chunks;
 use crate::config::cache::ConfigCache;
+use crate::documents;
 use crate::mcp;
+use crate::notes;
+use crate::notes::chunker::{DEFAULT_MAX_CHUNK_CHARS, chunk_markdown};
 use crate::uuid::uuidv7;
 
 use super::EmbeddingError;
+use super::ann;
 use super::config::EmbeddingConfig;
-use super::models;
+use super::models::{self, EmbeddingModelConfig};
 use super::provider;
 
+/// Make sure the model has its ANN indexes before its embeddings are
+/// stored. A failure only costs search speed, so it is logged, not returned.
+async fn ensure_indexes(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    model_config: &EmbeddingModelConfig,
+) {
+    if let Err(e) = ann::ensure_indexes_once(pool, config_cache, model_config).await {
+        tracing::warn!(model = %model_config.model, "Failed to create embedding indexes: {e}");
+    }
+}
+
 // @awa-impl: MCP-7_AC-2
 /// Build embedding text by concatenating server context with tool description.
 ///
@@ -37,15 +66,25 @@ pub fn build_embedding_text(
     parts.join("\n\n")
 }
 
-/// Generate and store embeddings for all tools belonging to an MCP server.
+/// Hash of the tool text an embedding is computed from. Migration 0053
+/// computes the same value in SQL; keep them in step.
+pub fn tool_content_hash(name: &str, description: &str) -> String {
+    format!("{:x}", Sha256::digest(format!("{name}\n{description}")))
+}
+
+/// Generate and store embeddings for the tools of an MCP server that have
+/// none under the active model, or whose name or description changed since
+/// they were embedded.
 ///
 /// This function:
 /// 1. Resolves the embedding config (provider/model/keys)
 /// 2. Fetches server info for embedding context
-/// 3. Fetches current tool rows (with their UUIDs)
-/// 4. For each tool, generates an embedding and upserts into the tool embedding table
+/// 3. Fetches current tool rows (with their UUIDs) and the content hashes
+///    of their embeddings
+/// 4. For each new or changed tool, generates an embedding and upserts it
+///    into the tool embedding table
 ///
-/// Returns the number of tools successfully embedded.
+/// Returns the number of tools embedded; unchanged tools are not counted.
 ///
 /// Errors are returned (not swallowed) — callers should log and continue.
 pub async fn embed_server_tools(
@@ -57,6 +96,7 @@ pub async fn embed_server_tools(
     // Resolve embedding config
     let config = EmbeddingConfig::resolve(pool, config_cache, encryption_key).await?;
     let model_config = models::get_active_model(pool, &config).await?;
+    ensure_indexes(pool, config_cache, &model_config).await;
 
     // Fetch server info
     let server = mcp::queries::get_server(pool, server_id)
@@ -73,10 +113,33 @@ pub async fn embed_server_tools(
         return Ok(0);
     }
 
+    // Unchanged tools keep their vectors; only the domain can have moved.
+    sqlx::query("UPDATE tool_embeddings SET domain = $2 WHERE server_id = $1 AND domain <> $2")
+        .bind(server.id)
+        .bind(&server.domain)
+        .execute(pool)
+        .await?;
+    let embedded: HashMap<Uuid, Option<String>> = sqlx::query_as(
+        "SELECT tool_id, content_hash FROM tool_embeddings WHERE server_id = $1 AND model_id = $2",
+    )
+    .bind(server.id)
+    .bind(model_config.id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+
     let client = Client::new();
     let mut count = 0;
 
     for tool in &tools {
+        let content_hash = tool_content_hash(&tool.name, &tool.description);
+        if embedded
+            .get(&tool.id)
+            .is_some_and(|h| h.as_ref() == Some(&content_hash))
+        {
+            continue;
+        }
         let embedding_text =
             build_embedding_text(&server.name, &server.description, &tool.description);
 
@@ -84,48 +147,243 @@ pub async fn embed_server_tools(
         let texts = vec![embedding_text];
         let results = provider::embed_with_model(&client, &config, &texts, &model_config).await?;
 
-        let embedding = results
+        let result = results
             .into_iter()
             .next()
-            .map(|r| r.embedding)
             .ok_or_else(|| EmbeddingError::Provider("No embedding result returned".to_string()))?;
 
+        check_dimensions(&result.embedding, &model_config)?;
+
         // Format vector as SQL literal: '[0.1,0.2,...]'
         let embedding_sql: String = format!(
             "[{}]",
-            embedding
+            result
+                .embedding
                 .iter()
                 .map(|v| v.to_string())
                 .collect::<Vec<_>>()
                 .join(",")
         );
 
-        // Upsert into dynamic tool embedding table
-        let query = format!(
-            r#"INSERT INTO "{}" (id, tool_id, server_id, domain, embedding)
-               VALUES ($1, $2, $3, $4, $5::vector)
-               ON CONFLICT (tool_id) DO UPDATE SET
+        // Upsert, tagged with the model that produced the vector
+        sqlx::query(
+            r#"INSERT INTO tool_embeddings
+                 (id, tool_id, server_id, domain, model_id, model, dimensions, embedding, truncated,
+                  content_hash)
+               VALUES ($1, $2, $3, $4, $5, $6, $7, $8::vector, $9, $10)
+               ON CONFLICT (tool_id, model_id) DO UPDATE SET
                  embedding = EXCLUDED.embedding,
-                 domain = EXCLUDED.domain"#,
-            model_config.tool_table_name
+                 domain = EXCLUDED.domain,
+                 truncated = EXCLUDED.truncated,
+                 content_hash = EXCLUDED.content_hash,
+                 created_at = now()"#,
+        )
+        .bind(uuidv7())
+        .bind(tool.id)
+        .bind(server.id)
+        .bind(&server.domain)
+        .bind(model_config.id)
+        .bind(&model_config.model)
+        .bind(model_config.dimensions)
+        .bind(&embedding_sql)
+        .bind(result.truncated)
+        .bind(&content_hash)
+        .execute(pool)
+        .await
+        .map_err(EmbeddingError::Db)?;
+
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// A server with tools whose embeddings under a model are missing or
+/// out of date.
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleToolServer {
+    pub server_id: Uuid,
+    pub server_name: String,
+    /// Tools whose description changed since they were embedded.
+    pub changed_tools: i64,
+    /// Tools with no embedding under the model.
+    pub unembedded_tools: i64,
+    /// When the server's newest tool embedding was computed.
+    pub last_embedded_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Servers whose tool embeddings under `model_id` are missing or stale,
+/// most changed first. [`embed_server_tools`] brings a server up to date.
+pub async fn stale_tool_servers(
+    pool: &PgPool,
+    model_id: &Uuid,
+) -> Result<Vec<StaleToolServer>, EmbeddingError> {
+    let rows = sqlx::query_as::<_, StaleToolServer>(
+        r#"
+        SELECT s.id AS server_id, s.name AS server_name,
+               COUNT(*) FILTER (
+                   WHERE e.id IS NOT NULL AND e.content_hash IS DISTINCT FROM
+                       encode(sha256(convert_to(t.name || E'\n' || t.description, 'UTF8')), 'hex')
+               ) AS changed_tools,
+               COUNT(*) FILTER (WHERE e.id IS NULL) AS unembedded_tools,
+               MAX(e.created_at) AS last_embedded_at
+        FROM mcp_servers s
+        JOIN mcp_server_tools t ON t.server_id = s.id
+        LEFT JOIN tool_embeddings e ON e.tool_id = t.id AND e.model_id = $1
+        GROUP BY s.id, s.name
+        HAVING COUNT(*) FILTER (
+                   WHERE e.id IS NULL OR e.content_hash IS DISTINCT FROM
+                       encode(sha256(convert_to(t.name || E'\n' || t.description, 'UTF8')), 'hex')
+               ) > 0
+        ORDER BY changed_tools DESC, unembedded_tools DESC, s.name
+        "#,
+    )
+    .bind(model_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Chunk a note body and make sure every chunk has an embedding.
+///
+/// Existing chunks are replaced, so this is safe to call after every edit;
+/// chunks whose text was embedded before (in this or any other note or
+/// document) are not embedded again. Returns the number of chunks the note
+/// now has; a deleted note yields `Ok(0)`.
+///
+/// Errors are returned (not swallowed) — callers should log and continue.
+pub async fn embed_note(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    note_id: &Uuid,
+    encryption_key: &str,
+) -> Result<usize, EmbeddingError> {
+    let config = EmbeddingConfig::resolve(pool, config_cache, encryption_key).await?;
+    let model_config = models::get_active_model(pool, &config).await?;
+    ensure_indexes(pool, config_cache, &model_config).await;
+
+    let Some(note) = notes::find_note(pool, note_id).await? else {
+        return Ok(0);
+    };
+
+    let chunks = notes::replace_note_chunks(
+        pool,
+        note_id,
+        &chunk_markdown(&note.body, DEFAULT_MAX_CHUNK_CHARS),
+    )
+    .await?;
+    let hashes: Vec<String> = chunks.into_iter().map(|c| c.chunk_hash).collect();
+    embed_chunks(pool, &config, &model_config, &hashes).await?;
+    Ok(hashes.len())
+}
+
+/// Make sure every stored chunk of a document has an embedding. Chunks are
+/// produced at upload (see [`crate::ingest`]); this only embeds those whose
+/// text has no embedding yet. Returns the number of chunks the document
+/// has; a deleted document yields `Ok(0)`.
+///
+/// Errors are returned (not swallowed) — callers should log and continue.
+pub async fn embed_document(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    document_id: &Uuid,
+    encryption_key: &str,
+) -> Result<usize, EmbeddingError> {
+    if documents::find_document(pool, document_id).await?.is_none() {
+        return Ok(0);
+    }
+    let chunks = documents::list_chunks(pool, document_id).await?;
+    if chunks.is_empty() {
+        return Ok(0);
+    }
+
+    let config = EmbeddingConfig::resolve(pool, config_cache, encryption_key).await?;
+    let model_config = models::get_active_model(pool, &config).await?;
+    ensure_indexes(pool, config_cache, &model_config).await;
+
+    let hashes: Vec<String> = chunks.into_iter().map(|c| c.chunk_hash).collect();
+    embed_chunks(pool, &config, &model_config, &hashes).await?;
+    Ok(hashes.len())
+}
+
+/// Embed those of the chunks `hashes` with no embedding under the model.
+/// Returns the number embedded.
+async fn embed_chunks(
+    pool: &PgPool,
+    config: &EmbeddingConfig,
+    model_config: &EmbeddingModelConfig,
+    hashes: &[String],
+) -> Result<usize, EmbeddingError> {
+    let pending = chunks::unembedded(pool, hashes, &model_config.id).await?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let texts: Vec<String> = pending.iter().map(|c| c.content.clone()).collect();
+    let client = Client::new();
+    let results = provider::embed_with_model(&client, config, &texts, model_config).await?;
+
+    let mut count = 0;
+    for (chunk, result) in pending.iter().zip(results) {
+        check_dimensions(&result.embedding, model_config)?;
+
+        // Format vector as SQL literal: '[0.1,0.2,...]'
+        let embedding_sql: String = format!(
+            "[{}]",
+            result
+                .embedding
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
         );
 
-        sqlx::query(&query)
-            .bind(uuidv7())
-            .bind(tool.id)
-            .bind(server.id)
-            .bind(&server.domain)
-            .bind(&embedding_sql)
-            .execute(pool)
-            .await
-            .map_err(EmbeddingError::Db)?;
+        // A concurrent indexer may have embedded the same text meanwhile.
+        sqlx::query(
+            r#"INSERT INTO chunk_embeddings
+                 (id, chunk_hash, model_id, model, dimensions, embedding, truncated)
+               VALUES ($1, $2, $3, $4, $5, $6::vector, $7)
+               ON CONFLICT (chunk_hash, model_id) DO NOTHING"#,
+        )
+        .bind(uuidv7())
+        .bind(&chunk.hash)
+        .bind(model_config.id)
+        .bind(&model_config.model)
+        .bind(model_config.dimensions)
+        .bind(&embedding_sql)
+        .bind(result.truncated)
+        .execute(pool)
+        .await
+        .map_err(EmbeddingError::Db)?;
 
         count += 1;
     }
+    tracing::debug!(
+        chunks = hashes.len(),
+        embedded = count,
+        "embedded new chunk contents"
+    );
 
     Ok(count)
 }
 
+/// Reject vectors whose length differs from the model's registered
+/// dimension, so rows never disagree with their `dimensions` tag.
+fn check_dimensions(
+    embedding: &[f32],
+    model_config: &models::EmbeddingModelConfig,
+) -> Result<(), EmbeddingError> {
+    let actual = embedding.len() as i32;
+    if actual != model_config.dimensions {
+        return Err(EmbeddingError::DimensionMismatch {
+            expected: model_config.dimensions,
+            actual,
+        });
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,4 +402,13 @@ mod tests {
         let text = build_embedding_text("MyServer", "", "Search the web");
         assert_eq!(text, "Server: MyServer\n\nSearch the web");
     }
+
+    #[test]
+    fn tool_content_hash_covers_name_and_description() {
+        let hash = tool_content_hash("search", "Search the web");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, tool_content_hash("search", "Search the web"));
+        assert_ne!(hash, tool_content_hash("search", "Search the web."));
+        assert_ne!(hash, tool_content_hash("find", "Search the web"));
+    }
 }
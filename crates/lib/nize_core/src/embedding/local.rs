@@ -43,6 +43,7 @@ pub fn embed_batch(texts: &[String], dimensions: i32, model: &str) -> Vec<Embedd
             text: text.clone(),
             embedding: embed(text, dimensions),
             model: model.to_string(),
+            truncated: false,
         })
         .collect()
 }
@@ -9,29 +9,59 @@
 //!
 //! - [`embed`] — embed multiple texts using all models for the active provider
 //! - [`embed_single`] — embed a single text using the active model
+//! - [`embed_query`] — embed a search query, cached per model and text
 //! - [`models::get_model_configs`] — get registered models for a provider
 //! - [`models::get_active_model`] — get the active model config
 //! - [`config::EmbeddingConfig`] — resolved embedding configuration
+//! - [`ann`] — per-model ANN index management and query-time tuning
+//! - [`rerank`] — optional cross-encoder reranking of search candidates
 //!
 //! # Providers
 //!
 //! - `"openai"` — OpenAI API (`text-embedding-3-small`)
 //! - `"ollama"` — Ollama local API (`nomic-embed-text`)
 //! - `"local"` — Deterministic FNV-1a hash (offline, no external deps)
+//! - any admin-configured OpenAI-compatible provider (see
+//!   [`crate::providers`])
 
+pub mod ann;
+pub mod compatible;
 pub mod config;
 pub mod indexer;
 pub mod local;
 pub mod models;
 pub mod ollama;
 pub mod openai;
+pub mod preprocess;
 pub mod provider;
+pub mod rerank;
+
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
 
 use reqwest::Client;
 use sqlx::PgPool;
 use thiserror::Error;
 
 use config::EmbeddingConfig;
+use models::EmbeddingModelConfig;
+
+use crate::cache::Cache;
+use crate::retry::RetryPolicy;
+
+/// Search query embeddings by model (provider, name, dimensions) and query
+/// text, so repeated searches skip the provider round trip.
+type QueryKey = (String, String, i32, String);
+
+static QUERY_EMBEDDINGS: LazyLock<Cache<QueryKey, Arc<Vec<f32>>>> =
+    LazyLock::new(|| Cache::new("query_embeddings", 1024, Duration::from_secs(60 * 60)));
+
+/// Retries for embedding provider requests. Indexing runs in the
+/// background, so it waits out rate limits rather than failing.
+pub(crate) const EMBEDDING_RETRY: RetryPolicy = RetryPolicy::new()
+    .max_attempts(3)
+    .base_delay(std::time::Duration::from_secs(2))
+    .max_delay(std::time::Duration::from_secs(30));
 
 /// Errors that can occur during embedding operations.
 #[derive(Debug, Error)]
@@ -45,6 +75,9 @@ pub enum EmbeddingError {
     #[error("Model not found: {0}")]
     ModelNotFound(String),
 
+    #[error("Unknown embedding table: {0}")]
+    UnknownTable(String),
+
     #[error("Unsupported provider: {0}")]
     UnsupportedProvider(String),
 
@@ -64,6 +97,9 @@ pub struct EmbeddingResult {
     pub text: String,
     pub embedding: Vec<f32>,
     pub model: String,
+    /// Whether the input was truncated to the model's limit before
+    /// embedding (see [`preprocess`]).
+    pub truncated: bool,
 }
 
 /// Embed multiple texts using ALL models for the active provider.
@@ -95,13 +131,72 @@ pub async fn embed_single(
     text: &str,
 ) -> Result<Vec<f32>, EmbeddingError> {
     let model_config = models::get_active_model(pool, config).await?;
-    let client = Client::new();
-    let texts = vec![text.to_string()];
-    let results = provider::embed_with_model(&client, config, &texts, &model_config).await?;
-
-    results
-        .into_iter()
-        .next()
-        .map(|r| r.embedding)
-        .ok_or_else(|| EmbeddingError::Provider("No embedding result returned".to_string()))
+    Ok(embed_query(config, &model_config, text).await?.to_vec())
+}
+
+/// Embed a search query with `model_config`, reusing the embedding of an
+/// earlier identical query.
+pub async fn embed_query(
+    config: &EmbeddingConfig,
+    model_config: &EmbeddingModelConfig,
+    query: &str,
+) -> Result<Arc<Vec<f32>>, EmbeddingError> {
+    let key = (
+        model_config.provider.clone(),
+        model_config.model.clone(),
+        model_config.dimensions,
+        query.to_string(),
+    );
+    QUERY_EMBEDDINGS
+        .get_or_try_insert_with(key, || async {
+            let texts = vec![query.to_string()];
+            provider::embed_with_model(&Client::new(), config, &texts, model_config)
+                .await?
+                .into_iter()
+                .next()
+                .map(|r| Arc::new(r.embedding))
+                .ok_or_else(|| EmbeddingError::Provider("No embedding result returned".to_string()))
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn local_model(dimensions: i32) -> EmbeddingModelConfig {
+        EmbeddingModelConfig {
+            id: Uuid::new_v4(),
+            provider: "local".to_string(),
+            model: "query-cache-test".to_string(),
+            dimensions,
+            table_name: "embeddings".to_string(),
+            max_input_tokens: 512,
+        }
+    }
+
+    #[tokio::test]
+    async fn query_embeddings_are_cached_per_model() {
+        let config = EmbeddingConfig {
+            provider: "local".to_string(),
+            active_model: "query-cache-test".to_string(),
+            ollama_base_url: "http://localhost:11434".to_string(),
+            openai_api_key: None,
+            compatible: None,
+        };
+        let first = embed_query(&config, &local_model(8), "cached query")
+            .await
+            .unwrap();
+        let again = embed_query(&config, &local_model(8), "cached query")
+            .await
+            .unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        let wider = embed_query(&config, &local_model(16), "cached query")
+            .await
+            .unwrap();
+        assert_eq!((first.len(), wider.len()), (8, 16));
+    }
 }
@@ -3,6 +3,7 @@
 //! Database queries for the embedding model registry.
 
 use sqlx::PgPool;
+use uuid::Uuid;
 
 use super::EmbeddingError;
 use super::config::EmbeddingConfig;
@@ -10,11 +11,43 @@ use super::config::EmbeddingConfig;
 /// A registered embedding model from the `embedding_models` table.
 #[derive(Debug, Clone)]
 pub struct EmbeddingModelConfig {
+    pub id: Uuid,
     pub provider: String,
     pub model: String,
     pub dimensions: i32,
     pub table_name: String,
-    pub tool_table_name: String,
+    /// Longest input, in tokens, the provider accepts for this model.
+    pub max_input_tokens: i32,
+}
+
+impl EmbeddingModelConfig {
+    /// SQL cosine distance between `column` and the vector parameter
+    /// `param`, cast to this model's dimension so the model's partial HNSW
+    /// index applies. Pair it with [`Self::filter_sql`].
+    pub fn distance_sql(&self, column: &str, param: &str) -> String {
+        format!(
+            "{column}::vector({dims}) <=> {param}::vector({dims})",
+            dims = self.dimensions
+        )
+    }
+
+    /// SQL predicate restricting embedding rows aliased `alias` to this
+    /// model. The ID is inlined so it matches the partial index predicate.
+    pub fn filter_sql(&self, alias: &str) -> String {
+        format!("{alias}.model_id = '{}'::uuid", self.id)
+    }
+}
+
+/// How many tools and stored chunks (distinct note and document chunk
+/// texts, see [`crate::chunks`]) a registered model has embedded, out of
+/// all that exist.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ModelCoverage {
+    pub model_id: Uuid,
+    pub tool_embeddings: i64,
+    pub tools: i64,
+    pub chunk_embeddings: i64,
+    pub chunks: i64,
 }
 
 /// Get all registered models for a given provider.
@@ -22,8 +55,8 @@ pub async fn get_model_configs(
     pool: &PgPool,
     provider: &str,
 ) -> Result<Vec<EmbeddingModelConfig>, EmbeddingError> {
-    let rows = sqlx::query_as::<_, (String, String, i32, String, String)>(
-        "SELECT provider, name, dimensions, table_name, tool_table_name \
+    let rows = sqlx::query_as::<_, (Uuid, String, String, i32, String, i32)>(
+        "SELECT id, provider, name, dimensions, table_name, max_input_tokens \
          FROM embedding_models WHERE provider = $1 ORDER BY name",
     )
     .bind(provider)
@@ -38,17 +71,39 @@ pub async fn get_model_configs(
     Ok(rows
         .into_iter()
         .map(
-            |(provider, name, dimensions, table_name, tool_table_name)| EmbeddingModelConfig {
+            |(id, provider, name, dimensions, table_name, max_input_tokens)| EmbeddingModelConfig {
+                id,
                 provider,
                 model: name,
                 dimensions,
                 table_name,
-                tool_table_name,
+                max_input_tokens,
             },
         )
         .collect())
 }
 
+/// Count stored tool and chunk embeddings of every registered model.
+pub async fn get_model_coverage(pool: &PgPool) -> Result<Vec<ModelCoverage>, EmbeddingError> {
+    let rows = sqlx::query_as::<_, ModelCoverage>(
+        r#"
+        SELECT m.id AS model_id,
+               (SELECT COUNT(*) FROM tool_embeddings te WHERE te.model_id = m.id)
+                   AS tool_embeddings,
+               t.tools,
+               (SELECT COUNT(*) FROM chunk_embeddings ce WHERE ce.model_id = m.id)
+                   AS chunk_embeddings,
+               c.chunks
+        FROM embedding_models m
+        CROSS JOIN (SELECT COUNT(*) AS tools FROM mcp_server_tools) t
+        CROSS JOIN (SELECT COUNT(*) AS chunks FROM chunks) c
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
 /// Get the active model config matching the active model name in config.
 pub async fn get_active_model(
     pool: &PgPool,
@@ -60,3 +115,28 @@ pub async fn get_active_model(
         .find(|c| c.model == config.active_model)
         .ok_or_else(|| EmbeddingError::ModelNotFound(config.active_model.clone()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sql_fragments_use_model_dimension_and_id() {
+        let model = EmbeddingModelConfig {
+            id: Uuid::nil(),
+            provider: "ollama".into(),
+            model: "nomic-embed-text".into(),
+            dimensions: 768,
+            table_name: "chunk_embeddings_ollama_nomic_embed_text".into(),
+            max_input_tokens: 2048,
+        };
+        assert_eq!(
+            model.distance_sql("te.embedding", "$1"),
+            "te.embedding::vector(768) <=> $1::vector(768)"
+        );
+        assert_eq!(
+            model.filter_sql("te"),
+            "te.model_id = '00000000-0000-0000-0000-000000000000'::uuid"
+        );
+    }
+}
@@ -11,7 +11,7 @@ use serde::{Deserialize, Serialize};
 
 use super::config::EmbeddingConfig;
 use super::models::EmbeddingModelConfig;
-use super::{EmbeddingError, EmbeddingResult};
+use super::{EMBEDDING_RETRY, EmbeddingError, EmbeddingResult};
 
 #[derive(Serialize)]
 struct OllamaRequest<'a> {
@@ -33,13 +33,13 @@ async fn embed_one(
 ) -> Result<Vec<f32>, EmbeddingError> {
     let url = format!("{}/api/embeddings", config.ollama_base_url);
 
-    let resp = client
-        .post(&url)
-        .json(&OllamaRequest {
-            model: &model_config.model,
-            prompt: text,
+    let resp = EMBEDDING_RETRY
+        .send(|| {
+            client.post(&url).json(&OllamaRequest {
+                model: &model_config.model,
+                prompt: text,
+            })
         })
-        .send()
         .await
         .map_err(|e| EmbeddingError::Provider(format!("Ollama request failed: {e}")))?;
 
@@ -90,6 +90,7 @@ pub async fn embed_batch(
             text: text.clone(),
             embedding,
             model: model_config.model.clone(),
+            truncated: false,
         });
     }
     Ok(results)
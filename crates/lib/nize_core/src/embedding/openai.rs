@@ -2,18 +2,16 @@
 //
 //! OpenAI embedding provider.
 //!
-//! Calls the OpenAI embeddings API (`/v1/embeddings`) with retry logic
-//! (max 3 attempts, exponential backoff).
+//! Calls the OpenAI embeddings API (`/v1/embeddings`), retrying connection
+//! failures, rate limits and server errors (see [`EMBEDDING_RETRY`]).
 
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use tokio::time::{Duration, sleep};
 
 use super::config::EmbeddingConfig;
 use super::models::EmbeddingModelConfig;
-use super::{EmbeddingError, EmbeddingResult};
+use super::{EMBEDDING_RETRY, EmbeddingError, EmbeddingResult};
 
-const MAX_RETRY_ATTEMPTS: u32 = 3;
 const OPENAI_API_URL: &str = "https://api.openai.com/v1/embeddings";
 
 #[derive(Serialize)]
@@ -44,70 +42,47 @@ async fn embed_one(
         EmbeddingError::Config("OPENAI_API_KEY is required for openai provider".to_string())
     })?;
 
-    let mut last_error = None;
+    let resp = EMBEDDING_RETRY
+        .send(|| {
+            client
+                .post(OPENAI_API_URL)
+                .header("Authorization", format!("Bearer {api_key}"))
+                .json(&OpenAIRequest {
+                    model: &model_config.model,
+                    input: text,
+                    dimensions: model_config.dimensions,
+                })
+        })
+        .await
+        .map_err(|e| EmbeddingError::Provider(format!("OpenAI request failed: {e}")))?;
 
-    for attempt in 0..MAX_RETRY_ATTEMPTS {
-        let result = client
-            .post(OPENAI_API_URL)
-            .header("Authorization", format!("Bearer {api_key}"))
-            .json(&OpenAIRequest {
-                model: &model_config.model,
-                input: text,
-                dimensions: model_config.dimensions,
-            })
-            .send()
-            .await;
-
-        match result {
-            Ok(resp) => {
-                if !resp.status().is_success() {
-                    let status = resp.status();
-                    let body = resp
-                        .text()
-                        .await
-                        .unwrap_or_else(|_| "<no body>".to_string());
-                    last_error = Some(EmbeddingError::Provider(format!(
-                        "OpenAI embeddings failed: {status} {body}"
-                    )));
-                } else {
-                    let data: OpenAIResponse = resp.json().await.map_err(|e| {
-                        EmbeddingError::Provider(format!("OpenAI response parse error: {e}"))
-                    })?;
-
-                    let embedding: Vec<f32> = data
-                        .data
-                        .into_iter()
-                        .next()
-                        .ok_or_else(|| {
-                            EmbeddingError::Provider("OpenAI returned empty data array".to_string())
-                        })?
-                        .embedding
-                        .into_iter()
-                        .map(|v| v as f32)
-                        .collect();
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp
+            .text()
+            .await
+            .unwrap_or_else(|_| "<no body>".to_string());
+        return Err(EmbeddingError::Provider(format!(
+            "OpenAI embeddings failed: {status} {body}"
+        )));
+    }
 
-                    return Ok(embedding);
-                }
-            }
-            Err(e) => {
-                last_error = Some(EmbeddingError::Provider(format!(
-                    "OpenAI request failed: {e}"
-                )));
-            }
-        }
+    let data: OpenAIResponse = resp
+        .json()
+        .await
+        .map_err(|e| EmbeddingError::Provider(format!("OpenAI response parse error: {e}")))?;
 
-        // Exponential backoff before retry
-        if attempt + 1 < MAX_RETRY_ATTEMPTS {
-            let backoff = Duration::from_secs(2u64.pow(attempt + 1));
-            sleep(backoff).await;
-        }
-    }
+    let embedding: Vec<f32> = data
+        .data
+        .into_iter()
+        .next()
+        .ok_or_else(|| EmbeddingError::Provider("OpenAI returned empty data array".to_string()))?
+        .embedding
+        .into_iter()
+        .map(|v| v as f32)
+        .collect();
 
-    Err(last_error.unwrap_or_else(|| {
-        EmbeddingError::Provider(format!(
-            "Failed to embed after {MAX_RETRY_ATTEMPTS} attempts"
-        ))
-    }))
+    Ok(embedding)
 }
 
 /// Embed a batch of texts via OpenAI (one at a time with retry).
@@ -124,6 +99,7 @@ pub async fn embed_batch(
             text: text.clone(),
             embedding,
             model: model_config.model.clone(),
+            truncated: false,
         });
     }
     Ok(results)
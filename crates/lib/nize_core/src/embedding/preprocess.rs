@@ -0,0 +1,104 @@
+// @awa-component: EMB-Preprocess
+//
+//! Text preprocessing applied before every embedding request.
+//!
+//! Inputs are NFKC-normalized, stripped of control characters, collapsed
+//! to single spaces and truncated to the model's input limit
+//! (`embedding_models.max_input_tokens`), so providers never reject them
+//! for length and equivalent texts embed identically.
+//!
+//! Token counts are estimated without a tokenizer: ASCII characters cost a
+//! quarter token and other characters a full token, which over-counts for
+//! the BPE and WordPiece vocabularies our providers use.
+
+use unicode_normalization::UnicodeNormalization;
+
+/// A text ready to embed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prepared {
+    pub text: String,
+    /// Whether the text was cut to fit the model's input limit.
+    pub truncated: bool,
+}
+
+/// Normalize `text` and truncate it to at most `max_tokens` estimated
+/// tokens, preferring to cut at a word boundary.
+pub fn prepare(text: &str, max_tokens: i32) -> Prepared {
+    let normalized = normalize(text);
+    // Estimated cost in quarter tokens.
+    let budget = (max_tokens.max(1) as usize).saturating_mul(4);
+
+    let mut cost = 0;
+    let mut cut = None;
+    let mut last_space = None;
+    for (i, c) in normalized.char_indices() {
+        cost += if c.is_ascii() { 1 } else { 4 };
+        if cost > budget {
+            cut = Some(i);
+            break;
+        }
+        if c == ' ' {
+            last_space = Some(i);
+        }
+    }
+
+    match cut {
+        None => Prepared {
+            text: normalized,
+            truncated: false,
+        },
+        Some(i) => {
+            let end = last_space.unwrap_or(i);
+            Prepared {
+                text: normalized[..end].trim_end().to_string(),
+                truncated: true,
+            }
+        }
+    }
+}
+
+/// NFKC-normalize, drop control characters and collapse whitespace runs
+/// (including newlines) into single spaces.
+fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut pending_space = false;
+    for c in text.nfkc() {
+        if c.is_whitespace() {
+            pending_space = !out.is_empty();
+        } else if !c.is_control() {
+            if pending_space {
+                out.push(' ');
+                pending_space = false;
+            }
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_unicode_and_whitespace() {
+        let p = prepare("  ﬁle\u{0}  name\n\n\tＡＢＣ  ", 100);
+        assert_eq!(p.text, "file name ABC");
+        assert!(!p.truncated);
+    }
+
+    #[test]
+    fn truncates_at_word_boundary() {
+        // Two tokens: eight ASCII characters.
+        let p = prepare("alpha beta gamma", 2);
+        assert_eq!(p.text, "alpha");
+        assert!(p.truncated);
+    }
+
+    #[test]
+    fn truncates_unspaced_text_on_char_boundary() {
+        let p = prepare("日本語のテキスト", 3);
+        assert_eq!(p.text, "日本語");
+        assert!(p.truncated);
+    }
+}
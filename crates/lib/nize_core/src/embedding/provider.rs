@@ -6,28 +6,45 @@ use reqwest::Client;
 
 use super::config::EmbeddingConfig;
 use super::models::EmbeddingModelConfig;
-use super::{EmbeddingError, EmbeddingResult, local, ollama, openai};
+use super::preprocess::{self, Prepared};
+use super::{EmbeddingError, EmbeddingResult, compatible, local, ollama, openai};
 
 /// Generate embeddings for a batch of texts using a specific model.
 ///
+/// Texts are first normalized and truncated to the model's
+/// `max_input_tokens` (see [`preprocess`]); results carry the prepared
+/// text and whether it was truncated.
+///
 /// Dispatches based on `model_config.provider`:
 /// - `"openai"` → OpenAI API with retry
 /// - `"ollama"` → Ollama local API
 /// - `"local"` → deterministic FNV hash
+/// - the OpenAI-compatible provider of `config`, if it has that name
 pub async fn embed_with_model(
     client: &Client,
     config: &EmbeddingConfig,
     texts: &[String],
     model_config: &EmbeddingModelConfig,
 ) -> Result<Vec<EmbeddingResult>, EmbeddingError> {
-    match model_config.provider.as_str() {
-        "local" => Ok(local::embed_batch(
-            texts,
-            model_config.dimensions,
-            &model_config.model,
-        )),
-        "ollama" => ollama::embed_batch(client, config, texts, model_config).await,
-        "openai" => openai::embed_batch(client, config, texts, model_config).await,
-        other => Err(EmbeddingError::UnsupportedProvider(other.to_string())),
+    let prepared: Vec<Prepared> = texts
+        .iter()
+        .map(|t| preprocess::prepare(t, model_config.max_input_tokens))
+        .collect();
+    let inputs: Vec<String> = prepared.iter().map(|p| p.text.clone()).collect();
+
+    let mut results = match model_config.provider.as_str() {
+        "local" => local::embed_batch(&inputs, model_config.dimensions, &model_config.model),
+        "ollama" => ollama::embed_batch(client, config, &inputs, model_config).await?,
+        "openai" => openai::embed_batch(client, config, &inputs, model_config).await?,
+        other => match &config.compatible {
+            Some(endpoint) if endpoint.provider.name == other => {
+                compatible::embed_batch(client, endpoint, &inputs, model_config).await?
+            }
+            _ => return Err(EmbeddingError::UnsupportedProvider(other.to_string())),
+        },
+    };
+    for (result, prepared) in results.iter_mut().zip(&prepared) {
+        result.truncated = prepared.truncated;
     }
+    Ok(results)
 }
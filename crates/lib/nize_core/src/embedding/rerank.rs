@@ -0,0 +1,390 @@
+//! Query-time reranking of vector search candidates.
+//!
+//! Vector similarity finds plausible candidates cheaply but orders them
+//! loosely. When reranking is on for a [`UseCase`], a search fetches the
+//! top `embedding.rerank.candidates` rows by similarity, a cross-encoder
+//! scores each one against the query, and the best `top_k` by that score
+//! are returned.
+//!
+//! # Providers
+//!
+//! - `"tei"` — a local cross-encoder served by text-embeddings-inference
+//!   (`POST {endpoint}/rerank`)
+//! - `"api"` — a Cohere-compatible rerank API (Cohere, Jina, Voyage);
+//!   `embedding.rerank.endpoint` is the full rerank URL
+//!
+//! Each use case is switched on separately and has its own latency budget
+//! (`embedding.rerank.{tools,documents}.*`). A reranker that fails or
+//! overruns its budget is logged and the vector order kept.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::Deserialize;
+use sqlx::PgPool;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use super::EmbeddingError;
+use super::config::EmbeddingConfig;
+use crate::config::cache::ConfigCache;
+use crate::config::resolver;
+use crate::retry::RetryPolicy;
+
+/// Candidates reranked per query when the config value is unusable.
+const DEFAULT_CANDIDATES: usize = 50;
+
+/// A search that can be reranked, with its own switch and budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UseCase {
+    /// MCP tool discovery.
+    Tools,
+    /// Document chunk search.
+    Documents,
+}
+
+impl UseCase {
+    fn key(self) -> &'static str {
+        match self {
+            UseCase::Tools => "tools",
+            UseCase::Documents => "documents",
+        }
+    }
+
+    fn default_budget_ms(self) -> u64 {
+        match self {
+            UseCase::Tools => 300,
+            UseCase::Documents => 1000,
+        }
+    }
+}
+
+/// A configured reranking service.
+#[derive(Debug, Clone)]
+pub enum Reranker {
+    Tei {
+        client: Client,
+        endpoint: String,
+        api_key: Option<String>,
+    },
+    Api {
+        client: Client,
+        endpoint: String,
+        api_key: Option<String>,
+        model: String,
+    },
+}
+
+impl Reranker {
+    /// The reranker selected in config, or `None` when reranking is off.
+    pub async fn configured(
+        pool: &PgPool,
+        cache: &Arc<RwLock<ConfigCache>>,
+        encryption_key: &str,
+    ) -> Result<Option<Self>, EmbeddingError> {
+        let value = async |key: &str| {
+            resolver::get_system_value(pool, cache, key)
+                .await
+                .map_err(|e| EmbeddingError::Config(e.to_string()))
+        };
+        let provider = value("embedding.rerank.provider").await?;
+        if matches!(provider.as_str(), "" | "none") {
+            return Ok(None);
+        }
+        let endpoint = value("embedding.rerank.endpoint")
+            .await?
+            .trim()
+            .trim_end_matches('/')
+            .to_string();
+        if endpoint.is_empty() {
+            return Err(EmbeddingError::Config(
+                "reranker endpoint is not set".into(),
+            ));
+        }
+        let api_key =
+            EmbeddingConfig::resolve_secret_config(pool, "embedding.rerank.apiKey", encryption_key)
+                .await;
+        let client = Client::new();
+        match provider.as_str() {
+            "tei" => Ok(Some(Reranker::Tei {
+                client,
+                endpoint,
+                api_key,
+            })),
+            "api" => Ok(Some(Reranker::Api {
+                client,
+                endpoint,
+                api_key,
+                model: value("embedding.rerank.model").await?,
+            })),
+            other => Err(EmbeddingError::UnsupportedProvider(format!(
+                "reranker {other}"
+            ))),
+        }
+    }
+
+    /// Relevance of each of `documents` to `query`, as `(index, score)`
+    /// pairs; higher is more relevant.
+    pub async fn score(
+        &self,
+        query: &str,
+        documents: &[String],
+    ) -> Result<Vec<(usize, f64)>, EmbeddingError> {
+        match self {
+            Reranker::Tei {
+                client,
+                endpoint,
+                api_key,
+            } => {
+                let body = serde_json::json!({
+                    "query": query,
+                    "texts": documents,
+                    "truncate": true,
+                });
+                let scores: Vec<TeiScore> =
+                    post(client, &format!("{endpoint}/rerank"), api_key, &body).await?;
+                Ok(scores.into_iter().map(|s| (s.index, s.score)).collect())
+            }
+            Reranker::Api {
+                client,
+                endpoint,
+                api_key,
+                model,
+            } => {
+                let body = serde_json::json!({
+                    "model": model,
+                    "query": query,
+                    "documents": documents,
+                    "top_n": documents.len(),
+                });
+                let response: ApiResponse = post(client, endpoint, api_key, &body).await?;
+                Ok(response
+                    .results
+                    .into_iter()
+                    .map(|r| (r.index, r.relevance_score))
+                    .collect())
+            }
+        }
+    }
+}
+
+/// One quick retry: reranking runs within a search's time budget.
+const RERANK_RETRY: RetryPolicy = RetryPolicy::new()
+    .max_attempts(2)
+    .base_delay(Duration::from_millis(50));
+
+async fn post<T: for<'de> Deserialize<'de>>(
+    client: &Client,
+    url: &str,
+    api_key: &Option<String>,
+    body: &serde_json::Value,
+) -> Result<T, EmbeddingError> {
+    let response = RERANK_RETRY
+        .send(|| {
+            let request = client.post(url).json(body);
+            match api_key {
+                Some(key) => request.bearer_auth(key),
+                None => request,
+            }
+        })
+        .await
+        .map_err(|e| EmbeddingError::Provider(format!("reranker request failed: {e}")))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(EmbeddingError::Provider(format!(
+            "reranker returned {status}: {body}"
+        )));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| EmbeddingError::Provider(format!("invalid reranker response: {e}")))
+}
+
+#[derive(Debug, Deserialize)]
+struct TeiScore {
+    index: usize,
+    score: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse {
+    results: Vec<ApiResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiResult {
+    index: usize,
+    relevance_score: f64,
+}
+
+/// Reranking as configured for one use case.
+#[derive(Debug, Clone)]
+pub struct RerankStage {
+    reranker: Reranker,
+    candidates: usize,
+    budget: Duration,
+}
+
+impl RerankStage {
+    /// The stage for `use_case`, or `None` when no reranker is configured
+    /// or the use case has reranking off. A misconfigured reranker is
+    /// logged and treated as off so searches keep working.
+    pub async fn configured(
+        pool: &PgPool,
+        cache: &Arc<RwLock<ConfigCache>>,
+        encryption_key: &str,
+        use_case: UseCase,
+    ) -> Option<Self> {
+        let key = use_case.key();
+        let enabled =
+            resolver::get_system_value(pool, cache, &format!("embedding.rerank.{key}.enabled"))
+                .await
+                .is_ok_and(|v| v.trim() == "true");
+        if !enabled {
+            return None;
+        }
+        let reranker = match Reranker::configured(pool, cache, encryption_key).await {
+            Ok(reranker) => reranker?,
+            Err(e) => {
+                warn!(use_case = key, "reranking skipped: {e}");
+                return None;
+            }
+        };
+        let candidates = number(pool, cache, "embedding.rerank.candidates")
+            .await
+            .map_or(DEFAULT_CANDIDATES, |n| n as usize)
+            .max(1);
+        let budget_ms = number(pool, cache, &format!("embedding.rerank.{key}.timeoutMs"))
+            .await
+            .unwrap_or(use_case.default_budget_ms());
+        Some(Self {
+            reranker,
+            candidates,
+            budget: Duration::from_millis(budget_ms),
+        })
+    }
+
+    /// Rows to fetch from vector search for a request of `top_k`.
+    pub fn candidates(&self, top_k: usize) -> usize {
+        self.candidates.max(top_k)
+    }
+
+    /// Reorder `hits` by relevance to `query` and keep the best `top_k`.
+    /// `text` gives the content scored for a hit and `set_score` records
+    /// its rerank score. On failure or timeout the hits keep their vector
+    /// order.
+    pub async fn rerank<T>(
+        &self,
+        query: &str,
+        mut hits: Vec<T>,
+        top_k: usize,
+        text: impl Fn(&T) -> String,
+        set_score: impl Fn(&mut T, f64),
+    ) -> Vec<T> {
+        if hits.is_empty() {
+            return hits;
+        }
+        let documents: Vec<String> = hits.iter().map(text).collect();
+        match tokio::time::timeout(self.budget, self.reranker.score(query, &documents)).await {
+            Ok(Ok(scores)) => apply_scores(hits, &scores, top_k, set_score),
+            Ok(Err(e)) => {
+                warn!("reranking failed, keeping vector order: {e}");
+                hits.truncate(top_k);
+                hits
+            }
+            Err(_) => {
+                warn!(
+                    budget_ms = self.budget.as_millis() as u64,
+                    "reranking exceeded its budget, keeping vector order"
+                );
+                hits.truncate(top_k);
+                hits
+            }
+        }
+    }
+}
+
+async fn number(pool: &PgPool, cache: &Arc<RwLock<ConfigCache>>, key: &str) -> Option<u64> {
+    resolver::get_system_value(pool, cache, key)
+        .await
+        .ok()
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|n| n.is_finite() && *n >= 0.0)
+        .map(|n| n as u64)
+}
+
+/// Order `hits` by `scores`, best first, and keep `top_k`. Hits the
+/// reranker left unscored follow in their original order; out-of-range and
+/// repeated indexes are ignored.
+fn apply_scores<T>(
+    hits: Vec<T>,
+    scores: &[(usize, f64)],
+    top_k: usize,
+    set_score: impl Fn(&mut T, f64),
+) -> Vec<T> {
+    let mut slots: Vec<Option<T>> = hits.into_iter().map(Some).collect();
+    let mut ranked: Vec<(usize, f64)> = scores
+        .iter()
+        .copied()
+        .filter(|(index, score)| *index < slots.len() && score.is_finite())
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut out = Vec::with_capacity(top_k.min(slots.len()));
+    for (index, score) in ranked {
+        if let Some(mut hit) = slots[index].take() {
+            set_score(&mut hit, score);
+            out.push(hit);
+        }
+    }
+    out.extend(slots.into_iter().flatten());
+    out.truncate(top_k);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hits() -> Vec<(&'static str, Option<f64>)> {
+        vec![("a", None), ("b", None), ("c", None), ("d", None)]
+    }
+
+    fn set(hit: &mut (&'static str, Option<f64>), score: f64) {
+        hit.1 = Some(score);
+    }
+
+    #[test]
+    fn apply_scores_orders_by_score_and_truncates() {
+        let out = apply_scores(hits(), &[(0, 0.1), (2, 0.9), (1, 0.5), (3, 0.3)], 2, set);
+        assert_eq!(out, vec![("c", Some(0.9)), ("b", Some(0.5))]);
+    }
+
+    #[test]
+    fn apply_scores_keeps_unscored_hits_after_scored_ones() {
+        let out = apply_scores(hits(), &[(3, 0.2), (9, 1.0), (3, 0.8)], 4, set);
+        assert_eq!(
+            out,
+            vec![("d", Some(0.8)), ("a", None), ("b", None), ("c", None)]
+        );
+    }
+
+    #[test]
+    fn parses_tei_response() {
+        let body = r#"[{"index":1,"score":0.98},{"index":0,"score":0.02}]"#;
+        let scores: Vec<TeiScore> = serde_json::from_str(body).unwrap();
+        assert_eq!(scores[0].index, 1);
+        assert_eq!(scores[1].score, 0.02);
+    }
+
+    #[test]
+    fn parses_cohere_style_response() {
+        let body = r#"{"id":"x","results":[{"index":2,"relevance_score":0.7}],"meta":{}}"#;
+        let response: ApiResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(response.results[0].index, 2);
+        assert_eq!(response.results[0].relevance_score, 0.7);
+    }
+}
@@ -0,0 +1,614 @@
+//! Prompt/response evaluation harness.
+//!
+//! A [`Suite`] is a list of prompts, each with [`Assertion`]s about the
+//! reply: text it must or must not contain, its length and which tools the
+//! model called. Suites are declared in TOML or JSON like seed fixtures;
+//! built-in suites are embedded at compile time (see [`BUILTIN_SUITES`]).
+//!
+//! Running a case is up to the caller (the API sends each prompt through the
+//! chat app); this module scores the [`EvalOutput`] and stores one
+//! `eval_runs` row per run with an `eval_results` row per case, so scores
+//! can be compared across runs of the same suite and model.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Built-in suites, addressable by name.
+pub const BUILTIN_SUITES: &[(&str, &str)] = &[("smoke", include_str!("fixtures/smoke.toml"))];
+
+/// Maximum number of cases in one suite.
+pub const MAX_CASES: usize = 200;
+
+/// Errors that can occur while loading, scoring or storing evals.
+#[derive(Debug, Error)]
+pub enum EvalError {
+    #[error("{0}")]
+    NotFound(Message),
+
+    #[error("Invalid suite: {0}")]
+    Parse(Message),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// A named list of eval cases.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suite {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Model spec (`provider:model`) used when a run does not name one.
+    #[serde(default)]
+    pub model: Option<String>,
+    pub cases: Vec<EvalCase>,
+}
+
+/// One prompt and the assertions its reply must satisfy.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct EvalCase {
+    pub name: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+}
+
+/// An expectation about a reply. Text matches ignore case unless
+/// `caseSensitive` is set (`case_sensitive` in suite files).
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum Assertion {
+    Contains {
+        value: String,
+        #[serde(default, alias = "case_sensitive")]
+        case_sensitive: bool,
+    },
+    NotContains {
+        value: String,
+        #[serde(default, alias = "case_sensitive")]
+        case_sensitive: bool,
+    },
+    ContainsAny {
+        values: Vec<String>,
+        #[serde(default, alias = "case_sensitive")]
+        case_sensitive: bool,
+    },
+    MaxChars {
+        value: usize,
+    },
+    ToolCalled {
+        name: String,
+    },
+    ToolNotCalled {
+        name: String,
+    },
+    NoToolCalled,
+}
+
+/// What the model produced for one case.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvalOutput {
+    pub content: String,
+    /// Names of the tools called, in call order.
+    #[serde(default)]
+    pub tool_calls: Vec<String>,
+}
+
+/// Outcome of one assertion.
+#[derive(Debug, Clone, Serialize)]
+pub struct AssertionResult {
+    #[serde(flatten)]
+    pub assertion: Assertion,
+    pub passed: bool,
+}
+
+/// Scored result of one case, ready to store.
+#[derive(Debug, Clone)]
+pub struct CaseResult {
+    pub case_name: String,
+    pub prompt: String,
+    pub output: Option<EvalOutput>,
+    pub assertions: Vec<AssertionResult>,
+    /// Fraction of assertions that passed; 0 when the case errored.
+    pub score: f64,
+    pub passed: bool,
+    pub error: Option<String>,
+    pub duration_ms: i32,
+}
+
+/// Row returned by run queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EvalRunRow {
+    pub id: Uuid,
+    pub suite: String,
+    pub model: Option<String>,
+    pub user_id: Option<Uuid>,
+    pub status: String,
+    pub case_count: i32,
+    pub passed_count: i32,
+    pub score: Option<f64>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Row returned by result queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EvalResultRow {
+    pub id: Uuid,
+    pub run_id: Uuid,
+    pub case_name: String,
+    pub prompt: String,
+    pub response: Option<String>,
+    pub tool_calls: serde_json::Value,
+    pub assertions: serde_json::Value,
+    pub score: f64,
+    pub passed: bool,
+    pub error: Option<String>,
+    pub duration_ms: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Resolve a suite by built-in name or file path, parse and validate it.
+///
+/// Files ending in `.json` are parsed as JSON; everything else as TOML.
+pub fn load_suite(name_or_path: &str) -> Result<Suite, EvalError> {
+    if let Some(suite) = builtin_suite(name_or_path) {
+        return suite;
+    }
+
+    let path = Path::new(name_or_path);
+    if !path.exists() {
+        return Err(EvalError::NotFound(
+            Message::new("evals.suite_not_found").arg("name", name_or_path),
+        ));
+    }
+    let content = std::fs::read_to_string(path)?;
+    if path.extension().is_some_and(|ext| ext == "json") {
+        parse_json(&content)
+    } else {
+        parse_toml(&content)
+    }
+}
+
+/// A built-in suite by name, if one exists.
+pub fn builtin_suite(name: &str) -> Option<Result<Suite, EvalError>> {
+    BUILTIN_SUITES
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, content)| parse_toml(content))
+}
+
+fn syntax_error(e: impl std::fmt::Display) -> EvalError {
+    EvalError::Parse(Message::new("evals.syntax_error").arg("error", e))
+}
+
+/// Parse and validate a TOML suite.
+pub fn parse_toml(content: &str) -> Result<Suite, EvalError> {
+    let suite: Suite = toml::from_str(content).map_err(syntax_error)?;
+    suite.validate()
+}
+
+/// Parse and validate a JSON suite.
+pub fn parse_json(content: &str) -> Result<Suite, EvalError> {
+    let suite: Suite = serde_json::from_str(content).map_err(syntax_error)?;
+    suite.validate()
+}
+
+impl Suite {
+    /// Check that the suite has a name and between 1 and [`MAX_CASES`]
+    /// uniquely named cases with non-empty prompts.
+    pub fn validate(self) -> Result<Self, EvalError> {
+        if self.name.trim().is_empty() {
+            return Err(EvalError::Parse(Message::new("evals.suite_name_required")));
+        }
+        if self.cases.is_empty() || self.cases.len() > MAX_CASES {
+            return Err(EvalError::Parse(
+                Message::new("evals.case_count_out_of_range").arg("max", MAX_CASES),
+            ));
+        }
+        for (i, case) in self.cases.iter().enumerate() {
+            if case.name.trim().is_empty() || case.prompt.trim().is_empty() {
+                return Err(EvalError::Parse(
+                    Message::new("evals.case_incomplete").arg("case", i + 1),
+                ));
+            }
+            if self.cases[..i].iter().any(|c| c.name == case.name) {
+                return Err(EvalError::Parse(
+                    Message::new("evals.duplicate_case").arg("name", &case.name),
+                ));
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl Assertion {
+    /// Whether `output` satisfies this assertion.
+    pub fn check(&self, output: &EvalOutput) -> bool {
+        match self {
+            Self::Contains {
+                value,
+                case_sensitive,
+            } => contains(&output.content, value, *case_sensitive),
+            Self::NotContains {
+                value,
+                case_sensitive,
+            } => !contains(&output.content, value, *case_sensitive),
+            Self::ContainsAny {
+                values,
+                case_sensitive,
+            } => values
+                .iter()
+                .any(|v| contains(&output.content, v, *case_sensitive)),
+            Self::MaxChars { value } => output.content.trim().chars().count() <= *value,
+            Self::ToolCalled { name } => output.tool_calls.iter().any(|t| t == name),
+            Self::ToolNotCalled { name } => !output.tool_calls.iter().any(|t| t == name),
+            Self::NoToolCalled => output.tool_calls.is_empty(),
+        }
+    }
+}
+
+fn contains(haystack: &str, needle: &str, case_sensitive: bool) -> bool {
+    if case_sensitive {
+        haystack.contains(needle)
+    } else {
+        haystack.to_lowercase().contains(&needle.to_lowercase())
+    }
+}
+
+impl CaseResult {
+    /// Score a reply against the case's assertions. A case without
+    /// assertions passes as long as the model replied.
+    pub fn scored(case: &EvalCase, output: EvalOutput, duration_ms: i32) -> Self {
+        let assertions: Vec<AssertionResult> = case
+            .assertions
+            .iter()
+            .map(|a| AssertionResult {
+                assertion: a.clone(),
+                passed: a.check(&output),
+            })
+            .collect();
+        let passed_count = assertions.iter().filter(|a| a.passed).count();
+        let score = if assertions.is_empty() {
+            1.0
+        } else {
+            passed_count as f64 / assertions.len() as f64
+        };
+        Self {
+            case_name: case.name.clone(),
+            prompt: case.prompt.clone(),
+            output: Some(output),
+            passed: passed_count == assertions.len(),
+            assertions,
+            score,
+            error: None,
+            duration_ms,
+        }
+    }
+
+    /// A case whose prompt could not be run.
+    pub fn failed(case: &EvalCase, error: String, duration_ms: i32) -> Self {
+        Self {
+            case_name: case.name.clone(),
+            prompt: case.prompt.clone(),
+            output: None,
+            assertions: Vec::new(),
+            score: 0.0,
+            passed: false,
+            error: Some(error),
+            duration_ms,
+        }
+    }
+}
+
+/// Names of cases that passed in `previous` but not in `current`.
+pub fn regressions(previous: &[EvalResultRow], current: &[EvalResultRow]) -> Vec<String> {
+    current
+        .iter()
+        .filter(|c| !c.passed)
+        .filter(|c| {
+            previous
+                .iter()
+                .any(|p| p.case_name == c.case_name && p.passed)
+        })
+        .map(|c| c.case_name.clone())
+        .collect()
+}
+
+const RUN_COLUMNS: &str = "id, suite, model, user_id, status, case_count, passed_count, score, \
+                           error, created_at, completed_at";
+
+const RESULT_COLUMNS: &str = "id, run_id, case_name, prompt, response, tool_calls, assertions, \
+                              score, passed, error, duration_ms, created_at";
+
+/// Start a run of `suite` against `model` (the configured chat model when
+/// `None`).
+pub async fn create_run(
+    pool: &PgPool,
+    user_id: &Uuid,
+    suite: &Suite,
+    model: Option<&str>,
+) -> Result<EvalRunRow, EvalError> {
+    let row = sqlx::query_as::<_, EvalRunRow>(&format!(
+        r#"
+        INSERT INTO eval_runs (id, suite, model, user_id, status, case_count)
+        VALUES ($1, $2, $3, $4, 'running', $5)
+        RETURNING {RUN_COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(&suite.name)
+    .bind(model)
+    .bind(user_id)
+    .bind(suite.cases.len() as i32)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Store the result of one case.
+pub async fn record_result(
+    pool: &PgPool,
+    run_id: &Uuid,
+    result: &CaseResult,
+) -> Result<(), EvalError> {
+    let (response, tool_calls) = match &result.output {
+        Some(o) => (Some(o.content.as_str()), serde_json::json!(o.tool_calls)),
+        None => (None, serde_json::json!([])),
+    };
+    sqlx::query(
+        r#"
+        INSERT INTO eval_results
+            (id, run_id, case_name, prompt, response, tool_calls, assertions, score, passed,
+             error, duration_ms)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        "#,
+    )
+    .bind(uuidv7())
+    .bind(run_id)
+    .bind(&result.case_name)
+    .bind(&result.prompt)
+    .bind(response)
+    .bind(tool_calls)
+    .bind(serde_json::json!(result.assertions))
+    .bind(result.score)
+    .bind(result.passed)
+    .bind(&result.error)
+    .bind(result.duration_ms)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Mark a run finished and compute its aggregate score (the mean case
+/// score). A run with an `error` is marked failed.
+pub async fn finish_run(
+    pool: &PgPool,
+    run_id: &Uuid,
+    error: Option<&str>,
+) -> Result<EvalRunRow, EvalError> {
+    let row = sqlx::query_as::<_, EvalRunRow>(&format!(
+        r#"
+        UPDATE eval_runs
+        SET status = CASE WHEN $2::text IS NULL THEN 'completed' ELSE 'failed' END,
+            error = $2,
+            passed_count = (SELECT count(*) FROM eval_results WHERE run_id = $1 AND passed),
+            score = (SELECT avg(score) FROM eval_results WHERE run_id = $1),
+            completed_at = now()
+        WHERE id = $1
+        RETURNING {RUN_COLUMNS}
+        "#
+    ))
+    .bind(run_id)
+    .bind(error)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| EvalError::NotFound(Message::new("evals.run_not_found").arg("id", run_id)))?;
+    Ok(row)
+}
+
+/// Most recent runs, optionally of one suite, newest first.
+pub async fn list_runs(
+    pool: &PgPool,
+    suite: Option<&str>,
+    limit: i64,
+) -> Result<Vec<EvalRunRow>, EvalError> {
+    let rows = sqlx::query_as::<_, EvalRunRow>(&format!(
+        r#"
+        SELECT {RUN_COLUMNS}
+        FROM eval_runs
+        WHERE ($1::text IS NULL OR suite = $1)
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#
+    ))
+    .bind(suite)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// A run by ID.
+pub async fn get_run(pool: &PgPool, run_id: &Uuid) -> Result<EvalRunRow, EvalError> {
+    sqlx::query_as::<_, EvalRunRow>(&format!(
+        "SELECT {RUN_COLUMNS} FROM eval_runs WHERE id = $1"
+    ))
+    .bind(run_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| EvalError::NotFound(Message::new("evals.run_not_found").arg("id", run_id)))
+}
+
+/// The completed run of the same suite and model preceding `run`, the
+/// baseline regressions are measured against.
+pub async fn previous_run(
+    pool: &PgPool,
+    run: &EvalRunRow,
+) -> Result<Option<EvalRunRow>, EvalError> {
+    let row = sqlx::query_as::<_, EvalRunRow>(&format!(
+        r#"
+        SELECT {RUN_COLUMNS}
+        FROM eval_runs
+        WHERE suite = $1
+          AND model IS NOT DISTINCT FROM $2
+          AND status = 'completed'
+          AND created_at < $3
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#
+    ))
+    .bind(&run.suite)
+    .bind(&run.model)
+    .bind(run.created_at)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Case results of a run, in the order they were recorded.
+pub async fn list_results(pool: &PgPool, run_id: &Uuid) -> Result<Vec<EvalResultRow>, EvalError> {
+    let rows = sqlx::query_as::<_, EvalResultRow>(&format!(
+        r#"
+        SELECT {RESULT_COLUMNS}
+        FROM eval_results
+        WHERE run_id = $1
+        ORDER BY created_at, id
+        "#
+    ))
+    .bind(run_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(content: &str, tools: &[&str]) -> EvalOutput {
+        EvalOutput {
+            content: content.to_string(),
+            tool_calls: tools.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    fn result_row(case_name: &str, passed: bool) -> EvalResultRow {
+        EvalResultRow {
+            id: Uuid::nil(),
+            run_id: Uuid::nil(),
+            case_name: case_name.to_string(),
+            prompt: String::new(),
+            response: None,
+            tool_calls: serde_json::json!([]),
+            assertions: serde_json::json!([]),
+            score: if passed { 1.0 } else { 0.0 },
+            passed,
+            error: None,
+            duration_ms: 0,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn builtin_suites_parse() {
+        for (name, _) in BUILTIN_SUITES {
+            let suite = load_suite(name).unwrap();
+            assert_eq!(suite.name, *name);
+        }
+    }
+
+    #[test]
+    fn validate_rejects_empty_and_duplicate_cases() {
+        assert!(parse_json(r#"{"name": "x", "cases": []}"#).is_err());
+        let dup = r#"{"name": "x", "cases": [
+            {"name": "a", "prompt": "p"},
+            {"name": "a", "prompt": "q"}
+        ]}"#;
+        assert!(parse_json(dup).is_err());
+        assert!(matches!(
+            load_suite("no-such-suite"),
+            Err(EvalError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn assertions_check_text_and_tools() {
+        let out = output("The answer is 51.", &["discover_tools"]);
+        let check = |json: &str| serde_json::from_str::<Assertion>(json).unwrap().check(&out);
+
+        assert!(check(r#"{"type": "contains", "value": "ANSWER"}"#));
+        assert!(!check(
+            r#"{"type": "contains", "value": "ANSWER", "case_sensitive": true}"#
+        ));
+        assert!(!check(
+            r#"{"type": "contains", "value": "ANSWER", "caseSensitive": true}"#
+        ));
+        assert!(check(r#"{"type": "not_contains", "value": "52"}"#));
+        assert!(check(r#"{"type": "contains_any", "values": ["42", "51"]}"#));
+        assert!(!check(r#"{"type": "max_chars", "value": 5}"#));
+        assert!(check(
+            r#"{"type": "tool_called", "name": "discover_tools"}"#
+        ));
+        assert!(check(
+            r#"{"type": "tool_not_called", "name": "execute_tool"}"#
+        ));
+        assert!(!check(r#"{"type": "no_tool_called"}"#));
+    }
+
+    #[test]
+    fn case_score_is_fraction_of_passed_assertions() {
+        let case = EvalCase {
+            name: "c".into(),
+            prompt: "p".into(),
+            assertions: vec![
+                Assertion::Contains {
+                    value: "yes".into(),
+                    case_sensitive: false,
+                },
+                Assertion::NoToolCalled,
+            ],
+        };
+        let half = CaseResult::scored(&case, output("Yes", &["t"]), 10);
+        assert_eq!(half.score, 0.5);
+        assert!(!half.passed);
+
+        let full = CaseResult::scored(&case, output("yes", &[]), 10);
+        assert_eq!(full.score, 1.0);
+        assert!(full.passed);
+
+        let failed = CaseResult::failed(&case, "timeout".into(), 10);
+        assert_eq!(failed.score, 0.0);
+        assert!(!failed.passed);
+    }
+
+    #[test]
+    fn regressions_are_cases_that_stopped_passing() {
+        let previous = vec![result_row("a", true), result_row("b", false)];
+        let current = vec![
+            result_row("a", false),
+            result_row("b", false),
+            result_row("c", false),
+        ];
+        assert_eq!(regressions(&previous, &current), vec!["a".to_string()]);
+    }
+}
@@ -0,0 +1,358 @@
+//! Per-message feedback on assistant replies.
+//!
+//! Users rate an assistant message up or down, optionally with a category
+//! and a comment. Message rows are replaced whenever a conversation is
+//! saved, so feedback is keyed by the UIMessage `id` inside `message_data`
+//! and stores a snapshot of the rated reply and of the user prompt that
+//! preceded it. Admins export all feedback as JSONL to evaluate prompt and
+//! tool changes against real conversations.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Maximum feedback comment length in characters.
+pub const MAX_COMMENT_CHARS: usize = 2000;
+
+/// Categories a piece of feedback can be filed under.
+pub const CATEGORIES: &[&str] = &[
+    "inaccurate",
+    "incomplete",
+    "unhelpful",
+    "tool_error",
+    "formatting",
+    "unsafe",
+    "other",
+];
+
+/// Maximum number of rows returned by one export.
+pub const MAX_EXPORT_ROWS: i64 = 50_000;
+
+/// Errors that can occur in feedback operations.
+#[derive(Debug, Error)]
+pub enum FeedbackError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Not found: {0}")]
+    NotFound(Message),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// Thumbs up or down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rating {
+    Up,
+    Down,
+}
+
+impl Rating {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+        }
+    }
+}
+
+impl FromStr for Rating {
+    type Err = FeedbackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(Self::Up),
+            "down" => Ok(Self::Down),
+            other => Err(FeedbackError::Validation(
+                Message::new("feedback.invalid_rating").arg("rating", other),
+            )),
+        }
+    }
+}
+
+/// Row returned by feedback queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FeedbackRow {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub message_id: String,
+    pub user_id: Uuid,
+    pub rating: String,
+    pub category: Option<String>,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Feedback row with the rated reply and its prompt, for export.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct FeedbackExportRow {
+    #[sqlx(flatten)]
+    pub feedback: FeedbackRow,
+    pub message: serde_json::Value,
+    pub prompt: Option<serde_json::Value>,
+}
+
+/// Feedback submitted for one message.
+#[derive(Debug, Clone)]
+pub struct FeedbackInput {
+    pub rating: Rating,
+    pub category: Option<String>,
+    pub comment: Option<String>,
+}
+
+impl FeedbackInput {
+    /// Validate the category and comment, dropping blank values.
+    pub fn validate(self) -> Result<Self, FeedbackError> {
+        let category = self.category.filter(|c| !c.is_empty());
+        if let Some(c) = &category
+            && !CATEGORIES.contains(&c.as_str())
+        {
+            return Err(FeedbackError::Validation(
+                Message::new("feedback.invalid_category").arg("categories", CATEGORIES.join(", ")),
+            ));
+        }
+        let comment = self
+            .comment
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty());
+        if comment
+            .as_ref()
+            .is_some_and(|c| c.chars().count() > MAX_COMMENT_CHARS)
+        {
+            return Err(FeedbackError::Validation(
+                Message::new("feedback.comment_too_long").arg("max", MAX_COMMENT_CHARS),
+            ));
+        }
+        Ok(Self {
+            rating: self.rating,
+            category,
+            comment,
+        })
+    }
+}
+
+/// Filter for [`export_feedback`].
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub rating: Option<Rating>,
+}
+
+const FEEDBACK_COLUMNS: &str = "id, conversation_id, message_id, user_id, rating, category, \
+                                comment, created_at, updated_at";
+
+/// Record (or replace) the user's feedback on an assistant message.
+///
+/// The caller must already have checked that the conversation is visible to
+/// the user.
+pub async fn set_feedback(
+    pool: &PgPool,
+    user_id: &Uuid,
+    conversation_id: &Uuid,
+    message_id: &str,
+    input: FeedbackInput,
+) -> Result<FeedbackRow, FeedbackError> {
+    let input = input.validate()?;
+    let messages: Vec<serde_json::Value> = sqlx::query_scalar(
+        "SELECT message_data FROM messages WHERE conversation_id = $1 ORDER BY sort_order",
+    )
+    .bind(conversation_id)
+    .fetch_all(pool)
+    .await?;
+    let (message, prompt) = find_rated_message(&messages, message_id)?;
+
+    let row = sqlx::query_as::<_, FeedbackRow>(&format!(
+        r#"
+        INSERT INTO message_feedback
+            (id, conversation_id, message_id, user_id, rating, category, comment, message, prompt)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ON CONFLICT (conversation_id, message_id, user_id) DO UPDATE
+        SET rating = EXCLUDED.rating,
+            category = EXCLUDED.category,
+            comment = EXCLUDED.comment,
+            message = EXCLUDED.message,
+            prompt = EXCLUDED.prompt,
+            updated_at = now()
+        RETURNING {FEEDBACK_COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(conversation_id)
+    .bind(message_id)
+    .bind(user_id)
+    .bind(input.rating.as_str())
+    .bind(&input.category)
+    .bind(&input.comment)
+    .bind(message)
+    .bind(prompt)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Remove the user's feedback on a message. Returns whether any existed.
+pub async fn delete_feedback(
+    pool: &PgPool,
+    user_id: &Uuid,
+    conversation_id: &Uuid,
+    message_id: &str,
+) -> Result<bool, FeedbackError> {
+    let result = sqlx::query(
+        "DELETE FROM message_feedback WHERE conversation_id = $1 AND message_id = $2 AND user_id = $3",
+    )
+    .bind(conversation_id)
+    .bind(message_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// The user's feedback on messages of one conversation.
+pub async fn list_conversation_feedback(
+    pool: &PgPool,
+    user_id: &Uuid,
+    conversation_id: &Uuid,
+) -> Result<Vec<FeedbackRow>, FeedbackError> {
+    let rows = sqlx::query_as::<_, FeedbackRow>(&format!(
+        r#"
+        SELECT {FEEDBACK_COLUMNS}
+        FROM message_feedback
+        WHERE conversation_id = $1 AND user_id = $2
+        ORDER BY created_at
+        "#
+    ))
+    .bind(conversation_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// All feedback matching `filter`, oldest first, capped at
+/// [`MAX_EXPORT_ROWS`].
+pub async fn export_feedback(
+    pool: &PgPool,
+    filter: &ExportFilter,
+) -> Result<Vec<FeedbackExportRow>, FeedbackError> {
+    let rows = sqlx::query_as::<_, FeedbackExportRow>(&format!(
+        r#"
+        SELECT {FEEDBACK_COLUMNS}, message, prompt
+        FROM message_feedback
+        WHERE ($1::timestamptz IS NULL OR created_at >= $1)
+          AND ($2::timestamptz IS NULL OR created_at < $2)
+          AND ($3::text IS NULL OR rating = $3)
+        ORDER BY created_at
+        LIMIT $4
+        "#
+    ))
+    .bind(filter.since)
+    .bind(filter.until)
+    .bind(filter.rating.map(|r| r.as_str()))
+    .bind(MAX_EXPORT_ROWS)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Find the assistant message with UIMessage id `message_id` and the
+/// closest user message before it.
+fn find_rated_message(
+    messages: &[serde_json::Value],
+    message_id: &str,
+) -> Result<(serde_json::Value, Option<serde_json::Value>), FeedbackError> {
+    let index = messages
+        .iter()
+        .position(|m| m.get("id").and_then(|v| v.as_str()) == Some(message_id))
+        .ok_or_else(|| {
+            FeedbackError::NotFound(
+                Message::new("feedback.message_not_found").arg("id", message_id),
+            )
+        })?;
+    let message = &messages[index];
+    if role(message) != Some("assistant") {
+        return Err(FeedbackError::Validation(Message::new(
+            "feedback.not_assistant_message",
+        )));
+    }
+    let prompt = messages[..index]
+        .iter()
+        .rev()
+        .find(|m| role(m) == Some("user"))
+        .cloned();
+    Ok((message.clone(), prompt))
+}
+
+fn role(message: &serde_json::Value) -> Option<&str> {
+    message.get("role").and_then(|v| v.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn input(category: Option<&str>, comment: Option<&str>) -> FeedbackInput {
+        FeedbackInput {
+            rating: Rating::Down,
+            category: category.map(String::from),
+            comment: comment.map(String::from),
+        }
+    }
+
+    #[test]
+    fn rating_parses_up_and_down() {
+        assert_eq!("up".parse::<Rating>().unwrap(), Rating::Up);
+        assert_eq!("down".parse::<Rating>().unwrap(), Rating::Down);
+        assert!("meh".parse::<Rating>().is_err());
+    }
+
+    #[test]
+    fn validate_checks_category_and_trims_comment() {
+        let ok = input(Some("tool_error"), Some("  wrong tool  "))
+            .validate()
+            .unwrap();
+        assert_eq!(ok.category.as_deref(), Some("tool_error"));
+        assert_eq!(ok.comment.as_deref(), Some("wrong tool"));
+
+        let blank = input(Some(""), Some("   ")).validate().unwrap();
+        assert_eq!(blank.category, None);
+        assert_eq!(blank.comment, None);
+
+        assert!(input(Some("boring"), None).validate().is_err());
+        let long = "x".repeat(MAX_COMMENT_CHARS + 1);
+        assert!(input(None, Some(&long)).validate().is_err());
+    }
+
+    #[test]
+    fn finds_assistant_message_and_its_prompt() {
+        let messages = vec![
+            json!({"id": "u1", "role": "user", "parts": [{"type": "text", "text": "first"}]}),
+            json!({"id": "a1", "role": "assistant", "parts": []}),
+            json!({"id": "u2", "role": "user", "parts": [{"type": "text", "text": "second"}]}),
+            json!({"id": "a2", "role": "assistant", "parts": []}),
+        ];
+        let (message, prompt) = find_rated_message(&messages, "a2").unwrap();
+        assert_eq!(message["id"], "a2");
+        assert_eq!(prompt.unwrap()["id"], "u2");
+
+        assert!(matches!(
+            find_rated_message(&messages, "u1"),
+            Err(FeedbackError::Validation(_))
+        ));
+        assert!(matches!(
+            find_rated_message(&messages, "missing"),
+            Err(FeedbackError::NotFound(_))
+        ));
+    }
+}
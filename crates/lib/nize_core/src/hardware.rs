@@ -0,0 +1,484 @@
+//! Hardware profile for gating local models.
+//!
+//! The desktop app probes the machine once per launch ([`probe`]): CPU
+//! features, memory and GPUs usable for inference (Metal on macOS, CUDA
+//! where `nvidia-smi` finds a card). It hands the profile to its API
+//! sidecar, which stores it in `system.hardware` ([`report`]). [`assess`]
+//! turns a profile into [`Capabilities`]: whether local embeddings and
+//! local transcription can run acceptably, and which defaults suit the
+//! machine. On the first run those defaults are written as system values;
+//! afterwards saving a local provider on a machine that cannot run it is
+//! rejected. Server deployments report no profile and are never gated.
+
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::config::{ConfigError, queries};
+use crate::i18n::Message;
+use crate::models::config::ConfigScope;
+
+/// Config key holding the reported profile as JSON.
+pub const HARDWARE_CONFIG_KEY: &str = "system.hardware";
+/// Config key selecting the embedding provider.
+pub const EMBEDDING_PROVIDER_KEY: &str = "embedding.provider";
+/// Config key selecting the transcription provider.
+pub const TRANSCRIPTION_PROVIDER_KEY: &str = "ingest.transcription.provider";
+/// Config key holding the whisper.cpp model file.
+pub const WHISPER_MODEL_KEY: &str = "ingest.transcription.whisperModel";
+/// Config key holding the Ollama server URL.
+const OLLAMA_BASE_URL_KEY: &str = "embedding.ollamaBaseUrl";
+
+const GIB: u64 = 1024 * 1024 * 1024;
+
+/// Least memory for running any local model.
+const MIN_LOCAL_MEMORY: u64 = 4 * GIB;
+
+/// What the desktop app found on its machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HardwareProfile {
+    /// `std::env::consts::OS`, e.g. `macos`.
+    pub os: String,
+    /// `std::env::consts::ARCH`, e.g. `aarch64`.
+    pub arch: String,
+    pub cpu: CpuInfo,
+    pub memory: MemoryInfo,
+    #[serde(default)]
+    pub gpus: Vec<GpuInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CpuInfo {
+    pub brand: String,
+    pub logical_cores: usize,
+    pub physical_cores: Option<usize>,
+    /// Detected SIMD extensions relevant to inference, e.g. `avx2`, `neon`.
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryInfo {
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+/// Compute API a GPU is usable through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuApi {
+    Metal,
+    Cuda,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpuInfo {
+    pub name: String,
+    pub api: GpuApi,
+    /// Dedicated memory; `None` for unified memory shared with the CPU.
+    pub memory_bytes: Option<u64>,
+}
+
+/// Whether a local feature can run, and why not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Gate {
+    pub supported: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Gate {
+    fn supported() -> Self {
+        Self {
+            supported: true,
+            reason: None,
+        }
+    }
+
+    fn unsupported(reason: impl Into<String>) -> Self {
+        Self {
+            supported: false,
+            reason: Some(reason.into()),
+        }
+    }
+}
+
+/// What the machine can run locally, and the defaults chosen for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    pub local_embeddings: Gate,
+    pub local_transcription: Gate,
+    /// Default for `embedding.provider`.
+    pub embedding_provider: &'static str,
+    /// Default for `ingest.transcription.provider`.
+    pub transcription_provider: &'static str,
+    /// Recommended whisper.cpp model file.
+    pub whisper_model: &'static str,
+}
+
+impl HardwareProfile {
+    /// Whether the CPU has SIMD extensions the inference runtimes rely on.
+    fn has_vector_extensions(&self) -> bool {
+        self.cpu.features.iter().any(|f| f == "avx2" || f == "neon")
+    }
+}
+
+/// Decide what `profile` can run locally.
+pub fn assess(profile: &HardwareProfile) -> Capabilities {
+    let total = profile.memory.total_bytes;
+    let local = match local_refusal(profile) {
+        Some(reason) => Gate::unsupported(reason.to_string()),
+        None => Gate::supported(),
+    };
+
+    let whisper_model = if !profile.gpus.is_empty() && total >= 16 * GIB {
+        "ggml-medium.bin"
+    } else if total >= 8 * GIB {
+        "ggml-small.bin"
+    } else {
+        "ggml-base.bin"
+    };
+
+    Capabilities {
+        embedding_provider: if local.supported { "ollama" } else { "local" },
+        transcription_provider: if local.supported { "whisper" } else { "http" },
+        whisper_model,
+        local_embeddings: local.clone(),
+        local_transcription: local,
+    }
+}
+
+/// Why local models cannot run acceptably on `profile`, if they cannot.
+fn local_refusal(profile: &HardwareProfile) -> Option<Message> {
+    let total = profile.memory.total_bytes;
+    if total < MIN_LOCAL_MEMORY {
+        Some(
+            Message::new("hardware.too_little_memory")
+                .arg("memory", format!("{:.1}", total as f64 / GIB as f64))
+                .arg("min", MIN_LOCAL_MEMORY / GIB),
+        )
+    } else if !profile.has_vector_extensions() {
+        Some(Message::new("hardware.no_vector_extensions"))
+    } else {
+        None
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Probe
+// ---------------------------------------------------------------------------
+
+/// Probe this machine. Runs `nvidia-smi` when present, so call it once and
+/// keep the result.
+pub fn probe() -> HardwareProfile {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    system.refresh_cpu_list(sysinfo::CpuRefreshKind::nothing());
+    let brand = system
+        .cpus()
+        .first()
+        .map(|cpu| cpu.brand().trim().to_string())
+        .unwrap_or_default();
+
+    let mut gpus = Vec::new();
+    if cfg!(target_os = "macos") {
+        gpus.push(GpuInfo {
+            name: if cfg!(target_arch = "aarch64") {
+                brand.clone()
+            } else {
+                "Metal".into()
+            },
+            api: GpuApi::Metal,
+            memory_bytes: None,
+        });
+    }
+    gpus.extend(cuda_gpus());
+
+    HardwareProfile {
+        os: std::env::consts::OS.into(),
+        arch: std::env::consts::ARCH.into(),
+        cpu: CpuInfo {
+            brand,
+            logical_cores: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            physical_cores: sysinfo::System::physical_core_count(),
+            features: cpu_features(),
+        },
+        memory: MemoryInfo {
+            total_bytes: system.total_memory(),
+            available_bytes: system.available_memory(),
+        },
+        gpus,
+    }
+}
+
+fn cpu_features() -> Vec<String> {
+    #[allow(unused_mut)]
+    let mut features = Vec::new();
+    #[cfg(target_arch = "x86_64")]
+    {
+        for (name, present) in [
+            ("sse4.2", std::arch::is_x86_feature_detected!("sse4.2")),
+            ("avx", std::arch::is_x86_feature_detected!("avx")),
+            ("avx2", std::arch::is_x86_feature_detected!("avx2")),
+            ("fma", std::arch::is_x86_feature_detected!("fma")),
+            ("avx512f", std::arch::is_x86_feature_detected!("avx512f")),
+        ] {
+            if present {
+                features.push(name.to_string());
+            }
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        for (name, present) in [
+            ("neon", std::arch::is_aarch64_feature_detected!("neon")),
+            (
+                "dotprod",
+                std::arch::is_aarch64_feature_detected!("dotprod"),
+            ),
+            ("fp16", std::arch::is_aarch64_feature_detected!("fp16")),
+        ] {
+            if present {
+                features.push(name.to_string());
+            }
+        }
+    }
+    features
+}
+
+/// NVIDIA GPUs reported by `nvidia-smi`; none when it is not installed.
+fn cuda_gpus() -> Vec<GpuInfo> {
+    let output = Command::new("nvidia-smi")
+        .args([
+            "--query-gpu=name,memory.total",
+            "--format=csv,noheader,nounits",
+        ])
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Parse `nvidia-smi` CSV lines of `name, memory in MiB`.
+fn parse_nvidia_smi(output: &str) -> Vec<GpuInfo> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, memory) = line.rsplit_once(',')?;
+            let name = name.trim();
+            (!name.is_empty()).then(|| GpuInfo {
+                name: name.to_string(),
+                api: GpuApi::Cuda,
+                memory_bytes: memory
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+                    .map(|mib| mib * 1024 * 1024),
+            })
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Stored profile
+// ---------------------------------------------------------------------------
+
+/// Store the desktop's `profile`. On the first report, also set the
+/// providers and whisper model [`assess`] picks, leaving any value an
+/// admin already saved alone.
+pub async fn report(pool: &PgPool, profile: &HardwareProfile) -> Result<(), ConfigError> {
+    let first_run = stored(pool).await?.is_none();
+    let json = serde_json::to_string(profile).map_err(|e| {
+        ConfigError::ValidationError(Message::new("config.serialize_failed").arg("error", e))
+    })?;
+    queries::upsert_value(pool, HARDWARE_CONFIG_KEY, &ConfigScope::System, None, &json).await?;
+    if !first_run {
+        return Ok(());
+    }
+
+    let capabilities = assess(profile);
+    for (key, value) in [
+        (EMBEDDING_PROVIDER_KEY, capabilities.embedding_provider),
+        (
+            TRANSCRIPTION_PROVIDER_KEY,
+            capabilities.transcription_provider,
+        ),
+        (WHISPER_MODEL_KEY, capabilities.whisper_model),
+    ] {
+        if queries::get_value(pool, key, &ConfigScope::System, None)
+            .await?
+            .is_none()
+        {
+            queries::upsert_value(pool, key, &ConfigScope::System, None, value).await?;
+        }
+    }
+    tracing::info!(
+        embedding = capabilities.embedding_provider,
+        transcription = capabilities.transcription_provider,
+        whisper_model = capabilities.whisper_model,
+        "first run: chose defaults for this machine"
+    );
+    Ok(())
+}
+
+/// The reported profile, if a desktop app reported one.
+pub async fn stored(pool: &PgPool) -> Result<Option<HardwareProfile>, ConfigError> {
+    let value = queries::get_value(pool, HARDWARE_CONFIG_KEY, &ConfigScope::System, None).await?;
+    Ok(value.and_then(|v| serde_json::from_str(&v.value).ok()))
+}
+
+/// Why setting `key` to `value` is refused on the reported machine: a
+/// local provider it cannot run. `None` when allowed, including when no
+/// profile was reported. Ollama only counts as local on a loopback URL.
+pub async fn local_provider_refusal(
+    pool: &PgPool,
+    key: &str,
+    value: &str,
+) -> Result<Option<Message>, ConfigError> {
+    let local = match key {
+        TRANSCRIPTION_PROVIDER_KEY => value == "whisper",
+        EMBEDDING_PROVIDER_KEY => value == "ollama" && is_loopback(&ollama_base_url(pool).await?),
+        _ => false,
+    };
+    if !local {
+        return Ok(None);
+    }
+    let Some(profile) = stored(pool).await? else {
+        return Ok(None);
+    };
+    Ok(local_refusal(&profile))
+}
+
+async fn ollama_base_url(pool: &PgPool) -> Result<String, ConfigError> {
+    if let Some(value) =
+        queries::get_value(pool, OLLAMA_BASE_URL_KEY, &ConfigScope::System, None).await?
+    {
+        return Ok(value.value);
+    }
+    Ok(queries::get_definition(pool, OLLAMA_BASE_URL_KEY)
+        .await?
+        .map(|d| d.default_value)
+        .unwrap_or_default())
+}
+
+/// Whether `url` points at this machine.
+fn is_loopback(url: &str) -> bool {
+    let Ok(url) = url::Url::parse(url.trim()) else {
+        return false;
+    };
+    match url.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(total_gib: u64, features: &[&str], gpus: Vec<GpuInfo>) -> HardwareProfile {
+        HardwareProfile {
+            os: "linux".into(),
+            arch: "x86_64".into(),
+            cpu: CpuInfo {
+                brand: "Test CPU".into(),
+                logical_cores: 8,
+                physical_cores: Some(4),
+                features: features.iter().map(|f| f.to_string()).collect(),
+            },
+            memory: MemoryInfo {
+                total_bytes: total_gib * GIB,
+                available_bytes: total_gib * GIB / 2,
+            },
+            gpus,
+        }
+    }
+
+    fn metal() -> GpuInfo {
+        GpuInfo {
+            name: "Apple M2".into(),
+            api: GpuApi::Metal,
+            memory_bytes: None,
+        }
+    }
+
+    #[test]
+    fn small_machines_fall_back_to_remote_providers() {
+        let caps = assess(&profile(2, &["avx2"], vec![]));
+        assert!(!caps.local_embeddings.supported);
+        assert!(!caps.local_transcription.supported);
+        assert!(caps.local_embeddings.reason.unwrap().contains("4 GiB"));
+        assert_eq!(caps.embedding_provider, "local");
+        assert_eq!(caps.transcription_provider, "http");
+        assert_eq!(caps.whisper_model, "ggml-base.bin");
+    }
+
+    #[test]
+    fn cpus_without_vector_extensions_are_gated() {
+        let caps = assess(&profile(16, &["sse4.2"], vec![]));
+        assert!(!caps.local_embeddings.supported);
+        assert!(caps.local_embeddings.reason.unwrap().contains("AVX2"));
+    }
+
+    #[test]
+    fn model_size_follows_memory_and_gpu() {
+        let caps = assess(&profile(8, &["avx2"], vec![]));
+        assert!(caps.local_transcription.supported);
+        assert_eq!(caps.embedding_provider, "ollama");
+        assert_eq!(caps.transcription_provider, "whisper");
+        assert_eq!(caps.whisper_model, "ggml-small.bin");
+
+        assert_eq!(
+            assess(&profile(32, &["neon"], vec![])).whisper_model,
+            "ggml-small.bin"
+        );
+        assert_eq!(
+            assess(&profile(32, &["neon"], vec![metal()])).whisper_model,
+            "ggml-medium.bin"
+        );
+    }
+
+    #[test]
+    fn parses_nvidia_smi_output() {
+        let gpus = parse_nvidia_smi("NVIDIA GeForce RTX 4090, 24564\nbroken\n, 10\n");
+        assert_eq!(
+            gpus,
+            vec![GpuInfo {
+                name: "NVIDIA GeForce RTX 4090".into(),
+                api: GpuApi::Cuda,
+                memory_bytes: Some(24564 * 1024 * 1024),
+            }]
+        );
+    }
+
+    #[test]
+    fn only_loopback_ollama_counts_as_local() {
+        assert!(is_loopback("http://localhost:11434"));
+        assert!(is_loopback("http://127.0.0.1:11434/"));
+        assert!(is_loopback("http://[::1]:11434"));
+        assert!(!is_loopback("http://gpu-box.lan:11434"));
+        assert!(!is_loopback("not a url"));
+    }
+
+    #[test]
+    fn profile_round_trips_as_camel_case_json() {
+        let profile = profile(16, &["avx2"], vec![metal()]);
+        let json = serde_json::to_value(&profile).unwrap();
+        assert_eq!(json["memory"]["totalBytes"], 16 * GIB);
+        assert_eq!(json["gpus"][0]["api"], "metal");
+        let back: HardwareProfile = serde_json::from_value(json).unwrap();
+        assert_eq!(back, profile);
+    }
+}
@@ -0,0 +1,88 @@
+//! Translatable messages.
+//!
+//! Texts shown to users — error messages above all — are identified by
+//! stable keys (`permissions.not_found`, `auth.invalid_credentials`, …)
+//! with named arguments for the template's `{name}` placeholders, instead
+//! of being formatted as English strings where they arise. An argument can
+//! itself be a message, rendered in the same language. The English
+//! templates are built in (`locales/en.json`) and used by `Display`; the
+//! API renders the same message in the caller's language
+//! (`nize_api::i18n`).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// The built-in English bundle: a flat JSON object mapping keys to
+/// templates.
+pub const ENGLISH_BUNDLE: &str = include_str!("../locales/en.json");
+
+/// A translatable message: a stable key plus named arguments for the
+/// template's `{name}` placeholders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub key: &'static str,
+    pub args: Vec<(&'static str, Arg)>,
+}
+
+/// A message argument.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    /// Inserted as is.
+    Text(String),
+    /// Rendered in the language of the enclosing message.
+    Message(Message),
+}
+
+impl Message {
+    pub fn new(key: &'static str) -> Self {
+        Self {
+            key,
+            args: Vec::new(),
+        }
+    }
+
+    /// Add an argument.
+    pub fn arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name, Arg::Text(value.to_string())));
+        self
+    }
+
+    /// Add a message as an argument.
+    pub fn nested(mut self, name: &'static str, message: Message) -> Self {
+        self.args.push((name, Arg::Message(message)));
+        self
+    }
+
+    /// Render with the templates `template` looks up by key; a key without
+    /// a template renders as itself.
+    pub fn render<'a>(&self, template: &dyn Fn(&str) -> Option<&'a str>) -> String {
+        let Some(text) = template(self.key) else {
+            return self.key.to_string();
+        };
+        self.args
+            .iter()
+            .fold(text.to_string(), |text, (name, value)| {
+                let value = match value {
+                    Arg::Text(value) => value.clone(),
+                    Arg::Message(message) => message.render(template),
+                };
+                text.replace(&format!("{{{name}}}"), &value)
+            })
+    }
+}
+
+/// Renders in English.
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(&|key| english().get(key).map(String::as_str)))
+    }
+}
+
+/// The English templates by key.
+pub fn english() -> &'static HashMap<String, String> {
+    static ENGLISH: OnceLock<HashMap<String, String>> = OnceLock::new();
+    ENGLISH.get_or_init(|| {
+        serde_json::from_str(ENGLISH_BUNDLE).expect("built-in English bundle is valid JSON")
+    })
+}
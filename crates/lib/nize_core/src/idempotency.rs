@@ -0,0 +1,244 @@
+//! Idempotency keys for retried requests.
+//!
+//! A client that may retry a `POST` (after a timeout, or when replaying its
+//! offline queue) sends an `Idempotency-Key` header. The first request with
+//! a key [`claim`]s it for the user along with a fingerprint of the request;
+//! once it has been handled its response is stored ([`complete`]) and
+//! returned to every retry until the key expires after [`TTL`]. A retry
+//! whose fingerprint differs is a client bug and is refused, as is one
+//! arriving while the first request is still running. Keys of requests
+//! that failed on the server are [`release`]d so a retry runs again.
+//!
+//! Stored bodies are encrypted with the deployment key and bound to their
+//! user and key: a response can carry a secret shown only once, such as a
+//! new MCP token.
+
+use chrono::Duration;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::mcp::McpError;
+use crate::mcp::secrets;
+
+/// How long a stored response is replayed.
+pub const TTL: Duration = Duration::hours(24);
+
+/// How long a claim without a response blocks its key. A request still
+/// running after this long is assumed to have died with its process.
+pub const CLAIM_TIMEOUT: Duration = Duration::minutes(10);
+
+/// Longest accepted key.
+pub const MAX_KEY_LEN: usize = 255;
+
+/// Errors that can occur while claiming or completing a key.
+#[derive(Debug, Error)]
+pub enum IdempotencyError {
+    #[error("Stored response cannot be read: {0}")]
+    Sealed(#[from] McpError),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// A response stored for replay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub body: Vec<u8>,
+}
+
+/// Outcome of [`claim`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Claim {
+    /// The key is new: handle the request, then [`complete`] or
+    /// [`release`] it.
+    Claimed,
+    /// The key's first request is still running.
+    InProgress,
+    /// The key was used for a different request.
+    Mismatch,
+    /// The key's first request finished with this response.
+    Completed(StoredResponse),
+}
+
+/// Whether `key` is acceptable: 1 to [`MAX_KEY_LEN`] visible ASCII
+/// characters.
+pub fn is_valid_key(key: &str) -> bool {
+    !key.is_empty() && key.len() <= MAX_KEY_LEN && key.bytes().all(|b| b.is_ascii_graphic())
+}
+
+/// Fingerprint of a request: what must match for a retry to count as the
+/// same request.
+pub fn fingerprint(method: &str, path: &str, workspace: Option<&str>, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        method.as_bytes(),
+        path.as_bytes(),
+        workspace.unwrap_or("").as_bytes(),
+    ] {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    hasher.update(body);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Associated data binding a stored body to its user and key.
+fn body_aad(user_id: &Uuid, key: &str) -> Vec<u8> {
+    format!("idempotency_keys.sealed_body\0{user_id}\0{key}").into_bytes()
+}
+
+/// Claim `key` for a request of `user_id` with `fingerprint`, or report
+/// what became of its earlier use. Expired keys are purged on the way.
+pub async fn claim(
+    pool: &PgPool,
+    encryption_key: &str,
+    user_id: &Uuid,
+    key: &str,
+    fingerprint: &str,
+) -> Result<Claim, IdempotencyError> {
+    sqlx::query(
+        r#"
+        DELETE FROM idempotency_keys
+        WHERE expires_at < now()
+           OR (user_id = $1 AND key = $2 AND status IS NULL
+               AND created_at < now() - make_interval(secs => $3))
+        "#,
+    )
+    .bind(user_id)
+    .bind(key)
+    .bind(CLAIM_TIMEOUT.num_seconds() as f64)
+    .execute(pool)
+    .await?;
+
+    let claimed = sqlx::query(
+        r#"
+        INSERT INTO idempotency_keys (user_id, key, fingerprint, expires_at)
+        VALUES ($1, $2, $3, now() + make_interval(secs => $4))
+        ON CONFLICT (user_id, key) DO NOTHING
+        "#,
+    )
+    .bind(user_id)
+    .bind(key)
+    .bind(fingerprint)
+    .bind(TTL.num_seconds() as f64)
+    .execute(pool)
+    .await?
+    .rows_affected()
+        == 1;
+    if claimed {
+        return Ok(Claim::Claimed);
+    }
+
+    let row = sqlx::query_as::<_, (String, Option<i16>, Option<String>, Option<String>)>(
+        "SELECT fingerprint, status, content_type, sealed_body FROM idempotency_keys \
+         WHERE user_id = $1 AND key = $2",
+    )
+    .bind(user_id)
+    .bind(key)
+    .fetch_optional(pool)
+    .await?;
+    Ok(match row {
+        // Released between the insert and this read: its request is
+        // still being retried by someone.
+        None => Claim::InProgress,
+        Some((stored, _, _, _)) if stored != fingerprint => Claim::Mismatch,
+        Some((_, None, _, _)) => Claim::InProgress,
+        Some((_, Some(status), content_type, sealed)) => Claim::Completed(StoredResponse {
+            status: status as u16,
+            content_type,
+            body: match sealed {
+                Some(sealed) => {
+                    secrets::decrypt_bytes(&sealed, encryption_key, &body_aad(user_id, key))?
+                }
+                None => Vec::new(),
+            },
+        }),
+    })
+}
+
+/// Store the response to the request that claimed `key`.
+pub async fn complete(
+    pool: &PgPool,
+    encryption_key: &str,
+    user_id: &Uuid,
+    key: &str,
+    response: &StoredResponse,
+) -> Result<(), IdempotencyError> {
+    let sealed = secrets::encrypt_bytes(&response.body, encryption_key, &body_aad(user_id, key))?;
+    sqlx::query(
+        "UPDATE idempotency_keys SET status = $3, content_type = $4, sealed_body = $5 \
+         WHERE user_id = $1 AND key = $2",
+    )
+    .bind(user_id)
+    .bind(key)
+    .bind(response.status as i16)
+    .bind(response.content_type.as_deref())
+    .bind(&sealed)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Give up `key` without a response, so a retry runs the request again.
+pub async fn release(pool: &PgPool, user_id: &Uuid, key: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM idempotency_keys WHERE user_id = $1 AND key = $2 AND status IS NULL")
+        .bind(user_id)
+        .bind(key)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_visible_ascii() {
+        assert!(is_valid_key("3f2b9c1e-7a4d-4e0b-9d5a-1c2b3d4e5f60"));
+        assert!(!is_valid_key(""));
+        assert!(!is_valid_key("has space"));
+        assert!(!is_valid_key("ümlaut"));
+        assert!(!is_valid_key(&"k".repeat(MAX_KEY_LEN + 1)));
+    }
+
+    #[test]
+    fn stored_bodies_are_bound_to_user_and_key() {
+        let user = Uuid::new_v4();
+        let sealed =
+            secrets::encrypt_bytes(b"{\"token\":\"nize_mcp_x\"}", "k", &body_aad(&user, "a"))
+                .unwrap();
+        assert!(!sealed.contains("nize_mcp_x"));
+        assert_eq!(
+            secrets::decrypt_bytes(&sealed, "k", &body_aad(&user, "a")).unwrap(),
+            b"{\"token\":\"nize_mcp_x\"}"
+        );
+        assert!(secrets::decrypt_bytes(&sealed, "k", &body_aad(&user, "b")).is_err());
+        assert!(secrets::decrypt_bytes(&sealed, "k", &body_aad(&Uuid::new_v4(), "a")).is_err());
+    }
+
+    #[test]
+    fn fingerprint_covers_every_part() {
+        let base = fingerprint("POST", "/conversations", None, b"{}");
+        assert_eq!(base, fingerprint("POST", "/conversations", None, b"{}"));
+        assert_eq!(base.len(), 64);
+        assert_ne!(
+            base,
+            fingerprint("POST", "/conversations", None, b"{\"a\":1}")
+        );
+        assert_ne!(base, fingerprint("POST", "/notes", None, b"{}"));
+        assert_ne!(
+            base,
+            fingerprint("POST", "/conversations", Some("w1"), b"{}")
+        );
+        // Parts are length-prefixed, so moving bytes between them changes it.
+        assert_ne!(
+            fingerprint("POST", "/a", Some("b"), b""),
+            fingerprint("POST", "/ab", None, b"")
+        );
+    }
+}
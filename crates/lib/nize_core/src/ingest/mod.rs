@@ -0,0 +1,346 @@
+//! Text extraction for uploaded documents.
+//!
+//! [`extract`] turns a stored file into pages of text:
+//!
+//! - text formats are decoded as UTF-8 (one page);
+//! - PDFs are read page by page with `pdftotext` (poppler); pages with no
+//!   text layer are treated as scanned and rendered for OCR;
+//! - images are passed to OCR.
+//!
+//! OCR ([`ocr`]) only runs when `ingest.ocr.enabled` is set; otherwise
+//! scanned pages and images yield no text. [`chunk_pages`] then splits the
+//! pages into chunks, carrying each page's number and OCR confidence.
+//!
+//! Audio is transcribed instead ([`transcribe`], behind
+//! `ingest.transcription.enabled`) and [`chunk_segments`] packs the timed
+//! segments into chunks that keep their start and end offsets.
+//! [`extract_chunks`] picks the right path for a file.
+
+pub mod ocr;
+pub mod pdf;
+pub mod transcribe;
+
+use std::path::Path;
+use std::time::Duration;
+
+use sqlx::PgPool;
+use thiserror::Error;
+
+use crate::config::queries;
+use crate::mcp::secrets;
+use crate::models::config::ConfigScope;
+use crate::notes::chunker;
+use ocr::OcrProvider;
+use transcribe::{Segment, Transcriber};
+
+/// Longest a single external tool (pdftotext, tesseract, …) may run.
+pub const TOOL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Errors that can occur while extracting text.
+#[derive(Debug, Error)]
+pub enum IngestError {
+    #[error("Extraction failed: {0}")]
+    Extract(String),
+
+    #[error("OCR failed: {0}")]
+    Ocr(String),
+
+    #[error("Ingest misconfigured: {0}")]
+    Config(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Text of one page (or of the whole file for unpaged formats).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractedPage {
+    /// 1-based page number; `None` for unpaged formats.
+    pub page: Option<i32>,
+    pub text: String,
+    /// Mean OCR word confidence in `0.0..=1.0`; `None` when the text was
+    /// not produced by OCR.
+    pub ocr_confidence: Option<f32>,
+}
+
+/// A chunk of extracted text, ready to store.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractedChunk {
+    pub page: Option<i32>,
+    pub content: String,
+    pub ocr_confidence: Option<f32>,
+    /// Offsets into the recording, for transcribed audio.
+    pub start_ms: Option<i64>,
+    pub end_ms: Option<i64>,
+}
+
+/// Whether a MIME type is transcribed as audio.
+pub fn is_audio_mime(mime_type: &str) -> bool {
+    essence(mime_type).starts_with("audio/")
+}
+
+/// Whether a MIME type is decoded as plain text.
+pub fn is_text_mime(mime_type: &str) -> bool {
+    let mime = essence(mime_type);
+    mime.starts_with("text/")
+        || matches!(
+            mime.as_str(),
+            "application/json" | "application/xml" | "application/x-yaml" | "application/yaml"
+        )
+}
+
+/// MIME type without parameters, lowercased.
+fn essence(mime_type: &str) -> String {
+    mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Extract the file at `path` and split it into chunks of at most
+/// `max_chars`: audio through `transcriber`, everything else through
+/// [`extract`]. Disabled providers are `None`.
+pub async fn extract_chunks(
+    path: &Path,
+    filename: &str,
+    mime_type: &str,
+    ocr: Option<&OcrProvider>,
+    transcriber: Option<&Transcriber>,
+    max_chars: usize,
+) -> Result<Vec<ExtractedChunk>, IngestError> {
+    if is_audio_mime(mime_type) {
+        let Some(transcriber) = transcriber else {
+            return Ok(Vec::new());
+        };
+        let segments = transcriber
+            .transcribe(path, filename, &essence(mime_type))
+            .await?;
+        return Ok(chunk_segments(&segments, max_chars));
+    }
+    let pages = extract(path, mime_type, ocr).await?;
+    Ok(chunk_pages(&pages, max_chars))
+}
+
+/// Extract the text of the file at `path`. `ocr` is the OCR provider to
+/// use for images and scanned PDF pages, if OCR is enabled.
+pub async fn extract(
+    path: &Path,
+    mime_type: &str,
+    ocr: Option<&OcrProvider>,
+) -> Result<Vec<ExtractedPage>, IngestError> {
+    let mime = essence(mime_type);
+    if is_text_mime(&mime) {
+        let bytes = tokio::fs::read(path).await?;
+        return Ok(vec![ExtractedPage {
+            page: None,
+            text: String::from_utf8_lossy(&bytes).into_owned(),
+            ocr_confidence: None,
+        }]);
+    }
+    if mime == "application/pdf" {
+        return extract_pdf(path, ocr).await;
+    }
+    if mime.starts_with("image/") {
+        let Some(ocr) = ocr else {
+            return Ok(Vec::new());
+        };
+        let result = ocr.recognize(path, &mime).await?;
+        return Ok(vec![ExtractedPage {
+            page: None,
+            text: result.text,
+            ocr_confidence: Some(result.confidence),
+        }]);
+    }
+    Ok(Vec::new())
+}
+
+/// Extract a PDF's text layer, OCRing pages that have none.
+async fn extract_pdf(
+    path: &Path,
+    ocr: Option<&OcrProvider>,
+) -> Result<Vec<ExtractedPage>, IngestError> {
+    let mut pages = Vec::new();
+    for (index, text) in pdf::page_texts(path).await?.into_iter().enumerate() {
+        let page = index as i32 + 1;
+        if !pdf::is_image_only(&text) {
+            pages.push(ExtractedPage {
+                page: Some(page),
+                text,
+                ocr_confidence: None,
+            });
+            continue;
+        }
+        let Some(ocr) = ocr else {
+            continue;
+        };
+        let image = pdf::render_page(path, page).await?;
+        let result = ocr.recognize(image.path(), "image/png").await?;
+        pages.push(ExtractedPage {
+            page: Some(page),
+            text: result.text,
+            ocr_confidence: Some(result.confidence),
+        });
+    }
+    Ok(pages)
+}
+
+/// Split pages into chunks of at most `max_chars`. Chunks never span
+/// pages, so each keeps its page's number and OCR confidence.
+pub fn chunk_pages(pages: &[ExtractedPage], max_chars: usize) -> Vec<ExtractedChunk> {
+    pages
+        .iter()
+        .flat_map(|page| {
+            chunker::chunk_markdown(&page.text, max_chars)
+                .into_iter()
+                .map(|content| ExtractedChunk {
+                    page: page.page,
+                    content,
+                    ocr_confidence: page.ocr_confidence,
+                    start_ms: None,
+                    end_ms: None,
+                })
+        })
+        .collect()
+}
+
+/// Pack transcript segments greedily into chunks of at most `max_chars`.
+/// Segments are never split, so a chunk spans from its first segment's
+/// start to its last segment's end; a single overlong segment becomes its
+/// own chunk.
+pub fn chunk_segments(segments: &[Segment], max_chars: usize) -> Vec<ExtractedChunk> {
+    let mut chunks: Vec<ExtractedChunk> = Vec::new();
+    for segment in segments {
+        if let Some(last) = chunks.last_mut() {
+            let len = last.content.chars().count() + 1 + segment.text.chars().count();
+            if len <= max_chars {
+                last.content.push(' ');
+                last.content.push_str(&segment.text);
+                last.end_ms = Some(segment.end_ms);
+                continue;
+            }
+        }
+        chunks.push(ExtractedChunk {
+            page: None,
+            content: segment.text.clone(),
+            ocr_confidence: None,
+            start_ms: Some(segment.start_ms),
+            end_ms: Some(segment.end_ms),
+        });
+    }
+    chunks
+}
+
+/// Run an external tool, killing it after `timeout`, and return its
+/// stdout.
+async fn run_tool(
+    command: &mut tokio::process::Command,
+    name: &str,
+    timeout: Duration,
+) -> Result<Vec<u8>, IngestError> {
+    command.kill_on_drop(true);
+    let output = match tokio::time::timeout(timeout, command.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(IngestError::Config(format!("{name} is not installed")));
+        }
+        Ok(Err(e)) => return Err(e.into()),
+        Err(_) => {
+            return Err(IngestError::Extract(format!(
+                "{name} timed out after {}s",
+                timeout.as_secs()
+            )));
+        }
+    };
+    if !output.status.success() {
+        return Err(IngestError::Extract(format!(
+            "{name} failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Decrypt a secret system config value; empty when unset.
+async fn secret_value(
+    pool: &PgPool,
+    key: &str,
+    encryption_key: &str,
+) -> Result<String, IngestError> {
+    let Some(row) = queries::get_value(pool, key, &ConfigScope::System, None)
+        .await
+        .map_err(|e| IngestError::Config(e.to_string()))?
+    else {
+        return Ok(String::new());
+    };
+    if row.value.is_empty() {
+        return Ok(String::new());
+    }
+    secrets::decrypt(&row.value, encryption_key).map_err(|e| IngestError::Config(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_mimes_ignore_parameters() {
+        assert!(is_text_mime("text/markdown; charset=utf-8"));
+        assert!(is_text_mime("Application/JSON"));
+        assert!(!is_text_mime("application/pdf"));
+        assert!(!is_text_mime("image/png"));
+    }
+
+    #[test]
+    fn chunks_keep_their_page_and_confidence() {
+        let pages = vec![
+            ExtractedPage {
+                page: Some(1),
+                text: "first page".into(),
+                ocr_confidence: None,
+            },
+            ExtractedPage {
+                page: Some(2),
+                text: "scanned\n\nsecond page".into(),
+                ocr_confidence: Some(0.8),
+            },
+        ];
+        let chunks = chunk_pages(&pages, 12);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].page, Some(1));
+        assert_eq!(chunks[0].ocr_confidence, None);
+        assert_eq!(chunks[2].content, "second page");
+        assert_eq!(chunks[2].page, Some(2));
+        assert_eq!(chunks[2].ocr_confidence, Some(0.8));
+    }
+
+    #[test]
+    fn segments_pack_into_timed_chunks() {
+        let segment = |start_ms, end_ms, text: &str| Segment {
+            start_ms,
+            end_ms,
+            text: text.into(),
+        };
+        let chunks = chunk_segments(
+            &[
+                segment(0, 1000, "Hello all."),
+                segment(1000, 2000, "Agenda."),
+                segment(2000, 5000, "First item is long."),
+            ],
+            20,
+        );
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].content, "Hello all. Agenda.");
+        assert_eq!(
+            (chunks[0].start_ms, chunks[0].end_ms),
+            (Some(0), Some(2000))
+        );
+        assert_eq!(
+            (chunks[1].start_ms, chunks[1].end_ms),
+            (Some(2000), Some(5000))
+        );
+        assert!(is_audio_mime("audio/mpeg"));
+    }
+}
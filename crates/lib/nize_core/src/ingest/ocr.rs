@@ -0,0 +1,214 @@
+//! OCR providers for images and scanned PDF pages.
+//!
+//! - `"tesseract"` — the local `tesseract` binary, read back as TSV so
+//!   word confidences come with the text.
+//! - `"http"` — a cloud OCR service. The image is POSTed as the request
+//!   body (with its `Content-Type` and a bearer API key) and the service
+//!   answers `{"text": "...", "confidence": 0.93}`, confidence in `0..=1`.
+//!
+//! Selected by `ingest.ocr.provider`; nothing runs unless
+//! `ingest.ocr.enabled` is `true`.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use reqwest::Client;
+use serde::Deserialize;
+use sqlx::PgPool;
+use tokio::process::Command;
+use tokio::sync::RwLock;
+
+use crate::config::cache::ConfigCache;
+use crate::config::resolver;
+
+use super::{IngestError, TOOL_TIMEOUT, run_tool, secret_value};
+
+/// Config key switching OCR on.
+pub const ENABLED_CONFIG_KEY: &str = "ingest.ocr.enabled";
+
+/// Recognized text of one image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OcrResult {
+    pub text: String,
+    /// Mean word confidence in `0.0..=1.0` (`0.0` when nothing was read).
+    pub confidence: f32,
+}
+
+/// A configured OCR provider.
+#[derive(Debug, Clone)]
+pub enum OcrProvider {
+    Tesseract {
+        /// Binary to run.
+        binary: String,
+        /// Tesseract language codes, `+`-joined (e.g. `eng+deu`).
+        languages: String,
+    },
+    Http {
+        client: Client,
+        endpoint: String,
+        api_key: String,
+    },
+}
+
+impl OcrProvider {
+    /// The provider selected in config, or `None` when OCR is disabled.
+    pub async fn configured(
+        pool: &PgPool,
+        cache: &Arc<RwLock<ConfigCache>>,
+        encryption_key: &str,
+    ) -> Result<Option<Self>, IngestError> {
+        let value = async |key: &str| {
+            resolver::get_system_value(pool, cache, key)
+                .await
+                .map_err(|e| IngestError::Config(e.to_string()))
+        };
+        if value(ENABLED_CONFIG_KEY).await? != "true" {
+            return Ok(None);
+        }
+        match value("ingest.ocr.provider").await?.as_str() {
+            "tesseract" => Ok(Some(OcrProvider::Tesseract {
+                binary: value("ingest.ocr.tesseractPath").await?,
+                languages: value("ingest.ocr.languages").await?,
+            })),
+            "http" => {
+                let endpoint = value("ingest.ocr.endpoint").await?;
+                if endpoint.trim().is_empty() {
+                    return Err(IngestError::Config("OCR endpoint is not set".into()));
+                }
+                Ok(Some(OcrProvider::Http {
+                    client: Client::builder()
+                        .timeout(TOOL_TIMEOUT)
+                        .build()
+                        .map_err(|e| IngestError::Config(e.to_string()))?,
+                    endpoint,
+                    api_key: secret_value(pool, "ingest.ocr.apiKey", encryption_key).await?,
+                }))
+            }
+            other => Err(IngestError::Config(format!(
+                "unknown OCR provider: {other}"
+            ))),
+        }
+    }
+
+    /// Recognize the text in the image at `path`.
+    pub async fn recognize(&self, path: &Path, mime_type: &str) -> Result<OcrResult, IngestError> {
+        match self {
+            OcrProvider::Tesseract { binary, languages } => {
+                let mut command = Command::new(binary);
+                command.arg(path).arg("stdout").arg("tsv");
+                if !languages.trim().is_empty() {
+                    command.args(["-l", languages.trim()]);
+                }
+                let stdout = run_tool(&mut command, "tesseract", TOOL_TIMEOUT).await?;
+                Ok(parse_tesseract_tsv(&String::from_utf8_lossy(&stdout)))
+            }
+            OcrProvider::Http {
+                client,
+                endpoint,
+                api_key,
+            } => {
+                #[derive(Deserialize)]
+                struct Response {
+                    text: String,
+                    #[serde(default)]
+                    confidence: f32,
+                }
+
+                let mut request = client
+                    .post(endpoint)
+                    .header("content-type", mime_type)
+                    .body(tokio::fs::read(path).await?);
+                if !api_key.is_empty() {
+                    request = request.bearer_auth(api_key);
+                }
+                let response = request
+                    .send()
+                    .await
+                    .map_err(|e| IngestError::Ocr(e.to_string()))?;
+                let status = response.status();
+                if !status.is_success() {
+                    let body = response.text().await.unwrap_or_default();
+                    return Err(IngestError::Ocr(format!(
+                        "OCR service returned {status}: {body}"
+                    )));
+                }
+                let body: Response = response
+                    .json()
+                    .await
+                    .map_err(|e| IngestError::Ocr(e.to_string()))?;
+                Ok(OcrResult {
+                    text: body.text,
+                    confidence: body.confidence.clamp(0.0, 1.0),
+                })
+            }
+        }
+    }
+}
+
+/// Rebuild text from tesseract's TSV output: words joined by spaces,
+/// lines by newlines, paragraphs and blocks by blank lines. Confidence is
+/// the mean word confidence.
+fn parse_tesseract_tsv(tsv: &str) -> OcrResult {
+    let mut text = String::new();
+    let mut last_line: Option<(&str, &str, &str)> = None;
+    let mut last_par: Option<(&str, &str)> = None;
+    let (mut sum, mut words) = (0.0f32, 0u32);
+
+    // level page block par line word left top width height conf text
+    for row in tsv.lines().skip(1) {
+        let cols: Vec<&str> = row.split('\t').collect();
+        if cols.len() < 12 || cols[0] != "5" {
+            continue;
+        }
+        let word = cols[11].trim();
+        let Ok(conf) = cols[10].parse::<f32>() else {
+            continue;
+        };
+        if word.is_empty() || conf < 0.0 {
+            continue;
+        }
+        let par = (cols[2], cols[3]);
+        let line = (cols[2], cols[3], cols[4]);
+        if last_par.is_some_and(|p| p != par) {
+            text.push_str("\n\n");
+        } else if last_line.is_some_and(|l| l != line) {
+            text.push('\n');
+        } else if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(word);
+        last_par = Some(par);
+        last_line = Some(line);
+        sum += conf;
+        words += 1;
+    }
+
+    OcrResult {
+        text,
+        confidence: if words == 0 {
+            0.0
+        } else {
+            (sum / words as f32 / 100.0).clamp(0.0, 1.0)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tesseract_tsv_keeps_layout_and_averages_word_confidence() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+            1\t1\t0\t0\t0\t0\t0\t0\t100\t100\t-1\t\n\
+            5\t1\t1\t1\t1\t1\t0\t0\t10\t10\t90\tHello\n\
+            5\t1\t1\t1\t1\t2\t0\t0\t10\t10\t80\tworld\n\
+            5\t1\t1\t1\t2\t1\t0\t0\t10\t10\t70\tagain\n\
+            5\t1\t2\t1\t1\t1\t0\t0\t10\t10\t60\tNext\n\
+            5\t1\t2\t1\t1\t2\t0\t0\t10\t10\t-1\t \n";
+        let result = parse_tesseract_tsv(tsv);
+        assert_eq!(result.text, "Hello world\nagain\n\nNext");
+        assert!((result.confidence - 0.75).abs() < 1e-6);
+        assert_eq!(parse_tesseract_tsv("").confidence, 0.0);
+    }
+}
@@ -0,0 +1,72 @@
+//! PDF reading via the poppler command-line tools (`pdftotext`,
+//! `pdftoppm`).
+
+use std::path::Path;
+
+use tokio::process::Command;
+
+use super::{IngestError, TOOL_TIMEOUT, run_tool};
+
+/// Pages with fewer non-whitespace characters than this are treated as
+/// scanned: stray page numbers or headers don't count as a text layer.
+const MIN_TEXT_CHARS: usize = 20;
+
+/// Resolution pages are rendered at for OCR.
+const RENDER_DPI: u32 = 300;
+
+/// The text layer of each page, in order.
+pub async fn page_texts(path: &Path) -> Result<Vec<String>, IngestError> {
+    let stdout = run_tool(
+        Command::new("pdftotext").arg("-layout").arg(path).arg("-"),
+        "pdftotext",
+        TOOL_TIMEOUT,
+    )
+    .await?;
+    Ok(split_pages(&String::from_utf8_lossy(&stdout)))
+}
+
+/// Split `pdftotext` output, which ends every page with a form feed.
+fn split_pages(output: &str) -> Vec<String> {
+    let mut pages: Vec<String> = output.split('\x0c').map(str::to_string).collect();
+    if pages.last().is_some_and(|last| last.trim().is_empty()) {
+        pages.pop();
+    }
+    pages
+}
+
+/// Whether a page's text layer is too thin to be the page's content.
+pub fn is_image_only(text: &str) -> bool {
+    text.chars().filter(|c| !c.is_whitespace()).count() < MIN_TEXT_CHARS
+}
+
+/// Render one page (1-based) to a PNG temp file.
+pub async fn render_page(path: &Path, page: i32) -> Result<tempfile::NamedTempFile, IngestError> {
+    let image = tempfile::Builder::new().suffix(".png").tempfile()?;
+    // pdftoppm appends ".png" to the output root itself.
+    let root = image.path().with_extension("");
+    run_tool(
+        Command::new("pdftoppm")
+            .args(["-png", "-singlefile", "-r", &RENDER_DPI.to_string()])
+            .args(["-f", &page.to_string(), "-l", &page.to_string()])
+            .arg(path)
+            .arg(&root),
+        "pdftoppm",
+        TOOL_TIMEOUT,
+    )
+    .await?;
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_split_on_form_feeds() {
+        let pages = split_pages("first page\n\x0c\x0cthird page\n\x0c");
+        assert_eq!(pages, vec!["first page\n", "", "third page\n"]);
+        assert!(!is_image_only("A page with a real text layer on it."));
+        assert!(is_image_only(&pages[1]));
+        assert!(is_image_only("  - 12 -  \n"));
+    }
+}
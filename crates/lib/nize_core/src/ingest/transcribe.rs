@@ -0,0 +1,365 @@
+//! Speech-to-text for uploaded audio.
+//!
+//! - `"whisper"` — a local whisper.cpp build (`whisper-cli`). Audio is first
+//!   converted to 16 kHz mono WAV with `ffmpeg`, then transcribed with JSON
+//!   output so every segment keeps its offsets.
+//! - `"http"` — an OpenAI-compatible `/audio/transcriptions` endpoint, asked
+//!   for `verbose_json` so the response carries segment timestamps.
+//!
+//! Selected by `ingest.transcription.provider`; nothing runs unless
+//! `ingest.transcription.enabled` is `true`.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::Deserialize;
+use sqlx::PgPool;
+use tokio::process::Command;
+use tokio::sync::RwLock;
+
+use crate::config::cache::ConfigCache;
+use crate::config::resolver;
+
+use super::{IngestError, run_tool, secret_value};
+
+/// Config key switching transcription on.
+pub const ENABLED_CONFIG_KEY: &str = "ingest.transcription.enabled";
+
+/// Longest a single transcription may run. Recordings run much longer than
+/// the pages other tools handle.
+const TRANSCRIBE_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// A transcribed stretch of speech.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub text: String,
+}
+
+/// A configured transcription provider.
+#[derive(Debug, Clone)]
+pub enum Transcriber {
+    WhisperCpp {
+        /// `whisper-cli` binary to run.
+        binary: String,
+        /// Path to the ggml model file.
+        model: String,
+        /// Spoken language code, or `auto`.
+        language: String,
+    },
+    Http {
+        client: Client,
+        endpoint: String,
+        api_key: String,
+        model: String,
+        language: String,
+    },
+}
+
+impl Transcriber {
+    /// The provider selected in config, or `None` when transcription is
+    /// disabled.
+    pub async fn configured(
+        pool: &PgPool,
+        cache: &Arc<RwLock<ConfigCache>>,
+        encryption_key: &str,
+    ) -> Result<Option<Self>, IngestError> {
+        let value = async |key: &str| {
+            resolver::get_system_value(pool, cache, key)
+                .await
+                .map_err(|e| IngestError::Config(e.to_string()))
+        };
+        if value(ENABLED_CONFIG_KEY).await? != "true" {
+            return Ok(None);
+        }
+        let language = value("ingest.transcription.language").await?;
+        match value("ingest.transcription.provider").await?.as_str() {
+            "whisper" => {
+                let model = value("ingest.transcription.whisperModel").await?;
+                if model.trim().is_empty() {
+                    return Err(IngestError::Config("whisper model is not set".into()));
+                }
+                Ok(Some(Transcriber::WhisperCpp {
+                    binary: value("ingest.transcription.whisperPath").await?,
+                    model,
+                    language,
+                }))
+            }
+            "http" => {
+                let endpoint = value("ingest.transcription.endpoint").await?;
+                if endpoint.trim().is_empty() {
+                    return Err(IngestError::Config(
+                        "transcription endpoint is not set".into(),
+                    ));
+                }
+                Ok(Some(Transcriber::Http {
+                    client: Client::builder()
+                        .timeout(TRANSCRIBE_TIMEOUT)
+                        .build()
+                        .map_err(|e| IngestError::Config(e.to_string()))?,
+                    endpoint,
+                    api_key: secret_value(pool, "ingest.transcription.apiKey", encryption_key)
+                        .await?,
+                    model: value("ingest.transcription.model").await?,
+                    language,
+                }))
+            }
+            other => Err(IngestError::Config(format!(
+                "unknown transcription provider: {other}"
+            ))),
+        }
+    }
+
+    /// Transcribe the audio file at `path` into timed segments. `filename`
+    /// is the original upload name; services detect the format from its
+    /// extension.
+    pub async fn transcribe(
+        &self,
+        path: &Path,
+        filename: &str,
+        mime_type: &str,
+    ) -> Result<Vec<Segment>, IngestError> {
+        match self {
+            Transcriber::WhisperCpp {
+                binary,
+                model,
+                language,
+            } => {
+                let wav = tempfile::Builder::new().suffix(".wav").tempfile()?;
+                run_tool(
+                    Command::new("ffmpeg")
+                        .args(["-nostdin", "-loglevel", "error", "-y", "-i"])
+                        .arg(path)
+                        .args(["-ar", "16000", "-ac", "1", "-c:a", "pcm_s16le"])
+                        .arg(wav.path()),
+                    "ffmpeg",
+                    TRANSCRIBE_TIMEOUT,
+                )
+                .await?;
+
+                // whisper-cli writes `<root>.json` next to the given root.
+                let root = wav.path().with_extension("");
+                let json_path = root.with_extension("json");
+                let mut command = Command::new(binary);
+                command
+                    .args(["-m", model, "-oj", "-np", "-f"])
+                    .arg(wav.path())
+                    .arg("-of")
+                    .arg(&root);
+                if !language.trim().is_empty() {
+                    command.args(["-l", language.trim()]);
+                }
+                let result = run_tool(&mut command, "whisper-cli", TRANSCRIBE_TIMEOUT).await;
+                let output = tokio::fs::read(&json_path).await;
+                let _ = tokio::fs::remove_file(&json_path).await;
+                result?;
+                parse_whisper_json(&output?)
+            }
+            Transcriber::Http {
+                client,
+                endpoint,
+                api_key,
+                model,
+                language,
+            } => {
+                let mut fields = vec![
+                    ("model", model.as_str()),
+                    ("response_format", "verbose_json"),
+                    ("timestamp_granularities[]", "segment"),
+                ];
+                if !language.trim().is_empty() && language.trim() != "auto" {
+                    fields.push(("language", language.trim()));
+                }
+                let boundary = format!("nize-{}", crate::uuid::uuidv7().simple());
+                let body = multipart_body(
+                    &boundary,
+                    &fields,
+                    filename,
+                    mime_type,
+                    &tokio::fs::read(path).await?,
+                );
+
+                let mut request = client
+                    .post(endpoint)
+                    .header(
+                        "content-type",
+                        format!("multipart/form-data; boundary={boundary}"),
+                    )
+                    .body(body);
+                if !api_key.is_empty() {
+                    request = request.bearer_auth(api_key);
+                }
+                let response = request
+                    .send()
+                    .await
+                    .map_err(|e| IngestError::Extract(e.to_string()))?;
+                let status = response.status();
+                if !status.is_success() {
+                    let body = response.text().await.unwrap_or_default();
+                    return Err(IngestError::Extract(format!(
+                        "transcription service returned {status}: {body}"
+                    )));
+                }
+                let body = response
+                    .bytes()
+                    .await
+                    .map_err(|e| IngestError::Extract(e.to_string()))?;
+                parse_verbose_json(&body)
+            }
+        }
+    }
+}
+
+/// Parse whisper.cpp `-oj` output.
+fn parse_whisper_json(bytes: &[u8]) -> Result<Vec<Segment>, IngestError> {
+    #[derive(Deserialize)]
+    struct Output {
+        transcription: Vec<Entry>,
+    }
+    #[derive(Deserialize)]
+    struct Entry {
+        offsets: Offsets,
+        text: String,
+    }
+    #[derive(Deserialize)]
+    struct Offsets {
+        from: i64,
+        to: i64,
+    }
+
+    let output: Output = serde_json::from_slice(bytes)
+        .map_err(|e| IngestError::Extract(format!("whisper output: {e}")))?;
+    Ok(output
+        .transcription
+        .into_iter()
+        .filter_map(|e| segment(e.offsets.from, e.offsets.to, &e.text))
+        .collect())
+}
+
+/// Parse an OpenAI-style `verbose_json` transcription (times in seconds).
+fn parse_verbose_json(bytes: &[u8]) -> Result<Vec<Segment>, IngestError> {
+    #[derive(Deserialize)]
+    struct Output {
+        #[serde(default)]
+        text: String,
+        #[serde(default)]
+        segments: Vec<Entry>,
+        #[serde(default)]
+        duration: f64,
+    }
+    #[derive(Deserialize)]
+    struct Entry {
+        start: f64,
+        end: f64,
+        text: String,
+    }
+
+    let output: Output = serde_json::from_slice(bytes)
+        .map_err(|e| IngestError::Extract(format!("transcription response: {e}")))?;
+    if output.segments.is_empty() {
+        // Providers without segment support still return the full text.
+        return Ok(segment(0, seconds_to_ms(output.duration), &output.text)
+            .into_iter()
+            .collect());
+    }
+    Ok(output
+        .segments
+        .into_iter()
+        .filter_map(|e| segment(seconds_to_ms(e.start), seconds_to_ms(e.end), &e.text))
+        .collect())
+}
+
+fn seconds_to_ms(seconds: f64) -> i64 {
+    (seconds * 1000.0).round() as i64
+}
+
+/// A segment, or `None` for silence.
+fn segment(start_ms: i64, end_ms: i64, text: &str) -> Option<Segment> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| Segment {
+        start_ms,
+        end_ms: end_ms.max(start_ms),
+        text: text.to_string(),
+    })
+}
+
+/// Build a `multipart/form-data` body with text `fields` and one file.
+fn multipart_body(
+    boundary: &str,
+    fields: &[(&str, &str)],
+    filename: &str,
+    mime_type: &str,
+    file: &[u8],
+) -> Vec<u8> {
+    let mut body = Vec::with_capacity(file.len() + 512);
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {mime_type}\r\n\r\n",
+            filename.replace(['"', '\\', '\r', '\n'], "_")
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(file);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_whisper_cpp_and_verbose_json_segments() {
+        let whisper = br#"{"transcription":[
+            {"timestamps":{"from":"00:00:00,000","to":"00:00:02,500"},"offsets":{"from":0,"to":2500},"text":" Hello there."},
+            {"timestamps":{"from":"00:00:02,500","to":"00:00:03,000"},"offsets":{"from":2500,"to":3000},"text":" "}
+        ]}"#;
+        assert_eq!(
+            parse_whisper_json(whisper).unwrap(),
+            vec![Segment {
+                start_ms: 0,
+                end_ms: 2500,
+                text: "Hello there.".into()
+            }]
+        );
+
+        let verbose = br#"{"text":"Hi. Bye.","segments":[
+            {"id":0,"start":0.0,"end":1.2,"text":" Hi."},
+            {"id":1,"start":1.2,"end":2.75,"text":" Bye."}
+        ]}"#;
+        let segments = parse_verbose_json(verbose).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[1].start_ms, segments[1].end_ms), (1200, 2750));
+
+        let plain = br#"{"text":"No segments here.","duration":4.0}"#;
+        assert_eq!(parse_verbose_json(plain).unwrap()[0].end_ms, 4000);
+    }
+
+    #[test]
+    fn multipart_body_has_fields_and_file() {
+        let body = multipart_body(
+            "b",
+            &[("model", "whisper-1")],
+            "a.mp3",
+            "audio/mpeg",
+            b"ID3",
+        );
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "--b\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n\
+             --b\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.mp3\"\r\n\
+             Content-Type: audio/mpeg\r\n\r\nID3\r\n--b--\r\n"
+        );
+    }
+}
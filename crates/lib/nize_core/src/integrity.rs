@@ -0,0 +1,375 @@
+//! Referential integrity audit.
+//!
+//! Most relations cascade through foreign keys, but a few cannot: tag
+//! assignments point at documents, conversations or notes by type and id,
+//! domain routes and conversation tool selections hold server ids in arrays
+//! and JSON, tool embedding rows keep denormalized copies of their parent's
+//! ids, and stored chunks count their references. Crashes and partial
+//! writes can leave these dangling. [`audit`] runs every [`CHECKS`] entry
+//! and reports what it finds; [`repair`] fixes the repairable ones in a
+//! single transaction, rolled back on a dry run so the report shows what a
+//! real run would change.
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use thiserror::Error;
+
+/// Number of offending keys listed per check.
+pub const SAMPLE_LIMIT: i64 = 5;
+
+/// Errors that can occur in integrity checks.
+#[derive(Debug, Error)]
+pub enum IntegrityError {
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// How a check's findings are fixed.
+#[derive(Debug, Clone, Copy)]
+pub enum Repair {
+    /// Delete the offending rows.
+    Delete,
+    /// Run this statement; it must only touch offending rows.
+    Sql(&'static str),
+    /// Not fixed here; the text says how to fix it.
+    Manual(&'static str),
+}
+
+/// One cross-table consistency check.
+#[derive(Debug, Clone, Copy)]
+pub struct Check {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Table, with an alias the predicate uses.
+    pub table: &'static str,
+    /// Condition matching offending rows.
+    pub predicate: &'static str,
+    /// Expression identifying an offending row in the report.
+    pub key: &'static str,
+    pub repair: Repair,
+}
+
+impl Check {
+    fn count_sql(&self) -> String {
+        format!(
+            "SELECT count(*) FROM {} WHERE {}",
+            self.table, self.predicate
+        )
+    }
+
+    fn sample_sql(&self) -> String {
+        format!(
+            "SELECT ({})::text FROM {} WHERE {} ORDER BY 1 LIMIT $1",
+            self.key, self.table, self.predicate
+        )
+    }
+
+    fn repair_sql(&self) -> Option<String> {
+        match self.repair {
+            Repair::Delete => Some(format!(
+                "DELETE FROM {} WHERE {}",
+                self.table, self.predicate
+            )),
+            Repair::Sql(sql) => Some(sql.to_string()),
+            Repair::Manual(_) => None,
+        }
+    }
+}
+
+const MISSING_ROUTE_SERVER: &str = "EXISTS (SELECT 1 FROM mcp_servers m WHERE m.id = ANY(r.server_ids)) \
+     AND EXISTS (SELECT 1 FROM unnest(r.server_ids) AS s(id) \
+     WHERE NOT EXISTS (SELECT 1 FROM mcp_servers m WHERE m.id = s.id))";
+
+const MISSING_SELECTION_SERVER: &str = "jsonb_typeof(c.tool_selection -> 'serverIds') = 'array' \
+     AND EXISTS (SELECT 1 FROM jsonb_array_elements_text(c.tool_selection -> 'serverIds') AS s(id) \
+     WHERE NOT EXISTS (SELECT 1 FROM mcp_servers m WHERE m.id::text = s.id))";
+
+/// Every check, in the order they run.
+pub const CHECKS: &[Check] = &[
+    Check {
+        name: "documentTags",
+        description: "Tag assignments on deleted documents",
+        table: "tag_assignments ta",
+        predicate: "ta.resource_type = 'document' \
+             AND NOT EXISTS (SELECT 1 FROM documents d WHERE d.id = ta.resource_id)",
+        key: "ta.resource_id",
+        repair: Repair::Delete,
+    },
+    Check {
+        name: "conversationTags",
+        description: "Tag assignments on deleted conversations",
+        table: "tag_assignments ta",
+        predicate: "ta.resource_type = 'conversation' \
+             AND NOT EXISTS (SELECT 1 FROM conversations c WHERE c.id = ta.resource_id)",
+        key: "ta.resource_id",
+        repair: Repair::Delete,
+    },
+    Check {
+        name: "noteTags",
+        description: "Tag assignments on deleted notes",
+        table: "tag_assignments ta",
+        predicate: "ta.resource_type = 'note' \
+             AND NOT EXISTS (SELECT 1 FROM notes n WHERE n.id = ta.resource_id)",
+        key: "ta.resource_id",
+        repair: Repair::Delete,
+    },
+    Check {
+        name: "emptyDomainRoutes",
+        description: "Domain routes whose servers were all deleted",
+        table: "mcp_domain_routes r",
+        predicate: "NOT EXISTS (SELECT 1 FROM mcp_servers m WHERE m.id = ANY(r.server_ids))",
+        key: "r.id",
+        repair: Repair::Delete,
+    },
+    Check {
+        name: "domainRouteServers",
+        description: "Domain routes listing deleted servers alongside live ones",
+        table: "mcp_domain_routes r",
+        predicate: MISSING_ROUTE_SERVER,
+        key: "r.id",
+        repair: Repair::Sql(
+            "UPDATE mcp_domain_routes r \
+             SET server_ids = ARRAY(\
+                 SELECT s.id FROM unnest(r.server_ids) WITH ORDINALITY AS s(id, i) \
+                 WHERE EXISTS (SELECT 1 FROM mcp_servers m WHERE m.id = s.id) ORDER BY s.i), \
+                 updated_at = now() \
+             WHERE EXISTS (SELECT 1 FROM unnest(r.server_ids) AS s(id) \
+                 WHERE NOT EXISTS (SELECT 1 FROM mcp_servers m WHERE m.id = s.id))",
+        ),
+    },
+    Check {
+        name: "conversationToolServers",
+        description: "Conversation tool selections listing deleted servers",
+        table: "conversations c",
+        predicate: MISSING_SELECTION_SERVER,
+        key: "c.id",
+        repair: Repair::Sql(
+            "UPDATE conversations c \
+             SET tool_selection = jsonb_set(c.tool_selection, '{serverIds}', COALESCE((\
+                 SELECT jsonb_agg(s.id) FROM jsonb_array_elements_text(c.tool_selection -> 'serverIds') AS s(id) \
+                 WHERE EXISTS (SELECT 1 FROM mcp_servers m WHERE m.id::text = s.id)), '[]'::jsonb)) \
+             WHERE jsonb_typeof(c.tool_selection -> 'serverIds') = 'array' \
+                 AND EXISTS (SELECT 1 FROM jsonb_array_elements_text(c.tool_selection -> 'serverIds') AS s(id) \
+                 WHERE NOT EXISTS (SELECT 1 FROM mcp_servers m WHERE m.id::text = s.id))",
+        ),
+    },
+    Check {
+        name: "toolEmbeddingServers",
+        description: "Tool embeddings whose server or domain differs from their tool's",
+        table: "tool_embeddings e",
+        predicate: "EXISTS (SELECT 1 FROM mcp_server_tools t JOIN mcp_servers s ON s.id = t.server_id \
+             WHERE t.id = e.tool_id AND (t.server_id <> e.server_id OR s.domain <> e.domain))",
+        key: "e.tool_id",
+        repair: Repair::Sql(
+            "UPDATE tool_embeddings e SET server_id = t.server_id, domain = s.domain \
+             FROM mcp_server_tools t JOIN mcp_servers s ON s.id = t.server_id \
+             WHERE t.id = e.tool_id AND (t.server_id <> e.server_id OR s.domain <> e.domain)",
+        ),
+    },
+    Check {
+        name: "chunkReferenceCounts",
+        description: "Stored chunks whose reference count differs from the document and note chunks mapped to them",
+        table: "chunks k",
+        predicate: "k.ref_count <> (SELECT count(*) FROM document_chunks c WHERE c.chunk_hash = k.hash) \
+             + (SELECT count(*) FROM note_chunks c WHERE c.chunk_hash = k.hash)",
+        key: "k.hash",
+        repair: Repair::Sql(
+            "UPDATE chunks k SET ref_count = \
+             (SELECT count(*) FROM document_chunks c WHERE c.chunk_hash = k.hash) \
+             + (SELECT count(*) FROM note_chunks c WHERE c.chunk_hash = k.hash) \
+             WHERE k.ref_count <> (SELECT count(*) FROM document_chunks c WHERE c.chunk_hash = k.hash) \
+             + (SELECT count(*) FROM note_chunks c WHERE c.chunk_hash = k.hash)",
+        ),
+    },
+    Check {
+        name: "unreferencedChunks",
+        description: "Stored chunks no document or note uses any more, with their embeddings",
+        table: "chunks k",
+        predicate: "k.ref_count = 0",
+        key: "k.hash",
+        repair: Repair::Delete,
+    },
+    Check {
+        name: "orphanedBlobs",
+        description: "Blobs no document references",
+        table: "blobs b",
+        predicate: "NOT EXISTS (SELECT 1 FROM documents d WHERE d.sha256 = b.sha256)",
+        key: "b.sha256",
+        repair: Repair::Manual(
+            "Run storage maintenance with orphanedBlobs to delete the stored bytes too",
+        ),
+    },
+];
+
+/// What one check found.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub count: i64,
+    /// Up to [`SAMPLE_LIMIT`] offending keys.
+    pub samples: Vec<String>,
+    pub repairable: bool,
+    /// How to fix findings this tool does not repair.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<&'static str>,
+}
+
+/// Result of [`audit`].
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub checks: Vec<CheckResult>,
+    /// Sum of all counts.
+    pub issues: i64,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// Options for [`repair`].
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RepairRequest {
+    /// Roll back instead of committing.
+    pub dry_run: bool,
+}
+
+impl Default for RepairRequest {
+    fn default() -> Self {
+        Self { dry_run: true }
+    }
+}
+
+/// Rows one check's repair changed.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairResult {
+    pub name: &'static str,
+    pub rows: u64,
+}
+
+/// Result of [`repair`].
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairReport {
+    pub dry_run: bool,
+    /// Only checks that changed rows.
+    pub repairs: Vec<RepairResult>,
+    /// Sum of changed rows.
+    pub rows: u64,
+}
+
+/// Run every check.
+pub async fn audit(pool: &PgPool) -> Result<IntegrityReport, IntegrityError> {
+    let mut checks = Vec::with_capacity(CHECKS.len());
+    for check in CHECKS {
+        let count: i64 = sqlx::query_scalar(&check.count_sql())
+            .fetch_one(pool)
+            .await?;
+        let samples = if count > 0 {
+            sqlx::query_scalar(&check.sample_sql())
+                .bind(SAMPLE_LIMIT)
+                .fetch_all(pool)
+                .await?
+        } else {
+            Vec::new()
+        };
+        let hint = match check.repair {
+            Repair::Manual(hint) => Some(hint),
+            _ => None,
+        };
+        checks.push(CheckResult {
+            name: check.name,
+            description: check.description,
+            count,
+            samples,
+            repairable: hint.is_none(),
+            hint,
+        });
+    }
+    Ok(IntegrityReport {
+        issues: checks.iter().map(|c| c.count).sum(),
+        checks,
+        checked_at: Utc::now(),
+    })
+}
+
+/// Repair every repairable check in one transaction, in [`CHECKS`] order.
+/// A dry run rolls the transaction back, so the counts are exact.
+pub async fn repair(pool: &PgPool, request: RepairRequest) -> Result<RepairReport, IntegrityError> {
+    let mut tx = pool.begin().await?;
+    let mut repairs = Vec::new();
+    for check in CHECKS {
+        let Some(sql) = check.repair_sql() else {
+            continue;
+        };
+        let rows = sqlx::query(&sql).execute(&mut *tx).await?.rows_affected();
+        if rows > 0 {
+            tracing::info!(
+                check = check.name,
+                rows,
+                dry_run = request.dry_run,
+                "integrity repair"
+            );
+            repairs.push(RepairResult {
+                name: check.name,
+                rows,
+            });
+        }
+    }
+    if request.dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+    Ok(RepairReport {
+        dry_run: request.dry_run,
+        rows: repairs.iter().map(|r| r.rows).sum(),
+        repairs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    #[test]
+    fn checks_are_well_formed() {
+        let mut names = BTreeSet::new();
+        for check in CHECKS {
+            assert!(names.insert(check.name), "duplicate {}", check.name);
+            let alias = check.table.split_whitespace().nth(1).unwrap();
+            assert!(
+                check.predicate.contains(&format!("{alias}.")),
+                "{} predicate does not use {alias}",
+                check.name
+            );
+            assert!(check.key.starts_with(&format!("{alias}.")));
+        }
+    }
+
+    #[test]
+    fn repairs_only_touch_the_checked_table() {
+        for check in CHECKS {
+            let Some(sql) = check.repair_sql() else {
+                continue;
+            };
+            assert!(
+                sql.starts_with(&format!("DELETE FROM {}", check.table))
+                    || sql.starts_with(&format!("UPDATE {}", check.table)),
+                "{}",
+                check.name
+            );
+        }
+    }
+
+    #[test]
+    fn repair_defaults_to_dry_run() {
+        let request: RepairRequest = serde_json::from_str("{}").unwrap();
+        assert!(request.dry_run);
+    }
+}
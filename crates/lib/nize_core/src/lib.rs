@@ -2,17 +2,54 @@
 //!
 //! Core domain logic for Nize.
 
+pub mod account;
+pub mod announcements;
 pub mod auth;
+pub mod blobs;
 pub mod bun_sidecar;
+pub mod cache;
+pub mod chat_import;
+pub mod chat_trace;
+pub mod chunks;
 pub mod config;
+pub mod connectors;
 pub mod conversations;
 pub mod db;
+pub mod db_transfer;
+pub mod documents;
+pub mod email;
 pub mod embedding;
+pub mod eval;
+pub mod feedback;
+pub mod hardware;
 pub mod hello;
+pub mod i18n;
+pub mod idempotency;
+pub mod ingest;
+pub mod integrity;
 pub mod mcp;
 pub mod migrate;
 pub mod models;
+pub mod moderation;
+pub mod notes;
+pub mod notifications;
+pub mod permissions;
+pub mod pricing;
+pub mod provider_check;
+pub mod providers;
+pub mod quotas;
+pub mod regeneration;
+pub mod retry;
+pub mod seed;
+pub mod storage;
+pub mod sync;
+pub mod tags;
+pub mod tasks;
+pub mod telemetry;
+pub mod timezone;
 pub mod uuid;
+pub mod warm_start;
+pub mod workspaces;
 
 /// Returns the crate version.
 pub fn version() -> &'static str {
@@ -0,0 +1,346 @@
+//! Tool usage analytics.
+//!
+//! Every proxied tool call is recorded in `mcp_tool_executions` by
+//! [`record_execution`]. [`rollup`] aggregates those events into daily
+//! per-user/per-server/per-tool rows (success rate, p50/p95 latency and error
+//! categories) in `mcp_tool_usage_daily`, which [`tool_usage`] reads back.
+//! Days are UTC calendar days.
+//!
+//! Calls made from chat carry a [`Correlation`] — the conversation and the
+//! turn (the id of the user message that started it) — so
+//! [`conversation_tool_uses`] can list the tools each turn used.
+
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::McpError;
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Error category recorded when the upstream tool ran but reported an error
+/// result (`isError: true`) rather than the call itself failing.
+pub const TOOL_ERROR_CATEGORY: &str = "tool_error";
+
+/// Longest accepted turn id.
+pub const MAX_TURN_ID_LEN: usize = 128;
+
+/// Number of days covered when no range start is given.
+pub const DEFAULT_RANGE_DAYS: i64 = 7;
+
+/// Longest range (in days) a single analytics query may cover.
+pub const MAX_RANGE_DAYS: i64 = 366;
+
+/// Inclusive range of UTC days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnalyticsRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl AnalyticsRange {
+    /// Build a range from optional bounds. `to` defaults to `today` and
+    /// `from` to [`DEFAULT_RANGE_DAYS`] days ending at `to`.
+    pub fn new(
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+        today: NaiveDate,
+    ) -> Result<Self, McpError> {
+        let to = to.unwrap_or(today);
+        let from = from.unwrap_or_else(|| to - chrono::Duration::days(DEFAULT_RANGE_DAYS - 1));
+
+        if from > to {
+            return Err(McpError::Validation(Message::new("mcp.range_reversed")));
+        }
+        if (to - from).num_days() + 1 > MAX_RANGE_DAYS {
+            return Err(McpError::Validation(
+                Message::new("mcp.range_too_long").arg("max", MAX_RANGE_DAYS),
+            ));
+        }
+        Ok(Self { from, to })
+    }
+}
+
+/// Optional filters applied to [`tool_usage`].
+#[derive(Debug, Clone, Default)]
+pub struct UsageFilter {
+    pub user_id: Option<Uuid>,
+    pub server_id: Option<Uuid>,
+    pub tool_name: Option<String>,
+}
+
+/// One day of usage for a single user/server/tool.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ToolUsageRow {
+    pub day: NaiveDate,
+    pub user_id: Uuid,
+    pub server_id: Uuid,
+    pub server_name: String,
+    pub tool_name: String,
+    pub call_count: i64,
+    pub success_count: i64,
+    pub p50_latency_ms: f64,
+    pub p95_latency_ms: f64,
+    /// Failure counts keyed by error category.
+    pub error_categories: serde_json::Value,
+}
+
+impl ToolUsageRow {
+    /// Fraction of calls that succeeded, in `[0, 1]`.
+    pub fn success_rate(&self) -> f64 {
+        if self.call_count == 0 {
+            return 0.0;
+        }
+        self.success_count as f64 / self.call_count as f64
+    }
+}
+
+/// Conversation turn a tool call was made for. Both parts are `None`
+/// outside chat; a turn is only recorded together with its conversation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Correlation {
+    pub conversation_id: Option<Uuid>,
+    pub turn_id: Option<String>,
+}
+
+impl Correlation {
+    /// Correlation of a call in `conversation_id`. A malformed `turn_id` is
+    /// an error; without a conversation the turn is dropped.
+    pub fn new(conversation_id: Option<Uuid>, turn_id: Option<&str>) -> Result<Self, McpError> {
+        let turn_id = match (conversation_id, turn_id.map(str::trim)) {
+            (Some(_), Some(turn)) => Some(parse_turn_id(turn)?),
+            _ => None,
+        };
+        Ok(Self {
+            conversation_id,
+            turn_id,
+        })
+    }
+}
+
+/// Validate a turn id: a non-empty, printable ASCII string of at most
+/// [`MAX_TURN_ID_LEN`] characters, as chat message ids are.
+fn parse_turn_id(turn_id: &str) -> Result<String, McpError> {
+    if turn_id.is_empty()
+        || turn_id.len() > MAX_TURN_ID_LEN
+        || !turn_id.chars().all(|c| c.is_ascii_graphic())
+    {
+        return Err(McpError::Validation(Message::new("mcp.invalid_turn_id")));
+    }
+    Ok(turn_id.to_string())
+}
+
+/// One tool execution made for a conversation turn.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ToolUseRow {
+    pub turn_id: Option<String>,
+    pub server_id: Uuid,
+    pub server_name: String,
+    pub tool_name: String,
+    pub success: bool,
+    pub latency_ms: i32,
+    pub error_category: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Record a single tool execution. `error_category` is `None` on success.
+pub async fn record_execution(
+    pool: &PgPool,
+    user_id: &str,
+    server_id: &Uuid,
+    tool_name: &str,
+    latency: Duration,
+    error_category: Option<&str>,
+    correlation: &Correlation,
+) -> Result<(), McpError> {
+    sqlx::query(
+        r#"
+        INSERT INTO mcp_tool_executions
+            (id, user_id, server_id, tool_name, success, latency_ms, error_category,
+             conversation_id, turn_id)
+        VALUES ($1, $2::uuid, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+    )
+    .bind(uuidv7())
+    .bind(user_id)
+    .bind(server_id)
+    .bind(tool_name)
+    .bind(error_category.is_none())
+    .bind(i32::try_from(latency.as_millis()).unwrap_or(i32::MAX))
+    .bind(error_category)
+    .bind(correlation.conversation_id)
+    .bind(correlation.turn_id.as_deref())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Tool executions made for `conversation_id`, oldest first. Failed-over
+/// attempts are listed too, each with the server that made it.
+pub async fn conversation_tool_uses(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+) -> Result<Vec<ToolUseRow>, McpError> {
+    let rows = sqlx::query_as::<_, ToolUseRow>(
+        r#"
+        SELECT e.turn_id, e.server_id, s.name AS server_name, e.tool_name, e.success,
+               e.latency_ms, e.error_category, e.created_at
+        FROM mcp_tool_executions e
+        JOIN mcp_servers s ON s.id = e.server_id
+        WHERE e.conversation_id = $1
+        ORDER BY e.created_at, e.id
+        "#,
+    )
+    .bind(conversation_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Recompute the daily rollups for every day in `range` from the raw
+/// execution events. Returns the number of rollup rows written.
+pub async fn rollup(pool: &PgPool, range: &AnalyticsRange) -> Result<u64, McpError> {
+    let result = sqlx::query(
+        r#"
+        WITH events AS (
+            SELECT (created_at AT TIME ZONE 'UTC')::date AS day,
+                   user_id, server_id, tool_name, success, latency_ms, error_category
+            FROM mcp_tool_executions
+            WHERE created_at >= $1::date::timestamp AT TIME ZONE 'UTC'
+              AND created_at < ($2::date + 1)::timestamp AT TIME ZONE 'UTC'
+        ),
+        totals AS (
+            SELECT day, user_id, server_id, tool_name,
+                   COUNT(*) AS call_count,
+                   COUNT(*) FILTER (WHERE success) AS success_count,
+                   percentile_cont(0.5) WITHIN GROUP (ORDER BY latency_ms) AS p50_latency_ms,
+                   percentile_cont(0.95) WITHIN GROUP (ORDER BY latency_ms) AS p95_latency_ms
+            FROM events
+            GROUP BY day, user_id, server_id, tool_name
+        ),
+        errors AS (
+            SELECT day, user_id, server_id, tool_name,
+                   jsonb_object_agg(error_category, n) AS error_categories
+            FROM (
+                SELECT day, user_id, server_id, tool_name, error_category, COUNT(*) AS n
+                FROM events
+                WHERE error_category IS NOT NULL
+                GROUP BY day, user_id, server_id, tool_name, error_category
+            ) c
+            GROUP BY day, user_id, server_id, tool_name
+        )
+        INSERT INTO mcp_tool_usage_daily
+            (day, user_id, server_id, tool_name, call_count, success_count,
+             p50_latency_ms, p95_latency_ms, error_categories, updated_at)
+        SELECT t.day, t.user_id, t.server_id, t.tool_name, t.call_count, t.success_count,
+               t.p50_latency_ms, t.p95_latency_ms,
+               COALESCE(e.error_categories, '{}'::jsonb), now()
+        FROM totals t
+        LEFT JOIN errors e USING (day, user_id, server_id, tool_name)
+        ON CONFLICT (day, user_id, server_id, tool_name) DO UPDATE
+        SET call_count = EXCLUDED.call_count,
+            success_count = EXCLUDED.success_count,
+            p50_latency_ms = EXCLUDED.p50_latency_ms,
+            p95_latency_ms = EXCLUDED.p95_latency_ms,
+            error_categories = EXCLUDED.error_categories,
+            updated_at = now()
+        "#,
+    )
+    .bind(range.from)
+    .bind(range.to)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Read the daily rollups in `range`, newest day first.
+pub async fn tool_usage(
+    pool: &PgPool,
+    range: &AnalyticsRange,
+    filter: &UsageFilter,
+) -> Result<Vec<ToolUsageRow>, McpError> {
+    let rows = sqlx::query_as::<_, ToolUsageRow>(
+        r#"
+        SELECT d.day, d.user_id, d.server_id, s.name AS server_name, d.tool_name,
+               d.call_count, d.success_count, d.p50_latency_ms, d.p95_latency_ms,
+               d.error_categories
+        FROM mcp_tool_usage_daily d
+        JOIN mcp_servers s ON s.id = d.server_id
+        WHERE d.day BETWEEN $1 AND $2
+          AND ($3::uuid IS NULL OR d.user_id = $3)
+          AND ($4::uuid IS NULL OR d.server_id = $4)
+          AND ($5::text IS NULL OR d.tool_name = $5)
+        ORDER BY d.day DESC, s.name, d.tool_name, d.user_id
+        "#,
+    )
+    .bind(range.from)
+    .bind(range.to)
+    .bind(filter.user_id)
+    .bind(filter.server_id)
+    .bind(filter.tool_name.as_deref())
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn range_defaults_to_last_week() {
+        let range = AnalyticsRange::new(None, None, date("2026-03-10")).unwrap();
+        assert_eq!(range.from, date("2026-03-04"));
+        assert_eq!(range.to, date("2026-03-10"));
+    }
+
+    #[test]
+    fn range_rejects_inverted_and_oversized() {
+        let today = date("2026-03-10");
+        assert!(
+            AnalyticsRange::new(Some(date("2026-03-11")), Some(date("2026-03-10")), today).is_err()
+        );
+        assert!(AnalyticsRange::new(Some(date("2024-01-01")), None, today).is_err());
+        assert!(AnalyticsRange::new(Some(date("2026-03-10")), None, today).is_ok());
+    }
+
+    #[test]
+    fn turns_are_only_kept_with_a_conversation() {
+        let conversation = Some(Uuid::new_v4());
+        let correlation = Correlation::new(conversation, Some(" msg-1 ")).unwrap();
+        assert_eq!(correlation.turn_id.as_deref(), Some("msg-1"));
+        assert_eq!(
+            Correlation::new(None, Some("msg-1")).unwrap(),
+            Correlation::default()
+        );
+        assert!(Correlation::new(conversation, Some("")).is_err());
+        assert!(Correlation::new(conversation, Some("a b")).is_err());
+        assert!(Correlation::new(conversation, Some(&"a".repeat(MAX_TURN_ID_LEN + 1))).is_err());
+    }
+
+    #[test]
+    fn success_rate_handles_empty_rows() {
+        let mut row = ToolUsageRow {
+            day: date("2026-03-10"),
+            user_id: Uuid::new_v4(),
+            server_id: Uuid::new_v4(),
+            server_name: "s".into(),
+            tool_name: "t".into(),
+            call_count: 0,
+            success_count: 0,
+            p50_latency_ms: 0.0,
+            p95_latency_ms: 0.0,
+            error_categories: serde_json::json!({}),
+        };
+        assert_eq!(row.success_rate(), 0.0);
+        row.call_count = 4;
+        row.success_count = 3;
+        assert_eq!(row.success_rate(), 0.75);
+    }
+}
@@ -0,0 +1,134 @@
+//! Request-scoped MCP config audit log.
+//!
+//! Changes made while handling one API request record their audit entries
+//! in an [`AuditLog`] instead of inserting them one by one; the log is
+//! flushed in a single multi-row insert once the request's changes are done
+//! (or inside the request's transaction with [`AuditLog::flush`] on it).
+//! Entries are only recorded after the change they describe has committed.
+
+use std::sync::{Arc, Mutex};
+
+use sqlx::{PgExecutor, Postgres, QueryBuilder};
+
+use super::McpError;
+use crate::uuid::uuidv7;
+
+/// One `mcp_config_audit` row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub actor_id: String,
+    pub server_id: Option<String>,
+    pub server_name: String,
+    pub action: String,
+    pub details: Option<serde_json::Value>,
+}
+
+impl AuditEntry {
+    pub fn new(
+        actor_id: &str,
+        server_id: Option<&str>,
+        server_name: &str,
+        action: &str,
+        details: serde_json::Value,
+    ) -> Self {
+        Self {
+            actor_id: actor_id.to_string(),
+            server_id: server_id.map(str::to_string),
+            server_name: server_name.to_string(),
+            action: action.to_string(),
+            details: Some(details),
+        }
+    }
+}
+
+/// Audit entries collected during one unit of work. Clones share the same
+/// entries.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    entries: Arc<Mutex<Vec<AuditEntry>>>,
+}
+
+impl AuditLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue an entry for the next flush.
+    pub fn record(&self, entry: AuditEntry) {
+        self.entries.lock().unwrap().push(entry);
+    }
+
+    /// Number of queued entries.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Write all queued entries in one statement. Returns how many were
+    /// written; on failure the entries are dropped with the error.
+    pub async fn flush(&self, conn: impl PgExecutor<'_>) -> Result<usize, McpError> {
+        let entries = std::mem::take(&mut *self.entries.lock().unwrap());
+        insert_entries(conn, &entries).await?;
+        Ok(entries.len())
+    }
+}
+
+/// Insert audit entries in a single statement.
+pub async fn insert_entries(
+    conn: impl PgExecutor<'_>,
+    entries: &[AuditEntry],
+) -> Result<(), McpError> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    insert_query(entries).build().execute(conn).await?;
+    Ok(())
+}
+
+fn insert_query(entries: &[AuditEntry]) -> QueryBuilder<'_, Postgres> {
+    let mut query = QueryBuilder::new(
+        "INSERT INTO mcp_config_audit (id, actor_id, server_id, server_name, action, details) ",
+    );
+    query.push_values(entries, |mut row, entry| {
+        row.push_bind(uuidv7())
+            .push_bind(&entry.actor_id)
+            .push_unseparated("::uuid")
+            .push_bind(entry.server_id.as_deref())
+            .push_unseparated("::uuid")
+            .push_bind(&entry.server_name)
+            .push_bind(&entry.action)
+            .push_bind(entry.details.as_ref());
+    });
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_inserted_in_one_statement() {
+        let log = AuditLog::new();
+        let shared = log.clone();
+        for action in ["created", "updated"] {
+            shared.record(AuditEntry::new(
+                "0190a1b2-c3d4-7e5f-8a9b-000000000001",
+                None,
+                "docs",
+                action,
+                serde_json::json!({}),
+            ));
+        }
+        assert_eq!(log.len(), 2);
+
+        let entries = log.entries.lock().unwrap().clone();
+        let sql = insert_query(&entries).into_sql();
+        assert_eq!(sql.matches("INSERT").count(), 1);
+        assert!(sql.ends_with(
+            "($1, $2::uuid, $3::uuid, $4, $5, $6), ($7, $8::uuid, $9::uuid, $10, $11, $12)"
+        ));
+    }
+}
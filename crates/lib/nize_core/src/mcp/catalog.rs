@@ -0,0 +1,558 @@
+//! Catalog of known MCP servers.
+//!
+//! A [`CatalogEntry`] is a server template: a transport config with the
+//! usual settings filled in, and descriptions of the secrets it needs. The
+//! shipped catalog ([`BUILTIN_CATALOG`]) is embedded at compile time;
+//! admins extend it with entries stored in `mcp_catalog_entries`, and a
+//! stored entry whose slug matches a shipped one replaces it.
+//!
+//! Installing an entry ([`instantiate`]) only asks for its secrets. An
+//! `apiKey` or `clientSecret` secret is stored encrypted like any server's;
+//! a `placeholder` secret is substituted for `{{name}}` in the config's
+//! strings (e.g. an environment variable of a stdio server) and so is kept
+//! in the server config itself.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::LazyLock;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::McpError;
+use crate::i18n::Message;
+use crate::models::mcp::{OAuthConfig, ServerConfig};
+
+/// The shipped catalog (a JSON array of [`CatalogEntry`]).
+pub const BUILTIN_CATALOG: &str = include_str!("catalog.json");
+
+/// Maximum length of a slug.
+pub const MAX_SLUG_LEN: usize = 64;
+
+static BUILTIN_ENTRIES: LazyLock<Vec<CatalogEntry>> = LazyLock::new(|| {
+    serde_json::from_str(BUILTIN_CATALOG).expect("the shipped MCP catalog is valid JSON")
+});
+
+/// Where an install puts a secret's value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SecretTarget {
+    /// Substituted for `{{name}}` in the config's strings.
+    #[default]
+    Placeholder,
+    /// The server's API key (config `authType` must be `api-key`).
+    ApiKey,
+    /// The OAuth client secret (config `authType` must be `oauth`).
+    ClientSecret,
+}
+
+/// A secret an entry needs at install time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogSecret {
+    /// Key of the value in the install request.
+    pub name: String,
+    /// Short label for the input field.
+    pub label: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub target: SecretTarget,
+    #[serde(default = "default_required")]
+    pub required: bool,
+    /// Prepended to the value, e.g. `"Bearer "` for an `Authorization`
+    /// header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+fn default_domain() -> String {
+    "general".to_string()
+}
+
+/// A server template.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    /// Stable identifier; taken from the path when an admin saves an entry.
+    #[serde(default)]
+    pub slug: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "default_domain")]
+    pub domain: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    /// Transport config; strings may contain `{{name}}` placeholders.
+    pub config: ServerConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oauth_config: Option<OAuthConfig>,
+    #[serde(default)]
+    pub secrets: Vec<CatalogSecret>,
+}
+
+impl CatalogEntry {
+    /// Whether users (not only admins) can install the entry: only remote
+    /// transports may be user-owned.
+    pub fn user_installable(&self) -> bool {
+        matches!(self.config, ServerConfig::Http(_) | ServerConfig::Sse(_))
+    }
+
+    fn auth_type(&self) -> &str {
+        match &self.config {
+            ServerConfig::Http(http) => &http.auth_type,
+            ServerConfig::Sse(sse) => &sse.auth_type,
+            _ => "none",
+        }
+    }
+}
+
+/// Whether an entry is shipped or defined by an admin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum CatalogSource {
+    Builtin,
+    Custom,
+}
+
+/// A catalog entry as listed to clients.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntryView {
+    #[serde(flatten)]
+    pub entry: CatalogEntry,
+    pub source: CatalogSource,
+    pub user_installable: bool,
+}
+
+impl CatalogEntryView {
+    fn new(entry: CatalogEntry, source: CatalogSource) -> Self {
+        Self {
+            user_installable: entry.user_installable(),
+            entry,
+            source,
+        }
+    }
+}
+
+/// A server config ready to be created from an entry.
+#[derive(Debug, Clone)]
+pub struct InstantiatedServer {
+    pub config: ServerConfig,
+    pub api_key: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+/// The shipped entries.
+pub fn builtin_entries() -> &'static [CatalogEntry] {
+    &BUILTIN_ENTRIES
+}
+
+/// `{{name}}` placeholders in `value`'s strings.
+fn collect_placeholders(value: &serde_json::Value, found: &mut BTreeSet<String>) {
+    match value {
+        serde_json::Value::String(s) => {
+            let mut rest = s.as_str();
+            while let Some(start) = rest.find("{{") {
+                let Some(len) = rest[start + 2..].find("}}") else {
+                    break;
+                };
+                found.insert(rest[start + 2..start + 2 + len].trim().to_string());
+                rest = &rest[start + 2 + len + 2..];
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_placeholders(item, found);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for item in map.values() {
+                collect_placeholders(item, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace `{{name}}` in `value`'s strings.
+fn substitute(value: &mut serde_json::Value, values: &HashMap<&str, String>) {
+    match value {
+        serde_json::Value::String(s) => {
+            for (name, replacement) in values {
+                *s = s.replace(&format!("{{{{{name}}}}}"), replacement);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                substitute(item, values);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for item in map.values_mut() {
+                substitute(item, values);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn config_json(config: &ServerConfig) -> Result<serde_json::Value, McpError> {
+    serde_json::to_value(config).map_err(|e| {
+        McpError::Validation(Message::new("mcp.config_serialize_failed").arg("error", e))
+    })
+}
+
+/// Check an entry's slug, secrets and placeholders.
+pub fn validate_entry(entry: &CatalogEntry) -> Result<(), McpError> {
+    if entry.slug.is_empty()
+        || entry.slug.len() > MAX_SLUG_LEN
+        || entry.slug.starts_with('-')
+        || !entry
+            .slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(McpError::Validation(
+            Message::new("mcp.invalid_catalog_slug")
+                .arg("max", MAX_SLUG_LEN)
+                .arg("slug", &entry.slug),
+        ));
+    }
+    if entry.name.trim().is_empty() {
+        return Err(McpError::Validation(Message::new(
+            "mcp.catalog_name_required",
+        )));
+    }
+
+    let mut names = BTreeSet::new();
+    for secret in &entry.secrets {
+        if secret.name.is_empty()
+            || !secret
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            return Err(McpError::Validation(
+                Message::new("mcp.invalid_secret_name").arg("name", &secret.name),
+            ));
+        }
+        if !names.insert(secret.name.as_str()) {
+            return Err(McpError::Validation(
+                Message::new("mcp.duplicate_secret").arg("name", &secret.name),
+            ));
+        }
+    }
+
+    let auth_type = entry.auth_type();
+    for (target, target_name, required_auth) in [
+        (SecretTarget::ApiKey, "apiKey", "api-key"),
+        (SecretTarget::ClientSecret, "clientSecret", "oauth"),
+    ] {
+        let count = entry.secrets.iter().filter(|s| s.target == target).count();
+        if count > 1 {
+            return Err(McpError::Validation(
+                Message::new("mcp.secret_target_taken").arg("target", target_name),
+            ));
+        }
+        if count == 1 && auth_type != required_auth {
+            return Err(McpError::Validation(
+                Message::new("mcp.secret_target_auth_type")
+                    .arg("target", target_name)
+                    .arg("auth_type", required_auth),
+            ));
+        }
+    }
+    if auth_type == "oauth" && entry.oauth_config.is_none() {
+        return Err(McpError::Validation(Message::new(
+            "mcp.oauth_config_required",
+        )));
+    }
+
+    let mut placeholders = BTreeSet::new();
+    collect_placeholders(&config_json(&entry.config)?, &mut placeholders);
+    let declared: BTreeSet<String> = entry
+        .secrets
+        .iter()
+        .filter(|s| s.target == SecretTarget::Placeholder)
+        .map(|s| s.name.clone())
+        .collect();
+    if let Some(unknown) = placeholders.difference(&declared).next() {
+        return Err(McpError::Validation(
+            Message::new("mcp.placeholder_without_secret").arg("name", unknown),
+        ));
+    }
+    if let Some(unused) = declared.difference(&placeholders).next() {
+        return Err(McpError::Validation(
+            Message::new("mcp.unused_secret").arg("name", unused),
+        ));
+    }
+    Ok(())
+}
+
+/// Fill in an entry's secrets. Required secrets must be present and
+/// non-blank; secrets the entry does not declare are rejected. An omitted
+/// optional placeholder becomes an empty string.
+pub fn instantiate(
+    entry: &CatalogEntry,
+    secrets: &HashMap<String, String>,
+) -> Result<InstantiatedServer, McpError> {
+    if let Some(unknown) = secrets
+        .keys()
+        .find(|name| !entry.secrets.iter().any(|s| &s.name == *name))
+    {
+        return Err(McpError::Validation(
+            Message::new("mcp.unknown_secret")
+                .arg("entry", &entry.name)
+                .arg("name", unknown),
+        ));
+    }
+
+    let mut placeholders = HashMap::new();
+    let mut api_key = None;
+    let mut client_secret = None;
+    for secret in &entry.secrets {
+        let value = secrets
+            .get(&secret.name)
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty());
+        let value = match (value, secret.required) {
+            (Some(value), _) => Some(format!("{}{value}", secret.prefix.as_deref().unwrap_or(""))),
+            (None, true) => {
+                return Err(McpError::Validation(
+                    Message::new("mcp.secret_required").arg("label", &secret.label),
+                ));
+            }
+            (None, false) => None,
+        };
+        match secret.target {
+            SecretTarget::Placeholder => {
+                placeholders.insert(secret.name.as_str(), value.unwrap_or_default());
+            }
+            SecretTarget::ApiKey => api_key = value,
+            SecretTarget::ClientSecret => client_secret = value,
+        }
+    }
+
+    let mut config = config_json(&entry.config)?;
+    substitute(&mut config, &placeholders);
+    let config = serde_json::from_value(config).map_err(|e| {
+        McpError::Validation(Message::new("mcp.invalid_substituted_config").arg("error", e))
+    })?;
+    Ok(InstantiatedServer {
+        config,
+        api_key,
+        client_secret,
+    })
+}
+
+// =============================================================================
+// Queries
+// =============================================================================
+
+/// Database row for `mcp_catalog_entries`.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CatalogEntryRow {
+    pub slug: String,
+    pub entry: serde_json::Value,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn parse_row(row: CatalogEntryRow) -> Option<CatalogEntry> {
+    match serde_json::from_value(row.entry) {
+        Ok(entry) => Some(entry),
+        Err(e) => {
+            tracing::warn!(slug = %row.slug, error = %e, "skipping invalid MCP catalog entry");
+            None
+        }
+    }
+}
+
+/// All entries: shipped ones, replaced or extended by admin-defined ones,
+/// ordered by name.
+pub async fn list_entries(pool: &PgPool) -> Result<Vec<CatalogEntryView>, McpError> {
+    let rows = sqlx::query_as::<_, CatalogEntryRow>(
+        "SELECT slug, entry, created_by, created_at, updated_at FROM mcp_catalog_entries",
+    )
+    .fetch_all(pool)
+    .await?;
+    let custom: Vec<CatalogEntry> = rows.into_iter().filter_map(parse_row).collect();
+
+    let mut entries: Vec<CatalogEntryView> = builtin_entries()
+        .iter()
+        .filter(|b| !custom.iter().any(|c| c.slug == b.slug))
+        .map(|b| CatalogEntryView::new(b.clone(), CatalogSource::Builtin))
+        .collect();
+    entries.extend(
+        custom
+            .into_iter()
+            .map(|c| CatalogEntryView::new(c, CatalogSource::Custom)),
+    );
+    entries.sort_by(|a, b| {
+        a.entry
+            .name
+            .to_lowercase()
+            .cmp(&b.entry.name.to_lowercase())
+    });
+    Ok(entries)
+}
+
+/// One entry by slug, admin-defined first.
+pub async fn get_entry(pool: &PgPool, slug: &str) -> Result<Option<CatalogEntryView>, McpError> {
+    let row = sqlx::query_as::<_, CatalogEntryRow>(
+        r#"
+        SELECT slug, entry, created_by, created_at, updated_at
+        FROM mcp_catalog_entries WHERE slug = $1
+        "#,
+    )
+    .bind(slug)
+    .fetch_optional(pool)
+    .await?;
+    if let Some(entry) = row.and_then(parse_row) {
+        return Ok(Some(CatalogEntryView::new(entry, CatalogSource::Custom)));
+    }
+    Ok(builtin_entries()
+        .iter()
+        .find(|e| e.slug == slug)
+        .map(|e| CatalogEntryView::new(e.clone(), CatalogSource::Builtin)))
+}
+
+/// Create or replace an admin-defined entry.
+pub async fn upsert_entry(
+    pool: &PgPool,
+    entry: &CatalogEntry,
+    admin_id: &Uuid,
+) -> Result<CatalogEntryView, McpError> {
+    validate_entry(entry)?;
+    let json = serde_json::to_value(entry).map_err(|e| {
+        McpError::Validation(Message::new("mcp.catalog_entry_serialize_failed").arg("error", e))
+    })?;
+    sqlx::query(
+        r#"
+        INSERT INTO mcp_catalog_entries (slug, entry, created_by)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (slug) DO UPDATE SET entry = EXCLUDED.entry, updated_at = now()
+        "#,
+    )
+    .bind(&entry.slug)
+    .bind(&json)
+    .bind(admin_id)
+    .execute(pool)
+    .await?;
+    Ok(CatalogEntryView::new(entry.clone(), CatalogSource::Custom))
+}
+
+/// Delete an admin-defined entry; a shipped entry it replaced reappears.
+/// Returns whether one existed.
+pub async fn delete_entry(pool: &PgPool, slug: &str) -> Result<bool, McpError> {
+    let result = sqlx::query("DELETE FROM mcp_catalog_entries WHERE slug = $1")
+        .bind(slug)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(json: serde_json::Value) -> CatalogEntry {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn builtin_catalog_is_valid() {
+        let mut slugs = BTreeSet::new();
+        for entry in builtin_entries() {
+            validate_entry(entry).unwrap_or_else(|e| panic!("{}: {e}", entry.slug));
+            assert!(
+                slugs.insert(entry.slug.as_str()),
+                "duplicate {}",
+                entry.slug
+            );
+        }
+        assert!(!slugs.is_empty());
+    }
+
+    #[test]
+    fn placeholders_must_match_secrets() {
+        let undeclared = entry(serde_json::json!({
+            "slug": "x",
+            "name": "X",
+            "config": { "transport": "stdio", "command": "x", "env": { "TOKEN": "{{token}}" } }
+        }));
+        assert!(validate_entry(&undeclared).is_err());
+
+        let unused = entry(serde_json::json!({
+            "slug": "x",
+            "name": "X",
+            "config": { "transport": "stdio", "command": "x" },
+            "secrets": [{ "name": "token", "label": "Token" }]
+        }));
+        assert!(validate_entry(&unused).is_err());
+
+        let wrong_auth = entry(serde_json::json!({
+            "slug": "x",
+            "name": "X",
+            "config": { "transport": "http", "url": "https://x", "authType": "none" },
+            "secrets": [{ "name": "key", "label": "Key", "target": "apiKey" }]
+        }));
+        assert!(validate_entry(&wrong_auth).is_err());
+    }
+
+    #[test]
+    fn instantiate_fills_secrets() {
+        let entry = entry(serde_json::json!({
+            "slug": "x",
+            "name": "X",
+            "config": {
+                "transport": "stdio",
+                "command": "x",
+                "args": ["--region", "{{region}}"],
+                "env": { "TOKEN": "{{token}}" }
+            },
+            "secrets": [
+                { "name": "token", "label": "Token" },
+                { "name": "region", "label": "Region", "required": false }
+            ]
+        }));
+        validate_entry(&entry).unwrap();
+
+        let secrets = HashMap::from([("token".to_string(), " abc ".to_string())]);
+        let server = instantiate(&entry, &secrets).unwrap();
+        let ServerConfig::Stdio(config) = server.config else {
+            panic!("expected stdio");
+        };
+        assert_eq!(config.env.unwrap()["TOKEN"], "abc");
+        assert_eq!(config.args.unwrap(), ["--region", ""]);
+
+        assert!(instantiate(&entry, &HashMap::new()).is_err());
+        let unknown = HashMap::from([
+            ("token".to_string(), "abc".to_string()),
+            ("other".to_string(), "x".to_string()),
+        ]);
+        assert!(instantiate(&entry, &unknown).is_err());
+    }
+
+    #[test]
+    fn api_key_secrets_get_their_prefix() {
+        let github = builtin_entries()
+            .iter()
+            .find(|e| e.slug == "github")
+            .unwrap();
+        assert!(github.user_installable());
+        let secrets = HashMap::from([("token".to_string(), "ghp_1".to_string())]);
+        let server = instantiate(github, &secrets).unwrap();
+        assert_eq!(server.api_key.as_deref(), Some("Bearer ghp_1"));
+        assert!(server.client_secret.is_none());
+    }
+}
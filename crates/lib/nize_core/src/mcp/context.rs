@@ -0,0 +1,189 @@
+//! Caller context forwarded to MCP servers.
+//!
+//! Some tools need to know who is asking and from where: the conversation a
+//! call is made in, the workspace it belongs to, the caller's locale. The
+//! MCP server collects this per session — the conversation and its
+//! workspace from the session's conversation, the locale from
+//! `Accept-Language` — and a tool call may add a locale of its own in
+//! `_meta["nize/context"]`.
+//!
+//! Nothing is sent upstream by default: each server lists the keys it gets
+//! (`mcp_servers.forwarded_context`), which are attached to its tool calls
+//! as `_meta["nize/context"]`.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use super::McpError;
+
+use crate::i18n::Message;
+
+/// `_meta` key holding the context object, both on calls into the MCP
+/// server and on calls it forwards.
+pub const CONTEXT_META_KEY: &str = "nize/context";
+
+/// Every context key a server can be configured to receive.
+pub const CONTEXT_KEYS: &[&str] = &["conversationId", "workspaceId", "locale"];
+
+/// Keys a tool call may set in its own `_meta`. The conversation and
+/// workspace come only from the session, where they were checked.
+const CALL_KEYS: &[&str] = &["locale"];
+
+/// Longest accepted locale tag.
+const MAX_LOCALE_LEN: usize = 35;
+
+/// Context of one MCP session or tool call; keys without a value are
+/// absent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionContext {
+    values: BTreeMap<&'static str, String>,
+}
+
+impl SessionContext {
+    pub fn new(
+        conversation_id: Option<Uuid>,
+        workspace_id: Option<Uuid>,
+        locale: Option<String>,
+    ) -> Self {
+        let mut values = BTreeMap::new();
+        if let Some(id) = conversation_id {
+            values.insert("conversationId", id.to_string());
+        }
+        if let Some(id) = workspace_id {
+            values.insert("workspaceId", id.to_string());
+        }
+        if let Some(locale) = locale {
+            values.insert("locale", locale);
+        }
+        Self { values }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// This context with the values a tool call set in `_meta`. Keys a
+    /// call may not set and malformed values are ignored.
+    pub fn with_call_meta(mut self, meta: &Map<String, Value>) -> Self {
+        let Some(Value::Object(context)) = meta.get(CONTEXT_META_KEY) else {
+            return self;
+        };
+        for key in CALL_KEYS {
+            if let Some(locale) = context
+                .get(*key)
+                .and_then(Value::as_str)
+                .and_then(parse_locale)
+            {
+                self.values.insert(key, locale);
+            }
+        }
+        self
+    }
+
+    /// The `_meta` of a call to a server that gets `keys`, or `None` when
+    /// none of them has a value.
+    pub fn forwarded(&self, keys: &[String]) -> Option<Map<String, Value>> {
+        let context: Map<String, Value> = self
+            .values
+            .iter()
+            .filter(|(key, _)| keys.iter().any(|k| k == *key))
+            .map(|(key, value)| (key.to_string(), Value::String(value.clone())))
+            .collect();
+        if context.is_empty() {
+            return None;
+        }
+        let mut meta = Map::new();
+        meta.insert(CONTEXT_META_KEY.to_string(), Value::Object(context));
+        Some(meta)
+    }
+}
+
+/// The preferred locale of an `Accept-Language` value (or a bare tag), if
+/// it is a well-formed language tag.
+pub fn parse_locale(value: &str) -> Option<String> {
+    let tag = value.split(',').next()?.split(';').next()?.trim();
+    let well_formed = !tag.is_empty()
+        && tag.len() <= MAX_LOCALE_LEN
+        && tag.split('-').all(|part| {
+            (1..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+        && tag
+            .chars()
+            .take_while(|c| *c != '-')
+            .all(|c| c.is_ascii_alphabetic());
+    well_formed.then(|| tag.to_string())
+}
+
+/// Check a server's forwarded keys are all known context keys.
+pub fn validate_keys(keys: &[String]) -> Result<(), McpError> {
+    match keys.iter().find(|k| !CONTEXT_KEYS.contains(&k.as_str())) {
+        Some(unknown) => Err(McpError::Validation(
+            Message::new("mcp.unknown_context_key")
+                .arg("key", unknown)
+                .arg("known", CONTEXT_KEYS.join(", ")),
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn context() -> SessionContext {
+        SessionContext::new(
+            Some(Uuid::nil()),
+            None,
+            parse_locale("de-CH,de;q=0.9,en;q=0.8"),
+        )
+    }
+
+    #[test]
+    fn forwards_only_configured_keys() {
+        let meta = context()
+            .forwarded(&["locale".into(), "workspaceId".into()])
+            .unwrap();
+        assert_eq!(
+            Value::Object(meta),
+            json!({ "nize/context": { "locale": "de-CH" } })
+        );
+        assert!(context().forwarded(&[]).is_none());
+        assert!(context().forwarded(&["workspaceId".into()]).is_none());
+    }
+
+    #[test]
+    fn call_meta_sets_only_the_locale() {
+        let meta = json!({
+            "nize/context": { "locale": "fr", "conversationId": "spoofed" }
+        });
+        let context = context().with_call_meta(meta.as_object().unwrap());
+        assert_eq!(context.get("locale"), Some("fr"));
+        assert_eq!(
+            context.get("conversationId"),
+            Some(Uuid::nil().to_string().as_str())
+        );
+    }
+
+    #[test]
+    fn parses_locale_tags() {
+        assert_eq!(parse_locale("en-GB").as_deref(), Some("en-GB"));
+        assert_eq!(parse_locale(" pt-BR;q=0.8 ").as_deref(), Some("pt-BR"));
+        assert_eq!(parse_locale("*"), None);
+        assert_eq!(parse_locale("en\r\nX: y"), None);
+        assert_eq!(parse_locale("123"), None);
+    }
+
+    #[test]
+    fn keys_must_be_known() {
+        assert!(validate_keys(&["locale".into(), "conversationId".into()]).is_ok());
+        assert!(validate_keys(&["email".into()]).is_err());
+    }
+}
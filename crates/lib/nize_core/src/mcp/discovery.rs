@@ -4,7 +4,10 @@
 //!
 //! Accepts a query string, embeds it via the embedding subsystem, and
 //! searches the tool embedding table using cosine similarity. Results
-//! are filtered by user-enabled servers (via `user_mcp_preferences`).
+//! are filtered by user-enabled servers (via `user_mcp_preferences`) or the
+//! conversation's pinned tool selection, and leave out tools switched off by
+//! an admin or the user. When `embedding.rerank.tools.enabled` is on, the
+//! top candidates are reranked by a cross-encoder before the cut to `top_k`.
 
 use std::sync::Arc;
 
@@ -15,9 +18,11 @@ use uuid::Uuid;
 use crate::config::cache::ConfigCache;
 use crate::embedding;
 use crate::embedding::config::EmbeddingConfig;
-use crate::embedding::models;
+use crate::embedding::rerank::{self, RerankStage};
+use crate::embedding::{ann, models};
 
 use super::McpError;
+use crate::conversations::ToolSelection;
 
 /// Parameters for a tool discovery search.
 #[derive(Debug, Clone)]
@@ -27,6 +32,8 @@ pub struct DiscoveryQuery {
     pub user_id: String,
     pub top_k: Option<i64>,
     pub min_similarity: Option<f64>,
+    /// Tool selection pinned by the conversation, if any.
+    pub selection: Option<ToolSelection>,
 }
 
 /// A row from a tool discovery search result.
@@ -40,6 +47,8 @@ pub struct DiscoveredToolRow {
     pub server_name: String,
     pub server_description: String,
     pub similarity: f64,
+    /// Cross-encoder relevance, when the row was reranked.
+    pub rerank_score: Option<f64>,
 }
 
 /// Discover tools by semantic similarity search.
@@ -59,7 +68,7 @@ pub async fn discover_tools(
         .await
         .map_err(|e| McpError::ConnectionFailed(format!("Embedding config error: {e}")))?;
 
-    // Get active model to know which table to search
+    // Get active model to know which embeddings to search
     let model_config = models::get_active_model(pool, &config)
         .await
         .map_err(|e| McpError::ConnectionFailed(format!("Embedding model error: {e}")))?;
@@ -81,6 +90,12 @@ pub async fn discover_tools(
 
     let top_k = query.top_k.unwrap_or(10);
     let min_similarity = query.min_similarity.unwrap_or(0.5);
+    let rerank_stage =
+        RerankStage::configured(pool, config_cache, encryption_key, rerank::UseCase::Tools).await;
+    let limit = match &rerank_stage {
+        Some(stage) => stage.candidates(top_k.max(0) as usize) as i64,
+        None => top_k,
+    };
 
     // Build the similarity search query with user preference filter.
     // A user sees tools from servers where:
@@ -96,11 +111,12 @@ pub async fn discover_tools(
                       s.id AS server_id,
                       s.name AS server_name,
                       s.description AS server_description,
-                      1 - (te.embedding <=> $1::vector) AS similarity
-               FROM "{tool_table}" te
+                      1 - ({distance}) AS similarity
+               FROM tool_embeddings te
                JOIN mcp_server_tools t ON t.id = te.tool_id
                JOIN mcp_servers s ON s.id = te.server_id
-               WHERE s.enabled = true
+               WHERE {model_filter}
+                 AND s.enabled = true
                  AND te.domain = $4
                  AND (
                    (s.visibility = 'visible' AND NOT EXISTS (
@@ -112,10 +128,15 @@ pub async fn discover_tools(
                      WHERE p.user_id = $5::uuid AND p.server_id = s.id AND p.enabled = true
                    )
                  )
-                 AND 1 - (te.embedding <=> $1::vector) >= $3
-               ORDER BY te.embedding <=> $1::vector
+                 AND {tool_enabled}
+                 AND {selected}
+                 AND 1 - ({distance}) >= $3
+               ORDER BY {distance}
                LIMIT $2"#,
-            tool_table = model_config.tool_table_name
+            distance = model_config.distance_sql("te.embedding", "$1"),
+            model_filter = model_config.filter_sql("te"),
+            tool_enabled = super::queries::tool_enabled_sql("t", "$5"),
+            selected = super::queries::tool_selection_sql("s", "$6"),
         )
     } else {
         format!(
@@ -126,11 +147,12 @@ pub async fn discover_tools(
                       s.id AS server_id,
                       s.name AS server_name,
                       s.description AS server_description,
-                      1 - (te.embedding <=> $1::vector) AS similarity
-               FROM "{tool_table}" te
+                      1 - ({distance}) AS similarity
+               FROM tool_embeddings te
                JOIN mcp_server_tools t ON t.id = te.tool_id
                JOIN mcp_servers s ON s.id = te.server_id
-               WHERE s.enabled = true
+               WHERE {model_filter}
+                 AND s.enabled = true
                  AND (
                    (s.visibility = 'visible' AND NOT EXISTS (
                      SELECT 1 FROM user_mcp_preferences p
@@ -141,35 +163,50 @@ pub async fn discover_tools(
                      WHERE p.user_id = $4::uuid AND p.server_id = s.id AND p.enabled = true
                    )
                  )
-                 AND 1 - (te.embedding <=> $1::vector) >= $3
-               ORDER BY te.embedding <=> $1::vector
+                 AND {tool_enabled}
+                 AND {selected}
+                 AND 1 - ({distance}) >= $3
+               ORDER BY {distance}
                LIMIT $2"#,
-            tool_table = model_config.tool_table_name
+            distance = model_config.distance_sql("te.embedding", "$1"),
+            model_filter = model_config.filter_sql("te"),
+            tool_enabled = super::queries::tool_enabled_sql("t", "$4"),
+            selected = super::queries::tool_selection_sql("s", "$5"),
         )
     };
 
+    let selection = super::queries::tool_selection_param(query.selection.as_ref());
+    let search_params = ann::SearchParams::resolve(pool, config_cache).await;
+    let mut tx = pool.begin().await.map_err(McpError::DbError)?;
+    search_params
+        .apply(&mut tx)
+        .await
+        .map_err(McpError::DbError)?;
     let rows = if query.domain.is_some() {
         sqlx::query_as::<_, (Uuid, String, String, String, Uuid, String, String, f64)>(&sql)
             .bind(&embedding_sql)
-            .bind(top_k)
+            .bind(limit)
             .bind(min_similarity)
             .bind(query.domain.as_deref().unwrap_or(""))
             .bind(&query.user_id)
-            .fetch_all(pool)
+            .bind(&selection)
+            .fetch_all(&mut *tx)
             .await
             .map_err(McpError::DbError)?
     } else {
         sqlx::query_as::<_, (Uuid, String, String, String, Uuid, String, String, f64)>(&sql)
             .bind(&embedding_sql)
-            .bind(top_k)
+            .bind(limit)
             .bind(min_similarity)
             .bind(&query.user_id)
-            .fetch_all(pool)
+            .bind(&selection)
+            .fetch_all(&mut *tx)
             .await
             .map_err(McpError::DbError)?
     };
+    tx.commit().await.map_err(McpError::DbError)?;
 
-    Ok(rows
+    let rows: Vec<DiscoveredToolRow> = rows
         .into_iter()
         .map(
             |(
@@ -191,8 +228,24 @@ pub async fn discover_tools(
                     server_name,
                     server_description,
                     similarity,
+                    rerank_score: None,
                 }
             },
         )
-        .collect())
+        .collect();
+
+    Ok(match rerank_stage {
+        Some(stage) => {
+            stage
+                .rerank(
+                    &query.query,
+                    rows,
+                    top_k.max(0) as usize,
+                    |row| format!("{}: {}", row.tool_name, row.tool_description),
+                    |row, score| row.rerank_score = Some(score),
+                )
+                .await
+        }
+        None => rows,
+    })
 }
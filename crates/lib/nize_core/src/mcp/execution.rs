@@ -6,32 +6,44 @@
 //! Supports HTTP (Streamable HTTP), SSE (legacy), stdio, and managed transports.
 
 use std::borrow::Cow;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
+use schemars::JsonSchema;
+use serde::Serialize;
 use sqlx::PgPool;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
 use tokio::sync::Mutex;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 use rmcp::ServiceExt;
-use rmcp::model::{CallToolRequestParams, CallToolResult};
+use rmcp::model::{CallToolRequestParams, CallToolResult, Meta};
 use rmcp::service::{RoleClient, RunningService};
 use rmcp::transport::StreamableHttpClientTransport;
 use rmcp::transport::TokioChildProcess;
 use rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig;
 
+use crate::conversations::ToolSelection;
+use crate::i18n::Message;
 use crate::models::mcp::{
-    AuthType, HttpServerConfig, ManagedHttpServerConfig, McpToolSummary, ServerConfig,
-    SseServerConfig, StdioServerConfig, TestConnectionResult, TransportType,
+    AuthType, HttpServerConfig, ManagedHttpServerConfig, McpServerRow, McpToolSummary,
+    ServerConfig, SseServerConfig, StdioServerConfig, TestConnectionResult, TransportType,
 };
+use crate::retry::RetryPolicy;
 
 use super::McpError;
+use super::analytics::{self, Correlation};
+use super::context::SessionContext;
 use super::queries;
+use super::recording::{self, CallOutcome};
+use super::routing::{self, CircuitBreaker};
+use super::sandbox;
+use super::secrets::{self, SecretBinding, ServerKey};
 
 /// Default timeout for tool execution (30 seconds).
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
@@ -64,6 +76,15 @@ pub struct ExecutionRequest {
     pub tool_name: String,
     pub params: Option<serde_json::Map<String, serde_json::Value>>,
     pub user_id: String,
+    /// Tool selection pinned by the conversation, if any; the tool and any
+    /// fallback server must be in it.
+    pub selection: Option<ToolSelection>,
+    /// Caller context, attached to the call as far as the serving server
+    /// is configured to receive it.
+    pub context: SessionContext,
+    /// Conversation turn the call was made for, recorded with the
+    /// execution and in the audit log.
+    pub correlation: Correlation,
 }
 
 /// Result of executing a tool on an external MCP server.
@@ -72,17 +93,22 @@ pub struct ExecutionResult {
     pub success: bool,
     pub tool_name: String,
     pub result: serde_json::Value,
+    /// Server that handled the call when the requested one failed over.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_server: Option<String>,
 }
 
 /// Entry in the connection pool, tracking transport type alongside the service.
 struct PoolEntry {
     service: RunningService<RoleClient, ()>,
     transport: TransportType,
+    server_name: String,
     /// Milliseconds since pool epoch when this entry was last accessed.
     last_accessed: AtomicU64,
     /// When the entry was created.
-    #[allow(dead_code)]
     created_at: Instant,
+    /// Process ID of the server's child process, for managed transports.
+    pid: Option<u32>,
     /// Child process handle for managed transports (stdio, managed-sse, managed-http).
     /// Killed when the pool entry is removed/evicted.
     child_process: Option<tokio::process::Child>,
@@ -103,6 +129,26 @@ impl PoolEntry {
     }
 }
 
+/// Resource usage of one managed MCP server process.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChildProcessStats {
+    pub server_id: Uuid,
+    pub server_name: String,
+    pub transport: TransportType,
+    pub pid: u32,
+    /// Seconds since the process was spawned.
+    pub uptime_secs: u64,
+    /// Times the server's process was spawned again after its first start
+    /// in this pool (after eviction, a failed call or exceeding the memory
+    /// limit).
+    pub restarts: u32,
+    /// CPU usage since the previous sample, in percent of one core.
+    pub cpu_percent: f32,
+    /// Resident memory in bytes.
+    pub memory_bytes: u64,
+}
+
 /// Client connection pool — reuses MCP client sessions across calls.
 ///
 /// Keyed by server ID. Connections are lazily created and kept alive.
@@ -112,6 +158,8 @@ pub struct ClientPool {
     connections: Arc<DashMap<Uuid, PoolEntry>>,
     /// Guard set to prevent duplicate concurrent spawns for the same server.
     connecting: Arc<Mutex<HashSet<Uuid>>>,
+    /// Keep-warm servers — never evicted for idleness or LRU capacity.
+    pinned: Arc<DashSet<Uuid>>,
     /// Path to the terminator manifest file for managed process PID registration.
     manifest_path: Option<PathBuf>,
     /// Maximum number of concurrent managed processes.
@@ -120,6 +168,15 @@ pub struct ClientPool {
     idle_timeout: Duration,
     /// Reference point for atomic last-accessed timestamps.
     epoch: Instant,
+    /// Per-server circuit breakers for tool calls.
+    circuits: CircuitBreaker,
+    /// Processes spawned per server, to count restarts.
+    spawns: Arc<DashMap<Uuid, u32>>,
+    /// Resident memory above which a managed process is restarted.
+    max_child_memory: Option<u64>,
+    /// Process table for sampling child CPU and memory usage. Kept between
+    /// samples, which CPU usage is measured across.
+    system: std::sync::Mutex<System>,
 }
 
 impl ClientPool {
@@ -127,10 +184,15 @@ impl ClientPool {
         Self {
             connections: Arc::new(DashMap::new()),
             connecting: Arc::new(Mutex::new(HashSet::new())),
+            pinned: Arc::new(DashSet::new()),
             manifest_path: None,
             max_managed_processes: DEFAULT_MAX_MANAGED_PROCESSES,
             idle_timeout: DEFAULT_IDLE_TIMEOUT,
             epoch: Instant::now(),
+            circuits: CircuitBreaker::new(),
+            spawns: Arc::new(DashMap::new()),
+            max_child_memory: None,
+            system: std::sync::Mutex::new(System::new()),
         }
     }
 
@@ -157,14 +219,211 @@ impl ClientPool {
         self.idle_timeout
     }
 
+    /// Restart managed processes whose resident memory exceeds `bytes`
+    /// (`None` disables the limit). Checked by the reaper.
+    pub fn set_max_child_memory(&mut self, bytes: Option<u64>) {
+        self.max_child_memory = bytes;
+    }
+
+    /// Count all pooled connections, regardless of transport.
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
     /// Count current managed connections (stdio + managed-sse + managed-http).
-    fn managed_count(&self) -> usize {
+    pub fn managed_count(&self) -> usize {
         self.connections
             .iter()
             .filter(|entry| entry.value().transport.is_managed())
             .count()
     }
 
+    /// CPU, memory, uptime and restart count of every managed process in
+    /// the pool. Processes that have already exited are left out.
+    pub fn child_stats(&self) -> Vec<ChildProcessStats> {
+        let children: Vec<(Uuid, String, TransportType, u32, Duration)> = self
+            .connections
+            .iter()
+            .filter_map(|e| {
+                let entry = e.value();
+                entry.pid.map(|pid| {
+                    let uptime = entry.created_at.elapsed();
+                    (
+                        *e.key(),
+                        entry.server_name.clone(),
+                        entry.transport.clone(),
+                        pid,
+                        uptime,
+                    )
+                })
+            })
+            .collect();
+        let pids: Vec<u32> = children.iter().map(|c| c.3).collect();
+        let usage = {
+            let mut system = self.system.lock().unwrap_or_else(|e| e.into_inner());
+            sample_processes(&mut system, &pids)
+        };
+        children
+            .into_iter()
+            .filter_map(|(server_id, server_name, transport, pid, uptime)| {
+                let &(cpu_percent, memory_bytes) = usage.get(&pid)?;
+                Some(ChildProcessStats {
+                    server_id,
+                    server_name,
+                    transport,
+                    pid,
+                    uptime_secs: uptime.as_secs(),
+                    restarts: self.restarts(&server_id),
+                    cpu_percent,
+                    memory_bytes,
+                })
+            })
+            .collect()
+    }
+
+    /// Times a server's process was spawned again after its first start.
+    pub fn restarts(&self, server_id: &Uuid) -> u32 {
+        self.spawns
+            .get(server_id)
+            .map_or(0, |n| n.saturating_sub(1))
+    }
+
+    /// Restarts across all servers since the pool was created.
+    pub fn total_restarts(&self) -> u32 {
+        self.spawns.iter().map(|n| n.saturating_sub(1)).sum()
+    }
+
+    /// Count a process spawn for `server_id`.
+    fn record_spawn(&self, server_id: Uuid) {
+        *self.spawns.entry(server_id).or_insert(0) += 1;
+    }
+
+    /// Kill managed processes over the memory limit; the next call to the
+    /// server spawns it again. Returns the servers restarted.
+    fn restart_over_memory_limit(&self) -> Vec<Uuid> {
+        let Some(limit) = self.max_child_memory else {
+            return Vec::new();
+        };
+        let over: Vec<ChildProcessStats> = self
+            .child_stats()
+            .into_iter()
+            .filter(|c| c.memory_bytes > limit)
+            .collect();
+        for child in &over {
+            warn!(
+                server_id = %child.server_id,
+                server_name = %child.server_name,
+                memory_bytes = child.memory_bytes,
+                limit,
+                "Managed MCP process exceeded memory limit, restarting"
+            );
+            self.remove(&child.server_id);
+        }
+        over.into_iter().map(|c| c.server_id).collect()
+    }
+
+    /// Per-server circuit breakers, consulted by tool discovery and
+    /// execution.
+    pub fn circuits(&self) -> &CircuitBreaker {
+        &self.circuits
+    }
+
+    /// Exempt a server's connection from idle and LRU eviction.
+    pub fn pin(&self, server_id: Uuid) {
+        self.pinned.insert(server_id);
+    }
+
+    /// Make a server's connection evictable again.
+    pub fn unpin(&self, server_id: &Uuid) {
+        self.pinned.remove(server_id);
+    }
+
+    /// Whether a server's connection is exempt from eviction.
+    pub fn is_pinned(&self, server_id: &Uuid) -> bool {
+        self.pinned.contains(server_id)
+    }
+
+    /// Pre-connect every enabled keep-warm server and pin it.
+    ///
+    /// OAuth servers are pinned but not connected — their sessions carry
+    /// per-user tokens, so they connect on first use. Connection failures
+    /// are logged and skipped. Returns the number of servers connected.
+    pub async fn warm_up(&self, pool: &PgPool) -> Result<usize, McpError> {
+        let servers = queries::list_keep_warm_servers(pool).await?;
+        let mut connected = 0;
+        for server in &servers {
+            if self.warm(pool, server).await {
+                connected += 1;
+            }
+        }
+        info!(
+            pinned = servers.len(),
+            connected, "MCP keep-warm warm-up complete"
+        );
+        Ok(connected)
+    }
+
+    /// Apply a server's current keep-warm setting after it changed: pin
+    /// and pre-connect an enabled keep-warm server (as [`Self::warm_up`]),
+    /// unpin any other.
+    pub async fn apply_keep_warm(&self, pool: &PgPool, server_id: Uuid) -> Result<(), McpError> {
+        match queries::get_server(pool, &server_id.to_string()).await? {
+            Some(server) if server.enabled && server.keep_warm => {
+                self.warm(pool, &server).await;
+            }
+            _ => self.unpin(&server_id),
+        }
+        Ok(())
+    }
+
+    /// Pin a keep-warm server and connect it unless it uses OAuth. Returns
+    /// whether it connected.
+    async fn warm(&self, pool: &PgPool, server: &McpServerRow) -> bool {
+        self.pin(server.id);
+        if queries::extract_auth_type(&server.config) == AuthType::OAuth {
+            debug!(server_id = %server.id, "Skipping warm-up for OAuth server");
+            return false;
+        }
+        match self.get_or_connect(pool, server.id, None).await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(server_id = %server.id, error = %e, "Keep-warm connection failed");
+                false
+            }
+        }
+    }
+
+    /// IDs of the servers with a pooled connection.
+    pub fn connected_server_ids(&self) -> Vec<Uuid> {
+        self.connections.iter().map(|e| *e.key()).collect()
+    }
+
+    /// Connect the given servers, e.g. the ones connected before a restart,
+    /// without pinning them. Servers that are gone, disabled or use OAuth
+    /// are skipped and failures logged, as in [`Self::warm_up`], which also
+    /// covers keep-warm servers. Returns the number of servers connected.
+    pub async fn reconnect(&self, pool: &PgPool, server_ids: &[Uuid]) -> usize {
+        let mut connected = 0;
+        for server_id in server_ids {
+            let server = match queries::get_server(pool, &server_id.to_string()).await {
+                Ok(Some(server)) if server.enabled => server,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!(server_id = %server_id, error = %e, "Reconnect lookup failed");
+                    continue;
+                }
+            };
+            if server.keep_warm || queries::extract_auth_type(&server.config) == AuthType::OAuth {
+                continue;
+            }
+            match self.get_or_connect(pool, server.id, None).await {
+                Ok(()) => connected += 1,
+                Err(e) => warn!(server_id = %server.id, error = %e, "Reconnect failed"),
+            }
+        }
+        connected
+    }
+
     // @awa-impl: PLAN-025 Phase 2.1 — atomic DashMap entry with connecting guard
     /// Get or create a connection to an MCP server.
     async fn get_or_connect(
@@ -216,7 +475,9 @@ impl ClientPool {
     ) -> Result<(), McpError> {
         let server = queries::get_server(pool, &server_id.to_string())
             .await?
-            .ok_or_else(|| McpError::NotFound(format!("Server {server_id}")))?;
+            .ok_or_else(|| {
+                McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id))
+            })?;
 
         let transport_type = server.transport.clone();
 
@@ -321,8 +582,10 @@ impl ClientPool {
             PoolEntry {
                 service,
                 transport: TransportType::Http,
+                server_name: server.name.clone(),
                 last_accessed: AtomicU64::new(self.epoch.elapsed().as_millis() as u64),
                 created_at: Instant::now(),
+                pid: None,
                 child_process: None,
             },
         );
@@ -351,24 +614,21 @@ impl ClientPool {
             .as_ref()
             .and_then(|c| serde_json::from_value(c.clone()).ok())
             .ok_or_else(|| {
-                McpError::Validation(format!(
-                    "Server \"{}\" has no valid stdio configuration",
-                    server.name
-                ))
+                McpError::Validation(
+                    Message::new("mcp.invalid_stdio_config").arg("name", &server.name),
+                )
             })?;
 
         let args = config.args.as_deref().unwrap_or_default();
 
         // @awa-impl: PLAN-025 Phase 4.4 — stderr inherits to server logs
-        let mut cmd = tokio::process::Command::new(&config.command);
-        cmd.args(args).stderr(std::process::Stdio::inherit());
-
-        // Set environment variables if provided
-        if let Some(env) = &config.env {
-            for (k, v) in env {
-                cmd.env(k, v);
-            }
-        }
+        let mut cmd = sandbox::command(
+            &config.command,
+            args,
+            config.env.as_ref(),
+            config.sandbox.as_ref(),
+        )?;
+        cmd.stderr(std::process::Stdio::inherit());
 
         // @awa-impl: PLAN-025 Phase 4.3 — command not found maps to ConnectionFailed
         let transport = TokioChildProcess::new(cmd).map_err(|e| {
@@ -377,10 +637,14 @@ impl ClientPool {
                 config.command
             ))
         })?;
+        // Dropping the transport kills the process.
+        sandbox::confine(transport.id(), config.sandbox.as_ref())?;
+        let pid = transport.id();
+        self.record_spawn(server_id);
 
         // @awa-impl: PLAN-025 Phase 5.2 — write PID to terminator manifest
         if let Some(ref manifest) = self.manifest_path
-            && let Some(pid) = transport.id()
+            && let Some(pid) = pid
             && let Err(e) = append_manifest(manifest, pid)
         {
             warn!("Failed to write stdio PID {pid} to manifest: {e}");
@@ -415,8 +679,10 @@ impl ClientPool {
             PoolEntry {
                 service,
                 transport: TransportType::Stdio,
+                server_name: server.name.clone(),
                 last_accessed: AtomicU64::new(self.epoch.elapsed().as_millis() as u64),
                 created_at: Instant::now(),
+                pid,
                 child_process: None, // TokioChildProcess manages its own child
             },
         );
@@ -444,10 +710,9 @@ impl ClientPool {
                 serde_json::from_value(c.clone()).ok()
             })
             .ok_or_else(|| {
-                McpError::Validation(format!(
-                    "Server \"{}\" has no valid SSE configuration",
-                    server.name
-                ))
+                McpError::Validation(
+                    Message::new("mcp.invalid_sse_config").arg("name", &server.name),
+                )
             })?;
 
         let mut extra_headers = reqwest::header::HeaderMap::new();
@@ -513,8 +778,10 @@ impl ClientPool {
             PoolEntry {
                 service,
                 transport: TransportType::Sse,
+                server_name: server.name.clone(),
                 last_accessed: AtomicU64::new(self.epoch.elapsed().as_millis() as u64),
                 created_at: Instant::now(),
+                pid: None,
                 child_process: None,
             },
         );
@@ -565,10 +832,9 @@ impl ClientPool {
                 serde_json::from_value(c.clone()).ok()
             })
             .ok_or_else(|| {
-                McpError::Validation(format!(
-                    "Server \"{}\" has no valid managed HTTP configuration",
-                    server.name
-                ))
+                McpError::Validation(
+                    Message::new("mcp.invalid_managed_http_config").arg("name", &server.name),
+                )
             })?;
 
         // Spawn the child process
@@ -578,6 +844,7 @@ impl ClientPool {
                 config.command
             ))
         })?;
+        self.record_spawn(server_id);
 
         // Write PID to terminator manifest
         if let Some(ref manifest) = self.manifest_path {
@@ -719,8 +986,10 @@ impl ClientPool {
             PoolEntry {
                 service,
                 transport: transport_type,
+                server_name: server.name.clone(),
                 last_accessed: AtomicU64::new(self.epoch.elapsed().as_millis() as u64),
                 created_at: Instant::now(),
+                pid: child.id(),
                 child_process: Some(child),
             },
         );
@@ -739,13 +1008,24 @@ impl ClientPool {
         }
     }
 
+    /// Close every connection, killing any child processes.
+    pub fn close_all(&self) {
+        let ids: Vec<Uuid> = self.connections.iter().map(|e| *e.key()).collect();
+        for id in &ids {
+            self.remove(id);
+        }
+    }
+
     // @awa-impl: PLAN-030 Phase 2.1 — evict idle managed connections
     // @awa-impl: PLAN-033 T-XMCP-052 — evict all managed transports, not just stdio
     /// Evict all managed connections that have been idle longer than `timeout`.
     fn evict_idle(&self, timeout: Duration) {
         let mut evicted = Vec::new();
         self.connections.retain(|id, entry| {
-            if entry.transport.is_managed() && entry.idle_duration(&self.epoch) > timeout {
+            if entry.transport.is_managed()
+                && !self.pinned.contains(id)
+                && entry.idle_duration(&self.epoch) > timeout
+            {
                 evicted.push(*id);
                 if let Some(ref mut child) = entry.child_process {
                     let _ = child.start_kill();
@@ -761,7 +1041,8 @@ impl ClientPool {
     }
 
     // @awa-impl: PLAN-030 Phase 2.2 — spawn background reaper
-    /// Spawn a background reaper task that evicts idle managed connections.
+    /// Spawn a background reaper task that evicts idle managed connections
+    /// and restarts managed processes over the memory limit.
     /// Returns a `JoinHandle` — the task runs until the Tokio runtime shuts down.
     pub fn spawn_reaper(self: &Arc<Self>, idle_timeout: Duration) -> tokio::task::JoinHandle<()> {
         let pool = Arc::clone(self);
@@ -770,6 +1051,7 @@ impl ClientPool {
             loop {
                 tokio::time::sleep(interval).await;
                 pool.evict_idle(idle_timeout);
+                pool.restart_over_memory_limit();
             }
         })
     }
@@ -782,7 +1064,7 @@ impl ClientPool {
         let oldest = self
             .connections
             .iter()
-            .filter(|e| e.value().transport.is_managed())
+            .filter(|e| e.value().transport.is_managed() && !self.pinned.contains(e.key()))
             .min_by_key(|e| e.value().last_accessed.load(Ordering::Relaxed))
             .map(|e| *e.key());
 
@@ -824,6 +1106,26 @@ fn append_manifest(manifest: &Path, pid: u32) -> Result<(), String> {
 // Managed process helpers
 // =============================================================================
 
+/// Refresh `pids` in `system` and return each live one's CPU usage (percent
+/// of one core) and resident memory (bytes).
+fn sample_processes(system: &mut System, pids: &[u32]) -> HashMap<u32, (f32, u64)> {
+    if pids.is_empty() {
+        return HashMap::new();
+    }
+    let pids: Vec<Pid> = pids.iter().map(|&pid| Pid::from_u32(pid)).collect();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&pids),
+        true,
+        ProcessRefreshKind::nothing().with_cpu().with_memory(),
+    );
+    pids.iter()
+        .filter_map(|&pid| {
+            let process = system.process(pid)?;
+            Some((pid.as_u32(), (process.cpu_usage(), process.memory())))
+        })
+        .collect()
+}
+
 // @awa-impl: PLAN-033 T-XMCP-041 — spawn managed child process
 /// Spawn a managed child process with piped stdin for lifecycle coupling.
 fn spawn_managed_process(
@@ -831,20 +1133,24 @@ fn spawn_managed_process(
 ) -> Result<tokio::process::Child, String> {
     let args = config.args.as_deref().unwrap_or_default();
 
-    let mut cmd = tokio::process::Command::new(&config.command);
-    cmd.args(args)
-        .stdin(std::process::Stdio::piped()) // lifecycle coupling
+    let mut cmd = sandbox::command(
+        &config.command,
+        args,
+        config.env.as_ref(),
+        config.sandbox.as_ref(),
+    )
+    .map_err(|e| e.to_string())?;
+    cmd.stdin(std::process::Stdio::piped()) // lifecycle coupling
         .stderr(std::process::Stdio::inherit());
 
-    // Set environment variables if provided
-    if let Some(env) = &config.env {
-        for (k, v) in env {
-            cmd.env(k, v);
-        }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("spawn '{}': {e}", config.command))?;
+    if let Err(e) = sandbox::confine(child.id(), config.sandbox.as_ref()) {
+        let _ = child.start_kill();
+        return Err(e.to_string());
     }
-
-    cmd.spawn()
-        .map_err(|e| format!("spawn '{}': {e}", config.command))
+    Ok(child)
 }
 
 // @awa-impl: PLAN-033 T-XMCP-042 — wait for managed server readiness
@@ -855,22 +1161,17 @@ async fn wait_for_ready(url: &str, timeout: Duration) -> Result<(), String> {
         .build()
         .map_err(|e| format!("build readiness client: {e}"))?;
 
-    let deadline = tokio::time::Instant::now() + timeout;
-    loop {
-        // Try a simple GET — we just need the server to accept connections
-        match client.get(url).send().await {
-            Ok(_) => return Ok(()),
-            Err(_) if tokio::time::Instant::now() < deadline => {
-                tokio::time::sleep(READY_RETRY_INTERVAL).await;
-            }
-            Err(e) => {
-                return Err(format!(
-                    "server not ready after {}s: {e}",
-                    timeout.as_secs()
-                ));
-            }
-        }
-    }
+    // Any response will do — we just need the server to accept connections
+    RetryPolicy::new()
+        .max_attempts(u32::MAX)
+        .base_delay(READY_RETRY_INTERVAL)
+        .max_delay(READY_RETRY_INTERVAL)
+        .jitter(false)
+        .deadline(timeout)
+        .run(|_| client.get(url).send(), |_| true)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("server not ready after {}s: {e}", timeout.as_secs()))
 }
 
 // =============================================================================
@@ -976,13 +1277,7 @@ pub async fn test_http_connection(
 
     // List all tools (handles pagination automatically)
     let tools: Vec<McpToolSummary> = match service.peer().list_all_tools().await {
-        Ok(rmcp_tools) => rmcp_tools
-            .into_iter()
-            .map(|t| McpToolSummary {
-                name: t.name.to_string(),
-                description: t.description.as_deref().unwrap_or("").to_string(),
-            })
-            .collect(),
+        Ok(rmcp_tools) => rmcp_tools.into_iter().map(tool_summary).collect(),
         Err(e) => {
             warn!("tools/list failed during connection test: {e}");
             vec![]
@@ -1026,19 +1321,25 @@ pub async fn test_stdio_connection(config: &StdioServerConfig) -> TestConnection
 
     let args = config.args.as_deref().unwrap_or_default();
 
-    let mut cmd = tokio::process::Command::new(&config.command);
-    cmd.args(args)
-        .stdin(std::process::Stdio::piped())
+    let mut cmd = match sandbox::command(
+        &config.command,
+        args,
+        config.env.as_ref(),
+        config.sandbox.as_ref(),
+    ) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            return TestConnectionResult {
+                success: false,
+                error: Some(e.to_string()),
+                ..Default::default()
+            };
+        }
+    };
+    cmd.stdin(std::process::Stdio::piped())
         .stdout(std::process::Stdio::piped())
         .stderr(std::process::Stdio::piped());
 
-    // Set environment variables if provided
-    if let Some(env) = &config.env {
-        for (k, v) in env {
-            cmd.env(k, v);
-        }
-    }
-
     let transport = match TokioChildProcess::new(cmd) {
         Ok(t) => t,
         Err(e) => {
@@ -1049,6 +1350,13 @@ pub async fn test_stdio_connection(config: &StdioServerConfig) -> TestConnection
             };
         }
     };
+    if let Err(e) = sandbox::confine(transport.id(), config.sandbox.as_ref()) {
+        return TestConnectionResult {
+            success: false,
+            error: Some(e.to_string()),
+            ..Default::default()
+        };
+    }
 
     // Connect via rmcp with a timeout (performs initialize + initialized notification)
     let service: RunningService<RoleClient, ()> =
@@ -1087,13 +1395,7 @@ pub async fn test_stdio_connection(config: &StdioServerConfig) -> TestConnection
 
     // List all tools (handles pagination automatically)
     let tools: Vec<McpToolSummary> = match service.peer().list_all_tools().await {
-        Ok(rmcp_tools) => rmcp_tools
-            .into_iter()
-            .map(|t| McpToolSummary {
-                name: t.name.to_string(),
-                description: t.description.as_deref().unwrap_or("").to_string(),
-            })
-            .collect(),
+        Ok(rmcp_tools) => rmcp_tools.into_iter().map(tool_summary).collect(),
         Err(e) => {
             warn!("tools/list failed during stdio connection test: {e}");
             vec![]
@@ -1218,13 +1520,7 @@ pub async fn test_sse_connection(
 
     // List all tools
     let tools: Vec<McpToolSummary> = match service.peer().list_all_tools().await {
-        Ok(rmcp_tools) => rmcp_tools
-            .into_iter()
-            .map(|t| McpToolSummary {
-                name: t.name.to_string(),
-                description: t.description.as_deref().unwrap_or("").to_string(),
-            })
-            .collect(),
+        Ok(rmcp_tools) => rmcp_tools.into_iter().map(tool_summary).collect(),
         Err(e) => {
             warn!("tools/list failed during SSE connection test: {e}");
             vec![]
@@ -1454,13 +1750,7 @@ async fn extract_test_result(
     };
 
     let tools: Vec<McpToolSummary> = match service.peer().list_all_tools().await {
-        Ok(rmcp_tools) => rmcp_tools
-            .into_iter()
-            .map(|t| McpToolSummary {
-                name: t.name.to_string(),
-                description: t.description.as_deref().unwrap_or("").to_string(),
-            })
-            .collect(),
+        Ok(rmcp_tools) => rmcp_tools.into_iter().map(tool_summary).collect(),
         Err(e) => {
             warn!("tools/list failed during {label} connection test: {e}");
             vec![]
@@ -1482,6 +1772,17 @@ async fn extract_test_result(
     }
 }
 
+/// Capture the manifest of a listed tool: name, description, input schema
+/// and annotations.
+fn tool_summary(tool: rmcp::model::Tool) -> McpToolSummary {
+    McpToolSummary {
+        name: tool.name.to_string(),
+        description: tool.description.as_deref().unwrap_or("").to_string(),
+        input_schema: Some(serde_json::Value::Object((*tool.input_schema).clone())),
+        annotations: tool.annotations.and_then(|a| serde_json::to_value(a).ok()),
+    }
+}
+
 /// Add custom headers from JSON config to a reqwest HeaderMap.
 fn add_custom_headers(
     header_map: &mut reqwest::header::HeaderMap,
@@ -1506,7 +1807,7 @@ fn add_custom_headers(
 /// Resolve OAuth headers for a server, refreshing tokens if needed.
 /// Returns `None` if the server does not use OAuth auth.
 // @awa-impl: PLAN-031 Phase 7.3 — token refresh before connection
-async fn resolve_oauth_headers(
+pub async fn resolve_oauth_headers(
     pool: &PgPool,
     user_id: &str,
     server_id: Uuid,
@@ -1514,7 +1815,9 @@ async fn resolve_oauth_headers(
 ) -> Result<Option<OAuthHeaders>, McpError> {
     let server = queries::get_server(pool, &server_id.to_string())
         .await?
-        .ok_or_else(|| McpError::NotFound(format!("Server {server_id}")))?;
+        .ok_or_else(|| {
+            McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id))
+        })?;
 
     let auth_type = queries::extract_auth_type(&server.config);
     let uses_oauth = auth_type == AuthType::OAuth || server.oauth_config.is_some();
@@ -1558,13 +1861,17 @@ async fn resolve_oauth_headers(
             serde_json::from_value(oauth_config_json)
                 .map_err(|e| McpError::ConnectionFailed(format!("Invalid OAuth config: {e}")))?;
 
-        let encrypted_secret =
-            queries::get_oauth_client_secret_encrypted(pool, &server_id.to_string())
-                .await?
-                .ok_or_else(|| {
-                    McpError::ConnectionFailed("No OAuth client secret stored".into())
-                })?;
-        let client_secret = super::secrets::decrypt(&encrypted_secret, encryption_key)?;
+        let stored = queries::get_server_secrets(pool, &server_id.to_string()).await?;
+        let (encrypted_secret, key_id) = stored
+            .and_then(|row| Some((row.oauth_client_secret_encrypted?, row.encryption_key_id)))
+            .ok_or_else(|| McpError::ConnectionFailed("No OAuth client secret stored".into()))?;
+        let client_secret = secrets::decrypt_row(
+            pool,
+            &encrypted_secret,
+            &ServerKey::from_id(encryption_key, &key_id).key,
+            &SecretBinding::oauth_client_secret(&server_id.to_string()),
+        )
+        .await?;
 
         let refresh_token_encrypted =
             token_row
@@ -1575,7 +1882,13 @@ async fn resolve_oauth_headers(
                         "No refresh token — please re-authorize this server".into(),
                     )
                 })?;
-        let refresh_token = super::secrets::decrypt(refresh_token_encrypted, encryption_key)?;
+        let refresh_token = secrets::decrypt_row(
+            pool,
+            refresh_token_encrypted,
+            encryption_key,
+            &SecretBinding::refresh_token(user_id, &server_id.to_string()),
+        )
+        .await?;
 
         // Refresh tokens
         let resp = super::oauth::refresh_google_tokens(
@@ -1588,10 +1901,18 @@ async fn resolve_oauth_headers(
 
         // Encrypt and store refreshed tokens
         let id_token_encrypted = match &resp.id_token {
-            Some(t) => Some(super::secrets::encrypt(t, encryption_key)?),
+            Some(t) => Some(secrets::encrypt_bound(
+                t,
+                encryption_key,
+                &SecretBinding::id_token(user_id, &server_id.to_string()),
+            )?),
             None => None,
         };
-        let access_token_encrypted = super::secrets::encrypt(&resp.access_token, encryption_key)?;
+        let access_token_encrypted = secrets::encrypt_bound(
+            &resp.access_token,
+            encryption_key,
+            &SecretBinding::access_token(user_id, &server_id.to_string()),
+        )?;
         let expires_at = chrono::Utc::now() + chrono::Duration::seconds(resp.expires_in);
         let scope_vec: Vec<String> = resp
             .scope
@@ -1628,10 +1949,22 @@ async fn resolve_oauth_headers(
     let id_token_encrypted = token_row.id_token_encrypted.as_deref().ok_or_else(|| {
         McpError::ConnectionFailed("No id_token stored — please re-authorize".into())
     })?;
-    let id_token = super::secrets::decrypt(id_token_encrypted, encryption_key)?;
+    let id_token = secrets::decrypt_row(
+        pool,
+        id_token_encrypted,
+        encryption_key,
+        &SecretBinding::id_token(user_id, &server_id.to_string()),
+    )
+    .await?;
 
     let access_token_encrypted = &token_row.access_token_encrypted;
-    let access_token = super::secrets::decrypt(access_token_encrypted, encryption_key)?;
+    let access_token = secrets::decrypt_row(
+        pool,
+        access_token_encrypted,
+        encryption_key,
+        &SecretBinding::access_token(user_id, &server_id.to_string()),
+    )
+    .await?;
 
     Ok(Some(OAuthHeaders {
         id_token,
@@ -1643,8 +1976,12 @@ async fn resolve_oauth_headers(
 ///
 /// 1. Validates the tool exists and the user has access.
 /// 2. Connects to the external server (or reuses a pooled connection).
-/// 3. Calls the tool with the provided parameters.
-/// 4. Records an audit log entry.
+/// 3. Calls the tool with the provided parameters. When the server is
+///    unavailable, its circuit is open or the call fails transiently, the
+///    call falls back to the same tool on the next server of the domain's
+///    route (see [`routing`]).
+/// 4. Records an analytics event (see [`analytics`]) per attempt and an
+///    audit log entry.
 /// 5. Returns the result.
 // @awa-impl: PLAN-031 Phase 7.3 — OAuth token lifecycle during tool execution
 pub async fn execute_tool(
@@ -1654,49 +1991,120 @@ pub async fn execute_tool(
     encryption_key: &str,
 ) -> Result<ExecutionResult, McpError> {
     // Validate tool exists and user has access
-    let tool = queries::get_tool_manifest(pool, &request.user_id, &request.tool_id.to_string())
-        .await?
-        .ok_or_else(|| {
-            McpError::NotFound(format!(
-                "Tool {} not found or access denied",
-                request.tool_id
-            ))
-        })?;
+    let tool = queries::get_tool_manifest(
+        pool,
+        &request.user_id,
+        &request.tool_id.to_string(),
+        request.selection.as_ref(),
+    )
+    .await?
+    .ok_or_else(|| {
+        McpError::NotFound(Message::new("mcp.tool_not_found").arg("id", request.tool_id))
+    })?;
 
-    let server_id = tool.server_id;
+    let requested = queries::get_server(pool, &tool.server_id.to_string()).await?;
+    let mut candidates = vec![routing::FallbackTool {
+        tool_id: tool.id,
+        server_id: tool.server_id,
+        server_name: requested
+            .as_ref()
+            .map(|s| s.name.clone())
+            .unwrap_or_else(|| "unknown".to_string()),
+        available: requested.as_ref().is_none_or(|s| s.available),
+    }];
+    let mut fallbacks =
+        routing::fallback_tools(pool, &request.user_id, &tool.server_id, &request.tool_name)
+            .await?;
+    // Fallbacks share the requested server's domain.
+    if let (Some(selection), Some(server)) = (&request.selection, &requested) {
+        fallbacks.retain(|f| selection.allows(&f.server_id, &server.domain));
+    }
+    candidates.extend(fallbacks);
+
+    let mut last_error = None;
+    let mut served = None;
+    let attempts = candidates.len();
+    for (i, candidate) in candidates.into_iter().enumerate() {
+        let is_last = i + 1 == attempts;
+        let server_id = candidate.server_id;
+        // Skip servers known to be down while there is somewhere else to go.
+        if !is_last && (!candidate.available || client_pool.circuits().is_open(&server_id)) {
+            debug!(server_id = %server_id, "skipping unavailable server");
+            continue;
+        }
+        if client_pool.circuits().is_open(&server_id) {
+            last_error = Some(
+                McpError::ConnectionFailed("Server circuit is open after repeated failures".into())
+                    .with_server(server_id),
+            );
+            break;
+        }
 
-    // Resolve OAuth headers if the server uses OAuth auth
-    let oauth_headers =
-        resolve_oauth_headers(pool, &request.user_id, server_id, encryption_key).await?;
-    debug!(
-        user_id = %request.user_id,
-        server_id = %server_id,
-        tool_id = %request.tool_id,
-        tool_name = %request.tool_name,
-        oauth_headers_resolved = oauth_headers.is_some(),
-        "execute_tool oauth header resolution"
-    );
+        let started = Instant::now();
+        let result = call_external_tool(pool, client_pool, request, server_id, encryption_key)
+            .await
+            .map_err(|e| e.with_server(server_id));
 
-    // Convert params to JsonObject
-    let arguments = request.params.clone();
+        let elapsed = started.elapsed();
+        if let Err(e) = recording::capture(
+            pool,
+            &request.user_id,
+            &server_id,
+            &candidate.server_name,
+            &request.tool_name,
+            request.params.as_ref(),
+            &CallOutcome::from_call(&result),
+            elapsed,
+        )
+        .await
+        {
+            warn!("Failed to record tool call: {e}");
+        }
 
-    // Build call params
-    let call_params = CallToolRequestParams {
-        meta: None,
-        name: Cow::Owned(request.tool_name.clone()),
-        arguments,
-        task: None,
-    };
+        // Record analytics event (fire-and-forget)
+        let error_category = match &result {
+            Ok(r) if r.is_error.unwrap_or(false) => Some(analytics::TOOL_ERROR_CATEGORY),
+            Ok(_) => None,
+            Err(e) => Some(e.info().category.as_str()),
+        };
+        if let Err(e) = analytics::record_execution(
+            pool,
+            &request.user_id,
+            &server_id,
+            &request.tool_name,
+            elapsed,
+            error_category,
+            &request.correlation,
+        )
+        .await
+        {
+            warn!("Failed to record tool execution: {e}");
+        }
 
-    // Try to execute with one retry on connection error
-    let result = execute_with_retry(
-        pool,
-        client_pool,
-        server_id,
-        &call_params,
-        oauth_headers.as_ref(),
-    )
-    .await?;
+        match result {
+            Ok(result) => {
+                client_pool.circuits().record_success(&server_id);
+                served = Some((candidate, i > 0, result));
+                break;
+            }
+            Err(e) if routing::is_failover_error(&e) => {
+                client_pool.circuits().record_failure(&server_id);
+                if !is_last {
+                    info!(server_id = %server_id, "tool call failed over: {e}");
+                }
+                last_error = Some(e);
+            }
+            Err(e) => {
+                last_error = Some(e);
+                break;
+            }
+        }
+    }
+    let Some((server, fell_back, result)) = served else {
+        return Err(last_error.unwrap_or_else(|| {
+            McpError::ConnectionFailed("No server available for this tool".into())
+        }));
+    };
 
     // Record audit log (fire-and-forget)
     let is_error = result.is_error.unwrap_or(false);
@@ -1704,19 +2112,16 @@ pub async fn execute_tool(
         "toolId": request.tool_id.to_string(),
         "toolName": request.tool_name,
         "success": !is_error,
+        "fallbackFrom": fell_back.then(|| tool.server_id.to_string()),
+        "conversationId": request.correlation.conversation_id.map(|id| id.to_string()),
+        "turnId": request.correlation.turn_id,
     });
 
-    let server = queries::get_server(pool, &server_id.to_string()).await?;
-    let server_name = server
-        .as_ref()
-        .map(|s| s.name.as_str())
-        .unwrap_or("unknown");
-
     if let Err(e) = queries::insert_audit_log(
         pool,
         &request.user_id,
-        Some(&server_id.to_string()),
-        server_name,
+        Some(&server.server_id.to_string()),
+        &server.server_name,
         "tool_execution",
         Some(&audit_details),
     )
@@ -1732,9 +2137,81 @@ pub async fn execute_tool(
         success: !is_error,
         tool_name: request.tool_name.clone(),
         result: result_json,
+        fallback_server: fell_back.then_some(server.server_name),
     })
 }
 
+/// Resolve credentials and call `request`'s tool on `server_id`.
+async fn call_external_tool(
+    pool: &PgPool,
+    client_pool: &ClientPool,
+    request: &ExecutionRequest,
+    server_id: Uuid,
+    encryption_key: &str,
+) -> Result<CallToolResult, McpError> {
+    call_server_tool(
+        pool,
+        client_pool,
+        &request.user_id,
+        server_id,
+        &request.tool_name,
+        request.params.clone(),
+        &request.context,
+        encryption_key,
+    )
+    .await
+}
+
+/// Call `tool_name` on `server_id` with `user_id`'s credentials, bypassing
+/// the tool-access checks and failover of [`execute_tool`]. The keys of
+/// `context` the server receives go into the call's `_meta`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn call_server_tool(
+    pool: &PgPool,
+    client_pool: &ClientPool,
+    user_id: &str,
+    server_id: Uuid,
+    tool_name: &str,
+    arguments: Option<serde_json::Map<String, serde_json::Value>>,
+    context: &SessionContext,
+    encryption_key: &str,
+) -> Result<CallToolResult, McpError> {
+    // Resolve OAuth headers if the server uses OAuth auth
+    let oauth_headers = resolve_oauth_headers(pool, user_id, server_id, encryption_key).await?;
+    debug!(
+        user_id = %user_id,
+        server_id = %server_id,
+        tool_name = %tool_name,
+        oauth_headers_resolved = oauth_headers.is_some(),
+        "execute_tool oauth header resolution"
+    );
+
+    let meta = if context.is_empty() {
+        None
+    } else {
+        let keys = queries::server_forwarded_context(pool, &server_id).await?;
+        context.forwarded(&keys).map(Meta)
+    };
+
+    // Build call params
+    let call_params = CallToolRequestParams {
+        meta,
+        name: Cow::Owned(tool_name.to_string()),
+        arguments,
+        task: None,
+    };
+
+    // Try to execute with one retry on connection error
+    execute_with_retry(
+        pool,
+        client_pool,
+        server_id,
+        &call_params,
+        oauth_headers.as_ref(),
+    )
+    .await
+}
+
 /// Execute a tool call with one retry on connection error.
 async fn execute_with_retry(
     pool: &PgPool,
@@ -1750,6 +2227,8 @@ async fn execute_with_retry(
 
     match call_tool(client_pool, server_id, params).await {
         Ok(result) => return Ok(result),
+        // The server answered — reconnecting won't change its mind.
+        Err(e @ McpError::Upstream { .. }) => return Err(e),
         Err(e) => {
             debug!("Tool call failed, retrying after reconnect: {e}");
             client_pool.remove(&server_id);
@@ -1780,14 +2259,26 @@ async fn call_tool(
 
     let result = tokio::time::timeout(DEFAULT_TIMEOUT, peer.call_tool(params.clone()))
         .await
-        .map_err(|_| McpError::ConnectionFailed("Tool execution timed out (30s)".into()))?
-        .map_err(|e| McpError::ConnectionFailed(format!("Tool call failed: {e}")))?;
+        .map_err(|_| McpError::Timeout("Tool execution timed out (30s)".into()))?
+        .map_err(service_error)?;
 
     Ok(result)
 }
 
+/// Classify an rmcp service error, preserving upstream JSON-RPC codes.
+fn service_error(e: rmcp::ServiceError) -> McpError {
+    match e {
+        rmcp::ServiceError::McpError(data) => McpError::Upstream {
+            code: data.code.0,
+            message: data.message.into_owned(),
+        },
+        rmcp::ServiceError::Timeout { .. } => McpError::Timeout(format!("Tool call failed: {e}")),
+        other => McpError::ConnectionFailed(format!("Tool call failed: {other}")),
+    }
+}
+
 /// Convert a `CallToolResult` to a JSON value for our response.
-fn call_tool_result_to_json(result: &CallToolResult) -> serde_json::Value {
+pub(crate) fn call_tool_result_to_json(result: &CallToolResult) -> serde_json::Value {
     use rmcp::model::RawContent;
 
     let content_values: Vec<serde_json::Value> = result
@@ -1981,6 +2472,17 @@ mod tests {
         assert_eq!(pool.connections.len(), 0);
     }
 
+    #[test]
+    fn pin_and_unpin_track_membership() {
+        let pool = ClientPool::new();
+        let id = Uuid::new_v4();
+        assert!(!pool.is_pinned(&id));
+        pool.pin(id);
+        assert!(pool.is_pinned(&id));
+        pool.unpin(&id);
+        assert!(!pool.is_pinned(&id));
+    }
+
     // @awa-test: PLAN-030 Phase 1.1 — default pool has idle_timeout and epoch
     #[test]
     fn client_pool_default_has_idle_timeout_and_epoch() {
@@ -1989,4 +2491,54 @@ mod tests {
         // epoch should be roughly now
         assert!(pool.epoch.elapsed() < Duration::from_secs(1));
     }
+
+    #[test]
+    fn restarts_count_spawns_after_the_first() {
+        let pool = ClientPool::new();
+        let id = Uuid::new_v4();
+        assert_eq!(pool.restarts(&id), 0);
+        pool.record_spawn(id);
+        assert_eq!(pool.restarts(&id), 0);
+        pool.record_spawn(id);
+        pool.record_spawn(id);
+        assert_eq!(pool.restarts(&id), 2);
+    }
+
+    #[test]
+    fn sample_processes_reports_live_processes_only() {
+        let mut system = System::new();
+        let me = std::process::id();
+        let usage = sample_processes(&mut system, &[me, u32::MAX]);
+        assert_eq!(usage.len(), 1);
+        assert!(usage[&me].1 > 0);
+    }
+
+    #[test]
+    fn memory_limit_is_off_by_default() {
+        let mut pool = ClientPool::new();
+        assert!(pool.restart_over_memory_limit().is_empty());
+        pool.set_max_child_memory(Some(1));
+        assert!(pool.child_stats().is_empty());
+        assert!(pool.restart_over_memory_limit().is_empty());
+    }
+
+    #[test]
+    fn tool_summary_keeps_input_schema_and_annotations() {
+        let tool: rmcp::model::Tool = serde_json::from_value(serde_json::json!({
+            "name": "search",
+            "description": "Search things",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "q": { "type": "string" } },
+                "required": ["q"]
+            },
+            "annotations": { "readOnlyHint": true }
+        }))
+        .unwrap();
+
+        let manifest = serde_json::to_value(tool_summary(tool)).unwrap();
+        assert_eq!(manifest["name"], "search");
+        assert_eq!(manifest["inputSchema"]["required"][0], "q");
+        assert_eq!(manifest["annotations"]["readOnlyHint"], true);
+    }
 }
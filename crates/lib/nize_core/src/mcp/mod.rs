@@ -3,35 +3,46 @@
 //! Provides database queries, secret encryption, and shared business logic
 //! for MCP server configuration.
 
+pub mod analytics;
+pub mod audit;
+pub mod catalog;
+pub mod context;
 pub mod discovery;
 pub mod execution;
 pub mod oauth;
 pub mod queries;
+pub mod recording;
+pub mod routing;
+pub mod sandbox;
 pub mod secrets;
+pub mod sharing;
 pub mod sse_transport;
+pub mod templating;
+pub mod transforms;
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use uuid::Uuid;
+
+use crate::i18n::Message;
 
 /// MCP configuration errors.
 #[derive(Debug, Error)]
 pub enum McpError {
     #[error("Server not found: {0}")]
-    NotFound(String),
+    NotFound(Message),
 
     #[error("Forbidden: {0}")]
-    Forbidden(String),
+    Forbidden(Message),
 
     #[error("Validation error: {0}")]
-    Validation(String),
-
-    #[error("Server limit exceeded: maximum {0} user servers allowed")]
-    ServerLimitExceeded(usize),
+    Validation(Message),
 
     #[error("Duplicate server name: {0}")]
     DuplicateServer(String),
 
     #[error("Invalid transport: {0}")]
-    InvalidTransport(String),
+    InvalidTransport(Message),
 
     #[error("Connection failed: {0}")]
     ConnectionFailed(String),
@@ -39,9 +50,194 @@ pub enum McpError {
     #[error("Resource exhausted: {0}")]
     ResourceExhausted(String),
 
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    /// A JSON-RPC error returned by an upstream MCP server.
+    #[error("Upstream error {code}: {message}")]
+    Upstream { code: i32, message: String },
+
+    /// An error attributed to a specific upstream server.
+    #[error("{source}")]
+    Server {
+        server_id: Uuid,
+        #[source]
+        source: Box<McpError>,
+    },
+
     #[error("Encryption error: {0}")]
     EncryptionError(String),
 
     #[error("Database error: {0}")]
     DbError(#[from] sqlx::Error),
 }
+
+impl McpError {
+    /// Attribute this error to `server_id` (no-op if already attributed).
+    pub fn with_server(self, server_id: Uuid) -> Self {
+        match self {
+            McpError::Server { .. } => self,
+            other => McpError::Server {
+                server_id,
+                source: Box::new(other),
+            },
+        }
+    }
+
+    /// The error with any server attribution stripped.
+    pub fn root(&self) -> &McpError {
+        match self {
+            McpError::Server { source, .. } => source.root(),
+            other => other,
+        }
+    }
+
+    /// Structured description for clients deciding whether to retry.
+    pub fn info(&self) -> McpErrorInfo {
+        let (category, retryable, upstream_code) = match self.root() {
+            McpError::NotFound(_) => (McpErrorCategory::NotFound, false, None),
+            McpError::Forbidden(_) => (McpErrorCategory::Forbidden, false, None),
+            McpError::Validation(_) | McpError::InvalidTransport(_) => {
+                (McpErrorCategory::Validation, false, None)
+            }
+            McpError::DuplicateServer(_) => (McpErrorCategory::Conflict, false, None),
+            McpError::ConnectionFailed(_) => (McpErrorCategory::Connection, true, None),
+            McpError::ResourceExhausted(_) => (McpErrorCategory::ResourceExhausted, true, None),
+            McpError::Timeout(_) => (McpErrorCategory::Timeout, true, None),
+            McpError::Upstream { code, .. } => (
+                McpErrorCategory::Upstream,
+                *code == JSONRPC_INTERNAL_ERROR,
+                Some(*code),
+            ),
+            McpError::EncryptionError(_) => (McpErrorCategory::Internal, false, None),
+            McpError::DbError(e) => (
+                McpErrorCategory::Internal,
+                matches!(e, sqlx::Error::PoolTimedOut | sqlx::Error::Io(_)),
+                None,
+            ),
+            McpError::Server { .. } => unreachable!("root() strips server attribution"),
+        };
+        let server_id = match self {
+            McpError::Server { server_id, .. } => Some(server_id.to_string()),
+            _ => None,
+        };
+        McpErrorInfo {
+            category,
+            retryable,
+            server_id,
+            upstream_code,
+            message: self.to_string(),
+        }
+    }
+}
+
+/// JSON-RPC "internal error" — the only upstream code treated as transient.
+const JSONRPC_INTERNAL_ERROR: i32 = -32603;
+
+/// Broad class of an [`McpError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpErrorCategory {
+    NotFound,
+    Forbidden,
+    Validation,
+    LimitExceeded,
+    Conflict,
+    Connection,
+    Timeout,
+    ResourceExhausted,
+    Upstream,
+    Internal,
+}
+
+impl McpErrorCategory {
+    /// Stable snake_case name, matching the serialized form.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            McpErrorCategory::NotFound => "not_found",
+            McpErrorCategory::Forbidden => "forbidden",
+            McpErrorCategory::Validation => "validation",
+            McpErrorCategory::LimitExceeded => "limit_exceeded",
+            McpErrorCategory::Conflict => "conflict",
+            McpErrorCategory::Connection => "connection",
+            McpErrorCategory::Timeout => "timeout",
+            McpErrorCategory::ResourceExhausted => "resource_exhausted",
+            McpErrorCategory::Upstream => "upstream",
+            McpErrorCategory::Internal => "internal",
+        }
+    }
+}
+
+/// Structured error payload surfaced to REST and MCP clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpErrorInfo {
+    pub category: McpErrorCategory,
+    pub retryable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_id: Option<String>,
+    /// JSON-RPC error code from the upstream server, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream_code: Option<i32>,
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_failures_are_retryable() {
+        let info = McpError::ConnectionFailed("refused".into()).info();
+        assert_eq!(info.category, McpErrorCategory::Connection);
+        assert!(info.retryable);
+        assert_eq!(info.server_id, None);
+    }
+
+    #[test]
+    fn upstream_errors_carry_code_and_server() {
+        let server_id = Uuid::new_v4();
+        let err = McpError::Upstream {
+            code: -32602,
+            message: "bad params".into(),
+        }
+        .with_server(server_id);
+        let info = err.info();
+        assert_eq!(info.category, McpErrorCategory::Upstream);
+        assert!(!info.retryable);
+        assert_eq!(info.upstream_code, Some(-32602));
+        assert_eq!(info.server_id, Some(server_id.to_string()));
+        assert_eq!(info.message, "Upstream error -32602: bad params");
+    }
+
+    #[test]
+    fn with_server_does_not_nest() {
+        let first = Uuid::new_v4();
+        let err = McpError::Timeout("30s".into())
+            .with_server(first)
+            .with_server(Uuid::new_v4());
+        assert_eq!(err.info().server_id, Some(first.to_string()));
+        assert!(matches!(err.root(), McpError::Timeout(_)));
+    }
+
+    #[test]
+    fn info_serializes_camel_case() {
+        let json =
+            serde_json::to_value(McpError::NotFound(Message::new("mcp.server_not_found")).info())
+                .unwrap();
+        assert_eq!(json["category"], "not_found");
+        assert_eq!(json["retryable"], false);
+        assert!(json.get("serverId").is_none());
+    }
+
+    #[test]
+    fn category_as_str_matches_serde() {
+        for category in [
+            McpErrorCategory::LimitExceeded,
+            McpErrorCategory::ResourceExhausted,
+            McpErrorCategory::Upstream,
+        ] {
+            assert_eq!(serde_json::to_value(category).unwrap(), category.as_str());
+        }
+    }
+}
@@ -3,23 +3,38 @@
 //! Google OAuth support for MCP servers.
 //!
 //! Provides PKCE state management, token exchange, and token refresh for
-//! Google OAuth flows used with gogmcp servers.
+//! Google OAuth flows used with gogmcp servers. Pending authorizations are
+//! kept in the database, so a flow started before an API restart can still
+//! complete after it.
 
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
-use dashmap::DashMap;
 use serde::Deserialize;
 use sha2::{Digest, Sha256};
+use sqlx::PgPool;
 use tracing::debug;
 
 use super::McpError;
+use super::queries;
+use super::secrets::{self, SecretBinding};
+use crate::retry::RetryPolicy;
 
-/// TTL for PKCE state entries (10 minutes).
+/// How long a pending authorization stays valid (10 minutes).
 const STATE_TTL: Duration = Duration::from_secs(600);
 
 /// Preemptive refresh threshold — refresh when 80% of token lifetime has passed.
 const REFRESH_THRESHOLD_PERCENT: f64 = 0.80;
 
+/// Retries for token endpoint requests, within a user-facing request.
+const TOKEN_RETRY: RetryPolicy = RetryPolicy::new()
+    .max_attempts(3)
+    .base_delay(Duration::from_millis(250))
+    .deadline(Duration::from_secs(10));
+
+/// An authorization code is single-use, so once the token endpoint has
+/// answered the exchange is not repeated.
+const CODE_EXCHANGE_RETRY: RetryPolicy = TOKEN_RETRY.retry_statuses(false);
+
 // =============================================================================
 // PKCE helpers
 // =============================================================================
@@ -53,7 +68,7 @@ pub fn generate_state() -> String {
 }
 
 // =============================================================================
-// PKCE state store
+// Pending authorizations
 // =============================================================================
 
 /// Pending OAuth state stored between initiate and callback.
@@ -62,61 +77,72 @@ pub struct OAuthPendingState {
     pub user_id: String,
     pub pkce_verifier: String,
     pub oauth_config_json: serde_json::Value,
-    pub client_secret: String,
     pub redirect_uri: String,
-    pub created_at: Instant,
 }
 
-/// In-memory store for OAuth PKCE state (keyed by state parameter).
-pub struct OAuthStateStore {
-    states: DashMap<String, OAuthPendingState>,
+/// Key a pending authorization is stored under: the SHA-256 of the state
+/// parameter, so the table alone cannot be used to complete a flow.
+fn state_hash(state_key: &str) -> String {
+    format!("{:x}", Sha256::digest(state_key.as_bytes()))
 }
 
-impl OAuthStateStore {
-    pub fn new() -> Self {
-        Self {
-            states: DashMap::new(),
-        }
-    }
-
-    /// Insert a pending state entry.
-    pub fn insert(&self, state_key: String, pending: OAuthPendingState) {
-        self.states.insert(state_key, pending);
-    }
-
-    /// Take (remove and return) a pending state entry.
-    /// Returns `None` if not found or expired.
-    pub fn take(&self, state_key: &str) -> Option<OAuthPendingState> {
-        let (_, pending) = self.states.remove(state_key)?;
-        if pending.created_at.elapsed() > STATE_TTL {
-            return None;
-        }
-        Some(pending)
-    }
-
-    /// Evict expired entries.
-    pub fn cleanup(&self) {
-        self.states
-            .retain(|_, v| v.created_at.elapsed() <= STATE_TTL);
-    }
-
-    /// Spawn a periodic cleanup task.
-    pub fn spawn_cleanup_task(self: &std::sync::Arc<Self>) -> tokio::task::JoinHandle<()> {
-        let store = std::sync::Arc::clone(self);
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(60));
-            loop {
-                interval.tick().await;
-                store.cleanup();
-            }
-        })
+/// Store a pending authorization under its state parameter, with the PKCE
+/// verifier encrypted. Expired authorizations are pruned first.
+pub async fn save_pending_state(
+    pool: &PgPool,
+    state_key: &str,
+    pending: &OAuthPendingState,
+    encryption_key: &str,
+) -> Result<(), McpError> {
+    let pruned = queries::prune_oauth_pending(pool).await?;
+    if pruned > 0 {
+        debug!(pruned, "Pruned expired OAuth authorizations");
     }
+    let verifier_encrypted = secrets::encrypt_bound(
+        &pending.pkce_verifier,
+        encryption_key,
+        &SecretBinding::pkce_verifier(&pending.user_id, &pending.server_id),
+    )?;
+    queries::insert_oauth_pending(
+        pool,
+        &state_hash(state_key),
+        &pending.user_id,
+        &pending.server_id,
+        &verifier_encrypted,
+        &pending.oauth_config_json,
+        &pending.redirect_uri,
+        STATE_TTL,
+    )
+    .await
 }
 
-impl Default for OAuthStateStore {
-    fn default() -> Self {
-        Self::new()
+/// Take (remove and return) a pending authorization.
+/// Returns `None` if not found or expired.
+pub async fn take_pending_state(
+    pool: &PgPool,
+    state_key: &str,
+    encryption_key: &str,
+) -> Result<Option<OAuthPendingState>, McpError> {
+    let Some(row) = queries::take_oauth_pending(pool, &state_hash(state_key)).await? else {
+        return Ok(None);
+    };
+    if row.expires_at <= chrono::Utc::now() {
+        return Ok(None);
     }
+    let (user_id, server_id) = (row.user_id.to_string(), row.server_id.to_string());
+    let pkce_verifier = secrets::decrypt_bound(
+        &row.pkce_verifier_encrypted,
+        encryption_key,
+        &SecretBinding::pkce_verifier(&user_id, &server_id),
+    )?
+    .plaintext;
+    Ok(Some(OAuthPendingState {
+        server_id,
+        user_id,
+        pkce_verifier,
+        oauth_config_json: row.oauth_config,
+        redirect_uri: row.redirect_uri,
+    }))
 }
 
 // =============================================================================
@@ -155,10 +181,8 @@ pub async fn exchange_authorization_code(
         ("code_verifier", code_verifier),
     ];
 
-    let resp = client
-        .post(token_url)
-        .form(&params)
-        .send()
+    let resp = CODE_EXCHANGE_RETRY
+        .send(|| client.post(token_url).form(&params))
         .await
         .map_err(|e| McpError::ConnectionFailed(format!("Token exchange failed: {e}")))?;
 
@@ -196,10 +220,8 @@ pub async fn refresh_google_tokens(
         ("refresh_token", refresh_token),
     ];
 
-    let resp = client
-        .post(token_url)
-        .form(&params)
-        .send()
+    let resp = TOKEN_RETRY
+        .send(|| client.post(token_url).form(&params))
         .await
         .map_err(|e| McpError::ConnectionFailed(format!("Token refresh failed: {e}")))?;
 
@@ -239,7 +261,6 @@ pub fn should_refresh(expires_at: &chrono::DateTime<chrono::Utc>) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::Arc;
 
     // @awa-test: PLAN-031 Phase 9.1 — PKCE code verifier generation
     #[test]
@@ -276,94 +297,12 @@ mod tests {
         assert!(s1.len() >= 20);
     }
 
-    // @awa-test: PLAN-031 Phase 9.1 — state store insert and take
-    #[test]
-    fn state_store_insert_and_take() {
-        let store = OAuthStateStore::new();
-        store.insert(
-            "test-key".into(),
-            OAuthPendingState {
-                server_id: "srv1".into(),
-                user_id: "usr1".into(),
-                pkce_verifier: "verifier".into(),
-                oauth_config_json: serde_json::json!({}),
-                client_secret: "secret".into(),
-                redirect_uri: "http://localhost/callback".into(),
-                created_at: Instant::now(),
-            },
-        );
-
-        let taken = store.take("test-key");
-        assert!(taken.is_some());
-        assert_eq!(taken.unwrap().server_id, "srv1");
-
-        // Second take returns None (consumed)
-        assert!(store.take("test-key").is_none());
-    }
-
-    // @awa-test: PLAN-031 Phase 9.1 — state store expired entry
     #[test]
-    fn state_store_expired_entry_returns_none() {
-        let store = OAuthStateStore::new();
-        store.insert(
-            "old-key".into(),
-            OAuthPendingState {
-                server_id: "srv1".into(),
-                user_id: "usr1".into(),
-                pkce_verifier: "verifier".into(),
-                oauth_config_json: serde_json::json!({}),
-                client_secret: "secret".into(),
-                redirect_uri: "http://localhost/callback".into(),
-                created_at: Instant::now() - Duration::from_secs(700), // past TTL
-            },
-        );
-
-        assert!(store.take("old-key").is_none());
-    }
-
-    // @awa-test: PLAN-031 Phase 9.1 — state store cleanup
-    #[test]
-    fn state_store_cleanup_removes_expired() {
-        let store = OAuthStateStore::new();
-        store.insert(
-            "fresh".into(),
-            OAuthPendingState {
-                server_id: "srv1".into(),
-                user_id: "usr1".into(),
-                pkce_verifier: "v".into(),
-                oauth_config_json: serde_json::json!({}),
-                client_secret: "s".into(),
-                redirect_uri: "http://localhost/callback".into(),
-                created_at: Instant::now(),
-            },
-        );
-        store.insert(
-            "stale".into(),
-            OAuthPendingState {
-                server_id: "srv2".into(),
-                user_id: "usr1".into(),
-                pkce_verifier: "v".into(),
-                oauth_config_json: serde_json::json!({}),
-                client_secret: "s".into(),
-                redirect_uri: "http://localhost/callback".into(),
-                created_at: Instant::now() - Duration::from_secs(700),
-            },
-        );
-
-        store.cleanup();
-        assert!(store.take("fresh").is_some());
-        // "stale" was already cleaned up
-        assert!(store.take("stale").is_none());
-    }
-
-    // @awa-test: PLAN-031 Phase 9.1 — spawn_cleanup_task compiles
-    #[tokio::test]
-    async fn spawn_cleanup_task_runs() {
-        let store = Arc::new(OAuthStateStore::new());
-        let handle = store.spawn_cleanup_task();
-        // Let it tick once
-        tokio::time::sleep(Duration::from_millis(50)).await;
-        handle.abort();
+    fn state_hash_is_stable_and_hides_the_state() {
+        let state = generate_state();
+        assert_eq!(state_hash(&state), state_hash(&state));
+        assert_eq!(state_hash(&state).len(), 64);
+        assert!(!state_hash(&state).contains(&state));
     }
 
     // @awa-test: PLAN-031 Phase 6.1 — should_refresh logic
@@ -2,12 +2,16 @@
 //!
 //! Raw SQLx queries for CRUD operations on MCP tables.
 
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgExecutor, PgPool};
+use uuid::Uuid;
 
 use super::McpError;
+use crate::conversations::ToolSelection;
+use crate::i18n::Message;
 use crate::models::mcp::{
-    AuthType, McpOauthTokenRow, McpServerRow, McpServerToolRow, McpToolSummary, ServerConfig,
-    TransportType, UserMcpPreferenceRow, VisibilityTier,
+    AuthType, DISCOVERY_RUNNING, McpDiscoveryRow, McpOauthPendingRow, McpOauthTokenRow,
+    McpServerRow, McpServerSecretRow, McpServerStatsRow, McpServerToolRow, McpToolSummary,
+    ServerConfig, TransportType, UserMcpPreferenceRow, VisibilityTier,
 };
 use crate::uuid::uuidv7;
 
@@ -15,7 +19,8 @@ use crate::uuid::uuidv7;
 // Server queries
 // =============================================================================
 
-/// List servers visible to a user (visibility=visible OR owner's user servers).
+/// List servers visible to a user (visibility=visible, the user's own servers,
+/// or user servers shared in one of their workspaces).
 pub async fn list_servers_for_user(
     pool: &PgPool,
     user_id: &str,
@@ -25,12 +30,15 @@ pub async fn list_servers_for_user(
         SELECT id, name, description, domain, endpoint,
                visibility, transport, config, oauth_config,
                default_response_size_limit, owner_id,
-               enabled, available, created_at, updated_at
+               enabled, available, keep_warm, forwarded_context, created_at, updated_at
         FROM mcp_servers
         WHERE enabled = true
           AND (
             visibility = 'visible'
             OR (visibility = 'user' AND owner_id = $1::uuid)
+            OR (visibility = 'user' AND workspace_id IN (
+              SELECT workspace_id FROM workspace_members WHERE user_id = $1::uuid
+            ))
           )
         ORDER BY name
         "#,
@@ -48,7 +56,7 @@ pub async fn list_all_servers(pool: &PgPool) -> Result<Vec<McpServerRow>, McpErr
         SELECT id, name, description, domain, endpoint,
                visibility, transport, config, oauth_config,
                default_response_size_limit, owner_id,
-               enabled, available, created_at, updated_at
+               enabled, available, keep_warm, forwarded_context, created_at, updated_at
         FROM mcp_servers
         ORDER BY visibility, name
         "#,
@@ -65,7 +73,7 @@ pub async fn get_server(pool: &PgPool, server_id: &str) -> Result<Option<McpServ
         SELECT id, name, description, domain, endpoint,
                visibility, transport, config, oauth_config,
                default_response_size_limit, owner_id,
-               enabled, available, created_at, updated_at
+               enabled, available, keep_warm, forwarded_context, created_at, updated_at
         FROM mcp_servers
         WHERE id = $1::uuid
         "#,
@@ -111,7 +119,7 @@ pub async fn user_has_server_named(
 /// Insert a new user server (visibility=user).
 #[allow(clippy::too_many_arguments)]
 pub async fn insert_user_server(
-    pool: &PgPool,
+    conn: impl PgExecutor<'_>,
     user_id: &str,
     name: &str,
     description: &str,
@@ -120,8 +128,9 @@ pub async fn insert_user_server(
     oauth_config: Option<&serde_json::Value>,
     available: bool,
 ) -> Result<McpServerRow, McpError> {
-    let config_json = serde_json::to_value(config)
-        .map_err(|e| McpError::Validation(format!("Failed to serialize config: {e}")))?;
+    let config_json = serde_json::to_value(config).map_err(|e| {
+        McpError::Validation(Message::new("mcp.config_serialize_failed").arg("error", e))
+    })?;
 
     let row = sqlx::query_as::<_, McpServerRow>(
         r#"
@@ -130,7 +139,7 @@ pub async fn insert_user_server(
         RETURNING id, name, description, domain, endpoint,
                   visibility, transport, config, oauth_config,
                   default_response_size_limit, owner_id,
-                  enabled, available, created_at, updated_at
+                  enabled, available, keep_warm, forwarded_context, created_at, updated_at
         "#,
     )
     .bind(uuidv7())
@@ -143,7 +152,7 @@ pub async fn insert_user_server(
     .bind(oauth_config)
     .bind(user_id)
     .bind(available)
-    .fetch_one(pool)
+    .fetch_one(conn)
     .await?;
     Ok(row)
 }
@@ -151,7 +160,7 @@ pub async fn insert_user_server(
 /// Insert a built-in server (admin).
 #[allow(clippy::too_many_arguments)]
 pub async fn insert_built_in_server(
-    pool: &PgPool,
+    conn: impl PgExecutor<'_>,
     name: &str,
     description: &str,
     domain: &str,
@@ -169,7 +178,7 @@ pub async fn insert_built_in_server(
         RETURNING id, name, description, domain, endpoint,
                   visibility, transport, config, oauth_config,
                   default_response_size_limit, owner_id,
-                  enabled, available, created_at, updated_at
+                  enabled, available, keep_warm, forwarded_context, created_at, updated_at
         "#,
     )
     .bind(uuidv7())
@@ -182,7 +191,7 @@ pub async fn insert_built_in_server(
     .bind(config)
     .bind(oauth_config)
     .bind(available)
-    .fetch_one(pool)
+    .fetch_one(conn)
     .await?;
     Ok(row)
 }
@@ -190,7 +199,7 @@ pub async fn insert_built_in_server(
 /// Update a server's fields. Only non-None fields are updated.
 #[allow(clippy::too_many_arguments)]
 pub async fn update_server(
-    pool: &PgPool,
+    conn: impl PgExecutor<'_>,
     server_id: &str,
     name: Option<&str>,
     description: Option<&str>,
@@ -220,7 +229,7 @@ pub async fn update_server(
         RETURNING id, name, description, domain, endpoint,
                   visibility, transport, config, oauth_config,
                   default_response_size_limit, owner_id,
-                  enabled, available, created_at, updated_at
+                  enabled, available, keep_warm, forwarded_context, created_at, updated_at
         "#,
     )
     .bind(server_id)
@@ -233,12 +242,123 @@ pub async fn update_server(
     .bind(visibility)
     .bind(available)
     .bind(oauth_config)
-    .fetch_optional(pool)
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id)))?;
+    Ok(row)
+}
+
+/// Set a server's keep-warm flag.
+pub async fn set_server_keep_warm(
+    conn: impl PgExecutor<'_>,
+    server_id: &str,
+    keep_warm: bool,
+) -> Result<McpServerRow, McpError> {
+    let row = sqlx::query_as::<_, McpServerRow>(
+        r#"
+        UPDATE mcp_servers SET keep_warm = $2, updated_at = now()
+        WHERE id = $1::uuid
+        RETURNING id, name, description, domain, endpoint,
+                  visibility, transport, config, oauth_config,
+                  default_response_size_limit, owner_id,
+                  enabled, available, keep_warm, forwarded_context, created_at, updated_at
+        "#,
+    )
+    .bind(server_id)
+    .bind(keep_warm)
+    .fetch_optional(conn)
     .await?
-    .ok_or_else(|| McpError::NotFound(format!("Server {server_id} not found")))?;
+    .ok_or_else(|| McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id)))?;
     Ok(row)
 }
 
+/// Set the caller context keys a server receives.
+pub async fn set_server_forwarded_context(
+    conn: impl PgExecutor<'_>,
+    server_id: &str,
+    keys: &[String],
+) -> Result<McpServerRow, McpError> {
+    let row = sqlx::query_as::<_, McpServerRow>(
+        r#"
+        UPDATE mcp_servers SET forwarded_context = $2, updated_at = now()
+        WHERE id = $1::uuid
+        RETURNING id, name, description, domain, endpoint,
+                  visibility, transport, config, oauth_config,
+                  default_response_size_limit, owner_id,
+                  enabled, available, keep_warm, forwarded_context, created_at, updated_at
+        "#,
+    )
+    .bind(server_id)
+    .bind(keys)
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| McpError::NotFound(Message::new("mcp.server_not_found").arg("id", server_id)))?;
+    Ok(row)
+}
+
+/// The caller context keys a server receives (none for unknown servers).
+pub async fn server_forwarded_context(
+    pool: &PgPool,
+    server_id: &Uuid,
+) -> Result<Vec<String>, McpError> {
+    let keys = sqlx::query_scalar::<_, Vec<String>>(
+        "SELECT forwarded_context FROM mcp_servers WHERE id = $1",
+    )
+    .bind(server_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(keys.unwrap_or_default())
+}
+
+/// List enabled servers flagged keep-warm.
+pub async fn list_keep_warm_servers(pool: &PgPool) -> Result<Vec<McpServerRow>, McpError> {
+    let rows = sqlx::query_as::<_, McpServerRow>(
+        r#"
+        SELECT id, name, description, domain, endpoint,
+               visibility, transport, config, oauth_config,
+               default_response_size_limit, owner_id,
+               enabled, available, keep_warm, forwarded_context, created_at, updated_at
+        FROM mcp_servers
+        WHERE enabled = true AND keep_warm = true
+        ORDER BY name
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// The workspace a server is shared with, if any.
+pub async fn get_server_workspace(
+    conn: impl PgExecutor<'_>,
+    server_id: &str,
+) -> Result<Option<Uuid>, McpError> {
+    let workspace_id = sqlx::query_scalar::<_, Option<Uuid>>(
+        "SELECT workspace_id FROM mcp_servers WHERE id = $1::uuid",
+    )
+    .bind(server_id)
+    .fetch_optional(conn)
+    .await?;
+    Ok(workspace_id.flatten())
+}
+
+/// Share a server with a workspace (or make it personal again with `None`).
+///
+/// Does not re-encrypt the server's secrets; see
+/// [`super::sharing::share_server`].
+pub async fn set_server_workspace(
+    conn: impl PgExecutor<'_>,
+    server_id: &str,
+    workspace_id: Option<&Uuid>,
+) -> Result<(), McpError> {
+    sqlx::query("UPDATE mcp_servers SET workspace_id = $2, updated_at = now() WHERE id = $1::uuid")
+        .bind(server_id)
+        .bind(workspace_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
 /// Delete a server by ID.
 pub async fn delete_server(pool: &PgPool, server_id: &str) -> Result<bool, McpError> {
     let result = sqlx::query("DELETE FROM mcp_servers WHERE id = $1::uuid")
@@ -293,6 +413,37 @@ pub async fn set_user_preference(
 // Tool queries
 // =============================================================================
 
+/// SQL condition: tool alias `t` (an `mcp_server_tools` row) is neither
+/// switched off by an admin nor by the user bound at `user_param` (`$1`).
+pub(crate) fn tool_enabled_sql(t: &str, user_param: &str) -> String {
+    format!(
+        r#"NOT EXISTS (
+              SELECT 1 FROM mcp_tool_overrides o
+              WHERE o.server_id = {t}.server_id AND o.tool_name = {t}.name AND o.enabled = false
+            )
+            AND NOT EXISTS (
+              SELECT 1 FROM user_mcp_tool_preferences tp
+              WHERE tp.user_id = {user_param}::uuid AND tp.server_id = {t}.server_id
+                AND tp.tool_name = {t}.name AND tp.enabled = false
+            )"#
+    )
+}
+
+/// SQL condition: server alias `s` is in the [`ToolSelection`] bound as
+/// JSONB at `param`, or no selection (NULL) is bound.
+pub(crate) fn tool_selection_sql(s: &str, param: &str) -> String {
+    format!(
+        r#"({param}::jsonb IS NULL
+            OR {s}.id::text IN (SELECT jsonb_array_elements_text({param}::jsonb -> 'serverIds'))
+            OR {s}.domain IN (SELECT jsonb_array_elements_text({param}::jsonb -> 'domains')))"#
+    )
+}
+
+/// Bind value for [`tool_selection_sql`].
+pub(crate) fn tool_selection_param(selection: Option<&ToolSelection>) -> Option<serde_json::Value> {
+    selection.map(|s| serde_json::to_value(s).unwrap_or_default())
+}
+
 /// Get tools for a server.
 pub async fn list_server_tools(
     pool: &PgPool,
@@ -312,6 +463,113 @@ pub async fn list_server_tools(
     Ok(rows)
 }
 
+/// Get one tool of a server.
+pub async fn get_server_tool(
+    pool: &PgPool,
+    server_id: &str,
+    tool_id: &str,
+) -> Result<Option<McpServerToolRow>, McpError> {
+    let row = sqlx::query_as::<_, McpServerToolRow>(
+        r#"
+        SELECT id, server_id, name, description, manifest, response_size_limit, created_at
+        FROM mcp_server_tools
+        WHERE id = $2::uuid AND server_id = $1::uuid
+        "#,
+    )
+    .bind(server_id)
+    .bind(tool_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Whether a tool is switched on by the admin and by one user.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ToolEnablementRow {
+    pub tool_id: Uuid,
+    pub tool_name: String,
+    pub admin_enabled: bool,
+    pub user_enabled: bool,
+}
+
+/// Enablement of each of a server's tools for a user.
+pub async fn list_tool_enablement(
+    pool: &PgPool,
+    server_id: &str,
+    user_id: &str,
+) -> Result<Vec<ToolEnablementRow>, McpError> {
+    let rows = sqlx::query_as::<_, ToolEnablementRow>(
+        r#"
+        SELECT t.id AS tool_id, t.name AS tool_name,
+               COALESCE(o.enabled, true) AS admin_enabled,
+               COALESCE(tp.enabled, true) AS user_enabled
+        FROM mcp_server_tools t
+        LEFT JOIN mcp_tool_overrides o
+          ON o.server_id = t.server_id AND o.tool_name = t.name
+        LEFT JOIN user_mcp_tool_preferences tp
+          ON tp.user_id = $2::uuid AND tp.server_id = t.server_id AND tp.tool_name = t.name
+        WHERE t.server_id = $1::uuid
+        ORDER BY t.name
+        "#,
+    )
+    .bind(server_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Switch a tool on or off for every user (admin).
+pub async fn set_tool_override(
+    pool: &PgPool,
+    server_id: &str,
+    tool_name: &str,
+    enabled: bool,
+    admin_id: &str,
+) -> Result<(), McpError> {
+    sqlx::query(
+        r#"
+        INSERT INTO mcp_tool_overrides (server_id, tool_name, enabled, updated_by, updated_at)
+        VALUES ($1::uuid, $2, $3, $4::uuid, now())
+        ON CONFLICT (server_id, tool_name)
+        DO UPDATE SET enabled = EXCLUDED.enabled, updated_by = EXCLUDED.updated_by,
+                      updated_at = now()
+        "#,
+    )
+    .bind(server_id)
+    .bind(tool_name)
+    .bind(enabled)
+    .bind(admin_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Upsert a user's tool preference (switch a tool on or off for them).
+pub async fn set_user_tool_preference(
+    pool: &PgPool,
+    user_id: &str,
+    server_id: &str,
+    tool_name: &str,
+    enabled: bool,
+) -> Result<(), McpError> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_mcp_tool_preferences (user_id, server_id, tool_name, enabled, updated_at)
+        VALUES ($1::uuid, $2::uuid, $3, $4, now())
+        ON CONFLICT (user_id, server_id, tool_name)
+        DO UPDATE SET enabled = EXCLUDED.enabled, updated_at = now()
+        "#,
+    )
+    .bind(user_id)
+    .bind(server_id)
+    .bind(tool_name)
+    .bind(enabled)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 /// Count tools for a server.
 pub async fn get_tool_count(pool: &PgPool, server_id: &str) -> Result<i64, McpError> {
     let count = sqlx::query_scalar::<_, i64>(
@@ -323,28 +581,76 @@ pub async fn get_tool_count(pool: &PgPool, server_id: &str) -> Result<i64, McpEr
     Ok(count)
 }
 
-/// Replace all tools for a server (delete existing + insert new).
-pub async fn replace_server_tools(
+/// Get tool counts, enabled-preference counts and (for `user_id`) OAuth
+/// token validity of many servers in one round-trip. Returns one row per
+/// requested server.
+pub async fn get_server_stats(
     pool: &PgPool,
+    server_ids: &[Uuid],
+    user_id: Option<&str>,
+) -> Result<Vec<McpServerStatsRow>, McpError> {
+    let rows = sqlx::query_as::<_, McpServerStatsRow>(
+        r#"
+        SELECT s.id AS server_id,
+               COALESCE(t.tool_count, 0) AS tool_count,
+               COALESCE(p.user_preference_count, 0) AS user_preference_count,
+               o.server_id IS NOT NULL AS has_valid_oauth_token
+        FROM unnest($1::uuid[]) AS s(id)
+        LEFT JOIN (
+            SELECT server_id, COUNT(*) AS tool_count
+            FROM mcp_server_tools
+            WHERE server_id = ANY($1)
+            GROUP BY server_id
+        ) t ON t.server_id = s.id
+        LEFT JOIN (
+            SELECT server_id, COUNT(*) AS user_preference_count
+            FROM user_mcp_preferences
+            WHERE server_id = ANY($1) AND enabled = true
+            GROUP BY server_id
+        ) p ON p.server_id = s.id
+        LEFT JOIN LATERAL (
+            SELECT server_id FROM mcp_oauth_tokens
+            WHERE user_id = $2::uuid AND server_id = s.id AND expires_at > now()
+            LIMIT 1
+        ) o ON true
+        "#,
+    )
+    .bind(server_ids)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Replace all tools for a server. Tools that are still offered keep their
+/// rows (and so their IDs and embeddings) with an updated description and
+/// manifest; the rest are deleted. Run it in a transaction so readers never
+/// see a partial tool list.
+pub async fn replace_server_tools(
+    conn: &mut PgConnection,
     server_id: &str,
     tools: &[McpToolSummary],
 ) -> Result<(), McpError> {
-    // Delete existing
-    sqlx::query("DELETE FROM mcp_server_tools WHERE server_id = $1::uuid")
+    // Delete tools the server no longer offers
+    let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+    sqlx::query("DELETE FROM mcp_server_tools WHERE server_id = $1::uuid AND name <> ALL($2)")
         .bind(server_id)
-        .execute(pool)
+        .bind(&names)
+        .execute(&mut *conn)
         .await?;
 
-    // Insert new
+    // Insert new tools, update the rest
     for tool in tools {
-        let manifest = serde_json::json!({
-            "name": tool.name,
-            "description": tool.description,
-        });
+        let manifest = serde_json::to_value(tool).map_err(|e| {
+            McpError::Validation(Message::new("mcp.tool_serialize_failed").arg("error", e))
+        })?;
         sqlx::query(
             r#"
             INSERT INTO mcp_server_tools (id, server_id, name, description, manifest)
             VALUES ($1, $2::uuid, $3, $4, $5)
+            ON CONFLICT (server_id, name) DO UPDATE SET
+                description = EXCLUDED.description,
+                manifest = EXCLUDED.manifest
             "#,
         )
         .bind(uuidv7())
@@ -352,12 +658,84 @@ pub async fn replace_server_tools(
         .bind(&tool.name)
         .bind(&tool.description)
         .bind(&manifest)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
     }
     Ok(())
 }
 
+// =============================================================================
+// Tool discovery status
+// =============================================================================
+
+/// Record that tool discovery started for a server, replacing the outcome
+/// of any previous run.
+pub async fn start_discovery(
+    conn: impl PgExecutor<'_>,
+    server_id: &str,
+) -> Result<McpDiscoveryRow, McpError> {
+    let row = sqlx::query_as::<_, McpDiscoveryRow>(
+        r#"
+        INSERT INTO mcp_server_discovery (server_id, status, tool_count, error, started_at, finished_at)
+        VALUES ($1::uuid, $2, 0, NULL, now(), NULL)
+        ON CONFLICT (server_id) DO UPDATE SET
+            status = EXCLUDED.status,
+            tool_count = 0,
+            error = NULL,
+            started_at = now(),
+            finished_at = NULL
+        RETURNING server_id, status, tool_count, error, started_at, finished_at
+        "#,
+    )
+    .bind(server_id)
+    .bind(DISCOVERY_RUNNING)
+    .fetch_one(conn)
+    .await?;
+    Ok(row)
+}
+
+/// Record the outcome of a server's tool discovery.
+pub async fn finish_discovery(
+    conn: impl PgExecutor<'_>,
+    server_id: &str,
+    status: &str,
+    tool_count: i32,
+    error: Option<&str>,
+) -> Result<(), McpError> {
+    sqlx::query(
+        r#"
+        UPDATE mcp_server_discovery
+        SET status = $2, tool_count = $3, error = $4, finished_at = now()
+        WHERE server_id = $1::uuid
+        "#,
+    )
+    .bind(server_id)
+    .bind(status)
+    .bind(tool_count)
+    .bind(error)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Get the latest tool discovery of each of the given servers that has one.
+pub async fn list_discoveries(
+    pool: &PgPool,
+    server_ids: &[Uuid],
+) -> Result<Vec<McpDiscoveryRow>, McpError> {
+    let rows = sqlx::query_as::<_, McpDiscoveryRow>(
+        r#"
+        SELECT server_id, status, tool_count, error, started_at, finished_at
+        FROM mcp_server_discovery
+        WHERE server_id = ANY($1)
+        "#,
+    )
+    .bind(server_ids)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
 // =============================================================================
 // Preference count (admin)
 // =============================================================================
@@ -474,11 +852,69 @@ pub async fn delete_oauth_token(
 
 /// Delete all OAuth tokens for a server (all users).
 pub async fn delete_all_oauth_tokens_for_server(
-    pool: &PgPool,
+    conn: impl PgExecutor<'_>,
     server_id: &str,
 ) -> Result<u64, McpError> {
     let result = sqlx::query("DELETE FROM mcp_oauth_tokens WHERE server_id = $1::uuid")
         .bind(server_id)
+        .execute(conn)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Store a pending OAuth authorization, valid for `ttl`.
+#[allow(clippy::too_many_arguments)]
+pub async fn insert_oauth_pending(
+    pool: &PgPool,
+    state_hash: &str,
+    user_id: &str,
+    server_id: &str,
+    pkce_verifier_encrypted: &str,
+    oauth_config: &serde_json::Value,
+    redirect_uri: &str,
+    ttl: std::time::Duration,
+) -> Result<(), McpError> {
+    sqlx::query(
+        r#"
+        INSERT INTO mcp_oauth_pending
+            (state_hash, user_id, server_id, pkce_verifier_encrypted, oauth_config,
+             redirect_uri, expires_at)
+        VALUES ($1, $2::uuid, $3::uuid, $4, $5, $6, now() + make_interval(secs => $7))
+        "#,
+    )
+    .bind(state_hash)
+    .bind(user_id)
+    .bind(server_id)
+    .bind(pkce_verifier_encrypted)
+    .bind(oauth_config)
+    .bind(redirect_uri)
+    .bind(ttl.as_secs_f64())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Remove and return a pending OAuth authorization, expired or not.
+pub async fn take_oauth_pending(
+    pool: &PgPool,
+    state_hash: &str,
+) -> Result<Option<McpOauthPendingRow>, McpError> {
+    let row = sqlx::query_as::<_, McpOauthPendingRow>(
+        r#"
+        DELETE FROM mcp_oauth_pending WHERE state_hash = $1
+        RETURNING user_id, server_id, pkce_verifier_encrypted, oauth_config,
+                  redirect_uri, expires_at
+        "#,
+    )
+    .bind(state_hash)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Delete expired pending OAuth authorizations.
+pub async fn prune_oauth_pending(pool: &PgPool) -> Result<u64, McpError> {
+    let result = sqlx::query("DELETE FROM mcp_oauth_pending WHERE expires_at <= now()")
         .execute(pool)
         .await?;
     Ok(result.rows_affected())
@@ -490,7 +926,7 @@ pub async fn delete_all_oauth_tokens_for_server(
 
 /// Store or update an encrypted API key for a server.
 pub async fn store_api_key(
-    pool: &PgPool,
+    conn: impl PgExecutor<'_>,
     server_id: &str,
     api_key_encrypted: &str,
     encryption_key_id: &str,
@@ -509,7 +945,7 @@ pub async fn store_api_key(
     .bind(server_id)
     .bind(api_key_encrypted)
     .bind(encryption_key_id)
-    .execute(pool)
+    .execute(conn)
     .await?;
     Ok(())
 }
@@ -530,7 +966,7 @@ pub async fn get_api_key_encrypted(
 
 /// Store or update an encrypted OAuth client secret for a server.
 pub async fn store_oauth_client_secret(
-    pool: &PgPool,
+    conn: impl PgExecutor<'_>,
     server_id: &str,
     secret_encrypted: &str,
     encryption_key_id: &str,
@@ -549,23 +985,86 @@ pub async fn store_oauth_client_secret(
     .bind(server_id)
     .bind(secret_encrypted)
     .bind(encryption_key_id)
-    .execute(pool)
+    .execute(conn)
     .await?;
     Ok(())
 }
 
-/// Get the encrypted OAuth client secret for a server.
-pub async fn get_oauth_client_secret_encrypted(
-    pool: &PgPool,
+/// Get a server's encrypted secrets and the key they are encrypted with.
+pub async fn get_server_secrets(
+    conn: impl PgExecutor<'_>,
     server_id: &str,
-) -> Result<Option<String>, McpError> {
-    let row = sqlx::query_scalar::<_, Option<String>>(
-        "SELECT oauth_client_secret_encrypted FROM mcp_server_secrets WHERE server_id = $1::uuid",
+) -> Result<Option<McpServerSecretRow>, McpError> {
+    let row = sqlx::query_as::<_, McpServerSecretRow>(
+        r#"
+        SELECT id, server_id, api_key_encrypted, oauth_client_secret_encrypted,
+               encryption_key_id, created_at, updated_at
+        FROM mcp_server_secrets
+        WHERE server_id = $1::uuid
+        "#,
     )
     .bind(server_id)
-    .fetch_optional(pool)
+    .fetch_optional(conn)
     .await?;
-    Ok(row.flatten())
+    Ok(row)
+}
+
+/// Overwrite all of a server's encrypted secrets and their key id, for
+/// re-encryption under another key.
+pub async fn replace_server_secrets(
+    conn: impl PgExecutor<'_>,
+    server_id: &str,
+    api_key_encrypted: Option<&str>,
+    oauth_client_secret_encrypted: Option<&str>,
+    encryption_key_id: &str,
+) -> Result<(), McpError> {
+    sqlx::query(
+        r#"
+        UPDATE mcp_server_secrets
+        SET api_key_encrypted = $2, oauth_client_secret_encrypted = $3,
+            encryption_key_id = $4, updated_at = now()
+        WHERE server_id = $1::uuid
+        "#,
+    )
+    .bind(server_id)
+    .bind(api_key_encrypted)
+    .bind(oauth_client_secret_encrypted)
+    .bind(encryption_key_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Replace an encrypted secret column, but only while it still holds
+/// `old_encrypted` — a concurrent update wins over the rewrite. `table` and
+/// `column` come from [`super::secrets::SecretField`], never from input.
+pub(crate) async fn replace_encrypted_secret(
+    pool: &PgPool,
+    table: &str,
+    column: &str,
+    server_id: &str,
+    user_id: Option<&str>,
+    old_encrypted: &str,
+    new_encrypted: &str,
+) -> Result<(), McpError> {
+    let user_filter = if user_id.is_some() {
+        " AND user_id = $4::uuid"
+    } else {
+        ""
+    };
+    let sql = format!(
+        "UPDATE {table} SET {column} = $1, updated_at = now() \
+         WHERE server_id = $2::uuid AND {column} = $3{user_filter}"
+    );
+    let mut query = sqlx::query(&sql)
+        .bind(new_encrypted)
+        .bind(server_id)
+        .bind(old_encrypted);
+    if let Some(user_id) = user_id {
+        query = query.bind(user_id);
+    }
+    query.execute(pool).await?;
+    Ok(())
 }
 
 // =============================================================================
@@ -574,7 +1073,7 @@ pub async fn get_oauth_client_secret_encrypted(
 
 /// Insert an audit log entry.
 pub async fn insert_audit_log(
-    pool: &PgPool,
+    conn: impl PgExecutor<'_>,
     actor_id: &str,
     server_id: Option<&str>,
     server_name: &str,
@@ -593,7 +1092,7 @@ pub async fn insert_audit_log(
     .bind(server_name)
     .bind(action)
     .bind(details)
-    .execute(pool)
+    .execute(conn)
     .await?;
     Ok(())
 }
@@ -643,12 +1142,14 @@ pub struct ToolDomainRow {
 /// List distinct tool domains visible to a user, with tool counts.
 ///
 /// Filters by servers the user has access to: globally visible servers
-/// (unless explicitly disabled) or explicitly enabled servers.
+/// (unless explicitly disabled) or explicitly enabled servers, narrowed to
+/// a conversation's `selection` when one is pinned.
 pub async fn list_tool_domains(
     pool: &PgPool,
     user_id: &str,
+    selection: Option<&ToolSelection>,
 ) -> Result<Vec<ToolDomainRow>, McpError> {
-    let rows = sqlx::query_as::<_, (String, i64)>(
+    let rows = sqlx::query_as::<_, (String, i64)>(&format!(
         r#"
         SELECT s.domain, COUNT(*) AS tool_count
         FROM mcp_server_tools t
@@ -664,11 +1165,16 @@ pub async fn list_tool_domains(
               WHERE p.user_id = $1::uuid AND p.server_id = s.id AND p.enabled = true
             )
           )
+          AND {tool_enabled}
+          AND {selected}
         GROUP BY s.domain
         ORDER BY s.domain
         "#,
-    )
+        tool_enabled = tool_enabled_sql("t", "$1"),
+        selected = tool_selection_sql("s", "$2"),
+    ))
     .bind(user_id)
+    .bind(tool_selection_param(selection))
     .fetch_all(pool)
     .await?;
 
@@ -689,11 +1195,13 @@ pub struct BrowseToolRow {
     pub server_name: String,
 }
 
-/// Browse all tools in a domain, filtered by user-enabled servers.
+/// Browse all tools in a domain, filtered by user-enabled servers and a
+/// conversation's pinned `selection`.
 pub async fn browse_tool_domain(
     pool: &PgPool,
     user_id: &str,
     domain: &str,
+    selection: Option<&ToolSelection>,
 ) -> Result<Vec<BrowseToolRow>, McpError> {
     let rows = sqlx::query_as::<
         _,
@@ -705,7 +1213,7 @@ pub async fn browse_tool_domain(
             sqlx::types::Uuid,
             String,
         ),
-    >(
+    >(&format!(
         r#"
         SELECT t.id, t.name, t.description, s.domain, s.id, s.name
         FROM mcp_server_tools t
@@ -722,11 +1230,16 @@ pub async fn browse_tool_domain(
               WHERE p.user_id = $1::uuid AND p.server_id = s.id AND p.enabled = true
             )
           )
+          AND {tool_enabled}
+          AND {selected}
         ORDER BY t.name
         "#,
-    )
+        tool_enabled = tool_enabled_sql("t", "$1"),
+        selected = tool_selection_sql("s", "$3"),
+    ))
     .bind(user_id)
     .bind(domain)
+    .bind(tool_selection_param(selection))
     .fetch_all(pool)
     .await?;
 
@@ -749,14 +1262,16 @@ pub async fn browse_tool_domain(
 
 /// Get a tool manifest by tool ID, verifying user access.
 ///
-/// Returns `None` if the tool doesn't exist or the user doesn't have access
-/// to the server hosting it.
+/// Returns `None` if the tool doesn't exist, is switched off for the user,
+/// the user doesn't have access to the server hosting it, or it is outside
+/// a conversation's pinned `selection`.
 pub async fn get_tool_manifest(
     pool: &PgPool,
     user_id: &str,
     tool_id: &str,
+    selection: Option<&ToolSelection>,
 ) -> Result<Option<McpServerToolRow>, McpError> {
-    let row = sqlx::query_as::<_, McpServerToolRow>(
+    let row = sqlx::query_as::<_, McpServerToolRow>(&format!(
         r#"
         SELECT t.id, t.server_id, t.name, t.description, t.manifest,
                t.response_size_limit, t.created_at
@@ -774,10 +1289,15 @@ pub async fn get_tool_manifest(
               WHERE p.user_id = $1::uuid AND p.server_id = s.id AND p.enabled = true
             )
           )
+          AND {tool_enabled}
+          AND {selected}
         "#,
-    )
+        tool_enabled = tool_enabled_sql("t", "$1"),
+        selected = tool_selection_sql("s", "$3"),
+    ))
     .bind(user_id)
     .bind(tool_id)
+    .bind(tool_selection_param(selection))
     .fetch_optional(pool)
     .await?;
     Ok(row)
@@ -787,6 +1307,8 @@ pub async fn get_tool_manifest(
 ///
 /// A user has access if:
 /// - The server is visible and user hasn't explicitly disabled it, OR
+/// - The server is a user server shared in one of the user's workspaces and
+///   the user hasn't explicitly disabled it, OR
 /// - The user has explicitly enabled it (including user-owned servers).
 pub async fn user_has_server_access(
     pool: &PgPool,
@@ -800,7 +1322,9 @@ pub async fn user_has_server_access(
             WHERE s.id = $2::uuid
               AND s.enabled = true
               AND (
-                (s.visibility = 'visible' AND NOT EXISTS (
+                ((s.visibility = 'visible' OR (s.visibility = 'user' AND s.workspace_id IN (
+                  SELECT m.workspace_id FROM workspace_members m WHERE m.user_id = $1::uuid
+                ))) AND NOT EXISTS (
                   SELECT 1 FROM user_mcp_preferences p
                   WHERE p.user_id = $1::uuid AND p.server_id = s.id AND p.enabled = false
                 ))
@@ -818,3 +1342,32 @@ pub async fn user_has_server_access(
     .await?;
     Ok(has_access)
 }
+
+/// Check if a user may call a specific tool: they have access to its
+/// server (see [`user_has_server_access`]) and the tool is switched on.
+pub async fn user_has_tool_access(
+    pool: &PgPool,
+    user_id: &str,
+    tool_id: &str,
+) -> Result<bool, McpError> {
+    let Some(server_id) =
+        sqlx::query_scalar::<_, Uuid>("SELECT server_id FROM mcp_server_tools WHERE id = $1::uuid")
+            .bind(tool_id)
+            .fetch_optional(pool)
+            .await?
+    else {
+        return Ok(false);
+    };
+    if !user_has_server_access(pool, user_id, &server_id.to_string()).await? {
+        return Ok(false);
+    }
+    let enabled = sqlx::query_scalar::<_, bool>(&format!(
+        "SELECT {tool_enabled} FROM mcp_server_tools t WHERE t.id = $2::uuid",
+        tool_enabled = tool_enabled_sql("t", "$1"),
+    ))
+    .bind(user_id)
+    .bind(tool_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(enabled)
+}
@@ -0,0 +1,700 @@
+//! Recording and replay of tool-call sessions.
+//!
+//! An admin starts a recording session for a user, a server or both. While
+//! it is active, every call [`execute_tool`](super::execution::execute_tool)
+//! makes that falls in its scope is captured by [`capture`]: the tool, its
+//! arguments, the result or error and the duration. A session stops
+//! capturing when stopped, when it expires or once it holds [`MAX_CALLS`]
+//! calls. [`archive`] exports a session with its calls as a self-contained
+//! [`RecordingArchive`].
+//!
+//! [`replay`] re-runs an archive's calls in order through a
+//! [`ReplayBackend`] and compares each outcome with the recorded one:
+//! [`LiveBackend`] calls a real server, [`MockBackend`] answers with the
+//! recorded responses so a reported sequence can be reproduced without the
+//! upstream server.
+//!
+//! Arguments and results are stored as sent and received, so they can hold
+//! anything a user passed to a tool; sessions are admin-only.
+
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use rmcp::model::CallToolResult;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::McpError;
+use super::context::SessionContext;
+use super::execution::{self, ClientPool};
+use super::queries;
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Session length when none is given.
+pub const DEFAULT_DURATION_MINUTES: i64 = 60;
+
+/// Longest a session may run.
+pub const MAX_DURATION_MINUTES: i64 = 24 * 60;
+
+/// Calls a session holds before it stops capturing.
+pub const MAX_CALLS: i32 = 1000;
+
+/// Format version of [`RecordingArchive`].
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// What a tool call produced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CallOutcome {
+    /// The server answered, possibly with `isError: true`.
+    Result { result: Value },
+    /// The call failed before the server answered, or with a protocol error.
+    Error { category: String, message: String },
+}
+
+impl CallOutcome {
+    pub fn from_call(result: &Result<CallToolResult, McpError>) -> Self {
+        match result {
+            Ok(result) => CallOutcome::Result {
+                result: execution::call_tool_result_to_json(result),
+            },
+            Err(e) => CallOutcome::Error {
+                category: e.info().category.as_str().to_string(),
+                message: e.root().to_string(),
+            },
+        }
+    }
+
+    /// Whether `other` reproduces this outcome. Errors are compared by
+    /// category only, since their messages often carry ids and timings.
+    pub fn matches(&self, other: &CallOutcome) -> bool {
+        match (self, other) {
+            (CallOutcome::Result { result: a }, CallOutcome::Result { result: b }) => a == b,
+            (CallOutcome::Error { category: a, .. }, CallOutcome::Error { category: b, .. }) => {
+                a == b
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A recording session.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingSession {
+    pub id: Uuid,
+    pub label: String,
+    /// Calls by this user are captured (`None`: any user).
+    pub user_id: Option<Uuid>,
+    /// Calls to this server are captured (`None`: any server).
+    pub server_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub stopped_at: Option<DateTime<Utc>>,
+    pub call_count: i32,
+    /// Whether the session is still capturing calls.
+    pub active: bool,
+}
+
+/// One captured tool call.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordedCall {
+    /// Position in the session, from 1.
+    pub seq: i32,
+    pub user_id: Uuid,
+    pub server_id: Uuid,
+    pub server_name: String,
+    pub tool_name: String,
+    pub arguments: Option<Value>,
+    pub outcome: CallOutcome,
+    pub duration_ms: i32,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RecordedCallRow {
+    seq: i32,
+    user_id: Uuid,
+    server_id: Uuid,
+    server_name: String,
+    tool_name: String,
+    arguments: Option<Value>,
+    outcome: Value,
+    duration_ms: i32,
+    recorded_at: DateTime<Utc>,
+}
+
+/// A session with all its calls, in order.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingArchive {
+    pub version: u32,
+    pub session: RecordingSession,
+    pub calls: Vec<RecordedCall>,
+}
+
+/// Parameters for [`start`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartRecording {
+    #[serde(default)]
+    pub label: String,
+    pub user_id: Option<Uuid>,
+    pub server_id: Option<Uuid>,
+    /// Defaults to [`DEFAULT_DURATION_MINUTES`].
+    pub duration_minutes: Option<i64>,
+}
+
+fn session_columns() -> String {
+    format!(
+        "id, label, user_id, server_id, created_by, started_at, expires_at, stopped_at, \
+         call_count, (stopped_at IS NULL AND expires_at > now() AND call_count < {MAX_CALLS}) \
+         AS active"
+    )
+}
+
+/// Start a recording session.
+pub async fn start(
+    pool: &PgPool,
+    params: &StartRecording,
+    created_by: &Uuid,
+) -> Result<RecordingSession, McpError> {
+    if params.user_id.is_none() && params.server_id.is_none() {
+        return Err(McpError::Validation(Message::new(
+            "mcp.recording_target_required",
+        )));
+    }
+    let minutes = params.duration_minutes.unwrap_or(DEFAULT_DURATION_MINUTES);
+    if !(1..=MAX_DURATION_MINUTES).contains(&minutes) {
+        return Err(McpError::Validation(
+            Message::new("mcp.recording_duration_out_of_range").arg("max", MAX_DURATION_MINUTES),
+        ));
+    }
+    if let Some(user_id) = params.user_id {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+        if !exists {
+            return Err(McpError::NotFound(
+                Message::new("mcp.user_not_found").arg("id", user_id),
+            ));
+        }
+    }
+    if let Some(server_id) = params.server_id
+        && queries::get_server(pool, &server_id.to_string())
+            .await?
+            .is_none()
+    {
+        return Err(McpError::NotFound(
+            Message::new("mcp.server_not_found").arg("id", server_id),
+        ));
+    }
+
+    let session = sqlx::query_as::<_, RecordingSession>(&format!(
+        r#"
+        INSERT INTO mcp_recording_sessions
+            (id, label, user_id, server_id, created_by, expires_at)
+        VALUES ($1, $2, $3, $4, $5, now() + make_interval(mins => $6))
+        RETURNING {}
+        "#,
+        session_columns()
+    ))
+    .bind(uuidv7())
+    .bind(params.label.trim())
+    .bind(params.user_id)
+    .bind(params.server_id)
+    .bind(created_by)
+    .bind(minutes as i32)
+    .fetch_one(pool)
+    .await?;
+    Ok(session)
+}
+
+/// Stop a session; stopping a stopped session is a no-op. Returns `None`
+/// if there is no such session.
+pub async fn stop(pool: &PgPool, id: &Uuid) -> Result<Option<RecordingSession>, McpError> {
+    let session = sqlx::query_as::<_, RecordingSession>(&format!(
+        r#"
+        UPDATE mcp_recording_sessions
+        SET stopped_at = COALESCE(stopped_at, now())
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        session_columns()
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(session)
+}
+
+/// All sessions, newest first.
+pub async fn list(pool: &PgPool) -> Result<Vec<RecordingSession>, McpError> {
+    let sessions = sqlx::query_as::<_, RecordingSession>(&format!(
+        "SELECT {} FROM mcp_recording_sessions ORDER BY started_at DESC",
+        session_columns()
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(sessions)
+}
+
+/// Delete a session and its calls. Returns `false` if there was none.
+pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<bool, McpError> {
+    let result = sqlx::query("DELETE FROM mcp_recording_sessions WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Export a session with its calls.
+pub async fn archive(pool: &PgPool, id: &Uuid) -> Result<Option<RecordingArchive>, McpError> {
+    let Some(session) = sqlx::query_as::<_, RecordingSession>(&format!(
+        "SELECT {} FROM mcp_recording_sessions WHERE id = $1",
+        session_columns()
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    else {
+        return Ok(None);
+    };
+
+    let rows = sqlx::query_as::<_, RecordedCallRow>(
+        r#"
+        SELECT seq, user_id, server_id, server_name, tool_name, arguments, outcome,
+               duration_ms, recorded_at
+        FROM mcp_recorded_calls
+        WHERE session_id = $1
+        ORDER BY seq
+        "#,
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await?;
+    let calls = rows
+        .into_iter()
+        .map(|row| {
+            let outcome = serde_json::from_value(row.outcome).map_err(|e| {
+                McpError::Validation(
+                    Message::new("mcp.recorded_call_unreadable")
+                        .arg("seq", row.seq)
+                        .arg("error", e),
+                )
+            })?;
+            Ok(RecordedCall {
+                seq: row.seq,
+                user_id: row.user_id,
+                server_id: row.server_id,
+                server_name: row.server_name,
+                tool_name: row.tool_name,
+                arguments: row.arguments,
+                outcome,
+                duration_ms: row.duration_ms,
+                recorded_at: row.recorded_at,
+            })
+        })
+        .collect::<Result<Vec<_>, McpError>>()?;
+
+    Ok(Some(RecordingArchive {
+        version: ARCHIVE_VERSION,
+        session,
+        calls,
+    }))
+}
+
+/// Capture a call in every active session whose scope covers it.
+#[allow(clippy::too_many_arguments)]
+pub async fn capture(
+    pool: &PgPool,
+    user_id: &str,
+    server_id: &Uuid,
+    server_name: &str,
+    tool_name: &str,
+    arguments: Option<&serde_json::Map<String, Value>>,
+    outcome: &CallOutcome,
+    duration: Duration,
+) -> Result<(), McpError> {
+    let Ok(user_id) = user_id.parse::<Uuid>() else {
+        return Ok(());
+    };
+    let outcome = serde_json::to_value(outcome).expect("outcome serializes");
+    sqlx::query(
+        r#"
+        WITH claimed AS (
+            UPDATE mcp_recording_sessions
+            SET call_count = call_count + 1
+            WHERE stopped_at IS NULL
+              AND expires_at > now()
+              AND call_count < $1
+              AND (user_id IS NULL OR user_id = $2)
+              AND (server_id IS NULL OR server_id = $3)
+            RETURNING id, call_count
+        )
+        INSERT INTO mcp_recorded_calls
+            (session_id, seq, user_id, server_id, server_name, tool_name, arguments,
+             outcome, duration_ms)
+        SELECT id, call_count, $2, $3, $4, $5, $6, $7, $8
+        FROM claimed
+        "#,
+    )
+    .bind(MAX_CALLS)
+    .bind(user_id)
+    .bind(server_id)
+    .bind(server_name)
+    .bind(tool_name)
+    .bind(arguments.map(|a| Value::Object(a.clone())))
+    .bind(outcome)
+    .bind(i32::try_from(duration.as_millis()).unwrap_or(i32::MAX))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Replay
+// ---------------------------------------------------------------------------
+
+/// Where [`replay`] sends the recorded calls.
+pub trait ReplayBackend {
+    /// Short name of the backend, reported in [`ReplayReport::target`].
+    fn name(&self) -> &'static str;
+
+    /// Make `call` again and return what happened.
+    fn call(&self, call: &RecordedCall) -> impl Future<Output = CallOutcome> + Send;
+}
+
+/// Replays calls against a real server through a private connection pool,
+/// closed when the backend is dropped.
+///
+/// Calls are made with the credentials of the user doing the replay, not
+/// those of the user who was recorded.
+pub struct LiveBackend<'a> {
+    pool: &'a PgPool,
+    client_pool: ClientPool,
+    user_id: String,
+    server_id: Option<Uuid>,
+    encryption_key: &'a str,
+}
+
+impl<'a> LiveBackend<'a> {
+    /// Call each recorded server, or `server_id` for every call (e.g. a
+    /// staging copy of the server, or a stub standing in for it).
+    pub fn new(
+        pool: &'a PgPool,
+        user_id: &str,
+        server_id: Option<Uuid>,
+        encryption_key: &'a str,
+    ) -> Self {
+        Self {
+            pool,
+            client_pool: ClientPool::new(),
+            user_id: user_id.to_string(),
+            server_id,
+            encryption_key,
+        }
+    }
+}
+
+impl Drop for LiveBackend<'_> {
+    fn drop(&mut self) {
+        self.client_pool.close_all();
+    }
+}
+
+impl ReplayBackend for LiveBackend<'_> {
+    fn name(&self) -> &'static str {
+        "live"
+    }
+
+    async fn call(&self, call: &RecordedCall) -> CallOutcome {
+        let arguments = match &call.arguments {
+            Some(Value::Object(map)) => Some(map.clone()),
+            _ => None,
+        };
+        let result = execution::call_server_tool(
+            self.pool,
+            &self.client_pool,
+            &self.user_id,
+            self.server_id.unwrap_or(call.server_id),
+            &call.tool_name,
+            arguments,
+            &SessionContext::default(),
+            self.encryption_key,
+        )
+        .await;
+        CallOutcome::from_call(&result)
+    }
+}
+
+/// Answers calls with the responses recorded in an archive: each distinct
+/// request (tool and arguments) gets its recorded responses in order.
+#[derive(Debug)]
+pub struct MockBackend {
+    responses: Mutex<Vec<MockResponses>>,
+}
+
+#[derive(Debug)]
+struct MockResponses {
+    tool_name: String,
+    arguments: Option<Value>,
+    outcomes: std::collections::VecDeque<CallOutcome>,
+}
+
+impl MockBackend {
+    pub fn from_archive(archive: &RecordingArchive) -> Self {
+        let mut responses: Vec<MockResponses> = Vec::new();
+        for call in &archive.calls {
+            match responses
+                .iter_mut()
+                .find(|r| r.tool_name == call.tool_name && r.arguments == call.arguments)
+            {
+                Some(r) => r.outcomes.push_back(call.outcome.clone()),
+                None => responses.push(MockResponses {
+                    tool_name: call.tool_name.clone(),
+                    arguments: call.arguments.clone(),
+                    outcomes: [call.outcome.clone()].into(),
+                }),
+            }
+        }
+        Self {
+            responses: Mutex::new(responses),
+        }
+    }
+
+    /// The next recorded response to this request, or a `not_found` error
+    /// once there are none left.
+    pub fn respond(&self, tool_name: &str, arguments: Option<&Value>) -> CallOutcome {
+        let mut responses = self.responses.lock().unwrap();
+        responses
+            .iter_mut()
+            .find(|r| r.tool_name == tool_name && r.arguments.as_ref() == arguments)
+            .and_then(|r| r.outcomes.pop_front())
+            .unwrap_or_else(|| CallOutcome::Error {
+                category: "not_found".into(),
+                message: format!("No recorded response left for {tool_name}"),
+            })
+    }
+}
+
+impl ReplayBackend for MockBackend {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn call(&self, call: &RecordedCall) -> CallOutcome {
+        self.respond(&call.tool_name, call.arguments.as_ref())
+    }
+}
+
+/// How a replayed call compared with its recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum ReplayStatus {
+    /// Same result, or an error of the same category.
+    Matched,
+    /// A different result, or a result where an error was recorded.
+    Diverged,
+    /// An error where a result (or another kind of error) was recorded.
+    Failed,
+}
+
+/// One replayed call.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayedCall {
+    pub seq: i32,
+    pub tool_name: String,
+    pub status: ReplayStatus,
+    pub recorded: CallOutcome,
+    pub actual: CallOutcome,
+    pub duration_ms: i32,
+}
+
+/// Result of [`replay`].
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayReport {
+    pub session_id: Uuid,
+    /// The backend's [`ReplayBackend::name`].
+    pub target: String,
+    pub matched: usize,
+    pub diverged: usize,
+    pub failed: usize,
+    pub calls: Vec<ReplayedCall>,
+}
+
+/// Re-run the archive's calls one at a time, in recorded order.
+pub async fn replay<B: ReplayBackend>(archive: &RecordingArchive, backend: &B) -> ReplayReport {
+    let mut report = ReplayReport {
+        session_id: archive.session.id,
+        target: backend.name().to_string(),
+        matched: 0,
+        diverged: 0,
+        failed: 0,
+        calls: Vec::with_capacity(archive.calls.len()),
+    };
+    for call in &archive.calls {
+        let started = Instant::now();
+        let actual = backend.call(call).await;
+        let status = if call.outcome.matches(&actual) {
+            report.matched += 1;
+            ReplayStatus::Matched
+        } else if matches!(actual, CallOutcome::Error { .. }) {
+            report.failed += 1;
+            ReplayStatus::Failed
+        } else {
+            report.diverged += 1;
+            ReplayStatus::Diverged
+        };
+        report.calls.push(ReplayedCall {
+            seq: call.seq,
+            tool_name: call.tool_name.clone(),
+            status,
+            recorded: call.outcome.clone(),
+            actual,
+            duration_ms: i32::try_from(started.elapsed().as_millis()).unwrap_or(i32::MAX),
+        });
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn result(text: &str) -> CallOutcome {
+        CallOutcome::Result {
+            result: json!({ "content": [{ "type": "text", "text": text }], "isError": false }),
+        }
+    }
+
+    fn error(category: &str, message: &str) -> CallOutcome {
+        CallOutcome::Error {
+            category: category.into(),
+            message: message.into(),
+        }
+    }
+
+    fn archive(calls: &[(&str, Value, CallOutcome)]) -> RecordingArchive {
+        let now = Utc::now();
+        RecordingArchive {
+            version: ARCHIVE_VERSION,
+            session: RecordingSession {
+                id: Uuid::nil(),
+                label: String::new(),
+                user_id: Some(Uuid::nil()),
+                server_id: None,
+                created_by: None,
+                started_at: now,
+                expires_at: now,
+                stopped_at: Some(now),
+                call_count: calls.len() as i32,
+                active: false,
+            },
+            calls: calls
+                .iter()
+                .enumerate()
+                .map(|(i, (tool, args, outcome))| RecordedCall {
+                    seq: i as i32 + 1,
+                    user_id: Uuid::nil(),
+                    server_id: Uuid::nil(),
+                    server_name: "s".into(),
+                    tool_name: tool.to_string(),
+                    arguments: Some(args.clone()),
+                    outcome: outcome.clone(),
+                    duration_ms: 1,
+                    recorded_at: now,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn outcome_serializes_tagged_and_matches_errors_by_category() {
+        assert_eq!(
+            serde_json::to_value(error("timeout", "30s")).unwrap(),
+            json!({ "kind": "error", "category": "timeout", "message": "30s" })
+        );
+        assert!(error("timeout", "after 30s").matches(&error("timeout", "after 31s")));
+        assert!(!error("timeout", "x").matches(&error("connection", "x")));
+        assert!(result("a").matches(&result("a")));
+        assert!(!result("a").matches(&result("b")));
+        assert!(!result("a").matches(&error("timeout", "x")));
+    }
+
+    #[test]
+    fn mock_serves_recorded_responses_in_order() {
+        let mock = MockBackend::from_archive(&archive(&[
+            ("search", json!({ "q": "x" }), result("1")),
+            ("search", json!({ "q": "y" }), result("other")),
+            ("search", json!({ "q": "x" }), result("2")),
+        ]));
+        let args = json!({ "q": "x" });
+        assert_eq!(mock.respond("search", Some(&args)), result("1"));
+        assert_eq!(mock.respond("search", Some(&args)), result("2"));
+        assert!(matches!(
+            mock.respond("search", Some(&args)),
+            CallOutcome::Error { category, .. } if category == "not_found"
+        ));
+        assert!(matches!(
+            mock.respond("fetch", None),
+            CallOutcome::Error { .. }
+        ));
+    }
+
+    struct Scripted(Mutex<Vec<CallOutcome>>);
+
+    impl ReplayBackend for Scripted {
+        fn name(&self) -> &'static str {
+            "scripted"
+        }
+
+        async fn call(&self, _call: &RecordedCall) -> CallOutcome {
+            self.0.lock().unwrap().remove(0)
+        }
+    }
+
+    #[tokio::test]
+    async fn replay_classifies_each_call() {
+        let recorded = archive(&[
+            ("a", json!({}), result("same")),
+            ("b", json!({}), result("before")),
+            ("c", json!({}), result("ok")),
+            ("d", json!({}), error("timeout", "slow")),
+        ]);
+        let backend = Scripted(Mutex::new(vec![
+            result("same"),
+            result("after"),
+            error("connection", "refused"),
+            result("now fine"),
+        ]));
+        let report = replay(&recorded, &backend).await;
+        let statuses: Vec<_> = report.calls.iter().map(|c| c.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                ReplayStatus::Matched,
+                ReplayStatus::Diverged,
+                ReplayStatus::Failed,
+                ReplayStatus::Diverged,
+            ]
+        );
+        assert_eq!((report.matched, report.diverged, report.failed), (1, 2, 1));
+        assert_eq!(report.target, "scripted");
+
+        let mock = MockBackend::from_archive(&recorded);
+        assert_eq!(replay(&recorded, &mock).await.matched, 4);
+    }
+}
@@ -0,0 +1,440 @@
+//! Per-domain server routing and fallback.
+//!
+//! When several servers cover the same domain, a route orders them by
+//! preference. Admins set a default route per domain; users can override it
+//! with their own. Tool discovery ranks each domain's tools by the effective
+//! route, and tool execution falls back along it — to a tool with the same
+//! name on the next server — when the chosen server is unavailable, fails
+//! to connect or times out, or has an open circuit.
+//!
+//! The [`CircuitBreaker`] opens a server's circuit after
+//! [`FAILURE_THRESHOLD`] consecutive transient failures and lets a trial
+//! call through once [`OPEN_DURATION`] has passed.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::McpError;
+
+use crate::i18n::Message;
+
+/// Consecutive transient failures that open a server's circuit.
+pub const FAILURE_THRESHOLD: u32 = 3;
+
+/// How long an open circuit rejects calls before allowing a trial call.
+pub const OPEN_DURATION: Duration = Duration::from_secs(30);
+
+/// Maximum servers in one route.
+pub const MAX_ROUTE_SERVERS: usize = 20;
+
+// =============================================================================
+// Circuit breaker
+// =============================================================================
+
+/// State of a server's circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    /// The open period elapsed; the next call is a trial.
+    HalfOpen,
+}
+
+#[derive(Debug, Default)]
+struct Circuit {
+    failures: u32,
+    opened_at: Option<Instant>,
+}
+
+/// Tracks consecutive failures per server.
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    circuits: DashMap<Uuid, Circuit>,
+}
+
+impl CircuitBreaker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current state of `server_id`'s circuit.
+    pub fn state(&self, server_id: &Uuid) -> CircuitState {
+        match self.circuits.get(server_id).and_then(|c| c.opened_at) {
+            None => CircuitState::Closed,
+            Some(at) if at.elapsed() < OPEN_DURATION => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Whether calls to `server_id` should be skipped.
+    pub fn is_open(&self, server_id: &Uuid) -> bool {
+        self.state(server_id) == CircuitState::Open
+    }
+
+    /// Close the circuit after a successful call.
+    pub fn record_success(&self, server_id: &Uuid) {
+        self.circuits.remove(server_id);
+    }
+
+    /// Count a transient failure, opening (or re-opening after a failed
+    /// trial) the circuit at [`FAILURE_THRESHOLD`].
+    pub fn record_failure(&self, server_id: &Uuid) {
+        let mut circuit = self.circuits.entry(*server_id).or_default();
+        circuit.failures += 1;
+        if circuit.failures >= FAILURE_THRESHOLD {
+            circuit.opened_at = Some(Instant::now());
+        }
+    }
+}
+
+/// Whether `error` should count against a server's circuit and trigger a
+/// fallback: the server could not be reached or did not answer in time.
+pub fn is_failover_error(error: &McpError) -> bool {
+    matches!(
+        error.root(),
+        McpError::ConnectionFailed(_) | McpError::Timeout(_) | McpError::ResourceExhausted(_)
+    )
+}
+
+// =============================================================================
+// Routes
+// =============================================================================
+
+/// Database row for `mcp_domain_routes`. `user_id` is `None` for the admin
+/// default route.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DomainRouteRow {
+    pub domain: String,
+    pub user_id: Option<Uuid>,
+    pub server_ids: Vec<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// List routes: the admin defaults when `user_id` is `None`, otherwise the
+/// user's own overrides.
+pub async fn list_routes(
+    pool: &PgPool,
+    user_id: Option<&Uuid>,
+) -> Result<Vec<DomainRouteRow>, McpError> {
+    let rows = sqlx::query_as::<_, DomainRouteRow>(
+        r#"
+        SELECT domain, user_id, server_ids, updated_at
+        FROM mcp_domain_routes
+        WHERE user_id IS NOT DISTINCT FROM $1
+        ORDER BY domain
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Effective route per domain for a user: their override, else the admin
+/// default.
+pub async fn effective_routes(
+    pool: &PgPool,
+    user_id: &str,
+) -> Result<HashMap<String, Vec<Uuid>>, McpError> {
+    let rows = sqlx::query_as::<_, (String, Vec<Uuid>)>(
+        r#"
+        SELECT DISTINCT ON (domain) domain, server_ids
+        FROM mcp_domain_routes
+        WHERE user_id IS NULL OR user_id = $1::uuid
+        ORDER BY domain, user_id NULLS LAST
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Create or replace a route. Every server must exist, cover `domain` and,
+/// for a user route, be accessible to the user.
+pub async fn set_route(
+    pool: &PgPool,
+    user_id: Option<&Uuid>,
+    domain: &str,
+    server_ids: &[Uuid],
+) -> Result<DomainRouteRow, McpError> {
+    validate_route(server_ids)?;
+    let matching: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT s.id
+        FROM mcp_servers s
+        WHERE s.id = ANY($1)
+          AND s.domain = $2
+          AND ($3::uuid IS NULL OR (
+            ((s.visibility = 'visible' OR (s.visibility = 'user' AND s.workspace_id IN (
+              SELECT m.workspace_id FROM workspace_members m WHERE m.user_id = $3
+            ))) AND NOT EXISTS (
+              SELECT 1 FROM user_mcp_preferences p
+              WHERE p.user_id = $3 AND p.server_id = s.id AND p.enabled = false
+            ))
+            OR EXISTS (
+              SELECT 1 FROM user_mcp_preferences p
+              WHERE p.user_id = $3 AND p.server_id = s.id AND p.enabled = true
+            )
+          ))
+        "#,
+    )
+    .bind(server_ids)
+    .bind(domain)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    if let Some(missing) = server_ids.iter().find(|id| !matching.contains(id)) {
+        return Err(McpError::Validation(
+            Message::new("mcp.route_server_unavailable")
+                .arg("id", missing)
+                .arg("domain", domain),
+        ));
+    }
+
+    // Two statements because the admin row has a NULL user_id, which a
+    // plain ON CONFLICT target cannot match.
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "DELETE FROM mcp_domain_routes WHERE domain = $1 AND user_id IS NOT DISTINCT FROM $2",
+    )
+    .bind(domain)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    let row = sqlx::query_as::<_, DomainRouteRow>(
+        r#"
+        INSERT INTO mcp_domain_routes (domain, user_id, server_ids)
+        VALUES ($1, $2, $3)
+        RETURNING domain, user_id, server_ids, updated_at
+        "#,
+    )
+    .bind(domain)
+    .bind(user_id)
+    .bind(server_ids)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Delete a route. Returns whether one existed.
+pub async fn delete_route(
+    pool: &PgPool,
+    user_id: Option<&Uuid>,
+    domain: &str,
+) -> Result<bool, McpError> {
+    let result = sqlx::query(
+        "DELETE FROM mcp_domain_routes WHERE domain = $1 AND user_id IS NOT DISTINCT FROM $2",
+    )
+    .bind(domain)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Check a route has between 1 and [`MAX_ROUTE_SERVERS`] distinct servers.
+fn validate_route(server_ids: &[Uuid]) -> Result<(), McpError> {
+    if server_ids.is_empty() || server_ids.len() > MAX_ROUTE_SERVERS {
+        return Err(McpError::Validation(
+            Message::new("mcp.route_server_count_out_of_range").arg("max", MAX_ROUTE_SERVERS),
+        ));
+    }
+    for (i, id) in server_ids.iter().enumerate() {
+        if server_ids[..i].contains(id) {
+            return Err(McpError::Validation(
+                Message::new("mcp.route_duplicate_server").arg("id", id),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// =============================================================================
+// Ranking and fallback
+// =============================================================================
+
+/// Reorder `items` so that, within each routed domain, items from healthy
+/// servers come first in route order, followed by unlisted servers and
+/// finally unhealthy ones. Each domain keeps the positions its items had,
+/// so the ranking across domains is unchanged.
+pub fn rank_by_route<T>(
+    items: Vec<T>,
+    routes: &HashMap<String, Vec<Uuid>>,
+    key: impl Fn(&T) -> (&str, Uuid),
+    healthy: impl Fn(&Uuid) -> bool,
+) -> Vec<T> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    for (domain, route) in routes {
+        let slots: Vec<usize> = (0..items.len())
+            .filter(|&i| key(&items[i]).0 == domain)
+            .collect();
+        let mut ranked = slots.clone();
+        ranked.sort_by_key(|&i| {
+            let server_id = key(&items[i]).1;
+            let position = route
+                .iter()
+                .position(|id| *id == server_id)
+                .unwrap_or(route.len());
+            (!healthy(&server_id), position, i)
+        });
+        for (slot, i) in slots.into_iter().zip(ranked) {
+            order[slot] = i;
+        }
+    }
+
+    let mut items: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    order.into_iter().filter_map(|i| items[i].take()).collect()
+}
+
+/// A tool that can stand in for another on a different server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FallbackTool {
+    pub tool_id: Uuid,
+    pub server_id: Uuid,
+    pub server_name: String,
+    /// Whether the server is marked available.
+    pub available: bool,
+}
+
+/// Tools named `tool_name` on other servers of `server_id`'s domain that
+/// appear in the user's effective route for it, in route order.
+pub async fn fallback_tools(
+    pool: &PgPool,
+    user_id: &str,
+    server_id: &Uuid,
+    tool_name: &str,
+) -> Result<Vec<FallbackTool>, McpError> {
+    let domain: Option<String> = sqlx::query_scalar("SELECT domain FROM mcp_servers WHERE id = $1")
+        .bind(server_id)
+        .fetch_optional(pool)
+        .await?;
+    let Some(domain) = domain else {
+        return Ok(Vec::new());
+    };
+    let Some(route) = effective_routes(pool, user_id).await?.remove(&domain) else {
+        return Ok(Vec::new());
+    };
+
+    let rows = sqlx::query_as::<_, (Uuid, Uuid, String, bool)>(&format!(
+        r#"
+        SELECT t.id, s.id, s.name, s.available
+        FROM mcp_server_tools t
+        JOIN mcp_servers s ON s.id = t.server_id
+        WHERE t.name = $2
+          AND s.id = ANY($3)
+          AND s.id <> $4
+          AND s.enabled = true
+          AND (
+            ((s.visibility = 'visible' OR (s.visibility = 'user' AND s.workspace_id IN (
+              SELECT m.workspace_id FROM workspace_members m WHERE m.user_id = $1::uuid
+            ))) AND NOT EXISTS (
+              SELECT 1 FROM user_mcp_preferences p
+              WHERE p.user_id = $1::uuid AND p.server_id = s.id AND p.enabled = false
+            ))
+            OR EXISTS (
+              SELECT 1 FROM user_mcp_preferences p
+              WHERE p.user_id = $1::uuid AND p.server_id = s.id AND p.enabled = true
+            )
+          )
+          AND {tool_enabled}
+        "#,
+        tool_enabled = super::queries::tool_enabled_sql("t", "$1"),
+    ))
+    .bind(user_id)
+    .bind(tool_name)
+    .bind(&route)
+    .bind(server_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut tools: Vec<FallbackTool> = rows
+        .into_iter()
+        .map(
+            |(tool_id, server_id, server_name, available)| FallbackTool {
+                tool_id,
+                server_id,
+                server_name,
+                available,
+            },
+        )
+        .collect();
+    tools.sort_by_key(|t| route.iter().position(|id| *id == t.server_id));
+    Ok(tools)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(n: u128) -> Uuid {
+        Uuid::from_u128(n)
+    }
+
+    #[test]
+    fn circuit_opens_after_threshold_and_closes_on_success() {
+        let breaker = CircuitBreaker::new();
+        let server = id(1);
+        for _ in 1..FAILURE_THRESHOLD {
+            breaker.record_failure(&server);
+        }
+        assert_eq!(breaker.state(&server), CircuitState::Closed);
+
+        breaker.record_failure(&server);
+        assert!(breaker.is_open(&server));
+
+        breaker.record_success(&server);
+        assert_eq!(breaker.state(&server), CircuitState::Closed);
+    }
+
+    #[test]
+    fn only_transient_errors_fail_over() {
+        assert!(is_failover_error(
+            &McpError::ConnectionFailed("refused".into()).with_server(id(1))
+        ));
+        assert!(is_failover_error(&McpError::Timeout("30s".into())));
+        assert!(!is_failover_error(&McpError::Upstream {
+            code: -32602,
+            message: "bad params".into(),
+        }));
+        assert!(!is_failover_error(&McpError::NotFound(Message::new(
+            "mcp.tool_not_found"
+        ))));
+    }
+
+    #[test]
+    fn route_validation_rejects_empty_and_duplicates() {
+        assert!(validate_route(&[]).is_err());
+        assert!(validate_route(&[id(1), id(1)]).is_err());
+        assert!(validate_route(&[id(1), id(2)]).is_ok());
+    }
+
+    #[test]
+    fn ranks_within_domains_by_route_and_health() {
+        // (domain, server, label), in similarity order.
+        let items = vec![
+            ("search", id(1), "a"),
+            ("files", id(9), "b"),
+            ("search", id(2), "c"),
+            ("search", id(3), "d"),
+        ];
+        let routes = HashMap::from([("search".to_string(), vec![id(3), id(2), id(1)])]);
+
+        let ranked = rank_by_route(items.clone(), &routes, |t| (t.0, t.1), |_| true);
+        let labels: Vec<&str> = ranked.iter().map(|t| t.2).collect();
+        assert_eq!(labels, vec!["d", "b", "c", "a"]);
+
+        // An unhealthy preferred server drops behind the rest of its domain.
+        let ranked = rank_by_route(items, &routes, |t| (t.0, t.1), |s| *s != id(3));
+        let labels: Vec<&str> = ranked.iter().map(|t| t.2).collect();
+        assert_eq!(labels, vec!["c", "b", "a", "d"]);
+    }
+}
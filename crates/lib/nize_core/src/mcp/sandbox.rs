@@ -0,0 +1,350 @@
+//! Sandboxing for spawned MCP server processes.
+//!
+//! Stdio and managed servers run as children of the app and by default
+//! inherit its privileges: its working directory, its whole environment
+//! (API keys and database URLs included) and unbounded resources. A
+//! server's optional [`SandboxConfig`] narrows that:
+//!
+//! - **Working directory** — the process starts in a dedicated directory,
+//!   and its home and temp directory variables point there, so well-behaved
+//!   tools keep their files in it. This is not a filesystem jail.
+//! - **Environment scrubbing** — the process gets only [`BASE_ENV`], the
+//!   allowlisted variables and its own `env`.
+//! - **Resource limits** — memory and total CPU time, set with rlimits
+//!   before `exec` on Unix (inherited by the server's own children) and
+//!   with a Job Object right after spawn on Windows.
+//!
+//! [`command`] builds the process with everything that can be set before
+//! it starts; [`confine`] applies what can only be set on a running
+//! process and must be called right after spawning.
+
+use std::collections::HashMap;
+use std::path::{Component, Path};
+
+use tokio::process::Command;
+
+use super::McpError;
+use crate::i18n::Message;
+use crate::models::mcp::SandboxConfig;
+
+/// Variables kept when the environment is scrubbed, if set in the app's.
+#[cfg(unix)]
+pub const BASE_ENV: &[&str] = &[
+    "PATH", "HOME", "USER", "LOGNAME", "SHELL", "LANG", "LC_ALL", "LC_CTYPE", "TZ", "TMPDIR",
+];
+
+/// Variables kept when the environment is scrubbed, if set in the app's.
+#[cfg(windows)]
+pub const BASE_ENV: &[&str] = &[
+    "PATH",
+    "PATHEXT",
+    "SystemRoot",
+    "SystemDrive",
+    "windir",
+    "ComSpec",
+    "USERNAME",
+    "USERPROFILE",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "TEMP",
+    "TMP",
+];
+
+/// Variables pointed at the sandbox working directory.
+#[cfg(unix)]
+const WORKING_DIR_ENV: &[&str] = &["HOME", "TMPDIR"];
+
+/// Variables pointed at the sandbox working directory.
+#[cfg(windows)]
+const WORKING_DIR_ENV: &[&str] = &["USERPROFILE", "TEMP", "TMP"];
+
+/// Smallest accepted memory limit; below this most runtimes cannot start.
+pub const MIN_MEMORY_MB: u64 = 32;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Check a sandbox block.
+pub fn validate(sandbox: &SandboxConfig) -> Result<(), McpError> {
+    if let Some(dir) = &sandbox.working_dir {
+        let path = Path::new(dir);
+        if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) {
+            return Err(McpError::Validation(
+                Message::new("mcp.sandbox_invalid_working_dir").arg("dir", dir),
+            ));
+        }
+    }
+    if let Some(name) = sandbox
+        .env_allowlist
+        .iter()
+        .find(|name| name.is_empty() || name.contains('='))
+    {
+        return Err(McpError::Validation(
+            Message::new("mcp.sandbox_invalid_env_name").arg("name", format!("{name:?}")),
+        ));
+    }
+    if sandbox.max_memory_mb.is_some_and(|mb| mb < MIN_MEMORY_MB) {
+        return Err(McpError::Validation(
+            Message::new("mcp.sandbox_memory_too_low").arg("min", MIN_MEMORY_MB),
+        ));
+    }
+    if sandbox.max_cpu_secs == Some(0) {
+        return Err(McpError::Validation(Message::new(
+            "mcp.sandbox_cpu_too_low",
+        )));
+    }
+    Ok(())
+}
+
+/// Build the command for a server process, applying the parts of
+/// `sandbox` that are set before it starts.
+pub fn command(
+    program: &str,
+    args: &[String],
+    env: Option<&HashMap<String, String>>,
+    sandbox: Option<&SandboxConfig>,
+) -> Result<Command, McpError> {
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+
+    if let Some(sandbox) = sandbox {
+        validate(sandbox)?;
+        if sandbox.scrub_env {
+            cmd.env_clear();
+            let kept = BASE_ENV
+                .iter()
+                .copied()
+                .chain(sandbox.env_allowlist.iter().map(String::as_str));
+            for name in kept {
+                if let Some(value) = std::env::var_os(name) {
+                    cmd.env(name, value);
+                }
+            }
+        }
+        if let Some(dir) = &sandbox.working_dir {
+            std::fs::create_dir_all(dir).map_err(|e| {
+                McpError::ConnectionFailed(format!("Failed to create sandbox directory {dir}: {e}"))
+            })?;
+            cmd.current_dir(dir);
+            for name in WORKING_DIR_ENV {
+                cmd.env(name, dir);
+            }
+        }
+        #[cfg(unix)]
+        set_rlimits(&mut cmd, sandbox);
+    }
+
+    if let Some(env) = env {
+        cmd.envs(env);
+    }
+    Ok(cmd)
+}
+
+/// Apply the limits that need a running process. Call right after
+/// spawning; on failure the caller should kill the process.
+pub fn confine(pid: Option<u32>, sandbox: Option<&SandboxConfig>) -> Result<(), McpError> {
+    let Some(sandbox) = sandbox else {
+        return Ok(());
+    };
+    #[cfg(windows)]
+    {
+        let Some(pid) = pid else {
+            return Err(McpError::ConnectionFailed(
+                "Sandboxed process exited before it could be confined".into(),
+            ));
+        };
+        assign_job(pid, sandbox).map_err(McpError::ConnectionFailed)
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = (pid, sandbox);
+        Ok(())
+    }
+}
+
+/// Set rlimits in the child between `fork` and `exec`.
+#[cfg(unix)]
+fn set_rlimits(cmd: &mut Command, sandbox: &SandboxConfig) {
+    let memory = sandbox
+        .max_memory_mb
+        .map(|mb| mb.saturating_mul(MIB) as libc::rlim_t);
+    let cpu = sandbox.max_cpu_secs.map(|secs| secs as libc::rlim_t);
+    if memory.is_none() && cpu.is_none() {
+        return;
+    }
+
+    // Address-space limits break runtimes that reserve large virtual
+    // ranges up front (V8, the JVM); Linux can limit the data segment,
+    // which counts only memory actually mapped writable.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    let memory_resource = libc::RLIMIT_DATA;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    let memory_resource = libc::RLIMIT_AS;
+
+    // SAFETY: the closure runs in the forked child before exec and only
+    // calls setrlimit, which is async-signal-safe; it does not allocate.
+    unsafe {
+        cmd.pre_exec(move || {
+            let set = |resource, value: libc::rlim_t| {
+                let limit = libc::rlimit {
+                    rlim_cur: value,
+                    rlim_max: value,
+                };
+                if libc::setrlimit(resource, &limit) == 0 {
+                    Ok(())
+                } else {
+                    Err(std::io::Error::last_os_error())
+                }
+            };
+            if let Some(bytes) = memory {
+                set(memory_resource, bytes)?;
+            }
+            if let Some(secs) = cpu {
+                set(libc::RLIMIT_CPU, secs)?;
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Put the process in a new Job Object carrying the limits. The job lives
+/// on as long as the process does, so its handle is closed here.
+///
+/// The process runs briefly before it is assigned; anything it starts in
+/// that window escapes the job.
+#[cfg(windows)]
+fn assign_job(pid: u32, sandbox: &SandboxConfig) -> Result<(), String> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JOB_OBJECT_LIMIT_PROCESS_MEMORY,
+        JOB_OBJECT_LIMIT_PROCESS_TIME, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JobObjectExtendedLimitInformation, SetInformationJobObject,
+    };
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, PROCESS_SET_QUOTA, PROCESS_TERMINATE,
+    };
+
+    // SAFETY: the struct is plain data; all-zero means "no limits".
+    let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+    if let Some(mb) = sandbox.max_memory_mb {
+        info.ProcessMemoryLimit = mb.saturating_mul(MIB) as usize;
+        info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+    }
+    if let Some(secs) = sandbox.max_cpu_secs {
+        // In 100ns ticks.
+        info.BasicLimitInformation.PerProcessUserTimeLimit =
+            i64::try_from(secs.saturating_mul(10_000_000)).unwrap_or(i64::MAX);
+        info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_TIME;
+    }
+    if info.BasicLimitInformation.LimitFlags == 0 {
+        return Ok(());
+    }
+
+    let last_error = |what: &str| format!("{what}: {}", std::io::Error::last_os_error());
+    // SAFETY: plain Win32 calls on handles created and closed here.
+    unsafe {
+        let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+        if job.is_null() {
+            return Err(last_error("create job object"));
+        }
+        let result = if SetInformationJobObject(
+            job,
+            JobObjectExtendedLimitInformation,
+            &info as *const JOBOBJECT_EXTENDED_LIMIT_INFORMATION as *const std::ffi::c_void,
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        ) == 0
+        {
+            Err(last_error("set job limits"))
+        } else {
+            let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid);
+            if process.is_null() {
+                Err(last_error("open process"))
+            } else {
+                let assigned = AssignProcessToJobObject(job, process);
+                let result = if assigned == 0 {
+                    Err(last_error("assign process to job"))
+                } else {
+                    Ok(())
+                };
+                CloseHandle(process);
+                result
+            }
+        };
+        CloseHandle(job);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_rejects_bad_blocks() {
+        assert!(validate(&SandboxConfig::default()).is_ok());
+        for sandbox in [
+            SandboxConfig {
+                working_dir: Some("relative/dir".into()),
+                ..Default::default()
+            },
+            SandboxConfig {
+                working_dir: Some(
+                    std::env::temp_dir()
+                        .join("..")
+                        .join("x")
+                        .to_string_lossy()
+                        .into_owned(),
+                ),
+                ..Default::default()
+            },
+            SandboxConfig {
+                env_allowlist: vec!["A=B".into()],
+                ..Default::default()
+            },
+            SandboxConfig {
+                max_memory_mb: Some(1),
+                ..Default::default()
+            },
+            SandboxConfig {
+                max_cpu_secs: Some(0),
+                ..Default::default()
+            },
+        ] {
+            assert!(validate(&sandbox).is_err(), "{sandbox:?}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn command_scrubs_env_and_sets_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let work = dir.path().join("work");
+        let sandbox = SandboxConfig {
+            working_dir: Some(work.to_string_lossy().into_owned()),
+            scrub_env: true,
+            env_allowlist: vec![],
+            max_memory_mb: Some(512),
+            max_cpu_secs: Some(30),
+        };
+        let env = HashMap::from([("SERVER_VAR".to_string(), "1".to_string())]);
+        let script = "echo \"$SERVER_VAR|${CARGO_PKG_NAME:-}|$HOME|$(pwd)|$(ulimit -t)\"";
+        let output = command(
+            "sh",
+            &["-c".to_string(), script.to_string()],
+            Some(&env),
+            Some(&sandbox),
+        )
+        .unwrap()
+        .output()
+        .await
+        .unwrap();
+        assert!(output.status.success());
+
+        let work = work.canonicalize().unwrap();
+        let expected = format!(
+            "1||{}|{}|30",
+            sandbox.working_dir.as_deref().unwrap(),
+            work.display()
+        );
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), expected);
+    }
+}
@@ -1,19 +1,47 @@
-//! AES-256-GCM encryption for MCP server secrets.
+//! XChaCha20-Poly1305 encryption for MCP server secrets and OAuth tokens.
 //!
-//! Provides encrypt/decrypt functions for API keys and OAuth client secrets.
-//! Uses AES-256-GCM with random 12-byte nonces (prepended to ciphertext).
-//! Output is base64-encoded for storage in TEXT columns.
+//! Provides encrypt/decrypt functions for API keys, OAuth client secrets,
+//! tokens and secret config values. Uses XChaCha20-Poly1305 with random
+//! 24-byte nonces; output is `v2:` followed by base64 `nonce || ciphertext
+//! || tag` for storage in TEXT columns.
+//!
+//! Secrets stored per row ([`SecretBinding`]) are encrypted with the row's
+//! table, column, server id and user id as associated data, so a
+//! ciphertext copied into another row — or another column of the same row
+//! — fails to decrypt.
+//!
+//! Server secrets are encrypted under a [`ServerKey`]: the deployment key
+//! for personal and admin servers, or a key derived from it per workspace
+//! for servers shared with a workspace. The key used is recorded in
+//! `mcp_server_secrets.encryption_key_id`.
+//!
+//! Values without the `v2:` prefix are legacy AES-256-GCM ciphertexts
+//! (12-byte nonce, no associated data). They still decrypt;
+//! [`decrypt_row`] re-encrypts such a row in the current format the first
+//! time it is read.
 
 use super::McpError;
+use super::queries;
 
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
 use rand::RngCore;
+use ring::hmac;
 use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
 
-/// Nonce size for AES-256-GCM (12 bytes).
-const NONCE_SIZE: usize = 12;
-/// AES-256 key size (32 bytes).
+/// Prefix marking the XChaCha20-Poly1305 format.
+const V2_PREFIX: &str = "v2:";
+/// XChaCha20 nonce size (24 bytes).
+const XNONCE_SIZE: usize = 24;
+/// Legacy AES-256-GCM nonce size (12 bytes).
+const LEGACY_NONCE_SIZE: usize = 12;
+/// Key size (32 bytes).
 const KEY_SIZE: usize = 32;
-/// GCM tag size (16 bytes).
+/// Poly1305 / GCM tag size (16 bytes).
 const TAG_SIZE: usize = 16;
 
 /// Derive a 32-byte key from a passphrase using SHA-256.
@@ -26,45 +54,341 @@ fn derive_key(passphrase: &str) -> [u8; KEY_SIZE] {
     key
 }
 
-/// Encrypt plaintext with AES-256-GCM.
+/// Key id of secrets encrypted with the deployment key.
+pub const DEFAULT_KEY_ID: &str = "v1";
+
+/// Key id prefix of secrets encrypted with a workspace's key.
+const WORKSPACE_KEY_PREFIX: &str = "workspace:";
+
+/// The key a server's secrets are encrypted with, and its id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerKey {
+    pub id: String,
+    pub key: String,
+}
+
+impl ServerKey {
+    /// The deployment key.
+    pub fn deployment(encryption_key: &str) -> Self {
+        Self {
+            id: DEFAULT_KEY_ID.to_string(),
+            key: encryption_key.to_string(),
+        }
+    }
+
+    /// The key for a server shared with `workspace_id`, or the deployment
+    /// key for a server in no workspace.
+    pub fn for_workspace(encryption_key: &str, workspace_id: Option<&Uuid>) -> Self {
+        match workspace_id {
+            Some(id) => Self {
+                id: format!("{WORKSPACE_KEY_PREFIX}{id}"),
+                key: workspace_key(encryption_key, id),
+            },
+            None => Self::deployment(encryption_key),
+        }
+    }
+
+    /// The key recorded as `key_id`. Unknown ids are the deployment key.
+    pub fn from_id(encryption_key: &str, key_id: &str) -> Self {
+        let workspace_id = key_id
+            .strip_prefix(WORKSPACE_KEY_PREFIX)
+            .and_then(|id| Uuid::parse_str(id).ok());
+        match workspace_id {
+            Some(id) => Self::for_workspace(encryption_key, Some(&id)),
+            None => Self {
+                id: key_id.to_string(),
+                key: encryption_key.to_string(),
+            },
+        }
+    }
+}
+
+/// A workspace's key: HMAC-SHA256 of the workspace id under the deployment
+/// key, hex encoded. Ciphertexts of one workspace do not open with
+/// another's key, even when the associated data matches.
+fn workspace_key(encryption_key: &str, workspace_id: &Uuid) -> String {
+    let mac = hmac::Key::new(hmac::HMAC_SHA256, encryption_key.as_bytes());
+    let tag = hmac::sign(
+        &mac,
+        format!("nize:workspace-key:{workspace_id}").as_bytes(),
+    );
+    tag.as_ref().iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Column a bound secret is stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretField {
+    ApiKey,
+    OAuthClientSecret,
+    AccessToken,
+    RefreshToken,
+    IdToken,
+    PkceVerifier,
+}
+
+impl SecretField {
+    fn table(self) -> &'static str {
+        match self {
+            SecretField::ApiKey | SecretField::OAuthClientSecret => "mcp_server_secrets",
+            SecretField::AccessToken | SecretField::RefreshToken | SecretField::IdToken => {
+                "mcp_oauth_tokens"
+            }
+            SecretField::PkceVerifier => "mcp_oauth_pending",
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            SecretField::ApiKey => "api_key_encrypted",
+            SecretField::OAuthClientSecret => "oauth_client_secret_encrypted",
+            SecretField::AccessToken => "access_token_encrypted",
+            SecretField::RefreshToken => "refresh_token_encrypted",
+            SecretField::IdToken => "id_token_encrypted",
+            SecretField::PkceVerifier => "pkce_verifier_encrypted",
+        }
+    }
+}
+
+/// The row a secret belongs to, bound into its ciphertext as associated
+/// data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretBinding {
+    field: SecretField,
+    server_id: String,
+    user_id: Option<String>,
+}
+
+impl SecretBinding {
+    /// A server's API key.
+    pub fn api_key(server_id: &str) -> Self {
+        Self::new(SecretField::ApiKey, server_id, None)
+    }
+
+    /// A server's OAuth client secret.
+    pub fn oauth_client_secret(server_id: &str) -> Self {
+        Self::new(SecretField::OAuthClientSecret, server_id, None)
+    }
+
+    /// A user's OAuth access token for a server.
+    pub fn access_token(user_id: &str, server_id: &str) -> Self {
+        Self::new(SecretField::AccessToken, server_id, Some(user_id))
+    }
+
+    /// A user's OAuth refresh token for a server.
+    pub fn refresh_token(user_id: &str, server_id: &str) -> Self {
+        Self::new(SecretField::RefreshToken, server_id, Some(user_id))
+    }
+
+    /// A user's OIDC id token for a server.
+    pub fn id_token(user_id: &str, server_id: &str) -> Self {
+        Self::new(SecretField::IdToken, server_id, Some(user_id))
+    }
+
+    /// The PKCE verifier of a user's pending authorization for a server.
+    pub fn pkce_verifier(user_id: &str, server_id: &str) -> Self {
+        Self::new(SecretField::PkceVerifier, server_id, Some(user_id))
+    }
+
+    fn new(field: SecretField, server_id: &str, user_id: Option<&str>) -> Self {
+        Self {
+            field,
+            server_id: canonical_id(server_id),
+            user_id: user_id.map(canonical_id),
+        }
+    }
+
+    /// Associated data: `nize:<table>.<column>:server=<id>[:user=<id>]`.
+    fn aad(&self) -> Vec<u8> {
+        let mut aad = format!(
+            "nize:{}.{}:server={}",
+            self.field.table(),
+            self.field.column(),
+            self.server_id
+        );
+        if let Some(user_id) = &self.user_id {
+            aad.push_str(":user=");
+            aad.push_str(user_id);
+        }
+        aad.into_bytes()
+    }
+}
+
+/// Ids are bound in their canonical (lowercase, hyphenated) form so the
+/// same row always yields the same associated data.
+fn canonical_id(id: &str) -> String {
+    uuid::Uuid::parse_str(id)
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| id.to_string())
+}
+
+/// A decrypted secret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decrypted {
+    pub plaintext: String,
+    /// Stored in the legacy AES-256-GCM format and due for re-encryption.
+    pub legacy: bool,
+}
+
+/// Encrypt plaintext with XChaCha20-Poly1305.
 ///
-/// Returns base64-encoded `nonce || ciphertext || tag`.
+/// Returns `v2:` + base64-encoded `nonce || ciphertext || tag`.
 pub fn encrypt(plaintext: &str, encryption_key: &str) -> Result<String, McpError> {
-    use aes_gcm::aead::Aead;
-    use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+    seal(plaintext.as_bytes(), encryption_key, &[])
+}
 
-    let key_bytes = derive_key(encryption_key);
-    let cipher = Aes256Gcm::new_from_slice(&key_bytes)
-        .map_err(|e| McpError::EncryptionError(format!("Key init failed: {e}")))?;
+/// Decrypt a value produced by [`encrypt`] or a legacy AES-256-GCM value.
+pub fn decrypt(encrypted: &str, encryption_key: &str) -> Result<String, McpError> {
+    open(encrypted, encryption_key, &[]).map(|d| d.plaintext)
+}
 
-    let mut nonce_bytes = [0u8; NONCE_SIZE];
-    rand::rng().fill_bytes(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
+/// Encrypt a secret for the row described by `binding`.
+pub fn encrypt_bound(
+    plaintext: &str,
+    encryption_key: &str,
+    binding: &SecretBinding,
+) -> Result<String, McpError> {
+    seal(plaintext.as_bytes(), encryption_key, &binding.aad())
+}
+
+/// Decrypt a secret read from the row described by `binding`. Fails when
+/// the ciphertext was encrypted for a different row.
+pub fn decrypt_bound(
+    encrypted: &str,
+    encryption_key: &str,
+    binding: &SecretBinding,
+) -> Result<Decrypted, McpError> {
+    open(encrypted, encryption_key, &binding.aad())
+}
 
-    let ciphertext = cipher
-        .encrypt(nonce, plaintext.as_bytes())
-        .map_err(|e| McpError::EncryptionError(format!("Encryption failed: {e}")))?;
+/// Encrypt arbitrary bytes bound to `aad`, in the same `v2:` format.
+pub fn encrypt_bytes(
+    plaintext: &[u8],
+    encryption_key: &str,
+    aad: &[u8],
+) -> Result<String, McpError> {
+    seal(plaintext, encryption_key, aad)
+}
+
+/// Decrypt a value produced by [`encrypt_bytes`] with the same `aad`.
+pub fn decrypt_bytes(
+    encrypted: &str,
+    encryption_key: &str,
+    aad: &[u8],
+) -> Result<Vec<u8>, McpError> {
+    let encoded = encrypted
+        .strip_prefix(V2_PREFIX)
+        .ok_or_else(|| McpError::EncryptionError("Unknown ciphertext format".into()))?;
+    open_v2(encoded, encryption_key, aad)
+}
+
+/// [`decrypt_bound`], then re-encrypt a legacy value bound to its row and
+/// write it back. The write only lands if the column still holds
+/// `encrypted`; failing to write is logged, not returned, since the secret
+/// itself was read fine.
+pub async fn decrypt_row(
+    pool: &PgPool,
+    encrypted: &str,
+    encryption_key: &str,
+    binding: &SecretBinding,
+) -> Result<String, McpError> {
+    let decrypted = decrypt_bound(encrypted, encryption_key, binding)?;
+    if decrypted.legacy {
+        let upgraded = encrypt_bound(&decrypted.plaintext, encryption_key, binding)?;
+        if let Err(e) = queries::replace_encrypted_secret(
+            pool,
+            binding.field.table(),
+            binding.field.column(),
+            &binding.server_id,
+            binding.user_id.as_deref(),
+            encrypted,
+            &upgraded,
+        )
+        .await
+        {
+            warn!(
+                column = binding.field.column(),
+                server_id = %binding.server_id,
+                "failed to re-encrypt legacy secret: {e}"
+            );
+        }
+    }
+    Ok(decrypted.plaintext)
+}
 
-    // Prepend nonce to ciphertext
-    let mut combined = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
-    combined.extend_from_slice(&nonce_bytes);
-    combined.extend_from_slice(&ciphertext);
+fn seal(plaintext: &[u8], encryption_key: &str, aad: &[u8]) -> Result<String, McpError> {
+    let mut nonce = [0u8; XNONCE_SIZE];
+    rand::rng().fill_bytes(&mut nonce);
+
+    let sealed = cipher(encryption_key)
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| McpError::EncryptionError("Encryption failed".into()))?;
+
+    let mut combined = Vec::with_capacity(XNONCE_SIZE + sealed.len());
+    combined.extend_from_slice(&nonce);
+    combined.extend_from_slice(&sealed);
+    Ok(format!(
+        "{V2_PREFIX}{}",
+        base64::engine::general_purpose::STANDARD.encode(&combined)
+    ))
+}
+
+fn open(encrypted: &str, encryption_key: &str, aad: &[u8]) -> Result<Decrypted, McpError> {
+    let Some(encoded) = encrypted.strip_prefix(V2_PREFIX) else {
+        return open_legacy(encrypted, encryption_key).map(|plaintext| Decrypted {
+            plaintext,
+            legacy: true,
+        });
+    };
+    let plaintext = String::from_utf8(open_v2(encoded, encryption_key, aad)?)
+        .map_err(|e| McpError::EncryptionError(format!("UTF-8 decode failed: {e}")))?;
+    Ok(Decrypted {
+        plaintext,
+        legacy: false,
+    })
+}
+
+/// Decrypt base64 `nonce || ciphertext || tag` (after the `v2:` prefix).
+fn open_v2(encoded: &str, encryption_key: &str, aad: &[u8]) -> Result<Vec<u8>, McpError> {
+    let combined = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| McpError::EncryptionError(format!("Base64 decode failed: {e}")))?;
+    if combined.len() < XNONCE_SIZE + TAG_SIZE {
+        return Err(McpError::EncryptionError("Ciphertext too short".into()));
+    }
+
+    let (nonce, ciphertext) = combined.split_at(XNONCE_SIZE);
+    cipher(encryption_key)
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| McpError::EncryptionError("Decryption failed".into()))
+}
 
-    use base64::Engine;
-    Ok(base64::engine::general_purpose::STANDARD.encode(&combined))
+/// XChaCha20-Poly1305 under the key derived from `encryption_key`.
+fn cipher(encryption_key: &str) -> XChaCha20Poly1305 {
+    XChaCha20Poly1305::new(&derive_key(encryption_key).into())
 }
 
-/// Decrypt base64-encoded `nonce || ciphertext || tag`.
-pub fn decrypt(encrypted_b64: &str, encryption_key: &str) -> Result<String, McpError> {
+/// Decrypt legacy base64-encoded AES-256-GCM `nonce || ciphertext || tag`.
+fn open_legacy(encrypted_b64: &str, encryption_key: &str) -> Result<String, McpError> {
     use aes_gcm::aead::Aead;
     use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
-    use base64::Engine;
 
     let combined = base64::engine::general_purpose::STANDARD
         .decode(encrypted_b64)
         .map_err(|e| McpError::EncryptionError(format!("Base64 decode failed: {e}")))?;
 
-    if combined.len() < NONCE_SIZE + TAG_SIZE {
+    if combined.len() < LEGACY_NONCE_SIZE + TAG_SIZE {
         return Err(McpError::EncryptionError("Ciphertext too short".into()));
     }
 
@@ -72,8 +396,8 @@ pub fn decrypt(encrypted_b64: &str, encryption_key: &str) -> Result<String, McpE
     let cipher = Aes256Gcm::new_from_slice(&key_bytes)
         .map_err(|e| McpError::EncryptionError(format!("Key init failed: {e}")))?;
 
-    let nonce = Nonce::from_slice(&combined[..NONCE_SIZE]);
-    let ciphertext = &combined[NONCE_SIZE..];
+    let nonce = Nonce::from_slice(&combined[..LEGACY_NONCE_SIZE]);
+    let ciphertext = &combined[LEGACY_NONCE_SIZE..];
 
     let plaintext = cipher
         .decrypt(nonce, ciphertext)
@@ -87,11 +411,19 @@ pub fn decrypt(encrypted_b64: &str, encryption_key: &str) -> Result<String, McpE
 mod tests {
     use super::*;
 
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
     #[test]
     fn encrypt_decrypt_round_trip() {
         let key = "test-encryption-key-for-nize";
         let plaintext = "sk-super-secret-api-key-12345";
         let encrypted = encrypt(plaintext, key).unwrap();
+        assert!(encrypted.starts_with(V2_PREFIX));
         let decrypted = decrypt(&encrypted, key).unwrap();
         assert_eq!(decrypted, plaintext);
     }
@@ -112,4 +444,106 @@ mod tests {
         let decrypted = decrypt(&encrypted, key).unwrap();
         assert_eq!(decrypted, "");
     }
+
+    #[test]
+    fn xchacha20_poly1305_matches_draft_vector() {
+        let key: [u8; 32] = std::array::from_fn(|i| 0x80 + i as u8);
+        let nonce: Vec<u8> = (0x40..0x58).collect();
+        let aad = hex("50515253c0c1c2c3c4c5c6c7");
+        let sealed = XChaCha20Poly1305::new(&key.into())
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: b"Ladies and Gentlemen of the class of '99: If I could offer you \
+                        only one tip for the future, sunscreen would be it."
+                        .as_slice(),
+                    aad: &aad,
+                },
+            )
+            .unwrap();
+        assert_eq!(sealed[..8], hex("bd6d179d3e83d43b"));
+        assert_eq!(
+            sealed[sealed.len() - TAG_SIZE..],
+            hex("c0875924c1c7987947deafd8780acf49")
+        );
+    }
+
+    #[test]
+    fn bound_secrets_do_not_decrypt_for_another_row() {
+        let key = "test-key";
+        let server = "0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b";
+        let other_server = "0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5c";
+        let user = "0190a1b2-c3d4-7e5f-8a9b-000000000001";
+
+        let binding = SecretBinding::access_token(user, server);
+        let encrypted = encrypt_bound("ya29.token", key, &binding).unwrap();
+        let decrypted = decrypt_bound(&encrypted, key, &binding).unwrap();
+        assert_eq!(decrypted.plaintext, "ya29.token");
+        assert!(!decrypted.legacy);
+
+        // Same row in another case still matches.
+        let upper = SecretBinding::access_token(&user.to_uppercase(), server);
+        assert!(decrypt_bound(&encrypted, key, &upper).is_ok());
+
+        for other in [
+            SecretBinding::access_token(user, other_server),
+            SecretBinding::refresh_token(user, server),
+            SecretBinding::access_token("0190a1b2-c3d4-7e5f-8a9b-000000000002", server),
+        ] {
+            assert!(decrypt_bound(&encrypted, key, &other).is_err());
+        }
+        assert!(decrypt(&encrypted, key).is_err());
+    }
+
+    #[test]
+    fn workspace_keys_are_derived_per_workspace() {
+        let key = "test-key";
+        let a = uuid::Uuid::parse_str("0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b").unwrap();
+        let b = uuid::Uuid::parse_str("0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5c").unwrap();
+
+        let key_a = ServerKey::for_workspace(key, Some(&a));
+        assert_eq!(key_a.id, format!("workspace:{a}"));
+        assert_ne!(key_a.key, key);
+        assert_ne!(key_a, ServerKey::for_workspace(key, Some(&b)));
+        assert_eq!(ServerKey::from_id(key, &key_a.id), key_a);
+        assert_eq!(
+            ServerKey::for_workspace(key, None),
+            ServerKey::deployment(key)
+        );
+        assert_eq!(ServerKey::from_id(key, DEFAULT_KEY_ID).key, key);
+
+        let binding = SecretBinding::api_key("s");
+        let encrypted = encrypt_bound("sk-shared", &key_a.key, &binding).unwrap();
+        assert!(decrypt_bound(&encrypted, key, &binding).is_err());
+        let key_b = ServerKey::for_workspace(key, Some(&b));
+        assert!(decrypt_bound(&encrypted, &key_b.key, &binding).is_err());
+        assert_eq!(
+            decrypt_bound(&encrypted, &key_a.key, &binding)
+                .unwrap()
+                .plaintext,
+            "sk-shared"
+        );
+    }
+
+    #[test]
+    fn legacy_ciphertexts_decrypt_and_are_flagged() {
+        use aes_gcm::aead::Aead;
+        use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+
+        let key = "test-key";
+        let cipher = Aes256Gcm::new_from_slice(&derive_key(key)).unwrap();
+        let nonce = [7u8; LEGACY_NONCE_SIZE];
+        let mut combined = nonce.to_vec();
+        combined.extend(
+            cipher
+                .encrypt(Nonce::from_slice(&nonce), b"old-secret".as_slice())
+                .unwrap(),
+        );
+        let legacy = base64::engine::general_purpose::STANDARD.encode(&combined);
+
+        assert_eq!(decrypt(&legacy, key).unwrap(), "old-secret");
+        let decrypted = decrypt_bound(&legacy, key, &SecretBinding::api_key("s")).unwrap();
+        assert_eq!(decrypted.plaintext, "old-secret");
+        assert!(decrypted.legacy);
+    }
 }
@@ -0,0 +1,261 @@
+//! Sharing user MCP servers with workspaces.
+//!
+//! A user server shared with a workspace is usable by all of its members
+//! (see [`queries::user_has_server_access`]). Its secrets belong to the
+//! workspace rather than to the user who added it: they are encrypted under
+//! the workspace's [`ServerKey`] and workspace owners and admins may replace
+//! them. Moving a server into or out of a workspace re-encrypts its secrets
+//! under the new key.
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::McpError;
+use super::queries;
+use super::secrets::{self, SecretBinding, ServerKey};
+use crate::i18n::Message;
+use crate::models::mcp::{McpServerSecretRow, TransportType};
+
+/// A server shared with a workspace, without its secrets.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SharedServerRow {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub domain: String,
+    pub transport: TransportType,
+    pub owner_id: Option<Uuid>,
+    pub enabled: bool,
+    pub has_api_key: bool,
+    pub has_oauth_client_secret: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Servers shared with a workspace, by name.
+pub async fn list_workspace_servers(
+    pool: &PgPool,
+    workspace_id: &Uuid,
+) -> Result<Vec<SharedServerRow>, McpError> {
+    let rows = sqlx::query_as::<_, SharedServerRow>(
+        r#"
+        SELECT s.id, s.name, s.description, s.domain, s.transport, s.owner_id, s.enabled,
+               sec.api_key_encrypted IS NOT NULL AS has_api_key,
+               sec.oauth_client_secret_encrypted IS NOT NULL AS has_oauth_client_secret,
+               s.updated_at
+        FROM mcp_servers s
+        LEFT JOIN mcp_server_secrets sec ON sec.server_id = s.id
+        WHERE s.workspace_id = $1 AND s.visibility = 'user'
+        ORDER BY s.name
+        "#,
+    )
+    .bind(workspace_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Share a server with a workspace, or make it personal again with `None`,
+/// re-encrypting its secrets under the matching key.
+///
+/// The caller must already have checked that the user may share the server
+/// and is a member of the workspace.
+pub async fn share_server(
+    pool: &PgPool,
+    server_id: &str,
+    workspace_id: Option<&Uuid>,
+    encryption_key: &str,
+) -> Result<(), McpError> {
+    let mut tx = pool.begin().await?;
+    queries::set_server_workspace(&mut *tx, server_id, workspace_id).await?;
+    let key = ServerKey::for_workspace(encryption_key, workspace_id);
+    rekey(&mut tx, server_id, encryption_key, &key).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Set a shared server's API key and/or OAuth client secret under the
+/// workspace's key. Fails with `NotFound` if the server is not shared with
+/// `workspace_id`.
+///
+/// The caller must already have checked that the user manages the
+/// workspace.
+pub async fn set_workspace_secrets(
+    pool: &PgPool,
+    workspace_id: &Uuid,
+    server_id: &str,
+    api_key: Option<&str>,
+    oauth_client_secret: Option<&str>,
+    encryption_key: &str,
+) -> Result<(), McpError> {
+    if api_key.is_none() && oauth_client_secret.is_none() {
+        return Err(McpError::Validation(Message::new(
+            "mcp.shared_credentials_required",
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+    if queries::get_server_workspace(&mut *tx, server_id).await? != Some(*workspace_id) {
+        return Err(McpError::NotFound(
+            Message::new("mcp.server_not_shared").arg("id", server_id),
+        ));
+    }
+    store_secrets(
+        &mut tx,
+        server_id,
+        api_key,
+        oauth_client_secret,
+        encryption_key,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Store a server's API key and/or OAuth client secret under the key of
+/// the workspace it is shared with, or the deployment key.
+pub async fn store_secrets(
+    conn: &mut PgConnection,
+    server_id: &str,
+    api_key: Option<&str>,
+    oauth_client_secret: Option<&str>,
+    encryption_key: &str,
+) -> Result<(), McpError> {
+    if api_key.is_none() && oauth_client_secret.is_none() {
+        return Ok(());
+    }
+    let workspace_id = queries::get_server_workspace(&mut *conn, server_id).await?;
+    let key = ServerKey::for_workspace(encryption_key, workspace_id.as_ref());
+    // The row has one key id, so any secret still under another key moves
+    // first.
+    rekey(conn, server_id, encryption_key, &key).await?;
+
+    if let Some(api_key) = api_key {
+        let encrypted =
+            secrets::encrypt_bound(api_key, &key.key, &SecretBinding::api_key(server_id))?;
+        queries::store_api_key(&mut *conn, server_id, &encrypted, &key.id).await?;
+    }
+    if let Some(secret) = oauth_client_secret {
+        let encrypted = secrets::encrypt_bound(
+            secret,
+            &key.key,
+            &SecretBinding::oauth_client_secret(server_id),
+        )?;
+        queries::store_oauth_client_secret(&mut *conn, server_id, &encrypted, &key.id).await?;
+    }
+    Ok(())
+}
+
+/// Load and decrypt a server's stored API key, if it has one.
+pub async fn load_api_key(
+    pool: &PgPool,
+    server_id: &str,
+    encryption_key: &str,
+) -> Result<Option<String>, McpError> {
+    let Some(row) = queries::get_server_secrets(pool, server_id).await? else {
+        return Ok(None);
+    };
+    let Some(encrypted) = row.api_key_encrypted.as_deref() else {
+        return Ok(None);
+    };
+    let key = ServerKey::from_id(encryption_key, &row.encryption_key_id);
+    secrets::decrypt_row(
+        pool,
+        encrypted,
+        &key.key,
+        &SecretBinding::api_key(server_id),
+    )
+    .await
+    .map(Some)
+}
+
+/// Re-encrypt a server's stored secrets under `to`, if they are under
+/// another key.
+async fn rekey(
+    conn: &mut PgConnection,
+    server_id: &str,
+    encryption_key: &str,
+    to: &ServerKey,
+) -> Result<(), McpError> {
+    let Some(row) = queries::get_server_secrets(&mut *conn, server_id).await? else {
+        return Ok(());
+    };
+    if row.encryption_key_id == to.id {
+        return Ok(());
+    }
+    let (api_key, client_secret) = reencrypt(server_id, &row, encryption_key, to)?;
+    queries::replace_server_secrets(
+        &mut *conn,
+        server_id,
+        api_key.as_deref(),
+        client_secret.as_deref(),
+        &to.id,
+    )
+    .await
+}
+
+/// A secrets row's API key and OAuth client secret, re-encrypted under `to`.
+fn reencrypt(
+    server_id: &str,
+    row: &McpServerSecretRow,
+    encryption_key: &str,
+    to: &ServerKey,
+) -> Result<(Option<String>, Option<String>), McpError> {
+    let from = ServerKey::from_id(encryption_key, &row.encryption_key_id);
+    let convert = |encrypted: Option<&str>, binding: SecretBinding| {
+        encrypted
+            .map(|e| {
+                let plaintext = secrets::decrypt_bound(e, &from.key, &binding)?.plaintext;
+                secrets::encrypt_bound(&plaintext, &to.key, &binding)
+            })
+            .transpose()
+    };
+    Ok((
+        convert(
+            row.api_key_encrypted.as_deref(),
+            SecretBinding::api_key(server_id),
+        )?,
+        convert(
+            row.oauth_client_secret_encrypted.as_deref(),
+            SecretBinding::oauth_client_secret(server_id),
+        )?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reencrypt_moves_secrets_between_keys() {
+        let key = "test-key";
+        let server_id = "0190a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b";
+        let workspace_id = Uuid::parse_str("0190a1b2-c3d4-7e5f-8a9b-000000000001").unwrap();
+        let personal = ServerKey::deployment(key);
+        let shared = ServerKey::for_workspace(key, Some(&workspace_id));
+
+        let row = McpServerSecretRow {
+            id: Uuid::nil(),
+            server_id: Uuid::parse_str(server_id).unwrap(),
+            api_key_encrypted: Some(
+                secrets::encrypt_bound("sk-1", key, &SecretBinding::api_key(server_id)).unwrap(),
+            ),
+            oauth_client_secret_encrypted: None,
+            encryption_key_id: personal.id.clone(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let (api_key, client_secret) = reencrypt(server_id, &row, key, &shared).unwrap();
+        assert_eq!(client_secret, None);
+        let api_key = api_key.unwrap();
+        let binding = SecretBinding::api_key(server_id);
+        assert!(secrets::decrypt_bound(&api_key, &personal.key, &binding).is_err());
+        assert_eq!(
+            secrets::decrypt_bound(&api_key, &shared.key, &binding)
+                .unwrap()
+                .plaintext,
+            "sk-1"
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! Profile variables in tool arguments.
+//!
+//! String arguments of a tool call may reference the caller's profile as
+//! `{{user.email}}` or `{{workspace.name}}`. The MCP server resolves them
+//! just before the call, so a prompt can say "send it to me" without the
+//! model ever seeing the address. Only variables on the admin's allowlist
+//! (`mcp.templating.allowedVariables`) are resolved; a call referencing any
+//! other `user.*` or `workspace.*` variable is refused rather than sent
+//! with the placeholder in it. Other `{{…}}` text is left alone.
+//!
+//! Workspace variables name the workspace of the tool's server, when the
+//! caller is a member of it.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::McpError;
+use crate::i18n::Message;
+use crate::timezone;
+
+/// Config key listing the variables that may be resolved.
+pub const ALLOWED_VARIABLES_CONFIG_KEY: &str = "mcp.templating.allowedVariables";
+
+/// Every variable that can be resolved.
+pub const VARIABLES: &[&str] = &[
+    "user.id",
+    "user.email",
+    "user.name",
+    "user.timezone",
+    "workspace.id",
+    "workspace.name",
+];
+
+/// A `{{user.*}}` or `{{workspace.*}}` reference, spaces allowed inside the
+/// braces.
+static VARIABLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\{\s*((?:user|workspace)\.[A-Za-z_]+)\s*\}\}").expect("valid regex")
+});
+
+/// Values of the variables for one call; variables without a value (no
+/// name set, no workspace) are absent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    values: HashMap<&'static str, String>,
+}
+
+impl Profile {
+    pub fn new(values: impl IntoIterator<Item = (&'static str, String)>) -> Self {
+        Self {
+            values: values.into_iter().collect(),
+        }
+    }
+}
+
+/// One resolved reference, for the audit log. Holds the variable's name,
+/// never its value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Substitution {
+    /// JSON pointer of the argument, e.g. `/to`.
+    pub pointer: String,
+    pub variable: String,
+}
+
+/// Parse the comma-separated allowlist, ignoring blanks.
+pub fn parse_allowlist(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Check an allowlist value names only known variables.
+pub fn validate_allowlist(value: &str) -> Result<(), Message> {
+    match parse_allowlist(value)
+        .into_iter()
+        .find(|v| !VARIABLES.contains(&v.as_str()))
+    {
+        Some(unknown) => Err(Message::new("mcp.unknown_allowed_variable")
+            .arg("name", unknown)
+            .arg("known", VARIABLES.join(", "))),
+        None => Ok(()),
+    }
+}
+
+/// Whether any string in `params` references a variable.
+pub fn has_references(params: &Value) -> bool {
+    match params {
+        Value::String(s) => VARIABLE.is_match(s),
+        Value::Array(items) => items.iter().any(has_references),
+        Value::Object(map) => map.values().any(has_references),
+        _ => false,
+    }
+}
+
+/// Replace the variable references in the strings of `params` with their
+/// values. Fails, leaving `params` partly substituted, on a reference
+/// that is unknown, not allowed or has no value.
+pub fn substitute(
+    params: &mut Value,
+    allowed: &[String],
+    profile: &Profile,
+) -> Result<Vec<Substitution>, McpError> {
+    let mut substitutions = Vec::new();
+    substitute_at(params, String::new(), allowed, profile, &mut substitutions)?;
+    Ok(substitutions)
+}
+
+fn substitute_at(
+    value: &mut Value,
+    pointer: String,
+    allowed: &[String],
+    profile: &Profile,
+    substitutions: &mut Vec<Substitution>,
+) -> Result<(), McpError> {
+    match value {
+        Value::String(s) => {
+            if !VARIABLE.is_match(s) {
+                return Ok(());
+            }
+            let mut resolved = String::with_capacity(s.len());
+            let mut last = 0;
+            for caps in VARIABLE.captures_iter(s) {
+                let whole = caps.get(0).expect("match");
+                let name = &caps[1];
+                resolved.push_str(&s[last..whole.start()]);
+                resolved.push_str(resolve(name, allowed, profile)?);
+                last = whole.end();
+                substitutions.push(Substitution {
+                    pointer: if pointer.is_empty() {
+                        "/".into()
+                    } else {
+                        pointer.clone()
+                    },
+                    variable: name.to_string(),
+                });
+            }
+            resolved.push_str(&s[last..]);
+            *s = resolved;
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                substitute_at(
+                    item,
+                    format!("{pointer}/{i}"),
+                    allowed,
+                    profile,
+                    substitutions,
+                )?;
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map.iter_mut() {
+                let escaped = key.replace('~', "~0").replace('/', "~1");
+                substitute_at(
+                    item,
+                    format!("{pointer}/{escaped}"),
+                    allowed,
+                    profile,
+                    substitutions,
+                )?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn resolve<'a>(name: &str, allowed: &[String], profile: &'a Profile) -> Result<&'a str, McpError> {
+    if !VARIABLES.contains(&name) {
+        return Err(McpError::Validation(
+            Message::new("mcp.unknown_variable")
+                .arg("name", name)
+                .arg("known", VARIABLES.join(", ")),
+        ));
+    }
+    if !allowed.iter().any(|a| a == name) {
+        return Err(McpError::Validation(
+            Message::new("mcp.variable_not_allowed").arg("name", name),
+        ));
+    }
+    profile.values.get(name).map(String::as_str).ok_or_else(|| {
+        McpError::Validation(Message::new("mcp.variable_without_value").arg("name", name))
+    })
+}
+
+/// The variables allowed by config: the system value, else the default.
+pub async fn allowed_variables(pool: &PgPool) -> Result<Vec<String>, McpError> {
+    let value = sqlx::query_scalar::<_, Option<String>>(
+        r#"
+        SELECT COALESCE(
+            (SELECT value FROM config_values
+             WHERE key = $1 AND scope = 'system'::config_scope AND user_id IS NULL),
+            (SELECT default_value FROM config_definitions WHERE key = $1)
+        )
+        "#,
+    )
+    .bind(ALLOWED_VARIABLES_CONFIG_KEY)
+    .fetch_one(pool)
+    .await?;
+    Ok(parse_allowlist(&value.unwrap_or_default()))
+}
+
+/// The profile of `user_id` for a call of `tool_id`.
+pub async fn load_profile(
+    pool: &PgPool,
+    user_id: &Uuid,
+    tool_id: &Uuid,
+) -> Result<Profile, McpError> {
+    let row = sqlx::query_as::<_, (String, Option<String>, Option<Uuid>, Option<String>)>(
+        r#"
+        SELECT u.email, u.name, w.id, w.name
+        FROM users u
+        LEFT JOIN mcp_server_tools t ON t.id = $2
+        LEFT JOIN mcp_servers s ON s.id = t.server_id
+        LEFT JOIN workspace_members m ON m.workspace_id = s.workspace_id AND m.user_id = u.id
+        LEFT JOIN workspaces w ON w.id = m.workspace_id
+        WHERE u.id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(tool_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| McpError::NotFound(Message::new("mcp.user_not_found").arg("id", user_id)))?;
+    let (email, name, workspace_id, workspace_name) = row;
+
+    let mut conn = pool.acquire().await?;
+    let zone = timezone::user_timezone(&mut conn, user_id).await?;
+
+    let mut values = vec![
+        ("user.id", user_id.to_string()),
+        ("user.email", email),
+        ("user.timezone", zone),
+    ];
+    values.extend(
+        name.filter(|n| !n.trim().is_empty())
+            .map(|n| ("user.name", n)),
+    );
+    values.extend(workspace_id.map(|id| ("workspace.id", id.to_string())));
+    values.extend(workspace_name.map(|n| ("workspace.name", n)));
+    Ok(Profile::new(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn profile() -> Profile {
+        Profile::new([
+            ("user.email", "ada@example.com".to_string()),
+            ("user.name", "Ada".to_string()),
+        ])
+    }
+
+    fn all() -> Vec<String> {
+        VARIABLES.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn substitutes_nested_strings() {
+        let mut params = json!({
+            "to": "{{user.email}}",
+            "body": { "lines": ["Hi {{ user.name }},", "bye"] },
+            "count": 3
+        });
+        assert!(has_references(&params));
+        let subs = substitute(&mut params, &all(), &profile()).unwrap();
+        assert_eq!(
+            params,
+            json!({
+                "to": "ada@example.com",
+                "body": { "lines": ["Hi Ada,", "bye"] },
+                "count": 3
+            })
+        );
+        let pointers: Vec<_> = subs.iter().map(|s| s.pointer.as_str()).collect();
+        assert_eq!(pointers, ["/body/lines/0", "/to"]);
+        assert!(!has_references(&params));
+    }
+
+    #[test]
+    fn other_braces_are_left_alone() {
+        let mut params = json!({ "template": "{{name}} and {{ item.price }}" });
+        assert!(!has_references(&params));
+        assert!(
+            substitute(&mut params, &all(), &profile())
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(params["template"], "{{name}} and {{ item.price }}");
+    }
+
+    #[test]
+    fn refuses_unknown_disallowed_and_missing_variables() {
+        let err = |params: Value, allowed: &[String]| {
+            substitute(&mut params.clone(), allowed, &profile())
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(err(json!("{{user.phone}}"), &all()).contains("Unknown variable {{user.phone}}"));
+        assert!(
+            err(json!("{{user.email}}"), &["user.name".into()])
+                .contains("{{user.email}} is not allowed")
+        );
+        assert!(err(json!(["{{workspace.name}}"]), &all()).contains("has no value"));
+    }
+
+    #[test]
+    fn allowlist_names_known_variables() {
+        assert_eq!(
+            parse_allowlist(" user.email, ,workspace.name "),
+            ["user.email", "workspace.name"]
+        );
+        assert!(validate_allowlist("user.email,workspace.id").is_ok());
+        assert!(validate_allowlist("").is_ok());
+        assert!(validate_allowlist("user.password").is_err());
+    }
+}
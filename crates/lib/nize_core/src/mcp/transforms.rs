@@ -0,0 +1,585 @@
+//! Per-tool output transformation templates.
+//!
+//! Raw tool outputs are often far larger than what the model needs. An
+//! admin can attach a template to a tool: a [`JsonPath`] selection applied
+//! to JSON text output, and truncation rules capping array lengths, string
+//! lengths and the size of each text output. The MCP server applies the
+//! tool's template to every successful call before the result is returned
+//! to the model, unless the caller asks for the raw output.
+//!
+//! Templates are keyed by server and tool name, like tool overrides,
+//! because rediscovery replaces tool rows.
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::McpError;
+
+use crate::i18n::Message;
+
+/// Longest selector accepted.
+pub const MAX_SELECTOR_LEN: usize = 500;
+
+// =============================================================================
+// JSONPath
+// =============================================================================
+
+/// One step of a [`JsonPath`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// `.name` or `['name']`
+    Child(String),
+    /// `[n]`; negative counts from the end
+    Index(i64),
+    /// `.*` or `[*]`
+    Wildcard,
+    /// `[start:end]`
+    Slice(Option<i64>, Option<i64>),
+    /// `..name` (that member at any depth) or `..*` (every descendant)
+    Descendant(Option<String>),
+}
+
+/// A JSONPath expression: `$` followed by `.name`, `['name']`, `[n]`,
+/// `[start:end]`, `.*`/`[*]` and `..name` steps. Filters and unions are not
+/// supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let rest = expr
+            .trim()
+            .strip_prefix('$')
+            .ok_or_else(|| "a JSONPath must start with '$'".to_string())?;
+        let chars: Vec<char> = rest.chars().collect();
+        let mut segments = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                '.' if chars.get(i + 1) == Some(&'.') => {
+                    let (name, next) = read_name(&chars, i + 2)?;
+                    segments.push(Segment::Descendant(name));
+                    i = next;
+                }
+                '.' => {
+                    let (name, next) = read_name(&chars, i + 1)?;
+                    segments.push(name.map_or(Segment::Wildcard, Segment::Child));
+                    i = next;
+                }
+                '[' => {
+                    let end = closing_bracket(&chars, i + 1)?;
+                    let inner: String = chars[i + 1..end].iter().collect();
+                    segments.push(parse_bracket(inner.trim())?);
+                    i = end + 1;
+                }
+                c => return Err(format!("unexpected '{c}' at position {}", i + 2)),
+            }
+        }
+        Ok(Self { segments })
+    }
+
+    /// Whether the path selects at most one value (no wildcard, slice or
+    /// descendant steps).
+    pub fn is_definite(&self) -> bool {
+        self.segments
+            .iter()
+            .all(|s| matches!(s, Segment::Child(_) | Segment::Index(_)))
+    }
+
+    /// The values the path selects in `root`, in document order.
+    pub fn select<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        let mut nodes = vec![root];
+        for segment in &self.segments {
+            let mut next = Vec::new();
+            for node in nodes {
+                match segment {
+                    Segment::Child(name) => {
+                        next.extend(node.as_object().and_then(|o| o.get(name)));
+                    }
+                    Segment::Index(index) => {
+                        if let Some(items) = node.as_array() {
+                            let index = if *index < 0 {
+                                items.len() as i64 + index
+                            } else {
+                                *index
+                            };
+                            if index >= 0 {
+                                next.extend(items.get(index as usize));
+                            }
+                        }
+                    }
+                    Segment::Wildcard => match node {
+                        Value::Array(items) => next.extend(items.iter()),
+                        Value::Object(members) => next.extend(members.values()),
+                        _ => {}
+                    },
+                    Segment::Slice(start, end) => {
+                        if let Some(items) = node.as_array() {
+                            let len = items.len() as i64;
+                            let bound = |i: i64| if i < 0 { (len + i).max(0) } else { i.min(len) };
+                            let start = start.map_or(0, bound);
+                            let end = end.map_or(len, bound);
+                            if start < end {
+                                next.extend(&items[start as usize..end as usize]);
+                            }
+                        }
+                    }
+                    Segment::Descendant(name) => descendants(node, name.as_deref(), &mut next),
+                }
+            }
+            nodes = next;
+        }
+        nodes
+    }
+
+    /// The selection as one value: the value itself (or null when missing)
+    /// for a definite path, otherwise an array of the matches.
+    pub fn select_value(&self, root: &Value) -> Value {
+        let selected = self.select(root);
+        if self.is_definite() {
+            selected.first().map_or(Value::Null, |v| (*v).clone())
+        } else {
+            Value::Array(selected.into_iter().cloned().collect())
+        }
+    }
+}
+
+/// Read a member name (or `*`, as `None`) starting at `start`.
+fn read_name(chars: &[char], start: usize) -> Result<(Option<String>, usize), String> {
+    if chars.get(start) == Some(&'*') {
+        return Ok((None, start + 1));
+    }
+    let end = chars[start.min(chars.len())..]
+        .iter()
+        .position(|c| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '$')))
+        .map_or(chars.len(), |p| start + p);
+    if end == start {
+        return Err(format!("expected a member name at position {}", start + 2));
+    }
+    Ok((Some(chars[start..end].iter().collect()), end))
+}
+
+/// Index of the `]` closing a bracket opened before `start`, skipping
+/// quoted names.
+fn closing_bracket(chars: &[char], start: usize) -> Result<usize, String> {
+    let mut quote = None;
+    for (i, &c) in chars.iter().enumerate().skip(start) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ']') => return Ok(i),
+            (None, _) => {}
+        }
+    }
+    Err("unclosed '['".to_string())
+}
+
+fn parse_bracket(inner: &str) -> Result<Segment, String> {
+    if inner == "*" {
+        return Ok(Segment::Wildcard);
+    }
+    for quote in ['\'', '"'] {
+        if let Some(name) = inner
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return Ok(Segment::Child(name.to_string()));
+        }
+    }
+    let int = |s: &str| {
+        s.trim()
+            .parse::<i64>()
+            .map_err(|_| format!("invalid index '{}'", s.trim()))
+    };
+    if let Some((start, end)) = inner.split_once(':') {
+        let bound = |s: &str| (!s.trim().is_empty()).then(|| int(s)).transpose();
+        return Ok(Segment::Slice(bound(start)?, bound(end)?));
+    }
+    Ok(Segment::Index(int(inner)?))
+}
+
+/// Collect the descendants of `node` (`name`: the members called `name` at
+/// any depth, including `node`'s own).
+fn descendants<'a>(node: &'a Value, name: Option<&str>, out: &mut Vec<&'a Value>) {
+    let children: Box<dyn Iterator<Item = &'a Value>> = match node {
+        Value::Array(items) => Box::new(items.iter()),
+        Value::Object(members) => {
+            if let Some(value) = name.and_then(|n| members.get(n)) {
+                out.push(value);
+            }
+            Box::new(members.values())
+        }
+        _ => return,
+    };
+    for child in children {
+        if name.is_none() {
+            out.push(child);
+        }
+        descendants(child, name, out);
+    }
+}
+
+// =============================================================================
+// Rules
+// =============================================================================
+
+/// What a template does to a tool's output. Limits are `None` when
+/// unlimited.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformRules {
+    /// [`JsonPath`] applied to text output that is JSON.
+    #[serde(default)]
+    pub selector: Option<String>,
+    /// Items kept per array.
+    #[serde(default)]
+    pub max_array_items: Option<i32>,
+    /// Characters kept per string value.
+    #[serde(default)]
+    pub max_string_chars: Option<i32>,
+    /// Characters kept per text output, after selection.
+    #[serde(default)]
+    pub max_output_chars: Option<i32>,
+}
+
+impl TransformRules {
+    /// Check the selector parses and the limits are positive.
+    pub fn validate(&self) -> Result<(), McpError> {
+        if let Some(selector) = &self.selector {
+            if selector.len() > MAX_SELECTOR_LEN {
+                return Err(McpError::Validation(
+                    Message::new("mcp.selector_too_long").arg("max", MAX_SELECTOR_LEN),
+                ));
+            }
+            JsonPath::parse(selector).map_err(|e| {
+                McpError::Validation(Message::new("mcp.invalid_selector").arg("error", e))
+            })?;
+        }
+        for (name, limit) in [
+            ("maxArrayItems", self.max_array_items),
+            ("maxStringChars", self.max_string_chars),
+            ("maxOutputChars", self.max_output_chars),
+        ] {
+            if limit.is_some_and(|n| n <= 0) {
+                return Err(McpError::Validation(
+                    Message::new("mcp.limit_not_positive").arg("name", name),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Transform a tool result (`{"content": [...], "isError": ...}`, as
+    /// produced by tool execution). Each text content that is JSON has the
+    /// selector and the array and string limits applied and is re-encoded
+    /// compactly; every text content is then cut to `max_output_chars`.
+    /// Other content is kept as is.
+    pub fn apply(&self, result: &Value) -> Result<Value, McpError> {
+        let path = self
+            .selector
+            .as_deref()
+            .map(JsonPath::parse)
+            .transpose()
+            .map_err(|e| {
+                McpError::Validation(Message::new("mcp.invalid_selector").arg("error", e))
+            })?;
+        let mut result = result.clone();
+        if let Some(content) = result.get_mut("content").and_then(Value::as_array_mut) {
+            for item in content {
+                if item.get("type").and_then(Value::as_str) != Some("text") {
+                    continue;
+                }
+                if let Some(text) = item.get_mut("text")
+                    && let Some(s) = text.as_str()
+                {
+                    *text = Value::String(self.transform_text(path.as_ref(), s));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    fn transform_text(&self, path: Option<&JsonPath>, text: &str) -> String {
+        let text = match serde_json::from_str::<Value>(text) {
+            Ok(json) => {
+                let selected = path.map_or(json.clone(), |p| p.select_value(&json));
+                serde_json::to_string(&self.truncate(selected)).unwrap_or_default()
+            }
+            Err(_) => text.to_string(),
+        };
+        cut(text, self.max_output_chars)
+    }
+
+    /// Apply the array and string limits throughout `value`.
+    fn truncate(&self, value: Value) -> Value {
+        match value {
+            Value::Array(items) => {
+                let total = items.len();
+                let keep = self
+                    .max_array_items
+                    .map_or(total, |n| total.min(n as usize));
+                let mut items: Vec<Value> = items
+                    .into_iter()
+                    .take(keep)
+                    .map(|v| self.truncate(v))
+                    .collect();
+                if keep < total {
+                    items.push(Value::String(format!("…[{} more items]", total - keep)));
+                }
+                Value::Array(items)
+            }
+            Value::Object(members) => Value::Object(
+                members
+                    .into_iter()
+                    .map(|(k, v)| (k, self.truncate(v)))
+                    .collect(),
+            ),
+            Value::String(s) => Value::String(cut(s, self.max_string_chars)),
+            other => other,
+        }
+    }
+}
+
+/// Cut `text` to `max` characters, noting how many were dropped.
+fn cut(text: String, max: Option<i32>) -> String {
+    let Some(max) = max.map(|n| n as usize) else {
+        return text;
+    };
+    let total = text.chars().count();
+    if total <= max {
+        return text;
+    }
+    let mut cut: String = text.chars().take(max).collect();
+    cut.push_str(&format!("…[{} more characters]", total - max));
+    cut
+}
+
+// =============================================================================
+// Templates
+// =============================================================================
+
+/// Database row for `mcp_tool_output_transforms`.
+#[derive(Debug, Clone, Serialize, JsonSchema, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputTransformRow {
+    pub server_id: Uuid,
+    pub tool_name: String,
+    pub selector: Option<String>,
+    pub max_array_items: Option<i32>,
+    pub max_string_chars: Option<i32>,
+    pub max_output_chars: Option<i32>,
+    pub enabled: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OutputTransformRow {
+    pub fn rules(&self) -> TransformRules {
+        TransformRules {
+            selector: self.selector.clone(),
+            max_array_items: self.max_array_items,
+            max_string_chars: self.max_string_chars,
+            max_output_chars: self.max_output_chars,
+        }
+    }
+}
+
+const TRANSFORM_COLUMNS: &str = "x.server_id, x.tool_name, x.selector, x.max_array_items, \
+     x.max_string_chars, x.max_output_chars, x.enabled, x.updated_at";
+
+/// List all templates, by server and tool.
+pub async fn list_transforms(pool: &PgPool) -> Result<Vec<OutputTransformRow>, McpError> {
+    let rows = sqlx::query_as::<_, OutputTransformRow>(&format!(
+        "SELECT {TRANSFORM_COLUMNS} FROM mcp_tool_output_transforms x \
+         ORDER BY x.server_id, x.tool_name"
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// The enabled template of the tool with row id `tool_id`, if any.
+pub async fn tool_transform(
+    pool: &PgPool,
+    tool_id: &Uuid,
+) -> Result<Option<OutputTransformRow>, McpError> {
+    let row = sqlx::query_as::<_, OutputTransformRow>(&format!(
+        "SELECT {TRANSFORM_COLUMNS} FROM mcp_tool_output_transforms x \
+         JOIN mcp_server_tools t ON t.server_id = x.server_id AND t.name = x.tool_name \
+         WHERE t.id = $1 AND x.enabled"
+    ))
+    .bind(tool_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Create or replace the template of `server_id`'s tool `tool_name`.
+pub async fn set_transform(
+    pool: &PgPool,
+    server_id: &Uuid,
+    tool_name: &str,
+    rules: &TransformRules,
+    enabled: bool,
+    admin_id: &Uuid,
+) -> Result<OutputTransformRow, McpError> {
+    rules.validate()?;
+    let selector = rules
+        .selector
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    let row = sqlx::query_as::<_, OutputTransformRow>(&format!(
+        r#"
+        INSERT INTO mcp_tool_output_transforms AS x
+            (server_id, tool_name, selector, max_array_items, max_string_chars,
+             max_output_chars, enabled, updated_by, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, now())
+        ON CONFLICT (server_id, tool_name) DO UPDATE SET
+            selector = EXCLUDED.selector,
+            max_array_items = EXCLUDED.max_array_items,
+            max_string_chars = EXCLUDED.max_string_chars,
+            max_output_chars = EXCLUDED.max_output_chars,
+            enabled = EXCLUDED.enabled,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = now()
+        RETURNING {TRANSFORM_COLUMNS}
+        "#
+    ))
+    .bind(server_id)
+    .bind(tool_name)
+    .bind(selector)
+    .bind(rules.max_array_items)
+    .bind(rules.max_string_chars)
+    .bind(rules.max_output_chars)
+    .bind(enabled)
+    .bind(admin_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Remove a tool's template. Returns whether one existed.
+pub async fn delete_transform(
+    pool: &PgPool,
+    server_id: &Uuid,
+    tool_name: &str,
+) -> Result<bool, McpError> {
+    let result = sqlx::query(
+        "DELETE FROM mcp_tool_output_transforms WHERE server_id = $1 AND tool_name = $2",
+    )
+    .bind(server_id)
+    .bind(tool_name)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn doc() -> Value {
+        json!({
+            "total": 3,
+            "items": [
+                {"title": "a", "meta": {"id": 1}},
+                {"title": "b", "meta": {"id": 2}},
+                {"title": "c", "meta": {"id": 3}}
+            ]
+        })
+    }
+
+    fn select(expr: &str) -> Value {
+        JsonPath::parse(expr).unwrap().select_value(&doc())
+    }
+
+    #[test]
+    fn paths_select_members_indexes_and_slices() {
+        assert_eq!(select("$.total"), json!(3));
+        assert_eq!(select("$['items'][1].title"), json!("b"));
+        assert_eq!(select("$.items[-1].meta.id"), json!(3));
+        assert_eq!(select("$.missing"), Value::Null);
+        assert_eq!(select("$.items[*].title"), json!(["a", "b", "c"]));
+        assert_eq!(select("$.items[1:].title"), json!(["b", "c"]));
+        assert_eq!(select("$..id"), json!([1, 2, 3]));
+        assert_eq!(select("$"), doc());
+    }
+
+    #[test]
+    fn invalid_paths_are_rejected() {
+        for expr in ["items", "$.", "$[1", "$.items[x]", "$ items"] {
+            assert!(JsonPath::parse(expr).is_err(), "{expr}");
+        }
+    }
+
+    #[test]
+    fn rules_select_and_truncate_json_text() {
+        let rules = TransformRules {
+            selector: Some("$.items[*].title".into()),
+            max_array_items: Some(2),
+            ..Default::default()
+        };
+        let result = json!({
+            "content": [
+                {"type": "text", "text": doc().to_string()},
+                {"type": "image", "data": "AAAA", "mimeType": "image/png"}
+            ],
+            "isError": false
+        });
+        let out = rules.apply(&result).unwrap();
+        assert_eq!(out["content"][0]["text"], r#"["a","b","…[1 more items]"]"#);
+        assert_eq!(out["content"][1], result["content"][1]);
+    }
+
+    #[test]
+    fn rules_cut_strings_and_outputs() {
+        let strings = TransformRules {
+            max_string_chars: Some(3),
+            ..Default::default()
+        };
+        let json_text = json!({"content": [{"type": "text", "text": r#"{"a": "abcdef"}"#}]});
+        assert_eq!(
+            strings.apply(&json_text).unwrap()["content"][0]["text"],
+            r#"{"a":"abc…[3 more characters]"}"#
+        );
+
+        let outputs = TransformRules {
+            max_output_chars: Some(5),
+            ..Default::default()
+        };
+        let plain = json!({"content": [{"type": "text", "text": "short"}]});
+        assert_eq!(outputs.apply(&plain).unwrap(), plain);
+        let long = json!({"content": [{"type": "text", "text": "longer text"}]});
+        assert_eq!(
+            outputs.apply(&long).unwrap()["content"][0]["text"],
+            "longe…[6 more characters]"
+        );
+    }
+
+    #[test]
+    fn rules_are_validated() {
+        assert!(TransformRules::default().validate().is_ok());
+        let bad_selector = TransformRules {
+            selector: Some("items".into()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            bad_selector.validate(),
+            Err(McpError::Validation(_))
+        ));
+        let bad_limit = TransformRules {
+            max_output_chars: Some(0),
+            ..Default::default()
+        };
+        assert!(bad_limit.validate().is_err());
+    }
+}
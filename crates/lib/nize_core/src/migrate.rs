@@ -2,9 +2,67 @@
 //!
 //! Embeds and runs SQL migrations from `nize_core/migrations/`.
 
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use sqlx::migrate::Migrator;
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
 
 /// Run all embedded database migrations against the given pool.
 pub async fn migrate(pool: &PgPool) -> Result<(), sqlx::migrate::MigrateError> {
-    sqlx::migrate!("./migrations").run(pool).await
+    MIGRATOR.run(pool).await
+}
+
+/// How far a database is behind the embedded migrations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationStatus {
+    /// Embedded migrations the database has applied.
+    pub applied: usize,
+    /// Embedded migrations not yet applied.
+    pub pending: usize,
+    /// Version of the newest embedded migration.
+    pub latest: Option<i64>,
+}
+
+/// Compare the database's applied migrations with the embedded ones.
+pub async fn status(pool: &PgPool) -> Result<MigrationStatus, sqlx::Error> {
+    let has_table: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(pool)
+        .await?;
+    let applied: Vec<i64> = if has_table {
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await?
+    } else {
+        Vec::new()
+    };
+    Ok(compare(&MIGRATOR, &applied))
+}
+
+fn compare(migrator: &Migrator, applied: &[i64]) -> MigrationStatus {
+    let versions: Vec<i64> = migrator.iter().map(|m| m.version).collect();
+    let done = versions.iter().filter(|v| applied.contains(v)).count();
+    MigrationStatus {
+        applied: done,
+        pending: versions.len() - done,
+        latest: versions.iter().max().copied(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unapplied_embedded_migrations_are_pending() {
+        let total = MIGRATOR.iter().count();
+        let fresh = compare(&MIGRATOR, &[]);
+        assert_eq!((fresh.applied, fresh.pending), (0, total));
+
+        let all: Vec<i64> = MIGRATOR.iter().map(|m| m.version).collect();
+        let current = compare(&MIGRATOR, &all[..total - 1]);
+        assert_eq!((current.applied, current.pending), (total - 1, 1));
+        assert_eq!(current.latest, all.iter().max().copied());
+    }
 }
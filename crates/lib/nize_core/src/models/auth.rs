@@ -56,7 +56,16 @@ pub struct McpTokenRecord {
     pub id: String,
     pub user_id: String,
     pub name: String,
+    /// Sessions may only use tools that change nothing.
+    pub read_only: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
     pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
 }
+
+/// A validated MCP token: its user and the token's restrictions.
+#[derive(Debug, Clone)]
+pub struct McpTokenAuth {
+    pub user: User,
+    pub read_only: bool,
+}
@@ -5,6 +5,7 @@
 //! Types matching the `config_definitions` and `config_values` tables,
 //! plus the resolved view used by the API layer.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Config scope — matches the `config_scope` Postgres enum.
@@ -68,7 +69,7 @@ pub struct ConfigValue {
 }
 
 /// Validator definition for config values.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ConfigValidator {
     #[serde(rename = "type")]
     pub validator_type: String,
@@ -78,7 +79,7 @@ pub struct ConfigValidator {
 
 /// Resolved config item — definition metadata + resolved value + override flag.
 /// This is the shape returned to API consumers.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ResolvedConfigItem {
     pub key: String,
     pub category: String,
@@ -3,6 +3,7 @@
 //! These are internal domain models for MCP server configuration,
 //! aligned with the reference project's `packages/agent/src/mcp/types.ts`.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 // =============================================================================
@@ -10,7 +11,7 @@ use serde::{Deserialize, Serialize};
 // =============================================================================
 
 /// Server visibility tier.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, JsonSchema)]
 #[sqlx(type_name = "visibility_tier", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum VisibilityTier {
@@ -20,7 +21,7 @@ pub enum VisibilityTier {
 }
 
 /// Transport type for MCP servers.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema, sqlx::Type)]
 #[sqlx(type_name = "transport_type", rename_all = "lowercase")]
 #[serde(rename_all = "kebab-case")]
 pub enum TransportType {
@@ -60,7 +61,7 @@ pub enum TransportProtocol {
 }
 
 /// Authentication type for MCP servers.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, JsonSchema)]
 #[sqlx(type_name = "auth_type")]
 #[serde(rename_all = "kebab-case")]
 pub enum AuthType {
@@ -76,7 +77,7 @@ pub enum AuthType {
 }
 
 /// Computed server status for display.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ServerStatus {
     Enabled,
@@ -105,6 +106,11 @@ pub struct McpServerRow {
     pub owner_id: Option<sqlx::types::Uuid>,
     pub enabled: bool,
     pub available: bool,
+    /// Pre-connect at startup and exempt from idle eviction.
+    pub keep_warm: bool,
+    /// Caller context keys attached to tool calls (see
+    /// [`crate::mcp::context`]).
+    pub forwarded_context: Vec<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -121,6 +127,37 @@ pub struct McpServerToolRow {
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Tool discovery status while the server is being queried.
+pub const DISCOVERY_RUNNING: &str = "running";
+/// Tool discovery status after the tools were stored.
+pub const DISCOVERY_SUCCEEDED: &str = "succeeded";
+/// Tool discovery status after a failure (see `error`).
+pub const DISCOVERY_FAILED: &str = "failed";
+
+/// Database row for `mcp_server_discovery`: the latest tool discovery run.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct McpDiscoveryRow {
+    #[serde(skip)]
+    pub server_id: sqlx::types::Uuid,
+    pub status: String,
+    pub tool_count: i32,
+    pub error: Option<String>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Per-server counts for server listings, fetched for many servers at once.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct McpServerStatsRow {
+    pub server_id: sqlx::types::Uuid,
+    pub tool_count: i64,
+    /// Users who enabled the server.
+    pub user_preference_count: i64,
+    /// Whether the requesting user holds an unexpired OAuth token.
+    pub has_valid_oauth_token: bool,
+}
+
 /// Database row for `user_mcp_preferences`.
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct UserMcpPreferenceRow {
@@ -156,12 +193,23 @@ pub struct McpOauthTokenRow {
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Database row for `mcp_oauth_pending`.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct McpOauthPendingRow {
+    pub user_id: sqlx::types::Uuid,
+    pub server_id: sqlx::types::Uuid,
+    pub pkce_verifier_encrypted: String,
+    pub oauth_config: serde_json::Value,
+    pub redirect_uri: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
 // =============================================================================
 // View structs (API responses)
 // =============================================================================
 
 /// User view of a server (used in `/settings/tools`).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UserServerView {
     pub id: String,
@@ -177,7 +225,7 @@ pub struct UserServerView {
 }
 
 /// Admin view of a server (used in `/admin/tools`).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AdminServerView {
     pub id: String,
@@ -194,19 +242,71 @@ pub struct AdminServerView {
     pub user_preference_count: i64,
     pub enabled: bool,
     pub available: bool,
+    pub keep_warm: bool,
+    /// Caller context keys attached to the server's tool calls.
+    pub forwarded_context: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub config: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub oauth_config: Option<serde_json::Value>,
+    /// Latest background tool discovery (absent when none ran).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discovery: Option<McpDiscoveryRow>,
     pub created_at: String,
     pub updated_at: String,
 }
 
-/// Tool summary returned from server tools endpoint.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Tool manifest captured from a server's tool listing, stored in
+/// `mcp_server_tools.manifest` and returned from the server tools endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct McpToolSummary {
     pub name: String,
     pub description: String,
+    /// JSON Schema of the tool's arguments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_schema: Option<serde_json::Value>,
+    /// Behaviour hints (`readOnlyHint`, `destructiveHint`, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<serde_json::Value>,
+}
+
+impl McpToolSummary {
+    /// Read the manifest of a stored tool. Rows stored before manifests
+    /// carried schemas yield just the name and description.
+    pub fn from_row(row: McpServerToolRow) -> Self {
+        serde_json::from_value(row.manifest).unwrap_or(Self {
+            name: row.name,
+            description: row.description,
+            input_schema: None,
+            annotations: None,
+        })
+    }
+
+    /// Whether the server declares the tool free of side effects: annotated
+    /// `readOnlyHint` and not `destructiveHint`. Unannotated tools are not.
+    pub fn is_read_only(&self) -> bool {
+        let hint = |name: &str| {
+            self.annotations
+                .as_ref()
+                .and_then(|a| a.get(name))
+                .and_then(|v| v.as_bool())
+        };
+        hint("readOnlyHint") == Some(true) && hint("destructiveHint") != Some(true)
+    }
+}
+
+/// A server tool as listed for a user, with whether it is switched on.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerToolView {
+    pub id: sqlx::types::Uuid,
+    #[serde(flatten)]
+    pub tool: McpToolSummary,
+    /// Switched on by the admin for everyone.
+    pub admin_enabled: bool,
+    /// Switched on by the user. The tool is usable when both are.
+    pub enabled: bool,
 }
 
 // =============================================================================
@@ -215,7 +315,7 @@ pub struct McpToolSummary {
 
 /// Stdio-based MCP server configuration.
 /// Admin-only: spawns local subprocess.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct StdioServerConfig {
     pub command: String,
@@ -223,11 +323,38 @@ pub struct StdioServerConfig {
     pub args: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub env: Option<std::collections::HashMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxConfig>,
+}
+
+/// Restrictions on a spawned server process (see [`crate::mcp::sandbox`]).
+/// Every field is optional; an empty block changes nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxConfig {
+    /// Absolute directory the process starts in, created if missing. Its
+    /// home and temp directory variables point there too.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    /// Start from an empty environment instead of the app's, keeping only
+    /// the basics a process needs (`PATH`, `HOME`, locale, ...), the
+    /// variables named in `envAllowlist` and the server's own `env`.
+    #[serde(default)]
+    pub scrub_env: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_allowlist: Vec<String>,
+    /// Memory limit in MiB (data segment on Linux, address space on other
+    /// Unixes, committed memory on Windows).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u64>,
+    /// Total CPU time in seconds after which the process is killed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cpu_secs: Option<u64>,
 }
 
 /// HTTP-based MCP server configuration.
 /// Supports no auth, API key, or OAuth authentication.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct HttpServerConfig {
     pub url: String,
@@ -239,7 +366,7 @@ pub struct HttpServerConfig {
 }
 
 /// External SSE MCP server configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SseServerConfig {
     pub url: String,
@@ -252,7 +379,7 @@ pub struct SseServerConfig {
 
 /// Managed HTTP/SSE MCP server configuration.
 /// Server is spawned as a child process, then connected via HTTP or SSE.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ManagedHttpServerConfig {
     pub command: String,
@@ -265,11 +392,13 @@ pub struct ManagedHttpServerConfig {
     pub path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub ready_timeout_secs: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxConfig>,
 }
 
 /// Discriminated union for MCP server transport configuration.
 /// Uses `transport` as the tag field, following idiomatic MCP conventions.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "transport")]
 pub enum ServerConfig {
     #[serde(rename = "stdio")]
@@ -295,6 +424,15 @@ impl ServerConfig {
         }
     }
 
+    /// Sandbox block of a transport that spawns a process.
+    pub fn sandbox(&self) -> Option<&SandboxConfig> {
+        match self {
+            Self::Stdio(c) => c.sandbox.as_ref(),
+            Self::ManagedSse(c) | Self::ManagedHttp(c) => c.sandbox.as_ref(),
+            Self::Http(_) | Self::Sse(_) => None,
+        }
+    }
+
     /// Get the transport type.
     pub fn transport_type(&self) -> TransportType {
         match self {
@@ -308,7 +446,7 @@ impl ServerConfig {
 }
 
 /// OAuth client configuration.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct OAuthConfig {
     pub client_id: String,
@@ -322,7 +460,7 @@ pub struct OAuthConfig {
 // =============================================================================
 
 /// Result from deleting a built-in server.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DeleteResult {
     pub deleted: bool,
@@ -333,7 +471,7 @@ pub struct DeleteResult {
 }
 
 /// Result from testing a connection.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TestConnectionResult {
     pub success: bool,
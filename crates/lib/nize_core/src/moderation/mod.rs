@@ -0,0 +1,513 @@
+//! Content moderation for chat prompts, model responses and ingested
+//! documents.
+//!
+//! Content is checked against admin-managed keyword/regex rules (see
+//! [`rules`]) and, optionally, an external provider (see [`provider`]).
+//! Every match is a [`Finding`] carrying an [`Action`]: `flag` only records
+//! it for review, `redact` also masks the matched text, and `block` rejects
+//! the content. The strongest action across the findings wins. Checks with
+//! findings are stored as [`ModerationResult`]s that admins review.
+//!
+//! Nothing runs unless `moderation.enabled` is `true`; the
+//! `moderation.check*` keys switch individual sources off.
+
+pub mod provider;
+pub mod rules;
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::config::cache::ConfigCache;
+use crate::config::{queries, resolver};
+use crate::i18n::Message;
+use crate::mcp::secrets;
+use crate::models::config::ConfigScope;
+use crate::uuid::uuidv7;
+
+use self::provider::Provider;
+use self::rules::Ruleset;
+
+/// Config key switching moderation on.
+pub const ENABLED_CONFIG_KEY: &str = "moderation.enabled";
+
+/// Longest excerpt of checked content kept with a result, in characters.
+pub const EXCERPT_CHARS: usize = 500;
+
+/// Maximum review note length in characters.
+pub const MAX_NOTE_CHARS: usize = 2000;
+
+/// Errors that can occur in moderation operations.
+#[derive(Debug, Error)]
+pub enum ModerationError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Not found: {0}")]
+    NotFound(Message),
+
+    #[error("Config error: {0}")]
+    Config(String),
+
+    #[error("Moderation provider error: {0}")]
+    Provider(String),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// What happens to content a finding applies to, weakest first.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// Record for review; the content passes unchanged.
+    Flag,
+    /// Mask the matched text. Provider findings have no span and cannot
+    /// redact.
+    Redact,
+    /// Reject the content.
+    Block,
+}
+
+impl Action {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Flag => "flag",
+            Self::Redact => "redact",
+            Self::Block => "block",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Action {
+    type Err = ModerationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flag" => Ok(Self::Flag),
+            "redact" => Ok(Self::Redact),
+            "block" => Ok(Self::Block),
+            other => Err(ModerationError::Validation(
+                Message::new("moderation.unknown_action").arg("action", other),
+            )),
+        }
+    }
+}
+
+/// Where checked content comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// The latest user message of a chat request.
+    Prompt,
+    /// A model response.
+    Response,
+    /// The extracted text of an uploaded document.
+    Document,
+}
+
+impl Source {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Prompt => "prompt",
+            Self::Response => "response",
+            Self::Document => "document",
+        }
+    }
+
+    /// Config key switching checks of this source on.
+    fn config_key(self) -> &'static str {
+        match self {
+            Self::Prompt => "moderation.checkPrompts",
+            Self::Response => "moderation.checkResponses",
+            Self::Document => "moderation.checkDocuments",
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Source {
+    type Err = ModerationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "prompt" => Ok(Self::Prompt),
+            "response" => Ok(Self::Response),
+            "document" => Ok(Self::Document),
+            other => Err(ModerationError::Validation(
+                Message::new("moderation.unknown_source").arg("source", other),
+            )),
+        }
+    }
+}
+
+/// What produced a finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Origin {
+    Rule,
+    Provider,
+}
+
+/// A rule or provider category that matched.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    pub origin: Origin,
+    /// Rule label, or the provider's category name.
+    pub label: String,
+    pub action: Action,
+}
+
+/// The outcome of checking a piece of content.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Verdict {
+    pub findings: Vec<Finding>,
+}
+
+impl Verdict {
+    /// The strongest action across the findings; `None` when clean.
+    pub fn action(&self) -> Option<Action> {
+        self.findings.iter().map(|finding| finding.action).max()
+    }
+
+    /// Labels of the findings that block.
+    pub fn blocking_labels(&self) -> Vec<String> {
+        self.findings
+            .iter()
+            .filter(|finding| finding.action == Action::Block)
+            .map(|finding| finding.label.clone())
+            .collect()
+    }
+}
+
+/// Content rejected by moderation, with the stored result admins review.
+#[derive(Debug, Clone, Error)]
+#[error("Content blocked by moderation ({content_source})")]
+pub struct ContentBlocked {
+    pub content_source: Source,
+    pub result_id: Uuid,
+    /// Labels of the blocking findings.
+    pub labels: Vec<String>,
+}
+
+/// Rules and provider for checking one source.
+pub struct Moderator {
+    rules: Ruleset,
+    provider: Option<Provider>,
+    provider_action: Action,
+}
+
+impl Moderator {
+    /// The moderator for `source`, or `None` when moderation or checks of
+    /// that source are off.
+    pub async fn configured(
+        pool: &PgPool,
+        cache: &Arc<RwLock<ConfigCache>>,
+        encryption_key: &str,
+        source: Source,
+    ) -> Result<Option<Self>, ModerationError> {
+        let value = async |key: &str| {
+            resolver::get_system_value(pool, cache, key)
+                .await
+                .map_err(|e| ModerationError::Config(e.to_string()))
+        };
+        if value(ENABLED_CONFIG_KEY).await? != "true" || value(source.config_key()).await? != "true"
+        {
+            return Ok(None);
+        }
+        let provider_action = match value("moderation.providerAction").await?.as_str() {
+            "block" => Action::Block,
+            _ => Action::Flag,
+        };
+        // A misconfigured provider is skipped like a failing one.
+        let provider = Provider::configured(pool, cache, encryption_key)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "moderation provider unavailable");
+                None
+            });
+        Ok(Some(Self {
+            rules: Ruleset::load(pool).await?,
+            provider,
+            provider_action,
+        }))
+    }
+
+    /// Check `text` against the rules and the provider. A failing provider
+    /// is logged and skipped so an outage does not stop chat or ingest; the
+    /// rules still apply.
+    pub async fn check(&self, text: &str) -> Verdict {
+        let mut findings = self.rules.check(text);
+        if let Some(provider) = &self.provider
+            && !text.trim().is_empty()
+        {
+            match provider.check(text).await {
+                Ok(categories) => findings.extend(categories.into_iter().map(|label| Finding {
+                    origin: Origin::Provider,
+                    label,
+                    action: self.provider_action,
+                })),
+                Err(e) => tracing::warn!(error = %e, "moderation provider check failed"),
+            }
+        }
+        Verdict { findings }
+    }
+
+    /// Mask the redact-rule matches in `parts`, checked as one text joined
+    /// by `separator`.
+    pub fn redact(&self, parts: &mut [&mut String], separator: &str) {
+        self.rules.redact(parts, separator);
+    }
+}
+
+/// Decrypt a secret system config value; empty when unset.
+async fn secret_value(
+    pool: &PgPool,
+    key: &str,
+    encryption_key: &str,
+) -> Result<String, ModerationError> {
+    let Some(row) = queries::get_value(pool, key, &ConfigScope::System, None)
+        .await
+        .map_err(|e| ModerationError::Config(e.to_string()))?
+    else {
+        return Ok(String::new());
+    };
+    if row.value.is_empty() {
+        return Ok(String::new());
+    }
+    secrets::decrypt(&row.value, encryption_key).map_err(|e| ModerationError::Config(e.to_string()))
+}
+
+// =============================================================================
+// Results
+// =============================================================================
+
+/// A check with findings, kept for admin review.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModerationResult {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub source: Source,
+    pub action: Action,
+    pub findings: Vec<Finding>,
+    /// Start of the checked content, before redaction.
+    pub excerpt: String,
+    /// The document, for document results.
+    pub resource_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub reviewed_by: Option<Uuid>,
+    pub review_note: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ResultRow {
+    id: Uuid,
+    user_id: Option<Uuid>,
+    source: String,
+    action: String,
+    findings: serde_json::Value,
+    excerpt: String,
+    resource_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    reviewed_at: Option<DateTime<Utc>>,
+    reviewed_by: Option<Uuid>,
+    review_note: Option<String>,
+}
+
+impl TryFrom<ResultRow> for ModerationResult {
+    type Error = ModerationError;
+
+    fn try_from(row: ResultRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            user_id: row.user_id,
+            source: row.source.parse()?,
+            action: row.action.parse()?,
+            findings: serde_json::from_value(row.findings).unwrap_or_default(),
+            excerpt: row.excerpt,
+            resource_id: row.resource_id,
+            created_at: row.created_at,
+            reviewed_at: row.reviewed_at,
+            reviewed_by: row.reviewed_by,
+            review_note: row.review_note,
+        })
+    }
+}
+
+const RESULT_COLUMNS: &str = "id, user_id, source, action, findings, excerpt, resource_id, \
+                              created_at, reviewed_at, reviewed_by, review_note";
+
+/// Store the result of a check with findings. Returns `None` for a clean
+/// verdict, which is not stored.
+pub async fn record(
+    pool: &PgPool,
+    user_id: Option<&Uuid>,
+    source: Source,
+    verdict: &Verdict,
+    content: &str,
+    resource_id: Option<&Uuid>,
+) -> Result<Option<Uuid>, ModerationError> {
+    let Some(action) = verdict.action() else {
+        return Ok(None);
+    };
+    let id = uuidv7();
+    sqlx::query(
+        r#"
+        INSERT INTO moderation_results
+            (id, user_id, source, action, findings, excerpt, resource_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(source.as_str())
+    .bind(action.as_str())
+    .bind(serde_json::to_value(&verdict.findings).unwrap_or_default())
+    .bind(excerpt(content))
+    .bind(resource_id)
+    .execute(pool)
+    .await?;
+    Ok(Some(id))
+}
+
+/// The first [`EXCERPT_CHARS`] characters of `content`.
+fn excerpt(content: &str) -> String {
+    content.chars().take(EXCERPT_CHARS).collect()
+}
+
+/// Results, newest first, optionally only those awaiting review, with the
+/// total count.
+pub async fn list_results(
+    pool: &PgPool,
+    pending_only: bool,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<ModerationResult>, i64), ModerationError> {
+    let total = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM moderation_results WHERE NOT $1 OR reviewed_at IS NULL",
+    )
+    .bind(pending_only)
+    .fetch_one(pool)
+    .await?;
+    let rows = sqlx::query_as::<_, ResultRow>(&format!(
+        r#"
+        SELECT {RESULT_COLUMNS}
+        FROM moderation_results
+        WHERE NOT $1 OR reviewed_at IS NULL
+        ORDER BY created_at DESC
+        LIMIT $2 OFFSET $3
+        "#
+    ))
+    .bind(pending_only)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+    let results = rows
+        .into_iter()
+        .map(ModerationResult::try_from)
+        .collect::<Result<_, _>>()?;
+    Ok((results, total))
+}
+
+/// Mark a result reviewed, with an optional note.
+pub async fn review_result(
+    pool: &PgPool,
+    id: &Uuid,
+    reviewed_by: &Uuid,
+    note: Option<&str>,
+) -> Result<ModerationResult, ModerationError> {
+    let note = note.map(str::trim).filter(|note| !note.is_empty());
+    if note.is_some_and(|note| note.chars().count() > MAX_NOTE_CHARS) {
+        return Err(ModerationError::Validation(
+            Message::new("moderation.note_too_long").arg("max", MAX_NOTE_CHARS),
+        ));
+    }
+    let row = sqlx::query_as::<_, ResultRow>(&format!(
+        r#"
+        UPDATE moderation_results
+        SET reviewed_at = now(), reviewed_by = $2, review_note = $3
+        WHERE id = $1
+        RETURNING {RESULT_COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .bind(reviewed_by)
+    .bind(note)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        ModerationError::NotFound(Message::new("moderation.result_not_found").arg("id", id))
+    })?;
+    row.try_into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(label: &str, action: Action) -> Finding {
+        Finding {
+            origin: Origin::Rule,
+            label: label.into(),
+            action,
+        }
+    }
+
+    #[test]
+    fn actions_round_trip_and_order() {
+        for action in [Action::Flag, Action::Redact, Action::Block] {
+            assert_eq!(action.as_str().parse::<Action>().unwrap(), action);
+        }
+        assert!("warn".parse::<Action>().is_err());
+        assert!(Action::Block > Action::Redact);
+        assert!(Action::Redact > Action::Flag);
+    }
+
+    #[test]
+    fn verdict_takes_the_strongest_action() {
+        assert_eq!(Verdict::default().action(), None);
+        let verdict = Verdict {
+            findings: vec![
+                finding("Profanity", Action::Redact),
+                finding("Threat", Action::Block),
+                finding("Spam", Action::Flag),
+            ],
+        };
+        assert_eq!(verdict.action(), Some(Action::Block));
+        assert_eq!(verdict.blocking_labels(), ["Threat"]);
+    }
+
+    #[test]
+    fn excerpts_are_bounded_by_characters() {
+        assert_eq!(excerpt("short"), "short");
+        assert_eq!(
+            excerpt(&"é".repeat(EXCERPT_CHARS + 10)).chars().count(),
+            EXCERPT_CHARS
+        );
+    }
+}
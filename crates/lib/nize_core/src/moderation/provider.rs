@@ -0,0 +1,156 @@
+//! External moderation services.
+//!
+//! - `"openai"` — the OpenAI `/moderations` endpoint. Every category the
+//!   service flags becomes a finding labelled with the category name.
+//!
+//! Selected by `moderation.provider`; `"none"` checks the rules only.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::Deserialize;
+use sqlx::PgPool;
+use tokio::sync::RwLock;
+
+use super::{ModerationError, secret_value};
+use crate::config::cache::ConfigCache;
+use crate::config::resolver;
+
+/// OpenAI moderation endpoint.
+const OPENAI_ENDPOINT: &str = "https://api.openai.com/v1/moderations";
+
+/// Longest a single provider call may take.
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A configured moderation provider.
+#[derive(Debug, Clone)]
+pub enum Provider {
+    OpenAi {
+        client: Client,
+        api_key: String,
+        model: String,
+    },
+}
+
+impl Provider {
+    /// The provider selected in config, or `None` for rules only.
+    pub async fn configured(
+        pool: &PgPool,
+        cache: &Arc<RwLock<ConfigCache>>,
+        encryption_key: &str,
+    ) -> Result<Option<Self>, ModerationError> {
+        let value = async |key: &str| {
+            resolver::get_system_value(pool, cache, key)
+                .await
+                .map_err(|e| ModerationError::Config(e.to_string()))
+        };
+        match value("moderation.provider").await?.as_str() {
+            "" | "none" => Ok(None),
+            "openai" => {
+                let api_key =
+                    secret_value(pool, "moderation.openai.apiKey", encryption_key).await?;
+                if api_key.is_empty() {
+                    return Err(ModerationError::Config(
+                        "OpenAI moderation API key is not set".into(),
+                    ));
+                }
+                Ok(Some(Provider::OpenAi {
+                    client: Client::builder()
+                        .timeout(PROVIDER_TIMEOUT)
+                        .build()
+                        .map_err(|e| ModerationError::Config(e.to_string()))?,
+                    api_key,
+                    model: value("moderation.openai.model").await?,
+                }))
+            }
+            other => Err(ModerationError::Config(format!(
+                "unknown moderation provider: {other}"
+            ))),
+        }
+    }
+
+    /// The categories the provider flags `text` under; empty when clean.
+    pub async fn check(&self, text: &str) -> Result<Vec<String>, ModerationError> {
+        match self {
+            Provider::OpenAi {
+                client,
+                api_key,
+                model,
+            } => {
+                let response = client
+                    .post(OPENAI_ENDPOINT)
+                    .bearer_auth(api_key)
+                    .json(&serde_json::json!({ "model": model, "input": text }))
+                    .send()
+                    .await
+                    .map_err(|e| ModerationError::Provider(e.to_string()))?;
+                let status = response.status();
+                if !status.is_success() {
+                    let body = response.text().await.unwrap_or_default();
+                    return Err(ModerationError::Provider(format!(
+                        "moderation service returned {status}: {body}"
+                    )));
+                }
+                let body: OpenAiResponse = response
+                    .json()
+                    .await
+                    .map_err(|e| ModerationError::Provider(e.to_string()))?;
+                Ok(flagged_categories(body))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiResponse {
+    results: Vec<OpenAiResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiResult {
+    flagged: bool,
+    #[serde(default)]
+    categories: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Flagged category names across all results, sorted and deduplicated.
+fn flagged_categories(body: OpenAiResponse) -> Vec<String> {
+    let mut categories: Vec<String> = body
+        .results
+        .into_iter()
+        .filter(|result| result.flagged)
+        .flat_map(|result| {
+            result
+                .categories
+                .into_iter()
+                .filter(|(_, flagged)| flagged.as_bool() == Some(true))
+                .map(|(category, _)| category)
+        })
+        .collect();
+    categories.sort();
+    categories.dedup();
+    categories
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_flagged_categories_are_reported() {
+        let body: OpenAiResponse = serde_json::from_value(serde_json::json!({
+            "id": "modr-1",
+            "model": "omni-moderation-latest",
+            "results": [
+                {
+                    "flagged": true,
+                    "categories": { "violence": true, "harassment": false, "hate": true }
+                },
+                { "flagged": false, "categories": { "sexual": true } }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(flagged_categories(body), ["hate", "violence"]);
+    }
+}
@@ -0,0 +1,445 @@
+//! Admin-managed keyword and regex rules, and matching content against
+//! them.
+
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use super::{Action, Finding, ModerationError, Origin};
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Maximum rule label length in characters.
+pub const MAX_LABEL_CHARS: usize = 100;
+
+/// Maximum rule pattern length in characters.
+pub const MAX_PATTERN_CHARS: usize = 1000;
+
+/// Upper bound on a compiled regex, so a rule cannot make every check slow.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+/// Marker that replaces redacted text.
+pub const REDACTED: &str = "[redacted]";
+
+/// How a rule's pattern is matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleKind {
+    /// The whole word or phrase, case-insensitively.
+    Keyword,
+    /// A Rust regex.
+    Regex,
+}
+
+impl RuleKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::Regex => "regex",
+        }
+    }
+}
+
+impl fmt::Display for RuleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RuleKind {
+    type Err = ModerationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keyword" => Ok(Self::Keyword),
+            "regex" => Ok(Self::Regex),
+            other => Err(ModerationError::Validation(
+                Message::new("moderation.unknown_rule_kind").arg("kind", other),
+            )),
+        }
+    }
+}
+
+/// A rule as written by an admin.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleInput {
+    pub label: String,
+    pub kind: RuleKind,
+    pub pattern: String,
+    pub action: Action,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl RuleInput {
+    fn validate(&self) -> Result<(), ModerationError> {
+        let label = self.label.trim();
+        if label.is_empty() {
+            return Err(ModerationError::Validation(Message::new(
+                "moderation.label_required",
+            )));
+        }
+        if label.chars().count() > MAX_LABEL_CHARS {
+            return Err(ModerationError::Validation(
+                Message::new("moderation.label_too_long").arg("max", MAX_LABEL_CHARS),
+            ));
+        }
+        if self.pattern.trim().is_empty() {
+            return Err(ModerationError::Validation(Message::new(
+                "moderation.pattern_required",
+            )));
+        }
+        if self.pattern.chars().count() > MAX_PATTERN_CHARS {
+            return Err(ModerationError::Validation(
+                Message::new("moderation.pattern_too_long").arg("max", MAX_PATTERN_CHARS),
+            ));
+        }
+        compile(self.kind, &self.pattern)?;
+        Ok(())
+    }
+}
+
+/// A stored rule.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    pub id: uuid::Uuid,
+    pub label: String,
+    pub kind: RuleKind,
+    pub pattern: String,
+    pub action: Action,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RuleRow {
+    id: uuid::Uuid,
+    label: String,
+    kind: String,
+    pattern: String,
+    action: String,
+    enabled: bool,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<RuleRow> for Rule {
+    type Error = ModerationError;
+
+    fn try_from(row: RuleRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            label: row.label,
+            kind: row.kind.parse()?,
+            pattern: row.pattern,
+            action: row.action.parse()?,
+            enabled: row.enabled,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+const COLUMNS: &str = "id, label, kind, pattern, action, enabled, created_at, updated_at";
+
+/// Every rule, by label.
+pub async fn list_rules(pool: &PgPool) -> Result<Vec<Rule>, ModerationError> {
+    let rows = sqlx::query_as::<_, RuleRow>(&format!(
+        "SELECT {COLUMNS} FROM moderation_rules ORDER BY lower(label), created_at"
+    ))
+    .fetch_all(pool)
+    .await?;
+    rows.into_iter().map(Rule::try_from).collect()
+}
+
+/// Create a rule.
+pub async fn create_rule(pool: &PgPool, input: &RuleInput) -> Result<Rule, ModerationError> {
+    input.validate()?;
+    let row = sqlx::query_as::<_, RuleRow>(&format!(
+        r#"
+        INSERT INTO moderation_rules (id, label, kind, pattern, action, enabled)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(input.label.trim())
+    .bind(input.kind.as_str())
+    .bind(&input.pattern)
+    .bind(input.action.as_str())
+    .bind(input.enabled)
+    .fetch_one(pool)
+    .await?;
+    row.try_into()
+}
+
+/// Replace a rule.
+pub async fn update_rule(
+    pool: &PgPool,
+    id: &uuid::Uuid,
+    input: &RuleInput,
+) -> Result<Rule, ModerationError> {
+    input.validate()?;
+    let row = sqlx::query_as::<_, RuleRow>(&format!(
+        r#"
+        UPDATE moderation_rules
+        SET label = $2, kind = $3, pattern = $4, action = $5, enabled = $6, updated_at = now()
+        WHERE id = $1
+        RETURNING {COLUMNS}
+        "#
+    ))
+    .bind(id)
+    .bind(input.label.trim())
+    .bind(input.kind.as_str())
+    .bind(&input.pattern)
+    .bind(input.action.as_str())
+    .bind(input.enabled)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        ModerationError::NotFound(Message::new("moderation.rule_not_found").arg("id", id))
+    })?;
+    row.try_into()
+}
+
+/// Delete a rule. Returns `false` if it does not exist.
+pub async fn delete_rule(pool: &PgPool, id: &uuid::Uuid) -> Result<bool, ModerationError> {
+    let result = sqlx::query("DELETE FROM moderation_rules WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Compile a pattern. Keywords match whole words: a word boundary is
+/// required on each side that starts or ends with a word character, so
+/// `c++` still matches.
+fn compile(kind: RuleKind, pattern: &str) -> Result<Regex, ModerationError> {
+    let source = match kind {
+        RuleKind::Regex => pattern.to_string(),
+        RuleKind::Keyword => {
+            let keyword = pattern.trim();
+            let boundary = |c: Option<char>| match c {
+                Some(c) if c.is_alphanumeric() || c == '_' => r"\b",
+                _ => "",
+            };
+            format!(
+                "{}{}{}",
+                boundary(keyword.chars().next()),
+                regex::escape(keyword),
+                boundary(keyword.chars().last()),
+            )
+        }
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(kind == RuleKind::Keyword)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| {
+            ModerationError::Validation(Message::new("moderation.invalid_pattern").arg("error", e))
+        })
+}
+
+struct CompiledRule {
+    label: String,
+    action: Action,
+    regex: Regex,
+}
+
+/// The enabled rules, compiled for matching.
+#[derive(Default)]
+pub struct Ruleset {
+    rules: Vec<CompiledRule>,
+}
+
+impl Ruleset {
+    /// Compile the enabled rules. Rules that no longer compile are skipped.
+    pub fn new(rules: &[Rule]) -> Self {
+        let rules = rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .filter_map(|rule| match compile(rule.kind, &rule.pattern) {
+                Ok(regex) => Some(CompiledRule {
+                    label: rule.label.clone(),
+                    action: rule.action,
+                    regex,
+                }),
+                Err(e) => {
+                    tracing::warn!(rule_id = %rule.id, error = %e, "skipping moderation rule");
+                    None
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Load and compile the enabled rules.
+    pub async fn load(pool: &PgPool) -> Result<Self, ModerationError> {
+        Ok(Self::new(&list_rules(pool).await?))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// One finding per rule that matches `text`.
+    pub fn check(&self, text: &str) -> Vec<Finding> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.regex.is_match(text))
+            .map(|rule| Finding {
+                origin: Origin::Rule,
+                label: rule.label.clone(),
+                action: rule.action,
+            })
+            .collect()
+    }
+
+    /// Byte ranges of `text` matched by redact rules, sorted and merged.
+    pub fn redaction_spans(&self, text: &str) -> Vec<Range<usize>> {
+        let mut spans: Vec<Range<usize>> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.action == Action::Redact)
+            .flat_map(|rule| rule.regex.find_iter(text).map(|m| m.range()))
+            .filter(|span| !span.is_empty())
+            .collect();
+        spans.sort_by_key(|span| span.start);
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(spans.len());
+        for span in spans {
+            match merged.last_mut() {
+                Some(last) if span.start <= last.end => last.end = last.end.max(span.end),
+                _ => merged.push(span),
+            }
+        }
+        merged
+    }
+
+    /// Redact the redact-rule matches in `parts`, which are checked as one
+    /// text joined by `separator`. A match spanning several parts leaves
+    /// the marker in the first and removes the rest of it from the others.
+    pub fn redact(&self, parts: &mut [&mut String], separator: &str) {
+        let joined = parts
+            .iter()
+            .map(|part| part.as_str())
+            .collect::<Vec<_>>()
+            .join(separator);
+        let spans = self.redaction_spans(&joined);
+        if spans.is_empty() {
+            return;
+        }
+        let mut offset = 0;
+        for part in parts.iter_mut() {
+            let range = offset..offset + part.len();
+            offset = range.end + separator.len();
+            let mut redacted = String::with_capacity(part.len());
+            let mut cursor = range.start;
+            for span in spans
+                .iter()
+                .filter(|span| span.start < range.end && span.end > range.start)
+            {
+                let start = span.start.max(range.start);
+                redacted.push_str(&joined[cursor..start]);
+                if span.start >= range.start {
+                    redacted.push_str(REDACTED);
+                }
+                cursor = span.end.min(range.end);
+            }
+            if cursor == range.start {
+                continue;
+            }
+            redacted.push_str(&joined[cursor..range.end]);
+            **part = redacted;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(label: &str, kind: RuleKind, pattern: &str, action: Action) -> Rule {
+        Rule {
+            id: uuidv7(),
+            label: label.into(),
+            kind,
+            pattern: pattern.into(),
+            action,
+            enabled: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn keywords_match_whole_words_case_insensitively() {
+        let rules = Ruleset::new(&[
+            rule("Secret", RuleKind::Keyword, "secret", Action::Flag),
+            rule("C++", RuleKind::Keyword, "c++", Action::Flag),
+        ]);
+        assert_eq!(rules.check("The SECRET plan").len(), 1);
+        assert!(rules.check("secretary").is_empty());
+        assert_eq!(rules.check("written in C++.")[0].label, "C++");
+    }
+
+    #[test]
+    fn disabled_and_invalid_rules_are_skipped() {
+        let mut disabled = rule("Off", RuleKind::Keyword, "off", Action::Block);
+        disabled.enabled = false;
+        let rules = Ruleset::new(&[
+            disabled,
+            rule("Broken", RuleKind::Regex, "(unclosed", Action::Block),
+        ]);
+        assert!(rules.is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_bad_patterns() {
+        let input = |pattern: &str| RuleInput {
+            label: "Card".into(),
+            kind: RuleKind::Regex,
+            pattern: pattern.into(),
+            action: Action::Redact,
+            enabled: true,
+        };
+        assert!(input(r"\b\d{4}-\d{4}\b").validate().is_ok());
+        assert!(input("(unclosed").validate().is_err());
+        assert!(input(" ").validate().is_err());
+    }
+
+    #[test]
+    fn redaction_merges_overlapping_matches() {
+        let rules = Ruleset::new(&[
+            rule("Digits", RuleKind::Regex, r"\d{4}", Action::Redact),
+            rule("Card", RuleKind::Regex, r"\d{4}-\d{4}", Action::Redact),
+            rule("Flag only", RuleKind::Keyword, "card", Action::Flag),
+        ]);
+        let mut text = "card 1234-5678 ok".to_string();
+        rules.redact(&mut [&mut text], "");
+        assert_eq!(text, "card [redacted] ok");
+    }
+
+    #[test]
+    fn redaction_spans_parts() {
+        let rules = Ruleset::new(&[rule("Word", RuleKind::Keyword, "badword", Action::Redact)]);
+        let (mut a, mut b, mut c) = ("a bad".to_string(), "word b".to_string(), "c".to_string());
+        rules.redact(&mut [&mut a, &mut b, &mut c], "");
+        assert_eq!(
+            (a.as_str(), b.as_str(), c.as_str()),
+            ("a [redacted]", " b", "c")
+        );
+    }
+}
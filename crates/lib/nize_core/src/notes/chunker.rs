@@ -0,0 +1,181 @@
+//! Markdown chunking for note embeddings.
+//!
+//! A note body is split into blocks — paragraphs, headings and fenced code
+//! blocks — which are then packed greedily into chunks of at most
+//! `max_chars` characters. A heading always starts a new chunk so each chunk
+//! stays within one section. Blocks longer than `max_chars` are split at the
+//! last whitespace before the limit.
+
+/// Default maximum chunk size in characters.
+pub const DEFAULT_MAX_CHUNK_CHARS: usize = 1500;
+
+/// Split a markdown document into embedding-sized chunks.
+pub fn chunk_markdown(body: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    for block in split_blocks(body) {
+        let is_heading = is_heading(&block);
+        let needed = if current.is_empty() {
+            char_len(&block)
+        } else {
+            char_len(&current) + 2 + char_len(&block)
+        };
+
+        if !current.is_empty() && (is_heading || needed > max_chars) {
+            chunks.push(std::mem::take(&mut current));
+        }
+
+        if char_len(&block) > max_chars {
+            let mut pieces = split_long(&block, max_chars);
+            let last = pieces.pop().unwrap_or_default();
+            chunks.extend(pieces);
+            current = last;
+            continue;
+        }
+
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(&block);
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Split markdown into blocks separated by blank lines or headings. Fenced
+/// code blocks are kept whole.
+fn split_blocks(body: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut in_fence = false;
+
+    let flush = |current: &mut Vec<&str>, blocks: &mut Vec<String>| {
+        let block = current.join("\n");
+        let block = block.trim();
+        if !block.is_empty() {
+            blocks.push(block.to_string());
+        }
+        current.clear();
+    };
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            if !in_fence {
+                flush(&mut current, &mut blocks);
+            }
+            current.push(line);
+            if in_fence {
+                flush(&mut current, &mut blocks);
+            }
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            current.push(line);
+            continue;
+        }
+        if trimmed.is_empty() {
+            flush(&mut current, &mut blocks);
+            continue;
+        }
+        if is_heading(trimmed) {
+            flush(&mut current, &mut blocks);
+            blocks.push(trimmed.trim_end().to_string());
+            continue;
+        }
+        current.push(line);
+    }
+    flush(&mut current, &mut blocks);
+
+    blocks
+}
+
+/// Whether a block is an ATX heading (`# Title` … `###### Title`).
+fn is_heading(block: &str) -> bool {
+    let hashes = block.chars().take_while(|c| *c == '#').count();
+    (1..=6).contains(&hashes) && block[hashes..].starts_with(' ')
+}
+
+/// Split a block into pieces of at most `max_chars`, preferring whitespace.
+fn split_long(block: &str, max_chars: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest = block;
+
+    while char_len(rest) > max_chars {
+        let limit = rest
+            .char_indices()
+            .nth(max_chars)
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        let cut = rest[..limit]
+            .rfind(char::is_whitespace)
+            .filter(|&i| i > 0)
+            .unwrap_or(limit);
+        pieces.push(rest[..cut].trim_end().to_string());
+        rest = rest[cut..].trim_start();
+    }
+    if !rest.is_empty() {
+        pieces.push(rest.to_string());
+    }
+    pieces
+}
+
+fn char_len(s: &str) -> usize {
+    s.chars().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_body_has_no_chunks() {
+        assert!(chunk_markdown("", 100).is_empty());
+        assert!(chunk_markdown("\n\n  \n", 100).is_empty());
+    }
+
+    #[test]
+    fn short_paragraphs_are_packed_together() {
+        let chunks = chunk_markdown("one\n\ntwo\n\nthree", 100);
+        assert_eq!(chunks, vec!["one\n\ntwo\n\nthree"]);
+    }
+
+    #[test]
+    fn headings_start_new_chunks() {
+        let chunks = chunk_markdown("intro\n# A\nalpha\n\n## B\nbeta", 100);
+        assert_eq!(chunks, vec!["intro", "# A\n\nalpha", "## B\n\nbeta"]);
+    }
+
+    #[test]
+    fn paragraphs_overflowing_the_limit_go_to_the_next_chunk() {
+        let chunks = chunk_markdown("aaaa\n\nbbbb\n\ncccc", 10);
+        assert_eq!(chunks, vec!["aaaa\n\nbbbb", "cccc"]);
+    }
+
+    #[test]
+    fn long_blocks_split_at_whitespace() {
+        let chunks = chunk_markdown("lorem ipsum dolor sit amet", 12);
+        assert_eq!(chunks, vec!["lorem ipsum", "dolor sit", "amet"]);
+        assert!(chunks.iter().all(|c| c.chars().count() <= 12));
+    }
+
+    #[test]
+    fn fenced_code_is_kept_whole() {
+        let body = "text\n\n```rust\nfn a() {}\n\nfn b() {}\n```\n\nafter";
+        let chunks = chunk_markdown(body, 1000);
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].contains("fn a() {}\n\nfn b() {}"));
+    }
+
+    #[test]
+    fn hashtags_are_not_headings() {
+        assert!(!is_heading("#todo later"));
+        assert!(is_heading("### Section"));
+    }
+}
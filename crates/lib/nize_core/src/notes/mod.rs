@@ -0,0 +1,308 @@
+//! Notes — user-authored markdown captures with tags.
+//!
+//! Notes live apart from ingested documents. Whenever a note is created or
+//! its content changes, [`crate::embedding::indexer::embed_note`] re-chunks
+//! the body (see [`chunker`]) into the shared [`crate::chunks`] store and
+//! embeds the chunks not embedded before, so that [`search::search_notes`]
+//! can retrieve relevant passages as chat context.
+
+pub mod chunker;
+pub mod search;
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::chunks;
+use crate::tags::{self, TagResourceType};
+use crate::uuid::uuidv7;
+
+/// Note columns plus the note's tag names (see [`crate::tags`]), for a
+/// query over `notes n`.
+const NOTE_COLUMNS: &str = r#"
+    n.id, n.user_id, n.title, n.body,
+    ARRAY(
+        SELECT t.name::text
+        FROM tag_assignments a
+        JOIN tags t ON t.id = a.tag_id
+        WHERE a.resource_type = 'note' AND a.resource_id = n.id
+        ORDER BY t.name
+    ) AS tags,
+    n.created_at, n.updated_at
+"#;
+
+/// SQL condition restricting `notes n` to notes tagged with the name bound
+/// at parameter `$2` (or all notes when it is NULL).
+const TAG_FILTER: &str = r#"
+    ($2::text IS NULL OR EXISTS (
+        SELECT 1
+        FROM tag_assignments a
+        JOIN tags t ON t.id = a.tag_id
+        WHERE a.resource_type = 'note' AND a.resource_id = n.id AND t.name = $2
+    ))
+"#;
+
+/// Row returned by note queries. `tags` holds the names of the note's
+/// attached tags.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct NoteRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub title: String,
+    pub body: String,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Row returned by note chunk queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct NoteChunkRow {
+    pub id: Uuid,
+    pub note_id: Uuid,
+    pub chunk_index: i32,
+    /// Key of the chunk's content in [`crate::chunks`].
+    pub chunk_hash: String,
+    pub content: String,
+}
+
+/// List notes for a user, most recently updated first, optionally filtered
+/// to notes carrying `tag`.
+pub async fn list_notes(
+    pool: &PgPool,
+    user_id: &Uuid,
+    tag: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<NoteRow>, i64), sqlx::Error> {
+    let total = sqlx::query_scalar::<_, i64>(&format!(
+        "SELECT COUNT(*) FROM notes n WHERE n.user_id = $1 AND {TAG_FILTER}"
+    ))
+    .bind(user_id)
+    .bind(tag)
+    .fetch_one(pool)
+    .await?;
+
+    let rows = sqlx::query_as::<_, NoteRow>(&format!(
+        r#"
+        SELECT {NOTE_COLUMNS}
+        FROM notes n
+        WHERE n.user_id = $1 AND {TAG_FILTER}
+        ORDER BY n.updated_at DESC
+        LIMIT $3 OFFSET $4
+        "#
+    ))
+    .bind(user_id)
+    .bind(tag)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((rows, total))
+}
+
+/// Create a new note with the given (normalized) tag names.
+pub async fn create_note(
+    pool: &PgPool,
+    user_id: &Uuid,
+    title: &str,
+    body: &str,
+    tags: &[String],
+) -> Result<NoteRow, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let note_id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO notes (id, user_id, title, body)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id
+        "#,
+    )
+    .bind(uuidv7())
+    .bind(user_id)
+    .bind(title)
+    .bind(body)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tags::set_resource_tags(&mut tx, user_id, TagResourceType::Note, &note_id, tags).await?;
+    let row = fetch_note(&mut tx, &note_id).await?;
+
+    tx.commit().await?;
+    Ok(row)
+}
+
+async fn fetch_note(conn: &mut PgConnection, note_id: &Uuid) -> Result<NoteRow, sqlx::Error> {
+    sqlx::query_as::<_, NoteRow>(&format!(
+        "SELECT {NOTE_COLUMNS} FROM notes n WHERE n.id = $1"
+    ))
+    .bind(note_id)
+    .fetch_one(conn)
+    .await
+}
+
+/// Get a note by ID (scoped to user).
+pub async fn get_note(
+    pool: &PgPool,
+    user_id: &Uuid,
+    note_id: &Uuid,
+) -> Result<NoteRow, sqlx::Error> {
+    sqlx::query_as::<_, NoteRow>(&format!(
+        "SELECT {NOTE_COLUMNS} FROM notes n WHERE n.id = $1 AND n.user_id = $2"
+    ))
+    .bind(note_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Get a note by ID regardless of owner (for background indexing).
+pub async fn find_note(pool: &PgPool, note_id: &Uuid) -> Result<Option<NoteRow>, sqlx::Error> {
+    sqlx::query_as::<_, NoteRow>(&format!(
+        "SELECT {NOTE_COLUMNS} FROM notes n WHERE n.id = $1"
+    ))
+    .bind(note_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Update a note. `None` fields are left unchanged; `tags` (normalized)
+/// replaces the note's tag set.
+pub async fn update_note(
+    pool: &PgPool,
+    user_id: &Uuid,
+    note_id: &Uuid,
+    title: Option<&str>,
+    body: Option<&str>,
+    tags: Option<&[String]>,
+) -> Result<NoteRow, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query_scalar::<_, Uuid>(
+        r#"
+        UPDATE notes
+        SET title = COALESCE($1, title),
+            body = COALESCE($2, body),
+            updated_at = now()
+        WHERE id = $3 AND user_id = $4
+        RETURNING id
+        "#,
+    )
+    .bind(title)
+    .bind(body)
+    .bind(note_id)
+    .bind(user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if let Some(tags) = tags {
+        tags::set_resource_tags(&mut tx, user_id, TagResourceType::Note, note_id, tags).await?;
+    }
+    let row = fetch_note(&mut tx, note_id).await?;
+
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Append markdown to a note's body (scoped to user), separated from the
+/// existing text by a blank line.
+pub async fn append_to_note(
+    pool: &PgPool,
+    user_id: &Uuid,
+    note_id: &Uuid,
+    text: &str,
+) -> Result<NoteRow, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query_scalar::<_, Uuid>(
+        r#"
+        UPDATE notes
+        SET body = CASE
+                WHEN btrim(body) = '' THEN $1
+                ELSE rtrim(body, E'\n') || E'\n\n' || $1
+            END,
+            updated_at = now()
+        WHERE id = $2 AND user_id = $3
+        RETURNING id
+        "#,
+    )
+    .bind(text)
+    .bind(note_id)
+    .bind(user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let row = fetch_note(&mut tx, note_id).await?;
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Delete a note (chunks and embeddings cascade; tag assignments are
+/// removed explicitly).
+pub async fn delete_note(
+    pool: &PgPool,
+    user_id: &Uuid,
+    note_id: &Uuid,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query("DELETE FROM notes WHERE id = $1 AND user_id = $2")
+        .bind(note_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+    if result.rows_affected() > 0 {
+        tags::clear_resource_tags(&mut tx, TagResourceType::Note, note_id).await?;
+    }
+
+    tx.commit().await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Replace all chunks of a note. Unchanged chunks keep their embeddings in
+/// the shared [`crate::chunks`] store; contents no longer used anywhere are
+/// released.
+pub async fn replace_note_chunks(
+    pool: &PgPool,
+    note_id: &Uuid,
+    contents: &[String],
+) -> Result<Vec<NoteChunkRow>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let refs: Vec<&str> = contents.iter().map(String::as_str).collect();
+    let hashes = chunks::put_chunks(&mut tx, &refs).await?;
+
+    sqlx::query("DELETE FROM note_chunks WHERE note_id = $1")
+        .bind(note_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let mut rows = Vec::with_capacity(contents.len());
+    for (i, (content, hash)) in contents.iter().zip(hashes).enumerate() {
+        let row = NoteChunkRow {
+            id: uuidv7(),
+            note_id: *note_id,
+            chunk_index: i as i32,
+            chunk_hash: hash,
+            content: content.clone(),
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO note_chunks (id, note_id, chunk_index, chunk_hash)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(row.id)
+        .bind(row.note_id)
+        .bind(row.chunk_index)
+        .bind(&row.chunk_hash)
+        .execute(&mut *tx)
+        .await?;
+        rows.push(row);
+    }
+
+    chunks::release_unreferenced(&mut tx).await?;
+    tx.commit().await?;
+    Ok(rows)
+}
@@ -0,0 +1,156 @@
+//! Semantic search over note chunks.
+//!
+//! Embeds the query with the active model and ranks the caller's note chunks
+//! by cosine similarity. [`format_context`] renders hits as a markdown block
+//! suitable for injecting into a chat prompt.
+
+use std::sync::Arc;
+
+use sqlx::PgPool;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::config::cache::ConfigCache;
+use crate::embedding::EmbeddingError;
+use crate::embedding::config::EmbeddingConfig;
+use crate::embedding::{self, ann, models};
+
+/// Default number of chunks returned by [`search_notes`].
+pub const DEFAULT_TOP_K: i64 = 5;
+
+/// A note chunk matching a search query.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct NoteSearchHit {
+    pub note_id: Uuid,
+    pub chunk_id: Uuid,
+    pub title: String,
+    pub tags: Vec<String>,
+    pub content: String,
+    pub similarity: f64,
+}
+
+/// Find the `top_k` note chunks owned by `user_id` most similar to `query`,
+/// optionally restricted to notes carrying `tag`.
+#[allow(clippy::too_many_arguments)]
+pub async fn search_notes(
+    pool: &PgPool,
+    config_cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+    user_id: &Uuid,
+    query: &str,
+    tag: Option<&str>,
+    top_k: i64,
+    min_similarity: f64,
+) -> Result<Vec<NoteSearchHit>, EmbeddingError> {
+    let config = EmbeddingConfig::resolve(pool, config_cache, encryption_key).await?;
+    let model_config = models::get_active_model(pool, &config).await?;
+    let embedding = embedding::embed_query(&config, &model_config, query).await?;
+
+    // Format vector as SQL literal: '[0.1,0.2,...]'
+    let embedding_sql = format!(
+        "[{}]",
+        embedding
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+
+    let sql = format!(
+        r#"SELECT n.id AS note_id,
+                  c.id AS chunk_id,
+                  n.title,
+                  ARRAY(
+                    SELECT t.name::text
+                    FROM tag_assignments a
+                    JOIN tags t ON t.id = a.tag_id
+                    WHERE a.resource_type = 'note' AND a.resource_id = n.id
+                    ORDER BY t.name
+                  ) AS tags,
+                  k.content,
+                  1 - ({distance}) AS similarity
+           FROM chunk_embeddings ne
+           JOIN note_chunks c ON c.chunk_hash = ne.chunk_hash
+           JOIN chunks k ON k.hash = ne.chunk_hash
+           JOIN notes n ON n.id = c.note_id
+           WHERE {model_filter}
+             AND n.user_id = $2
+             AND 1 - ({distance}) >= $4
+             AND ($5::text IS NULL OR EXISTS (
+               SELECT 1
+               FROM tag_assignments a
+               JOIN tags t ON t.id = a.tag_id
+               WHERE a.resource_type = 'note' AND a.resource_id = n.id AND t.name = $5
+             ))
+           ORDER BY {distance}
+           LIMIT $3"#,
+        distance = model_config.distance_sql("ne.embedding", "$1"),
+        model_filter = model_config.filter_sql("ne"),
+    );
+
+    let search_params = ann::SearchParams::resolve(pool, config_cache).await;
+    let mut tx = pool.begin().await?;
+    search_params.apply(&mut tx).await?;
+    let hits = sqlx::query_as::<_, NoteSearchHit>(&sql)
+        .bind(&embedding_sql)
+        .bind(user_id)
+        .bind(top_k)
+        .bind(min_similarity)
+        .bind(tag)
+        .fetch_all(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(hits)
+}
+
+/// Render search hits as a markdown context block, one section per chunk.
+pub fn format_context(hits: &[NoteSearchHit]) -> String {
+    hits.iter()
+        .map(|hit| {
+            let title = if hit.title.is_empty() {
+                "Untitled note"
+            } else {
+                hit.title.as_str()
+            };
+            let mut section = format!("### {title}\n");
+            if !hit.tags.is_empty() {
+                section.push_str(&format!("Tags: {}\n", hit.tags.join(", ")));
+            }
+            section.push('\n');
+            section.push_str(&hit.content);
+            section
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n---\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(title: &str, tags: &[&str], content: &str) -> NoteSearchHit {
+        NoteSearchHit {
+            note_id: Uuid::new_v4(),
+            chunk_id: Uuid::new_v4(),
+            title: title.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            content: content.to_string(),
+            similarity: 0.9,
+        }
+    }
+
+    #[test]
+    fn format_context_renders_sections() {
+        let context = format_context(&[hit("Groceries", &["home"], "milk"), hit("", &[], "idea")]);
+        assert_eq!(
+            context,
+            "### Groceries\nTags: home\n\nmilk\n\n---\n\n### Untitled note\n\nidea"
+        );
+    }
+
+    #[test]
+    fn format_context_empty() {
+        assert_eq!(format_context(&[]), "");
+    }
+}
@@ -0,0 +1,108 @@
+//! User notifications — events surfaced to the user outside a chat, such as
+//! a scheduled task finishing.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::uuid::uuidv7;
+
+/// Notification kind emitted when a scheduled task run succeeds.
+pub const KIND_TASK_COMPLETED: &str = "task.completed";
+/// Notification kind emitted when a scheduled task run fails.
+pub const KIND_TASK_FAILED: &str = "task.failed";
+
+/// Row returned by notification queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct NotificationRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: String,
+    pub title: String,
+    pub body: String,
+    pub payload: serde_json::Value,
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Create a notification for a user.
+pub async fn create_notification(
+    pool: &PgPool,
+    user_id: &Uuid,
+    kind: &str,
+    title: &str,
+    body: &str,
+    payload: &serde_json::Value,
+) -> Result<NotificationRow, sqlx::Error> {
+    sqlx::query_as::<_, NotificationRow>(
+        r#"
+        INSERT INTO notifications (id, user_id, kind, title, body, payload)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, user_id, kind, title, body, payload, read_at, created_at
+        "#,
+    )
+    .bind(uuidv7())
+    .bind(user_id)
+    .bind(kind)
+    .bind(title)
+    .bind(body)
+    .bind(payload)
+    .fetch_one(pool)
+    .await
+}
+
+/// List a user's notifications, newest first. Returns the page and the
+/// number of unread notifications.
+pub async fn list_notifications(
+    pool: &PgPool,
+    user_id: &Uuid,
+    unread_only: bool,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<NotificationRow>, i64), sqlx::Error> {
+    let unread = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND read_at IS NULL",
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    let rows = sqlx::query_as::<_, NotificationRow>(
+        r#"
+        SELECT id, user_id, kind, title, body, payload, read_at, created_at
+        FROM notifications
+        WHERE user_id = $1 AND (NOT $2 OR read_at IS NULL)
+        ORDER BY created_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(user_id)
+    .bind(unread_only)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((rows, unread))
+}
+
+/// Mark a notification as read (scoped to user). Returns `false` if it does
+/// not exist.
+pub async fn mark_read(
+    pool: &PgPool,
+    user_id: &Uuid,
+    notification_id: &Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE notifications
+        SET read_at = COALESCE(read_at, now())
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(notification_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,614 @@
+//! Grants and share links on conversations and documents.
+//!
+//! A resource's owner shares it with a person by email (a grant) or with
+//! anyone holding a link's token. Both carry a [`PermissionLevel`]; the
+//! levels are ordered, each allowing everything the ones before it do, so a
+//! handler asks for the least level its operation needs (see
+//! [`PermissionLevel::allows`]). A link's level and expiry can change
+//! without a new token, so a link already handed out can be upgraded or
+//! narrowed in place.
+//!
+//! Only the owner — or someone granted [`PermissionLevel::Full`] — shares a
+//! resource further.
+//!
+//! A grant reaches an account by its email only once that address is
+//! verified, so registering an address nobody confirmed does not pick up
+//! what was shared with it.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use rand::distr::Alphanumeric;
+use rand::{Rng, rng};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Errors that can occur in grant and link operations.
+#[derive(Debug, Error)]
+pub enum PermissionError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Not found: {0}")]
+    NotFound(Message),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(Message),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// What a grant or link allows, from least to most.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    sqlx::Type,
+)]
+#[sqlx(type_name = "permission_level", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionLevel {
+    /// Read the resource.
+    View,
+    /// Also leave feedback on its messages.
+    Comment,
+    /// Also continue it — run the model and tools in it.
+    Execute,
+    /// Also change it.
+    Edit,
+    /// Also share it further.
+    Full,
+}
+
+impl PermissionLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::View => "view",
+            Self::Comment => "comment",
+            Self::Execute => "execute",
+            Self::Edit => "edit",
+            Self::Full => "full",
+        }
+    }
+
+    /// Whether this level allows an operation that needs `needed`.
+    pub fn allows(self, needed: PermissionLevel) -> bool {
+        self >= needed
+    }
+}
+
+/// Kind of resource that can be shared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, sqlx::Type)]
+#[sqlx(type_name = "permission_resource_type", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ResourceType {
+    Conversation,
+    Document,
+}
+
+impl FromStr for ResourceType {
+    type Err = PermissionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "conversation" => Ok(Self::Conversation),
+            "document" => Ok(Self::Document),
+            other => Err(PermissionError::Validation(
+                Message::new("permissions.unknown_resource_type").arg("type", other),
+            )),
+        }
+    }
+}
+
+/// A grant of access to one person.
+#[derive(Debug, Clone, Serialize, JsonSchema, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct GrantRow {
+    pub id: Uuid,
+    pub granter_id: Uuid,
+    pub grantee_id: Option<Uuid>,
+    pub grantee_email: String,
+    pub resource_type: ResourceType,
+    pub resource_id: Uuid,
+    pub level: PermissionLevel,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A share link; anyone holding its token gets its level.
+#[derive(Debug, Clone, Serialize, JsonSchema, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareLinkRow {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub resource_type: ResourceType,
+    pub resource_id: Uuid,
+    pub token: String,
+    pub level: PermissionLevel,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ShareLinkRow {
+    /// Whether the link no longer grants access at `now`.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+const GRANT_COLUMNS: &str = "id, granter_id, grantee_id, grantee_email, resource_type, \
+     resource_id, level, created_at";
+
+const LINK_COLUMNS: &str =
+    "id, owner_id, resource_type, resource_id, token, level, expires_at, created_at";
+
+/// Generate a link token (48 alphanumeric chars).
+fn generate_token() -> String {
+    rng()
+        .sample_iter(&Alphanumeric)
+        .take(48)
+        .map(char::from)
+        .collect()
+}
+
+// =============================================================================
+// Access checks
+// =============================================================================
+
+/// Owner of a shared resource, or `NotFound`.
+async fn resource_owner(
+    pool: &PgPool,
+    resource_type: ResourceType,
+    resource_id: &Uuid,
+) -> Result<Uuid, PermissionError> {
+    let sql = match resource_type {
+        ResourceType::Conversation => "SELECT user_id FROM conversations WHERE id = $1",
+        ResourceType::Document => "SELECT user_id FROM documents WHERE id = $1",
+    };
+    sqlx::query_scalar(sql)
+        .bind(resource_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| PermissionError::NotFound(Message::new("permissions.resource_not_found")))
+}
+
+/// The level `user_id` holds on a resource through grants: `Full` for its
+/// owner, the granted level otherwise, `None` without access.
+pub async fn granted_level(
+    pool: &PgPool,
+    user_id: &Uuid,
+    resource_type: ResourceType,
+    resource_id: &Uuid,
+) -> Result<Option<PermissionLevel>, PermissionError> {
+    if resource_owner(pool, resource_type, resource_id).await? == *user_id {
+        return Ok(Some(PermissionLevel::Full));
+    }
+    let level = sqlx::query_scalar(
+        r#"
+        SELECT g.level
+        FROM permission_grants g
+        JOIN users u ON u.id = $1
+        WHERE g.resource_type = $2 AND g.resource_id = $3
+          AND (g.grantee_id = $1
+               OR (lower(g.grantee_email) = lower(u.email) AND u.email_verified IS NOT NULL))
+        ORDER BY g.level DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(resource_type)
+    .bind(resource_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(level)
+}
+
+/// Check `user_id` holds at least `needed` on a resource. `NotFound`
+/// without any access, so unshared resources are not disclosed.
+pub async fn require_level(
+    pool: &PgPool,
+    user_id: &Uuid,
+    resource_type: ResourceType,
+    resource_id: &Uuid,
+    needed: PermissionLevel,
+) -> Result<PermissionLevel, PermissionError> {
+    match granted_level(pool, user_id, resource_type, resource_id).await? {
+        Some(level) if level.allows(needed) => Ok(level),
+        Some(level) => Err(PermissionError::Forbidden(
+            Message::new("permissions.grant_insufficient")
+                .arg("level", level.as_str())
+                .arg("needed", needed.as_str()),
+        )),
+        None => Err(PermissionError::NotFound(Message::new(
+            "permissions.resource_not_found",
+        ))),
+    }
+}
+
+/// The valid link with `token`, or `NotFound` for unknown and expired ones.
+pub async fn resolve_link(pool: &PgPool, token: &str) -> Result<ShareLinkRow, PermissionError> {
+    let link = sqlx::query_as::<_, ShareLinkRow>(&format!(
+        "SELECT {LINK_COLUMNS} FROM share_links WHERE token = $1"
+    ))
+    .bind(token)
+    .fetch_optional(pool)
+    .await?
+    .filter(|link| !link.is_expired(Utc::now()))
+    .ok_or_else(|| {
+        PermissionError::NotFound(Message::new("permissions.link_not_found_or_expired"))
+    })?;
+    Ok(link)
+}
+
+/// Resolve `token` and check its link allows `needed`.
+pub async fn require_link_level(
+    pool: &PgPool,
+    token: &str,
+    needed: PermissionLevel,
+) -> Result<ShareLinkRow, PermissionError> {
+    let link = resolve_link(pool, token).await?;
+    if !link.level.allows(needed) {
+        return Err(PermissionError::Forbidden(
+            Message::new("permissions.link_insufficient")
+                .arg("level", link.level.as_str())
+                .arg("needed", needed.as_str()),
+        ));
+    }
+    Ok(link)
+}
+
+// =============================================================================
+// Grants
+// =============================================================================
+
+/// Grant `email` access to a resource, or change the level of its existing
+/// grant. Needs `Full` access.
+pub async fn create_grant(
+    pool: &PgPool,
+    granter_id: &Uuid,
+    resource_type: ResourceType,
+    resource_id: &Uuid,
+    email: &str,
+    level: PermissionLevel,
+) -> Result<GrantRow, PermissionError> {
+    let email = email.trim();
+    if !email.contains('@') {
+        return Err(PermissionError::Validation(
+            Message::new("permissions.invalid_email").arg("email", email),
+        ));
+    }
+    require_level(
+        pool,
+        granter_id,
+        resource_type,
+        resource_id,
+        PermissionLevel::Full,
+    )
+    .await?;
+
+    let row = sqlx::query_as::<_, GrantRow>(&format!(
+        r#"
+        INSERT INTO permission_grants
+            (id, granter_id, grantee_id, grantee_email, resource_type, resource_id, level)
+        VALUES ($1, $2,
+                (SELECT id FROM users
+                 WHERE lower(email) = lower($3) AND email_verified IS NOT NULL),
+                $3, $4, $5, $6, $7)
+        ON CONFLICT (resource_type, resource_id, lower(grantee_email)) DO UPDATE
+            SET level = EXCLUDED.level, updated_at = now()
+        RETURNING {GRANT_COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(granter_id)
+    .bind(email)
+    .bind(resource_type)
+    .bind(resource_id)
+    .bind(level)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Grants on a resource. Needs `Full` access.
+pub async fn list_grants(
+    pool: &PgPool,
+    user_id: &Uuid,
+    resource_type: ResourceType,
+    resource_id: &Uuid,
+) -> Result<Vec<GrantRow>, PermissionError> {
+    require_level(
+        pool,
+        user_id,
+        resource_type,
+        resource_id,
+        PermissionLevel::Full,
+    )
+    .await?;
+    let rows = sqlx::query_as::<_, GrantRow>(&format!(
+        "SELECT {GRANT_COLUMNS} FROM permission_grants \
+         WHERE resource_type = $1 AND resource_id = $2 ORDER BY created_at"
+    ))
+    .bind(resource_type)
+    .bind(resource_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// All grants (admin).
+pub async fn list_all_grants(pool: &PgPool) -> Result<Vec<GrantRow>, PermissionError> {
+    let rows = sqlx::query_as::<_, GrantRow>(&format!(
+        "SELECT {GRANT_COLUMNS} FROM permission_grants ORDER BY created_at DESC"
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Revoke a grant. Needs `Full` access to its resource, unless `user_id`
+/// is `None` (admin).
+pub async fn revoke_grant(
+    pool: &PgPool,
+    user_id: Option<&Uuid>,
+    grant_id: &Uuid,
+) -> Result<(), PermissionError> {
+    let grant = sqlx::query_as::<_, GrantRow>(&format!(
+        "SELECT {GRANT_COLUMNS} FROM permission_grants WHERE id = $1"
+    ))
+    .bind(grant_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| PermissionError::NotFound(Message::new("permissions.grant_not_found")))?;
+    if let Some(user_id) = user_id {
+        require_level(
+            pool,
+            user_id,
+            grant.resource_type,
+            &grant.resource_id,
+            PermissionLevel::Full,
+        )
+        .await?;
+    }
+    sqlx::query("DELETE FROM permission_grants WHERE id = $1")
+        .bind(grant_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// =============================================================================
+// Share links
+// =============================================================================
+
+/// Create a share link to a resource. Needs `Full` access.
+pub async fn create_link(
+    pool: &PgPool,
+    owner_id: &Uuid,
+    resource_type: ResourceType,
+    resource_id: &Uuid,
+    level: PermissionLevel,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<ShareLinkRow, PermissionError> {
+    if expires_at.is_some_and(|at| at <= Utc::now()) {
+        return Err(PermissionError::Validation(Message::new(
+            "permissions.expiry_in_past",
+        )));
+    }
+    require_level(
+        pool,
+        owner_id,
+        resource_type,
+        resource_id,
+        PermissionLevel::Full,
+    )
+    .await?;
+
+    let row = sqlx::query_as::<_, ShareLinkRow>(&format!(
+        r#"
+        INSERT INTO share_links
+            (id, owner_id, resource_type, resource_id, token, level, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING {LINK_COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(owner_id)
+    .bind(resource_type)
+    .bind(resource_id)
+    .bind(generate_token())
+    .bind(level)
+    .bind(expires_at)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Share links to a resource. Needs `Full` access.
+pub async fn list_links(
+    pool: &PgPool,
+    user_id: &Uuid,
+    resource_type: ResourceType,
+    resource_id: &Uuid,
+) -> Result<Vec<ShareLinkRow>, PermissionError> {
+    require_level(
+        pool,
+        user_id,
+        resource_type,
+        resource_id,
+        PermissionLevel::Full,
+    )
+    .await?;
+    let rows = sqlx::query_as::<_, ShareLinkRow>(&format!(
+        "SELECT {LINK_COLUMNS} FROM share_links \
+         WHERE resource_type = $1 AND resource_id = $2 ORDER BY created_at"
+    ))
+    .bind(resource_type)
+    .bind(resource_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// All share links (admin).
+pub async fn list_all_links(pool: &PgPool) -> Result<Vec<ShareLinkRow>, PermissionError> {
+    let rows = sqlx::query_as::<_, ShareLinkRow>(&format!(
+        "SELECT {LINK_COLUMNS} FROM share_links ORDER BY created_at DESC"
+    ))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// A link and a check that `user_id` has `Full` access to its resource
+/// (skipped for admins, `None`).
+async fn manageable_link(
+    pool: &PgPool,
+    user_id: Option<&Uuid>,
+    link_id: &Uuid,
+) -> Result<ShareLinkRow, PermissionError> {
+    let link = sqlx::query_as::<_, ShareLinkRow>(&format!(
+        "SELECT {LINK_COLUMNS} FROM share_links WHERE id = $1"
+    ))
+    .bind(link_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| PermissionError::NotFound(Message::new("permissions.link_not_found")))?;
+    if let Some(user_id) = user_id {
+        require_level(
+            pool,
+            user_id,
+            link.resource_type,
+            &link.resource_id,
+            PermissionLevel::Full,
+        )
+        .await?;
+    }
+    Ok(link)
+}
+
+/// Change a link's level and expiry, keeping its token. `expires_at` of
+/// `Some(None)` removes the expiry. Needs `Full` access.
+pub async fn update_link(
+    pool: &PgPool,
+    user_id: &Uuid,
+    link_id: &Uuid,
+    level: Option<PermissionLevel>,
+    expires_at: Option<Option<DateTime<Utc>>>,
+) -> Result<ShareLinkRow, PermissionError> {
+    if let Some(Some(at)) = expires_at
+        && at <= Utc::now()
+    {
+        return Err(PermissionError::Validation(Message::new(
+            "permissions.expiry_in_past",
+        )));
+    }
+    let link = manageable_link(pool, Some(user_id), link_id).await?;
+    let row = sqlx::query_as::<_, ShareLinkRow>(&format!(
+        r#"
+        UPDATE share_links SET level = $2, expires_at = $3, updated_at = now()
+        WHERE id = $1
+        RETURNING {LINK_COLUMNS}
+        "#
+    ))
+    .bind(link_id)
+    .bind(level.unwrap_or(link.level))
+    .bind(expires_at.unwrap_or(link.expires_at))
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Revoke a share link. Needs `Full` access to its resource, unless
+/// `user_id` is `None` (admin).
+pub async fn revoke_link(
+    pool: &PgPool,
+    user_id: Option<&Uuid>,
+    link_id: &Uuid,
+) -> Result<(), PermissionError> {
+    manageable_link(pool, user_id, link_id).await?;
+    sqlx::query("DELETE FROM share_links WHERE id = $1")
+        .bind(link_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Remove every grant and share link of a resource. Call when deleting it.
+pub async fn clear_resource_shares(
+    conn: &mut PgConnection,
+    resource_type: ResourceType,
+    resource_id: &Uuid,
+) -> Result<(), sqlx::Error> {
+    for table in ["permission_grants", "share_links"] {
+        sqlx::query(&format!(
+            "DELETE FROM {table} WHERE resource_type = $1 AND resource_id = $2"
+        ))
+        .bind(resource_type)
+        .bind(resource_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn levels_allow_everything_below_them() {
+        use PermissionLevel::*;
+        assert!(Full.allows(Execute));
+        assert!(Execute.allows(Comment));
+        assert!(Comment.allows(View));
+        assert!(View.allows(View));
+        assert!(!View.allows(Comment));
+        assert!(!Comment.allows(Execute));
+        assert!(!Execute.allows(Edit));
+    }
+
+    #[test]
+    fn levels_serialize_lowercase() {
+        assert_eq!(
+            serde_json::to_value(PermissionLevel::Execute).unwrap(),
+            "execute"
+        );
+        let level: PermissionLevel = serde_json::from_value("comment".into()).unwrap();
+        assert_eq!(level.as_str(), "comment");
+    }
+
+    #[test]
+    fn expired_links_grant_nothing() {
+        let now = Utc::now();
+        let link = |expires_at| ShareLinkRow {
+            id: Uuid::nil(),
+            owner_id: Uuid::nil(),
+            resource_type: ResourceType::Conversation,
+            resource_id: Uuid::nil(),
+            token: generate_token(),
+            level: PermissionLevel::View,
+            expires_at,
+            created_at: now,
+        };
+        assert!(!link(None).is_expired(now));
+        assert!(!link(Some(now + Duration::hours(1))).is_expired(now));
+        assert!(link(Some(now)).is_expired(now));
+        assert_eq!(link(None).token.len(), 48);
+    }
+}
@@ -0,0 +1,305 @@
+//! Token and cost estimates for chat requests, before they are sent.
+//!
+//! [`estimate`] prices what a request would send — the prompt, the
+//! conversation history and attached documents — without calling a
+//! provider. Tokens are counted without a tokenizer (see
+//! [`estimate_tokens`]), so figures are approximate and err high.
+//!
+//! Prices come from the `agent.pricing` config value: a JSON object of
+//! [`ModelPrice`]s keyed by model spec (`provider:model`), or by
+//! `provider:*` for every model of a provider, e.g.
+//! `{"anthropic:claude-haiku-4-5-20251001":{"inputPerMillion":1,"outputPerMillion":5,"contextWindow":200000}}`.
+//! It is validated with [`parse`] when saved. A request is flagged for a
+//! warning when its estimated cost passes `agent.pricing.warnAboveUsd` or
+//! its input does not fit the model's context window.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::config::ConfigError;
+use crate::config::cache::ConfigCache;
+use crate::config::resolver;
+use crate::i18n::Message;
+
+/// System config key holding the price table.
+pub const PRICING_CONFIG_KEY: &str = "agent.pricing";
+
+/// System config key holding the cost, in USD, above which a request is
+/// flagged; `0` never flags on cost.
+pub const WARN_ABOVE_CONFIG_KEY: &str = "agent.pricing.warnAboveUsd";
+
+/// Output tokens priced when a request does not say how many it expects.
+pub const DEFAULT_OUTPUT_TOKENS: u64 = 1_000;
+
+/// Errors that can occur while estimating.
+#[derive(Debug, Error)]
+pub enum PricingError {
+    #[error("{0}")]
+    InvalidTable(Message),
+
+    #[error("Config error: {0}")]
+    Config(#[from] ConfigError),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// Price of one model, in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+    /// Input tokens the model accepts, if known.
+    #[serde(default)]
+    pub context_window: Option<u64>,
+}
+
+/// Parse and validate the `agent.pricing` value. An empty value is an
+/// empty table.
+pub fn parse(value: &str) -> Result<BTreeMap<String, ModelPrice>, Message> {
+    if value.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    let table: BTreeMap<String, ModelPrice> = serde_json::from_str(value)
+        .map_err(|e| Message::new("pricing.invalid_table").arg("error", e))?;
+    for (spec, price) in &table {
+        match spec.split_once(':') {
+            Some((provider, model)) if !provider.is_empty() && !model.is_empty() => {}
+            _ => {
+                return Err(Message::new("pricing.invalid_key").arg("spec", spec));
+            }
+        }
+        let valid = |p: f64| p.is_finite() && p >= 0.0;
+        if !valid(price.input_per_million) || !valid(price.output_per_million) {
+            return Err(Message::new("pricing.negative_price").arg("spec", spec));
+        }
+        if price.context_window == Some(0) {
+            return Err(Message::new("pricing.zero_context_window").arg("spec", spec));
+        }
+    }
+    Ok(table)
+}
+
+/// The price of `model`: its own entry, else its provider's `provider:*`.
+pub fn price_for(table: &BTreeMap<String, ModelPrice>, model: &str) -> Option<ModelPrice> {
+    table.get(model).copied().or_else(|| {
+        let (provider, _) = model.split_once(':')?;
+        table.get(&format!("{provider}:*")).copied()
+    })
+}
+
+/// Estimated tokens of `text`: a quarter token per ASCII character and a
+/// full token for any other, as for embedding inputs.
+pub fn estimate_tokens(text: &str) -> u64 {
+    let quarters: u64 = text.chars().map(|c| if c.is_ascii() { 1 } else { 4 }).sum();
+    quarters.div_ceil(4)
+}
+
+/// Estimated tokens of the text in a stored message: every string in it,
+/// which over-counts ids and part types a little.
+pub fn message_tokens(message: &serde_json::Value) -> u64 {
+    match message {
+        serde_json::Value::String(s) => estimate_tokens(s),
+        serde_json::Value::Array(items) => items.iter().map(message_tokens).sum(),
+        serde_json::Value::Object(map) => map.values().map(message_tokens).sum(),
+        _ => 0,
+    }
+}
+
+/// Tokens a request would send, by source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenCounts {
+    pub prompt: u64,
+    pub history: u64,
+    pub documents: u64,
+    /// Output tokens priced.
+    pub output: u64,
+}
+
+impl TokenCounts {
+    pub fn input(&self) -> u64 {
+        self.prompt + self.history + self.documents
+    }
+}
+
+/// Why a request is flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum EstimateWarning {
+    /// The estimated cost passes `agent.pricing.warnAboveUsd`.
+    CostAboveThreshold,
+    /// The input does not fit the model's context window.
+    ExceedsContextWindow,
+}
+
+/// Estimated size and cost of a request.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CostEstimate {
+    pub model: String,
+    pub tokens: TokenCounts,
+    pub input_tokens: u64,
+    /// `None` when the price table has no entry for the model.
+    pub price: Option<ModelPrice>,
+    pub input_cost_usd: Option<f64>,
+    pub output_cost_usd: Option<f64>,
+    pub total_cost_usd: Option<f64>,
+    pub warn_above_usd: Option<f64>,
+    pub warnings: Vec<EstimateWarning>,
+}
+
+/// Price `tokens` for `model`.
+pub fn estimate(
+    model: &str,
+    tokens: TokenCounts,
+    price: Option<ModelPrice>,
+    warn_above_usd: Option<f64>,
+) -> CostEstimate {
+    let input_tokens = tokens.input();
+    let cost = |tokens: u64, per_million: f64| tokens as f64 * per_million / 1_000_000.0;
+    let input_cost_usd = price.map(|p| cost(input_tokens, p.input_per_million));
+    let output_cost_usd = price.map(|p| cost(tokens.output, p.output_per_million));
+    let total_cost_usd = input_cost_usd.zip(output_cost_usd).map(|(i, o)| i + o);
+
+    let mut warnings = Vec::new();
+    if let (Some(total), Some(limit)) = (total_cost_usd, warn_above_usd)
+        && total > limit
+    {
+        warnings.push(EstimateWarning::CostAboveThreshold);
+    }
+    if let Some(window) = price.and_then(|p| p.context_window)
+        && input_tokens > window
+    {
+        warnings.push(EstimateWarning::ExceedsContextWindow);
+    }
+
+    CostEstimate {
+        model: model.to_string(),
+        tokens,
+        input_tokens,
+        price,
+        input_cost_usd,
+        output_cost_usd,
+        total_cost_usd,
+        warn_above_usd,
+        warnings,
+    }
+}
+
+/// Read the price table and the warning threshold (`None` when off). An
+/// invalid stored table is an error rather than an empty one, so a typo
+/// does not silently turn off the warnings.
+pub async fn load(
+    pool: &PgPool,
+    cache: &Arc<RwLock<ConfigCache>>,
+) -> Result<(BTreeMap<String, ModelPrice>, Option<f64>), PricingError> {
+    let table = parse(&resolver::get_system_value(pool, cache, PRICING_CONFIG_KEY).await?)
+        .map_err(PricingError::InvalidTable)?;
+    let warn_above = resolver::get_system_value(pool, cache, WARN_ABOVE_CONFIG_KEY)
+        .await?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite() && *v > 0.0);
+    Ok((table, warn_above))
+}
+
+/// Estimated tokens of a conversation's stored messages.
+pub async fn conversation_tokens(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+) -> Result<u64, sqlx::Error> {
+    Ok(crate::conversations::get_messages(pool, conversation_id)
+        .await?
+        .iter()
+        .map(|m| message_tokens(&m.message_data))
+        .sum())
+}
+
+/// Estimated tokens of a document's extracted text.
+pub async fn document_tokens(pool: &PgPool, document_id: &Uuid) -> Result<u64, sqlx::Error> {
+    Ok(crate::documents::list_chunks(pool, document_id)
+        .await?
+        .iter()
+        .map(|c| estimate_tokens(&c.content))
+        .sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = r#"{
+        "anthropic:claude-haiku": {"inputPerMillion": 1, "outputPerMillion": 5, "contextWindow": 1000},
+        "openai:*": {"inputPerMillion": 2.5, "outputPerMillion": 10}
+    }"#;
+
+    #[test]
+    fn parses_and_validates_the_table() {
+        let table = parse(TABLE).unwrap();
+        assert_eq!(table.len(), 2);
+        assert!(parse("").unwrap().is_empty());
+        assert!(parse(r#"{"haiku": {"inputPerMillion": 1, "outputPerMillion": 5}}"#).is_err());
+        assert!(parse(r#"{"a:b": {"inputPerMillion": -1, "outputPerMillion": 5}}"#).is_err());
+        assert!(
+            parse(r#"{"a:b": {"inputPerMillion": 1, "outputPerMillion": 5, "x": 1}}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn prices_fall_back_to_the_provider() {
+        let table = parse(TABLE).unwrap();
+        assert_eq!(
+            price_for(&table, "openai:gpt-4o").map(|p| p.input_per_million),
+            Some(2.5)
+        );
+        assert!(price_for(&table, "anthropic:claude-opus").is_none());
+        assert!(price_for(&table, "local").is_none());
+    }
+
+    #[test]
+    fn counts_tokens_like_embedding_inputs() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        assert_eq!(estimate_tokens("日本"), 2);
+        let message = serde_json::json!({ "parts": [{ "text": "abcdabcd" }] });
+        assert_eq!(message_tokens(&message), 2);
+    }
+
+    #[test]
+    fn flags_expensive_and_oversized_requests() {
+        let table = parse(TABLE).unwrap();
+        let tokens = TokenCounts {
+            prompt: 600,
+            history: 300,
+            documents: 200,
+            output: 1_000,
+        };
+        let price = price_for(&table, "anthropic:claude-haiku");
+        let estimate = estimate("anthropic:claude-haiku", tokens, price, Some(0.005));
+        assert_eq!(estimate.input_tokens, 1_100);
+        let total = estimate.total_cost_usd.unwrap();
+        assert!((total - 0.0061).abs() < 1e-12, "{total}");
+        assert_eq!(
+            estimate.warnings,
+            vec![
+                EstimateWarning::CostAboveThreshold,
+                EstimateWarning::ExceedsContextWindow
+            ]
+        );
+
+        let unpriced = super::estimate("local:llama", tokens, None, Some(0.005));
+        assert_eq!(unpriced.total_cost_usd, None);
+        assert!(unpriced.warnings.is_empty());
+    }
+}
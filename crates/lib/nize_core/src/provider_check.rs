@@ -0,0 +1,283 @@
+//! Provider credential checks.
+//!
+//! Verifies an LLM or embedding provider's credentials with the cheapest
+//! call each provider offers — listing models — so an admin can tell a
+//! working key from a broken one without sending a prompt. A check reports
+//! the call's latency, whether the configured model is listed, and the
+//! rate limit headers the provider returned. Nothing about the response is
+//! kept beyond the [`CheckResult`].
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use reqwest::Client;
+use reqwest::header::HeaderMap;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::providers::CompatibleEndpoint;
+
+/// Longest a single check may take.
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `anthropic-version` sent to the Anthropic API.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// API flavour of a provider endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Api {
+    Anthropic,
+    /// OpenAI and OpenAI-compatible endpoints.
+    OpenAi,
+    Google,
+    Ollama,
+    /// An admin-configured OpenAI-compatible endpoint (see
+    /// [`crate::providers`]); its key is optional and sent in
+    /// [`CheckTarget::auth_header`].
+    Compatible,
+}
+
+impl Api {
+    /// Base URL used when `agent.baseUrl.*` is left empty.
+    pub fn default_base_url(self) -> &'static str {
+        match self {
+            Api::Anthropic => "https://api.anthropic.com/v1",
+            Api::OpenAi => "https://api.openai.com/v1",
+            Api::Google => "https://generativelanguage.googleapis.com/v1beta",
+            Api::Ollama => "http://localhost:11434",
+            Api::Compatible => "",
+        }
+    }
+}
+
+/// What to check.
+#[derive(Debug, Clone)]
+pub struct CheckTarget {
+    pub api: Api,
+    /// API base URL including the version, as the AI SDK takes it (see
+    /// [`Api::default_base_url`]), or the Ollama server.
+    pub base_url: String,
+    pub api_key: Option<String>,
+    /// Header name and value prefix carrying `api_key` for
+    /// [`Api::Compatible`].
+    pub auth_header: Option<(String, String)>,
+    /// Model expected to be available, if any.
+    pub model: Option<String>,
+}
+
+impl CheckTarget {
+    /// Check an OpenAI-compatible endpoint, expecting `model`.
+    pub fn compatible(endpoint: &CompatibleEndpoint, model: Option<String>) -> Self {
+        Self {
+            api: Api::Compatible,
+            base_url: endpoint.provider.base_url.clone(),
+            api_key: endpoint.api_key.clone(),
+            auth_header: Some((
+                endpoint.provider.auth_header.clone(),
+                endpoint.provider.auth_prefix.clone(),
+            )),
+            model,
+        }
+    }
+}
+
+/// Outcome of a check.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckResult {
+    pub ok: bool,
+    /// HTTP status of the provider's response, if one arrived.
+    pub status: Option<u16>,
+    pub latency_ms: u64,
+    /// Whether the configured model is listed; `None` when no model was
+    /// given or the listing failed.
+    pub model_available: Option<bool>,
+    /// Models the credentials can use.
+    pub model_count: Option<usize>,
+    /// Rate limit headers of the response (e.g.
+    /// `x-ratelimit-remaining-requests`).
+    pub quota: BTreeMap<String, String>,
+    pub error: Option<String>,
+}
+
+impl CheckResult {
+    /// A failed check that never reached the provider.
+    pub fn failed(error: impl Into<String>) -> Self {
+        Self {
+            error: Some(error.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Check `target`'s credentials by listing its models.
+pub async fn check(client: &Client, target: &CheckTarget) -> CheckResult {
+    if !matches!(target.api, Api::Ollama | Api::Compatible)
+        && target.api_key.as_deref().is_none_or(str::is_empty)
+    {
+        return CheckResult::failed("No API key configured");
+    }
+    let base = target.base_url.trim_end_matches('/');
+    let key = target.api_key.as_deref().unwrap_or_default();
+    let request = match target.api {
+        Api::Anthropic => client
+            .get(format!("{base}/models?limit=1000"))
+            .header("x-api-key", key)
+            .header("anthropic-version", ANTHROPIC_VERSION),
+        Api::OpenAi => client.get(format!("{base}/models")).bearer_auth(key),
+        Api::Google => client
+            .get(format!("{base}/models?pageSize=1000"))
+            .header("x-goog-api-key", key),
+        Api::Ollama => client.get(format!("{base}/api/tags")),
+        Api::Compatible => {
+            let request = client.get(format!("{base}/models"));
+            match (&target.auth_header, &target.api_key) {
+                (Some((header, prefix)), Some(key)) => {
+                    request.header(header, format!("{prefix}{key}"))
+                }
+                _ => request,
+            }
+        }
+    };
+
+    let started = Instant::now();
+    let response = request.timeout(CHECK_TIMEOUT).send().await;
+    let mut result = CheckResult {
+        latency_ms: started.elapsed().as_millis() as u64,
+        ..Default::default()
+    };
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            result.error = Some(format!("Request failed: {e}"));
+            return result;
+        }
+    };
+
+    let status = response.status();
+    result.status = Some(status.as_u16());
+    result.quota = quota_headers(response.headers());
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+    if !status.is_success() {
+        result.error = Some(match error_message(&body) {
+            Some(message) => format!("{status}: {message}"),
+            None => status.to_string(),
+        });
+        return result;
+    }
+
+    let ids = model_ids(target.api, &body);
+    result.ok = true;
+    result.model_count = Some(ids.len());
+    result.model_available = target
+        .model
+        .as_deref()
+        .map(|model| ids.iter().any(|id| model_matches(target.api, id, model)));
+    result
+}
+
+/// Model IDs in a model listing.
+fn model_ids(api: Api, body: &Value) -> Vec<String> {
+    let (list, field) = match api {
+        Api::Anthropic | Api::OpenAi | Api::Compatible => ("data", "id"),
+        Api::Google | Api::Ollama => ("models", "name"),
+    };
+    body[list]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|m| m[field].as_str())
+        .map(|id| id.strip_prefix("models/").unwrap_or(id).to_string())
+        .collect()
+}
+
+/// Whether listed model `id` is `model`; Ollama lists untagged models as
+/// `:latest`.
+fn model_matches(api: Api, id: &str, model: &str) -> bool {
+    id == model || (api == Api::Ollama && id.strip_suffix(":latest") == Some(model))
+}
+
+/// The provider's error message, which all supported APIs put in
+/// `error.message` (Ollama: `error`).
+fn error_message(body: &Value) -> Option<String> {
+    body["error"]["message"]
+        .as_str()
+        .or_else(|| body["error"].as_str())
+        .map(str::to_string)
+}
+
+fn quota_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter(|(name, _)| name.as_str().contains("ratelimit"))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_ids_cover_each_listing_format() {
+        let openai = serde_json::json!({ "data": [{ "id": "gpt-4o" }, { "id": "o3" }] });
+        assert_eq!(model_ids(Api::OpenAi, &openai), ["gpt-4o", "o3"]);
+
+        let google = serde_json::json!({ "models": [{ "name": "models/gemini-2.0-flash" }] });
+        assert_eq!(model_ids(Api::Google, &google), ["gemini-2.0-flash"]);
+
+        assert!(model_ids(Api::Anthropic, &Value::Null).is_empty());
+    }
+
+    #[test]
+    fn ollama_models_match_without_latest_tag() {
+        assert!(model_matches(
+            Api::Ollama,
+            "nomic-embed-text:latest",
+            "nomic-embed-text"
+        ));
+        assert!(!model_matches(Api::OpenAi, "gpt-4o:latest", "gpt-4o"));
+        assert!(!model_matches(
+            Api::Ollama,
+            "nomic-embed-text:v1.5",
+            "nomic-embed-text"
+        ));
+    }
+
+    #[test]
+    fn errors_and_quota_are_extracted() {
+        let body = serde_json::json!({ "error": { "type": "authentication_error", "message": "invalid x-api-key" } });
+        assert_eq!(error_message(&body).as_deref(), Some("invalid x-api-key"));
+        assert_eq!(
+            error_message(&serde_json::json!({ "error": "model not found" })).as_deref(),
+            Some("model not found")
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining-requests", "499".parse().unwrap());
+        headers.insert("content-type", "application/json".parse().unwrap());
+        let quota = quota_headers(&headers);
+        assert_eq!(quota.len(), 1);
+        assert_eq!(quota["x-ratelimit-remaining-requests"], "499");
+    }
+
+    #[tokio::test]
+    async fn missing_key_fails_without_a_request() {
+        let result = check(
+            &Client::new(),
+            &CheckTarget {
+                api: Api::Anthropic,
+                base_url: Api::Anthropic.default_base_url().into(),
+                api_key: None,
+                auth_header: None,
+                model: None,
+            },
+        )
+        .await;
+        assert!(!result.ok);
+        assert_eq!(result.status, None);
+        assert_eq!(result.error.as_deref(), Some("No API key configured"));
+    }
+}
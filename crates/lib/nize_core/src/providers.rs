@@ -0,0 +1,472 @@
+//! OpenAI-compatible provider endpoints.
+//!
+//! Besides the built-in providers, admins can plug in any server speaking
+//! the OpenAI API (vLLM, LM Studio, llama.cpp, ...) through the
+//! `providers.compatible` config value: a JSON array of
+//! [`CompatibleProvider`]s, each naming the provider used in model specs
+//! (`<name>:<model>`), its base URL, how its API key is sent, its chat
+//! models and its embedding models with their dimensions. API keys live in
+//! the secret `providers.compatible.apiKeys`, a JSON object keyed by
+//! provider name, so they are encrypted like every other provider key.
+//!
+//! Both values are validated with [`parse`] and [`parse_api_keys`] when
+//! they are saved. Saving registers the embedding models (see
+//! [`register_embedding_models`]) and offers the providers that have some as
+//! `embedding.provider` choices.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use reqwest::header::HeaderName;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use tokio::sync::RwLock;
+
+use crate::config::cache::ConfigCache;
+use crate::config::{queries, resolver};
+use crate::i18n::Message;
+use crate::mcp::secrets;
+use crate::models::config::ConfigScope;
+
+/// Config key of the provider list.
+pub const PROVIDERS_CONFIG_KEY: &str = "providers.compatible";
+/// Config key of the providers' API keys.
+pub const API_KEYS_CONFIG_KEY: &str = "providers.compatible.apiKeys";
+/// Config key whose choices include providers with embedding models.
+pub const EMBEDDING_PROVIDER_CONFIG_KEY: &str = "embedding.provider";
+
+/// Names taken by built-in providers.
+pub const BUILT_IN_PROVIDERS: &[&str] = &["anthropic", "openai", "google", "ollama", "local"];
+/// Built-in embedding providers, offered before the compatible ones.
+const BUILT_IN_EMBEDDING_PROVIDERS: &[&str] = &["openai", "ollama", "local"];
+
+/// Longest provider name (`embedding_models.provider` is `VARCHAR(50)`).
+const MAX_NAME_LEN: usize = 50;
+/// Longest model name (`embedding_models.name` is `VARCHAR(100)`).
+const MAX_MODEL_LEN: usize = 100;
+/// Most dimensions the ANN indexes support.
+pub const MAX_DIMENSIONS: i32 = 2000;
+
+/// An OpenAI-compatible endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CompatibleProvider {
+    /// Provider name in model specs; lowercase letters, digits and `-`.
+    pub name: String,
+    /// API base URL including the version, e.g. `http://localhost:8000/v1`.
+    pub base_url: String,
+    /// Header carrying the API key.
+    #[serde(default = "default_auth_header")]
+    pub auth_header: String,
+    /// Text before the API key in [`Self::auth_header`].
+    #[serde(default = "default_auth_prefix")]
+    pub auth_prefix: String,
+    /// Chat models.
+    #[serde(default)]
+    pub models: Vec<String>,
+    #[serde(default)]
+    pub embedding_models: Vec<CompatibleEmbeddingModel>,
+}
+
+/// An embedding model of a [`CompatibleProvider`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct CompatibleEmbeddingModel {
+    pub name: String,
+    pub dimensions: i32,
+    /// Longest input the model accepts, in tokens.
+    #[serde(default = "default_max_input_tokens")]
+    pub max_input_tokens: i32,
+}
+
+fn default_auth_header() -> String {
+    "Authorization".into()
+}
+
+fn default_auth_prefix() -> String {
+    "Bearer ".into()
+}
+
+fn default_max_input_tokens() -> i32 {
+    512
+}
+
+impl CompatibleProvider {
+    /// Whether `url` is under this provider's base URL, so requests to it
+    /// may carry the provider's API key.
+    pub fn owns_url(&self, url: &url::Url) -> bool {
+        let Ok(base) = url::Url::parse(&self.base_url) else {
+            return false;
+        };
+        let base_path = base.path().trim_end_matches('/');
+        url.origin() == base.origin()
+            && url
+                .path()
+                .strip_prefix(base_path)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+
+    /// `{base_url}/{path}`.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}/{path}", self.base_url.trim_end_matches('/'))
+    }
+}
+
+/// A provider with its API key, ready to call.
+#[derive(Debug, Clone)]
+pub struct CompatibleEndpoint {
+    pub provider: CompatibleProvider,
+    pub api_key: Option<String>,
+}
+
+impl CompatibleEndpoint {
+    /// The header carrying the API key, if there is one.
+    pub fn auth_header(&self) -> Option<(&str, String)> {
+        let key = self.api_key.as_deref()?;
+        Some((
+            &self.provider.auth_header,
+            format!("{}{key}", self.provider.auth_prefix),
+        ))
+    }
+}
+
+/// Parse and validate a `providers.compatible` value; empty means none.
+pub fn parse(value: &str) -> Result<Vec<CompatibleProvider>, Message> {
+    if value.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let providers: Vec<CompatibleProvider> = serde_json::from_str(value)
+        .map_err(|e| Message::new("providers.invalid_json").arg("error", e))?;
+    for (i, provider) in providers.iter().enumerate() {
+        validate(provider).map_err(|e| e.arg("provider", provider_label(provider, i)))?;
+        if providers[..i].iter().any(|p| p.name == provider.name) {
+            return Err(Message::new("providers.name_used_twice").arg("name", &provider.name));
+        }
+    }
+    Ok(providers)
+}
+
+fn provider_label(provider: &CompatibleProvider, index: usize) -> String {
+    if provider.name.is_empty() {
+        format!("#{}", index + 1)
+    } else {
+        provider.name.clone()
+    }
+}
+
+/// Check one provider. The messages take a `provider` argument, added by
+/// [`parse`].
+fn validate(provider: &CompatibleProvider) -> Result<(), Message> {
+    let name = &provider.name;
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(Message::new("providers.name_length").arg("max", MAX_NAME_LEN));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(Message::new("providers.name_characters"));
+    }
+    if BUILT_IN_PROVIDERS.contains(&name.as_str()) {
+        return Err(Message::new("providers.name_built_in").arg("name", name));
+    }
+
+    let url = url::Url::parse(&provider.base_url)
+        .map_err(|e| Message::new("providers.invalid_base_url").arg("error", e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(Message::new("providers.base_url_scheme"));
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(Message::new("providers.base_url_query"));
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return Err(Message::new("providers.base_url_credentials"));
+    }
+
+    HeaderName::from_bytes(provider.auth_header.as_bytes()).map_err(|_| {
+        Message::new("providers.invalid_auth_header").arg("header", &provider.auth_header)
+    })?;
+
+    if provider.models.is_empty() && provider.embedding_models.is_empty() {
+        return Err(Message::new("providers.no_models"));
+    }
+    for model in &provider.models {
+        check_model_name(model)?;
+    }
+    for (i, model) in provider.embedding_models.iter().enumerate() {
+        check_model_name(&model.name)?;
+        if !(1..=MAX_DIMENSIONS).contains(&model.dimensions) {
+            return Err(Message::new("providers.dimensions_out_of_range")
+                .arg("model", &model.name)
+                .arg("max", MAX_DIMENSIONS));
+        }
+        if model.max_input_tokens < 1 {
+            return Err(Message::new("providers.max_input_tokens").arg("model", &model.name));
+        }
+        if provider.embedding_models[..i]
+            .iter()
+            .any(|m| m.name == model.name)
+        {
+            return Err(Message::new("providers.embedding_model_twice").arg("model", &model.name));
+        }
+    }
+    Ok(())
+}
+
+fn check_model_name(model: &str) -> Result<(), Message> {
+    if model.trim().is_empty() || model.len() > MAX_MODEL_LEN {
+        return Err(Message::new("providers.model_name_length").arg("max", MAX_MODEL_LEN));
+    }
+    Ok(())
+}
+
+/// Parse a `providers.compatible.apiKeys` value: a JSON object of API keys
+/// by provider name; empty means none.
+pub fn parse_api_keys(value: &str) -> Result<HashMap<String, String>, Message> {
+    if value.trim().is_empty() {
+        return Ok(HashMap::new());
+    }
+    serde_json::from_str(value).map_err(|_| Message::new("providers.invalid_api_keys"))
+}
+
+/// The configured providers. An invalid stored value (saved before
+/// validation existed, or edited in the database) counts as none.
+pub async fn load(pool: &PgPool, cache: &Arc<RwLock<ConfigCache>>) -> Vec<CompatibleProvider> {
+    let value = resolver::get_system_value(pool, cache, PROVIDERS_CONFIG_KEY)
+        .await
+        .unwrap_or_default();
+    parse(&value).unwrap_or_else(|e| {
+        tracing::warn!("Ignoring invalid {PROVIDERS_CONFIG_KEY}: {e}");
+        Vec::new()
+    })
+}
+
+/// The configured providers with their API keys.
+pub async fn endpoints(
+    pool: &PgPool,
+    cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+) -> Vec<CompatibleEndpoint> {
+    let providers = load(pool, cache).await;
+    if providers.is_empty() {
+        return Vec::new();
+    }
+    let mut keys = api_keys(pool, encryption_key).await;
+    providers
+        .into_iter()
+        .map(|provider| CompatibleEndpoint {
+            api_key: keys.remove(&provider.name),
+            provider,
+        })
+        .collect()
+}
+
+/// The provider named `name` with its API key, if one is configured.
+pub async fn endpoint(
+    pool: &PgPool,
+    cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+    name: &str,
+) -> Option<CompatibleEndpoint> {
+    endpoints(pool, cache, encryption_key)
+        .await
+        .into_iter()
+        .find(|e| e.provider.name == name)
+}
+
+/// Decrypted API keys by provider name.
+async fn api_keys(pool: &PgPool, encryption_key: &str) -> HashMap<String, String> {
+    let stored = queries::get_value(pool, API_KEYS_CONFIG_KEY, &ConfigScope::System, None)
+        .await
+        .ok()
+        .flatten()
+        .filter(|v| !v.value.is_empty());
+    let Some(stored) = stored else {
+        return HashMap::new();
+    };
+    secrets::decrypt(&stored.value, encryption_key)
+        .map_err(|e| e.to_string())
+        .and_then(|plain| parse_api_keys(&plain).map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| {
+            tracing::warn!("Ignoring unreadable {API_KEYS_CONFIG_KEY}: {e}");
+            HashMap::new()
+        })
+}
+
+/// Embedding models already registered for `providers` whose dimensions
+/// differ from the configured ones, as `(provider, model, registered)`.
+/// Stored vectors cannot change dimension, so such a change is refused.
+pub async fn dimension_conflicts(
+    pool: &PgPool,
+    providers: &[CompatibleProvider],
+) -> Result<Vec<(String, String, i32)>, sqlx::Error> {
+    let registered = sqlx::query_as::<_, (String, String, i32)>(
+        "SELECT provider, name, dimensions FROM embedding_models",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(registered
+        .into_iter()
+        .filter(|(provider, model, dimensions)| {
+            providers
+                .iter()
+                .filter(|p| &p.name == provider)
+                .flat_map(|p| &p.embedding_models)
+                .any(|m| &m.name == model && m.dimensions != *dimensions)
+        })
+        .collect())
+}
+
+/// Register the providers' embedding models in `embedding_models` and
+/// offer the providers that have some as `embedding.provider` choices.
+/// Models of removed providers stay registered, so their embeddings are
+/// kept until an admin deletes them.
+pub async fn register_embedding_models(
+    conn: &mut PgConnection,
+    providers: &[CompatibleProvider],
+) -> Result<(), sqlx::Error> {
+    for provider in providers {
+        for model in &provider.embedding_models {
+            sqlx::query(
+                "INSERT INTO embedding_models (provider, name, table_name, dimensions, \
+                 max_input_tokens) VALUES ($1, $2, $3, $4, $5) \
+                 ON CONFLICT (provider, name) \
+                 DO UPDATE SET max_input_tokens = EXCLUDED.max_input_tokens",
+            )
+            .bind(&provider.name)
+            .bind(&model.name)
+            .bind(table_name(&provider.name, &model.name))
+            .bind(model.dimensions)
+            .bind(model.max_input_tokens)
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+
+    let choices: Vec<&str> = BUILT_IN_EMBEDDING_PROVIDERS
+        .iter()
+        .copied()
+        .chain(
+            providers
+                .iter()
+                .filter(|p| !p.embedding_models.is_empty())
+                .map(|p| p.name.as_str()),
+        )
+        .collect();
+    sqlx::query("UPDATE config_definitions SET possible_values = $2 WHERE key = $1")
+        .bind(EMBEDDING_PROVIDER_CONFIG_KEY)
+        .bind(serde_json::json!(choices))
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Legacy per-model table name required by `embedding_models`; unique per
+/// provider and model, not a real table.
+fn table_name(provider: &str, model: &str) -> String {
+    let model: String = model
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut name = format!("compatible_{}_{model}", provider.replace('-', "_"));
+    name.truncate(120);
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VLLM: &str = r#"[{
+        "name": "vllm",
+        "baseUrl": "http://gpu-box:8000/v1",
+        "models": ["meta-llama/Llama-3.1-8B-Instruct"],
+        "embeddingModels": [{ "name": "BAAI/bge-m3", "dimensions": 1024 }]
+    }]"#;
+
+    #[test]
+    fn parses_with_defaults() {
+        let providers = parse(VLLM).unwrap();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].auth_header, "Authorization");
+        assert_eq!(providers[0].auth_prefix, "Bearer ");
+        assert_eq!(providers[0].embedding_models[0].max_input_tokens, 512);
+        assert!(parse("  ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_providers() {
+        let with = |patch: &str| {
+            let mut value: serde_json::Value = serde_json::from_str(VLLM).unwrap();
+            let patch: serde_json::Value = serde_json::from_str(patch).unwrap();
+            for (k, v) in patch.as_object().unwrap() {
+                value[0][k] = v.clone();
+            }
+            parse(&value.to_string())
+        };
+        assert!(
+            with(r#"{"name": "openai"}"#)
+                .unwrap_err()
+                .to_string()
+                .contains("built-in")
+        );
+        assert!(with(r#"{"name": "My Server"}"#).is_err());
+        assert!(with(r#"{"baseUrl": "ftp://host/v1"}"#).is_err());
+        assert!(with(r#"{"baseUrl": "http://user:pw@host/v1"}"#).is_err());
+        assert!(with(r#"{"authHeader": "bad header"}"#).is_err());
+        assert!(with(r#"{"models": [], "embeddingModels": []}"#).is_err());
+        assert!(
+            with(r#"{"embeddingModels": [{"name": "e", "dimensions": 4096}]}"#)
+                .unwrap_err()
+                .to_string()
+                .contains("dimensions")
+        );
+        assert!(with(r#"{"apiKey": "sk-1"}"#).is_err());
+        assert!(parse("{}").is_err());
+
+        let twice = format!("[{0},{0}]", &VLLM.trim()[1..VLLM.trim().len() - 1]);
+        assert!(parse(&twice).unwrap_err().to_string().contains("twice"));
+    }
+
+    #[test]
+    fn owns_only_urls_under_the_base_url() {
+        let provider = parse(VLLM).unwrap().remove(0);
+        let owns = |url: &str| provider.owns_url(&url::Url::parse(url).unwrap());
+        assert!(owns("http://gpu-box:8000/v1/chat/completions"));
+        assert!(owns("http://gpu-box:8000/v1"));
+        assert!(!owns("http://gpu-box:8000/v10/models"));
+        assert!(!owns("http://gpu-box:8001/v1/models"));
+        assert!(!owns("https://gpu-box:8000/v1/models"));
+        assert!(!owns("http://evil.example/v1/models"));
+    }
+
+    #[test]
+    fn endpoint_sends_key_with_configured_header() {
+        let mut provider = parse(VLLM).unwrap().remove(0);
+        provider.auth_header = "api-key".into();
+        provider.auth_prefix = String::new();
+        let endpoint = CompatibleEndpoint {
+            provider,
+            api_key: Some("k1".into()),
+        };
+        assert_eq!(endpoint.auth_header(), Some(("api-key", "k1".to_string())));
+        assert_eq!(
+            endpoint.provider.url("embeddings"),
+            "http://gpu-box:8000/v1/embeddings"
+        );
+    }
+
+    #[test]
+    fn table_names_are_sanitized() {
+        assert_eq!(
+            table_name("lm-studio", "BAAI/bge-m3"),
+            "compatible_lm_studio_baai_bge_m3"
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Per-user quotas for shared deployments.
+//!
+//! Limits are system config values (`system.quotas.*`); `0` means unlimited.
+//! Usage is computed from the user's own rows: conversations they created,
+//! documents (notes and uploaded files), MCP servers they own, and storage —
+//! the bytes of their note titles and bodies, the messages of their
+//! conversations and their uploaded files.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::config::ConfigError;
+use crate::config::cache::ConfigCache;
+use crate::config::resolver;
+
+/// Errors that can occur in quota operations.
+#[derive(Debug, Error)]
+pub enum QuotaError {
+    #[error("{0}")]
+    Exceeded(QuotaExceeded),
+
+    #[error("Config error: {0}")]
+    Config(#[from] ConfigError),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// A limited resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Quota {
+    Conversations,
+    Documents,
+    StorageBytes,
+    McpServers,
+}
+
+impl Quota {
+    pub const ALL: &[Quota] = &[
+        Quota::Conversations,
+        Quota::Documents,
+        Quota::StorageBytes,
+        Quota::McpServers,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Quota::Conversations => "conversations",
+            Quota::Documents => "documents",
+            Quota::StorageBytes => "storageBytes",
+            Quota::McpServers => "mcpServers",
+        }
+    }
+
+    /// System config key holding the limit.
+    pub fn config_key(self) -> &'static str {
+        match self {
+            Quota::Conversations => "system.quotas.maxConversations",
+            Quota::Documents => "system.quotas.maxDocuments",
+            Quota::StorageBytes => "system.quotas.maxStorageBytes",
+            Quota::McpServers => "system.quotas.maxMcpServers",
+        }
+    }
+}
+
+/// A rejected request: `used + requested` would pass `limit`.
+#[derive(Debug, Clone, Serialize, Error)]
+#[serde(rename_all = "camelCase")]
+#[error("Quota exceeded: {used} of {limit} {} used", .quota.as_str())]
+pub struct QuotaExceeded {
+    pub quota: Quota,
+    pub limit: i64,
+    pub used: i64,
+    pub requested: i64,
+}
+
+/// Configured limits; `None` is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuotaLimits {
+    pub conversations: Option<i64>,
+    pub documents: Option<i64>,
+    pub storage_bytes: Option<i64>,
+    pub mcp_servers: Option<i64>,
+}
+
+impl QuotaLimits {
+    pub fn get(&self, quota: Quota) -> Option<i64> {
+        match quota {
+            Quota::Conversations => self.conversations,
+            Quota::Documents => self.documents,
+            Quota::StorageBytes => self.storage_bytes,
+            Quota::McpServers => self.mcp_servers,
+        }
+    }
+}
+
+/// A user's current consumption.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, sqlx::FromRow)]
+pub struct QuotaUsage {
+    pub conversations: i64,
+    pub documents: i64,
+    pub storage_bytes: i64,
+    pub mcp_servers: i64,
+}
+
+impl QuotaUsage {
+    pub fn get(&self, quota: Quota) -> i64 {
+        match quota {
+            Quota::Conversations => self.conversations,
+            Quota::Documents => self.documents,
+            Quota::StorageBytes => self.storage_bytes,
+            Quota::McpServers => self.mcp_servers,
+        }
+    }
+}
+
+/// Parse a configured limit; `0`, negative or unparsable values are
+/// unlimited.
+fn parse_limit(value: &str) -> Option<i64> {
+    value.trim().parse::<i64>().ok().filter(|n| *n > 0)
+}
+
+/// Read the configured limits.
+pub async fn get_limits(
+    pool: &PgPool,
+    cache: &Arc<RwLock<ConfigCache>>,
+) -> Result<QuotaLimits, ConfigError> {
+    let mut limits = QuotaLimits::default();
+    for &quota in Quota::ALL {
+        let value =
+            parse_limit(&resolver::get_system_value(pool, cache, quota.config_key()).await?);
+        match quota {
+            Quota::Conversations => limits.conversations = value,
+            Quota::Documents => limits.documents = value,
+            Quota::StorageBytes => limits.storage_bytes = value,
+            Quota::McpServers => limits.mcp_servers = value,
+        }
+    }
+    Ok(limits)
+}
+
+/// Compute a user's current usage.
+pub async fn get_usage(pool: &PgPool, user_id: &Uuid) -> Result<QuotaUsage, sqlx::Error> {
+    sqlx::query_as::<_, QuotaUsage>(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM conversations WHERE user_id = $1) AS conversations,
+            (SELECT COUNT(*) FROM notes WHERE user_id = $1)
+            + (SELECT COUNT(*) FROM documents WHERE user_id = $1) AS documents,
+            (SELECT COALESCE(SUM(octet_length(title) + octet_length(body)), 0)::BIGINT
+                FROM notes WHERE user_id = $1)
+            + (SELECT COALESCE(SUM(octet_length(m.message_data::text)), 0)::BIGINT
+                FROM messages m JOIN conversations c ON c.id = m.conversation_id
+                WHERE c.user_id = $1)
+            + (SELECT COALESCE(SUM(size_bytes), 0)::BIGINT
+                FROM documents WHERE user_id = $1) AS storage_bytes,
+            (SELECT COUNT(*) FROM mcp_servers
+                WHERE visibility = 'user' AND owner_id = $1) AS mcp_servers
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Stored bytes of a conversation's messages, measured as [`get_usage`]
+/// does.
+pub async fn conversation_message_bytes(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COALESCE(SUM(octet_length(message_data::text)), 0)::BIGINT FROM messages WHERE conversation_id = $1",
+    )
+    .bind(conversation_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Bytes `messages` would take once stored, measured as [`get_usage`] does
+/// (JSONB text, not the request's formatting).
+pub async fn message_bytes(
+    pool: &PgPool,
+    messages: &[serde_json::Value],
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COALESCE(SUM(octet_length(m::text)), 0)::BIGINT FROM jsonb_array_elements($1) AS m",
+    )
+    .bind(serde_json::Value::Array(messages.to_vec()))
+    .fetch_one(pool)
+    .await
+}
+
+/// Check that adding `requested` to `quota` stays within the limit.
+/// Non-positive requests (deletions, shrinking edits) always pass.
+pub fn check(
+    limits: &QuotaLimits,
+    usage: &QuotaUsage,
+    quota: Quota,
+    requested: i64,
+) -> Result<(), QuotaExceeded> {
+    let Some(limit) = limits.get(quota) else {
+        return Ok(());
+    };
+    let used = usage.get(quota);
+    if requested > 0 && used + requested > limit {
+        return Err(QuotaExceeded {
+            quota,
+            limit,
+            used,
+            requested,
+        });
+    }
+    Ok(())
+}
+
+/// Load limits and usage and [`check`] each `(quota, requested)` pair.
+pub async fn ensure_within(
+    pool: &PgPool,
+    cache: &Arc<RwLock<ConfigCache>>,
+    user_id: &Uuid,
+    requests: &[(Quota, i64)],
+) -> Result<(), QuotaError> {
+    let limits = get_limits(pool, cache).await?;
+    if requests.iter().all(|(q, _)| limits.get(*q).is_none()) {
+        return Ok(());
+    }
+    let usage = get_usage(pool, user_id).await?;
+    for &(quota, requested) in requests {
+        check(&limits, &usage, quota, requested).map_err(QuotaError::Exceeded)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_and_invalid_limits_are_unlimited() {
+        assert_eq!(parse_limit("0"), None);
+        assert_eq!(parse_limit("-5"), None);
+        assert_eq!(parse_limit("abc"), None);
+        assert_eq!(parse_limit(" 10 "), Some(10));
+    }
+
+    #[test]
+    fn check_rejects_requests_past_the_limit() {
+        let limits = QuotaLimits {
+            documents: Some(3),
+            ..Default::default()
+        };
+        let usage = QuotaUsage {
+            documents: 3,
+            conversations: 100,
+            ..Default::default()
+        };
+        let err = check(&limits, &usage, Quota::Documents, 1).unwrap_err();
+        assert_eq!((err.limit, err.used, err.requested), (3, 3, 1));
+        // Shrinking is allowed even when over the limit.
+        assert!(check(&limits, &usage, Quota::Documents, -1).is_ok());
+        // Unlimited quotas always pass.
+        assert!(check(&limits, &usage, Quota::Conversations, 1).is_ok());
+    }
+}
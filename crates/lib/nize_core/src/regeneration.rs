@@ -0,0 +1,375 @@
+//! Alternative assistant replies.
+//!
+//! Regenerating an assistant turn produces a candidate reply, stored next to
+//! the original rather than replacing it. Message rows are replaced whenever
+//! a conversation is saved, so candidates are keyed by the UIMessage `id` of
+//! the reply they stand in for, and every candidate carries that same `id`.
+//! The first regeneration of a reply also stores the original as a
+//! candidate. One candidate per reply is selected; selecting another writes
+//! its content into the conversation.
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Most candidates kept per reply, the original included.
+pub const MAX_CANDIDATES: i64 = 10;
+
+/// Highest temperature accepted as an override.
+pub const MAX_TEMPERATURE: f64 = 2.0;
+
+/// Errors that can occur in regeneration operations.
+#[derive(Debug, Error)]
+pub enum RegenerationError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Not found: {0}")]
+    NotFound(Message),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// Overrides for one regeneration; unset fields use the chat config.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RegenerationParams {
+    pub temperature: Option<f64>,
+    /// Model spec (`provider:model`).
+    pub model: Option<String>,
+    /// Whether MCP tools are offered to the model.
+    pub tools_enabled: Option<bool>,
+}
+
+impl RegenerationParams {
+    /// Validate the overrides, dropping a blank model.
+    pub fn validate(self) -> Result<Self, RegenerationError> {
+        if let Some(t) = self.temperature
+            && !(0.0..=MAX_TEMPERATURE).contains(&t)
+        {
+            return Err(RegenerationError::Validation(
+                Message::new("regeneration.temperature_out_of_range").arg("max", MAX_TEMPERATURE),
+            ));
+        }
+        let model = self
+            .model
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty());
+        if model.as_ref().is_some_and(|m| !m.contains(':')) {
+            return Err(RegenerationError::Validation(Message::new(
+                "regeneration.invalid_model_spec",
+            )));
+        }
+        Ok(Self { model, ..self })
+    }
+}
+
+/// Row returned by candidate queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CandidateRow {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub message_id: String,
+    pub message_data: Value,
+    /// [`RegenerationParams`] of a regenerated candidate; `None` for the
+    /// original reply.
+    pub params: Option<Value>,
+    pub selected: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+const CANDIDATE_COLUMNS: &str =
+    "id, conversation_id, message_id, message_data, params, selected, created_at";
+
+/// An assistant reply and the history it answered.
+#[derive(Debug, Clone)]
+pub struct Turn {
+    pub reply: Value,
+    /// Messages before the reply, ending with the user message it answered.
+    pub history: Vec<Value>,
+}
+
+/// The assistant reply with UIMessage id `message_id` in a conversation's
+/// messages, with the messages that preceded it.
+pub fn find_turn(messages: &[Value], message_id: &str) -> Result<Turn, RegenerationError> {
+    let index = messages
+        .iter()
+        .position(|m| m.get("id").and_then(|v| v.as_str()) == Some(message_id))
+        .ok_or_else(|| {
+            RegenerationError::NotFound(
+                Message::new("regeneration.message_not_found").arg("id", message_id),
+            )
+        })?;
+    if role(&messages[index]) != Some("assistant") {
+        return Err(RegenerationError::Validation(Message::new(
+            "regeneration.not_assistant_message",
+        )));
+    }
+    let history = &messages[..index];
+    if history.last().and_then(role) != Some("user") {
+        return Err(RegenerationError::Validation(Message::new(
+            "regeneration.not_reply_to_user",
+        )));
+    }
+    Ok(Turn {
+        reply: messages[index].clone(),
+        history: history.to_vec(),
+    })
+}
+
+fn role(message: &Value) -> Option<&str> {
+    message.get("role").and_then(|v| v.as_str())
+}
+
+/// `message` with its UIMessage id set to `message_id`.
+fn with_id(mut message: Value, message_id: &str) -> Value {
+    if let Some(obj) = message.as_object_mut() {
+        obj.insert("id".into(), Value::String(message_id.to_string()));
+    }
+    message
+}
+
+/// The conversation's messages, in order.
+pub async fn load_messages(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+) -> Result<Vec<Value>, RegenerationError> {
+    Ok(sqlx::query_scalar(
+        "SELECT message_data FROM messages WHERE conversation_id = $1 ORDER BY sort_order",
+    )
+    .bind(conversation_id)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Store `reply`, generated with `params`, as a candidate for `turn`'s
+/// reply `message_id`. The original is stored (selected) first if this is
+/// the reply's first regeneration.
+///
+/// The caller must already have checked that the conversation is visible to
+/// the user.
+pub async fn add_candidate(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+    message_id: &str,
+    turn: &Turn,
+    reply: Value,
+    params: &RegenerationParams,
+) -> Result<CandidateRow, RegenerationError> {
+    let mut tx = pool.begin().await?;
+
+    // Keeps the original if a candidate is already selected.
+    sqlx::query(
+        r#"
+        INSERT INTO message_candidates (id, conversation_id, message_id, message_data, selected)
+        VALUES ($1, $2, $3, $4, true)
+        ON CONFLICT (conversation_id, message_id) WHERE selected DO NOTHING
+        "#,
+    )
+    .bind(uuidv7())
+    .bind(conversation_id)
+    .bind(message_id)
+    .bind(&turn.reply)
+    .execute(&mut *tx)
+    .await?;
+
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM message_candidates WHERE conversation_id = $1 AND message_id = $2",
+    )
+    .bind(conversation_id)
+    .bind(message_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if count >= MAX_CANDIDATES {
+        return Err(RegenerationError::Validation(
+            Message::new("regeneration.too_many_candidates").arg("max", MAX_CANDIDATES),
+        ));
+    }
+
+    let params = serde_json::to_value(params).unwrap_or_default();
+    let row = sqlx::query_as::<_, CandidateRow>(&format!(
+        r#"
+        INSERT INTO message_candidates (id, conversation_id, message_id, message_data, params)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING {CANDIDATE_COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(conversation_id)
+    .bind(message_id)
+    .bind(with_id(reply, message_id))
+    .bind(params)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Candidates for reply `message_id`, oldest (the original) first.
+pub async fn list_candidates(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+    message_id: &str,
+) -> Result<Vec<CandidateRow>, RegenerationError> {
+    let rows = sqlx::query_as::<_, CandidateRow>(&format!(
+        r#"
+        SELECT {CANDIDATE_COLUMNS}
+        FROM message_candidates
+        WHERE conversation_id = $1 AND message_id = $2
+        ORDER BY created_at, id
+        "#
+    ))
+    .bind(conversation_id)
+    .bind(message_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Make candidate `candidate_id` the reply shown for `message_id`, writing
+/// its content into the conversation.
+pub async fn select_candidate(
+    pool: &PgPool,
+    conversation_id: &Uuid,
+    message_id: &str,
+    candidate_id: &Uuid,
+) -> Result<CandidateRow, RegenerationError> {
+    let mut tx = pool.begin().await?;
+
+    let message_data: Value = sqlx::query_scalar(
+        r#"
+        SELECT message_data FROM message_candidates
+        WHERE id = $1 AND conversation_id = $2 AND message_id = $3
+        FOR UPDATE
+        "#,
+    )
+    .bind(candidate_id)
+    .bind(conversation_id)
+    .bind(message_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| RegenerationError::NotFound(Message::new("regeneration.candidate_not_found")))?;
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE messages SET message_data = $3
+        WHERE conversation_id = $1 AND message_data->>'id' = $2
+        "#,
+    )
+    .bind(conversation_id)
+    .bind(message_id)
+    .bind(&message_data)
+    .execute(&mut *tx)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Err(RegenerationError::NotFound(
+            Message::new("regeneration.message_not_found").arg("id", message_id),
+        ));
+    }
+
+    // Two statements: the partial unique index allows one selected row at
+    // any point.
+    sqlx::query(
+        r#"
+        UPDATE message_candidates SET selected = false
+        WHERE conversation_id = $1 AND message_id = $2 AND selected
+        "#,
+    )
+    .bind(conversation_id)
+    .bind(message_id)
+    .execute(&mut *tx)
+    .await?;
+    let row = sqlx::query_as::<_, CandidateRow>(&format!(
+        "UPDATE message_candidates SET selected = true WHERE id = $1 RETURNING {CANDIDATE_COLUMNS}"
+    ))
+    .bind(candidate_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE conversations SET updated_at = now() WHERE id = $1")
+        .bind(conversation_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(row)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn conversation() -> Vec<Value> {
+        vec![
+            json!({ "id": "u1", "role": "user", "parts": [] }),
+            json!({ "id": "a1", "role": "assistant", "parts": [] }),
+            json!({ "id": "a2", "role": "assistant", "parts": [] }),
+        ]
+    }
+
+    #[test]
+    fn find_turn_returns_reply_and_history() {
+        let turn = find_turn(&conversation(), "a1").unwrap();
+        assert_eq!(turn.reply["id"], "a1");
+        assert_eq!(turn.history.len(), 1);
+        assert_eq!(turn.history[0]["id"], "u1");
+    }
+
+    #[test]
+    fn find_turn_rejects_other_messages() {
+        let messages = conversation();
+        assert!(matches!(
+            find_turn(&messages, "missing"),
+            Err(RegenerationError::NotFound(_))
+        ));
+        assert!(matches!(
+            find_turn(&messages, "u1"),
+            Err(RegenerationError::Validation(_))
+        ));
+        assert!(matches!(
+            find_turn(&messages, "a2"),
+            Err(RegenerationError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn params_validate_temperature_and_model() {
+        let params = RegenerationParams {
+            temperature: Some(0.2),
+            model: Some("  ".into()),
+            tools_enabled: Some(false),
+        }
+        .validate()
+        .unwrap();
+        assert_eq!(params.model, None);
+        assert_eq!(params.tools_enabled, Some(false));
+
+        let hot = RegenerationParams {
+            temperature: Some(2.5),
+            ..Default::default()
+        };
+        assert!(hot.validate().is_err());
+        let bare = RegenerationParams {
+            model: Some("gpt-4o".into()),
+            ..Default::default()
+        };
+        assert!(bare.validate().is_err());
+    }
+
+    #[test]
+    fn candidates_carry_the_reply_id() {
+        let reply = with_id(json!({ "id": "new", "role": "assistant" }), "a1");
+        assert_eq!(reply["id"], "a1");
+    }
+}
@@ -0,0 +1,324 @@
+//! Retry with exponential backoff for outbound calls.
+//!
+//! A [`RetryPolicy`] says how often and how long to retry: a maximum number
+//! of attempts, a base delay doubled per retry up to a cap, optional jitter,
+//! and an overall deadline after which no further attempt is started.
+//! Policies are `const`, so call sites declare theirs next to the code that
+//! uses it:
+//!
+//! ```
+//! use std::time::Duration;
+//! use nize_core::retry::RetryPolicy;
+//!
+//! const TOKEN_RETRY: RetryPolicy = RetryPolicy::new()
+//!     .max_attempts(3)
+//!     .base_delay(Duration::from_millis(250))
+//!     .deadline(Duration::from_secs(10));
+//! ```
+//!
+//! [`RetryPolicy::send`] retries a `reqwest` request on connection failures,
+//! timeouts and [retryable statuses](is_retryable_status), honouring
+//! `Retry-After`; [`RetryPolicy::run`] retries any fallible operation.
+
+use std::future::Future;
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use tokio::time::{Instant, sleep};
+use tracing::debug;
+
+/// How to retry a failing call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    deadline: Option<Duration>,
+    retry_statuses: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy {
+    /// Three attempts, 500 ms doubling up to 10 s, with jitter and no
+    /// deadline.
+    pub const fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+            deadline: None,
+            retry_statuses: true,
+        }
+    }
+
+    /// Total attempts, including the first (at least one).
+    pub const fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = if attempts == 0 { 1 } else { attempts };
+        self
+    }
+
+    /// Delay before the first retry; doubled for each later one.
+    pub const fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Longest delay between attempts, including one asked for by
+    /// `Retry-After`.
+    pub const fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Whether to randomise each delay between half and all of it, so
+    /// clients that failed together do not retry together.
+    pub const fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Do not start an attempt, or sleep towards one, past this long after
+    /// the first attempt started.
+    pub const fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Whether [`send`](Self::send) retries retryable statuses, or only
+    /// requests that never got a response. Turn off for requests that must
+    /// not be repeated once the server has seen them, such as redeeming a
+    /// single-use code.
+    pub const fn retry_statuses(mut self, retry: bool) -> Self {
+        self.retry_statuses = retry;
+        self
+    }
+
+    /// Delay after failed attempt number `attempt` (0-based), before jitter.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = self.backoff(attempt);
+        if self.jitter && !delay.is_zero() {
+            rand::rng().random_range(delay / 2..=delay)
+        } else {
+            delay
+        }
+    }
+
+    /// Sleep before the attempt after `attempt`, or return `false` when the
+    /// attempts or the deadline are used up.
+    async fn pause(&self, attempt: u32, started: Instant, requested: Option<Duration>) -> bool {
+        if attempt + 1 >= self.max_attempts {
+            return false;
+        }
+        let delay = requested
+            .map(|d| d.min(self.max_delay))
+            .unwrap_or_else(|| self.delay(attempt));
+        if let Some(deadline) = self.deadline
+            && started.elapsed() + delay >= deadline
+        {
+            return false;
+        }
+        sleep(delay).await;
+        true
+    }
+
+    /// Run `op` until it succeeds, fails with an error `retryable` rejects,
+    /// or the policy gives up; returns the last result. `op` receives the
+    /// 0-based attempt number.
+    pub async fn run<T, E, F, Fut>(&self, mut op: F, retryable: impl Fn(&E) -> bool) -> Result<T, E>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let started = Instant::now();
+        let mut attempt = 0;
+        loop {
+            match op(attempt).await {
+                Err(e) if retryable(&e) && self.pause(attempt, started, None).await => {
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Send the request `build` returns, retrying connection failures,
+    /// timeouts and retryable statuses. The last response is returned
+    /// whatever its status, so callers report errors as before.
+    pub async fn send(
+        &self,
+        build: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, reqwest::Error> {
+        let started = Instant::now();
+        let mut attempt = 0;
+        loop {
+            let (requested, retry) = match build().send().await {
+                Ok(response) if self.retry_statuses && is_retryable_status(response.status()) => {
+                    let requested = retry_after(&response);
+                    debug!(status = %response.status(), attempt, "Retryable response");
+                    (requested, Ok(response))
+                }
+                Ok(response) => return Ok(response),
+                Err(e) if e.is_connect() || e.is_timeout() => {
+                    debug!(attempt, "Request failed, may retry: {e}");
+                    (None, Err(e))
+                }
+                Err(e) => return Err(e),
+            };
+            if !self.pause(attempt, started, requested).await {
+                return retry;
+            }
+            attempt += 1;
+        }
+    }
+}
+
+/// Statuses worth retrying: the server timed out, is rate limiting, or is
+/// temporarily unable to answer.
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::REQUEST_TIMEOUT
+            | StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// A `Retry-After` given in seconds. HTTP dates are ignored.
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    const FAST: RetryPolicy = RetryPolicy::new()
+        .max_attempts(4)
+        .base_delay(Duration::from_millis(1))
+        .jitter(false);
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy::new()
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(350));
+        let delays: Vec<_> = (0..4).map(|a| policy.backoff(a).as_millis()).collect();
+        assert_eq!(delays, [100, 200, 350, 350]);
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_millis(350));
+    }
+
+    #[test]
+    fn jitter_stays_within_half_to_full_delay() {
+        let policy = RetryPolicy::new().base_delay(Duration::from_millis(100));
+        for _ in 0..50 {
+            let d = policy.delay(1);
+            assert!(d >= Duration::from_millis(100) && d <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable_status(StatusCode::UNAUTHORIZED));
+    }
+
+    #[tokio::test]
+    async fn run_retries_until_success() {
+        let calls = AtomicU32::new(0);
+        let result: Result<u32, &str> = FAST
+            .run(
+                |attempt| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    async move {
+                        if attempt < 2 {
+                            Err("flaky")
+                        } else {
+                            Ok(attempt)
+                        }
+                    }
+                },
+                |_| true,
+            )
+            .await;
+        assert_eq!(result, Ok(2));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn run_stops_at_max_attempts_and_on_permanent_errors() {
+        let calls = AtomicU32::new(0);
+        let result: Result<(), &str> = FAST
+            .run(
+                |_| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    async { Err("down") }
+                },
+                |_| true,
+            )
+            .await;
+        assert_eq!(result, Err("down"));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        calls.store(0, Ordering::SeqCst);
+        let _: Result<(), &str> = FAST
+            .run(
+                |_| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    async { Err("bad request") }
+                },
+                |e| *e != "bad request",
+            )
+            .await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn deadline_stops_further_attempts() {
+        let policy = RetryPolicy::new()
+            .max_attempts(u32::MAX)
+            .base_delay(Duration::from_millis(20))
+            .jitter(false)
+            .deadline(Duration::from_millis(50));
+        let calls = AtomicU32::new(0);
+        let _: Result<(), ()> = policy
+            .run(
+                |_| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    async { Err(()) }
+                },
+                |_| true,
+            )
+            .await;
+        // Attempts at 0 ms and 20 ms; the next would start at 60 ms.
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
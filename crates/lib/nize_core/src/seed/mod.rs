@@ -0,0 +1,456 @@
+//! Deterministic seed data for demos and E2E tests.
+//!
+//! A [`Fixture`] declares users, conversations, documents and MCP servers
+//! in TOML or JSON. [`apply_fixture`] inserts them idempotently, keyed on
+//! natural keys (user email, conversation title and document filename per
+//! user, server name per owner), so re-seeding the same database is a no-op.
+//!
+//! Built-in fixtures are embedded at compile time and addressed by name
+//! (see [`BUILTIN_FIXTURES`]); anything else is treated as a file path.
+//!
+//! Documents are plain text given inline. They are stored in the configured
+//! blob store and chunked like an upload; embedding them is best effort,
+//! since the embedding provider may not be reachable while seeding.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::auth::{AuthError, password, queries as auth_queries};
+use crate::blobs::{BlobError, BlobStore};
+use crate::config::cache::ConfigCache;
+use crate::conversations;
+use crate::documents;
+use crate::embedding::indexer;
+use crate::ingest::{self, ExtractedPage};
+use crate::mcp::{McpError, queries as mcp_queries};
+use crate::models::mcp::{McpToolSummary, ServerConfig, VisibilityTier};
+use crate::notes::chunker::DEFAULT_MAX_CHUNK_CHARS;
+use crate::workspaces::Scope;
+
+/// Built-in fixtures, addressable by name.
+pub const BUILTIN_FIXTURES: &[(&str, &str)] = &[("demo", include_str!("fixtures/demo.toml"))];
+
+/// Errors that can occur while loading or applying fixtures.
+#[derive(Debug, Error)]
+pub enum SeedError {
+    #[error("Fixture not found: {0}")]
+    NotFound(String),
+
+    #[error("Invalid fixture: {0}")]
+    Parse(String),
+
+    #[error("Fixture references unknown user: {0}")]
+    UnknownUser(String),
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+
+    #[error("Auth error: {0}")]
+    Auth(#[from] AuthError),
+
+    #[error("MCP error: {0}")]
+    Mcp(#[from] McpError),
+
+    #[error("Blob storage error: {0}")]
+    Blob(#[from] BlobError),
+}
+
+/// A declarative seed data set.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fixture {
+    #[serde(default)]
+    pub users: Vec<SeedUser>,
+    #[serde(default)]
+    pub conversations: Vec<SeedConversation>,
+    #[serde(default)]
+    pub documents: Vec<SeedDocument>,
+    #[serde(default)]
+    pub mcp_servers: Vec<SeedServer>,
+}
+
+/// A user account; `roles` are granted on creation.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedUser {
+    pub email: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub password: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+/// A conversation owned by the user with email `user`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedConversation {
+    pub user: String,
+    pub title: String,
+    #[serde(default)]
+    pub messages: Vec<SeedMessage>,
+}
+
+/// A plain-text chat message.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedMessage {
+    pub role: String,
+    pub text: String,
+}
+
+/// A personal document of the user with email `user`, with inline text
+/// content of a text MIME type.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedDocument {
+    pub user: String,
+    pub filename: String,
+    #[serde(default = "default_document_mime_type")]
+    pub mime_type: String,
+    pub text: String,
+}
+
+fn default_document_mime_type() -> String {
+    "text/plain".into()
+}
+
+/// An MCP server registration. `owner` is required for `user` visibility.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeedServer {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub domain: String,
+    pub visibility: VisibilityTier,
+    #[serde(default)]
+    pub owner: Option<String>,
+    pub config: ServerConfig,
+    #[serde(default)]
+    pub tools: Vec<McpToolSummary>,
+}
+
+/// Counts of rows created vs. already present.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedReport {
+    pub users_created: usize,
+    pub conversations_created: usize,
+    pub documents_created: usize,
+    pub servers_created: usize,
+    pub skipped: usize,
+}
+
+/// Resolve a fixture by built-in name or file path and parse it.
+///
+/// Files ending in `.json` are parsed as JSON; everything else as TOML.
+pub fn load_fixture(name_or_path: &str) -> Result<Fixture, SeedError> {
+    if let Some((_, content)) = BUILTIN_FIXTURES.iter().find(|(n, _)| *n == name_or_path) {
+        return parse_toml(content);
+    }
+
+    let path = Path::new(name_or_path);
+    if !path.exists() {
+        return Err(SeedError::NotFound(name_or_path.to_string()));
+    }
+    let content = std::fs::read_to_string(path)?;
+    if path.extension().is_some_and(|ext| ext == "json") {
+        parse_json(&content)
+    } else {
+        parse_toml(&content)
+    }
+}
+
+/// Parse a TOML fixture.
+pub fn parse_toml(content: &str) -> Result<Fixture, SeedError> {
+    toml::from_str(content).map_err(|e| SeedError::Parse(e.to_string()))
+}
+
+/// Parse a JSON fixture.
+pub fn parse_json(content: &str) -> Result<Fixture, SeedError> {
+    serde_json::from_str(content).map_err(|e| SeedError::Parse(e.to_string()))
+}
+
+/// Insert everything in `fixture` that does not exist yet. Secret config
+/// values (blob store and embedding credentials) are decrypted with
+/// `encryption_key`.
+pub async fn apply_fixture(
+    pool: &PgPool,
+    fixture: &Fixture,
+    encryption_key: &str,
+) -> Result<SeedReport, SeedError> {
+    let mut report = SeedReport::default();
+
+    let policy = password::PasswordPolicy::load(pool).await?;
+    for user in &fixture.users {
+        if auth_queries::find_user_by_email(pool, &user.email)
+            .await?
+            .is_some()
+        {
+            report.skipped += 1;
+            continue;
+        }
+        let pw_hash = password::hash_password_with(&user.password, &policy)?;
+        let user_id =
+            auth_queries::create_user(pool, &user.email, user.name.as_deref(), &pw_hash).await?;
+        for role in &user.roles {
+            auth_queries::grant_role(pool, &user_id, role).await?;
+        }
+        report.users_created += 1;
+    }
+
+    for conv in &fixture.conversations {
+        let user_id = resolve_user(pool, &conv.user).await?;
+        if conversation_exists(pool, &user_id, &conv.title).await? {
+            report.skipped += 1;
+            continue;
+        }
+        let row = conversations::create_conversation(pool, &Scope::personal(user_id), &conv.title)
+            .await?;
+        let messages: Vec<serde_json::Value> = conv
+            .messages
+            .iter()
+            .enumerate()
+            .map(|(i, m)| message_json(i, m))
+            .collect();
+        conversations::save_messages(pool, &row.id, &messages).await?;
+        report.conversations_created += 1;
+    }
+
+    if !fixture.documents.is_empty() {
+        let cache = Arc::new(RwLock::new(ConfigCache::new()));
+        let store = BlobStore::configured(pool, &cache, encryption_key).await?;
+        for doc in &fixture.documents {
+            if !ingest::is_text_mime(&doc.mime_type) {
+                return Err(SeedError::Parse(format!(
+                    "document '{}' has non-text MIME type {}",
+                    doc.filename, doc.mime_type
+                )));
+            }
+            let user_id = resolve_user(pool, &doc.user).await?;
+            if document_exists(pool, &user_id, &doc.filename).await? {
+                report.skipped += 1;
+                continue;
+            }
+            let blob = store.put_bytes(doc.text.clone().into_bytes(), None).await?;
+            let row = documents::create_document(
+                pool,
+                &Scope::personal(user_id),
+                &doc.filename,
+                &doc.mime_type,
+                &blob,
+                store.backend(),
+            )
+            .await?;
+            let page = ExtractedPage {
+                page: None,
+                text: doc.text.clone(),
+                ocr_confidence: None,
+            };
+            let chunks = ingest::chunk_pages(&[page], DEFAULT_MAX_CHUNK_CHARS);
+            documents::replace_chunks(pool, &row.id, &chunks).await?;
+            if let Err(e) = indexer::embed_document(pool, &cache, &row.id, encryption_key).await {
+                warn!(document = %doc.filename, "seeded document not embedded: {e}");
+            }
+            report.documents_created += 1;
+        }
+    }
+
+    for server in &fixture.mcp_servers {
+        let owner_id = match &server.owner {
+            Some(email) => Some(resolve_user(pool, email).await?),
+            None => None,
+        };
+        if server_exists(pool, &server.name, owner_id.as_ref()).await? {
+            report.skipped += 1;
+            continue;
+        }
+
+        let mut tx = pool.begin().await?;
+        let row = match (&server.visibility, owner_id) {
+            (VisibilityTier::User, Some(owner_id)) => {
+                mcp_queries::insert_user_server(
+                    &mut *tx,
+                    &owner_id.to_string(),
+                    &server.name,
+                    &server.description,
+                    &server.domain,
+                    &server.config,
+                    None,
+                    true,
+                )
+                .await?
+            }
+            (VisibilityTier::User, None) => {
+                return Err(SeedError::Parse(format!(
+                    "server '{}' has user visibility but no owner",
+                    server.name
+                )));
+            }
+            (visibility, _) => {
+                let config_json = serde_json::to_value(&server.config)
+                    .map_err(|e| SeedError::Parse(e.to_string()))?;
+                mcp_queries::insert_built_in_server(
+                    &mut *tx,
+                    &server.name,
+                    &server.description,
+                    &server.domain,
+                    server.config.endpoint(),
+                    visibility,
+                    &server.config.transport_type(),
+                    Some(&config_json),
+                    None,
+                    true,
+                )
+                .await?
+            }
+        };
+        mcp_queries::replace_server_tools(&mut tx, &row.id.to_string(), &server.tools).await?;
+        tx.commit().await?;
+        report.servers_created += 1;
+    }
+
+    Ok(report)
+}
+
+/// Build a UI message (`{id, role, parts}`) as stored by the chat frontend.
+fn message_json(index: usize, message: &SeedMessage) -> serde_json::Value {
+    serde_json::json!({
+        "id": format!("seed-{index}"),
+        "role": message.role,
+        "parts": [{ "type": "text", "text": message.text }],
+    })
+}
+
+async fn resolve_user(pool: &PgPool, email: &str) -> Result<Uuid, SeedError> {
+    let (id, _, _) = auth_queries::find_user_by_email(pool, email)
+        .await?
+        .ok_or_else(|| SeedError::UnknownUser(email.to_string()))?;
+    Uuid::parse_str(&id).map_err(|e| SeedError::Parse(e.to_string()))
+}
+
+async fn conversation_exists(
+    pool: &PgPool,
+    user_id: &Uuid,
+    title: &str,
+) -> Result<bool, SeedError> {
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM conversations WHERE user_id = $1 AND title = $2)",
+    )
+    .bind(user_id)
+    .bind(title)
+    .fetch_one(pool)
+    .await?;
+    Ok(exists)
+}
+
+async fn document_exists(pool: &PgPool, user_id: &Uuid, filename: &str) -> Result<bool, SeedError> {
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM documents \
+         WHERE user_id = $1 AND workspace_id IS NULL AND filename = $2)",
+    )
+    .bind(user_id)
+    .bind(filename)
+    .fetch_one(pool)
+    .await?;
+    Ok(exists)
+}
+
+async fn server_exists(
+    pool: &PgPool,
+    name: &str,
+    owner_id: Option<&Uuid>,
+) -> Result<bool, SeedError> {
+    let exists = sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM mcp_servers WHERE name = $1 AND owner_id IS NOT DISTINCT FROM $2)",
+    )
+    .bind(name)
+    .bind(owner_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(exists)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_demo_fixture_parses() {
+        let fixture = load_fixture("demo").expect("demo fixture");
+        assert!(!fixture.users.is_empty());
+        assert!(!fixture.conversations.is_empty());
+        assert!(!fixture.documents.is_empty());
+        assert!(!fixture.mcp_servers.is_empty());
+    }
+
+    #[test]
+    fn demo_fixture_references_only_declared_users() {
+        let fixture = load_fixture("demo").unwrap();
+        let emails: Vec<&str> = fixture.users.iter().map(|u| u.email.as_str()).collect();
+        for conv in &fixture.conversations {
+            assert!(emails.contains(&conv.user.as_str()), "{}", conv.user);
+        }
+        for doc in &fixture.documents {
+            assert!(emails.contains(&doc.user.as_str()), "{}", doc.user);
+            assert!(ingest::is_text_mime(&doc.mime_type), "{}", doc.mime_type);
+        }
+        for server in &fixture.mcp_servers {
+            if let Some(owner) = &server.owner {
+                assert!(emails.contains(&owner.as_str()), "{owner}");
+            }
+        }
+    }
+
+    #[test]
+    fn json_fixture_parses() {
+        let fixture = parse_json(
+            r#"{
+                "users": [{ "email": "a@b.c", "password": "password123", "roles": ["admin"] }],
+                "mcp_servers": [{
+                    "name": "S", "domain": "d", "visibility": "visible",
+                    "config": { "transport": "http", "url": "http://x", "authType": "none" }
+                }]
+            }"#,
+        )
+        .expect("json fixture");
+        assert_eq!(fixture.users[0].roles, vec!["admin".to_string()]);
+        assert_eq!(fixture.mcp_servers[0].visibility, VisibilityTier::Visible);
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let err = parse_toml("[[users]]\nemail = \"a@b.c\"\npassword = \"x\"\nrole = \"admin\"\n")
+            .unwrap_err();
+        assert!(matches!(err, SeedError::Parse(_)));
+    }
+
+    #[test]
+    fn missing_fixture_is_not_found() {
+        let err = load_fixture("no-such-fixture").unwrap_err();
+        assert!(matches!(err, SeedError::NotFound(_)));
+    }
+
+    #[test]
+    fn message_json_uses_ui_message_shape() {
+        let msg = message_json(
+            2,
+            &SeedMessage {
+                role: "user".into(),
+                text: "hi".into(),
+            },
+        );
+        assert_eq!(msg["id"], "seed-2");
+        assert_eq!(msg["parts"][0]["text"], "hi");
+    }
+}
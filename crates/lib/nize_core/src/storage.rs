@@ -0,0 +1,337 @@
+//! Database storage monitoring and maintenance.
+//!
+//! Embedded PGlite databases on desktops grow without bound: tool execution
+//! traces, config audit entries and dead tuples accumulate, and blob records
+//! can outlive their documents. [`measure`] reports the size of the data
+//! directory (when the database is local), the database and the stored
+//! blobs, graded against the `system.storage.*` soft limits;
+//! [`run_maintenance`] reclaims space on demand.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::blobs::{self, BlobError, BlobStore};
+use crate::config::ConfigError;
+use crate::config::cache::ConfigCache;
+use crate::config::resolver;
+
+/// Size in MiB at which storage is reported as [`StorageLevel::Warning`]
+/// (`0` disables the level).
+pub const WARN_MB_CONFIG_KEY: &str = "system.storage.warnMb";
+/// Size in MiB at which storage is reported as [`StorageLevel::Critical`]
+/// (`0` disables the level).
+pub const CRITICAL_MB_CONFIG_KEY: &str = "system.storage.criticalMb";
+
+const MIB: u64 = 1024 * 1024;
+
+/// Errors that can occur in storage operations.
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+
+    #[error("IO error reading {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Config error: {0}")]
+    Config(#[from] ConfigError),
+
+    #[error("Blob error: {0}")]
+    Blob(#[from] BlobError),
+}
+
+/// How close storage is to the configured limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageLevel {
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl StorageLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StorageLevel::Ok => "ok",
+            StorageLevel::Warning => "warning",
+            StorageLevel::Critical => "critical",
+        }
+    }
+}
+
+/// Soft limits in bytes; `None` when disabled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageThresholds {
+    pub warn_bytes: Option<u64>,
+    pub critical_bytes: Option<u64>,
+}
+
+impl StorageThresholds {
+    /// Read the thresholds from system config.
+    pub async fn configured(
+        pool: &PgPool,
+        cache: &Arc<RwLock<ConfigCache>>,
+    ) -> Result<Self, StorageError> {
+        let mb = |value: String| {
+            value
+                .trim()
+                .parse::<u64>()
+                .ok()
+                .filter(|mb| *mb > 0)
+                .map(|mb| mb * MIB)
+        };
+        Ok(Self {
+            warn_bytes: mb(resolver::get_system_value(pool, cache, WARN_MB_CONFIG_KEY).await?),
+            critical_bytes: mb(
+                resolver::get_system_value(pool, cache, CRITICAL_MB_CONFIG_KEY).await?,
+            ),
+        })
+    }
+
+    /// Grade a total size.
+    pub fn level(&self, total_bytes: u64) -> StorageLevel {
+        if self
+            .critical_bytes
+            .is_some_and(|limit| total_bytes >= limit)
+        {
+            StorageLevel::Critical
+        } else if self.warn_bytes.is_some_and(|limit| total_bytes >= limit) {
+            StorageLevel::Warning
+        } else {
+            StorageLevel::Ok
+        }
+    }
+}
+
+/// Storage usage at a point in time.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageReport {
+    /// Local database data directory; `None` for an external server.
+    pub data_dir: Option<PathBuf>,
+    /// Size of `data_dir` on disk (WAL and indexes included).
+    pub data_dir_bytes: Option<u64>,
+    /// `pg_database_size` of the current database.
+    pub database_bytes: u64,
+    /// Sum of recorded blob sizes, in whichever backend they live.
+    pub blob_bytes: u64,
+    /// What the thresholds are compared against: the data directory when
+    /// known, otherwise the database.
+    pub total_bytes: u64,
+    pub level: StorageLevel,
+    pub thresholds: StorageThresholds,
+    pub measured_at: DateTime<Utc>,
+}
+
+/// Size of everything under `path`, not following symlinks. A missing
+/// directory counts as empty.
+pub fn dir_size(path: &Path) -> std::io::Result<u64> {
+    let mut total = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                // Removed between listing and stat (e.g. a finished WAL segment).
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else if metadata.is_file() {
+                total += metadata.len();
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// Measure storage usage. `data_dir` is the local data directory, if any.
+pub async fn measure(
+    pool: &PgPool,
+    cache: &Arc<RwLock<ConfigCache>>,
+    data_dir: Option<&Path>,
+) -> Result<StorageReport, StorageError> {
+    let data_dir_bytes = match data_dir {
+        Some(dir) => {
+            let path = dir.to_path_buf();
+            let size = tokio::task::spawn_blocking(move || dir_size(&path))
+                .await
+                .map_err(|e| StorageError::Io {
+                    path: dir.to_path_buf(),
+                    source: std::io::Error::other(e),
+                })?
+                .map_err(|source| StorageError::Io {
+                    path: dir.to_path_buf(),
+                    source,
+                })?;
+            Some(size)
+        }
+        None => None,
+    };
+    let (database_bytes, blob_bytes) = sqlx::query_as::<_, (i64, i64)>(
+        r#"
+        SELECT pg_database_size(current_database()),
+               COALESCE((SELECT SUM(size_bytes) FROM blobs), 0)::BIGINT
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+    let database_bytes = database_bytes.max(0) as u64;
+    let total_bytes = data_dir_bytes.unwrap_or(database_bytes);
+    let thresholds = StorageThresholds::configured(pool, cache).await?;
+
+    Ok(StorageReport {
+        data_dir: data_dir.map(Path::to_path_buf),
+        data_dir_bytes,
+        database_bytes,
+        blob_bytes: blob_bytes.max(0) as u64,
+        total_bytes,
+        level: thresholds.level(total_bytes),
+        thresholds,
+        measured_at: Utc::now(),
+    })
+}
+
+/// Maintenance operations to run; all are off by default.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MaintenanceRequest {
+    /// `VACUUM (ANALYZE)` the database, returning dead tuples to the
+    /// free space map.
+    pub vacuum: bool,
+    /// Delete blobs no document references anymore.
+    pub orphaned_blobs: bool,
+    /// Delete tool execution traces older than this many days.
+    pub trace_retention_days: Option<u32>,
+    /// Delete MCP config audit entries older than this many days.
+    pub audit_retention_days: Option<u32>,
+}
+
+/// What [`run_maintenance`] did.
+#[derive(Debug, Clone, Default, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceReport {
+    pub vacuumed: bool,
+    pub orphaned_blobs_deleted: u64,
+    pub traces_deleted: u64,
+    pub audit_entries_deleted: u64,
+}
+
+/// Delete tool execution traces older than `days`.
+pub async fn prune_tool_executions(pool: &PgPool, days: u32) -> Result<u64, StorageError> {
+    let result = sqlx::query(
+        "DELETE FROM mcp_tool_executions WHERE created_at < now() - make_interval(days => $1)",
+    )
+    .bind(days as i32)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Delete MCP config audit entries older than `days`.
+pub async fn prune_config_audit(pool: &PgPool, days: u32) -> Result<u64, StorageError> {
+    let result = sqlx::query(
+        "DELETE FROM mcp_config_audit WHERE created_at < now() - make_interval(days => $1)",
+    )
+    .bind(days as i32)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// `VACUUM (ANALYZE)` the whole database. Runs outside a transaction, as
+/// `VACUUM` requires.
+pub async fn vacuum(pool: &PgPool) -> Result<(), StorageError> {
+    sqlx::raw_sql("VACUUM (ANALYZE)").execute(pool).await?;
+    Ok(())
+}
+
+/// Run the requested operations: pruning first, so the vacuum reclaims
+/// the rows it deleted. Orphaned blob bytes that fail to delete are logged
+/// and left for the next run of the backend's own cleanup.
+pub async fn run_maintenance(
+    pool: &PgPool,
+    cache: &Arc<RwLock<ConfigCache>>,
+    encryption_key: &str,
+    request: &MaintenanceRequest,
+) -> Result<MaintenanceReport, StorageError> {
+    let mut report = MaintenanceReport::default();
+    if let Some(days) = request.trace_retention_days {
+        report.traces_deleted = prune_tool_executions(pool, days).await?;
+    }
+    if let Some(days) = request.audit_retention_days {
+        report.audit_entries_deleted = prune_config_audit(pool, days).await?;
+    }
+    if request.orphaned_blobs {
+        let released = blobs::release_unreferenced_blobs(pool).await?;
+        report.orphaned_blobs_deleted = released.len() as u64;
+        for (sha256, backend) in released {
+            let result = match BlobStore::from_config(pool, cache, encryption_key, backend).await {
+                Ok(store) => store.delete(&sha256).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                tracing::warn!(sha256 = %sha256, error = %e, "failed to delete orphaned blob");
+            }
+        }
+    }
+    if request.vacuum {
+        vacuum(pool).await?;
+        report.vacuumed = true;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_follow_the_enabled_thresholds() {
+        let thresholds = StorageThresholds {
+            warn_bytes: Some(100),
+            critical_bytes: Some(200),
+        };
+        assert_eq!(thresholds.level(99), StorageLevel::Ok);
+        assert_eq!(thresholds.level(100), StorageLevel::Warning);
+        assert_eq!(thresholds.level(250), StorageLevel::Critical);
+
+        let critical_only = StorageThresholds {
+            warn_bytes: None,
+            critical_bytes: Some(200),
+        };
+        assert_eq!(critical_only.level(150), StorageLevel::Ok);
+        assert_eq!(
+            StorageThresholds::default().level(u64::MAX),
+            StorageLevel::Ok
+        );
+    }
+
+    #[test]
+    fn dir_size_sums_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a"), [0u8; 10]).unwrap();
+        std::fs::create_dir(dir.path().join("base")).unwrap();
+        std::fs::write(dir.path().join("base").join("b"), [0u8; 32]).unwrap();
+        assert_eq!(dir_size(dir.path()).unwrap(), 42);
+        assert_eq!(dir_size(&dir.path().join("missing")).unwrap(), 0);
+    }
+}
@@ -0,0 +1,720 @@
+//! Delta sync for offline-capable clients.
+//!
+//! A client keeps a local copy of the user's personal conversations (with
+//! their messages), documents (metadata only) and config overrides, and
+//! catches up with [`pull`]: every change since its [`Cursor`], deletions
+//! included. Local edits come back through [`apply_conversation`] and the
+//! `delete_*` functions, which answer [`Outcome::Conflict`] instead of
+//! writing when the server copy has moved on from the version the edit
+//! was based on.
+//!
+//! Every synced row records the transaction that last wrote it, and a
+//! deleted row leaves a tombstone (migration `0048_sync`). Versions are
+//! those transaction ids. A pull only returns changes of transactions that
+//! had finished when it ran, so the cursor it hands out never passes a
+//! change that commits later. Tombstones are kept for
+//! [`TOMBSTONE_TTL_DAYS`]; a cursor older than the newest pruned one gets
+//! a full resync flagged with [`ChangeSet::reset`].
+//!
+//! Secret config values are not synced.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::conversations::{self, CONVERSATION_COLUMNS, ConversationRow};
+use crate::documents::{DOCUMENT_COLUMNS, DocumentRow};
+use crate::i18n::Message;
+use crate::permissions::{self, ResourceType};
+use crate::tags::{self, TagResourceType};
+
+/// Days a tombstone is kept.
+pub const TOMBSTONE_TTL_DAYS: i32 = 90;
+
+/// Most changes returned by one [`pull`].
+pub const MAX_PAGE_SIZE: i64 = 500;
+
+/// Longest conversation title.
+const MAX_TITLE_CHARS: usize = 500;
+
+/// Errors that can occur in sync operations.
+#[derive(Debug, Error)]
+pub enum SyncError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// A kind of synced record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Entity {
+    Conversation,
+    Document,
+    Config,
+}
+
+impl Entity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Entity::Conversation => "conversation",
+            Entity::Document => "document",
+            Entity::Config => "config",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "conversation" => Some(Entity::Conversation),
+            "document" => Some(Entity::Document),
+            "config" => Some(Entity::Config),
+            _ => None,
+        }
+    }
+}
+
+/// A position in the change feed: after the change of `entity`/`id` by
+/// transaction `txid`. Clients treat the text form as opaque.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    txid: i64,
+    entity: String,
+    id: String,
+}
+
+impl Cursor {
+    /// Parse the text form handed out by [`pull`].
+    pub fn parse(s: &str) -> Result<Self, SyncError> {
+        let mut parts = s.splitn(3, ':');
+        let txid = parts
+            .next()
+            .and_then(|t| t.parse::<i64>().ok())
+            .filter(|t| *t >= 0);
+        match (txid, parts.next(), parts.next()) {
+            (Some(txid), Some(entity), Some(id))
+                if entity.is_empty() || Entity::parse(entity).is_some() =>
+            {
+                Ok(Self {
+                    txid,
+                    entity: entity.to_string(),
+                    id: id.to_string(),
+                })
+            }
+            _ => Err(SyncError::Validation(
+                Message::new("sync.invalid_cursor").arg("cursor", s),
+            )),
+        }
+    }
+
+    /// Past every change of transactions before `txid`.
+    fn before(txid: i64) -> Self {
+        Self {
+            txid,
+            entity: String::new(),
+            id: String::new(),
+        }
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.txid, self.entity, self.id)
+    }
+}
+
+/// A personal conversation with its messages.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SyncedConversation {
+    #[sqlx(flatten)]
+    pub row: ConversationRow,
+    pub version: i64,
+    #[sqlx(skip)]
+    pub messages: Vec<serde_json::Value>,
+}
+
+/// A document's metadata.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SyncedDocument {
+    #[sqlx(flatten)]
+    pub row: DocumentRow,
+    pub version: i64,
+}
+
+/// A config override of the user.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SyncedConfig {
+    pub key: String,
+    pub value: String,
+    pub updated_at: DateTime<Utc>,
+    pub version: i64,
+}
+
+/// A record deleted since the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deletion {
+    pub entity: Entity,
+    pub id: String,
+}
+
+/// One page of changes. Each record appears once, in its latest state.
+#[derive(Debug, Clone)]
+pub struct ChangeSet {
+    pub conversations: Vec<SyncedConversation>,
+    pub documents: Vec<SyncedDocument>,
+    pub config: Vec<SyncedConfig>,
+    pub deleted: Vec<Deletion>,
+    /// Where the next pull continues.
+    pub cursor: Cursor,
+    /// More changes are waiting past `cursor`.
+    pub has_more: bool,
+    /// The given cursor was too old: this is the start of a full resync,
+    /// and records the client holds that it does not return are gone.
+    pub reset: bool,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct ChangeRef {
+    txid: i64,
+    entity: String,
+    entity_id: String,
+    deleted: bool,
+}
+
+/// Changes to `user_id`'s records since `cursor`, or everything when it is
+/// `None`, at most `limit` per page.
+pub async fn pull(
+    pool: &PgPool,
+    user_id: &Uuid,
+    cursor: Option<&Cursor>,
+    limit: i64,
+) -> Result<ChangeSet, SyncError> {
+    prune_tombstones(pool).await?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+    // Every transaction before the horizon has finished, and this
+    // snapshot sees all of those that committed.
+    let (horizon, pruned_before) = sqlx::query_as::<_, (i64, i64)>(
+        "SELECT pg_snapshot_xmin(pg_current_snapshot())::text::bigint, pruned_before FROM sync_state",
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let reset = cursor.is_some_and(|c| c.txid < pruned_before);
+    let cursor = cursor.filter(|_| !reset);
+    let start = cursor.cloned().unwrap_or_else(|| Cursor::before(0));
+    let limit = limit.clamp(1, MAX_PAGE_SIZE);
+
+    let mut refs = sqlx::query_as::<_, ChangeRef>(
+        r#"
+        SELECT txid::text::bigint AS txid, entity, entity_id, deleted
+        FROM (
+            SELECT c.change_txid AS txid, 'conversation' AS entity, c.id::text AS entity_id, false AS deleted
+            FROM conversations c
+            WHERE c.user_id = $1 AND c.workspace_id IS NULL
+            UNION ALL
+            SELECT d.change_txid, 'document', d.id::text, false
+            FROM documents d
+            WHERE d.user_id = $1 AND d.workspace_id IS NULL
+            UNION ALL
+            SELECT v.change_txid, 'config', v.key, false
+            FROM config_values v
+            JOIN config_definitions def ON def.key = v.key
+            WHERE v.scope = 'user-override' AND v.user_id = $1 AND def.display_type <> 'secret'
+            UNION ALL
+            SELECT t.change_txid, t.entity, t.entity_id, true
+            FROM sync_tombstones t
+            WHERE t.user_id = $1
+              AND $6
+              AND NOT (t.entity = 'config' AND t.entity_id IN (
+                SELECT key FROM config_definitions WHERE display_type = 'secret'
+              ))
+        ) changes
+        WHERE (txid, entity, entity_id) > ($2::text::xid8, $3, $4)
+          AND txid < $5::text::xid8
+        ORDER BY txid, entity, entity_id
+        LIMIT $7
+        "#,
+    )
+    .bind(user_id)
+    .bind(start.txid.to_string())
+    .bind(&start.entity)
+    .bind(&start.id)
+    .bind(horizon.to_string())
+    .bind(cursor.is_some())
+    .bind(limit + 1)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let has_more = refs.len() as i64 > limit;
+    refs.truncate(limit as usize);
+    let next = match refs.last() {
+        Some(last) if has_more => Cursor {
+            txid: last.txid,
+            entity: last.entity.clone(),
+            id: last.entity_id.clone(),
+        },
+        _ => Cursor::before(horizon.max(start.txid)),
+    };
+
+    let mut ids: HashMap<Entity, Vec<String>> = HashMap::new();
+    let mut deleted = Vec::new();
+    for change in latest_changes(refs) {
+        let Some(entity) = Entity::parse(&change.entity) else {
+            continue;
+        };
+        if change.deleted {
+            deleted.push(Deletion {
+                entity,
+                id: change.entity_id,
+            });
+        } else {
+            ids.entry(entity).or_default().push(change.entity_id);
+        }
+    }
+    let uuids = |entity: Entity| -> Vec<Uuid> {
+        ids.get(&entity)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| Uuid::parse_str(id).ok())
+            .collect()
+    };
+    let conversation_ids = uuids(Entity::Conversation);
+    let document_ids = uuids(Entity::Document);
+    let config_keys = ids.get(&Entity::Config).cloned().unwrap_or_default();
+
+    let conversations = load_conversations(&mut tx, user_id, &conversation_ids).await?;
+    let documents = load_documents(&mut tx, user_id, &document_ids).await?;
+    let config = load_config(&mut tx, user_id, &config_keys).await?;
+    tx.commit().await?;
+
+    Ok(ChangeSet {
+        conversations,
+        documents,
+        config,
+        deleted,
+        cursor: next,
+        has_more,
+        reset,
+    })
+}
+
+/// Keep the last change of each record, in feed order.
+fn latest_changes(refs: Vec<ChangeRef>) -> Vec<ChangeRef> {
+    let mut last: HashMap<(String, String), usize> = HashMap::new();
+    for (i, change) in refs.iter().enumerate() {
+        last.insert((change.entity.clone(), change.entity_id.clone()), i);
+    }
+    refs.into_iter()
+        .enumerate()
+        .filter(|(i, change)| {
+            last.get(&(change.entity.clone(), change.entity_id.clone())) == Some(i)
+        })
+        .map(|(_, change)| change)
+        .collect()
+}
+
+/// Drop expired tombstones and remember the newest one dropped.
+async fn prune_tombstones(pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        WITH pruned AS (
+            DELETE FROM sync_tombstones
+            WHERE deleted_at < now() - make_interval(days => $1)
+            RETURNING change_txid
+        )
+        UPDATE sync_state
+        SET pruned_before = GREATEST(
+            pruned_before,
+            (SELECT MAX(change_txid::text::bigint) + 1 FROM pruned)
+        )
+        WHERE EXISTS (SELECT 1 FROM pruned)
+        "#,
+    )
+    .bind(TOMBSTONE_TTL_DAYS)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+async fn load_conversations(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    ids: &[Uuid],
+) -> Result<Vec<SyncedConversation>, sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut rows = sqlx::query_as::<_, SyncedConversation>(&format!(
+        r#"
+        SELECT {CONVERSATION_COLUMNS}, c.change_txid::text::bigint AS version
+        FROM conversations c
+        WHERE c.id = ANY($1) AND c.user_id = $2 AND c.workspace_id IS NULL
+        ORDER BY c.change_txid, c.id
+        "#
+    ))
+    .bind(ids)
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let messages = sqlx::query_as::<_, (Uuid, serde_json::Value)>(
+        r#"
+        SELECT conversation_id, message_data
+        FROM messages
+        WHERE conversation_id = ANY($1)
+        ORDER BY conversation_id, sort_order
+        "#,
+    )
+    .bind(ids)
+    .fetch_all(&mut *conn)
+    .await?;
+    let mut by_conversation: HashMap<Uuid, Vec<serde_json::Value>> = HashMap::new();
+    for (conversation_id, message) in messages {
+        by_conversation
+            .entry(conversation_id)
+            .or_default()
+            .push(message);
+    }
+    for row in &mut rows {
+        row.messages = by_conversation.remove(&row.row.id).unwrap_or_default();
+    }
+    Ok(rows)
+}
+
+async fn load_documents(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    ids: &[Uuid],
+) -> Result<Vec<SyncedDocument>, sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    sqlx::query_as::<_, SyncedDocument>(&format!(
+        r#"
+        SELECT {DOCUMENT_COLUMNS}, change_txid::text::bigint AS version
+        FROM documents
+        WHERE id = ANY($1) AND user_id = $2 AND workspace_id IS NULL
+        ORDER BY change_txid, id
+        "#
+    ))
+    .bind(ids)
+    .bind(user_id)
+    .fetch_all(conn)
+    .await
+}
+
+async fn load_config(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    keys: &[String],
+) -> Result<Vec<SyncedConfig>, sqlx::Error> {
+    if keys.is_empty() {
+        return Ok(Vec::new());
+    }
+    sqlx::query_as::<_, SyncedConfig>(
+        r#"
+        SELECT v.key, v.value, v.updated_at, v.change_txid::text::bigint AS version
+        FROM config_values v
+        JOIN config_definitions def ON def.key = v.key
+        WHERE v.key = ANY($1)
+          AND v.scope = 'user-override'
+          AND v.user_id = $2
+          AND def.display_type <> 'secret'
+        ORDER BY v.change_txid, v.key
+        "#,
+    )
+    .bind(keys)
+    .bind(user_id)
+    .fetch_all(conn)
+    .await
+}
+
+/// The user's personal conversation `id`, if it exists.
+pub async fn get_conversation(
+    pool: &PgPool,
+    user_id: &Uuid,
+    id: &Uuid,
+) -> Result<Option<SyncedConversation>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    Ok(load_conversations(&mut conn, user_id, &[*id]).await?.pop())
+}
+
+/// The user's document `id`, if it exists.
+pub async fn get_document(
+    pool: &PgPool,
+    user_id: &Uuid,
+    id: &Uuid,
+) -> Result<Option<SyncedDocument>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    Ok(load_documents(&mut conn, user_id, &[*id]).await?.pop())
+}
+
+/// The user's override of config `key`, if set and not a secret.
+pub async fn get_config(
+    pool: &PgPool,
+    user_id: &Uuid,
+    key: &str,
+) -> Result<Option<SyncedConfig>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    Ok(load_config(&mut conn, user_id, &[key.to_string()])
+        .await?
+        .pop())
+}
+
+/// What a client change expects of the server copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precondition {
+    /// The edit was made to this version.
+    Version(i64),
+    /// The client created the record.
+    Absent,
+    /// Write over whatever is there.
+    Any,
+}
+
+impl Precondition {
+    /// Whether a record at `current` (`None` when missing) may be written.
+    pub fn holds(self, current: Option<i64>) -> bool {
+        match (self, current) {
+            (Precondition::Any, _) => true,
+            (Precondition::Absent, None) => true,
+            (Precondition::Version(base), Some(current)) => base == current,
+            _ => false,
+        }
+    }
+}
+
+/// What became of a client change.
+#[derive(Debug, Clone)]
+pub enum Outcome<T> {
+    /// Written; the record's new version, `None` once deleted.
+    Applied(Option<i64>),
+    /// Not written: the server copy moved on. Holds the server copy,
+    /// `None` when it was deleted.
+    Conflict(Option<T>),
+}
+
+/// Client edits to a conversation; `None` leaves a field as it is.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationChange {
+    pub title: Option<String>,
+    /// Replaces all messages.
+    pub messages: Option<Vec<serde_json::Value>>,
+}
+
+/// Lock conversation `id` and return its version, failing when it exists
+/// but is not a personal conversation of `user_id`.
+async fn lock_conversation(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    id: &Uuid,
+) -> Result<Option<i64>, SyncError> {
+    let row = sqlx::query_as::<_, (Uuid, Option<Uuid>, i64)>(
+        r#"
+        SELECT user_id, workspace_id, change_txid::text::bigint
+        FROM conversations
+        WHERE id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await?;
+    match row {
+        None => Ok(None),
+        Some((owner, None, version)) if owner == *user_id => Ok(Some(version)),
+        Some(_) => Err(SyncError::Validation(
+            Message::new("sync.not_personal_conversation").arg("id", id),
+        )),
+    }
+}
+
+/// Create or update the user's personal conversation `id`.
+pub async fn apply_conversation(
+    pool: &PgPool,
+    user_id: &Uuid,
+    id: &Uuid,
+    precondition: Precondition,
+    change: &ConversationChange,
+) -> Result<Outcome<SyncedConversation>, SyncError> {
+    let title = change.title.as_deref().map(str::trim);
+    if let Some(title) = title {
+        if title.is_empty() {
+            return Err(SyncError::Validation(Message::new("sync.title_required")));
+        }
+        if title.chars().count() > MAX_TITLE_CHARS {
+            return Err(SyncError::Validation(
+                Message::new("sync.title_too_long").arg("max", MAX_TITLE_CHARS),
+            ));
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    let current = lock_conversation(&mut tx, user_id, id).await?;
+    if !precondition.holds(current) {
+        tx.rollback().await?;
+        return Ok(Outcome::Conflict(
+            get_conversation(pool, user_id, id).await?,
+        ));
+    }
+
+    if current.is_none() {
+        sqlx::query("INSERT INTO conversations (id, user_id, title) VALUES ($1, $2, $3)")
+            .bind(id)
+            .bind(user_id)
+            .bind(title.unwrap_or("New Chat"))
+            .execute(&mut *tx)
+            .await?;
+    } else if let Some(title) = title {
+        sqlx::query("UPDATE conversations SET title = $2, updated_at = now() WHERE id = $1")
+            .bind(id)
+            .bind(title)
+            .execute(&mut *tx)
+            .await?;
+    }
+    if let Some(messages) = &change.messages {
+        conversations::write_messages(&mut tx, id, messages).await?;
+    }
+
+    let version = sqlx::query_scalar::<_, i64>(
+        "SELECT change_txid::text::bigint FROM conversations WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(Outcome::Applied(Some(version)))
+}
+
+/// Delete the user's personal conversation `id`. Deleting one that is
+/// already gone succeeds.
+pub async fn delete_conversation(
+    pool: &PgPool,
+    user_id: &Uuid,
+    id: &Uuid,
+    precondition: Precondition,
+) -> Result<Outcome<SyncedConversation>, SyncError> {
+    let mut tx = pool.begin().await?;
+    let Some(current) = lock_conversation(&mut tx, user_id, id).await? else {
+        return Ok(Outcome::Applied(None));
+    };
+    if !precondition.holds(Some(current)) {
+        tx.rollback().await?;
+        return Ok(Outcome::Conflict(
+            get_conversation(pool, user_id, id).await?,
+        ));
+    }
+    sqlx::query("DELETE FROM conversations WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tags::clear_resource_tags(&mut tx, TagResourceType::Conversation, id).await?;
+    permissions::clear_resource_shares(&mut tx, ResourceType::Conversation, id).await?;
+    tx.commit().await?;
+    Ok(Outcome::Applied(None))
+}
+
+/// Delete the user's document `id`, returning its row with the outcome so
+/// the caller can release the blob. Deleting one that is already gone
+/// succeeds.
+pub async fn delete_document(
+    pool: &PgPool,
+    user_id: &Uuid,
+    id: &Uuid,
+    precondition: Precondition,
+) -> Result<(Outcome<SyncedDocument>, Option<DocumentRow>), SyncError> {
+    let mut tx = pool.begin().await?;
+    let current = sqlx::query_scalar::<_, i64>(
+        "SELECT change_txid::text::bigint FROM documents \
+         WHERE id = $1 AND user_id = $2 AND workspace_id IS NULL FOR UPDATE",
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(current) = current else {
+        return Ok((Outcome::Applied(None), None));
+    };
+    if !precondition.holds(Some(current)) {
+        tx.rollback().await?;
+        let server = get_document(pool, user_id, id).await?;
+        return Ok((Outcome::Conflict(server), None));
+    }
+    let row = sqlx::query_as::<_, DocumentRow>(&format!(
+        "DELETE FROM documents WHERE id = $1 RETURNING {DOCUMENT_COLUMNS}"
+    ))
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok((Outcome::Applied(None), Some(row)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(txid: i64, entity: &str, id: &str, deleted: bool) -> ChangeRef {
+        ChangeRef {
+            txid,
+            entity: entity.to_string(),
+            entity_id: id.to_string(),
+            deleted,
+        }
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursor = Cursor::parse("42:config:ui.theme").unwrap();
+        assert_eq!(cursor.txid, 42);
+        assert_eq!(cursor.entity, "config");
+        assert_eq!(cursor.id, "ui.theme");
+        assert_eq!(cursor.to_string(), "42:config:ui.theme");
+        assert_eq!(Cursor::parse("42::").unwrap(), Cursor::before(42));
+    }
+
+    #[test]
+    fn cursor_rejects_garbage() {
+        for bad in ["", "42", "x:config:a", "-1::", "42:widget:a"] {
+            assert!(Cursor::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn latest_changes_keeps_last_change_per_record() {
+        let out = latest_changes(vec![
+            change(1, "config", "a", false),
+            change(2, "conversation", "x", false),
+            change(3, "config", "a", true),
+            change(4, "config", "b", false),
+        ]);
+        let out: Vec<_> = out
+            .iter()
+            .map(|c| (c.txid, c.entity_id.as_str(), c.deleted))
+            .collect();
+        assert_eq!(out, vec![(2, "x", false), (3, "a", true), (4, "b", false)]);
+    }
+
+    #[test]
+    fn preconditions() {
+        assert!(Precondition::Any.holds(None));
+        assert!(Precondition::Any.holds(Some(7)));
+        assert!(Precondition::Absent.holds(None));
+        assert!(!Precondition::Absent.holds(Some(7)));
+        assert!(Precondition::Version(7).holds(Some(7)));
+        assert!(!Precondition::Version(6).holds(Some(7)));
+        assert!(!Precondition::Version(7).holds(None));
+    }
+}
@@ -0,0 +1,475 @@
+//! Personal tags shared across documents, conversations and notes.
+//!
+//! A tag belongs to one user and is attached to resources through the
+//! polymorphic `tag_assignments` table. Tags only organize the owner's own
+//! resources — they are never consulted for permission checks.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Maximum tag name length in characters.
+pub const MAX_TAG_NAME_CHARS: usize = 64;
+
+/// Errors that can occur in tag operations.
+#[derive(Debug, Error)]
+pub enum TagError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Not found: {0}")]
+    NotFound(Message),
+
+    #[error("Tag already exists: {0}")]
+    Duplicate(String),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// Kind of resource a tag can be attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "tag_resource_type", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum TagResourceType {
+    Document,
+    Conversation,
+    Note,
+}
+
+impl TagResourceType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Document => "document",
+            Self::Conversation => "conversation",
+            Self::Note => "note",
+        }
+    }
+}
+
+impl FromStr for TagResourceType {
+    type Err = TagError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "document" => Ok(Self::Document),
+            "conversation" => Ok(Self::Conversation),
+            "note" => Ok(Self::Note),
+            other => Err(TagError::Validation(
+                Message::new("tags.unknown_resource_type").arg("type", other),
+            )),
+        }
+    }
+}
+
+/// Row returned by tag queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TagRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub color: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A tag with the number of resources it is attached to.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TagWithCount {
+    #[sqlx(flatten)]
+    pub tag: TagRow,
+    pub usage_count: i64,
+}
+
+/// Normalize a tag name: trimmed and lowercased, non-empty and at most
+/// [`MAX_TAG_NAME_CHARS`] characters.
+pub fn normalize_name(name: &str) -> Result<String, TagError> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return Err(TagError::Validation(Message::new("tags.name_required")));
+    }
+    if name.chars().count() > MAX_TAG_NAME_CHARS {
+        return Err(TagError::Validation(
+            Message::new("tags.name_too_long").arg("max", MAX_TAG_NAME_CHARS),
+        ));
+    }
+    Ok(name)
+}
+
+/// Normalize a list of tag names, dropping duplicates (order kept).
+pub fn normalize_names(names: &[String]) -> Result<Vec<String>, TagError> {
+    let mut out: Vec<String> = Vec::with_capacity(names.len());
+    for name in names {
+        let name = normalize_name(name)?;
+        if !out.contains(&name) {
+            out.push(name);
+        }
+    }
+    Ok(out)
+}
+
+/// Validate an optional `#rrggbb` color.
+pub fn validate_color(color: Option<&str>) -> Result<(), TagError> {
+    match color {
+        None => Ok(()),
+        Some(c)
+            if c.len() == 7
+                && c.starts_with('#')
+                && c[1..].chars().all(|ch| ch.is_ascii_hexdigit()) =>
+        {
+            Ok(())
+        }
+        Some(c) => Err(TagError::Validation(
+            Message::new("tags.invalid_color").arg("color", c),
+        )),
+    }
+}
+
+/// Map a unique violation on `(user_id, name)` to [`TagError::Duplicate`].
+fn map_duplicate(e: sqlx::Error, name: &str) -> TagError {
+    match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            TagError::Duplicate(name.to_string())
+        }
+        _ => TagError::Db(e),
+    }
+}
+
+/// List a user's tags with usage counts, ordered by name.
+pub async fn list_tags(pool: &PgPool, user_id: &Uuid) -> Result<Vec<TagWithCount>, TagError> {
+    let rows = sqlx::query_as::<_, TagWithCount>(
+        r#"
+        SELECT t.id, t.user_id, t.name, t.color, t.created_at, t.updated_at,
+               COUNT(a.tag_id) AS usage_count
+        FROM tags t
+        LEFT JOIN tag_assignments a ON a.tag_id = t.id
+        WHERE t.user_id = $1
+        GROUP BY t.id
+        ORDER BY t.name
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Create a tag.
+pub async fn create_tag(
+    pool: &PgPool,
+    user_id: &Uuid,
+    name: &str,
+    color: Option<&str>,
+) -> Result<TagRow, TagError> {
+    let name = normalize_name(name)?;
+    validate_color(color)?;
+
+    sqlx::query_as::<_, TagRow>(
+        r#"
+        INSERT INTO tags (id, user_id, name, color)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, user_id, name, color, created_at, updated_at
+        "#,
+    )
+    .bind(uuidv7())
+    .bind(user_id)
+    .bind(&name)
+    .bind(color)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| map_duplicate(e, &name))
+}
+
+/// Rename and/or recolor a tag. `None` leaves a field unchanged; an empty
+/// `color` clears it.
+pub async fn update_tag(
+    pool: &PgPool,
+    user_id: &Uuid,
+    tag_id: &Uuid,
+    name: Option<&str>,
+    color: Option<&str>,
+) -> Result<TagRow, TagError> {
+    let name = name.map(normalize_name).transpose()?;
+    let color = color.map(|c| (!c.is_empty()).then_some(c));
+    if let Some(c) = color {
+        validate_color(c)?;
+    }
+
+    sqlx::query_as::<_, TagRow>(
+        r#"
+        UPDATE tags
+        SET name = COALESCE($1, name),
+            color = CASE WHEN $2 THEN $3 ELSE color END,
+            updated_at = now()
+        WHERE id = $4 AND user_id = $5
+        RETURNING id, user_id, name, color, created_at, updated_at
+        "#,
+    )
+    .bind(name.as_deref())
+    .bind(color.is_some())
+    .bind(color.flatten())
+    .bind(tag_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| map_duplicate(e, name.as_deref().unwrap_or_default()))?
+    .ok_or_else(|| TagError::NotFound(Message::new("tags.not_found")))
+}
+
+/// Delete a tag (assignments cascade).
+pub async fn delete_tag(pool: &PgPool, user_id: &Uuid, tag_id: &Uuid) -> Result<bool, TagError> {
+    let result = sqlx::query("DELETE FROM tags WHERE id = $1 AND user_id = $2")
+        .bind(tag_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get or create the user's tag named `name` (already normalized).
+async fn ensure_tag(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    name: &str,
+) -> Result<Uuid, sqlx::Error> {
+    sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO tags (id, user_id, name)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, name) DO UPDATE SET name = EXCLUDED.name
+        RETURNING id
+        "#,
+    )
+    .bind(uuidv7())
+    .bind(user_id)
+    .bind(name)
+    .fetch_one(&mut *conn)
+    .await
+}
+
+/// Whether `user_id` owns the resource.
+async fn resource_exists(
+    pool: &PgPool,
+    user_id: &Uuid,
+    resource_type: TagResourceType,
+    resource_id: &Uuid,
+) -> Result<bool, TagError> {
+    let sql = match resource_type {
+        TagResourceType::Document => {
+            "SELECT EXISTS(SELECT 1 FROM documents WHERE id = $1 AND user_id = $2)"
+        }
+        TagResourceType::Conversation => {
+            "SELECT EXISTS(SELECT 1 FROM conversations WHERE id = $1 AND user_id = $2)"
+        }
+        TagResourceType::Note => {
+            "SELECT EXISTS(SELECT 1 FROM notes WHERE id = $1 AND user_id = $2)"
+        }
+    };
+    let exists = sqlx::query_scalar::<_, bool>(sql)
+        .bind(resource_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(exists)
+}
+
+/// Tags the user has attached to a resource, ordered by name.
+pub async fn resource_tags(
+    pool: &PgPool,
+    user_id: &Uuid,
+    resource_type: TagResourceType,
+    resource_id: &Uuid,
+) -> Result<Vec<TagRow>, TagError> {
+    let rows = sqlx::query_as::<_, TagRow>(
+        r#"
+        SELECT t.id, t.user_id, t.name, t.color, t.created_at, t.updated_at
+        FROM tags t
+        JOIN tag_assignments a ON a.tag_id = t.id
+        WHERE t.user_id = $1 AND a.resource_type = $2 AND a.resource_id = $3
+        ORDER BY t.name
+        "#,
+    )
+    .bind(user_id)
+    .bind(resource_type)
+    .bind(resource_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// Attach a tag to a resource, identified either by tag ID or by name (a
+/// named tag is created if missing). Attaching twice is a no-op.
+pub async fn attach_tag(
+    pool: &PgPool,
+    user_id: &Uuid,
+    tag: TagRef<'_>,
+    resource_type: TagResourceType,
+    resource_id: &Uuid,
+) -> Result<TagRow, TagError> {
+    if !resource_exists(pool, user_id, resource_type, resource_id).await? {
+        return Err(TagError::NotFound(
+            Message::new("tags.resource_not_found").arg("resource_type", resource_type.as_str()),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    let tag_id = match tag {
+        TagRef::Id(id) => *id,
+        TagRef::Name(name) => ensure_tag(&mut tx, user_id, &normalize_name(name)?).await?,
+    };
+    let row = sqlx::query_as::<_, TagRow>(
+        r#"
+        SELECT id, user_id, name, color, created_at, updated_at
+        FROM tags
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(tag_id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| TagError::NotFound(Message::new("tags.not_found")))?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO tag_assignments (tag_id, resource_type, resource_id)
+        VALUES ($1, $2, $3)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(row.id)
+    .bind(resource_type)
+    .bind(resource_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// How a tag is referenced when attaching it.
+#[derive(Debug, Clone, Copy)]
+pub enum TagRef<'a> {
+    Id(&'a Uuid),
+    Name(&'a str),
+}
+
+/// Detach one of the user's tags from a resource.
+pub async fn detach_tag(
+    pool: &PgPool,
+    user_id: &Uuid,
+    tag_id: &Uuid,
+    resource_type: TagResourceType,
+    resource_id: &Uuid,
+) -> Result<bool, TagError> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM tag_assignments a
+        USING tags t
+        WHERE a.tag_id = t.id
+          AND t.id = $1 AND t.user_id = $2
+          AND a.resource_type = $3 AND a.resource_id = $4
+        "#,
+    )
+    .bind(tag_id)
+    .bind(user_id)
+    .bind(resource_type)
+    .bind(resource_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Replace the user's tags on a resource with `names` (already normalized),
+/// creating missing tags.
+pub async fn set_resource_tags(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    resource_type: TagResourceType,
+    resource_id: &Uuid,
+    names: &[String],
+) -> Result<(), sqlx::Error> {
+    clear_resource_tags(&mut *conn, resource_type, resource_id).await?;
+
+    for name in names {
+        let tag_id = ensure_tag(&mut *conn, user_id, name).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO tag_assignments (tag_id, resource_type, resource_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(tag_id)
+        .bind(resource_type)
+        .bind(resource_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Remove every tag assignment of a resource. Call when deleting it.
+pub async fn clear_resource_tags(
+    conn: &mut PgConnection,
+    resource_type: TagResourceType,
+    resource_id: &Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM tag_assignments WHERE resource_type = $1 AND resource_id = $2")
+        .bind(resource_type)
+        .bind(resource_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_name_trims_and_lowercases() {
+        assert_eq!(normalize_name("  Work ").unwrap(), "work");
+        assert!(matches!(normalize_name("  "), Err(TagError::Validation(_))));
+        assert!(normalize_name(&"x".repeat(MAX_TAG_NAME_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn normalize_names_dedupes_in_order() {
+        let names = vec!["B".to_string(), "a".to_string(), "b ".to_string()];
+        assert_eq!(normalize_names(&names).unwrap(), vec!["b", "a"]);
+    }
+
+    #[test]
+    fn validate_color_accepts_hex_only() {
+        assert!(validate_color(None).is_ok());
+        assert!(validate_color(Some("#1a2B3c")).is_ok());
+        assert!(validate_color(Some("red")).is_err());
+        assert!(validate_color(Some("#12345g")).is_err());
+    }
+
+    #[test]
+    fn resource_type_round_trips() {
+        for t in [
+            TagResourceType::Document,
+            TagResourceType::Conversation,
+            TagResourceType::Note,
+        ] {
+            assert_eq!(t.as_str().parse::<TagResourceType>().unwrap(), t);
+        }
+        assert!("folder".parse::<TagResourceType>().is_err());
+    }
+}
@@ -0,0 +1,414 @@
+//! Assistant tasks — prompts run on a recurring schedule.
+//!
+//! A task pairs a prompt with a cron [`schedule`]. The API's task scheduler
+//! periodically claims due tasks with [`claim_due_tasks`], runs each prompt
+//! through the chat pipeline (appending to the task's conversation), and
+//! records the outcome with [`finish_task_run`]. Schedules run in the
+//! owner's time zone; [`reschedule_user_tasks`] recomputes next runs when
+//! it changes.
+
+pub mod schedule;
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::i18n::Message;
+use crate::timezone;
+use crate::uuid::uuidv7;
+
+/// Maximum task name length in characters.
+pub const MAX_TASK_NAME_CHARS: usize = 200;
+
+/// `last_status` while a run is in progress.
+pub const STATUS_RUNNING: &str = "running";
+/// `last_status` after a successful run.
+pub const STATUS_SUCCEEDED: &str = "succeeded";
+/// `last_status` after a failed run.
+pub const STATUS_FAILED: &str = "failed";
+
+/// Errors that can occur in task operations.
+#[derive(Debug, Error)]
+pub enum TaskError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Not found: {0}")]
+    NotFound(Message),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// Row returned by task queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TaskRow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prompt: String,
+    pub schedule: String,
+    pub enabled: bool,
+    pub conversation_id: Option<Uuid>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+const TASK_COLUMNS: &str = "id, user_id, name, prompt, schedule, enabled, conversation_id, \
+     next_run_at, last_run_at, last_status, last_error, created_at, updated_at";
+
+/// Trim and validate a task name.
+fn validate_name(name: &str) -> Result<&str, TaskError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(TaskError::Validation(Message::new("tasks.name_required")));
+    }
+    if name.chars().count() > MAX_TASK_NAME_CHARS {
+        return Err(TaskError::Validation(
+            Message::new("tasks.name_too_long").arg("max", MAX_TASK_NAME_CHARS),
+        ));
+    }
+    Ok(name)
+}
+
+fn validate_prompt(prompt: &str) -> Result<&str, TaskError> {
+    if prompt.trim().is_empty() {
+        return Err(TaskError::Validation(Message::new("tasks.prompt_required")));
+    }
+    Ok(prompt)
+}
+
+/// Next run time for an (enabled) schedule in UTC, counted from now.
+fn next_run(schedule_expr: &str, enabled: bool) -> Result<Option<DateTime<Utc>>, TaskError> {
+    if !enabled {
+        return Ok(None);
+    }
+    schedule::next_run_after(schedule_expr, Utc::now()).map(Some)
+}
+
+/// Next run time for an (enabled) schedule in the user's time zone,
+/// counted from now.
+async fn next_run_for_user(
+    conn: &mut PgConnection,
+    user_id: &Uuid,
+    schedule_expr: &str,
+    enabled: bool,
+) -> Result<Option<DateTime<Utc>>, TaskError> {
+    if !enabled {
+        return Ok(None);
+    }
+    let zone = timezone::user_timezone(conn, user_id).await?;
+    if zone == timezone::DEFAULT_TIMEZONE {
+        return next_run(schedule_expr, enabled);
+    }
+    schedule::next_run_in_zone(conn, schedule_expr, Utc::now(), &zone)
+        .await
+        .map(Some)
+}
+
+/// List a user's tasks, by name.
+pub async fn list_tasks(pool: &PgPool, user_id: &Uuid) -> Result<Vec<TaskRow>, TaskError> {
+    let rows = sqlx::query_as::<_, TaskRow>(&format!(
+        "SELECT {TASK_COLUMNS} FROM tasks WHERE user_id = $1 ORDER BY name, created_at"
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Get a task by ID (scoped to user).
+pub async fn get_task(pool: &PgPool, user_id: &Uuid, task_id: &Uuid) -> Result<TaskRow, TaskError> {
+    sqlx::query_as::<_, TaskRow>(&format!(
+        "SELECT {TASK_COLUMNS} FROM tasks WHERE id = $1 AND user_id = $2"
+    ))
+    .bind(task_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| TaskError::NotFound(Message::new("tasks.not_found")))
+}
+
+/// Create a task. The schedule is validated and the first run computed.
+pub async fn create_task(
+    pool: &PgPool,
+    user_id: &Uuid,
+    name: &str,
+    prompt: &str,
+    schedule_expr: &str,
+    enabled: bool,
+) -> Result<TaskRow, TaskError> {
+    let name = validate_name(name)?;
+    let prompt = validate_prompt(prompt)?;
+    let schedule_expr = schedule_expr.trim();
+    schedule::parse_schedule(schedule_expr)?;
+    let mut conn = pool.acquire().await?;
+    let next_run_at = next_run_for_user(&mut conn, user_id, schedule_expr, enabled).await?;
+
+    let row = sqlx::query_as::<_, TaskRow>(&format!(
+        r#"
+        INSERT INTO tasks (id, user_id, name, prompt, schedule, enabled, next_run_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING {TASK_COLUMNS}
+        "#
+    ))
+    .bind(uuidv7())
+    .bind(user_id)
+    .bind(name)
+    .bind(prompt)
+    .bind(schedule_expr)
+    .bind(enabled)
+    .bind(next_run_at)
+    .fetch_one(&mut *conn)
+    .await?;
+    Ok(row)
+}
+
+/// Update a task. `None` fields are left unchanged; the next run is
+/// recomputed from the resulting schedule and enabled flag.
+pub async fn update_task(
+    pool: &PgPool,
+    user_id: &Uuid,
+    task_id: &Uuid,
+    name: Option<&str>,
+    prompt: Option<&str>,
+    schedule_expr: Option<&str>,
+    enabled: Option<bool>,
+) -> Result<TaskRow, TaskError> {
+    let current = get_task(pool, user_id, task_id).await?;
+
+    let name = name.map(validate_name).transpose()?;
+    let prompt = prompt.map(validate_prompt).transpose()?;
+    let schedule_expr = schedule_expr.map(str::trim).unwrap_or(&current.schedule);
+    schedule::parse_schedule(schedule_expr)?;
+    let enabled = enabled.unwrap_or(current.enabled);
+    let mut conn = pool.acquire().await?;
+    let next_run_at = next_run_for_user(&mut conn, user_id, schedule_expr, enabled).await?;
+
+    let row = sqlx::query_as::<_, TaskRow>(&format!(
+        r#"
+        UPDATE tasks
+        SET name = COALESCE($1, name),
+            prompt = COALESCE($2, prompt),
+            schedule = $3,
+            enabled = $4,
+            next_run_at = $5,
+            updated_at = now()
+        WHERE id = $6 AND user_id = $7
+        RETURNING {TASK_COLUMNS}
+        "#
+    ))
+    .bind(name)
+    .bind(prompt)
+    .bind(schedule_expr)
+    .bind(enabled)
+    .bind(next_run_at)
+    .bind(task_id)
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| TaskError::NotFound(Message::new("tasks.not_found")))?;
+    Ok(row)
+}
+
+/// Recompute the next run of a user's enabled tasks, e.g. after their time
+/// zone changed. Tasks whose schedule no longer parses are left as they are
+/// (the scheduler disables them when due). Returns the number rescheduled.
+pub async fn reschedule_user_tasks(pool: &PgPool, user_id: &Uuid) -> Result<u64, TaskError> {
+    let mut tx = pool.begin().await?;
+    let tasks = sqlx::query_as::<_, (Uuid, String)>(
+        "SELECT id, schedule FROM tasks WHERE user_id = $1 AND enabled FOR UPDATE",
+    )
+    .bind(user_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let zone = timezone::user_timezone(&mut tx, user_id).await?;
+    let now = Utc::now();
+    let mut rescheduled = 0;
+    for (task_id, schedule_expr) in tasks {
+        let next_run_at =
+            match schedule::next_run_in_zone(&mut tx, &schedule_expr, now, &zone).await {
+                Ok(next_run_at) => next_run_at,
+                Err(TaskError::Validation(_)) => continue,
+                Err(e) => return Err(e),
+            };
+        sqlx::query("UPDATE tasks SET next_run_at = $1 WHERE id = $2")
+            .bind(next_run_at)
+            .bind(task_id)
+            .execute(&mut *tx)
+            .await?;
+        rescheduled += 1;
+    }
+    tx.commit().await?;
+    Ok(rescheduled)
+}
+
+/// [`reschedule_user_tasks`] for every user with enabled tasks, e.g. after
+/// the system default time zone changed.
+pub async fn reschedule_all_tasks(pool: &PgPool) -> Result<u64, TaskError> {
+    let users = sqlx::query_scalar::<_, Uuid>("SELECT DISTINCT user_id FROM tasks WHERE enabled")
+        .fetch_all(pool)
+        .await?;
+    let mut rescheduled = 0;
+    for user_id in users {
+        rescheduled += reschedule_user_tasks(pool, &user_id).await?;
+    }
+    Ok(rescheduled)
+}
+
+/// Delete a task. Its conversation is kept.
+pub async fn delete_task(pool: &PgPool, user_id: &Uuid, task_id: &Uuid) -> Result<bool, TaskError> {
+    let result = sqlx::query("DELETE FROM tasks WHERE id = $1 AND user_id = $2")
+        .bind(task_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Make a task due immediately (it is picked up on the scheduler's next tick).
+pub async fn trigger_task(
+    pool: &PgPool,
+    user_id: &Uuid,
+    task_id: &Uuid,
+) -> Result<TaskRow, TaskError> {
+    sqlx::query_as::<_, TaskRow>(&format!(
+        r#"
+        UPDATE tasks
+        SET next_run_at = now(), updated_at = now()
+        WHERE id = $1 AND user_id = $2
+        RETURNING {TASK_COLUMNS}
+        "#
+    ))
+    .bind(task_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| TaskError::NotFound(Message::new("tasks.not_found")))
+}
+
+/// Claim up to `limit` enabled tasks due at `now`: each is marked running
+/// and its next run scheduled, so concurrent schedulers never claim the same
+/// run twice. Tasks whose schedule no longer parses are disabled.
+pub async fn claim_due_tasks(
+    pool: &PgPool,
+    now: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<TaskRow>, TaskError> {
+    let mut tx = pool.begin().await?;
+
+    let due = sqlx::query_as::<_, TaskRow>(&format!(
+        r#"
+        SELECT {TASK_COLUMNS}
+        FROM tasks
+        WHERE enabled AND next_run_at <= $1
+        ORDER BY next_run_at
+        LIMIT $2
+        FOR UPDATE SKIP LOCKED
+        "#
+    ))
+    .bind(now)
+    .bind(limit)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut claimed = Vec::with_capacity(due.len());
+    for task in due {
+        let zone = timezone::user_timezone(&mut tx, &task.user_id).await?;
+        match schedule::next_run_in_zone(&mut tx, &task.schedule, now, &zone).await {
+            Ok(next_run_at) => {
+                let row = sqlx::query_as::<_, TaskRow>(&format!(
+                    r#"
+                    UPDATE tasks
+                    SET next_run_at = $1, last_run_at = $2, last_status = $3, last_error = NULL
+                    WHERE id = $4
+                    RETURNING {TASK_COLUMNS}
+                    "#
+                ))
+                .bind(next_run_at)
+                .bind(now)
+                .bind(STATUS_RUNNING)
+                .bind(task.id)
+                .fetch_one(&mut *tx)
+                .await?;
+                claimed.push(row);
+            }
+            Err(e) => {
+                sqlx::query(
+                    r#"
+                    UPDATE tasks
+                    SET enabled = false, next_run_at = NULL, last_status = $1, last_error = $2
+                    WHERE id = $3
+                    "#,
+                )
+                .bind(STATUS_FAILED)
+                .bind(e.to_string())
+                .bind(task.id)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+    }
+
+    tx.commit().await?;
+    Ok(claimed)
+}
+
+/// Record the outcome of a run. `conversation_id` is the conversation the
+/// result was written to (remembered for later runs); `error` is `None` on
+/// success.
+pub async fn finish_task_run(
+    pool: &PgPool,
+    task_id: &Uuid,
+    conversation_id: Option<&Uuid>,
+    error: Option<&str>,
+) -> Result<(), TaskError> {
+    let status = if error.is_some() {
+        STATUS_FAILED
+    } else {
+        STATUS_SUCCEEDED
+    };
+    sqlx::query(
+        r#"
+        UPDATE tasks
+        SET last_status = $1,
+            last_error = $2,
+            conversation_id = COALESCE($3, conversation_id)
+        WHERE id = $4
+        "#,
+    )
+    .bind(status)
+    .bind(error)
+    .bind(conversation_id)
+    .bind(task_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_name_trims_and_bounds() {
+        assert_eq!(
+            validate_name("  Morning digest ").unwrap(),
+            "Morning digest"
+        );
+        assert!(validate_name("   ").is_err());
+        assert!(validate_name(&"x".repeat(MAX_TASK_NAME_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn disabled_tasks_have_no_next_run() {
+        assert_eq!(next_run("0 7 * * *", false).unwrap(), None);
+        assert!(next_run("0 7 * * *", true).unwrap().unwrap() > Utc::now());
+    }
+}
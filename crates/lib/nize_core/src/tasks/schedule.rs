@@ -0,0 +1,148 @@
+//! Cron schedule parsing for tasks.
+//!
+//! Accepts standard 5-field cron expressions (`minute hour day-of-month month
+//! day-of-week`) as well as the 6/7-field form with seconds (and year)
+//! understood by the `cron` crate. Schedules are evaluated in the task
+//! owner's time zone (see [`crate::timezone`]): the cron expression picks
+//! the next wall-clock time, which is then resolved to an instant, so
+//! "every day at 07:30" stays at 07:30 local across DST changes.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use cron::Schedule;
+use sqlx::PgConnection;
+
+use super::TaskError;
+use crate::i18n::Message;
+use crate::timezone;
+
+/// Shortest allowed gap between two runs of a task, in seconds.
+pub const MIN_INTERVAL_SECS: i64 = 5 * 60;
+
+/// Parse a cron expression, rejecting schedules that never fire or fire more
+/// often than every [`MIN_INTERVAL_SECS`].
+pub fn parse_schedule(expr: &str) -> Result<Schedule, TaskError> {
+    let expr = expr.trim();
+    let normalized = if expr.split_whitespace().count() == 5 {
+        format!("0 {expr}")
+    } else {
+        expr.to_string()
+    };
+
+    let schedule = Schedule::from_str(&normalized).map_err(|e| {
+        TaskError::Validation(
+            Message::new("tasks.invalid_schedule")
+                .arg("schedule", expr)
+                .arg("error", e),
+        )
+    })?;
+
+    let mut upcoming = schedule.upcoming(Utc).take(3);
+    let first = upcoming.next().ok_or_else(|| {
+        TaskError::Validation(Message::new("tasks.schedule_never_runs").arg("schedule", expr))
+    })?;
+    let mut previous = first;
+    for next in upcoming {
+        if (next - previous).num_seconds() < MIN_INTERVAL_SECS {
+            return Err(TaskError::Validation(
+                Message::new("tasks.schedule_too_frequent")
+                    .arg("schedule", expr)
+                    .arg("minutes", MIN_INTERVAL_SECS / 60),
+            ));
+        }
+        previous = next;
+    }
+
+    Ok(schedule)
+}
+
+/// The first run time of `expr` strictly after `after`.
+pub fn next_run_after(expr: &str, after: DateTime<Utc>) -> Result<DateTime<Utc>, TaskError> {
+    parse_schedule(expr)?.after(&after).next().ok_or_else(|| {
+        TaskError::Validation(Message::new("tasks.schedule_never_runs").arg("schedule", expr))
+    })
+}
+
+/// The first wall-clock time of `expr` strictly after wall-clock `after`.
+pub fn next_local_run_after(schedule: &Schedule, after: NaiveDateTime) -> Option<NaiveDateTime> {
+    // Wall-clock arithmetic: evaluate the schedule on a zone without DST.
+    schedule
+        .after(&after.and_utc())
+        .next()
+        .map(|t| t.naive_utc())
+}
+
+/// The first run time of `expr` in `zone` strictly after `after`.
+pub async fn next_run_in_zone(
+    conn: &mut PgConnection,
+    expr: &str,
+    after: DateTime<Utc>,
+    zone: &str,
+) -> Result<DateTime<Utc>, TaskError> {
+    if zone == timezone::DEFAULT_TIMEZONE {
+        return next_run_after(expr, after);
+    }
+    let schedule = parse_schedule(expr)?;
+    let never =
+        || TaskError::Validation(Message::new("tasks.schedule_never_runs").arg("schedule", expr));
+    let mut local = timezone::to_local(conn, after, zone).await?;
+    // A wall-clock time repeated when clocks go back can resolve to an
+    // instant not after `after`; move on to the next one.
+    for _ in 0..8 {
+        local = next_local_run_after(&schedule, local).ok_or_else(never)?;
+        let at = timezone::to_utc(conn, local, zone).await?;
+        if at > after {
+            return Ok(at);
+        }
+    }
+    Err(never())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn five_field_expressions_run_at_whole_minutes() {
+        let next = next_run_after("30 7 * * *", at("2026-03-10T08:00:00Z")).unwrap();
+        assert_eq!(next, at("2026-03-11T07:30:00Z"));
+    }
+
+    #[test]
+    fn six_field_expressions_are_accepted() {
+        let next = next_run_after("0 0 9 * * Mon", at("2026-03-10T08:00:00Z")).unwrap();
+        assert_eq!(next, at("2026-03-16T09:00:00Z"));
+    }
+
+    #[test]
+    fn local_runs_follow_the_wall_clock() {
+        let schedule = parse_schedule("30 7 * * *").unwrap();
+        let local = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        assert_eq!(
+            next_local_run_after(&schedule, local("2026-03-28 08:00")),
+            Some(local("2026-03-29 07:30"))
+        );
+        assert_eq!(
+            next_local_run_after(&schedule, local("2026-03-29 07:29")),
+            Some(local("2026-03-29 07:30"))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_and_too_frequent_schedules() {
+        assert!(matches!(
+            parse_schedule("every morning"),
+            Err(TaskError::Validation(_))
+        ));
+        assert!(matches!(
+            parse_schedule("* * * * *"),
+            Err(TaskError::Validation(_))
+        ));
+        assert!(parse_schedule("*/5 * * * *").is_ok());
+    }
+}
@@ -0,0 +1,286 @@
+//! Opt-in anonymous usage statistics.
+//!
+//! Counts which features are used (by route template, never by path or
+//! content) and which error categories occur, and builds a report with the
+//! app version, OS and a random install ID unrelated to any user. Nothing
+//! leaves the process unless `system.telemetry.enabled` is set and
+//! `system.telemetry.endpoint` is configured; both are off by default.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Config key enabling submission.
+pub const ENABLED_CONFIG_KEY: &str = "system.telemetry.enabled";
+/// Config key holding the submission URL.
+pub const ENDPOINT_CONFIG_KEY: &str = "system.telemetry.endpoint";
+/// Config key for the submission interval, in hours.
+pub const INTERVAL_CONFIG_KEY: &str = "system.telemetry.intervalHours";
+
+/// Report format version, bumped when fields change.
+pub const REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Errors that can occur in telemetry operations.
+#[derive(Debug, Error)]
+pub enum TelemetryError {
+    #[error("Submission failed: {0}")]
+    Submit(String),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// Counter groups.
+const FEATURES: &str = "features";
+const ERRORS: &str = "errors";
+
+/// In-memory usage counters since the last successful submission (or
+/// process start).
+#[derive(Debug)]
+pub struct UsageCounters {
+    since: Mutex<DateTime<Utc>>,
+    counts: Mutex<BTreeMap<(&'static str, String), u64>>,
+}
+
+impl Default for UsageCounters {
+    fn default() -> Self {
+        Self {
+            since: Mutex::new(Utc::now()),
+            counts: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl UsageCounters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one request to `route` (a route template) that returned
+    /// `status`.
+    pub fn record_request(&self, route: &str, status: u16) {
+        let mut counts = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(feature) = feature_for_route(route) {
+            *counts.entry((FEATURES, feature)).or_default() += 1;
+        }
+        if let Some(category) = error_category(status) {
+            *counts.entry((ERRORS, category.to_string())).or_default() += 1;
+        }
+    }
+
+    /// Build a report of the current counts.
+    pub fn report(&self, install_id: Uuid) -> Report {
+        let counts = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        let group = |name: &str| -> BTreeMap<String, u64> {
+            counts
+                .iter()
+                .filter(|((g, _), _)| *g == name)
+                .map(|((_, k), v)| (k.clone(), *v))
+                .collect()
+        };
+        Report {
+            schema_version: REPORT_SCHEMA_VERSION,
+            install_id,
+            app_version: crate::version().to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            period_start: *self.since.lock().unwrap_or_else(|e| e.into_inner()),
+            period_end: Utc::now(),
+            features: group(FEATURES),
+            errors: group(ERRORS),
+        }
+    }
+
+    /// Remove the counts in a submitted `report`, keeping anything recorded
+    /// since it was built.
+    pub fn subtract(&self, report: &Report) {
+        let mut counts = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        for (group, values) in [(FEATURES, &report.features), (ERRORS, &report.errors)] {
+            for (name, sent) in values {
+                let key = (group, name.clone());
+                if let Some(count) = counts.get_mut(&key) {
+                    *count = count.saturating_sub(*sent);
+                    if *count == 0 {
+                        counts.remove(&key);
+                    }
+                }
+            }
+        }
+        *self.since.lock().unwrap_or_else(|e| e.into_inner()) = report.period_end;
+    }
+}
+
+/// Feature name for a route template: its first path segment after the
+/// `/api` (or `/api/v1`) prefix, qualified by the second for admin routes
+/// (`/api/admin/config/{scope}/{key}` → `admin.config`). Parameters are
+/// never included.
+pub fn feature_for_route(route: &str) -> Option<String> {
+    let mut segments = route
+        .trim_start_matches('/')
+        .trim_start_matches("api/")
+        .split('/')
+        .filter(|s| !s.is_empty() && !s.starts_with('{'))
+        .skip_while(|s| is_version_segment(s));
+    let first = segments.next()?;
+    match first {
+        "admin" => Some(match segments.next() {
+            Some(second) => format!("admin.{second}"),
+            None => "admin".to_string(),
+        }),
+        _ => Some(first.to_string()),
+    }
+}
+
+/// `v1`, `v2`, … in a versioned route prefix.
+fn is_version_segment(segment: &str) -> bool {
+    segment
+        .strip_prefix('v')
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Error category for an HTTP status, `None` for successes.
+pub fn error_category(status: u16) -> Option<&'static str> {
+    match status {
+        400 | 422 => Some("validation"),
+        401 => Some("unauthorized"),
+        403 => Some("forbidden"),
+        404 => Some("not_found"),
+        409 => Some("conflict"),
+        429 => Some("rate_limited"),
+        400..=499 => Some("client_other"),
+        503 => Some("unavailable"),
+        504 => Some("timeout"),
+        500..=599 => Some("server_error"),
+        _ => None,
+    }
+}
+
+/// An anonymous usage report — exactly what is submitted.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub schema_version: u32,
+    /// Random per-installation ID; not derived from any user or machine
+    /// identifier.
+    pub install_id: Uuid,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Request counts per feature.
+    pub features: BTreeMap<String, u64>,
+    /// Error counts per category.
+    pub errors: BTreeMap<String, u64>,
+}
+
+// =============================================================================
+// Install state
+// =============================================================================
+
+/// Persistent telemetry state (a single row).
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TelemetryState {
+    pub install_id: Uuid,
+    pub last_submitted_at: Option<DateTime<Utc>>,
+}
+
+/// Load the telemetry state, creating the install ID on first use.
+pub async fn get_state(pool: &PgPool) -> Result<TelemetryState, TelemetryError> {
+    sqlx::query(
+        "INSERT INTO telemetry_state (id, install_id) VALUES (true, $1) ON CONFLICT (id) DO NOTHING",
+    )
+    .bind(Uuid::new_v4())
+    .execute(pool)
+    .await?;
+    let state = sqlx::query_as::<_, TelemetryState>(
+        "SELECT install_id, last_submitted_at FROM telemetry_state WHERE id = true",
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(state)
+}
+
+/// Record a successful submission.
+pub async fn mark_submitted(pool: &PgPool, at: DateTime<Utc>) -> Result<(), TelemetryError> {
+    sqlx::query("UPDATE telemetry_state SET last_submitted_at = $1 WHERE id = true")
+        .bind(at)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// POST `report` as JSON to `endpoint`.
+pub async fn submit(
+    client: &reqwest::Client,
+    endpoint: &str,
+    report: &Report,
+) -> Result<(), TelemetryError> {
+    let response = client
+        .post(endpoint)
+        .json(report)
+        .send()
+        .await
+        .map_err(|e| TelemetryError::Submit(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(TelemetryError::Submit(format!(
+            "endpoint returned {}",
+            response.status()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feature_names_use_route_templates_only() {
+        assert_eq!(
+            feature_for_route("/api/conversations/{id}/messages").as_deref(),
+            Some("conversations")
+        );
+        assert_eq!(
+            feature_for_route("/api/admin/config/{scope}/{key}").as_deref(),
+            Some("admin.config")
+        );
+        assert_eq!(
+            feature_for_route("/api/v1/notes/{id}").as_deref(),
+            Some("notes")
+        );
+        assert_eq!(feature_for_route("/").as_deref(), None);
+    }
+
+    #[test]
+    fn counts_features_and_errors() {
+        let counters = UsageCounters::new();
+        counters.record_request("/api/notes", 200);
+        counters.record_request("/api/notes/{id}", 404);
+        counters.record_request("/api/chat", 500);
+        let report = counters.report(Uuid::nil());
+        assert_eq!(report.features["notes"], 2);
+        assert_eq!(report.features["chat"], 1);
+        assert_eq!(report.errors["not_found"], 1);
+        assert_eq!(report.errors["server_error"], 1);
+    }
+
+    #[test]
+    fn subtract_keeps_counts_recorded_after_the_report() {
+        let counters = UsageCounters::new();
+        counters.record_request("/api/notes", 200);
+        let report = counters.report(Uuid::nil());
+        counters.record_request("/api/notes", 200);
+        counters.subtract(&report);
+        let next = counters.report(Uuid::nil());
+        assert_eq!(next.features["notes"], 1);
+        assert!(next.errors.is_empty());
+        assert_eq!(next.period_start, report.period_end);
+    }
+}
@@ -0,0 +1,96 @@
+//! Per-user time zones.
+//!
+//! Each user picks an IANA zone (`ui.timezone`, default UTC) used to run
+//! their schedules and to display times. Zone rules come from the
+//! database's tz database (`AT TIME ZONE`), which PostgreSQL and PGlite
+//! both bundle, so conversions are DST-aware on every platform without a
+//! second copy of the rules.
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// Config key holding a user's IANA time zone name.
+pub const TIMEZONE_CONFIG_KEY: &str = "ui.timezone";
+
+/// Zone used when none is configured or the configured one is unknown.
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+
+/// Whether `name` is a zone known to the database.
+pub async fn is_valid(conn: &mut PgConnection, name: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)")
+        .bind(name)
+        .fetch_one(&mut *conn)
+        .await
+}
+
+/// The user's effective time zone: their override, else the system value,
+/// else the definition default — falling back to [`DEFAULT_TIMEZONE`] if
+/// that is not a known zone.
+pub async fn user_timezone(conn: &mut PgConnection, user_id: &Uuid) -> Result<String, sqlx::Error> {
+    let zone = sqlx::query_scalar::<_, Option<String>>(
+        r#"
+        SELECT COALESCE(
+            (SELECT value FROM config_values
+             WHERE key = $1 AND scope = 'user-override'::config_scope AND user_id = $2),
+            (SELECT value FROM config_values
+             WHERE key = $1 AND scope = 'system'::config_scope AND user_id IS NULL),
+            (SELECT default_value FROM config_definitions WHERE key = $1)
+        )
+        "#,
+    )
+    .bind(TIMEZONE_CONFIG_KEY)
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    match zone.map(|z| z.trim().to_string()).filter(|z| !z.is_empty()) {
+        Some(zone) if is_valid(conn, &zone).await? => Ok(zone),
+        Some(zone) => {
+            tracing::warn!(user_id = %user_id, zone = %zone, "unknown time zone, using UTC");
+            Ok(DEFAULT_TIMEZONE.to_string())
+        }
+        None => Ok(DEFAULT_TIMEZONE.to_string()),
+    }
+}
+
+/// Wall-clock time in `zone` at `at`.
+pub async fn to_local(
+    conn: &mut PgConnection,
+    at: DateTime<Utc>,
+    zone: &str,
+) -> Result<NaiveDateTime, sqlx::Error> {
+    sqlx::query_scalar::<_, NaiveDateTime>("SELECT $1::timestamptz AT TIME ZONE $2")
+        .bind(at)
+        .bind(zone)
+        .fetch_one(&mut *conn)
+        .await
+}
+
+/// The instant of wall-clock time `local` in `zone`. A time skipped by a
+/// DST change moves forward by the gap; a repeated time resolves to its
+/// later (standard time) occurrence, as PostgreSQL does.
+pub async fn to_utc(
+    conn: &mut PgConnection,
+    local: NaiveDateTime,
+    zone: &str,
+) -> Result<DateTime<Utc>, sqlx::Error> {
+    sqlx::query_scalar::<_, DateTime<Utc>>("SELECT $1::timestamp AT TIME ZONE $2")
+        .bind(local)
+        .bind(zone)
+        .fetch_one(&mut *conn)
+        .await
+}
+
+/// `at` with the UTC offset in effect in `zone` at that instant, for
+/// displaying local times without client-side zone rules.
+pub async fn with_offset(
+    conn: &mut PgConnection,
+    at: DateTime<Utc>,
+    zone: &str,
+) -> Result<DateTime<FixedOffset>, sqlx::Error> {
+    let local = to_local(conn, at, zone).await?;
+    let offset_secs = (local - at.naive_utc()).num_seconds() as i32;
+    let offset = FixedOffset::east_opt(offset_secs).unwrap_or(FixedOffset::east_opt(0).unwrap());
+    Ok(at.with_timezone(&offset))
+}
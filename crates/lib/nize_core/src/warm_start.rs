@@ -0,0 +1,259 @@
+//! Warm-start state kept across API server restarts.
+//!
+//! A restarted server starts cold: the config cache is empty and MCP
+//! servers connect on first use, so the first minute after a sidecar
+//! restart is slow. With a [`WarmStart`] file the server [`WarmStart::save`]s
+//! a small snapshot on graceful shutdown — the live config cache entries and
+//! the MCP servers it had connections to — and [`WarmStart::restore`]s it on
+//! the next boot.
+//!
+//! The file is read once and removed, so a crash never restores a stale
+//! snapshot twice. A snapshot taken against another database, with another
+//! format version or older than [`MAX_AGE`] is ignored. Cache entries come
+//! back only for what is left of their TTL. Pending OAuth authorizations
+//! need no snapshot: they are kept in the database (see
+//! [`crate::mcp::oauth`]).
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+use crate::config::cache::{CacheEntryInfo, ConfigCache};
+use crate::mcp::execution::ClientPool;
+
+/// Version of the snapshot format; snapshots of another version are ignored.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Oldest snapshot that is still restored.
+pub const MAX_AGE: chrono::Duration = chrono::Duration::hours(24);
+
+/// Errors that can occur while saving or reading a snapshot.
+#[derive(Debug, Error)]
+pub enum WarmStartError {
+    #[error("IO error on {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("Invalid warm-start file {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
+}
+
+/// Contents of a warm-start file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WarmState {
+    pub version: u32,
+    pub saved_at: DateTime<Utc>,
+    /// Fingerprint of the database the snapshot was taken against.
+    pub database: String,
+    /// Unexpired config cache entries.
+    pub config: Vec<CacheEntryInfo>,
+    /// MCP servers with a pooled connection.
+    pub mcp_servers: Vec<Uuid>,
+}
+
+/// What [`WarmStart::restore`] brought back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RestoreReport {
+    pub config_entries: usize,
+    /// Servers queued for reconnection in the background.
+    pub mcp_servers: usize,
+}
+
+/// A warm-start file for one database.
+#[derive(Debug, Clone)]
+pub struct WarmStart {
+    path: PathBuf,
+    database: String,
+}
+
+impl WarmStart {
+    /// Warm-start state at `path` for the database at `database_url`. The
+    /// URL is kept only as a hash.
+    pub fn new(path: impl Into<PathBuf>, database_url: &str) -> Self {
+        Self {
+            path: path.into(),
+            database: format!("{:x}", Sha256::digest(database_url.as_bytes())),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Snapshot `cache` and `clients` to the file (temp file + rename).
+    pub async fn save(
+        &self,
+        cache: &Arc<RwLock<ConfigCache>>,
+        clients: &ClientPool,
+    ) -> Result<(), WarmStartError> {
+        let config = cache
+            .read()
+            .await
+            .entries()
+            .into_iter()
+            .filter(|e| !e.expired)
+            .collect::<Vec<_>>();
+        let state = WarmState {
+            version: FORMAT_VERSION,
+            saved_at: Utc::now(),
+            database: self.database.clone(),
+            config,
+            mcp_servers: clients.connected_server_ids(),
+        };
+        self.write(&state)?;
+        info!(
+            path = %self.path.display(),
+            config_entries = state.config.len(),
+            mcp_servers = state.mcp_servers.len(),
+            "saved warm-start state"
+        );
+        Ok(())
+    }
+
+    /// Read and remove the file, and restore what still applies: config
+    /// entries into `cache` right away, MCP connections in the background.
+    /// A missing or unusable snapshot restores nothing.
+    pub async fn restore(
+        &self,
+        pool: &PgPool,
+        cache: &Arc<RwLock<ConfigCache>>,
+        clients: &Arc<ClientPool>,
+    ) -> Result<RestoreReport, WarmStartError> {
+        let Some(state) = self.take()? else {
+            return Ok(RestoreReport::default());
+        };
+        if !self.applies(&state, Utc::now()) {
+            debug!(path = %self.path.display(), "ignoring outdated warm-start state");
+            return Ok(RestoreReport::default());
+        }
+
+        let config_entries = cache.write().await.restore(&state.config);
+        let mcp_servers = state.mcp_servers.len();
+        if mcp_servers > 0 {
+            let (pool, clients) = (pool.clone(), clients.clone());
+            tokio::spawn(async move {
+                let connected = clients.reconnect(&pool, &state.mcp_servers).await;
+                info!(connected, "reconnected MCP servers from warm-start state");
+            });
+        }
+        let report = RestoreReport {
+            config_entries,
+            mcp_servers,
+        };
+        info!(?report, "restored warm-start state");
+        Ok(report)
+    }
+
+    /// Whether a snapshot is for this database, in this format and recent.
+    fn applies(&self, state: &WarmState, now: DateTime<Utc>) -> bool {
+        state.version == FORMAT_VERSION
+            && state.database == self.database
+            && now - state.saved_at <= MAX_AGE
+    }
+
+    fn write(&self, state: &WarmState) -> Result<(), WarmStartError> {
+        let io = |source| WarmStartError::Io {
+            path: self.path.clone(),
+            source,
+        };
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(io)?;
+        }
+        let json = serde_json::to_vec(state).map_err(|source| WarmStartError::Parse {
+            path: self.path.clone(),
+            source,
+        })?;
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).map_err(io)?;
+        std::fs::rename(&tmp, &self.path).map_err(io)
+    }
+
+    /// Read the snapshot and remove the file. An unreadable snapshot is
+    /// removed too, so it does not fail every later boot.
+    fn take(&self) -> Result<Option<WarmState>, WarmStartError> {
+        let io = |source| WarmStartError::Io {
+            path: self.path.clone(),
+            source,
+        };
+        let bytes = match std::fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(io(e)),
+        };
+        std::fs::remove_file(&self.path).map_err(io)?;
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|source| WarmStartError::Parse {
+                path: self.path.clone(),
+                source,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(warm: &WarmStart, saved_at: DateTime<Utc>) -> WarmState {
+        let mut cache = ConfigCache::new();
+        cache.set("k1", "system", None, "v1".to_string());
+        WarmState {
+            version: FORMAT_VERSION,
+            saved_at,
+            database: warm.database.clone(),
+            config: cache.entries(),
+            mcp_servers: vec![Uuid::new_v4()],
+        }
+    }
+
+    #[test]
+    fn snapshots_are_read_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let warm = WarmStart::new(dir.path().join("warm-start.json"), "postgres://a/nize");
+        assert!(warm.take().unwrap().is_none());
+
+        let saved = state(&warm, Utc::now());
+        warm.write(&saved).unwrap();
+        let read = warm.take().unwrap().unwrap();
+        assert_eq!(read.config.len(), 1);
+        assert_eq!(read.mcp_servers, saved.mcp_servers);
+        assert!(!warm.path().exists());
+        assert!(warm.take().unwrap().is_none());
+
+        std::fs::write(warm.path(), "not json").unwrap();
+        assert!(warm.take().is_err());
+        assert!(!warm.path().exists());
+    }
+
+    #[test]
+    fn only_recent_snapshots_of_the_same_database_apply() {
+        let now = Utc::now();
+        let warm = WarmStart::new("warm-start.json", "postgres://a/nize");
+        assert!(warm.applies(&state(&warm, now), now));
+        assert!(!warm.applies(
+            &state(&warm, now - MAX_AGE - chrono::Duration::seconds(1)),
+            now
+        ));
+
+        let other = WarmStart::new("warm-start.json", "postgres://b/nize");
+        assert!(!other.applies(&state(&warm, now), now));
+
+        let mut old_format = state(&warm, now);
+        old_format.version = FORMAT_VERSION + 1;
+        assert!(!warm.applies(&old_format, now));
+    }
+}
@@ -0,0 +1,537 @@
+//! Workspaces — an optional team scope on top of per-user resources.
+//!
+//! Resources without a workspace are personal to their owner, as before. A
+//! resource created while a workspace is active belongs to that workspace and
+//! is visible to all of its members. Members hold a [`WorkspaceRole`]:
+//! owners manage the workspace itself, owners and admins manage members and
+//! any shared resource, members use shared resources and manage their own.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::i18n::Message;
+use crate::uuid::uuidv7;
+
+/// Maximum workspace name length in characters.
+pub const MAX_WORKSPACE_NAME_CHARS: usize = 100;
+
+/// Errors that can occur in workspace operations.
+#[derive(Debug, Error)]
+pub enum WorkspaceError {
+    #[error("Validation error: {0}")]
+    Validation(Message),
+
+    #[error("Not found: {0}")]
+    NotFound(Message),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(Message),
+
+    #[error("Database error: {0}")]
+    Db(#[from] sqlx::Error),
+}
+
+/// A member's role within a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, sqlx::Type)]
+#[sqlx(type_name = "workspace_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WorkspaceRole {
+    Owner,
+    Admin,
+    Member,
+}
+
+impl WorkspaceRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Owner => "owner",
+            Self::Admin => "admin",
+            Self::Member => "member",
+        }
+    }
+
+    /// Whether the role may add, remove and re-role members and manage any
+    /// shared resource.
+    pub fn can_manage(&self) -> bool {
+        matches!(self, Self::Owner | Self::Admin)
+    }
+}
+
+impl FromStr for WorkspaceRole {
+    type Err = WorkspaceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "owner" => Ok(Self::Owner),
+            "admin" => Ok(Self::Admin),
+            "member" => Ok(Self::Member),
+            other => Err(WorkspaceError::Validation(
+                Message::new("workspaces.unknown_role").arg("role", other),
+            )),
+        }
+    }
+}
+
+/// The workspace a request acts in, with the caller's role there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Membership {
+    pub workspace_id: Uuid,
+    pub role: WorkspaceRole,
+}
+
+/// Resource scope of a request: the calling user plus, when a workspace is
+/// active, their membership in it. Without a workspace the scope is the
+/// user's personal resources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scope {
+    pub user_id: Uuid,
+    pub workspace: Option<Membership>,
+}
+
+impl Scope {
+    /// The user's personal scope.
+    pub fn personal(user_id: Uuid) -> Self {
+        Self {
+            user_id,
+            workspace: None,
+        }
+    }
+
+    pub fn workspace_id(&self) -> Option<Uuid> {
+        self.workspace.map(|m| m.workspace_id)
+    }
+
+    /// Whether the caller may modify or delete a resource created by
+    /// `owner_id` in this scope: their own resources always, others' only
+    /// as a workspace owner or admin.
+    pub fn can_manage(&self, owner_id: &Uuid) -> bool {
+        *owner_id == self.user_id || self.workspace.is_some_and(|m| m.role.can_manage())
+    }
+}
+
+/// Row returned by workspace queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct WorkspaceRow {
+    pub id: Uuid,
+    pub name: String,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A workspace with the calling user's role and the member count.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct WorkspaceWithRole {
+    #[sqlx(flatten)]
+    pub workspace: WorkspaceRow,
+    pub role: WorkspaceRole,
+    pub member_count: i64,
+}
+
+/// Row returned by member queries.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct MemberRow {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: Option<String>,
+    pub role: WorkspaceRole,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Trim and validate a workspace name.
+fn validate_name(name: &str) -> Result<&str, WorkspaceError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(WorkspaceError::Validation(Message::new(
+            "workspaces.name_required",
+        )));
+    }
+    if name.chars().count() > MAX_WORKSPACE_NAME_CHARS {
+        return Err(WorkspaceError::Validation(
+            Message::new("workspaces.name_too_long").arg("max", MAX_WORKSPACE_NAME_CHARS),
+        ));
+    }
+    Ok(name)
+}
+
+/// Create a workspace; the creator becomes its owner.
+pub async fn create_workspace(
+    pool: &PgPool,
+    user_id: &Uuid,
+    name: &str,
+) -> Result<WorkspaceRow, WorkspaceError> {
+    let name = validate_name(name)?;
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query_as::<_, WorkspaceRow>(
+        r#"
+        INSERT INTO workspaces (id, name, created_by)
+        VALUES ($1, $2, $3)
+        RETURNING id, name, created_by, created_at, updated_at
+        "#,
+    )
+    .bind(uuidv7())
+    .bind(name)
+    .bind(user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        "INSERT INTO workspace_members (workspace_id, user_id, role) VALUES ($1, $2, 'owner')",
+    )
+    .bind(row.id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Workspaces the user is a member of, by name.
+pub async fn list_workspaces(
+    pool: &PgPool,
+    user_id: &Uuid,
+) -> Result<Vec<WorkspaceWithRole>, WorkspaceError> {
+    let rows = sqlx::query_as::<_, WorkspaceWithRole>(
+        r#"
+        SELECT w.id, w.name, w.created_by, w.created_at, w.updated_at, m.role,
+               (SELECT COUNT(*) FROM workspace_members c WHERE c.workspace_id = w.id) AS member_count
+        FROM workspaces w
+        JOIN workspace_members m ON m.workspace_id = w.id
+        WHERE m.user_id = $1
+        ORDER BY w.name, w.created_at
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// The user's membership in a workspace, if any.
+pub async fn get_membership(
+    pool: &PgPool,
+    workspace_id: &Uuid,
+    user_id: &Uuid,
+) -> Result<Option<Membership>, WorkspaceError> {
+    let role = sqlx::query_scalar::<_, WorkspaceRole>(
+        "SELECT role FROM workspace_members WHERE workspace_id = $1 AND user_id = $2",
+    )
+    .bind(workspace_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(role.map(|role| Membership {
+        workspace_id: *workspace_id,
+        role,
+    }))
+}
+
+/// Membership of `user_id` in `workspace_id`, or `NotFound` for non-members
+/// (so workspace IDs are not disclosed).
+pub async fn require_membership(
+    pool: &PgPool,
+    workspace_id: &Uuid,
+    user_id: &Uuid,
+) -> Result<Membership, WorkspaceError> {
+    get_membership(pool, workspace_id, user_id)
+        .await?
+        .ok_or_else(|| WorkspaceError::NotFound(Message::new("workspaces.not_found")))
+}
+
+/// Rename a workspace (owners and admins).
+pub async fn rename_workspace(
+    pool: &PgPool,
+    user_id: &Uuid,
+    workspace_id: &Uuid,
+    name: &str,
+) -> Result<WorkspaceRow, WorkspaceError> {
+    let name = validate_name(name)?;
+    let membership = require_membership(pool, workspace_id, user_id).await?;
+    if !membership.role.can_manage() {
+        return Err(WorkspaceError::Forbidden(Message::new(
+            "workspaces.rename_forbidden",
+        )));
+    }
+
+    let row = sqlx::query_as::<_, WorkspaceRow>(
+        r#"
+        UPDATE workspaces
+        SET name = $1, updated_at = now()
+        WHERE id = $2
+        RETURNING id, name, created_by, created_at, updated_at
+        "#,
+    )
+    .bind(name)
+    .bind(workspace_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Delete a workspace and everything scoped to it (owners only).
+pub async fn delete_workspace(
+    pool: &PgPool,
+    user_id: &Uuid,
+    workspace_id: &Uuid,
+) -> Result<(), WorkspaceError> {
+    let membership = require_membership(pool, workspace_id, user_id).await?;
+    if membership.role != WorkspaceRole::Owner {
+        return Err(WorkspaceError::Forbidden(Message::new(
+            "workspaces.delete_forbidden",
+        )));
+    }
+
+    sqlx::query("DELETE FROM workspaces WHERE id = $1")
+        .bind(workspace_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Members of a workspace (visible to any member), owners first.
+pub async fn list_members(
+    pool: &PgPool,
+    user_id: &Uuid,
+    workspace_id: &Uuid,
+) -> Result<Vec<MemberRow>, WorkspaceError> {
+    require_membership(pool, workspace_id, user_id).await?;
+
+    let rows = sqlx::query_as::<_, MemberRow>(
+        r#"
+        SELECT m.user_id, u.email, u.name, m.role, m.created_at
+        FROM workspace_members m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.workspace_id = $1
+        ORDER BY m.role, u.email
+        "#,
+    )
+    .bind(workspace_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Check that `actor` may give `target_role` to someone: managing members
+/// needs owner or admin, and only owners hand out (or take away) ownership.
+fn check_can_assign(actor: &Membership, target_role: WorkspaceRole) -> Result<(), WorkspaceError> {
+    if !actor.role.can_manage() {
+        return Err(WorkspaceError::Forbidden(Message::new(
+            "workspaces.manage_members_forbidden",
+        )));
+    }
+    if target_role == WorkspaceRole::Owner && actor.role != WorkspaceRole::Owner {
+        return Err(WorkspaceError::Forbidden(Message::new(
+            "workspaces.manage_owners_forbidden",
+        )));
+    }
+    Ok(())
+}
+
+/// Fail if removing or demoting `member_id` would leave the workspace
+/// without an owner.
+async fn ensure_other_owner(
+    pool: &PgPool,
+    workspace_id: &Uuid,
+    member_id: &Uuid,
+) -> Result<(), WorkspaceError> {
+    let others = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*) FROM workspace_members
+        WHERE workspace_id = $1 AND role = 'owner' AND user_id <> $2
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(member_id)
+    .fetch_one(pool)
+    .await?;
+    if others == 0 {
+        return Err(WorkspaceError::Validation(Message::new(
+            "workspaces.last_owner",
+        )));
+    }
+    Ok(())
+}
+
+/// Add a registered user (by email) to a workspace, or change their role if
+/// they are already a member.
+pub async fn add_member(
+    pool: &PgPool,
+    user_id: &Uuid,
+    workspace_id: &Uuid,
+    email: &str,
+    role: WorkspaceRole,
+) -> Result<MemberRow, WorkspaceError> {
+    let actor = require_membership(pool, workspace_id, user_id).await?;
+    check_can_assign(&actor, role)?;
+
+    let member_id = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE email = $1")
+        .bind(email.trim().to_lowercase())
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| {
+            WorkspaceError::NotFound(
+                Message::new("workspaces.unknown_user_email").arg("email", email.trim()),
+            )
+        })?;
+
+    set_member_role(pool, &actor, workspace_id, &member_id, role).await
+}
+
+/// Change a member's role.
+pub async fn update_member_role(
+    pool: &PgPool,
+    user_id: &Uuid,
+    workspace_id: &Uuid,
+    member_id: &Uuid,
+    role: WorkspaceRole,
+) -> Result<MemberRow, WorkspaceError> {
+    let actor = require_membership(pool, workspace_id, user_id).await?;
+    check_can_assign(&actor, role)?;
+
+    if get_membership(pool, workspace_id, member_id)
+        .await?
+        .is_none()
+    {
+        return Err(WorkspaceError::NotFound(Message::new(
+            "workspaces.member_not_found",
+        )));
+    }
+    set_member_role(pool, &actor, workspace_id, member_id, role).await
+}
+
+async fn set_member_role(
+    pool: &PgPool,
+    actor: &Membership,
+    workspace_id: &Uuid,
+    member_id: &Uuid,
+    role: WorkspaceRole,
+) -> Result<MemberRow, WorkspaceError> {
+    if let Some(current) = get_membership(pool, workspace_id, member_id).await?
+        && current.role == WorkspaceRole::Owner
+        && role != WorkspaceRole::Owner
+    {
+        check_can_assign(actor, WorkspaceRole::Owner)?;
+        ensure_other_owner(pool, workspace_id, member_id).await?;
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO workspace_members (workspace_id, user_id, role)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (workspace_id, user_id) DO UPDATE SET role = EXCLUDED.role
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(member_id)
+    .bind(role)
+    .execute(pool)
+    .await?;
+
+    let row = sqlx::query_as::<_, MemberRow>(
+        r#"
+        SELECT m.user_id, u.email, u.name, m.role, m.created_at
+        FROM workspace_members m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.workspace_id = $1 AND m.user_id = $2
+        "#,
+    )
+    .bind(workspace_id)
+    .bind(member_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(row)
+}
+
+/// Remove a member. Members may remove themselves (leave); removing others
+/// needs owner or admin, and only owners remove owners. The last owner
+/// cannot leave.
+pub async fn remove_member(
+    pool: &PgPool,
+    user_id: &Uuid,
+    workspace_id: &Uuid,
+    member_id: &Uuid,
+) -> Result<(), WorkspaceError> {
+    let actor = require_membership(pool, workspace_id, user_id).await?;
+    let target = get_membership(pool, workspace_id, member_id)
+        .await?
+        .ok_or_else(|| WorkspaceError::NotFound(Message::new("workspaces.member_not_found")))?;
+
+    if member_id != user_id {
+        check_can_assign(&actor, target.role)?;
+    }
+    if target.role == WorkspaceRole::Owner {
+        ensure_other_owner(pool, workspace_id, member_id).await?;
+    }
+
+    sqlx::query("DELETE FROM workspace_members WHERE workspace_id = $1 AND user_id = $2")
+        .bind(workspace_id)
+        .bind(member_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(role: WorkspaceRole) -> Membership {
+        Membership {
+            workspace_id: Uuid::new_v4(),
+            role,
+        }
+    }
+
+    #[test]
+    fn only_owners_assign_ownership() {
+        assert!(check_can_assign(&member(WorkspaceRole::Owner), WorkspaceRole::Owner).is_ok());
+        assert!(check_can_assign(&member(WorkspaceRole::Admin), WorkspaceRole::Member).is_ok());
+        assert!(matches!(
+            check_can_assign(&member(WorkspaceRole::Admin), WorkspaceRole::Owner),
+            Err(WorkspaceError::Forbidden(_))
+        ));
+        assert!(matches!(
+            check_can_assign(&member(WorkspaceRole::Member), WorkspaceRole::Member),
+            Err(WorkspaceError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn scope_manages_own_resources_and_admins_manage_all() {
+        let me = Uuid::new_v4();
+        let other = Uuid::new_v4();
+
+        let personal = Scope::personal(me);
+        assert!(personal.can_manage(&me));
+        assert!(!personal.can_manage(&other));
+
+        let as_member = Scope {
+            user_id: me,
+            workspace: Some(member(WorkspaceRole::Member)),
+        };
+        assert!(!as_member.can_manage(&other));
+
+        let as_admin = Scope {
+            user_id: me,
+            workspace: Some(member(WorkspaceRole::Admin)),
+        };
+        assert!(as_admin.can_manage(&other));
+    }
+
+    #[test]
+    fn roles_parse() {
+        assert_eq!(
+            "admin".parse::<WorkspaceRole>().unwrap(),
+            WorkspaceRole::Admin
+        );
+        assert!("guest".parse::<WorkspaceRole>().is_err());
+    }
+}
@@ -4,16 +4,38 @@
 //!
 //! Validates `Authorization: Bearer <token>` headers against the `mcp_tokens` table.
 //! On success, inserts a [`McpUser`] into request extensions for downstream handlers.
+//!
+//! Chat sessions also send [`CONVERSATION_HEADER`]; the conversation's pinned
+//! tool selection then restricts the tools the session can discover and run.
+//! The conversation, its workspace and the `Accept-Language` locale make up
+//! the session's [`McpUser::context`], forwarded to servers configured to
+//! receive it. With [`TURN_HEADER`] the session's tool executions are also
+//! recorded against that turn of the conversation ([`McpUser::correlation`]).
+//! A read-only token marks the user [`McpUser::read_only`], which the access
+//! control hook enforces on every tool call.
 
 use axum::{
     extract::State,
-    http::{Request, StatusCode, header::AUTHORIZATION},
+    http::{
+        Request, StatusCode,
+        header::{ACCEPT_LANGUAGE, AUTHORIZATION},
+    },
     middleware::Next,
     response::Response,
 };
+use nize_core::conversations::{SessionConversation, ToolSelection};
+use nize_core::mcp::analytics::Correlation;
+use nize_core::mcp::context::{self, SessionContext};
 use sqlx::PgPool;
 use tracing::debug;
 
+/// Request header naming the conversation an MCP session serves.
+pub const CONVERSATION_HEADER: &str = "x-nize-conversation";
+
+/// Request header naming the turn of the conversation an MCP session
+/// serves: the id of the user message that started it.
+pub const TURN_HEADER: &str = "x-nize-turn";
+
 /// Authenticated MCP user, inserted into request extensions by the auth middleware.
 ///
 /// Tool handlers extract this via `Extension<http::request::Parts>` →
@@ -23,6 +45,14 @@ pub struct McpUser {
     pub id: String,
     pub email: String,
     pub name: Option<String>,
+    /// Tool selection pinned by the session's conversation, if any.
+    pub tool_selection: Option<ToolSelection>,
+    /// The session's token only allows tools that change nothing.
+    pub read_only: bool,
+    /// Caller context of the session.
+    pub context: SessionContext,
+    /// Conversation turn the session's tool executions are recorded for.
+    pub correlation: Correlation,
 }
 
 /// Axum middleware: validates MCP bearer tokens.
@@ -58,12 +88,39 @@ pub async fn mcp_auth_middleware(
     };
 
     match nize_core::auth::mcp_tokens::validate_mcp_token(&pool, &token).await {
-        Ok(Some(user)) => {
+        Ok(Some(auth)) => {
+            let user = auth.user;
+            let headers = request.headers();
+            let conversation_id = headers
+                .get(CONVERSATION_HEADER)
+                .map(|v| {
+                    v.to_str()
+                        .ok()
+                        .and_then(|id| uuid::Uuid::parse_str(id.trim()).ok())
+                        .ok_or(StatusCode::BAD_REQUEST)
+                })
+                .transpose()?;
+            let turn_id = headers
+                .get(TURN_HEADER)
+                .map(|v| v.to_str().map_err(|_| StatusCode::BAD_REQUEST))
+                .transpose()?;
+            let correlation =
+                Correlation::new(conversation_id, turn_id).map_err(|_| StatusCode::BAD_REQUEST)?;
+            let locale = headers
+                .get(ACCEPT_LANGUAGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(context::parse_locale);
+            let conversation =
+                session_conversation(&pool, conversation_id.as_ref(), &user.id).await?;
             // @awa-impl: MCP-1.6_AC-1
             request.extensions_mut().insert(McpUser {
                 id: user.id,
                 email: user.email,
                 name: user.name,
+                tool_selection: conversation.tool_selection,
+                read_only: auth.read_only,
+                context: SessionContext::new(conversation_id, conversation.workspace_id, locale),
+                correlation,
             });
             Ok(next.run(request).await)
         }
@@ -77,3 +134,31 @@ pub async fn mcp_auth_middleware(
         }
     }
 }
+
+/// Workspace and tool selection of the conversation named by
+/// [`CONVERSATION_HEADER`]. Returns 404 when the conversation is not visible
+/// to the user, so a session cannot escape a pinned selection (or claim a
+/// workspace) by naming a foreign one.
+async fn session_conversation(
+    pool: &PgPool,
+    conversation_id: Option<&uuid::Uuid>,
+    user_id: &str,
+) -> Result<SessionConversation, StatusCode> {
+    let Some(conversation_id) = conversation_id else {
+        return Ok(SessionConversation::default());
+    };
+    let user_id = uuid::Uuid::parse_str(user_id).map_err(|_| StatusCode::UNAUTHORIZED)?;
+    match nize_core::conversations::session_conversation_for_user(pool, &user_id, conversation_id)
+        .await
+    {
+        Ok(conversation) => Ok(conversation),
+        Err(sqlx::Error::RowNotFound) => {
+            debug!("MCP auth: conversation {conversation_id} not found");
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            debug!("MCP auth: database error: {e}");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
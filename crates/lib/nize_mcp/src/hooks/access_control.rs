@@ -3,14 +3,35 @@
 //! Access control hook — verifies user has access to the target MCP server.
 //!
 //! Checks `user_mcp_preferences` and server visibility before allowing a
-//! tool call. Meta-tool calls (no server_id) are always allowed.
+//! tool call, and for calls naming a tool that the tool is not switched off
+//! by an admin or the user. Meta-tool calls (no server or tool) are always
+//! allowed.
+//!
+//! Sessions with a read-only MCP token are further limited to the
+//! [`READ_ONLY_META_TOOLS`] and to proxied tools their server annotates as
+//! read-only and not destructive.
 
 use async_trait::async_trait;
+use nize_core::models::mcp::McpToolSummary;
 use sqlx::PgPool;
 
 use super::{HookContext, HookError, ToolCallOutcome, ToolHook};
 
-/// Access control hook: blocks calls to servers the user hasn't enabled.
+/// Meta-tools that only list, search or browse, allowed for read-only
+/// tokens. `execute_tool` is checked against the tool it runs.
+pub const READ_ONLY_META_TOOLS: &[&str] = &[
+    "hello",
+    "discover_tools",
+    "get_tool_schema",
+    "list_tool_domains",
+    "browse_tool_domain",
+    "search_documents",
+    "get_document",
+    "list_conversations",
+];
+
+/// Access control hook: blocks calls to servers the user hasn't enabled and
+/// to tools switched off for them.
 pub struct AccessControlHook {
     pool: PgPool,
 }
@@ -19,6 +40,38 @@ impl AccessControlHook {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
+
+    /// Deny a read-only session a tool not annotated as read-only.
+    async fn require_read_only_tool(
+        &self,
+        ctx: &HookContext,
+        tool_id: &str,
+    ) -> Result<(), HookError> {
+        let tool =
+            nize_core::mcp::queries::get_tool_manifest(&self.pool, &ctx.user_id, tool_id, None)
+                .await
+                .map_err(|e| HookError::Internal(format!("Access check failed: {e}")))?
+                .map(McpToolSummary::from_row);
+        if tool.is_some_and(|t| t.is_read_only()) {
+            Ok(())
+        } else {
+            Err(HookError::AccessDenied(format!(
+                "Tool {} is not read-only and this MCP token is",
+                ctx.tool_name
+            )))
+        }
+    }
+}
+
+/// Deny a read-only session a meta-tool outside [`READ_ONLY_META_TOOLS`].
+pub fn require_read_only_meta_tool(ctx: &HookContext) -> Result<(), HookError> {
+    if ctx.read_only && !READ_ONLY_META_TOOLS.contains(&ctx.tool_name.as_str()) {
+        return Err(HookError::AccessDenied(format!(
+            "Tool {} is not available to read-only MCP tokens",
+            ctx.tool_name
+        )));
+    }
+    Ok(())
 }
 
 // @awa-impl: MCP-1.3_AC-1 (partial: access control for tool execution)
@@ -29,10 +82,34 @@ impl ToolHook for AccessControlHook {
         ctx: &HookContext,
         _params: &mut serde_json::Value,
     ) -> Result<(), HookError> {
-        // Meta-tool calls (no server_id) are always allowed.
+        // A named tool implies its server; check both in one go.
+        if let Some(tool_id) = ctx.tool_id {
+            let has_access = nize_core::mcp::queries::user_has_tool_access(
+                &self.pool,
+                &ctx.user_id,
+                &tool_id.to_string(),
+            )
+            .await
+            .map_err(|e| HookError::Internal(format!("Access check failed: {e}")))?;
+
+            if !has_access {
+                return Err(HookError::AccessDenied(format!(
+                    "User {} may not call tool {} ({})",
+                    ctx.user_id, ctx.tool_name, tool_id
+                )));
+            }
+            if ctx.read_only {
+                self.require_read_only_tool(ctx, &tool_id.to_string())
+                    .await?;
+            }
+            return Ok(());
+        }
+
+        // Meta-tool calls (no server_id) are always allowed, except those
+        // that write for read-only tokens.
         let server_id = match ctx.server_id {
             Some(id) => id,
-            None => return Ok(()),
+            None => return require_read_only_meta_tool(ctx),
         };
 
         let has_access = nize_core::mcp::queries::user_has_server_access(
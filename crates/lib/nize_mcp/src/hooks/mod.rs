@@ -4,10 +4,13 @@
 //!
 //! Provides a trait-based hook system that runs before and after every tool
 //! call. Hooks can inspect, transform, or reject calls. Built-in hooks
-//! provide audit logging and access control.
+//! provide audit logging, access control, argument templating and tool
+//! output transforms.
 
 pub mod access_control;
 pub mod audit;
+pub mod templating;
+pub mod transform;
 
 use std::sync::Arc;
 
@@ -23,6 +26,10 @@ pub struct HookContext {
     pub tool_name: String,
     /// None for meta-tools, Some for proxied external tool calls.
     pub tool_id: Option<Uuid>,
+    /// The session's MCP token is read-only.
+    pub read_only: bool,
+    /// The caller asked for the tool's output without its output transform.
+    pub raw_output: bool,
     pub scope: HookScope,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
@@ -49,6 +56,11 @@ pub enum HookError {
     #[error("Access denied: {0}")]
     AccessDenied(String),
 
+    /// The call's arguments cannot be used, e.g. a template variable
+    /// that is not allowed.
+    #[error("Invalid params: {0}")]
+    InvalidParams(String),
+
     #[error("Hook error: {0}")]
     Internal(String),
 }
@@ -139,7 +151,8 @@ fn scope_matches(scope: &HookScope, ctx: &HookContext) -> bool {
 
 /// Build the default hook pipeline with built-in hooks.
 ///
-/// Pipeline order: AuditHook → AccessControlHook
+/// Pipeline order: AuditHook → AccessControlHook → TemplatingHook →
+/// TransformHook
 pub fn default_pipeline(pool: sqlx::PgPool) -> HookPipeline {
     HookPipeline::new(vec![
         (
@@ -148,7 +161,15 @@ pub fn default_pipeline(pool: sqlx::PgPool) -> HookPipeline {
         ),
         (
             HookScope::Global,
-            Arc::new(access_control::AccessControlHook::new(pool)),
+            Arc::new(access_control::AccessControlHook::new(pool.clone())),
+        ),
+        (
+            HookScope::Global,
+            Arc::new(templating::TemplatingHook::new(pool.clone())),
+        ),
+        (
+            HookScope::Global,
+            Arc::new(transform::TransformHook::new(pool)),
         ),
     ])
 }
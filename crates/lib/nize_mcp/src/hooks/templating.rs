@@ -0,0 +1,96 @@
+// @awa-component: MCP-TemplatingHook
+//
+//! Templating hook — resolves profile variables in tool arguments.
+//!
+//! Replaces `{{user.email}}`-style references in the arguments of a proxied
+//! tool call with the caller's profile values (see
+//! [`nize_core::mcp::templating`]), so the model can pass them without
+//! knowing them. Each resolved call is audited with the names of the
+//! variables and the arguments they were found in, never the values.
+//! Meta-tool calls are left alone.
+
+use async_trait::async_trait;
+use nize_core::mcp::McpError;
+use nize_core::mcp::templating;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::{HookContext, HookError, ToolCallOutcome, ToolHook};
+
+/// Templating hook: substitutes allowed profile variables in the `params`
+/// of `execute_tool` calls.
+pub struct TemplatingHook {
+    pool: PgPool,
+}
+
+impl TemplatingHook {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+fn hook_error(e: McpError) -> HookError {
+    match e {
+        McpError::Validation(msg) => HookError::InvalidParams(msg.to_string()),
+        other => HookError::Internal(format!("Argument templating failed: {other}")),
+    }
+}
+
+#[async_trait]
+impl ToolHook for TemplatingHook {
+    async fn before_call(
+        &self,
+        ctx: &HookContext,
+        params: &mut serde_json::Value,
+    ) -> Result<(), HookError> {
+        let Some(tool_id) = ctx.tool_id else {
+            return Ok(());
+        };
+        let Some(args) = params.get_mut("params") else {
+            return Ok(());
+        };
+        if !templating::has_references(args) {
+            return Ok(());
+        }
+
+        let user_id = Uuid::parse_str(&ctx.user_id)
+            .map_err(|e| HookError::Internal(format!("Invalid user id: {e}")))?;
+        let allowed = templating::allowed_variables(&self.pool)
+            .await
+            .map_err(hook_error)?;
+        let profile = templating::load_profile(&self.pool, &user_id, &tool_id)
+            .await
+            .map_err(hook_error)?;
+        let substitutions = templating::substitute(args, &allowed, &profile).map_err(hook_error)?;
+
+        let details = serde_json::json!({
+            "toolName": ctx.tool_name,
+            "toolId": tool_id.to_string(),
+            "substitutions": substitutions,
+        });
+        nize_core::mcp::queries::insert_audit_log(
+            &self.pool,
+            &ctx.user_id,
+            None,
+            "nize-mcp",
+            "param_substitution",
+            Some(&details),
+        )
+        .await
+        .map_err(|e| HookError::Internal(format!("Failed to audit substitution: {e}")))?;
+
+        Ok(())
+    }
+
+    async fn after_call(
+        &self,
+        _ctx: &HookContext,
+        _outcome: &mut ToolCallOutcome,
+    ) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "TemplatingHook"
+    }
+}
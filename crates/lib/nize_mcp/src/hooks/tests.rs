@@ -79,6 +79,8 @@ mod tests {
             server_id: None,
             tool_name: "test_tool".to_string(),
             tool_id: None,
+            read_only: false,
+            raw_output: false,
             scope: HookScope::Global,
             timestamp: chrono::Utc::now(),
         }
@@ -258,4 +260,22 @@ mod tests {
         let order = before_order.lock().unwrap().clone();
         assert_eq!(order, vec!["global", "server-specific"]);
     }
+
+    // Read-only tokens keep the listing meta-tools but lose writes.
+    #[test]
+    fn read_only_meta_tools() {
+        use crate::hooks::access_control::require_read_only_meta_tool;
+
+        let ctx = |tool_name: &str, read_only: bool| HookContext {
+            tool_name: tool_name.to_string(),
+            read_only,
+            ..make_ctx()
+        };
+        assert!(require_read_only_meta_tool(&ctx("search_documents", true)).is_ok());
+        assert!(require_read_only_meta_tool(&ctx("append_note", false)).is_ok());
+        assert!(matches!(
+            require_read_only_meta_tool(&ctx("append_note", true)),
+            Err(HookError::AccessDenied(_))
+        ));
+    }
 }
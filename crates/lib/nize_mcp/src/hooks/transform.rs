@@ -0,0 +1,69 @@
+// @awa-component: MCP-TransformHook
+//
+//! Output transform hook — applies the admin-configured output template of
+//! a tool (see `nize_core::mcp::transforms`) to its successful results.
+//!
+//! Innermost in the pipeline, so its `after_call` runs first and later hooks
+//! see the transformed output. Skipped when the caller asked for the raw
+//! output. A template that fails to load or apply leaves the output as is.
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+use tracing::warn;
+
+use nize_core::mcp::transforms;
+
+use super::{HookContext, HookError, ToolCallOutcome, ToolHook};
+
+/// Transform hook: narrows and truncates tool outputs per tool template.
+pub struct TransformHook {
+    pool: PgPool,
+}
+
+impl TransformHook {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ToolHook for TransformHook {
+    async fn before_call(
+        &self,
+        _ctx: &HookContext,
+        _params: &mut serde_json::Value,
+    ) -> Result<(), HookError> {
+        Ok(())
+    }
+
+    async fn after_call(
+        &self,
+        ctx: &HookContext,
+        outcome: &mut ToolCallOutcome,
+    ) -> Result<(), HookError> {
+        let (Some(tool_id), ToolCallOutcome::Success(output)) = (ctx.tool_id, &mut *outcome) else {
+            return Ok(());
+        };
+        if ctx.raw_output {
+            return Ok(());
+        }
+
+        let template = match transforms::tool_transform(&self.pool, &tool_id).await {
+            Ok(Some(template)) => template,
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                warn!("TransformHook: failed to load template for {tool_id}: {e}");
+                return Ok(());
+            }
+        };
+        match template.rules().apply(output) {
+            Ok(transformed) => *output = transformed,
+            Err(e) => warn!("TransformHook: template for {tool_id} not applied: {e}"),
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "TransformHook"
+    }
+}
@@ -59,17 +59,40 @@ pub fn mcp_router_with_manifest(
     manifest_path: Option<std::path::PathBuf>,
     encryption_key: String,
 ) -> axum::Router {
-    let pool_for_service = pool.clone();
-
-    let hook_pipeline = Arc::new(hooks::default_pipeline(pool.clone()));
     let client_pool = Arc::new(match manifest_path {
         Some(path) => ClientPool::with_manifest(path),
         None => ClientPool::new(),
     });
+    mcp_router_with_client_pool(pool, config_cache, ct, client_pool, encryption_key)
+}
+
+/// Build an Axum router around a caller-owned client pool.
+///
+/// Lets the host share the pool with other subsystems (e.g. the REST API's
+/// metrics registry, which exports its size as a gauge).
+pub fn mcp_router_with_client_pool(
+    pool: PgPool,
+    config_cache: Arc<RwLock<ConfigCache>>,
+    ct: CancellationToken,
+    client_pool: Arc<ClientPool>,
+    encryption_key: String,
+) -> axum::Router {
+    let pool_for_service = pool.clone();
+
+    let hook_pipeline = Arc::new(hooks::default_pipeline(pool.clone()));
 
     // @awa-impl: PLAN-030 Phase 2.3 — spawn idle timeout reaper
     let _reaper = client_pool.spawn_reaper(client_pool.idle_timeout());
 
+    // Pre-connect keep-warm servers in the background so startup isn't blocked.
+    let warm_pool = client_pool.clone();
+    let warm_db = pool.clone();
+    tokio::spawn(async move {
+        if let Err(e) = warm_pool.warm_up(&warm_db).await {
+            tracing::warn!("MCP keep-warm warm-up failed: {e}");
+        }
+    });
+
     let service: StreamableHttpService<server::NizeMcpServer, LocalSessionManager> =
         StreamableHttpService::new(
             move || {
@@ -14,11 +14,14 @@ use sqlx::PgPool;
 use tokio::sync::RwLock;
 
 use crate::auth::McpUser;
-use crate::hooks::{HookContext, HookPipeline, HookScope, ToolCallOutcome};
+use crate::hooks::{HookContext, HookError, HookPipeline, HookScope, ToolCallOutcome};
 use crate::tools::discovery::{
     BrowseToolDomainRequest, DiscoverToolsRequest, ExecuteToolRequest, GetToolSchemaRequest,
 };
 use crate::tools::hello::HelloRequest;
+use crate::tools::knowledge::{
+    AppendNoteRequest, GetDocumentRequest, ListConversationsRequest, SearchDocumentsRequest,
+};
 use crate::tools::types::{
     DiscoveredTool, DiscoveryResult, ServerInfo as ToolServerInfo, ToolDomain,
 };
@@ -43,6 +46,10 @@ pub struct NizeMcpServer {
     tool_router: ToolRouter<Self>,
 }
 
+/// `_meta` key of an `execute_tool` result whose output was transformed;
+/// holds the untransformed output as `original`.
+pub const TRANSFORM_META_KEY: &str = "nize/transform";
+
 /// Extract the authenticated user from rmcp request context.
 ///
 /// The auth middleware inserts `McpUser` into HTTP request extensions;
@@ -64,13 +71,50 @@ fn json_result<T: serde::Serialize>(value: &T) -> Result<CallToolResult, ErrorDa
     Ok(CallToolResult::success(vec![Content::text(json)]))
 }
 
+/// Tool-call error result carrying the structured error taxonomy, so agents
+/// can decide programmatically whether to retry.
+fn mcp_error_result(e: &nize_core::mcp::McpError) -> CallToolResult {
+    CallToolResult::structured_error(serde_json::json!({ "error": e.info() }))
+}
+
+/// Map an internal failure to a tool-call error.
+fn internal_error(e: impl std::fmt::Display) -> ErrorData {
+    ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None)
+}
+
+/// Parse a UUID tool parameter.
+fn parse_id(value: &str, field: &str) -> Result<uuid::Uuid, ErrorData> {
+    uuid::Uuid::parse_str(value).map_err(|e| {
+        ErrorData::new(
+            ErrorCode::INVALID_PARAMS,
+            format!("Invalid {field}: {e}"),
+            None,
+        )
+    })
+}
+
+/// Map a lookup failure, treating a missing row as "not found or access
+/// denied" so tools never reveal other users' resources.
+fn not_found_or(e: sqlx::Error, what: &str) -> ErrorData {
+    match e {
+        sqlx::Error::RowNotFound => ErrorData::new(
+            ErrorCode::INVALID_PARAMS,
+            format!("{what} not found or access denied"),
+            None,
+        ),
+        e => internal_error(e),
+    }
+}
+
 /// Helper to create a hook context for meta-tools (no server_id).
-fn meta_hook_ctx(user_id: &str, tool_name: &str) -> HookContext {
+fn meta_hook_ctx(user: &McpUser, tool_name: &str) -> HookContext {
     HookContext {
-        user_id: user_id.to_string(),
+        user_id: user.id.clone(),
         server_id: None,
         tool_name: tool_name.to_string(),
         tool_id: None,
+        read_only: user.read_only,
+        raw_output: false,
         scope: HookScope::Global,
         timestamp: chrono::Utc::now(),
     }
@@ -96,6 +140,26 @@ impl NizeMcpServer {
         }
     }
 
+    /// Order tools within each domain by the user's routing preferences,
+    /// demoting servers whose circuit is open.
+    async fn rank_by_route<T>(
+        &self,
+        user_id: &str,
+        items: Vec<T>,
+        key: impl Fn(&T) -> (&str, uuid::Uuid),
+    ) -> Result<Vec<T>, ErrorData> {
+        let routes = nize_core::mcp::routing::effective_routes(&self.pool, user_id)
+            .await
+            .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let circuits = self.client_pool.circuits();
+        Ok(nize_core::mcp::routing::rank_by_route(
+            items,
+            &routes,
+            key,
+            |id| !circuits.is_open(id),
+        ))
+    }
+
     /// Return tool definitions registered in this server.
     #[cfg(test)]
     pub(crate) fn list_tools() -> Vec<rmcp::model::Tool> {
@@ -126,7 +190,7 @@ impl NizeMcpServer {
     ) -> Result<CallToolResult, ErrorData> {
         let user = extract_user(&parts)?;
         let mut params = serde_json::json!({"query": query, "domain": domain});
-        let ctx = meta_hook_ctx(&user.id, "discover_tools");
+        let ctx = meta_hook_ctx(&user, "discover_tools");
 
         self.hook_pipeline
             .run_before(&ctx, &mut params)
@@ -139,6 +203,7 @@ impl NizeMcpServer {
             user_id: user.id.clone(),
             top_k: Some(10),
             min_similarity: Some(0.5),
+            selection: user.tool_selection.clone(),
         };
 
         let rows = nize_core::mcp::discovery::discover_tools(
@@ -149,6 +214,9 @@ impl NizeMcpServer {
         )
         .await
         .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let rows = self
+            .rank_by_route(&user.id, rows, |r| (r.domain.as_str(), r.server_id))
+            .await?;
 
         // Build response matching the existing DiscoveryResult shape
         let mut servers = std::collections::HashMap::new();
@@ -168,7 +236,7 @@ impl NizeMcpServer {
                     description: row.tool_description.clone(),
                     domain: row.domain.clone(),
                     server_id: row.server_id.to_string(),
-                    score: row.similarity,
+                    score: row.rerank_score.unwrap_or(row.similarity),
                 }
             })
             .collect();
@@ -204,23 +272,28 @@ impl NizeMcpServer {
     ) -> Result<CallToolResult, ErrorData> {
         let user = extract_user(&parts)?;
         let mut params = serde_json::json!({"toolId": tool_id});
-        let ctx = meta_hook_ctx(&user.id, "get_tool_schema");
+        let ctx = meta_hook_ctx(&user, "get_tool_schema");
 
         self.hook_pipeline
             .run_before(&ctx, &mut params)
             .await
             .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
 
-        let tool = nize_core::mcp::queries::get_tool_manifest(&self.pool, &user.id, &tool_id)
-            .await
-            .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?
-            .ok_or_else(|| {
-                ErrorData::new(
-                    ErrorCode::INVALID_PARAMS,
-                    format!("Tool {tool_id} not found or access denied"),
-                    None,
-                )
-            })?;
+        let tool = nize_core::mcp::queries::get_tool_manifest(
+            &self.pool,
+            &user.id,
+            &tool_id,
+            user.tool_selection.as_ref(),
+        )
+        .await
+        .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?
+        .ok_or_else(|| {
+            ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("Tool {tool_id} not found or access denied"),
+                None,
+            )
+        })?;
 
         // Return the manifest JSONB directly — it contains the full tool schema
         let manifest = &tool.manifest;
@@ -237,10 +310,12 @@ impl NizeMcpServer {
     async fn execute_tool(
         &self,
         Extension(parts): Extension<http::request::Parts>,
+        call_meta: Meta,
         Parameters(ExecuteToolRequest {
             tool_id,
             tool_name,
             params,
+            raw,
         }): Parameters<ExecuteToolRequest>,
     ) -> Result<CallToolResult, ErrorData> {
         let user = extract_user(&parts)?;
@@ -263,6 +338,8 @@ impl NizeMcpServer {
             server_id: None, // Will be filled after lookup
             tool_name: tool_name.clone(),
             tool_id: Some(tool_uuid),
+            read_only: user.read_only,
+            raw_output: raw,
             scope: HookScope::Global,
             timestamp: chrono::Utc::now(),
         };
@@ -270,23 +347,45 @@ impl NizeMcpServer {
         self.hook_pipeline
             .run_before(&ctx, &mut hook_params)
             .await
-            .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+            .map_err(|e| {
+                let code = match e {
+                    HookError::InvalidParams(_) => ErrorCode::INVALID_PARAMS,
+                    _ => ErrorCode::INTERNAL_ERROR,
+                };
+                ErrorData::new(code, e.to_string(), None)
+            })?;
 
+        // Run the tool with the params as the hooks left them, e.g. with
+        // profile variables resolved.
+        let params = match hook_params.get_mut("params").map(serde_json::Value::take) {
+            Some(serde_json::Value::Object(map)) => Some(map),
+            _ => params,
+        };
         let exec_request = nize_core::mcp::execution::ExecutionRequest {
             tool_id: tool_uuid,
             tool_name: tool_name.clone(),
             params,
             user_id: user.id.clone(),
+            selection: user.tool_selection.clone(),
+            context: user.context.clone().with_call_meta(&call_meta.0),
+            correlation: user.correlation.clone(),
         };
 
-        let result = nize_core::mcp::execution::execute_tool(
+        let mut result = match nize_core::mcp::execution::execute_tool(
             &self.pool,
             &self.client_pool,
             &exec_request,
             &self.encryption_key,
         )
         .await
-        .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        {
+            Ok(result) => result,
+            Err(e) => {
+                let mut outcome = ToolCallOutcome::Error(e.to_string());
+                let _ = self.hook_pipeline.run_after(&ctx, &mut outcome).await;
+                return Ok(mcp_error_result(&e));
+            }
+        };
 
         let mut outcome = if result.success {
             ToolCallOutcome::Success(result.result.clone())
@@ -295,7 +394,24 @@ impl NizeMcpServer {
         };
         let _ = self.hook_pipeline.run_after(&ctx, &mut outcome).await;
 
-        json_result(&result)
+        // An output transform replaced the output: return the transformed
+        // one, with the original in `_meta` for the chat trace.
+        let original = match outcome {
+            ToolCallOutcome::Success(output) if output != result.result => {
+                Some(std::mem::replace(&mut result.result, output))
+            }
+            _ => None,
+        };
+        let mut response = json_result(&result)?;
+        if let Some(original) = original {
+            let mut meta = Meta::new();
+            meta.insert(
+                TRANSFORM_META_KEY.to_string(),
+                serde_json::json!({ "original": original }),
+            );
+            response.meta = Some(meta);
+        }
+        Ok(response)
     }
 
     // @awa-impl: MCP-1.4_AC-1
@@ -307,16 +423,20 @@ impl NizeMcpServer {
     ) -> Result<CallToolResult, ErrorData> {
         let user = extract_user(&parts)?;
         let mut params = serde_json::json!({});
-        let ctx = meta_hook_ctx(&user.id, "list_tool_domains");
+        let ctx = meta_hook_ctx(&user, "list_tool_domains");
 
         self.hook_pipeline
             .run_before(&ctx, &mut params)
             .await
             .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
 
-        let domain_rows = nize_core::mcp::queries::list_tool_domains(&self.pool, &user.id)
-            .await
-            .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let domain_rows = nize_core::mcp::queries::list_tool_domains(
+            &self.pool,
+            &user.id,
+            user.tool_selection.as_ref(),
+        )
+        .await
+        .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
 
         let domains: Vec<ToolDomain> = domain_rows
             .into_iter()
@@ -345,17 +465,24 @@ impl NizeMcpServer {
     ) -> Result<CallToolResult, ErrorData> {
         let user = extract_user(&parts)?;
         let mut params = serde_json::json!({"domainId": domain_id});
-        let ctx = meta_hook_ctx(&user.id, "browse_tool_domain");
+        let ctx = meta_hook_ctx(&user, "browse_tool_domain");
 
         self.hook_pipeline
             .run_before(&ctx, &mut params)
             .await
             .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
 
-        let tool_rows =
-            nize_core::mcp::queries::browse_tool_domain(&self.pool, &user.id, &domain_id)
-                .await
-                .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let tool_rows = nize_core::mcp::queries::browse_tool_domain(
+            &self.pool,
+            &user.id,
+            &domain_id,
+            user.tool_selection.as_ref(),
+        )
+        .await
+        .map_err(|e| ErrorData::new(ErrorCode::INTERNAL_ERROR, e.to_string(), None))?;
+        let tool_rows = self
+            .rank_by_route(&user.id, tool_rows, |r| (r.domain.as_str(), r.server_id))
+            .await?;
 
         let mut servers = std::collections::HashMap::new();
         let tools: Vec<DiscoveredTool> = tool_rows
@@ -399,13 +526,248 @@ impl NizeMcpServer {
 
         json_result(&result)
     }
+
+    /// Search the user's uploaded documents.
+    #[tool(
+        description = "Search the user's Nize documents (files, OCR text and audio transcripts) for passages relevant to a query"
+    )]
+    async fn search_documents(
+        &self,
+        Extension(parts): Extension<http::request::Parts>,
+        Parameters(SearchDocumentsRequest { query, limit }): Parameters<SearchDocumentsRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let user = extract_user(&parts)?;
+        let user_id = parse_id(&user.id, "user ID")?;
+        let mut params = serde_json::json!({"query": query, "limit": limit});
+        let ctx = meta_hook_ctx(&user, "search_documents");
+
+        self.hook_pipeline
+            .run_before(&ctx, &mut params)
+            .await
+            .map_err(internal_error)?;
+
+        let limit = limit
+            .unwrap_or(nize_core::documents::search::DEFAULT_TOP_K)
+            .clamp(1, 50);
+        // MCP tokens carry no workspace, so only personal documents.
+        let hits = nize_core::documents::search::search_documents(
+            &self.pool,
+            &self.config_cache,
+            &self.encryption_key,
+            &nize_core::workspaces::Scope::personal(user_id),
+            &query,
+            limit,
+            0.0,
+        )
+        .await
+        .map_err(internal_error)?;
+
+        let result = serde_json::json!({
+            "results": hits
+                .iter()
+                .map(|h| serde_json::json!({
+                    "documentId": h.document_id,
+                    "filename": h.filename,
+                    "location": nize_core::documents::search::hit_location(h),
+                    "email": nize_core::documents::search::hit_email(h),
+                    "content": h.content,
+                    "similarity": h.similarity,
+                    "rerankScore": h.rerank_score,
+                }))
+                .collect::<Vec<_>>(),
+        });
+
+        let mut outcome = ToolCallOutcome::Success(result.clone());
+        let _ = self.hook_pipeline.run_after(&ctx, &mut outcome).await;
+
+        json_result(&result)
+    }
+
+    /// Get a document's metadata and extracted text.
+    #[tool(description = "Get a Nize document's metadata and full extracted text")]
+    async fn get_document(
+        &self,
+        Extension(parts): Extension<http::request::Parts>,
+        Parameters(GetDocumentRequest { document_id }): Parameters<GetDocumentRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let user = extract_user(&parts)?;
+        let user_id = parse_id(&user.id, "user ID")?;
+        let id = parse_id(&document_id, "document_id")?;
+        let mut params = serde_json::json!({"documentId": document_id});
+        let ctx = meta_hook_ctx(&user, "get_document");
+
+        self.hook_pipeline
+            .run_before(&ctx, &mut params)
+            .await
+            .map_err(internal_error)?;
+
+        let scope = nize_core::workspaces::Scope::personal(user_id);
+        let document = nize_core::documents::get_document(&self.pool, &scope, &id)
+            .await
+            .map_err(|e| not_found_or(e, &format!("Document {document_id}")))?;
+        let chunks = nize_core::documents::list_chunks(&self.pool, &id)
+            .await
+            .map_err(internal_error)?;
+
+        let result = serde_json::json!({
+            "id": document.id,
+            "filename": document.filename,
+            "mimeType": document.mime_type,
+            "size": document.size_bytes,
+            "createdAt": document.created_at.to_rfc3339(),
+            "chunks": chunks
+                .iter()
+                .map(|c| serde_json::json!({
+                    "page": c.page,
+                    "startMs": c.start_ms,
+                    "endMs": c.end_ms,
+                    "content": c.content,
+                }))
+                .collect::<Vec<_>>(),
+        });
+
+        let mut outcome = ToolCallOutcome::Success(result.clone());
+        let _ = self.hook_pipeline.run_after(&ctx, &mut outcome).await;
+
+        json_result(&result)
+    }
+
+    /// List the user's personal conversations.
+    #[tool(description = "List the user's Nize chat conversations, most recent first")]
+    async fn list_conversations(
+        &self,
+        Extension(parts): Extension<http::request::Parts>,
+        Parameters(ListConversationsRequest { limit, offset }): Parameters<
+            ListConversationsRequest,
+        >,
+    ) -> Result<CallToolResult, ErrorData> {
+        let user = extract_user(&parts)?;
+        let user_id = parse_id(&user.id, "user ID")?;
+        let mut params = serde_json::json!({"limit": limit, "offset": offset});
+        let ctx = meta_hook_ctx(&user, "list_conversations");
+
+        self.hook_pipeline
+            .run_before(&ctx, &mut params)
+            .await
+            .map_err(internal_error)?;
+
+        // MCP tokens carry no workspace, so only personal conversations.
+        let limit = limit.unwrap_or(20).clamp(1, 100);
+        let offset = offset.unwrap_or(0).max(0);
+        let (rows, total) = nize_core::conversations::list_conversations(
+            &self.pool,
+            &nize_core::workspaces::Scope::personal(user_id),
+            None,
+            limit,
+            offset,
+        )
+        .await
+        .map_err(internal_error)?;
+
+        let result = serde_json::json!({
+            "items": rows
+                .iter()
+                .map(|c| serde_json::json!({
+                    "id": c.id,
+                    "title": c.title,
+                    "updatedAt": c.updated_at.to_rfc3339(),
+                }))
+                .collect::<Vec<_>>(),
+            "total": total,
+        });
+
+        let mut outcome = ToolCallOutcome::Success(result.clone());
+        let _ = self.hook_pipeline.run_after(&ctx, &mut outcome).await;
+
+        json_result(&result)
+    }
+
+    /// Append markdown to one of the user's notes.
+    #[tool(description = "Append markdown text to the end of one of the user's Nize notes")]
+    async fn append_note(
+        &self,
+        Extension(parts): Extension<http::request::Parts>,
+        Parameters(AppendNoteRequest { note_id, text }): Parameters<AppendNoteRequest>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let user = extract_user(&parts)?;
+        let user_id = parse_id(&user.id, "user ID")?;
+        let id = parse_id(&note_id, "note_id")?;
+        if text.trim().is_empty() {
+            return Err(ErrorData::new(
+                ErrorCode::INVALID_PARAMS,
+                "text must not be empty".to_string(),
+                None,
+            ));
+        }
+        let mut params = serde_json::json!({"noteId": note_id, "text": text});
+        let ctx = meta_hook_ctx(&user, "append_note");
+
+        self.hook_pipeline
+            .run_before(&ctx, &mut params)
+            .await
+            .map_err(internal_error)?;
+
+        // Same storage quota as editing the note over the API.
+        nize_core::notes::get_note(&self.pool, &user_id, &id)
+            .await
+            .map_err(|e| not_found_or(e, &format!("Note {note_id}")))?;
+        nize_core::quotas::ensure_within(
+            &self.pool,
+            &self.config_cache,
+            &user_id,
+            &[(
+                nize_core::quotas::Quota::StorageBytes,
+                text.len() as i64 + 2,
+            )],
+        )
+        .await
+        .map_err(|e| match e {
+            nize_core::quotas::QuotaError::Exceeded(e) => {
+                ErrorData::new(ErrorCode::INVALID_REQUEST, e.to_string(), None)
+            }
+            e => internal_error(e),
+        })?;
+
+        let note = nize_core::notes::append_to_note(&self.pool, &user_id, &id, &text)
+            .await
+            .map_err(|e| not_found_or(e, &format!("Note {note_id}")))?;
+
+        // Re-embed in the background, as the API does after an edit.
+        let (pool, cache, key) = (
+            self.pool.clone(),
+            self.config_cache.clone(),
+            self.encryption_key.clone(),
+        );
+        tokio::spawn(async move {
+            if let Err(e) =
+                nize_core::embedding::indexer::embed_note(&pool, &cache, &id, &key).await
+            {
+                tracing::warn!("Failed to embed note {id}: {e}");
+            }
+        });
+
+        let result = serde_json::json!({
+            "id": note.id,
+            "title": note.title,
+            "updatedAt": note.updated_at.to_rfc3339(),
+        });
+
+        let mut outcome = ToolCallOutcome::Success(result.clone());
+        let _ = self.hook_pipeline.run_after(&ctx, &mut outcome).await;
+
+        json_result(&result)
+    }
 }
 
 #[tool_handler]
 impl ServerHandler for NizeMcpServer {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
-            instructions: Some("Nize MCP server — tools for interacting with Nize".into()),
+            instructions: Some(
+                "Nize MCP server — tools for interacting with Nize: discover and run the \
+                 user's MCP tools, and search or add to their Nize knowledge base"
+                    .into(),
+            ),
             capabilities: ServerCapabilities::builder().enable_tools().build(),
             ..Default::default()
         }
@@ -28,8 +28,11 @@ pub struct ExecuteToolRequest {
     pub tool_id: String,
     /// Human-readable tool name for display.
     pub tool_name: String,
-    /// Parameters matching the tool schema (JSON object). Omit or pass null for tools with no parameters.
+    /// Parameters matching the tool schema (JSON object). Omit or pass null for tools with no parameters. String values may reference the user's profile as {{user.id}}, {{user.email}}, {{user.name}}, {{user.timezone}}, {{workspace.id}} or {{workspace.name}}; these are filled in before the tool runs.
     pub params: Option<serde_json::Map<String, serde_json::Value>>,
+    /// Return the tool's full output, skipping the output template an admin configured for it. Use only when the shortened output lacks what you need.
+    #[serde(default)]
+    pub raw: bool,
 }
 
 /// Parameters for the `browse_tool_domain` meta-tool.
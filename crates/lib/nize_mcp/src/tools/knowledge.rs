@@ -0,0 +1,42 @@
+//! Knowledge-base tools — parameter types.
+//!
+//! These tools are served by Nize itself rather than proxied to an external
+//! MCP server: they read and write the token owner's documents, notes and
+//! conversations directly.
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Parameters for the `search_documents` tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SearchDocumentsRequest {
+    /// What to look for, in natural language.
+    pub query: String,
+    /// Maximum number of passages (1-50, default 5).
+    pub limit: Option<i64>,
+}
+
+/// Parameters for the `get_document` tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetDocumentRequest {
+    /// Document ID from `search_documents` results.
+    pub document_id: String,
+}
+
+/// Parameters for the `list_conversations` tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ListConversationsRequest {
+    /// Maximum number of conversations (1-100, default 20).
+    pub limit: Option<i64>,
+    /// Number of conversations to skip.
+    pub offset: Option<i64>,
+}
+
+/// Parameters for the `append_note` tool.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AppendNoteRequest {
+    /// ID of the note to append to.
+    pub note_id: String,
+    /// Markdown to add at the end of the note.
+    pub text: String,
+}
@@ -3,6 +3,7 @@
 pub mod discovery;
 pub mod dummy;
 pub mod hello;
+pub mod knowledge;
 pub mod types;
 
 #[cfg(test)]
@@ -12,16 +12,20 @@ mod tests {
 
     // @awa-test: MCP-1_AC-1
     #[test]
-    fn server_exposes_six_tools() {
+    fn server_exposes_ten_tools() {
         let tools = NizeMcpServer::list_tools();
         let names: Vec<&str> = tools.iter().map(|t| t.name.as_ref()).collect();
-        assert_eq!(tools.len(), 6, "Expected 6 tools, got: {names:?}");
+        assert_eq!(tools.len(), 10, "Expected 10 tools, got: {names:?}");
         assert!(names.contains(&"hello"));
         assert!(names.contains(&"discover_tools"));
         assert!(names.contains(&"get_tool_schema"));
         assert!(names.contains(&"execute_tool"));
         assert!(names.contains(&"list_tool_domains"));
         assert!(names.contains(&"browse_tool_domain"));
+        assert!(names.contains(&"search_documents"));
+        assert!(names.contains(&"get_document"));
+        assert!(names.contains(&"list_conversations"));
+        assert!(names.contains(&"append_note"));
     }
 
     // @awa-test: MCP-1.1_AC-1
@@ -0,0 +1,147 @@
+// @awa-component: PLAN-029-AgentLoop
+
+import { stepCountIs, type StepResult, type StopCondition, type ToolSet } from "ai";
+import type { ChatConfig } from "./types";
+import type { TransformedOutput } from "./mcp-client";
+
+// ============================================================================
+// Guards
+// ============================================================================
+
+/** Limits of the model → tools → model loop for one reply */
+export interface LoopLimits {
+  /** Maximum number of model steps */
+  maxSteps: number;
+  /** Input + output token budget over all steps (0 = unlimited) */
+  maxTokens: number;
+  /** Identical tool calls (same tool and arguments) allowed per reply (0 = unlimited) */
+  maxRepeatedCalls: number;
+}
+
+/** Why the loop stopped */
+export type StopReason = "completed" | "max_steps" | "token_budget" | "repeated_tool_call";
+
+type AnyStep = Pick<StepResult<ToolSet>, "toolCalls" | "usage">;
+
+export function loopLimits(config: ChatConfig): LoopLimits {
+  return {
+    maxSteps: config.toolsMaxSteps,
+    maxTokens: config.toolsMaxTokensPerTurn,
+    maxRepeatedCalls: config.toolsMaxRepeatedCalls,
+  };
+}
+
+function stepTokens(step: AnyStep): number {
+  return step.usage.totalTokens ?? (step.usage.inputTokens ?? 0) + (step.usage.outputTokens ?? 0);
+}
+
+/** Tokens used by `steps` */
+export function usedTokens(steps: AnyStep[]): number {
+  return steps.reduce((sum, step) => sum + stepTokens(step), 0);
+}
+
+/** Highest number of identical tool calls in `steps` */
+export function maxRepeats(steps: AnyStep[]): number {
+  const counts = new Map<string, number>();
+  let max = 0;
+  for (const step of steps) {
+    for (const call of step.toolCalls) {
+      const key = `${call.toolName}:${JSON.stringify(call.input)}`;
+      const count = (counts.get(key) ?? 0) + 1;
+      counts.set(key, count);
+      max = Math.max(max, count);
+    }
+  }
+  return max;
+}
+
+/** The budget or repetition guard `steps` tripped, if any */
+export function trippedGuard(steps: AnyStep[], limits: LoopLimits): StopReason | null {
+  if (limits.maxTokens > 0 && usedTokens(steps) >= limits.maxTokens) return "token_budget";
+  if (limits.maxRepeatedCalls > 0 && maxRepeats(steps) >= limits.maxRepeatedCalls) return "repeated_tool_call";
+  return null;
+}
+
+/** Why a loop that produced `steps` stopped */
+export function stopReason(steps: AnyStep[], limits: LoopLimits): StopReason {
+  const last = steps[steps.length - 1];
+  if (!last || last.toolCalls.length === 0) {
+    // A guard trips before the final step, which then answers without tools
+    return trippedGuard(steps.slice(0, -1), limits) ?? "completed";
+  }
+  return trippedGuard(steps, limits) ?? (steps.length >= limits.maxSteps ? "max_steps" : "completed");
+}
+
+/**
+ * Options for streamText/generateText that run the tool loop under the
+ * guards. Once the token budget or repetition guard trips, the next step may
+ * not call tools, so the model answers with what it has instead of stopping
+ * mid-task; the loop stops after that step even if the provider ignores it.
+ */
+export function loopOptions(limits: LoopLimits): {
+  stopWhen: StopCondition<ToolSet>[];
+  prepareStep: (options: { steps: AnyStep[] }) => { toolChoice: "none" } | undefined;
+} {
+  return {
+    stopWhen: [stepCountIs(limits.maxSteps), ({ steps }) => trippedGuard(steps.slice(0, -1), limits) !== null],
+    prepareStep: ({ steps }) => (trippedGuard(steps, limits) ? { toolChoice: "none" } : undefined),
+  };
+}
+
+// ============================================================================
+// Trace
+// ============================================================================
+
+/** A trace event, as stored by the Rust API */
+export interface TraceEvent {
+  type: string;
+  timestamp: string;
+  [key: string]: unknown;
+}
+
+/** Records one event per model step and the reason the loop stopped */
+export class LoopTrace {
+  readonly events: TraceEvent[] = [];
+  private readonly steps: AnyStep[] = [];
+
+  constructor(private readonly limits: LoopLimits) {}
+
+  onStep(step: AnyStep & Pick<StepResult<ToolSet>, "finishReason">): void {
+    this.steps.push(step);
+    this.events.push({
+      type: "step",
+      timestamp: new Date().toISOString(),
+      index: this.steps.length - 1,
+      finishReason: step.finishReason,
+      toolCalls: step.toolCalls.map((call) => ({ toolName: call.toolName, input: call.input })),
+      inputTokens: step.usage.inputTokens ?? null,
+      outputTokens: step.usage.outputTokens ?? null,
+    });
+  }
+
+  /** Record the original outputs of tool results an output template transformed */
+  onTransformedOutputs(outputs: TransformedOutput[]): void {
+    for (const output of outputs) {
+      this.events.push({
+        type: "tool_output_transformed",
+        timestamp: new Date().toISOString(),
+        toolId: output.toolId ?? null,
+        toolName: output.toolName ?? null,
+        original: output.original,
+      });
+    }
+  }
+
+  /** Append the stop event and return all events */
+  finish(): TraceEvent[] {
+    this.events.push({
+      type: "loop_stop",
+      timestamp: new Date().toISOString(),
+      reason: stopReason(this.steps, this.limits),
+      steps: this.steps.length,
+      totalTokens: usedTokens(this.steps),
+      limits: this.limits,
+    });
+    return this.events;
+  }
+}
@@ -1,32 +1,41 @@
 // @awa-component: PLAN-027-HonoApp
 
 import { Hono } from "hono";
-import { processChat, ConversationNotFoundError } from "./chat-service";
+import { processChat, runEval, runRegenerate, runStructuredChat, runTask, ConversationNotFoundError } from "./chat-service";
+import { responseFormatError } from "./structured-output";
 import { fetchChatConfig } from "./chat-config";
-import type { ChatRequest } from "./types";
+import type { ChatRequest, EvalRunRequest, RegenerateRequest, TaskRunRequest } from "./types";
 
 /**
  * Hono app that handles chat requests.
  *
- * Mounted at `/api` basePath — expects POST /chat.
+ * Mounted at `/api` basePath — expects POST /chat, POST /chat/tasks/run,
+ * POST /chat/evals/run and POST /chat/regenerate.
  * Auth is delegated to the Rust API (cookie forwarded on all backend calls).
  */
 export const chatApp = new Hono().basePath("/api");
 
+// Resolve API base URL:
+// - NIZE_API_URL env var (explicit)
+// - NIZE_API_PORT env var (e.g. set by nize-web server.js)
+// - fallback to localhost:3001
+function resolveApiBaseUrl(): string {
+  return process.env.NIZE_API_URL ?? (process.env.NIZE_API_PORT ? `http://127.0.0.1:${process.env.NIZE_API_PORT}` : "http://127.0.0.1:3001");
+}
+
+// @awa-impl: PLAN-029-2.3 — resolve MCP base URL for tool calling
+// - NIZE_MCP_URL env var (explicit)
+// - NIZE_MCP_PORT env var (e.g. set by nize-web-server.mjs)
+// - fallback to localhost:19560
+function resolveMcpBaseUrl(): string {
+  return process.env.NIZE_MCP_URL ?? (process.env.NIZE_MCP_PORT ? `http://127.0.0.1:${process.env.NIZE_MCP_PORT}` : "http://127.0.0.1:19560");
+}
+
 chatApp.post("/chat", async (c) => {
   const cookie = c.req.header("cookie") ?? "";
 
-  // Resolve API base URL:
-  // - NIZE_API_URL env var (explicit)
-  // - NIZE_API_PORT env var (e.g. set by nize-web server.js)
-  // - fallback to localhost:3001
-  const apiBaseUrl = process.env.NIZE_API_URL ?? (process.env.NIZE_API_PORT ? `http://127.0.0.1:${process.env.NIZE_API_PORT}` : "http://127.0.0.1:3001");
-
-  // @awa-impl: PLAN-029-2.3 — resolve MCP base URL for tool calling
-  // - NIZE_MCP_URL env var (explicit)
-  // - NIZE_MCP_PORT env var (e.g. set by nize-web-server.mjs)
-  // - fallback to localhost:19560
-  const mcpBaseUrl = process.env.NIZE_MCP_URL ?? (process.env.NIZE_MCP_PORT ? `http://127.0.0.1:${process.env.NIZE_MCP_PORT}` : "http://127.0.0.1:19560");
+  const apiBaseUrl = resolveApiBaseUrl();
+  const mcpBaseUrl = resolveMcpBaseUrl();
 
   try {
     // Parse request body
@@ -35,10 +44,23 @@ chatApp.post("/chat", async (c) => {
     if (!body.messages || !Array.isArray(body.messages) || body.messages.length === 0) {
       return c.json({ error: "validation_error", message: "messages array is required" }, 400);
     }
+    if (body.responseFormat !== undefined) {
+      const formatError = responseFormatError(body.responseFormat);
+      if (formatError) {
+        return c.json({ error: "validation_error", message: formatError }, 400);
+      }
+    }
+
+    body.locale ??= c.req.header("accept-language");
 
     // Fetch config from Rust API
     const config = await fetchChatConfig(apiBaseUrl, cookie);
 
+    // Structured replies are checked (and retried) whole, so not streamed
+    if (body.responseFormat) {
+      return c.json(await runStructuredChat(body, body.responseFormat, config, apiBaseUrl, cookie, mcpBaseUrl));
+    }
+
     // Process chat
     const result = await processChat(body, config, apiBaseUrl, cookie, mcpBaseUrl);
 
@@ -59,3 +81,81 @@ chatApp.post("/chat", async (c) => {
     return c.json({ error: "internal_error", message: "Internal server error" }, 500);
   }
 });
+
+/**
+ * Run a scheduled task (called by the Rust task scheduler with a token for
+ * the task owner). Responds with the conversation ID and the reply text.
+ */
+chatApp.post("/chat/tasks/run", async (c) => {
+  const cookie = c.req.header("cookie") ?? "";
+  const apiBaseUrl = resolveApiBaseUrl();
+  const mcpBaseUrl = resolveMcpBaseUrl();
+
+  try {
+    const body = (await c.req.json()) as TaskRunRequest;
+
+    if (!body.prompt || typeof body.prompt !== "string" || !body.name) {
+      return c.json({ error: "validation_error", message: "name and prompt are required" }, 400);
+    }
+
+    const config = await fetchChatConfig(apiBaseUrl, cookie);
+    const result = await runTask(body, config, apiBaseUrl, cookie, mcpBaseUrl);
+
+    return c.json(result);
+  } catch (error) {
+    console.error("Task run error:", error instanceof Error ? error.stack : error);
+    return c.json({ error: "internal_error", message: error instanceof Error ? error.message : "Internal server error" }, 500);
+  }
+});
+
+/**
+ * Run one eval case (called by the Rust eval runner with a token for the
+ * admin who started the run). Responds with the reply text and tool calls.
+ */
+chatApp.post("/chat/evals/run", async (c) => {
+  const cookie = c.req.header("cookie") ?? "";
+  const apiBaseUrl = resolveApiBaseUrl();
+  const mcpBaseUrl = resolveMcpBaseUrl();
+
+  try {
+    const body = (await c.req.json()) as EvalRunRequest;
+
+    if (!body.prompt || typeof body.prompt !== "string") {
+      return c.json({ error: "validation_error", message: "prompt is required" }, 400);
+    }
+
+    const config = await fetchChatConfig(apiBaseUrl, cookie);
+    const result = await runEval(body, config, apiBaseUrl, cookie, mcpBaseUrl);
+
+    return c.json(result);
+  } catch (error) {
+    console.error("Eval run error:", error instanceof Error ? error.stack : error);
+    return c.json({ error: "internal_error", message: error instanceof Error ? error.message : "Internal server error" }, 500);
+  }
+});
+
+/**
+ * Regenerate an assistant turn (called by the Rust API with a token for the
+ * caller). Responds with the new reply; the Rust API stores it.
+ */
+chatApp.post("/chat/regenerate", async (c) => {
+  const cookie = c.req.header("cookie") ?? "";
+  const apiBaseUrl = resolveApiBaseUrl();
+  const mcpBaseUrl = resolveMcpBaseUrl();
+
+  try {
+    const body = (await c.req.json()) as RegenerateRequest;
+
+    if (!body.conversationId || !Array.isArray(body.messages) || body.messages.length === 0) {
+      return c.json({ error: "validation_error", message: "conversationId and messages are required" }, 400);
+    }
+
+    const config = await fetchChatConfig(apiBaseUrl, cookie);
+    const result = await runRegenerate(body, config, apiBaseUrl, cookie, mcpBaseUrl);
+
+    return c.json(result);
+  } catch (error) {
+    console.error("Regenerate error:", error instanceof Error ? error.stack : error);
+    return c.json({ error: "internal_error", message: error instanceof Error ? error.message : "Internal server error" }, 500);
+  }
+});
@@ -6,7 +6,9 @@ import { ChatConfig, DEFAULT_CHAT_CONFIG, DEFAULT_TOOLS_SYSTEM_PROMPT } from "./
  * Fetch chat configuration from the Rust API.
  *
  * Reads agent.model.name, agent.model.temperature,
- * agent.compaction.maxMessages, and agent.baseUrl.* from the config endpoint.
+ * agent.compaction.maxMessages, agent.context.*, agent.tools.*,
+ * agent.trace.enabled, agent.baseUrl.* and providers.compatible from the
+ * config endpoint.
  *
  * @param apiBaseUrl - Base URL of the Rust API (e.g. "http://127.0.0.1:3001")
  * @param cookie - Cookie header to forward for auth
@@ -42,19 +44,42 @@ export async function fetchChatConfig(apiBaseUrl: string, cookie: string): Promi
     if (anthropicBaseUrl) baseUrls.anthropic = anthropicBaseUrl;
     if (openaiBaseUrl) baseUrls.openai = openaiBaseUrl;
     if (googleBaseUrl) baseUrls.google = googleBaseUrl;
+    for (const provider of parseCompatibleProviders(get("providers.compatible", ""))) {
+      baseUrls[provider.name] = provider.baseUrl;
+    }
 
     return {
       modelName: get("agent.model.name", DEFAULT_CHAT_CONFIG.modelName),
       temperature: parseFloat(get("agent.model.temperature", String(DEFAULT_CHAT_CONFIG.temperature))),
       compactionMaxMessages: parseInt(get("agent.compaction.maxMessages", String(DEFAULT_CHAT_CONFIG.compactionMaxMessages)), 10),
+      contextTargetTokens: parseInt(get("agent.context.targetTokens", String(DEFAULT_CHAT_CONFIG.contextTargetTokens)), 10),
+      summarizerModel: get("agent.context.summarizerModel", DEFAULT_CHAT_CONFIG.summarizerModel),
       baseUrls: Object.keys(baseUrls).length > 0 ? baseUrls : undefined,
       // @awa-impl: PLAN-029-3.4 — read tool calling config
       toolsEnabled: get("agent.tools.enabled", String(DEFAULT_CHAT_CONFIG.toolsEnabled)) === "true",
       toolsMaxSteps: parseInt(get("agent.tools.maxSteps", String(DEFAULT_CHAT_CONFIG.toolsMaxSteps)), 10),
+      toolsMaxTokensPerTurn: parseInt(get("agent.tools.maxTokensPerTurn", String(DEFAULT_CHAT_CONFIG.toolsMaxTokensPerTurn)), 10),
+      toolsMaxRepeatedCalls: parseInt(get("agent.tools.maxRepeatedCalls", String(DEFAULT_CHAT_CONFIG.toolsMaxRepeatedCalls)), 10),
       toolsSystemPrompt: get("agent.tools.systemPrompt", DEFAULT_TOOLS_SYSTEM_PROMPT),
+      traceEnabled: get("agent.trace.enabled", String(DEFAULT_CHAT_CONFIG.traceEnabled)) === "true",
     };
   } catch (error) {
     console.error("Error fetching chat config, using defaults:", error);
     return { ...DEFAULT_CHAT_CONFIG };
   }
 }
+
+/**
+ * Parse the providers.compatible config value (validated by the API when
+ * saved); anything unreadable counts as no providers.
+ */
+function parseCompatibleProviders(value: string): Array<{ name: string; baseUrl: string }> {
+  if (!value.trim()) return [];
+  try {
+    const parsed: unknown = JSON.parse(value);
+    if (!Array.isArray(parsed)) return [];
+    return parsed.filter((p): p is { name: string; baseUrl: string } => typeof p?.name === "string" && typeof p?.baseUrl === "string");
+  } catch {
+    return [];
+  }
+}
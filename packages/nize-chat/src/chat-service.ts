@@ -1,12 +1,14 @@
 // @awa-component: PLAN-027-ChatService
 
-import { streamText, convertToModelMessages, stepCountIs, type UIMessage, type ToolSet } from "ai";
-import type { ChatConfig, ChatRequest, CompactMessage } from "./types";
+import { generateText, streamText, convertToModelMessages, type LanguageModel, type ModelMessage, type UIMessage, type ToolSet } from "ai";
+import type { ChatConfig, ChatRequest, EvalRunRequest, EvalRunResult, RegenerateRequest, RegenerateResult, StructuredChatResult, TaskRunRequest, TaskRunResult } from "./types";
 import { getChatModel, getProviderFromSpec } from "./model-registry";
 import type { GetChatModelOptions } from "./model-registry";
-import { maybeCompact } from "./compaction";
+import { createSummarizer, manageContext, summaryMessage, type RollingSummary } from "./context-manager";
 import { createProxyFetch } from "./proxy-fetch";
 import { createMcpSession } from "./mcp-client";
+import { LoopTrace, loopLimits, loopOptions, type TraceEvent } from "./agent-loop";
+import { MAX_STRUCTURED_ATTEMPTS, outputOption, parseStructured, retryMessage, structuredSystemMessage, type ResponseFormat, type StructuredOutput } from "./structured-output";
 
 // ============================================================================
 // Helpers
@@ -23,14 +25,6 @@ function getMessageText(message: UIMessage): string {
   return (message as unknown as { content?: string }).content ?? "";
 }
 
-/** Convert UIMessages to CompactMessages for compaction (text-only) */
-function toCompactMessages(messages: UIMessage[]): CompactMessage[] {
-  return messages.map((msg) => ({
-    role: msg.role as "user" | "assistant" | "system",
-    content: getMessageText(msg),
-  }));
-}
-
 // ============================================================================
 // Title Generation
 // ============================================================================
@@ -63,15 +57,33 @@ async function generateTitle(userMessage: string, modelSpec: string, modelOption
 // Rust API Helpers
 // ============================================================================
 
-async function getOrCreateConversation(apiBaseUrl: string, cookie: string, conversationId?: string): Promise<{ id: string; title: string; isNew: boolean }> {
+interface Conversation {
+  id: string;
+  title: string;
+  isNew: boolean;
+  summary?: RollingSummary;
+  /** False when the API is read-only: nothing is written back. */
+  persist: boolean;
+}
+
+/** Whether the Rust API reported read-only mode on this response. */
+function isReadOnly(res: Response): boolean {
+  return res.headers.get("x-nize-read-only") === "true";
+}
+
+async function getOrCreateConversation(apiBaseUrl: string, cookie: string, conversationId?: string): Promise<Conversation> {
   if (conversationId) {
     // Validate conversation exists and belongs to user
     const res = await fetch(`${apiBaseUrl}/api/conversations/${conversationId}`, { headers: { cookie } });
     if (!res.ok) {
+      // Read-only servers hand out ephemeral IDs that never hit the database
+      if (res.status === 404 && isReadOnly(res)) {
+        return { id: conversationId, title: "New Chat", isNew: false, persist: false };
+      }
       throw new ConversationNotFoundError("Conversation not found");
     }
-    const data = (await res.json()) as { id: string; title: string };
-    return { id: data.id, title: data.title, isNew: false };
+    const data = (await res.json()) as { id: string; title: string; summary?: RollingSummary | null };
+    return { id: data.id, title: data.title, isNew: false, summary: data.summary ?? undefined, persist: !isReadOnly(res) };
   }
 
   // Create new conversation
@@ -80,11 +92,14 @@ async function getOrCreateConversation(apiBaseUrl: string, cookie: string, conve
     headers: { "Content-Type": "application/json", cookie },
     body: JSON.stringify({ title: "New Chat" }),
   });
+  if (res.status === 403 && isReadOnly(res)) {
+    return { id: crypto.randomUUID(), title: "New Chat", isNew: true, persist: false };
+  }
   if (!res.ok) {
     throw new Error(`Failed to create conversation: ${res.status}`);
   }
   const data = (await res.json()) as { id: string; title: string };
-  return { id: data.id, title: data.title, isNew: true };
+  return { id: data.id, title: data.title, isNew: true, persist: true };
 }
 
 async function persistMessages(apiBaseUrl: string, cookie: string, conversationId: string, messages: UIMessage[]): Promise<void> {
@@ -98,6 +113,17 @@ async function persistMessages(apiBaseUrl: string, cookie: string, conversationI
   }
 }
 
+async function saveSummary(apiBaseUrl: string, cookie: string, conversationId: string, summary: RollingSummary): Promise<void> {
+  const res = await fetch(`${apiBaseUrl}/api/conversations/${conversationId}/summary`, {
+    method: "PUT",
+    headers: { "Content-Type": "application/json", cookie },
+    body: JSON.stringify({ summary: summary.text, messageCount: summary.messageCount }),
+  });
+  if (!res.ok) {
+    console.error(`Failed to save summary: ${res.status}`);
+  }
+}
+
 async function updateConversationTitle(apiBaseUrl: string, cookie: string, conversationId: string, title: string): Promise<void> {
   const res = await fetch(`${apiBaseUrl}/api/conversations/${conversationId}`, {
     method: "PATCH",
@@ -109,6 +135,113 @@ async function updateConversationTitle(apiBaseUrl: string, cookie: string, conve
   }
 }
 
+async function recordTrace(apiBaseUrl: string, cookie: string, conversationId: string, messageId: string, events: TraceEvent[]): Promise<void> {
+  const res = await fetch(`${apiBaseUrl}/api/conversations/${conversationId}/trace`, {
+    method: "POST",
+    headers: { "Content-Type": "application/json", cookie },
+    body: JSON.stringify({ messageId, events }),
+  });
+  if (!res.ok) {
+    console.error(`Failed to record trace: ${res.status}`);
+  }
+}
+
+// ============================================================================
+// Model Context
+// ============================================================================
+
+/**
+ * Fit the history into the context budget (rolling summary of older turns +
+ * recent messages verbatim, converted with convertToModelMessages to keep
+ * tool calls/results) and persist a regenerated summary.
+ */
+async function buildModelMessages(
+  messages: UIMessage[],
+  conversation: { id: string; summary?: RollingSummary; persist: boolean },
+  config: ChatConfig,
+  model: LanguageModel,
+  apiBaseUrl: string,
+  cookie: string,
+): Promise<ModelMessage[]> {
+  const summarizerSpec = config.summarizerModel || config.modelName;
+  const summarizerModel =
+    summarizerSpec === config.modelName
+      ? model
+      : getChatModel(summarizerSpec, {
+          fetch: createProxyFetch(apiBaseUrl, cookie, getProviderFromSpec(summarizerSpec)),
+          baseUrls: config.baseUrls,
+        });
+  const context = await manageContext(
+    messages,
+    conversation.summary,
+    { targetTokens: config.contextTargetTokens, maxRecentMessages: config.compactionMaxMessages },
+    createSummarizer(summarizerModel),
+  );
+  if (conversation.persist && context.summaryUpdated && context.summary) {
+    await saveSummary(apiBaseUrl, cookie, conversation.id, context.summary);
+  }
+
+  return [...(context.summary ? [summaryMessage(context.summary.text)] : []), ...(await convertToModelMessages(context.recentMessages))];
+}
+
+// @awa-impl: PLAN-029-3.5 — create MCP session for tool calling
+// A stored conversation's pinned tool selection applies to its session.
+async function openTools(
+  config: ChatConfig,
+  apiBaseUrl: string,
+  cookie: string,
+  mcpBaseUrl?: string,
+  conversationId?: string,
+  locale?: string,
+  turnId?: string,
+): Promise<{ mcpClient: Awaited<ReturnType<typeof createMcpSession>> | null; tools: ToolSet | undefined }> {
+  if (!config.toolsEnabled || !mcpBaseUrl) {
+    return { mcpClient: null, tools: undefined };
+  }
+  try {
+    console.log("[mcp] Creating MCP session...");
+    const mcpClient = await createMcpSession(apiBaseUrl, cookie, mcpBaseUrl, conversationId, locale, turnId);
+    console.log("[mcp] Session created, fetching tools...");
+    const tools = await mcpClient.tools();
+    console.log(`[mcp] Got ${Object.keys(tools).length} tools`);
+    return { mcpClient, tools };
+  } catch (err) {
+    console.error("Failed to create MCP session, continuing without tools:", err);
+    return { mcpClient: null, tools: undefined };
+  }
+}
+
+/** Notes retrieved as context for a message: at most this many chunks, */
+const NOTES_CONTEXT_LIMIT = 5;
+/** each at least this similar to the message. */
+const NOTES_CONTEXT_MIN_SIMILARITY = 0.5;
+
+/**
+ * The user's notes relevant to `query`, as a system message. Best effort:
+ * without embeddings or on any failure the chat goes on without them.
+ */
+async function notesSystemMessages(apiBaseUrl: string, cookie: string, query: string): Promise<{ role: "system"; content: string }[]> {
+  if (!query.trim()) return [];
+  const params = new URLSearchParams({ q: query, limit: String(NOTES_CONTEXT_LIMIT), minSimilarity: String(NOTES_CONTEXT_MIN_SIMILARITY) });
+  try {
+    const res = await fetch(`${apiBaseUrl}/api/notes/search?${params}`, { headers: { cookie } });
+    if (!res.ok) {
+      console.warn(`Notes search failed: ${res.status}`);
+      return [];
+    }
+    const { context } = (await res.json()) as { context?: string };
+    return context ? [{ role: "system", content: `Relevant notes from the user's knowledge base:\n\n${context}` }] : [];
+  } catch (err) {
+    console.error("Notes search failed, continuing without notes:", err);
+    return [];
+  }
+}
+
+/** When tools are enabled, prepend the tools system prompt */
+function toolsSystemMessages(config: ChatConfig, tools: ToolSet | undefined): { role: "system"; content: string }[] {
+  return config.toolsEnabled && tools && config.toolsSystemPrompt ? [{ role: "system", content: config.toolsSystemPrompt }] : [];
+}
+
 // ============================================================================
 // processChat
 // ============================================================================
@@ -120,8 +253,9 @@ export interface ProcessChatResult {
 }
 
 /**
- * Process a chat request: get/create conversation, compact history,
- * stream AI response, persist on finish.
+ * Process a chat request: get/create conversation, fit history into the
+ * context budget, stream the AI response through the guarded tool loop,
+ * persist on finish (with the reply's trace when tracing is enabled).
  */
 // @awa-impl: PLAN-028-3.5
 // @awa-impl: PLAN-029-3.5
@@ -134,17 +268,7 @@ export async function processChat(request: ChatRequest, config: ChatConfig, apiB
 
   // Check if title generation is needed
   const isFirstMessage = allMessages.filter((m) => m.role === "user").length === 1;
-  const shouldGenerateTitle = isFirstMessage && conversation.title === "New Chat";
-
-  // Compaction: flatten to text, run maybeCompact, decide message format
-  const compactMessages = toCompactMessages(allMessages);
-  const compactedState = maybeCompact(compactMessages, config.compactionMaxMessages);
-  const wasCompacted = !!compactedState.summary;
-
-  // Build model messages:
-  // - If compacted: use text-only compacted messages
-  // - Otherwise: use convertToModelMessages to preserve tool calls/results
-  const modelMessages = wasCompacted ? compactedState.messages.filter((msg) => msg.content.trim() !== "").map((msg) => ({ role: msg.role, content: msg.content })) : await convertToModelMessages(allMessages);
+  const shouldGenerateTitle = conversation.persist && isFirstMessage && conversation.title === "New Chat";
 
   // Create proxy fetch and model options for the provider
   const providerType = getProviderFromSpec(config.modelName);
@@ -156,34 +280,21 @@ export async function processChat(request: ChatRequest, config: ChatConfig, apiB
 
   const model = getChatModel(config.modelName, modelOptions);
 
-  // @awa-impl: PLAN-029-3.5 — create MCP session for tool calling
-  let mcpClient: Awaited<ReturnType<typeof createMcpSession>> | null = null;
-  let tools: ToolSet | undefined;
-
-  if (config.toolsEnabled && mcpBaseUrl) {
-    try {
-      console.log("[mcp] Creating MCP session...");
-      mcpClient = await createMcpSession(apiBaseUrl, cookie, mcpBaseUrl);
-      console.log("[mcp] Session created, fetching tools...");
-      tools = await mcpClient.tools();
-      console.log(`[mcp] Got ${Object.keys(tools).length} tools`);
-    } catch (err) {
-      console.error("Failed to create MCP session, continuing without tools:", err);
-      mcpClient = null;
-      tools = undefined;
-    }
-  }
+  const modelMessages = await buildModelMessages(allMessages, conversation, config, model, apiBaseUrl, cookie);
+  const { mcpClient, tools } = await openTools(config, apiBaseUrl, cookie, mcpBaseUrl, conversation.persist ? conversation.id : undefined, request.locale, lastMessage.id);
+  const systemMessages = [...toolsSystemMessages(config, tools), ...(await notesSystemMessages(apiBaseUrl, cookie, userMessageText))];
 
-  // When tools are enabled, prepend the tools system prompt
-  const systemMessages = config.toolsEnabled && tools && config.toolsSystemPrompt ? [{ role: "system" as const, content: config.toolsSystemPrompt }] : [];
+  const limits = loopLimits(config);
+  const trace = new LoopTrace(limits);
 
   const result = streamText({
     model,
     messages: [...systemMessages, ...modelMessages],
     temperature: config.temperature,
-    ...(tools ? { tools, stopWhen: stepCountIs(config.toolsMaxSteps) } : {}),
-    onStepFinish: ({ finishReason, toolCalls }) => {
-      console.log(`[mcp] Step finished: reason=${finishReason}, toolCalls=${toolCalls?.length ?? 0}`);
+    ...(tools ? { tools, ...loopOptions(limits) } : {}),
+    onStepFinish: (step) => {
+      console.log(`[mcp] Step finished: reason=${step.finishReason}, toolCalls=${step.toolCalls?.length ?? 0}`);
+      trace.onStep(step);
     },
     onError: (error) => {
       console.error("Chat stream error:", error);
@@ -197,14 +308,26 @@ export async function processChat(request: ChatRequest, config: ChatConfig, apiB
     toUIMessageStreamResponse: () =>
       result.toUIMessageStreamResponse({
         originalMessages: allMessages,
-        onFinish: async ({ messages: finalMessages }) => {
+        onFinish: async ({ messages: finalMessages, responseMessage }) => {
+          trace.onTransformedOutputs(mcpClient?.transformedOutputs ?? []);
+          const events = trace.finish();
+          const stop = events[events.length - 1];
+          if (stop.reason !== "completed") {
+            console.warn(`[mcp] Tool loop stopped early: ${stop.reason}`);
+          }
+
           // Persist all messages via Rust API — do this BEFORE closing the
           // MCP client so the database is still healthy. Closing the MCP
           // session can trigger PGlite instability, so treat it as best-effort.
-          try {
-            await persistMessages(apiBaseUrl, cookie, conversation.id, finalMessages);
-          } catch (err) {
-            console.error("Failed to persist messages:", err);
+          if (conversation.persist) {
+            try {
+              await persistMessages(apiBaseUrl, cookie, conversation.id, finalMessages);
+              if (config.traceEnabled) {
+                await recordTrace(apiBaseUrl, cookie, conversation.id, responseMessage.id, events);
+              }
+            } catch (err) {
+              console.error("Failed to persist messages:", err);
+            }
           }
 
           // Close MCP client after messages are persisted
@@ -229,6 +352,261 @@ export async function processChat(request: ChatRequest, config: ChatConfig, apiB
   };
 }
 
+// ============================================================================
+// runStructuredChat
+// ============================================================================
+
+/**
+ * Process a chat request whose reply must be JSON matching
+ * `format.schema`: generate without streaming, constrained by the provider's
+ * structured output where it has one, check the reply against the schema
+ * and ask again with the problems found, up to MAX_STRUCTURED_ATTEMPTS
+ * times. The last reply is persisted with the structured payload in its
+ * message metadata, whether or not it passed.
+ */
+export async function runStructuredChat(
+  request: ChatRequest,
+  format: ResponseFormat,
+  config: ChatConfig,
+  apiBaseUrl: string,
+  cookie: string,
+  mcpBaseUrl?: string,
+): Promise<StructuredChatResult> {
+  const conversation = await getOrCreateConversation(apiBaseUrl, cookie, request.conversationId);
+
+  const allMessages = request.messages;
+  const lastMessage = allMessages[allMessages.length - 1];
+  const userMessageText = getMessageText(lastMessage);
+  const isFirstMessage = allMessages.filter((m) => m.role === "user").length === 1;
+  const shouldGenerateTitle = conversation.persist && isFirstMessage && conversation.title === "New Chat";
+
+  const modelOptions: GetChatModelOptions = {
+    fetch: createProxyFetch(apiBaseUrl, cookie, getProviderFromSpec(config.modelName)),
+    baseUrls: config.baseUrls,
+  };
+  const model = getChatModel(config.modelName, modelOptions);
+
+  const modelMessages = await buildModelMessages(allMessages, conversation, config, model, apiBaseUrl, cookie);
+  const { mcpClient, tools } = await openTools(config, apiBaseUrl, cookie, mcpBaseUrl, conversation.persist ? conversation.id : undefined, request.locale, lastMessage.id);
+
+  const limits = loopLimits(config);
+  const trace = new LoopTrace(limits);
+
+  try {
+    let messages: ModelMessage[] = [...toolsSystemMessages(config, tools), structuredSystemMessage(format), ...modelMessages];
+    let attempts = 0;
+    let text: string;
+    let structured: StructuredOutput;
+    do {
+      attempts += 1;
+      const result = await generateText({
+        model,
+        messages,
+        temperature: config.temperature,
+        output: outputOption(format),
+        ...(tools ? { tools, ...loopOptions(limits) } : {}),
+        onStepFinish: (step) => trace.onStep(step),
+      });
+      text = result.text;
+      structured = parseStructured(text, format.schema, attempts);
+      if (structured.status !== "valid") {
+        console.warn(`[structured] Attempt ${attempts} ${structured.status}: ${structured.errors.join("; ")}`);
+        messages = [...messages, ...result.response.messages, retryMessage(structured)];
+      }
+    } while (structured.status !== "valid" && attempts < MAX_STRUCTURED_ATTEMPTS);
+
+    const reply: UIMessage = { id: crypto.randomUUID(), role: "assistant", parts: [{ type: "text", text }], metadata: { structured } };
+    if (conversation.persist) {
+      await persistMessages(apiBaseUrl, cookie, conversation.id, [...allMessages, reply]);
+      if (config.traceEnabled) {
+        trace.onTransformedOutputs(mcpClient?.transformedOutputs ?? []);
+        await recordTrace(apiBaseUrl, cookie, conversation.id, reply.id, trace.finish());
+      }
+    }
+
+    if (shouldGenerateTitle) {
+      generateTitle(userMessageText, config.modelName, modelOptions)
+        .then((title) => updateConversationTitle(apiBaseUrl, cookie, conversation.id, title))
+        .catch((err) => console.error("Title generation failed:", err));
+    }
+
+    return { conversationId: conversation.id, messageId: reply.id, content: text, structured };
+  } finally {
+    if (mcpClient) {
+      try {
+        await mcpClient.close();
+      } catch (err) {
+        console.error("Failed to close MCP client:", err);
+      }
+    }
+  }
+}
+
+// ============================================================================
+// runTask
+// ============================================================================
+
+/**
+ * Run a scheduled task: append the task prompt to its conversation (created
+ * on first run, titled after the task), generate the reply without streaming
+ * and persist both messages.
+ */
+export async function runTask(request: TaskRunRequest, config: ChatConfig, apiBaseUrl: string, cookie: string, mcpBaseUrl?: string): Promise<TaskRunResult> {
+  let conversationId = request.conversationId;
+  let history: UIMessage[] = [];
+  let summary: RollingSummary | undefined;
+
+  if (conversationId) {
+    const res = await fetch(`${apiBaseUrl}/api/conversations/${conversationId}`, { headers: { cookie } });
+    if (res.ok) {
+      const data = (await res.json()) as { messages: UIMessage[]; summary?: RollingSummary | null };
+      history = data.messages ?? [];
+      summary = data.summary ?? undefined;
+    } else {
+      // The conversation was deleted; start a new one
+      conversationId = undefined;
+    }
+  }
+
+  if (!conversationId) {
+    const res = await fetch(`${apiBaseUrl}/api/conversations`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json", cookie },
+      body: JSON.stringify({ title: request.name.slice(0, 50) }),
+    });
+    if (!res.ok) {
+      throw new Error(`Failed to create conversation: ${res.status}`);
+    }
+    conversationId = ((await res.json()) as { id: string }).id;
+  }
+
+  const prompt: UIMessage = { id: crypto.randomUUID(), role: "user", parts: [{ type: "text", text: request.prompt }] };
+  const messages = [...history, prompt];
+
+  const model = getChatModel(config.modelName, {
+    fetch: createProxyFetch(apiBaseUrl, cookie, getProviderFromSpec(config.modelName)),
+    baseUrls: config.baseUrls,
+  });
+  const modelMessages = await buildModelMessages(messages, { id: conversationId, summary, persist: true }, config, model, apiBaseUrl, cookie);
+  const { mcpClient, tools } = await openTools(config, apiBaseUrl, cookie, mcpBaseUrl);
+
+  try {
+    const result = await generateText({
+      model,
+      messages: [...toolsSystemMessages(config, tools), ...modelMessages],
+      temperature: config.temperature,
+      ...(tools ? { tools, ...loopOptions(loopLimits(config)) } : {}),
+    });
+
+    const reply: UIMessage = { id: crypto.randomUUID(), role: "assistant", parts: [{ type: "text", text: result.text }] };
+    await persistMessages(apiBaseUrl, cookie, conversationId, [...messages, reply]);
+
+    return { conversationId, content: result.text };
+  } finally {
+    if (mcpClient) {
+      try {
+        await mcpClient.close();
+      } catch (err) {
+        console.error("Failed to close MCP client:", err);
+      }
+    }
+  }
+}
+
+// ============================================================================
+// runEval
+// ============================================================================
+
+/**
+ * Run one eval case: send the prompt as a fresh single-message chat with the
+ * configured system prompt and tools (optionally on another model) and
+ * report the reply and the tools called. Nothing is persisted.
+ */
+export async function runEval(request: EvalRunRequest, config: ChatConfig, apiBaseUrl: string, cookie: string, mcpBaseUrl?: string): Promise<EvalRunResult> {
+  const evalConfig = request.model ? { ...config, modelName: request.model } : config;
+  const prompt: UIMessage = { id: crypto.randomUUID(), role: "user", parts: [{ type: "text", text: request.prompt }] };
+
+  const model = getChatModel(evalConfig.modelName, {
+    fetch: createProxyFetch(apiBaseUrl, cookie, getProviderFromSpec(evalConfig.modelName)),
+    baseUrls: evalConfig.baseUrls,
+  });
+  const modelMessages = await buildModelMessages([prompt], { id: prompt.id, persist: false }, evalConfig, model, apiBaseUrl, cookie);
+  const { mcpClient, tools } = await openTools(evalConfig, apiBaseUrl, cookie, mcpBaseUrl);
+
+  try {
+    const result = await generateText({
+      model,
+      messages: [...toolsSystemMessages(evalConfig, tools), ...modelMessages],
+      temperature: evalConfig.temperature,
+      ...(tools ? { tools, ...loopOptions(loopLimits(evalConfig)) } : {}),
+    });
+
+    return {
+      content: result.text,
+      toolCalls: result.steps.flatMap((step) => step.toolCalls.map((call) => call.toolName)),
+    };
+  } finally {
+    if (mcpClient) {
+      try {
+        await mcpClient.close();
+      } catch (err) {
+        console.error("Failed to close MCP client:", err);
+      }
+    }
+  }
+}
+
+// ============================================================================
+// runRegenerate
+// ============================================================================
+
+/**
+ * Regenerate an assistant turn: answer the given history again, with the
+ * request's temperature, model and tool usage overriding the config, and
+ * return the reply. The Rust API stores it; nothing is persisted here.
+ */
+export async function runRegenerate(request: RegenerateRequest, config: ChatConfig, apiBaseUrl: string, cookie: string, mcpBaseUrl?: string): Promise<RegenerateResult> {
+  const regenConfig: ChatConfig = {
+    ...config,
+    ...(request.model ? { modelName: request.model } : {}),
+    ...(request.temperature != null ? { temperature: request.temperature } : {}),
+    ...(request.toolsEnabled != null ? { toolsEnabled: request.toolsEnabled } : {}),
+  };
+
+  const model = getChatModel(regenConfig.modelName, {
+    fetch: createProxyFetch(apiBaseUrl, cookie, getProviderFromSpec(regenConfig.modelName)),
+    baseUrls: regenConfig.baseUrls,
+  });
+  const modelMessages = await buildModelMessages(
+    request.messages,
+    { id: request.conversationId, summary: request.summary ?? undefined, persist: false },
+    regenConfig,
+    model,
+    apiBaseUrl,
+    cookie,
+  );
+  const { mcpClient, tools } = await openTools(regenConfig, apiBaseUrl, cookie, mcpBaseUrl, request.conversationId, undefined, request.messages[request.messages.length - 1]?.id);
+
+  try {
+    const result = await generateText({
+      model,
+      messages: [...toolsSystemMessages(regenConfig, tools), ...modelMessages],
+      temperature: regenConfig.temperature,
+      ...(tools ? { tools, ...loopOptions(loopLimits(regenConfig)) } : {}),
+    });
+
+    return { message: { id: crypto.randomUUID(), role: "assistant", parts: [{ type: "text", text: result.text }] } };
+  } finally {
+    if (mcpClient) {
+      try {
+        await mcpClient.close();
+      } catch (err) {
+        console.error("Failed to close MCP client:", err);
+      }
+    }
+  }
+}
+
 // ============================================================================
 // Errors
 // ============================================================================
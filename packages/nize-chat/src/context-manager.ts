@@ -0,0 +1,186 @@
+// @awa-component: PLAN-027-ContextManager
+
+import { generateText, type LanguageModel, type UIMessage } from "ai";
+
+// Rough per-message framing cost (role markers, separators)
+const MESSAGE_OVERHEAD_TOKENS = 4;
+
+// Longest text taken from a single message when building the summarizer transcript
+const TRANSCRIPT_MESSAGE_CHARS = 4000;
+
+// =============================================================================
+// Types
+// =============================================================================
+
+/** Rolling summary stored on the conversation */
+export interface RollingSummary {
+  /** Summary text */
+  text: string;
+  /** Number of leading messages the summary covers */
+  messageCount: number;
+}
+
+/** Limits for the composed context */
+export interface ContextBudget {
+  /** Approximate token budget for summary + recent messages */
+  targetTokens: number;
+  /** Maximum number of messages sent verbatim */
+  maxRecentMessages: number;
+}
+
+/** Which messages are summarized and which are sent verbatim */
+export interface ContextPlan {
+  /** Per-message token estimates, parallel to the input messages */
+  tokenCounts: number[];
+  /** Number of leading messages covered by the (still valid) stored summary */
+  summarizedCount: number;
+  /** Index of the first message sent verbatim */
+  recentStart: number;
+}
+
+/** Folds `turns` into `previous` (if any) and returns the new summary text */
+export type Summarize = (previous: string | undefined, turns: UIMessage[]) => Promise<string>;
+
+/** Result of {@link manageContext} */
+export interface ManagedContext {
+  /** Summary to send ahead of the recent messages, if any */
+  summary?: RollingSummary;
+  /** Messages to send verbatim */
+  recentMessages: UIMessage[];
+  /** Whether `summary` was (re)generated and should be persisted */
+  summaryUpdated: boolean;
+}
+
+// =============================================================================
+// Token Estimation
+// =============================================================================
+
+/** Approximate token count of a string (~4 characters per token) */
+export function estimateTokens(text: string): number {
+  return Math.ceil(text.length / 4);
+}
+
+/**
+ * Approximate token count of a message. Counts all parts, so tool inputs
+ * and outputs are included.
+ */
+export function estimateMessageTokens(message: UIMessage): number {
+  return MESSAGE_OVERHEAD_TOKENS + estimateTokens(JSON.stringify(message.parts ?? []));
+}
+
+// =============================================================================
+// Planning
+// =============================================================================
+
+/**
+ * Decide which messages to send verbatim.
+ *
+ * While the stored summary plus every later message fits the budget, nothing
+ * new is summarized. Otherwise the newest messages are kept within half the
+ * budget and everything between the summary and them is folded in, so the
+ * next few turns fit without summarizing again. The last message is always
+ * kept, and the verbatim window starts at a user message where possible.
+ *
+ * A stored summary covering as many messages as the conversation has is
+ * stale (history was rewritten) and is ignored.
+ */
+export function planContext(messages: readonly UIMessage[], stored: RollingSummary | undefined, budget: ContextBudget): ContextPlan {
+  const tokenCounts = messages.map(estimateMessageTokens);
+  const summarizedCount = stored && stored.messageCount < messages.length ? stored.messageCount : 0;
+  const summaryTokens = summarizedCount > 0 && stored ? estimateTokens(stored.text) : 0;
+
+  const tokensFrom = (start: number) => tokenCounts.slice(start).reduce((sum, n) => sum + n, 0);
+
+  if (summaryTokens + tokensFrom(summarizedCount) <= budget.targetTokens && messages.length - summarizedCount <= budget.maxRecentMessages) {
+    return { tokenCounts, summarizedCount, recentStart: summarizedCount };
+  }
+
+  const keepBudget = Math.floor(budget.targetTokens / 2);
+  const maxRecent = Math.max(1, budget.maxRecentMessages);
+  let recentStart = messages.length - 1;
+  let kept = tokenCounts[recentStart];
+  while (recentStart > summarizedCount && messages.length - recentStart < maxRecent && kept + tokenCounts[recentStart - 1] <= keepBudget) {
+    recentStart -= 1;
+    kept += tokenCounts[recentStart];
+  }
+  while (recentStart < messages.length - 1 && messages[recentStart].role !== "user") {
+    recentStart += 1;
+  }
+
+  return { tokenCounts, summarizedCount, recentStart };
+}
+
+// =============================================================================
+// Summarization
+// =============================================================================
+
+/** Render messages as a plain-text transcript for the summarizer */
+export function toTranscript(messages: readonly UIMessage[]): string {
+  return messages
+    .map((message) => {
+      const text = (message.parts ?? [])
+        .map((part) => {
+          if (part.type === "text") return part.text;
+          if (part.type.startsWith("tool-")) return `[used tool ${part.type.slice("tool-".length)}]`;
+          return "";
+        })
+        .filter((text) => text !== "")
+        .join("\n");
+      const clipped = text.length > TRANSCRIPT_MESSAGE_CHARS ? `${text.slice(0, TRANSCRIPT_MESSAGE_CHARS)}...` : text;
+      return `${message.role}: ${clipped}`;
+    })
+    .join("\n\n");
+}
+
+/** Create a {@link Summarize} function backed by a language model */
+export function createSummarizer(model: LanguageModel): Summarize {
+  return async (previous, turns) => {
+    const prior = previous ? `Existing summary of the conversation so far:\n${previous}\n\n` : "";
+    const result = await generateText({
+      model,
+      system:
+        "You maintain a running summary of a conversation between a user and an AI assistant. " +
+        "Preserve facts, decisions, user preferences, open questions and results of tool use. " +
+        "Write concise prose in the third person. Return only the updated summary.",
+      prompt: `${prior}New turns to fold into the summary:\n${toTranscript(turns)}`,
+    });
+    return result.text.trim();
+  };
+}
+
+/** System message carrying the rolling summary ahead of the recent messages */
+export function summaryMessage(text: string): { role: "system"; content: string } {
+  return { role: "system", content: `[Summary of earlier conversation]\n${text}` };
+}
+
+// =============================================================================
+// Context Management
+// =============================================================================
+
+/**
+ * Compose the context for a model call as rolling summary + recent messages,
+ * summarizing older turns when the history exceeds the budget.
+ *
+ * If summarization fails the stored summary is kept and the turns that would
+ * have been folded in are dropped from this request.
+ */
+export async function manageContext(messages: readonly UIMessage[], stored: RollingSummary | undefined, budget: ContextBudget, summarize: Summarize): Promise<ManagedContext> {
+  const plan = planContext(messages, stored, budget);
+  const current = plan.summarizedCount > 0 ? stored : undefined;
+  const recentMessages = messages.slice(plan.recentStart);
+
+  if (plan.recentStart === plan.summarizedCount) {
+    return { summary: current, recentMessages, summaryUpdated: false };
+  }
+
+  try {
+    const text = await summarize(current?.text, messages.slice(plan.summarizedCount, plan.recentStart));
+    if (text) {
+      return { summary: { text, messageCount: plan.recentStart }, recentMessages, summaryUpdated: true };
+    }
+    console.error("Summarizer returned an empty summary, dropping older turns");
+  } catch (err) {
+    console.error("Failed to summarize older turns, dropping them:", err);
+  }
+  return { summary: current, recentMessages, summaryUpdated: false };
+}
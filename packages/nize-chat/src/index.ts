@@ -1,14 +1,19 @@
 // @awa-component: PLAN-027-Barrel
 
 export { chatApp } from "./app";
-export { processChat, ConversationNotFoundError } from "./chat-service";
+export { processChat, runEval, runRegenerate, runStructuredChat, runTask, ConversationNotFoundError } from "./chat-service";
 export type { ProcessChatResult } from "./chat-service";
 export { fetchChatConfig } from "./chat-config";
 export { createMcpSession } from "./mcp-client";
+export type { TransformedOutput } from "./mcp-client";
 export { maybeCompact, DefaultToolOutputSummarizer } from "./compaction";
 export type { ToolOutputSummarizer } from "./compaction";
+export { createSummarizer, estimateMessageTokens, estimateTokens, manageContext, planContext, summaryMessage } from "./context-manager";
+export type { ContextBudget, ContextPlan, ManagedContext, RollingSummary, Summarize } from "./context-manager";
 export { getChatModel, getProviderFromSpec } from "./model-registry";
 export type { GetChatModelOptions } from "./model-registry";
 export { createProxyFetch } from "./proxy-fetch";
-export type { ChatRequest, ChatConfig, CompactMessage, CompactState, ContextSummary } from "./types";
+export { parseStructured, responseFormatError, validateJson } from "./structured-output";
+export type { JsonSchema, ResponseFormat, StructuredOutput } from "./structured-output";
+export type { ChatRequest, EvalRunRequest, EvalRunResult, RegenerateRequest, RegenerateResult, StructuredChatResult, TaskRunRequest, TaskRunResult, ChatConfig, CompactMessage, CompactState, ContextSummary } from "./types";
 export { DEFAULT_CHAT_CONFIG, DEFAULT_TOOLS_SYSTEM_PROMPT } from "./types";
@@ -8,9 +8,25 @@ const MCP_TOKEN_NAME = "nize-desktop-chat";
 /** Timeout for MCP client connection + initialization (ms). */
 const MCP_CONNECT_TIMEOUT_MS = 10_000;
 
+/** Header naming the conversation a session serves (see nize_mcp auth). */
+const CONVERSATION_HEADER = "X-Nize-Conversation";
+
+/** Header naming the conversation turn a session serves (see nize_mcp auth). */
+const TURN_HEADER = "X-Nize-Turn";
+
 /** MCP protocol version (must match rmcp's LATEST_PROTOCOL_VERSION). */
 const MCP_PROTOCOL_VERSION = "2025-03-26";
 
+/** `_meta` key of execute_tool results whose output an admin template transformed (see nize_mcp server). */
+const TRANSFORM_META_KEY = "nize/transform";
+
+/** The untransformed output of an execute_tool call, kept for the chat trace */
+export interface TransformedOutput {
+  toolId?: string;
+  toolName?: string;
+  original: unknown;
+}
+
 // ============================================================================
 // Custom Streamable HTTP Transport
 // ============================================================================
@@ -32,6 +48,10 @@ class StreamableHttpTransport implements MCPTransport {
   private headers: Record<string, string>;
   private sessionId?: string;
   private abortController?: AbortController;
+  /** Arguments of tools/call requests awaiting a response, by request ID */
+  private pendingCalls = new Map<unknown, Record<string, unknown>>();
+  /** Originals of transformed outputs, in arrival order */
+  readonly transformedOutputs: TransformedOutput[] = [];
 
   onclose?: () => void;
   onerror?: (error: Error) => void;
@@ -47,6 +67,11 @@ class StreamableHttpTransport implements MCPTransport {
   }
 
   async send(message: Record<string, unknown>): Promise<void> {
+    if (message.method === "tools/call" && "id" in message) {
+      const params = message.params as { arguments?: Record<string, unknown> } | undefined;
+      this.pendingCalls.set(message.id, params?.arguments ?? {});
+    }
+
     const headers: Record<string, string> = {
       ...this.headers,
       "Content-Type": "application/json",
@@ -82,7 +107,7 @@ class StreamableHttpTransport implements MCPTransport {
     if (contentType.includes("application/json")) {
       const data = await response.json();
       const msgs = Array.isArray(data) ? data : [data];
-      for (const m of msgs) this.onmessage?.(m);
+      for (const m of msgs) this.deliver(m);
       return;
     }
 
@@ -104,6 +129,27 @@ class StreamableHttpTransport implements MCPTransport {
     this.onclose?.();
   }
 
+  /**
+   * Pass a JSON-RPC message on to the client. The original output of a
+   * transformed tool result is moved out of its `_meta` first, so it never
+   * reaches the model.
+   */
+  private deliver(message: { id?: unknown; result?: { _meta?: Record<string, unknown> } }): void {
+    const args = this.pendingCalls.get(message.id);
+    this.pendingCalls.delete(message.id);
+    const meta = message.result?._meta;
+    const transform = meta?.[TRANSFORM_META_KEY] as { original?: unknown } | undefined;
+    if (meta && transform) {
+      delete meta[TRANSFORM_META_KEY];
+      this.transformedOutputs.push({
+        toolId: typeof args?.tool_id === "string" ? args.tool_id : undefined,
+        toolName: typeof args?.tool_name === "string" ? args.tool_name : undefined,
+        original: transform.original,
+      });
+    }
+    this.onmessage?.(message);
+  }
+
   /**
    * Read an SSE stream and deliver JSON-RPC messages via onmessage.
    *
@@ -149,7 +195,7 @@ class StreamableHttpTransport implements MCPTransport {
 
             try {
               const parsed = JSON.parse(data);
-              this.onmessage?.(parsed);
+              this.deliver(parsed);
             } catch {
               // Non-JSON data (e.g. priming events with empty data)
             }
@@ -181,10 +227,16 @@ class StreamableHttpTransport implements MCPTransport {
  * @param apiBaseUrl - Base URL of the Rust API (e.g. "http://127.0.0.1:3001")
  * @param cookie - Cookie header for JWT auth against the REST API
  * @param mcpBaseUrl - Base URL of the MCP server (e.g. "http://127.0.0.1:19560")
- * @returns MCPClient instance (caller must close when done)
+ * @param conversationId - Conversation the session serves; its pinned tool
+ *   selection (if any) restricts the tools the session can discover and run
+ * @param locale - Caller's locale, forwarded to servers configured to receive it
+ * @param turnId - ID of the user message that started the turn; the session's
+ *   tool executions are recorded against it
+ * @returns MCPClient instance (caller must close when done), with the
+ *   originals of tool outputs transformed during the session
  */
 // @awa-impl: PLAN-029-3.2
-export async function createMcpSession(apiBaseUrl: string, cookie: string, mcpBaseUrl: string) {
+export async function createMcpSession(apiBaseUrl: string, cookie: string, mcpBaseUrl: string, conversationId?: string, locale?: string, turnId?: string) {
   // Create/overwrite MCP bearer token via REST API
   const tokenRes = await fetch(`${apiBaseUrl}/api/auth/mcp-tokens`, {
     method: "POST",
@@ -207,11 +259,14 @@ export async function createMcpSession(apiBaseUrl: string, cookie: string, mcpBa
   // without `event: message` — rmcp omits this field, causing a hang.
   const transport = new StreamableHttpTransport(`${mcpBaseUrl}/mcp`, {
     Authorization: `Bearer ${bearerToken}`,
+    ...(conversationId ? { [CONVERSATION_HEADER]: conversationId } : {}),
+    ...(locale ? { "Accept-Language": locale } : {}),
+    ...(conversationId && turnId ? { [TURN_HEADER]: turnId } : {}),
   });
 
   const mcpClient = await Promise.race([createMCPClient({ transport }), new Promise<never>((_, reject) => setTimeout(() => reject(new Error("MCP client connection timed out")), MCP_CONNECT_TIMEOUT_MS))]);
 
   console.log("[mcp] MCP client connected (initialize done)");
 
-  return mcpClient;
+  return Object.assign(mcpClient, { transformedOutputs: transport.transformedOutputs });
 }
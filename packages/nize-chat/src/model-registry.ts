@@ -13,18 +13,20 @@ const PROXY_PLACEHOLDER_KEY = "proxy-managed";
 export interface GetChatModelOptions {
   /** Custom fetch function (e.g. proxy fetch) */
   fetch?: typeof globalThis.fetch;
-  /** Custom base URLs per provider */
+  /** Custom base URLs per provider, including OpenAI-compatible providers */
   baseUrls?: {
     anthropic?: string;
     openai?: string;
     google?: string;
+    [compatibleProvider: string]: string | undefined;
   };
 }
 
 /**
  * Resolve an AI SDK model instance from a `provider:model` spec string.
  *
- * Supported providers: anthropic, openai, google.
+ * Supported providers: anthropic, openai, google, and any OpenAI-compatible
+ * provider with a base URL in `options.baseUrls` (see providers.compatible).
  *
  * @param spec - Model spec, e.g. "anthropic:claude-haiku-4-5-20251001"
  * @param options - Optional custom fetch and base URLs
@@ -65,8 +67,20 @@ export function getChatModel(spec: string, options?: GetChatModelOptions) {
       });
       return google(modelName);
     }
-    default:
-      throw new Error(`Unsupported model provider: ${provider}`);
+    default: {
+      const baseURL = options?.baseUrls?.[provider];
+      if (!baseURL) {
+        throw new Error(`Unsupported model provider: ${provider}`);
+      }
+      // Compatible servers implement Chat Completions, not the Responses API
+      const compatible = createOpenAI({
+        name: provider,
+        apiKey: PROXY_PLACEHOLDER_KEY,
+        baseURL,
+        ...(options?.fetch ? { fetch: options.fetch } : {}),
+      });
+      return compatible.chat(modelName);
+    }
   }
 }
 
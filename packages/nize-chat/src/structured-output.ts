@@ -0,0 +1,197 @@
+// @awa-component: PLAN-027-StructuredOutput
+
+import { jsonSchema, Output } from "ai";
+
+// ============================================================================
+// Response Format
+// ============================================================================
+
+/** A JSON Schema, as sent by the client */
+export type JsonSchema = { [key: string]: unknown };
+
+/** Constrains a reply to JSON matching `schema` */
+export interface ResponseFormat {
+  type: "json_schema";
+  schema: JsonSchema;
+}
+
+/** Structured payload of a reply, stored in its message metadata */
+export interface StructuredOutput {
+  /** `valid`: parsed and matches the schema; `invalid`: parsed but does not match; `unparsed`: not JSON */
+  status: "valid" | "invalid" | "unparsed";
+  /** The parsed reply (null when unparsed) */
+  data: unknown;
+  /** Why the last attempt failed, empty when valid */
+  errors: string[];
+  /** Generation attempts made, including retries */
+  attempts: number;
+}
+
+/** Replies generated before giving up on a schema-conforming one */
+export const MAX_STRUCTURED_ATTEMPTS = 3;
+
+/** Checks a request's `responseFormat`; returns why it is unusable, or null */
+export function responseFormatError(value: unknown): string | null {
+  if (typeof value !== "object" || value === null) return "responseFormat must be an object";
+  const format = value as Partial<ResponseFormat>;
+  if (format.type !== "json_schema") return 'responseFormat.type must be "json_schema"';
+  if (typeof format.schema !== "object" || format.schema === null || Array.isArray(format.schema)) {
+    return "responseFormat.schema must be a JSON schema object";
+  }
+  return null;
+}
+
+/**
+ * `output` option for generateText: providers with native structured output
+ * (OpenAI, Gemini, Anthropic) constrain the reply to the schema; the rest
+ * get the system instruction only, and the reply is checked either way.
+ */
+export function outputOption(format: ResponseFormat) {
+  return Output.object({ schema: jsonSchema(format.schema as Parameters<typeof jsonSchema>[0]) });
+}
+
+/** System message asking for a reply that is only JSON matching the schema */
+export function structuredSystemMessage(format: ResponseFormat): { role: "system"; content: string } {
+  return {
+    role: "system",
+    content: "Respond only with a JSON value that matches this JSON schema, without code fences or any other text:\n" + JSON.stringify(format.schema),
+  };
+}
+
+/** User message asking the model to correct a reply that failed the check */
+export function retryMessage(output: StructuredOutput): { role: "user"; content: string } {
+  const problem = output.status === "unparsed" ? "was not valid JSON" : `did not match the schema:\n- ${output.errors.join("\n- ")}`;
+  return { role: "user", content: `Your previous reply ${problem}\nReply again with only the corrected JSON.` };
+}
+
+// ============================================================================
+// Checking
+// ============================================================================
+
+/** Parse a reply and check it against the schema */
+export function parseStructured(text: string, schema: JsonSchema, attempts: number): StructuredOutput {
+  let data: unknown;
+  try {
+    data = JSON.parse(stripCodeFence(text));
+  } catch (err) {
+    return { status: "unparsed", data: null, errors: [err instanceof Error ? err.message : String(err)], attempts };
+  }
+  const errors = validateJson(data, schema);
+  return { status: errors.length === 0 ? "valid" : "invalid", data, errors, attempts };
+}
+
+/** Models without native structured output often wrap JSON in a fence */
+function stripCodeFence(text: string): string {
+  const match = /^\s*```(?:json)?\s*\n([\s\S]*?)\n?```\s*$/.exec(text);
+  return match ? match[1] : text.trim();
+}
+
+/**
+ * Check `value` against the commonly used subset of JSON Schema: `type`,
+ * `enum`, `const`, `properties`, `required`, `additionalProperties`,
+ * `items`, `minItems`/`maxItems`, `minLength`/`maxLength`,
+ * `minimum`/`maximum` and `anyOf`/`oneOf`. Other keywords are ignored.
+ * Returns one message per violation, prefixed with its JSON path.
+ */
+export function validateJson(value: unknown, schema: JsonSchema, path = "$"): string[] {
+  const errors: string[] = [];
+
+  if (schema.type !== undefined) {
+    const types = Array.isArray(schema.type) ? (schema.type as string[]) : [schema.type as string];
+    if (!types.some((type) => hasType(value, type))) {
+      return [`${path}: expected ${types.join(" or ")}, got ${typeName(value)}`];
+    }
+  }
+  if (Array.isArray(schema.enum) && !schema.enum.some((option) => deepEqual(option, value))) {
+    errors.push(`${path}: must be one of ${JSON.stringify(schema.enum)}`);
+  }
+  if ("const" in schema && !deepEqual(schema.const, value)) {
+    errors.push(`${path}: must be ${JSON.stringify(schema.const)}`);
+  }
+  for (const keyword of ["anyOf", "oneOf"] as const) {
+    const options = schema[keyword];
+    if (Array.isArray(options)) {
+      const matches = options.filter((option) => validateJson(value, option as JsonSchema, path).length === 0).length;
+      if (matches === 0 || (keyword === "oneOf" && matches > 1)) {
+        errors.push(`${path}: must match ${keyword === "oneOf" ? "exactly one" : "at least one"} of the ${keyword} schemas`);
+      }
+    }
+  }
+
+  if (typeof value === "string") {
+    if (typeof schema.minLength === "number" && value.length < schema.minLength) {
+      errors.push(`${path}: must be at least ${schema.minLength} characters`);
+    }
+    if (typeof schema.maxLength === "number" && value.length > schema.maxLength) {
+      errors.push(`${path}: must be at most ${schema.maxLength} characters`);
+    }
+  }
+  if (typeof value === "number") {
+    if (typeof schema.minimum === "number" && value < schema.minimum) errors.push(`${path}: must be >= ${schema.minimum}`);
+    if (typeof schema.maximum === "number" && value > schema.maximum) errors.push(`${path}: must be <= ${schema.maximum}`);
+  }
+
+  if (Array.isArray(value)) {
+    if (typeof schema.minItems === "number" && value.length < schema.minItems) {
+      errors.push(`${path}: must have at least ${schema.minItems} items`);
+    }
+    if (typeof schema.maxItems === "number" && value.length > schema.maxItems) {
+      errors.push(`${path}: must have at most ${schema.maxItems} items`);
+    }
+    if (isSchema(schema.items)) {
+      value.forEach((item, i) => errors.push(...validateJson(item, schema.items as JsonSchema, `${path}[${i}]`)));
+    }
+  } else if (typeof value === "object" && value !== null) {
+    const object = value as Record<string, unknown>;
+    const properties = isSchema(schema.properties) ? (schema.properties as Record<string, JsonSchema>) : {};
+    if (Array.isArray(schema.required)) {
+      for (const key of schema.required as string[]) {
+        if (!(key in object)) errors.push(`${path}: missing required property "${key}"`);
+      }
+    }
+    for (const [key, item] of Object.entries(object)) {
+      if (key in properties) {
+        errors.push(...validateJson(item, properties[key], `${path}.${key}`));
+      } else if (schema.additionalProperties === false) {
+        errors.push(`${path}: unexpected property "${key}"`);
+      } else if (isSchema(schema.additionalProperties)) {
+        errors.push(...validateJson(item, schema.additionalProperties as JsonSchema, `${path}.${key}`));
+      }
+    }
+  }
+
+  return errors;
+}
+
+function isSchema(value: unknown): boolean {
+  return typeof value === "object" && value !== null && !Array.isArray(value);
+}
+
+function hasType(value: unknown, type: string): boolean {
+  switch (type) {
+    case "null":
+      return value === null;
+    case "array":
+      return Array.isArray(value);
+    case "object":
+      return isSchema(value);
+    case "integer":
+      return Number.isInteger(value);
+    case "number":
+    case "string":
+    case "boolean":
+      return typeof value === type;
+    default:
+      return true;
+  }
+}
+
+function typeName(value: unknown): string {
+  if (value === null) return "null";
+  if (Array.isArray(value)) return "array";
+  return typeof value;
+}
+
+function deepEqual(a: unknown, b: unknown): boolean {
+  return JSON.stringify(a) === JSON.stringify(b);
+}
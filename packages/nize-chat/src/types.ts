@@ -1,6 +1,7 @@
 // @awa-component: PLAN-027-Types
 
 import type { UIMessage } from "ai";
+import type { ResponseFormat, StructuredOutput } from "./structured-output";
 
 // ============================================================================
 // Chat Request/Response
@@ -12,6 +13,80 @@ export interface ChatRequest {
   messages: UIMessage[];
   /** Conversation ID (optional, creates new if not provided) */
   conversationId?: string;
+  /** Constrain the reply to JSON matching a schema (optional; the reply is then not streamed) */
+  responseFormat?: ResponseFormat;
+  /** Caller's locale, forwarded to tools that receive it (defaults to the request's Accept-Language) */
+  locale?: string;
+}
+
+/** Reply to a chat request with a responseFormat */
+export interface StructuredChatResult {
+  /** Conversation the reply was appended to */
+  conversationId: string;
+  /** ID of the assistant message */
+  messageId: string;
+  /** Assistant reply text */
+  content: string;
+  /** Parse status and payload of the reply, also stored in the message metadata */
+  structured: StructuredOutput;
+}
+
+/** Scheduled task run requested by the Rust task scheduler */
+export interface TaskRunRequest {
+  /** Task ID */
+  taskId: string;
+  /** Task name (titles the task's conversation) */
+  name: string;
+  /** Prompt sent as the user message */
+  prompt: string;
+  /** Conversation from previous runs (optional, creates new if not provided) */
+  conversationId?: string;
+}
+
+/** Outcome of a task run */
+export interface TaskRunResult {
+  /** Conversation the run was appended to */
+  conversationId: string;
+  /** Assistant reply text */
+  content: string;
+}
+
+/** Eval case requested by the Rust eval runner */
+export interface EvalRunRequest {
+  /** Prompt sent as the only user message */
+  prompt: string;
+  /** Model spec overriding the configured chat model (optional) */
+  model?: string | null;
+}
+
+/** Reply to an eval case */
+export interface EvalRunResult {
+  /** Assistant reply text */
+  content: string;
+  /** Names of the tools called, in call order */
+  toolCalls: string[];
+}
+
+/** Regeneration of an assistant turn requested by the Rust API */
+export interface RegenerateRequest {
+  /** Conversation the turn belongs to (scopes the tool session) */
+  conversationId: string;
+  /** History before the reply, ending with the user message it answered */
+  messages: UIMessage[];
+  /** Stored rolling summary covering the first messageCount messages (optional) */
+  summary?: { text: string; messageCount: number } | null;
+  /** Temperature overriding the configured one (optional) */
+  temperature?: number | null;
+  /** Model spec overriding the configured chat model (optional) */
+  model?: string | null;
+  /** Whether to offer MCP tools, overriding the config (optional) */
+  toolsEnabled?: boolean | null;
+}
+
+/** Regenerated reply */
+export interface RegenerateResult {
+  /** The new assistant message */
+  message: UIMessage;
 }
 
 // ============================================================================
@@ -25,34 +100,50 @@ export interface ChatConfig {
   modelName: string;
   /** Temperature for LLM generation (0–2) */
   temperature: number;
-  /** Max messages before compaction triggers */
+  /** Max messages sent verbatim; older ones are summarized */
   compactionMaxMessages: number;
-  /** Custom base URLs per provider (from agent.baseUrl.* config) */
+  /** Approximate token budget for conversation history (summary + recent messages) */
+  contextTargetTokens: number;
+  /** Model spec used to summarize older turns (empty = use modelName) */
+  summarizerModel: string;
+  /** Custom base URLs per provider (from agent.baseUrl.* config), plus the base URL of each OpenAI-compatible provider (from providers.compatible) */
   baseUrls?: {
     anthropic?: string;
     openai?: string;
     google?: string;
+    [compatibleProvider: string]: string | undefined;
   };
   // @awa-impl: PLAN-029-3.3
   /** Whether MCP tool calling is enabled */
   toolsEnabled: boolean;
   /** Maximum number of tool-call steps per message */
   toolsMaxSteps: number;
+  /** Token budget over all steps of one reply (0 = unlimited) */
+  toolsMaxTokensPerTurn: number;
+  /** Identical tool calls allowed per reply before the loop stops (0 = unlimited) */
+  toolsMaxRepeatedCalls: number;
   /** System prompt to prepend when tools are enabled */
   toolsSystemPrompt: string;
+  /** Record per-step traces of replies for the developer trace view */
+  traceEnabled: boolean;
 }
 
 /** Default system prompt for MCP tools guidance */
 export const DEFAULT_TOOLS_SYSTEM_PROMPT = "You have access to tools for discovering and executing external MCP tools. " + "Use `discover_tools` to find relevant tools, `get_tool_schema` to understand parameters, " + "and `execute_tool` to run them. Use `list_tool_domains` and `browse_tool_domain` to explore available categories.";
 
-/** Default chat configuration values (must match migration 0003_config.sql) */
+/** Default chat configuration values (must match the config_definitions migrations) */
 export const DEFAULT_CHAT_CONFIG: ChatConfig = {
   modelName: "anthropic:claude-haiku-4-5-20251001",
   temperature: 0.7,
   compactionMaxMessages: 20,
+  contextTargetTokens: 24000,
+  summarizerModel: "",
   toolsEnabled: true,
   toolsMaxSteps: 10,
+  toolsMaxTokensPerTurn: 200000,
+  toolsMaxRepeatedCalls: 3,
   toolsSystemPrompt: DEFAULT_TOOLS_SYSTEM_PROMPT,
+  traceEnabled: false,
 };
 
 // ============================================================================
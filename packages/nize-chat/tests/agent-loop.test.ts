@@ -0,0 +1,71 @@
+import { describe, it, expect } from "vitest";
+import { LoopTrace, maxRepeats, stopReason, trippedGuard, usedTokens, type LoopLimits } from "../src/agent-loop.js";
+
+// @awa-test: PLAN-029-AgentLoop
+
+const limits: LoopLimits = { maxSteps: 5, maxTokens: 1000, maxRepeatedCalls: 3 };
+
+function step(tokens: number, calls: Array<[string, unknown]> = []) {
+  return {
+    usage: { inputTokens: tokens, outputTokens: 0, totalTokens: tokens },
+    toolCalls: calls.map(([toolName, input], i) => ({ type: "tool-call", toolCallId: `call-${i}`, toolName, input })),
+    finishReason: calls.length > 0 ? "tool-calls" : "stop",
+  } as unknown as Parameters<LoopTrace["onStep"]>[0];
+}
+
+describe("guards", () => {
+  it("should sum token usage over steps", () => {
+    expect(usedTokens([step(100), step(250)])).toBe(350);
+  });
+
+  it("should count identical calls by tool and arguments", () => {
+    const steps = [step(1, [["search", { q: "a" }]]), step(1, [["search", { q: "b" }]]), step(1, [["search", { q: "a" }]])];
+    expect(maxRepeats(steps)).toBe(2);
+  });
+
+  it("should trip on the token budget and on repetition", () => {
+    expect(trippedGuard([step(600), step(500)], limits)).toBe("token_budget");
+    const repeated = [1, 2, 3].map(() => step(10, [["search", { q: "a" }]]));
+    expect(trippedGuard(repeated, limits)).toBe("repeated_tool_call");
+    expect(trippedGuard([step(10)], limits)).toBeNull();
+    expect(trippedGuard([step(5000)], { ...limits, maxTokens: 0 })).toBeNull();
+  });
+});
+
+describe("stopReason", () => {
+  it("should report a natural finish as completed", () => {
+    expect(stopReason([step(10, [["search", {}]]), step(10)], limits)).toBe("completed");
+  });
+
+  it("should report the guard that forced the final answer", () => {
+    expect(stopReason([step(1200, [["search", {}]]), step(10)], limits)).toBe("token_budget");
+  });
+
+  it("should report running out of steps", () => {
+    const steps = [1, 2, 3, 4, 5].map((i) => step(10, [["search", { i }]]));
+    expect(stopReason(steps, limits)).toBe("max_steps");
+  });
+});
+
+describe("LoopTrace", () => {
+  it("should record one event per step and a stop event", () => {
+    const trace = new LoopTrace(limits);
+    trace.onStep(step(100, [["search", { q: "a" }]]));
+    trace.onStep(step(50));
+    const events = trace.finish();
+
+    expect(events.map((e) => e.type)).toEqual(["step", "step", "loop_stop"]);
+    expect(events[0].toolCalls).toEqual([{ toolName: "search", input: { q: "a" } }]);
+    expect(events[2]).toMatchObject({ reason: "completed", steps: 2, totalTokens: 150 });
+  });
+
+  it("should record original outputs of transformed tool results before the stop event", () => {
+    const trace = new LoopTrace(limits);
+    trace.onStep(step(10, [["execute_tool", { tool_name: "search" }]]));
+    trace.onTransformedOutputs([{ toolId: "t-1", toolName: "search", original: { items: [1, 2, 3] } }]);
+    const events = trace.finish();
+
+    expect(events.map((e) => e.type)).toEqual(["step", "tool_output_transformed", "loop_stop"]);
+    expect(events[1]).toMatchObject({ toolId: "t-1", toolName: "search", original: { items: [1, 2, 3] } });
+  });
+});
@@ -0,0 +1,106 @@
+import { describe, it, expect, vi } from "vitest";
+import type { UIMessage } from "ai";
+import { estimateMessageTokens, manageContext, planContext, toTranscript } from "../src/context-manager.js";
+
+function message(i: number, text: string): UIMessage {
+  return {
+    id: `m${i}`,
+    role: i % 2 === 0 ? "user" : "assistant",
+    parts: [{ type: "text", text }],
+  };
+}
+
+/** Messages of roughly `tokens` tokens each */
+function history(count: number, tokens: number): UIMessage[] {
+  return Array.from({ length: count }, (_, i) => message(i, "x".repeat(tokens * 4)));
+}
+
+// @awa-test: PLAN-027-ContextManager
+describe("planContext", () => {
+  it("keeps everything when history fits the budget", () => {
+    const messages = history(6, 100);
+    const plan = planContext(messages, undefined, { targetTokens: 10_000, maxRecentMessages: 20 });
+    expect(plan.recentStart).toBe(0);
+    expect(plan.summarizedCount).toBe(0);
+    expect(plan.tokenCounts).toHaveLength(6);
+  });
+
+  it("folds older turns down to half the budget, starting at a user message", () => {
+    const messages = history(20, 100);
+    const perMessage = estimateMessageTokens(messages[0]);
+    const plan = planContext(messages, undefined, { targetTokens: perMessage * 10, maxRecentMessages: 20 });
+    // Five messages fit in half the budget; the window then skips the
+    // leading assistant message.
+    expect(plan.recentStart).toBe(16);
+    expect(messages[plan.recentStart].role).toBe("user");
+  });
+
+  it("caps the verbatim window at maxRecentMessages", () => {
+    const plan = planContext(history(30, 10), undefined, { targetTokens: 1_000_000, maxRecentMessages: 6 });
+    expect(30 - plan.recentStart).toBeLessThanOrEqual(6);
+  });
+
+  it("reuses a stored summary while later messages fit", () => {
+    const messages = history(12, 100);
+    const plan = planContext(messages, { text: "earlier", messageCount: 8 }, { targetTokens: 10_000, maxRecentMessages: 20 });
+    expect(plan.summarizedCount).toBe(8);
+    expect(plan.recentStart).toBe(8);
+  });
+
+  it("ignores a summary covering the whole history", () => {
+    const plan = planContext(history(4, 10), { text: "stale", messageCount: 4 }, { targetTokens: 10_000, maxRecentMessages: 20 });
+    expect(plan.summarizedCount).toBe(0);
+  });
+});
+
+describe("manageContext", () => {
+  const budget = { targetTokens: 1_000, maxRecentMessages: 20 };
+
+  it("summarizes only turns not yet covered by the stored summary", async () => {
+    const messages = history(40, 100);
+    const summarize = vi.fn(async (previous: string | undefined, turns: UIMessage[]) => `${previous} + ${turns.length}`);
+
+    const result = await manageContext(messages, { text: "first 10", messageCount: 10 }, budget, summarize);
+
+    expect(summarize).toHaveBeenCalledOnce();
+    expect(summarize.mock.calls[0][1][0].id).toBe("m10");
+    expect(result.summaryUpdated).toBe(true);
+    expect(result.summary!.messageCount).toBe(40 - result.recentMessages.length);
+    expect(result.summary!.text).toBe(`first 10 + ${result.summary!.messageCount - 10}`);
+  });
+
+  it("does not call the summarizer when history fits", async () => {
+    const summarize = vi.fn();
+    const result = await manageContext(history(4, 10), undefined, budget, summarize);
+    expect(summarize).not.toHaveBeenCalled();
+    expect(result.summaryUpdated).toBe(false);
+    expect(result.recentMessages).toHaveLength(4);
+  });
+
+  it("keeps the stored summary when summarization fails", async () => {
+    const stored = { text: "first 2", messageCount: 2 };
+    const result = await manageContext(history(40, 100), stored, budget, async () => {
+      throw new Error("provider down");
+    });
+    expect(result.summaryUpdated).toBe(false);
+    expect(result.summary).toEqual(stored);
+    expect(result.recentMessages.length).toBeLessThan(38);
+  });
+});
+
+describe("toTranscript", () => {
+  it("renders roles, text and tool use", () => {
+    const messages: UIMessage[] = [
+      message(0, "What's the weather?"),
+      {
+        id: "m1",
+        role: "assistant",
+        parts: [
+          { type: "tool-weather", toolCallId: "c1", state: "output-available", input: {}, output: { temp: 20 } },
+          { type: "text", text: "It is 20 degrees." },
+        ],
+      },
+    ];
+    expect(toTranscript(messages)).toBe("user: What's the weather?\n\nassistant: [used tool weather]\nIt is 20 degrees.");
+  });
+});
@@ -0,0 +1,64 @@
+import { describe, it, expect } from "vitest";
+import { parseStructured, responseFormatError, retryMessage, validateJson, type JsonSchema } from "../src/structured-output.js";
+
+// @awa-test: PLAN-027-StructuredOutput
+
+const schema: JsonSchema = {
+  type: "object",
+  properties: {
+    name: { type: "string", minLength: 1 },
+    priority: { enum: ["low", "high"] },
+    tags: { type: "array", items: { type: "string" }, maxItems: 2 },
+    due: { type: ["string", "null"] },
+  },
+  required: ["name", "priority"],
+  additionalProperties: false,
+};
+
+describe("validateJson", () => {
+  it("should accept a matching value", () => {
+    expect(validateJson({ name: "Ship", priority: "high", tags: ["a"], due: null }, schema)).toEqual([]);
+  });
+
+  it("should report each violation with its path", () => {
+    const errors = validateJson({ name: "", tags: ["a", 2, "c"], extra: true }, schema);
+    expect(errors).toEqual([
+      '$: missing required property "priority"',
+      "$.name: must be at least 1 characters",
+      "$.tags: must have at most 2 items",
+      "$.tags[1]: expected string, got number",
+      '$: unexpected property "extra"',
+    ]);
+  });
+
+  it("should check types, integers and alternatives", () => {
+    expect(validateJson([], { type: "object" })).toEqual(["$: expected object, got array"]);
+    expect(validateJson(1.5, { type: "integer" })).toEqual(["$: expected integer, got number"]);
+    expect(validateJson(3, { anyOf: [{ type: "string" }, { type: "number", maximum: 5 }] })).toEqual([]);
+    expect(validateJson(3, { oneOf: [{ type: "number" }, { type: "integer" }] })).toEqual(["$: must match exactly one of the oneOf schemas"]);
+  });
+});
+
+describe("parseStructured", () => {
+  it("should parse JSON, also inside a code fence", () => {
+    const output = parseStructured('```json\n{"name": "Ship", "priority": "low"}\n```', schema, 1);
+    expect(output).toEqual({ status: "valid", data: { name: "Ship", priority: "low" }, errors: [], attempts: 1 });
+  });
+
+  it("should report replies that are not JSON or do not match", () => {
+    expect(parseStructured("Sure! Here it is.", schema, 2).status).toBe("unparsed");
+    const invalid = parseStructured('{"name": "Ship", "priority": "urgent"}', schema, 3);
+    expect(invalid.status).toBe("invalid");
+    expect(invalid.data).toEqual({ name: "Ship", priority: "urgent" });
+    expect(retryMessage(invalid).content).toContain('$.priority: must be one of ["low","high"]');
+  });
+});
+
+describe("responseFormatError", () => {
+  it("should require a json_schema type and a schema object", () => {
+    expect(responseFormatError({ type: "json_schema", schema })).toBeNull();
+    expect(responseFormatError({ type: "json_object", schema })).toMatch(/type/);
+    expect(responseFormatError({ type: "json_schema", schema: [] })).toMatch(/schema/);
+    expect(responseFormatError("json")).toMatch(/object/);
+  });
+});
@@ -7,9 +7,19 @@
 //
 // Usage:
 //   node pglite-server.mjs --db=<path> --port=<N> --database=<name>
+//
+// Encryption at rest (the hex AES-256 key is read from the first line of
+// stdin, never from argv):
+//   --sealed=<file>            run from an encrypted snapshot instead of --db;
+//                              the database lives in memory and is re-sealed
+//                              every --checkpoint seconds and on shutdown
+//   --sealed=<file> --seal     encrypt the plaintext --db directory and exit
+//   --sealed=<file> --unseal   decrypt the snapshot into --db and exit
 
 import { PGlite } from "@electric-sql/pglite";
 import { PGLiteSocketServer } from "@electric-sql/pglite-socket";
+import { readFileSync, writeFileSync, renameSync, existsSync } from "node:fs";
+import { createInterface } from "node:readline";
 
 // Define the vector extension inline so that the bundle path resolves
 // relative to this file (./vector.tar.gz) rather than the library's
@@ -28,21 +38,97 @@ const { values: args } = parseArgs({
     db: { type: "string", default: "./pgdata" },
     port: { type: "string", default: "0" },
     database: { type: "string", default: "nize" },
+    sealed: { type: "string" },
+    seal: { type: "boolean", default: false },
+    unseal: { type: "boolean", default: false },
+    checkpoint: { type: "string", default: "60" },
   },
 });
 
 const dataDir = args.db;
 const requestedPort = parseInt(args.port, 10);
 const databaseName = args.database;
+const sealedFile = args.sealed;
+
+// ---------------------------------------------------------------------------
+// Sealed snapshots: "NIZESEAL" || 12-byte IV || AES-256-GCM(tar.gz dump)
+// ---------------------------------------------------------------------------
+
+const MAGIC = new TextEncoder().encode("NIZESEAL");
+const IV_SIZE = 12;
+
+// In encrypted mode stdin carries the key, then control lines.
+const stdinLines = sealedFile
+  ? createInterface({ input: process.stdin })[Symbol.asyncIterator]()
+  : null;
+
+async function readKeyFromStdin() {
+  const { value, done } = await stdinLines.next();
+  const hex = done ? "" : value.trim();
+  if (!/^[0-9a-f]{64}$/i.test(hex)) {
+    throw new Error("expected a 64-character hex encryption key on stdin");
+  }
+  const raw = Uint8Array.from(hex.match(/../g), (b) => parseInt(b, 16));
+  return crypto.subtle.importKey("raw", raw, "AES-GCM", false, ["encrypt", "decrypt"]);
+}
+
+async function unsealSnapshot(key) {
+  const bytes = new Uint8Array(readFileSync(sealedFile));
+  const magic = bytes.subarray(0, MAGIC.length);
+  if (!magic.every((b, i) => b === MAGIC[i])) {
+    throw new Error(`${sealedFile} is not a sealed PGlite snapshot`);
+  }
+  const iv = bytes.subarray(MAGIC.length, MAGIC.length + IV_SIZE);
+  const ciphertext = bytes.subarray(MAGIC.length + IV_SIZE);
+  const plain = await crypto.subtle.decrypt({ name: "AES-GCM", iv }, key, ciphertext);
+  return new Blob([plain]);
+}
+
+async function sealSnapshot(db, key) {
+  const dump = await db.dumpDataDir("gzip");
+  const iv = crypto.getRandomValues(new Uint8Array(IV_SIZE));
+  const ciphertext = new Uint8Array(
+    await crypto.subtle.encrypt({ name: "AES-GCM", iv }, key, await dump.arrayBuffer()),
+  );
+  // Write then rename so a crash mid-write never corrupts the snapshot.
+  const tmp = `${sealedFile}.tmp`;
+  writeFileSync(tmp, Buffer.concat([MAGIC, iv, ciphertext]));
+  renameSync(tmp, sealedFile);
+}
+
+const key = sealedFile ? await readKeyFromStdin() : null;
 
 // @awa-impl: PLAN-007-1.2 — create PGlite instance with vector extension
-const db = new PGlite({
-  dataDir: `file://${dataDir}`,
-  extensions: { vector },
-});
+let db;
+if (!sealedFile || args.seal) {
+  db = new PGlite({ dataDir: `file://${dataDir}`, extensions: { vector } });
+} else if (args.unseal) {
+  db = new PGlite({
+    dataDir: `file://${dataDir}`,
+    loadDataDir: await unsealSnapshot(key),
+    extensions: { vector },
+  });
+} else {
+  db = new PGlite({
+    loadDataDir: existsSync(sealedFile) ? await unsealSnapshot(key) : undefined,
+    extensions: { vector },
+  });
+}
 
 await db.waitReady;
 
+// One-shot migration modes: convert between the plaintext directory and a
+// sealed snapshot, then exit.
+if (args.seal) {
+  await sealSnapshot(db, key);
+  await db.close();
+  process.exit(0);
+}
+if (args.unseal) {
+  await db.close();
+  process.exit(0);
+}
+
 // @awa-impl: PLAN-007-1.2 — enable pgvector extension
 await db.exec("CREATE EXTENSION IF NOT EXISTS vector");
 
@@ -66,10 +152,20 @@ server.addEventListener("listening", (event) => {
 
 await server.start();
 
+// Encrypted mode keeps the database in memory; checkpoint it periodically
+// so a crash loses at most one interval of writes.
+if (sealedFile) {
+  const interval = Math.max(parseInt(args.checkpoint, 10) || 60, 5) * 1000;
+  setInterval(() => {
+    sealSnapshot(db, key).catch((e) => console.error(`pglite-server: checkpoint failed: ${e}`));
+  }, interval);
+}
+
 // @awa-impl: PLAN-007-1.2 — graceful shutdown
 async function shutdown() {
   try {
     await server.stop();
+    if (sealedFile) await sealSnapshot(db, key);
     await db.close();
   } catch {
     // Ignore errors during shutdown.
@@ -79,3 +175,17 @@ async function shutdown() {
 
 process.on("SIGTERM", shutdown);
 process.on("SIGINT", shutdown);
+
+// Encrypted mode: a "shutdown" line, or stdin closing because the parent
+// died, seals and exits. This is the graceful path on Windows, where the
+// process cannot be signalled.
+if (stdinLines) {
+  (async () => {
+    for (;;) {
+      const { value, done } = await stdinLines.next();
+      if (done || value.trim() === "shutdown") {
+        await shutdown();
+      }
+    }
+  })();
+}
@@ -6,6 +6,7 @@ import { useState } from "react";
 import Link from "next/link";
 import { useRouter } from "next/navigation";
 import { useAuth } from "@/lib/auth-context";
+import { apiUrl } from "@/lib/api";
 
 export default function RegisterPage() {
   const router = useRouter();
@@ -16,6 +17,8 @@ export default function RegisterPage() {
   const [confirmPassword, setConfirmPassword] = useState("");
   const [error, setError] = useState("");
   const [loading, setLoading] = useState(false);
+  const [pendingEmail, setPendingEmail] = useState<string | null>(null);
+  const [resent, setResent] = useState(false);
 
   const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
@@ -41,6 +44,11 @@ export default function RegisterPage() {
         return;
       }
 
+      if (result.verificationRequired) {
+        setPendingEmail(email);
+        return;
+      }
+
       // Registration successful, user is logged in
       router.push("/");
     } catch {
@@ -50,6 +58,37 @@ export default function RegisterPage() {
     }
   };
 
+  const handleResend = async () => {
+    if (!pendingEmail) return;
+    await fetch(apiUrl("/auth/verify/resend"), {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ email: pendingEmail }),
+    }).catch(() => {});
+    setResent(true);
+  };
+
+  if (pendingEmail) {
+    return (
+      <div style={styles.container}>
+        <div style={styles.card}>
+          <div style={styles.header}>
+            <h1 style={styles.title}>Check your email</h1>
+            <p style={styles.subtitle}>We sent a verification link to {pendingEmail}. Open it to activate your account.</p>
+          </div>
+          <button type="button" onClick={handleResend} disabled={resent} style={{ ...styles.button, ...(resent ? styles.buttonDisabled : {}) }}>
+            {resent ? "Link sent again" : "Resend link"}
+          </button>
+          <div style={styles.footer}>
+            <Link href="/login" style={styles.link}>
+              Back to sign in
+            </Link>
+          </div>
+        </div>
+      </div>
+    );
+  }
+
   return (
     <div style={styles.container}>
       <div style={styles.card}>
@@ -0,0 +1,109 @@
+"use client";
+
+// @awa-component: CFG-VerifyEmailPage
+
+import { useEffect, useRef, useState, Suspense } from "react";
+import Link from "next/link";
+import { useSearchParams } from "next/navigation";
+import { apiUrl } from "@/lib/api";
+
+type Status = { state: "verifying" } | { state: "verified" } | { state: "failed"; error: string };
+
+function VerifyEmail() {
+  const searchParams = useSearchParams();
+  const token = searchParams.get("token");
+  const [status, setStatus] = useState<Status>({ state: "verifying" });
+  // Tokens are single-use: verify once even if the effect runs twice.
+  const started = useRef(false);
+
+  useEffect(() => {
+    if (started.current) return;
+    started.current = true;
+    if (!token) {
+      setStatus({ state: "failed", error: "This link is missing its verification token." });
+      return;
+    }
+    fetch(apiUrl(`/auth/verify?token=${encodeURIComponent(token)}`))
+      .then(async (res) => {
+        if (res.ok) {
+          setStatus({ state: "verified" });
+        } else {
+          const data = await res.json().catch(() => ({}));
+          setStatus({ state: "failed", error: data.message || data.error || "Verification failed" });
+        }
+      })
+      .catch(() => setStatus({ state: "failed", error: "Network error" }));
+  }, [token]);
+
+  return (
+    <div style={styles.container}>
+      <div style={styles.card}>
+        <h1 style={styles.title}>Email verification</h1>
+        {status.state === "verifying" && <p style={styles.text}>Verifying your email address…</p>}
+        {status.state === "verified" && <p style={styles.text}>Your email address is verified. You can now sign in.</p>}
+        {status.state === "failed" && <div style={styles.error}>{status.error}</div>}
+        <div style={styles.footer}>
+          <Link href="/login" style={styles.link}>
+            Go to sign in
+          </Link>
+        </div>
+      </div>
+    </div>
+  );
+}
+
+export default function VerifyEmailPage() {
+  return (
+    <Suspense fallback={null}>
+      <VerifyEmail />
+    </Suspense>
+  );
+}
+
+const styles: Record<string, React.CSSProperties> = {
+  container: {
+    display: "flex",
+    minHeight: "100vh",
+    alignItems: "center",
+    justifyContent: "center",
+    backgroundColor: "#f5f5f5",
+    padding: "1rem",
+    fontFamily: "system-ui, sans-serif",
+  },
+  card: {
+    width: "100%",
+    maxWidth: "400px",
+    backgroundColor: "#fff",
+    borderRadius: "8px",
+    boxShadow: "0 2px 8px rgba(0,0,0,0.1)",
+    padding: "2rem",
+    textAlign: "center" as const,
+  },
+  title: {
+    fontSize: "1.5rem",
+    fontWeight: 700,
+    color: "#111",
+    margin: 0,
+    marginBottom: "1rem",
+  },
+  text: {
+    fontSize: "0.875rem",
+    color: "#333",
+  },
+  error: {
+    backgroundColor: "#fef2f2",
+    color: "#991b1b",
+    padding: "0.75rem",
+    borderRadius: "6px",
+    fontSize: "0.875rem",
+  },
+  footer: {
+    marginTop: "1.5rem",
+    fontSize: "0.875rem",
+  },
+  link: {
+    color: "#2563eb",
+    textDecoration: "none",
+    fontWeight: 500,
+  },
+};
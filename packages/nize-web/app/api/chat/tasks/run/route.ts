@@ -0,0 +1,7 @@
+// @awa-component: PLAN-027-NextAdapter
+
+import { chatApp } from "@six5536/nize-chat";
+
+// Scheduled task runs, called by the Rust task scheduler. Like /api/chat this
+// is handled by the chat app rather than proxied to the Rust API.
+export const POST = (request: Request) => chatApp.fetch(request);
@@ -10,7 +10,7 @@ import { useParams, useRouter } from "next/navigation";
 import { useEffect, useState, useMemo, useCallback, useRef } from "react";
 import { ChatLayout, ChatHeader, ChatInput, ChatUpload, MessageBubble, ThinkingBubble, EmptyState, useChatScroll, useFileUpload, useChatSubmit } from "@/components/chat";
 import { useDevPanel } from "@/lib/dev-panel-context";
-import { useAuth, useAuthFetch } from "@/lib/auth-context";
+import { csrfHeaders, useAuth, useAuthFetch } from "@/lib/auth-context";
 import { apiUrl } from "@/lib/api";
 import { extractTextContent } from "@/lib/message-parts";
 
@@ -33,6 +33,7 @@ export default function ConversationChatPage() {
       new DefaultChatTransport({
         api: apiUrl("/chat"),
         body: () => ({ conversationId }),
+        headers: () => csrfHeaders(),
         credentials: "include", // Send httpOnly cookies
       }),
     [conversationId],
@@ -55,7 +56,7 @@ export default function ConversationChatPage() {
   });
 
   const { uploading, uploadMessage, handleUpload } = useFileUpload();
-  const { handleChatSubmit } = useChatSubmit({ input, setInput, sendMessage, setMessages });
+  const { handleChatSubmit } = useChatSubmit({ input, conversationId, setInput, sendMessage, setMessages });
 
   // Reset messages when conversation changes
   useEffect(() => {
@@ -6,6 +6,7 @@ import type { Metadata } from "next";
 import { Toaster } from "sonner";
 import { AuthProvider } from "@/lib/auth-context";
 import { WebviewBridgeLoader } from "@/components/WebviewBridgeLoader";
+import { StartupErrorScreen } from "@/components/desktop/StartupErrorScreen";
 import { DevPanelProvider } from "@/components/dev-panel/dev-panel-provider";
 import { DevPanel } from "@/components/dev-panel/dev-panel";
 import "./globals.css";
@@ -35,6 +36,7 @@ export default function RootLayout({
       <body className="h-full">
         <AuthProvider>
           <WebviewBridgeLoader />
+          <StartupErrorScreen />
           <DevPanelProvider>
             <div className="flex flex-col lg:flex-row h-full">
               <div className="flex-1 min-w-0 overflow-auto">{children}</div>
@@ -0,0 +1,75 @@
+// @awa-impl: DESKTOP-QuickCapture — capture window opened by the global shortcut (Tauri-only)
+
+"use client";
+
+import { useEffect, useRef, useState } from "react";
+import { isTauri } from "@/lib/tauri";
+
+type CaptureTarget = "message" | "note";
+
+/**
+ * Minimal capture form. Enter sends the text as a new chat message,
+ * Cmd/Ctrl+Enter saves it as a note, Escape hides the window.
+ */
+export default function QuickCapturePage() {
+  const [text, setText] = useState("");
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const inputRef = useRef<HTMLTextAreaElement>(null);
+
+  useEffect(() => {
+    inputRef.current?.focus();
+  }, []);
+
+  async function hide() {
+    if (!isTauri()) return;
+    const { invoke } = await import("@tauri-apps/api/core");
+    await invoke("hide_quick_capture");
+  }
+
+  async function submit(target: CaptureTarget) {
+    if (!text.trim() || busy) return;
+    setBusy(true);
+    setError(null);
+    try {
+      const { invoke } = await import("@tauri-apps/api/core");
+      await invoke("submit_quick_capture", { text, target });
+      setText("");
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setBusy(false);
+    }
+  }
+
+  function handleKeyDown(e: React.KeyboardEvent<HTMLTextAreaElement>) {
+    if (e.key === "Escape") {
+      e.preventDefault();
+      void hide();
+    } else if (e.key === "Enter" && !e.shiftKey) {
+      e.preventDefault();
+      void submit(e.metaKey || e.ctrlKey ? "note" : "message");
+    }
+  }
+
+  if (!isTauri()) {
+    return <p className="p-4 text-sm text-gray-500">Quick capture is only available in the Nize desktop app.</p>;
+  }
+
+  return (
+    <div className="flex h-full flex-col gap-2 p-3">
+      <textarea ref={inputRef} value={text} onChange={(e) => setText(e.target.value)} onKeyDown={handleKeyDown} disabled={busy} placeholder="Capture a thought… (Enter: chat, ⌘/Ctrl+Enter: note, Esc: close)" className="flex-1 resize-none rounded-md border border-gray-300 p-2 text-sm focus:outline-none focus:ring-2 focus:ring-blue-500" />
+      <div className="flex items-center justify-between">
+        <span className="truncate text-xs text-red-600">{error}</span>
+        <div className="flex gap-2">
+          <button onClick={() => submit("note")} disabled={busy || !text.trim()} className="rounded-md border border-gray-300 px-3 py-1 text-sm disabled:opacity-50">
+            Save note
+          </button>
+          <button onClick={() => submit("message")} disabled={busy || !text.trim()} className="rounded-md bg-blue-600 px-3 py-1 text-sm text-white disabled:opacity-50">
+            Send to chat
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
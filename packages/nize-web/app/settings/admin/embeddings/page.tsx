@@ -40,8 +40,13 @@ interface EmbeddingModel {
   name: string;
   dimensions: number;
   tableName: string;
-  toolTableName: string;
   isActive: boolean;
+  coverage: {
+    toolEmbeddings: number;
+    tools: number;
+    chunkEmbeddings: number;
+    chunks: number;
+  };
 }
 
 interface ReindexResult {
@@ -302,7 +307,8 @@ export default function EmbeddingsConfigPage() {
                 <th style={s.th}>Provider</th>
                 <th style={s.th}>Model</th>
                 <th style={s.th}>Dimensions</th>
-                <th style={s.th}>Tool Table</th>
+                <th style={s.th}>Tools</th>
+                <th style={s.th}>Chunks</th>
                 <th style={s.th}>Status</th>
               </tr>
             </thead>
@@ -315,7 +321,10 @@ export default function EmbeddingsConfigPage() {
                   </td>
                   <td style={s.td}>{model.dimensions}</td>
                   <td style={s.td}>
-                    <span style={{ fontFamily: "monospace", fontSize: "0.75rem" }}>{model.toolTableName}</span>
+                    {model.coverage.toolEmbeddings} / {model.coverage.tools}
+                  </td>
+                  <td style={s.td}>
+                    {model.coverage.chunkEmbeddings} / {model.coverage.chunks}
                   </td>
                   <td style={s.td}>{model.isActive ? <span style={s.activeBadge}>Active</span> : <span style={s.inactiveBadge}>Inactive</span>}</td>
                 </tr>
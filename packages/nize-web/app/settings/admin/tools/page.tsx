@@ -4,14 +4,15 @@
  * Admin MCP server management page at /settings/admin/tools
  *
  * Allows admins to view, create, edit, toggle, and delete
- * built-in MCP server configurations system-wide.
+ * built-in MCP server configurations system-wide, and shows the
+ * resource usage of running managed server processes.
  */
 
 "use client";
 
 import { useEffect, useState, useCallback } from "react";
 import { useAuth, useAuthFetch } from "@/lib/auth-context";
-import { ServerForm, type ServerConfig, type TestConnectionResult } from "@/components/mcp-server";
+import { CatalogPicker, ServerForm, type ServerConfig, type TestConnectionResult } from "@/components/mcp-server";
 
 // =============================================================================
 // Types
@@ -37,15 +38,88 @@ interface AdminServerView {
   userPreferenceCount: number;
   enabled: boolean;
   available: boolean;
+  keepWarm: boolean;
+  forwardedContext: ContextKey[];
   config?: Record<string, unknown>;
   oauthConfig?: { clientId: string; authorizationUrl: string; tokenUrl: string; scopes: string[] };
 }
 
+/** Caller context a server can receive in its tool calls' `_meta` */
+type ContextKey = "conversationId" | "workspaceId" | "locale";
+
+const CONTEXT_KEYS: { key: ContextKey; label: string }[] = [
+  { key: "conversationId", label: "Conversation" },
+  { key: "workspaceId", label: "Workspace" },
+  { key: "locale", label: "Locale" },
+];
+
+interface ChildProcessStats {
+  serverId: string;
+  serverName: string;
+  transport: TransportType;
+  pid: number;
+  uptimeSecs: number;
+  restarts: number;
+  cpuPercent: number;
+  memoryBytes: number;
+}
+
 // =============================================================================
 // Components
 // =============================================================================
 
-function AdminServerList({ servers, groupBy, onEdit, onDelete, onToggleEnabled }: { servers: AdminServerView[]; groupBy: "visibility" | "transport"; onEdit: (serverId: string) => void; onDelete: (serverId: string) => void; onToggleEnabled: (serverId: string, enabled: boolean) => void }) {
+function formatUptime(secs: number): string {
+  if (secs < 60) return `${secs}s`;
+  if (secs < 3600) return `${Math.floor(secs / 60)}m`;
+  return `${Math.floor(secs / 3600)}h ${Math.floor((secs % 3600) / 60)}m`;
+}
+
+function ManagedProcessList({ processes, onRefresh }: { processes: ChildProcessStats[]; onRefresh: () => void }) {
+  return (
+    <div className="mb-8">
+      <div className="flex items-center justify-between mb-4">
+        <h3 className="text-lg font-medium text-gray-900">Running Processes</h3>
+        <button onClick={onRefresh} className="px-3 py-1 text-sm text-gray-700 bg-white border rounded-md hover:bg-gray-50">
+          Refresh
+        </button>
+      </div>
+      {processes.length === 0 ? (
+        <p className="text-sm text-gray-500">No managed MCP server processes are running.</p>
+      ) : (
+        <div className="bg-white shadow overflow-hidden rounded-md">
+          <table className="min-w-full divide-y divide-gray-200 text-sm">
+            <thead className="bg-gray-50 text-left text-gray-500">
+              <tr>
+                <th className="px-6 py-2 font-medium">Server</th>
+                <th className="px-6 py-2 font-medium">PID</th>
+                <th className="px-6 py-2 font-medium">CPU</th>
+                <th className="px-6 py-2 font-medium">Memory</th>
+                <th className="px-6 py-2 font-medium">Uptime</th>
+                <th className="px-6 py-2 font-medium">Restarts</th>
+              </tr>
+            </thead>
+            <tbody className="divide-y divide-gray-200">
+              {processes.map((p) => (
+                <tr key={p.serverId}>
+                  <td className="px-6 py-2 text-gray-900">
+                    {p.serverName} <span className="text-gray-500">({p.transport})</span>
+                  </td>
+                  <td className="px-6 py-2 text-gray-500">{p.pid}</td>
+                  <td className="px-6 py-2 text-gray-500">{p.cpuPercent.toFixed(1)}%</td>
+                  <td className="px-6 py-2 text-gray-500">{(p.memoryBytes / (1024 * 1024)).toFixed(1)} MiB</td>
+                  <td className="px-6 py-2 text-gray-500">{formatUptime(p.uptimeSecs)}</td>
+                  <td className={`px-6 py-2 ${p.restarts > 0 ? "text-orange-600" : "text-gray-500"}`}>{p.restarts}</td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+      )}
+    </div>
+  );
+}
+
+function AdminServerList({ servers, groupBy, onEdit, onDelete, onToggleEnabled, onToggleKeepWarm, onChangeForwardedContext }: { servers: AdminServerView[]; groupBy: "visibility" | "transport"; onEdit: (serverId: string) => void; onDelete: (serverId: string) => void; onToggleEnabled: (serverId: string, enabled: boolean) => void; onToggleKeepWarm: (serverId: string, keepWarm: boolean) => void; onChangeForwardedContext: (serverId: string, keys: ContextKey[]) => void }) {
   const grouped = servers.reduce(
     (acc, server) => {
       const key = groupBy === "visibility" ? server.visibility : server.transport;
@@ -99,11 +173,29 @@ function AdminServerList({ servers, groupBy, onEdit, onDelete, onToggleEnabled }
                           <span className="text-sm text-gray-500">{server.toolCount} tools</span>
                           {server.visibility !== "user" && server.userPreferenceCount > 0 && <span className="text-sm text-orange-600">{server.userPreferenceCount} users enabled</span>}
                         </div>
+                        {server.visibility !== "user" && (
+                          <div className="flex items-center gap-3 mt-1 text-xs text-gray-600" title="Caller context attached to this server's tool calls">
+                            <span>Forward context:</span>
+                            {CONTEXT_KEYS.map(({ key, label }) => (
+                              <label key={key} className="inline-flex items-center gap-1 cursor-pointer">
+                                <input type="checkbox" checked={server.forwardedContext.includes(key)} onChange={(e) => onChangeForwardedContext(server.id, e.target.checked ? [...server.forwardedContext, key] : server.forwardedContext.filter((k) => k !== key))} />
+                                {label}
+                              </label>
+                            ))}
+                          </div>
+                        )}
                       </div>
 
                       <div className="flex items-center gap-3">
                         <span className={`px-2 py-1 text-xs font-medium rounded-full ${server.status === "enabled" ? "bg-green-100 text-green-800" : server.status === "disabled" ? "bg-gray-100 text-gray-800" : "bg-red-100 text-red-800"}`}>{server.status}</span>
 
+                        {server.visibility !== "user" && (
+                          <label className="inline-flex items-center gap-1 text-xs text-gray-600 cursor-pointer" title="Pre-connect at startup and never evict for idleness">
+                            <input type="checkbox" checked={server.keepWarm} onChange={(e) => onToggleKeepWarm(server.id, e.target.checked)} />
+                            Keep warm
+                          </label>
+                        )}
+
                         {server.visibility !== "user" && (
                           <label className="relative inline-flex items-center cursor-pointer">
                             <input type="checkbox" className="sr-only peer" checked={server.status === "enabled"} onChange={(e) => onToggleEnabled(server.id, e.target.checked)} />
@@ -154,9 +246,11 @@ export default function AdminToolsPage() {
   const authFetch = useAuthFetch();
 
   const [servers, setServers] = useState<AdminServerView[]>([]);
+  const [processes, setProcesses] = useState<ChildProcessStats[]>([]);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
   const [showCreateForm, setShowCreateForm] = useState(false);
+  const [showCatalog, setShowCatalog] = useState(false);
   const [editingServerId, setEditingServerId] = useState<string | null>(null);
   const [groupBy, setGroupBy] = useState<"visibility" | "transport">("visibility");
 
@@ -179,11 +273,24 @@ export default function AdminToolsPage() {
     }
   }, [authFetch]);
 
+  const loadProcesses = useCallback(async () => {
+    try {
+      const res = await authFetch("/mcp/admin/processes");
+      if (res.ok) {
+        const data = await res.json();
+        setProcesses(data.processes || []);
+      }
+    } catch (err) {
+      console.error("Failed to load processes", err);
+    }
+  }, [authFetch]);
+
   useEffect(() => {
     if (authLoading) return;
     if (!isAuthenticated) return;
     loadServers();
-  }, [authLoading, isAuthenticated, loadServers]);
+    loadProcesses();
+  }, [authLoading, isAuthenticated, loadServers, loadProcesses]);
 
   const handleToggleEnabled = async (serverId: string, enabled: boolean) => {
     try {
@@ -200,6 +307,36 @@ export default function AdminToolsPage() {
     }
   };
 
+  const handleToggleKeepWarm = async (serverId: string, keepWarm: boolean) => {
+    try {
+      const res = await authFetch(`/mcp/admin/servers/${serverId}`, {
+        method: "PATCH",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ keepWarm }),
+      });
+      if (res.ok) {
+        setServers((prev) => prev.map((s) => (s.id === serverId ? { ...s, keepWarm } : s)));
+      }
+    } catch (err) {
+      console.error("Failed to toggle keep warm", err);
+    }
+  };
+
+  const handleChangeForwardedContext = async (serverId: string, forwardedContext: ContextKey[]) => {
+    try {
+      const res = await authFetch(`/mcp/admin/servers/${serverId}`, {
+        method: "PATCH",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ forwardedContext }),
+      });
+      if (res.ok) {
+        setServers((prev) => prev.map((s) => (s.id === serverId ? { ...s, forwardedContext } : s)));
+      }
+    } catch (err) {
+      console.error("Failed to update forwarded context", err);
+    }
+  };
+
   const handleDelete = async (serverId: string) => {
     const server = servers.find((s) => s.id === serverId);
     if (!server) return;
@@ -301,17 +438,44 @@ export default function AdminToolsPage() {
             <option value="transport">Transport</option>
           </select>
         </div>
-        <button
-          onClick={() => {
-            setShowCreateForm(!showCreateForm);
-            setEditingServerId(null);
-          }}
-          className="px-4 py-2 text-sm font-medium text-white bg-blue-600 rounded-md hover:bg-blue-700"
-        >
-          {showCreateForm ? "Cancel" : "Create Server"}
-        </button>
+        <div className="flex items-center gap-2">
+          <button
+            onClick={() => {
+              setShowCatalog(!showCatalog);
+              setShowCreateForm(false);
+              setEditingServerId(null);
+            }}
+            className="px-4 py-2 text-sm font-medium text-gray-700 bg-white border rounded-md hover:bg-gray-50"
+          >
+            {showCatalog ? "Close Catalog" : "Install from Catalog"}
+          </button>
+          <button
+            onClick={() => {
+              setShowCreateForm(!showCreateForm);
+              setShowCatalog(false);
+              setEditingServerId(null);
+            }}
+            className="px-4 py-2 text-sm font-medium text-white bg-blue-600 rounded-md hover:bg-blue-700"
+          >
+            {showCreateForm ? "Cancel" : "Create Server"}
+          </button>
+        </div>
       </div>
 
+      {showCatalog && (
+        <div className="mb-6">
+          <CatalogPicker
+            authFetch={authFetch}
+            installPath={(slug) => `/mcp/admin/catalog/${slug}/install`}
+            onCancel={() => setShowCatalog(false)}
+            onInstalled={() => {
+              setShowCatalog(false);
+              loadServers();
+            }}
+          />
+        </div>
+      )}
+
       {showCreateForm && (
         <div className="mb-6">
           <ServerForm
@@ -365,13 +529,15 @@ export default function AdminToolsPage() {
           );
         })()}
 
+      <ManagedProcessList processes={processes} onRefresh={loadProcesses} />
+
       {servers.length === 0 ? (
         <div className="text-center py-12 bg-white rounded-lg border">
           <p className="text-gray-500">No MCP servers configured.</p>
           <p className="text-sm text-gray-400 mt-1">Create a server to get started.</p>
         </div>
       ) : (
-        <AdminServerList servers={servers} groupBy={groupBy} onEdit={handleEdit} onDelete={handleDelete} onToggleEnabled={handleToggleEnabled} />
+        <AdminServerList servers={servers} groupBy={groupBy} onEdit={handleEdit} onDelete={handleDelete} onToggleEnabled={handleToggleEnabled} onToggleKeepWarm={handleToggleKeepWarm} onChangeForwardedContext={handleChangeForwardedContext} />
       )}
     </div>
   );
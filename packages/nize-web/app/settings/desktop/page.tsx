@@ -30,16 +30,32 @@ export default function DesktopSettingsPage() {
  * Separate component so Tauri imports only load when needed.
  */
 function DesktopSettingsContent() {
+  const [AppSettings, setAppSettings] = useState<React.ComponentType | null>(null);
   const [McpClientSettings, setMcpClientSettings] = useState<React.ComponentType | null>(null);
   const [UpdateChecker, setUpdateChecker] = useState<React.ComponentType | null>(null);
-  const [HelloResponse, setHelloResponse] = useState<{ greeting: string; dbConnected: boolean; bunAvailable: boolean; bunVersion: string | null } | null>(null);
+  const [QuickCaptureSettings, setQuickCaptureSettings] = useState<React.ComponentType | null>(null);
+  const [DbEncryptionSettings, setDbEncryptionSettings] = useState<React.ComponentType | null>(null);
+  const [ExternalDatabaseSettings, setExternalDatabaseSettings] = useState<React.ComponentType | null>(null);
+  const [LocalModeSettings, setLocalModeSettings] = useState<React.ComponentType | null>(null);
+  const [StorageSettings, setStorageSettings] = useState<React.ComponentType | null>(null);
+  const [OfflineQueueSettings, setOfflineQueueSettings] = useState<React.ComponentType | null>(null);
+  const [HardwareSettings, setHardwareSettings] = useState<React.ComponentType | null>(null);
+  const [HelloResponse, setHelloResponse] = useState<{ greeting: string; dbConnected: boolean; bunAvailable: boolean; bunVersion: string | null; storageLevel: string | null } | null>(null);
   const [helloError, setHelloError] = useState<string | null>(null);
   const [helloLoading, setHelloLoading] = useState(false);
 
   useEffect(() => {
     // Dynamically import desktop-only components
+    import("@/components/desktop/AppSettings").then((mod) => setAppSettings(() => mod.AppSettings));
     import("@/components/desktop/McpClientSettings").then((mod) => setMcpClientSettings(() => mod.McpClientSettings));
     import("@/components/desktop/UpdateChecker").then((mod) => setUpdateChecker(() => mod.UpdateChecker));
+    import("@/components/desktop/QuickCaptureSettings").then((mod) => setQuickCaptureSettings(() => mod.QuickCaptureSettings));
+    import("@/components/desktop/DbEncryptionSettings").then((mod) => setDbEncryptionSettings(() => mod.DbEncryptionSettings));
+    import("@/components/desktop/ExternalDatabaseSettings").then((mod) => setExternalDatabaseSettings(() => mod.ExternalDatabaseSettings));
+    import("@/components/desktop/LocalModeSettings").then((mod) => setLocalModeSettings(() => mod.LocalModeSettings));
+    import("@/components/desktop/StorageSettings").then((mod) => setStorageSettings(() => mod.StorageSettings));
+    import("@/components/desktop/OfflineQueueSettings").then((mod) => setOfflineQueueSettings(() => mod.OfflineQueueSettings));
+    import("@/components/desktop/HardwareSettings").then((mod) => setHardwareSettings(() => mod.HardwareSettings));
   }, []);
 
   async function handleHelloClick() {
@@ -47,7 +63,7 @@ function DesktopSettingsContent() {
     setHelloError(null);
     try {
       const { invoke } = await import("@tauri-apps/api/core");
-      const result = await invoke<{ greeting: string; dbConnected: boolean; bunAvailable: boolean; bunVersion: string | null }>("hello_world");
+      const result = await invoke<{ greeting: string; dbConnected: boolean; bunAvailable: boolean; bunVersion: string | null; storageLevel: string | null }>("hello_world");
       setHelloResponse(result);
     } catch (e) {
       setHelloError(String(e));
@@ -79,10 +95,31 @@ function DesktopSettingsContent() {
             <p>
               <strong>Bun:</strong> <span style={{ color: HelloResponse.bunAvailable ? "green" : "red" }}>{HelloResponse.bunAvailable ? `✓ ${HelloResponse.bunVersion}` : "✗ Unavailable"}</span>
             </p>
+            {HelloResponse.storageLevel && (
+              <p>
+                <strong>Storage:</strong> <span style={{ color: HelloResponse.storageLevel === "ok" ? "green" : "red" }}>{HelloResponse.storageLevel}</span>
+              </p>
+            )}
           </div>
         )}
       </section>
 
+      <section style={{ marginTop: "2rem", borderTop: "1px solid #ddd", paddingTop: "1.5rem" }}>{AppSettings && <AppSettings />}</section>
+
+      <section style={{ marginTop: "2rem", borderTop: "1px solid #ddd", paddingTop: "1.5rem" }}>{QuickCaptureSettings && <QuickCaptureSettings />}</section>
+
+      <section style={{ marginTop: "2rem", borderTop: "1px solid #ddd", paddingTop: "1.5rem" }}>{DbEncryptionSettings && <DbEncryptionSettings />}</section>
+
+      <section style={{ marginTop: "2rem", borderTop: "1px solid #ddd", paddingTop: "1.5rem" }}>{ExternalDatabaseSettings && <ExternalDatabaseSettings />}</section>
+
+      <section style={{ marginTop: "2rem", borderTop: "1px solid #ddd", paddingTop: "1.5rem" }}>{LocalModeSettings && <LocalModeSettings />}</section>
+
+      <section style={{ marginTop: "2rem", borderTop: "1px solid #ddd", paddingTop: "1.5rem" }}>{StorageSettings && <StorageSettings />}</section>
+
+      <section style={{ marginTop: "2rem", borderTop: "1px solid #ddd", paddingTop: "1.5rem" }}>{HardwareSettings && <HardwareSettings />}</section>
+
+      <section style={{ marginTop: "2rem", borderTop: "1px solid #ddd", paddingTop: "1.5rem" }}>{OfflineQueueSettings && <OfflineQueueSettings />}</section>
+
       <section style={{ marginTop: "2rem", borderTop: "1px solid #ddd", paddingTop: "1.5rem" }}>{McpClientSettings && <McpClientSettings />}</section>
     </div>
   );
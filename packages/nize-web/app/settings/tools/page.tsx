@@ -3,14 +3,15 @@
 /**
  * User MCP server management page at /settings/tools
  *
- * Allows users to view, add, toggle, and delete their MCP server connections.
+ * Allows users to view, add (by hand or from the catalog), toggle, and
+ * delete their MCP server connections.
  */
 
 "use client";
 
 import { useEffect, useState, useCallback } from "react";
 import { useAuth, useAuthFetch } from "@/lib/auth-context";
-import { ServerForm, type ServerConfig, type TestConnectionResult } from "@/components/mcp-server";
+import { CatalogPicker, ServerForm, type ServerConfig, type TestConnectionResult } from "@/components/mcp-server";
 
 // =============================================================================
 // Types
@@ -30,15 +31,18 @@ interface UserServerView {
 }
 
 interface ServerTool {
+  id: string;
   name: string;
   description: string;
+  adminEnabled: boolean;
+  enabled: boolean;
 }
 
 // =============================================================================
 // Components
 // =============================================================================
 
-function ServerListItem({ server, onToggle, onExpand, onDelete, onEdit, isExpanded, tools }: { server: UserServerView; onToggle: (enabled: boolean) => void; onExpand: () => void; onDelete?: () => void; onEdit?: () => void; isExpanded: boolean; tools: ServerTool[] }) {
+function ServerListItem({ server, onToggle, onToggleTool, onExpand, onDelete, onEdit, isExpanded, tools }: { server: UserServerView; onToggle: (enabled: boolean) => void; onToggleTool: (toolId: string, enabled: boolean) => void; onExpand: () => void; onDelete?: () => void; onEdit?: () => void; isExpanded: boolean; tools: ServerTool[] }) {
   const statusColors: Record<ServerStatus, string> = {
     enabled: "bg-green-100 text-green-800",
     disabled: "bg-gray-100 text-gray-800",
@@ -106,9 +110,13 @@ function ServerListItem({ server, onToggle, onExpand, onDelete, onEdit, isExpand
           ) : (
             <ul className="space-y-2">
               {tools.map((tool) => (
-                <li key={tool.name} className="text-sm">
-                  <span className="font-mono text-blue-600">{tool.name}</span>
-                  <p className="text-gray-500 text-xs">{tool.description}</p>
+                <li key={tool.name} className="text-sm flex items-start gap-2">
+                  <input type="checkbox" className="mt-1" checked={tool.adminEnabled && tool.enabled} disabled={!tool.adminEnabled} onChange={(e) => onToggleTool(tool.id, e.target.checked)} aria-label={`Enable ${tool.name}`} />
+                  <div>
+                    <span className={`font-mono ${tool.adminEnabled && tool.enabled ? "text-blue-600" : "text-gray-400"}`}>{tool.name}</span>
+                    {!tool.adminEnabled && <span className="ml-2 text-xs text-gray-500">Disabled by an administrator</span>}
+                    <p className="text-gray-500 text-xs">{tool.description}</p>
+                  </div>
                 </li>
               ))}
             </ul>
@@ -131,6 +139,7 @@ export default function UserToolsPage() {
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
   const [showAddForm, setShowAddForm] = useState(false);
+  const [showCatalog, setShowCatalog] = useState(false);
   const [editingServerId, setEditingServerId] = useState<string | null>(null);
   const [expandedServerId, setExpandedServerId] = useState<string | null>(null);
   const [serverTools, setServerTools] = useState<Record<string, ServerTool[]>>({});
@@ -173,6 +182,21 @@ export default function UserToolsPage() {
     }
   };
 
+  const handleToggleTool = async (serverId: string, toolId: string, enabled: boolean) => {
+    try {
+      const res = await authFetch(`/mcp/servers/${serverId}/tools/${toolId}`, {
+        method: "PATCH",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ enabled }),
+      });
+      if (res.ok) {
+        setServerTools((prev) => ({ ...prev, [serverId]: (prev[serverId] || []).map((t) => (t.id === toolId ? { ...t, enabled } : t)) }));
+      }
+    } catch (err) {
+      console.error("Failed to toggle tool", err);
+    }
+  };
+
   const handleExpand = async (serverId: string) => {
     if (expandedServerId === serverId) {
       setExpandedServerId(null);
@@ -306,10 +330,21 @@ export default function UserToolsPage() {
 
       {error && <div className="mb-4 p-3 bg-red-50 text-red-800 rounded-md">{error}</div>}
 
-      <div className="mb-6 flex justify-end">
+      <div className="mb-6 flex justify-end gap-2">
+        <button
+          onClick={() => {
+            setShowCatalog(!showCatalog);
+            setShowAddForm(false);
+            setEditingServerId(null);
+          }}
+          className="px-4 py-2 text-sm font-medium text-gray-700 bg-white border rounded-md hover:bg-gray-50"
+        >
+          {showCatalog ? "Close Catalog" : "Browse Catalog"}
+        </button>
         <button
           onClick={() => {
             setShowAddForm(!showAddForm);
+            setShowCatalog(false);
             setEditingServerId(null);
           }}
           className="px-4 py-2 text-sm font-medium text-white bg-blue-600 rounded-md hover:bg-blue-700"
@@ -318,6 +353,21 @@ export default function UserToolsPage() {
         </button>
       </div>
 
+      {showCatalog && (
+        <div className="mb-6">
+          <CatalogPicker
+            authFetch={authFetch}
+            installPath={(slug) => `/mcp/catalog/${slug}/install`}
+            userInstallableOnly
+            onCancel={() => setShowCatalog(false)}
+            onInstalled={() => {
+              setShowCatalog(false);
+              loadServers();
+            }}
+          />
+        </div>
+      )}
+
       {showAddForm && (
         <div className="mb-6">
           <ServerForm
@@ -376,7 +426,7 @@ export default function UserToolsPage() {
             <p className="text-sm text-gray-400 mt-1">Add a server to get started.</p>
           </div>
         ) : (
-          servers.map((server) => <ServerListItem key={server.id} server={server} onToggle={(enabled) => handleToggle(server.id, enabled)} onExpand={() => handleExpand(server.id)} onDelete={server.isOwned ? () => handleDelete(server.id) : undefined} onEdit={server.isOwned ? () => handleEdit(server.id) : undefined} isExpanded={expandedServerId === server.id} tools={serverTools[server.id] || []} />)
+          servers.map((server) => <ServerListItem key={server.id} server={server} onToggle={(enabled) => handleToggle(server.id, enabled)} onToggleTool={(toolId, enabled) => handleToggleTool(server.id, toolId, enabled)} onExpand={() => handleExpand(server.id)} onDelete={server.isOwned ? () => handleDelete(server.id) : undefined} onEdit={server.isOwned ? () => handleEdit(server.id) : undefined} isExpanded={expandedServerId === server.id} tools={serverTools[server.id] || []} />)
         )}
       </div>
     </div>
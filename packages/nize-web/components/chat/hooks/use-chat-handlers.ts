@@ -3,7 +3,7 @@
 import { useCallback, useState } from "react";
 import { type UIMessage } from "ai";
 import { apiUrl } from "@/lib/api";
-import { useAuthFetch } from "@/lib/auth-context";
+import { csrfHeaders, useAuthFetch } from "@/lib/auth-context";
 import { nanoid } from "nanoid";
 
 interface UseFileUploadReturn {
@@ -30,6 +30,7 @@ export function useFileUpload(): UseFileUploadReturn {
       const res = await fetch(apiUrl("/ingest"), {
         method: "POST",
         credentials: "include", // Send httpOnly cookies
+        headers: csrfHeaders(),
         body: formData,
       });
 
@@ -48,6 +49,8 @@ export function useFileUpload(): UseFileUploadReturn {
 
 interface UseChatSubmitOptions {
   input: string;
+  /** Conversation the message continues, counted in the cost estimate. */
+  conversationId?: string;
   setInput: (value: string) => void;
   sendMessage: (options: { text: string }) => Promise<void>;
   setMessages: React.Dispatch<React.SetStateAction<UIMessage[]>>;
@@ -57,9 +60,45 @@ interface UseChatSubmitReturn {
   handleChatSubmit: (event: React.FormEvent<HTMLFormElement>) => Promise<void>;
 }
 
-export function useChatSubmit({ input, setInput, sendMessage, setMessages }: UseChatSubmitOptions): UseChatSubmitReturn {
+/** Subset of `POST /chat/estimate` the confirmation needs. */
+interface ChatEstimate {
+  model: string;
+  inputTokens: number;
+  totalCostUsd: number | null;
+  warnings: Array<"costAboveThreshold" | "exceedsContextWindow">;
+}
+
+/** Confirmation text for a flagged estimate. */
+function estimateWarning(estimate: ChatEstimate): string {
+  const lines = [`This message is large for ${estimate.model}:`, `about ${estimate.inputTokens.toLocaleString()} input tokens`];
+  if (estimate.totalCostUsd !== null) lines.push(`an estimated cost of $${estimate.totalCostUsd.toFixed(2)}`);
+  if (estimate.warnings.includes("exceedsContextWindow")) lines.push("more than the model's context window");
+  return `${lines.join("\n- ")}\n\nSend it anyway?`;
+}
+
+export function useChatSubmit({ input, conversationId, setInput, sendMessage, setMessages }: UseChatSubmitOptions): UseChatSubmitReturn {
   const authFetch = useAuthFetch();
 
+  // Ask before sending a message the server flags as expensive or too
+  // large. A failed estimate never blocks sending.
+  const confirmCost = useCallback(
+    async (prompt: string): Promise<boolean> => {
+      try {
+        const res = await authFetch("/chat/estimate", {
+          method: "POST",
+          headers: { "Content-Type": "application/json" },
+          body: JSON.stringify({ prompt, conversationId }),
+        });
+        if (!res.ok) return true;
+        const estimate = (await res.json()) as ChatEstimate;
+        return estimate.warnings.length === 0 || window.confirm(estimateWarning(estimate));
+      } catch {
+        return true;
+      }
+    },
+    [authFetch, conversationId],
+  );
+
   const handleChatSubmit = useCallback(
     async (event: React.FormEvent<HTMLFormElement>) => {
       event.preventDefault();
@@ -69,6 +108,7 @@ export function useChatSubmit({ input, setInput, sendMessage, setMessages }: Use
       const isListFilesQuery = /\b(list|show)\s+(my\s+)?(files|documents)\b/i.test(trimmed);
 
       if (!isListFilesQuery) {
+        if (!(await confirmCost(trimmed))) return;
         setInput("");
         await sendMessage({ text: trimmed });
         return;
@@ -88,7 +128,7 @@ export function useChatSubmit({ input, setInput, sendMessage, setMessages }: Use
         setMessages((prev) => [...prev, { id: nanoid(), role: "assistant", content: "Unable to list files right now.", parts: [] }]);
       }
     },
-    [input, setInput, sendMessage, setMessages, authFetch],
+    [input, setInput, sendMessage, setMessages, authFetch, confirmCost],
   );
 
   return { handleChatSubmit };
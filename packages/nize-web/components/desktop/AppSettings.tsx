@@ -0,0 +1,76 @@
+// @awa-impl: DESKTOP-AppSettings — window, tray, update channel and telemetry preferences
+
+"use client";
+
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+// Matches Rust AppSettings
+interface AppSettingsData {
+  window: { restoreState: boolean; startMinimized: boolean; closeToTray: boolean };
+  tray: { enabled: boolean; showUnreadCount: boolean };
+  updateChannel: "stable" | "beta";
+  telemetryOptIn: boolean;
+}
+
+type Patch = { [K in keyof AppSettingsData]?: AppSettingsData[K] extends object ? Partial<AppSettingsData[K]> : AppSettingsData[K] };
+
+/**
+ * General desktop preferences. Each change is saved immediately; changes made
+ * in another window arrive through the "app-settings-changed" event.
+ */
+export function AppSettings() {
+  const [settings, setSettings] = useState<AppSettingsData | null>(null);
+  const [status, setStatus] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<AppSettingsData>("get_app_settings")
+      .then(setSettings)
+      .catch((e) => setStatus(String(e)));
+    const unlisten = listen<AppSettingsData>("app-settings-changed", (event) => setSettings(event.payload));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  async function update(patch: Patch) {
+    setStatus(null);
+    try {
+      setSettings(await invoke<AppSettingsData>("update_app_settings", { patch }));
+    } catch (e) {
+      setStatus(String(e));
+    }
+  }
+
+  if (!settings) {
+    return status ? <p style={{ fontSize: "0.875rem" }}>{status}</p> : null;
+  }
+
+  const checkbox = (label: string, checked: boolean, patch: (value: boolean) => Patch) => (
+    <label style={{ display: "block", marginBottom: "0.25rem" }}>
+      <input type="checkbox" checked={checked} onChange={(e) => update(patch(e.target.checked))} style={{ marginRight: "0.5rem" }} />
+      {label}
+    </label>
+  );
+
+  return (
+    <div>
+      <h3 style={{ marginBottom: "0.5rem" }}>General</h3>
+      {checkbox("Restore window size and position", settings.window.restoreState, (v) => ({ window: { restoreState: v } }))}
+      {checkbox("Start minimized", settings.window.startMinimized, (v) => ({ window: { startMinimized: v } }))}
+      {checkbox("Keep running in the tray when the window is closed", settings.window.closeToTray, (v) => ({ window: { closeToTray: v } }))}
+      {checkbox("Show tray icon", settings.tray.enabled, (v) => ({ tray: { enabled: v } }))}
+      {checkbox("Show unread count on the tray icon", settings.tray.showUnreadCount, (v) => ({ tray: { showUnreadCount: v } }))}
+      {checkbox("Share anonymous usage statistics", settings.telemetryOptIn, (v) => ({ telemetryOptIn: v }))}
+      <label style={{ display: "block", marginTop: "0.5rem" }}>
+        Update channel{" "}
+        <select value={settings.updateChannel} onChange={(e) => update({ updateChannel: e.target.value as AppSettingsData["updateChannel"] })}>
+          <option value="stable">Stable</option>
+          <option value="beta">Beta</option>
+        </select>
+      </label>
+      {status && <p style={{ fontSize: "0.875rem", marginTop: "0.5rem" }}>{status}</p>}
+    </div>
+  );
+}
@@ -0,0 +1,56 @@
+// @awa-impl: DESKTOP-DbEncryption — encryption-at-rest toggle
+
+"use client";
+
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+
+// Matches Rust DbEncryptionStatus
+interface DbEncryptionStatus {
+  enabled: boolean;
+  active: boolean;
+}
+
+/**
+ * Toggles encryption at rest for the bundled database. The key lives in the
+ * OS keychain; existing data is converted on the next launch.
+ */
+export function DbEncryptionSettings() {
+  const [state, setState] = useState<DbEncryptionStatus | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<DbEncryptionStatus>("get_db_encryption")
+      .then(setState)
+      .catch((e) => setError(String(e)));
+  }, []);
+
+  async function handleToggle(enabled: boolean) {
+    setError(null);
+    try {
+      setState(await invoke<DbEncryptionStatus>("set_db_encryption", { enabled }));
+    } catch (e) {
+      setError(String(e));
+    }
+  }
+
+  return (
+    <div>
+      <h3 style={{ marginBottom: "0.5rem" }}>Database Encryption</h3>
+      <p style={{ fontSize: "0.875rem", color: "#666", marginBottom: "0.5rem" }}>Encrypt conversations, notes and secrets stored on this computer with a key kept in the OS keychain.</p>
+      {state && (
+        <>
+          <label style={{ display: "flex", gap: "0.5rem", alignItems: "center" }}>
+            <input type="checkbox" checked={state.enabled} onChange={(e) => handleToggle(e.target.checked)} />
+            Encrypt database at rest
+          </label>
+          <p style={{ fontSize: "0.875rem", marginTop: "0.5rem" }}>
+            Currently {state.active ? "encrypted" : "not encrypted"}
+            {state.enabled !== state.active && " — restart Nize to apply"}
+          </p>
+        </>
+      )}
+      {error && <p style={{ fontSize: "0.875rem", marginTop: "0.5rem", color: "red" }}>{error}</p>}
+    </div>
+  );
+}
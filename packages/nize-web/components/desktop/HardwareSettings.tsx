@@ -0,0 +1,85 @@
+// @awa-impl: DESKTOP-Hardware — detected hardware and local model support
+
+"use client";
+
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+
+// Matches Rust HardwareProfile
+interface HardwareProfile {
+  os: string;
+  arch: string;
+  cpu: { brand: string; logicalCores: number; physicalCores: number | null; features: string[] };
+  memory: { totalBytes: number; availableBytes: number };
+  gpus: { name: string; api: "metal" | "cuda"; memoryBytes: number | null }[];
+}
+
+// Matches Rust Gate
+interface Gate {
+  supported: boolean;
+  reason?: string;
+}
+
+// Matches Rust HardwareReport
+interface HardwareReport {
+  profile: HardwareProfile;
+  capabilities: {
+    localEmbeddings: Gate;
+    localTranscription: Gate;
+    embeddingProvider: string;
+    transcriptionProvider: string;
+    whisperModel: string;
+  };
+}
+
+function formatGiB(bytes: number): string {
+  return `${(bytes / (1024 * 1024 * 1024)).toFixed(1)} GiB`;
+}
+
+function GateLine({ label, gate }: { label: string; gate: Gate }) {
+  return (
+    <p style={{ fontSize: "0.875rem" }}>
+      {label}: <span style={{ color: gate.supported ? "green" : "red" }}>{gate.supported ? "supported" : "unsupported"}</span>
+      {gate.reason && <span style={{ color: "#666" }}> — {gate.reason}</span>}
+    </p>
+  );
+}
+
+/**
+ * The machine's CPU, memory and GPUs, and whether it can run local
+ * embedding and transcription models.
+ */
+export function HardwareSettings() {
+  const [report, setReport] = useState<HardwareReport | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<HardwareReport>("get_hardware_profile")
+      .then(setReport)
+      .catch((e) => setError(String(e)));
+  }, []);
+
+  return (
+    <div>
+      <h3 style={{ marginBottom: "0.5rem" }}>Hardware</h3>
+      {report && (
+        <>
+          <p style={{ fontSize: "0.875rem" }}>
+            CPU: {report.profile.cpu.brand || report.profile.arch} ({report.profile.cpu.logicalCores} threads
+            {report.profile.cpu.features.length > 0 && `, ${report.profile.cpu.features.join(" ")}`})
+          </p>
+          <p style={{ fontSize: "0.875rem" }}>
+            Memory: {formatGiB(report.profile.memory.totalBytes)} ({formatGiB(report.profile.memory.availableBytes)} free)
+          </p>
+          <p style={{ fontSize: "0.875rem" }}>
+            GPU: {report.profile.gpus.length === 0 ? "none detected" : report.profile.gpus.map((gpu) => `${gpu.name} (${gpu.api}${gpu.memoryBytes !== null ? `, ${formatGiB(gpu.memoryBytes)}` : ""})`).join(", ")}
+          </p>
+          <GateLine label="Local embeddings" gate={report.capabilities.localEmbeddings} />
+          <GateLine label="Local transcription" gate={report.capabilities.localTranscription} />
+          <p style={{ fontSize: "0.75rem", color: "#666", marginTop: "0.25rem" }}>Recommended whisper model: {report.capabilities.whisperModel}</p>
+        </>
+      )}
+      {error && <p style={{ fontSize: "0.875rem", color: "red" }}>{error}</p>}
+    </div>
+  );
+}
@@ -0,0 +1,53 @@
+// @awa-impl: DESKTOP-LocalMode — single-user mode toggle
+
+"use client";
+
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+
+// Matches Rust LocalModeStatus
+interface LocalModeStatus {
+  enabled: boolean;
+  active: boolean;
+}
+
+/**
+ * Toggles local single-user mode: the app signs in automatically as the
+ * local user instead of asking for an account.
+ */
+export function LocalModeSettings() {
+  const [state, setState] = useState<LocalModeStatus | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<LocalModeStatus>("get_local_mode")
+      .then(setState)
+      .catch((e) => setError(String(e)));
+  }, []);
+
+  async function handleToggle(enabled: boolean) {
+    setError(null);
+    try {
+      setState(await invoke<LocalModeStatus>("set_local_mode", { enabled }));
+    } catch (e) {
+      setError(String(e));
+    }
+  }
+
+  return (
+    <div>
+      <h3 style={{ marginBottom: "0.5rem" }}>Local Mode</h3>
+      <p style={{ fontSize: "0.875rem", color: "#666", marginBottom: "0.5rem" }}>Use Nize on this computer without an account. Anyone who can open the app gets in, so only enable this on a computer you do not share.</p>
+      {state && (
+        <>
+          <label style={{ display: "flex", gap: "0.5rem", alignItems: "center" }}>
+            <input type="checkbox" checked={state.enabled} onChange={(e) => handleToggle(e.target.checked)} />
+            Sign in automatically
+          </label>
+          {state.enabled !== state.active && <p style={{ fontSize: "0.875rem", marginTop: "0.5rem" }}>Restart Nize to apply</p>}
+        </>
+      )}
+      {error && <p style={{ fontSize: "0.875rem", marginTop: "0.5rem", color: "red" }}>{error}</p>}
+    </div>
+  );
+}
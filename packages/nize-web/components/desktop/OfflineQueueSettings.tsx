@@ -0,0 +1,111 @@
+// @awa-impl: DESKTOP-OfflineQueue — requests waiting for connectivity
+
+"use client";
+
+import { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+// Matches Rust QueuedRequest
+interface QueuedRequest {
+  id: number;
+  method: string;
+  path: string;
+  label: string;
+  status: "pending" | "conflict" | "failed";
+  attempts: number;
+  lastError?: string;
+  queuedAtMs: number;
+}
+
+// Matches Rust QueueSnapshot
+interface QueueSnapshot {
+  entries: QueuedRequest[];
+  pending: number;
+  conflicts: number;
+  failed: number;
+  replaying: boolean;
+  lastReplayAtMs: number | null;
+}
+
+const STATUS_COLORS = { pending: "#666", conflict: "#b45309", failed: "red" };
+
+/**
+ * Changes made while the API was unreachable. Pending requests are sent
+ * when the browser reports connectivity again (or on "Send now");
+ * conflicting or rejected ones wait for the user to retry or discard them.
+ */
+export function OfflineQueueSettings() {
+  const [queue, setQueue] = useState<QueueSnapshot | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const replay = useCallback(async () => {
+    setError(null);
+    try {
+      await invoke("replay_offline_queue");
+    } catch (e) {
+      setError(String(e));
+    }
+  }, []);
+
+  useEffect(() => {
+    invoke<QueueSnapshot>("get_offline_queue")
+      .then(setQueue)
+      .catch((e) => setError(String(e)));
+    const unlisten = listen<QueueSnapshot>("offline-queue-changed", (event) => setQueue(event.payload));
+    window.addEventListener("online", replay);
+    return () => {
+      unlisten.then((fn) => fn());
+      window.removeEventListener("online", replay);
+    };
+  }, [replay]);
+
+  async function retry(id: number, force: boolean) {
+    setError(null);
+    try {
+      setQueue(await invoke<QueueSnapshot>("retry_queued_request", { id, force }));
+      await replay();
+    } catch (e) {
+      setError(String(e));
+    }
+  }
+
+  async function discard(id: number) {
+    setError(null);
+    try {
+      setQueue(await invoke<QueueSnapshot>("discard_queued_request", { id }));
+    } catch (e) {
+      setError(String(e));
+    }
+  }
+
+  return (
+    <div>
+      <h3 style={{ marginBottom: "0.5rem" }}>Offline Changes</h3>
+      <p style={{ fontSize: "0.875rem", color: "#666", marginBottom: "0.5rem" }}>Changes saved while the server was unreachable are sent once it is back.</p>
+      {error && <p style={{ color: "red", fontSize: "0.875rem" }}>{error}</p>}
+      {queue && queue.entries.length === 0 && <p style={{ fontSize: "0.875rem" }}>Nothing waiting.</p>}
+      {queue && queue.entries.length > 0 && (
+        <>
+          <ul style={{ fontSize: "0.875rem", listStyle: "none", padding: 0 }}>
+            {queue.entries.map((entry) => (
+              <li key={entry.id} style={{ marginBottom: "0.5rem" }}>
+                <strong>{entry.label}</strong> <span style={{ color: STATUS_COLORS[entry.status] }}>{entry.status}</span>
+                <span style={{ color: "#666" }}> · queued {new Date(entry.queuedAtMs).toLocaleString()}</span>
+                {entry.lastError && <div style={{ color: "#666" }}>{entry.lastError}</div>}
+                <div style={{ display: "flex", gap: "0.5rem", marginTop: "0.25rem" }}>
+                  {entry.status === "conflict" && <button onClick={() => retry(entry.id, true)}>Overwrite</button>}
+                  {entry.status === "failed" && <button onClick={() => retry(entry.id, false)}>Retry</button>}
+                  <button onClick={() => discard(entry.id)}>Discard</button>
+                </div>
+              </li>
+            ))}
+          </ul>
+          <button onClick={replay} disabled={queue.replaying || queue.pending === 0}>
+            {queue.replaying ? "Sending…" : `Send now (${queue.pending})`}
+          </button>
+        </>
+      )}
+    </div>
+  );
+}
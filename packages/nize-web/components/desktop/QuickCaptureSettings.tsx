@@ -0,0 +1,50 @@
+// @awa-impl: DESKTOP-QuickCapture — global shortcut configuration
+
+"use client";
+
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+
+// Matches Rust QuickCaptureSettings
+interface QuickCaptureSettingsData {
+  shortcut: string;
+}
+
+/**
+ * Lets the user change the global shortcut that opens the quick-capture window.
+ * Accepts accelerator strings such as "CommandOrControl+Shift+Space".
+ */
+export function QuickCaptureSettings() {
+  const [shortcut, setShortcut] = useState("");
+  const [status, setStatus] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<QuickCaptureSettingsData>("get_quick_capture_settings")
+      .then((s) => setShortcut(s.shortcut))
+      .catch((e) => setStatus(String(e)));
+  }, []);
+
+  async function handleSave() {
+    setStatus(null);
+    try {
+      await invoke("set_quick_capture_shortcut", { shortcut });
+      setStatus("Shortcut saved");
+    } catch (e) {
+      setStatus(String(e));
+    }
+  }
+
+  return (
+    <div>
+      <h3 style={{ marginBottom: "0.5rem" }}>Quick Capture</h3>
+      <p style={{ fontSize: "0.875rem", color: "#666", marginBottom: "0.5rem" }}>Global shortcut that opens the quick-capture window from anywhere.</p>
+      <div style={{ display: "flex", gap: "0.5rem" }}>
+        <input value={shortcut} onChange={(e) => setShortcut(e.target.value)} style={{ flex: 1, padding: "0.25rem 0.5rem", border: "1px solid #ccc", borderRadius: 4 }} />
+        <button onClick={handleSave} disabled={!shortcut.trim()}>
+          Save
+        </button>
+      </div>
+      {status && <p style={{ fontSize: "0.875rem", marginTop: "0.5rem" }}>{status}</p>}
+    </div>
+  );
+}
@@ -0,0 +1,78 @@
+// @awa-impl: DESKTOP-StartupError — error screen for failed service startup
+
+"use client";
+
+import { useEffect, useState } from "react";
+import { isTauri } from "@/lib/tauri";
+
+// Matches Rust StartupCheck
+interface StartupCheck {
+  name: string;
+  status: "passed" | "failed" | "skipped";
+  message: string | null;
+  hint: string | null;
+}
+
+// Matches Rust StartupError
+interface StartupError {
+  message: string;
+  checks: StartupCheck[];
+}
+
+const CHECK_LABELS: Record<string, string> = {
+  apiPort: "API port",
+  mcpPort: "MCP port",
+  database: "Database connection",
+  pgvector: "pgvector extension",
+  migrations: "Database migrations",
+  secrets: "Stored secrets",
+};
+
+const STATUS_COLORS = { passed: "green", failed: "red", skipped: "#666" };
+
+/**
+ * Covers the app when its services failed to start, listing the failed
+ * startup checks with what to do about each. Renders nothing outside the
+ * desktop app or when startup succeeded.
+ */
+export function StartupErrorScreen() {
+  const [error, setError] = useState<StartupError | null>(null);
+  const [restarting, setRestarting] = useState(false);
+
+  useEffect(() => {
+    if (!isTauri()) return;
+    import("@tauri-apps/api/core")
+      .then(({ invoke }) => invoke<StartupError | null>("get_startup_error"))
+      .then(setError)
+      .catch(() => setError(null));
+  }, []);
+
+  if (!error) return null;
+
+  async function handleRestart() {
+    setRestarting(true);
+    const { relaunch } = await import("@tauri-apps/plugin-process");
+    await relaunch();
+  }
+
+  return (
+    <div role="alert" style={{ position: "fixed", inset: 0, zIndex: 1000, overflow: "auto", background: "white", padding: "2rem" }}>
+      <h2 style={{ marginBottom: "0.5rem" }}>Nize could not start</h2>
+      <p style={{ fontSize: "0.875rem", marginBottom: "1rem" }}>{error.message}</p>
+      {error.checks.length > 0 && (
+        <ul style={{ listStyle: "none", padding: 0, marginBottom: "1rem" }}>
+          {error.checks.map((check) => (
+            <li key={check.name} style={{ marginBottom: "0.75rem", fontSize: "0.875rem" }}>
+              <span style={{ color: STATUS_COLORS[check.status] }}>{check.status}</span> {CHECK_LABELS[check.name] ?? check.name}
+              {check.message && <div style={{ color: "#666" }}>{check.message}</div>}
+              {check.hint && <div>{check.hint}</div>}
+            </li>
+          ))}
+        </ul>
+      )}
+      <button onClick={handleRestart} disabled={restarting}>
+        {restarting ? "Restarting…" : "Restart Nize"}
+      </button>
+    </div>
+  );
+}
@@ -0,0 +1,125 @@
+// @awa-impl: DESKTOP-Storage — database disk usage and maintenance
+
+"use client";
+
+import { useCallback, useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { useAuthFetch } from "@/lib/auth-context";
+
+// Matches Rust StorageUsage
+interface StorageUsage {
+  dataDir: string | null;
+  dataDirBytes: number;
+  sealedBytes: number | null;
+}
+
+// Matches the API's StorageReport (admins only)
+interface StorageReport {
+  level: "ok" | "warning" | "critical";
+  totalBytes: number;
+  blobBytes: number;
+  thresholds: { warnBytes: number | null; criticalBytes: number | null };
+}
+
+// Matches the API's MaintenanceReport
+interface MaintenanceReport {
+  vacuumed: boolean;
+  orphanedBlobsDeleted: number;
+  tracesDeleted: number;
+  auditEntriesDeleted: number;
+}
+
+/** Days of tool traces and config audit entries kept by "Clean up". */
+const RETENTION_DAYS = 90;
+
+const LEVEL_COLORS = { ok: "green", warning: "#b45309", critical: "red" };
+
+function formatBytes(bytes: number): string {
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(0)} KiB`;
+  if (bytes < 1024 * 1024 * 1024) return `${(bytes / (1024 * 1024)).toFixed(1)} MiB`;
+  return `${(bytes / (1024 * 1024 * 1024)).toFixed(2)} GiB`;
+}
+
+/**
+ * Disk usage of the bundled database, measured locally. Administrators also
+ * see the configured limits and can reclaim space.
+ */
+export function StorageSettings() {
+  const authFetch = useAuthFetch();
+  const [usage, setUsage] = useState<StorageUsage | null>(null);
+  const [report, setReport] = useState<StorageReport | null>(null);
+  const [result, setResult] = useState<MaintenanceReport | null>(null);
+  const [running, setRunning] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const load = useCallback(async () => {
+    try {
+      setUsage(await invoke<StorageUsage>("get_storage_usage"));
+      // Non-admins get 403; they only see the local size.
+      const res = await authFetch("/admin/storage");
+      setReport(res.ok ? await res.json() : null);
+    } catch (e) {
+      setError(String(e));
+    }
+  }, [authFetch]);
+
+  useEffect(() => {
+    load();
+  }, [load]);
+
+  async function handleCleanup() {
+    setRunning(true);
+    setError(null);
+    try {
+      const res = await authFetch("/admin/storage/maintenance", {
+        method: "POST",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ vacuum: true, orphanedBlobs: true, traceRetentionDays: RETENTION_DAYS, auditRetentionDays: RETENTION_DAYS }),
+      });
+      if (!res.ok) {
+        const data = await res.json().catch(() => ({}));
+        throw new Error(data.message || "Maintenance failed");
+      }
+      setResult(await res.json());
+      await load();
+    } catch (e) {
+      setError(e instanceof Error ? e.message : String(e));
+    } finally {
+      setRunning(false);
+    }
+  }
+
+  return (
+    <div>
+      <h3 style={{ marginBottom: "0.5rem" }}>Storage</h3>
+      {usage && (
+        <p style={{ fontSize: "0.875rem" }}>
+          Database: {formatBytes(usage.dataDirBytes + (usage.sealedBytes ?? 0))}
+          {usage.dataDir && <span style={{ color: "#666" }}> ({usage.dataDir})</span>}
+        </p>
+      )}
+      {report && (
+        <>
+          <p style={{ fontSize: "0.875rem" }}>
+            Status: <span style={{ color: LEVEL_COLORS[report.level] }}>{report.level}</span>
+            {report.thresholds.warnBytes !== null && ` — warning at ${formatBytes(report.thresholds.warnBytes)}`}
+            {report.thresholds.criticalBytes !== null && `, critical at ${formatBytes(report.thresholds.criticalBytes)}`}
+          </p>
+          <p style={{ fontSize: "0.875rem" }}>Files: {formatBytes(report.blobBytes)}</p>
+          <button onClick={handleCleanup} disabled={running} style={{ marginTop: "0.5rem" }}>
+            {running ? "Cleaning up…" : "Clean up"}
+          </button>
+          <p style={{ fontSize: "0.75rem", color: "#666", marginTop: "0.25rem" }}>
+            Deletes tool traces and MCP audit entries older than {RETENTION_DAYS} days and files no document uses, then compacts the database.
+          </p>
+        </>
+      )}
+      {result && (
+        <p style={{ fontSize: "0.875rem", marginTop: "0.5rem" }}>
+          Removed {result.tracesDeleted} traces, {result.auditEntriesDeleted} audit entries and {result.orphanedBlobsDeleted} unused files.
+        </p>
+      )}
+      {error && <p style={{ fontSize: "0.875rem", marginTop: "0.5rem", color: "red" }}>{error}</p>}
+    </div>
+  );
+}
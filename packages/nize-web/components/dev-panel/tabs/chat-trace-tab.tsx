@@ -4,21 +4,33 @@
 
 /**
  * Dev panel tab for displaying chat trace data.
- * Connects to SSE endpoint when conversation is active.
+ * Shows the latest recorded trace of the active conversation (recorded by
+ * the chat service when agent.trace.enabled is set) and polls for the next
+ * one while a reply is in progress.
  */
 
-import { useState, useEffect, useRef, useCallback } from "react";
+import { useState, useEffect, useCallback } from "react";
 import { useDevPanel } from "@/lib/dev-panel-context";
 import { apiUrl } from "@/lib/api";
 
-// Trace event types matching backend
+// Trace event types matching backend; other fields depend on the type
 interface TraceEvent {
   type: string;
   timestamp: string;
+  [key: string]: unknown;
+}
+
+// Matches ChatTraceResponse
+interface ChatTrace {
+  id: string;
   messageId: string;
-  payload: Record<string, unknown>;
+  events: TraceEvent[];
 }
 
+/** Poll interval and duration while waiting for a reply's trace */
+const POLL_MS = 3000;
+const POLL_ATTEMPTS = 40;
+
 interface PromptSection {
   name: string;
   content: string;
@@ -38,7 +50,6 @@ export function ChatTraceTab() {
   const [isLoading, setIsLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [expandedSections, setExpandedSections] = useState<Set<string>>(new Set());
-  const eventSourceRef = useRef<EventSource | null>(null);
 
   // Toggle section expansion - must be before any conditional returns
   const toggleSection = useCallback((key: string) => {
@@ -59,68 +70,59 @@ export function ChatTraceTab() {
     setError(null);
   }, [conversationId]);
 
-  // @awa-impl: TRC-5_AC-3 - SSE connection for live updates
+  // @awa-impl: TRC-5_AC-3 - refresh when chat activity starts
   // Must be before any conditional returns to satisfy React's Rules of Hooks
-  // traceKey changes when new chat activity starts, triggering reconnection
+  // traceKey changes when new chat activity starts; the reply's trace is
+  // recorded when it finishes, so poll until a new trace appears
   useEffect(() => {
     if (!conversationId || !isAdmin) return;
 
-    // Small delay to let backend set up the trace emitter
-    const delayMs = traceKey > 0 ? 100 : 0;
+    let cancelled = false;
+    let timeoutId: ReturnType<typeof setTimeout> | undefined;
+    // On chat activity, wait for a trace other than the one shown now
+    const waitForNew = traceKey > 0;
+    let firstTraceId: string | null | undefined;
+    let attempts = 0;
 
-    const timeoutId = setTimeout(() => {
+    const poll = async () => {
       setIsLoading(true);
-      setError(null);
-
-      // Connect to SSE endpoint
-      const url = apiUrl(`/dev/chat_trace?conversationId=${conversationId}`);
-      const eventSource = new EventSource(url, { withCredentials: true });
-      eventSourceRef.current = eventSource;
-
-      eventSource.addEventListener("trace", (e) => {
-        try {
-          const event = JSON.parse(e.data) as TraceEvent;
-          // Append new events, avoiding duplicates by messageId+type+timestamp
-          setEvents((prev) => {
-            const key = `${event.messageId}-${event.type}-${event.timestamp}`;
-            const exists = prev.some((p) => `${p.messageId}-${p.type}-${p.timestamp}` === key);
-            if (exists) return prev;
-            return [...prev, event];
-          });
+      let traceId: string | null = null;
+      try {
+        const res = await fetch(apiUrl(`/dev/chat_trace?conversationId=${conversationId}`), { credentials: "include" });
+        if (cancelled) return;
+        if (res.ok) {
+          const trace = (await res.json()) as ChatTrace;
+          traceId = trace.id;
+          setEvents(trace.events);
+          setError(null);
+        } else if (res.status !== 404) {
+          setError(`Failed to load trace (${res.status})`);
+          setIsLoading(false);
+          return;
+        }
+      } catch {
+        if (!cancelled) {
+          setError("Failed to load trace");
           setIsLoading(false);
-        } catch {
-          console.error("[ChatTraceTab] Failed to parse trace event");
         }
-      });
-
-      eventSource.addEventListener("done", () => {
-        setIsLoading(false);
-        eventSource.close();
-      });
-
-      eventSource.addEventListener("error", () => {
-        setIsLoading(false);
-        // Only set error if we didn't get any events (404 case)
-        setEvents((prev) => {
-          if (prev.length === 0) {
-            setError("No trace data available");
-          }
-          return prev;
-        });
-        eventSource.close();
-      });
-
-      eventSource.onerror = () => {
-        setIsLoading(false);
-      };
-    }, delayMs);
+        return;
+      }
+      if (cancelled) return;
+      if (firstTraceId === undefined) firstTraceId = traceId;
+      attempts += 1;
+      const done = !waitForNew || traceId !== firstTraceId || attempts >= POLL_ATTEMPTS;
+      if (!done) {
+        timeoutId = setTimeout(poll, POLL_MS);
+        return;
+      }
+      setIsLoading(false);
+      if (traceId === null) setError("No trace data available");
+    };
+    poll();
 
     return () => {
+      cancelled = true;
       clearTimeout(timeoutId);
-      if (eventSourceRef.current) {
-        eventSourceRef.current.close();
-        eventSourceRef.current = null;
-      }
     };
   }, [conversationId, isAdmin, traceKey]);
 
@@ -154,6 +156,7 @@ export function ChatTraceTab() {
   const renderEventContent = (event: TraceEvent, index: number) => {
     const key = `${event.type}-${index}`;
     const isExpanded = expandedSections.has(key);
+    const { type: _type, timestamp: _timestamp, ...payload } = event;
 
     return (
       <div key={key} className="border border-gray-700 rounded mb-2">
@@ -165,7 +168,7 @@ export function ChatTraceTab() {
           <span className="text-gray-500">{isExpanded ? "▼" : "▶"}</span>
         </button>
 
-        {isExpanded && <div className="px-3 py-2 border-t border-gray-700 bg-gray-800/50">{event.type === "prompt_construction" ? renderPromptBreakdown(event.payload as { breakdown: PromptBreakdown }) : <pre className="text-xs overflow-x-auto whitespace-pre-wrap text-green-400">{JSON.stringify(event.payload, null, 2)}</pre>}</div>}
+        {isExpanded && <div className="px-3 py-2 border-t border-gray-700 bg-gray-800/50">{event.type === "prompt_construction" ? renderPromptBreakdown(payload as { breakdown: PromptBreakdown }) : <pre className="text-xs overflow-x-auto whitespace-pre-wrap text-green-400">{JSON.stringify(payload, null, 2)}</pre>}</div>}
       </div>
     );
   };
@@ -210,7 +213,7 @@ export function ChatTraceTab() {
         <div className="text-xs text-gray-400">
           Conversation: <span className="font-mono text-gray-300">{conversationId.slice(0, 8)}...</span>
         </div>
-        {isLoading && <div className="text-xs text-blue-400 animate-pulse">Streaming...</div>}
+        {isLoading && <div className="text-xs text-blue-400 animate-pulse">Waiting for trace...</div>}
       </div>
 
       {/* Event summary */}
@@ -240,7 +243,7 @@ export function ChatTraceTab() {
         <div className="text-center py-4 text-gray-400 text-sm">
           <div className="text-lg mb-2">⏳</div>
           <div>Waiting for trace events...</div>
-          <div className="text-xs mt-1 text-gray-500">Send a message to generate trace data</div>
+          <div className="text-xs mt-1 text-gray-500">Send a message with chat tracing (agent.trace.enabled) on</div>
         </div>
       )}
     </div>
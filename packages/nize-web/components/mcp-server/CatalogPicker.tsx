@@ -0,0 +1,138 @@
+// @awa-component: PLAN-032-CatalogPicker
+
+/**
+ * Catalog of known MCP servers: pick an entry, fill in only its secrets,
+ * and install it.
+ */
+
+"use client";
+
+import { useEffect, useState } from "react";
+
+interface CatalogSecret {
+  name: string;
+  label: string;
+  description?: string;
+  required?: boolean;
+}
+
+interface CatalogEntry {
+  slug: string;
+  name: string;
+  description: string;
+  domain: string;
+  homepage?: string;
+  secrets: CatalogSecret[];
+  source: "builtin" | "custom";
+  userInstallable: boolean;
+}
+
+interface CatalogPickerProps {
+  authFetch: (path: string, options?: RequestInit) => Promise<Response>;
+  /** Install endpoint for a slug, e.g. `/mcp/catalog/${slug}/install`. */
+  installPath: (slug: string) => string;
+  /** Only list entries users may install. */
+  userInstallableOnly?: boolean;
+  onInstalled: () => void;
+  onCancel: () => void;
+}
+
+export function CatalogPicker({ authFetch, installPath, userInstallableOnly = false, onInstalled, onCancel }: CatalogPickerProps) {
+  const [entries, setEntries] = useState<CatalogEntry[]>([]);
+  const [selected, setSelected] = useState<CatalogEntry | null>(null);
+  const [secrets, setSecrets] = useState<Record<string, string>>({});
+  const [installing, setInstalling] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    authFetch("/mcp/catalog")
+      .then((res) => (res.ok ? res.json() : Promise.reject(new Error("Failed to load catalog"))))
+      .then((data) => setEntries((data.entries || []).filter((e: CatalogEntry) => !userInstallableOnly || e.userInstallable)))
+      .catch((err) => setError(err.message));
+  }, [authFetch, userInstallableOnly]);
+
+  const select = (entry: CatalogEntry) => {
+    setSelected(entry);
+    setSecrets({});
+    setError(null);
+  };
+
+  const install = async (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!selected) return;
+    setInstalling(true);
+    setError(null);
+    try {
+      const res = await authFetch(installPath(selected.slug), {
+        method: "POST",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ secrets }),
+      });
+      if (!res.ok) {
+        const data = await res.json().catch(() => ({}));
+        throw new Error(data.message || "Failed to install server");
+      }
+      onInstalled();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : "Failed to install server");
+    } finally {
+      setInstalling(false);
+    }
+  };
+
+  return (
+    <div className="border rounded-lg p-4 bg-white shadow-sm">
+      {error && <div className="mb-3 p-3 bg-red-50 text-red-800 rounded-md text-sm">{error}</div>}
+
+      {!selected ? (
+        <ul className="grid gap-3 sm:grid-cols-2">
+          {entries.map((entry) => (
+            <li key={entry.slug}>
+              <button onClick={() => select(entry)} className="w-full text-left border rounded-md p-3 hover:border-blue-500">
+                <span className="font-medium text-gray-900">{entry.name}</span>
+                <span className="ml-2 text-xs text-gray-500">{entry.domain}</span>
+                <p className="text-sm text-gray-500 mt-1">{entry.description}</p>
+              </button>
+            </li>
+          ))}
+        </ul>
+      ) : (
+        <form onSubmit={install} className="space-y-4">
+          <div>
+            <h3 className="font-medium text-gray-900">{selected.name}</h3>
+            <p className="text-sm text-gray-500">{selected.description}</p>
+            {selected.homepage && (
+              <a href={selected.homepage} target="_blank" rel="noreferrer" className="text-sm text-blue-600 hover:underline">
+                {selected.homepage}
+              </a>
+            )}
+          </div>
+          {selected.secrets.map((secret) => (
+            <div key={secret.name}>
+              <label className="block text-sm font-medium text-gray-700">{secret.label}</label>
+              <input type="password" value={secrets[secret.name] ?? ""} onChange={(e) => setSecrets((prev) => ({ ...prev, [secret.name]: e.target.value }))} className="mt-1 block w-full rounded-md border-gray-300 shadow-sm focus:border-blue-500 focus:ring-blue-500 sm:text-sm" required={secret.required !== false} />
+              {secret.description && <p className="mt-1 text-xs text-gray-500">{secret.description}</p>}
+            </div>
+          ))}
+          {selected.secrets.length === 0 && <p className="text-sm text-gray-500">No secrets needed.</p>}
+          <div className="flex justify-end gap-2">
+            <button type="button" onClick={() => setSelected(null)} className="px-4 py-2 text-sm font-medium text-gray-700 bg-white border rounded-md hover:bg-gray-50">
+              Back
+            </button>
+            <button type="submit" disabled={installing} className="px-4 py-2 text-sm font-medium text-white bg-blue-600 rounded-md hover:bg-blue-700 disabled:opacity-50">
+              {installing ? "Installing..." : "Install"}
+            </button>
+          </div>
+        </form>
+      )}
+
+      {!selected && (
+        <div className="mt-4 flex justify-end">
+          <button onClick={onCancel} className="px-4 py-2 text-sm font-medium text-gray-700 bg-white border rounded-md hover:bg-gray-50">
+            Cancel
+          </button>
+        </div>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,54 @@
+// @awa-component: PLAN-032-SandboxConfigFields
+
+/**
+ * Sandbox fields for transports that spawn a process: working directory,
+ * environment scrubbing, and memory / CPU limits.
+ */
+
+"use client";
+
+import type { SandboxConfig } from "./types";
+
+interface SandboxConfigFieldsProps {
+  sandbox: SandboxConfig;
+  onChange: (sandbox: SandboxConfig) => void;
+}
+
+const parseLimit = (value: string) => (value ? parseInt(value) || undefined : undefined);
+
+export function SandboxConfigFields({ sandbox, onChange }: SandboxConfigFieldsProps) {
+  const update = (patch: Partial<SandboxConfig>) => onChange({ ...sandbox, ...patch });
+
+  return (
+    <details className="border rounded-md p-3" open={Object.keys(sandbox).length > 0}>
+      <summary className="text-sm font-medium text-gray-700 cursor-pointer">Sandbox</summary>
+      <div className="mt-3 space-y-3">
+        <div>
+          <label className="block text-sm font-medium text-gray-700">Working directory</label>
+          <input type="text" value={sandbox.workingDir ?? ""} onChange={(e) => update({ workingDir: e.target.value || undefined })} className="mt-1 block w-full rounded-md border-gray-300 shadow-sm focus:border-blue-500 focus:ring-blue-500 sm:text-sm font-mono" placeholder="/var/lib/nize/mcp/filesystem" />
+          <p className="mt-1 text-xs text-gray-500">Absolute path, created if missing. The process starts there and uses it as its home and temp directory.</p>
+        </div>
+        <label className="flex items-center gap-2 text-sm text-gray-700">
+          <input type="checkbox" checked={sandbox.scrubEnv ?? false} onChange={(e) => update({ scrubEnv: e.target.checked || undefined })} className="rounded border-gray-300" />
+          Scrub environment (pass only PATH, HOME, locale and the variables below)
+        </label>
+        {sandbox.scrubEnv && (
+          <div>
+            <label className="block text-sm font-medium text-gray-700">Also pass (space-separated)</label>
+            <input type="text" value={(sandbox.envAllowlist ?? []).join(" ")} onChange={(e) => update({ envAllowlist: e.target.value.split(/\s+/).filter(Boolean) })} className="mt-1 block w-full rounded-md border-gray-300 shadow-sm focus:border-blue-500 focus:ring-blue-500 sm:text-sm font-mono" placeholder="HTTPS_PROXY NODE_EXTRA_CA_CERTS" />
+          </div>
+        )}
+        <div className="grid grid-cols-2 gap-4">
+          <div>
+            <label className="block text-sm font-medium text-gray-700">Memory limit (MiB)</label>
+            <input type="number" value={sandbox.maxMemoryMb ?? ""} onChange={(e) => update({ maxMemoryMb: parseLimit(e.target.value) })} min={32} className="mt-1 block w-full rounded-md border-gray-300 shadow-sm focus:border-blue-500 focus:ring-blue-500 sm:text-sm" placeholder="Unlimited" />
+          </div>
+          <div>
+            <label className="block text-sm font-medium text-gray-700">CPU time limit (s)</label>
+            <input type="number" value={sandbox.maxCpuSecs ?? ""} onChange={(e) => update({ maxCpuSecs: parseLimit(e.target.value) })} min={1} className="mt-1 block w-full rounded-md border-gray-300 shadow-sm focus:border-blue-500 focus:ring-blue-500 sm:text-sm" placeholder="Unlimited" />
+          </div>
+        </div>
+      </div>
+    </details>
+  );
+}
@@ -21,6 +21,7 @@ import { useServerForm } from "./useServerForm";
 import { useOAuthFlow } from "./useOAuthFlow";
 import { HttpConfigFields } from "./HttpConfigFields";
 import { StdioConfigFields } from "./StdioConfigFields";
+import { SandboxConfigFields } from "./SandboxConfigFields";
 import { OAuthConfigFields } from "./OAuthConfigFields";
 import { OAuthStatusBanner } from "./OAuthStatusBanner";
 
@@ -349,6 +350,7 @@ export function ServerForm({ mode, initialValues, showTransport = false, transpo
       ) : form.transport === "managed-sse" || form.transport === "managed-http" ? (
         <>
           <StdioConfigFields command={form.command} args={form.args} envPairs={form.envPairs} onCommandChange={form.setCommand} onArgsChange={form.setArgs} onEnvPairsChange={form.setEnvPairs} />
+          <SandboxConfigFields sandbox={form.sandbox} onChange={form.setSandbox} />
           <div className="grid grid-cols-3 gap-4">
             <div>
               <label className="block text-sm font-medium text-gray-700">Port</label>
@@ -383,7 +385,10 @@ export function ServerForm({ mode, initialValues, showTransport = false, transpo
           )}
         </>
       ) : (
-        <StdioConfigFields command={form.command} args={form.args} envPairs={form.envPairs} onCommandChange={form.setCommand} onArgsChange={form.setArgs} onEnvPairsChange={form.setEnvPairs} />
+        <>
+          <StdioConfigFields command={form.command} args={form.args} envPairs={form.envPairs} onCommandChange={form.setCommand} onArgsChange={form.setArgs} onEnvPairsChange={form.setEnvPairs} />
+          <SandboxConfigFields sandbox={form.sandbox} onChange={form.setSandbox} />
+        </>
       )}
 
       {/* Test result */}
@@ -1,10 +1,12 @@
 // @awa-component: PLAN-032-McpServerIndex
 
 export { ServerForm } from "./ServerForm";
+export { CatalogPicker } from "./CatalogPicker";
 export { HttpConfigFields } from "./HttpConfigFields";
 export { StdioConfigFields } from "./StdioConfigFields";
+export { SandboxConfigFields } from "./SandboxConfigFields";
 export { OAuthConfigFields } from "./OAuthConfigFields";
 export { OAuthStatusBanner } from "./OAuthStatusBanner";
 export { useOAuthFlow } from "./useOAuthFlow";
 export { useServerForm } from "./useServerForm";
-export type { AuthType, HttpConfig, ManagedHttpConfig, OAuthConfig, OAuthStatus, ServerConfig, ServerFormPayload, ServerFormValues, SseConfig, StdioConfig, TestConnectionResult, TransportType, VisibilityTier } from "./types";
+export type { AuthType, HttpConfig, ManagedHttpConfig, OAuthConfig, OAuthStatus, SandboxConfig, ServerConfig, ServerFormPayload, ServerFormValues, SseConfig, StdioConfig, TestConnectionResult, TransportType, VisibilityTier } from "./types";
@@ -23,11 +23,21 @@ export interface OAuthStatus {
   expiresAt?: string;
 }
 
+/** Restrictions on a spawned server process; every field is optional. */
+export interface SandboxConfig {
+  workingDir?: string;
+  scrubEnv?: boolean;
+  envAllowlist?: string[];
+  maxMemoryMb?: number;
+  maxCpuSecs?: number;
+}
+
 export interface StdioConfig {
   transport: "stdio";
   command: string;
   args?: string[];
   env?: Record<string, string>;
+  sandbox?: SandboxConfig;
 }
 
 export interface HttpConfig {
@@ -55,6 +65,7 @@ export interface ManagedHttpConfig {
   port: number;
   path?: string;
   readyTimeoutSecs?: number;
+  sandbox?: SandboxConfig;
 }
 
 export type ServerConfig = StdioConfig | HttpConfig | SseConfig | ManagedHttpConfig;
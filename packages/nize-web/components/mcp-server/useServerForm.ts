@@ -9,7 +9,7 @@
 "use client";
 
 import { useState, useMemo } from "react";
-import type { AuthType, OAuthConfig, ServerConfig, ServerFormValues, TransportType } from "./types";
+import type { AuthType, OAuthConfig, SandboxConfig, ServerConfig, ServerFormValues, TransportType } from "./types";
 
 interface EnvPair {
   key: string;
@@ -56,6 +56,8 @@ interface UseServerFormReturn {
   setArgs: (v: string) => void;
   envPairs: EnvPair[];
   setEnvPairs: (v: EnvPair[]) => void;
+  sandbox: SandboxConfig;
+  setSandbox: (v: SandboxConfig) => void;
 
   // Managed transport fields
   port: number;
@@ -108,6 +110,7 @@ export function useServerForm(initialValues?: ServerFormValues, options?: { mode
     }
     return [];
   });
+  const [sandbox, setSandbox] = useState<SandboxConfig>(() => (cfg.sandbox as SandboxConfig | undefined) || {});
 
   // Managed transport fields
   const [port, setPort] = useState<number>((cfg.port as number) || 3100);
@@ -135,6 +138,17 @@ export function useServerForm(initialValues?: ServerFormValues, options?: { mode
     return true;
   }, [name, domain, transport, command, url, authType, apiKey, clientId, mode, port]);
 
+  // Drop unset fields so an untouched sandbox is omitted entirely.
+  const buildSandbox = (): SandboxConfig | undefined => {
+    const result: SandboxConfig = {};
+    if (sandbox.workingDir) result.workingDir = sandbox.workingDir;
+    if (sandbox.scrubEnv) result.scrubEnv = true;
+    if (sandbox.envAllowlist?.length) result.envAllowlist = sandbox.envAllowlist;
+    if (sandbox.maxMemoryMb) result.maxMemoryMb = sandbox.maxMemoryMb;
+    if (sandbox.maxCpuSecs) result.maxCpuSecs = sandbox.maxCpuSecs;
+    return Object.keys(result).length > 0 ? result : undefined;
+  };
+
   const buildConfig = (): ServerConfig => {
     if (transport === "stdio") {
       const env: Record<string, string> = {};
@@ -146,6 +160,7 @@ export function useServerForm(initialValues?: ServerFormValues, options?: { mode
         command,
         args: args ? args.split(/\s+/) : undefined,
         env: Object.keys(env).length > 0 ? env : undefined,
+        sandbox: buildSandbox(),
       };
     }
     if (transport === "sse") {
@@ -169,6 +184,7 @@ export function useServerForm(initialValues?: ServerFormValues, options?: { mode
         port,
         path: path || undefined,
         readyTimeoutSecs: readyTimeoutSecs !== 30 ? readyTimeoutSecs : undefined,
+        sandbox: buildSandbox(),
       };
     }
     return {
@@ -228,6 +244,8 @@ export function useServerForm(initialValues?: ServerFormValues, options?: { mode
     setArgs,
     envPairs,
     setEnvPairs,
+    sandbox,
+    setSandbox,
     port,
     setPort,
     path,
@@ -9,10 +9,26 @@
 
 import { createContext, useContext, useState, useEffect, useCallback, type ReactNode } from "react";
 import { apiUrl } from "./api";
+import { isTauri } from "./tauri";
 
 // User info storage key (not sensitive — tokens are in httpOnly cookies)
 const USER_KEY = "nize_user";
 
+// CSRF token issued by the API with each session. Kept in memory only; a
+// reload gets it again from the session refresh on mount.
+const CSRF_HEADER = "x-csrf-token";
+let csrfToken: string | null = null;
+
+function rememberCsrfToken(res: Response): void {
+  const token = res.headers.get(CSRF_HEADER);
+  if (token) csrfToken = token;
+}
+
+/** Headers to send with cookie-authenticated mutations (CSRF token). */
+export function csrfHeaders(): Record<string, string> {
+  return csrfToken ? { [CSRF_HEADER]: csrfToken } : {};
+}
+
 export interface User {
   id: string;
   email: string;
@@ -25,7 +41,7 @@ interface AuthContextType {
   isLoading: boolean;
   isAuthenticated: boolean;
   login: (email: string, password: string) => Promise<{ success: boolean; error?: string }>;
-  register: (email: string, password: string, name?: string) => Promise<{ success: boolean; error?: string }>;
+  register: (email: string, password: string, name?: string) => Promise<{ success: boolean; error?: string; verificationRequired?: boolean }>;
   logout: () => Promise<void>;
 }
 
@@ -53,25 +69,46 @@ function clearStoredUser(): void {
   localStorage.removeItem(USER_KEY);
 }
 
+/** Sign in with the desktop's local token, if the app runs in local mode. */
+async function localSignIn(): Promise<Response | null> {
+  if (!isTauri()) return null;
+  const { invoke } = await import("@tauri-apps/api/core");
+  const token = await invoke<string | null>("get_local_token").catch(() => null);
+  if (!token) return null;
+  return fetch(apiUrl("/auth/local"), {
+    method: "POST",
+    credentials: "include",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify({ token }),
+  });
+}
+
 export function AuthProvider({ children }: { children: ReactNode }) {
   const [user, setUser] = useState<User | null>(null);
   const [isLoading, setIsLoading] = useState(true);
 
-  // Validate session by trying to refresh tokens (cookies sent automatically)
+  // Validate session by trying to refresh tokens (cookies sent automatically).
+  // Without a session, the desktop app in local mode signs in as the local user.
   const validateSession = useCallback(async (): Promise<User | null> => {
     try {
-      const res = await fetch(apiUrl("/auth/refresh"), {
+      let res = await fetch(apiUrl("/auth/refresh"), {
         method: "POST",
         credentials: "include",
         headers: { "Content-Type": "application/json" },
         body: JSON.stringify({}), // Empty body — refresh token comes from cookie
       });
 
+      if (!res.ok) {
+        const local = await localSignIn();
+        if (local) res = local;
+      }
+
       if (!res.ok) {
         clearStoredUser();
         setUser(null);
         return null;
       }
+      rememberCsrfToken(res);
 
       const data = await res.json();
       if (data.user) {
@@ -106,6 +143,7 @@ export function AuthProvider({ children }: { children: ReactNode }) {
         headers: { "Content-Type": "application/json" },
         body: JSON.stringify({ email, password }),
       });
+      rememberCsrfToken(res);
 
       const data = await res.json();
 
@@ -123,7 +161,7 @@ export function AuthProvider({ children }: { children: ReactNode }) {
     }
   }, []);
 
-  const register = useCallback(async (email: string, password: string, name?: string): Promise<{ success: boolean; error?: string }> => {
+  const register = useCallback(async (email: string, password: string, name?: string): Promise<{ success: boolean; error?: string; verificationRequired?: boolean }> => {
     try {
       const res = await fetch(apiUrl("/auth/register"), {
         method: "POST",
@@ -131,6 +169,7 @@ export function AuthProvider({ children }: { children: ReactNode }) {
         headers: { "Content-Type": "application/json" },
         body: JSON.stringify({ email, password, name }),
       });
+      rememberCsrfToken(res);
 
       const data = await res.json();
 
@@ -138,6 +177,11 @@ export function AuthProvider({ children }: { children: ReactNode }) {
         return { success: false, error: data.message || data.error || "Registration failed" };
       }
 
+      // 202: the account must be verified by email before signing in
+      if (data.verificationRequired) {
+        return { success: true, verificationRequired: true };
+      }
+
       // Store user info (tokens are in httpOnly cookies)
       setStoredUser(data.user);
       setUser(data.user);
@@ -153,12 +197,13 @@ export function AuthProvider({ children }: { children: ReactNode }) {
       await fetch(apiUrl("/auth/logout"), {
         method: "POST",
         credentials: "include",
-        headers: { "Content-Type": "application/json" },
+        headers: { "Content-Type": "application/json", ...csrfHeaders() },
         body: JSON.stringify({}),
       });
     } catch {
       // Ignore logout errors
     }
+    csrfToken = null;
 
     clearStoredUser();
     setUser(null);
@@ -190,15 +235,22 @@ export function useAuth() {
 
 /**
  * Hook for making authenticated API calls.
- * Cookies are sent automatically with credentials: "include".
+ * Cookies are sent automatically with credentials: "include"; mutations
+ * also carry the CSRF token.
  */
 export function useAuthFetch() {
   const { logout } = useAuth();
 
   return useCallback(
     async (path: string, options: RequestInit = {}): Promise<Response> => {
+      const headers = new Headers(options.headers);
+      const method = (options.method ?? "GET").toUpperCase();
+      if (method !== "GET" && method !== "HEAD") {
+        for (const [name, value] of Object.entries(csrfHeaders())) headers.set(name, value);
+      }
       const res = await fetch(apiUrl(path), {
         ...options,
+        headers,
         credentials: "include",
       });
 
@@ -2,7 +2,7 @@
 # Full API code generation pipeline:
 #   1. TypeSpec compile → OpenAPI YAML
 #   2. YAML → JSON (for swagger docs / client codegen)
-#   3. nize-codegen → Rust models + route constants
+#   3. nize-codegen → Rust models + route constants + mock router
 set -euo pipefail
 
 SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
@@ -22,5 +22,5 @@ log "JSON conversion complete."
 
 # --- Step 3: nize-codegen → Rust ---
 log "Generating Rust models via nize-codegen..."
-cargo run -p nize_codegen
+cargo run -p nize_codegen -- --mock-server
 log "Pipeline complete."